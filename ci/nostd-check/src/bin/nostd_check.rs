@@ -13,12 +13,20 @@
 //
 
 #![no_std]
+extern crate alloc;
 
+use alloc::vec::Vec;
 use core::panic::PanicInfo;
 use getrandom::{register_custom_getrandom, Error};
 use linked_list_allocator::LockedHeap;
-#[allow(unused_imports)]
-use {zenoh_buffers, zenoh_codec, zenoh_protocol};
+use zenoh_buffers::{reader::HasReader, writer::HasWriter, ZBuf};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::{
+    core::{Encoding, Reliability, WireExpr},
+    network::{push, NetworkMessage, Push},
+    transport::{frame, Frame, TransportMessage},
+    zenoh::{PushBody, Put},
+};
 
 #[panic_handler]
 fn dummy_panic_handler(_: &PanicInfo) -> ! {
@@ -32,6 +40,45 @@ fn dummy_get_rand(_: &mut [u8]) -> Result<(), Error> {
     Ok(())
 }
 
+// Encodes a frame carrying a put, as a minimal client would, and decodes it back
+fn codec_roundtrip() -> bool {
+    let put: NetworkMessage = Push {
+        wire_expr: WireExpr::from(0).with_suffix("demo/example").to_owned(),
+        ext_qos: push::ext::QoSType::default(),
+        ext_tstamp: None,
+        ext_nodeid: push::ext::NodeIdType::default(),
+        payload: PushBody::Put(Put {
+            timestamp: None,
+            encoding: Encoding::default(),
+            ext_sinfo: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
+            ext_unknown: Vec::new(),
+            payload: ZBuf::from(Vec::from(*b"Hello World!")),
+        }),
+    }
+    .into();
+    let message: TransportMessage = Frame {
+        reliability: Reliability::Reliable,
+        sn: 0,
+        payload: alloc::vec![put],
+        ext_qos: frame::ext::QoSType::default(),
+    }
+    .into();
+
+    let codec = Zenoh080::new();
+    let mut buffer = Vec::new();
+    let mut writer = buffer.writer();
+    if codec.write(&mut writer, &message).is_err() {
+        return false;
+    }
+    let mut reader = buffer.reader();
+    let decoded: Result<TransportMessage, _> = codec.read(&mut reader);
+    decoded.map_or(false, |decoded| decoded == message)
+}
+
 fn main() {
     register_custom_getrandom!(dummy_get_rand);
+    assert!(codec_roundtrip());
 }
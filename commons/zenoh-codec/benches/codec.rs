@@ -91,6 +91,9 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 8]),
         }),
@@ -136,6 +139,9 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 8]),
         }),
@@ -176,6 +182,9 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 8]),
         }),
@@ -216,6 +225,9 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 1_000_000]),
         }),
@@ -243,6 +255,9 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 1_000_000]),
         }),
@@ -281,6 +296,9 @@ fn criterion_benchmark(c: &mut Criterion) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::from(vec![0u8; 1_000_000]),
         }),
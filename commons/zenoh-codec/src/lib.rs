@@ -17,6 +17,14 @@
 //! This crate is intended for Zenoh's internal use.
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! The applications encode and decode the zenoh messages with the `zenoh::codec` module instead.
+//!
+//! Without its default `std` feature, this crate and the message definitions of `zenoh-protocol` only
+//! depend on `core` and `alloc`: constrained devices can then encode and decode the zenoh messages
+//! with [`Zenoh080`], from and into the `Vec<u8>` and `&[u8]` buffers of `zenoh-buffers`, to implement
+//! a minimal client. On stream-oriented links (e.g. TCP), each batch of messages is preceded by its
+//! length, as a 16-bit little-endian integer. `ci/nostd-check` checks this subset for a bare-metal target.
 #![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
 
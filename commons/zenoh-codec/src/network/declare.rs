@@ -11,7 +11,9 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use crate::{common::extension, RCodec, WCodec, Zenoh080, Zenoh080Condition, Zenoh080Header};
+use crate::{
+    common::extension, LCodec, RCodec, WCodec, Zenoh080, Zenoh080Condition, Zenoh080Header,
+};
 use alloc::string::String;
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
@@ -19,7 +21,7 @@ use zenoh_buffers::{
     ZBuf,
 };
 use zenoh_protocol::{
-    common::{iext, imsg, ZExtZ64},
+    common::{iext, imsg, ZExtZ64, ZExtZBufHeader},
     core::{ExprId, ExprLen, WireExpr},
     network::{
         declare::{
@@ -313,6 +315,36 @@ where
 // SubscriberInfo
 crate::impl_zextz64!(subscriber::ext::SubscriberInfo, subscriber::ext::Info::ID);
 
+// Filter
+impl<W> WCodec<(&subscriber::ext::FilterType, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (&subscriber::ext::FilterType, bool)) -> Self::Output {
+        let (filter, more) = x;
+        let header: ZExtZBufHeader<{ subscriber::ext::Filter::ID }> =
+            ZExtZBufHeader::new(self.w_len(&filter.filter));
+        self.write(&mut *writer, (&header, more))?;
+        self.write(&mut *writer, &filter.filter)
+    }
+}
+
+impl<R> RCodec<(subscriber::ext::FilterType, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(subscriber::ext::FilterType, bool), Self::Error> {
+        let (_, more): (ZExtZBufHeader<{ subscriber::ext::Filter::ID }>, bool) =
+            self.read(&mut *reader)?;
+        let filter: String = self.codec.read(&mut *reader)?;
+        Ok((subscriber::ext::FilterType { filter }, more))
+    }
+}
+
 // DeclareSubscriber
 impl<W> WCodec<&subscriber::DeclareSubscriber, &mut W> for Zenoh080
 where
@@ -323,7 +355,8 @@ where
     fn write(self, writer: &mut W, x: &subscriber::DeclareSubscriber) -> Self::Output {
         // Header
         let mut header = declare::id::D_SUBSCRIBER;
-        let mut n_exts = (x.ext_info != subscriber::ext::SubscriberInfo::default()) as u8;
+        let mut n_exts = (x.ext_info != subscriber::ext::SubscriberInfo::default()) as u8
+            + (x.ext_filter.is_some() as u8);
         if n_exts != 0 {
             header |= subscriber::flag::Z;
         }
@@ -344,6 +377,10 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (x.ext_info, n_exts != 0))?;
         }
+        if let Some(filter) = x.ext_filter.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (filter, n_exts != 0))?;
+        }
 
         Ok(())
     }
@@ -386,6 +423,7 @@ where
 
         // Extensions
         let mut ext_info = subscriber::ext::SubscriberInfo::default();
+        let mut ext_filter = None;
 
         let mut has_ext = imsg::has_flag(self.header, subscriber::flag::Z);
         while has_ext {
@@ -398,6 +436,11 @@ where
                     ext_info = i;
                     has_ext = ext;
                 }
+                subscriber::ext::Filter::ID => {
+                    let (f, ext): (subscriber::ext::FilterType, bool) = eodec.read(&mut *reader)?;
+                    ext_filter = Some(f);
+                    has_ext = ext;
+                }
                 _ => {
                     has_ext = extension::skip(reader, "DeclareSubscriber", ext)?;
                 }
@@ -408,6 +451,7 @@ where
             id,
             wire_expr,
             ext_info,
+            ext_filter,
         })
     }
 }
@@ -42,8 +42,9 @@ where
             ext::TargetType::BestMatching => 0,
             ext::TargetType::All => 1,
             ext::TargetType::AllComplete => 2,
+            ext::TargetType::CompleteStorage => 3,
             #[cfg(feature = "complete_n")]
-            ext::TargetType::Complete(n) => 3 + *n,
+            ext::TargetType::Complete(n) => 4 + *n,
         };
         let ext = ext::Target::new(v);
         self.write(&mut *writer, (&ext, more))
@@ -62,8 +63,9 @@ where
             0 => ext::TargetType::BestMatching,
             1 => ext::TargetType::All,
             2 => ext::TargetType::AllComplete,
+            3 => ext::TargetType::CompleteStorage,
             #[cfg(feature = "complete_n")]
-            n => ext::TargetType::Complete(n - 3),
+            n => ext::TargetType::Complete(n - 4),
             #[cfg(not(feature = "complete_n"))]
             _ => return Err(DidntRead),
         };
@@ -11,17 +11,17 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use crate::{RCodec, WCodec, Zenoh080, Zenoh080Header, Zenoh080Length};
+use crate::{common::extension, RCodec, WCodec, Zenoh080, Zenoh080Header, Zenoh080Length};
 use alloc::{vec, vec::Vec};
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
     writer::{DidntWrite, Writer},
 };
 use zenoh_protocol::{
-    common::{imsg, ZExtUnknown},
+    common::{iext, imsg},
     core::{Locator, WhatAmI, ZenohId},
     scouting::{
-        hello::{flag, Hello},
+        hello::{ext, flag, Hello},
         id,
     },
 };
@@ -38,6 +38,9 @@ where
         if !x.locators.is_empty() {
             header |= flag::L;
         }
+        if x.ext_auth.is_some() {
+            header |= flag::Z;
+        }
         self.write(&mut *writer, header)?;
 
         // Body
@@ -60,6 +63,11 @@ where
             self.write(&mut *writer, x.locators.as_slice())?;
         }
 
+        // Extensions
+        if let Some(auth) = x.ext_auth.as_ref() {
+            self.write(&mut *writer, (auth, false))?;
+        }
+
         Ok(())
     }
 }
@@ -109,10 +117,22 @@ where
         };
 
         // Extensions
-        let mut has_extensions = imsg::has_flag(self.header, flag::Z);
-        while has_extensions {
-            let (_, more): (ZExtUnknown, bool) = self.codec.read(&mut *reader)?;
-            has_extensions = more;
+        let mut ext_auth = None;
+
+        let mut has_ext = imsg::has_flag(self.header, flag::Z);
+        while has_ext {
+            let ext: u8 = self.codec.read(&mut *reader)?;
+            let eodec = Zenoh080Header::new(ext);
+            match iext::eid(ext) {
+                ext::Auth::ID => {
+                    let (a, ext): (ext::Auth, bool) = eodec.read(&mut *reader)?;
+                    ext_auth = Some(a);
+                    has_ext = ext;
+                }
+                _ => {
+                    has_ext = extension::skip(reader, "Hello", ext)?;
+                }
+            }
         }
 
         Ok(Hello {
@@ -120,6 +140,7 @@ where
             zid,
             whatami,
             locators,
+            ext_auth,
         })
     }
 }
@@ -11,18 +11,18 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use crate::{RCodec, WCodec, Zenoh080, Zenoh080Header, Zenoh080Length};
+use crate::{common::extension, RCodec, WCodec, Zenoh080, Zenoh080Header, Zenoh080Length};
 use core::convert::TryFrom;
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
     writer::{DidntWrite, Writer},
 };
 use zenoh_protocol::{
-    common::{imsg, ZExtUnknown},
+    common::{iext, imsg},
     core::{whatami::WhatAmIMatcher, ZenohId},
     scouting::{
         id,
-        scout::{flag, Scout},
+        scout::{ext, flag, Scout},
     },
 };
 
@@ -34,7 +34,10 @@ where
 
     fn write(self, writer: &mut W, x: &Scout) -> Self::Output {
         // Header
-        let header = id::SCOUT;
+        let mut header = id::SCOUT;
+        if x.ext_auth.is_some() {
+            header |= flag::Z;
+        }
         self.write(&mut *writer, header)?;
 
         // Body
@@ -53,6 +56,11 @@ where
             lodec.write(&mut *writer, zid)?;
         }
 
+        // Extensions
+        if let Some(auth) = x.ext_auth.as_ref() {
+            self.write(&mut *writer, (auth, false))?;
+        }
+
         Ok(())
     }
 }
@@ -96,12 +104,29 @@ where
         };
 
         // Extensions
-        let mut has_extensions = imsg::has_flag(self.header, flag::Z);
-        while has_extensions {
-            let (_, more): (ZExtUnknown, bool) = self.codec.read(&mut *reader)?;
-            has_extensions = more;
+        let mut ext_auth = None;
+
+        let mut has_ext = imsg::has_flag(self.header, flag::Z);
+        while has_ext {
+            let ext: u8 = self.codec.read(&mut *reader)?;
+            let eodec = Zenoh080Header::new(ext);
+            match iext::eid(ext) {
+                ext::Auth::ID => {
+                    let (a, ext): (ext::Auth, bool) = eodec.read(&mut *reader)?;
+                    ext_auth = Some(a);
+                    has_ext = ext;
+                }
+                _ => {
+                    has_ext = extension::skip(reader, "Scout", ext)?;
+                }
+            }
         }
 
-        Ok(Scout { version, what, zid })
+        Ok(Scout {
+            version,
+            what,
+            zid,
+            ext_auth,
+        })
     }
 }
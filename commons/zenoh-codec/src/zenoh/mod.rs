@@ -24,6 +24,7 @@ use crate::Zenoh080Bounded;
 #[cfg(feature = "shared-memory")]
 use crate::Zenoh080Sliced;
 use crate::{LCodec, RCodec, WCodec, Zenoh080, Zenoh080Header, Zenoh080Length};
+use alloc::{string::String, vec::Vec};
 use zenoh_buffers::{
     reader::{DidntRead, Reader},
     writer::{DidntWrite, Writer},
@@ -33,7 +34,7 @@ use zenoh_buffers::{
 use zenoh_protocol::common::{iext, ZExtUnit};
 use zenoh_protocol::{
     common::{imsg, ZExtZBufHeader},
-    core::{Encoding, ZenohId},
+    core::{Encoding, ZenohId, NTP64},
     zenoh::{ext, id, PushBody, RequestBody, ResponseBody},
 };
 
@@ -124,6 +125,7 @@ where
             ResponseBody::Err(b) => self.write(&mut *writer, b),
             ResponseBody::Ack(b) => self.write(&mut *writer, b),
             ResponseBody::Put(b) => self.write(&mut *writer, b),
+            ResponseBody::Del(b) => self.write(&mut *writer, b),
         }
     }
 }
@@ -143,6 +145,7 @@ where
             id::ERR => ResponseBody::Err(codec.read(&mut *reader)?),
             id::ACK => ResponseBody::Ack(codec.read(&mut *reader)?),
             id::PUT => ResponseBody::Put(codec.read(&mut *reader)?),
+            id::DEL => ResponseBody::Del(codec.read(&mut *reader)?),
             _ => return Err(DidntRead),
         };
 
@@ -202,6 +205,186 @@ where
     }
 }
 
+// Extension: Trace
+impl LCodec<&ext::TraceHop> for Zenoh080 {
+    fn w_len(self, x: &ext::TraceHop) -> usize {
+        1 + self.w_len(&x.zid) + self.w_len(x.ingress.as_u64()) + self.w_len(x.egress.as_u64())
+    }
+}
+
+impl<const ID: u8> LCodec<&ext::TraceType<{ ID }>> for Zenoh080 {
+    fn w_len(self, x: &ext::TraceType<{ ID }>) -> usize {
+        self.w_len(x.hops.len()) + x.hops.iter().map(|h| self.w_len(h)).sum::<usize>()
+    }
+}
+
+impl<W> WCodec<&ext::TraceHop, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &ext::TraceHop) -> Self::Output {
+        let flags: u8 = (x.zid.size() as u8 - 1) << 4;
+        self.write(&mut *writer, flags)?;
+
+        let lodec = Zenoh080Length::new(x.zid.size());
+        lodec.write(&mut *writer, &x.zid)?;
+
+        self.write(&mut *writer, x.ingress.as_u64())?;
+        self.write(&mut *writer, x.egress.as_u64())?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<ext::TraceHop, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<ext::TraceHop, Self::Error> {
+        let flags: u8 = self.read(&mut *reader)?;
+        let length = 1 + ((flags >> 4) as usize);
+
+        let lodec = Zenoh080Length::new(length);
+        let zid: ZenohId = lodec.read(&mut *reader)?;
+
+        let ingress: u64 = self.read(&mut *reader)?;
+        let egress: u64 = self.read(&mut *reader)?;
+
+        Ok(ext::TraceHop {
+            zid,
+            ingress: NTP64(ingress),
+            egress: NTP64(egress),
+        })
+    }
+}
+
+impl<W, const ID: u8> WCodec<(&ext::TraceType<{ ID }>, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (&ext::TraceType<{ ID }>, bool)) -> Self::Output {
+        let (x, more) = x;
+        let header: ZExtZBufHeader<{ ID }> = ZExtZBufHeader::new(self.w_len(x));
+        self.write(&mut *writer, (&header, more))?;
+
+        self.write(&mut *writer, x.hops.len())?;
+        for hop in x.hops.iter() {
+            self.write(&mut *writer, hop)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R, const ID: u8> RCodec<(ext::TraceType<{ ID }>, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(ext::TraceType<{ ID }>, bool), Self::Error> {
+        let (_, more): (ZExtZBufHeader<{ ID }>, bool) = self.read(&mut *reader)?;
+
+        let num: usize = self.codec.read(&mut *reader)?;
+        let mut hops = Vec::with_capacity(num.min(u8::MAX as usize));
+        for _ in 0..num {
+            let hop: ext::TraceHop = self.codec.read(&mut *reader)?;
+            hops.push(hop);
+        }
+
+        Ok((ext::TraceType { hops }, more))
+    }
+}
+
+// Extension: Batch
+impl LCodec<&ext::BatchEntry> for Zenoh080 {
+    fn w_len(self, x: &ext::BatchEntry) -> usize {
+        self.w_len(&x.key) + self.w_len(&x.encoding) + self.w_len(&x.payload)
+    }
+}
+
+impl<const ID: u8> LCodec<&ext::BatchType<{ ID }>> for Zenoh080 {
+    fn w_len(self, x: &ext::BatchType<{ ID }>) -> usize {
+        self.w_len(x.entries.len()) + x.entries.iter().map(|e| self.w_len(e)).sum::<usize>()
+    }
+}
+
+impl<W> WCodec<&ext::BatchEntry, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &ext::BatchEntry) -> Self::Output {
+        self.write(&mut *writer, &x.key)?;
+        self.write(&mut *writer, &x.encoding)?;
+        self.write(&mut *writer, &x.payload)?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<ext::BatchEntry, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<ext::BatchEntry, Self::Error> {
+        let key: String = self.read(&mut *reader)?;
+        let encoding: Encoding = self.read(&mut *reader)?;
+        let payload: ZBuf = self.read(&mut *reader)?;
+
+        Ok(ext::BatchEntry {
+            key,
+            encoding,
+            payload,
+        })
+    }
+}
+
+impl<W, const ID: u8> WCodec<(&ext::BatchType<{ ID }>, bool), &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: (&ext::BatchType<{ ID }>, bool)) -> Self::Output {
+        let (x, more) = x;
+        let header: ZExtZBufHeader<{ ID }> = ZExtZBufHeader::new(self.w_len(x));
+        self.write(&mut *writer, (&header, more))?;
+
+        self.write(&mut *writer, x.entries.len())?;
+        for entry in x.entries.iter() {
+            self.write(&mut *writer, entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R, const ID: u8> RCodec<(ext::BatchType<{ ID }>, bool), &mut R> for Zenoh080Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<(ext::BatchType<{ ID }>, bool), Self::Error> {
+        let (_, more): (ZExtZBufHeader<{ ID }>, bool) = self.read(&mut *reader)?;
+
+        let num: usize = self.codec.read(&mut *reader)?;
+        let mut entries = Vec::with_capacity(num.min(u8::MAX as usize));
+        for _ in 0..num {
+            let entry: ext::BatchEntry = self.codec.read(&mut *reader)?;
+            entries.push(entry);
+        }
+
+        Ok((ext::BatchType { entries }, more))
+    }
+}
+
 // Extension: Shm
 #[cfg(feature = "shared-memory")]
 impl<W, const ID: u8> WCodec<(&ext::ShmType<{ ID }>, bool), &mut W> for Zenoh080
@@ -24,7 +24,7 @@ use zenoh_buffers::{
 };
 use zenoh_protocol::{
     common::{iext, imsg},
-    core::Encoding,
+    core::{Encoding, NTP64},
     zenoh::{
         id,
         put::{ext, flag, Put},
@@ -46,7 +46,11 @@ where
         if x.encoding != Encoding::default() {
             header |= flag::E;
         }
-        let mut n_exts = (x.ext_sinfo.is_some()) as u8 + (x.ext_unknown.len() as u8);
+        let mut n_exts = (x.ext_sinfo.is_some()) as u8
+            + (x.ext_trace.is_some()) as u8
+            + (x.ext_deadline.is_some()) as u8
+            + (x.ext_batch.is_some()) as u8
+            + (x.ext_unknown.len() as u8);
         #[cfg(feature = "shared-memory")]
         {
             n_exts += x.ext_shm.is_some() as u8;
@@ -74,6 +78,19 @@ where
             n_exts -= 1;
             self.write(&mut *writer, (eshm, n_exts != 0))?;
         }
+        if let Some(trace) = x.ext_trace.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (trace, n_exts != 0))?;
+        }
+        if let Some(deadline) = x.ext_deadline.as_ref() {
+            n_exts -= 1;
+            let e = ext::Deadline::new(deadline.as_u64());
+            self.write(&mut *writer, (&e, n_exts != 0))?;
+        }
+        if let Some(batch) = x.ext_batch.as_ref() {
+            n_exts -= 1;
+            self.write(&mut *writer, (batch, n_exts != 0))?;
+        }
         for u in x.ext_unknown.iter() {
             n_exts -= 1;
             self.write(&mut *writer, (u, n_exts != 0))?;
@@ -135,6 +152,9 @@ where
         let mut ext_sinfo: Option<ext::SourceInfoType> = None;
         #[cfg(feature = "shared-memory")]
         let mut ext_shm: Option<ext::ShmType> = None;
+        let mut ext_trace: Option<ext::TraceType> = None;
+        let mut ext_deadline: Option<ext::DeadlineType> = None;
+        let mut ext_batch: Option<ext::BatchType> = None;
         let mut ext_unknown = Vec::new();
 
         let mut has_ext = imsg::has_flag(self.header, flag::Z);
@@ -153,6 +173,21 @@ where
                     ext_shm = Some(s);
                     has_ext = ext;
                 }
+                ext::Trace::ID => {
+                    let (t, ext): (ext::TraceType, bool) = eodec.read(&mut *reader)?;
+                    ext_trace = Some(t);
+                    has_ext = ext;
+                }
+                ext::Deadline::ID => {
+                    let (d, ext): (ext::Deadline, bool) = eodec.read(&mut *reader)?;
+                    ext_deadline = Some(NTP64(d.value));
+                    has_ext = ext;
+                }
+                ext::Batch::ID => {
+                    let (b, ext): (ext::BatchType, bool) = eodec.read(&mut *reader)?;
+                    ext_batch = Some(b);
+                    has_ext = ext;
+                }
                 _ => {
                     let (u, ext) = extension::read(reader, "Put", ext)?;
                     ext_unknown.push(u);
@@ -182,6 +217,9 @@ where
             ext_sinfo,
             #[cfg(feature = "shared-memory")]
             ext_shm,
+            ext_trace,
+            ext_deadline,
+            ext_batch,
             ext_unknown,
             payload,
         })
@@ -40,6 +40,22 @@ impl<T> RingBuffer<T> {
         Some(elem)
     }
 
+    /// Pushes `elem`, evicting and returning the oldest element if the buffer is full.
+    #[inline]
+    pub fn push_force(&mut self, elem: T) -> Option<T> {
+        if self.capacity == 0 {
+            return Some(elem);
+        }
+        let evicted = if self.len == self.capacity {
+            self.pull()
+        } else {
+            None
+        };
+        self.buffer.push_back(elem);
+        self.len += 1;
+        evicted
+    }
+
     #[inline]
     pub fn pull(&mut self) -> Option<T> {
         let x = self.buffer.pop_front();
@@ -74,3 +90,17 @@ impl<T> RingBuffer<T> {
         self.capacity
     }
 }
+
+#[test]
+fn ring_buffer_push_force() {
+    let mut ring = RingBuffer::new(2);
+    assert_eq!(ring.push_force(1), None);
+    assert_eq!(ring.push_force(2), None);
+    assert!(ring.is_full());
+    assert_eq!(ring.push(3), Some(3));
+    assert_eq!(ring.push_force(3), Some(1));
+    assert_eq!(ring.len(), 2);
+    assert_eq!(ring.pull(), Some(2));
+    assert_eq!(ring.pull(), Some(3));
+    assert_eq!(ring.pull(), None);
+}
@@ -0,0 +1,335 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! A fluent builder of [`Config`]s, for the applications configuring zenoh programmatically.
+use super::{Config, EndPoint, WhatAmI, ZenohId};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{fmt, path::Path, time::Duration};
+use zenoh_result::{bail, ZResult};
+
+/// A fluent builder of [`Config`].
+///
+/// The settings are recorded in the structure of the configuration files, and only validated by
+/// [`build`](ConfigBuilder::build): the configuration is then built exactly as if it was read from a file
+/// holding these settings, on top of the defaults of its mode.
+///
+/// ```
+/// use zenoh_config::{Config, WhatAmI};
+///
+/// let config = Config::builder()
+///     .mode(WhatAmI::Client)
+///     .connect(["tcp/127.0.0.1:7447"])
+///     .multicast_scouting(false)
+///     .build()
+///     .unwrap();
+/// assert_eq!(config.mode(), &Some(WhatAmI::Client));
+///
+/// assert!(Config::builder().connect(["not an endpoint"]).build().is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ConfigBuilder {
+    values: Map<String, Value>,
+    errors: Vec<String>,
+}
+
+impl Config {
+    /// Returns a [`ConfigBuilder`] with no setting.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+}
+
+impl ConfigBuilder {
+    /// Returns a builder holding the settings of a configuration file, see [`Config::from_file`].
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ZResult<Self> {
+        match Config::read_file(path.as_ref())? {
+            Value::Object(values) => Ok(ConfigBuilder {
+                values,
+                errors: vec![],
+            }),
+            _ => bail!("Invalid configuration: the configuration must be an object"),
+        }
+    }
+
+    /// Sets the mode of the node, which selects the defaults of the settings left unset.
+    pub fn mode(self, mode: WhatAmI) -> Self {
+        self.set("mode", mode)
+    }
+
+    /// Sets the Zenoh ID of the node.
+    pub fn id(self, id: ZenohId) -> Self {
+        self.set("id", id)
+    }
+
+    /// Sets the endpoints to connect to, e.g. `"tcp/192.168.0.1:7447"`.
+    pub fn connect<I: IntoIterator<Item = T>, T: Into<String>>(self, endpoints: I) -> Self {
+        self.set_endpoints("connect/endpoints", endpoints)
+    }
+
+    /// Sets the endpoints to listen on, e.g. `"tcp/[::]:7447"`.
+    pub fn listen<I: IntoIterator<Item = T>, T: Into<String>>(self, endpoints: I) -> Self {
+        self.set_endpoints("listen/endpoints", endpoints)
+    }
+
+    /// Enables or disables the multicast scouting.
+    pub fn multicast_scouting(self, enabled: bool) -> Self {
+        self.set("scouting/multicast/enabled", enabled)
+    }
+
+    /// Enables or disables the gossip scouting.
+    pub fn gossip_scouting(self, enabled: bool) -> Self {
+        self.set("scouting/gossip/enabled", enabled)
+    }
+
+    /// Enables or disables the timestamping of the data messages.
+    pub fn timestamping(self, enabled: bool) -> Self {
+        self.set("timestamping/enabled", enabled)
+    }
+
+    /// Sets the default timeout of the queries.
+    pub fn queries_default_timeout(self, timeout: Duration) -> Self {
+        self.set("queries_default_timeout", timeout.as_millis() as u64)
+    }
+
+    /// Sets the TLS configuration of the links.
+    pub fn tls(self, tls: TlsBuilder) -> Self {
+        self.set("transport/link/tls", tls)
+    }
+
+    /// Sets the user and the password authenticating the node.
+    pub fn user_password<U: Into<String>, P: Into<String>>(self, user: U, password: P) -> Self {
+        self.set("transport/auth/usrpwd/user", user.into())
+            .set("transport/auth/usrpwd/password", password.into())
+    }
+
+    /// Enables or disables the shared memory transport.
+    pub fn shared_memory(self, enabled: bool) -> Self {
+        self.set("transport/shared_memory/enabled", enabled)
+    }
+
+    /// Sets the configuration of a plugin.
+    pub fn plugin<N: AsRef<str>>(self, name: N, config: Value) -> Self {
+        let key = format!("plugins/{}", name.as_ref());
+        self.set(&key, config)
+    }
+
+    /// Sets any setting from its key in the configuration files (e.g. `"transport/link/tx/lease"`) and its
+    /// JSON5 value (e.g. `"10000"`).
+    pub fn insert_json5<K: AsRef<str>>(mut self, key: K, value: &str) -> Self {
+        let key = key.as_ref();
+        match json5::from_str::<Value>(value) {
+            Ok(value) => self.set(key, value),
+            Err(e) => {
+                self.errors.push(format!("{key}: {e}"));
+                self
+            }
+        }
+    }
+
+    /// Validates the settings and builds the configuration, with the defaults of its mode for the settings
+    /// left unset.
+    pub fn build(self) -> ZResult<Config> {
+        if !self.errors.is_empty() {
+            bail!("Invalid configuration: {}", self.errors.join(", "))
+        }
+        let mut config = Config::from_value_with_profile(Value::Object(self.values))?;
+        config.plugins.load_external_configs()?;
+        Ok(config)
+    }
+
+    fn set_endpoints<I: IntoIterator<Item = T>, T: Into<String>>(
+        mut self,
+        key: &str,
+        endpoints: I,
+    ) -> Self {
+        let endpoints = endpoints
+            .into_iter()
+            .map(|e| {
+                let e = e.into();
+                if let Err(err) = EndPoint::try_from(e.clone()) {
+                    self.errors.push(format!("{key}: {err}"));
+                }
+                e
+            })
+            .collect::<Vec<_>>();
+        self.set(key, endpoints)
+    }
+
+    fn set<T: Serialize>(mut self, key: &str, value: T) -> Self {
+        let value = match serde_json::to_value(value) {
+            Ok(value) => value,
+            Err(e) => {
+                self.errors.push(format!("{key}: {e}"));
+                return self;
+            }
+        };
+        let mut keys = key.split('/').filter(|k| !k.is_empty()).peekable();
+        let mut values = &mut self.values;
+        while let Some(k) = keys.next() {
+            if keys.peek().is_none() {
+                values.insert(k.to_string(), value);
+                break;
+            }
+            let entry = values.entry(k).or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            values = entry.as_object_mut().unwrap();
+        }
+        self
+    }
+}
+
+impl From<Config> for ConfigBuilder {
+    fn from(config: Config) -> Self {
+        match serde_json::to_value(config) {
+            Ok(Value::Object(values)) => ConfigBuilder {
+                values,
+                errors: vec![],
+            },
+            Ok(_) => unreachable!("Configurations are serialized as objects"),
+            Err(e) => ConfigBuilder {
+                values: Map::new(),
+                errors: vec![e.to_string()],
+            },
+        }
+    }
+}
+
+impl TryFrom<ConfigBuilder> for Config {
+    type Error = zenoh_result::Error;
+
+    fn try_from(builder: ConfigBuilder) -> ZResult<Self> {
+        builder.build()
+    }
+}
+
+/// Displays the settings in the format of the configuration files (JSON, which is valid JSON5).
+impl fmt::Display for ConfigBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string_pretty(&self.values).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
+
+/// The TLS configuration of a [`ConfigBuilder`].
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TlsBuilder {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root_ca_certificate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server_private_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server_certificate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_auth: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_private_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_certificate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server_name_verification: Option<bool>,
+}
+
+impl TlsBuilder {
+    /// Sets the path of the certificate of the certificate authority validating the remote certificates.
+    pub fn root_ca_certificate<P: Into<String>>(mut self, path: P) -> Self {
+        self.root_ca_certificate = Some(path.into());
+        self
+    }
+
+    /// Sets the paths of the private key and of the certificate presented when accepting TLS links.
+    pub fn server<K: Into<String>, C: Into<String>>(
+        mut self,
+        private_key: K,
+        certificate: C,
+    ) -> Self {
+        self.server_private_key = Some(private_key.into());
+        self.server_certificate = Some(certificate.into());
+        self
+    }
+
+    /// Sets the paths of the private key and of the certificate presented when opening TLS links.
+    pub fn client<K: Into<String>, C: Into<String>>(
+        mut self,
+        private_key: K,
+        certificate: C,
+    ) -> Self {
+        self.client_private_key = Some(private_key.into());
+        self.client_certificate = Some(certificate.into());
+        self
+    }
+
+    /// Whether the clients are required to present a certificate (mutual authentication).
+    pub fn client_auth(mut self, enabled: bool) -> Self {
+        self.client_auth = Some(enabled);
+        self
+    }
+
+    /// Whether the name of the servers is verified against their certificate.
+    pub fn server_name_verification(mut self, enabled: bool) -> Self {
+        self.server_name_verification = Some(enabled);
+        self
+    }
+}
+
+#[test]
+fn config_builder() {
+    use super::ModeDependent;
+
+    let builder = Config::builder()
+        .mode(WhatAmI::Client)
+        .id("a1b2c3".parse().unwrap())
+        .connect(["tcp/127.0.0.1:7447", "udp/127.0.0.1:7447"])
+        .multicast_scouting(false)
+        .queries_default_timeout(Duration::from_secs(5))
+        .tls(TlsBuilder::default().root_ca_certificate("ca.pem"))
+        .insert_json5("transport/link/tx/lease", "5000");
+    let config = builder.clone().build().unwrap();
+    assert_eq!(config.mode(), &Some(WhatAmI::Client));
+    assert_eq!(config.connect.endpoints.len(), 2);
+    assert_eq!(config.scouting.multicast.enabled(), &Some(false));
+    assert_eq!(config.queries_default_timeout(), &Some(5000));
+    assert_eq!(
+        config.transport.link.tls.root_ca_certificate(),
+        &Some("ca.pem".to_string())
+    );
+    assert_eq!(config.transport.link.tx.lease(), &5000);
+    // The unset settings keep the defaults of the mode.
+    let client = Config::profile(WhatAmI::Client);
+    assert_eq!(
+        config.scouting.multicast.autoconnect().get(WhatAmI::Client),
+        client.scouting.multicast.autoconnect().get(WhatAmI::Client)
+    );
+
+    // The settings are displayed in the file format, and round-trip through it.
+    let path =
+        std::env::temp_dir().join(format!("zenoh-config-builder-{}.json5", std::process::id()));
+    std::fs::write(&path, builder.to_string()).unwrap();
+    let from_file = ConfigBuilder::from_file(&path).unwrap().build().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(from_file.to_string(), config.to_string());
+    let from_config = ConfigBuilder::from(config.clone()).build().unwrap();
+    assert_eq!(from_config.to_string(), config.to_string());
+
+    assert!(Config::builder().connect(["tcp"]).build().is_err());
+    assert!(Config::builder()
+        .insert_json5("transport/link/tx/lease", "{")
+        .build()
+        .is_err());
+    assert!(Config::builder()
+        .insert_json5("transport/unknown", "1")
+        .build()
+        .is_err());
+}
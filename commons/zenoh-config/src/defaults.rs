@@ -32,6 +32,90 @@ macro_rules! mode_accessor {
 #[allow(dead_code)]
 pub const mode: WhatAmI = WhatAmI::Peer;
 
+/// The built-in configurations tuned for each mode, as JSON5 documents.
+///
+/// The profile matching a configuration's `mode` is used as its base, any value explicitly set in the configuration taking precedence.
+/// The profile of the default mode only states the default values, see [`Config::with_profile`](crate::Config::with_profile).
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod profile {
+    // Routers forward the traffic of many sessions: larger queues absorb its bursts.
+    // They don't scout before serving, and queries through them may go several hops away.
+    pub const router: &str = r#"{
+        scouting: { timeout: 3000, delay: 0 },
+        transport: { link: { tx: {
+            lease: 10000,
+            keep_alive: 4,
+            queue: { size: {
+                control: 2,
+                real_time: 2,
+                interactive_high: 2,
+                interactive_low: 2,
+                data_high: 4,
+                data: 8,
+                data_low: 4,
+                background: 2,
+            } },
+        } } },
+        queries_default_timeout: 15000,
+    }"#;
+    // The default values, peers being the default mode.
+    pub const peer: &str = r#"{
+        scouting: { timeout: 3000, delay: 200 },
+        transport: { link: { tx: {
+            lease: 10000,
+            keep_alive: 4,
+            queue: { size: {
+                control: 1,
+                real_time: 1,
+                interactive_high: 1,
+                interactive_low: 1,
+                data_high: 2,
+                data: 4,
+                data_low: 2,
+                background: 1,
+            } },
+        } } },
+        queries_default_timeout: 10000,
+    }"#;
+    // Clients only hold a session with a single router: smaller queues are enough. They give up scouting for
+    // a router sooner, and tolerate a longer silence of their link, often a constrained one.
+    pub const client: &str = r#"{
+        scouting: { timeout: 2000, delay: 0 },
+        transport: { link: { tx: {
+            lease: 15000,
+            keep_alive: 4,
+            queue: { size: {
+                control: 1,
+                real_time: 1,
+                interactive_high: 1,
+                interactive_low: 1,
+                data_high: 1,
+                data: 2,
+                data_low: 1,
+                background: 1,
+            } },
+        } } },
+        queries_default_timeout: 10000,
+    }"#;
+    mode_accessor!(str);
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod connect {
+    pub mod retry {
+        pub const period_init_ms: u64 = 1000;
+        pub const period_max_ms: u64 = 4000;
+        pub const period_increase_factor: u32 = 2;
+    }
+    pub mod offline_buffer {
+        pub const enabled: bool = false;
+        pub const capacity: usize = 10_000;
+        pub const replay_delay_ms: u64 = 500;
+    }
+}
+
 #[allow(non_upper_case_globals)]
 #[allow(dead_code)]
 pub mod scouting {
@@ -70,6 +154,10 @@ pub mod scouting {
             mode_accessor!(crate::WhatAmIMatcher);
         }
     }
+    pub mod rendezvous {
+        pub const enabled: bool = false;
+        pub const timeout: u64 = 5000;
+    }
 }
 
 #[allow(non_upper_case_globals)]
@@ -93,10 +181,25 @@ pub const queries_default_timeout: u64 = 10000;
 pub mod routing {
     pub mod router {
         pub const peers_failover_brokering: bool = true;
+        pub const trace: bool = false;
+        pub const relay: bool = true;
     }
     pub mod peer {
         pub const mode: &str = "peer_to_peer";
     }
+    pub mod dissemination {
+        pub const enabled: bool = false;
+        pub const group: &str = "udp/224.0.0.225:7448";
+        pub const threshold: usize = 4;
+        pub const repair_history: usize = 1024;
+    }
+}
+
+#[allow(non_upper_case_globals)]
+#[allow(dead_code)]
+pub mod adminspace {
+    pub const config_history: usize = 10;
+    pub const events_history: usize = 1000;
 }
 
 impl Default for TransportUnicastConf {
@@ -137,6 +240,7 @@ impl Default for LinkTxConf {
             batch_size: BatchSize::MAX,
             queue: QueueConf::default(),
             threads: num,
+            affinity: None,
         }
     }
 }
@@ -145,7 +249,11 @@ impl Default for QueueConf {
     fn default() -> Self {
         Self {
             size: QueueSizeConf::default(),
+            inline_threshold: InlineThresholdConf::default(),
             backoff: 100,
+            max_backoff: 1_000_000,
+            max_delay: 10_000_000,
+            adaptive_delay: true,
         }
     }
 }
@@ -170,6 +278,22 @@ impl Default for QueueSizeConf {
     }
 }
 
+impl Default for InlineThresholdConf {
+    fn default() -> Self {
+        let max = BatchSize::MAX as usize;
+        Self {
+            control: max,
+            real_time: max,
+            interactive_low: max,
+            interactive_high: max,
+            data_high: max,
+            data: max,
+            data_low: max,
+            background: max,
+        }
+    }
+}
+
 impl Default for LinkRxConf {
     fn default() -> Self {
         Self {
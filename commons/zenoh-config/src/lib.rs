@@ -13,8 +13,10 @@
 //
 
 //! Configuration to pass to `zenoh::open()` and `zenoh::scout()` functions and associated constants.
+mod builder;
 pub mod defaults;
 mod include;
+pub use builder::{ConfigBuilder, TlsBuilder};
 use include::recursive_include;
 use serde::{
     de::{self, MapAccess, Visitor},
@@ -25,7 +27,7 @@ use serde_json::Value;
 use std::convert::TryFrom; // This is a false positive from the rust analyser
 use std::{
     any::Any,
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     fmt,
     io::Read,
     marker::PhantomData,
@@ -68,15 +70,12 @@ pub fn default() -> Config {
 
 /// Creates a default `'peer'` mode zenoh net Session configuration.
 pub fn peer() -> Config {
-    let mut config = Config::default();
-    config.set_mode(Some(WhatAmI::Peer)).unwrap();
-    config
+    Config::profile(WhatAmI::Peer)
 }
 
 /// Creates a default `'client'` mode zenoh net Session configuration.
 pub fn client<I: IntoIterator<Item = T>, T: Into<EndPoint>>(peers: I) -> Config {
-    let mut config = Config::default();
-    config.set_mode(Some(WhatAmI::Client)).unwrap();
+    let mut config = Config::profile(WhatAmI::Client);
     config
         .connect
         .endpoints
@@ -109,23 +108,56 @@ validated_struct::validator! {
     #[derive(Default)]
     #[recursive_attrs]
     #[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+    #[derive(schemars::JsonSchema)]
     #[serde(default)]
     #[serde(deny_unknown_fields)]
     Config {
         /// The Zenoh ID of the instance. This ID MUST be unique throughout your Zenoh infrastructure and cannot exceed 16 bytes of length. If left unset, a random u128 will be generated.
+        #[schemars(with = "String")]
         id: ZenohId,
         /// The metadata of the instance. Arbitrary json data available from the admin space
         metadata: Value,
         /// The node's mode ("router" (default value in `zenohd`), "peer" or "client").
+        #[schemars(with = "Option<String>")]
         mode: Option<whatami::WhatAmI>,
         /// Which zenoh nodes to connect to.
         pub connect: #[derive(Default)]
         ConnectConfig {
+            #[schemars(with = "Vec<String>")]
             pub endpoints: Vec<EndPoint>,
+            /// In client mode, the SOCKS5 or HTTP CONNECT proxy the TCP and TLS links are established through,
+            /// e.g. "socks5://[<user>:<password>@]<host>:<port>" or "http://[<user>:<password>@]<host>:<port>".
+            proxy: Option<String>,
+            /// In client mode, how the connection to a router is reestablished once it dropped.
+            pub retry: #[derive(Default)]
+            ConnectRetryConf {
+                /// The delay after the first failed reconnection attempt. In milliseconds.
+                period_init_ms: Option<u64>,
+                /// The maximum delay between two reconnection attempts. In milliseconds.
+                period_max_ms: Option<u64>,
+                /// The factor the delay is multiplied by after each failed attempt.
+                period_increase_factor: Option<u32>,
+            },
+            /// In client mode, the buffering of the publications while the session isn't connected to any router,
+            /// the buffered publications being sent in order, with their original timestamps, once reconnected.
+            pub offline_buffer: #[derive(Default)]
+            OfflineBufferConf {
+                /// Whether the publications are buffered while disconnected (false by default).
+                enabled: Option<bool>,
+                /// The maximum number of buffered publications: once reached, the oldest ones are dropped.
+                capacity: Option<usize>,
+                /// The path of a file the buffered publications are kept in, so that they are sent even if
+                /// the application restarted before reconnecting (kept in memory by default).
+                file: Option<String>,
+                /// The delay between the reconnection and the sending of the buffered publications, leaving
+                /// time to the router to declare its subscriptions. In milliseconds.
+                replay_delay_ms: Option<u64>,
+            },
         },
         /// Which endpoints to listen on. `zenohd` will add `tcp/[::]:7447` to these locators if left empty.
         pub listen: #[derive(Default)]
         ListenConfig {
+            #[schemars(with = "Vec<String>")]
             pub endpoints: Vec<EndPoint>,
         },
         pub scouting: #[derive(Default)]
@@ -145,9 +177,13 @@ validated_struct::validator! {
                 interface: Option<String>,
                 /// Which type of Zenoh instances to automatically establish sessions with upon discovery through UDP multicast.
                 #[serde(deserialize_with = "treat_error_as_none")]
+                #[schemars(with = "Option<ModeDependentValue<String>>")]
                 autoconnect: Option<ModeDependentValue<WhatAmIMatcher>>,
                 /// Whether or not to listen for scout messages on UDP multicast and reply to them.
                 listen: Option<ModeDependentValue<bool>>,
+                /// The pre-shared key authenticating the scout and hello messages with an HMAC.
+                /// When set, the scouting messages without a valid HMAC are ignored.
+                auth_key: Option<String>,
             },
             /// The gossip scouting configuration.
             pub gossip: #[derive(Default)]
@@ -162,8 +198,19 @@ validated_struct::validator! {
                 multihop: Option<bool>,
                 /// Which type of Zenoh instances to automatically establish sessions with upon discovery through gossip.
                 #[serde(deserialize_with = "treat_error_as_none")]
+                #[schemars(with = "Option<ModeDependentValue<String>>")]
                 autoconnect: Option<ModeDependentValue<WhatAmIMatcher>>,
             },
+            /// The rendezvous (NAT traversal) configuration.
+            pub rendezvous: #[derive(Default)]
+            RendezvousConf {
+                /// Whether peers unable to reach each other directly ask a router they are both connected to
+                /// for their observed public endpoints and attempt a UDP/QUIC hole punching.
+                /// Routers only broker the attempts when enabled.
+                enabled: Option<bool>,
+                /// How long a hole punching attempt lasts before falling back to routing through the router. In milliseconds.
+                timeout: Option<u64>,
+            },
         },
 
         /// Configuration of data messages timestamps management.
@@ -180,6 +227,10 @@ validated_struct::validator! {
         /// The default timeout to apply to queries in milliseconds.
         queries_default_timeout: Option<u64>,
 
+        /// The maximum number of queryables of a session a query is dispatched to concurrently,
+        /// by default the number of CPU cores. 1 dispatches the queries sequentially.
+        queryables_parallelism: Option<usize>,
+
         /// The routing strategy to use and it's configuration.
         pub routing: #[derive(Default)]
         RoutingConf {
@@ -191,6 +242,14 @@ validated_struct::validator! {
                 /// connected to each other.
                 /// The failover brokering only works if gossip discovery is enabled.
                 peers_failover_brokering: Option<bool>,
+                /// When set to true a router appends a record of its ingress and egress times
+                /// to the traced samples it routes.
+                trace: Option<bool>,
+                /// When set to true a router forwards data between a peer that marked it as relay
+                /// (with the "relay=true" option on the connect endpoint) and the peers directly
+                /// connected to it that the peer is not known to be connected to.
+                /// Contrary to the failover brokering, it doesn't require gossip discovery.
+                relay: Option<bool>,
             },
             /// The routing strategy to use in peers and it's configuration.
             pub peer: #[derive(Default)]
@@ -198,6 +257,21 @@ validated_struct::validator! {
                 /// The routing strategy to use in peers. ("peer_to_peer" or "linkstate").
                 mode: Option<String>,
             },
+            /// The dissemination of the samples with many subscribers on a UDP multicast group.
+            pub dissemination: #[derive(Default)]
+            DisseminationConf {
+                /// When set to true a router offers its clients to join the multicast group and publishes
+                /// there once the samples that at least `threshold` of the joined clients subscribe to.
+                /// Clients only join the group offered by their router when enabled.
+                enabled: Option<bool>,
+                /// The UDP multicast group the samples are disseminated on.
+                group: Option<String>,
+                /// The minimum number of joined subscribers for a sample to be disseminated on the group
+                /// rather than sent to each of them.
+                threshold: Option<usize>,
+                /// The number of disseminated samples a router keeps to repair the losses reported by the clients.
+                repair_history: Option<usize>,
+            },
         },
 
         /// The declarations aggregation strategy.
@@ -244,6 +318,7 @@ validated_struct::validator! {
                     /// The resolution in bits to be used for the message sequence numbers.
                     /// When establishing a session with another Zenoh instance, the lowest value of the two instances will be used.
                     /// Accepted values: 8bit, 16bit, 32bit, 64bit.
+                    #[schemars(with = "String")]
                     sequence_number_resolution: Bits where (sequence_number_resolution_validator),
                     /// Link lease duration in milliseconds (default: 10000)
                     lease: u64,
@@ -267,12 +342,38 @@ validated_struct::validator! {
                             data_low: usize,
                             background: usize,
                         } where (queue_size_validator),
+                        /// The payload size in bytes above which the messages of each priority are not copied
+                        /// into the current batch along with the other messages, but sent as fragments in
+                        /// dedicated batches. Lower values keep the batches of small messages compact,
+                        /// higher values pack more mid-size samples per batch.
+                        pub inline_threshold: InlineThresholdConf {
+                            control: usize,
+                            real_time: usize,
+                            interactive_high: usize,
+                            interactive_low: usize,
+                            data_high: usize,
+                            data: usize,
+                            data_low: usize,
+                            background: usize,
+                        },
                         /// The initial exponential backoff time in nanoseconds to allow the batching to eventually progress.
                         /// Higher values lead to a more aggressive batching but it will introduce additional latency.
                         backoff: u64,
+                        /// The maximum time in nanoseconds the exponential backoff can grow to.
+                        max_backoff: u64,
+                        /// The maximum time in nanoseconds a batch is held while messages keep being added to it
+                        /// before being sent. Lower values reduce the latency of small messages under load,
+                        /// higher values favor the throughput of bulk transfers.
+                        max_delay: u64,
+                        /// Whether the batching delay adapts to the load: it grows from no added delay at low message rates
+                        /// up to `max_delay` while full batches keep being queued for transmission, and shrinks back once
+                        /// the queue drains. If false, batches are always held up to `max_delay`.
+                        adaptive_delay: bool,
                     },
                     // Number of threads used for TX
                     threads: usize,
+                    /// The CPU cores the TX threads are pinned to (not pinned by default).
+                    affinity: Option<Vec<usize>>,
                 },
                 pub rx: LinkRxConf {
                     /// Receiving buffer size in bytes for each link
@@ -293,7 +394,15 @@ validated_struct::validator! {
                     client_auth: Option<bool>,
                     client_private_key: Option<String>,
                     client_certificate: Option<String>,
-                    server_name_verification: Option<bool>
+                    server_name_verification: Option<bool>,
+                    /// The certificate revocation lists (PEM or DER) the certificates of the remote nodes are checked against.
+                    crl: Option<String>,
+                    /// The interval in seconds at which the listeners reload the `crl` file (3600 by default, 0 to disable).
+                    crl_reload_interval: Option<u64>,
+                    /// The DER-encoded OCSP response stapled by the listeners to their certificate.
+                    server_ocsp_response: Option<String>,
+                    /// Whether the servers must staple a valid OCSP response proving their certificate is not revoked.
+                    ocsp_required: Option<bool>,
                 },
                 pub unixpipe: #[derive(Default)]
                 UnixPipeConf {
@@ -337,10 +446,71 @@ validated_struct::validator! {
                     public_key_file: Option<String>,
                     private_key_file: Option<String>,
                     key_size: Option<usize>,
+                    /// The path to a file containing the PKCS#1 PEM public keys of the trusted nodes
                     known_keys_file: Option<String>,
                 },
+                pub token: #[derive(Default)]
+                TokenConf {
+                    /// The signed token (JWT) presented when opening a session
+                    token: Option<String>,
+                    /// The path to a file containing the signed token (JWT) presented when opening a session
+                    token_file: Option<String>,
+                    /// The secret validating the HS256 signature of the tokens presented by the remote nodes
+                    secret: Option<String>,
+                    /// The path to a file containing the PEM public key (SPKI or PKCS#1) validating the RS256 signature
+                    /// of the tokens presented by the remote nodes
+                    public_key_file: Option<String>,
+                    /// The issuer (`iss` claim) the presented tokens are required to have
+                    issuer: Option<String>,
+                    /// The audience (`aud` claim) the presented tokens are required to include
+                    audience: Option<String>,
+                    /// The claim identifying the remote node, `sub` if not set
+                    identity_claim: Option<String>,
+                } where (token_conf_validator),
+            },
+            /// Capture of the zenoh messages sent and received by the transports, for debugging and load reproduction.
+            pub capture: #[derive(Default)]
+            CaptureConf {
+                /// The path of the file the messages are written to, nothing is captured if not set.
+                file: Option<String>,
             },
         },
+        /// Configuration of the access control of the declarations and messages received from the remote nodes.
+        pub access_control: #[derive(Default)]
+        AclConf {
+            /// Whether the access control rules are enforced (false by default).
+            pub enabled: bool,
+            /// The permission of the declarations and messages matching no rule ("allow" by default).
+            pub default_permission: AclPermission,
+            /// The rules granting or denying actions on key expressions to the remote nodes.
+            pub rules: Vec<AclRule>,
+        },
+        /// Configuration of the limits enforced on the messages received from the remote nodes.
+        pub quotas: #[derive(Default)]
+        QuotasConf {
+            /// Whether the quotas are enforced (false by default).
+            pub enabled: bool,
+            /// The limits of the remote nodes: a node matching several rules is subject to the lowest limits.
+            pub rules: Vec<QuotaRule>,
+        },
+        /// The QoS rewritten by routers on the publications they receive, so that the traffic of
+        /// applications can be reprioritized without modifying them.
+        /// The first override whose key expression includes the key of a publication applies to it.
+        pub qos_overrides: Vec<QosOverride>,
+        /// The namespaces of the clients, each one confined to the key expressions under its prefix: the router prepends
+        /// the prefix to the key expressions of the declarations and messages of a client, and strips it from the ones it
+        /// sends it. The first rule matching one of the identities of a client applies to it.
+        pub namespaces: Vec<NamespaceRule>,
+        /// Configuration of the executor running the tasks of zenohd, such as the reception and the routing of the messages.
+        /// It is only applied by zenohd, at startup: applications configure their own executor.
+        pub executor: #[derive(Default)]
+        ExecutorConf {
+            /// The number of threads of the executor (by default, the value of the ASYNC_STD_THREAD_COUNT
+            /// environment variable or the number of CPU cores).
+            pub threads: Option<usize>,
+            /// The CPU cores the threads of the executor are pinned to (not pinned by default).
+            pub affinity: Option<Vec<usize>>,
+        },
         /// Configuration of the admin space.
         pub adminspace: #[derive(Default)]
         /// <div class="stab unstable">
@@ -359,6 +529,18 @@ validated_struct::validator! {
                 #[serde(default = "set_false")]
                 pub write: bool,
             },
+            /// The number of previously accepted configurations kept by the runtime, to which it may be rolled back
+            /// through the admin space (default: 10).
+            config_history: Option<usize>,
+            /// The number of recent runtime events (sessions opening and closing, plugin restarts, config and
+            /// route changes) kept by the runtime and queryable through the admin space (default: 1000).
+            events_history: Option<usize>,
+            /// Key expressions for which the router measures the rate and the end-to-end latency (computed from
+            /// the samples' timestamps) of the data it routes, queryable through the admin space (none by default).
+            measurements: Vec<OwnedKeyExpr>,
+            /// The path of a file to which the administrative actions (adminspace writes and operations, plugins
+            /// loading and unloading) are appended, with the identities of the nodes requesting them (none by default).
+            audit_log: Option<String>,
 
         },
         /// A list of directories where plugins may be searched for if no `__path__` was specified for them.
@@ -369,6 +551,29 @@ validated_struct::validator! {
         ///
         /// Please refer to [`PluginsConfig`]'s documentation for further details.
         plugins: PluginsConfig,
+        /// The settings taken from the profile of the configuration's mode rather than set explicitly.
+        #[serde(skip)]
+        from_profile: FromProfile,
+    }
+}
+
+/// The settings of a [`Config`] taken from a [profile](defaults::profile), with the values it gave them, as opposed
+/// to the ones set explicitly: only the former follow the profile of a mode set later (see [`Config::with_profile`]).
+///
+/// `None` stands for a configuration built with [`Config::default`], whose values are the ones of the default mode's
+/// profile. It is only set by zenoh, and isn't serialized.
+#[derive(Clone, Debug, Default)]
+pub struct FromProfile(Option<Vec<(String, Value)>>);
+impl Serialize for FromProfile {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_none()
+    }
+}
+impl<'a> Deserialize<'a> for FromProfile {
+    fn deserialize<D: serde::Deserializer<'a>>(_: D) -> Result<Self, D::Error> {
+        Err(de::Error::custom(
+            "the settings taken from a profile can't be set",
+        ))
     }
 }
 
@@ -486,6 +691,10 @@ impl Config {
         self.plugins.validators.insert(name.into(), validator);
     }
 
+    pub fn remove_plugin_validator(&mut self, name: &str) {
+        self.plugins.validators.remove(name);
+    }
+
     pub fn plugin(&self, name: &str) -> Option<&Value> {
         self.plugins.values.get(name)
     }
@@ -496,6 +705,50 @@ impl Config {
         copy
     }
 
+    /// Returns the settings differing between this configuration and `other`, as a JSON object mapping their paths
+    /// (e.g. `transport/link/tx/lease`) to `{"from": <value in self>, "to": <value in other>}`, `null` standing for
+    /// an absent setting.
+    ///
+    /// The private settings of the plugins are left out, see [`Config::sift_privates`].
+    pub fn diff(&self, other: &Config) -> Value {
+        fn diff(
+            path: &str,
+            from: &Value,
+            to: &Value,
+            changes: &mut serde_json::Map<String, Value>,
+        ) {
+            match (from, to) {
+                (Value::Object(from), Value::Object(to)) => {
+                    let added = to.keys().filter(|key| !from.contains_key(*key));
+                    for key in from.keys().chain(added) {
+                        let path = match path {
+                            "" => key.clone(),
+                            _ => format!("{path}/{key}"),
+                        };
+                        let from = from.get(key).unwrap_or(&Value::Null);
+                        let to = to.get(key).unwrap_or(&Value::Null);
+                        diff(&path, from, to, changes);
+                    }
+                }
+                _ if from != to => {
+                    changes.insert(
+                        path.to_string(),
+                        serde_json::json!({ "from": from, "to": to }),
+                    );
+                }
+                _ => {}
+            }
+        }
+        let mut changes = serde_json::Map::new();
+        diff(
+            "",
+            &serde_json::to_value(self.sift_privates()).unwrap(),
+            &serde_json::to_value(other.sift_privates()).unwrap(),
+            &mut changes,
+        );
+        Value::Object(changes)
+    }
+
     pub fn remove<K: AsRef<str>>(&mut self, key: K) -> ZResult<()> {
         let key = key.as_ref();
         self._remove(key)
@@ -547,6 +800,11 @@ impl Config {
     }
 
     fn _from_file(path: &Path) -> ZResult<Config> {
+        Config::from_value_with_profile(Self::read_file(path)?)
+    }
+
+    /// Reads the settings of a configuration file, without applying the defaults of its mode.
+    fn read_file(path: &Path) -> ZResult<Value> {
         match std::fs::File::open(path) {
             Ok(mut f) => {
                 let mut content = String::new();
@@ -557,17 +815,14 @@ impl Config {
                     .extension()
                     .map(|s| s.to_str().unwrap())
                 {
-                    Some("json") | Some("json5") => match json5::Deserializer::from_str(&content) {
-                        Ok(mut d) => Config::from_deserializer(&mut d).map_err(|e| match e {
-                            Ok(c) => zerror!("Invalid configuration: {}", c).into(),
-                            Err(e) => zerror!("JSON error: {}", e).into(),
-                        }),
+                    Some("json") | Some("json5") => match json5::from_str(&content) {
+                        Ok(value) => Ok(value),
                         Err(e) => bail!(e),
                     },
-                    Some("yaml") => Config::from_deserializer(serde_yaml::Deserializer::from_str(&content)).map_err(|e| match e {
-                        Ok(c) => zerror!("Invalid configuration: {}", c).into(),
-                        Err(e) => zerror!("YAML error: {}", e).into(),
-                    }),
+                    Some("yaml") => match serde_yaml::from_str(&content) {
+                        Ok(value) => Ok(value),
+                        Err(e) => bail!("YAML error: {}", e),
+                    },
                     Some(other) => bail!("Unsupported file type '.{}' (.json, .json5 and .yaml are supported)", other),
                     None => bail!("Unsupported file type. Configuration files must have an extension (.json, .json5 and .yaml supported)")
                 }
@@ -576,6 +831,185 @@ impl Config {
         }
     }
 
+    /// Returns the [JSON Schema](https://json-schema.org) of the configuration, generated from its structure.
+    ///
+    /// Plugins' sections only describe the properties reserved by `zenohd` (`__required__`, `__on_panic__`, `__max_restarts__`, `__dependencies__`, `__path__` and `__config__`).
+    pub fn json_schema() -> Value {
+        serde_json::to_value(schemars::schema_for!(Config)).unwrap()
+    }
+
+    /// Returns the default configuration for `mode`, built from the [profile](defaults::profile) tuned for this mode.
+    pub fn profile(mode: WhatAmI) -> Self {
+        let mut value = serde_json::Map::new();
+        value.insert("mode".into(), serde_json::to_value(mode).unwrap());
+        Self::from_value_with_profile(Value::Object(value)).unwrap()
+    }
+
+    /// Builds a configuration from `value`, using the [profile](defaults::profile) matching its `mode` as a base.
+    fn from_value_with_profile(value: Value) -> ZResult<Self> {
+        let mode = match value.get("mode") {
+            Some(mode) if !mode.is_null() => WhatAmI::deserialize(mode)
+                .map_err(|e| zerror!("Invalid configuration: mode: {}", e))?,
+            _ => defaults::mode,
+        };
+        let from_profile = profile_settings(mode)
+            .into_iter()
+            .filter(|(path, _)| value.pointer(&format!("/{path}")).is_none())
+            .collect();
+        let mut config: Value = json5::from_str(defaults::profile::get(mode))?;
+        merge_values(&mut config, value);
+        let mut config = Config::from_deserializer(config).map_err(|e| match e {
+            Ok(c) => zerror!("Invalid configuration: {}", c),
+            Err(e) => zerror!("JSON error: {}", e),
+        })?;
+        config.from_profile = FromProfile(Some(from_profile));
+        Ok(config)
+    }
+
+    /// Returns a copy of this configuration where the settings left unset take the values of the
+    /// [profile](defaults::profile) of its mode, e.g. when the mode was set after the configuration was built.
+    ///
+    /// A setting is considered left unset if it isn't set, or if it was taken from the profile the configuration was
+    /// built from and still holds the value this profile gave it, even if equal to a default one: a setting
+    /// explicitly set in the value or file the configuration was built from is always kept.
+    pub fn with_profile(&self) -> Self {
+        let mode = self.mode.unwrap_or(defaults::mode);
+        let current = serde_json::to_value(self).unwrap();
+        let from_profile = match &self.from_profile.0 {
+            Some(settings) => settings.clone(),
+            None => profile_settings(defaults::mode),
+        };
+        let mut config = self.clone();
+        let mut applied = Vec::new();
+        for (path, value) in profile_settings(mode) {
+            let set = current.pointer(&format!("/{path}")).unwrap_or(&Value::Null);
+            let unset = set.is_null() || from_profile.iter().any(|(p, v)| *p == path && v == set);
+            if unset {
+                // The built-in profiles are valid, see `config_profiles_by_mode`
+                config.insert_json5(&path, &value.to_string()).unwrap();
+                applied.push((path, value));
+            }
+        }
+        config.from_profile = FromProfile(Some(applied));
+        config
+    }
+
+    /// Returns a copy of this configuration where the settings left unset are replaced by the default values that
+    /// zenoh uses for this configuration's mode, including the ones of its [profile](Config::with_profile).
+    ///
+    /// This allows inspecting the configuration that is effectively used by a zenoh instance.
+    pub fn resolved(&self) -> Self {
+        fn resolve<T: Copy>(
+            value: &Option<ModeDependentValue<T>>,
+            whatami: WhatAmI,
+            default: &T,
+        ) -> Option<ModeDependentValue<T>> {
+            Some(ModeDependentValue::Unique(
+                *value.get(whatami).unwrap_or(default),
+            ))
+        }
+
+        let mut config = self.with_profile();
+        let whatami = *config.mode.get_or_insert(defaults::mode);
+
+        let retry = &mut config.connect.retry;
+        retry
+            .period_init_ms
+            .get_or_insert(defaults::connect::retry::period_init_ms);
+        retry
+            .period_max_ms
+            .get_or_insert(defaults::connect::retry::period_max_ms);
+        retry
+            .period_increase_factor
+            .get_or_insert(defaults::connect::retry::period_increase_factor);
+        let offline_buffer = &mut config.connect.offline_buffer;
+        offline_buffer
+            .enabled
+            .get_or_insert(defaults::connect::offline_buffer::enabled);
+        offline_buffer
+            .capacity
+            .get_or_insert(defaults::connect::offline_buffer::capacity);
+        offline_buffer
+            .replay_delay_ms
+            .get_or_insert(defaults::connect::offline_buffer::replay_delay_ms);
+
+        let scouting = &mut config.scouting;
+        scouting.timeout.get_or_insert(defaults::scouting::timeout);
+        scouting.delay.get_or_insert(defaults::scouting::delay);
+        let multicast = &mut scouting.multicast;
+        multicast
+            .enabled
+            .get_or_insert(defaults::scouting::multicast::enabled);
+        multicast
+            .address
+            .get_or_insert(defaults::scouting::multicast::address.into());
+        multicast
+            .interface
+            .get_or_insert_with(|| defaults::scouting::multicast::interface.into());
+        multicast.autoconnect = resolve(
+            &multicast.autoconnect,
+            whatami,
+            defaults::scouting::multicast::autoconnect::get(whatami),
+        );
+        multicast.listen = resolve(
+            &multicast.listen,
+            whatami,
+            defaults::scouting::multicast::listen::get(whatami),
+        );
+        let gossip = &mut scouting.gossip;
+        gossip
+            .enabled
+            .get_or_insert(defaults::scouting::gossip::enabled);
+        gossip
+            .multihop
+            .get_or_insert(defaults::scouting::gossip::multihop);
+        gossip.autoconnect = resolve(
+            &gossip.autoconnect,
+            whatami,
+            defaults::scouting::gossip::autoconnect::get(whatami),
+        );
+        let rendezvous = &mut scouting.rendezvous;
+        rendezvous
+            .enabled
+            .get_or_insert(defaults::scouting::rendezvous::enabled);
+        rendezvous
+            .timeout
+            .get_or_insert(defaults::scouting::rendezvous::timeout);
+
+        let timestamping = &mut config.timestamping;
+        timestamping.enabled = resolve(
+            &timestamping.enabled,
+            whatami,
+            defaults::timestamping::enabled::get(whatami),
+        );
+        timestamping
+            .drop_future_timestamp
+            .get_or_insert(defaults::timestamping::drop_future_timestamp);
+
+        config
+            .queries_default_timeout
+            .get_or_insert(defaults::queries_default_timeout);
+        config
+            .routing
+            .router
+            .peers_failover_brokering
+            .get_or_insert(defaults::routing::router::peers_failover_brokering);
+        config
+            .routing
+            .peer
+            .mode
+            .get_or_insert_with(|| defaults::routing::peer::mode.into());
+        config
+            .adminspace
+            .config_history
+            .get_or_insert(defaults::adminspace::config_history);
+        config
+            .adminspace
+            .events_history
+            .get_or_insert(defaults::adminspace::events_history);
+        config
+    }
+
     pub fn libloader(&self) -> LibLoader {
         if self.plugins_search_dirs.is_empty() {
             LibLoader::default()
@@ -603,11 +1037,366 @@ fn config_from_json() {
     println!("{}", serde_json::to_string_pretty(&config).unwrap());
 }
 
+#[test]
+fn config_schema() {
+    let schema = Config::json_schema();
+    let properties = &schema["properties"];
+    assert!(properties["transport"].is_object());
+    assert_eq!(schema["additionalProperties"], Value::Bool(false));
+    let definitions = &schema["definitions"];
+    let plugin = &definitions["PluginsConfig"]["additionalProperties"];
+    assert!(plugin["properties"]["__required__"].is_object());
+    assert!(plugin["properties"]["__on_panic__"].is_object());
+    assert!(plugin["properties"]["__max_restarts__"].is_object());
+    assert!(plugin["properties"]["__dependencies__"].is_object());
+    let queue_size = &definitions["QueueSizeConf"]["properties"];
+    assert_eq!(queue_size["data"]["type"], "integer");
+}
+
+#[test]
+fn plugins_dependencies() {
+    let config = Config::from_deserializer(
+        &mut json5::Deserializer::from_str(
+            r#"{
+            plugins: {
+                bridge: { __dependencies__: ["rest", "storage_manager"] },
+                storage_manager: { __dependencies__: ["metrics"] },
+                metrics: {},
+                rest: {},
+                orphan: { __dependencies__: ["missing"] },
+                a: { __dependencies__: ["b"] },
+                b: { __dependencies__: ["a"] },
+            }
+        }"#,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let (ordered, failed) = config.plugins().ordered_load_requests().unwrap();
+    let position = |name: &str| ordered.iter().position(|r| r.name == name).unwrap();
+    assert_eq!(ordered.len(), 4);
+    assert!(position("metrics") < position("storage_manager"));
+    assert!(position("storage_manager") < position("bridge"));
+    assert!(position("rest") < position("bridge"));
+    let mut failed = failed.into_iter().map(|(r, _)| r.name).collect::<Vec<_>>();
+    failed.sort();
+    assert_eq!(failed, ["a", "b", "orphan"]);
+}
+
+#[test]
+fn plugins_reserved_properties() {
+    let mut config = Config::default();
+    config.insert_json5("plugins/demo", "{}").unwrap();
+    for (key, value) in [
+        ("plugins/demo/__required__", "1"),
+        ("plugins/demo/__on_panic__", r#""ignore""#),
+        ("plugins/demo/__max_restarts__", "-1"),
+        ("plugins/demo/__dependencies__", r#""rest""#),
+        ("plugins/demo/__path__", "[1]"),
+        ("plugins/other", "3"),
+    ] {
+        assert!(config.insert_json5(key, value).is_err(), "{key}: {value}");
+    }
+    assert_eq!(config.plugin("demo"), Some(&serde_json::json!({})));
+    assert!(config.plugin("other").is_none());
+    let requests = config
+        .plugins()
+        .load_requests()
+        .collect::<ZResult<Vec<_>>>();
+    assert_eq!(requests.unwrap().len(), 1);
+
+    config
+        .insert_json5("plugins/demo/__on_panic__", r#""restart""#)
+        .unwrap();
+    let request = config.plugins().load_requests().next().unwrap().unwrap();
+    assert_eq!(request.on_panic, PluginPanicPolicy::Restart);
+    assert!(Config::from_deserializer(
+        &mut json5::Deserializer::from_str(r#"{ plugins: { demo: { __max_restarts__: "5" } } }"#)
+            .unwrap()
+    )
+    .is_err());
+}
+
+#[test]
+fn config_history() {
+    let from_str = serde_json::Deserializer::from_str;
+    let config = Notifier::new(Config::default());
+    config.set_history_size(2);
+    for lease in ["1000", "2000", "3000"] {
+        (&config)
+            .insert("transport/link/tx/lease", &mut from_str(lease))
+            .unwrap();
+    }
+    (&config)
+        .insert("plugins/demo", &mut from_str(r#"{"key": "value"}"#))
+        .unwrap();
+    assert_eq!(config.version(), 4);
+    let history = config.history();
+    assert_eq!(
+        history.iter().map(|(v, _)| *v).collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+    assert_eq!(*history[0].1.transport().link().tx().lease(), 2000);
+
+    let rx = config.subscribe();
+    config.rollback(2).unwrap();
+    assert_eq!(config.version(), 5);
+    assert_eq!(*config.lock().transport().link().tx().lease(), 2000);
+    assert!(config.lock().plugin("demo").is_none());
+    let mut notifications = rx.try_iter().collect::<Vec<_>>();
+    notifications.sort();
+    assert_eq!(
+        notifications,
+        vec![Arc::from("plugins"), Arc::from("transport")]
+    );
+    assert!(config.rollback(1).is_err());
+    let (version, previous) = config.history().pop().unwrap();
+    assert_eq!(version, 4);
+    assert_eq!(*previous.transport().link().tx().lease(), 3000);
+    assert_eq!(
+        previous.plugin("demo"),
+        Some(&serde_json::json!({"key": "value"}))
+    );
+
+    let diff = config.initial().diff(&config.lock());
+    assert_eq!(
+        diff,
+        serde_json::json!({
+            "transport/link/tx/lease": { "from": 10000, "to": 2000 },
+        })
+    );
+}
+
+#[test]
+fn config_history_endpoints() {
+    let endpoints = |config: &Config| config.connect().endpoints().clone();
+    let config = Notifier::new(Config::default());
+    config.set_history_size(2);
+    assert_eq!(
+        Undo::sections(&*config.lock(), "connect/endpoints/0"),
+        vec!["connect/endpoints".to_string()]
+    );
+    (&config)
+        .insert_json5("connect/endpoints", r#"["tcp/127.0.0.1:7447"]"#)
+        .unwrap();
+    (&config)
+        .insert_json5(
+            "connect/endpoints",
+            r#"["tcp/127.0.0.1:7447", "tcp/127.0.0.1:7448"]"#,
+        )
+        .unwrap();
+    let history = config.history();
+    assert_eq!(history.len(), 2);
+    assert!(endpoints(&history[0].1).is_empty());
+    assert_eq!(endpoints(&history[1].1).len(), 1);
+
+    config.rollback(1).unwrap();
+    assert_eq!(
+        endpoints(&config.lock()),
+        vec!["tcp/127.0.0.1:7447".parse::<EndPoint>().unwrap()]
+    );
+    assert!(config.rollback(0).is_err());
+}
+
+#[test]
+fn config_profiles() {
+    let client = client::<_, EndPoint>([]);
+    assert_eq!(*client.mode(), Some(WhatAmI::Client));
+    assert_eq!(*client.transport().link().tx().queue().size().data(), 2);
+    assert_eq!(
+        *peer().transport().link().tx().queue().size().data(),
+        *QueueSizeConf::default().data()
+    );
+    let config = Config::from_value_with_profile(
+        json5::from_str(
+            r#"{
+        mode: "client",
+        transport: { link: { tx: { queue: { size: { data: 3 } } } } },
+      }"#,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(*config.transport().link().tx().queue().size().data(), 3);
+    assert_eq!(*config.transport().link().tx().queue().size().data_low(), 1);
+
+    let resolved = config.resolved();
+    assert_eq!(
+        resolved.scouting().multicast().listen().client(),
+        Some(&false)
+    );
+    // The client profile scouts for a shorter time than the default
+    assert_eq!(*resolved.scouting().timeout(), Some(2000));
+    assert_eq!(
+        *resolved.scouting().rendezvous().timeout(),
+        Some(defaults::scouting::rendezvous::timeout)
+    );
+    assert_eq!(*Config::default().resolved().mode(), Some(defaults::mode));
+}
+
+#[test]
+fn config_profiles_by_mode() {
+    // The profile of the default mode only states the default values
+    let default = Config::default();
+    let mut default_profile = Config::profile(defaults::mode);
+    default_profile.set_id(*default.id()).unwrap();
+    assert_eq!(
+        default.resolved().diff(&default_profile.resolved()),
+        serde_json::json!({})
+    );
+
+    for mode in [WhatAmI::Router, WhatAmI::Peer, WhatAmI::Client] {
+        let mut set_mode = Config::default();
+        set_mode.set_mode(Some(mode)).unwrap();
+        let mut default_set_mode = crate::default();
+        default_set_mode.set_mode(Some(mode)).unwrap();
+        let mut file: Value =
+            json5::from_str(r#"{ transport: { link: { tx: { lease: 1000 } } } }"#).unwrap();
+        file["mode"] = serde_json::to_value(mode).unwrap();
+        let from_file = Config::from_value_with_profile(file).unwrap();
+        for config in [Config::profile(mode), set_mode, default_set_mode, from_file] {
+            let resolved = serde_json::to_value(config.resolved()).unwrap();
+            for (path, value) in profile_settings(mode) {
+                let expected = match path.as_str() {
+                    "transport/link/tx/lease" if config.transport.link.tx.lease == 1000 => {
+                        1000.into()
+                    }
+                    _ => value,
+                };
+                assert_eq!(
+                    resolved.pointer(&format!("/{path}")),
+                    Some(&expected),
+                    "{path} in {mode} mode"
+                );
+            }
+        }
+    }
+    assert_eq!(
+        *crate::client::<_, EndPoint>([])
+            .with_profile()
+            .transport()
+            .link()
+            .tx()
+            .lease(),
+        15000
+    );
+
+    // A value set explicitly is kept, even if equal to the default one or to the one of the default mode's profile
+    for mode in [None, Some("client")] {
+        let mut file: Value =
+            json5::from_str(r#"{ transport: { link: { tx: { lease: 10000 } } } }"#).unwrap();
+        if let Some(mode) = mode {
+            file["mode"] = mode.into();
+        }
+        let mut config = Config::from_value_with_profile(file).unwrap();
+        config.set_mode(Some(WhatAmI::Client)).unwrap();
+        let config = config.with_profile();
+        assert_eq!(*config.transport().link().tx().lease(), 10000, "{mode:?}");
+        assert_eq!(*config.transport().link().tx().queue().size().data(), 2);
+    }
+}
+
 pub type Notification = Arc<str>;
 
 struct NotifierInner<T> {
     inner: Mutex<T>,
+    // The value the notifier was created with
+    initial: T,
     subscribers: Mutex<Vec<flume::Sender<Notification>>>,
+    history: Mutex<History>,
+}
+
+/// The last changes accepted by a [`Notifier`], associated to the version they were accepted at.
+///
+/// Rather than copies of the previous values, only what is needed to undo each change is kept, the previous values
+/// being rebuilt from the current one on demand.
+struct History {
+    size: usize,
+    version: u64,
+    changes: VecDeque<(u64, Undo)>,
+}
+impl History {
+    /// Takes what is needed to undo a change at `keys` of `value`, unless no history is kept.
+    fn before<T: ValidatedMap>(&self, value: &T, keys: &[&str]) -> Option<Undo> {
+        (self.size > 0).then(|| Undo::before(value, keys))
+    }
+
+    fn push(&mut self, undo: Option<Undo>) {
+        if let Some(undo) = undo.filter(|_| self.size > 0) {
+            if self.changes.len() >= self.size {
+                self.changes.pop_front();
+            }
+            self.changes.push_back((self.version, undo));
+        }
+        self.version += 1;
+    }
+}
+
+/// The previous values, as JSON, of the sections of a value a change affected, `None` standing for an absent one.
+///
+/// A section is the one of a plugin for the plugins' settings, whose paths may be created by a change, and otherwise
+/// the closest setting to the changed one that can be read (e.g. `connect/endpoints` for `connect/endpoints/0`), so
+/// that it is always restored with its previous value.
+struct Undo(Vec<(String, Option<String>)>);
+impl Undo {
+    fn sections<T: ValidatedMap>(value: &T, key: &str) -> Vec<String> {
+        let key = key.trim_start_matches('/');
+        let mut split = key.split('/');
+        match (split.next(), split.next()) {
+            (Some("plugins"), Some(plugin)) => vec![format!("plugins/{plugin}")],
+            (Some("plugins"), None) => value
+                .get_json("plugins")
+                .ok()
+                .and_then(|plugins| serde_json::from_str::<serde_json::Map<_, _>>(&plugins).ok())
+                .map(|plugins| plugins.keys().map(|p| format!("plugins/{p}")).collect())
+                .unwrap_or_default(),
+            (None | Some(""), _) => vec![],
+            (Some(_), _) => {
+                let mut section = key;
+                while value.get_json(section).is_err() {
+                    match section.rsplit_once('/') {
+                        Some((parent, _)) => section = parent,
+                        None => return vec![],
+                    }
+                }
+                vec![section.to_string()]
+            }
+        }
+    }
+
+    fn before<T: ValidatedMap>(value: &T, keys: &[&str]) -> Self {
+        let mut previous = Vec::new();
+        for key in keys {
+            for section in Self::sections(value, key) {
+                let json = value.get_json(&section).ok();
+                previous.push((section, json));
+            }
+        }
+        Undo(previous)
+    }
+
+    /// Completes with the sections the change at `keys` added to `value`.
+    fn after<T: ValidatedMap>(mut self, value: &T, keys: &[&str]) -> Self {
+        for key in keys {
+            for section in Self::sections(value, key) {
+                if !self.0.iter().any(|(s, _)| *s == section) {
+                    self.0.push((section, None));
+                }
+            }
+        }
+        self
+    }
+
+    /// Undoes the change on `config`, whose plugins' validators should have been removed.
+    fn apply(&self, config: &mut Config) -> ZResult<()> {
+        for (section, previous) in &self.0 {
+            match previous {
+                Some(json) => config.insert_json5(section, json)?,
+                None => config.remove(section)?,
+            }
+        }
+        Ok(())
+    }
 }
 pub struct Notifier<T> {
     inner: Arc<NotifierInner<T>>,
@@ -628,21 +1417,129 @@ impl Notifier<Config> {
     fn _remove(&self, key: &str) -> ZResult<()> {
         {
             let mut guard = zlock!(self.inner.inner);
+            let undo = zlock!(self.inner.history).before(&*guard, &[key]);
             guard.remove(key)?;
+            zlock!(self.inner.history).push(undo);
         }
         self.notify(key);
         Ok(())
     }
+
+    /// Replaces the current configuration with the one accepted at `version` (see [`Notifier::history`]).
+    ///
+    /// Plugins' sections that differ from the current ones go through their validators, so that running plugins may
+    /// take them into account or refuse them, in which case the rollback is cancelled altogether.
+    pub fn rollback(&self, version: u64) -> ZResult<()> {
+        let changed = {
+            let mut guard = zlock!(self.inner.inner);
+            let target = {
+                let history = zlock!(self.inner.history);
+                let changes = history.changes.iter().rev();
+                let undone = changes
+                    .take_while(|(v, _)| *v >= version)
+                    .collect::<Vec<_>>();
+                match undone.last() {
+                    Some((v, _)) if *v == version => {
+                        let mut target = guard.clone();
+                        target.plugins.validators.clear();
+                        for (_, undo) in undone {
+                            undo.apply(&mut target)?;
+                        }
+                        target
+                    }
+                    _ => bail!("No configuration with version {} in history", version),
+                }
+            };
+            let mut new = target.clone();
+            new.plugins = guard.plugins.clone();
+            let current_plugins = guard.plugins.values.as_object().unwrap();
+            let target_plugins = target.plugins.values.as_object().unwrap();
+            for name in current_plugins.keys() {
+                if !target_plugins.contains_key(name) {
+                    new.plugins.remove(name)?;
+                }
+            }
+            for (name, value) in target_plugins {
+                if current_plugins.get(name) != Some(value) {
+                    new.plugins
+                        .insert(name, value.clone())
+                        .map_err(|e| zerror!("{}", e))?;
+                }
+            }
+
+            let current_json = serde_json::to_value(&*guard)?;
+            let new_json = serde_json::to_value(&new)?;
+            let mut changed = Vec::new();
+            if let (Value::Object(current_json), Value::Object(new_json)) = (current_json, new_json)
+            {
+                for (key, value) in new_json {
+                    if current_json.get(&key) != Some(&value) {
+                        changed.push(key);
+                    }
+                }
+            }
+            let keys = changed.iter().map(String::as_str).collect::<Vec<_>>();
+            let undo = zlock!(self.inner.history).before(&*guard, &keys);
+            *guard = new;
+            zlock!(self.inner.history).push(undo.map(|undo| undo.after(&*guard, &keys)));
+            changed
+        };
+        for key in changed {
+            self.notify(key);
+        }
+        Ok(())
+    }
+
+    /// Returns the previously accepted configurations still kept in the history, associated to their version, from
+    /// oldest to newest.
+    pub fn history(&self) -> Vec<(u64, Config)> {
+        let guard = zlock!(self.inner.inner);
+        let history = zlock!(self.inner.history);
+        let mut previous = guard.clone();
+        previous.plugins.validators.clear();
+        let mut configs = Vec::with_capacity(history.changes.len());
+        for (version, undo) in history.changes.iter().rev() {
+            // An older configuration is better left out than wrong
+            if undo.apply(&mut previous).is_err() {
+                break;
+            }
+            configs.push((*version, previous.clone()));
+        }
+        configs.reverse();
+        configs
+    }
 }
-impl<T: ValidatedMap> Notifier<T> {
+impl<T: ValidatedMap + Clone> Notifier<T> {
     pub fn new(inner: T) -> Self {
         Notifier {
             inner: Arc::new(NotifierInner {
+                initial: inner.clone(),
                 inner: Mutex::new(inner),
                 subscribers: Mutex::new(Vec::new()),
+                history: Mutex::new(History {
+                    size: 0,
+                    version: 0,
+                    changes: VecDeque::new(),
+                }),
             }),
         }
     }
+    /// Sets how many previously accepted values are kept in the history (none by default).
+    pub fn set_history_size(&self, size: usize) {
+        let mut history = zlock!(self.inner.history);
+        history.size = size;
+        while history.changes.len() > size {
+            history.changes.pop_front();
+        }
+    }
+    /// Returns the version of the current value, which is incremented each time a change is accepted.
+    pub fn version(&self) -> u64 {
+        zlock!(self.inner.history).version
+    }
+    /// Returns the value the notifier was created with, before any change.
+    pub fn initial(&self) -> T {
+        self.inner.initial.clone()
+    }
     pub fn subscribe(&self) -> flume::Receiver<Notification> {
         let (tx, rx) = flume::unbounded();
         {
@@ -679,7 +1576,7 @@ impl<'a, T: 'a> ValidatedMapAssociatedTypes<'a> for Notifier<T> {
 impl<'a, T: 'a> ValidatedMapAssociatedTypes<'a> for &Notifier<T> {
     type Accessor = GetGuard<'a, T>;
 }
-impl<T: ValidatedMap + 'static> ValidatedMap for Notifier<T>
+impl<T: ValidatedMap + Clone + 'static> ValidatedMap for Notifier<T>
 where
     T: for<'a> ValidatedMapAssociatedTypes<'a, Accessor = &'a dyn Any>,
 {
@@ -693,7 +1590,9 @@ where
     {
         {
             let mut guard = zlock!(self.inner.inner);
+            let undo = zlock!(self.inner.history).before(&*guard, &[key]);
             guard.insert(key, value)?;
+            zlock!(self.inner.history).push(undo.map(|undo| undo.after(&*guard, &[key])));
         }
         self.notify(key);
         Ok(())
@@ -719,7 +1618,7 @@ where
         self.lock().keys()
     }
 }
-impl<T: ValidatedMap + 'static> ValidatedMap for &Notifier<T>
+impl<T: ValidatedMap + Clone + 'static> ValidatedMap for &Notifier<T>
 where
     T: for<'a> ValidatedMapAssociatedTypes<'a, Accessor = &'a dyn Any>,
 {
@@ -733,7 +1632,9 @@ where
     {
         {
             let mut guard = zlock!(self.inner.inner);
+            let undo = zlock!(self.inner.history).before(&*guard, &[key]);
             guard.insert(key, value)?;
+            zlock!(self.inner.history).push(undo.map(|undo| undo.after(&*guard, &[key])));
         }
         self.notify(key);
         Ok(())
@@ -811,6 +1712,11 @@ fn user_conf_validator(u: &UsrPwdConf) -> bool {
     (u.password().is_none() && u.user().is_none()) || (u.password().is_some() && u.user().is_some())
 }
 
+fn token_conf_validator(t: &TokenConf) -> bool {
+    (t.token().is_none() || t.token_file().is_none())
+        && (t.secret().is_none() || t.public_key_file().is_none())
+}
+
 /// This part of the configuration is highly dynamic (any [`serde_json::Value`] may be put in there), but should follow this scheme:
 /// ```javascript
 /// plugins: {
@@ -818,11 +1724,25 @@ fn user_conf_validator(u: &UsrPwdConf) -> bool {
 ///     // dynamic library to load if no `__path__` is specified
 ///     [plugin_name]: {
 ///         // Defaults to `false`. Setting this to `true` does 2 things:
-///         // * If `zenohd` fails to locate the requested plugin, it will crash instead of logging an error.
+///         // * If `zenohd` fails to locate or to start the requested plugin, it will exit instead of logging an error
+///         //   (failures of optional plugins are reported in the adminspace under `@/router/<zid>/status/plugins/<plugin_name>/__error__`).
 ///         // * Plugins are expected to check this value to set their panic-behaviour: plugins are encouraged
 ///         //   to panic upon non-recoverable errors if their `__required__` flag is set to `true`, and to
 ///         //   simply log them otherwise
 ///         __required__: bool,
+///         // What `zenohd` does when a call into the running plugin panics: "disable" stops the plugin and reports
+///         // the panic under `@/router/<zid>/status/plugins/<plugin_name>/__error__`, "restart" does the same and then
+///         // restarts the plugin with an exponential backoff, and "abort" aborts `zenohd`.
+///         // Defaults to "abort" for required plugins, and to "disable" otherwise.
+///         // Catching panics requires `zenohd` to be built with `panic = "unwind"`: otherwise only "abort" is accepted.
+///         __on_panic__: "disable" | "restart" | "abort",
+///         // How many times `zenohd` restarts the plugin, after it panicked or its task terminated unexpectedly,
+///         // before giving up and disabling it. Restarts are reported under `@/router/<zid>/status/plugins/<plugin_name>/__restarts__`.
+///         // Defaults to 5.
+///         __max_restarts__: int,
+///         // The names of the plugins this plugin depends on: plugins are started after their dependencies, and stopped before them.
+///         // A plugin whose dependencies aren't all configured, or are part of a dependency cycle, isn't started.
+///         __dependencies__: [string],
 ///         // The path(s) where the plugin is expected to be located.
 ///         // If none is specified, `zenohd` will search for a `<dylib_prefix>zenoh_plugin_<plugin_name>.<dylib_suffix>` file in the search directories.
 ///         // If any path is specified, file-search will be disabled, and the first path leading to
@@ -861,6 +1781,207 @@ pub struct PluginLoad {
     pub name: String,
     pub paths: Option<Vec<String>>,
     pub required: bool,
+    pub on_panic: PluginPanicPolicy,
+    pub max_restarts: u32,
+    pub dependencies: Vec<String>,
+}
+
+/// The default value of plugins' `__max_restarts__` property.
+pub const DEFAULT_PLUGIN_MAX_RESTARTS: u32 = 5;
+
+/// Reads the load request of the plugin `name` from the reserved properties of its configuration `value`.
+fn load_request(name: &str, value: &Value) -> ZResult<PluginLoad> {
+    let Some(value) = value.as_object() else {
+        bail!(
+            "Plugin '{}' has an invalid configuration (must be an object)",
+            name
+        )
+    };
+    let required = match value.get("__required__") {
+        None => false,
+        Some(Value::Bool(b)) => *b,
+        _ => bail!(
+            "Plugin '{}' has an invalid '__required__' configuration property (must be a boolean)",
+            name
+        ),
+    };
+    let mut on_panic = match value.get("__on_panic__") {
+        None if required => PluginPanicPolicy::Abort,
+        None => PluginPanicPolicy::Disable,
+        Some(policy) => serde_json::from_value(policy.clone()).map_err(|_| zerror!("Plugin '{}' has an invalid '__on_panic__' configuration property (must be one of \"disable\", \"restart\" or \"abort\")", name))?
+    };
+    // Panics can only be caught when they unwind
+    if cfg!(panic = "abort") && on_panic != PluginPanicPolicy::Abort {
+        if value.contains_key("__on_panic__") {
+            bail!("Plugin '{}' has an '__on_panic__' configuration property other than \"abort\", which requires zenohd to be built with `panic = \"unwind\"`", name)
+        }
+        on_panic = PluginPanicPolicy::Abort;
+    }
+    let max_restarts = match value.get("__max_restarts__") {
+        None => DEFAULT_PLUGIN_MAX_RESTARTS,
+        Some(max) => serde_json::from_value(max.clone()).map_err(|_| zerror!("Plugin '{}' has an invalid '__max_restarts__' configuration property (must be a positive integer)", name))?
+    };
+    let dependencies = match value.get("__dependencies__") {
+        None => Vec::new(),
+        Some(dependencies) => serde_json::from_value(dependencies.clone()).map_err(|_| zerror!("Plugin '{}' has an invalid '__dependencies__' configuration property (must be an array of strings)", name))?
+    };
+    let paths = match value.get("__path__") {
+        None => None,
+        Some(Value::String(s)) => Some(vec![s.clone()]),
+        Some(Value::Array(a)) if a.iter().all(Value::is_string) => {
+            Some(a.iter().filter_map(|s| s.as_str().map(String::from)).collect())
+        }
+        Some(_) => bail!("Plugin '{}' has an invalid '__path__' configuration property (must be either string or array of strings)", name)
+    };
+    Ok(PluginLoad {
+        name: name.to_string(),
+        paths,
+        required,
+        on_panic,
+        max_restarts,
+        dependencies,
+    })
+}
+
+/// Whether an access control rule grants or denies its actions.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AclPermission {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// An action of a remote node subject to access control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AclAction {
+    /// Publishing data (puts and deletes).
+    Pub,
+    /// Declaring a subscriber.
+    Sub,
+    /// Querying.
+    Get,
+    /// Declaring a queryable.
+    Queryable,
+}
+
+/// An access control rule, granting or denying actions on key expressions to some remote nodes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AclRule {
+    /// The identities of the remote nodes the rule applies to, all of them if empty:
+    /// `zid:<zid>`, `username:<user>` (`transport/auth/usrpwd`), `token:<identity>` (`transport/auth/token`)
+    /// or `cert:<name>` (`transport/link/tls/client_auth`).
+    #[serde(default)]
+    pub identities: Vec<String>,
+    /// The key expressions the rule applies to.
+    #[schemars(with = "Vec<String>")]
+    pub key_exprs: Vec<OwnedKeyExpr>,
+    /// The actions the rule applies to.
+    pub actions: Vec<AclAction>,
+    pub permission: AclPermission,
+}
+
+/// The limits enforced on the messages received from some remote nodes, each one separately.
+///
+/// The publications exceeding a limit are dropped, and the queries exceeding it receive no reply.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QuotaRule {
+    /// The identities of the remote nodes the rule applies to, all of them if empty (see [`AclRule::identities`]).
+    #[serde(default)]
+    pub identities: Vec<String>,
+    /// The maximum number of publications per second of each node.
+    #[serde(default)]
+    pub max_publications_per_sec: Option<u64>,
+    /// The maximum number of payload bytes published per second by each node.
+    #[serde(default)]
+    pub max_bytes_per_sec: Option<u64>,
+    /// The maximum number of queries of each node awaiting their final reply.
+    #[serde(default)]
+    pub max_concurrent_queries: Option<u64>,
+}
+
+/// The namespace of some clients, confining them to the key expressions under its prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NamespaceRule {
+    /// The identities of the clients the rule applies to, all of them if empty (see [`AclRule::identities`]).
+    #[serde(default)]
+    pub identities: Vec<String>,
+    /// The prefix of the key expressions of the clients, which can't contain wildcards.
+    #[serde(deserialize_with = "deserialize_namespace_prefix")]
+    #[schemars(with = "String")]
+    pub prefix: OwnedKeyExpr,
+}
+
+fn deserialize_namespace_prefix<'a, D>(deserializer: D) -> Result<OwnedKeyExpr, D::Error>
+where
+    D: serde::de::Deserializer<'a>,
+{
+    let prefix = OwnedKeyExpr::deserialize(deserializer)?;
+    if prefix.is_wild() {
+        return Err(de::Error::custom(format!(
+            "The namespace prefix {prefix} contains wildcards"
+        )));
+    }
+    Ok(prefix)
+}
+
+/// The QoS a router applies to the publications it receives on some key expression.
+///
+/// The QoS settings left unset are kept as published.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QosOverride {
+    /// The key expression the override applies to.
+    #[schemars(with = "String")]
+    pub keyexpr: OwnedKeyExpr,
+    #[serde(default)]
+    pub priority: Option<QosPriority>,
+    #[serde(default)]
+    pub congestion_control: Option<QosCongestionControl>,
+    /// Whether the publications are sent without being batched.
+    #[serde(default)]
+    pub express: Option<bool>,
+}
+
+/// The priority of a publication, from the highest to the lowest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QosPriority {
+    RealTime,
+    InteractiveHigh,
+    InteractiveLow,
+    DataHigh,
+    Data,
+    DataLow,
+    Background,
+}
+
+/// What happens to a publication when the transmission queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QosCongestionControl {
+    /// The publication is dropped.
+    Drop,
+    /// The sending blocks until there is room in the queue.
+    Block,
+}
+
+/// What `zenohd` does when a call into a running plugin panics, as set by the plugin's `__on_panic__` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginPanicPolicy {
+    /// Stop the plugin and report the panic in the adminspace.
+    Disable,
+    /// Stop the plugin, report the panic in the adminspace and restart the plugin after a backoff delay.
+    Restart,
+    /// Abort `zenohd`.
+    Abort,
 }
 impl PluginsConfig {
     pub fn sift_privates(&mut self) {
@@ -872,28 +1993,57 @@ impl PluginsConfig {
         };
         for (name, value) in values.iter_mut() {
             load_external_plugin_config(format!("plugins.{}", name.as_str()).as_str(), value)?;
+            load_request(name, value)?;
         }
         Ok(())
     }
-    pub fn load_requests(&'_ self) -> impl Iterator<Item = PluginLoad> + '_ {
-        self.values.as_object().unwrap().iter().map(|(name, value)| {
-            let value = value.as_object().expect("Plugin configurations must be objects");
-            let required = match value.get("__required__") {
-                None => false,
-                Some(Value::Bool(b)) => *b,
-                _ => panic!("Plugin '{}' has an invalid '__required__' configuration property (must be a boolean)", name)
-            };
-            if let Some(paths) = value.get("__path__"){
-                let paths = match paths {
-                    Value::String(s) => vec![s.clone()],
-                    Value::Array(a) => a.iter().map(|s| if let Value::String(s) = s {s.clone()} else {panic!("Plugin '{}' has an invalid '__path__' configuration property (must be either string or array of strings)", name)}).collect(),
-                    _ => panic!("Plugin '{}' has an invalid '__path__' configuration property (must be either string or array of strings)", name)
-                };
-                PluginLoad {name: name.clone(), paths: Some(paths), required}
-            } else {
-                PluginLoad {name: name.clone(), paths: None, required}
+    /// Returns the plugins' load requests, as set by their reserved properties, an error for the plugins whose
+    /// reserved properties are invalid.
+    pub fn load_requests(&'_ self) -> impl Iterator<Item = ZResult<PluginLoad>> + '_ {
+        self.values
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(name, value)| load_request(name, value))
+    }
+    /// Returns the plugins' load requests ordered so that each plugin comes after its dependencies, along with the
+    /// requests of the plugins whose dependencies can't be satisfied and the corresponding error.
+    pub fn ordered_load_requests(&self) -> ZResult<(Vec<PluginLoad>, Vec<(PluginLoad, String)>)> {
+        let mut pending = self.load_requests().collect::<ZResult<Vec<_>>>()?;
+        let mut ordered: Vec<PluginLoad> = Vec::with_capacity(pending.len());
+        loop {
+            let (ready, blocked): (Vec<_>, Vec<_>) = pending.into_iter().partition(|request| {
+                request
+                    .dependencies
+                    .iter()
+                    .all(|dependency| ordered.iter().any(|r| &r.name == dependency))
+            });
+            pending = blocked;
+            if ready.is_empty() {
+                break;
             }
-        })
+            ordered.extend(ready);
+        }
+        let failed = pending
+            .iter()
+            .map(|request| {
+                let error = request
+                    .dependencies
+                    .iter()
+                    .find_map(|dependency| {
+                        if pending.iter().any(|r| &r.name == dependency) {
+                            Some(format!("depends on `{dependency}`, whose dependencies can't be satisfied (they are missing or cyclic)"))
+                        } else if !ordered.iter().any(|r| &r.name == dependency) {
+                            Some(format!("depends on `{dependency}`, which isn't configured"))
+                        } else {
+                            None
+                        }
+                    })
+                    .unwrap_or_default();
+                (request.clone(), error)
+            })
+            .collect();
+        Ok((ordered, failed))
     }
     pub fn remove(&mut self, key: &str) -> ZResult<()> {
         let mut split = key.split('/');
@@ -986,18 +2136,121 @@ impl<'a> serde::Deserialize<'a> for PluginsConfig {
     where
         D: serde::Deserializer<'a>,
     {
+        let values: Value = serde::Deserialize::deserialize(deserializer)?;
+        let Some(plugins) = values.as_object() else {
+            return Err(serde::de::Error::custom(
+                "plugins configuration must be an object",
+            ));
+        };
+        for (name, value) in plugins {
+            load_request(name, value).map_err(<D::Error as serde::de::Error>::custom)?;
+        }
         Ok(PluginsConfig {
             validators: Default::default(),
-            values: serde::Deserialize::deserialize(deserializer)?,
+            values,
         })
     }
 }
+impl schemars::JsonSchema for PluginsConfig {
+    fn schema_name() -> String {
+        "PluginsConfig".into()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::{
+            InstanceType, ObjectValidation, Schema, SchemaObject, SubschemaValidation,
+        };
+        // Plugins are free to define their own configuration, only the reserved properties are described here.
+        let mut plugin = SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            ..Default::default()
+        };
+        let properties = &mut plugin.object().properties;
+        properties.insert("__required__".into(), gen.subschema_for::<bool>());
+        properties.insert(
+            "__on_panic__".into(),
+            gen.subschema_for::<PluginPanicPolicy>(),
+        );
+        properties.insert("__max_restarts__".into(), gen.subschema_for::<u32>());
+        properties.insert(
+            "__dependencies__".into(),
+            gen.subschema_for::<Vec<String>>(),
+        );
+        properties.insert(
+            "__path__".into(),
+            SchemaObject {
+                subschemas: Some(Box::new(SubschemaValidation {
+                    any_of: Some(vec![
+                        gen.subschema_for::<String>(),
+                        gen.subschema_for::<Vec<String>>(),
+                    ]),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }
+            .into(),
+        );
+        properties.insert("__config__".into(), gen.subschema_for::<String>());
+        SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            object: Some(Box::new(ObjectValidation {
+                additional_properties: Some(Box::new(Schema::Object(plugin))),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
 impl std::fmt::Debug for PluginsConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", &self.values)
     }
 }
 
+/// The settings of the [profile](defaults::profile) of `mode`, as their paths (e.g. `transport/link/tx/lease`)
+/// and values.
+fn profile_settings(mode: WhatAmI) -> Vec<(String, Value)> {
+    fn flatten(path: String, value: Value, settings: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    let path = match path.as_str() {
+                        "" => key,
+                        _ => format!("{path}/{key}"),
+                    };
+                    flatten(path, value, settings);
+                }
+            }
+            value => settings.push((path, value)),
+        }
+    }
+    let mut settings = vec![];
+    flatten(
+        String::new(),
+        json5::from_str(defaults::profile::get(mode)).unwrap(),
+        &mut settings,
+    );
+    settings
+}
+
+/// Recursively merges `value` into `base`, the properties of `value` taking precedence.
+fn merge_values(base: &mut Value, value: Value) {
+    match (base, value) {
+        (Value::Object(base), Value::Object(value)) => {
+            for (key, value) in value {
+                match base.get_mut(&key) {
+                    Some(base) => merge_values(base, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
+
 trait PartialMerge: Sized {
     fn merge(self, path: &str, value: Self) -> Result<Self, validated_struct::InsertionError>;
 }
@@ -1075,13 +2328,12 @@ impl validated_struct::ValidatedMap for PluginsConfig {
         let (plugin, key) = validated_struct::split_once(key, '/');
         let validator = self.validators.get(plugin);
         let new_value: Value = serde::Deserialize::deserialize(deserializer)?;
-        let value = self
-            .values
-            .as_object_mut()
-            .unwrap()
-            .entry(plugin)
-            .or_insert(Value::Null);
+        let value = self.values.get(plugin).cloned().unwrap_or(Value::Null);
         let mut new_value = value.clone().merge(key, new_value)?;
+        // The reserved properties are checked before the plugin gets to validate its new configuration
+        if let Err(e) = load_request(plugin, &new_value) {
+            return Err(format!("{e}").into());
+        }
         if let Some(validator) = validator {
             match validator(
                 key,
@@ -1093,7 +2345,10 @@ impl validated_struct::ValidatedMap for PluginsConfig {
                 Err(e) => return Err(format!("{e}").into()),
             }
         }
-        *value = new_value;
+        self.values
+            .as_object_mut()
+            .unwrap()
+            .insert(plugin.to_string(), new_value);
         Ok(())
     }
     fn get<'a>(&'a self, mut key: &str) -> Result<&'a dyn Any, GetError> {
@@ -1174,7 +2429,7 @@ pub trait ModeDependent<T> {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct ModeValues<T> {
     #[serde(skip_serializing_if = "Option::is_none")]
     router: Option<T>,
@@ -1233,6 +2488,27 @@ impl<T> ModeDependent<T> for ModeDependentValue<T> {
     }
 }
 
+impl<T: schemars::JsonSchema> schemars::JsonSchema for ModeDependentValue<T> {
+    fn schema_name() -> String {
+        format!("ModeDependentValue_{}", T::schema_name())
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::{SchemaObject, SubschemaValidation};
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![
+                    gen.subschema_for::<T>(),
+                    gen.subschema_for::<ModeValues<T>>(),
+                ]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 impl<T> serde::Serialize for ModeDependentValue<T>
 where
     T: Serialize,
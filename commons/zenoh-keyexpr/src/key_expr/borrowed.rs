@@ -36,6 +36,8 @@ use zenoh_result::{bail, Error as ZError, ZResult};
 /// * Key expressions may never start or end with `'/'`, nor contain `"//"` or any of the following characters: `#$?`
 /// * Key expression must be in canon-form (this ensure that key expressions representing the same set are always the same string).  
 ///   Note that safe constructors will perform canonization for you if this can be done without extraneous allocations.
+/// * Chunks starting with `@` are verbatim: they may not contain wildcards, and are only matched by identical chunks,
+///   never by `*` or `**` (e.g. `**` doesn't intersect `@/router/a`, but `@/**` does).
 ///
 /// Since Key Expressions define sets of keys, you may want to be aware of the hierarchy of [relations](keyexpr::relation_to) between such sets:
 /// * Trivially, two sets can have no elements in common: `a/**` and `b/**` for example define two disjoint sets of keys.
@@ -124,6 +126,11 @@ impl keyexpr {
         OwnedKeyExpr::autocanonize(format!("{}/{}", self, other.as_ref()))
     }
 
+    /// Returns `true` if `self` contains any verbatim chunk (starting with `@`), which wildcards never match.
+    pub fn has_verbatim(&self) -> bool {
+        super::utils::has_verbatim(self.as_bytes())
+    }
+
     /// Returns `true` if `self` contains any wildcard character (`**` or `$*`).
     pub fn is_wild(&self) -> bool {
         self.0.contains(super::SINGLE_WILD as char)
@@ -344,6 +351,7 @@ enum KeyExprConstructionError {
     DollarAfterDollarOrStar = -6,
     ContainsSharpOrQMark = -7,
     ContainsUnboundDollar = -8,
+    VerbatimContainsWildcard = -9,
 }
 
 impl<'a> TryFrom<&'a str> for &'a keyexpr {
@@ -355,6 +363,12 @@ impl<'a> TryFrom<&'a str> for &'a keyexpr {
             if chunk.is_empty() {
                 bail!((KeyExprConstructionError::EmpyChunk) "Invalid Key Expr `{}`: empty chunks are forbidden, as well as leading and trailing slashes", value)
             }
+            if chunk.starts_with('@') && chunk.contains('*') {
+                bail!((KeyExprConstructionError::VerbatimContainsWildcard)
+                    "Invalid Key Expr `{}`: verbatim chunks (starting with `@`) may not contain wildcards",
+                    value
+                )
+            }
             if chunk == "$*" {
                 bail!((KeyExprConstructionError::LoneDollarStar)
                     "Invalid Key Expr `{}`: lone `$*`s must be replaced by `*` to reach canon-form",
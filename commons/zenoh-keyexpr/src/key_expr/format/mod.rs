@@ -32,6 +32,7 @@ use support::{IterativeConstructor, Spec};
 /// Formats are written like KEs, except sections can be substituted for specs using the `${id:pattern#default}` format to define fields.  
 /// `id` is the name of the field that gets encoded in that section, it must be non-empty and will stop at the first encountered `:`.  
 /// `pattern` is a KE pattern that any value set for that field must match. It stops at the first encountered `#` or end of spec.  
+/// `${id}` is a shorthand for `${id:*}`, i.e. a field holding a single chunk.  
 /// `default` is optional, and lets you specify a value at construction for the field.
 ///
 /// Note that the spec is considered to end at the first encountered `}`; if you need your id, pattern or default to contain `}`, you may use `$#{spec}#.
@@ -497,6 +498,23 @@ fn formatting() {
         .try_into()
         .unwrap();
     assert_eq!(ke.as_str(), "a/1/b/c");
+
+    let format = KeFormat::new("factory/${line}/sensor/${id}").unwrap();
+    assert_eq!(format.storage[0].spec.id(), "line");
+    assert_eq!(format.storage[0].spec.pattern(), "*");
+    assert!(format.storage[0].spec.default().is_none());
+    let ke: OwnedKeyExpr = format
+        .formatter()
+        .set("line", "a")
+        .unwrap()
+        .set("id", 3)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert_eq!(ke.as_str(), "factory/a/sensor/3");
+    assert!(format.formatter().set("line", "a/b").is_err());
+    assert!(KeFormat::new("factory/${}/sensor").is_err());
+    assert!(KeFormat::new("factory/${:*}/sensor").is_err());
 }
 
 mod parsing;
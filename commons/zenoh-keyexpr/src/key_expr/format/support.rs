@@ -29,8 +29,22 @@ impl<'a> TryFrom<&'a str> for Spec<'a> {
     type Error = Error;
     fn try_from(spec: &'a str) -> Result<Self, Self::Error> {
         let Some(id_end) = spec.find(':') else {
-            bail!("Spec {spec} didn't contain `:`")
+            // `${id}` is a shorthand for `${id:*}`
+            if spec.is_empty() {
+                bail!("Spec {spec} has an empty id")
+            }
+            let Ok(id_end) = spec.len().try_into() else {
+                bail!("Spec {spec} contains an id longer than {}", u16::MAX)
+            };
+            return Ok(Self {
+                spec,
+                id_end,
+                pattern_end: u16::MAX,
+            });
         };
+        if id_end == 0 {
+            bail!("Spec {spec} has an empty id")
+        }
         let pattern_start = id_end + 1;
         let pattern_end = spec[pattern_start..].find('#').unwrap_or(u16::MAX as usize);
         if pattern_start < spec.len() {
@@ -55,6 +69,9 @@ impl<'a> Spec<'a> {
         &self.spec[..self.id_end as usize]
     }
     pub fn pattern(&self) -> &keyexpr {
+        if self.id_end as usize == self.spec.len() {
+            return unsafe { keyexpr::from_str_unchecked("*") };
+        }
         unsafe {
             keyexpr::from_str_unchecked(if self.pattern_end != u16::MAX {
                 &self.spec[(self.id_end + 1) as usize..self.pattern_end as usize]
@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::OwnedKeyExpr;
+use super::{utils::is_verbatim, OwnedKeyExpr};
 
 fn random_chunk(rng: &'_ mut impl rand::Rng) -> impl Iterator<Item = u8> + '_ {
     let n = rng.gen_range(1..3);
@@ -26,7 +26,9 @@ fn make(ke: &mut Vec<u8>, rng: &mut impl rand::Rng) {
             0..=15 => ke.extend(b"/**"),
             16..=31 => ke.extend(b"/*"),
             32..=47 => {
-                if !ke.is_empty() && !ke.ends_with(b"*") {
+                // verbatim chunks may not contain wildcards
+                let verbatim = ke.rsplit(|c| *c == b'/').next().map_or(false, is_verbatim);
+                if !ke.is_empty() && !ke.ends_with(b"*") && !verbatim {
                     ke.extend(b"$*")
                 } else {
                     continue;
@@ -34,7 +36,10 @@ fn make(ke: &mut Vec<u8>, rng: &mut impl rand::Rng) {
             }
             48.. => {
                 if n >= 128 || ke.ends_with(b"**") || ke.ends_with(b"/*") {
-                    ke.push(b'/')
+                    ke.push(b'/');
+                    if n >= 240 {
+                        ke.push(b'@')
+                    }
                 }
                 ke.extend(random_chunk(rng))
             }
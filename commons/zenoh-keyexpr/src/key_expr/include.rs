@@ -11,7 +11,11 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::{keyexpr, utils::Split, DELIMITER, DOUBLE_WILD, STAR_DSL};
+use super::{
+    keyexpr,
+    utils::{has_verbatim, is_verbatim, Split},
+    DELIMITER, DOUBLE_WILD, STAR_DSL,
+};
 
 pub const DEFAULT_INCLUDER: LTRIncluder = LTRIncluder;
 
@@ -24,9 +28,12 @@ impl<T: for<'a> Includer<&'a [u8], &'a [u8]>> Includer<&keyexpr, &keyexpr> for T
     fn includes(&self, left: &keyexpr, right: &keyexpr) -> bool {
         let left = left.as_bytes();
         let right = right.as_bytes();
-        if left == right || left == b"**" {
+        if left == right {
             return true;
         }
+        if left == b"**" {
+            return !has_verbatim(right);
+        }
         self.includes(left, right)
     }
 }
@@ -38,10 +45,18 @@ impl Includer<&[u8], &[u8]> for LTRIncluder {
             let (lchunk, lrest) = left.split_once(&DELIMITER);
             let lempty = lrest.is_empty();
             if lchunk == DOUBLE_WILD {
-                if lempty || self.includes(lrest, right) {
+                if lempty {
+                    return !has_verbatim(right);
+                }
+                if self.includes(lrest, right) {
                     return true;
                 }
-                right = right.split_once(&DELIMITER).1;
+                // `**` may not consume verbatim chunks
+                let (rchunk, rrest) = right.split_once(&DELIMITER);
+                if is_verbatim(rchunk) {
+                    return false;
+                }
+                right = rrest;
                 if right.is_empty() {
                     return false;
                 }
@@ -63,7 +78,11 @@ impl Includer<&[u8], &[u8]> for LTRIncluder {
 
 impl LTRIncluder {
     fn non_double_wild_chunk_includes(&self, lchunk: &[u8], rchunk: &[u8]) -> bool {
-        if lchunk == b"*" || lchunk == rchunk {
+        if lchunk == rchunk {
+            true
+        } else if is_verbatim(lchunk) || is_verbatim(rchunk) {
+            false
+        } else if lchunk == b"*" {
             true
         } else if lchunk.contains(&b'$') {
             let mut spleft = lchunk.splitter(STAR_DSL);
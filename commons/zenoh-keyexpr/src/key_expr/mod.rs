@@ -18,6 +18,7 @@ pub(crate) const DELIMITER: u8 = b'/';
 pub(crate) const SINGLE_WILD: u8 = b'*';
 pub(crate) const DOUBLE_WILD: &[u8] = b"**";
 pub(crate) const STAR_DSL: &[u8] = b"$*";
+pub(crate) const VERBATIM: u8 = b'@';
 pub(crate) const FORBIDDEN_CHARS: [u8; 3] = [b'#', b'?', b'$'];
 
 pub(crate) mod owned;
@@ -84,6 +84,17 @@ fn intersections() {
     assert!(intersect("x/a$*d$*e", "x/ade"));
     assert!(!intersect("x/c$*", "x/abc$*"));
     assert!(!intersect("x/$*d", "x/$*e"));
+    assert!(intersect("@a", "@a"));
+    assert!(!intersect("*", "@a"));
+    assert!(!intersect("**", "@a"));
+    assert!(!intersect("**", "x/@a/y"));
+    assert!(!intersect("$*a", "@a"));
+    assert!(intersect("@a/**", "@a/x/y"));
+    assert!(!intersect("@a/**", "@a/x/@b"));
+    assert!(intersect("@a/**/@b", "@a/x/@b"));
+    assert!(intersect("**/@b", "a/**/@b"));
+    assert!(!intersect("**/@b", "**/@c"));
+    assert!(!intersect("*/**", "@/x"));
 }
 
 fn includes<
@@ -146,6 +157,25 @@ fn inclusions() {
     assert!(!includes("x/c$*", "x/abc$*"));
     assert!(includes("x/$*c$*", "x/abc$*"));
     assert!(!includes("x/$*d", "x/$*e"));
+    assert!(includes("@a", "@a"));
+    assert!(!includes("*", "@a"));
+    assert!(!includes("**", "@a"));
+    assert!(!includes("**", "x/@a"));
+    assert!(includes("@a/**", "@a/x/y"));
+    assert!(!includes("@a/**", "@a/x/@b"));
+    assert!(includes("@a/**/@b", "@a/x/@b"));
+    assert!(includes("**/@b", "x/y/@b"));
+    assert!(!includes("**/@b", "@x/y/@b"));
+}
+
+#[test]
+fn verbatim() {
+    assert!(keyexpr::new("@/router/a").unwrap().has_verbatim());
+    assert!(keyexpr::new("a/@b").unwrap().has_verbatim());
+    assert!(!keyexpr::new("a/b@").unwrap().has_verbatim());
+    assert!(keyexpr::new("@a/**").is_ok());
+    assert!(keyexpr::new("@*").is_err());
+    assert!(keyexpr::new("a/@b$*").is_err());
 }
 
 #[test]
@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::{DELIMITER, VERBATIM};
 use core::{ptr, str};
 
 pub(crate) struct Writer {
@@ -228,6 +229,17 @@ impl<const N: usize> Split<[u8; N]> for [u8] {
     }
 }
 
+/// Returns `true` if `chunk` is verbatim (starts with `@`): it may only be matched by an identical chunk.
+#[inline(always)]
+pub(crate) fn is_verbatim(chunk: &[u8]) -> bool {
+    chunk.first() == Some(&VERBATIM)
+}
+
+/// Returns `true` if any of the chunks of `ke` is verbatim.
+pub(crate) fn has_verbatim(ke: &[u8]) -> bool {
+    is_verbatim(ke) || ke.windows(2).any(|w| w == [DELIMITER, VERBATIM])
+}
+
 pub(crate) trait Utf {
     fn utf(&self) -> &str;
 }
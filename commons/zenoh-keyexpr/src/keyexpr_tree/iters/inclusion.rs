@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::key_expr::utils::is_verbatim;
 use crate::keyexpr_tree::*;
 use alloc::vec::Vec;
 use zenoh_result::unlikely;
@@ -107,8 +108,11 @@ where
                                 let subkey =
                                     unsafe { keyexpr::from_slice_unchecked(&key[..kec_end]) };
                                 if unlikely(subkey == "**") {
-                                    push!(kec_start);
-                                    push!(kec_start + kec_end + 1);
+                                    // `**` may not consume verbatim chunks
+                                    if !is_verbatim(chunk.as_bytes()) {
+                                        push!(kec_start);
+                                        push!(kec_start + kec_end + 1);
+                                    }
                                     let post_key = &key[kec_end + 1..];
                                     match post_key.iter().position(|&c| c == b'/') {
                                         Some(sec_end) => {
@@ -133,7 +137,7 @@ where
                             }
                             None => {
                                 let key = unsafe { keyexpr::from_slice_unchecked(key) };
-                                if unlikely(key == "**") {
+                                if unlikely(key == "**") && !is_verbatim(chunk.as_bytes()) {
                                     push!(kec_start);
                                     node_matches = true;
                                 } else if key.includes(chunk) {
@@ -267,8 +271,11 @@ where
                                 let subkey =
                                     unsafe { keyexpr::from_slice_unchecked(&key[..kec_end]) };
                                 if unlikely(subkey == "**") {
-                                    push!(kec_start);
-                                    push!(kec_start + kec_end + 1);
+                                    // `**` may not consume verbatim chunks
+                                    if !is_verbatim(chunk.as_bytes()) {
+                                        push!(kec_start);
+                                        push!(kec_start + kec_end + 1);
+                                    }
                                     let post_key = &key[kec_end + 1..];
                                     match post_key.iter().position(|&c| c == b'/') {
                                         Some(sec_end) => {
@@ -293,7 +300,7 @@ where
                             }
                             None => {
                                 let key = unsafe { keyexpr::from_slice_unchecked(key) };
-                                if unlikely(key == "**") {
+                                if unlikely(key == "**") && !is_verbatim(chunk.as_bytes()) {
                                     push!(kec_start);
                                     node_matches = true;
                                 } else if key.includes(chunk) {
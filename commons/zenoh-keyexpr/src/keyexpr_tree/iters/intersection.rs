@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use crate::key_expr::utils::is_verbatim;
 use crate::keyexpr_tree::*;
 use alloc::vec::Vec;
 use zenoh_result::unlikely;
@@ -96,13 +97,30 @@ where
                     }
                     let chunk = node.chunk();
                     if unlikely(chunk.as_bytes() == b"**") {
-                        // If the current node is `**`, it is guaranteed to match
-                        node_matches = true;
-                        // and may consume any number of chunks from the KE
-                        push!(self.ke_indices[*start]);
-                        for i in self.ke_indices[*start]..self.key.len() {
-                            if self.key.as_bytes()[i] == b'/' {
-                                push!(i + 1);
+                        // If the current node is `**`, it may consume any number of chunks from the KE
+                        // up to its first verbatim chunk, and matches if it may consume all of them
+                        for i in *start..*end {
+                            let mut kec_start = self.ke_indices[i];
+                            push!(kec_start);
+                            loop {
+                                let key = &self.key.as_bytes()[kec_start..];
+                                if key.is_empty() {
+                                    node_matches = true;
+                                    break;
+                                }
+                                if is_verbatim(key) {
+                                    break;
+                                }
+                                match key.iter().position(|&c| c == b'/') {
+                                    Some(kec_end) => {
+                                        kec_start += kec_end + 1;
+                                        push!(kec_start);
+                                    }
+                                    None => {
+                                        node_matches = true;
+                                        break;
+                                    }
+                                }
                             }
                         }
                     } else {
@@ -122,8 +140,10 @@ where
                                         unsafe { keyexpr::from_slice_unchecked(&key[..kec_end]) };
                                     if unlikely(subkey.as_bytes() == b"**") {
                                         // If the chunk is `**`:
-                                        // children will have to process it again
-                                        push!(kec_start);
+                                        // children will have to process it again, unless it may not consume the verbatim chunk
+                                        if !is_verbatim(chunk.as_bytes()) {
+                                            push!(kec_start);
+                                        }
                                         // and we need to process this chunk as if the `**` wasn't there,
                                         // but with the knowledge that the next chunk won't be `**`.
                                         let post_key = &key[kec_end + 1..];
@@ -155,7 +175,9 @@ where
                                 None => {
                                     // If it's the last chunk of the query, check whether it's `**`
                                     let key = unsafe { keyexpr::from_slice_unchecked(key) };
-                                    if unlikely(key.as_bytes() == b"**") {
+                                    if unlikely(key.as_bytes() == b"**")
+                                        && !is_verbatim(chunk.as_bytes())
+                                    {
                                         // If yes, it automatically matches, and must be reused from now on for iteration.
                                         push!(kec_start);
                                         node_matches = true;
@@ -287,11 +309,28 @@ where
                     }
                     let chunk = node.chunk();
                     if unlikely(chunk == "**") {
-                        node_matches = true;
-                        push!(self.ke_indices[*start]);
-                        for i in self.ke_indices[*start]..self.key.len() {
-                            if self.key.as_bytes()[i] == b'/' {
-                                push!(i + 1);
+                        for i in *start..*end {
+                            let mut kec_start = self.ke_indices[i];
+                            push!(kec_start);
+                            loop {
+                                let key = &self.key.as_bytes()[kec_start..];
+                                if key.is_empty() {
+                                    node_matches = true;
+                                    break;
+                                }
+                                if is_verbatim(key) {
+                                    break;
+                                }
+                                match key.iter().position(|&c| c == b'/') {
+                                    Some(kec_end) => {
+                                        kec_start += kec_end + 1;
+                                        push!(kec_start);
+                                    }
+                                    None => {
+                                        node_matches = true;
+                                        break;
+                                    }
+                                }
                             }
                         }
                     } else {
@@ -306,7 +345,9 @@ where
                                     let subkey =
                                         unsafe { keyexpr::from_slice_unchecked(&key[..kec_end]) };
                                     if unlikely(subkey == "**") {
-                                        push!(kec_start);
+                                        if !is_verbatim(chunk.as_bytes()) {
+                                            push!(kec_start);
+                                        }
                                         push!(kec_start + kec_end + 1);
                                         let post_key = &key[kec_end + 1..];
                                         match post_key.iter().position(|&c| c == b'/') {
@@ -336,7 +377,7 @@ where
                                 }
                                 None => {
                                     let key = unsafe { keyexpr::from_slice_unchecked(key) };
-                                    if unlikely(key == "**") {
+                                    if unlikely(key == "**") && !is_verbatim(chunk.as_bytes()) {
                                         push!(kec_start);
                                         node_matches = true;
                                     } else if chunk.intersects(key) {
@@ -70,13 +70,22 @@ pub fn unstable(_attr: TokenStream, item: TokenStream) -> TokenStream {
     })
 }
 
-fn keformat_support(source: &str) -> proc_macro2::TokenStream {
-    let format = match KeFormat::new(&source) {
-        Ok(format) => format,
-        Err(e) => panic!("{}", e),
-    };
+fn keformat_support(source: &str) -> Result<proc_macro2::TokenStream, String> {
+    let format = KeFormat::new(&source).map_err(|e| e.to_string())?;
     let specs = unsafe { macro_support::specs(&format) };
     let len = specs.len();
+    let ids = specs
+        .iter()
+        .map(|spec| {
+            let id = &source[spec.spec_start..(spec.spec_start + spec.id_end as usize)];
+            syn::parse_str::<syn::Ident>(id).map_err(|_| {
+                format!("Invalid KeFormat: the id `{id}` isn't a valid Rust identifier")
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let generics = (0..len)
+        .map(|i| quote::format_ident!("__T{}", i))
+        .collect::<Vec<_>>();
     let setters = specs.iter().map(|spec| {
         let id = &source[spec.spec_start..(spec.spec_start + spec.id_end as usize)];
         let set_id = quote::format_ident!("{}", id);
@@ -92,10 +101,17 @@ fn keformat_support(source: &str) -> proc_macro2::TokenStream {
     let getters = specs.iter().map(|spec| {
         let id = &source[spec.spec_start..(spec.spec_start + spec.id_end as usize)];
         let get_id = quote::format_ident!("{}", id);
+        let get_id_as = quote::format_ident!("{}_as", id);
+        let get_id_as_doc = format!("Parses the value of the `{id}` field, an empty string if it matched a sequence of 0 chunks.");
         quote! {
             pub fn #get_id (&self) -> Option<& ::zenoh::key_expr::keyexpr> {
                 unsafe {self._0.get(#id).unwrap_unchecked()}
             }
+            #[doc = #get_id_as_doc]
+            pub fn #get_id_as <T: ::core::str::FromStr>(&self) -> ZResult<T> where T::Err: ::core::fmt::Display {
+                let value = self.#get_id().map_or("", |ke| ke.as_str());
+                value.parse().map_err(|e| format!("Invalid value `{}` for the `{}` field: {}", value, #id, e).into())
+            }
         }
     });
     let segments = specs.iter().map(|spec| {
@@ -121,10 +137,13 @@ fn keformat_support(source: &str) -> proc_macro2::TokenStream {
         }
     });
 
+    let format_fn_doc = format!(
+        "Builds a key expression of the `{source}` format from the values of its fields, in the order of the format."
+    );
     let format_doc = format!("The `{source}` format, as a zero-sized-type.");
     let formatter_doc = format!("And instance of a formatter for `{source}`.");
 
-    quote! {
+    Ok(quote! {
             use ::zenoh::Result as ZResult;
             const FORMAT_INNER: ::zenoh::key_expr::format::KeFormat<'static, [::zenoh::key_expr::format::Segment<'static>; #len]> = unsafe {
                 ::zenoh::key_expr::format::macro_support::const_new(#source, [#(#segments)*])
@@ -177,6 +196,12 @@ fn keformat_support(source: &str) -> proc_macro2::TokenStream {
                 pub fn formatter() -> Formatter {
                     Formatter(Format.formatter())
                 }
+                #[doc = #format_fn_doc]
+                pub fn format<#(#generics: ::core::fmt::Display),*>(#(#ids: #generics),*) -> ZResult<::zenoh::key_expr::OwnedKeyExpr> {
+                    let mut __formatter = Format::formatter();
+                    #(__formatter.#ids(#ids)?;)*
+                    __formatter.build()
+                }
                 pub fn parse<'s>(target: &'s ::zenoh::key_expr::keyexpr) -> ZResult<Parsed<'s>> {
                     Ok(Parsed{_0: Format.parse(target)?})
                 }
@@ -187,10 +212,14 @@ fn keformat_support(source: &str) -> proc_macro2::TokenStream {
             pub fn formatter() -> Formatter {
                 Format::formatter()
             }
+            #[doc = #format_fn_doc]
+            pub fn format<#(#generics: ::core::fmt::Display),*>(#(#ids: #generics),*) -> ZResult<::zenoh::key_expr::OwnedKeyExpr> {
+                Format::format(#(#ids),*)
+            }
             pub fn parse<'s>(target: &'s ::zenoh::key_expr::keyexpr) -> ZResult<Parsed<'s>> {
                 Format::parse(target)
             }
-    }
+    })
 }
 
 struct FormatDeclaration {
@@ -226,6 +255,10 @@ impl syn::parse::Parse for FormatDeclarations {
 ///     - for every spec in your format, `Formatter` will have a method named after the spec's `id` that lets you set a value for that field of your format. These methods will return `Result<&mut Formatter, FormatError>`.
 /// - `parse(target: &keyexpr) -> ZResult<Parsed<'_>>` will parse the provided key expression according to your format. Just like `KeFormat::parse`, parsing is lazy: each field will match the smallest subsection of your `target` that is included in its pattern.
 ///     - like `Formatter`, `Parsed` will have a method named after each spec's `id` that returns `Option<&keyexpr>`. That `Option` will only be `None` if the spec's format was `**` and matched a sequence of 0 chunks.
+///     - `Parsed` will also have a `<id>_as::<T: FromStr>()` method for each spec, that parses the value of the field into a `T`.
+/// - `format(...)`, a function that takes the values of the fields in the order of the format, and builds them into an `OwnedKeyExpr`.
+///
+/// Invalid formats, as well as ids that aren't valid Rust identifiers, are reported as compilation errors.
 #[proc_macro]
 pub fn kedefine(tokens: TokenStream) -> TokenStream {
     let declarations: FormatDeclarations = syn::parse(tokens).unwrap();
@@ -238,14 +271,18 @@ pub fn kedefine(tokens: TokenStream) -> TokenStream {
 - `formatter()`, a function that constructs a `Formatter` specialized for your format:
     - for every spec in your format, `Formatter` will have a method named after the spec's `id` that lets you set a value for that field of your format. These methods will return `Result<&mut Formatter, FormatError>`.
 - `parse(target: &keyexpr) -> ZResult<Parsed<'_>>` will parse the provided key expression according to your format. Just like `KeFormat::parse`, parsing is lazy: each field will match the smallest subsection of your `target` that is included in its pattern.
-    - like `Formatter`, `Parsed` will have a method named after each spec's `id` that returns `Option<&keyexpr>`. That `Option` will only be `None` if the spec's format was `**` and matched a sequence of 0 chunks."
+    - like `Formatter`, `Parsed` will have a method named after each spec's `id` that returns `Option<&keyexpr>`. That `Option` will only be `None` if the spec's format was `**` and matched a sequence of 0 chunks.
+    - `Parsed` will also have a `<id>_as::<T: FromStr>()` method for each spec, that parses the value of the field into a `T`.
+- `format(...)`, a function that takes the values of the fields in the order of the format, and builds them into an `OwnedKeyExpr`."
     );
-    let support = keformat_support(&source);
-    quote! {
-        #[doc = #docstring]
-        #vis mod #name{
-            #support
-        }
+    match keformat_support(&source) {
+        Ok(support) => quote! {
+            #[doc = #docstring]
+            #vis mod #name{
+                #support
+            }
+        },
+        Err(e) => syn::Error::new(lit.span(), e).to_compile_error(),
     }});
     quote!(#(#content)*).into()
 }
@@ -428,6 +428,9 @@ pub enum QueryTarget {
     BestMatching,
     All,
     AllComplete,
+    /// The nearest complete queryable (typically a storage) covering the queried key expression,
+    /// or else the minimal set of complete queryables covering parts of it.
+    CompleteStorage,
     #[cfg(feature = "complete_n")]
     Complete(u64),
 }
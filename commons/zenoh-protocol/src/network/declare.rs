@@ -17,7 +17,7 @@ use crate::{
     network::Mapping,
     zextz64, zextzbuf,
 };
-use alloc::borrow::Cow;
+use alloc::{borrow::Cow, string::String};
 use core::ops::BitOr;
 pub use interest::*;
 pub use keyexpr::*;
@@ -323,6 +323,7 @@ pub mod subscriber {
         pub id: SubscriberId,
         pub wire_expr: WireExpr<'static>,
         pub ext_info: ext::SubscriberInfo,
+        pub ext_filter: Option<ext::FilterType>,
     }
 
     pub mod ext {
@@ -391,6 +392,40 @@ pub mod subscriber {
                 Info::new(v)
             }
         }
+
+        pub type Filter = zextzbuf!(0x02, false);
+
+        /// # The payload filter of the subscription.
+        ///
+        /// A predicate on the fields of the JSON payloads (e.g. `temp > 90`),
+        /// evaluated by the routers before forwarding the publications to the subscriber.
+        ///
+        /// ```text
+        ///  7 6 5 4 3 2 1 0
+        /// +-+-+-+-+-+-+-+-+
+        /// |Z|1_0|    ID   |
+        /// +-+-+-+---------+
+        /// ~ <utf8;z32>    ~
+        /// +---------------+
+        /// ```
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct FilterType {
+            pub filter: String,
+        }
+
+        impl FilterType {
+            #[cfg(feature = "test")]
+            pub fn rand() -> Self {
+                use rand::{
+                    distributions::{Alphanumeric, DistString},
+                    Rng,
+                };
+                let mut rng = rand::thread_rng();
+                let len = rng.gen_range(1..16);
+                let filter = Alphanumeric.sample_string(&mut rng, len);
+                Self { filter }
+            }
+        }
     }
 
     impl DeclareSubscriber {
@@ -402,11 +437,13 @@ pub mod subscriber {
             let id: SubscriberId = rng.gen();
             let wire_expr = WireExpr::rand();
             let ext_info = ext::SubscriberInfo::rand();
+            let ext_filter = rng.gen_bool(0.5).then(ext::FilterType::rand);
 
             Self {
                 id,
                 wire_expr,
                 ext_info,
+                ext_filter,
             }
         }
     }
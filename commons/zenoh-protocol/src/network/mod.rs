@@ -126,6 +126,63 @@ impl NetworkMessage {
         cc == CongestionControl::Drop
     }
 
+    #[inline]
+    pub fn is_express(&self) -> bool {
+        match &self.body {
+            NetworkBody::Declare(msg) => msg.ext_qos.is_express(),
+            NetworkBody::Push(msg) => msg.ext_qos.is_express(),
+            NetworkBody::Request(msg) => msg.ext_qos.is_express(),
+            NetworkBody::Response(msg) => msg.ext_qos.is_express(),
+            NetworkBody::ResponseFinal(msg) => msg.ext_qos.is_express(),
+            NetworkBody::OAM(msg) => msg.ext_qos.is_express(),
+        }
+    }
+
+    /// Whether the message carries data whose deadline is passed.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        use crate::zenoh::PushBody;
+
+        match &self.body {
+            NetworkBody::Push(msg) => match &msg.payload {
+                PushBody::Put(put) => put.is_expired(),
+                PushBody::Del(_) => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// The length of the user payload carried by the message, 0 if it carries none.
+    pub fn payload_len(&self) -> usize {
+        use crate::zenoh::{PushBody, RequestBody, ResponseBody};
+        use zenoh_buffers::SplitBuffer;
+
+        match &self.body {
+            NetworkBody::Push(msg) => match &msg.payload {
+                PushBody::Put(put) => {
+                    put.payload.len()
+                        + put.ext_batch.as_ref().map_or(0, |batch| {
+                            batch.entries.iter().map(|e| e.payload.len()).sum()
+                        })
+                }
+                PushBody::Del(_) => 0,
+            },
+            NetworkBody::Request(msg) => match &msg.payload {
+                RequestBody::Query(query) => query.ext_body.as_ref().map_or(0, |b| b.payload.len()),
+                RequestBody::Put(put) => put.payload.len(),
+                RequestBody::Del(_) | RequestBody::Pull(_) => 0,
+            },
+            NetworkBody::Response(msg) => match &msg.payload {
+                ResponseBody::Reply(reply) => reply.payload.len(),
+                ResponseBody::Err(err) => err.ext_body.as_ref().map_or(0, |b| b.payload.len()),
+                ResponseBody::Put(put) => put.payload.len(),
+                ResponseBody::Del(_) | ResponseBody::Ack(_) => 0,
+            },
+            NetworkBody::Declare(_) | NetworkBody::ResponseFinal(_) | NetworkBody::OAM(_) => 0,
+        }
+    }
+
     #[inline]
     pub fn priority(&self) -> Priority {
         match &self.body {
@@ -234,7 +291,7 @@ pub mod ext {
         }
 
         pub fn set_priority(&mut self, priority: Priority) {
-            self.inner = imsg::set_flag(self.inner, priority as u8);
+            self.inner = imsg::set_flag(self.inner & !Self::P_MASK, priority as u8);
         }
 
         pub const fn get_priority(&self) -> Priority {
@@ -259,6 +316,13 @@ pub mod ext {
             imsg::has_flag(self.inner, Self::E_FLAG)
         }
 
+        pub fn set_is_express(&mut self, is_express: bool) {
+            match is_express {
+                true => self.inner = imsg::set_flag(self.inner, Self::E_FLAG),
+                false => self.inner = imsg::unset_flag(self.inner, Self::E_FLAG),
+            }
+        }
+
         #[cfg(feature = "test")]
         pub fn rand() -> Self {
             use rand::Rng;
@@ -25,6 +25,9 @@ pub mod id {
     use super::OamId;
 
     pub const OAM_LINKSTATE: OamId = 0x0001;
+    pub const OAM_RENDEZVOUS: OamId = 0x0002;
+    pub const OAM_RELAY: OamId = 0x0003;
+    pub const OAM_DISSEMINATION: OamId = 0x0004;
 }
 
 /// ```text
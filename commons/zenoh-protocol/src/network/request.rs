@@ -99,6 +99,7 @@ pub mod ext {
             *[
                 TargetType::All,
                 TargetType::AllComplete,
+                TargetType::CompleteStorage,
                 TargetType::BestMatching,
                 #[cfg(feature = "complete_n")]
                 TargetType::Complete(rng.gen()),
@@ -83,6 +83,8 @@ use core::fmt;
 /// +---------------+
 /// ~   <utf8;z8>   ~ if Flag(L)==1 -- List of locators
 /// +---------------+
+/// ~  [HelloExts]  ~ if Flag(Z)==1
+/// +---------------+
 ///
 /// (*) WhatAmI. It indicates the role of the zenoh node sending the HELLO message.
 ///    The valid WhatAmI values are:
@@ -104,6 +106,15 @@ pub struct Hello {
     pub whatami: WhatAmI,
     pub zid: ZenohId,
     pub locators: Vec<Locator>,
+    pub ext_auth: Option<ext::Auth>,
+}
+
+pub mod ext {
+    use crate::{common::ZExtZBuf, zextzbuf};
+
+    /// # Auth extension
+    /// Used to authenticate the message with a pre-shared key
+    pub type Auth = zextzbuf!(0x1, false);
 }
 
 impl fmt::Display for Hello {
@@ -119,6 +130,7 @@ impl fmt::Display for Hello {
 impl Hello {
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
+        use crate::common::ZExtZBuf;
         use rand::Rng;
 
         let mut rng = rand::thread_rng();
@@ -131,11 +143,13 @@ impl Hello {
         } else {
             vec![]
         };
+        let ext_auth = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
         Self {
             version,
             zid,
             whatami,
             locators,
+            ext_auth,
         }
     }
 }
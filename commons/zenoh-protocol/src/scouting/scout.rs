@@ -54,6 +54,8 @@ use crate::core::{whatami::WhatAmIMatcher, ZenohId};
 /// +-+-+-+-+-+-+-+-+
 /// ~      [u8]     ~ if Flag(I)==1 -- ZenohID
 /// +---------------+
+/// ~  [ScoutExts]  ~ if Flag(Z)==1
+/// +---------------+
 ///
 /// (#) ZID length. If Flag(I)==1 it indicates how many bytes are used for the ZenohID bytes.
 ///     A ZenohID is minimum 1 byte and maximum 16 bytes. Therefore, the actual lenght is computed as:
@@ -76,11 +78,21 @@ pub struct Scout {
     pub version: u8,
     pub what: WhatAmIMatcher,
     pub zid: Option<ZenohId>,
+    pub ext_auth: Option<ext::Auth>,
+}
+
+pub mod ext {
+    use crate::{common::ZExtZBuf, zextzbuf};
+
+    /// # Auth extension
+    /// Used to authenticate the message with a pre-shared key
+    pub type Auth = zextzbuf!(0x1, false);
 }
 
 impl Scout {
     #[cfg(feature = "test")]
     pub fn rand() -> Self {
+        use crate::common::ZExtZBuf;
         use rand::Rng;
 
         let mut rng = rand::thread_rng();
@@ -88,6 +100,12 @@ impl Scout {
         let version: u8 = rng.gen();
         let what = WhatAmIMatcher::rand();
         let zid = rng.gen_bool(0.5).then_some(ZenohId::rand());
-        Self { version, what, zid }
+        let ext_auth = rng.gen_bool(0.5).then_some(ZExtZBuf::rand());
+        Self {
+            version,
+            what,
+            zid,
+            ext_auth,
+        }
     }
 }
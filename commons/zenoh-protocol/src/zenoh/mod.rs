@@ -129,6 +129,7 @@ pub enum ResponseBody {
     Err(Err),
     Ack(Ack),
     Put(Put),
+    Del(Del),
 }
 
 impl ResponseBody {
@@ -138,11 +139,12 @@ impl ResponseBody {
 
         let mut rng = rand::thread_rng();
 
-        match rng.gen_range(0..4) {
+        match rng.gen_range(0..5) {
             0 => ResponseBody::Reply(Reply::rand()),
             1 => ResponseBody::Err(Err::rand()),
             2 => ResponseBody::Ack(Ack::rand()),
             3 => ResponseBody::Put(Put::rand()),
+            4 => ResponseBody::Del(Del::rand()),
             _ => unreachable!(),
         }
     }
@@ -166,10 +168,17 @@ impl From<Ack> for ResponseBody {
     }
 }
 
+impl From<Del> for ResponseBody {
+    fn from(d: Del) -> ResponseBody {
+        ResponseBody::Del(d)
+    }
+}
+
 pub mod ext {
+    use alloc::{string::String, vec::Vec};
     use zenoh_buffers::ZBuf;
 
-    use crate::core::{Encoding, ZenohId};
+    use crate::core::{Encoding, ZenohId, NTP64};
 
     ///  7 6 5 4 3 2 1 0
     /// +-+-+-+-+-+-+-+-+
@@ -201,6 +210,127 @@ pub mod ext {
         }
     }
 
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// %   num_hops    %
+    /// +---------------+
+    /// ~  [TraceHop]   ~
+    /// +---------------+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct TraceType<const ID: u8> {
+        pub hops: Vec<TraceHop>,
+    }
+
+    impl<const ID: u8> TraceType<{ ID }> {
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let hops = (0..rng.gen_range(0..4)).map(|_| TraceHop::rand()).collect();
+            Self { hops }
+        }
+    }
+
+    /// A node a traced message went through, with the times (according to the node's clock) at which
+    /// the node received and forwarded the message.
+    ///
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// |zid_len|X|X|X|X|
+    /// +-------+-+-+---+
+    /// ~      zid      ~
+    /// +---------------+
+    /// %    ingress    %
+    /// +---------------+
+    /// %    egress     %
+    /// +---------------+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct TraceHop {
+        pub zid: ZenohId,
+        pub ingress: NTP64,
+        pub egress: NTP64,
+    }
+
+    impl TraceHop {
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let zid = ZenohId::rand();
+            let ingress = NTP64(rng.gen());
+            let egress = NTP64(rng.gen());
+            Self {
+                zid,
+                ingress,
+                egress,
+            }
+        }
+    }
+
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// % num_entries   %
+    /// +---------------+
+    /// ~ [BatchEntry]  ~
+    /// +---------------+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct BatchType<const ID: u8> {
+        pub entries: Vec<BatchEntry>,
+    }
+
+    impl<const ID: u8> BatchType<{ ID }> {
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            let entries = (0..rng.gen_range(1..4))
+                .map(|_| BatchEntry::rand())
+                .collect();
+            Self { entries }
+        }
+    }
+
+    /// A value of an atomic batch of publications, along with the full key expression it is published on.
+    ///
+    ///  7 6 5 4 3 2 1 0
+    /// +-+-+-+-+-+-+-+-+
+    /// ~ key: <u8;z16> ~
+    /// +---------------+
+    /// ~   encoding    ~
+    /// +---------------+
+    /// ~ pl: <u8;z32>  ~
+    /// +---------------+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct BatchEntry {
+        pub key: String,
+        pub encoding: Encoding,
+        pub payload: ZBuf,
+    }
+
+    impl BatchEntry {
+        #[cfg(feature = "test")]
+        pub fn rand() -> Self {
+            use rand::{
+                distributions::{Alphanumeric, DistString},
+                Rng,
+            };
+            let mut rng = rand::thread_rng();
+
+            let len = rng.gen_range(1..16);
+            let key = Alphanumeric.sample_string(&mut rng, len);
+            let encoding = Encoding::rand();
+            let payload = ZBuf::rand(rng.gen_range(1..=64));
+            Self {
+                key,
+                encoding,
+                payload,
+            }
+        }
+    }
+
     ///  7 6 5 4 3 2 1 0
     /// +-+-+-+-+-+-+-+-+
     /// +-+-+-+-+-+-+-+-+
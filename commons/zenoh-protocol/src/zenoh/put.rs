@@ -50,6 +50,9 @@ pub struct Put {
     pub ext_sinfo: Option<ext::SourceInfoType>,
     #[cfg(feature = "shared-memory")]
     pub ext_shm: Option<ext::ShmType>,
+    pub ext_trace: Option<ext::TraceType>,
+    pub ext_deadline: Option<ext::DeadlineType>,
+    pub ext_batch: Option<ext::BatchType>,
     pub ext_unknown: Vec<ZExtUnknown>,
     pub payload: ZBuf,
 }
@@ -57,7 +60,10 @@ pub struct Put {
 pub mod ext {
     #[cfg(feature = "shared-memory")]
     use crate::{common::ZExtUnit, zextunit};
-    use crate::{common::ZExtZBuf, zextzbuf};
+    use crate::{
+        common::{ZExtZ64, ZExtZBuf},
+        zextz64, zextzbuf,
+    };
 
     /// # SourceInfo extension
     /// Used to carry additional information about the source of data
@@ -70,6 +76,24 @@ pub mod ext {
     pub type Shm = zextunit!(0x2, true);
     #[cfg(feature = "shared-memory")]
     pub type ShmType = crate::zenoh::ext::ShmType<{ Shm::ID }>;
+
+    /// # Trace extension
+    /// Used to record the nodes a message went through, along with the times at which they received and forwarded it
+    pub type Trace = zextzbuf!(0x3, false);
+    pub type TraceType = crate::zenoh::ext::TraceType<{ Trace::ID }>;
+
+    /// # Deadline extension
+    /// The time (NTP64, according to the publisher's clock) after which the data is expired and
+    /// must neither be forwarded nor stored anymore
+    pub type Deadline = zextz64!(0x4, false);
+    pub type DeadlineType = uhlc::NTP64;
+
+    /// # Batch extension
+    /// The values of an atomic batch of publications, carried by a single put whose key expression
+    /// covers the keys of all the values. The payload of the put itself is empty, hence the extension is
+    /// mandatory: a node unable to decode it must not handle the put as a publication of an empty value.
+    pub type Batch = zextzbuf!(0x5, true);
+    pub type BatchType = crate::zenoh::ext::BatchType<{ Batch::ID }>;
 }
 
 impl Put {
@@ -88,12 +112,12 @@ impl Put {
         let ext_sinfo = rng.gen_bool(0.5).then_some(ext::SourceInfoType::rand());
         #[cfg(feature = "shared-memory")]
         let ext_shm = rng.gen_bool(0.5).then_some(ext::ShmType::rand());
+        let ext_trace = rng.gen_bool(0.5).then_some(ext::TraceType::rand());
+        let ext_deadline = rng.gen_bool(0.5).then(|| uhlc::NTP64(rng.gen()));
+        let ext_batch = rng.gen_bool(0.5).then(ext::BatchType::rand);
         let mut ext_unknown = Vec::new();
         for _ in 0..rng.gen_range(0..4) {
-            ext_unknown.push(ZExtUnknown::rand2(
-                iext::mid(ext::SourceInfo::ID) + 1,
-                false,
-            ));
+            ext_unknown.push(ZExtUnknown::rand2(iext::mid(ext::Batch::ID) + 1, false));
         }
         let payload = ZBuf::rand(rng.gen_range(1..=64));
 
@@ -103,8 +127,18 @@ impl Put {
             ext_sinfo,
             #[cfg(feature = "shared-memory")]
             ext_shm,
+            ext_trace,
+            ext_deadline,
+            ext_batch,
             ext_unknown,
             payload,
         }
     }
+
+    /// Whether the deadline of the put, if any, is passed.
+    #[cfg(feature = "std")]
+    pub fn is_expired(&self) -> bool {
+        self.ext_deadline
+            .map_or(false, |deadline| uhlc::system_time_clock() > deadline)
+    }
 }
@@ -0,0 +1,137 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::fmt;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use zenoh_core::zlock;
+
+/// A read-copy-update cell: readers get a snapshot of the current value without taking any lock,
+/// writers replace the whole value and wait for the readers of the replaced one before releasing it.
+///
+/// The readers register in one of two counters, selected by the parity of the current epoch.
+/// A writer flips the epoch and only waits for the readers registered before the flip, so that it
+/// is not starved by a continuous flow of readers.
+pub struct RcuCell<T> {
+    value: AtomicPtr<T>,
+    epoch: AtomicUsize,
+    readers: [AtomicUsize; 2],
+    writer: Mutex<()>,
+}
+
+impl<T> RcuCell<T> {
+    pub fn new(value: T) -> Self {
+        Self::from(Arc::new(value))
+    }
+
+    /// Returns a snapshot of the current value, unaffected by the following updates.
+    pub fn load(&self) -> Arc<T> {
+        let readers = loop {
+            let epoch = self.epoch.load(Ordering::SeqCst);
+            let readers = &self.readers[epoch & 1];
+            readers.fetch_add(1, Ordering::SeqCst);
+            if self.epoch.load(Ordering::SeqCst) == epoch {
+                break readers;
+            }
+            readers.fetch_sub(1, Ordering::SeqCst);
+        };
+        let value = self.value.load(Ordering::SeqCst);
+        // SAFETY: the value comes from `Arc::into_raw` and the writers do not release it
+        // while a reader registered before its replacement is still running.
+        let value = unsafe {
+            Arc::increment_strong_count(value);
+            Arc::from_raw(value)
+        };
+        readers.fetch_sub(1, Ordering::SeqCst);
+        value
+    }
+
+    /// Replaces the current value, the readers holding a snapshot of it are not affected.
+    pub fn store(&self, value: Arc<T>) {
+        let _writer = zlock!(self.writer);
+        let old = self
+            .value
+            .swap(Arc::into_raw(value) as *mut T, Ordering::SeqCst);
+        let epoch = self.epoch.fetch_add(1, Ordering::SeqCst);
+        while self.readers[epoch & 1].load(Ordering::SeqCst) != 0 {
+            std::thread::yield_now();
+        }
+        // SAFETY: the old value is no longer reachable from the cell and no reader can access it anymore.
+        drop(unsafe { Arc::from_raw(old) });
+    }
+}
+
+impl<T> From<Arc<T>> for RcuCell<T> {
+    fn from(value: Arc<T>) -> Self {
+        RcuCell {
+            value: AtomicPtr::new(Arc::into_raw(value) as *mut T),
+            epoch: AtomicUsize::new(0),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            writer: Mutex::new(()),
+        }
+    }
+}
+
+impl<T: Default> Default for RcuCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> Drop for RcuCell<T> {
+    fn drop(&mut self) {
+        // SAFETY: the cell is exclusively owned, no reader can access its value anymore.
+        drop(unsafe { Arc::from_raw(*self.value.get_mut()) });
+    }
+}
+
+// SAFETY: the cell hands out clones of an `Arc<T>` to any thread, like an `Arc<T>` would.
+unsafe impl<T: Send + Sync> Send for RcuCell<T> {}
+unsafe impl<T: Send + Sync> Sync for RcuCell<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for RcuCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RcuCell").field(&self.load()).finish()
+    }
+}
+
+#[test]
+fn rcu_cell() {
+    let cell = Arc::new(RcuCell::new(0_usize));
+    let snapshot = cell.load();
+    cell.store(Arc::new(1));
+    assert_eq!(*snapshot, 0);
+    assert_eq!(*cell.load(), 1);
+
+    // The replaced values are released as soon as the cell is updated
+    let value = Arc::new(2);
+    cell.store(value.clone());
+    cell.store(Arc::new(3));
+    assert_eq!(Arc::strong_count(&value), 1);
+
+    let writer = {
+        let cell = cell.clone();
+        std::thread::spawn(move || {
+            for i in 4..10_000 {
+                cell.store(Arc::new(i));
+            }
+        })
+    };
+    let mut last = 3;
+    while last < 9_999 {
+        let value = *cell.load();
+        assert!(value >= last);
+        last = value;
+    }
+    writer.join().unwrap();
+}
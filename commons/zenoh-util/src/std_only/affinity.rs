@@ -0,0 +1,82 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_result::ZResult;
+
+/// Pins the current thread to the given CPU cores. The threads it spawns afterwards inherit this affinity.
+#[cfg(target_os = "linux")]
+pub fn set_current_thread_affinity(cores: &[usize]) -> ZResult<()> {
+    use zenoh_result::bail;
+
+    if cores.is_empty() {
+        bail!("No CPU core to pin the thread to");
+    }
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for core in cores {
+            if *core >= libc::CPU_SETSIZE as usize {
+                bail!("Invalid CPU core {}", core);
+            }
+            libc::CPU_SET(*core, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            bail!(
+                "Unable to pin the thread to the CPU cores {:?}: {}",
+                cores,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Pins the current thread to the given CPU cores. The threads it spawns afterwards inherit this affinity.
+#[cfg(not(target_os = "linux"))]
+pub fn set_current_thread_affinity(_cores: &[usize]) -> ZResult<()> {
+    zenoh_result::bail!("Pinning threads to CPU cores is only supported on Linux")
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn current_thread_affinity() {
+    std::thread::spawn(|| {
+        assert!(set_current_thread_affinity(&[]).is_err());
+        assert!(set_current_thread_affinity(&[usize::MAX]).is_err());
+        // Pinned to a core of the cpuset the test runs in, which may not include the first one
+        let core = unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            assert_eq!(
+                libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set),
+                0
+            );
+            (0..libc::CPU_SETSIZE as usize)
+                .find(|core| libc::CPU_ISSET(*core, &set))
+                .unwrap()
+        };
+        set_current_thread_affinity(&[core]).unwrap();
+        // Inherited by the spawned threads
+        std::thread::spawn(move || unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            assert_eq!(
+                libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set),
+                0
+            );
+            assert_eq!(libc::CPU_COUNT(&set), 1);
+            assert!(libc::CPU_ISSET(core, &set));
+        })
+        .join()
+        .unwrap();
+    })
+    .join()
+    .unwrap();
+}
@@ -1,3 +1,5 @@
+mod affinity;
+pub use affinity::*;
 pub mod ffi;
 mod lib_loader;
 pub mod net;
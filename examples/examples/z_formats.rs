@@ -30,4 +30,8 @@ fn main() {
     let parsed = settings_format::parse(settings_ke).unwrap();
     assert_eq!(parsed.user_id(), keyexpr::new("30").ok());
     assert_eq!(parsed.setting(), keyexpr::new("dark_mode").ok());
+    // Typed formatting and parsing
+    let ke = settings_format::format(42, "dark_mode").unwrap();
+    let parsed = settings_format::parse(&ke).unwrap();
+    assert_eq!(parsed.user_id_as::<u32>().unwrap(), 42);
 }
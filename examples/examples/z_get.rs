@@ -70,7 +70,7 @@ fn parse_args() -> (Config, String, Option<String>, QueryTarget, Duration) {
         ))
         .arg(
             Arg::from_usage("-t, --target=[TARGET] 'The target queryables of the query'")
-                .possible_values(["BEST_MATCHING", "ALL", "ALL_COMPLETE"])
+                .possible_values(["BEST_MATCHING", "ALL", "ALL_COMPLETE", "COMPLETE_STORAGE"])
                 .default_value("BEST_MATCHING"),
         )
         .arg(
@@ -111,6 +111,7 @@ fn parse_args() -> (Config, String, Option<String>, QueryTarget, Duration) {
         Some("BEST_MATCHING") => QueryTarget::BestMatching,
         Some("ALL") => QueryTarget::All,
         Some("ALL_COMPLETE") => QueryTarget::AllComplete,
+        Some("COMPLETE_STORAGE") => QueryTarget::CompleteStorage,
         _ => QueryTarget::default(),
     };
 
@@ -0,0 +1,96 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use clap::{App, Arg};
+use zenoh::capture::{Direction, Replay};
+use zenoh::config::Config;
+use zenoh::prelude::r#async::*;
+
+#[async_std::main]
+async fn main() {
+    // initiate logging
+    env_logger::init();
+
+    let (config, file, speed, direction) = parse_args();
+
+    println!("Opening session...");
+    let session = zenoh::open(config).res().await.unwrap();
+
+    println!("Replaying the publications of '{file}' at speed {speed}...");
+    let replay = Replay::open(&file)
+        .unwrap()
+        .speed(speed)
+        .direction(direction);
+    let count = replay.run(&session).await.unwrap();
+    println!("Replayed {count} publications");
+}
+
+fn parse_args() -> (Config, String, f64, Direction) {
+    let args = App::new("zenoh replay example")
+        .arg(
+            Arg::from_usage("-m, --mode=[MODE] 'The zenoh session mode (peer by default).")
+                .possible_values(["peer", "client"]),
+        )
+        .arg(Arg::from_usage(
+            "-e, --connect=[ENDPOINT]...  'Endpoints to connect to.'",
+        ))
+        .arg(Arg::from_usage(
+            "-l, --listen=[ENDPOINT]...   'Endpoints to listen on.'",
+        ))
+        .arg(Arg::from_usage(
+            "-f, --file=<FILE>        'The capture file to replay.'",
+        ))
+        .arg(
+            Arg::from_usage("-s, --speed=[SPEED]      'The speed factor of the replay, 0 to replay without delay.'")
+                .default_value("1"),
+        )
+        .arg(Arg::from_usage(
+            "--tx 'Replay the sent publications instead of the received ones.'",
+        ))
+        .arg(Arg::from_usage(
+            "-c, --config=[FILE]      'A configuration file.'",
+        ))
+        .arg(Arg::from_usage(
+            "--no-multicast-scouting 'Disable the multicast-based scouting mechanism.'",
+        ))
+        .get_matches();
+
+    let mut config = if let Some(conf_file) = args.value_of("config") {
+        Config::from_file(conf_file).unwrap()
+    } else {
+        Config::default()
+    };
+    if let Some(Ok(mode)) = args.value_of("mode").map(|mode| mode.parse()) {
+        config.set_mode(Some(mode)).unwrap();
+    }
+    if let Some(values) = args.values_of("connect") {
+        config.connect.endpoints = values.map(|v| v.parse().unwrap()).collect();
+    }
+    if let Some(values) = args.values_of("listen") {
+        config.listen.endpoints = values.map(|v| v.parse().unwrap()).collect();
+    }
+    if args.is_present("no-multicast-scouting") {
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    }
+
+    let file = args.value_of("file").unwrap().to_string();
+    let speed: f64 = args.value_of("speed").unwrap().parse().unwrap();
+    let speed = if speed == 0.0 { f64::INFINITY } else { speed };
+    let direction = if args.is_present("tx") {
+        Direction::Tx
+    } else {
+        Direction::Rx
+    };
+
+    (config, file, speed, direction)
+}
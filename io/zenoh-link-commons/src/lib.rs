@@ -35,6 +35,17 @@ use zenoh_result::ZResult;
 /*************************************/
 /*            GENERAL                */
 /*************************************/
+pub mod config {
+    /// When set to `true`, the link is opened from the socket of a local listener so that the
+    /// NAT binding observed by the remote end can be reused to reach that listener.
+    pub const PUNCH: &str = "punch";
+    /// When set to `true` on a connect endpoint of a router, the router is asked to relay
+    /// the traffic between the local peer and the peers it cannot reach directly.
+    pub const RELAY: &str = "relay";
+    /// The `socks5://` or `http://` proxy the link is tunneled through.
+    pub const PROXY: &str = "proxy";
+}
+
 #[derive(Clone, Debug, Serialize, Hash, PartialEq, Eq)]
 pub struct Link {
     pub src: Locator,
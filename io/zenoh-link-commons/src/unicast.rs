@@ -11,7 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use alloc::{boxed::Box, string::String, sync::Arc, vec, vec::Vec};
 use async_trait::async_trait;
 use core::{
     convert::TryFrom,
@@ -28,7 +28,7 @@ use zenoh_protocol::{
     core::{EndPoint, Locator},
     transport::{BatchSize, TransportMessage},
 };
-use zenoh_result::{zerror, ZResult};
+use zenoh_result::{bail, zerror, ZResult};
 
 pub type LinkManagerUnicast = Arc<dyn LinkManagerUnicastTrait>;
 #[async_trait]
@@ -38,6 +38,11 @@ pub trait LinkManagerUnicastTrait: Send + Sync {
     async fn del_listener(&self, endpoint: &EndPoint) -> ZResult<()>;
     fn get_listeners(&self) -> Vec<EndPoint>;
     fn get_locators(&self) -> Vec<Locator>;
+    /// Send a probe towards the endpoint from a local listener, opening the NAT binding that
+    /// allows a connection initiated by the endpoint to reach that listener.
+    async fn punch(&self, endpoint: &EndPoint) -> ZResult<()> {
+        bail!("Hole punching is not supported towards {}", endpoint)
+    }
 }
 pub type NewLinkChannelSender = flume::Sender<LinkUnicast>;
 pub trait ConstructibleLinkManagerUnicast<T>: Sized {
@@ -65,6 +70,10 @@ pub trait LinkUnicastTrait: Send + Sync {
     async fn read(&self, buffer: &mut [u8]) -> ZResult<usize>;
     async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()>;
     async fn close(&self) -> ZResult<()>;
+    /// The names of the certificate the remote end authenticated with when establishing this link, if any.
+    fn get_peer_cert_names(&self) -> Vec<String> {
+        vec![]
+    }
 }
 
 impl LinkUnicast {
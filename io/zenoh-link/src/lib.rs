@@ -18,6 +18,8 @@
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
 use std::collections::HashMap;
+#[cfg(any(feature = "transport_tcp", feature = "transport_tls"))]
+use std::iter;
 use std::sync::Arc;
 use zenoh_config::Config;
 use zenoh_result::{bail, ZResult};
@@ -73,7 +75,19 @@ use zenoh_link_unixpipe::{
     LinkManagerUnicastPipe, UnixPipeConfigurator, UnixPipeLocatorInspector, UNIXPIPE_LOCATOR_PREFIX,
 };
 
+#[cfg(all(feature = "transport_ble", target_os = "linux"))]
+pub use zenoh_link_ble as ble;
+#[cfg(all(feature = "transport_ble", target_os = "linux"))]
+use zenoh_link_ble::{BleLocatorInspector, LinkManagerUnicastBle, BLE_LOCATOR_PREFIX};
+
+#[cfg(feature = "transport_mem")]
+pub use zenoh_link_mem as mem;
+#[cfg(feature = "transport_mem")]
+use zenoh_link_mem::{LinkManagerUnicastMem, MemLocatorInspector, MEM_LOCATOR_PREFIX};
+
 pub use zenoh_link_commons::*;
+#[cfg(any(feature = "transport_tcp", feature = "transport_tls"))]
+use zenoh_protocol::core::{endpoint::Parameters, WhatAmI};
 pub use zenoh_protocol::core::{EndPoint, Locator};
 
 pub const PROTOCOLS: &[&str] = &[
@@ -93,6 +107,10 @@ pub const PROTOCOLS: &[&str] = &[
     serial::SERIAL_LOCATOR_PREFIX,
     #[cfg(feature = "transport_unixpipe")]
     unixpipe::UNIXPIPE_LOCATOR_PREFIX,
+    #[cfg(all(feature = "transport_ble", target_os = "linux"))]
+    ble::BLE_LOCATOR_PREFIX,
+    #[cfg(feature = "transport_mem")]
+    mem::MEM_LOCATOR_PREFIX,
 ];
 
 #[derive(Default, Clone)]
@@ -113,6 +131,10 @@ pub struct LocatorInspector {
     serial_inspector: SerialLocatorInspector,
     #[cfg(feature = "transport_unixpipe")]
     unixpipe_inspector: UnixPipeLocatorInspector,
+    #[cfg(all(feature = "transport_ble", target_os = "linux"))]
+    ble_inspector: BleLocatorInspector,
+    #[cfg(feature = "transport_mem")]
+    mem_inspector: MemLocatorInspector,
 }
 impl LocatorInspector {
     pub async fn is_multicast(&self, locator: &Locator) -> ZResult<bool> {
@@ -138,6 +160,10 @@ impl LocatorInspector {
             SERIAL_LOCATOR_PREFIX => self.serial_inspector.is_multicast(locator).await,
             #[cfg(feature = "transport_unixpipe")]
             UNIXPIPE_LOCATOR_PREFIX => self.unixpipe_inspector.is_multicast(locator).await,
+            #[cfg(all(feature = "transport_ble", target_os = "linux"))]
+            BLE_LOCATOR_PREFIX => self.ble_inspector.is_multicast(locator).await,
+            #[cfg(feature = "transport_mem")]
+            MEM_LOCATOR_PREFIX => self.mem_inspector.is_multicast(locator).await,
             _ => bail!("Unsupported protocol: {}.", protocol),
         }
     }
@@ -192,6 +218,23 @@ impl LinkConfigurator {
                 self.unixpipe_inspector.inspect_config(config).await,
             );
         }
+        // Clients tunnel their TCP based links through the configured proxy
+        #[cfg(any(feature = "transport_tcp", feature = "transport_tls"))]
+        if let (Some(WhatAmI::Client), Some(proxy)) = (config.mode(), config.connect().proxy()) {
+            for proto in [
+                #[cfg(feature = "transport_tcp")]
+                TCP_LOCATOR_PREFIX,
+                #[cfg(feature = "transport_tls")]
+                TLS_LOCATOR_PREFIX,
+            ] {
+                if !errors.contains_key(proto) {
+                    Parameters::extend(
+                        iter::once((zenoh_link_commons::config::PROXY, proxy.as_str())),
+                        configs.entry(proto.into()).or_default(),
+                    );
+                }
+            }
+        }
         (configs, errors)
     }
 }
@@ -223,6 +266,10 @@ impl LinkManagerBuilderUnicast {
             SERIAL_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerUnicastSerial::new(_manager))),
             #[cfg(feature = "transport_unixpipe")]
             UNIXPIPE_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerUnicastPipe::new(_manager))),
+            #[cfg(all(feature = "transport_ble", target_os = "linux"))]
+            BLE_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerUnicastBle::new(_manager))),
+            #[cfg(feature = "transport_mem")]
+            MEM_LOCATOR_PREFIX => Ok(Arc::new(LinkManagerUnicastMem::new(_manager))),
             _ => bail!("Unicast not supported for {} protocol", protocol),
         }
     }
@@ -0,0 +1,32 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! Bluetooth Low Energy link based on a GATT service with two characteristics:
+//! the central writes zenoh messages on the RX characteristic and the peripheral
+//! notifies them on the TX characteristic. Messages larger than an ATT PDU are
+//! fragmented.
+//!
+//! Endpoints have the form `ble/<BD_ADDR>`: a listener acts as a peripheral
+//! serving the zenoh GATT service on the given local adapter address
+//! (`00:00:00:00:00:00` for any adapter) while a connection acts as a central
+//! towards the given remote address.
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+pub use linux::*;
@@ -0,0 +1,830 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! A minimal Attribute Protocol implementation: the GATT server exposing the zenoh
+//! service on the peripheral side and its discovery on the central side.
+use async_trait::async_trait;
+use zenoh_result::{bail, zerror, ZResult};
+
+pub(crate) const ATT_DEFAULT_MTU: u16 = 23;
+
+const ATT_OP_ERROR_RSP: u8 = 0x01;
+const ATT_OP_MTU_REQ: u8 = 0x02;
+const ATT_OP_MTU_RSP: u8 = 0x03;
+const ATT_OP_FIND_INFO_REQ: u8 = 0x04;
+const ATT_OP_FIND_INFO_RSP: u8 = 0x05;
+const ATT_OP_FIND_BY_TYPE_VALUE_REQ: u8 = 0x06;
+const ATT_OP_FIND_BY_TYPE_VALUE_RSP: u8 = 0x07;
+const ATT_OP_READ_BY_TYPE_REQ: u8 = 0x08;
+const ATT_OP_READ_BY_TYPE_RSP: u8 = 0x09;
+const ATT_OP_READ_REQ: u8 = 0x0a;
+const ATT_OP_READ_RSP: u8 = 0x0b;
+const ATT_OP_READ_BLOB_REQ: u8 = 0x0c;
+const ATT_OP_READ_MULTIPLE_REQ: u8 = 0x0e;
+const ATT_OP_READ_BY_GROUP_TYPE_REQ: u8 = 0x10;
+const ATT_OP_READ_BY_GROUP_TYPE_RSP: u8 = 0x11;
+const ATT_OP_WRITE_REQ: u8 = 0x12;
+const ATT_OP_WRITE_RSP: u8 = 0x13;
+const ATT_OP_PREPARE_WRITE_REQ: u8 = 0x16;
+const ATT_OP_EXECUTE_WRITE_REQ: u8 = 0x18;
+pub(crate) const ATT_OP_NOTIFICATION: u8 = 0x1b;
+pub(crate) const ATT_OP_INDICATION: u8 = 0x1d;
+pub(crate) const ATT_OP_CONFIRMATION: u8 = 0x1e;
+const ATT_OP_READ_MULTIPLE_VARIABLE_REQ: u8 = 0x20;
+pub(crate) const ATT_OP_WRITE_CMD: u8 = 0x52;
+
+const ATT_ECODE_INVALID_HANDLE: u8 = 0x01;
+const ATT_ECODE_READ_NOT_PERMITTED: u8 = 0x02;
+const ATT_ECODE_WRITE_NOT_PERMITTED: u8 = 0x03;
+const ATT_ECODE_INVALID_PDU: u8 = 0x04;
+const ATT_ECODE_REQUEST_NOT_SUPPORTED: u8 = 0x06;
+const ATT_ECODE_ATTRIBUTE_NOT_FOUND: u8 = 0x0a;
+const ATT_ECODE_INVALID_ATTRIBUTE_VALUE_LEN: u8 = 0x0d;
+const ATT_ECODE_UNSUPPORTED_GROUP_TYPE: u8 = 0x10;
+
+const BASE_UUID: u128 = 0x0000_0000_0000_1000_8000_0080_5f9b_34fb;
+const UUID_PRIMARY_SERVICE: u16 = 0x2800;
+const UUID_CHARACTERISTIC: u16 = 0x2803;
+const UUID_CCCD: u16 = 0x2902;
+
+pub(crate) const ZENOH_SERVICE_UUID: u128 = 0x7a65_6e6f_6801_4ae0_8b1e_3c5d_2f9a_6e10;
+const ZENOH_RX_UUID: u128 = 0x7a65_6e6f_6802_4ae0_8b1e_3c5d_2f9a_6e10;
+const ZENOH_TX_UUID: u128 = 0x7a65_6e6f_6803_4ae0_8b1e_3c5d_2f9a_6e10;
+
+const PROP_WRITE_WITHOUT_RESPONSE: u8 = 0x04;
+const PROP_WRITE: u8 = 0x08;
+const PROP_NOTIFY: u8 = 0x10;
+
+// The attribute handles of the zenoh service served by the peripheral.
+const SERVICE_HANDLE: u16 = 0x0001;
+const RX_DECL_HANDLE: u16 = 0x0002;
+const RX_HANDLE: u16 = 0x0003;
+const TX_DECL_HANDLE: u16 = 0x0004;
+pub(crate) const TX_HANDLE: u16 = 0x0005;
+const CCCD_HANDLE: u16 = 0x0006;
+
+fn short_uuid(uuid: u16) -> u128 {
+    BASE_UUID | ((uuid as u128) << 96)
+}
+
+fn encode_uuid(uuid: u128, buffer: &mut Vec<u8>) {
+    if uuid & !(0xffff << 96) == BASE_UUID {
+        buffer.extend_from_slice(&((uuid >> 96) as u16).to_le_bytes());
+    } else {
+        buffer.extend_from_slice(&uuid.to_le_bytes());
+    }
+}
+
+fn decode_uuid(bytes: &[u8]) -> Option<u128> {
+    match bytes.len() {
+        2 => Some(short_uuid(u16::from_le_bytes([bytes[0], bytes[1]]))),
+        16 => Some(u128::from_le_bytes(bytes.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+fn u16_at(bytes: &[u8], index: usize) -> Option<u16> {
+    Some(u16::from_le_bytes([
+        *bytes.get(index)?,
+        *bytes.get(index + 1)?,
+    ]))
+}
+
+fn is_request(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        ATT_OP_MTU_REQ
+            | ATT_OP_FIND_INFO_REQ
+            | ATT_OP_FIND_BY_TYPE_VALUE_REQ
+            | ATT_OP_READ_BY_TYPE_REQ
+            | ATT_OP_READ_REQ
+            | ATT_OP_READ_BLOB_REQ
+            | ATT_OP_READ_MULTIPLE_REQ
+            | ATT_OP_READ_BY_GROUP_TYPE_REQ
+            | ATT_OP_WRITE_REQ
+            | ATT_OP_PREPARE_WRITE_REQ
+            | ATT_OP_EXECUTE_WRITE_REQ
+            | ATT_OP_READ_MULTIPLE_VARIABLE_REQ
+    )
+}
+
+fn error_rsp(opcode: u8, handle: u16, ecode: u8, rsp: &mut Vec<u8>) {
+    rsp.clear();
+    rsp.push(ATT_OP_ERROR_RSP);
+    rsp.push(opcode);
+    rsp.extend_from_slice(&handle.to_le_bytes());
+    rsp.push(ecode);
+}
+
+/// Writes in `rsp` the response to a request received by a central, which does not
+/// serve any attribute but the ones needed to agree on the ATT MTU.
+pub(crate) fn client_rsp(pdu: &[u8], max_mtu: u16, rsp: &mut Vec<u8>) {
+    rsp.clear();
+    match pdu.first() {
+        Some(&ATT_OP_MTU_REQ) => {
+            rsp.push(ATT_OP_MTU_RSP);
+            rsp.extend_from_slice(&max_mtu.to_le_bytes());
+        }
+        Some(&opcode) if is_request(opcode) => {
+            error_rsp(opcode, 0, ATT_ECODE_REQUEST_NOT_SUPPORTED, rsp)
+        }
+        _ => {}
+    }
+}
+
+struct Attribute {
+    handle: u16,
+    uuid: u128,
+    value: Vec<u8>,
+    readable: bool,
+}
+
+fn characteristic_decl(props: u8, handle: u16, uuid: u128) -> Vec<u8> {
+    let mut value = vec![props];
+    value.extend_from_slice(&handle.to_le_bytes());
+    encode_uuid(uuid, &mut value);
+    value
+}
+
+fn attributes() -> Vec<Attribute> {
+    let mut service = vec![];
+    encode_uuid(ZENOH_SERVICE_UUID, &mut service);
+    vec![
+        Attribute {
+            handle: SERVICE_HANDLE,
+            uuid: short_uuid(UUID_PRIMARY_SERVICE),
+            value: service,
+            readable: true,
+        },
+        Attribute {
+            handle: RX_DECL_HANDLE,
+            uuid: short_uuid(UUID_CHARACTERISTIC),
+            value: characteristic_decl(
+                PROP_WRITE_WITHOUT_RESPONSE | PROP_WRITE,
+                RX_HANDLE,
+                ZENOH_RX_UUID,
+            ),
+            readable: true,
+        },
+        Attribute {
+            handle: RX_HANDLE,
+            uuid: ZENOH_RX_UUID,
+            value: vec![],
+            readable: false,
+        },
+        Attribute {
+            handle: TX_DECL_HANDLE,
+            uuid: short_uuid(UUID_CHARACTERISTIC),
+            value: characteristic_decl(PROP_NOTIFY, TX_HANDLE, ZENOH_TX_UUID),
+            readable: true,
+        },
+        Attribute {
+            handle: TX_HANDLE,
+            uuid: ZENOH_TX_UUID,
+            value: vec![],
+            readable: false,
+        },
+        Attribute {
+            handle: CCCD_HANDLE,
+            uuid: short_uuid(UUID_CCCD),
+            value: vec![0, 0],
+            readable: true,
+        },
+    ]
+}
+
+/// What a PDU received by the GATT server means for the link.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ServerEvent<'a> {
+    None,
+    // A fragment has been written on the RX characteristic
+    Data(&'a [u8]),
+    // Notifications on the TX characteristic have been enabled or disabled
+    Subscribed(bool),
+}
+
+/// The GATT server of a peripheral, serving the zenoh service only.
+pub(crate) struct GattServer {
+    attributes: Vec<Attribute>,
+    max_mtu: u16,
+    mtu: u16,
+}
+
+impl GattServer {
+    pub(crate) fn new(max_mtu: u16) -> Self {
+        Self {
+            attributes: attributes(),
+            max_mtu: max_mtu.max(ATT_DEFAULT_MTU),
+            mtu: ATT_DEFAULT_MTU,
+        }
+    }
+
+    /// The ATT MTU agreed with the client.
+    pub(crate) fn mtu(&self) -> u16 {
+        self.mtu
+    }
+
+    fn range(&self, start: u16, end: u16) -> impl Iterator<Item = &Attribute> {
+        self.attributes
+            .iter()
+            .filter(move |a| a.handle >= start && a.handle <= end)
+    }
+
+    /// Handles a PDU received from the client, writing in `rsp` the PDU to send back if any.
+    pub(crate) fn handle<'a>(&mut self, pdu: &'a [u8], rsp: &mut Vec<u8>) -> ServerEvent<'a> {
+        rsp.clear();
+        let Some((&opcode, params)) = pdu.split_first() else {
+            return ServerEvent::None;
+        };
+        match opcode {
+            ATT_OP_MTU_REQ => match u16_at(params, 0) {
+                Some(mtu) => {
+                    self.mtu = mtu.clamp(ATT_DEFAULT_MTU, self.max_mtu);
+                    rsp.push(ATT_OP_MTU_RSP);
+                    rsp.extend_from_slice(&self.max_mtu.to_le_bytes());
+                }
+                None => error_rsp(opcode, 0, ATT_ECODE_INVALID_PDU, rsp),
+            },
+            ATT_OP_FIND_INFO_REQ => self.find_info(params, rsp),
+            ATT_OP_FIND_BY_TYPE_VALUE_REQ => self.find_by_type_value(params, rsp),
+            ATT_OP_READ_BY_TYPE_REQ => self.read_by_type(params, rsp),
+            ATT_OP_READ_BY_GROUP_TYPE_REQ => self.read_by_group_type(params, rsp),
+            ATT_OP_READ_REQ => self.read(params, rsp),
+            ATT_OP_WRITE_REQ | ATT_OP_WRITE_CMD => return self.write(opcode, params, rsp),
+            _ if is_request(opcode) => error_rsp(opcode, 0, ATT_ECODE_REQUEST_NOT_SUPPORTED, rsp),
+            _ => {}
+        }
+        ServerEvent::None
+    }
+
+    fn handle_range(opcode: u8, params: &[u8], rsp: &mut Vec<u8>) -> Option<(u16, u16)> {
+        let (Some(start), Some(end)) = (u16_at(params, 0), u16_at(params, 2)) else {
+            error_rsp(opcode, 0, ATT_ECODE_INVALID_PDU, rsp);
+            return None;
+        };
+        if start == 0 || start > end {
+            error_rsp(opcode, start, ATT_ECODE_INVALID_HANDLE, rsp);
+            return None;
+        }
+        Some((start, end))
+    }
+
+    fn find_info(&self, params: &[u8], rsp: &mut Vec<u8>) {
+        let Some((start, end)) = Self::handle_range(ATT_OP_FIND_INFO_REQ, params, rsp) else {
+            return;
+        };
+        let mut format = None;
+        for a in self.range(start, end) {
+            let mut entry = a.handle.to_le_bytes().to_vec();
+            encode_uuid(a.uuid, &mut entry);
+            let f = if entry.len() == 4 { 0x01 } else { 0x02 };
+            match format {
+                None => {
+                    format = Some(f);
+                    rsp.push(ATT_OP_FIND_INFO_RSP);
+                    rsp.push(f);
+                }
+                Some(format) if format != f => break,
+                Some(_) => {}
+            }
+            if rsp.len() + entry.len() > self.mtu as usize {
+                break;
+            }
+            rsp.extend_from_slice(&entry);
+        }
+        if format.is_none() {
+            error_rsp(
+                ATT_OP_FIND_INFO_REQ,
+                start,
+                ATT_ECODE_ATTRIBUTE_NOT_FOUND,
+                rsp,
+            );
+        }
+    }
+
+    fn find_by_type_value(&self, params: &[u8], rsp: &mut Vec<u8>) {
+        let opcode = ATT_OP_FIND_BY_TYPE_VALUE_REQ;
+        let Some((start, end)) = Self::handle_range(opcode, params, rsp) else {
+            return;
+        };
+        let Some(uuid) = u16_at(params, 4) else {
+            return error_rsp(opcode, start, ATT_ECODE_INVALID_PDU, rsp);
+        };
+        let value = &params[6..];
+        let service = &self.attributes[0];
+        if uuid == UUID_PRIMARY_SERVICE
+            && service.value == value
+            && (start..=end).contains(&service.handle)
+        {
+            rsp.push(ATT_OP_FIND_BY_TYPE_VALUE_RSP);
+            rsp.extend_from_slice(&SERVICE_HANDLE.to_le_bytes());
+            rsp.extend_from_slice(&CCCD_HANDLE.to_le_bytes());
+        } else {
+            error_rsp(opcode, start, ATT_ECODE_ATTRIBUTE_NOT_FOUND, rsp);
+        }
+    }
+
+    fn read_by_type(&self, params: &[u8], rsp: &mut Vec<u8>) {
+        let opcode = ATT_OP_READ_BY_TYPE_REQ;
+        let Some((start, end)) = Self::handle_range(opcode, params, rsp) else {
+            return;
+        };
+        let Some(uuid) = decode_uuid(&params[4..]) else {
+            return error_rsp(opcode, start, ATT_ECODE_INVALID_PDU, rsp);
+        };
+        for a in self.range(start, end).filter(|a| a.uuid == uuid) {
+            if !a.readable {
+                if rsp.is_empty() {
+                    error_rsp(opcode, a.handle, ATT_ECODE_READ_NOT_PERMITTED, rsp);
+                }
+                break;
+            }
+            let len = (a.value.len() + 2).min(self.mtu as usize - 2);
+            if rsp.is_empty() {
+                rsp.push(ATT_OP_READ_BY_TYPE_RSP);
+                rsp.push(len as u8);
+            } else if rsp[1] as usize != len || rsp.len() + len > self.mtu as usize {
+                break;
+            }
+            rsp.extend_from_slice(&a.handle.to_le_bytes());
+            rsp.extend_from_slice(&a.value[..len - 2]);
+        }
+        if rsp.is_empty() {
+            error_rsp(opcode, start, ATT_ECODE_ATTRIBUTE_NOT_FOUND, rsp);
+        }
+    }
+
+    fn read_by_group_type(&self, params: &[u8], rsp: &mut Vec<u8>) {
+        let opcode = ATT_OP_READ_BY_GROUP_TYPE_REQ;
+        let Some((start, end)) = Self::handle_range(opcode, params, rsp) else {
+            return;
+        };
+        match decode_uuid(&params[4..]) {
+            Some(uuid) if uuid == short_uuid(UUID_PRIMARY_SERVICE) => {
+                let service = &self.attributes[0];
+                if (start..=end).contains(&service.handle) {
+                    rsp.push(ATT_OP_READ_BY_GROUP_TYPE_RSP);
+                    rsp.push(4 + service.value.len() as u8);
+                    rsp.extend_from_slice(&SERVICE_HANDLE.to_le_bytes());
+                    rsp.extend_from_slice(&CCCD_HANDLE.to_le_bytes());
+                    rsp.extend_from_slice(&service.value);
+                } else {
+                    error_rsp(opcode, start, ATT_ECODE_ATTRIBUTE_NOT_FOUND, rsp);
+                }
+            }
+            Some(_) => error_rsp(opcode, start, ATT_ECODE_UNSUPPORTED_GROUP_TYPE, rsp),
+            None => error_rsp(opcode, start, ATT_ECODE_INVALID_PDU, rsp),
+        }
+    }
+
+    fn read(&self, params: &[u8], rsp: &mut Vec<u8>) {
+        let Some(handle) = u16_at(params, 0) else {
+            return error_rsp(ATT_OP_READ_REQ, 0, ATT_ECODE_INVALID_PDU, rsp);
+        };
+        match self.attributes.iter().find(|a| a.handle == handle) {
+            Some(a) if a.readable => {
+                let len = a.value.len().min(self.mtu as usize - 1);
+                rsp.push(ATT_OP_READ_RSP);
+                rsp.extend_from_slice(&a.value[..len]);
+            }
+            Some(_) => error_rsp(ATT_OP_READ_REQ, handle, ATT_ECODE_READ_NOT_PERMITTED, rsp),
+            None => error_rsp(ATT_OP_READ_REQ, handle, ATT_ECODE_INVALID_HANDLE, rsp),
+        }
+    }
+
+    fn write<'a>(&mut self, opcode: u8, params: &'a [u8], rsp: &mut Vec<u8>) -> ServerEvent<'a> {
+        // Write commands are never answered, even on error
+        let answer = |ecode: Option<u8>, handle: u16, rsp: &mut Vec<u8>| {
+            if opcode == ATT_OP_WRITE_REQ {
+                match ecode {
+                    Some(ecode) => error_rsp(opcode, handle, ecode, rsp),
+                    None => rsp.push(ATT_OP_WRITE_RSP),
+                }
+            }
+        };
+        let Some(handle) = u16_at(params, 0) else {
+            answer(Some(ATT_ECODE_INVALID_PDU), 0, rsp);
+            return ServerEvent::None;
+        };
+        let value = &params[2..];
+        match handle {
+            RX_HANDLE => {
+                answer(None, handle, rsp);
+                ServerEvent::Data(value)
+            }
+            CCCD_HANDLE if value.len() == 2 => {
+                answer(None, handle, rsp);
+                self.attributes[CCCD_HANDLE as usize - 1].value = value.to_vec();
+                ServerEvent::Subscribed(value[0] & 0x01 != 0)
+            }
+            CCCD_HANDLE => {
+                answer(Some(ATT_ECODE_INVALID_ATTRIBUTE_VALUE_LEN), handle, rsp);
+                ServerEvent::None
+            }
+            SERVICE_HANDLE..=CCCD_HANDLE => {
+                answer(Some(ATT_ECODE_WRITE_NOT_PERMITTED), handle, rsp);
+                ServerEvent::None
+            }
+            _ => {
+                answer(Some(ATT_ECODE_INVALID_HANDLE), handle, rsp);
+                ServerEvent::None
+            }
+        }
+    }
+}
+
+/// The bearer used by the central to talk to the GATT server of the peripheral.
+#[async_trait]
+pub(crate) trait Bearer {
+    async fn send(&self, pdu: &[u8]) -> ZResult<()>;
+    async fn recv(&self, buffer: &mut [u8]) -> ZResult<usize>;
+}
+
+/// The zenoh service as discovered by a central.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Service {
+    // The ATT MTU agreed with the server
+    pub(crate) mtu: u16,
+    // The value handle of the RX characteristic
+    pub(crate) rx: u16,
+    // The value handle of the TX characteristic
+    pub(crate) tx: u16,
+}
+
+struct Client<'a, B: Bearer + ?Sized> {
+    bearer: &'a B,
+    max_mtu: u16,
+    buffer: Vec<u8>,
+    rsp: Vec<u8>,
+}
+
+impl<'a, B: Bearer + ?Sized> Client<'a, B> {
+    /// Sends a request and waits for its response, answering the requests of the server
+    /// meanwhile. Returns `None` if the server answered with ATTRIBUTE_NOT_FOUND.
+    async fn request(&mut self, req: &[u8], opcode: u8) -> ZResult<Option<&[u8]>> {
+        self.bearer.send(req).await?;
+        loop {
+            let n = self.bearer.recv(&mut self.buffer).await?;
+            let pdu = &self.buffer[..n];
+            match pdu.first() {
+                Some(&op) if op == opcode => return Ok(Some(&self.buffer[1..n])),
+                Some(&ATT_OP_ERROR_RSP) if pdu.get(1) == req.first() => {
+                    let ecode = pdu.get(4).copied().unwrap_or_default();
+                    if ecode == ATT_ECODE_ATTRIBUTE_NOT_FOUND {
+                        return Ok(None);
+                    }
+                    bail!(
+                        "ATT request 0x{:02x} failed with error 0x{:02x}",
+                        req[0],
+                        ecode
+                    )
+                }
+                _ => {
+                    client_rsp(pdu, self.max_mtu, &mut self.rsp);
+                    if !self.rsp.is_empty() {
+                        self.bearer.send(&self.rsp).await?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Discovers the zenoh service on the peripheral and enables the notifications of its
+/// TX characteristic.
+pub(crate) async fn discover<B: Bearer + ?Sized>(bearer: &B, max_mtu: u16) -> ZResult<Service> {
+    let max_mtu = max_mtu.max(ATT_DEFAULT_MTU);
+    let mut client = Client {
+        bearer,
+        max_mtu,
+        buffer: vec![0; max_mtu as usize],
+        rsp: vec![],
+    };
+
+    // Agree on the ATT MTU
+    let mut req = vec![ATT_OP_MTU_REQ];
+    req.extend_from_slice(&max_mtu.to_le_bytes());
+    let mtu = match client.request(&req, ATT_OP_MTU_RSP).await? {
+        Some(rsp) => u16_at(rsp, 0)
+            .ok_or_else(|| zerror!("Invalid ATT MTU response"))?
+            .clamp(ATT_DEFAULT_MTU, max_mtu),
+        None => ATT_DEFAULT_MTU,
+    };
+
+    // Find the zenoh service
+    req.clear();
+    req.push(ATT_OP_FIND_BY_TYPE_VALUE_REQ);
+    req.extend_from_slice(&1u16.to_le_bytes());
+    req.extend_from_slice(&u16::MAX.to_le_bytes());
+    req.extend_from_slice(&UUID_PRIMARY_SERVICE.to_le_bytes());
+    encode_uuid(ZENOH_SERVICE_UUID, &mut req);
+    let rsp = client
+        .request(&req, ATT_OP_FIND_BY_TYPE_VALUE_RSP)
+        .await?
+        .ok_or_else(|| zerror!("The remote device does not provide the zenoh service"))?;
+    let (Some(start), Some(end)) = (u16_at(rsp, 0), u16_at(rsp, 2)) else {
+        bail!("Invalid ATT find by type value response")
+    };
+
+    // Find the RX and TX characteristics of the service
+    let (mut rx, mut tx) = (None, None);
+    let mut next = start;
+    while next <= end {
+        req.clear();
+        req.push(ATT_OP_READ_BY_TYPE_REQ);
+        req.extend_from_slice(&next.to_le_bytes());
+        req.extend_from_slice(&end.to_le_bytes());
+        req.extend_from_slice(&UUID_CHARACTERISTIC.to_le_bytes());
+        let Some(rsp) = client.request(&req, ATT_OP_READ_BY_TYPE_RSP).await? else {
+            break;
+        };
+        let Some((&len, entries)) = rsp.split_first() else {
+            bail!("Invalid ATT read by type response")
+        };
+        if len < 7 {
+            bail!("Invalid ATT read by type response")
+        }
+        for entry in entries.chunks_exact(len as usize) {
+            // Handle, properties, value handle and UUID
+            let uuid = decode_uuid(&entry[5..]);
+            if uuid == Some(ZENOH_RX_UUID) {
+                rx = u16_at(entry, 3);
+            } else if uuid == Some(ZENOH_TX_UUID) {
+                tx = u16_at(entry, 3);
+            }
+            next = u16_at(entry, 0).unwrap_or(u16::MAX).saturating_add(1);
+        }
+        if next == u16::MAX {
+            break;
+        }
+    }
+    let rx = rx.ok_or_else(|| zerror!("The zenoh service has no RX characteristic"))?;
+    let tx = tx.ok_or_else(|| zerror!("The zenoh service has no TX characteristic"))?;
+
+    // Find the client characteristic configuration descriptor of TX
+    let mut cccd = None;
+    let mut next = tx.saturating_add(1);
+    'find: while next <= end {
+        req.clear();
+        req.push(ATT_OP_FIND_INFO_REQ);
+        req.extend_from_slice(&next.to_le_bytes());
+        req.extend_from_slice(&end.to_le_bytes());
+        let Some(rsp) = client.request(&req, ATT_OP_FIND_INFO_RSP).await? else {
+            break;
+        };
+        let len = match rsp.first() {
+            Some(0x01) => 4,
+            Some(0x02) => 18,
+            _ => bail!("Invalid ATT find information response"),
+        };
+        for entry in rsp[1..].chunks_exact(len) {
+            let handle = u16_at(entry, 0).unwrap_or(u16::MAX);
+            match decode_uuid(&entry[2..]) {
+                Some(uuid) if uuid == short_uuid(UUID_CCCD) => {
+                    cccd = Some(handle);
+                    break 'find;
+                }
+                // The next characteristic has been reached
+                Some(uuid) if uuid == short_uuid(UUID_CHARACTERISTIC) => break 'find,
+                _ => {}
+            }
+            next = handle.saturating_add(1);
+        }
+        if next == u16::MAX {
+            break;
+        }
+    }
+    let cccd =
+        cccd.ok_or_else(|| zerror!("The TX characteristic does not support notifications"))?;
+
+    // Enable the notifications
+    req.clear();
+    req.push(ATT_OP_WRITE_REQ);
+    req.extend_from_slice(&cccd.to_le_bytes());
+    req.extend_from_slice(&0x0001u16.to_le_bytes());
+    client
+        .request(&req, ATT_OP_WRITE_RSP)
+        .await?
+        .ok_or_else(|| zerror!("Unable to enable the notifications of the TX characteristic"))?;
+
+    Ok(Service { mtu, rx, tx })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    // A bearer connected to an in-memory GATT server
+    struct Loopback {
+        server: Mutex<GattServer>,
+        pending: Mutex<VecDeque<Vec<u8>>>,
+        subscribed: Mutex<Option<bool>>,
+    }
+
+    #[async_trait]
+    impl Bearer for Loopback {
+        async fn send(&self, pdu: &[u8]) -> ZResult<()> {
+            let mut rsp = vec![];
+            let event = self.server.lock().unwrap().handle(pdu, &mut rsp);
+            if let ServerEvent::Subscribed(s) = event {
+                *self.subscribed.lock().unwrap() = Some(s);
+            }
+            if !rsp.is_empty() {
+                self.pending.lock().unwrap().push_back(rsp);
+            }
+            Ok(())
+        }
+
+        async fn recv(&self, buffer: &mut [u8]) -> ZResult<usize> {
+            let pdu = self
+                .pending
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| zerror!("No pending PDU"))?;
+            buffer[..pdu.len()].copy_from_slice(&pdu);
+            Ok(pdu.len())
+        }
+    }
+
+    #[test]
+    fn uuid() {
+        let mut buffer = vec![];
+        encode_uuid(short_uuid(UUID_CCCD), &mut buffer);
+        assert_eq!(buffer, [0x02, 0x29]);
+        assert_eq!(decode_uuid(&buffer), Some(short_uuid(UUID_CCCD)));
+
+        buffer.clear();
+        encode_uuid(ZENOH_SERVICE_UUID, &mut buffer);
+        assert_eq!(buffer.len(), 16);
+        assert_eq!(buffer[15], 0x7a);
+        assert_eq!(decode_uuid(&buffer), Some(ZENOH_SERVICE_UUID));
+        assert_eq!(decode_uuid(&buffer[1..]), None);
+    }
+
+    #[test]
+    fn discovery() {
+        for (max_mtu, mtu) in [(517, 247), (23, 23), (10, 23)] {
+            let bearer = Loopback {
+                server: Mutex::new(GattServer::new(247)),
+                pending: Mutex::new(VecDeque::new()),
+                subscribed: Mutex::new(None),
+            };
+            let service = async_std::task::block_on(discover(&bearer, max_mtu)).unwrap();
+            assert_eq!(
+                service,
+                Service {
+                    mtu,
+                    rx: RX_HANDLE,
+                    tx: TX_HANDLE
+                }
+            );
+            assert_eq!(bearer.server.lock().unwrap().mtu(), mtu);
+            assert_eq!(*bearer.subscribed.lock().unwrap(), Some(true));
+            assert!(bearer.pending.lock().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn server() {
+        let mut server = GattServer::new(247);
+        let mut rsp = vec![];
+
+        // Data written on RX, answered only for write requests
+        let cmd = [ATT_OP_WRITE_CMD, 0x03, 0x00, 0xaa, 0xbb];
+        assert_eq!(
+            server.handle(&cmd, &mut rsp),
+            ServerEvent::Data(&[0xaa, 0xbb])
+        );
+        assert!(rsp.is_empty());
+        let req = [ATT_OP_WRITE_REQ, 0x03, 0x00, 0xcc];
+        assert_eq!(server.handle(&req, &mut rsp), ServerEvent::Data(&[0xcc]));
+        assert_eq!(rsp, [ATT_OP_WRITE_RSP]);
+
+        // TX is neither readable nor writable
+        let req = [ATT_OP_READ_REQ, 0x05, 0x00];
+        assert_eq!(server.handle(&req, &mut rsp), ServerEvent::None);
+        assert_eq!(
+            rsp,
+            [
+                ATT_OP_ERROR_RSP,
+                ATT_OP_READ_REQ,
+                0x05,
+                0x00,
+                ATT_ECODE_READ_NOT_PERMITTED
+            ]
+        );
+        let cmd = [ATT_OP_WRITE_CMD, 0x05, 0x00, 0xaa];
+        assert_eq!(server.handle(&cmd, &mut rsp), ServerEvent::None);
+        assert!(rsp.is_empty());
+
+        // Notifications
+        let req = [ATT_OP_WRITE_REQ, 0x06, 0x00, 0x01, 0x00];
+        assert_eq!(server.handle(&req, &mut rsp), ServerEvent::Subscribed(true));
+        let req = [ATT_OP_READ_REQ, 0x06, 0x00];
+        server.handle(&req, &mut rsp);
+        assert_eq!(rsp, [ATT_OP_READ_RSP, 0x01, 0x00]);
+        let req = [ATT_OP_WRITE_REQ, 0x06, 0x00, 0x00, 0x00];
+        assert_eq!(
+            server.handle(&req, &mut rsp),
+            ServerEvent::Subscribed(false)
+        );
+
+        // Primary services
+        let req = [
+            ATT_OP_READ_BY_GROUP_TYPE_REQ,
+            0x01,
+            0x00,
+            0xff,
+            0xff,
+            0x00,
+            0x28,
+        ];
+        server.handle(&req, &mut rsp);
+        assert_eq!(
+            rsp[..6],
+            [ATT_OP_READ_BY_GROUP_TYPE_RSP, 20, 0x01, 0x00, 0x06, 0x00]
+        );
+        assert_eq!(decode_uuid(&rsp[6..]), Some(ZENOH_SERVICE_UUID));
+        let req = [
+            ATT_OP_READ_BY_GROUP_TYPE_REQ,
+            0x07,
+            0x00,
+            0xff,
+            0xff,
+            0x00,
+            0x28,
+        ];
+        server.handle(&req, &mut rsp);
+        assert_eq!(
+            rsp,
+            [
+                ATT_OP_ERROR_RSP,
+                ATT_OP_READ_BY_GROUP_TYPE_REQ,
+                0x07,
+                0x00,
+                ATT_ECODE_ATTRIBUTE_NOT_FOUND
+            ]
+        );
+
+        // Unsupported requests and invalid handles
+        let req = [ATT_OP_PREPARE_WRITE_REQ, 0x03, 0x00, 0x00, 0x00];
+        assert_eq!(server.handle(&req, &mut rsp), ServerEvent::None);
+        assert_eq!(rsp[..2], [ATT_OP_ERROR_RSP, ATT_OP_PREPARE_WRITE_REQ]);
+        assert_eq!(rsp[4], ATT_ECODE_REQUEST_NOT_SUPPORTED);
+        let req = [ATT_OP_FIND_INFO_REQ, 0x00, 0x00, 0xff, 0xff];
+        server.handle(&req, &mut rsp);
+        assert_eq!(rsp[4], ATT_ECODE_INVALID_HANDLE);
+        let req = [ATT_OP_READ_REQ, 0x07, 0x00];
+        server.handle(&req, &mut rsp);
+        assert_eq!(rsp[4], ATT_ECODE_INVALID_HANDLE);
+
+        // Commands and notifications are never answered
+        server.handle(&[ATT_OP_CONFIRMATION], &mut rsp);
+        assert!(rsp.is_empty());
+    }
+
+    #[test]
+    fn client() {
+        let mut rsp = vec![];
+        client_rsp(&[ATT_OP_MTU_REQ, 0x17, 0x00], 247, &mut rsp);
+        assert_eq!(rsp, [ATT_OP_MTU_RSP, 0xf7, 0x00]);
+        client_rsp(
+            &[
+                ATT_OP_READ_BY_GROUP_TYPE_REQ,
+                0x01,
+                0x00,
+                0xff,
+                0xff,
+                0x00,
+                0x28,
+            ],
+            247,
+            &mut rsp,
+        );
+        assert_eq!(
+            rsp,
+            [
+                ATT_OP_ERROR_RSP,
+                ATT_OP_READ_BY_GROUP_TYPE_REQ,
+                0x00,
+                0x00,
+                ATT_ECODE_REQUEST_NOT_SUPPORTED
+            ]
+        );
+        client_rsp(&[ATT_OP_NOTIFICATION, 0x05, 0x00, 0x00], 247, &mut rsp);
+        assert!(rsp.is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Fragmentation of zenoh messages over ATT PDUs.
+//!
+//! Each fragment starts with a one byte header made of a START flag, set on the
+//! first fragment of a message, a MORE flag, set on all but the last fragment of a
+//! message, and a 6 bits sequence number used to detect lost fragments.
+//!
+//! ```text
+//!  7 6 5 4 3 2 1 0
+//! +-+-+-+-+-+-+-+-+
+//! |M|S|    SN     |
+//! +-+-+-+-+-+-+-+-+
+//! ```
+const FLAG_MORE: u8 = 0x80;
+const FLAG_START: u8 = 0x40;
+const SN_MASK: u8 = 0x3f;
+
+const HEADER_LEN: usize = 1;
+
+#[derive(Default)]
+pub(crate) struct Fragmenter {
+    sn: u8,
+}
+
+impl Fragmenter {
+    /// Returns the headers and chunks of the fragments of `message`, each fragment being
+    /// at most `size` bytes long header included.
+    pub(crate) fn fragment<'a>(
+        &'a mut self,
+        message: &'a [u8],
+        size: usize,
+    ) -> impl Iterator<Item = (u8, &'a [u8])> + 'a {
+        let mut chunks = message.chunks(size - HEADER_LEN).peekable();
+        let mut start = FLAG_START;
+        std::iter::from_fn(move || {
+            let chunk = chunks.next()?;
+            let mut header = start | self.sn;
+            if chunks.peek().is_some() {
+                header |= FLAG_MORE;
+            }
+            self.sn = (self.sn + 1) & SN_MASK;
+            start = 0;
+            Some((header, chunk))
+        })
+    }
+}
+
+pub(crate) struct Reassembler {
+    buffer: Vec<u8>,
+    next_sn: Option<u8>,
+    active: bool,
+    max_len: usize,
+}
+
+impl Reassembler {
+    pub(crate) fn new(max_len: usize) -> Self {
+        Self {
+            buffer: Vec::with_capacity(max_len),
+            next_sn: None,
+            active: false,
+            max_len,
+        }
+    }
+
+    /// Pushes a fragment and returns the message it completes if any.
+    /// Messages with missing fragments are dropped.
+    pub(crate) fn push(&mut self, fragment: &[u8]) -> Option<&[u8]> {
+        let (&header, data) = fragment.split_first()?;
+        let sn = header & SN_MASK;
+        let in_order = self.next_sn == Some(sn);
+        self.next_sn = Some((sn + 1) & SN_MASK);
+
+        if header & FLAG_START != 0 {
+            self.buffer.clear();
+            self.active = true;
+        } else if !in_order && self.active {
+            log::debug!("BLE fragment lost: dropping the message being reassembled");
+            self.active = false;
+        }
+        if !self.active {
+            return None;
+        }
+        if self.buffer.len() + data.len() > self.max_len {
+            log::debug!(
+                "BLE message larger than {} bytes: dropping it",
+                self.max_len
+            );
+            self.active = false;
+            return None;
+        }
+        self.buffer.extend_from_slice(data);
+        if header & FLAG_MORE != 0 {
+            return None;
+        }
+        self.active = false;
+        Some(&self.buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fragments(fragmenter: &mut Fragmenter, message: &[u8], size: usize) -> Vec<Vec<u8>> {
+        fragmenter
+            .fragment(message, size)
+            .map(|(header, chunk)| [&[header], chunk].concat())
+            .collect()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let message: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let mut fragmenter = Fragmenter::default();
+        let mut reassembler = Reassembler::new(2048);
+
+        for size in [2, 20, 244, 514, 2048] {
+            let fragments = fragments(&mut fragmenter, &message, size);
+            assert_eq!(fragments.len(), (message.len() + size - 2) / (size - 1));
+            assert!(fragments.iter().all(|f| f.len() <= size));
+            let (last, others) = fragments.split_last().unwrap();
+            for f in others {
+                assert!(reassembler.push(f).is_none());
+            }
+            assert_eq!(reassembler.push(last), Some(&message[..]));
+        }
+    }
+
+    #[test]
+    fn lost_fragment() {
+        let first = vec![1u8; 50];
+        let second = vec![2u8; 50];
+        let third = vec![3u8; 50];
+        let mut fragmenter = Fragmenter::default();
+        let mut reassembler = Reassembler::new(2048);
+
+        let mut all = fragments(&mut fragmenter, &first, 20);
+        all.extend(fragments(&mut fragmenter, &second, 20));
+        all.extend(fragments(&mut fragmenter, &third, 20));
+        assert_eq!(all.len(), 9);
+
+        // Lose a fragment in the middle of the first message and the start of the second one
+        let mut messages = vec![];
+        for (i, f) in all.iter().enumerate() {
+            if i == 1 || i == 3 {
+                continue;
+            }
+            if let Some(m) = reassembler.push(f) {
+                messages.push(m.to_vec());
+            }
+        }
+        assert_eq!(messages, vec![third]);
+    }
+
+    #[test]
+    fn too_large() {
+        let mut fragmenter = Fragmenter::default();
+        let mut reassembler = Reassembler::new(100);
+
+        let large = vec![0u8; 101];
+        let small = vec![1u8; 100];
+        for f in fragments(&mut fragmenter, &large, 20) {
+            assert!(reassembler.push(&f).is_none());
+        }
+        let fragments = fragments(&mut fragmenter, &small, 20);
+        let (last, others) = fragments.split_last().unwrap();
+        for f in others {
+            assert!(reassembler.push(f).is_none());
+        }
+        assert_eq!(reassembler.push(last), Some(&small[..]));
+    }
+}
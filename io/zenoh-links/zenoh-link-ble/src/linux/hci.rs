@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Advertising of the zenoh service through HCI LE controller commands.
+use super::att::ZENOH_SERVICE_UUID;
+use super::socket::HciSocket;
+use std::io;
+
+const HCI_COMMAND_PKT: u8 = 0x01;
+const OGF_LE_CTL: u16 = 0x08;
+const OCF_LE_SET_ADVERTISING_PARAMETERS: u16 = 0x0006;
+const OCF_LE_SET_ADVERTISING_DATA: u16 = 0x0008;
+const OCF_LE_SET_ADVERTISE_ENABLE: u16 = 0x000a;
+
+// Advertising interval in units of 0.625 ms: 100 ms.
+const ADVERTISING_INTERVAL: u16 = 0x00a0;
+// Connectable undirected advertising.
+const ADV_IND: u8 = 0x00;
+// All of the three advertising channels.
+const ADVERTISING_CHANNELS: u8 = 0x07;
+
+const AD_FLAGS: u8 = 0x01;
+const AD_COMPLETE_128_BIT_UUIDS: u8 = 0x07;
+const AD_COMPLETE_LOCAL_NAME: u8 = 0x09;
+// LE general discoverable mode, BR/EDR not supported.
+const AD_FLAGS_VALUE: u8 = 0x06;
+const AD_LOCAL_NAME: &[u8] = b"zenoh";
+
+fn command(ocf: u16, params: &[u8]) -> Vec<u8> {
+    let opcode = (OGF_LE_CTL << 10) | ocf;
+    let mut packet = vec![HCI_COMMAND_PKT];
+    packet.extend_from_slice(&opcode.to_le_bytes());
+    packet.push(params.len() as u8);
+    packet.extend_from_slice(params);
+    packet
+}
+
+fn advertising_parameters() -> Vec<u8> {
+    let mut params = vec![];
+    params.extend_from_slice(&ADVERTISING_INTERVAL.to_le_bytes());
+    params.extend_from_slice(&ADVERTISING_INTERVAL.to_le_bytes());
+    // Type, own address type, peer address type and peer address
+    params.extend_from_slice(&[ADV_IND, 0x00, 0x00, 0, 0, 0, 0, 0, 0]);
+    // Channel map and filter policy
+    params.extend_from_slice(&[ADVERTISING_CHANNELS, 0x00]);
+    params
+}
+
+fn advertising_data() -> Vec<u8> {
+    let mut data = vec![2, AD_FLAGS, AD_FLAGS_VALUE];
+    data.extend_from_slice(&[17, AD_COMPLETE_128_BIT_UUIDS]);
+    data.extend_from_slice(&ZENOH_SERVICE_UUID.to_le_bytes());
+    data.extend_from_slice(&[AD_LOCAL_NAME.len() as u8 + 1, AD_COMPLETE_LOCAL_NAME]);
+    data.extend_from_slice(AD_LOCAL_NAME);
+    // The advertising data is always 31 bytes long, preceded by its significant length
+    let mut params = vec![data.len() as u8];
+    params.extend_from_slice(&data);
+    params.resize(32, 0);
+    params
+}
+
+/// Starts or stops advertising the zenoh service on the HCI device `dev`.
+/// This requires the CAP_NET_ADMIN capability.
+pub(crate) fn set_advertising(dev: u16, enable: bool) -> io::Result<()> {
+    let socket = HciSocket::open(dev)?;
+    socket.send(&command(OCF_LE_SET_ADVERTISE_ENABLE, &[0x00]))?;
+    if enable {
+        socket.send(&command(
+            OCF_LE_SET_ADVERTISING_PARAMETERS,
+            &advertising_parameters(),
+        ))?;
+        socket.send(&command(OCF_LE_SET_ADVERTISING_DATA, &advertising_data()))?;
+        socket.send(&command(OCF_LE_SET_ADVERTISE_ENABLE, &[0x01]))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands() {
+        let enable = command(OCF_LE_SET_ADVERTISE_ENABLE, &[0x01]);
+        assert_eq!(enable, [0x01, 0x0a, 0x20, 0x01, 0x01]);
+
+        let parameters = command(OCF_LE_SET_ADVERTISING_PARAMETERS, &advertising_parameters());
+        assert_eq!(parameters[..4], [0x01, 0x06, 0x20, 15]);
+        assert_eq!(parameters.len(), 4 + 15);
+
+        let data = advertising_data();
+        assert_eq!(data.len(), 32);
+        assert_eq!(data[0], 3 + 18 + 7);
+        assert_eq!(data[4..6], [17, AD_COMPLETE_128_BIT_UUIDS]);
+        assert_eq!(&data[23..29], b"\x09zenoh");
+        assert!(data[29..].iter().all(|b| *b == 0));
+    }
+}
@@ -0,0 +1,107 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+mod att;
+mod fragment;
+mod hci;
+mod socket;
+mod unicast;
+
+use async_trait::async_trait;
+use socket::{AddrType, BdAddr};
+use std::str::FromStr;
+pub use unicast::*;
+use zenoh_core::zconfigurable;
+use zenoh_link_commons::LocatorInspector;
+use zenoh_protocol::core::{EndPoint, Locator};
+use zenoh_result::{zerror, ZResult};
+
+pub const BLE_LOCATOR_PREFIX: &str = "ble";
+
+const DEFAULT_ADAPTER: u16 = 0;
+
+const DEFAULT_ADVERTISE: bool = true;
+
+zconfigurable! {
+    // Default MTU (BLE PDU) in bytes. Messages are fragmented over ATT PDUs.
+    static ref BLE_DEFAULT_MTU: u16 = 2_048;
+    // The ATT MTU proposed to the remote device in bytes.
+    static ref BLE_ATT_MTU: u16 = 517;
+    // Amount of time in microseconds to throttle the accept loop upon an error.
+    // Default set to 100 ms.
+    static ref BLE_ACCEPT_THROTTLE_TIME: u64 = 100_000;
+    // Amount of time in milliseconds to wait for the GATT setup of a new connection.
+    // Default set to 10 s.
+    static ref BLE_SETUP_TIMEOUT: u64 = 10_000;
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct BleLocatorInspector;
+#[async_trait]
+impl LocatorInspector for BleLocatorInspector {
+    fn protocol(&self) -> &str {
+        BLE_LOCATOR_PREFIX
+    }
+
+    async fn is_multicast(&self, _locator: &Locator) -> ZResult<bool> {
+        Ok(false)
+    }
+}
+
+fn get_bd_addr(endpoint: &EndPoint) -> ZResult<BdAddr> {
+    let address = endpoint.address();
+    BdAddr::from_str(address.as_str()).map_err(|_| {
+        zerror!(
+            "Invalid BLE address {}: expected AA:BB:CC:DD:EE:FF",
+            address
+        )
+        .into()
+    })
+}
+
+fn get_addr_type(endpoint: &EndPoint) -> ZResult<AddrType> {
+    match endpoint.config().get(config::BLE_ADDR_TYPE_RAW) {
+        Some(t) => AddrType::from_str(t).map_err(|_| {
+            zerror!("Invalid BLE address type {}: expected public or random", t).into()
+        }),
+        None => Ok(AddrType::Public),
+    }
+}
+
+fn get_adapter(endpoint: &EndPoint) -> u16 {
+    if let Some(adapter) = endpoint.config().get(config::BLE_ADAPTER_RAW) {
+        adapter
+            .trim_start_matches("hci")
+            .parse()
+            .unwrap_or(DEFAULT_ADAPTER)
+    } else {
+        DEFAULT_ADAPTER
+    }
+}
+
+fn get_advertise(endpoint: &EndPoint) -> bool {
+    if let Some(advertise) = endpoint.config().get(config::BLE_ADVERTISE_RAW) {
+        bool::from_str(advertise).unwrap_or(DEFAULT_ADVERTISE)
+    } else {
+        DEFAULT_ADVERTISE
+    }
+}
+
+pub mod config {
+    // The type of the remote address when connecting: public or random.
+    pub const BLE_ADDR_TYPE_RAW: &str = "addr_type";
+    // The HCI adapter used by a listener to advertise the zenoh service, e.g. hci0.
+    pub const BLE_ADAPTER_RAW: &str = "adapter";
+    // Whether a listener advertises the zenoh service.
+    pub const BLE_ADVERTISE_RAW: &str = "advertise";
+}
@@ -0,0 +1,368 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_io::Async;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::str::FromStr;
+
+const BTPROTO_L2CAP: libc::c_int = 0;
+const BTPROTO_HCI: libc::c_int = 1;
+const HCI_CHANNEL_RAW: u16 = 0;
+// The fixed L2CAP channel of the Attribute Protocol.
+const ATT_CID: u16 = 0x0004;
+const BDADDR_LE_PUBLIC: u8 = 0x01;
+const BDADDR_LE_RANDOM: u8 = 0x02;
+
+#[repr(C)]
+struct SockAddrL2 {
+    l2_family: libc::sa_family_t,
+    l2_psm: u16,
+    l2_bdaddr: [u8; 6],
+    l2_cid: u16,
+    l2_bdaddr_type: u8,
+}
+
+#[repr(C)]
+struct SockAddrHci {
+    hci_family: libc::sa_family_t,
+    hci_dev: u16,
+    hci_channel: u16,
+}
+
+/// A Bluetooth device address, most significant byte first.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct BdAddr(pub(crate) [u8; 6]);
+
+impl BdAddr {
+    pub(crate) const ANY: BdAddr = BdAddr([0; 6]);
+
+    fn from_le(mut bytes: [u8; 6]) -> Self {
+        bytes.reverse();
+        Self(bytes)
+    }
+
+    fn to_le(self) -> [u8; 6] {
+        let mut bytes = self.0;
+        bytes.reverse();
+        bytes
+    }
+}
+
+impl FromStr for BdAddr {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0; 6];
+        let mut parts = s.split(':');
+        for b in bytes.iter_mut() {
+            let part = parts.next().ok_or(())?;
+            if part.len() != 2 {
+                return Err(());
+            }
+            *b = u8::from_str_radix(part, 16).map_err(|_| ())?;
+        }
+        match parts.next() {
+            Some(_) => Err(()),
+            None => Ok(Self(bytes)),
+        }
+    }
+}
+
+impl fmt::Display for BdAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{a:02X}:{b:02X}:{c:02X}:{d:02X}:{e:02X}:{g:02X}")
+    }
+}
+
+/// The type of a LE device address.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum AddrType {
+    Public,
+    Random,
+}
+
+impl FromStr for AddrType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "public" => Ok(AddrType::Public),
+            "random" => Ok(AddrType::Random),
+            _ => Err(()),
+        }
+    }
+}
+
+impl AddrType {
+    fn raw(self) -> u8 {
+        match self {
+            AddrType::Public => BDADDR_LE_PUBLIC,
+            AddrType::Random => BDADDR_LE_RANDOM,
+        }
+    }
+}
+
+fn cvt(res: libc::c_int) -> io::Result<libc::c_int> {
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res)
+    }
+}
+
+fn l2cap_socket() -> io::Result<OwnedFd> {
+    let fd = cvt(unsafe {
+        libc::socket(
+            libc::AF_BLUETOOTH,
+            libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC,
+            BTPROTO_L2CAP,
+        )
+    })?;
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+fn att_sockaddr(addr: &BdAddr, kind: AddrType) -> SockAddrL2 {
+    SockAddrL2 {
+        l2_family: libc::AF_BLUETOOTH as libc::sa_family_t,
+        l2_psm: 0,
+        l2_bdaddr: addr.to_le(),
+        l2_cid: ATT_CID.to_le(),
+        l2_bdaddr_type: kind.raw(),
+    }
+}
+
+fn bind(fd: RawFd, addr: &BdAddr, kind: AddrType) -> io::Result<()> {
+    let sa = att_sockaddr(addr, kind);
+    cvt(unsafe {
+        libc::bind(
+            fd,
+            &sa as *const SockAddrL2 as *const libc::sockaddr,
+            mem::size_of::<SockAddrL2>() as libc::socklen_t,
+        )
+    })?;
+    Ok(())
+}
+
+fn local_addr(fd: RawFd) -> io::Result<BdAddr> {
+    let mut sa: SockAddrL2 = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<SockAddrL2>() as libc::socklen_t;
+    cvt(unsafe {
+        libc::getsockname(
+            fd,
+            &mut sa as *mut SockAddrL2 as *mut libc::sockaddr,
+            &mut len,
+        )
+    })?;
+    Ok(BdAddr::from_le(sa.l2_bdaddr))
+}
+
+/// A L2CAP socket bound to the ATT channel of a LE connection.
+pub(crate) struct AttSocket(Async<OwnedFd>);
+
+impl AttSocket {
+    pub(crate) async fn connect(addr: &BdAddr, kind: AddrType) -> io::Result<Self> {
+        let fd = l2cap_socket()?;
+        bind(fd.as_raw_fd(), &BdAddr::ANY, AddrType::Public)?;
+        let socket = Async::new(fd)?;
+
+        let sa = att_sockaddr(addr, kind);
+        let res = unsafe {
+            libc::connect(
+                socket.as_raw_fd(),
+                &sa as *const SockAddrL2 as *const libc::sockaddr,
+                mem::size_of::<SockAddrL2>() as libc::socklen_t,
+            )
+        };
+        if res < 0 {
+            let e = io::Error::last_os_error();
+            if e.raw_os_error() != Some(libc::EINPROGRESS) {
+                return Err(e);
+            }
+            socket.writable().await?;
+            let mut err: libc::c_int = 0;
+            let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+            cvt(unsafe {
+                libc::getsockopt(
+                    socket.as_raw_fd(),
+                    libc::SOL_SOCKET,
+                    libc::SO_ERROR,
+                    &mut err as *mut libc::c_int as *mut libc::c_void,
+                    &mut len,
+                )
+            })?;
+            if err != 0 {
+                return Err(io::Error::from_raw_os_error(err));
+            }
+        }
+        Ok(Self(socket))
+    }
+
+    pub(crate) fn local_addr(&self) -> io::Result<BdAddr> {
+        local_addr(self.0.as_raw_fd())
+    }
+
+    /// Sends a single ATT PDU.
+    pub(crate) async fn send(&self, pdu: &[u8]) -> io::Result<()> {
+        self.0
+            .write_with(|fd| {
+                let n = unsafe {
+                    libc::send(
+                        fd.as_raw_fd(),
+                        pdu.as_ptr() as *const libc::c_void,
+                        pdu.len(),
+                        libc::MSG_NOSIGNAL,
+                    )
+                };
+                if n < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(())
+                }
+            })
+            .await
+    }
+
+    /// Receives a single ATT PDU.
+    pub(crate) async fn recv(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        let n = self
+            .0
+            .read_with(|fd| {
+                let n = unsafe {
+                    libc::recv(
+                        fd.as_raw_fd(),
+                        buffer.as_mut_ptr() as *mut libc::c_void,
+                        buffer.len(),
+                        0,
+                    )
+                };
+                if n < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(n as usize)
+                }
+            })
+            .await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(n)
+    }
+
+    pub(crate) fn shutdown(&self) -> io::Result<()> {
+        cvt(unsafe { libc::shutdown(self.0.as_raw_fd(), libc::SHUT_RDWR) })?;
+        Ok(())
+    }
+}
+
+/// A L2CAP socket accepting LE connections on the ATT channel.
+pub(crate) struct AttListener(Async<OwnedFd>);
+
+impl AttListener {
+    pub(crate) fn bind(addr: &BdAddr) -> io::Result<Self> {
+        let fd = l2cap_socket()?;
+        bind(fd.as_raw_fd(), addr, AddrType::Public)?;
+        cvt(unsafe { libc::listen(fd.as_raw_fd(), 8) })?;
+        Ok(Self(Async::new(fd)?))
+    }
+
+    pub(crate) fn local_addr(&self) -> io::Result<BdAddr> {
+        local_addr(self.0.as_raw_fd())
+    }
+
+    pub(crate) async fn accept(&self) -> io::Result<(AttSocket, BdAddr)> {
+        let (fd, sa) = self
+            .0
+            .read_with(|fd| {
+                let mut sa: SockAddrL2 = unsafe { mem::zeroed() };
+                let mut len = mem::size_of::<SockAddrL2>() as libc::socklen_t;
+                let fd = cvt(unsafe {
+                    libc::accept4(
+                        fd.as_raw_fd(),
+                        &mut sa as *mut SockAddrL2 as *mut libc::sockaddr,
+                        &mut len,
+                        libc::SOCK_CLOEXEC,
+                    )
+                })?;
+                Ok((unsafe { OwnedFd::from_raw_fd(fd) }, sa))
+            })
+            .await?;
+        Ok((AttSocket(Async::new(fd)?), BdAddr::from_le(sa.l2_bdaddr)))
+    }
+}
+
+/// A raw HCI socket used to send commands to a local adapter.
+pub(crate) struct HciSocket(OwnedFd);
+
+impl HciSocket {
+    pub(crate) fn open(dev: u16) -> io::Result<Self> {
+        let fd = cvt(unsafe {
+            libc::socket(
+                libc::AF_BLUETOOTH,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                BTPROTO_HCI,
+            )
+        })?;
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let sa = SockAddrHci {
+            hci_family: libc::AF_BLUETOOTH as libc::sa_family_t,
+            hci_dev: dev,
+            hci_channel: HCI_CHANNEL_RAW,
+        };
+        cvt(unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &sa as *const SockAddrHci as *const libc::sockaddr,
+                mem::size_of::<SockAddrHci>() as libc::socklen_t,
+            )
+        })?;
+        Ok(Self(fd))
+    }
+
+    pub(crate) fn send(&self, packet: &[u8]) -> io::Result<()> {
+        let n = unsafe {
+            libc::write(
+                self.0.as_raw_fd(),
+                packet.as_ptr() as *const libc::c_void,
+                packet.len(),
+            )
+        };
+        if n < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bd_addr() {
+        let addr = BdAddr::from_str("00:1A:7d:DA:71:13").unwrap();
+        assert_eq!(addr.0, [0x00, 0x1A, 0x7D, 0xDA, 0x71, 0x13]);
+        assert_eq!(addr.to_string(), "00:1A:7D:DA:71:13");
+        assert_eq!(addr.to_le(), [0x13, 0x71, 0xDA, 0x7D, 0x1A, 0x00]);
+        assert_eq!(BdAddr::from_le(addr.to_le()), addr);
+
+        assert!(BdAddr::from_str("00:1A:7D:DA:71").is_err());
+        assert!(BdAddr::from_str("00:1A:7D:DA:71:13:00").is_err());
+        assert!(BdAddr::from_str("00:1A:7D:DA:71:1").is_err());
+        assert!(BdAddr::from_str("00:1A:7D:DA:71:GG").is_err());
+    }
+}
@@ -0,0 +1,569 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::att::{
+    self, Bearer, GattServer, ServerEvent, ATT_OP_CONFIRMATION, ATT_OP_INDICATION,
+    ATT_OP_NOTIFICATION, ATT_OP_WRITE_CMD, TX_HANDLE,
+};
+use super::fragment::{Fragmenter, Reassembler};
+use super::hci;
+use super::socket::{AttListener, AttSocket, BdAddr};
+use super::{
+    get_adapter, get_addr_type, get_advertise, get_bd_addr, BLE_ACCEPT_THROTTLE_TIME, BLE_ATT_MTU,
+    BLE_DEFAULT_MTU, BLE_LOCATOR_PREFIX, BLE_SETUP_TIMEOUT,
+};
+use async_std::prelude::*;
+use async_std::sync::Mutex as AsyncMutex;
+use async_std::task;
+use async_std::task::JoinHandle;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use zenoh_core::{zasynclock, zread, zwrite};
+use zenoh_link_commons::{
+    ConstructibleLinkManagerUnicast, LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait,
+    NewLinkChannelSender,
+};
+use zenoh_protocol::core::{EndPoint, Locator};
+use zenoh_result::{bail, zerror, ZResult};
+use zenoh_sync::Signal;
+
+#[async_trait]
+impl Bearer for AttSocket {
+    async fn send(&self, pdu: &[u8]) -> ZResult<()> {
+        AttSocket::send(self, pdu)
+            .await
+            .map_err(|e| zerror!("{}", e).into())
+    }
+
+    async fn recv(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        AttSocket::recv(self, buffer)
+            .await
+            .map_err(|e| zerror!("{}", e).into())
+    }
+}
+
+enum Role {
+    // The link has been established by connecting to a peripheral: zenoh messages are
+    // written on its RX characteristic and notified on its TX characteristic.
+    Central { rx: u16, tx: u16 },
+    // The link has been accepted by a listener serving the zenoh GATT service.
+    Peripheral { subscribed: AtomicBool },
+}
+
+struct Reader {
+    pdu: Vec<u8>,
+    rsp: Vec<u8>,
+    reassembler: Reassembler,
+    // The GATT server of a peripheral
+    server: Option<GattServer>,
+}
+
+struct LinkUnicastBle {
+    // The underlying L2CAP socket bound to the ATT channel
+    socket: AttSocket,
+    // The local BLE address
+    src_locator: Locator,
+    // The remote BLE address
+    dst_locator: Locator,
+    role: Role,
+    // The ATT MTU of the connection
+    att_mtu: AtomicU16,
+    // The sending and receiving state of the link, also acting as write and read locks
+    fragmenter: AsyncMutex<(Fragmenter, Vec<u8>)>,
+    reader: AsyncMutex<Reader>,
+}
+
+impl LinkUnicastBle {
+    fn new(
+        socket: AttSocket,
+        src_addr: BdAddr,
+        dst_addr: BdAddr,
+        role: Role,
+        server: Option<GattServer>,
+        att_mtu: u16,
+    ) -> Self {
+        Self {
+            socket,
+            src_locator: Locator::new(BLE_LOCATOR_PREFIX, src_addr.to_string(), "").unwrap(),
+            dst_locator: Locator::new(BLE_LOCATOR_PREFIX, dst_addr.to_string(), "").unwrap(),
+            role,
+            att_mtu: AtomicU16::new(att_mtu),
+            fragmenter: AsyncMutex::new((Fragmenter::default(), vec![])),
+            reader: AsyncMutex::new(Reader {
+                pdu: vec![0; *BLE_ATT_MTU as usize],
+                rsp: vec![],
+                reassembler: Reassembler::new(*BLE_DEFAULT_MTU as usize),
+                server,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastBle {
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing BLE link: {}", self);
+        self.socket.shutdown().map_err(|e| {
+            let e = zerror!("Unable to close BLE link {}: {}", self, e);
+            log::error!("{}", e);
+            e.into()
+        })
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        let (opcode, handle) = match &self.role {
+            Role::Central { rx, .. } => (ATT_OP_WRITE_CMD, *rx),
+            Role::Peripheral { subscribed } => {
+                if !subscribed.load(Ordering::Acquire) {
+                    bail!(
+                        "Unable to write on BLE link {}: notifications are disabled",
+                        self
+                    )
+                }
+                (ATT_OP_NOTIFICATION, TX_HANDLE)
+            }
+        };
+        // Opcode and handle precede every fragment
+        let size = self.att_mtu.load(Ordering::Acquire) as usize - 3;
+
+        let mut guard = zasynclock!(self.fragmenter);
+        let (fragmenter, pdu) = &mut *guard;
+        for (header, chunk) in fragmenter.fragment(buffer, size) {
+            pdu.clear();
+            pdu.push(opcode);
+            pdu.extend_from_slice(&handle.to_le_bytes());
+            pdu.push(header);
+            pdu.extend_from_slice(chunk);
+            self.socket.send(pdu).await.map_err(|e| {
+                let e = zerror!("Unable to write on BLE link {}: {}", self, e);
+                log::trace!("{}", e);
+                e
+            })?;
+        }
+        Ok(buffer.len())
+    }
+
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        let mut written: usize = 0;
+        while written < buffer.len() {
+            written += self.write(&buffer[written..]).await?;
+        }
+        Ok(())
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        let mut guard = zasynclock!(self.reader);
+        let Reader {
+            pdu,
+            rsp,
+            reassembler,
+            server,
+        } = &mut *guard;
+        loop {
+            let n = self.socket.recv(pdu).await.map_err(|e| {
+                let e = zerror!("Read error on BLE link {}: {}", self, e);
+                log::trace!("{}", e);
+                e
+            })?;
+            let pdu = &pdu[..n];
+
+            let fragment = match (&self.role, server.as_mut()) {
+                (Role::Peripheral { subscribed }, Some(server)) => {
+                    let event = server.handle(pdu, rsp);
+                    self.att_mtu.store(server.mtu(), Ordering::Release);
+                    match event {
+                        ServerEvent::Data(fragment) => Some(fragment),
+                        ServerEvent::Subscribed(s) => {
+                            subscribed.store(s, Ordering::Release);
+                            None
+                        }
+                        ServerEvent::None => None,
+                    }
+                }
+                (Role::Central { tx, .. }, _) => match pdu[0] {
+                    ATT_OP_NOTIFICATION | ATT_OP_INDICATION if pdu[1..3] == tx.to_le_bytes() => {
+                        rsp.clear();
+                        if pdu[0] == ATT_OP_INDICATION {
+                            rsp.push(ATT_OP_CONFIRMATION);
+                        }
+                        Some(&pdu[3..])
+                    }
+                    _ => {
+                        att::client_rsp(pdu, *BLE_ATT_MTU, rsp);
+                        None
+                    }
+                },
+                (Role::Peripheral { .. }, None) => unreachable!(),
+            };
+            if !rsp.is_empty() {
+                self.socket.send(rsp).await.map_err(|e| {
+                    let e = zerror!("Unable to write on BLE link {}: {}", self, e);
+                    log::trace!("{}", e);
+                    e
+                })?;
+            }
+            if let Some(message) = fragment.and_then(|f| reassembler.push(f)) {
+                if message.len() > buffer.len() {
+                    log::debug!(
+                        "Dropping BLE message of {} bytes larger than the read buffer on {}",
+                        message.len(),
+                        self
+                    );
+                    continue;
+                }
+                buffer[..message.len()].copy_from_slice(message);
+                return Ok(message.len());
+            }
+        }
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        let mut read: usize = 0;
+        while read < buffer.len() {
+            let n = self.read(&mut buffer[read..]).await?;
+            read += n;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn get_src(&self) -> &Locator {
+        &self.src_locator
+    }
+
+    #[inline(always)]
+    fn get_dst(&self) -> &Locator {
+        &self.dst_locator
+    }
+
+    #[inline(always)]
+    fn get_mtu(&self) -> u16 {
+        *BLE_DEFAULT_MTU
+    }
+
+    // Neither ATT write commands nor notifications are acknowledged
+    #[inline(always)]
+    fn is_reliable(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn is_streamed(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for LinkUnicastBle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.src_locator, self.dst_locator)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LinkUnicastBle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ble")
+            .field("src", &self.src_locator)
+            .field("dst", &self.dst_locator)
+            .field("att_mtu", &self.att_mtu.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+/*************************************/
+/*          LISTENER                 */
+/*************************************/
+struct ListenerUnicastBle {
+    endpoint: EndPoint,
+    active: Arc<AtomicBool>,
+    signal: Signal,
+    handle: JoinHandle<ZResult<()>>,
+}
+
+impl ListenerUnicastBle {
+    fn new(
+        endpoint: EndPoint,
+        active: Arc<AtomicBool>,
+        signal: Signal,
+        handle: JoinHandle<ZResult<()>>,
+    ) -> Self {
+        Self {
+            endpoint,
+            active,
+            signal,
+            handle,
+        }
+    }
+}
+
+pub struct LinkManagerUnicastBle {
+    manager: NewLinkChannelSender,
+    listeners: Arc<RwLock<HashMap<BdAddr, ListenerUnicastBle>>>,
+}
+
+impl LinkManagerUnicastBle {
+    pub fn new(manager: NewLinkChannelSender) -> Self {
+        Self {
+            manager,
+            listeners: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+impl ConstructibleLinkManagerUnicast<()> for LinkManagerUnicastBle {
+    fn new(new_link_sender: NewLinkChannelSender, _: ()) -> ZResult<Self> {
+        Ok(Self::new(new_link_sender))
+    }
+}
+
+#[async_trait]
+impl LinkManagerUnicastTrait for LinkManagerUnicastBle {
+    async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
+        let dst_addr = get_bd_addr(&endpoint)?;
+        let addr_type = get_addr_type(&endpoint)?;
+
+        let socket = AttSocket::connect(&dst_addr, addr_type)
+            .await
+            .map_err(|e| zerror!("Can not create a new BLE link bound to {}: {}", dst_addr, e))?;
+        let src_addr = socket
+            .local_addr()
+            .map_err(|e| zerror!("Can not create a new BLE link bound to {}: {}", dst_addr, e))?;
+
+        // Discover the zenoh service and subscribe to its notifications
+        let service = att::discover(&socket, *BLE_ATT_MTU)
+            .timeout(Duration::from_millis(*BLE_SETUP_TIMEOUT))
+            .await
+            .map_err(|e| zerror!("Can not create a new BLE link bound to {}: {}", dst_addr, e))?
+            .map_err(|e| zerror!("Can not create a new BLE link bound to {}: {}", dst_addr, e))?;
+        log::trace!(
+            "Zenoh service found on {} with ATT MTU {}",
+            dst_addr,
+            service.mtu
+        );
+
+        let link = Arc::new(LinkUnicastBle::new(
+            socket,
+            src_addr,
+            dst_addr,
+            Role::Central {
+                rx: service.rx,
+                tx: service.tx,
+            },
+            None,
+            service.mtu,
+        ));
+
+        Ok(LinkUnicast(link))
+    }
+
+    async fn new_listener(&self, endpoint: EndPoint) -> ZResult<Locator> {
+        let addr = get_bd_addr(&endpoint)?;
+        let adapter = get_adapter(&endpoint);
+        let advertise = get_advertise(&endpoint);
+
+        let socket = AttListener::bind(&addr)
+            .map_err(|e| zerror!("Can not create a new BLE listener on {}: {}", addr, e))?;
+        let local_addr = socket
+            .local_addr()
+            .map_err(|e| zerror!("Can not create a new BLE listener on {}: {}", addr, e))?;
+        if advertise {
+            start_advertising(adapter);
+        }
+
+        // Spawn the accept loop for the listener
+        let active = Arc::new(AtomicBool::new(true));
+        let signal = Signal::new();
+        let mut listeners = zwrite!(self.listeners);
+
+        let c_active = active.clone();
+        let c_signal = signal.clone();
+        let c_manager = self.manager.clone();
+        let c_listeners = self.listeners.clone();
+        let handle = task::spawn(async move {
+            // Wait for the accept loop to terminate
+            let res = accept_task(
+                socket,
+                local_addr,
+                c_active,
+                c_signal,
+                c_manager,
+                advertise.then_some(adapter),
+            )
+            .await;
+            if advertise {
+                stop_advertising(adapter);
+            }
+            zwrite!(c_listeners).remove(&addr);
+            res
+        });
+
+        let locator = endpoint.to_locator();
+        let listener = ListenerUnicastBle::new(endpoint, active, signal, handle);
+        // Update the list of active listeners on the manager
+        listeners.insert(addr, listener);
+
+        Ok(locator)
+    }
+
+    async fn del_listener(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let addr = get_bd_addr(endpoint)?;
+
+        // Stop the listener
+        let listener = zwrite!(self.listeners).remove(&addr).ok_or_else(|| {
+            let e = zerror!(
+                "Can not delete the BLE listener because it has not been found: {}",
+                addr
+            );
+            log::trace!("{}", e);
+            e
+        })?;
+
+        // Send the stop signal
+        listener.active.store(false, Ordering::Release);
+        listener.signal.trigger();
+        listener.handle.await
+    }
+
+    fn get_listeners(&self) -> Vec<EndPoint> {
+        zread!(self.listeners)
+            .values()
+            .map(|l| l.endpoint.clone())
+            .collect()
+    }
+
+    fn get_locators(&self) -> Vec<Locator> {
+        zread!(self.listeners)
+            .values()
+            .map(|x| x.endpoint.to_locator())
+            .collect()
+    }
+}
+
+fn start_advertising(adapter: u16) {
+    if let Err(e) = hci::set_advertising(adapter, true) {
+        log::warn!(
+            "Unable to advertise the zenoh service on hci{}: {}. Hint: advertising requires the CAP_NET_ADMIN capability.",
+            adapter,
+            e
+        );
+    }
+}
+
+fn stop_advertising(adapter: u16) {
+    if let Err(e) = hci::set_advertising(adapter, false) {
+        log::debug!("Unable to stop advertising on hci{}: {}", adapter, e);
+    }
+}
+
+/// Serves the zenoh GATT service on a new connection until the central enables the
+/// notifications of the TX characteristic.
+async fn setup(socket: &AttSocket, server: &mut GattServer) -> ZResult<()> {
+    let mut pdu = vec![0; *BLE_ATT_MTU as usize];
+    let mut rsp = vec![];
+    loop {
+        let n = socket.recv(&mut pdu).await?;
+        let event = server.handle(&pdu[..n], &mut rsp);
+        if !rsp.is_empty() {
+            socket.send(&rsp).await?;
+        }
+        match event {
+            ServerEvent::Subscribed(true) => return Ok(()),
+            ServerEvent::Data(_) => log::debug!("Dropping BLE data received before subscription"),
+            _ => {}
+        }
+    }
+}
+
+async fn accept_task(
+    socket: AttListener,
+    src_addr: BdAddr,
+    active: Arc<AtomicBool>,
+    signal: Signal,
+    manager: NewLinkChannelSender,
+    adapter: Option<u16>,
+) -> ZResult<()> {
+    enum Action {
+        Accept((AttSocket, BdAddr)),
+        Stop,
+    }
+
+    async fn accept(socket: &AttListener) -> ZResult<Action> {
+        let res = socket.accept().await.map_err(|e| zerror!(e))?;
+        Ok(Action::Accept(res))
+    }
+
+    async fn stop(signal: Signal) -> ZResult<Action> {
+        signal.wait().await;
+        Ok(Action::Stop)
+    }
+
+    log::trace!("Ready to accept BLE connections on: {}", src_addr);
+    while active.load(Ordering::Acquire) {
+        // Wait for incoming connections
+        let (socket, dst_addr) = match accept(&socket).race(stop(signal.clone())).await {
+            Ok(action) => match action {
+                Action::Accept((socket, dst_addr)) => (socket, dst_addr),
+                Action::Stop => break,
+            },
+            Err(e) => {
+                log::warn!("{}. Hint: is the Bluetooth adapter up?", e);
+                // Throttle the accept loop upon an error
+                task::sleep(Duration::from_micros(*BLE_ACCEPT_THROTTLE_TIME)).await;
+                continue;
+            }
+        };
+        log::debug!("Accepted BLE connection on {}: {}", src_addr, dst_addr);
+
+        // The controller stops advertising once connected
+        if let Some(adapter) = adapter {
+            start_advertising(adapter);
+        }
+
+        // Wait for the central to discover the zenoh service before creating the link
+        let manager = manager.clone();
+        task::spawn(async move {
+            let mut server = GattServer::new(*BLE_ATT_MTU);
+            let res = match setup(&socket, &mut server)
+                .timeout(Duration::from_millis(*BLE_SETUP_TIMEOUT))
+                .await
+            {
+                Ok(res) => res,
+                Err(e) => Err(zerror!(e).into()),
+            };
+            if let Err(e) = res {
+                log::debug!("BLE setup with {} failed: {}", dst_addr, e);
+                return;
+            }
+            let att_mtu = server.mtu();
+            let link = Arc::new(LinkUnicastBle::new(
+                socket,
+                src_addr,
+                dst_addr,
+                Role::Peripheral {
+                    subscribed: AtomicBool::new(true),
+                },
+                Some(server),
+                att_mtu,
+            ));
+            // Communicate the new link to the initial transport manager
+            if let Err(e) = manager.send_async(LinkUnicast(link)).await {
+                log::error!("{}-{}: {}", file!(), line!(), e)
+            }
+        });
+    }
+
+    Ok(())
+}
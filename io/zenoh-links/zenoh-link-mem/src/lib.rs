@@ -0,0 +1,53 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! In-memory links between the zenoh nodes of a same process, e.g. `mem/router1`.
+//! The address of a listener is a name registered in a process-wide registry.
+use async_trait::async_trait;
+use zenoh_core::zconfigurable;
+use zenoh_link_commons::LocatorInspector;
+use zenoh_protocol::core::Locator;
+use zenoh_result::ZResult;
+mod unicast;
+pub use unicast::*;
+
+// Default MTU (MEM PDU) in bytes.
+// NOTE: The messages are exchanged as whole batches, whose size is encoded on 16 bits,
+//       the MEM MTU is then constrained to 2^16 - 1 bytes (i.e., 65535).
+const MEM_MAX_MTU: u16 = u16::MAX;
+
+pub const MEM_LOCATOR_PREFIX: &str = "mem";
+
+zconfigurable! {
+    // Default MTU (MEM PDU) in bytes.
+    static ref MEM_DEFAULT_MTU: u16 = MEM_MAX_MTU;
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct MemLocatorInspector;
+#[async_trait]
+impl LocatorInspector for MemLocatorInspector {
+    fn protocol(&self) -> &str {
+        MEM_LOCATOR_PREFIX
+    }
+
+    async fn is_multicast(&self, _locator: &Locator) -> ZResult<bool> {
+        Ok(false)
+    }
+}
@@ -0,0 +1,249 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::{MEM_DEFAULT_MTU, MEM_LOCATOR_PREFIX};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use zenoh_core::{zlock, zread, zwrite};
+use zenoh_link_commons::{
+    LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
+};
+use zenoh_protocol::core::{EndPoint, Locator};
+use zenoh_result::{bail, zerror, ZResult};
+
+lazy_static::lazy_static! {
+    // The listeners of the process, indexed by their address
+    static ref LISTENERS: Mutex<HashMap<String, NewLinkChannelSender>> = Mutex::new(HashMap::new());
+}
+
+// The counter used to give a unique source address to the links opened towards a listener
+static LINK_ID: AtomicU64 = AtomicU64::new(0);
+
+pub struct LinkUnicastMem {
+    // The channel towards the other end of the link, dropped when the link is closed
+    tx: Mutex<Option<flume::Sender<Vec<u8>>>>,
+    // The channel from the other end of the link
+    rx: flume::Receiver<Vec<u8>>,
+    src_locator: Locator,
+    dst_locator: Locator,
+}
+
+impl LinkUnicastMem {
+    fn pair(listener: &str) -> ZResult<(LinkUnicastMem, LinkUnicastMem)> {
+        let id = LINK_ID.fetch_add(1, Ordering::Relaxed);
+        let src = Locator::new(MEM_LOCATOR_PREFIX, format!("{listener}-{id}"), "")?;
+        let dst = Locator::new(MEM_LOCATOR_PREFIX, listener, "")?;
+        let (tx_a, rx_a) = flume::unbounded();
+        let (tx_b, rx_b) = flume::unbounded();
+        let local = LinkUnicastMem {
+            tx: Mutex::new(Some(tx_a)),
+            rx: rx_b,
+            src_locator: src.clone(),
+            dst_locator: dst.clone(),
+        };
+        let remote = LinkUnicastMem {
+            tx: Mutex::new(Some(tx_b)),
+            rx: rx_a,
+            src_locator: dst,
+            dst_locator: src,
+        };
+        Ok((local, remote))
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastMem {
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing MEM link: {}", self);
+        // Dropping the sender makes the reads of the other end fail
+        zlock!(self.tx).take();
+        Ok(())
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        let tx = zlock!(self.tx)
+            .clone()
+            .ok_or_else(|| zerror!("Write error on MEM link {}: link closed", self))?;
+        tx.send(buffer.to_vec())
+            .map_err(|_| zerror!("Write error on MEM link {}: link closed", self))?;
+        Ok(buffer.len())
+    }
+
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        self.write(buffer).await.map(|_| ())
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        let msg = self
+            .rx
+            .recv_async()
+            .await
+            .map_err(|_| zerror!("Read error on MEM link {}: link closed", self))?;
+        if msg.len() > buffer.len() {
+            bail!(
+                "Read error on MEM link {}: message of {} bytes larger than the buffer of {} bytes",
+                self,
+                msg.len(),
+                buffer.len()
+            );
+        }
+        buffer[..msg.len()].copy_from_slice(&msg);
+        Ok(msg.len())
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        let mut read: usize = 0;
+        while read < buffer.len() {
+            let n = self.read(&mut buffer[read..]).await?;
+            read += n;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn get_src(&self) -> &Locator {
+        &self.src_locator
+    }
+
+    #[inline(always)]
+    fn get_dst(&self) -> &Locator {
+        &self.dst_locator
+    }
+
+    #[inline(always)]
+    fn get_mtu(&self) -> u16 {
+        *MEM_DEFAULT_MTU
+    }
+
+    #[inline(always)]
+    fn is_reliable(&self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn is_streamed(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for LinkUnicastMem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.src_locator, self.dst_locator)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LinkUnicastMem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mem")
+            .field("src", &self.src_locator)
+            .field("dst", &self.dst_locator)
+            .finish()
+    }
+}
+
+pub struct LinkManagerUnicastMem {
+    manager: NewLinkChannelSender,
+    listeners: Arc<RwLock<HashMap<String, EndPoint>>>,
+}
+
+impl LinkManagerUnicastMem {
+    pub fn new(manager: NewLinkChannelSender) -> Self {
+        Self {
+            manager,
+            listeners: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkManagerUnicastTrait for LinkManagerUnicastMem {
+    async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
+        let address = endpoint.address().to_string();
+        let listener = zlock!(LISTENERS).get(&address).cloned().ok_or_else(|| {
+            let e = zerror!("Can not create a new MEM link to {}: no listener", address);
+            log::warn!("{}", e);
+            e
+        })?;
+
+        let (local, remote) = LinkUnicastMem::pair(&address)?;
+        listener
+            .send_async(LinkUnicast(Arc::new(remote)))
+            .await
+            .map_err(|_| {
+                let e = zerror!(
+                    "Can not create a new MEM link to {}: listener closed",
+                    address
+                );
+                log::warn!("{}", e);
+                e
+            })?;
+
+        Ok(LinkUnicast(Arc::new(local)))
+    }
+
+    async fn new_listener(&self, endpoint: EndPoint) -> ZResult<Locator> {
+        let address = endpoint.address().to_string();
+        {
+            let mut listeners = zlock!(LISTENERS);
+            if listeners.contains_key(&address) {
+                bail!(
+                    "Can not create a new MEM listener on {}: address already in use",
+                    address
+                );
+            }
+            listeners.insert(address.clone(), self.manager.clone());
+        }
+        let locator = endpoint.to_locator();
+        zwrite!(self.listeners).insert(address, endpoint);
+        Ok(locator)
+    }
+
+    async fn del_listener(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let address = endpoint.address().to_string();
+        zwrite!(self.listeners).remove(&address).ok_or_else(|| {
+            let e = zerror!(
+                "Can not delete the MEM listener because it has not been found: {}",
+                address
+            );
+            log::trace!("{}", e);
+            e
+        })?;
+        zlock!(LISTENERS).remove(&address);
+        Ok(())
+    }
+
+    fn get_listeners(&self) -> Vec<EndPoint> {
+        zread!(self.listeners).values().cloned().collect()
+    }
+
+    fn get_locators(&self) -> Vec<Locator> {
+        zread!(self.listeners)
+            .values()
+            .map(|e| e.to_locator())
+            .collect()
+    }
+}
+
+impl Drop for LinkManagerUnicastMem {
+    fn drop(&mut self) {
+        // Release the addresses of the listeners for other nodes of the process
+        let mut listeners = zlock!(LISTENERS);
+        for address in zread!(self.listeners).keys() {
+            listeners.remove(address);
+        }
+    }
+}
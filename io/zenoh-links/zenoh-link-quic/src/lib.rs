@@ -106,6 +106,9 @@ zconfigurable! {
     // Amount of time in microseconds to throttle the accept loop upon an error.
     // Default set to 100 ms.
     static ref QUIC_ACCEPT_THROTTLE_TIME: u64 = 100_000;
+    // Amount of time in microseconds a hole punching connection attempt is kept alive
+    // to let its initial packets out. Default set to 100 ms.
+    static ref QUIC_PUNCH_TIME: u64 = 100_000;
 }
 
 pub mod config {
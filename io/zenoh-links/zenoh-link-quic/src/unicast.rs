@@ -14,7 +14,7 @@
 
 use crate::{
     config::*, get_quic_addr, verify::WebPkiVerifierAnyServerName, ALPN_QUIC_HTTP,
-    QUIC_ACCEPT_THROTTLE_TIME, QUIC_DEFAULT_MTU, QUIC_LOCATOR_PREFIX,
+    QUIC_ACCEPT_THROTTLE_TIME, QUIC_DEFAULT_MTU, QUIC_LOCATOR_PREFIX, QUIC_PUNCH_TIME,
 };
 use async_std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
 use async_std::prelude::FutureExt;
@@ -31,7 +31,7 @@ use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use zenoh_core::{zasynclock, zread, zwrite};
 use zenoh_link_commons::{
-    LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
+    config::PUNCH, LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
 };
 use zenoh_protocol::core::{EndPoint, Locator};
 use zenoh_result::{bail, zerror, ZResult};
@@ -183,6 +183,7 @@ impl fmt::Debug for LinkUnicastQuic {
 /*************************************/
 struct ListenerUnicastQuic {
     endpoint: EndPoint,
+    quic_endpoint: quinn::Endpoint,
     active: Arc<AtomicBool>,
     signal: Signal,
     handle: JoinHandle<ZResult<()>>,
@@ -191,12 +192,14 @@ struct ListenerUnicastQuic {
 impl ListenerUnicastQuic {
     fn new(
         endpoint: EndPoint,
+        quic_endpoint: quinn::Endpoint,
         active: Arc<AtomicBool>,
         signal: Signal,
         handle: JoinHandle<ZResult<()>>,
     ) -> ListenerUnicastQuic {
         ListenerUnicastQuic {
             endpoint,
+            quic_endpoint,
             active,
             signal,
             handle,
@@ -216,92 +219,57 @@ impl LinkManagerUnicastQuic {
             listeners: Arc::new(RwLock::new(HashMap::new())),
         }
     }
+
+    fn get_listener_endpoint(&self, dst_addr: &SocketAddr) -> Option<quinn::Endpoint> {
+        zread!(self.listeners)
+            .iter()
+            .find(|(addr, _)| addr.is_ipv4() == dst_addr.is_ipv4())
+            .map(|(_, l)| l.quic_endpoint.clone())
+    }
 }
 
 #[async_trait]
 impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
     async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
         let epaddr = endpoint.address();
-        let host = epaddr
-            .as_str()
-            .split(':')
-            .next()
-            .ok_or("Endpoints must be of the form quic/<address>:<port>")?;
+        let host = get_quic_host(&endpoint)?;
         let epconf = endpoint.config();
 
         let addr = get_quic_addr(&epaddr).await?;
 
-        let server_name_verification: bool = epconf
-            .get(TLS_SERVER_NAME_VERIFICATION)
-            .unwrap_or(TLS_SERVER_NAME_VERIFICATION_DEFAULT)
-            .parse()?;
-
-        if !server_name_verification {
-            log::warn!("Skipping name verification of servers");
-        }
+        let punch: bool = epconf
+            .get(PUNCH)
+            .unwrap_or("false")
+            .parse()
+            .map_err(|_| zerror!("Invalid QUIC {} value in {}", PUNCH, endpoint))?;
 
         // Initialize the QUIC connection
-        let mut root_cert_store = rustls::RootCertStore::empty();
-
-        // Read the certificates
-        let f = if let Some(value) = epconf.get(TLS_ROOT_CA_CERTIFICATE_RAW) {
-            value.as_bytes().to_vec()
-        } else if let Some(value) = epconf.get(TLS_ROOT_CA_CERTIFICATE_FILE) {
-            async_std::fs::read(value)
-                .await
-                .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
-        } else {
-            vec![]
-        };
-
-        let certificates = if f.is_empty() {
-            rustls_native_certs::load_native_certs()
-                .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
-                .drain(..)
-                .map(|x| rustls::Certificate(x.0))
-                .collect::<Vec<rustls::Certificate>>()
-        } else {
-            rustls_pemfile::certs(&mut BufReader::new(f.as_slice()))
-                .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
-                .drain(..)
-                .map(rustls::Certificate)
-                .collect::<Vec<rustls::Certificate>>()
-        };
-        for c in certificates.iter() {
-            root_cert_store.add(c).map_err(|e| zerror!("{}", e))?;
-        }
-
-        let client_crypto = rustls::ClientConfig::builder().with_safe_defaults();
-
-        let mut client_crypto = if server_name_verification {
-            client_crypto
-                .with_root_certificates(root_cert_store)
-                .with_no_client_auth()
-        } else {
-            client_crypto
-                .with_custom_certificate_verifier(Arc::new(WebPkiVerifierAnyServerName::new(
-                    root_cert_store,
-                )))
-                .with_no_client_auth()
-        };
-
-        client_crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
-
-        let ip_addr: IpAddr = if addr.is_ipv4() {
-            Ipv4Addr::UNSPECIFIED.into()
+        let client_config = get_quic_client_config(&endpoint).await?;
+
+        let quic_endpoint = if punch {
+            // Reuse the listener socket so that the NAT binding observed by the remote end is kept
+            self.get_listener_endpoint(&addr).ok_or_else(|| {
+                zerror!(
+                    "Can not create a new QUIC link bound to {}: no QUIC listener to punch from",
+                    host
+                )
+            })?
         } else {
-            Ipv6Addr::UNSPECIFIED.into()
+            let ip_addr: IpAddr = if addr.is_ipv4() {
+                Ipv4Addr::UNSPECIFIED.into()
+            } else {
+                Ipv6Addr::UNSPECIFIED.into()
+            };
+            quinn::Endpoint::client(SocketAddr::new(ip_addr, 0))
+                .map_err(|e| zerror!("Can not create a new QUIC link bound to {}: {}", host, e))?
         };
-        let mut quic_endpoint = quinn::Endpoint::client(SocketAddr::new(ip_addr, 0))
-            .map_err(|e| zerror!("Can not create a new QUIC link bound to {}: {}", host, e))?;
-        quic_endpoint.set_default_client_config(quinn::ClientConfig::new(Arc::new(client_crypto)));
 
         let src_addr = quic_endpoint
             .local_addr()
             .map_err(|e| zerror!("Can not create a new QUIC link bound to {}: {}", host, e))?;
 
         let quic_conn = quic_endpoint
-            .connect(addr, host)
+            .connect_with(client_config, addr, &host)
             .map_err(|e| zerror!("Can not create a new QUIC link bound to {}: {}", host, e))?
             .await
             .map_err(|e| zerror!("Can not create a new QUIC link bound to {}: {}", host, e))?;
@@ -415,16 +383,17 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
         let c_manager = self.manager.clone();
         let c_listeners = self.listeners.clone();
         let c_addr = local_addr;
+        let c_endpoint = quic_endpoint.clone();
         let handle = task::spawn(async move {
             // Wait for the accept loop to terminate
-            let res = accept_task(quic_endpoint, c_active, c_signal, c_manager).await;
+            let res = accept_task(c_endpoint, c_active, c_signal, c_manager).await;
             zwrite!(c_listeners).remove(&c_addr);
             res
         });
 
         // Initialize the QuicAcceptor
         let locator = endpoint.to_locator();
-        let listener = ListenerUnicastQuic::new(endpoint, active, signal, handle);
+        let listener = ListenerUnicastQuic::new(endpoint, quic_endpoint, active, signal, handle);
         // Update the list of active listeners on the manager
         listeners.insert(local_addr, listener);
 
@@ -488,6 +457,95 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
 
         locators
     }
+
+    async fn punch(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let host = get_quic_host(endpoint)?;
+        let addr = get_quic_addr(&endpoint.address()).await?;
+        let client_config = get_quic_client_config(endpoint).await?;
+
+        let quic_endpoint = self
+            .get_listener_endpoint(&addr)
+            .ok_or_else(|| zerror!("Can not punch towards {}: no QUIC listener", endpoint))?;
+        let connecting = quic_endpoint
+            .connect_with(client_config, addr, &host)
+            .map_err(|e| zerror!("Can not punch towards {}: {}", endpoint, e))?;
+        // Only the initial packets are needed to open the NAT binding: the connection is dropped
+        let _ = connecting
+            .timeout(Duration::from_micros(*QUIC_PUNCH_TIME))
+            .await;
+        Ok(())
+    }
+}
+
+fn get_quic_host(endpoint: &EndPoint) -> ZResult<String> {
+    let epaddr = endpoint.address();
+    let host = epaddr
+        .as_str()
+        .split(':')
+        .next()
+        .ok_or("Endpoints must be of the form quic/<address>:<port>")?;
+    Ok(host.to_string())
+}
+
+async fn get_quic_client_config(endpoint: &EndPoint) -> ZResult<quinn::ClientConfig> {
+    let epconf = endpoint.config();
+
+    let server_name_verification: bool = epconf
+        .get(TLS_SERVER_NAME_VERIFICATION)
+        .unwrap_or(TLS_SERVER_NAME_VERIFICATION_DEFAULT)
+        .parse()?;
+
+    if !server_name_verification {
+        log::warn!("Skipping name verification of servers");
+    }
+
+    let mut root_cert_store = rustls::RootCertStore::empty();
+
+    // Read the certificates
+    let f = if let Some(value) = epconf.get(TLS_ROOT_CA_CERTIFICATE_RAW) {
+        value.as_bytes().to_vec()
+    } else if let Some(value) = epconf.get(TLS_ROOT_CA_CERTIFICATE_FILE) {
+        async_std::fs::read(value)
+            .await
+            .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
+    } else {
+        vec![]
+    };
+
+    let certificates = if f.is_empty() {
+        rustls_native_certs::load_native_certs()
+            .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
+            .drain(..)
+            .map(|x| rustls::Certificate(x.0))
+            .collect::<Vec<rustls::Certificate>>()
+    } else {
+        rustls_pemfile::certs(&mut BufReader::new(f.as_slice()))
+            .map_err(|e| zerror!("Invalid QUIC CA certificate file: {}", e))?
+            .drain(..)
+            .map(rustls::Certificate)
+            .collect::<Vec<rustls::Certificate>>()
+    };
+    for c in certificates.iter() {
+        root_cert_store.add(c).map_err(|e| zerror!("{}", e))?;
+    }
+
+    let client_crypto = rustls::ClientConfig::builder().with_safe_defaults();
+
+    let mut client_crypto = if server_name_verification {
+        client_crypto
+            .with_root_certificates(root_cert_store)
+            .with_no_client_auth()
+    } else {
+        client_crypto
+            .with_custom_certificate_verifier(Arc::new(WebPkiVerifierAnyServerName::new(
+                root_cert_store,
+            )))
+            .with_no_client_auth()
+    };
+
+    client_crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
+
+    Ok(quinn::ClientConfig::new(Arc::new(client_crypto)))
 }
 
 async fn accept_task(
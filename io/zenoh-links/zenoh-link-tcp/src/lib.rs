@@ -25,8 +25,11 @@ use zenoh_link_commons::LocatorInspector;
 use zenoh_protocol::core::{endpoint::Address, Locator};
 use zenoh_result::{zerror, ZResult};
 
+pub mod proxy;
 mod unicast;
 pub use unicast::*;
+#[cfg(all(target_os = "linux", feature = "io_uring"))]
+mod uring;
 
 // Default MTU (TCP PDU) in bytes.
 // NOTE: Since TCP is a byte-stream oriented transport, theoretically it has
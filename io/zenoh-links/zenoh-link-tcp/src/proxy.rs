@@ -0,0 +1,232 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::net::TcpStream;
+use async_std::prelude::*;
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use zenoh_result::{bail, zerror, Error as ZError, ZResult};
+
+const SOCKS5_VERSION: u8 = 0x05;
+const SOCKS5_AUTH_NONE: u8 = 0x00;
+const SOCKS5_AUTH_USRPWD: u8 = 0x02;
+const SOCKS5_AUTH_USRPWD_VERSION: u8 = 0x01;
+const SOCKS5_AUTH_UNACCEPTABLE: u8 = 0xff;
+const SOCKS5_CMD_CONNECT: u8 = 0x01;
+const SOCKS5_ATYP_IPV4: u8 = 0x01;
+const SOCKS5_ATYP_DOMAIN: u8 = 0x03;
+const SOCKS5_ATYP_IPV6: u8 = 0x04;
+const SOCKS5_REP_SUCCEEDED: u8 = 0x00;
+
+// The maximum size of the response header of an HTTP proxy
+const HTTP_MAX_RESPONSE_LEN: usize = 8_192;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProxyKind {
+    Socks5,
+    Http,
+}
+
+/// A proxy outgoing TCP connections are tunneled through.
+///
+/// It is parsed from a `socks5://[<user>:<password>@]<host>:<port>` or
+/// `http://[<user>:<password>@]<host>:<port>` URL.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Proxy {
+    pub kind: ProxyKind,
+    pub address: String,
+    credentials: Option<(String, String)>,
+}
+
+impl FromStr for Proxy {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> ZResult<Self> {
+        let (kind, rest) = match s.split_once("://") {
+            Some(("socks5", rest)) => (ProxyKind::Socks5, rest),
+            Some(("http", rest)) => (ProxyKind::Http, rest),
+            _ => bail!("Invalid proxy {}: expected socks5:// or http:// scheme", s),
+        };
+        let rest = rest.trim_end_matches('/');
+        let (credentials, address) = match rest.rsplit_once('@') {
+            Some((credentials, address)) => {
+                let (user, password) = credentials
+                    .split_once(':')
+                    .ok_or_else(|| zerror!("Invalid proxy credentials in {}", s))?;
+                (Some((user.to_string(), password.to_string())), address)
+            }
+            None => (None, rest),
+        };
+        if address.rsplit_once(':').is_none() {
+            bail!("Invalid proxy {}: missing port", s);
+        }
+        Ok(Proxy {
+            kind,
+            address: address.to_string(),
+            credentials,
+        })
+    }
+}
+
+impl fmt::Display for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The credentials are not displayed on purpose
+        match self.kind {
+            ProxyKind::Socks5 => write!(f, "socks5://{}", self.address),
+            ProxyKind::Http => write!(f, "http://{}", self.address),
+        }
+    }
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl Proxy {
+    /// Opens a TCP connection to `target` (a `<host>:<port>` address) through the proxy.
+    /// The host name is resolved by the proxy.
+    pub async fn connect(&self, target: &str) -> ZResult<TcpStream> {
+        let mut stream = TcpStream::connect(&self.address)
+            .await
+            .map_err(|e| zerror!("Can not connect to proxy {}: {}", self, e))?;
+        match self.kind {
+            ProxyKind::Socks5 => self.socks5_handshake(&mut stream, target).await?,
+            ProxyKind::Http => self.http_handshake(&mut stream, target).await?,
+        }
+        Ok(stream)
+    }
+
+    async fn socks5_handshake(&self, stream: &mut TcpStream, target: &str) -> ZResult<()> {
+        let (host, port) = split_target(target)?;
+
+        // Method selection
+        let method = match self.credentials {
+            Some(_) => SOCKS5_AUTH_USRPWD,
+            None => SOCKS5_AUTH_NONE,
+        };
+        stream.write_all(&[SOCKS5_VERSION, 1, method]).await?;
+        let mut reply = [0u8; 2];
+        stream.read_exact(&mut reply).await?;
+        if reply[0] != SOCKS5_VERSION {
+            bail!("Proxy {} is not a SOCKS5 proxy", self);
+        }
+        if reply[1] == SOCKS5_AUTH_UNACCEPTABLE || reply[1] != method {
+            bail!("Proxy {} rejected the authentication method", self);
+        }
+
+        // Username/password authentication (RFC 1929)
+        if let Some((user, password)) = &self.credentials {
+            if user.len() > u8::MAX as usize || password.len() > u8::MAX as usize {
+                bail!("Credentials of proxy {} are too long", self);
+            }
+            let mut msg = vec![SOCKS5_AUTH_USRPWD_VERSION, user.len() as u8];
+            msg.extend_from_slice(user.as_bytes());
+            msg.push(password.len() as u8);
+            msg.extend_from_slice(password.as_bytes());
+            stream.write_all(&msg).await?;
+            stream.read_exact(&mut reply).await?;
+            if reply[1] != 0 {
+                bail!("Proxy {} rejected the credentials", self);
+            }
+        }
+
+        // Connect request
+        let mut msg = vec![SOCKS5_VERSION, SOCKS5_CMD_CONNECT, 0];
+        match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                msg.push(SOCKS5_ATYP_IPV4);
+                msg.extend_from_slice(&ip.octets());
+            }
+            Ok(IpAddr::V6(ip)) => {
+                msg.push(SOCKS5_ATYP_IPV6);
+                msg.extend_from_slice(&ip.octets());
+            }
+            Err(_) => {
+                if host.len() > u8::MAX as usize {
+                    bail!("Host name {} is too long for proxy {}", host, self);
+                }
+                msg.push(SOCKS5_ATYP_DOMAIN);
+                msg.push(host.len() as u8);
+                msg.extend_from_slice(host.as_bytes());
+            }
+        }
+        msg.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&msg).await?;
+
+        let mut reply = [0u8; 4];
+        stream.read_exact(&mut reply).await?;
+        if reply[1] != SOCKS5_REP_SUCCEEDED {
+            bail!(
+                "Proxy {} failed to connect to {}: error code {}",
+                self,
+                target,
+                reply[1]
+            );
+        }
+        // Skip the bound address and port
+        let len = match reply[3] {
+            SOCKS5_ATYP_IPV4 => 4,
+            SOCKS5_ATYP_IPV6 => 16,
+            SOCKS5_ATYP_DOMAIN => {
+                let mut len = [0u8; 1];
+                stream.read_exact(&mut len).await?;
+                len[0] as usize
+            }
+            atyp => bail!("Proxy {} replied with invalid address type {}", self, atyp),
+        };
+        let mut bound = vec![0u8; len + 2];
+        stream.read_exact(&mut bound).await?;
+        Ok(())
+    }
+
+    async fn http_handshake(&self, stream: &mut TcpStream, target: &str) -> ZResult<()> {
+        let mut request = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+        if let Some((user, password)) = &self.credentials {
+            let token = b64_std_engine.encode(format!("{user}:{password}"));
+            request.push_str(&format!("Proxy-Authorization: Basic {token}\r\n"));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        // Read the response header byte by byte so as not to consume any tunneled data
+        let mut response = Vec::new();
+        let mut byte = [0u8; 1];
+        while !response.ends_with(b"\r\n\r\n") {
+            if response.len() >= HTTP_MAX_RESPONSE_LEN {
+                bail!("Proxy {} replied with a too long response", self);
+            }
+            stream.read_exact(&mut byte).await?;
+            response.push(byte[0]);
+        }
+        let response = String::from_utf8_lossy(&response);
+        let status = response.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(()),
+            _ => bail!("Proxy {} failed to connect to {}: {}", self, target, status),
+        }
+    }
+}
+
+fn split_target(target: &str) -> ZResult<(&str, u16)> {
+    let (host, port) = target
+        .rsplit_once(':')
+        .ok_or_else(|| zerror!("Invalid address {}: missing port", target))?;
+    let port = port
+        .parse::<u16>()
+        .map_err(|e| zerror!("Invalid port in {}: {}", target, e))?;
+    Ok((host.trim_start_matches('[').trim_end_matches(']'), port))
+}
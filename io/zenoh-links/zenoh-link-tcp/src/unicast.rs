@@ -25,12 +25,15 @@ use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use zenoh_core::{zread, zwrite};
 use zenoh_link_commons::{
-    LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
+    config::PROXY, LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
 };
 use zenoh_protocol::core::{EndPoint, Locator};
 use zenoh_result::{bail, zerror, Error as ZError, ZResult};
 use zenoh_sync::Signal;
 
+use super::proxy::Proxy;
+#[cfg(all(target_os = "linux", feature = "io_uring"))]
+use super::uring::UringSocket;
 use super::{
     get_tcp_addrs, TCP_ACCEPT_THROTTLE_TIME, TCP_DEFAULT_MTU, TCP_LINGER_TIMEOUT,
     TCP_LOCATOR_PREFIX,
@@ -39,6 +42,9 @@ use super::{
 pub struct LinkUnicastTcp {
     // The underlying socket as returned from the async-std library
     socket: TcpStream,
+    // The io_uring rings the socket is read and written with, if supported by the kernel
+    #[cfg(all(target_os = "linux", feature = "io_uring"))]
+    uring: Option<UringSocket>,
     // The source socket address of this link (address used on the local host)
     src_addr: SocketAddr,
     src_locator: Locator,
@@ -74,9 +80,25 @@ impl LinkUnicastTcp {
             );
         }
 
+        #[cfg(all(target_os = "linux", feature = "io_uring"))]
+        let uring = match UringSocket::new(&socket) {
+            Ok(uring) => Some(uring),
+            Err(err) => {
+                log::debug!(
+                    "Unable to use io_uring on TCP link {} => {}, falling back to epoll: {}",
+                    src_addr,
+                    dst_addr,
+                    err
+                );
+                None
+            }
+        };
+
         // Build the Tcp object
         LinkUnicastTcp {
             socket,
+            #[cfg(all(target_os = "linux", feature = "io_uring"))]
+            uring,
             src_addr,
             src_locator: Locator::new(TCP_LOCATOR_PREFIX, src_addr.to_string(), "").unwrap(),
             dst_addr,
@@ -98,6 +120,14 @@ impl LinkUnicastTrait for LinkUnicastTcp {
     }
 
     async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        #[cfg(all(target_os = "linux", feature = "io_uring"))]
+        if let Some(uring) = &self.uring {
+            return uring.write(buffer).await.map_err(|e| {
+                let e = zerror!("Write error on TCP link {}: {}", self, e);
+                log::trace!("{}", e);
+                e.into()
+            });
+        }
         (&self.socket).write(buffer).await.map_err(|e| {
             let e = zerror!("Write error on TCP link {}: {}", self, e);
             log::trace!("{}", e);
@@ -106,6 +136,14 @@ impl LinkUnicastTrait for LinkUnicastTcp {
     }
 
     async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        #[cfg(all(target_os = "linux", feature = "io_uring"))]
+        if let Some(uring) = &self.uring {
+            return uring.write_all(buffer).await.map_err(|e| {
+                let e = zerror!("Write error on TCP link {}: {}", self, e);
+                log::trace!("{}", e);
+                e.into()
+            });
+        }
         (&self.socket).write_all(buffer).await.map_err(|e| {
             let e = zerror!("Write error on TCP link {}: {}", self, e);
             log::trace!("{}", e);
@@ -114,6 +152,14 @@ impl LinkUnicastTrait for LinkUnicastTcp {
     }
 
     async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        #[cfg(all(target_os = "linux", feature = "io_uring"))]
+        if let Some(uring) = &self.uring {
+            return uring.read(buffer).await.map_err(|e| {
+                let e = zerror!("Read error on TCP link {}: {}", self, e);
+                log::trace!("{}", e);
+                e.into()
+            });
+        }
         (&self.socket).read(buffer).await.map_err(|e| {
             let e = zerror!("Read error on TCP link {}: {}", self, e);
             log::trace!("{}", e);
@@ -122,6 +168,14 @@ impl LinkUnicastTrait for LinkUnicastTcp {
     }
 
     async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        #[cfg(all(target_os = "linux", feature = "io_uring"))]
+        if let Some(uring) = &self.uring {
+            return uring.read_exact(buffer).await.map_err(|e| {
+                let e = zerror!("Read error on TCP link {}: {}", self, e);
+                log::trace!("{}", e);
+                e.into()
+            });
+        }
         (&self.socket).read_exact(buffer).await.map_err(|e| {
             let e = zerror!("Read error on TCP link {}: {}", self, e);
             log::trace!("{}", e);
@@ -238,6 +292,25 @@ impl LinkManagerUnicastTcp {
         Ok((stream, src_addr, dst_addr))
     }
 
+    async fn new_link_proxy_inner(
+        &self,
+        proxy: &Proxy,
+        dst_addr: &str,
+    ) -> ZResult<(TcpStream, SocketAddr, SocketAddr)> {
+        let stream = proxy.connect(dst_addr).await?;
+
+        let src_addr = stream
+            .local_addr()
+            .map_err(|e| zerror!("{}: {}", dst_addr, e))?;
+
+        // The remote end of the socket is the proxy
+        let dst_addr = stream
+            .peer_addr()
+            .map_err(|e| zerror!("{}: {}", dst_addr, e))?;
+
+        Ok((stream, src_addr, dst_addr))
+    }
+
     async fn new_listener_inner(&self, addr: &SocketAddr) -> ZResult<(TcpListener, SocketAddr)> {
         // Bind the TCP socket
         let socket = TcpListener::bind(addr)
@@ -255,6 +328,18 @@ impl LinkManagerUnicastTcp {
 #[async_trait]
 impl LinkManagerUnicastTrait for LinkManagerUnicastTcp {
     async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
+        if let Some(proxy) = endpoint.config().get(PROXY) {
+            let proxy: Proxy = proxy.parse()?;
+            let (stream, src_addr, dst_addr) = self
+                .new_link_proxy_inner(&proxy, endpoint.address().as_str())
+                .await
+                .map_err(|e| {
+                    zerror!("Can not create a new TCP link bound to {}: {}", endpoint, e)
+                })?;
+            let link = Arc::new(LinkUnicastTcp::new(stream, src_addr, dst_addr));
+            return Ok(LinkUnicast(link));
+        }
+
         let dst_addrs = get_tcp_addrs(endpoint.address()).await?;
 
         let mut errs: Vec<ZError> = vec![];
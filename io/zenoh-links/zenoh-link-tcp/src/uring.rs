@@ -0,0 +1,526 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! An io_uring backend for the TCP links.
+//!
+//! The links share a ring, driven by a thread which submits at once all the operations queued since its last
+//! submission, and wakes the tasks awaiting their completion: at high message rates, many reads and writes
+//! are submitted with a single system call.
+//!
+//! Each link reads and writes through two buffers registered with the ring. A buffer is only reused once the
+//! operation on it completes, so that a cancelled read or write never leaves the kernel with a dangling
+//! pointer. Likewise, each link reads and writes through a duplicate of its socket's file descriptor, only closed
+//! once the operations on it complete, so that they never run against a descriptor reused in the meantime.
+//! The sockets stay non-blocking: each read or write is linked to a poll of the socket.
+use async_std::net::TcpStream;
+use async_std::sync::Mutex as AsyncMutex;
+use io_uring::{opcode, squeue, types, IoUring, Probe};
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::future::Future;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::os::fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::pin::Pin;
+use std::sync::atomic::{fence, AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use zenoh_core::zlock;
+
+const ENTRIES: u32 = 256;
+// The number of registered buffers, two per link: the links beyond fall back to epoll
+const BUFFERS: u16 = 64;
+// The size of each registered buffer, the one of a batch
+const BUFFER_SIZE: usize = 1 << 16;
+
+// The user data of the completions not awaited by a link, the operations being numbered from 2
+const WAKE: u64 = 0;
+const CANCEL: u64 = 1;
+// Set in the user data of the poll an operation is linked to
+const POLL: u64 = 1 << 63;
+
+lazy_static::lazy_static! {
+    // The ring shared by the links, if supported by the kernel
+    static ref DRIVER: Result<Arc<Driver>, String> = Driver::new().map_err(|e| e.to_string());
+}
+
+fn driver() -> io::Result<&'static Driver> {
+    match &*DRIVER {
+        Ok(driver) => Ok(driver),
+        Err(e) => Err(io::Error::new(io::ErrorKind::Unsupported, e.clone())),
+    }
+}
+
+enum Op {
+    // Awaited by a read or write, if polled
+    Pending(Option<Waker>),
+    Complete(i32),
+    // Of a dropped link, its buffer being released and its file descriptor closed on completion
+    Orphan(u16, Arc<OwnedFd>),
+}
+
+struct Driver {
+    ring: IoUring,
+    // Held while pushing to the submission queue
+    submission: Mutex<()>,
+    ops: Mutex<HashMap<u64, Op>>,
+    next_id: AtomicU64,
+    // Whether the driver thread waits for completions, in which case it has to be woken to submit new entries
+    waiting: AtomicBool,
+    // Written to wake the driver thread, which always has a read of it in flight
+    wake: File,
+    wake_buffer: Box<UnsafeCell<u64>>,
+    // The registered buffers, only accessed by the link owning them while no operation is in flight on them
+    memory: Box<[UnsafeCell<u8>]>,
+    free: Mutex<Vec<u16>>,
+}
+
+unsafe impl Send for Driver {}
+unsafe impl Sync for Driver {}
+
+impl Driver {
+    fn new() -> io::Result<Arc<Self>> {
+        let ring = IoUring::new(ENTRIES)?;
+        // Sockets are polled by the kernel instead of a worker thread since Linux 5.7
+        if !ring.params().is_feature_fast_poll() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "io_uring fast poll is not supported by the kernel",
+            ));
+        }
+        let mut probe = Probe::new();
+        ring.submitter().register_probe(&mut probe)?;
+        for code in [
+            opcode::PollAdd::CODE,
+            opcode::ReadFixed::CODE,
+            opcode::Send::CODE,
+            opcode::Read::CODE,
+            opcode::AsyncCancel::CODE,
+        ] {
+            if !probe.is_supported(code) {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("io_uring operation {} is not supported by the kernel", code),
+                ));
+            }
+        }
+
+        let memory: Box<[UnsafeCell<u8>]> = (0..BUFFERS as usize * BUFFER_SIZE)
+            .map(|_| UnsafeCell::new(0))
+            .collect();
+        let base = UnsafeCell::raw_get(memory.as_ptr());
+        let iovecs: Vec<libc::iovec> = (0..BUFFERS as usize)
+            .map(|index| libc::iovec {
+                iov_base: unsafe { base.add(index * BUFFER_SIZE) } as *mut libc::c_void,
+                iov_len: BUFFER_SIZE,
+            })
+            .collect();
+        // Safety: the buffers live as long as the ring
+        unsafe { ring.submitter().register_buffers(&iovecs)? };
+
+        let wake = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC) };
+        if wake < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let driver = Arc::new(Driver {
+            ring,
+            submission: Mutex::new(()),
+            ops: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(2),
+            waiting: AtomicBool::new(false),
+            wake: unsafe { File::from_raw_fd(wake) },
+            wake_buffer: Box::new(UnsafeCell::new(0)),
+            memory,
+            free: Mutex::new((0..BUFFERS).collect()),
+        });
+        driver.arm_wake()?;
+        let runner = driver.clone();
+        std::thread::Builder::new()
+            .name("zenoh-tcp-uring".into())
+            .spawn(move || runner.run())?;
+        Ok(driver)
+    }
+
+    fn arm_wake(&self) -> io::Result<()> {
+        let read = opcode::Read::new(
+            types::Fd(self.wake.as_raw_fd()),
+            self.wake_buffer.get() as *mut u8,
+            8,
+        )
+        .build()
+        .user_data(WAKE);
+        self.push(&[read])
+    }
+
+    // Queues entries to be submitted at once by the driver thread, waking it if it waits for completions
+    fn push(&self, entries: &[squeue::Entry]) -> io::Result<()> {
+        {
+            let _guard = zlock!(self.submission);
+            // Safety: the submission queue is only accessed with the submission lock held,
+            // and the entries point to buffers which outlive their operations
+            while unsafe { self.ring.submission_shared().push_multiple(entries) }.is_err() {
+                // The submission queue is full of entries not yet submitted
+                self.ring.submitter().submit()?;
+            }
+        }
+        fence(Ordering::SeqCst);
+        if self.waiting.swap(false, Ordering::SeqCst) {
+            (&self.wake).write_all(&1u64.to_ne_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn run(&self) {
+        loop {
+            self.waiting.store(true, Ordering::SeqCst);
+            fence(Ordering::SeqCst);
+            if let Err(e) = self.ring.submitter().submit_and_wait(1) {
+                match e.raw_os_error() {
+                    Some(libc::EINTR) | Some(libc::EAGAIN) | Some(libc::EBUSY) => {}
+                    _ => {
+                        log::error!("Unable to submit io_uring operations: {}", e);
+                        std::thread::sleep(Duration::from_millis(100));
+                    }
+                }
+            }
+            self.waiting.store(false, Ordering::SeqCst);
+            // Safety: the completion queue is only accessed by the driver thread
+            let completions: Vec<(u64, i32)> = unsafe { self.ring.completion_shared() }
+                .map(|cqe| (cqe.user_data(), cqe.result()))
+                .collect();
+            for (user_data, res) in completions {
+                self.complete(user_data, res);
+            }
+        }
+    }
+
+    fn complete(&self, user_data: u64, res: i32) {
+        match user_data {
+            WAKE => {
+                if let Err(e) = self.arm_wake() {
+                    log::error!("Unable to wait for io_uring operations: {}", e);
+                }
+            }
+            CANCEL => {}
+            _ if user_data & POLL != 0 => {}
+            id => {
+                let mut ops = zlock!(self.ops);
+                match ops.remove(&id) {
+                    Some(Op::Pending(waker)) => {
+                        ops.insert(id, Op::Complete(res));
+                        drop(ops);
+                        if let Some(waker) = waker {
+                            waker.wake();
+                        }
+                    }
+                    Some(Op::Orphan(buffer, fd)) => {
+                        drop(ops);
+                        drop(fd);
+                        self.release(buffer);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn acquire(&self) -> Option<u16> {
+        zlock!(self.free).pop()
+    }
+
+    fn release(&self, buffer: u16) {
+        zlock!(self.free).push(buffer);
+    }
+
+    // Safety: the buffer must be owned by the caller, and not used by an operation in flight
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn buffer(&self, index: u16) -> &mut [u8] {
+        let base = UnsafeCell::raw_get(self.memory.as_ptr());
+        std::slice::from_raw_parts_mut(base.add(index as usize * BUFFER_SIZE), BUFFER_SIZE)
+    }
+}
+
+struct Completion {
+    driver: &'static Driver,
+    id: u64,
+}
+
+impl Future for Completion {
+    type Output = i32;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut ops = zlock!(self.driver.ops);
+        match ops.get_mut(&self.id) {
+            Some(Op::Pending(waker)) => {
+                *waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            Some(Op::Complete(res)) => {
+                let res = *res;
+                ops.remove(&self.id);
+                Poll::Ready(res)
+            }
+            _ => Poll::Ready(-libc::ECANCELED),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    // The operation in flight of a cancelled read or write
+    pending: Option<u64>,
+    // The bytes of the buffer read by a cancelled read, not yet returned
+    unread: Range<usize>,
+}
+
+struct Channel {
+    buffer: u16,
+    state: AsyncMutex<State>,
+}
+
+/// The reads and writes of a TCP socket through io_uring.
+pub(crate) struct UringSocket {
+    driver: &'static Driver,
+    // A duplicate of the socket's file descriptor, outliving the operations on it
+    fd: Arc<OwnedFd>,
+    rx: Channel,
+    tx: Channel,
+}
+
+impl UringSocket {
+    /// Fails if io_uring is not supported by the kernel, or is disabled, or if all its buffers are in use.
+    pub(crate) fn new(socket: &TcpStream) -> io::Result<Self> {
+        let driver = driver()?;
+        // Safety: the socket's file descriptor is open while borrowed
+        let fd = unsafe { BorrowedFd::borrow_raw(socket.as_raw_fd()) };
+        let fd = Arc::new(fd.try_clone_to_owned()?);
+        let Some(rx) = driver.acquire() else {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "all the io_uring buffers are in use",
+            ));
+        };
+        let Some(tx) = driver.acquire() else {
+            driver.release(rx);
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "all the io_uring buffers are in use",
+            ));
+        };
+        let channel = |buffer| Channel {
+            buffer,
+            state: AsyncMutex::new(State::default()),
+        };
+        Ok(UringSocket {
+            driver,
+            fd,
+            rx: channel(rx),
+            tx: channel(tx),
+        })
+    }
+
+    // Queues an operation, linked to a poll of the socket for `events`, and awaits its completion.
+    // If cancelled, the operation is awaited by the next one.
+    async fn operation(
+        &self,
+        state: &mut State,
+        events: i16,
+        op: squeue::Entry,
+    ) -> io::Result<i32> {
+        let id = self.driver.next_id.fetch_add(1, Ordering::Relaxed);
+        zlock!(self.driver.ops).insert(id, Op::Pending(None));
+        let poll = opcode::PollAdd::new(types::Fd(self.fd.as_raw_fd()), events as u32)
+            .build()
+            .flags(squeue::Flags::IO_LINK)
+            .user_data(id | POLL);
+        if let Err(e) = self.driver.push(&[poll, op.user_data(id)]) {
+            zlock!(self.driver.ops).remove(&id);
+            return Err(e);
+        }
+        state.pending = Some(id);
+        let res = self.settle(state).await.unwrap_or(-libc::ECANCELED);
+        Ok(res)
+    }
+
+    // Awaits the completion of the operation in flight, if any
+    async fn settle(&self, state: &mut State) -> Option<i32> {
+        let id = state.pending?;
+        let res = Completion {
+            driver: self.driver,
+            id,
+        }
+        .await;
+        state.pending = None;
+        Some(res)
+    }
+
+    pub(crate) async fn write(&self, buffer: &[u8]) -> io::Result<usize> {
+        let mut state = self.tx.state.lock().await;
+        // The bytes written by a cancelled write are lost to its caller, which can't resume the stream anyway
+        self.settle(&mut state).await;
+        let len = buffer.len().min(BUFFER_SIZE);
+        // Safety: no operation is in flight on the buffer of the link
+        let data = unsafe { self.driver.buffer(self.tx.buffer) };
+        data[..len].copy_from_slice(&buffer[..len]);
+        loop {
+            // A write of a registered buffer can't be asked not to raise SIGPIPE, unlike a send
+            let op = opcode::Send::new(types::Fd(self.fd.as_raw_fd()), data.as_ptr(), len as u32)
+                .flags(libc::MSG_NOSIGNAL)
+                .build();
+            match self.operation(&mut state, libc::POLLOUT, op).await? {
+                res if res == -libc::EAGAIN => continue,
+                res if res < 0 => return Err(io::Error::from_raw_os_error(-res)),
+                res => return Ok(res as usize),
+            }
+        }
+    }
+
+    pub(crate) async fn write_all(&self, mut buffer: &[u8]) -> io::Result<()> {
+        while !buffer.is_empty() {
+            match self.write(buffer).await? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                n => buffer = &buffer[n..],
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn read(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        let mut state = self.rx.state.lock().await;
+        // The bytes read by a cancelled read are returned first
+        if let Some(res) = self.settle(&mut state).await {
+            if res > 0 {
+                state.unread = 0..res as usize;
+            }
+        }
+        // Safety: no operation is in flight on the buffer of the link
+        let data = unsafe { self.driver.buffer(self.rx.buffer) };
+        while state.unread.is_empty() {
+            let len = buffer.len().min(BUFFER_SIZE) as u32;
+            let fd = types::Fd(self.fd.as_raw_fd());
+            let op = opcode::ReadFixed::new(fd, data.as_mut_ptr(), len, self.rx.buffer).build();
+            match self.operation(&mut state, libc::POLLIN, op).await? {
+                0 => return Ok(0),
+                res if res == -libc::EAGAIN => continue,
+                res if res < 0 => return Err(io::Error::from_raw_os_error(-res)),
+                res => state.unread = 0..res as usize,
+            }
+        }
+        let len = buffer.len().min(state.unread.len());
+        buffer[..len].copy_from_slice(&data[state.unread.start..state.unread.start + len]);
+        state.unread.start += len;
+        Ok(len)
+    }
+
+    pub(crate) async fn read_exact(&self, mut buffer: &mut [u8]) -> io::Result<()> {
+        while !buffer.is_empty() {
+            match self.read(buffer).await? {
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                n => buffer = &mut buffer[n..],
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for UringSocket {
+    fn drop(&mut self) {
+        // The operations still in flight are cancelled, their buffers being released and the file descriptor closed
+        // on completion
+        let driver = self.driver;
+        let mut cancels = vec![];
+        let mut ops = zlock!(driver.ops);
+        for channel in [&mut self.rx, &mut self.tx] {
+            match channel.state.get_mut().pending {
+                Some(id) if matches!(ops.get(&id), Some(Op::Pending(_))) => {
+                    ops.insert(id, Op::Orphan(channel.buffer, self.fd.clone()));
+                    for target in [id | POLL, id] {
+                        cancels.push(opcode::AsyncCancel::new(target).build().user_data(CANCEL));
+                    }
+                }
+                Some(id) => {
+                    ops.remove(&id);
+                    driver.release(channel.buffer);
+                }
+                None => driver.release(channel.buffer),
+            }
+        }
+        drop(ops);
+        if !cancels.is_empty() {
+            if let Err(e) = driver.push(&cancels) {
+                log::error!("Unable to cancel io_uring operations: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::future::timeout;
+    use async_std::net::TcpListener;
+    use async_std::prelude::*;
+    use async_std::task;
+
+    #[test]
+    fn uring_loopback() {
+        task::block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let client = TcpStream::connect(listener.local_addr().unwrap())
+                .await
+                .unwrap();
+            let (server, _) = listener.accept().await.unwrap();
+            let uring = match UringSocket::new(&client) {
+                Ok(uring) => uring,
+                Err(e) => {
+                    println!("Skipping the io_uring test: {}", e);
+                    return;
+                }
+            };
+            let free = || zlock!(driver().unwrap().free).len();
+            let available = free();
+
+            let mut buffer = [0; 5];
+            uring.write_all(b"hello").await.unwrap();
+            (&server).read_exact(&mut buffer).await.unwrap();
+            assert_eq!(&buffer, b"hello");
+            (&server).write_all(b"world").await.unwrap();
+            uring.read_exact(&mut buffer).await.unwrap();
+            assert_eq!(&buffer, b"world");
+
+            // The bytes received after a read is cancelled are returned by the next one
+            let read = timeout(Duration::from_millis(100), uring.read(&mut buffer)).await;
+            assert!(read.is_err());
+            (&server).write_all(b"again").await.unwrap();
+            uring.read_exact(&mut buffer).await.unwrap();
+            assert_eq!(&buffer, b"again");
+
+            // The buffers of a socket dropped with a read in flight are released once the read is cancelled
+            let read = timeout(Duration::from_millis(100), uring.read(&mut buffer)).await;
+            assert!(read.is_err());
+            drop(uring);
+            timeout(Duration::from_secs(10), async {
+                while free() != available + 2 {
+                    task::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .unwrap();
+        });
+    }
+}
@@ -0,0 +1,183 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::convert::TryFrom;
+use webpki::EndEntityCert;
+
+// The DER encoding of the commonName attribute type (2.5.4.3)
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+// The DER encoding of the extKeyUsage extension type (2.5.29.37)
+const OID_EXT_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x25];
+const TAG_VERSION: u8 = 0xa0;
+const TAG_EXTENSIONS: u8 = 0xa3;
+const TAG_BOOLEAN: u8 = 0x01;
+
+/// Returns the names of a DER-encoded certificate: its subject common name
+/// followed by its subject alternative DNS names.
+pub(crate) fn cert_names(der: &[u8]) -> Vec<String> {
+    let mut names: Vec<String> = common_name(der).into_iter().collect();
+    if let Ok(cert) = EndEntityCert::try_from(der) {
+        if let Ok(dns_names) = cert.dns_names() {
+            for name in dns_names.map(<&str>::from) {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Reads a DER element, returning its tag, its content and the bytes following it.
+pub(crate) fn read(bytes: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, bytes) = bytes.split_first()?;
+    let (&len, mut bytes) = bytes.split_first()?;
+    let len = if len & 0x80 == 0 {
+        len as usize
+    } else {
+        let n = (len & 0x7f) as usize;
+        if n == 0 || n > 4 || bytes.len() < n {
+            return None;
+        }
+        let (len, rest) = bytes.split_at(n);
+        bytes = rest;
+        len.iter().fold(0, |len, b| (len << 8) | *b as usize)
+    };
+    if bytes.len() < len {
+        return None;
+    }
+    let (content, rest) = bytes.split_at(len);
+    Some((tag, content, rest))
+}
+
+/// Reads a DER element, returning its whole encoding and the bytes following it.
+pub(crate) fn read_raw(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (_, _, rest) = read(bytes)?;
+    Some((&bytes[..bytes.len() - rest.len()], rest))
+}
+
+/// The fields of a DER-encoded certificate involved in the revocation checks.
+pub(crate) struct TbsFields<'a> {
+    /// The content of the serial number.
+    pub(crate) serial: &'a [u8],
+    /// The DER encoding of the issuer name.
+    pub(crate) issuer: &'a [u8],
+    /// The content of the validity, i.e. its notBefore and notAfter times.
+    pub(crate) validity: &'a [u8],
+    /// The DER encoding of the subject name.
+    pub(crate) subject: &'a [u8],
+    /// The subject public key, without the number of unused bits of its bit string.
+    pub(crate) public_key: &'a [u8],
+    /// The content of the extensions, if any.
+    pub(crate) extensions: Option<&'a [u8]>,
+}
+
+pub(crate) fn tbs_fields(der: &[u8]) -> Option<TbsFields<'_>> {
+    let (_, cert, _) = read(der)?;
+    let (_, tbs, _) = read(cert)?;
+    // Skip the optional version
+    let (tag, _, mut fields) = read(tbs)?;
+    if tag != TAG_VERSION {
+        fields = tbs;
+    }
+    let (_, serial, fields) = read(fields)?;
+    // Skip the signature algorithm
+    let (_, _, fields) = read(fields)?;
+    let (issuer, fields) = read_raw(fields)?;
+    let (_, validity, fields) = read(fields)?;
+    let (subject, fields) = read_raw(fields)?;
+    // SubjectPublicKeyInfo ::= SEQUENCE { algorithm AlgorithmIdentifier, subjectPublicKey BIT STRING }
+    let (_, spki, mut fields) = read(fields)?;
+    let (_, _, spki) = read(spki)?;
+    let (_, public_key, _) = read(spki)?;
+    let public_key = public_key.get(1..)?;
+    // Look for the extensions among the optional fields
+    let mut extensions = None;
+    while let Some((tag, content, rest)) = read(fields) {
+        if tag == TAG_EXTENSIONS {
+            extensions = Some(read(content)?.1);
+        }
+        fields = rest;
+    }
+    Some(TbsFields {
+        serial,
+        issuer,
+        validity,
+        subject,
+        public_key,
+        extensions,
+    })
+}
+
+/// Splits a DER-encoded signed structure (e.g. a certificate) into the DER encoding of its signed data,
+/// the object identifier of its signature algorithm and its signature.
+pub(crate) fn signed_parts(der: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let (_, signed, _) = read(der)?;
+    let (data, rest) = read_raw(signed)?;
+    let (_, algorithm, rest) = read(rest)?;
+    let (_, algorithm, _) = read(algorithm)?;
+    let (_, signature, _) = read(rest)?;
+    // Skip the number of unused bits of the signature bit string
+    Some((data, algorithm, signature.get(1..)?))
+}
+
+/// Whether a DER-encoded certificate has the given extended key usage.
+pub(crate) fn has_extended_key_usage(der: &[u8], usage: &[u8]) -> bool {
+    let has_usage = || {
+        let mut extensions = tbs_fields(der)?.extensions?;
+        // Extension ::= SEQUENCE { extnID OBJECT IDENTIFIER, critical BOOLEAN DEFAULT FALSE, extnValue OCTET STRING }
+        while !extensions.is_empty() {
+            let (_, extension, rest) = read(extensions)?;
+            extensions = rest;
+            let (_, oid, mut value) = read(extension)?;
+            if oid != OID_EXT_KEY_USAGE {
+                continue;
+            }
+            let (tag, _, rest) = read(value)?;
+            if tag == TAG_BOOLEAN {
+                value = rest;
+            }
+            let (_, value, _) = read(value)?;
+            // ExtKeyUsageSyntax ::= SEQUENCE OF KeyPurposeId
+            let (_, mut usages, _) = read(value)?;
+            while !usages.is_empty() {
+                let (_, oid, rest) = read(usages)?;
+                if oid == usage {
+                    return Some(true);
+                }
+                usages = rest;
+            }
+        }
+        Some(false)
+    };
+    has_usage().unwrap_or(false)
+}
+
+fn common_name(der: &[u8]) -> Option<String> {
+    // Name ::= SEQUENCE OF SET OF SEQUENCE { type OBJECT IDENTIFIER, value ANY }
+    let (_, mut rdns, _) = read(tbs_fields(der)?.subject)?;
+    while !rdns.is_empty() {
+        let (_, mut attributes, rest) = read(rdns)?;
+        rdns = rest;
+        while !attributes.is_empty() {
+            let (_, attribute, rest) = read(attributes)?;
+            attributes = rest;
+            let (_, oid, value) = read(attribute)?;
+            if oid == OID_COMMON_NAME {
+                let (_, name, _) = read(value)?;
+                return std::str::from_utf8(name).ok().map(str::to_string);
+            }
+        }
+    }
+    None
+}
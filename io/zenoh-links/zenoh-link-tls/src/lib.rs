@@ -20,11 +20,16 @@
 use async_rustls::rustls::ServerName;
 use async_std::net::ToSocketAddrs;
 use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
 use config::{
-    TLS_CLIENT_AUTH, TLS_CLIENT_CERTIFICATE_FILE, TLS_CLIENT_PRIVATE_KEY_FILE,
-    TLS_ROOT_CA_CERTIFICATE_FILE, TLS_SERVER_CERTIFICATE_FILE, TLS_SERVER_NAME_VERIFICATION,
-    TLS_SERVER_PRIVATE_KEY_FILE,
+    TLS_CLIENT_AUTH, TLS_CLIENT_CERTIFICATE_FILE, TLS_CLIENT_CERTIFICATE_RAW,
+    TLS_CLIENT_PRIVATE_KEY_FILE, TLS_CLIENT_PRIVATE_KEY_RAW, TLS_CRL_FILE, TLS_CRL_RAW,
+    TLS_CRL_RELOAD_INTERVAL, TLS_OCSP_REQUIRED, TLS_ROOT_CA_CERTIFICATE_FILE,
+    TLS_ROOT_CA_CERTIFICATE_RAW, TLS_SERVER_CERTIFICATE_FILE, TLS_SERVER_CERTIFICATE_RAW,
+    TLS_SERVER_NAME_VERIFICATION, TLS_SERVER_OCSP_RESPONSE_FILE, TLS_SERVER_PRIVATE_KEY_FILE,
+    TLS_SERVER_PRIVATE_KEY_RAW,
 };
+use std::borrow::Cow;
 use std::{convert::TryFrom, net::SocketAddr};
 use zenoh_config::Config;
 use zenoh_core::zconfigurable;
@@ -35,6 +40,8 @@ use zenoh_protocol::core::{
 };
 use zenoh_result::{bail, zerror, ZResult};
 
+mod cert;
+mod revocation;
 mod unicast;
 mod verify;
 pub use unicast::*;
@@ -66,44 +73,112 @@ pub struct TlsConfigurator;
 #[async_trait]
 impl ConfigurationInspector<Config> for TlsConfigurator {
     async fn inspect_config(&self, config: &Config) -> ZResult<String> {
-        let mut ps: Vec<(&str, &str)> = vec![];
+        let mut ps: Vec<(&str, Cow<str>)> = vec![];
 
         let c = config.transport().link().tls();
         if let Some(ca_certificate) = c.root_ca_certificate() {
-            ps.push((TLS_ROOT_CA_CERTIFICATE_FILE, ca_certificate));
+            ps.push(tls_material(
+                TLS_ROOT_CA_CERTIFICATE_FILE,
+                TLS_ROOT_CA_CERTIFICATE_RAW,
+                ca_certificate,
+            )?);
         }
         if let Some(server_private_key) = c.server_private_key() {
-            ps.push((TLS_SERVER_PRIVATE_KEY_FILE, server_private_key));
+            ps.push(tls_material(
+                TLS_SERVER_PRIVATE_KEY_FILE,
+                TLS_SERVER_PRIVATE_KEY_RAW,
+                server_private_key,
+            )?);
         }
         if let Some(server_certificate) = c.server_certificate() {
-            ps.push((TLS_SERVER_CERTIFICATE_FILE, server_certificate));
+            ps.push(tls_material(
+                TLS_SERVER_CERTIFICATE_FILE,
+                TLS_SERVER_CERTIFICATE_RAW,
+                server_certificate,
+            )?);
         }
         if let Some(client_auth) = c.client_auth() {
             match client_auth {
-                true => ps.push((TLS_CLIENT_AUTH, "true")),
-                false => ps.push((TLS_CLIENT_AUTH, "false")),
+                true => ps.push((TLS_CLIENT_AUTH, "true".into())),
+                false => ps.push((TLS_CLIENT_AUTH, "false".into())),
             };
         }
         if let Some(client_private_key) = c.client_private_key() {
-            ps.push((TLS_CLIENT_PRIVATE_KEY_FILE, client_private_key));
+            ps.push(tls_material(
+                TLS_CLIENT_PRIVATE_KEY_FILE,
+                TLS_CLIENT_PRIVATE_KEY_RAW,
+                client_private_key,
+            )?);
         }
         if let Some(client_certificate) = c.client_certificate() {
-            ps.push((TLS_CLIENT_CERTIFICATE_FILE, client_certificate));
+            ps.push(tls_material(
+                TLS_CLIENT_CERTIFICATE_FILE,
+                TLS_CLIENT_CERTIFICATE_RAW,
+                client_certificate,
+            )?);
         }
         if let Some(server_name_verification) = c.server_name_verification() {
             match server_name_verification {
-                true => ps.push((TLS_SERVER_NAME_VERIFICATION, "true")),
-                false => ps.push((TLS_SERVER_NAME_VERIFICATION, "false")),
+                true => ps.push((TLS_SERVER_NAME_VERIFICATION, "true".into())),
+                false => ps.push((TLS_SERVER_NAME_VERIFICATION, "false".into())),
+            };
+        }
+        if let Some(crl) = c.crl() {
+            ps.push(tls_material(TLS_CRL_FILE, TLS_CRL_RAW, crl)?);
+        }
+        if let Some(crl_reload_interval) = c.crl_reload_interval() {
+            ps.push((
+                TLS_CRL_RELOAD_INTERVAL,
+                crl_reload_interval.to_string().into(),
+            ));
+        }
+        if let Some(server_ocsp_response) = c.server_ocsp_response() {
+            ps.push((TLS_SERVER_OCSP_RESPONSE_FILE, server_ocsp_response.into()));
+        }
+        if let Some(ocsp_required) = c.ocsp_required() {
+            match ocsp_required {
+                true => ps.push((TLS_OCSP_REQUIRED, "true".into())),
+                false => ps.push((TLS_OCSP_REQUIRED, "false".into())),
             };
         }
 
         let mut s = String::new();
-        endpoint::Parameters::extend(ps.drain(..), &mut s);
+        endpoint::Parameters::extend(ps.iter().map(|(k, v)| (*k, v.as_ref())), &mut s);
 
         Ok(s)
     }
 }
 
+const PEM_PREFIX: &str = "-----BEGIN";
+const ENV_PREFIX: &str = "env:";
+
+/// Returns the endpoint configuration entry of a certificate or a key given in the configuration either as
+/// a path (`file_key`), as inline PEM, or as a reference to an environment variable holding it as PEM or
+/// base64-encoded PEM (`env:<VAR>`); the latter ones are passed as raw PEM (`raw_key`).
+fn tls_material<'a>(
+    file_key: &'a str,
+    raw_key: &'a str,
+    value: &'a str,
+) -> ZResult<(&'a str, Cow<'a, str>)> {
+    if value.trim_start().starts_with(PEM_PREFIX) {
+        return Ok((raw_key, value.into()));
+    }
+    let Some(var) = value.strip_prefix(ENV_PREFIX) else {
+        return Ok((file_key, value.into()));
+    };
+    let value = std::env::var(var)
+        .map_err(|e| zerror!("Invalid TLS environment variable {}: {}", var, e))?;
+    if value.trim_start().starts_with(PEM_PREFIX) {
+        return Ok((raw_key, value.into()));
+    }
+    let pem = b64_std_engine
+        .decode(value.trim())
+        .map_err(|e| zerror!("Invalid base64 in TLS environment variable {}: {}", var, e))?;
+    let pem = String::from_utf8(pem)
+        .map_err(|e| zerror!("Invalid PEM in TLS environment variable {}: {}", var, e))?;
+    Ok((raw_key, pem.into()))
+}
+
 zconfigurable! {
     // Default MTU (TLS PDU) in bytes.
     static ref TLS_DEFAULT_MTU: u16 = TLS_MAX_MTU;
@@ -115,6 +190,8 @@ zconfigurable! {
     // Amount of time in microseconds to throttle the accept loop upon an error.
     // Default set to 100 ms.
     static ref TLS_ACCEPT_THROTTLE_TIME: u64 = 100_000;
+    // Interval in seconds at which the listeners reload their CRL file, unless configured.
+    static ref TLS_CRL_RELOAD_INTERVAL_DEFAULT: u64 = 3600;
 }
 
 pub mod config {
@@ -136,6 +213,13 @@ pub mod config {
     pub const TLS_CLIENT_AUTH: &str = "client_auth";
 
     pub const TLS_SERVER_NAME_VERIFICATION: &str = "server_name_verification";
+
+    pub const TLS_CRL_FILE: &str = "crl_file";
+    pub const TLS_CRL_RAW: &str = "crl_raw";
+    pub const TLS_CRL_RELOAD_INTERVAL: &str = "crl_reload_interval";
+
+    pub const TLS_SERVER_OCSP_RESPONSE_FILE: &str = "server_ocsp_response_file";
+    pub const TLS_OCSP_REQUIRED: &str = "ocsp_required";
 }
 
 pub async fn get_tls_addr(address: &Address<'_>) -> ZResult<SocketAddr> {
@@ -156,3 +240,21 @@ pub fn get_tls_host<'a>(address: &'a Address<'a>) -> ZResult<&'a str> {
 pub fn get_tls_server_name(address: &Address<'_>) -> ZResult<ServerName> {
     Ok(ServerName::try_from(get_tls_host(address)?).map_err(|e| zerror!(e))?)
 }
+
+#[test]
+fn tls_material_sources() {
+    let pem = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n";
+    let material = |value| tls_material("file", "raw", value).unwrap();
+
+    assert_eq!(
+        material("/path/to/cert.pem"),
+        ("file", "/path/to/cert.pem".into())
+    );
+    assert_eq!(material(pem), ("raw", pem.into()));
+
+    std::env::set_var("ZENOH_TLS_TEST_PEM", pem);
+    assert_eq!(material("env:ZENOH_TLS_TEST_PEM"), ("raw", pem.into()));
+    std::env::set_var("ZENOH_TLS_TEST_BASE64", b64_std_engine.encode(pem));
+    assert_eq!(material("env:ZENOH_TLS_TEST_BASE64"), ("raw", pem.into()));
+    assert!(tls_material("file", "raw", "env:ZENOH_TLS_TEST_UNSET").is_err());
+}
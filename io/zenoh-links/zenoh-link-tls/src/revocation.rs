@@ -0,0 +1,656 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::cert::{has_extended_key_usage, read, read_raw, signed_parts, tbs_fields, TbsFields};
+use crate::config::{
+    TLS_CRL_FILE, TLS_CRL_RAW, TLS_ROOT_CA_CERTIFICATE_FILE, TLS_ROOT_CA_CERTIFICATE_RAW,
+};
+use async_rustls::rustls::{
+    client::{ServerCertVerified, ServerCertVerifier},
+    server::{ClientCertVerified, ClientCertVerifier},
+    Certificate, CertificateError, DistinguishedName, Error, ServerName,
+};
+use async_std::fs;
+use async_std::task;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::io::Cursor;
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use webpki::{EndEntityCert, SignatureAlgorithm};
+use zenoh_core::{zread, zwrite};
+use zenoh_protocol::core::endpoint::Config;
+use zenoh_result::{bail, zerror, ZResult};
+
+const PEM_PREFIX: &[u8] = b"-----BEGIN";
+const TAG_INTEGER: u8 = 0x02;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_RESPONSE_BYTES: u8 = 0xa0;
+const TAG_VERSION: u8 = 0xa0;
+const TAG_CERTS: u8 = 0xa0;
+const TAG_NEXT_UPDATE: u8 = 0xa0;
+const TAG_STATUS_GOOD: u8 = 0x80;
+const TAG_STATUS_REVOKED: u8 = 0xa1;
+
+// id-pkix-ocsp-basic (1.3.6.1.5.5.7.48.1.1)
+const OID_OCSP_BASIC: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x01];
+// id-kp-OCSPSigning (1.3.6.1.5.5.7.3.9)
+const OID_OCSP_SIGNING: &[u8] = &[0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x09];
+// sha256WithRSAEncryption, sha384WithRSAEncryption and sha512WithRSAEncryption (1.2.840.113549.1.1.11-13)
+const OID_RSA_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+const OID_RSA_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0c];
+const OID_RSA_SHA512: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0d];
+// ecdsa-with-SHA256 and ecdsa-with-SHA384 (1.2.840.10045.4.3.2-3)
+const OID_ECDSA_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+const OID_ECDSA_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03];
+// id-Ed25519 (1.3.101.112)
+const OID_ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+// id-sha1 (1.3.14.3.2.26)
+const OID_SHA1: &[u8] = &[0x2b, 0x0e, 0x03, 0x02, 0x1a];
+// id-sha256, id-sha384 and id-sha512 (2.16.840.1.101.3.4.2.1-3)
+const OID_SHA256: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+const OID_SHA384: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+const OID_SHA512: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+
+/// The certificates revoked by a set of certificate revocation lists, identified by their issuer and serial number.
+///
+/// The CRLs issued by a configured root certificate are verified when loaded, and rejected if their signature is invalid.
+/// The other CRLs only revoke a certificate if one of the intermediate certificates of its chain verifies their signature.
+pub(crate) struct Crls {
+    file: Option<String>,
+    roots: Vec<Vec<u8>>,
+    crls: RwLock<Vec<Crl>>,
+}
+
+/// A certificate revocation list.
+struct Crl {
+    /// The DER encoding of the CRL.
+    der: Vec<u8>,
+    /// The DER encoding of its issuer name.
+    issuer: Vec<u8>,
+    /// The serial numbers of the certificates it revokes.
+    serials: HashSet<Vec<u8>>,
+    /// Whether a root certificate verified its signature.
+    verified: bool,
+}
+
+impl Crl {
+    /// Whether a DER-encoded certificate is the issuer of the CRL and verifies its signature.
+    fn is_signed_by(&self, issuer: &[u8]) -> bool {
+        let (Some(fields), Some((data, algorithm, signature))) =
+            (tbs_fields(issuer), signed_parts(&self.der))
+        else {
+            return false;
+        };
+        fields.subject == self.issuer && verify_signature(issuer, algorithm, data, signature)
+    }
+}
+
+impl Crls {
+    /// Loads the CRLs of the endpoint configuration, if any.
+    pub(crate) async fn load(config: &Config<'_>) -> ZResult<Option<Arc<Crls>>> {
+        let (file, bytes) = if let Some(value) = config.get(TLS_CRL_RAW) {
+            (None, value.as_bytes().to_vec())
+        } else if let Some(file) = config.get(TLS_CRL_FILE) {
+            let bytes = fs::read(file)
+                .await
+                .map_err(|e| zerror!("Invalid TLS CRL file: {}", e))?;
+            (Some(file.to_string()), bytes)
+        } else {
+            return Ok(None);
+        };
+        let roots = load_root_certs(config).await?;
+        let crls = parse_crls(&bytes, &roots)?;
+        Ok(Some(Arc::new(Crls {
+            file,
+            roots,
+            crls: RwLock::new(crls),
+        })))
+    }
+
+    /// Reloads the CRL file every `interval`, as long as the CRLs are in use.
+    pub(crate) fn reload_every(self: &Arc<Self>, interval: Duration) {
+        let Some(file) = self.file.clone() else {
+            return;
+        };
+        let crls = Arc::downgrade(self);
+        task::spawn(async move {
+            loop {
+                task::sleep(interval).await;
+                let Some(crls) = Weak::upgrade(&crls) else {
+                    break;
+                };
+                let reloaded = match fs::read(&file).await {
+                    Ok(bytes) => parse_crls(&bytes, &crls.roots),
+                    Err(e) => Err(e.into()),
+                };
+                match reloaded {
+                    Ok(reloaded) => {
+                        log::debug!(
+                            "Reloaded {} revoked certificates from {}",
+                            reloaded.iter().map(|crl| crl.serials.len()).sum::<usize>(),
+                            file
+                        );
+                        *zwrite!(crls.crls) = reloaded;
+                    }
+                    Err(e) => log::warn!("Unable to reload the TLS CRL file {}: {}", file, e),
+                }
+            }
+        });
+    }
+
+    /// Fails if a certificate of the chain is revoked.
+    fn check(&self, end_entity: &Certificate, intermediates: &[Certificate]) -> Result<(), Error> {
+        let crls = zread!(self.crls);
+        for cert in std::iter::once(end_entity).chain(intermediates) {
+            let Some(fields) = tbs_fields(&cert.0) else {
+                continue;
+            };
+            let revoked = crls.iter().any(|crl| {
+                crl.issuer == fields.issuer
+                    && crl.serials.contains(fields.serial)
+                    && (crl.verified
+                        || intermediates
+                            .iter()
+                            .any(|issuer| crl.is_signed_by(&issuer.0)))
+            });
+            if revoked {
+                return Err(Error::InvalidCertificate(CertificateError::Revoked));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads the DER-encoded root certificates of the endpoint configuration, if any.
+async fn load_root_certs(config: &Config<'_>) -> ZResult<Vec<Vec<u8>>> {
+    let bytes = if let Some(value) = config.get(TLS_ROOT_CA_CERTIFICATE_RAW) {
+        value.as_bytes().to_vec()
+    } else if let Some(file) = config.get(TLS_ROOT_CA_CERTIFICATE_FILE) {
+        fs::read(file)
+            .await
+            .map_err(|e| zerror!("Invalid TLS root CA certificate file: {}", e))?
+    } else {
+        return Ok(vec![]);
+    };
+    Ok(rustls_pemfile::certs(&mut Cursor::new(bytes))?)
+}
+
+/// Parses PEM or DER-encoded CRLs, verifying those issued by one of the DER-encoded root certificates.
+fn parse_crls(bytes: &[u8], roots: &[Vec<u8>]) -> ZResult<Vec<Crl>> {
+    let start = bytes.iter().position(|b| !b.is_ascii_whitespace());
+    let ders = match start {
+        Some(start) if bytes[start..].starts_with(PEM_PREFIX) => {
+            rustls_pemfile::crls(&mut Cursor::new(bytes))?
+        }
+        _ => vec![bytes.to_vec()],
+    };
+    let mut crls = vec![];
+    for der in ders {
+        let (issuer, serials) =
+            revoked_serials(&der).ok_or_else(|| zerror!("Invalid certificate revocation list"))?;
+        let (issuer, serials) = (issuer.to_vec(), serials.into_iter().map(<[u8]>::to_vec));
+        let mut crl = Crl {
+            serials: serials.collect(),
+            issuer,
+            der,
+            verified: false,
+        };
+        let issued_by_root = roots
+            .iter()
+            .any(|root| tbs_fields(root).map_or(false, |fields| fields.subject == crl.issuer));
+        if issued_by_root {
+            if !roots.iter().any(|root| crl.is_signed_by(root)) {
+                bail!("Invalid signature of a certificate revocation list");
+            }
+            crl.verified = true;
+        }
+        crls.push(crl);
+    }
+    Ok(crls)
+}
+
+/// Returns the DER encoding of the issuer name of a DER-encoded CRL, and the serial numbers of the certificates it revokes.
+fn revoked_serials(der: &[u8]) -> Option<(&[u8], Vec<&[u8]>)> {
+    let (_, crl, _) = read(der)?;
+    let (_, tbs, _) = read(crl)?;
+    // Skip the optional version and the signature algorithm
+    let (tag, _, mut fields) = read(tbs)?;
+    if tag != TAG_INTEGER {
+        fields = tbs;
+    }
+    let (_, _, fields) = read(fields)?;
+    let (issuer, fields) = read_raw(fields)?;
+    // Skip this update, then look for the revoked certificates after the optional next update
+    let (_, _, mut fields) = read(fields)?;
+    let mut serials = vec![];
+    while let Some((tag, content, rest)) = read(fields) {
+        match tag {
+            TAG_UTC_TIME | TAG_GENERALIZED_TIME => {}
+            TAG_SEQUENCE => {
+                let mut entries = content;
+                while !entries.is_empty() {
+                    let (_, entry, rest) = read(entries)?;
+                    entries = rest;
+                    serials.push(read(entry)?.1);
+                }
+            }
+            _ => break,
+        }
+        fields = rest;
+    }
+    Some((issuer, serials))
+}
+
+/// A `ClientCertVerifier` rejecting the client certificates revoked by the CRLs.
+pub(crate) struct RevocationClientVerifier<V> {
+    inner: V,
+    crls: Arc<Crls>,
+}
+
+impl<V> RevocationClientVerifier<V> {
+    pub(crate) fn new(inner: V, crls: Arc<Crls>) -> Self {
+        Self { inner, crls }
+    }
+}
+
+impl<V: ClientCertVerifier> ClientCertVerifier for RevocationClientVerifier<V> {
+    fn client_auth_root_subjects(&self) -> &[DistinguishedName] {
+        self.inner.client_auth_root_subjects()
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        now: SystemTime,
+    ) -> Result<ClientCertVerified, Error> {
+        let verified = self
+            .inner
+            .verify_client_cert(end_entity, intermediates, now)?;
+        self.crls.check(end_entity, intermediates)?;
+        Ok(verified)
+    }
+}
+
+/// A `ServerCertVerifier` rejecting the server certificates revoked by the CRLs or by their stapled OCSP response.
+pub(crate) struct RevocationServerVerifier {
+    inner: Arc<dyn ServerCertVerifier>,
+    crls: Option<Arc<Crls>>,
+    ocsp_required: bool,
+}
+
+impl RevocationServerVerifier {
+    pub(crate) fn new(
+        inner: Arc<dyn ServerCertVerifier>,
+        crls: Option<Arc<Crls>>,
+        ocsp_required: bool,
+    ) -> Self {
+        Self {
+            inner,
+            crls,
+            ocsp_required,
+        }
+    }
+}
+
+impl ServerCertVerifier for RevocationServerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            scts,
+            ocsp_response,
+            now,
+        )?;
+        if let Some(crls) = &self.crls {
+            crls.check(end_entity, intermediates)?;
+        }
+        if ocsp_response.is_empty() {
+            if self.ocsp_required {
+                return Err(Error::General("Missing OCSP response".into()));
+            }
+            return Ok(verified);
+        }
+        match ocsp_status(&end_entity.0, intermediates, ocsp_response, now) {
+            Some(OcspStatus::Good) => Ok(verified),
+            Some(OcspStatus::Revoked) => Err(Error::InvalidCertificate(CertificateError::Revoked)),
+            Some(OcspStatus::Unknown) | None if self.ocsp_required => {
+                Err(Error::General("Invalid OCSP response".into()))
+            }
+            Some(OcspStatus::Unknown) | None => Ok(verified),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OcspStatus {
+    Good,
+    Revoked,
+    Unknown,
+}
+
+/// Returns the status of a certificate in a DER-encoded OCSP response, or `None` if the response is invalid,
+/// outdated or not signed by the issuer of the certificate (or a responder it delegated).
+fn ocsp_status(
+    end_entity: &[u8],
+    intermediates: &[Certificate],
+    response: &[u8],
+    now: SystemTime,
+) -> Option<OcspStatus> {
+    let cert = tbs_fields(end_entity)?;
+    let issuer = intermediates
+        .iter()
+        .find(|c| tbs_fields(&c.0).map_or(false, |fields| fields.subject == cert.issuer))?;
+    let issuer_fields = tbs_fields(&issuer.0)?;
+
+    // OCSPResponse ::= SEQUENCE { responseStatus ENUMERATED, responseBytes [0] EXPLICIT ResponseBytes OPTIONAL }
+    let (_, response, _) = read(response)?;
+    let (_, status, rest) = read(response)?;
+    if status != [0] {
+        return None;
+    }
+    let (tag, bytes, _) = read(rest)?;
+    if tag != TAG_RESPONSE_BYTES {
+        return None;
+    }
+    // ResponseBytes ::= SEQUENCE { responseType OBJECT IDENTIFIER, response OCTET STRING }
+    let (_, bytes, _) = read(bytes)?;
+    let (_, response_type, rest) = read(bytes)?;
+    if response_type != OID_OCSP_BASIC {
+        return None;
+    }
+    let (_, basic, _) = read(rest)?;
+
+    // BasicOCSPResponse ::= SEQUENCE { tbsResponseData, signatureAlgorithm, signature, certs [0] EXPLICIT OPTIONAL }
+    let (data, algorithm, signature) = signed_parts(basic)?;
+    let mut responders = vec![];
+    let (_, fields, _) = read(basic)?;
+    let (_, _, mut fields) = read(fields)?;
+    while let Some((tag, content, rest)) = read(fields) {
+        if tag == TAG_CERTS {
+            let (_, mut certs, _) = read(content)?;
+            while !certs.is_empty() {
+                let (cert, rest) = read_raw(certs)?;
+                responders.push(cert);
+                certs = rest;
+            }
+        }
+        fields = rest;
+    }
+    let signed_by_issuer = verify_signature(&issuer.0, algorithm, data, signature)
+        || responders.iter().any(|responder| {
+            is_delegated_responder(responder, &issuer.0, now)
+                && verify_signature(responder, algorithm, data, signature)
+        });
+    if !signed_by_issuer {
+        return None;
+    }
+
+    // ResponseData ::= SEQUENCE { version [0] EXPLICIT DEFAULT v1, responderID, producedAt, responses SEQUENCE OF SingleResponse, ... }
+    let (_, data, _) = read(data)?;
+    let (tag, _, mut fields) = read(data)?;
+    if tag != TAG_VERSION {
+        fields = data;
+    }
+    let (_, _, fields) = read(fields)?;
+    let (_, _, fields) = read(fields)?;
+    let (_, mut responses, _) = read(fields)?;
+    while !responses.is_empty() {
+        // SingleResponse ::= SEQUENCE { certID, certStatus, thisUpdate, nextUpdate [0] EXPLICIT OPTIONAL, ... }
+        let (_, single, rest) = read(responses)?;
+        responses = rest;
+        // CertID ::= SEQUENCE { hashAlgorithm, issuerNameHash, issuerKeyHash, serialNumber }
+        let (_, cert_id, fields) = read(single)?;
+        let (_, hash_algorithm, cert_id) = read(cert_id)?;
+        let (_, name_hash, cert_id) = read(cert_id)?;
+        let (_, key_hash, cert_id) = read(cert_id)?;
+        let (_, serial, _) = read(cert_id)?;
+        // The issuer is identified by the hashes of its name and public key, with the hash algorithm of the CertID
+        let (_, hash_algorithm, _) = read(hash_algorithm)?;
+        let same_issuer = digest(hash_algorithm, cert.issuer)
+            .map_or(false, |hash| hash == name_hash)
+            && digest(hash_algorithm, issuer_fields.public_key)
+                .map_or(false, |hash| hash == key_hash);
+        if serial != cert.serial || !same_issuer {
+            continue;
+        }
+        let (tag, _, fields) = read(fields)?;
+        let (_, this_update, fields) = read(fields)?;
+        if generalized_time(this_update)? > now {
+            return None;
+        }
+        if let Some((TAG_NEXT_UPDATE, next_update, _)) = read(fields) {
+            let (_, next_update, _) = read(next_update)?;
+            if generalized_time(next_update)? < now {
+                return None;
+            }
+        }
+        return Some(match tag {
+            TAG_STATUS_GOOD => OcspStatus::Good,
+            TAG_STATUS_REVOKED => OcspStatus::Revoked,
+            _ => OcspStatus::Unknown,
+        });
+    }
+    None
+}
+
+/// Whether a DER-encoded certificate is an OCSP responder delegated by the issuer certificate and valid at `now`.
+fn is_delegated_responder(responder: &[u8], issuer: &[u8], now: SystemTime) -> bool {
+    let (Some(fields), Some(issuer_fields), Some((data, algorithm, signature))) = (
+        tbs_fields(responder),
+        tbs_fields(issuer),
+        signed_parts(responder),
+    ) else {
+        return false;
+    };
+    fields.issuer == issuer_fields.subject
+        && validity(&fields).map_or(false, |(not_before, not_after)| {
+            not_before <= now && now <= not_after
+        })
+        && has_extended_key_usage(responder, OID_OCSP_SIGNING)
+        && verify_signature(issuer, algorithm, data, signature)
+}
+
+/// Whether the key of a DER-encoded certificate verifies the signature of a message.
+fn verify_signature(signer: &[u8], algorithm: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let algorithms: &[&SignatureAlgorithm] = match algorithm {
+        OID_RSA_SHA256 => &[&webpki::RSA_PKCS1_2048_8192_SHA256],
+        OID_RSA_SHA384 => &[&webpki::RSA_PKCS1_2048_8192_SHA384],
+        OID_RSA_SHA512 => &[&webpki::RSA_PKCS1_2048_8192_SHA512],
+        OID_ECDSA_SHA256 => &[&webpki::ECDSA_P256_SHA256, &webpki::ECDSA_P384_SHA256],
+        OID_ECDSA_SHA384 => &[&webpki::ECDSA_P384_SHA384, &webpki::ECDSA_P256_SHA384],
+        OID_ED25519 => &[&webpki::ED25519],
+        _ => return false,
+    };
+    let Ok(signer) = EndEntityCert::try_from(signer) else {
+        return false;
+    };
+    algorithms.iter().any(|algorithm| {
+        signer
+            .verify_signature(algorithm, message, signature)
+            .is_ok()
+    })
+}
+
+/// Hashes data with the hash algorithm of an object identifier, if supported.
+fn digest(algorithm: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    Some(match algorithm {
+        OID_SHA1 => Sha1::digest(data).to_vec(),
+        OID_SHA256 => Sha256::digest(data).to_vec(),
+        OID_SHA384 => Sha384::digest(data).to_vec(),
+        OID_SHA512 => Sha512::digest(data).to_vec(),
+        _ => return None,
+    })
+}
+
+/// Returns the notBefore and notAfter times of a certificate.
+fn validity(fields: &TbsFields) -> Option<(SystemTime, SystemTime)> {
+    let (tag, not_before, rest) = read(fields.validity)?;
+    let not_before = time(tag, not_before)?;
+    let (tag, not_after, _) = read(rest)?;
+    Some((not_before, time(tag, not_after)?))
+}
+
+/// Parses a DER UTCTime or GeneralizedTime.
+fn time(tag: u8, time: &[u8]) -> Option<SystemTime> {
+    match tag {
+        TAG_GENERALIZED_TIME => generalized_time(time),
+        TAG_UTC_TIME => {
+            // The two-digit years from 50 to 99 stand for 1950 to 1999, the others for 2000 to 2049 (RFC 5280)
+            let year: u8 = std::str::from_utf8(time.get(..2)?).ok()?.parse().ok()?;
+            let century: &[u8] = if year >= 50 { b"19" } else { b"20" };
+            generalized_time(&[century, time].concat())
+        }
+        _ => None,
+    }
+}
+
+/// Parses a DER GeneralizedTime (`YYYYMMDDHHMMSSZ`, possibly with fractional seconds).
+fn generalized_time(time: &[u8]) -> Option<SystemTime> {
+    let time = std::str::from_utf8(time).ok()?.strip_suffix('Z')?;
+    let field = |range: std::ops::Range<usize>| time.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (field(0..4)?, field(4..6)?, field(6..8)?);
+    let (hour, minute, second) = (field(8..10)?, field(10..12)?, field(12..14)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // The number of days since the epoch of a date of the proleptic Gregorian calendar
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    let seconds = days * 86_400 + hour * 3600 + minute * 60 + second;
+    Some(UNIX_EPOCH + Duration::from_secs(u64::try_from(seconds).ok()?))
+}
+
+#[test]
+fn revocation_parsing() {
+    // Encodes a DER element of short length
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        [&[tag, content.len() as u8][..], content].concat()
+    }
+
+    assert_eq!(
+        generalized_time(b"20231016120000Z"),
+        Some(UNIX_EPOCH + Duration::from_secs(1_697_457_600))
+    );
+    assert_eq!(
+        generalized_time(b"19700101000001Z"),
+        Some(UNIX_EPOCH + Duration::from_secs(1))
+    );
+    assert!(generalized_time(b"20231316120000Z").is_none());
+    assert!(generalized_time(b"20231016120000").is_none());
+    assert_eq!(
+        time(TAG_UTC_TIME, b"231016120000Z"),
+        generalized_time(b"20231016120000Z")
+    );
+    assert_eq!(
+        time(TAG_UTC_TIME, b"700101000001Z"),
+        Some(UNIX_EPOCH + Duration::from_secs(1))
+    );
+
+    let algorithm = der(TAG_SEQUENCE, &der(0x06, OID_ECDSA_SHA256));
+    let issuer = der(TAG_SEQUENCE, &der(0x31, b"issuer"));
+    let entry = |serial: &[u8]| {
+        der(
+            TAG_SEQUENCE,
+            &[
+                der(TAG_INTEGER, serial),
+                der(TAG_UTC_TIME, b"231016120000Z"),
+            ]
+            .concat(),
+        )
+    };
+    let tbs = der(
+        TAG_SEQUENCE,
+        &[
+            der(TAG_INTEGER, &[1]),
+            algorithm.clone(),
+            issuer.clone(),
+            der(TAG_UTC_TIME, b"231016120000Z"),
+            der(TAG_UTC_TIME, b"231116120000Z"),
+            der(
+                TAG_SEQUENCE,
+                &[entry(&[0x2a]), entry(&[0x01, 0x00])].concat(),
+            ),
+        ]
+        .concat(),
+    );
+    let crl = der(TAG_SEQUENCE, &[tbs, algorithm, der(0x03, &[0])].concat());
+    let (crl_issuer, serials) = revoked_serials(&crl).unwrap();
+    assert_eq!(crl_issuer, &issuer[..]);
+    assert_eq!(serials, [&[0x2a][..], &[0x01, 0x00][..]]);
+
+    let crls = parse_crls(&crl, &[]).unwrap();
+    assert_eq!(crls[0].issuer, issuer);
+    assert!(crls[0].serials.contains(&[0x2a][..]));
+    assert!(!crls[0].serials.contains(&[0x2b][..]));
+    assert!(!crls[0].verified);
+    assert!(parse_crls(b"not a crl", &[]).is_err());
+
+    // A CRL issued by a root certificate must be signed by it
+    let valid = der(
+        TAG_SEQUENCE,
+        &[
+            der(TAG_UTC_TIME, b"231016120000Z"),
+            der(TAG_UTC_TIME, b"331016120000Z"),
+        ]
+        .concat(),
+    );
+    let spki = der(
+        TAG_SEQUENCE,
+        &[algorithm.clone(), der(0x03, &[0, 0x04, 0x01])].concat(),
+    );
+    let tbs = der(
+        TAG_SEQUENCE,
+        &[
+            der(TAG_VERSION, &der(TAG_INTEGER, &[2])),
+            der(TAG_INTEGER, &[1]),
+            algorithm.clone(),
+            issuer.clone(),
+            valid,
+            issuer.clone(),
+            spki,
+        ]
+        .concat(),
+    );
+    let root = der(TAG_SEQUENCE, &[tbs, algorithm, der(0x03, &[0])].concat());
+    let fields = tbs_fields(&root).unwrap();
+    assert_eq!(fields.subject, &issuer[..]);
+    assert_eq!(fields.public_key, [0x04, 0x01]);
+    assert_eq!(
+        validity(&fields),
+        generalized_time(b"20231016120000Z").zip(generalized_time(b"20331016120000Z"))
+    );
+    assert!(parse_crls(&crl, &[root]).is_err());
+}
@@ -12,14 +12,20 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::{
-    config::*, get_tls_addr, get_tls_host, get_tls_server_name,
-    verify::WebPkiVerifierAnyServerName, TLS_ACCEPT_THROTTLE_TIME, TLS_DEFAULT_MTU,
-    TLS_LINGER_TIMEOUT, TLS_LOCATOR_PREFIX,
+    cert::cert_names,
+    config::*,
+    get_tls_addr, get_tls_host, get_tls_server_name,
+    revocation::{Crls, RevocationClientVerifier, RevocationServerVerifier},
+    verify::WebPkiVerifierAnyServerName,
+    TLS_ACCEPT_THROTTLE_TIME, TLS_CRL_RELOAD_INTERVAL_DEFAULT, TLS_DEFAULT_MTU, TLS_LINGER_TIMEOUT,
+    TLS_LOCATOR_PREFIX,
 };
 use async_rustls::{
     rustls::{
-        server::AllowAnyAuthenticatedClient, version::TLS13, Certificate, ClientConfig,
-        OwnedTrustAnchor, PrivateKey, RootCertStore, ServerConfig,
+        client::{ServerCertVerifier, WebPkiVerifier},
+        server::{AllowAnyAuthenticatedClient, ClientCertVerifier},
+        version::TLS13,
+        Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerConfig,
     },
     TlsAcceptor, TlsConnector, TlsStream,
 };
@@ -45,11 +51,12 @@ use std::time::Duration;
 use webpki::TrustAnchor;
 use zenoh_core::{zasynclock, zread, zwrite};
 use zenoh_link_commons::{
-    LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
+    config::PROXY, LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
 };
+use zenoh_link_tcp::proxy::Proxy;
 use zenoh_protocol::core::endpoint::Config;
 use zenoh_protocol::core::{EndPoint, Locator};
-use zenoh_result::{bail, zerror, ZResult};
+use zenoh_result::{bail, zerror, Error as ZError, ZResult};
 use zenoh_sync::Signal;
 
 pub struct LinkUnicastTls {
@@ -68,6 +75,8 @@ pub struct LinkUnicastTls {
     // The destination socket address of this link (address used on the local host)
     dst_addr: SocketAddr,
     dst_locator: Locator,
+    // The names of the client certificate, if this link was accepted with client authentication
+    peer_cert_names: Vec<String>,
     // Make sure there are no concurrent read or writes
     write_mtx: AsyncMutex<()>,
     read_mtx: AsyncMutex<()>,
@@ -108,6 +117,17 @@ impl LinkUnicastTls {
             );
         }
 
+        let peer_cert_names = match &socket {
+            TlsStream::Server(stream) => stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .map(|cert| cert_names(&cert.0))
+                .unwrap_or_default(),
+            TlsStream::Client(_) => vec![],
+        };
+
         // Build the Tls object
         LinkUnicastTls {
             inner: UnsafeCell::new(socket),
@@ -115,6 +135,7 @@ impl LinkUnicastTls {
             src_locator: Locator::new(TLS_LOCATOR_PREFIX, src_addr.to_string(), "").unwrap(),
             dst_addr,
             dst_locator: Locator::new(TLS_LOCATOR_PREFIX, dst_addr.to_string(), "").unwrap(),
+            peer_cert_names,
             write_mtx: AsyncMutex::new(()),
             read_mtx: AsyncMutex::new(()),
         }
@@ -201,6 +222,10 @@ impl LinkUnicastTrait for LinkUnicastTls {
     fn is_streamed(&self) -> bool {
         true
     }
+
+    fn get_peer_cert_names(&self) -> Vec<String> {
+        self.peer_cert_names.clone()
+    }
 }
 
 impl Drop for LinkUnicastTls {
@@ -274,7 +299,6 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
         let epconf = endpoint.config();
 
         let server_name = get_tls_server_name(&epaddr)?;
-        let addr = get_tls_addr(&epaddr).await?;
 
         // Initialize the TLS Config
         let client_config = TlsClientConfig::new(&epconf)
@@ -283,8 +307,14 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
         let config = Arc::new(client_config.client_config);
         let connector = TlsConnector::from(config);
 
-        // Initialize the TcpStream
-        let tcp_stream = TcpStream::connect(addr).await.map_err(|e| {
+        // Initialize the TcpStream, possibly through a proxy
+        let tcp_stream = match epconf.get(PROXY) {
+            Some(proxy) => proxy.parse::<Proxy>()?.connect(epaddr.as_str()).await,
+            None => TcpStream::connect(get_tls_addr(&epaddr).await?)
+                .await
+                .map_err(ZError::from),
+        }
+        .map_err(|e| {
             zerror!(
                 "Can not create a new TLS link bound to {:?}: {}",
                 server_name,
@@ -524,6 +554,24 @@ impl TlsServerConfig {
         };
         let tls_server_private_key = TlsServerConfig::load_tls_private_key(config).await?;
         let tls_server_certificate = TlsServerConfig::load_tls_certificate(config).await?;
+        let tls_server_ocsp_response = match config.get(TLS_SERVER_OCSP_RESPONSE_FILE) {
+            Some(file) => fs::read(file)
+                .await
+                .map_err(|e| zerror!("Invalid TLS OCSP response file: {}", e))?,
+            None => vec![],
+        };
+        let crls = Crls::load(config).await?;
+        if let Some(crls) = &crls {
+            let interval = match config.get(TLS_CRL_RELOAD_INTERVAL) {
+                Some(s) => s
+                    .parse()
+                    .map_err(|_| zerror!("Unknown CRL reload interval argument: {}", s))?,
+                None => *TLS_CRL_RELOAD_INTERVAL_DEFAULT,
+            };
+            if interval > 0 {
+                crls.reload_every(Duration::from_secs(interval));
+            }
+        }
 
         let mut keys: Vec<PrivateKey> =
             rustls_pemfile::rsa_private_keys(&mut Cursor::new(&tls_server_private_key))
@@ -560,19 +608,34 @@ impl TlsServerConfig {
                 },
                 Ok,
             )?;
+            let verifier = AllowAnyAuthenticatedClient::new(root_cert_store);
+            let verifier: Arc<dyn ClientCertVerifier> = match crls {
+                Some(crls) => Arc::new(RevocationClientVerifier::new(verifier, crls)),
+                None => Arc::new(verifier),
+            };
             ServerConfig::builder()
                 .with_safe_default_cipher_suites()
                 .with_safe_default_kx_groups()
                 .with_protocol_versions(&[&TLS13]) // Force TLS 1.3
                 .map_err(|e| zerror!(e))?
-                .with_client_cert_verifier(Arc::new(AllowAnyAuthenticatedClient::new(root_cert_store)))
-                .with_single_cert(certs, keys.remove(0))
+                .with_client_cert_verifier(verifier)
+                .with_single_cert_with_ocsp_and_sct(
+                    certs,
+                    keys.remove(0),
+                    tls_server_ocsp_response,
+                    vec![],
+                )
                 .map_err(|e| zerror!(e))?
         } else {
             ServerConfig::builder()
                 .with_safe_defaults()
                 .with_no_client_auth()
-                .with_single_cert(certs, keys.remove(0))
+                .with_single_cert_with_ocsp_and_sct(
+                    certs,
+                    keys.remove(0),
+                    tls_server_ocsp_response,
+                    vec![],
+                )
                 .map_err(|e| zerror!(e))?
         };
         Ok(TlsServerConfig { server_config: sc })
@@ -623,6 +686,13 @@ impl TlsClientConfig {
             None => false,
         };
 
+        let tls_ocsp_required: bool = match config.get(TLS_OCSP_REQUIRED) {
+            Some(s) => s
+                .parse()
+                .map_err(|_| zerror!("Unknown OCSP required argument: {}", s))?,
+            None => false,
+        };
+
         // Allows mixed user-generated CA and webPKI CA
         log::debug!("Loading default Web PKI certificates.");
         let mut root_cert_store: RootCertStore = RootCertStore {
@@ -634,6 +704,18 @@ impl TlsClientConfig {
             root_cert_store.add_trust_anchors(custom_root_cert.roots.into_iter());
         }
 
+        let verifier: Arc<dyn ServerCertVerifier> = if tls_server_name_verification {
+            Arc::new(WebPkiVerifier::new(root_cert_store, None))
+        } else {
+            Arc::new(WebPkiVerifierAnyServerName::new(root_cert_store))
+        };
+        // The servers' certificates are checked against the CRLs and their stapled OCSP responses
+        let verifier = Arc::new(RevocationServerVerifier::new(
+            verifier,
+            Crls::load(config).await?,
+            tls_ocsp_required,
+        ));
+
         let cc = if tls_client_server_auth {
             log::debug!("Loading client authentication key and certificate...");
             let tls_client_private_key = TlsClientConfig::load_tls_private_key(config).await?;
@@ -666,31 +748,15 @@ impl TlsClientConfig {
                 .with_protocol_versions(&[&TLS13])
                 .map_err(|e| zerror!("Config parameters should be valid: {}", e))?;
 
-            if tls_server_name_verification {
-                builder
-                    .with_root_certificates(root_cert_store)
-                    .with_client_auth_cert(certs, keys.remove(0))
-            } else {
-                builder
-                    .with_custom_certificate_verifier(Arc::new(WebPkiVerifierAnyServerName::new(
-                        root_cert_store,
-                    )))
-                    .with_client_auth_cert(certs, keys.remove(0))
-            }
-            .map_err(|e| zerror!("Bad certificate/key: {}", e))?
+            builder
+                .with_custom_certificate_verifier(verifier)
+                .with_client_auth_cert(certs, keys.remove(0))
+                .map_err(|e| zerror!("Bad certificate/key: {}", e))?
         } else {
-            let builder = ClientConfig::builder().with_safe_defaults();
-            if tls_server_name_verification {
-                builder
-                    .with_root_certificates(root_cert_store)
-                    .with_no_client_auth()
-            } else {
-                builder
-                    .with_custom_certificate_verifier(Arc::new(WebPkiVerifierAnyServerName::new(
-                        root_cert_store,
-                    )))
-                    .with_no_client_auth()
-            }
+            ClientConfig::builder()
+                .with_safe_defaults()
+                .with_custom_certificate_verifier(verifier)
+                .with_no_client_auth()
         };
         Ok(TlsClientConfig { client_config: cc })
     }
@@ -29,8 +29,8 @@ use std::sync::{Arc, Mutex, RwLock, Weak};
 use std::time::Duration;
 use zenoh_core::{zasynclock, zlock, zread, zwrite};
 use zenoh_link_commons::{
-    ConstructibleLinkManagerUnicast, LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait,
-    NewLinkChannelSender,
+    config::PUNCH, ConstructibleLinkManagerUnicast, LinkManagerUnicastTrait, LinkUnicast,
+    LinkUnicastTrait, NewLinkChannelSender,
 };
 use zenoh_protocol::core::{EndPoint, Locator};
 use zenoh_result::{bail, zerror, Error as ZError, ZResult};
@@ -240,6 +240,8 @@ impl fmt::Debug for LinkUnicastUdp {
 /*************************************/
 struct ListenerUnicastUdp {
     endpoint: EndPoint,
+    socket: Arc<UdpSocket>,
+    links: LinkHashMap,
     active: Arc<AtomicBool>,
     signal: Signal,
     handle: JoinHandle<ZResult<()>>,
@@ -248,12 +250,16 @@ struct ListenerUnicastUdp {
 impl ListenerUnicastUdp {
     fn new(
         endpoint: EndPoint,
+        socket: Arc<UdpSocket>,
+        links: LinkHashMap,
         active: Arc<AtomicBool>,
         signal: Signal,
         handle: JoinHandle<ZResult<()>>,
     ) -> ListenerUnicastUdp {
         ListenerUnicastUdp {
             endpoint,
+            socket,
+            links,
             active,
             signal,
             handle,
@@ -340,6 +346,40 @@ impl LinkManagerUnicastUdp {
 
         Ok((socket, local_addr))
     }
+
+    fn get_listener_socket(
+        &self,
+        dst_addr: &SocketAddr,
+    ) -> Option<(SocketAddr, Arc<UdpSocket>, LinkHashMap)> {
+        zread!(self.listeners)
+            .iter()
+            .find(|(addr, _)| addr.is_ipv4() == dst_addr.is_ipv4())
+            .map(|(addr, l)| (*addr, l.socket.clone(), l.links.clone()))
+    }
+
+    fn new_link_punch(&self, dst_addr: &SocketAddr) -> ZResult<LinkUnicastUdp> {
+        let (src_addr, socket, links) = self.get_listener_socket(dst_addr).ok_or_else(|| {
+            zerror!(
+                "Can not create a new UDP link bound to {}: no UDP listener to punch from",
+                dst_addr
+            )
+        })?;
+
+        // Register the link on the listener so that the replies are dispatched to it
+        let unconnected = Arc::new(LinkUnicastUdpUnconnected {
+            socket: Arc::downgrade(&socket),
+            links: links.clone(),
+            input: Mvar::new(),
+            leftover: AsyncMutex::new(None),
+        });
+        zlock!(links).insert((src_addr, *dst_addr), Arc::downgrade(&unconnected));
+
+        Ok(LinkUnicastUdp::new(
+            src_addr,
+            *dst_addr,
+            LinkUnicastUdpVariant::Unconnected(unconnected),
+        ))
+    }
 }
 
 #[async_trait]
@@ -348,9 +388,25 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUdp {
         let dst_addrs = get_udp_addrs(endpoint.address())
             .await?
             .filter(|a| !a.ip().is_multicast());
+        let punch: bool = endpoint
+            .config()
+            .get(PUNCH)
+            .unwrap_or("false")
+            .parse()
+            .map_err(|_| zerror!("Invalid UDP {} value in {}", PUNCH, endpoint))?;
 
         let mut errs: Vec<ZError> = vec![];
         for da in dst_addrs {
+            if punch {
+                match self.new_link_punch(&da) {
+                    Ok(link) => return Ok(LinkUnicast(Arc::new(link))),
+                    Err(e) => {
+                        errs.push(e);
+                        continue;
+                    }
+                }
+            }
+
             match self.new_link_inner(&da).await {
                 Ok((socket, src_addr, dst_addr)) => {
                     // Create UDP link
@@ -399,10 +455,14 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUdp {
                     )?;
 
                     // Spawn the accept loop for the listener
+                    let socket = Arc::new(socket);
+                    let links: LinkHashMap = Arc::new(Mutex::new(HashMap::new()));
                     let active = Arc::new(AtomicBool::new(true));
                     let signal = Signal::new();
                     let mut listeners = zwrite!(self.listeners);
 
+                    let c_socket = socket.clone();
+                    let c_links = links.clone();
                     let c_active = active.clone();
                     let c_signal = signal.clone();
                     let c_manager = self.manager.clone();
@@ -410,13 +470,16 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUdp {
                     let c_addr = local_addr;
                     let handle = task::spawn(async move {
                         // Wait for the accept loop to terminate
-                        let res = accept_read_task(socket, c_active, c_signal, c_manager).await;
+                        let res =
+                            accept_read_task(c_socket, c_links, c_active, c_signal, c_manager)
+                                .await;
                         zwrite!(c_listeners).remove(&c_addr);
                         res
                     });
 
                     let locator = endpoint.to_locator();
-                    let listener = ListenerUnicastUdp::new(endpoint, active, signal, handle);
+                    let listener =
+                        ListenerUnicastUdp::new(endpoint, socket, links, active, signal, handle);
                     // Update the list of active listeners on the manager
                     listeners.insert(local_addr, listener);
 
@@ -514,17 +577,35 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUdp {
 
         locators
     }
+
+    async fn punch(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let dst_addrs = get_udp_addrs(endpoint.address())
+            .await?
+            .filter(|a| !a.ip().is_multicast());
+
+        let mut errs: Vec<ZError> = vec![];
+        for da in dst_addrs {
+            match self.get_listener_socket(&da) {
+                // An empty datagram opens the NAT binding and is ignored by the remote listener
+                Some((_, socket, _)) => match socket.send_to(&[], &da).await {
+                    Ok(_) => return Ok(()),
+                    Err(e) => errs.push(zerror!("{}: {}", da, e).into()),
+                },
+                None => errs.push(zerror!("{}: no UDP listener to punch from", da).into()),
+            }
+        }
+
+        bail!("Can not punch towards {}: {:?}", endpoint, errs)
+    }
 }
 
 async fn accept_read_task(
-    socket: UdpSocket,
+    socket: Arc<UdpSocket>,
+    links: LinkHashMap,
     active: Arc<AtomicBool>,
     signal: Signal,
     manager: NewLinkChannelSender,
 ) -> ZResult<()> {
-    let socket = Arc::new(socket);
-    let links: LinkHashMap = Arc::new(Mutex::new(HashMap::new()));
-
     macro_rules! zaddlink {
         ($src:expr, $dst:expr, $link:expr) => {
             zlock!(links).insert(($src, $dst), $link);
@@ -590,6 +671,12 @@ async fn accept_read_task(
             }
         };
 
+        // Empty datagrams are only sent to open NAT bindings
+        if n == 0 {
+            log::trace!("Received UDP punch on {}: {}", src_addr, dst_addr);
+            continue;
+        }
+
         let link = loop {
             let res = zgetlink!(src_addr, dst_addr);
             match res {
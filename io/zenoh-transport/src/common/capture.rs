@@ -0,0 +1,237 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Capture of the zenoh messages sent and received by the transports.
+//!
+//! A capture file starts with a magic number and a version, followed by one record per message.
+//! A record is encoded with the zenoh codec and holds the time the message was sent or received,
+//! its direction, the zid of the remote node, the source and destination locators of the link
+//! and the message itself.
+use std::{
+    fs::File,
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zenoh_buffers::{
+    reader::{HasReader, Reader},
+    writer::{HasWriter, Writer},
+    DidntRead,
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_core::zlock;
+use zenoh_link::LinkUnicast;
+use zenoh_protocol::{core::ZenohId, network::NetworkMessage};
+use zenoh_result::{bail, zerror, ZResult};
+
+const MAGIC: &[u8; 4] = b"ZCAP";
+const VERSION: u8 = 1;
+
+/// The direction of a captured message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The message was received from the remote node.
+    Rx,
+    /// The message was sent to the remote node.
+    Tx,
+}
+
+impl Direction {
+    pub fn reverse(self) -> Self {
+        match self {
+            Direction::Rx => Direction::Tx,
+            Direction::Tx => Direction::Rx,
+        }
+    }
+}
+
+/// A captured message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureRecord {
+    /// The time the message was sent or received, in nanoseconds since the UNIX epoch.
+    pub time: u64,
+    pub direction: Direction,
+    /// The zid of the remote node.
+    pub zid: ZenohId,
+    /// The source locator of the link.
+    pub src: String,
+    /// The destination locator of the link.
+    pub dst: String,
+    pub msg: NetworkMessage,
+}
+
+impl CaptureRecord {
+    fn write<W: Writer>(
+        writer: &mut W,
+        time: u64,
+        direction: Direction,
+        zid: &ZenohId,
+        src: &str,
+        dst: &str,
+        msg: &NetworkMessage,
+    ) -> ZResult<()> {
+        let codec = Zenoh080::new();
+        let direction: u8 = match direction {
+            Direction::Rx => 0,
+            Direction::Tx => 1,
+        };
+        codec
+            .write(&mut *writer, time)
+            .and_then(|_| codec.write(&mut *writer, direction))
+            .and_then(|_| codec.write(&mut *writer, zid))
+            .and_then(|_| codec.write(&mut *writer, src))
+            .and_then(|_| codec.write(&mut *writer, dst))
+            .and_then(|_| codec.write(&mut *writer, msg))
+            .map_err(|_| zerror!("Failed to encode a capture record").into())
+    }
+
+    fn read<R: Reader>(reader: &mut R) -> Result<Self, DidntRead> {
+        let codec = Zenoh080::new();
+        let time: u64 = codec.read(&mut *reader)?;
+        let direction: u8 = codec.read(&mut *reader)?;
+        let direction = match direction {
+            0 => Direction::Rx,
+            1 => Direction::Tx,
+            _ => return Err(DidntRead),
+        };
+        let zid: ZenohId = codec.read(&mut *reader)?;
+        let src: String = codec.read(&mut *reader)?;
+        let dst: String = codec.read(&mut *reader)?;
+        let msg: NetworkMessage = codec.read(&mut *reader)?;
+        Ok(CaptureRecord {
+            time,
+            direction,
+            zid,
+            src,
+            dst,
+            msg,
+        })
+    }
+}
+
+/// Reads all the records of a capture file.
+pub fn read_capture<P: AsRef<Path>>(path: P) -> ZResult<Vec<CaptureRecord>> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)
+        .map_err(|e| zerror!("Unable to read the capture file {}: {}", path.display(), e))?;
+    let mut reader = bytes.reader();
+    let mut header = [0u8; MAGIC.len() + 1];
+    if reader.read_exact(&mut header).is_err() || &header[..MAGIC.len()] != MAGIC {
+        bail!("{} is not a capture file", path.display());
+    }
+    if header[MAGIC.len()] != VERSION {
+        bail!(
+            "Unsupported version {} of the capture file {}",
+            header[MAGIC.len()],
+            path.display()
+        );
+    }
+    let mut records = vec![];
+    while reader.can_read() {
+        let record = CaptureRecord::read(&mut reader)
+            .map_err(|_| zerror!("Failed to decode a record of {}", path.display()))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// The writer of the capture file of a transport manager.
+pub(crate) struct Capture {
+    file: Mutex<File>,
+}
+
+impl Capture {
+    pub(crate) fn create(path: &str) -> ZResult<Capture> {
+        let mut file = File::create(path)
+            .map_err(|e| zerror!("Unable to create the capture file {}: {}", path, e))?;
+        file.write_all(MAGIC)?;
+        file.write_all(&[VERSION])?;
+        Ok(Capture {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub(crate) fn record(
+        &self,
+        direction: Direction,
+        zid: &ZenohId,
+        link: &LinkUnicast,
+        msg: &NetworkMessage,
+    ) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        let mut buffer = vec![];
+        let mut writer = buffer.writer();
+        let res = CaptureRecord::write(
+            &mut writer,
+            time,
+            direction,
+            zid,
+            link.get_src().as_str(),
+            link.get_dst().as_str(),
+            msg,
+        );
+        // Each record is written at once for the records of concurrent transports not to interleave
+        if let Err(e) = res.and_then(|_| Ok(zlock!(self.file).write_all(&buffer)?)) {
+            log::warn!("Unable to capture a message to {}: {}", zid, e);
+        }
+    }
+}
+
+#[test]
+fn capture_record() {
+    let records = (0..8)
+        .map(|i| CaptureRecord {
+            time: i,
+            direction: if i % 2 == 0 {
+                Direction::Rx
+            } else {
+                Direction::Tx
+            },
+            zid: ZenohId::rand(),
+            src: "tcp/127.0.0.1:7447".to_string(),
+            dst: format!("tcp/127.0.0.1:{}", 50000 + i),
+            msg: NetworkMessage::rand(),
+        })
+        .collect::<Vec<_>>();
+
+    let path = std::env::temp_dir().join(format!("zenoh-capture-{}.zcap", ZenohId::rand()));
+    let mut bytes = vec![];
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    let mut writer = bytes.writer();
+    for r in records.iter() {
+        CaptureRecord::write(
+            &mut writer,
+            r.time,
+            r.direction,
+            &r.zid,
+            &r.src,
+            &r.dst,
+            &r.msg,
+        )
+        .unwrap();
+    }
+    std::fs::write(&path, &bytes).unwrap();
+    let read = read_capture(&path);
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(read.unwrap(), records);
+
+    std::fs::write(&path, b"ZCAP").unwrap();
+    assert!(read_capture(&path).is_err());
+    let _ = std::fs::remove_file(&path);
+}
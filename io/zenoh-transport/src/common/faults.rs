@@ -0,0 +1,204 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Injection of faults in the messages received by the unicast transports, to validate the routing
+//! and the replication under degraded networks. Only available with the `fault_injection` feature.
+//!
+//! A [`FaultProfile`] is a list of rules, each one applying to the messages received on some links
+//! and made of steps which are played in sequence from the moment the profile is set.
+//! A step drops, delays, duplicates or reorders the messages with the given probabilities.
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use zenoh_core::{zread, zwrite};
+use zenoh_link::LinkUnicast;
+
+// The maximum extra delay of a reordered message, for the next messages to overtake it
+const REORDER_WINDOW: Duration = Duration::from_millis(10);
+
+/// A scripted profile of the faults injected in the messages received on the links.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaultProfile {
+    /// The rules of the profile: the first rule matching a link applies to its messages.
+    pub rules: Vec<FaultRule>,
+}
+
+/// The faults injected in the messages received on some links.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaultRule {
+    /// The locators of the links the rule applies to, matching either end of a link.
+    /// The rule applies to all the links if empty.
+    #[serde(default)]
+    pub links: Vec<String>,
+    /// The steps played in sequence, the messages are left untouched once they are all elapsed.
+    pub steps: Vec<FaultStep>,
+    /// Whether the steps are played again once they are all elapsed.
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+/// A step of a [`FaultRule`].
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct FaultStep {
+    /// The duration of the step in milliseconds, the step lasts forever if not set.
+    pub duration: Option<u64>,
+    /// The probability for a message to be dropped.
+    pub drop: f64,
+    /// The delay of the messages in milliseconds.
+    pub delay: u64,
+    /// The probability for a message to be delivered twice.
+    pub duplicate: f64,
+    /// The probability for a message to be overtaken by the next ones.
+    pub reorder: f64,
+}
+
+impl FaultRule {
+    fn matches(&self, link: &LinkUnicast) -> bool {
+        self.links.is_empty()
+            || self.links.iter().any(|locator| {
+                locator == link.get_src().as_str() || locator == link.get_dst().as_str()
+            })
+    }
+
+    fn step(&self, elapsed: Duration) -> Option<&FaultStep> {
+        let mut elapsed = elapsed.as_millis() as u64;
+        if self.repeat {
+            let total: Option<u64> = self.steps.iter().map(|step| step.duration).sum();
+            if let Some(total) = total.filter(|total| *total > 0) {
+                elapsed %= total;
+            }
+        }
+        for step in self.steps.iter() {
+            match step.duration {
+                Some(duration) if elapsed >= duration => elapsed -= duration,
+                _ => return Some(step),
+            }
+        }
+        None
+    }
+}
+
+impl FaultStep {
+    fn faults<R: Rng>(&self, rng: &mut R) -> Faults {
+        let mut delay = Duration::from_millis(self.delay);
+        if rng.gen::<f64>() < self.reorder {
+            delay += REORDER_WINDOW.mul_f64(rng.gen());
+        }
+        Faults {
+            drop: rng.gen::<f64>() < self.drop,
+            duplicate: rng.gen::<f64>() < self.duplicate,
+            delay,
+        }
+    }
+}
+
+/// The faults injected in a received message.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Faults {
+    pub(crate) drop: bool,
+    pub(crate) duplicate: bool,
+    pub(crate) delay: Duration,
+}
+
+#[derive(Default)]
+pub(crate) struct FaultInjector {
+    profile: RwLock<Option<(FaultProfile, Instant)>>,
+}
+
+impl FaultInjector {
+    pub(crate) fn set(&self, profile: Option<FaultProfile>) {
+        *zwrite!(self.profile) = profile.map(|profile| (profile, Instant::now()));
+    }
+
+    pub(crate) fn get(&self) -> Option<FaultProfile> {
+        zread!(self.profile)
+            .as_ref()
+            .map(|(profile, _)| profile.clone())
+    }
+
+    pub(crate) fn faults(&self, link: &LinkUnicast) -> Faults {
+        let guard = zread!(self.profile);
+        let step = guard.as_ref().and_then(|(profile, start)| {
+            let rule = profile.rules.iter().find(|rule| rule.matches(link))?;
+            rule.step(start.elapsed())
+        });
+        match step {
+            Some(step) => step.faults(&mut rand::thread_rng()),
+            None => Faults::default(),
+        }
+    }
+}
+
+#[test]
+fn fault_steps() {
+    let rule = |repeat: bool| FaultRule {
+        links: vec![],
+        steps: vec![
+            FaultStep {
+                duration: Some(100),
+                drop: 1.0,
+                ..Default::default()
+            },
+            FaultStep {
+                duration: Some(200),
+                delay: 50,
+                ..Default::default()
+            },
+        ],
+        repeat,
+    };
+    let millis = Duration::from_millis;
+
+    let once = rule(false);
+    assert_eq!(once.step(millis(0)), Some(&once.steps[0]));
+    assert_eq!(once.step(millis(150)), Some(&once.steps[1]));
+    assert_eq!(once.step(millis(300)), None);
+
+    let repeated = rule(true);
+    assert_eq!(repeated.step(millis(350)), Some(&repeated.steps[0]));
+    assert_eq!(repeated.step(millis(650)), Some(&repeated.steps[1]));
+
+    let mut rng = rand::thread_rng();
+    let dropped = once.steps[0].faults(&mut rng);
+    assert!(dropped.drop && !dropped.duplicate);
+    let delayed = once.steps[1].faults(&mut rng);
+    assert_eq!(
+        delayed,
+        Faults {
+            drop: false,
+            duplicate: false,
+            delay: millis(50),
+        }
+    );
+
+    let profile: FaultProfile = serde_json::from_str(
+        r#"{"rules": [{"links": ["tcp/127.0.0.1:7447"], "steps": [{"reorder": 0.5}]}]}"#,
+    )
+    .unwrap();
+    assert_eq!(profile.rules[0].steps[0].reorder, 0.5);
+    assert_eq!(
+        profile.rules[0]
+            .step(millis(u32::MAX as u64))
+            .unwrap()
+            .duration,
+        None
+    );
+    assert!(
+        serde_json::from_str::<FaultProfile>(r#"{"rules": [{"steps": [{"lost": 1}]}]}"#).is_err()
+    );
+}
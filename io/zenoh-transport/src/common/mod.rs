@@ -12,8 +12,12 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 pub(crate) mod batch;
+pub mod capture;
 pub(crate) mod defragmentation;
+#[cfg(feature = "fault_injection")]
+pub mod faults;
 pub(crate) mod pipeline;
+pub(crate) mod pool;
 pub(crate) mod priority;
 pub(crate) mod seq_num;
 #[cfg(feature = "stats")]
@@ -19,7 +19,7 @@ use ringbuffer_spsc::{RingBuffer, RingBufferReader, RingBufferWriter};
 use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use zenoh_buffers::{
     reader::{HasReader, Reader},
     writer::HasWriter,
@@ -44,7 +44,6 @@ use zenoh_protocol::{
 type NanoSeconds = u32;
 
 const RBLEN: usize = QueueSizeConf::MAX;
-const TSLOT: NanoSeconds = 100;
 
 // Inner structure to reuse serialization batches
 struct StageInRefill {
@@ -115,6 +114,7 @@ struct StageIn {
     s_out: StageInOut,
     mutex: StageInMutex,
     fragbuf: ZBuf,
+    inline_threshold: usize,
 }
 
 impl StageIn {
@@ -124,6 +124,8 @@ impl StageIn {
 
         // Check congestion control
         let is_droppable = msg.is_droppable();
+        // Express messages are sent right away instead of waiting for the batch to fill
+        let is_express = msg.is_express();
 
         macro_rules! zgetbatch_rets {
             ($fragment:expr) => {
@@ -158,20 +160,32 @@ impl StageIn {
 
         macro_rules! zretok {
             ($batch:expr) => {{
-                let bytes = $batch.len();
-                *c_guard = Some($batch);
-                drop(c_guard);
-                self.s_out.notify(bytes);
+                if is_express {
+                    drop(c_guard);
+                    self.s_out.move_batch($batch);
+                } else {
+                    let bytes = $batch.len();
+                    *c_guard = Some($batch);
+                    drop(c_guard);
+                    self.s_out.notify(bytes);
+                }
                 return true;
             }};
         }
 
         // Get the current serialization batch.
         let mut batch = zgetbatch_rets!(false);
+        // The payloads above the inline threshold are not copied along with the other messages,
+        // they are directly fragmented in dedicated batches
+        let is_inline = msg.payload_len() <= self.inline_threshold;
         // Attempt the serialization on the current batch
-        let e = match batch.encode(&*msg) {
-            Ok(_) => zretok!(batch),
-            Err(e) => e,
+        let e = if is_inline {
+            match batch.encode(&*msg) {
+                Ok(_) => zretok!(batch),
+                Err(e) => Some(e),
+            }
+        } else {
+            None
         };
 
         // Lock the channel. We are the only one that will be writing on it.
@@ -187,7 +201,7 @@ impl StageIn {
             ext_qos: frame::ext::QoSType::new(priority),
         };
 
-        if let WError::NewFrame = e {
+        if let Some(WError::NewFrame) = e {
             // Attempt a serialization with a new frame
             if batch.encode((&*msg, frame)).is_ok() {
                 zretok!(batch);
@@ -201,12 +215,13 @@ impl StageIn {
         }
 
         // Attempt a second serialization on fully empty batch
-        if batch.encode((&*msg, frame)).is_ok() {
+        if is_inline && batch.encode((&*msg, frame)).is_ok() {
             zretok!(batch);
         };
 
-        // The second serialization attempt has failed. This means that the message is
-        // too large for the current batch size: we need to fragment.
+        // The second serialization attempt has failed or the payload is above the inline
+        // threshold. This means that the message is too large for the current batch size
+        // or is not inlined: we need to fragment.
         // Reinsert the current batch for fragmentation.
         *c_guard = Some(batch);
 
@@ -326,16 +341,38 @@ enum Pull {
 // Inner structure to keep track and signal backoff operations
 #[derive(Clone)]
 struct Backoff {
+    slot: NanoSeconds,
+    max: NanoSeconds,
+    max_delay: Duration,
+    adaptive_delay: bool,
+    // The maximum time a batch is currently held while messages keep being added to it
+    delay: Duration,
     retry_time: NanoSeconds,
+    start: Option<Instant>,
     last_bytes: BatchSize,
     bytes: Arc<AtomicU16>,
     backoff: Arc<AtomicBool>,
 }
 
 impl Backoff {
-    fn new(bytes: Arc<AtomicU16>, backoff: Arc<AtomicBool>) -> Self {
+    fn new(
+        config: &TransmissionPipelineConf,
+        bytes: Arc<AtomicU16>,
+        backoff: Arc<AtomicBool>,
+    ) -> Self {
+        let nanos = |d: Duration| d.as_nanos().min(NanoSeconds::MAX as u128) as NanoSeconds;
         Self {
+            slot: nanos(config.backoff).max(1),
+            max: nanos(config.max_backoff).max(1),
+            max_delay: config.max_delay,
+            adaptive_delay: config.adaptive_delay,
+            delay: if config.adaptive_delay {
+                Duration::ZERO
+            } else {
+                config.max_delay
+            },
             retry_time: 0,
+            start: None,
             last_bytes: 0,
             bytes,
             backoff,
@@ -344,26 +381,39 @@ impl Backoff {
 
     fn next(&mut self) {
         if self.retry_time == 0 {
-            self.retry_time = TSLOT;
+            self.retry_time = self.slot.min(self.max);
+            self.start = Some(Instant::now());
             self.backoff.store(true, Ordering::Relaxed);
         } else {
-            match self.retry_time.checked_mul(2) {
-                Some(rt) => {
-                    self.retry_time = rt;
-                }
-                None => {
-                    self.retry_time = NanoSeconds::MAX;
-                    log::warn!(
-                        "Pipeline pull backoff overflow detected! Retrying in {}ns.",
-                        self.retry_time
-                    );
-                }
-            }
+            self.retry_time = self.retry_time.saturating_mul(2).min(self.max);
         }
     }
 
+    // Whether the backoff has lasted for more than the batching delay
+    fn is_expired(&self) -> bool {
+        self.start
+            .map_or(false, |start| start.elapsed() >= self.delay)
+    }
+
+    // Adapts the batching delay to the depth of the queue: full batches being queued mean that messages
+    // are produced faster than they are sent, which is worth batching more, while pulling incomplete
+    // batches means that the load is low, which is better served with less latency
+    fn adapt(&mut self, queued: bool) {
+        if !self.adaptive_delay {
+            return;
+        }
+        self.delay = if queued {
+            (self.delay * 2)
+                .max(Duration::from_nanos(self.slot as u64))
+                .min(self.max_delay)
+        } else {
+            self.delay / 2
+        };
+    }
+
     fn stop(&mut self) {
         self.retry_time = 0;
+        self.start = None;
         self.backoff.store(false, Ordering::Relaxed);
     }
 }
@@ -381,12 +431,36 @@ impl StageOutIn {
         if let Some(mut batch) = self.s_out_r.pull() {
             batch.write_len();
             self.backoff.stop();
+            self.backoff.adapt(true);
             return Pull::Some(batch);
         }
 
         self.try_pull_deep()
     }
 
+    // Try to pull the current incomplete batch, returns None if it is locked by the stage IN
+    fn try_pull_current(&mut self) -> Option<Pull> {
+        let mut g = self.current.try_lock().ok()?;
+        // First try to pull from stage OUT
+        if let Some(mut batch) = self.s_out_r.pull() {
+            batch.write_len();
+            self.backoff.stop();
+            self.backoff.adapt(true);
+            return Some(Pull::Some(batch));
+        }
+
+        // An incomplete (non-empty) batch is available in the state IN pipeline.
+        self.backoff.stop();
+        match g.take() {
+            Some(mut batch) => {
+                batch.write_len();
+                self.backoff.adapt(false);
+                Some(Pull::Some(batch))
+            }
+            None => Some(Pull::None),
+        }
+    }
+
     fn try_pull_deep(&mut self) -> Pull {
         let new_bytes = self.backoff.bytes.load(Ordering::Relaxed);
         let old_bytes = self.backoff.last_bytes;
@@ -395,26 +469,8 @@ impl StageOutIn {
         match new_bytes.cmp(&old_bytes) {
             std::cmp::Ordering::Equal => {
                 // No new bytes have been written on the batch, try to pull
-                if let Ok(mut g) = self.current.try_lock() {
-                    // First try to pull from stage OUT
-                    if let Some(mut batch) = self.s_out_r.pull() {
-                        batch.write_len();
-                        self.backoff.stop();
-                        return Pull::Some(batch);
-                    }
-
-                    // An incomplete (non-empty) batch is available in the state IN pipeline.
-                    match g.take() {
-                        Some(mut batch) => {
-                            batch.write_len();
-                            self.backoff.stop();
-                            return Pull::Some(batch);
-                        }
-                        None => {
-                            self.backoff.stop();
-                            return Pull::None;
-                        }
-                    }
+                if let Some(pull) = self.try_pull_current() {
+                    return pull;
                 }
                 // Go to backoff
             }
@@ -423,11 +479,18 @@ impl StageOutIn {
                 if let Some(mut batch) = self.s_out_r.pull() {
                     batch.write_len();
                     self.backoff.stop();
+                    self.backoff.adapt(true);
                     return Pull::Some(batch);
                 }
                 // Go to backoff
             }
             std::cmp::Ordering::Greater => {
+                // New bytes keep being written on the batch, pull it anyway once the maximum delay is reached
+                if self.backoff.is_expired() {
+                    if let Some(pull) = self.try_pull_current() {
+                        return pull;
+                    }
+                }
                 // Go to backoff
             }
         }
@@ -486,7 +549,11 @@ pub(crate) struct TransmissionPipelineConf {
     pub(crate) is_streamed: bool,
     pub(crate) batch_size: BatchSize,
     pub(crate) queue_size: [usize; Priority::NUM],
+    pub(crate) inline_threshold: [usize; Priority::NUM],
     pub(crate) backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    pub(crate) max_delay: Duration,
+    pub(crate) adaptive_delay: bool,
 }
 
 impl Default for TransmissionPipelineConf {
@@ -495,7 +562,11 @@ impl Default for TransmissionPipelineConf {
             is_streamed: false,
             batch_size: BatchSize::MAX,
             queue_size: [1; Priority::NUM],
+            inline_threshold: [BatchSize::MAX as usize; Priority::NUM],
             backoff: Duration::from_micros(1),
+            max_backoff: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+            adaptive_delay: true,
         }
     }
 }
@@ -517,6 +588,12 @@ impl TransmissionPipeline {
         } else {
             config.queue_size.iter()
         };
+        let default_inline_threshold = [config.inline_threshold[Priority::default() as usize]];
+        let inline_threshold = if priority.len() == 1 {
+            &default_inline_threshold[..]
+        } else {
+            &config.inline_threshold[..]
+        };
 
         // Create the channel for notifying that new batches are in the out ring buffer
         // This is a MPSC channel
@@ -558,6 +635,7 @@ impl TransmissionPipeline {
                     priority: priority[prio].clone(),
                 },
                 fragbuf: ZBuf::empty(),
+                inline_threshold: inline_threshold[prio],
             }));
 
             // The stage out for this priority
@@ -565,7 +643,7 @@ impl TransmissionPipeline {
                 s_in: StageOutIn {
                     s_out_r,
                     current,
-                    backoff: Backoff::new(bytes, backoff),
+                    backoff: Backoff::new(&config, bytes, backoff),
                 },
                 s_ref: StageOutRefill { n_ref_w, s_ref_w },
             });
@@ -596,6 +674,11 @@ pub(crate) struct TransmissionPipelineProducer {
 impl TransmissionPipelineProducer {
     #[inline]
     pub(crate) fn push_network_message(&self, mut msg: NetworkMessage) -> bool {
+        // The data whose deadline is passed is worthless: it is not queued
+        if msg.is_expired() {
+            log::trace!("Dropping expired message: {:?}", msg);
+            return true;
+        }
         // If the queue is not QoS, it means that we only have one priority with index 0.
         let (idx, priority) = if self.stage_in.len() > 1 {
             let priority = msg.priority();
@@ -732,7 +815,11 @@ mod tests {
         is_streamed: true,
         batch_size: BatchSize::MAX,
         queue_size: [1; Priority::NUM],
+        inline_threshold: [BatchSize::MAX as usize; Priority::NUM],
         backoff: Duration::from_micros(1),
+        max_backoff: Duration::from_millis(1),
+        max_delay: Duration::from_millis(10),
+        adaptive_delay: false,
     };
 
     #[test]
@@ -753,6 +840,9 @@ mod tests {
                     ext_sinfo: None,
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
+                    ext_trace: None,
+                    ext_deadline: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                     payload,
                 }),
@@ -882,6 +972,9 @@ mod tests {
                     ext_sinfo: None,
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
+                    ext_trace: None,
+                    ext_deadline: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                     payload,
                 }),
@@ -954,6 +1047,179 @@ mod tests {
         });
     }
 
+    #[test]
+    fn tx_pipeline_express() {
+        fn message(is_express: bool) -> NetworkMessage {
+            Push {
+                wire_expr: "test".into(),
+                ext_qos: ext::QoSType::new(Priority::Data, CongestionControl::Block, is_express),
+                ext_tstamp: None,
+                ext_nodeid: ext::NodeIdType::default(),
+                payload: PushBody::Put(Put {
+                    timestamp: None,
+                    encoding: Encoding::default(),
+                    ext_sinfo: None,
+                    #[cfg(feature = "shared-memory")]
+                    ext_shm: None,
+                    ext_trace: None,
+                    ext_deadline: None,
+                    ext_batch: None,
+                    ext_unknown: vec![],
+                    payload: ZBuf::from(vec![0_u8; 8]),
+                }),
+            }
+            .into()
+        }
+
+        fn count(batch: &WBatch) -> usize {
+            let bytes = batch.as_bytes();
+            let mut reader = bytes.reader();
+            let codec = Zenoh080::new();
+            let mut msgs = 0;
+            while let Ok(msg) = codec.read(&mut reader) {
+                let msg: TransportMessage = msg;
+                if let TransportBody::Frame(Frame { payload, .. }) = msg.body {
+                    msgs += payload.len();
+                }
+            }
+            msgs
+        }
+
+        let tct = TransportPriorityTx::make(Bits::from(TransportSn::MAX)).unwrap();
+        let priorities = vec![tct];
+        let config = TransmissionPipelineConf {
+            is_streamed: false,
+            queue_size: [2; Priority::NUM],
+            ..CONFIG
+        };
+        let (producer, mut consumer) = TransmissionPipeline::make(config, priorities.as_slice());
+
+        task::block_on(async {
+            // Regular messages are batched together
+            assert!(producer.push_network_message(message(false)));
+            assert!(producer.push_network_message(message(false)));
+            let (batch, priority) = consumer.pull().timeout(TIMEOUT).await.unwrap().unwrap();
+            assert_eq!(count(&batch), 2);
+            consumer.refill(batch, priority);
+
+            // Express messages are sent in their own batch
+            assert!(producer.push_network_message(message(true)));
+            assert!(producer.push_network_message(message(true)));
+            for _ in 0..2 {
+                let (batch, priority) = consumer.pull().timeout(TIMEOUT).await.unwrap().unwrap();
+                assert_eq!(count(&batch), 1);
+                consumer.refill(batch, priority);
+            }
+        });
+    }
+
+    #[test]
+    fn tx_pipeline_inline_threshold() {
+        fn message(size: usize) -> NetworkMessage {
+            Push {
+                wire_expr: "test".into(),
+                ext_qos: ext::QoSType::new(Priority::Data, CongestionControl::Block, false),
+                ext_tstamp: None,
+                ext_nodeid: ext::NodeIdType::default(),
+                payload: PushBody::Put(Put {
+                    timestamp: None,
+                    encoding: Encoding::default(),
+                    ext_sinfo: None,
+                    #[cfg(feature = "shared-memory")]
+                    ext_shm: None,
+                    ext_trace: None,
+                    ext_deadline: None,
+                    ext_batch: None,
+                    ext_unknown: vec![],
+                    payload: ZBuf::from(vec![0_u8; size]),
+                }),
+            }
+            .into()
+        }
+
+        // The number of frame messages and of fragments in the batch
+        fn count(batch: &WBatch) -> (usize, usize) {
+            let bytes = batch.as_bytes();
+            let mut reader = bytes.reader();
+            let codec = Zenoh080::new();
+            let (mut msgs, mut fragments) = (0, 0);
+            while let Ok(msg) = codec.read(&mut reader) {
+                let msg: TransportMessage = msg;
+                match msg.body {
+                    TransportBody::Frame(Frame { payload, .. }) => msgs += payload.len(),
+                    TransportBody::Fragment(_) => fragments += 1,
+                    _ => {}
+                }
+            }
+            (msgs, fragments)
+        }
+
+        let tct = TransportPriorityTx::make(Bits::from(TransportSn::MAX)).unwrap();
+        let priorities = vec![tct];
+        let config = TransmissionPipelineConf {
+            is_streamed: false,
+            queue_size: [2; Priority::NUM],
+            inline_threshold: [16; Priority::NUM],
+            ..CONFIG
+        };
+        let (producer, mut consumer) = TransmissionPipeline::make(config, priorities.as_slice());
+
+        task::block_on(async {
+            // The small payload is inlined, the larger one is sent as a fragment after it
+            assert!(producer.push_network_message(message(8)));
+            assert!(producer.push_network_message(message(32)));
+            for expected in [(1, 0), (0, 1)] {
+                let (batch, priority) = consumer.pull().timeout(TIMEOUT).await.unwrap().unwrap();
+                assert_eq!(count(&batch), expected);
+                consumer.refill(batch, priority);
+            }
+        });
+    }
+
+    #[test]
+    fn tx_pipeline_adaptive_delay() {
+        let config = TransmissionPipelineConf {
+            adaptive_delay: true,
+            ..CONFIG
+        };
+        let mut backoff = Backoff::new(
+            &config,
+            Arc::new(AtomicU16::new(0)),
+            Arc::new(AtomicBool::new(false)),
+        );
+        // No delay is added at low load
+        assert_eq!(backoff.delay, Duration::ZERO);
+        backoff.adapt(false);
+        assert_eq!(backoff.delay, Duration::ZERO);
+
+        // The delay grows toward the maximum under sustained load
+        backoff.adapt(true);
+        assert_eq!(backoff.delay, config.backoff);
+        backoff.adapt(true);
+        assert_eq!(backoff.delay, config.backoff * 2);
+        for _ in 0..64 {
+            backoff.adapt(true);
+        }
+        assert_eq!(backoff.delay, config.max_delay);
+
+        // And shrinks back once the queue drains
+        backoff.adapt(false);
+        assert_eq!(backoff.delay, config.max_delay / 2);
+        for _ in 0..64 {
+            backoff.adapt(false);
+        }
+        assert_eq!(backoff.delay, Duration::ZERO);
+
+        // The delay is fixed if not adaptive
+        let mut backoff = Backoff::new(
+            &CONFIG,
+            Arc::new(AtomicU16::new(0)),
+            Arc::new(AtomicBool::new(false)),
+        );
+        backoff.adapt(false);
+        assert_eq!(backoff.delay, CONFIG.max_delay);
+    }
+
     #[test]
     #[ignore]
     fn tx_pipeline_thr() {
@@ -993,6 +1259,9 @@ mod tests {
                             ext_sinfo: None,
                             #[cfg(feature = "shared-memory")]
                             ext_shm: None,
+                            ext_trace: None,
+                            ext_deadline: None,
+                            ext_batch: None,
                             ext_unknown: vec![],
                             payload,
                         }),
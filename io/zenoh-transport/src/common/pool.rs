@@ -0,0 +1,167 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, Weak};
+use zenoh_buffers::ZSliceBuffer;
+use zenoh_core::zlock;
+
+/// The pools of reusable batch buffers of a transport manager, one per buffer size.
+///
+/// The links of the same MTU share the same pool, so that the buffers released by the
+/// messages of one link are reused by the others instead of being freed.
+#[derive(Default)]
+pub(crate) struct BufferPools {
+    pools: Mutex<HashMap<usize, BufferPool>>,
+}
+
+impl BufferPools {
+    /// Returns the pool of the buffers of `size` bytes, creating it with `capacity` buffers if needed.
+    pub(crate) fn get(&self, size: usize, capacity: usize) -> BufferPool {
+        zlock!(self.pools)
+            .entry(size)
+            .or_insert_with(|| BufferPool::new(size, capacity))
+            .clone()
+    }
+}
+
+struct BufferPoolInner {
+    size: usize,
+    capacity: usize,
+    buffers: Mutex<Vec<Box<[u8]>>>,
+}
+
+/// A pool of batch buffers of the same size. The buffers are allocated on demand and
+/// return to the pool when the last slice referring to them is dropped, up to `capacity`
+/// buffers are kept.
+#[derive(Clone)]
+pub(crate) struct BufferPool {
+    inner: Arc<BufferPoolInner>,
+}
+
+impl BufferPool {
+    pub(crate) fn new(size: usize, capacity: usize) -> Self {
+        BufferPool {
+            inner: Arc::new(BufferPoolInner {
+                size,
+                capacity,
+                buffers: Mutex::new(Vec::with_capacity(capacity)),
+            }),
+        }
+    }
+
+    /// Returns a buffer of the pool, `None` if the pool is empty.
+    pub(crate) fn try_take(&self) -> Option<PooledBuffer> {
+        zlock!(self.inner.buffers).pop().map(|buffer| PooledBuffer {
+            buffer: Some(buffer),
+            pool: Arc::downgrade(&self.inner),
+        })
+    }
+
+    /// Allocates a new buffer, returning to the pool when released.
+    pub(crate) fn alloc(&self) -> PooledBuffer {
+        PooledBuffer {
+            buffer: Some(vec![0_u8; self.inner.size].into_boxed_slice()),
+            pool: Arc::downgrade(&self.inner),
+        }
+    }
+
+    /// Returns a buffer of the pool, allocating one if the pool is empty,
+    /// and whether it was taken from the pool.
+    pub(crate) fn take(&self) -> (PooledBuffer, bool) {
+        match self.try_take() {
+            Some(buffer) => (buffer, true),
+            None => (self.alloc(), false),
+        }
+    }
+}
+
+/// A buffer taken from a [`BufferPool`].
+pub(crate) struct PooledBuffer {
+    buffer: Option<Box<[u8]>>,
+    pool: Weak<BufferPoolInner>,
+}
+
+impl Deref for PooledBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.buffer.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.buffer.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if let (Some(pool), Some(buffer)) = (self.pool.upgrade(), self.buffer.take()) {
+            let mut buffers = zlock!(pool.buffers);
+            if buffers.len() < pool.capacity {
+                buffers.push(buffer);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for PooledBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledBuffer")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl ZSliceBuffer for PooledBuffer {
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[test]
+fn buffer_pool() {
+    let pools = BufferPools::default();
+    let pool = pools.get(16, 2);
+    assert!(pool.try_take().is_none());
+
+    let (a, hit) = pool.take();
+    assert!(!hit);
+    assert_eq!(a.len(), 16);
+    let (b, _) = pool.take();
+    let (c, _) = pool.take();
+    drop((a, b, c));
+
+    // Only up to capacity buffers are kept, and they are shared by the links of the same size
+    let pool = pools.get(16, 2);
+    let (a, hit) = pool.take();
+    assert!(hit);
+    let (b, hit) = pool.take();
+    assert!(hit);
+    let (_c, hit) = pool.take();
+    assert!(!hit);
+    drop((a, b));
+    assert!(pools.get(32, 2).try_take().is_none());
+    assert!(pool.try_take().is_some());
+}
@@ -240,6 +240,14 @@ stats_struct! {
         # TYPE "counter"
         pub rx_n_dropped,
 
+        # HELP "Counter of receive buffers reused from the buffer pool."
+        # TYPE "counter"
+        pub rx_pool_hits,
+
+        # HELP "Counter of receive buffers allocated because the buffer pool was empty."
+        # TYPE "counter"
+        pub rx_pool_misses,
+
         # HELP "Counter of received zenoh put messages."
         # TYPE "counter"
         pub rx_z_put_msgs DiscriminatedStats,
@@ -19,10 +19,14 @@
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
 mod common;
 mod manager;
+#[cfg(feature = "transport_multicast")]
 mod multicast;
 mod primitives;
 pub mod unicast;
 
+pub use common::capture;
+#[cfg(feature = "fault_injection")]
+pub use common::faults;
 #[cfg(feature = "stats")]
 pub use common::stats;
 
@@ -30,6 +34,7 @@ pub use common::stats;
 mod shm;
 
 pub use manager::*;
+#[cfg(feature = "transport_multicast")]
 pub use multicast::*;
 pub use primitives::*;
 use serde::Serialize;
@@ -51,6 +56,7 @@ pub trait TransportEventHandler: Send + Sync {
         transport: TransportUnicast,
     ) -> ZResult<Arc<dyn TransportPeerEventHandler>>;
 
+    #[cfg(feature = "transport_multicast")]
     fn new_multicast(
         &self,
         _transport: TransportMulticast,
@@ -69,6 +75,7 @@ impl TransportEventHandler for DummyTransportEventHandler {
         Ok(Arc::new(DummyTransportPeerEventHandler))
     }
 
+    #[cfg(feature = "transport_multicast")]
     fn new_multicast(
         &self,
         _transport: TransportMulticast,
@@ -80,6 +87,7 @@ impl TransportEventHandler for DummyTransportEventHandler {
 /*************************************/
 /*            MULTICAST              */
 /*************************************/
+#[cfg(feature = "transport_multicast")]
 pub trait TransportMulticastEventHandler: Send + Sync {
     fn new_peer(&self, peer: TransportPeer) -> ZResult<Arc<dyn TransportPeerEventHandler>>;
     fn closing(&self);
@@ -88,9 +96,11 @@ pub trait TransportMulticastEventHandler: Send + Sync {
 }
 
 // Define an empty TransportCallback for the listener transport
+#[cfg(feature = "transport_multicast")]
 #[derive(Default)]
 pub struct DummyTransportMulticastEventHandler;
 
+#[cfg(feature = "transport_multicast")]
 impl TransportMulticastEventHandler for DummyTransportMulticastEventHandler {
     fn new_peer(&self, _peer: TransportPeer) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
         Ok(Arc::new(DummyTransportPeerEventHandler))
@@ -15,6 +15,11 @@ use super::unicast::manager::{
     TransportManagerBuilderUnicast, TransportManagerConfigUnicast, TransportManagerStateUnicast,
 };
 use super::TransportEventHandler;
+use crate::common::capture::Capture;
+#[cfg(feature = "fault_injection")]
+use crate::common::faults::{FaultInjector, FaultProfile};
+use crate::common::pool::{BufferPool, BufferPools};
+#[cfg(feature = "transport_multicast")]
 use crate::multicast::manager::{
     TransportManagerBuilderMulticast, TransportManagerConfigMulticast,
     TransportManagerStateMulticast,
@@ -24,7 +29,7 @@ use rand::{RngCore, SeedableRng};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use zenoh_config::{Config, LinkRxConf, QueueConf, QueueSizeConf};
+use zenoh_config::{Config, InlineThresholdConf, LinkRxConf, QueueConf, QueueSizeConf};
 use zenoh_crypto::{BlockCipher, PseudoRng};
 use zenoh_link::NewLinkChannelSender;
 use zenoh_protocol::{
@@ -94,20 +99,30 @@ pub struct TransportManagerConfig {
     pub resolution: Resolution,
     pub batch_size: u16,
     pub queue_size: [usize; Priority::NUM],
+    pub queue_inline_threshold: [usize; Priority::NUM],
     pub queue_backoff: Duration,
+    pub queue_max_backoff: Duration,
+    pub queue_max_delay: Duration,
+    pub queue_adaptive_delay: bool,
     pub defrag_buff_size: usize,
     pub link_rx_buffer_size: usize,
     pub unicast: TransportManagerConfigUnicast,
+    #[cfg(feature = "transport_multicast")]
     pub multicast: TransportManagerConfigMulticast,
     pub endpoints: HashMap<String, String>, // (protocol, config)
     pub handler: Arc<dyn TransportEventHandler>,
     pub tx_threads: usize,
+    pub tx_affinity: Option<Vec<usize>>,
     pub protocols: Vec<String>,
 }
 
 pub struct TransportManagerState {
     pub unicast: TransportManagerStateUnicast,
+    #[cfg(feature = "transport_multicast")]
     pub multicast: TransportManagerStateMulticast,
+    pub(crate) capture: Option<Arc<Capture>>,
+    #[cfg(feature = "fault_injection")]
+    pub(crate) faults: FaultInjector,
 }
 
 pub struct TransportManagerParams {
@@ -122,14 +137,21 @@ pub struct TransportManagerBuilder {
     resolution: Resolution,
     batch_size: u16,
     queue_size: QueueSizeConf,
+    queue_inline_threshold: InlineThresholdConf,
     queue_backoff: Duration,
+    queue_max_backoff: Duration,
+    queue_max_delay: Duration,
+    queue_adaptive_delay: bool,
     defrag_buff_size: usize,
     link_rx_buffer_size: usize,
     unicast: TransportManagerBuilderUnicast,
+    #[cfg(feature = "transport_multicast")]
     multicast: TransportManagerBuilderMulticast,
     endpoints: HashMap<String, String>, // (protocol, config)
     tx_threads: usize,
+    tx_affinity: Option<Vec<usize>>,
     protocols: Option<Vec<String>>,
+    capture: Option<String>,
 }
 
 impl TransportManagerBuilder {
@@ -158,11 +180,31 @@ impl TransportManagerBuilder {
         self
     }
 
+    pub fn queue_inline_threshold(mut self, queue_inline_threshold: InlineThresholdConf) -> Self {
+        self.queue_inline_threshold = queue_inline_threshold;
+        self
+    }
+
     pub fn queue_backoff(mut self, queue_backoff: Duration) -> Self {
         self.queue_backoff = queue_backoff;
         self
     }
 
+    pub fn queue_max_backoff(mut self, queue_max_backoff: Duration) -> Self {
+        self.queue_max_backoff = queue_max_backoff;
+        self
+    }
+
+    pub fn queue_max_delay(mut self, queue_max_delay: Duration) -> Self {
+        self.queue_max_delay = queue_max_delay;
+        self
+    }
+
+    pub fn queue_adaptive_delay(mut self, queue_adaptive_delay: bool) -> Self {
+        self.queue_adaptive_delay = queue_adaptive_delay;
+        self
+    }
+
     pub fn defrag_buff_size(mut self, defrag_buff_size: usize) -> Self {
         self.defrag_buff_size = defrag_buff_size;
         self
@@ -183,6 +225,7 @@ impl TransportManagerBuilder {
         self
     }
 
+    #[cfg(feature = "transport_multicast")]
     pub fn multicast(mut self, multicast: TransportManagerBuilderMulticast) -> Self {
         self.multicast = multicast;
         self
@@ -193,11 +236,21 @@ impl TransportManagerBuilder {
         self
     }
 
+    pub fn tx_affinity(mut self, cores: Option<Vec<usize>>) -> Self {
+        self.tx_affinity = cores;
+        self
+    }
+
     pub fn protocols(mut self, protocols: Option<Vec<String>>) -> Self {
         self.protocols = protocols;
         self
     }
 
+    pub fn capture(mut self, file: Option<String>) -> Self {
+        self.capture = file;
+        self
+    }
+
     pub async fn from_config(mut self, config: &Config) -> ZResult<TransportManagerBuilder> {
         self = self.zid(*config.id());
         if let Some(v) = config.mode() {
@@ -212,8 +265,15 @@ impl TransportManagerBuilder {
         self = self.defrag_buff_size(*link.rx().max_message_size());
         self = self.link_rx_buffer_size(*link.rx().buffer_size());
         self = self.queue_size(link.tx().queue().size().clone());
+        self = self.queue_inline_threshold(link.tx().queue().inline_threshold().clone());
+        self = self.queue_backoff(Duration::from_nanos(*link.tx().queue().backoff()));
+        self = self.queue_max_backoff(Duration::from_nanos(*link.tx().queue().max_backoff()));
+        self = self.queue_max_delay(Duration::from_nanos(*link.tx().queue().max_delay()));
+        self = self.queue_adaptive_delay(*link.tx().queue().adaptive_delay());
         self = self.tx_threads(*link.tx().threads());
+        self = self.tx_affinity(link.tx().affinity().clone());
         self = self.protocols(link.protocols().clone());
+        self = self.capture(config.transport().capture().file().clone());
 
         let (c, errors) = zenoh_link::LinkConfigurator::default()
             .configurations(config)
@@ -232,11 +292,14 @@ impl TransportManagerBuilder {
                 .from_config(config)
                 .await?,
         );
-        self = self.multicast(
-            TransportManagerBuilderMulticast::default()
-                .from_config(config)
-                .await?,
-        );
+        #[cfg(feature = "transport_multicast")]
+        {
+            self = self.multicast(
+                TransportManagerBuilderMulticast::default()
+                    .from_config(config)
+                    .await?,
+            );
+        }
 
         Ok(self)
     }
@@ -246,6 +309,7 @@ impl TransportManagerBuilder {
         let mut prng = PseudoRng::from_entropy();
 
         let unicast = self.unicast.build(&mut prng)?;
+        #[cfg(feature = "transport_multicast")]
         let multicast = self.multicast.build()?;
 
         let mut queue_size = [0; Priority::NUM];
@@ -258,6 +322,17 @@ impl TransportManagerBuilder {
         queue_size[Priority::DataLow as usize] = *self.queue_size.data_low();
         queue_size[Priority::Background as usize] = *self.queue_size.background();
 
+        let threshold = &self.queue_inline_threshold;
+        let mut queue_inline_threshold = [0; Priority::NUM];
+        queue_inline_threshold[Priority::Control as usize] = *threshold.control();
+        queue_inline_threshold[Priority::RealTime as usize] = *threshold.real_time();
+        queue_inline_threshold[Priority::InteractiveHigh as usize] = *threshold.interactive_high();
+        queue_inline_threshold[Priority::InteractiveLow as usize] = *threshold.interactive_low();
+        queue_inline_threshold[Priority::DataHigh as usize] = *threshold.data_high();
+        queue_inline_threshold[Priority::Data as usize] = *threshold.data();
+        queue_inline_threshold[Priority::DataLow as usize] = *threshold.data_low();
+        queue_inline_threshold[Priority::Background as usize] = *threshold.background();
+
         let config = TransportManagerConfig {
             version: self.version,
             zid: self.zid,
@@ -265,14 +340,20 @@ impl TransportManagerBuilder {
             resolution: self.resolution,
             batch_size: self.batch_size,
             queue_size,
+            queue_inline_threshold,
             queue_backoff: self.queue_backoff,
+            queue_max_backoff: self.queue_max_backoff,
+            queue_max_delay: self.queue_max_delay,
+            queue_adaptive_delay: self.queue_adaptive_delay,
             defrag_buff_size: self.defrag_buff_size,
             link_rx_buffer_size: self.link_rx_buffer_size,
             unicast: unicast.config,
+            #[cfg(feature = "transport_multicast")]
             multicast: multicast.config,
             endpoints: self.endpoints,
             handler,
             tx_threads: self.tx_threads,
+            tx_affinity: self.tx_affinity,
             protocols: self.protocols.unwrap_or_else(|| {
                 zenoh_link::PROTOCOLS
                     .iter()
@@ -281,9 +362,18 @@ impl TransportManagerBuilder {
             }),
         };
 
+        let capture = match self.capture.as_deref() {
+            Some(file) => Some(Arc::new(Capture::create(file)?)),
+            None => None,
+        };
+
         let state = TransportManagerState {
             unicast: unicast.state,
+            #[cfg(feature = "transport_multicast")]
             multicast: multicast.state,
+            capture,
+            #[cfg(feature = "fault_injection")]
+            faults: FaultInjector::default(),
         };
 
         let params = TransportManagerParams { config, state };
@@ -297,6 +387,8 @@ impl Default for TransportManagerBuilder {
         let link_rx = LinkRxConf::default();
         let queue = QueueConf::default();
         let backoff = *queue.backoff();
+        let max_backoff = *queue.max_backoff();
+        let max_delay = *queue.max_delay();
         Self {
             version: VERSION,
             zid: ZenohId::rand(),
@@ -304,14 +396,21 @@ impl Default for TransportManagerBuilder {
             resolution: Resolution::default(),
             batch_size: BatchSize::MAX,
             queue_size: queue.size,
+            queue_inline_threshold: queue.inline_threshold,
             queue_backoff: Duration::from_nanos(backoff),
+            queue_max_backoff: Duration::from_nanos(max_backoff),
+            queue_max_delay: Duration::from_nanos(max_delay),
+            queue_adaptive_delay: *queue.adaptive_delay(),
             defrag_buff_size: *link_rx.max_message_size(),
             link_rx_buffer_size: *link_rx.buffer_size(),
             endpoints: HashMap::new(),
             unicast: TransportManagerBuilderUnicast::default(),
+            #[cfg(feature = "transport_multicast")]
             multicast: TransportManagerBuilderMulticast::default(),
             tx_threads: 1,
+            tx_affinity: None,
             protocols: None,
+            capture: None,
         }
     }
 }
@@ -323,15 +422,23 @@ pub(crate) struct TransportExecutor {
 }
 
 impl TransportExecutor {
-    fn new(num_threads: usize) -> Self {
+    fn new(num_threads: usize, affinity: Option<Vec<usize>>) -> Self {
         let (sender, receiver) = async_std::channel::bounded(1);
         let executor = Arc::new(async_executor::Executor::new());
         for i in 0..num_threads {
             let exec = executor.clone();
             let recv = receiver.clone();
+            let affinity = affinity.clone();
             std::thread::Builder::new()
                 .name(format!("zenoh-tx-{}", i))
-                .spawn(move || async_std::task::block_on(exec.run(recv.recv())))
+                .spawn(move || {
+                    if let Some(cores) = affinity {
+                        if let Err(e) = zenoh_util::set_current_thread_affinity(&cores) {
+                            log::warn!("Unable to pin zenoh-tx-{}: {}", i, e);
+                        }
+                    }
+                    async_std::task::block_on(exec.run(recv.recv()))
+                })
                 .unwrap();
         }
         Self { executor, sender }
@@ -358,6 +465,7 @@ pub struct TransportManager {
     pub(crate) locator_inspector: zenoh_link::LocatorInspector,
     pub(crate) new_unicast_link_sender: NewLinkChannelSender,
     pub(crate) tx_executor: TransportExecutor,
+    pub(crate) rx_buffers: Arc<BufferPools>,
     #[cfg(feature = "stats")]
     pub(crate) stats: Arc<crate::stats::TransportStats>,
 }
@@ -373,6 +481,7 @@ impl TransportManager {
         let (new_unicast_link_sender, new_unicast_link_receiver) = flume::unbounded();
 
         let tx_threads = params.config.tx_threads;
+        let tx_affinity = params.config.tx_affinity.clone();
         let this = TransportManager {
             config: Arc::new(params.config),
             state: Arc::new(params.state),
@@ -380,7 +489,8 @@ impl TransportManager {
             cipher: Arc::new(cipher),
             locator_inspector: Default::default(),
             new_unicast_link_sender,
-            tx_executor: TransportExecutor::new(tx_threads),
+            tx_executor: TransportExecutor::new(tx_threads, tx_affinity),
+            rx_buffers: Arc::new(BufferPools::default()),
             #[cfg(feature = "stats")]
             stats: std::sync::Arc::new(crate::stats::TransportStats::default()),
         };
@@ -406,11 +516,29 @@ impl TransportManager {
         self.config.zid
     }
 
+    /// Returns the pool of the receive buffers of `mtu` bytes shared by the links of this manager.
+    pub(crate) fn rx_buffer_pool(&self, mtu: usize) -> BufferPool {
+        let capacity = (self.config.link_rx_buffer_size + mtu - 1) / mtu;
+        self.rx_buffers.get(mtu, capacity)
+    }
+
     #[cfg(feature = "stats")]
     pub fn get_stats(&self) -> std::sync::Arc<crate::stats::TransportStats> {
         self.stats.clone()
     }
 
+    /// Sets the profile of the faults injected in the messages received by the unicast transports,
+    /// or stops injecting faults if `None`.
+    #[cfg(feature = "fault_injection")]
+    pub fn set_fault_profile(&self, profile: Option<FaultProfile>) {
+        self.state.faults.set(profile);
+    }
+
+    #[cfg(feature = "fault_injection")]
+    pub fn fault_profile(&self) -> Option<FaultProfile> {
+        self.state.faults.get()
+    }
+
     pub async fn close(&self) {
         self.close_unicast().await;
         self.tx_executor.stop().await;
@@ -425,10 +553,15 @@ impl TransportManager {
             .is_multicast(&endpoint.to_locator())
             .await?
         {
-            self.add_listener_multicast(endpoint).await
-        } else {
-            self.add_listener_unicast(endpoint).await
+            #[cfg(feature = "transport_multicast")]
+            return self.add_listener_multicast(endpoint).await;
+            #[cfg(not(feature = "transport_multicast"))]
+            bail!(
+                "Multicast transports are not supported in this build: {}",
+                endpoint
+            );
         }
+        self.add_listener_unicast(endpoint).await
     }
 
     pub async fn del_listener(&self, endpoint: &EndPoint) -> ZResult<()> {
@@ -437,23 +570,30 @@ impl TransportManager {
             .is_multicast(&endpoint.to_locator())
             .await?
         {
-            self.del_listener_multicast(endpoint).await
-        } else {
-            self.del_listener_unicast(endpoint).await
+            #[cfg(feature = "transport_multicast")]
+            return self.del_listener_multicast(endpoint).await;
+            #[cfg(not(feature = "transport_multicast"))]
+            bail!(
+                "Multicast transports are not supported in this build: {}",
+                endpoint
+            );
         }
+        self.del_listener_unicast(endpoint).await
     }
 
     pub fn get_listeners(&self) -> Vec<EndPoint> {
+        #[allow(unused_mut)]
         let mut lsu = task::block_on(self.get_listeners_unicast());
-        let mut lsm = task::block_on(self.get_listeners_multicast());
-        lsu.append(&mut lsm);
+        #[cfg(feature = "transport_multicast")]
+        lsu.append(&mut task::block_on(self.get_listeners_multicast()));
         lsu
     }
 
     pub fn get_locators(&self) -> Vec<Locator> {
+        #[allow(unused_mut)]
         let mut lsu = task::block_on(self.get_locators_unicast());
-        let mut lsm = task::block_on(self.get_locators_multicast());
-        lsu.append(&mut lsm);
+        #[cfg(feature = "transport_multicast")]
+        lsu.append(&mut task::block_on(self.get_locators_multicast()));
         lsu
     }
 }
@@ -33,7 +33,7 @@ use zenoh_protocol::{
     transport::{BatchSize, Join, PrioritySn, TransportMessage, TransportSn},
 };
 use zenoh_result::{bail, zerror, ZResult};
-use zenoh_sync::{RecyclingObjectPool, Signal};
+use zenoh_sync::Signal;
 
 pub(super) struct TransportLinkMulticastConfig {
     pub(super) version: u8,
@@ -108,7 +108,11 @@ impl TransportLinkMulticast {
                 is_streamed: false,
                 batch_size: config.batch_size,
                 queue_size: self.transport.manager.config.queue_size,
+                inline_threshold: self.transport.manager.config.queue_inline_threshold,
                 backoff: self.transport.manager.config.queue_backoff,
+                max_backoff: self.transport.manager.config.queue_max_backoff,
+                max_delay: self.transport.manager.config.queue_max_delay,
+                adaptive_delay: self.transport.manager.config.queue_adaptive_delay,
             };
             // The pipeline
             let (producer, consumer) = TransmissionPipeline::make(tpc, &priority_tx);
@@ -150,7 +154,6 @@ impl TransportLinkMulticast {
             let c_link = self.link.clone();
             let ctransport = self.transport.clone();
             let c_signal = self.signal_rx.clone();
-            let c_rx_buffer_size = self.transport.manager.config.link_rx_buffer_size;
 
             let handle = task::spawn(async move {
                 // Start the consume task
@@ -158,7 +161,6 @@ impl TransportLinkMulticast {
                     c_link.clone(),
                     ctransport.clone(),
                     c_signal.clone(),
-                    c_rx_buffer_size,
                     batch_size,
                 )
                 .await;
@@ -327,7 +329,6 @@ async fn rx_task(
     link: LinkMulticast,
     transport: TransportMulticastInner,
     signal: Signal,
-    rx_buffer_size: usize,
     batch_size: BatchSize,
 ) -> ZResult<()> {
     enum Action {
@@ -347,14 +348,16 @@ async fn rx_task(
 
     // The pool of buffers
     let mtu = link.get_mtu() as usize;
-    let mut n = rx_buffer_size / mtu;
-    if rx_buffer_size % mtu != 0 {
-        n += 1;
-    }
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_buffer_pool(mtu);
     while !signal.is_triggered() {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let (mut buffer, _hit) = pool.take();
+        #[cfg(feature = "stats")]
+        if _hit {
+            transport.stats.inc_rx_pool_hits(1);
+        } else {
+            transport.stats.inc_rx_pool_misses(1);
+        }
         // Async read from the underlying link
         let action = read(&link, &mut buffer).race(stop(signal.clone())).await?;
         match action {
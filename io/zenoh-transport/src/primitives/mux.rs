@@ -11,7 +11,9 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::super::{TransportMulticast, TransportUnicast};
+#[cfg(feature = "transport_multicast")]
+use super::super::TransportMulticast;
+use super::super::TransportUnicast;
 use super::Primitives;
 use zenoh_protocol::network::{
     Declare, NetworkBody, NetworkMessage, Push, Request, Response, ResponseFinal,
@@ -73,16 +75,19 @@ impl Primitives for Mux {
     }
 }
 
+#[cfg(feature = "transport_multicast")]
 pub struct McastMux {
     handler: TransportMulticast,
 }
 
+#[cfg(feature = "transport_multicast")]
 impl McastMux {
     pub fn new(handler: TransportMulticast) -> McastMux {
         McastMux { handler }
     }
 }
 
+#[cfg(feature = "transport_multicast")]
 impl Primitives for McastMux {
     fn send_declare(&self, msg: Declare) {
         let _ = self.handler.handle_message(NetworkMessage {
@@ -166,7 +166,7 @@ pub fn map_zmsg_to_shminfo(msg: &mut NetworkMessage) -> ZResult<bool> {
             ResponseBody::Reply(b) => b.map_to_shminfo(),
             ResponseBody::Put(b) => b.map_to_shminfo(),
             ResponseBody::Err(b) => b.map_to_shminfo(),
-            ResponseBody::Ack(_) => Ok(false),
+            ResponseBody::Del(_) | ResponseBody::Ack(_) => Ok(false),
         },
         NetworkBody::ResponseFinal(_) | NetworkBody::Declare(_) | NetworkBody::OAM(_) => Ok(false),
     }
@@ -221,7 +221,7 @@ pub fn map_zmsg_to_shmbuf(
             ResponseBody::Put(b) => b.map_to_shmbuf(shmr),
             ResponseBody::Err(b) => b.map_to_shmbuf(shmr),
             ResponseBody::Reply(b) => b.map_to_shmbuf(shmr),
-            ResponseBody::Ack(_) => Ok(false),
+            ResponseBody::Del(_) | ResponseBody::Ack(_) => Ok(false),
         },
         NetworkBody::ResponseFinal(_) | NetworkBody::Declare(_) | NetworkBody::OAM(_) => Ok(false),
     }
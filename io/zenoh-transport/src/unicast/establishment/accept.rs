@@ -556,11 +556,13 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
         };
     }
 
+    let profile = manager.get_listener_profile_unicast(link).await;
+
     let iack_out = {
         let mut state = State {
             zenoh: StateZenoh {
-                batch_size: manager.config.batch_size,
-                resolution: manager.config.resolution,
+                batch_size: profile.batch_size(manager.config.batch_size),
+                resolution: profile.resolution(manager.config.resolution),
             },
             ext_qos: ext::qos::StateAccept::new(manager.config.unicast.is_qos),
             ext_lowlatency: ext::lowlatency::StateAccept::new(manager.config.unicast.is_lowlatency),
@@ -607,9 +609,10 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
     let (mut state, osyn_out) = step!(fsm.recv_open_syn(osyn_in).await);
 
     // Create the OpenAck but not send it yet
+    let mine_lease = profile.lease(manager.config.unicast.lease);
     let oack_in = SendOpenAckIn {
         mine_zid: manager.config.zid,
-        mine_lease: manager.config.unicast.lease,
+        mine_lease,
         other_zid: osyn_out.other_zid,
     };
     let oack_out = step!(fsm.send_open_ack((&mut state, oack_in)).await);
@@ -626,11 +629,27 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
         #[cfg(feature = "shared-memory")]
         is_shm: state.ext_shm.is_shm(),
         is_lowlatency: state.ext_lowlatency.is_lowlatency(),
+        #[cfg(feature = "transport_auth")]
+        auth_ids: state
+            .ext_auth
+            .auth_ids()
+            .into_iter()
+            .chain(
+                link.get_peer_cert_names()
+                    .into_iter()
+                    .map(ext::auth::AuthId::CertName),
+            )
+            .collect(),
     };
 
     let transport = step!(
         manager
-            .init_transport_unicast(config, link.clone(), LinkUnicastDirection::Inbound)
+            .init_transport_unicast(
+                config,
+                link.clone(),
+                LinkUnicastDirection::Inbound,
+                &profile
+            )
             .await
     );
 
@@ -650,6 +669,7 @@ pub(crate) async fn accept_link(link: &LinkUnicast, manager: &TransportManager)
     // Finalize the transport
     let input = InputFinalize {
         transport: transport.clone(),
+        mine_lease,
         other_lease: osyn_out.other_lease,
         agreed_batch_size: state.zenoh.batch_size,
     };
@@ -13,6 +13,8 @@
 //
 #[cfg(feature = "auth_pubkey")]
 pub(crate) mod pubkey;
+#[cfg(feature = "auth_token")]
+pub(crate) mod token;
 #[cfg(feature = "auth_usrpwd")]
 pub(crate) mod usrpwd;
 
@@ -23,7 +25,10 @@ use async_trait::async_trait;
 pub use pubkey::*;
 use rand::{CryptoRng, Rng};
 use std::convert::TryInto;
+use std::fmt;
 use std::marker::PhantomData;
+#[cfg(feature = "auth_token")]
+pub use token::*;
 #[cfg(feature = "auth_usrpwd")]
 pub use usrpwd::*;
 use zenoh_buffers::reader::SiphonableReader;
@@ -34,18 +39,42 @@ use zenoh_buffers::{
 };
 use zenoh_codec::{RCodec, WCodec, Zenoh080};
 use zenoh_config::Config;
-use zenoh_core::{bail, zerror, Error as ZError, Result as ZResult};
+use zenoh_core::{bail, zasyncwrite, zerror, Error as ZError, Result as ZResult};
 use zenoh_crypto::PseudoRng;
 use zenoh_protocol::{
     common::{iext, ZExtUnknown},
     transport::{init, open},
 };
 
+/// An identity proven by the remote peer while establishing a transport.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AuthId {
+    /// A user authenticated by the user-password authenticator.
+    Username(String),
+    /// The identity claimed by a token validated by the token authenticator.
+    Token(String),
+    /// A name of the TLS client certificate the link was established with:
+    /// its subject common name or one of its subject alternative DNS names.
+    CertName(String),
+}
+
+impl fmt::Display for AuthId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthId::Username(user) => write!(f, "username:{user}"),
+            AuthId::Token(identity) => write!(f, "token:{identity}"),
+            AuthId::CertName(name) => write!(f, "cert:{name}"),
+        }
+    }
+}
+
 pub(crate) mod id {
     #[cfg(feature = "auth_pubkey")]
     pub(crate) const PUBKEY: u8 = 0x1;
     #[cfg(feature = "auth_usrpwd")]
     pub(crate) const USRPWD: u8 = 0x2;
+    #[cfg(feature = "auth_token")]
+    pub(crate) const TOKEN: u8 = 0x3;
 }
 
 #[derive(Debug, Default)]
@@ -54,6 +83,8 @@ pub struct Auth {
     pubkey: Option<RwLock<AuthPubKey>>,
     #[cfg(feature = "auth_usrpwd")]
     usrpwd: Option<RwLock<AuthUsrPwd>>,
+    #[cfg(feature = "auth_token")]
+    token: Option<RwLock<AuthToken>>,
 }
 
 impl Auth {
@@ -69,9 +100,55 @@ impl Auth {
             usrpwd: AuthUsrPwd::from_config(auth.usrpwd())
                 .await?
                 .map(RwLock::new),
+            #[cfg(feature = "auth_token")]
+            token: AuthToken::from_config(auth.token()).await?.map(RwLock::new),
         })
     }
 
+    /// Reloads the secrets of the authenticators from the configuration and the files it refers to
+    /// (credentials dictionary, known public keys, token validation keys), without affecting the transports
+    /// already established. Authenticators can't be enabled or disabled this way: nothing is reloaded if
+    /// the configuration would do so.
+    pub(crate) async fn reload(&self, config: &Config) -> ZResult<()> {
+        #[allow(unused)]
+        let auth = Self::from_config(config).await?;
+
+        #[allow(unused_mut)]
+        let mut toggled: Vec<&str> = vec![];
+        #[cfg(feature = "auth_pubkey")]
+        if self.pubkey.is_some() != auth.pubkey.is_some() {
+            toggled.push("pubkey");
+        }
+        #[cfg(feature = "auth_usrpwd")]
+        if self.usrpwd.is_some() != auth.usrpwd.is_some() {
+            toggled.push("usrpwd");
+        }
+        #[cfg(feature = "auth_token")]
+        if self.token.is_some() != auth.token.is_some() {
+            toggled.push("token");
+        }
+        if !toggled.is_empty() {
+            bail!(
+                "Authenticators can't be enabled or disabled at runtime: {}",
+                toggled.join(", ")
+            );
+        }
+
+        #[cfg(feature = "auth_pubkey")]
+        if let (Some(current), Some(new)) = (&self.pubkey, auth.pubkey) {
+            *zasyncwrite!(current) = new.into_inner();
+        }
+        #[cfg(feature = "auth_usrpwd")]
+        if let (Some(current), Some(new)) = (&self.usrpwd, auth.usrpwd) {
+            *zasyncwrite!(current) = new.into_inner();
+        }
+        #[cfg(feature = "auth_token")]
+        if let (Some(current), Some(new)) = (&self.token, auth.token) {
+            *zasyncwrite!(current) = new.into_inner();
+        }
+        Ok(())
+    }
+
     pub(crate) fn open<R>(&self, #[allow(unused)] prng: &mut R) -> StateOpen
     where
         R: Rng + CryptoRng,
@@ -84,6 +161,8 @@ impl Auth {
                 .usrpwd
                 .is_some()
                 .then_some(usrpwd::StateOpen::new(prng)),
+            #[cfg(feature = "auth_token")]
+            token: self.token.is_some().then_some(token::StateOpen::new()),
         }
     }
 
@@ -99,6 +178,8 @@ impl Auth {
                 .usrpwd
                 .is_some()
                 .then_some(usrpwd::StateAccept::new(prng)),
+            #[cfg(feature = "auth_token")]
+            token: self.token.is_some().then_some(token::StateAccept::new()),
         }
     }
 
@@ -108,6 +189,8 @@ impl Auth {
             pubkey: self.pubkey.as_ref().map(|x| AuthPubKeyFsm::new(x, prng)),
             #[cfg(feature = "auth_usrpwd")]
             usrpwd: self.usrpwd.as_ref().map(AuthUsrPwdFsm::new),
+            #[cfg(feature = "auth_token")]
+            token: self.token.as_ref().map(AuthTokenFsm::new),
             _a: PhantomData,
         }
     }
@@ -121,6 +204,8 @@ impl Auth {
             pubkey: None,
             #[cfg(feature = "auth_usrpwd")]
             usrpwd: None,
+            #[cfg(feature = "auth_token")]
+            token: None,
         }
     }
 
@@ -143,6 +228,16 @@ impl Auth {
     pub fn get_usrpwd(&self) -> Option<&RwLock<AuthUsrPwd>> {
         self.usrpwd.as_ref()
     }
+
+    #[cfg(feature = "auth_token")]
+    pub fn set_token(&mut self, token: Option<AuthToken>) {
+        self.token = token.map(RwLock::new);
+    }
+
+    #[cfg(feature = "auth_token")]
+    pub fn get_token(&self) -> Option<&RwLock<AuthToken>> {
+        self.token.as_ref()
+    }
 }
 
 pub(crate) struct AuthFsm<'a> {
@@ -150,6 +245,8 @@ pub(crate) struct AuthFsm<'a> {
     pubkey: Option<AuthPubKeyFsm<'a>>,
     #[cfg(feature = "auth_usrpwd")]
     usrpwd: Option<AuthUsrPwdFsm<'a>>,
+    #[cfg(feature = "auth_token")]
+    token: Option<AuthTokenFsm<'a>>,
     _a: PhantomData<&'a ()>, // Required only when all auth features are disabled
 }
 
@@ -159,6 +256,8 @@ pub(crate) struct StateOpen {
     pubkey: Option<pubkey::StateOpen>,
     #[cfg(feature = "auth_usrpwd")]
     usrpwd: Option<usrpwd::StateOpen>,
+    #[cfg(feature = "auth_token")]
+    token: Option<token::StateOpen>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -167,9 +266,26 @@ pub(crate) struct StateAccept {
     pubkey: Option<pubkey::StateAccept>,
     #[cfg(feature = "auth_usrpwd")]
     usrpwd: Option<usrpwd::StateAccept>,
+    #[cfg(feature = "auth_token")]
+    token: Option<token::StateAccept>,
 }
 
 impl StateAccept {
+    /// Returns the identities the remote peer proved during the handshake.
+    pub(crate) fn auth_ids(&self) -> Vec<AuthId> {
+        #[allow(unused_mut)]
+        let mut ids = vec![];
+        #[cfg(feature = "auth_usrpwd")]
+        if let Some(user) = self.usrpwd.as_ref().and_then(|s| s.user()) {
+            ids.push(AuthId::Username(String::from_utf8_lossy(user).into_owned()));
+        }
+        #[cfg(feature = "auth_token")]
+        if let Some(identity) = self.token.as_ref().and_then(|s| s.identity()) {
+            ids.push(AuthId::Token(identity.to_string()));
+        }
+        ids
+    }
+
     #[cfg(test)]
     pub(crate) fn rand() -> Self {
         let mut rng = rand::thread_rng();
@@ -178,6 +294,8 @@ impl StateAccept {
             pubkey: rng.gen_bool(0.5).then_some(pubkey::StateAccept::rand()),
             #[cfg(feature = "auth_usrpwd")]
             usrpwd: rng.gen_bool(0.5).then_some(usrpwd::StateAccept::rand()),
+            #[cfg(feature = "auth_token")]
+            token: rng.gen_bool(0.5).then_some(token::StateAccept::rand()),
         }
     }
 }
@@ -212,6 +330,15 @@ where
             }
         }
 
+        #[cfg(feature = "auth_token")]
+        {
+            if let Some(token) = x.token.as_ref() {
+                self.write(&mut wbuf, id::TOKEN)?;
+                self.write(&mut wbuf, token)?;
+                count += 1;
+            }
+        }
+
         self.write(&mut *writer, count)?;
         if !buff.is_empty() {
             let mut rbuf = buff.reader();
@@ -235,6 +362,8 @@ where
         let mut pubkey: Option<pubkey::StateAccept> = None;
         #[cfg(feature = "auth_usrpwd")]
         let mut usrpwd: Option<usrpwd::StateAccept> = None;
+        #[cfg(feature = "auth_token")]
+        let mut token: Option<token::StateAccept> = None;
 
         while count > 0 {
             let e: u8 = self.read(&mut *reader)?;
@@ -247,6 +376,10 @@ where
                 id::USRPWD => {
                     usrpwd = Some(self.read(&mut *reader)?);
                 }
+                #[cfg(feature = "auth_token")]
+                id::TOKEN => {
+                    token = Some(self.read(&mut *reader)?);
+                }
                 _ => return Err(DidntRead),
             }
 
@@ -258,6 +391,8 @@ where
             pubkey,
             #[cfg(feature = "auth_usrpwd")]
             usrpwd,
+            #[cfg(feature = "auth_token")]
+            token,
         };
         Ok(state)
     }
@@ -327,6 +462,19 @@ impl<'a> OpenFsm for AuthFsm<'a> {
             }
         }
 
+        #[cfg(feature = "auth_token")]
+        {
+            match (self.token.as_ref(), state.token.as_ref()) {
+                (Some(e), Some(s)) => {
+                    if let Some(e) = e.send_init_syn(s).await?.take() {
+                        exts.push(e.into())
+                    }
+                }
+                (None, None) => {}
+                _ => bail!("{S} Invalid Token configuration."),
+            }
+        }
+
         let codec = Zenoh080::new();
         let mut buff = vec![];
         let mut writer = buff.writer();
@@ -379,6 +527,18 @@ impl<'a> OpenFsm for AuthFsm<'a> {
             }
         }
 
+        #[cfg(feature = "auth_token")]
+        {
+            match (self.token.as_ref(), state.token.as_mut()) {
+                (Some(e), Some(s)) => {
+                    let x = ztake!(exts, id::TOKEN);
+                    e.recv_init_ack((s, ztryinto!(x, S))).await?;
+                }
+                (None, None) => {}
+                _ => bail!("{S} Invalid Token configuration."),
+            }
+        }
+
         Ok(())
     }
 
@@ -418,6 +578,19 @@ impl<'a> OpenFsm for AuthFsm<'a> {
             }
         }
 
+        #[cfg(feature = "auth_token")]
+        {
+            match (self.token.as_ref(), state.token.as_ref()) {
+                (Some(e), Some(s)) => {
+                    if let Some(e) = e.send_open_syn(s).await?.take() {
+                        exts.push(e.into())
+                    }
+                }
+                (None, None) => {}
+                _ => bail!("{S} Invalid Token configuration."),
+            }
+        }
+
         let codec = Zenoh080::new();
         let mut buff = vec![];
         let mut writer = buff.writer();
@@ -470,6 +643,18 @@ impl<'a> OpenFsm for AuthFsm<'a> {
             }
         }
 
+        #[cfg(feature = "auth_token")]
+        {
+            match (self.token.as_ref(), state.token.as_mut()) {
+                (Some(e), Some(s)) => {
+                    let x = ztake!(exts, id::TOKEN);
+                    e.recv_open_ack((s, ztryinto!(x, S))).await?;
+                }
+                (None, None) => {}
+                _ => bail!("{S} Invalid Token configuration."),
+            }
+        }
+
         Ok(())
     }
 }
@@ -522,6 +707,18 @@ impl<'a> AcceptFsm for AuthFsm<'a> {
             }
         }
 
+        #[cfg(feature = "auth_token")]
+        {
+            match (self.token.as_ref(), state.token.as_mut()) {
+                (Some(e), Some(s)) => {
+                    let x = ztake!(exts, id::TOKEN);
+                    e.recv_init_syn((s, ztryinto!(x, S))).await?;
+                }
+                (None, None) => {}
+                _ => bail!("{S} Invalid Token configuration."),
+            }
+        }
+
         Ok(())
     }
 
@@ -561,6 +758,19 @@ impl<'a> AcceptFsm for AuthFsm<'a> {
             }
         }
 
+        #[cfg(feature = "auth_token")]
+        {
+            match (self.token.as_ref(), state.token.as_ref()) {
+                (Some(e), Some(s)) => {
+                    if let Some(e) = e.send_init_ack(s).await?.take() {
+                        exts.push(e.into())
+                    }
+                }
+                (None, None) => {}
+                _ => bail!("{S} Invalid Token configuration."),
+            }
+        }
+
         let codec = Zenoh080::new();
         let mut buff = vec![];
         let mut writer = buff.writer();
@@ -613,6 +823,18 @@ impl<'a> AcceptFsm for AuthFsm<'a> {
             }
         }
 
+        #[cfg(feature = "auth_token")]
+        {
+            match (self.token.as_ref(), state.token.as_mut()) {
+                (Some(e), Some(s)) => {
+                    let x = ztake!(exts, id::TOKEN);
+                    e.recv_open_syn((s, ztryinto!(x, S))).await?;
+                }
+                (None, None) => {}
+                _ => bail!("{S} Invalid Token configuration."),
+            }
+        }
+
         Ok(())
     }
 
@@ -652,6 +874,19 @@ impl<'a> AcceptFsm for AuthFsm<'a> {
             }
         }
 
+        #[cfg(feature = "auth_token")]
+        {
+            match (self.token.as_ref(), state.token.as_ref()) {
+                (Some(e), Some(s)) => {
+                    if let Some(e) = e.send_open_ack(s).await?.take() {
+                        exts.push(e.into())
+                    }
+                }
+                (None, None) => {}
+                _ => bail!("{S} Invalid Token configuration."),
+            }
+        }
+
         let codec = Zenoh080::new();
         let mut buff = vec![];
         let mut writer = buff.writer();
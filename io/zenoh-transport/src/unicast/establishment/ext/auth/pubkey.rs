@@ -12,7 +12,10 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::unicast::establishment::{ext::auth::id, AcceptFsm, OpenFsm};
-use async_std::sync::{Mutex, RwLock};
+use async_std::{
+    fs,
+    sync::{Mutex, RwLock},
+};
 use async_trait::async_trait;
 use rand::Rng;
 use rsa::{
@@ -79,6 +82,61 @@ impl AuthPubKey {
     pub async fn from_config(config: &PubKeyConf) -> ZResult<Option<Self>> {
         const S: &str = "PubKey extension - From config.";
 
+        let mut auth = match Self::keys_from_config(config)? {
+            Some((pub_key, pri_key)) => Self::new(pub_key, pri_key),
+            None => {
+                if config.known_keys_file().is_some() {
+                    bail!("{S} Missing Rsa Public and Private Keys.")
+                }
+                return Ok(None);
+            }
+        };
+
+        if let Some(known_keys) = config.known_keys_file() {
+            let content = fs::read_to_string(known_keys)
+                .await
+                .map_err(|e| zerror!("{S} Invalid known keys file: {}.", e))?;
+
+            // Populate the lookup of the trusted public keys
+            // The known keys file is expected to contain a sequence of PKCS#1 PEM public keys:
+            //      -----BEGIN RSA PUBLIC KEY-----
+            //      ...
+            //      -----END RSA PUBLIC KEY-----
+            for pem in Self::pem_blocks(&content)? {
+                let pub_key = RsaPublicKey::from_pkcs1_pem(pem)
+                    .map_err(|e| zerror!("{} Invalid known keys file: {}.", S, e))?;
+                auth.add_pubkey(pub_key.into()).await?;
+            }
+            log::debug!("{S} Known public keys have been configured.");
+        }
+
+        Ok(Some(auth))
+    }
+
+    fn pem_blocks(content: &str) -> ZResult<Vec<&str>> {
+        const S: &str = "PubKey extension - From config.";
+        const BEGIN: &str = "-----BEGIN RSA PUBLIC KEY-----";
+        const END: &str = "-----END RSA PUBLIC KEY-----";
+
+        let mut blocks = vec![];
+        let mut rest = content;
+        while let Some(start) = rest.find(BEGIN) {
+            let end = rest[start..]
+                .find(END)
+                .ok_or_else(|| zerror!("{S} Invalid known keys file: unterminated key."))?;
+            let end = start + end + END.len();
+            blocks.push(&rest[start..end]);
+            rest = &rest[end..];
+        }
+        if blocks.is_empty() {
+            bail!("{S} Invalid known keys file: no public key.")
+        }
+        Ok(blocks)
+    }
+
+    fn keys_from_config(config: &PubKeyConf) -> ZResult<Option<(ZPublicKey, ZPrivateKey)>> {
+        const S: &str = "PubKey extension - From config.";
+
         // First, check if PEM keys are provided
         match (config.public_key_pem(), config.private_key_pem()) {
             (Some(public), Some(private)) => {
@@ -86,7 +144,7 @@ impl AuthPubKey {
                     .map_err(|e| zerror!("{} Rsa Public Key: {}.", S, e))?;
                 let pri_key = RsaPrivateKey::from_pkcs1_pem(private)
                     .map_err(|e| zerror!("{} Rsa Private Key: {}.", S, e))?;
-                return Ok(Some(Self::new(pub_key.into(), pri_key.into())));
+                return Ok(Some((pub_key.into(), pri_key.into())));
             }
             (Some(_), None) => {
                 bail!("{S} Missing Rsa Private Key: PEM.")
@@ -106,7 +164,7 @@ impl AuthPubKey {
                 let path = Path::new(private);
                 let pri_key = RsaPrivateKey::read_pkcs1_pem_file(path)
                     .map_err(|e| zerror!("{} Rsa Private Key: {}.", S, e))?;
-                return Ok(Some(Self::new(pub_key.into(), pri_key.into())));
+                return Ok(Some((pub_key.into(), pri_key.into())));
             }
             (Some(_), None) => {
                 bail!("{S} Missing Rsa Private Key: file.")
@@ -117,8 +175,6 @@ impl AuthPubKey {
             (None, None) => {}
         }
 
-        // @TODO: populate lookup file
-
         Ok(None)
     }
 }
@@ -655,3 +711,91 @@ impl<'a> AcceptFsm for AuthPubKeyFsm<'a> {
         Ok(Some(ZExtUnit::new()))
     }
 }
+
+mod tests {
+    #[test]
+    fn authenticator_pubkey_config() {
+        use zenoh_core::zasync_executor_init;
+
+        async fn inner() {
+            use super::AuthPubKey;
+            use rsa::{
+                pkcs1::{EncodeRsaPrivateKey, EncodeRsaPublicKey, LineEnding},
+                RsaPrivateKey, RsaPublicKey,
+            };
+            use std::{fs::File, io::Write};
+            use zenoh_config::PubKeyConf;
+
+            /* [CONFIG] */
+            let f1 = "zenoh-test-auth-pubkey.txt";
+
+            let mut rng = rand::thread_rng();
+            let pri_key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+            let pub_key = RsaPublicKey::from(&pri_key);
+            let known_key = RsaPublicKey::from(&RsaPrivateKey::new(&mut rng, 512).unwrap());
+            let known_pem = known_key.to_pkcs1_pem(LineEnding::LF).unwrap();
+
+            let mut config = PubKeyConf::default();
+            config
+                .set_public_key_pem(Some(pub_key.to_pkcs1_pem(LineEnding::LF).unwrap()))
+                .unwrap();
+            config
+                .set_private_key_pem(Some(
+                    pri_key.to_pkcs1_pem(LineEnding::LF).unwrap().to_string(),
+                ))
+                .unwrap();
+            // No known keys
+            assert!(AuthPubKey::from_config(&config).await.unwrap().is_some());
+
+            config.set_known_keys_file(Some(f1.to_owned())).unwrap();
+            macro_rules! zconfig {
+                () => {
+                    File::options()
+                        .create(true)
+                        .write(true)
+                        .truncate(true)
+                        .open(f1)
+                        .unwrap()
+                };
+            }
+            // Valid config
+            let mut c = zconfig!();
+            write!(
+                c,
+                "{known_pem}{}",
+                pub_key.to_pkcs1_pem(LineEnding::LF).unwrap()
+            )
+            .unwrap();
+            drop(c);
+            let auth = AuthPubKey::from_config(&config).await.unwrap().unwrap();
+            let lookup = auth.lookup.as_ref().unwrap();
+            assert_eq!(lookup.len(), 2);
+            assert!(lookup.contains(&known_key.into()));
+            // Empty file
+            zconfig!();
+            assert!(AuthPubKey::from_config(&config).await.is_err());
+            // Unterminated key
+            let mut c = zconfig!();
+            write!(c, "{}", &known_pem[..known_pem.len() / 2]).unwrap();
+            drop(c);
+            assert!(AuthPubKey::from_config(&config).await.is_err());
+            // Invalid key
+            let mut c = zconfig!();
+            writeln!(c, "-----BEGIN RSA PUBLIC KEY-----").unwrap();
+            writeln!(c, "-----END RSA PUBLIC KEY-----").unwrap();
+            drop(c);
+            assert!(AuthPubKey::from_config(&config).await.is_err());
+            // Known keys without key pair
+            let mut config = PubKeyConf::default();
+            config.set_known_keys_file(Some(f1.to_owned())).unwrap();
+            assert!(AuthPubKey::from_config(&config).await.is_err());
+
+            let _ = std::fs::remove_file(f1);
+        }
+
+        async_std::task::block_on(async {
+            zasync_executor_init!();
+            inner().await;
+        });
+    }
+}
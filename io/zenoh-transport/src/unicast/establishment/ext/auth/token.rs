@@ -0,0 +1,602 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::unicast::establishment::{ext::auth::id, AcceptFsm, OpenFsm};
+use async_std::{fs, sync::RwLock};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use rsa::{pkcs1::DecodeRsaPublicKey, pkcs8::DecodePublicKey, Pkcs1v15Sign, RsaPublicKey};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zenoh_buffers::{
+    reader::{DidntRead, HasReader, Reader},
+    writer::{DidntWrite, HasWriter, Writer},
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_config::TokenConf;
+use zenoh_core::{bail, zasyncread, zerror, Error as ZError, Result as ZResult};
+use zenoh_protocol::common::{ZExtUnit, ZExtZBuf};
+
+mod ext {
+    use super::{id::TOKEN, ZExtUnit, ZExtZBuf};
+    use zenoh_protocol::{zextunit, zextzbuf};
+
+    pub(super) type InitSyn = zextunit!(TOKEN, false);
+    pub(super) type InitAck = zextunit!(TOKEN, false);
+    pub(super) type OpenSyn = zextzbuf!(TOKEN, false);
+    pub(super) type OpenAck = zextunit!(TOKEN, false);
+}
+
+// Authenticator
+/// The key validating the signature of the presented tokens.
+pub enum TokenKey {
+    /// A secret shared with the issuer of HS256 tokens.
+    Hs256(Vec<u8>),
+    /// The public key of the issuer of RS256 tokens.
+    Rs256(RsaPublicKey),
+}
+
+/// The validation of the tokens presented by the remote nodes.
+pub struct TokenValidator {
+    key: TokenKey,
+    issuer: Option<String>,
+    audience: Option<String>,
+    identity_claim: String,
+}
+
+impl TokenValidator {
+    pub fn new(key: TokenKey) -> Self {
+        Self {
+            key,
+            issuer: None,
+            audience: None,
+            identity_claim: "sub".to_string(),
+        }
+    }
+
+    pub fn issuer(mut self, issuer: String) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    pub fn audience(mut self, audience: String) -> Self {
+        self.audience = Some(audience);
+        self
+    }
+
+    pub fn identity_claim(mut self, identity_claim: String) -> Self {
+        self.identity_claim = identity_claim;
+        self
+    }
+
+    /// Validates the signature, the time window and the claims of a token,
+    /// returning the identity of its bearer.
+    pub(crate) fn validate(&self, token: &str) -> ZResult<String> {
+        const S: &str = "Token extension - Validate.";
+
+        let (signed, signature) = token
+            .rsplit_once('.')
+            .ok_or_else(|| zerror!("{S} Invalid format."))?;
+        let (header, claims) = signed
+            .split_once('.')
+            .ok_or_else(|| zerror!("{S} Invalid format."))?;
+
+        let decode = |part: &str| -> ZResult<Vec<u8>> {
+            URL_SAFE_NO_PAD
+                .decode(part)
+                .map_err(|e| zerror!("{} Invalid encoding: {}.", S, e).into())
+        };
+        let parse = |part: &str| -> ZResult<Value> {
+            serde_json::from_slice(&decode(part)?)
+                .map_err(|e| zerror!("{} Invalid JSON: {}.", S, e).into())
+        };
+
+        // Check the signature with the algorithm matching the configured key
+        let header = parse(header)?;
+        let signature = decode(signature)?;
+        match (&self.key, header.get("alg").and_then(Value::as_str)) {
+            (TokenKey::Hs256(secret), Some("HS256")) => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+                    .map_err(|_| zerror!("{S} Invalid secret."))?;
+                mac.update(signed.as_bytes());
+                mac.verify_slice(&signature)
+                    .map_err(|_| zerror!("{S} Invalid signature."))?;
+            }
+            (TokenKey::Rs256(key), Some("RS256")) => {
+                let hashed = Sha256::digest(signed.as_bytes());
+                key.verify(Pkcs1v15Sign::new::<Sha256>(), &hashed, &signature)
+                    .map_err(|_| zerror!("{S} Invalid signature."))?;
+            }
+            (_, alg) => bail!("{S} Unsupported algorithm: {:?}.", alg),
+        }
+
+        // Check the time window of the token
+        let claims = parse(claims)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| zerror!("{} {}.", S, e))?
+            .as_secs_f64();
+        match claims.get("exp").and_then(Value::as_f64) {
+            Some(exp) if exp > now => {}
+            Some(_) => bail!("{S} Expired token."),
+            None => bail!("{S} Missing expiration."),
+        }
+        if let Some(nbf) = claims.get("nbf").and_then(Value::as_f64) {
+            if nbf > now {
+                bail!("{S} Token not valid yet.");
+            }
+        }
+
+        // Check the claims
+        if let Some(issuer) = self.issuer.as_deref() {
+            if claims.get("iss").and_then(Value::as_str) != Some(issuer) {
+                bail!("{S} Invalid issuer.");
+            }
+        }
+        if let Some(audience) = self.audience.as_deref() {
+            let valid = match claims.get("aud") {
+                Some(Value::String(aud)) => aud == audience,
+                Some(Value::Array(auds)) => auds.iter().any(|aud| aud.as_str() == Some(audience)),
+                _ => false,
+            };
+            if !valid {
+                bail!("{S} Invalid audience.");
+            }
+        }
+
+        claims
+            .get(&self.identity_claim)
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+            .ok_or_else(|| zerror!("{S} Missing claim: {}.", self.identity_claim).into())
+    }
+}
+
+pub struct AuthToken {
+    token: Option<String>,
+    validator: Option<TokenValidator>,
+}
+
+impl AuthToken {
+    pub fn new(token: Option<String>, validator: Option<TokenValidator>) -> Self {
+        Self { token, validator }
+    }
+
+    pub async fn set_token(&mut self, token: Option<String>) -> ZResult<()> {
+        self.token = token;
+        Ok(())
+    }
+
+    pub async fn from_config(config: &TokenConf) -> ZResult<Option<Self>> {
+        const S: &str = "Token extension - From config.";
+
+        let mut token = config.token().as_ref().map(|t| t.trim().to_string());
+        if let Some(file) = config.token_file() {
+            let content = fs::read_to_string(file)
+                .await
+                .map_err(|e| zerror!("{S} Invalid token file: {}.", e))?;
+            token = Some(content.trim().to_string());
+        }
+        if token.is_some() {
+            log::debug!("{S} Token has been configured.");
+        }
+
+        let mut key: Option<TokenKey> = None;
+        if let Some(secret) = config.secret() {
+            key = Some(TokenKey::Hs256(secret.as_bytes().to_owned()));
+        }
+        if let Some(file) = config.public_key_file() {
+            let pem = fs::read_to_string(file)
+                .await
+                .map_err(|e| zerror!("{S} Invalid public key file: {}.", e))?;
+            // Identity providers usually publish their keys as SPKI, accept PKCS#1 as well
+            let pub_key = RsaPublicKey::from_public_key_pem(&pem)
+                .or_else(|_| RsaPublicKey::from_pkcs1_pem(&pem))
+                .map_err(|e| zerror!("{} Invalid public key file: {}.", S, e))?;
+            key = Some(TokenKey::Rs256(pub_key));
+        }
+        let validator = key.map(|key| {
+            let mut validator = TokenValidator::new(key);
+            validator.issuer = config.issuer().clone();
+            validator.audience = config.audience().clone();
+            if let Some(claim) = config.identity_claim() {
+                validator.identity_claim = claim.clone();
+            }
+            log::debug!("{S} Token validation has been configured.");
+            validator
+        });
+
+        if token.is_some() || validator.is_some() {
+            log::debug!("{S} Token authentication is enabled.");
+            Ok(Some(Self { token, validator }))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.token.as_ref() {
+            Some(_) => write!(f, "Token: '***', ")?,
+            None => write!(f, "Token: '', ")?,
+        }
+        match self.validator.as_ref().map(|v| &v.key) {
+            Some(TokenKey::Hs256(_)) => write!(f, "Validation: HS256"),
+            Some(TokenKey::Rs256(_)) => write!(f, "Validation: RS256"),
+            None => write!(f, "Validation: none"),
+        }
+    }
+}
+
+// OpenFsm / AcceptFsm
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct StateOpen;
+
+impl StateOpen {
+    pub(crate) const fn new() -> Self {
+        Self
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct StateAccept {
+    // The identity authenticated upon OpenSyn, not part of the cookie
+    identity: Option<String>,
+}
+
+impl StateAccept {
+    pub(crate) const fn new() -> Self {
+        Self { identity: None }
+    }
+
+    pub(crate) fn identity(&self) -> Option<&str> {
+        self.identity.as_deref()
+    }
+
+    #[cfg(all(test, feature = "test"))]
+    pub(crate) fn rand() -> Self {
+        Self::new()
+    }
+}
+
+// Codec
+impl<W> WCodec<&StateAccept, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, _writer: &mut W, _x: &StateAccept) -> Self::Output {
+        Ok(())
+    }
+}
+
+impl<R> RCodec<StateAccept, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, _reader: &mut R) -> Result<StateAccept, Self::Error> {
+        Ok(StateAccept::new())
+    }
+}
+
+pub(crate) struct AuthTokenFsm<'a> {
+    inner: &'a RwLock<AuthToken>,
+}
+
+impl<'a> AuthTokenFsm<'a> {
+    pub(super) const fn new(inner: &'a RwLock<AuthToken>) -> Self {
+        Self { inner }
+    }
+}
+
+/*************************************/
+/*             InitSyn               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// +---------------+
+///
+/// ZExtUnit
+
+/*************************************/
+/*             InitAck               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// +---------------+
+///
+/// ZExtUnit
+
+/*************************************/
+/*             OpenSyn               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// ~     token     ~
+/// +---------------+
+///
+/// ZExtZBuf
+struct OpenSyn {
+    token: Vec<u8>,
+}
+
+impl<W> WCodec<&OpenSyn, &mut W> for Zenoh080
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &OpenSyn) -> Self::Output {
+        self.write(&mut *writer, x.token.as_slice())
+    }
+}
+
+impl<R> RCodec<OpenSyn, &mut R> for Zenoh080
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<OpenSyn, Self::Error> {
+        let token: Vec<u8> = self.read(&mut *reader)?;
+        Ok(OpenSyn { token })
+    }
+}
+
+/*************************************/
+/*             OpenAck               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// +---------------+
+///
+/// ZExtUnit
+
+#[async_trait]
+impl<'a> OpenFsm for AuthTokenFsm<'a> {
+    type Error = ZError;
+
+    type SendInitSynIn = &'a StateOpen;
+    type SendInitSynOut = Option<ext::InitSyn>;
+    async fn send_init_syn(
+        &self,
+        _input: Self::SendInitSynIn,
+    ) -> Result<Self::SendInitSynOut, Self::Error> {
+        let output = zasyncread!(self.inner)
+            .token
+            .is_some()
+            .then_some(ZExtUnit::new());
+        Ok(output)
+    }
+
+    type RecvInitAckIn = (&'a mut StateOpen, Option<ext::InitAck>);
+    type RecvInitAckOut = ();
+    async fn recv_init_ack(
+        &self,
+        _input: Self::RecvInitAckIn,
+    ) -> Result<Self::RecvInitAckOut, Self::Error> {
+        Ok(())
+    }
+
+    type SendOpenSynIn = &'a StateOpen;
+    type SendOpenSynOut = Option<ext::OpenSyn>;
+    async fn send_open_syn(
+        &self,
+        _input: Self::SendOpenSynIn,
+    ) -> Result<Self::SendOpenSynOut, Self::Error> {
+        const S: &str = "Token extension - Send OpenSyn.";
+
+        // If a token is not configured, don't continue the TOKEN authentication
+        let open_syn = match zasyncread!(self.inner).token.as_ref() {
+            Some(token) => OpenSyn {
+                token: token.as_bytes().to_vec(),
+            },
+            None => return Ok(None),
+        };
+
+        let codec = Zenoh080::new();
+        let mut buff = vec![];
+        let mut writer = buff.writer();
+        codec
+            .write(&mut writer, &open_syn)
+            .map_err(|_| zerror!("{S} Encoding error."))?;
+
+        let output = Some(ZExtZBuf::new(buff.into()));
+        Ok(output)
+    }
+
+    type RecvOpenAckIn = (&'a mut StateOpen, Option<ext::OpenAck>);
+    type RecvOpenAckOut = ();
+    async fn recv_open_ack(
+        &self,
+        input: Self::RecvOpenAckIn,
+    ) -> Result<Self::RecvOpenAckOut, Self::Error> {
+        const S: &str = "Token extension - Recv OpenAck.";
+
+        let (_, ext) = input;
+        if zasyncread!(self.inner).token.is_some() && ext.is_none() {
+            bail!("{S} Expected extension.");
+        }
+
+        Ok(())
+    }
+}
+
+/*************************************/
+/*            ACCEPT                 */
+/*************************************/
+#[async_trait]
+impl<'a> AcceptFsm for AuthTokenFsm<'a> {
+    type Error = ZError;
+
+    type RecvInitSynIn = (&'a mut StateAccept, Option<ext::InitSyn>);
+    type RecvInitSynOut = ();
+    async fn recv_init_syn(
+        &self,
+        input: Self::RecvInitSynIn,
+    ) -> Result<Self::RecvInitSynOut, Self::Error> {
+        const S: &str = "Token extension - Recv InitSyn.";
+
+        let (_, ext_token) = input;
+        if zasyncread!(self.inner).validator.is_some() && ext_token.is_none() {
+            bail!("{S} Expected extension.");
+        }
+
+        Ok(())
+    }
+
+    type SendInitAckIn = &'a StateAccept;
+    type SendInitAckOut = Option<ext::InitAck>;
+    async fn send_init_ack(
+        &self,
+        _input: Self::SendInitAckIn,
+    ) -> Result<Self::SendInitAckOut, Self::Error> {
+        Ok(None)
+    }
+
+    type RecvOpenSynIn = (&'a mut StateAccept, Option<ext::OpenSyn>);
+    type RecvOpenSynOut = ();
+    async fn recv_open_syn(
+        &self,
+        input: Self::RecvOpenSynIn,
+    ) -> Result<Self::RecvOpenSynOut, Self::Error> {
+        const S: &str = "Token extension - Recv OpenSyn.";
+
+        // If the validation is not configured, don't continue the TOKEN authentication
+        let r_inner = zasyncread!(self.inner);
+        let validator = match r_inner.validator.as_ref() {
+            Some(validator) => validator,
+            None => return Ok(()),
+        };
+
+        let (state, mut ext_token) = input;
+        let ext_token = ext_token
+            .take()
+            .ok_or_else(|| zerror!("{S} Expected extension."))?;
+
+        let codec = Zenoh080::new();
+        let mut reader = ext_token.value.reader();
+        let open_syn: OpenSyn = codec
+            .read(&mut reader)
+            .map_err(|_| zerror!("{S} Decoding error."))?;
+
+        let token =
+            std::str::from_utf8(&open_syn.token).map_err(|_| zerror!("{S} Decoding error."))?;
+        state.identity = Some(validator.validate(token)?);
+
+        Ok(())
+    }
+
+    type SendOpenAckIn = &'a StateAccept;
+    type SendOpenAckOut = Option<ext::OpenAck>;
+    async fn send_open_ack(
+        &self,
+        _input: Self::SendOpenAckIn,
+    ) -> Result<Self::SendOpenAckOut, Self::Error> {
+        Ok(Some(ZExtUnit::new()))
+    }
+}
+
+mod tests {
+    #[test]
+    fn authenticator_token_validate() {
+        use super::{TokenKey, TokenValidator};
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        fn sign(secret: &[u8], header: &str, claims: &str) -> String {
+            let signed = format!(
+                "{}.{}",
+                URL_SAFE_NO_PAD.encode(header),
+                URL_SAFE_NO_PAD.encode(claims)
+            );
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+            mac.update(signed.as_bytes());
+            let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+            format!("{signed}.{signature}")
+        }
+
+        let secret = b"secret";
+        let header = r#"{"alg":"HS256","typ":"JWT"}"#;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let exp = now + 60;
+
+        let validator = TokenValidator::new(TokenKey::Hs256(secret.to_vec()))
+            .issuer("idp".to_string())
+            .audience("zenoh".to_string());
+
+        // Valid token
+        let claims = format!(r#"{{"sub":"robot-1","iss":"idp","aud":"zenoh","exp":{exp}}}"#);
+        let token = sign(secret, header, &claims);
+        assert_eq!(validator.validate(&token).unwrap(), "robot-1");
+        // Audience in a list
+        let claims =
+            format!(r#"{{"sub":"robot-1","iss":"idp","aud":["other","zenoh"],"exp":{exp}}}"#);
+        assert!(validator.validate(&sign(secret, header, &claims)).is_ok());
+        // Invalid signature
+        assert!(validator
+            .validate(&sign(b"other", header, &claims))
+            .is_err());
+        let mut tampered = token.clone();
+        tampered.pop();
+        assert!(validator.validate(&tampered).is_err());
+        // Unsupported algorithm
+        let none = r#"{"alg":"none"}"#;
+        assert!(validator.validate(&sign(secret, none, &claims)).is_err());
+        // Expired token
+        let claims = format!(
+            r#"{{"sub":"robot-1","iss":"idp","aud":"zenoh","exp":{}}}"#,
+            now - 1
+        );
+        assert!(validator.validate(&sign(secret, header, &claims)).is_err());
+        // Missing expiration
+        let claims = r#"{"sub":"robot-1","iss":"idp","aud":"zenoh"}"#;
+        assert!(validator.validate(&sign(secret, header, claims)).is_err());
+        // Token not valid yet
+        let claims =
+            format!(r#"{{"sub":"robot-1","iss":"idp","aud":"zenoh","exp":{exp},"nbf":{exp}}}"#);
+        assert!(validator.validate(&sign(secret, header, &claims)).is_err());
+        // Invalid issuer
+        let claims = format!(r#"{{"sub":"robot-1","iss":"other","aud":"zenoh","exp":{exp}}}"#);
+        assert!(validator.validate(&sign(secret, header, &claims)).is_err());
+        // Invalid audience
+        let claims = format!(r#"{{"sub":"robot-1","iss":"idp","aud":"other","exp":{exp}}}"#);
+        assert!(validator.validate(&sign(secret, header, &claims)).is_err());
+        // Missing identity
+        let claims = format!(r#"{{"iss":"idp","aud":"zenoh","exp":{exp}}}"#);
+        assert!(validator.validate(&sign(secret, header, &claims)).is_err());
+        // Custom identity claim
+        let validator = validator.identity_claim("fleet".to_string());
+        let claims = format!(r#"{{"fleet":"f-42","iss":"idp","aud":"zenoh","exp":{exp}}}"#);
+        assert_eq!(
+            validator.validate(&sign(secret, header, &claims)).unwrap(),
+            "f-42"
+        );
+        // Invalid format
+        assert!(validator.validate("not-a-token").is_err());
+    }
+}
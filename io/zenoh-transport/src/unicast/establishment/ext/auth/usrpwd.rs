@@ -154,6 +154,8 @@ impl StateOpen {
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) struct StateAccept {
     nonce: u64,
+    // The user authenticated upon OpenSyn, not part of the cookie
+    user: Option<User>,
 }
 
 impl StateAccept {
@@ -161,7 +163,14 @@ impl StateAccept {
     where
         R: Rng + CryptoRng,
     {
-        Self { nonce: prng.gen() }
+        Self {
+            nonce: prng.gen(),
+            user: None,
+        }
+    }
+
+    pub(crate) fn user(&self) -> Option<&[u8]> {
+        self.user.as_deref()
     }
 
     #[cfg(all(test, feature = "test"))]
@@ -191,7 +200,7 @@ where
 
     fn read(self, reader: &mut R) -> Result<StateAccept, Self::Error> {
         let nonce: u64 = self.read(&mut *reader)?;
-        Ok(StateAccept { nonce })
+        Ok(StateAccept { nonce, user: None })
     }
 }
 
@@ -429,6 +438,7 @@ impl<'a> AcceptFsm for AuthUsrPwdFsm<'a> {
         if hmac != open_syn.hmac {
             bail!("{S} Invalid password.");
         }
+        state.user = Some(open_syn.user);
 
         Ok(())
     }
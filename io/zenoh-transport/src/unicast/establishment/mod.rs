@@ -134,6 +134,7 @@ pub(super) async fn close_link(link: &LinkUnicast, reason: Option<u8>) {
 
 pub(super) struct InputFinalize {
     pub(super) transport: TransportUnicast,
+    pub(super) mine_lease: Duration,
     pub(super) other_lease: Duration,
     pub(super) agreed_batch_size: BatchSize,
 }
@@ -147,7 +148,7 @@ pub(super) async fn finalize_transport(
     let transport = input.transport.get_inner()?;
 
     // Start the TX loop
-    let keep_alive = manager.config.unicast.lease / manager.config.unicast.keep_alive as u32;
+    let keep_alive = input.mine_lease / manager.config.unicast.keep_alive as u32;
     transport.start_tx(
         link,
         &manager.tx_executor,
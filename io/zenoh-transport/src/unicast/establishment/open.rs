@@ -14,8 +14,9 @@
 #[cfg(feature = "shared-memory")]
 use crate::unicast::shared_memory_unicast::Challenge;
 use crate::{
-    unicast::establishment::{
-        close_link, compute_sn, ext, finalize_transport, InputFinalize, OpenFsm,
+    unicast::{
+        establishment::{close_link, compute_sn, ext, finalize_transport, InputFinalize, OpenFsm},
+        profile::LinkProfile,
     },
     TransportConfigUnicast, TransportManager, TransportUnicast,
 };
@@ -462,6 +463,7 @@ impl<'a> OpenFsm for OpenLink<'a> {
 pub(crate) async fn open_link(
     link: &LinkUnicast,
     manager: &TransportManager,
+    profile: &LinkProfile,
 ) -> ZResult<TransportUnicast> {
     let fsm = OpenLink {
         link,
@@ -477,8 +479,8 @@ pub(crate) async fn open_link(
 
     let mut state = State {
         zenoh: StateZenoh {
-            batch_size: manager.config.batch_size.min(batch_size::UNICAST),
-            resolution: manager.config.resolution,
+            batch_size: profile.batch_size(manager.config.batch_size.min(batch_size::UNICAST)),
+            resolution: profile.resolution(manager.config.resolution),
         },
         ext_qos: ext::qos::StateOpen::new(manager.config.unicast.is_qos),
         #[cfg(feature = "transport_multilink")]
@@ -521,10 +523,11 @@ pub(crate) async fn open_link(
     let iack_out = step!(fsm.recv_init_ack(&mut state).await);
 
     // Open handshake
+    let mine_lease = profile.lease(manager.config.unicast.lease);
     let osyn_in = SendOpenSynIn {
         mine_zid: manager.config.zid,
         other_zid: iack_out.other_zid,
-        mine_lease: manager.config.unicast.lease,
+        mine_lease,
         other_cookie: iack_out.other_cookie,
         #[cfg(feature = "shared-memory")]
         ext_shm: iack_out.ext_shm,
@@ -545,11 +548,18 @@ pub(crate) async fn open_link(
         #[cfg(feature = "shared-memory")]
         is_shm: state.ext_shm.is_shm(),
         is_lowlatency: state.ext_lowlatency.is_lowlatency(),
+        #[cfg(feature = "transport_auth")]
+        auth_ids: vec![],
     };
 
     let transport = step!(
         manager
-            .init_transport_unicast(config, link.clone(), LinkUnicastDirection::Outbound)
+            .init_transport_unicast(
+                config,
+                link.clone(),
+                LinkUnicastDirection::Outbound,
+                profile
+            )
             .await
     );
 
@@ -562,6 +572,7 @@ pub(crate) async fn open_link(
 
     let output = InputFinalize {
         transport,
+        mine_lease,
         other_lease: oack_out.other_lease,
         agreed_batch_size: state.zenoh.batch_size,
     };
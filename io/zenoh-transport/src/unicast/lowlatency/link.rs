@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::transport::TransportUnicastLowlatency;
+use crate::common::capture::Direction;
 #[cfg(feature = "stats")]
 use crate::stats::TransportStats;
 use crate::TransportExecutor;
@@ -28,7 +29,6 @@ use zenoh_protocol::transport::{
     BatchSize, KeepAlive, TransportBodyLowLatency, TransportMessageLowLatency,
 };
 use zenoh_result::{zerror, ZResult};
-use zenoh_sync::RecyclingObjectPool;
 
 pub(crate) async fn send_with_link(
     link: &LinkUnicast,
@@ -81,6 +81,11 @@ impl TransportUnicastLowlatency {
 
     pub(super) async fn send_async(&self, msg: TransportMessageLowLatency) -> ZResult<()> {
         let guard = zasyncwrite!(self.link);
+        if let (Some(capture), TransportBodyLowLatency::Network(msg)) =
+            (self.manager.state.capture.as_ref(), &msg.body)
+        {
+            capture.record(Direction::Tx, &self.config.zid, &guard, msg);
+        }
         send_with_link(
             &guard,
             msg,
@@ -138,10 +143,9 @@ impl TransportUnicastLowlatency {
             let guard = zasyncread!(c_transport.link);
             let link = guard.clone();
             drop(guard);
-            let rx_buffer_size = c_transport.manager.config.link_rx_buffer_size;
 
             // Start the rx task
-            let res = rx_task(link, c_transport.clone(), lease, batch_size, rx_buffer_size).await;
+            let res = rx_task(link, c_transport.clone(), lease, batch_size).await;
             log::debug!(
                 "[{}] Rx task finished with result {:?}",
                 c_transport.manager.config.zid,
@@ -205,7 +209,6 @@ async fn rx_task_stream(
     transport: TransportUnicastLowlatency,
     lease: Duration,
     rx_batch_size: BatchSize,
-    rx_buffer_size: usize,
 ) -> ZResult<()> {
     async fn read(link: &LinkUnicast, buffer: &mut [u8]) -> ZResult<usize> {
         // 16 bits for reading the batch length
@@ -219,15 +222,16 @@ async fn rx_task_stream(
 
     // The pool of buffers
     let mtu = link.get_mtu().min(rx_batch_size) as usize;
-    let mut n = rx_buffer_size / mtu;
-    if rx_buffer_size % mtu != 0 {
-        n += 1;
-    }
-
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_buffer_pool(mtu);
     loop {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let (mut buffer, _hit) = pool.take();
+        #[cfg(feature = "stats")]
+        if _hit {
+            transport.stats.inc_rx_pool_hits(1);
+        } else {
+            transport.stats.inc_rx_pool_misses(1);
+        }
 
         // Async read from the underlying link
         let bytes = read(&link, &mut buffer)
@@ -248,19 +252,19 @@ async fn rx_task_dgram(
     transport: TransportUnicastLowlatency,
     lease: Duration,
     rx_batch_size: BatchSize,
-    rx_buffer_size: usize,
 ) -> ZResult<()> {
     // The pool of buffers
     let mtu = link.get_mtu().min(rx_batch_size) as usize;
-    let mut n = rx_buffer_size / mtu;
-    if rx_buffer_size % mtu != 0 {
-        n += 1;
-    }
-
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_buffer_pool(mtu);
     loop {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let (mut buffer, _hit) = pool.take();
+        #[cfg(feature = "stats")]
+        if _hit {
+            transport.stats.inc_rx_pool_hits(1);
+        } else {
+            transport.stats.inc_rx_pool_misses(1);
+        }
 
         // Async read from the underlying link
         let bytes =
@@ -282,11 +286,10 @@ async fn rx_task(
     transport: TransportUnicastLowlatency,
     lease: Duration,
     rx_batch_size: u16,
-    rx_buffer_size: usize,
 ) -> ZResult<()> {
     if link.is_streamed() {
-        rx_task_stream(link, transport, lease, rx_batch_size, rx_buffer_size).await
+        rx_task_stream(link, transport, lease, rx_batch_size).await
     } else {
-        rx_task_dgram(link, transport, lease, rx_batch_size, rx_buffer_size).await
+        rx_task_dgram(link, transport, lease, rx_batch_size).await
     }
 }
@@ -12,6 +12,9 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::transport::TransportUnicastLowlatency;
+use crate::common::capture::Direction;
+#[cfg(feature = "fault_injection")]
+use async_std::task;
 use zenoh_buffers::{
     reader::{HasReader, Reader},
     ZSlice,
@@ -26,7 +29,37 @@ use zenoh_result::{zerror, ZResult};
 /*            TRANSPORT RX           */
 /*************************************/
 impl TransportUnicastLowlatency {
-    fn trigger_callback(
+    fn trigger_callback(&self, msg: NetworkMessage, link: &LinkUnicast) -> ZResult<()> {
+        if let Some(capture) = self.manager.state.capture.as_ref() {
+            capture.record(Direction::Rx, &self.config.zid, link, &msg);
+        }
+        #[cfg(feature = "fault_injection")]
+        {
+            let faults = self.manager.state.faults.faults(link);
+            if faults.drop {
+                log::trace!(
+                    "Transport: {}. Fault injection dropped: {}",
+                    self.config.zid,
+                    msg
+                );
+                return Ok(());
+            }
+            if faults.duplicate {
+                self.deliver(msg.clone())?;
+            }
+            if !faults.delay.is_zero() {
+                let transport = self.clone();
+                task::spawn(async move {
+                    task::sleep(faults.delay).await;
+                    let _ = transport.deliver(msg);
+                });
+                return Ok(());
+            }
+        }
+        self.deliver(msg)
+    }
+
+    fn deliver(
         &self,
         #[allow(unused_mut)] // shared-memory feature requires mut
         mut msg: NetworkMessage,
@@ -75,7 +108,7 @@ impl TransportUnicastLowlatency {
                 }
                 zenoh_protocol::transport::TransportBodyLowLatency::KeepAlive(_) => {}
                 zenoh_protocol::transport::TransportBodyLowLatency::Network(msg) => {
-                    let _ = self.trigger_callback(msg);
+                    let _ = self.trigger_callback(msg, link);
                 }
             }
         }
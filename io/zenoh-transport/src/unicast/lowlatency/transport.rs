@@ -26,7 +26,7 @@ use async_std::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard, RwLock
 use async_std::task::JoinHandle;
 use async_trait::async_trait;
 use std::sync::{Arc, RwLock as SyncRwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 #[cfg(feature = "transport_unixpipe")]
 use zenoh_core::zasyncread_upgradable;
 use zenoh_core::{zasynclock, zasyncread, zread, zwrite};
@@ -59,6 +59,8 @@ pub(crate) struct TransportUnicastLowlatency {
     pub(super) callback: Arc<SyncRwLock<Option<Arc<dyn TransportPeerEventHandler>>>>,
     // Mutex for notification
     alive: Arc<AsyncMutex<bool>>,
+    // The instant the transport has been opened
+    opened: Instant,
     // Transport statistics
     #[cfg(feature = "stats")]
     pub(super) stats: Arc<TransportStats>,
@@ -82,6 +84,7 @@ impl TransportUnicastLowlatency {
             link: Arc::new(RwLock::new(link)),
             callback: Arc::new(SyncRwLock::new(None)),
             alive: Arc::new(AsyncMutex::new(false)),
+            opened: Instant::now(),
             #[cfg(feature = "stats")]
             stats,
             handle_keepalive: Arc::new(RwLock::new(None)),
@@ -192,6 +195,10 @@ impl TransportUnicastTrait for TransportUnicastLowlatency {
         &self.config
     }
 
+    fn get_uptime(&self) -> Duration {
+        self.opened.elapsed()
+    }
+
     #[cfg(feature = "stats")]
     fn stats(&self) -> std::sync::Arc<crate::stats::TransportStats> {
         self.stats.clone()
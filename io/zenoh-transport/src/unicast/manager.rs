@@ -20,8 +20,11 @@ use crate::unicast::establishment::ext::multilink::MultiLink;
 use crate::{
     lowlatency::transport::TransportUnicastLowlatency,
     transport_unicast_inner::TransportUnicastTrait,
-    unicast::{TransportConfigUnicast, TransportUnicast},
-    universal::transport::TransportUnicastUniversal,
+    unicast::{
+        profile::{self, LinkProfile},
+        TransportConfigUnicast, TransportUnicast,
+    },
+    universal::{store::Store, transport::TransportUnicastUniversal},
     TransportManager,
 };
 use async_std::{prelude::FutureExt, sync::Mutex, task};
@@ -34,6 +37,7 @@ use zenoh_crypto::PseudoRng;
 use zenoh_link::*;
 use zenoh_protocol::{
     core::{endpoint, ZenohId},
+    network::NetworkMessage,
     transport::close,
 };
 use zenoh_result::{bail, zerror, Error, ZResult};
@@ -365,6 +369,8 @@ impl TransportManager {
                 .config_mut()
                 .extend(endpoint::Parameters::iter(config))?;
         };
+        // Check the link profile before accepting any link
+        LinkProfile::from_endpoint(&endpoint)?;
         manager.new_listener(endpoint).await
     }
 
@@ -388,6 +394,16 @@ impl TransportManager {
         vec
     }
 
+    /// Returns the link profile of the listener which accepted `link`.
+    pub(super) async fn get_listener_profile_unicast(&self, link: &LinkUnicast) -> LinkProfile {
+        self.get_listeners_unicast()
+            .await
+            .iter()
+            .find(|ep| profile::is_listener_of(ep, link.get_src()))
+            .and_then(|ep| LinkProfile::from_endpoint(ep).ok())
+            .unwrap_or_default()
+    }
+
     pub async fn get_locators_unicast(&self) -> Vec<Locator> {
         let mut vec: Vec<Locator> = vec![];
         for p in zasynclock!(self.state.unicast.protocols).values() {
@@ -404,7 +420,12 @@ impl TransportManager {
         config: TransportConfigUnicast,
         link: LinkUnicast,
         direction: LinkUnicastDirection,
+        profile: &LinkProfile,
     ) -> Result<TransportUnicast, (Error, Option<u8>)> {
+        // A transport suspended by the loss of its last link is replaced by the new one,
+        // the messages stored for the peer meanwhile being forwarded on it
+        let stored = self.resume_transport_unicast(&config.zid).await;
+
         let mut guard = zasynclock!(self.state.unicast.transports);
 
         // First verify if the transport already exists
@@ -457,8 +478,17 @@ impl TransportManager {
                             .map(|v| Arc::new(v) as Arc<dyn TransportUnicastTrait>)?
                     } else {
                         log::debug!("Will use Universal transport!");
+                        // Store-and-forward is honored on listeners only
+                        let store = (profile.store_forward > 0
+                            && matches!(direction, LinkUnicastDirection::Inbound))
+                        .then(|| {
+                            Store::new(
+                                profile.store_forward,
+                                profile.lease(self.config.unicast.lease),
+                            )
+                        });
                         let t: Arc<dyn TransportUnicastTrait> =
-                            TransportUnicastUniversal::make(self.clone(), config.clone())
+                            TransportUnicastUniversal::make(self.clone(), config.clone(), store)
                                 .map_err(|e| (e, Some(close::reason::INVALID)))
                                 .map(|v| Arc::new(v) as Arc<dyn TransportUnicastTrait>)?;
                         // Add the link to the transport
@@ -469,6 +499,10 @@ impl TransportManager {
                     }
                 };
 
+                if let Some(msgs) = stored {
+                    a_t.forward(msgs);
+                }
+
                 // Add the transport transport to the list of active transports
                 let transport = TransportUnicast(Arc::downgrade(&a_t));
                 guard.insert(config.zid, a_t);
@@ -509,6 +543,20 @@ impl TransportManager {
         }
     }
 
+    async fn resume_transport_unicast(&self, zid: &ZenohId) -> Option<Vec<NetworkMessage>> {
+        let transport = zasynclock!(self.state.unicast.transports)
+            .get(zid)
+            .cloned()?;
+        let msgs = transport.resume()?;
+        log::debug!(
+            "Replacing the suspended transport with peer {}: {} stored messages to forward",
+            zid,
+            msgs.len()
+        );
+        let _ = transport.close(close::reason::GENERIC).await;
+        Some(msgs)
+    }
+
     pub async fn open_transport_unicast(
         &self,
         mut endpoint: EndPoint,
@@ -535,10 +583,27 @@ impl TransportManager {
                 .extend(endpoint::Parameters::iter(config))?;
         };
 
+        let profile = LinkProfile::from_endpoint(&endpoint)?;
+
         // Create a new link associated by calling the Link Manager
         let link = manager.new_link(endpoint).await?;
         // Open the link
-        super::establishment::open::open_link(&link, self).await
+        super::establishment::open::open_link(&link, self, &profile).await
+    }
+
+    /// Sends a probe towards the endpoint from a local listener of the same protocol, so that
+    /// a transport opened by the remote end with the [`PUNCH`](zenoh_link::config::PUNCH)
+    /// option can traverse the local NAT.
+    pub async fn punch_unicast(&self, mut endpoint: EndPoint) -> ZResult<()> {
+        let manager = self
+            .get_link_manager_unicast(endpoint.protocol().as_str())
+            .await?;
+        if let Some(config) = self.config.endpoints.get(endpoint.protocol().as_str()) {
+            endpoint
+                .config_mut()
+                .extend(endpoint::Parameters::iter(config))?;
+        };
+        manager.punch(&endpoint).await
     }
 
     pub async fn get_transport_unicast(&self, peer: &ZenohId) -> Option<TransportUnicast> {
@@ -607,6 +672,16 @@ impl TransportManager {
     }
 }
 
+#[cfg(feature = "transport_auth")]
+impl TransportManager {
+    /// Reloads the secrets of the authenticators from the given configuration, keeping the established transports.
+    pub async fn reload_auth(&self, config: &Config) -> ZResult<()> {
+        self.state.unicast.authenticator.reload(config).await?;
+        log::info!("Reloaded the authentication secrets");
+        Ok(())
+    }
+}
+
 #[cfg(all(feature = "test", feature = "transport_auth"))]
 impl TransportManager {
     pub fn get_auth_handle_unicast(&self) -> Arc<Auth> {
@@ -14,6 +14,7 @@
 pub mod establishment;
 pub(crate) mod lowlatency;
 pub(crate) mod manager;
+pub mod profile;
 pub(crate) mod transport_unicast_inner;
 pub(crate) mod universal;
 
@@ -26,11 +27,14 @@ pub(crate) mod shared_memory_unicast;
 use self::transport_unicast_inner::TransportUnicastTrait;
 
 use super::{TransportPeer, TransportPeerEventHandler};
+#[cfg(feature = "transport_auth")]
+use establishment::ext::auth::AuthId;
 #[cfg(feature = "transport_multilink")]
 use establishment::ext::auth::ZPublicKey;
 pub use manager::*;
 use std::fmt;
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 use zenoh_core::zcondfeat;
 use zenoh_link::Link;
 use zenoh_protocol::network::NetworkMessage;
@@ -55,6 +59,8 @@ pub(crate) struct TransportConfigUnicast {
     #[cfg(feature = "shared-memory")]
     pub(crate) is_shm: bool,
     pub(crate) is_lowlatency: bool,
+    #[cfg(feature = "transport_auth")]
+    pub(crate) auth_ids: Vec<AuthId>,
 }
 
 /// [`TransportUnicast`] is the transport handler returned
@@ -122,6 +128,21 @@ impl TransportUnicast {
             .collect())
     }
 
+    /// Returns how long ago this transport has been opened.
+    #[inline(always)]
+    pub fn get_uptime(&self) -> ZResult<Duration> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_uptime())
+    }
+
+    /// Returns the identities the remote peer proved when opening this transport,
+    /// empty if it was opened by this node.
+    #[cfg(feature = "transport_auth")]
+    pub fn get_auth_ids(&self) -> ZResult<Vec<AuthId>> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_config().auth_ids.clone())
+    }
+
     #[inline(always)]
     pub fn schedule(&self, message: NetworkMessage) -> ZResult<()> {
         let transport = self.get_inner()?;
@@ -0,0 +1,230 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Link profiles tune the transport for the links of a given endpoint, through the
+//! `profile` key of its configuration and the keys overriding single parameters, e.g.
+//! `serial//dev/ttyUSB0#baudrate=9600;profile=constrained;store_forward=100`.
+use std::str::FromStr;
+use std::time::Duration;
+use zenoh_protocol::core::{Bits, EndPoint, Field, Locator, Resolution};
+use zenoh_protocol::transport::BatchSize;
+use zenoh_result::{bail, zerror, ZResult};
+
+pub mod config {
+    // The name of the profile: default or constrained.
+    pub const PROFILE_RAW: &str = "profile";
+    // The maximum batch size in bytes.
+    pub const BATCH_SIZE_RAW: &str = "batch_size";
+    // The lease in milliseconds.
+    pub const LEASE_RAW: &str = "lease";
+    // Whether the smallest sequence number and request id resolutions are used.
+    pub const COMPACT_RAW: &str = "compact";
+    // The maximum number of messages stored by a listener for a peer which lost its link.
+    pub const STORE_FORWARD_RAW: &str = "store_forward";
+}
+
+pub const PROFILE_DEFAULT: &str = "default";
+pub const PROFILE_CONSTRAINED: &str = "constrained";
+
+/// The transport parameters applied to the links of an endpoint.
+///
+/// The `constrained` profile targets very low MTU and high latency radio links such as
+/// 802.15.4 or LoRa modems: it uses tiny batches, the smallest sequence number
+/// resolution to shrink the frame headers and a long lease to save keep-alives.
+///
+/// Store-and-forward is honored on listeners only: when the last link of a peer is
+/// lost, its transport is kept for a lease during which up to `store_forward` data
+/// messages are stored, and forwarded to the peer if it reconnects meanwhile.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkProfile {
+    pub batch_size: Option<BatchSize>,
+    pub lease: Option<Duration>,
+    pub compact: bool,
+    pub store_forward: usize,
+}
+
+impl LinkProfile {
+    pub fn constrained() -> Self {
+        Self {
+            batch_size: Some(128),
+            lease: Some(Duration::from_secs(120)),
+            compact: true,
+            store_forward: 0,
+        }
+    }
+
+    pub fn from_endpoint(endpoint: &EndPoint) -> ZResult<Self> {
+        fn parse<T: FromStr>(endpoint: &EndPoint, key: &str) -> ZResult<Option<T>> {
+            match endpoint.config().get(key) {
+                Some(value) => value.parse().map(Some).map_err(|_| {
+                    zerror!("Invalid {} in endpoint {}: {}", key, endpoint, value).into()
+                }),
+                None => Ok(None),
+            }
+        }
+
+        let mut profile = match endpoint.config().get(config::PROFILE_RAW) {
+            None | Some(PROFILE_DEFAULT) => Self::default(),
+            Some(PROFILE_CONSTRAINED) => Self::constrained(),
+            Some(p) => bail!("Unknown link profile in endpoint {}: {}", endpoint, p),
+        };
+        if let Some(batch_size) = parse(endpoint, config::BATCH_SIZE_RAW)? {
+            profile.batch_size = Some(batch_size);
+        }
+        if let Some(lease) = parse(endpoint, config::LEASE_RAW)? {
+            profile.lease = Some(Duration::from_millis(lease));
+        }
+        if let Some(compact) = parse(endpoint, config::COMPACT_RAW)? {
+            profile.compact = compact;
+        }
+        if let Some(store_forward) = parse(endpoint, config::STORE_FORWARD_RAW)? {
+            profile.store_forward = store_forward;
+        }
+        Ok(profile)
+    }
+
+    pub(crate) fn batch_size(&self, batch_size: BatchSize) -> BatchSize {
+        self.batch_size.map_or(batch_size, |b| b.min(batch_size))
+    }
+
+    pub(crate) fn resolution(&self, resolution: Resolution) -> Resolution {
+        let mut resolution = resolution;
+        if self.compact {
+            resolution.set(Field::FrameSN, Bits::U8);
+            resolution.set(Field::RequestID, Bits::U8);
+        }
+        resolution
+    }
+
+    pub(crate) fn lease(&self, lease: Duration) -> Duration {
+        self.lease.unwrap_or(lease)
+    }
+}
+
+/// Returns whether the links accepted on `src` have been accepted by the listener of `endpoint`.
+pub(crate) fn is_listener_of(endpoint: &EndPoint, src: &Locator) -> bool {
+    if endpoint.protocol() != src.protocol() {
+        return false;
+    }
+    let (ep_addr, src_addr) = (endpoint.address(), src.address());
+    if ep_addr == src_addr {
+        return true;
+    }
+    // Listeners on an unspecified address accept links on any address of the same port
+    match (
+        ep_addr.as_str().rsplit_once(':'),
+        src_addr.as_str().rsplit_once(':'),
+    ) {
+        (Some((host, port)), Some((_, src_port))) => {
+            matches!(host, "0.0.0.0" | "[::]") && port == src_port
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_from_endpoint() {
+        let ep = EndPoint::from_str("tcp/127.0.0.1:7447").unwrap();
+        assert_eq!(
+            LinkProfile::from_endpoint(&ep).unwrap(),
+            LinkProfile::default()
+        );
+
+        let ep =
+            EndPoint::from_str("serial//dev/ttyUSB0#baudrate=9600;profile=constrained").unwrap();
+        assert_eq!(
+            LinkProfile::from_endpoint(&ep).unwrap(),
+            LinkProfile::constrained()
+        );
+
+        let ep = EndPoint::from_str(
+            "serial//dev/ttyUSB0#profile=constrained;batch_size=64;lease=600000;compact=false;store_forward=10",
+        )
+        .unwrap();
+        assert_eq!(
+            LinkProfile::from_endpoint(&ep).unwrap(),
+            LinkProfile {
+                batch_size: Some(64),
+                lease: Some(Duration::from_secs(600)),
+                compact: false,
+                store_forward: 10,
+            }
+        );
+
+        let ep = EndPoint::from_str("tcp/127.0.0.1:7447#batch_size=512").unwrap();
+        let profile = LinkProfile::from_endpoint(&ep).unwrap();
+        assert_eq!(profile.batch_size(BatchSize::MAX), 512);
+        assert_eq!(profile.batch_size(256), 256);
+        assert_eq!(
+            profile.lease(Duration::from_secs(10)),
+            Duration::from_secs(10)
+        );
+
+        for ep in [
+            "tcp/127.0.0.1:7447#profile=lora",
+            "tcp/127.0.0.1:7447#batch_size=70000",
+            "tcp/127.0.0.1:7447#lease=forever",
+            "tcp/127.0.0.1:7447#compact=1",
+        ] {
+            let ep = EndPoint::from_str(ep).unwrap();
+            assert!(LinkProfile::from_endpoint(&ep).is_err());
+        }
+    }
+
+    #[test]
+    fn profile_resolution() {
+        let resolution = Resolution::default();
+        assert_eq!(LinkProfile::default().resolution(resolution), resolution);
+        let compact = LinkProfile::constrained().resolution(resolution);
+        assert_eq!(compact.get(Field::FrameSN), Bits::U8);
+        assert_eq!(compact.get(Field::RequestID), Bits::U8);
+    }
+
+    #[test]
+    fn profile_listener() {
+        let ep = |s| EndPoint::from_str(s).unwrap();
+        let loc = |s| Locator::from_str(s).unwrap();
+        assert!(is_listener_of(
+            &ep("serial//dev/ttyUSB0#baudrate=9600"),
+            &loc("serial//dev/ttyUSB0")
+        ));
+        assert!(!is_listener_of(
+            &ep("serial//dev/ttyUSB0"),
+            &loc("serial//dev/ttyUSB1")
+        ));
+        assert!(is_listener_of(
+            &ep("tcp/0.0.0.0:7447"),
+            &loc("tcp/192.168.1.2:7447")
+        ));
+        assert!(is_listener_of(
+            &ep("tcp/[::]:7447"),
+            &loc("tcp/[fe80::1]:7447")
+        ));
+        assert!(!is_listener_of(
+            &ep("tcp/0.0.0.0:7447"),
+            &loc("tcp/192.168.1.2:7448")
+        ));
+        assert!(!is_listener_of(
+            &ep("tcp/127.0.0.1:7447"),
+            &loc("tcp/192.168.1.2:7447")
+        ));
+        assert!(!is_listener_of(
+            &ep("tcp/0.0.0.0:7447"),
+            &loc("udp/192.168.1.2:7447")
+        ));
+    }
+}
@@ -44,6 +44,7 @@ pub(crate) trait TransportUnicastTrait: Send + Sync {
     fn is_shm(&self) -> bool;
     fn is_qos(&self) -> bool;
     fn get_config(&self) -> &TransportConfigUnicast;
+    fn get_uptime(&self) -> Duration;
     #[cfg(feature = "stats")]
     fn stats(&self) -> Arc<crate::stats::TransportStats>;
 
@@ -80,6 +81,17 @@ pub(crate) trait TransportUnicastTrait: Send + Sync {
     async fn close_link(&self, link: &LinkUnicast, reason: u8) -> ZResult<()>;
     async fn close(&self, reason: u8) -> ZResult<()>;
 
+    /*************************************/
+    /*         STORE AND FORWARD         */
+    /*************************************/
+    /// Ends the suspension of a transport which lost its last link, returning the messages
+    /// stored for the peer meanwhile.
+    fn resume(&self) -> Option<Vec<NetworkMessage>> {
+        None
+    }
+    /// Forwards the messages stored by a previous transport once this one is started.
+    fn forward(&self, _msgs: Vec<NetworkMessage>) {}
+
     fn add_debug_fields<'a, 'b: 'a, 'c>(
         &self,
         s: &'c mut DebugStruct<'a, 'b>,
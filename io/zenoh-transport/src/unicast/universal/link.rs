@@ -16,6 +16,8 @@ use crate::common::pipeline::{
     TransmissionPipeline, TransmissionPipelineConf, TransmissionPipelineConsumer,
     TransmissionPipelineProducer,
 };
+#[cfg(all(feature = "unstable", feature = "transport_compression"))]
+use crate::common::pool::{BufferPool, PooledBuffer};
 use crate::common::priority::TransportPriorityTx;
 #[cfg(feature = "stats")]
 use crate::common::stats::TransportStats;
@@ -32,7 +34,7 @@ use zenoh_buffers::ZSlice;
 use zenoh_link::{LinkUnicast, LinkUnicastDirection};
 use zenoh_protocol::transport::{BatchSize, KeepAlive, TransportMessage};
 use zenoh_result::{bail, zerror, ZResult};
-use zenoh_sync::{RecyclingObjectPool, Signal};
+use zenoh_sync::Signal;
 
 #[cfg(all(feature = "unstable", feature = "transport_compression"))]
 const HEADER_BYTES_SIZE: usize = 2;
@@ -102,7 +104,11 @@ impl TransportLinkUnicast {
                 is_streamed: self.link.is_streamed(),
                 batch_size: batch_size.min(self.link.get_mtu()),
                 queue_size: self.transport.manager.config.queue_size,
+                inline_threshold: self.transport.manager.config.queue_inline_threshold,
                 backoff: self.transport.manager.config.queue_backoff,
+                max_backoff: self.transport.manager.config.queue_max_backoff,
+                max_delay: self.transport.manager.config.queue_max_delay,
+                adaptive_delay: self.transport.manager.config.queue_adaptive_delay,
             };
 
             #[cfg(all(feature = "unstable", feature = "transport_compression"))]
@@ -130,7 +136,7 @@ impl TransportLinkUnicast {
                     log::debug!("{}", e);
                     // Spawn a task to avoid a deadlock waiting for this same task
                     // to finish in the close() joining its handle
-                    task::spawn(async move { c_transport.del_link(&c_link).await });
+                    task::spawn(async move { c_transport.lose_link(&c_link).await });
                 }
             });
             self.handle_tx = Some(Arc::new(handle));
@@ -149,7 +155,6 @@ impl TransportLinkUnicast {
             let c_link = self.link.clone();
             let c_transport = self.transport.clone();
             let c_signal = self.signal_rx.clone();
-            let c_rx_buffer_size = self.transport.manager.config.link_rx_buffer_size;
 
             let handle = task::spawn(async move {
                 // Start the consume task
@@ -159,7 +164,6 @@ impl TransportLinkUnicast {
                     lease,
                     c_signal.clone(),
                     batch_size,
-                    c_rx_buffer_size,
                 )
                 .await;
                 c_signal.trigger();
@@ -167,7 +171,7 @@ impl TransportLinkUnicast {
                     log::debug!("{}", e);
                     // Spawn a task to avoid a deadlock waiting for this same task
                     // to finish in the close() joining its handle
-                    task::spawn(async move { c_transport.del_link(&c_link).await });
+                    task::spawn(async move { c_transport.lose_link(&c_link).await });
                 }
             });
             self.handle_rx = Some(Arc::new(handle));
@@ -282,7 +286,6 @@ async fn rx_task_stream(
     lease: Duration,
     signal: Signal,
     rx_batch_size: BatchSize,
-    rx_buffer_size: usize,
 ) -> ZResult<()> {
     enum Action {
         Read(usize),
@@ -305,15 +308,16 @@ async fn rx_task_stream(
 
     // The pool of buffers
     let mtu = link.get_mtu().min(rx_batch_size) as usize;
-    let mut n = rx_buffer_size / mtu;
-    if rx_buffer_size % mtu != 0 {
-        n += 1;
-    }
-
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_buffer_pool(mtu);
     while !signal.is_triggered() {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let (mut buffer, _hit) = pool.take();
+        #[cfg(feature = "stats")]
+        if _hit {
+            transport.stats.inc_rx_pool_hits(1);
+        } else {
+            transport.stats.inc_rx_pool_misses(1);
+        }
         // Async read from the underlying link
         let action = read(&link, &mut buffer)
             .race(stop(signal.clone()))
@@ -353,7 +357,6 @@ async fn rx_task_dgram(
     lease: Duration,
     signal: Signal,
     rx_batch_size: BatchSize,
-    rx_buffer_size: usize,
 ) -> ZResult<()> {
     enum Action {
         Read(usize),
@@ -372,15 +375,17 @@ async fn rx_task_dgram(
 
     // The pool of buffers
     let mtu = link.get_mtu().min(rx_batch_size) as usize;
-    let mut n = rx_buffer_size / mtu;
-    if rx_buffer_size % mtu != 0 {
-        n += 1;
-    }
-    let pool = RecyclingObjectPool::new(n, || vec![0_u8; mtu].into_boxed_slice());
+    let pool = transport.manager.rx_buffer_pool(mtu);
 
     while !signal.is_triggered() {
         // Retrieve one buffer
-        let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
+        let (mut buffer, _hit) = pool.take();
+        #[cfg(feature = "stats")]
+        if _hit {
+            transport.stats.inc_rx_pool_hits(1);
+        } else {
+            transport.stats.inc_rx_pool_misses(1);
+        }
         // Async read from the underlying link
         let action = read(&link, &mut buffer)
             .race(stop(signal.clone()))
@@ -425,36 +430,19 @@ async fn rx_task(
     lease: Duration,
     signal: Signal,
     rx_batch_size: u16,
-    rx_buffer_size: usize,
 ) -> ZResult<()> {
     if link.is_streamed() {
-        rx_task_stream(
-            link,
-            transport,
-            lease,
-            signal,
-            rx_batch_size,
-            rx_buffer_size,
-        )
-        .await
+        rx_task_stream(link, transport, lease, signal, rx_batch_size).await
     } else {
-        rx_task_dgram(
-            link,
-            transport,
-            lease,
-            signal,
-            rx_batch_size,
-            rx_buffer_size,
-        )
-        .await
+        rx_task_dgram(link, transport, lease, signal, rx_batch_size).await
     }
 }
 
 #[cfg(all(feature = "unstable", feature = "transport_compression"))]
 /// Decompresses the received contents contained in the buffer.
 fn rx_decompress(
-    buffer: &mut zenoh_sync::RecyclingObject<Box<[u8]>>,
-    pool: &RecyclingObjectPool<Box<[u8]>, impl Fn() -> Box<[u8]>>,
+    buffer: &mut PooledBuffer,
+    pool: &BufferPool,
     read_bytes: usize,
     start_pos: &mut usize,
     end_pos: &mut usize,
@@ -659,7 +647,7 @@ fn tx_compression_test() {
 #[cfg(all(feature = "transport_compression", feature = "unstable"))]
 #[test]
 fn rx_compression_test() {
-    let pool = RecyclingObjectPool::new(2, || vec![0_u8; MAX_BATCH_SIZE].into_boxed_slice());
+    let pool = BufferPool::new(MAX_BATCH_SIZE, 2);
     let mut buffer = pool.try_take().unwrap_or_else(|| pool.alloc());
 
     // Compressed batch
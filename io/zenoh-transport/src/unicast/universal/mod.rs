@@ -15,4 +15,5 @@ pub(crate) mod transport;
 
 mod link;
 mod rx;
+pub(crate) mod store;
 mod tx;
@@ -14,7 +14,7 @@ use crate::transport_unicast_inner::TransportUnicastTrait;
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::transport::TransportUnicastUniversal;
-use crate::common::priority::TransportChannelRx;
+use crate::common::{capture::Direction, priority::TransportChannelRx};
 use async_std::task;
 use std::sync::MutexGuard;
 use zenoh_buffers::{
@@ -35,7 +35,37 @@ use zenoh_result::{bail, zerror, ZResult};
 /*            TRANSPORT RX           */
 /*************************************/
 impl TransportUnicastUniversal {
-    fn trigger_callback(
+    fn trigger_callback(&self, msg: NetworkMessage, link: &LinkUnicast) -> ZResult<()> {
+        if let Some(capture) = self.manager.state.capture.as_ref() {
+            capture.record(Direction::Rx, &self.config.zid, link, &msg);
+        }
+        #[cfg(feature = "fault_injection")]
+        {
+            let faults = self.manager.state.faults.faults(link);
+            if faults.drop {
+                log::trace!(
+                    "Transport: {}. Fault injection dropped: {}",
+                    self.config.zid,
+                    msg
+                );
+                return Ok(());
+            }
+            if faults.duplicate {
+                self.deliver(msg.clone())?;
+            }
+            if !faults.delay.is_zero() {
+                let transport = self.clone();
+                task::spawn(async move {
+                    task::sleep(faults.delay).await;
+                    let _ = transport.deliver(msg);
+                });
+                return Ok(());
+            }
+        }
+        self.deliver(msg)
+    }
+
+    fn deliver(
         &self,
         #[allow(unused_mut)] // shared-memory feature requires mut
         mut msg: NetworkMessage,
@@ -83,7 +113,7 @@ impl TransportUnicastUniversal {
         Ok(())
     }
 
-    fn handle_frame(&self, frame: Frame) -> ZResult<()> {
+    fn handle_frame(&self, frame: Frame, link: &LinkUnicast) -> ZResult<()> {
         let Frame {
             reliability,
             sn,
@@ -112,12 +142,12 @@ impl TransportUnicastUniversal {
         self.verify_sn(sn, &mut guard)?;
 
         for msg in payload.drain(..) {
-            self.trigger_callback(msg)?;
+            self.trigger_callback(msg, link)?;
         }
         Ok(())
     }
 
-    fn handle_fragment(&self, fragment: Fragment) -> ZResult<()> {
+    fn handle_fragment(&self, fragment: Fragment, link: &LinkUnicast) -> ZResult<()> {
         let Fragment {
             reliability,
             more,
@@ -155,7 +185,7 @@ impl TransportUnicastUniversal {
                 .defrag
                 .defragment()
                 .ok_or_else(|| zerror!("Transport: {}. Defragmentation error.", self.config.zid))?;
-            return self.trigger_callback(msg);
+            return self.trigger_callback(msg, link);
         }
 
         Ok(())
@@ -205,8 +235,8 @@ impl TransportUnicastUniversal {
             }
 
             match msg.body {
-                TransportBody::Frame(msg) => self.handle_frame(msg)?,
-                TransportBody::Fragment(fragment) => self.handle_fragment(fragment)?,
+                TransportBody::Frame(msg) => self.handle_frame(msg, link)?,
+                TransportBody::Fragment(fragment) => self.handle_fragment(fragment, link)?,
                 TransportBody::Close(Close { reason, session }) => {
                     self.handle_close(link, reason, session)?
                 }
@@ -0,0 +1,165 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use zenoh_core::zlock;
+use zenoh_protocol::core::EMPTY_EXPR_ID;
+use zenoh_protocol::network::{NetworkBody, NetworkMessage};
+
+/// The data messages stored for a peer which lost its last link, to be forwarded to it
+/// when it reconnects.
+pub(crate) struct Store {
+    capacity: usize,
+    // How long the transport is kept after the loss of its last link
+    pub(super) timeout: Duration,
+    suspended: AtomicBool,
+    messages: Mutex<VecDeque<NetworkMessage>>,
+}
+
+impl Store {
+    pub(crate) fn new(capacity: usize, timeout: Duration) -> Self {
+        Self {
+            capacity,
+            timeout,
+            suspended: AtomicBool::new(false),
+            messages: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub(crate) fn is_suspended(&self) -> bool {
+        self.suspended.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn suspend(&self) {
+        self.suspended.store(true, Ordering::Release);
+    }
+
+    /// Ends the suspension, returning false if it already ended.
+    pub(crate) fn resume(&self) -> bool {
+        self.suspended
+            .compare_exchange(true, false, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Stores a message, dropping the expired ones then the oldest one if full. Only the data
+    /// messages with a self-contained key expression are stored: declarations, queries and replies
+    /// are meaningless after a reconnection.
+    pub(crate) fn push(&self, msg: NetworkMessage) -> bool {
+        match &msg.body {
+            NetworkBody::Push(push) if push.wire_expr.scope == EMPTY_EXPR_ID => {}
+            _ => return false,
+        }
+        let mut guard = zlock!(self.messages);
+        if guard.len() >= self.capacity {
+            guard.retain(|msg| !msg.is_expired());
+        }
+        if guard.len() >= self.capacity {
+            guard.pop_front();
+        }
+        guard.push_back(msg);
+        true
+    }
+
+    pub(crate) fn extend(&self, msgs: Vec<NetworkMessage>) {
+        let mut guard = zlock!(self.messages);
+        guard.extend(msgs);
+        while guard.len() > self.capacity {
+            guard.pop_front();
+        }
+    }
+
+    /// Takes the stored messages, but the expired ones.
+    pub(crate) fn drain(&self) -> Vec<NetworkMessage> {
+        zlock!(self.messages)
+            .drain(..)
+            .filter(|msg| !msg.is_expired())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh_protocol::core::{WireExpr, NTP64};
+    use zenoh_protocol::network::{push, Push};
+    use zenoh_protocol::zenoh::{PushBody, Put};
+
+    fn push(key: &str, scope: u16) -> NetworkMessage {
+        let mut put = Put::rand();
+        put.ext_deadline = None;
+        Push {
+            wire_expr: WireExpr {
+                scope,
+                suffix: key.to_string().into(),
+                mapping: Default::default(),
+            },
+            ext_qos: push::ext::QoSType::default(),
+            ext_tstamp: None,
+            ext_nodeid: push::ext::NodeIdType::default(),
+            payload: PushBody::Put(put),
+        }
+        .into()
+    }
+
+    fn expired(key: &str) -> NetworkMessage {
+        let mut msg = push(key, 0);
+        if let NetworkBody::Push(Push {
+            payload: PushBody::Put(put),
+            ..
+        }) = &mut msg.body
+        {
+            put.ext_deadline = Some(NTP64(0));
+        }
+        msg
+    }
+
+    #[test]
+    fn store() {
+        let store = Store::new(2, Duration::from_secs(1));
+        assert!(!store.is_suspended());
+        assert!(!store.resume());
+        store.suspend();
+        assert!(store.is_suspended());
+
+        assert!(store.push(push("a", 0)));
+        assert!(!store.push(push("b", 1)));
+        assert!(store.push(push("c", 0)));
+        assert!(store.push(push("d", 0)));
+        let keys = |msgs: Vec<NetworkMessage>| {
+            msgs.into_iter()
+                .map(|m| match m.body {
+                    NetworkBody::Push(p) => p.wire_expr.suffix.to_string(),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys(store.drain()), ["c", "d"]);
+
+        store.extend(vec![push("e", 0), push("f", 0), push("g", 0)]);
+        assert_eq!(keys(store.drain()), ["f", "g"]);
+
+        // the expired messages are dropped first, and never forwarded
+        assert!(store.push(expired("h")));
+        assert!(store.push(push("i", 0)));
+        assert!(store.push(push("j", 0)));
+        assert_eq!(keys(store.drain()), ["i", "j"]);
+        assert!(store.push(expired("k")));
+        assert!(store.drain().is_empty());
+
+        assert!(store.resume());
+        assert!(!store.is_suspended());
+    }
+}
@@ -16,13 +16,15 @@ use crate::common::priority::{TransportPriorityRx, TransportPriorityTx};
 use crate::stats::TransportStats;
 use crate::transport_unicast_inner::TransportUnicastTrait;
 use crate::unicast::universal::link::TransportLinkUnicast;
+use crate::unicast::universal::store::Store;
 use crate::TransportConfigUnicast;
 use crate::{TransportExecutor, TransportManager, TransportPeerEventHandler};
 use async_std::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard};
+use async_std::task;
 use async_trait::async_trait;
 use std::fmt::DebugStruct;
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use zenoh_core::{zasynclock, zcondfeat, zread, zwrite};
 use zenoh_link::{Link, LinkUnicast, LinkUnicastDirection};
 use zenoh_protocol::network::NetworkMessage;
@@ -69,6 +71,10 @@ pub(crate) struct TransportUnicastUniversal {
     pub(super) callback: Arc<RwLock<Option<Arc<dyn TransportPeerEventHandler>>>>,
     // Mutex for notification
     pub(super) alive: Arc<AsyncMutex<bool>>,
+    // The instant the transport has been opened
+    opened: Instant,
+    // The messages stored while the transport is suspended, if store-and-forward is enabled
+    pub(super) store: Option<Arc<Store>>,
     // Transport statistics
     #[cfg(feature = "stats")]
     pub(super) stats: Arc<TransportStats>,
@@ -78,6 +84,7 @@ impl TransportUnicastUniversal {
     pub fn make(
         manager: TransportManager,
         config: TransportConfigUnicast,
+        store: Option<Store>,
     ) -> ZResult<TransportUnicastUniversal> {
         let mut priority_tx = vec![];
         let mut priority_rx = vec![];
@@ -113,6 +120,8 @@ impl TransportUnicastUniversal {
             links: Arc::new(RwLock::new(vec![].into_boxed_slice())),
             callback: Arc::new(RwLock::new(None)),
             alive: Arc::new(AsyncMutex::new(false)),
+            opened: Instant::now(),
+            store: store.map(Arc::new),
             #[cfg(feature = "stats")]
             stats,
         };
@@ -133,6 +142,9 @@ impl TransportUnicastUniversal {
         // to avoid concurrent new_transport and closing/closed notifications
         let mut a_guard = self.get_alive().await;
         *a_guard = false;
+        if let Some(store) = self.store.as_ref() {
+            store.resume();
+        }
 
         // Notify the callback that we are going to close the transport
         let callback = zwrite!(self.callback).take();
@@ -206,6 +218,57 @@ impl TransportUnicastUniversal {
         }
     }
 
+    /// Deletes a link lost upon an error. If it was the last link and store-and-forward is
+    /// enabled, the transport is suspended instead of being deleted: the messages for the
+    /// peer are stored until it reconnects or the suspension times out.
+    pub(crate) async fn lose_link(&self, link: &LinkUnicast) -> ZResult<()> {
+        let Some(store) = self.store.as_ref() else {
+            return self.del_link(link).await;
+        };
+
+        let stl = {
+            let mut guard = zwrite!(self.links);
+            match zlinkindex!(guard, link) {
+                Some(index) if guard.len() == 1 => {
+                    let mut links = guard.to_vec();
+                    let stl = links.remove(index);
+                    *guard = links.into_boxed_slice();
+                    Some(stl)
+                }
+                _ => None,
+            }
+        };
+        let Some(stl) = stl else {
+            return self.del_link(link).await;
+        };
+
+        log::debug!(
+            "[{}] Suspending transport with peer {} for {:?}: lost its last link {}",
+            self.manager.config.zid,
+            self.config.zid,
+            store.timeout,
+            link
+        );
+        store.suspend();
+
+        // Notify the callback
+        if let Some(callback) = zread!(self.callback).as_ref() {
+            callback.del_link(Link::from(link));
+        }
+
+        // Delete the transport if the peer does not reconnect in time
+        let c_transport = self.clone();
+        let c_store = store.clone();
+        task::spawn(async move {
+            task::sleep(c_store.timeout).await;
+            if c_store.resume() {
+                let _ = c_transport.delete().await;
+            }
+        });
+
+        stl.close().await
+    }
+
     pub(crate) fn stop_tx(&self, link: &LinkUnicast) -> ZResult<()> {
         let mut guard = zwrite!(self.links);
         match zlinkgetmut!(guard, link) {
@@ -323,6 +386,10 @@ impl TransportUnicastTrait for TransportUnicastUniversal {
         &self.config
     }
 
+    fn get_uptime(&self) -> Duration {
+        self.opened.elapsed()
+    }
+
     #[cfg(feature = "stats")]
     fn stats(&self) -> std::sync::Arc<crate::stats::TransportStats> {
         self.stats.clone()
@@ -403,6 +470,24 @@ impl TransportUnicastTrait for TransportUnicastUniversal {
         self.delete().await
     }
 
+    fn resume(&self) -> Option<Vec<NetworkMessage>> {
+        self.store
+            .as_ref()
+            .filter(|s| s.resume())
+            .map(|s| s.drain())
+    }
+
+    fn forward(&self, msgs: Vec<NetworkMessage>) {
+        match self.store.as_ref() {
+            Some(store) => store.extend(msgs),
+            None => log::debug!(
+                "Dropping {} messages stored for peer {}: store-and-forward is disabled",
+                msgs.len(),
+                self.config.zid
+            ),
+        }
+    }
+
     fn get_links(&self) -> Vec<LinkUnicast> {
         zread!(self.links).iter().map(|l| l.link.clone()).collect()
     }
@@ -429,6 +514,13 @@ impl TransportUnicastTrait for TransportUnicastUniversal {
             Some(l) => {
                 assert!(!self.priority_tx.is_empty());
                 l.start_tx(executor, keep_alive, batch_size, &self.priority_tx);
+                drop(guard);
+                // Forward the messages stored for the peer by a previous transport
+                if let Some(store) = self.store.as_ref() {
+                    for msg in store.drain() {
+                        self.internal_schedule(msg);
+                    }
+                }
                 Ok(())
             }
             None => {
@@ -12,13 +12,17 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::transport::TransportUnicastUniversal;
+use crate::common::capture::Direction;
 use zenoh_core::zread;
 use zenoh_protocol::network::NetworkMessage;
 
 impl TransportUnicastUniversal {
     fn schedule_on_link(&self, msg: NetworkMessage) -> bool {
         macro_rules! zpush {
-            ($guard:expr, $pipeline:expr, $msg:expr) => {
+            ($guard:expr, $pipeline:expr, $link:expr, $msg:expr) => {
+                if let Some(capture) = self.manager.state.capture.as_ref() {
+                    capture.record(Direction::Tx, &self.config.zid, $link, &$msg);
+                }
                 // Drop the guard before the push_zenoh_message since
                 // the link could be congested and this operation could
                 // block for fairly long time
@@ -31,23 +35,33 @@ impl TransportUnicastUniversal {
 
         let guard = zread!(self.links);
         // First try to find the best match between msg and link reliability
-        if let Some(pl) = guard
+        if let Some((pl, link)) = guard
             .iter()
             .filter_map(|tl| {
                 if msg.is_reliable() == tl.link.is_reliable() {
-                    tl.pipeline.as_ref()
+                    tl.pipeline.as_ref().map(|pl| (pl, &tl.link))
                 } else {
                     None
                 }
             })
             .next()
         {
-            zpush!(guard, pl, msg);
+            zpush!(guard, pl, link, msg);
         }
 
         // No best match found, take the first available link
-        if let Some(pl) = guard.iter().filter_map(|tl| tl.pipeline.as_ref()).next() {
-            zpush!(guard, pl, msg);
+        if let Some((pl, link)) = guard
+            .iter()
+            .filter_map(|tl| tl.pipeline.as_ref().map(|pl| (pl, &tl.link)))
+            .next()
+        {
+            zpush!(guard, pl, link, msg);
+        }
+
+        // Store the message while the transport is suspended
+        if let Some(store) = self.store.as_ref().filter(|s| s.is_suspended()) {
+            drop(guard);
+            return store.push(msg);
         }
 
         // No Link found
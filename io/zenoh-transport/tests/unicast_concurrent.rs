@@ -204,6 +204,9 @@ async fn transport_concurrent(endpoint01: Vec<EndPoint>, endpoint02: Vec<EndPoin
                 ext_sinfo: None,
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
+                ext_trace: None,
+                ext_deadline: None,
+                ext_batch: None,
                 ext_unknown: vec![],
             }
             .into(),
@@ -308,6 +311,9 @@ async fn transport_concurrent(endpoint01: Vec<EndPoint>, endpoint02: Vec<EndPoin
                 ext_sinfo: None,
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
+                ext_trace: None,
+                ext_deadline: None,
+                ext_batch: None,
                 ext_unknown: vec![],
             }
             .into(),
@@ -89,6 +89,9 @@ async fn run(endpoint: &EndPoint, channel: Channel, msg_size: usize) {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
         }
         .into(),
@@ -306,6 +306,9 @@ async fn transport_intermittent(endpoint: &EndPoint, lowlatency_transport: bool)
                 ext_sinfo: None,
                 #[cfg(feature = "shared-memory")]
                 ext_shm: None,
+                ext_trace: None,
+                ext_deadline: None,
+                ext_batch: None,
                 ext_unknown: vec![],
             }
             .into(),
@@ -305,6 +305,9 @@ async fn single_run(router_handler: Arc<SHRouter>, client_transport: TransportUn
                     ext_sinfo: None,
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
+                    ext_trace: None,
+                    ext_deadline: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                 }
                 .into(),
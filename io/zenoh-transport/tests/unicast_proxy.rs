@@ -0,0 +1,187 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "transport_tcp")]
+mod tests {
+    use async_std::{
+        io,
+        net::{TcpListener, TcpStream},
+        prelude::*,
+        task,
+    };
+    use std::{convert::TryFrom, sync::Arc, time::Duration};
+    use zenoh_core::zasync_executor_init;
+    use zenoh_link::{config::PROXY, EndPoint};
+    use zenoh_protocol::core::{WhatAmI, ZenohId};
+    use zenoh_result::ZResult;
+    use zenoh_transport::{
+        DummyTransportPeerEventHandler, TransportEventHandler, TransportManager,
+        TransportMulticast, TransportMulticastEventHandler, TransportPeer,
+        TransportPeerEventHandler, TransportUnicast,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    #[derive(Default)]
+    struct SHPeer;
+
+    impl TransportEventHandler for SHPeer {
+        fn new_unicast(
+            &self,
+            _peer: TransportPeer,
+            _transport: TransportUnicast,
+        ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+            Ok(Arc::new(DummyTransportPeerEventHandler))
+        }
+
+        fn new_multicast(
+            &self,
+            _transport: TransportMulticast,
+        ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+            panic!();
+        }
+    }
+
+    fn make_manager(id: u8, whatami: WhatAmI) -> TransportManager {
+        TransportManager::builder()
+            .whatami(whatami)
+            .zid(ZenohId::try_from([id]).unwrap())
+            .build(Arc::new(SHPeer))
+            .unwrap()
+    }
+
+    async fn pipe(client: TcpStream, target: &str) {
+        let server = TcpStream::connect(target).await.unwrap();
+        let (mut cr, mut cw) = (client.clone(), client);
+        let (mut sr, mut sw) = (server.clone(), server);
+        let _ = io::copy(&mut cr, &mut sw)
+            .race(io::copy(&mut sr, &mut cw))
+            .await;
+    }
+
+    // A SOCKS5 proxy without authentication serving a single connection
+    async fn socks5_proxy(listener: TcpListener) {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 3];
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x05, 0x01, 0x00]);
+        client.write_all(&[0x05, 0x00]).await.unwrap();
+
+        let mut buf = [0u8; 4];
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, [0x05, 0x01, 0x00, 0x01]);
+        let mut addr = [0u8; 6];
+        client.read_exact(&mut addr).await.unwrap();
+        let target = format!(
+            "{}.{}.{}.{}:{}",
+            addr[0],
+            addr[1],
+            addr[2],
+            addr[3],
+            u16::from_be_bytes([addr[4], addr[5]])
+        );
+        client
+            .write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+        pipe(client, &target).await;
+    }
+
+    // An HTTP CONNECT proxy serving a single connection
+    async fn http_proxy(listener: TcpListener) {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let mut request = vec![];
+        let mut byte = [0u8; 1];
+        while !request.ends_with(b"\r\n\r\n") {
+            client.read_exact(&mut byte).await.unwrap();
+            request.push(byte[0]);
+        }
+        let request = String::from_utf8(request).unwrap();
+        let target = request
+            .strip_prefix("CONNECT ")
+            .and_then(|r| r.split_whitespace().next())
+            .unwrap()
+            .to_string();
+        client
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .await
+            .unwrap();
+        pipe(client, &target).await;
+    }
+
+    async fn proxy_transport(listener: &EndPoint, proxy: &str) {
+        let router_manager = make_manager(1, WhatAmI::Router);
+        let client_manager = make_manager(2, WhatAmI::Client);
+
+        let locator = ztimeout!(router_manager.add_listener_unicast(listener.clone())).unwrap();
+
+        let mut endpoint: EndPoint = locator.into();
+        endpoint.config_mut().insert(PROXY, proxy).unwrap();
+        let transport = ztimeout!(client_manager.open_transport_unicast(endpoint)).unwrap();
+
+        // The transport is established with the router through the proxy
+        ztimeout!(async {
+            while router_manager
+                .get_transport_unicast(&ZenohId::try_from([2]).unwrap())
+                .await
+                .is_none()
+            {
+                task::sleep(SLEEP).await;
+            }
+        });
+
+        ztimeout!(transport.close()).unwrap();
+        ztimeout!(router_manager.close());
+        ztimeout!(client_manager.close());
+
+        // Wait a little bit
+        task::sleep(SLEEP).await;
+    }
+
+    #[test]
+    fn transport_tcp_socks5_proxy() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        let listener: EndPoint = format!("tcp/127.0.0.1:{}", 19460).parse().unwrap();
+        task::block_on(async {
+            let proxy = TcpListener::bind("127.0.0.1:19461").await.unwrap();
+            task::spawn(socks5_proxy(proxy));
+            proxy_transport(&listener, "socks5://127.0.0.1:19461").await;
+        });
+    }
+
+    #[test]
+    fn transport_tcp_http_proxy() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        let listener: EndPoint = format!("tcp/127.0.0.1:{}", 19462).parse().unwrap();
+        task::block_on(async {
+            let proxy = TcpListener::bind("127.0.0.1:19463").await.unwrap();
+            task::spawn(http_proxy(proxy));
+            proxy_transport(&listener, "http://127.0.0.1:19463").await;
+        });
+    }
+}
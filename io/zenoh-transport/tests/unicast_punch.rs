@@ -0,0 +1,118 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "transport_udp")]
+mod tests {
+    use async_std::{prelude::FutureExt, task};
+    use std::{convert::TryFrom, sync::Arc, time::Duration};
+    use zenoh_core::zasync_executor_init;
+    use zenoh_link::{config::PUNCH, EndPoint};
+    use zenoh_protocol::core::{WhatAmI, ZenohId};
+    use zenoh_result::ZResult;
+    use zenoh_transport::{
+        DummyTransportPeerEventHandler, TransportEventHandler, TransportManager,
+        TransportMulticast, TransportMulticastEventHandler, TransportPeer,
+        TransportPeerEventHandler, TransportUnicast,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    #[derive(Default)]
+    struct SHPeer;
+
+    impl TransportEventHandler for SHPeer {
+        fn new_unicast(
+            &self,
+            _peer: TransportPeer,
+            _transport: TransportUnicast,
+        ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+            Ok(Arc::new(DummyTransportPeerEventHandler))
+        }
+
+        fn new_multicast(
+            &self,
+            _transport: TransportMulticast,
+        ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+            panic!();
+        }
+    }
+
+    fn make_manager(id: u8) -> TransportManager {
+        TransportManager::builder()
+            .whatami(WhatAmI::Peer)
+            .zid(ZenohId::try_from([id]).unwrap())
+            .build(Arc::new(SHPeer))
+            .unwrap()
+    }
+
+    async fn punch_transport(listener01: &EndPoint, listener02: &EndPoint) {
+        let peer01_manager = make_manager(1);
+        let peer02_manager = make_manager(2);
+
+        // Punching requires a listener to punch from
+        let mut endpoint = listener02.clone();
+        endpoint.config_mut().insert(PUNCH, "true").unwrap();
+        let res = ztimeout!(peer01_manager.open_transport_unicast(endpoint.clone()));
+        assert!(res.is_err());
+
+        let locator01 = ztimeout!(peer01_manager.add_listener_unicast(listener01.clone())).unwrap();
+        let locator02 = ztimeout!(peer02_manager.add_listener_unicast(listener02.clone())).unwrap();
+
+        // The responder opens its binding towards the opener
+        ztimeout!(peer02_manager.punch_unicast(locator01.clone().into())).unwrap();
+
+        // The opener opens the transport from its listener
+        let transport = ztimeout!(peer01_manager.open_transport_unicast(endpoint)).unwrap();
+        let links = transport.get_links().unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].src, locator01);
+        assert_eq!(links[0].dst, locator02);
+
+        // The transport is established on the responder side as well
+        ztimeout!(async {
+            while peer02_manager
+                .get_transport_unicast(&ZenohId::try_from([1]).unwrap())
+                .await
+                .is_none()
+            {
+                task::sleep(SLEEP).await;
+            }
+        });
+
+        ztimeout!(transport.close()).unwrap();
+        ztimeout!(peer01_manager.close());
+        ztimeout!(peer02_manager.close());
+
+        // Wait a little bit
+        task::sleep(SLEEP).await;
+    }
+
+    #[test]
+    fn transport_udp_punch() {
+        let _ = env_logger::try_init();
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+
+        let listener01: EndPoint = format!("udp/127.0.0.1:{}", 19450).parse().unwrap();
+        let listener02: EndPoint = format!("udp/127.0.0.1:{}", 19451).parse().unwrap();
+        task::block_on(punch_transport(&listener01, &listener02));
+    }
+}
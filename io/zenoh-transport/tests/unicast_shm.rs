@@ -266,6 +266,9 @@ mod tests {
                     encoding: Encoding::default(),
                     ext_sinfo: None,
                     ext_shm: None,
+                    ext_trace: None,
+                    ext_deadline: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                 }
                 .into(),
@@ -313,6 +316,9 @@ mod tests {
                     encoding: Encoding::default(),
                     ext_sinfo: None,
                     ext_shm: None,
+                    ext_trace: None,
+                    ext_deadline: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                 }
                 .into(),
@@ -86,6 +86,9 @@ mod tests {
                     ext_sinfo: None,
                     #[cfg(feature = "shared-memory")]
                     ext_shm: None,
+                    ext_trace: None,
+                    ext_deadline: None,
+                    ext_batch: None,
                     ext_unknown: vec![],
                 }
                 .into(),
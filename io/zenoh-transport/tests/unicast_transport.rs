@@ -475,6 +475,9 @@ async fn test_transport(
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
         }
         .into(),
@@ -871,6 +874,39 @@ fn transport_unicast_ws_only_with_lowlatency_transport() {
     ));
 }
 
+#[cfg(feature = "transport_mem")]
+#[test]
+fn transport_unicast_mem_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locator
+    let endpoints: Vec<EndPoint> = vec![
+        "mem/transport_unicast_mem_only".parse().unwrap(),
+        "mem/transport_unicast_mem_only2".parse().unwrap(),
+    ];
+    // Define the reliability and congestion control
+    let channel = [
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::Reliable,
+        },
+        Channel {
+            priority: Priority::RealTime,
+            reliability: Reliability::Reliable,
+        },
+    ];
+    // Run
+    task::block_on(run_with_universal_transport(
+        &endpoints,
+        &endpoints,
+        &channel,
+        &MSG_SIZE_ALL,
+    ));
+}
+
 #[cfg(feature = "transport_unixpipe")]
 #[test]
 fn transport_unicast_unixpipe_only() {
@@ -1282,6 +1318,80 @@ fn transport_unicast_tls_only_mutual_success() {
     ));
 }
 
+#[cfg(all(
+    feature = "transport_tls",
+    feature = "transport_auth",
+    target_family = "unix"
+))]
+#[test]
+fn transport_unicast_tls_only_mutual_cert_names() {
+    use zenoh_link::tls::config::*;
+    use zenoh_transport::unicast::establishment::ext::auth::AuthId;
+
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locator
+    let mut client_endpoint: EndPoint = ("tls/localhost:10464").parse().unwrap();
+    client_endpoint
+        .config_mut()
+        .extend(
+            [
+                (TLS_ROOT_CA_CERTIFICATE_RAW, SERVER_CA),
+                (TLS_CLIENT_CERTIFICATE_RAW, CLIENT_CERT),
+                (TLS_CLIENT_PRIVATE_KEY_RAW, CLIENT_KEY),
+                (TLS_CLIENT_AUTH, "true"),
+            ]
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned())),
+        )
+        .unwrap();
+
+    // Define the locator
+    let mut server_endpoint: EndPoint = ("tls/localhost:10464").parse().unwrap();
+    server_endpoint
+        .config_mut()
+        .extend(
+            [
+                (TLS_ROOT_CA_CERTIFICATE_RAW, CLIENT_CA),
+                (TLS_SERVER_CERTIFICATE_RAW, SERVER_CERT),
+                (TLS_SERVER_PRIVATE_KEY_RAW, SERVER_KEY),
+                (TLS_CLIENT_AUTH, "true"),
+            ]
+            .iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned())),
+        )
+        .unwrap();
+
+    task::block_on(async {
+        let client_endpoints = vec![client_endpoint];
+        let server_endpoints = vec![server_endpoint];
+        let (router_manager, _, client_manager, client_transport) =
+            open_transport_unicast(&client_endpoints, &server_endpoints, false).await;
+
+        // The router identifies the client by the names of its certificate
+        let router_transport = router_manager
+            .get_transport_unicast(&ZenohId::try_from([1]).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            router_transport.get_auth_ids().unwrap(),
+            vec![AuthId::CertName("localhost".to_string())]
+        );
+        // The client authenticated nobody
+        assert!(client_transport.get_auth_ids().unwrap().is_empty());
+
+        close_transport(
+            router_manager,
+            client_manager,
+            client_transport,
+            &server_endpoints,
+        )
+        .await;
+    });
+}
+
 // Constants replicating the alert descriptions thrown by the Rustls library.
 // These alert descriptions are internal of the library and cannot be reached from these tests
 // as to do a proper comparison. For the sake of simplicity we verify these constants are contained
@@ -147,7 +147,8 @@ impl Drop for RunningPlugin {
 }
 
 async fn run(runtime: Runtime, selector: KeyExpr<'_>, flag: Arc<AtomicBool>) {
-    env_logger::init();
+    // Plugins may be restarted: the logger may already be initialized
+    let _ = env_logger::try_init();
 
     // create a zenoh Session that shares the same Runtime than zenohd
     let session = zenoh::init(runtime).res().await.unwrap();
@@ -0,0 +1,528 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! A sink backend for the zenoh storage manager: the numeric samples stored in its storages are
+//! converted into time series and pushed to a Prometheus remote-write endpoint.
+//!
+//! ```json5
+//! plugins: {
+//!   storage_manager: {
+//!     volumes: {
+//!       prometheus: {
+//!         url: "http://localhost:9090/api/v1/write",
+//!         // optional: extra HTTP headers, labels of all the time series, batching
+//!         headers: { Authorization: "Bearer ..." },
+//!         labels: { job: "zenoh" },
+//!         batch_size: 500,
+//!         flush_interval: 1000,
+//!       },
+//!     },
+//!     storages: {
+//!       sensors: {
+//!         key_expr: "factory/**",
+//!         strip_prefix: "factory",
+//!         volume: {
+//!           id: "prometheus",
+//!           // optional: the labels captured from the keys, see `MetricMapping`
+//!           metric: "{line}/{sensor}/{__name__}",
+//!           labels: { site: "plant-1" },
+//!         },
+//!       },
+//!     },
+//!   },
+//! },
+//! ```
+//!
+//! The samples are batched until `batch_size` samples are pending or `flush_interval` ms elapsed.
+//! Their payloads must be numbers, and their timestamps are the ones of the time series samples.
+use async_rustls::TlsConnector;
+use async_trait::async_trait;
+use http_types::{StatusCode, Url};
+use remote_write::{Batch, Labels};
+use serde_json::{Map, Value as JsonValue};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh::time::Timestamp;
+use zenoh_backend_traits::config::{StorageConfig, VolumeConfig};
+use zenoh_backend_traits::*;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod remote_write;
+
+pub const PROP_URL: &str = "url";
+pub const PROP_HEADERS: &str = "headers";
+pub const PROP_LABELS: &str = "labels";
+pub const PROP_BATCH_SIZE: &str = "batch_size";
+pub const PROP_FLUSH_INTERVAL: &str = "flush_interval";
+pub const PROP_METRIC: &str = "metric";
+
+const DEFAULT_BATCH_SIZE: u64 = 500;
+const DEFAULT_FLUSH_INTERVAL_MS: u64 = 1000;
+// the number of batches kept while the endpoint is unreachable, before the oldest samples are dropped
+const MAX_PENDING_BATCHES: usize = 10;
+
+const METRIC_NAME_LABEL: &str = "__name__";
+
+#[no_mangle]
+pub fn create_volume(config: VolumeConfig) -> ZResult<Box<dyn Volume>> {
+    Ok(Box::new(PrometheusVolume::new(config)?))
+}
+
+zenoh_backend_traits::declare_backend_compatibility!();
+
+/// The remote-write endpoint of a volume, shared by its storages.
+struct Endpoint {
+    url: Url,
+    tls: TlsConnector,
+    headers: Vec<(String, String)>,
+    batch_size: usize,
+    flush_interval: Duration,
+}
+
+pub struct PrometheusVolume {
+    config: VolumeConfig,
+    endpoint: Arc<Endpoint>,
+    labels: Labels,
+}
+
+impl PrometheusVolume {
+    fn new(config: VolumeConfig) -> ZResult<Self> {
+        let url = match config.rest.get(PROP_URL) {
+            Some(JsonValue::String(url)) => remote_write::parse_url(url)?,
+            _ => bail!(
+                "Volume `{}` requires a `{}` string option: the remote-write endpoint",
+                config.name,
+                PROP_URL
+            ),
+        };
+        let headers = string_map(&config.rest, PROP_HEADERS)?
+            .into_iter()
+            .collect();
+        let labels = labels(&config.rest)?;
+        let batch_size = match unsigned(&config.rest, PROP_BATCH_SIZE, DEFAULT_BATCH_SIZE)? {
+            0 => bail!("Option `{}` must be positive", PROP_BATCH_SIZE),
+            batch_size => batch_size as usize,
+        };
+        let flush_interval = Duration::from_millis(unsigned(
+            &config.rest,
+            PROP_FLUSH_INTERVAL,
+            DEFAULT_FLUSH_INTERVAL_MS,
+        )?);
+        Ok(PrometheusVolume {
+            config,
+            endpoint: Arc::new(Endpoint {
+                url,
+                tls: remote_write::tls_connector(),
+                headers,
+                batch_size,
+                flush_interval,
+            }),
+            labels,
+        })
+    }
+}
+
+#[async_trait]
+impl Volume for PrometheusVolume {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.config.to_json_value()
+    }
+
+    fn get_capability(&self) -> Capability {
+        Capability {
+            persistence: Persistence::Volatile,
+            history: History::Latest,
+            read_cost: 0,
+        }
+    }
+
+    async fn create_storage(&mut self, config: StorageConfig) -> ZResult<Box<dyn Storage>> {
+        log::debug!("Create Prometheus Storage with configuration: {:?}", config);
+        let no_options = Map::new();
+        let options = match &config.volume_cfg {
+            JsonValue::Object(options) => options,
+            _ => &no_options,
+        };
+        let metric = match options.get(PROP_METRIC) {
+            Some(JsonValue::String(template)) => MetricMapping::new(Some(template))?,
+            Some(v) => bail!(
+                "Invalid `{}` option: {} (expecting a string)",
+                PROP_METRIC,
+                v
+            ),
+            None => MetricMapping::new(None)?,
+        };
+        // the labels of the storage take precedence over the ones of the volume
+        let mut labels = self.labels.clone();
+        labels.extend(self::labels(options)?);
+
+        let (tx, rx) = flume::unbounded();
+        async_std::task::spawn(flush(config.name.clone(), rx, self.endpoint.clone()));
+        Ok(Box::new(PrometheusStorage {
+            config,
+            metric,
+            labels,
+            tx,
+        }))
+    }
+
+    fn incoming_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>> {
+        None
+    }
+
+    fn outgoing_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>> {
+        None
+    }
+}
+
+struct PrometheusStorage {
+    config: StorageConfig,
+    metric: MetricMapping,
+    labels: Labels,
+    tx: flume::Sender<(Labels, remote_write::Sample)>,
+}
+
+#[async_trait]
+impl Storage for PrometheusStorage {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.config.to_json_value()
+    }
+
+    async fn put(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        value: Value,
+        timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        log::trace!("put for {:?}", key);
+        let mut labels = self
+            .metric
+            .labels(key.as_ref().map_or("", |key| key.as_str()))?;
+        for (name, value) in &self.labels {
+            labels.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+        let sample = remote_write::Sample {
+            value: parse_value(&value)?,
+            timestamp: timestamp.get_time().to_duration().as_millis() as i64,
+        };
+        self.tx
+            .send((labels, sample))
+            .map_err(|_| zerror!("Storage `{}` is closed", self.config.name))?;
+        Ok(StorageInsertionResult::Inserted)
+    }
+
+    async fn delete(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        _timestamp: Timestamp,
+    ) -> ZResult<StorageInsertionResult> {
+        // remote-write has no way to delete samples: they expire with the retention of Prometheus
+        log::trace!("delete for {:?} ignored", key);
+        Ok(StorageInsertionResult::Deleted)
+    }
+
+    async fn get(
+        &mut self,
+        key: Option<OwnedKeyExpr>,
+        _parameters: &str,
+    ) -> ZResult<Vec<StoredData>> {
+        // this is a sink: the samples are queried from Prometheus
+        log::trace!("get for {:?} ignored", key);
+        Ok(Vec::new())
+    }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Batches the samples received on `rx` and pushes them to `endpoint`, until the storage is dropped.
+async fn flush(
+    storage: String,
+    rx: flume::Receiver<(Labels, remote_write::Sample)>,
+    endpoint: Arc<Endpoint>,
+) {
+    let mut batch = Batch::default();
+    let mut last_flush = Instant::now();
+    let mut closed = false;
+    while !closed {
+        let timeout = endpoint.flush_interval.saturating_sub(last_flush.elapsed());
+        match async_std::future::timeout(timeout, rx.recv_async()).await {
+            Ok(Ok((labels, sample))) => {
+                batch.push(labels, sample);
+                if batch.len() < endpoint.batch_size {
+                    continue;
+                }
+            }
+            Ok(Err(_)) => closed = true,
+            Err(_) => {}
+        }
+        last_flush = Instant::now();
+        if batch.is_empty() {
+            continue;
+        }
+        match remote_write::push(&endpoint.url, &endpoint.tls, &endpoint.headers, &batch).await {
+            Ok(status) if status.is_success() => batch = Batch::default(),
+            Ok(status) if status.is_client_error() && status != StatusCode::TooManyRequests => {
+                log::warn!(
+                    "Storage `{}`: {} rejected {} samples: {}",
+                    storage,
+                    endpoint.url,
+                    batch.len(),
+                    status
+                );
+                batch = Batch::default();
+            }
+            Ok(status) => log::warn!(
+                "Storage `{}`: failed to push {} samples to {}: {}",
+                storage,
+                batch.len(),
+                endpoint.url,
+                status
+            ),
+            Err(e) => log::warn!(
+                "Storage `{}`: failed to push {} samples to {}: {}",
+                storage,
+                batch.len(),
+                endpoint.url,
+                e
+            ),
+        }
+        // the samples which failed to be pushed are retried with the next batch
+        batch.truncate(endpoint.batch_size * MAX_PENDING_BATCHES);
+    }
+    if !batch.is_empty() {
+        log::warn!(
+            "Storage `{}` closed: {} samples were not pushed to {}",
+            storage,
+            batch.len(),
+            endpoint.url
+        );
+    }
+}
+
+/// Parses a numeric payload, whatever its encoding, e.g. `21.5`.
+fn parse_value(value: &Value) -> ZResult<f64> {
+    let payload = value.payload.contiguous();
+    let text = std::str::from_utf8(&payload)
+        .map_err(|_| zerror!("Non-numeric payload can't be stored in Prometheus"))?;
+    text.trim().parse().map_err(|_| {
+        zerror!(
+            "Non-numeric payload `{}` can't be stored in Prometheus",
+            text
+        )
+        .into()
+    })
+}
+
+/// The translation of the keys of a storage into the labels of a time series.
+/// Each `{<label>}` chunk of the template captures a chunk of the keys as a label, `{__name__}`
+/// capturing the metric name; otherwise the metric is named after the other chunks of the keys.
+/// e.g. with `{site}/{sensor}/temperature`, `lab/s1/temperature` is the `temperature` metric,
+/// labelled with `site="lab"` and `sensor="s1"`.
+#[derive(Debug)]
+struct MetricMapping {
+    template: Option<Vec<TemplateChunk>>,
+}
+
+#[derive(Debug)]
+enum TemplateChunk {
+    Literal(String),
+    Label(String),
+}
+
+impl MetricMapping {
+    fn new(template: Option<&str>) -> ZResult<Self> {
+        let Some(template) = template else {
+            return Ok(MetricMapping { template: None });
+        };
+        let mut chunks = Vec::new();
+        for chunk in template.split('/') {
+            match chunk.strip_prefix('{').and_then(|c| c.strip_suffix('}')) {
+                Some(label) if label == METRIC_NAME_LABEL || is_label_name(label) => {
+                    if chunks
+                        .iter()
+                        .any(|c| matches!(c, TemplateChunk::Label(l) if l == label))
+                    {
+                        bail!("Label `{}` is captured twice by `{}`", label, template)
+                    }
+                    chunks.push(TemplateChunk::Label(label.to_string()))
+                }
+                None if !chunk.is_empty() && !chunk.contains(['{', '}', '*', '$']) => {
+                    chunks.push(TemplateChunk::Literal(chunk.to_string()))
+                }
+                _ => bail!(
+                    "Invalid chunk `{}` in metric template `{}`: only literal chunks without wildcards, or `{{<label>}}` chunks are accepted",
+                    chunk,
+                    template
+                ),
+            }
+        }
+        Ok(MetricMapping {
+            template: Some(chunks),
+        })
+    }
+
+    /// The labels of the time series of `key`, including its metric name.
+    fn labels(&self, key: &str) -> ZResult<Labels> {
+        let mut labels = Labels::new();
+        let mut name = Vec::new();
+        match &self.template {
+            None => name.extend(key.split('/')),
+            Some(template) => {
+                let chunks = key.split('/').collect::<Vec<_>>();
+                if chunks.len() != template.len() {
+                    bail!("Key `{}` doesn't match the metric template", key)
+                }
+                for (chunk, part) in chunks.into_iter().zip(template) {
+                    match part {
+                        TemplateChunk::Literal(literal) if literal == chunk => name.push(chunk),
+                        TemplateChunk::Literal(_) => {
+                            bail!("Key `{}` doesn't match the metric template", key)
+                        }
+                        TemplateChunk::Label(label) => {
+                            labels.insert(label.clone(), chunk.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        if !labels.contains_key(METRIC_NAME_LABEL) {
+            labels.insert(METRIC_NAME_LABEL.into(), name.join("_"));
+        }
+        let name = labels.get_mut(METRIC_NAME_LABEL).unwrap();
+        *name = metric_name(name);
+        if name.is_empty() {
+            bail!("No metric name for key `{}`", key)
+        }
+        Ok(labels)
+    }
+}
+
+/// Replaces the characters of `name` which are invalid in a metric name by `_`.
+fn metric_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 1);
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        result.push('_');
+    }
+    result.extend(name.chars().map(|c| match c {
+        'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | ':' => c,
+        _ => '_',
+    }));
+    result
+}
+
+fn is_label_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && !name.starts_with("__")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn unsigned(options: &Map<String, JsonValue>, name: &str, default: u64) -> ZResult<u64> {
+    match options.get(name) {
+        None => Ok(default),
+        Some(v) => v.as_u64().ok_or_else(|| {
+            zerror!(
+                "Invalid `{}` option: {} (expecting an unsigned integer)",
+                name,
+                v
+            )
+            .into()
+        }),
+    }
+}
+
+fn string_map(options: &Map<String, JsonValue>, name: &str) -> ZResult<Labels> {
+    match options.get(name) {
+        None => Ok(Labels::new()),
+        Some(JsonValue::Object(map)) => map
+            .iter()
+            .map(|(k, v)| match v {
+                JsonValue::String(v) => Ok((k.clone(), v.clone())),
+                _ => bail!(
+                    "Invalid `{}.{}` option: {} (expecting a string)",
+                    name,
+                    k,
+                    v
+                ),
+            })
+            .collect(),
+        Some(v) => bail!("Invalid `{}` option: {} (expecting an object)", name, v),
+    }
+}
+
+fn labels(options: &Map<String, JsonValue>) -> ZResult<Labels> {
+    let labels = string_map(options, PROP_LABELS)?;
+    if let Some(name) = labels.keys().find(|name| !is_label_name(name)) {
+        bail!("Invalid label name `{}`", name)
+    }
+    Ok(labels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> Labels {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn metric_mapping() {
+        let mapping = MetricMapping::new(None).unwrap();
+        assert_eq!(
+            mapping.labels("lab/room-1/temperature").unwrap(),
+            labels(&[("__name__", "lab_room_1_temperature")])
+        );
+        assert_eq!(
+            mapping.labels("1wire/temp").unwrap(),
+            labels(&[("__name__", "_1wire_temp")])
+        );
+        assert!(mapping.labels("").is_err());
+
+        let mapping = MetricMapping::new(Some("{site}/{sensor}/temperature")).unwrap();
+        assert_eq!(
+            mapping.labels("lab/s1/temperature").unwrap(),
+            labels(&[
+                ("__name__", "temperature"),
+                ("sensor", "s1"),
+                ("site", "lab")
+            ])
+        );
+        assert!(mapping.labels("lab/s1/humidity").is_err());
+        assert!(mapping.labels("lab/temperature").is_err());
+
+        let mapping = MetricMapping::new(Some("sensors/{id}/{__name__}")).unwrap();
+        assert_eq!(
+            mapping.labels("sensors/42/humidity").unwrap(),
+            labels(&[("__name__", "humidity"), ("id", "42")])
+        );
+
+        assert!(MetricMapping::new(Some("{site}/{site}")).is_err());
+        assert!(MetricMapping::new(Some("{__site}/x")).is_err());
+        assert!(MetricMapping::new(Some("{site}/*")).is_err());
+        assert!(MetricMapping::new(Some("{site}/x{y}")).is_err());
+    }
+
+    #[test]
+    fn numeric_payloads() {
+        assert_eq!(parse_value(&Value::from(21.5)).unwrap(), 21.5);
+        assert_eq!(parse_value(&Value::from(42i64)).unwrap(), 42.0);
+        assert_eq!(parse_value(&Value::from(" -3e2\n")).unwrap(), -300.0);
+        assert!(parse_value(&Value::from("warm")).is_err());
+    }
+}
@@ -0,0 +1,358 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! The Prometheus remote-write protocol: a `WriteRequest` protobuf message, compressed with the
+//! snappy block format and POSTed to the endpoint.
+use async_rustls::TlsConnector;
+use async_std::net::TcpStream;
+use http_types::{Method, Request, StatusCode, Url};
+use rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The labels identifying a time series, `__name__` holding the metric name.
+pub(crate) type Labels = BTreeMap<String, String>;
+
+/// A sample of a time series, its timestamp being in milliseconds since the UNIX epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Sample {
+    pub(crate) value: f64,
+    pub(crate) timestamp: i64,
+}
+
+/// The samples to push, in their order of arrival.
+#[derive(Debug, Default)]
+pub(crate) struct Batch {
+    samples: Vec<(Labels, Sample)>,
+}
+
+impl Batch {
+    pub(crate) fn push(&mut self, labels: Labels, sample: Sample) {
+        self.samples.push((labels, sample));
+    }
+
+    /// Drops the oldest samples until at most `max` samples remain.
+    pub(crate) fn truncate(&mut self, max: usize) {
+        let excess = self.samples.len().saturating_sub(max);
+        self.samples.drain(..excess);
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Encodes this batch as a `WriteRequest` protobuf message.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut series = BTreeMap::<_, Vec<_>>::new();
+        for (labels, sample) in &self.samples {
+            series.entry(labels).or_default().push(sample);
+        }
+        let mut request = Vec::new();
+        for (labels, samples) in series {
+            let mut series = Vec::new();
+            for (name, value) in labels {
+                let mut label = Vec::new();
+                write_bytes(&mut label, 1, name.as_bytes());
+                write_bytes(&mut label, 2, value.as_bytes());
+                write_bytes(&mut series, 1, &label);
+            }
+            for sample in samples {
+                let mut s = Vec::new();
+                write_key(&mut s, 1, 1);
+                s.extend_from_slice(&sample.value.to_le_bytes());
+                write_key(&mut s, 2, 0);
+                write_varint(&mut s, sample.timestamp as u64);
+                write_bytes(&mut series, 2, &s);
+            }
+            write_bytes(&mut request, 1, &series);
+        }
+        request
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_key(buf: &mut Vec<u8>, field: u64, wire_type: u64) {
+    write_varint(buf, field << 3 | wire_type);
+}
+
+fn write_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    write_key(buf, field, 2);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// Compresses `input` with the snappy block format, as required by the remote-write protocol.
+pub(crate) fn snappy_compress(input: &[u8]) -> Vec<u8> {
+    const HASH_BITS: u32 = 14;
+    let hash = |bytes: &[u8]| {
+        let word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        (word.wrapping_mul(0x1e35_a7bd) >> (32 - HASH_BITS)) as usize
+    };
+
+    let mut out = Vec::with_capacity(input.len() / 2 + 16);
+    write_varint(&mut out, input.len() as u64);
+    let mut table = vec![0usize; 1 << HASH_BITS];
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i + 4 <= input.len() {
+        let h = hash(&input[i..]);
+        let candidate = table[h];
+        table[h] = i;
+        if candidate < i
+            && i - candidate <= 0xffff
+            && input[candidate..candidate + 4] == input[i..i + 4]
+        {
+            let mut len = 4;
+            while i + len < input.len() && input[candidate + len] == input[i + len] {
+                len += 1;
+            }
+            write_literal(&mut out, &input[literal_start..i]);
+            write_copy(&mut out, i - candidate, len);
+            i += len;
+            literal_start = i;
+        } else {
+            i += 1;
+        }
+    }
+    write_literal(&mut out, &input[literal_start..]);
+    out
+}
+
+fn write_literal(out: &mut Vec<u8>, literal: &[u8]) {
+    if literal.is_empty() {
+        return;
+    }
+    let n = literal.len() - 1;
+    if n < 60 {
+        out.push((n as u8) << 2);
+    } else {
+        // tags 60 to 63 are followed by 1 to 4 bytes of length
+        let len_bytes = (usize::BITS - n.leading_zeros() + 7) as usize / 8;
+        out.push((59 + len_bytes as u8) << 2);
+        out.extend_from_slice(&(n as u32).to_le_bytes()[..len_bytes]);
+    }
+    out.extend_from_slice(literal);
+}
+
+fn write_copy(out: &mut Vec<u8>, offset: usize, mut len: usize) {
+    // copies with a 2-bytes offset encode lengths from 1 to 64
+    while len > 0 {
+        let chunk = len.min(64);
+        out.push(((chunk - 1) as u8) << 2 | 0b10);
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        len -= chunk;
+    }
+}
+
+/// Returns a TLS connector trusting the webpki root certificates, used for `https` endpoints.
+pub(crate) fn tls_connector() -> TlsConnector {
+    let mut root_cert_store = RootCertStore::empty();
+    root_cert_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            ta.subject,
+            ta.spki,
+            ta.name_constraints,
+        )
+    }));
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_cert_store)
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+/// Checks that `url` can be used as a remote-write endpoint.
+pub(crate) fn parse_url(url: &str) -> ZResult<Url> {
+    let url = Url::parse(url).map_err(|e| zerror!("Invalid remote-write URL {}: {}", url, e))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        bail!(
+            "Invalid remote-write URL {}: scheme must be http or https",
+            url
+        )
+    }
+    if url.host_str().is_none() {
+        bail!("Invalid remote-write URL {}: missing host", url)
+    }
+    Ok(url)
+}
+
+/// POSTs `batch` to the remote-write endpoint at `url`, and returns the response status.
+pub(crate) async fn push(
+    url: &Url,
+    tls: &TlsConnector,
+    headers: &[(String, String)],
+    batch: &Batch,
+) -> ZResult<StatusCode> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| zerror!("Missing host in {}", url))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| zerror!("Missing port in {}", url))?;
+
+    let mut req = Request::new(Method::Post, url.clone());
+    req.insert_header("Content-Encoding", "snappy");
+    req.insert_header("Content-Type", "application/x-protobuf");
+    req.insert_header("User-Agent", concat!("zenoh/", env!("CARGO_PKG_VERSION")));
+    req.insert_header("X-Prometheus-Remote-Write-Version", "0.1.0");
+    for (name, value) in headers {
+        req.insert_header(name.as_str(), value.as_str());
+    }
+    req.set_body(snappy_compress(&batch.encode()));
+
+    let stream = TcpStream::connect((host, port)).await?;
+    let res = if url.scheme() == "https" {
+        let domain = ServerName::try_from(host).map_err(|e| zerror!("{}: {}", host, e))?;
+        let stream = tls.connect(domain, stream).await?;
+        async_h1::connect(stream, req).await
+    } else {
+        async_h1::connect(stream, req).await
+    };
+    res.map(|res| res.status())
+        .map_err(|e| zerror!("{}", e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_varint(input: &[u8], i: &mut usize) -> u64 {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = input[*i];
+            *i += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte < 0x80 {
+                return value;
+            }
+            shift += 7;
+        }
+    }
+
+    fn snappy_decompress(input: &[u8]) -> Vec<u8> {
+        let mut i = 0;
+        let len = read_varint(input, &mut i) as usize;
+        let mut out = Vec::with_capacity(len);
+        while i < input.len() {
+            let tag = input[i];
+            i += 1;
+            match tag & 0b11 {
+                0b00 => {
+                    let mut n = (tag >> 2) as usize;
+                    if n >= 60 {
+                        let len_bytes = n - 59;
+                        let mut bytes = [0; 4];
+                        bytes[..len_bytes].copy_from_slice(&input[i..i + len_bytes]);
+                        n = u32::from_le_bytes(bytes) as usize;
+                        i += len_bytes;
+                    }
+                    out.extend_from_slice(&input[i..i + n + 1]);
+                    i += n + 1;
+                }
+                0b10 => {
+                    let n = (tag >> 2) as usize + 1;
+                    let offset = u16::from_le_bytes([input[i], input[i + 1]]) as usize;
+                    i += 2;
+                    for _ in 0..n {
+                        out.push(out[out.len() - offset]);
+                    }
+                }
+                _ => panic!("unexpected tag {:#x}", tag),
+            }
+        }
+        assert_eq!(out.len(), len);
+        out
+    }
+
+    #[test]
+    fn snappy() {
+        let inputs: [Vec<u8>; 5] = [
+            vec![],
+            b"abc".to_vec(),
+            b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".to_vec(),
+            (0..100_000u32).map(|i| (i * 7919 % 251) as u8).collect(),
+            b"temperature{site=\"lab\"} ".repeat(1000),
+        ];
+        for input in inputs {
+            let compressed = snappy_compress(&input);
+            assert_eq!(snappy_decompress(&compressed), input);
+        }
+        assert!(snappy_compress(&b"0123456789".repeat(100)).len() < 100);
+    }
+
+    #[test]
+    fn write_request() {
+        let labels = Labels::from([
+            ("__name__".to_string(), "t".to_string()),
+            ("id".to_string(), "1".to_string()),
+        ]);
+        let mut batch = Batch::default();
+        batch.push(
+            labels.clone(),
+            Sample {
+                value: 1.0,
+                timestamp: 2,
+            },
+        );
+        batch.push(
+            labels,
+            Sample {
+                value: 3.0,
+                timestamp: 4,
+            },
+        );
+        assert_eq!(batch.len(), 2);
+
+        let label = |name: &[u8], value: &[u8]| {
+            let mut label = vec![0x0a, name.len() as u8];
+            label.extend_from_slice(name);
+            label.extend_from_slice(&[0x12, value.len() as u8]);
+            label.extend_from_slice(value);
+            label
+        };
+        let sample = |value: f64, timestamp: u8| {
+            let mut sample = vec![0x09];
+            sample.extend_from_slice(&value.to_le_bytes());
+            sample.extend_from_slice(&[0x10, timestamp]);
+            sample
+        };
+        let mut series = Vec::new();
+        for field in [label(b"__name__", b"t"), label(b"id", b"1")] {
+            series.extend_from_slice(&[0x0a, field.len() as u8]);
+            series.extend_from_slice(&field);
+        }
+        for field in [sample(1.0, 2), sample(3.0, 4)] {
+            series.extend_from_slice(&[0x12, field.len() as u8]);
+            series.extend_from_slice(&field);
+        }
+        let mut request = vec![0x0a, series.len() as u8];
+        request.extend_from_slice(&series);
+        assert_eq!(batch.encode(), request);
+
+        batch.truncate(1);
+        assert_eq!(batch.len(), 1);
+    }
+}
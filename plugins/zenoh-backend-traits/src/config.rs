@@ -14,9 +14,14 @@
 use derive_more::{AsMut, AsRef};
 use schemars::JsonSchema;
 use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::time::Duration;
-use zenoh::{key_expr::keyexpr, prelude::OwnedKeyExpr, Result as ZResult};
+use zenoh::{
+    key_expr::keyexpr,
+    prelude::{OwnedKeyExpr, Priority},
+    Result as ZResult,
+};
 use zenoh_result::{bail, zerror, Error};
 
 #[derive(JsonSchema, Debug, Clone, AsMut, AsRef)]
@@ -30,6 +35,8 @@ pub struct PluginConfig {
     pub volumes: Vec<VolumeConfig>,
     #[schemars(with = "Map<String, Value>")]
     pub storages: Vec<StorageConfig>,
+    #[schemars(with = "Option<Map<String, Value>>")]
+    pub secrets: HashMap<String, SecretProvider>,
     #[as_ref]
     #[as_mut]
     #[schemars(skip)]
@@ -53,12 +60,267 @@ pub struct StorageConfig {
     pub key_expr: OwnedKeyExpr,
     pub complete: bool,
     pub strip_prefix: Option<OwnedKeyExpr>,
+    // Note: KeyMapping is optional. The keys are translated into native keys for the backend only if it is set
+    pub key_mapping: Option<KeyMapping>,
     pub volume_id: String,
     pub volume_cfg: Value,
     pub garbage_collection_config: GarbageCollectionConfig,
     // Note: ReplicaConfig is optional. Alignment will be performed only if it is a replica
     pub replica_config: Option<ReplicaConfig>,
+    // Note: ReplicationConfig is optional. Persistence is confirmed to the other replicas only if it is set
+    pub replication: Option<ReplicationConfig>,
+    // Note: FederationConfig is optional. Updates are exchanged with the other sites only if it is set
+    pub federation: Option<FederationConfig>,
+    // Whether the storage publishes a notification on <admin_key>/cdc for each sample it persists or deletes
+    pub cdc: bool,
+    // The fields of the JSON payloads the backend maintains secondary indexes on, if it supports them
+    pub index: Vec<IndexPath>,
+    // Note: EventLogConfig is optional. The samples are recorded in an append-only log only if it is set
+    pub event_log: Option<EventLogConfig>,
+    // The priority of the replies to the queries, below the default priority of the publications so that
+    // large replies don't delay the live data on the links they share
+    #[schemars(with = "String")]
+    pub reply_priority: Priority,
+    // Note: AggregateConfig is optional. The storage mirrors a downsampling of another key expression only if it is set
+    pub aggregate: Option<AggregateConfig>,
 }
+// The translation between the zenoh keys of a storage and the native keys of its backend
+// Each `{<name>}` chunk of the `zenoh` template captures a chunk of the keys, which replaces `{<name>}` in the `native` template
+// e.g. with `demo/sensors/{id}/temp` and `sensor-{id}`, `demo/sensors/42/temp` is stored as `sensor-42`
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct KeyMapping {
+    pub zenoh: String,
+    pub native: String,
+    #[schemars(skip)]
+    zenoh_parts: Vec<TemplatePart>,
+    #[schemars(skip)]
+    native_parts: Vec<TemplatePart>,
+}
+
+// A JSON path selecting a field of the JSON payloads of a storage, e.g. `$.vehicle_id` or `$.status.code`
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IndexPath {
+    pub path: String,
+    #[schemars(skip)]
+    fields: Vec<String>,
+}
+
+impl IndexPath {
+    pub fn new(path: &str) -> ZResult<Self> {
+        let fields = match path.strip_prefix("$.") {
+            Some(fields) => fields.split('.').map(str::to_string).collect::<Vec<_>>(),
+            None => bail!(
+                "Invalid index `{}`: a path starting with `$.` is required",
+                path
+            ),
+        };
+        if fields.iter().any(|field| field.is_empty()) {
+            bail!("Invalid index `{}`: empty fields are not accepted", path)
+        }
+        Ok(IndexPath {
+            path: path.to_string(),
+            fields,
+        })
+    }
+
+    /// Selects the field of a JSON payload at this path, if any.
+    pub fn select<'a>(&self, payload: &'a Value) -> Option<&'a Value> {
+        self.fields
+            .iter()
+            .try_fold(payload, |value, field| value.get(field.as_str()))
+    }
+}
+
+impl std::fmt::Display for IndexPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Capture(String),
+}
+
+impl KeyMapping {
+    pub fn new(zenoh: &str, native: &str) -> ZResult<Self> {
+        let mut zenoh_parts = Vec::new();
+        for chunk in zenoh.split('/') {
+            match parse_template(chunk)?.as_slice() {
+                [part @ TemplatePart::Capture(_)] => zenoh_parts.push(part.clone()),
+                [TemplatePart::Literal(chunk)] if !chunk.contains(['*', '$']) => {
+                    zenoh_parts.push(TemplatePart::Literal(chunk.clone()))
+                }
+                _ => bail!(
+                    "Invalid chunk `{}` in key mapping `{}`: only literal chunks without wildcards, or `{{<name>}}` chunks are accepted",
+                    chunk,
+                    zenoh
+                ),
+            }
+        }
+        let native_parts = parse_template(native)?;
+        for parts in native_parts.windows(2) {
+            if let [TemplatePart::Capture(a), TemplatePart::Capture(b)] = parts {
+                bail!(
+                    "Captures `{}` and `{}` of native key mapping `{}` must be separated",
+                    a,
+                    b,
+                    native
+                )
+            }
+        }
+        let captures = |parts: &[TemplatePart]| {
+            let mut captures = parts
+                .iter()
+                .filter_map(|part| match part {
+                    TemplatePart::Capture(name) => Some(name.clone()),
+                    TemplatePart::Literal(_) => None,
+                })
+                .collect::<Vec<_>>();
+            captures.sort();
+            captures
+        };
+        let zenoh_captures = captures(&zenoh_parts);
+        if zenoh_captures.is_empty() || zenoh_captures.windows(2).any(|c| c[0] == c[1]) {
+            bail!("Key mapping `{}` must capture distinct chunks", zenoh)
+        }
+        if zenoh_captures != captures(&native_parts) {
+            bail!(
+                "Native key mapping `{}` must use each capture of `{}` once",
+                native,
+                zenoh
+            )
+        }
+        Ok(KeyMapping {
+            zenoh: zenoh.to_string(),
+            native: native.to_string(),
+            zenoh_parts,
+            native_parts,
+        })
+    }
+
+    /// The key expression matching all the keys translated by this mapping.
+    pub fn key_expr(&self) -> ZResult<OwnedKeyExpr> {
+        let chunks = self
+            .zenoh_parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Literal(chunk) => chunk.as_str(),
+                TemplatePart::Capture(_) => "*",
+            })
+            .collect::<Vec<_>>();
+        OwnedKeyExpr::autocanonize(chunks.join("/"))
+    }
+
+    /// Translates a zenoh key into the native key of the backend.
+    pub fn to_native(&self, key: &keyexpr) -> ZResult<OwnedKeyExpr> {
+        let chunks = key.as_str().split('/').collect::<Vec<_>>();
+        if chunks.len() != self.zenoh_parts.len() {
+            bail!("Key `{}` doesn't match key mapping `{}`", key, self.zenoh)
+        }
+        let mut captures = HashMap::new();
+        for (chunk, part) in chunks.into_iter().zip(&self.zenoh_parts) {
+            match part {
+                TemplatePart::Literal(literal) if literal == chunk => {}
+                TemplatePart::Literal(_) => {
+                    bail!("Key `{}` doesn't match key mapping `{}`", key, self.zenoh)
+                }
+                TemplatePart::Capture(name) => {
+                    captures.insert(name.as_str(), chunk);
+                }
+            }
+        }
+        OwnedKeyExpr::new(fill_template(&self.native_parts, &captures))
+    }
+
+    /// Translates a native key of the backend into the zenoh key.
+    pub fn to_zenoh(&self, native: &keyexpr) -> ZResult<OwnedKeyExpr> {
+        let mismatch = || {
+            zerror!(
+                "Native key `{}` doesn't match key mapping `{}`",
+                native,
+                self.native
+            )
+        };
+        let mut rest = native.as_str();
+        let mut captures = HashMap::new();
+        for (i, part) in self.native_parts.iter().enumerate() {
+            match part {
+                TemplatePart::Literal(literal) => {
+                    rest = rest.strip_prefix(literal.as_str()).ok_or_else(mismatch)?;
+                }
+                TemplatePart::Capture(name) => {
+                    // the captured value extends up to the next literal
+                    let end = match self.native_parts.get(i + 1) {
+                        Some(TemplatePart::Literal(literal)) => {
+                            rest.find(literal.as_str()).ok_or_else(mismatch)?
+                        }
+                        _ => rest.len(),
+                    };
+                    let value = &rest[..end];
+                    if value.is_empty() || value.contains('/') {
+                        return Err(mismatch().into());
+                    }
+                    captures.insert(name.as_str(), value);
+                    rest = &rest[end..];
+                }
+            }
+        }
+        if !rest.is_empty() {
+            return Err(mismatch().into());
+        }
+        let chunks = self
+            .zenoh_parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Literal(chunk) => chunk.as_str(),
+                TemplatePart::Capture(name) => captures[name.as_str()],
+            })
+            .collect::<Vec<_>>();
+        OwnedKeyExpr::new(chunks.join("/"))
+    }
+}
+
+fn parse_template(template: &str) -> ZResult<Vec<TemplatePart>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while !rest.is_empty() {
+        match rest.find('{') {
+            Some(0) => {
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| zerror!("Unclosed capture in key mapping `{}`", template))?;
+                let name = &rest[1..end];
+                if name.is_empty() || name.contains(['{', '/']) {
+                    bail!("Invalid capture `{}` in key mapping `{}`", name, template)
+                }
+                parts.push(TemplatePart::Capture(name.to_string()));
+                rest = &rest[end + 1..];
+            }
+            Some(start) => {
+                parts.push(TemplatePart::Literal(rest[..start].to_string()));
+                rest = &rest[start..];
+            }
+            None => {
+                parts.push(TemplatePart::Literal(rest.to_string()));
+                rest = "";
+            }
+        }
+    }
+    Ok(parts)
+}
+
+fn fill_template(parts: &[TemplatePart], captures: &HashMap<&str, &str>) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            TemplatePart::Literal(literal) => literal.as_str(),
+            TemplatePart::Capture(name) => captures[name.as_str()],
+        })
+        .collect()
+}
+
 // Note: All parameters should be same for replicas, else will result on huge overhead
 #[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
 pub struct ReplicaConfig {
@@ -85,6 +347,284 @@ impl Default for ReplicaConfig {
     }
 }
 
+// The durability requirements of the writes on the storages subscribing to the same key_expr
+// Note: both parameters should be the same for all the replicas
+#[derive(JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplicationConfig {
+    // The number of replicas expected to store the samples
+    pub factor: usize,
+    // The number of replicas which must have persisted a sample for its publication to be acknowledged
+    pub write_quorum: usize,
+}
+
+impl ReplicationConfig {
+    // By default, a majority of the replicas must persist the samples
+    pub fn new(factor: usize) -> Self {
+        Self {
+            factor,
+            write_quorum: factor / 2 + 1,
+        }
+    }
+}
+
+// The federation of the storages subscribing to the same key_expr across sites
+// Each site accepts writes locally and asynchronously propagates them to the other sites
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct FederationConfig {
+    // The name of the site of this storage, unique among the federated sites
+    pub site: String,
+    // The duration between two propagations of the batched local updates to the other sites
+    pub propagation_interval: Duration,
+}
+
+impl FederationConfig {
+    pub fn new(site: String) -> Self {
+        Self {
+            site,
+            propagation_interval: Duration::from_millis(1000),
+        }
+    }
+}
+
+// The append-only log of all the samples received by a storage, which can be read by offsets and replayed
+#[derive(JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventLogConfig {
+    // The default rate of the replays, in samples per second
+    pub replay_rate: u64,
+}
+
+impl Default for EventLogConfig {
+    fn default() -> Self {
+        Self { replay_rate: 100 }
+    }
+}
+
+// The downsampled mirror of another key expression kept by a storage, e.g. for a long-term low-resolution history
+// The non-wild prefix of the `source` keys is replaced by the non-wild prefix of the storage key expression:
+// e.g. with `demo/sensors/**` as source of a storage on `history/sensors/**`, `demo/sensors/a` is stored as `history/sensors/a`
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct AggregateConfig {
+    pub source: OwnedKeyExpr,
+    pub strategy: AggregateStrategy,
+}
+
+// The samples of each key kept by a downsampled storage, the deletions being all kept
+#[derive(JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateStrategy {
+    // One sample in `n`, written `1-in-<n>`
+    OneIn(u64),
+    // One sample per bucket of `width` of the sample timestamps, written `time-bucket: <width> first|latest`
+    TimeBucket { width: Duration, keep: BucketSample },
+}
+
+// The sample kept in each bucket of a `time-bucket` strategy
+#[derive(JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketSample {
+    First,
+    Latest,
+}
+
+// The units of the bucket widths, from the largest
+const DURATION_UNITS: [(&str, u64); 5] = [
+    ("d", 86_400_000),
+    ("h", 3_600_000),
+    ("m", 60_000),
+    ("s", 1000),
+    ("ms", 1),
+];
+
+impl std::str::FromStr for AggregateStrategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> ZResult<Self> {
+        let s = s.trim();
+        if let Some(n) = s.strip_prefix("1-in-") {
+            return match n.parse::<u64>() {
+                Ok(n) if n > 0 => Ok(AggregateStrategy::OneIn(n)),
+                _ => bail!(
+                    "Invalid strategy `{}`: a positive integer is required after `1-in-`",
+                    s
+                ),
+            };
+        }
+        let Some(bucket) = s.strip_prefix("time-bucket:") else {
+            bail!(
+                "Invalid strategy `{}`: only `1-in-<n>` and `time-bucket: <width> first|latest` are accepted",
+                s
+            )
+        };
+        let mut words = bucket.split_whitespace();
+        let width = words.next().unwrap_or_default();
+        let width = DURATION_UNITS
+            .iter()
+            .rev()
+            .find_map(|(unit, millis)| {
+                let n = width.strip_suffix(unit)?.parse::<u64>().ok()?;
+                Some(Duration::from_millis(n.checked_mul(*millis)?))
+            })
+            .filter(|width| !width.is_zero())
+            .ok_or_else(|| {
+                zerror!(
+                    "Invalid strategy `{}`: a positive width in ms, s, m, h or d (e.g. `1m`) is required after `time-bucket:`",
+                    s
+                )
+            })?;
+        let keep = match words.next() {
+            Some("latest") | None => BucketSample::Latest,
+            Some("first") => BucketSample::First,
+            Some(keep) => bail!(
+                "Invalid strategy `{}`: `{}` is neither `first` nor `latest`",
+                s,
+                keep
+            ),
+        };
+        if words.next().is_some() {
+            bail!("Invalid strategy `{}`: unexpected trailing words", s)
+        }
+        Ok(AggregateStrategy::TimeBucket { width, keep })
+    }
+}
+
+impl std::fmt::Display for AggregateStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AggregateStrategy::OneIn(n) => write!(f, "1-in-{n}"),
+            AggregateStrategy::TimeBucket { width, keep } => {
+                let millis = width.as_millis() as u64;
+                let (unit, unit_millis) = DURATION_UNITS
+                    .iter()
+                    .find(|(_, unit_millis)| millis % unit_millis == 0)
+                    .unwrap();
+                let keep = match keep {
+                    BucketSample::First => "first",
+                    BucketSample::Latest => "latest",
+                };
+                write!(f, "time-bucket: {}{} {}", millis / unit_millis, unit, keep)
+            }
+        }
+    }
+}
+
+/// The scheme of the strings referencing a secret by its name in the options of the volumes and storages.
+pub const SECRET_SCHEME: &str = "secret://";
+
+/// The provider of a secret, resolved only when the volume or storage referencing it is created,
+/// so that its value is neither stored in the configuration nor exposed in the adminspace.
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub enum SecretProvider {
+    /// The content of a file, without its trailing newlines.
+    File(String),
+    /// The value of an environment variable.
+    Env(String),
+    /// The standard output of a command and its arguments, without its trailing newlines.
+    Exec(Vec<String>),
+}
+
+impl SecretProvider {
+    pub fn resolve(&self) -> ZResult<String> {
+        let secret = match self {
+            SecretProvider::File(path) => std::fs::read_to_string(path)
+                .map_err(|e| zerror!("Failed to read secret file `{}`: {}", path, e))?,
+            SecretProvider::Env(var) => std::env::var(var)
+                .map_err(|e| zerror!("Failed to read secret variable `{}`: {}", var, e))?,
+            SecretProvider::Exec(command) => {
+                let output = std::process::Command::new(&command[0])
+                    .args(&command[1..])
+                    .output()
+                    .map_err(|e| zerror!("Failed to run secret command `{}`: {}", command[0], e))?;
+                if !output.status.success() {
+                    bail!(
+                        "Secret command `{}` failed with {}",
+                        command[0],
+                        output.status
+                    )
+                }
+                String::from_utf8(output.stdout).map_err(|e| {
+                    zerror!("Secret command `{}` output isn't UTF-8: {}", command[0], e)
+                })?
+            }
+        };
+        Ok(secret.trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    fn try_from(name: &str, config: &Value) -> ZResult<Self> {
+        match config.as_object().map(|o| o.iter().collect::<Vec<_>>()).as_deref() {
+            Some([(kind, Value::String(path))]) if kind.as_str() == "file" => Ok(SecretProvider::File(path.clone())),
+            Some([(kind, Value::String(var))]) if kind.as_str() == "env" => Ok(SecretProvider::Env(var.clone())),
+            Some([(kind, Value::String(program))]) if kind.as_str() == "exec" => Ok(SecretProvider::Exec(vec![program.clone()])),
+            Some([(kind, Value::Array(command))]) if kind.as_str() == "exec" && !command.is_empty() => Ok(SecretProvider::Exec(
+                command
+                    .iter()
+                    .map(|arg| arg.as_str().map(String::from))
+                    .collect::<Option<_>>()
+                    .ok_or_else(|| zerror!("`exec` field of secret `{}` must be a string or a non-empty array of strings", name))?,
+            )),
+            _ => bail!("Secret `{}` must be an object with a single `file`, `env` or `exec` field", name),
+        }
+    }
+}
+
+/// Replaces the references to secrets in `value` by their values, returning the resolved secrets by name.
+pub fn resolve_secrets(
+    value: &mut Value,
+    secrets: &HashMap<String, SecretProvider>,
+) -> ZResult<HashMap<String, String>> {
+    let mut resolved = HashMap::new();
+    resolve_secrets_into(value, secrets, &mut resolved)?;
+    Ok(resolved)
+}
+
+fn resolve_secrets_into(
+    value: &mut Value,
+    secrets: &HashMap<String, SecretProvider>,
+    resolved: &mut HashMap<String, String>,
+) -> ZResult<()> {
+    match value {
+        Value::String(s) => {
+            if let Some(name) = s.strip_prefix(SECRET_SCHEME) {
+                let secret = match resolved.get(name) {
+                    Some(secret) => secret.clone(),
+                    None => {
+                        let provider = secrets
+                            .get(name)
+                            .ok_or_else(|| zerror!("Unknown secret `{}`", name))?;
+                        let secret = provider.resolve()?;
+                        resolved.insert(name.to_string(), secret.clone());
+                        secret
+                    }
+                };
+                *s = secret;
+            }
+        }
+        Value::Array(a) => {
+            for v in a {
+                resolve_secrets_into(v, secrets, resolved)?;
+            }
+        }
+        Value::Object(o) => {
+            for v in o.values_mut() {
+                resolve_secrets_into(v, secrets, resolved)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replaces the values of the `resolved` secrets in `value` by their references.
+pub fn redact_secrets(value: &mut Value, resolved: &HashMap<String, String>) {
+    match value {
+        Value::String(s) => {
+            if let Some((name, _)) = resolved.iter().find(|(_, secret)| *secret == s) {
+                *s = format!("{SECRET_SCHEME}{name}");
+            }
+        }
+        Value::Array(a) => a.iter_mut().for_each(|v| redact_secrets(v, resolved)),
+        Value::Object(o) => o.values_mut().for_each(|v| redact_secrets(v, resolved)),
+        _ => {}
+    }
+}
+
 // The configuration for periodic garbage collection of metadata in storage manager
 #[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
 pub struct GarbageCollectionConfig {
@@ -161,6 +701,23 @@ impl<S: Into<String> + AsRef<str>, V: AsObject> TryFrom<(S, &V)> for PluginConfi
             Some(configs) => VolumeConfig::try_from(name.as_ref(), configs)?,
             None => Vec::new(),
         };
+        let secrets = match value.get("secrets") {
+            Some(serde_json::Value::Object(configs)) => {
+                let mut secrets = HashMap::with_capacity(configs.len());
+                for (secret_name, config) in configs {
+                    secrets.insert(
+                        secret_name.clone(),
+                        SecretProvider::try_from(secret_name, config)?,
+                    );
+                }
+                secrets
+            }
+            None => HashMap::new(),
+            _ => bail!(
+                "`secrets` field of `{}`'s configuration must be an object",
+                name.as_ref()
+            ),
+        };
         let storages = match value.get("storages") {
             Some(serde_json::Value::Object(configs)) => {
                 let mut storages = Vec::with_capacity(configs.len());
@@ -185,11 +742,18 @@ impl<S: Into<String> + AsRef<str>, V: AsObject> TryFrom<(S, &V)> for PluginConfi
             backend_search_dirs,
             volumes,
             storages,
+            secrets,
             rest: value
                 .into_iter()
                 .filter_map(|(k, v)| {
-                    (!["__required__", "backend_search_dirs", "volumes", "storages"]
-                        .contains(&k.as_str()))
+                    (![
+                        "__required__",
+                        "backend_search_dirs",
+                        "volumes",
+                        "storages",
+                        "secrets",
+                    ]
+                    .contains(&k.as_str()))
                     .then(|| (k.clone(), v.clone()))
                 })
                 .collect(),
@@ -240,6 +804,18 @@ impl VolumeConfig {
         }
         Value::Object(result)
     }
+    /// Replaces the references to secrets in the options by their values, returning the resolved secrets by name.
+    pub fn resolve_secrets(
+        &mut self,
+        secrets: &HashMap<String, SecretProvider>,
+    ) -> ZResult<HashMap<String, String>> {
+        let mut rest = Value::Object(std::mem::take(&mut self.rest));
+        let resolved = resolve_secrets(&mut rest, secrets);
+        if let Value::Object(rest) = rest {
+            self.rest = rest;
+        }
+        resolved
+    }
     pub fn backend_search_method(&self) -> BackendSearchMethod {
         match &self.paths {
             None => BackendSearchMethod::ByName(self.backend.as_deref().unwrap_or(&self.name)),
@@ -311,6 +887,18 @@ impl VolumeConfig {
         Ok(volumes)
     }
 }
+// The priorities accepted for `reply_priority`, by name
+const PRIORITIES: [(&str, Priority); 7] = [
+    ("real_time", Priority::RealTime),
+    ("interactive_high", Priority::InteractiveHigh),
+    ("interactive_low", Priority::InteractiveLow),
+    ("data_high", Priority::DataHigh),
+    ("data", Priority::Data),
+    ("data_low", Priority::DataLow),
+    ("background", Priority::Background),
+];
+pub const DEFAULT_REPLY_PRIORITY: Priority = Priority::DataLow;
+
 impl StorageConfig {
     pub fn to_json_value(&self) -> Value {
         let mut result = serde_json::Map::new();
@@ -318,6 +906,12 @@ impl StorageConfig {
         if let Some(s) = &self.strip_prefix {
             result.insert("strip_prefix".into(), Value::String(s.to_string()));
         }
+        if let Some(m) = &self.key_mapping {
+            result.insert(
+                "key_mapping".into(),
+                serde_json::json!({ "zenoh": m.zenoh, "native": m.native }),
+            );
+        }
         result.insert(
             "volume".into(),
             match &self.volume_cfg {
@@ -330,8 +924,41 @@ impl StorageConfig {
                 _ => unreachable!(),
             },
         );
+        if !self.index.is_empty() {
+            result.insert(
+                "index".into(),
+                self.index.iter().map(|i| i.path.clone()).collect(),
+            );
+        }
+        if let Some(event_log) = &self.event_log {
+            result.insert(
+                "event_log".into(),
+                serde_json::json!({ "replay_rate": event_log.replay_rate }),
+            );
+        }
+        if self.reply_priority != DEFAULT_REPLY_PRIORITY {
+            if let Some((name, _)) = PRIORITIES.iter().find(|(_, p)| *p == self.reply_priority) {
+                result.insert("reply_priority".into(), Value::String(name.to_string()));
+            }
+        }
+        if let Some(aggregate) = &self.aggregate {
+            result.insert(
+                "aggregate".into(),
+                serde_json::json!({
+                    "source": aggregate.source.to_string(),
+                    "strategy": aggregate.strategy.to_string(),
+                }),
+            );
+        }
         Value::Object(result)
     }
+    /// Replaces the references to secrets in the volume options by their values, returning the resolved secrets by name.
+    pub fn resolve_secrets(
+        &mut self,
+        secrets: &HashMap<String, SecretProvider>,
+    ) -> ZResult<HashMap<String, String>> {
+        resolve_secrets(&mut self.volume_cfg, secrets)
+    }
     fn try_from<V: AsObject>(plugin_name: &str, storage_name: &str, config: &V) -> ZResult<Self> {
         let config = config.as_object().ok_or_else(|| {
             zerror!(
@@ -389,6 +1016,34 @@ impl StorageConfig {
                 storage_name
             ),
         };
+        let key_mapping = match config.get("key_mapping") {
+            Some(Value::Object(m)) => {
+                let (zenoh, native) = match (m.get("zenoh"), m.get("native")) {
+                    (Some(Value::String(zenoh)), Some(Value::String(native))) => (zenoh, native),
+                    _ => bail!("Invalid field `key_mapping` of storage `{}`. An object with `zenoh` and `native` string-typed fields is required.", storage_name),
+                };
+                let key_mapping = KeyMapping::new(zenoh, native)?;
+                if strip_prefix.is_some() {
+                    bail!(
+                        "Storage `{}` can't have both `strip_prefix` and `key_mapping`",
+                        storage_name
+                    )
+                }
+                if !key_expr.includes(&key_mapping.key_expr()?) {
+                    bail!(
+                        r#"The specified "key_mapping={}" doesn't match keys of "key_expr={}""#,
+                        zenoh,
+                        key_expr
+                    )
+                }
+                Some(key_mapping)
+            }
+            None => None,
+            _ => bail!(
+                "Invalid type for field `key_mapping` of storage `{}`. Only objects are accepted.",
+                storage_name
+            ),
+        };
         let (volume_id, volume_cfg) = match config.get("volume") {
             Some(Value::String(volume_id)) => (volume_id.clone(), Value::Null),
             Some(Value::Object(volume)) => {
@@ -466,15 +1121,174 @@ impl StorageConfig {
             }
             None => None,
         };
+        let replication = match config.get("replication") {
+            Some(Value::Object(s)) => {
+                let factor = match s.get("factor") {
+                    Some(Value::Number(f)) if f.as_u64().map_or(false, |f| f > 0) => f.as_u64().unwrap() as usize,
+                    _ => bail!("Invalid field `factor` in `replication` of storage `{}`. A positive integer is required.", storage_name),
+                };
+                let mut replication = ReplicationConfig::new(factor);
+                match s.get("write_quorum") {
+                    Some(Value::Number(q)) if q.as_u64().map_or(false, |q| q > 0 && q as usize <= factor) => {
+                        replication.write_quorum = q.as_u64().unwrap() as usize
+                    }
+                    Some(_) => bail!("Invalid field `write_quorum` in `replication` of storage `{}`. Only positive integers not greater than `factor` are accepted.", storage_name),
+                    None => {}
+                }
+                Some(replication)
+            }
+            None => None,
+            _ => bail!(
+                "Invalid type for field `replication` of storage `{}`. Only objects are accepted.",
+                storage_name
+            ),
+        };
+        let federation = match config.get("federation") {
+            Some(Value::Object(s)) => {
+                let site = match s.get("site") {
+                    Some(Value::String(site)) if keyexpr::new(site.as_str()).map_or(false, |ke| !ke.is_wild() && !site.contains('/')) => site.clone(),
+                    _ => bail!("Invalid field `site` in `federation` of storage `{}`. A single non-wild key-expression chunk is required.", storage_name),
+                };
+                let mut federation = FederationConfig::new(site);
+                match s.get("propagation_interval") {
+                    Some(Value::Number(p)) if p.as_u64().map_or(false, |p| p > 0) => {
+                        federation.propagation_interval = Duration::from_millis(p.as_u64().unwrap())
+                    }
+                    Some(_) => bail!("Invalid field `propagation_interval` in `federation` of storage `{}`. Only positive integer values are accepted.", storage_name),
+                    None => {}
+                }
+                Some(federation)
+            }
+            None => None,
+            _ => bail!(
+                "Invalid type for field `federation` of storage `{}`. Only objects are accepted.",
+                storage_name
+            ),
+        };
+        let cdc = match config.get("cdc") {
+            Some(Value::Bool(cdc)) => *cdc,
+            None => false,
+            _ => bail!(
+                "Invalid type for field `cdc` of storage `{}`. Only booleans are accepted.",
+                storage_name
+            ),
+        };
+        let index = match config.get("index") {
+            Some(Value::Array(paths)) => paths
+                .iter()
+                .map(|path| match path {
+                    Value::String(path) => IndexPath::new(path),
+                    _ => bail!(
+                        "Invalid field `index` of storage `{}`. Only arrays of JSON path strings are accepted.",
+                        storage_name
+                    ),
+                })
+                .collect::<ZResult<Vec<_>>>()?,
+            None => Vec::new(),
+            _ => bail!(
+                "Invalid type for field `index` of storage `{}`. Only arrays are accepted.",
+                storage_name
+            ),
+        };
+        let event_log = match config.get("event_log") {
+            Some(Value::Bool(true)) => Some(EventLogConfig::default()),
+            Some(Value::Bool(false)) | None => None,
+            Some(Value::Object(s)) => {
+                let mut event_log = EventLogConfig::default();
+                match s.get("replay_rate") {
+                    Some(Value::Number(r)) if r.as_u64().map_or(false, |r| r > 0) => {
+                        event_log.replay_rate = r.as_u64().unwrap()
+                    }
+                    Some(_) => bail!("Invalid field `replay_rate` in `event_log` of storage `{}`. Only positive integer values are accepted.", storage_name),
+                    None => {}
+                }
+                Some(event_log)
+            }
+            _ => bail!(
+                "Invalid type for field `event_log` of storage `{}`. Only booleans and objects are accepted.",
+                storage_name
+            ),
+        };
+        let reply_priority = match config.get("reply_priority") {
+            Some(Value::String(name)) => match PRIORITIES.iter().find(|(n, _)| n == name) {
+                Some((_, priority)) => *priority,
+                None => bail!(
+                    "Invalid field `reply_priority` of storage `{}`. Only {} are accepted.",
+                    storage_name,
+                    PRIORITIES.map(|(n, _)| format!("\"{n}\"")).join(", ")
+                ),
+            },
+            None => DEFAULT_REPLY_PRIORITY,
+            _ => bail!(
+                "Invalid type for field `reply_priority` of storage `{}`. Only strings are accepted.",
+                storage_name
+            ),
+        };
+        let aggregate = match config.get("aggregate") {
+            Some(Value::Object(s)) => {
+                let source = match s.get("source") {
+                    Some(Value::String(source)) => match keyexpr::new(source.as_str()) {
+                        Ok(source) => source.to_owned(),
+                        Err(e) => bail!(
+                            "Invalid field `source` in `aggregate` of storage `{}`: {}",
+                            storage_name,
+                            e
+                        ),
+                    },
+                    _ => bail!("Invalid field `source` in `aggregate` of storage `{}`. A key expression string is required.", storage_name),
+                };
+                let strategy = match s.get("strategy") {
+                    Some(Value::String(strategy)) => strategy.parse::<AggregateStrategy>().map_err(|e| {
+                        zerror!("Invalid field `strategy` in `aggregate` of storage `{}`: {}", storage_name, e)
+                    })?,
+                    _ => bail!("Invalid field `strategy` in `aggregate` of storage `{}`. A string is required.", storage_name),
+                };
+                // the mirrored keys are the source keys with another non-wild prefix
+                let wild_suffix = |ke: &keyexpr| {
+                    let prefix = ke.get_nonwild_prefix().map_or(0, |p| p.len());
+                    ke.as_str()[prefix..].trim_start_matches('/').to_string()
+                };
+                if wild_suffix(&source) != wild_suffix(&key_expr) {
+                    bail!(
+                        "Invalid field `source` in `aggregate` of storage `{}`: `{}` and the storage key expression `{}` must only differ by their non-wild prefix.",
+                        storage_name,
+                        source,
+                        key_expr
+                    )
+                }
+                if source.intersects(&key_expr) {
+                    bail!(
+                        "Invalid field `source` in `aggregate` of storage `{}`: `{}` intersects the storage key expression `{}`.",
+                        storage_name,
+                        source,
+                        key_expr
+                    )
+                }
+                Some(AggregateConfig { source, strategy })
+            }
+            None => None,
+            _ => bail!(
+                "Invalid type for field `aggregate` of storage `{}`. Only objects are accepted.",
+                storage_name
+            ),
+        };
         Ok(StorageConfig {
             name: storage_name.into(),
             key_expr,
             complete,
             strip_prefix,
+            key_mapping,
             volume_id,
             volume_cfg,
             garbage_collection_config,
             replica_config,
+            replication,
+            federation,
+            cdc,
+            index,
+            event_log,
+            reply_priority,
+            aggregate,
         })
     }
 }
@@ -20,8 +20,13 @@
 //!  - [`Volume`]
 //!  - [`Storage`]
 //!
+//! A [`Storage`] may also maintain secondary indexes on the fields of the JSON payloads listed in the `index`
+//! of its configuration (e.g. `index: ["$.vehicle_id", "$.status"]`), and answer the queries filtering on them
+//! (e.g. `vehicles/**?$.status=active`) through [`Storage::get_by_index`] without a full scan.
+//!
 //! Such library must also declare a `create_volume()` operation
-//! with the `#[no_mangle]` attribute as an entrypoint to be called for the Backend creation.
+//! with the `#[no_mangle]` attribute as an entrypoint to be called for the Backend creation,
+//! and its compatibility record with [`declare_backend_compatibility`], which is checked before calling into the library.
 //!
 //! # Example
 //! ```
@@ -38,6 +43,8 @@
 //!     Ok(Box::new(MyVolumeType { config }))
 //! }
 //!
+//! zenoh_backend_traits::declare_backend_compatibility!();
+//!
 //! // Your Backend implementation
 //! struct MyVolumeType {
 //!     config: VolumeConfig,
@@ -140,7 +147,7 @@ use zenoh::value::Value;
 pub use zenoh::Result as ZResult;
 
 pub mod config;
-use config::{StorageConfig, VolumeConfig};
+use config::{IndexPath, StorageConfig, VolumeConfig};
 
 /// Capability of a storage indicates the guarantees of the storage
 /// It is used by the storage manager to take decisions on the trade-offs to ensure correct performance
@@ -166,7 +173,8 @@ pub enum Persistence {
 
 /// History is the number of values that the backend is expected to save per key
 /// History::Latest saves only the latest value per key
-/// History::All saves all the values including historical values
+/// History::All saves all the values including historical values, and is required to reply to the as-of queries
+/// (`_time=<instant>`), which are translated into `get` calls with the `_time=[..<instant>]` parameter
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum History {
     Latest, //default
@@ -177,6 +185,23 @@ pub enum History {
 pub const CREATE_VOLUME_FN_NAME: &[u8] = b"create_volume";
 pub type CreateVolume = fn(VolumeConfig) -> ZResult<Box<dyn Volume>>;
 
+pub use zenoh_plugin_trait::Compatibility;
+/// The compatibility record of backends built against this version of the traits.
+pub const BACKEND_COMPATIBILITY: Compatibility =
+    Compatibility::new::<VolumeConfig, Box<dyn Volume>>();
+/// The name of the [`Compatibility`] static declared by [`declare_backend_compatibility`] in backend libraries.
+pub const BACKEND_COMPATIBILITY_SYMBOL: &[u8] = b"ZENOH_BACKEND_COMPATIBILITY";
+
+/// Adds the backend library's [`Compatibility`] record, which the storage manager checks before calling `create_volume`.
+#[macro_export]
+macro_rules! declare_backend_compatibility {
+    () => {
+        #[no_mangle]
+        pub static ZENOH_BACKEND_COMPATIBILITY: $crate::Compatibility =
+            $crate::BACKEND_COMPATIBILITY;
+    };
+}
+
 ///
 pub enum StorageInsertionResult {
     Outdated,
@@ -191,6 +216,79 @@ pub struct StoredData {
     pub timestamp: Timestamp,
 }
 
+/// A predicate on a field of the JSON payloads, expressed in the parameters of a query as `$.<field>=<value>`
+/// (e.g. `vehicles/**?$.status=active`). The value is compared as JSON if it parses as such, and as a string otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexPredicate {
+    pub path: IndexPath,
+    pub value: serde_json::Value,
+}
+
+impl IndexPredicate {
+    /// Returns the predicates of the parameters of a query, ignoring its other parameters.
+    pub fn from_parameters(parameters: &str) -> ZResult<Vec<IndexPredicate>> {
+        use zenoh::selector::Parameters;
+        parameters
+            .decode()
+            .filter(|(name, _)| name.starts_with("$."))
+            .map(|(name, value)| {
+                Ok(IndexPredicate {
+                    path: IndexPath::new(&name)?,
+                    value: serde_json::from_str(&value)
+                        .unwrap_or_else(|_| serde_json::Value::String(value.into_owned())),
+                })
+            })
+            .collect()
+    }
+
+    /// Whether a JSON payload satisfies this predicate.
+    pub fn matches(&self, payload: &serde_json::Value) -> bool {
+        self.path.select(payload) == Some(&self.value)
+    }
+
+    /// Whether a value has a JSON payload satisfying this predicate.
+    pub fn matches_value(&self, value: &Value) -> bool {
+        use zenoh::buffers::SplitBuffer;
+        serde_json::from_slice::<serde_json::Value>(&value.payload.contiguous())
+            .map_or(false, |payload| self.matches(&payload))
+    }
+}
+
+/// The outcome of the resolution of a conflict by a [`ConflictResolver`].
+#[derive(Debug, Clone)]
+pub enum Resolution {
+    /// Keep the stored data and discard the update.
+    Keep,
+    /// Replace the stored data with the update.
+    Accept,
+    /// Replace the stored data with a merged value, timestamped with the latest of both timestamps.
+    Merge(Value),
+}
+
+/// Trait to be implemented to customize how a federated storage reconciles the data stored for a key
+/// with a concurrent update of this key, either published locally or received from another site.
+///
+/// For all the sites to converge, the resolution must be deterministic,
+/// and the merges must be commutative, associative and idempotent.
+pub trait ConflictResolver: Send + Sync {
+    /// Resolves the conflict between the `stored` data of `key` and an `update` of it.
+    /// Deletions are always resolved by the latest timestamp and are not submitted to the resolver.
+    fn resolve(&self, key: &OwnedKeyExpr, stored: &StoredData, update: &StoredData) -> Resolution;
+}
+
+/// The default [`ConflictResolver`]: the data with the latest HLC timestamp wins.
+pub struct LastWriterWins;
+
+impl ConflictResolver for LastWriterWins {
+    fn resolve(&self, _key: &OwnedKeyExpr, stored: &StoredData, update: &StoredData) -> Resolution {
+        if update.timestamp > stored.timestamp {
+            Resolution::Accept
+        } else {
+            Resolution::Keep
+        }
+    }
+}
+
 /// Trait to be implemented by a Backend.
 ///
 #[async_trait]
@@ -212,6 +310,12 @@ pub trait Volume: Send + Sync {
     /// Returns an interceptor that will be called before sending any reply
     /// to a query from a storage created by this backend. `None` can be returned for no interception point.
     fn outgoing_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>;
+
+    /// Returns the resolver of the conflicts between the concurrent updates of the federated storages
+    /// created by this backend. `None` can be returned to resolve them with [`LastWriterWins`].
+    fn conflict_resolver(&self) -> Option<Arc<dyn ConflictResolver>> {
+        None
+    }
 }
 
 /// Trait to be implemented by a Storage.
@@ -232,6 +336,23 @@ pub trait Storage: Send + Sync {
         timestamp: Timestamp,
     ) -> ZResult<StorageInsertionResult>;
 
+    /// Function called for each incoming atomic batch of data to be stored in this storage,
+    /// returning the result of the insertion of each entry.
+    /// The entries must be stored as a unit: readers of the storage must see either all or none of them.
+    /// The storage manager doesn't query the storage while storing a batch, hence the default implementation
+    /// calling [`put`](Storage::put) for each entry: backends shared with other processes should override it
+    /// with a transaction.
+    async fn put_batch(
+        &mut self,
+        entries: Vec<(Option<OwnedKeyExpr>, Value, Timestamp)>,
+    ) -> ZResult<Vec<StorageInsertionResult>> {
+        let mut results = Vec::with_capacity(entries.len());
+        for (key, value, timestamp) in entries {
+            results.push(self.put(key, value, timestamp).await?);
+        }
+        Ok(results)
+    }
+
     /// Function called for each incoming delete request to this storage.
     /// A key can be `None` if it matches the `strip_prefix` exactly.
     /// In order to avoid data loss, the storage must delete the entry corresponding to the `None` key
@@ -256,6 +377,17 @@ pub trait Storage: Send + Sync {
     /// The latest Timestamp corresponding to each key is either the timestamp of the delete or put whichever is the latest.
     /// Remember to fetch the entry corresponding to the `None` key
     async fn get_all_entries(&self) -> ZResult<Vec<(Option<OwnedKeyExpr>, Timestamp)>>;
+
+    /// Function called to retrieve the entries whose JSON payloads satisfy all the `predicates`,
+    /// which are all on fields declared in the `index` of the storage's configuration.
+    /// `None` can be returned if the storage doesn't maintain these indexes,
+    /// in which case the storage manager falls back to a scan of the entries.
+    async fn get_by_index(
+        &mut self,
+        _predicates: &[IndexPredicate],
+    ) -> ZResult<Option<Vec<(Option<OwnedKeyExpr>, StoredData)>>> {
+        Ok(None)
+    }
 }
 
 /// A wrapper around the [`zenoh::queryable::Query`] allowing to call the
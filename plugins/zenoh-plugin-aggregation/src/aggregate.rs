@@ -0,0 +1,123 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::config::Function;
+use std::collections::BTreeMap;
+use zenoh::prelude::r#async::*;
+
+/// The running aggregation of numeric values.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Accumulator {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Accumulator {
+    pub fn add(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the result of `function`, or `None` for the min, max or average of no values.
+    pub fn result(&self, function: Function) -> Option<f64> {
+        match function {
+            Function::Count => Some(self.count as f64),
+            Function::Sum => Some(self.sum),
+            _ if self.count == 0 => None,
+            Function::Min => Some(self.min),
+            Function::Max => Some(self.max),
+            Function::Avg => Some(self.sum / self.count as f64),
+        }
+    }
+}
+
+/// Returns the numeric value of a payload, zenoh encoding the integers and floats as text.
+pub fn parse_number(value: &Value) -> Option<f64> {
+    let payload = value.payload.contiguous();
+    std::str::from_utf8(&payload).ok()?.trim().parse().ok()
+}
+
+/// Aggregates the values per time bucket of `bucket_ms` milliseconds, the values being
+/// given with their time in milliseconds since the UNIX epoch.
+pub fn buckets(
+    values: impl IntoIterator<Item = (u64, f64)>,
+    bucket_ms: u64,
+) -> BTreeMap<u64, Accumulator> {
+    let mut buckets = BTreeMap::<u64, Accumulator>::new();
+    for (time, value) in values {
+        let start = time - time % bucket_ms.max(1);
+        buckets.entry(start).or_default().add(value);
+    }
+    buckets
+}
+
+/// Returns the JSON array of the results of `function` per bucket, ordered by bucket start time.
+pub fn buckets_to_json(
+    buckets: &BTreeMap<u64, Accumulator>,
+    function: Function,
+    bucket_ms: u64,
+) -> serde_json::Value {
+    buckets
+        .iter()
+        .map(|(start, acc)| {
+            serde_json::json!({
+                "start": start,
+                "end": start + bucket_ms,
+                "count": acc.count(),
+                "value": acc.result(function),
+            })
+        })
+        .collect::<Vec<_>>()
+        .into()
+}
+
+#[test]
+fn aggregate() {
+    let mut acc = Accumulator::default();
+    assert_eq!(acc.result(Function::Avg), None);
+    assert_eq!(acc.result(Function::Count), Some(0.0));
+    for value in [3.0, -1.0, 4.0] {
+        acc.add(value);
+    }
+    assert_eq!(acc.result(Function::Min), Some(-1.0));
+    assert_eq!(acc.result(Function::Max), Some(4.0));
+    assert_eq!(acc.result(Function::Avg), Some(2.0));
+    assert_eq!(acc.result(Function::Sum), Some(6.0));
+    assert_eq!(acc.result(Function::Count), Some(3.0));
+
+    assert_eq!(parse_number(&Value::from(21.5)), Some(21.5));
+    assert_eq!(parse_number(&Value::from(" 7\n")), Some(7.0));
+    assert_eq!(parse_number(&Value::from("warm")), None);
+
+    let buckets = buckets([(1_000, 1.0), (1_999, 3.0), (3_500, 10.0)], 1_000);
+    assert_eq!(
+        buckets_to_json(&buckets, Function::Avg, 1_000),
+        serde_json::json!([
+            {"start": 1_000, "end": 2_000, "count": 2, "value": 2.0},
+            {"start": 3_000, "end": 4_000, "count": 1, "value": 10.0},
+        ])
+    );
+}
@@ -0,0 +1,63 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub aggregates: Vec<AggregateConf>,
+    /// The time after which the sub-queries of an aggregate stop waiting for replies.
+    #[serde(default = "default_query_timeout_ms")]
+    pub query_timeout_ms: u64,
+    __path__: Option<String>,
+    __required__: Option<bool>,
+    __on_panic__: Option<String>,
+    __max_restarts__: Option<u32>,
+    __dependencies__: Option<Vec<String>>,
+    __config__: Option<String>,
+}
+
+/// An aggregate: the queries on `key_expr` are answered by querying the same key expression with its
+/// last chunk replaced by `source`, and applying `function` to the numeric values of the replies.
+/// With `bucket_ms`, the values are aggregated per time bucket of `bucket_ms` milliseconds.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AggregateConf {
+    pub key_expr: String,
+    pub source: String,
+    pub function: Function,
+    #[serde(default)]
+    pub bucket_ms: Option<u64>,
+}
+
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Function {
+    Min,
+    Max,
+    Avg,
+    Sum,
+    Count,
+}
+
+fn default_query_timeout_ms() -> u64 {
+    10_000
+}
+
+impl From<&Config> for serde_json::Value {
+    fn from(c: &Config) -> Self {
+        serde_json::to_value(c).unwrap()
+    }
+}
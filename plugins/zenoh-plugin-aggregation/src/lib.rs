@@ -0,0 +1,373 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! Declares a queryable on the key expression of each configured aggregate, answering the queries by
+//! querying the aggregate's source (typically storages) with the same parameters (e.g. `_time`), and
+//! replying with the aggregate (min, max, avg, sum or count) of the numeric values received.
+use async_std::prelude::FutureExt;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use zenoh::plugins::{Metric, MetricKind, Plugin, RunningPluginTrait, TaskMonitor, ZenohPlugin};
+use zenoh::prelude::r#async::*;
+use zenoh::queryable::Query;
+use zenoh::runtime::Runtime;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod aggregate;
+mod config;
+use aggregate::{buckets, buckets_to_json, parse_number, Accumulator};
+pub use config::{AggregateConf, Config, Function};
+
+const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
+lazy_static::lazy_static! {
+    static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
+}
+
+zenoh_plugin_trait::declare_plugin!(AggregationPlugin);
+pub struct AggregationPlugin {}
+
+impl ZenohPlugin for AggregationPlugin {}
+
+impl Plugin for AggregationPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = zenoh::plugins::RunningPlugin;
+    const STATIC_NAME: &'static str = "aggregation";
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+        // Try to initiate login.
+        // Required in case of dynamic lib, otherwise no logs.
+        // But cannot be done twice in case of static link.
+        let _ = env_logger::try_init();
+        log::debug!("Aggregation plugin {}", LONG_VERSION.as_str());
+
+        let runtime_conf = runtime.config.lock();
+        let plugin_conf = runtime_conf
+            .plugin(name)
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+
+        let conf: Config = serde_json::from_value(plugin_conf.clone())
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let aggregates = conf
+            .aggregates
+            .iter()
+            .map(Aggregate::new)
+            .collect::<ZResult<Vec<_>>>()
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let aggregates = Arc::new(aggregates);
+        let monitor = TaskMonitor::default();
+        let task = async_std::task::spawn(monitor.clone().watch(run(
+            runtime.clone(),
+            conf.clone(),
+            aggregates.clone(),
+        )));
+        let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
+        if let Ok(Err(e)) = task {
+            bail!("Aggregates failed within 1ms: {e}")
+        }
+        Ok(Box::new(RunningPlugin {
+            conf,
+            aggregates,
+            monitor,
+        }))
+    }
+}
+
+/// A configured aggregate and its statistics.
+struct Aggregate {
+    key_expr: OwnedKeyExpr,
+    // the last chunk of `key_expr`, replaced by `source_suffix` in the sub-queries
+    name: String,
+    source_suffix: OwnedKeyExpr,
+    source: OwnedKeyExpr,
+    function: Function,
+    bucket_ms: Option<u64>,
+    answered: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl Aggregate {
+    fn new(conf: &AggregateConf) -> ZResult<Self> {
+        let key_expr = OwnedKeyExpr::autocanonize(conf.key_expr.clone())?;
+        let (parent, name) = match key_expr.rsplit_once('/') {
+            Some((parent, name)) if !name.contains('*') => (keyexpr::new(parent)?, name),
+            _ => bail!(
+                "the key expression of aggregate {} must end with a chunk without wildcard",
+                key_expr
+            ),
+        };
+        let source_suffix = OwnedKeyExpr::autocanonize(conf.source.clone())?;
+        let source = parent / &*source_suffix;
+        if source.intersects(&key_expr) {
+            bail!(
+                "the source {} of aggregate {} intersects its key expression",
+                source,
+                key_expr
+            )
+        }
+        if conf.bucket_ms == Some(0) {
+            bail!("the bucket_ms of aggregate {} must be positive", key_expr)
+        }
+        Ok(Aggregate {
+            name: name.to_string(),
+            key_expr,
+            source_suffix,
+            source,
+            function: conf.function,
+            bucket_ms: conf.bucket_ms,
+            answered: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        })
+    }
+
+    /// Returns the key expression to query to answer a query on `key_expr`, and the key expression of the reply.
+    ///
+    /// A query on a single aggregate (e.g. `demo/room1/avg_temp`) queries its own source (`demo/room1/temp`),
+    /// while the other queries aggregate all the source of the aggregate.
+    fn sub_query(&self, key_expr: &keyexpr) -> (OwnedKeyExpr, OwnedKeyExpr) {
+        match key_expr.rsplit_once('/') {
+            Some((parent, name)) if name == self.name => match keyexpr::new(parent) {
+                Ok(parent) => (parent / &*self.source_suffix, key_expr.into()),
+                Err(_) => (self.source.clone(), self.key_expr.clone()),
+            },
+            _ => (self.source.clone(), self.key_expr.clone()),
+        }
+    }
+
+    fn status(&self) -> serde_json::Value {
+        serde_json::json!({
+            "key_expr": self.key_expr.as_str(),
+            "source": self.source.as_str(),
+            "function": self.function,
+            "bucket_ms": self.bucket_ms,
+            "answered": self.answered.load(Ordering::Relaxed),
+            "failed": self.failed.load(Ordering::Relaxed),
+        })
+    }
+}
+
+struct RunningPlugin {
+    conf: Config,
+    aggregates: Arc<Vec<Aggregate>>,
+    monitor: TaskMonitor,
+}
+
+impl RunningPluginTrait for RunningPlugin {
+    fn terminated(&self) -> Option<String> {
+        self.monitor.terminated()
+    }
+
+    fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
+        Arc::new(|_, _, _| {
+            bail!("zenoh-plugin-aggregation doesn't accept any runtime configuration changes")
+        })
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<zenoh::plugins::Response>> {
+        let mut responses = Vec::new();
+        for (suffix, value) in [
+            ("/version", GIT_VERSION.into()),
+            ("/config", (&self.conf).into()),
+            (
+                "/aggregates",
+                self.aggregates
+                    .iter()
+                    .map(Aggregate::status)
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+        ] {
+            let key = format!("{plugin_status_key}{suffix}");
+            if keyexpr::new(key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(key, value))
+            }
+        }
+        Ok(responses)
+    }
+
+    fn metrics(&self) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        for aggregate in self.aggregates.iter() {
+            for (result, count) in [
+                ("answered", &aggregate.answered),
+                ("failed", &aggregate.failed),
+            ] {
+                metrics.push(
+                    Metric::new(
+                        "zenoh_aggregation_queries_total",
+                        MetricKind::Counter,
+                        "Number of queries received by an aggregate, by result.",
+                        count.load(Ordering::Relaxed) as f64,
+                    )
+                    .with_label("key_expr", aggregate.key_expr.as_str())
+                    .with_label("result", result),
+                );
+            }
+        }
+        metrics
+    }
+}
+
+async fn run(runtime: Runtime, conf: Config, aggregates: Arc<Vec<Aggregate>>) -> ZResult<()> {
+    // Try to initiate login.
+    // Required in case of dynamic lib, otherwise no logs.
+    // But cannot be done twice in case of static link.
+    let _ = env_logger::try_init();
+
+    let session = zenoh::init(runtime).res().await?.into_arc();
+    let timeout = Duration::from_millis(conf.query_timeout_ms);
+    let mut queryables = Vec::with_capacity(aggregates.len());
+    for index in 0..aggregates.len() {
+        let session_clone = session.clone();
+        let aggregates_clone = aggregates.clone();
+        let queryable = session
+            .declare_queryable(&aggregates[index].key_expr)
+            .callback(move |query| {
+                async_std::task::spawn(answer(
+                    session_clone.clone(),
+                    aggregates_clone.clone(),
+                    index,
+                    query,
+                    timeout,
+                ));
+            })
+            .res()
+            .await?;
+        queryables.push(queryable);
+    }
+    // Keep the queryables declared for the whole lifetime of the plugin
+    async_std::future::pending::<()>().await;
+    drop(queryables);
+    Ok(())
+}
+
+/// Answers a query on the aggregate at `index` with the aggregate of the replies to its sub-query.
+async fn answer(
+    session: Arc<Session>,
+    aggregates: Arc<Vec<Aggregate>>,
+    index: usize,
+    query: Query,
+    timeout: Duration,
+) {
+    let aggregate = &aggregates[index];
+    let (source, reply_key_expr) = aggregate.sub_query(query.key_expr());
+    let result =
+        match aggregate_source(&session, aggregate, &source, query.parameters(), timeout).await {
+            Ok(value) => Ok(Sample::new(reply_key_expr, value)),
+            Err(e) => {
+                log::debug!(
+                    "Aggregate {} failed on {}: {}",
+                    aggregate.key_expr,
+                    source,
+                    e
+                );
+                Err(Value::from(e.to_string()))
+            }
+        };
+    let counter = if result.is_ok() {
+        &aggregate.answered
+    } else {
+        &aggregate.failed
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+    if let Err(e) = query.reply(result).res().await {
+        log::warn!("Aggregate {} failed to reply: {}", aggregate.key_expr, e);
+    }
+}
+
+/// Queries `source` and returns the aggregate of the numeric values replied.
+async fn aggregate_source(
+    session: &Session,
+    aggregate: &Aggregate,
+    source: &keyexpr,
+    parameters: &str,
+    timeout: Duration,
+) -> ZResult<Value> {
+    let selector = if parameters.is_empty() {
+        source.to_string()
+    } else {
+        format!("{source}?{parameters}")
+    };
+    let replies = session.get(selector).timeout(timeout).res().await?;
+    let mut received = HashSet::new();
+    let mut total = Accumulator::default();
+    let mut timed = Vec::new();
+    while let Ok(reply) = replies.recv_async().await {
+        let sample = match reply.sample {
+            Ok(sample) if sample.kind == SampleKind::Put => sample,
+            _ => continue,
+        };
+        if !aggregate.source.intersects(&sample.key_expr) {
+            continue;
+        }
+        // the same value may be replied by several storages
+        if let Some(timestamp) = sample.timestamp {
+            if !received.insert((sample.key_expr.to_string(), timestamp)) {
+                continue;
+            }
+        }
+        let value = match parse_number(&sample.value) {
+            Some(value) => value,
+            None => {
+                log::trace!(
+                    "Aggregate {}: ignoring non-numeric value on {}",
+                    aggregate.key_expr,
+                    sample.key_expr
+                );
+                continue;
+            }
+        };
+        match (aggregate.bucket_ms, sample.timestamp) {
+            (None, _) => total.add(value),
+            (Some(_), Some(timestamp)) => {
+                let time = timestamp
+                    .get_time()
+                    .to_system_time()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                timed.push((time.as_millis() as u64, value));
+            }
+            (Some(_), None) => log::trace!(
+                "Aggregate {}: ignoring value without timestamp on {}",
+                aggregate.key_expr,
+                sample.key_expr
+            ),
+        }
+    }
+    match aggregate.bucket_ms {
+        Some(bucket_ms) => {
+            Ok(buckets_to_json(&buckets(timed, bucket_ms), aggregate.function, bucket_ms).into())
+        }
+        None if aggregate.function == Function::Count => Ok(total.count().into()),
+        None => match total.result(aggregate.function) {
+            Some(result) => Ok(result.into()),
+            None => bail!("no numeric value to aggregate on {}", source),
+        },
+    }
+}
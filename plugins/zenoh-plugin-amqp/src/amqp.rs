@@ -0,0 +1,836 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! A minimal codec for the AMQP 1.0 frames, performatives and messages, as exchanged by a client
+//! attaching links to the nodes of a broker.
+use futures::{AsyncRead, AsyncReadExt};
+use std::io::{Error, ErrorKind, Result};
+
+pub(crate) const AMQP_HEADER: [u8; 8] = *b"AMQP\x00\x01\x00\x00";
+pub(crate) const SASL_HEADER: [u8; 8] = *b"AMQP\x03\x01\x00\x00";
+
+const FRAME_AMQP: u8 = 0;
+const FRAME_SASL: u8 = 1;
+const FRAME_HEADER_SIZE: usize = 8;
+
+pub(crate) const OPEN: u64 = 0x10;
+pub(crate) const BEGIN: u64 = 0x11;
+pub(crate) const ATTACH: u64 = 0x12;
+pub(crate) const FLOW: u64 = 0x13;
+pub(crate) const TRANSFER: u64 = 0x14;
+pub(crate) const DISPOSITION: u64 = 0x15;
+pub(crate) const DETACH: u64 = 0x16;
+pub(crate) const END: u64 = 0x17;
+pub(crate) const CLOSE: u64 = 0x18;
+const ERROR: u64 = 0x1d;
+const ACCEPTED: u64 = 0x24;
+const SOURCE: u64 = 0x28;
+const TARGET: u64 = 0x29;
+pub(crate) const SASL_MECHANISMS: u64 = 0x40;
+const SASL_INIT: u64 = 0x41;
+pub(crate) const SASL_OUTCOME: u64 = 0x44;
+const PROPERTIES: u64 = 0x73;
+const DATA: u64 = 0x75;
+const AMQP_VALUE: u64 = 0x77;
+
+pub(crate) const SASL_OK: u8 = 0;
+const SND_SETTLED: u8 = 1;
+const SND_MIXED: u8 = 2;
+const RCV_FIRST: u8 = 0;
+
+/// The values of the AMQP type system, the ones the bridge doesn't interpret being kept encoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Ubyte(u8),
+    Ushort(u16),
+    Uint(u32),
+    Ulong(u64),
+    Binary(Vec<u8>),
+    String(String),
+    Symbol(String),
+    List(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Array(Vec<Value>),
+    Described(Box<Value>, Box<Value>),
+    /// A value of another type, with its format code and its encoding following the format code.
+    Other(u8, Vec<u8>),
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+fn described(descriptor: u64, fields: Vec<Value>) -> Value {
+    Value::Described(
+        Box::new(Value::Ulong(descriptor)),
+        Box::new(Value::List(fields)),
+    )
+}
+
+impl Value {
+    pub(crate) fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Ubyte(v) => Some(*v as u64),
+            Value::Ushort(v) => Some(*v as u64),
+            Value::Uint(v) => Some(*v as u64),
+            Value::Ulong(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_u32(&self) -> Option<u32> {
+        self.as_u64().and_then(|v| v.try_into().ok())
+    }
+
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) | Value::Symbol(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn encode(&self, buf: &mut Vec<u8>) {
+        let (constructor, data) = self.constructor_and_data(false);
+        buf.extend_from_slice(&constructor);
+        buf.extend_from_slice(&data);
+    }
+
+    // Splits the encoding of the value into its constructor and its data, with the widest
+    // format code of its type if `wide` is true, as required for the elements of arrays
+    fn constructor_and_data(&self, wide: bool) -> (Vec<u8>, Vec<u8>) {
+        let (code, data) = match self {
+            Value::Null => (0x40, Vec::new()),
+            Value::Bool(v) if wide => (0x56, vec![*v as u8]),
+            Value::Bool(v) => (if *v { 0x41 } else { 0x42 }, Vec::new()),
+            Value::Ubyte(v) => (0x50, vec![*v]),
+            Value::Ushort(v) => (0x60, v.to_be_bytes().to_vec()),
+            Value::Uint(v) if wide || *v > 0xff => (0x70, v.to_be_bytes().to_vec()),
+            Value::Uint(0) => (0x43, Vec::new()),
+            Value::Uint(v) => (0x52, vec![*v as u8]),
+            Value::Ulong(v) if wide || *v > 0xff => (0x80, v.to_be_bytes().to_vec()),
+            Value::Ulong(0) => (0x44, Vec::new()),
+            Value::Ulong(v) => (0x53, vec![*v as u8]),
+            Value::Binary(v) => variable(0xa0, v, wide),
+            Value::String(s) => variable(0xa1, s.as_bytes(), wide),
+            Value::Symbol(s) => variable(0xa3, s.as_bytes(), wide),
+            Value::List(items) if items.is_empty() && !wide => (0x45, Vec::new()),
+            Value::List(items) => {
+                let mut data = Vec::new();
+                items.iter().for_each(|item| item.encode(&mut data));
+                compound(0xc0, items.len(), &data, wide)
+            }
+            Value::Map(entries) => {
+                let mut data = Vec::new();
+                for (key, value) in entries {
+                    key.encode(&mut data);
+                    value.encode(&mut data);
+                }
+                compound(0xc1, entries.len() * 2, &data, wide)
+            }
+            Value::Array(items) => {
+                let mut data = match items.first() {
+                    Some(item) => item.constructor_and_data(true).0,
+                    None => vec![0x40],
+                };
+                for item in items {
+                    data.extend_from_slice(&item.constructor_and_data(true).1);
+                }
+                compound(0xe0, items.len(), &data, wide)
+            }
+            Value::Described(descriptor, value) => {
+                let mut constructor = vec![0x00];
+                descriptor.encode(&mut constructor);
+                let (value_constructor, data) = value.constructor_and_data(wide);
+                constructor.extend_from_slice(&value_constructor);
+                return (constructor, data);
+            }
+            Value::Other(code, data) => (*code, data.clone()),
+        };
+        (vec![code], data)
+    }
+}
+
+// The format code and data of a binary, string or symbol, `code` being its 8 bits size format code
+fn variable(code: u8, bytes: &[u8], wide: bool) -> (u8, Vec<u8>) {
+    let mut data = Vec::with_capacity(bytes.len() + 4);
+    let code = match u8::try_from(bytes.len()) {
+        Ok(len) if !wide => {
+            data.push(len);
+            code
+        }
+        _ => {
+            data.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+            code + 0x10
+        }
+    };
+    data.extend_from_slice(bytes);
+    (code, data)
+}
+
+// The format code and data of a list, map or array, `code` being its 8 bits size format code
+fn compound(code: u8, count: usize, elements: &[u8], wide: bool) -> (u8, Vec<u8>) {
+    let mut data = Vec::with_capacity(elements.len() + 8);
+    // The size includes the count
+    let code = match (u8::try_from(elements.len() + 1), u8::try_from(count)) {
+        (Ok(size), Ok(count)) if !wide => {
+            data.extend_from_slice(&[size, count]);
+            code
+        }
+        _ => {
+            data.extend_from_slice(&(elements.len() as u32 + 4).to_be_bytes());
+            data.extend_from_slice(&(count as u32).to_be_bytes());
+            code + 0x10
+        }
+    };
+    data.extend_from_slice(elements);
+    (code, data)
+}
+
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(invalid("Truncated AMQP value"));
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    // Reads a size or count, on 1 byte for 8 bits size format codes, and 4 bytes otherwise
+    fn size(&mut self, code: u8) -> Result<usize> {
+        match code & 0x10 {
+            0 => self.u8().map(usize::from),
+            _ => self.u32().map(|size| size as usize),
+        }
+    }
+
+    pub(crate) fn value(&mut self) -> Result<Value> {
+        let constructor = self.constructor()?;
+        self.data(&constructor)
+    }
+
+    // Reads a format code, with its descriptor if the value is described
+    fn constructor(&mut self) -> Result<(Option<Value>, u8)> {
+        match self.u8()? {
+            0x00 => {
+                let descriptor = self.value()?;
+                match self.u8()? {
+                    0x00 => Err(invalid("Invalid AMQP descriptor of a descriptor")),
+                    code => Ok((Some(descriptor), code)),
+                }
+            }
+            code => Ok((None, code)),
+        }
+    }
+
+    fn data(&mut self, (descriptor, code): &(Option<Value>, u8)) -> Result<Value> {
+        let code = *code;
+        let value = match code {
+            0x40 => Value::Null,
+            0x41 => Value::Bool(true),
+            0x42 => Value::Bool(false),
+            0x56 => Value::Bool(self.u8()? != 0),
+            0x50 => Value::Ubyte(self.u8()?),
+            0x60 => {
+                let bytes = self.bytes(2)?;
+                Value::Ushort(u16::from_be_bytes([bytes[0], bytes[1]]))
+            }
+            0x70 => Value::Uint(self.u32()?),
+            0x52 => Value::Uint(self.u8()? as u32),
+            0x43 => Value::Uint(0),
+            0x80 => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(self.bytes(8)?);
+                Value::Ulong(u64::from_be_bytes(bytes))
+            }
+            0x53 => Value::Ulong(self.u8()? as u64),
+            0x44 => Value::Ulong(0),
+            0xa0 | 0xb0 => {
+                let len = self.size(code)?;
+                Value::Binary(self.bytes(len)?.to_vec())
+            }
+            0xa1 | 0xb1 | 0xa3 | 0xb3 => {
+                let len = self.size(code)?;
+                let s = std::str::from_utf8(self.bytes(len)?)
+                    .map_err(|_| invalid("Invalid UTF-8 string in AMQP value"))?
+                    .to_string();
+                match code & 0x0f {
+                    0x01 => Value::String(s),
+                    _ => Value::Symbol(s),
+                }
+            }
+            0x45 => Value::List(Vec::new()),
+            0xc0 | 0xd0 | 0xc1 | 0xd1 | 0xe0 | 0xf0 => {
+                let size = self.size(code)?;
+                let mut reader = Reader(self.bytes(size)?);
+                let count = reader.size(code)?;
+                // Don't trust the count to preallocate
+                let mut items = Vec::new();
+                if code & 0xe0 == 0xe0 {
+                    let constructor = reader.constructor()?;
+                    for _ in 0..count {
+                        items.push(reader.data(&constructor)?);
+                    }
+                } else {
+                    for _ in 0..count {
+                        items.push(reader.value()?);
+                    }
+                }
+                match code & 0xef {
+                    0xc0 => Value::List(items),
+                    0xc1 => {
+                        if count % 2 != 0 {
+                            return Err(invalid("Odd number of elements in AMQP map"));
+                        }
+                        let mut items = items.into_iter();
+                        let mut entries = Vec::with_capacity(count / 2);
+                        while let (Some(key), Some(value)) = (items.next(), items.next()) {
+                            entries.push((key, value));
+                        }
+                        Value::Map(entries)
+                    }
+                    _ => Value::Array(items),
+                }
+            }
+            code => {
+                let data = match code >> 4 {
+                    0x4 => Vec::new(),
+                    0x5 => self.bytes(1)?.to_vec(),
+                    0x6 => self.bytes(2)?.to_vec(),
+                    0x7 => self.bytes(4)?.to_vec(),
+                    0x8 => self.bytes(8)?.to_vec(),
+                    0x9 => self.bytes(16)?.to_vec(),
+                    0xa | 0xc | 0xe => {
+                        let size = self.bytes(1)?;
+                        [size, self.bytes(size[0] as usize)?].concat()
+                    }
+                    0xb | 0xd | 0xf => {
+                        let size = self.bytes(4)?;
+                        let len = u32::from_be_bytes([size[0], size[1], size[2], size[3]]);
+                        [size, self.bytes(len as usize)?].concat()
+                    }
+                    _ => return Err(invalid(format!("Unknown AMQP format code {code:#x}"))),
+                };
+                Value::Other(code, data)
+            }
+        };
+        Ok(wrap(descriptor, value))
+    }
+}
+
+fn wrap(descriptor: &Option<Value>, value: Value) -> Value {
+    match descriptor {
+        Some(descriptor) => Value::Described(Box::new(descriptor.clone()), Box::new(value)),
+        None => value,
+    }
+}
+
+// Omits the trailing null fields of a list
+fn trimmed(mut fields: Vec<Value>) -> Vec<Value> {
+    while fields.last() == Some(&Value::Null) {
+        fields.pop();
+    }
+    fields
+}
+
+/// A performative, i.e. the described list carried by a frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Performative {
+    pub(crate) descriptor: u64,
+    pub(crate) fields: Vec<Value>,
+}
+
+impl Performative {
+    fn new(descriptor: u64, fields: Vec<Value>) -> Self {
+        Performative {
+            descriptor,
+            fields: trimmed(fields),
+        }
+    }
+
+    pub(crate) fn open(container_id: &str, hostname: &str) -> Self {
+        Self::new(
+            OPEN,
+            vec![
+                Value::String(container_id.into()),
+                Value::String(hostname.into()),
+                Value::Uint(MAX_FRAME_SIZE),
+            ],
+        )
+    }
+
+    pub(crate) fn begin(next_outgoing_id: u32, incoming_window: u32, outgoing_window: u32) -> Self {
+        Self::new(
+            BEGIN,
+            vec![
+                Value::Null,
+                Value::Uint(next_outgoing_id),
+                Value::Uint(incoming_window),
+                Value::Uint(outgoing_window),
+            ],
+        )
+    }
+
+    /// Attaches a link receiving the messages of `address` if `receiver` is true,
+    /// or sending settled messages to `address` otherwise.
+    pub(crate) fn attach(name: &str, handle: u32, receiver: bool, address: &str) -> Self {
+        let node = |descriptor| described(descriptor, vec![Value::String(address.into())]);
+        let (source, target, snd_settle_mode, initial_delivery_count) = if receiver {
+            (
+                node(SOURCE),
+                described(TARGET, vec![]),
+                SND_MIXED,
+                Value::Null,
+            )
+        } else {
+            (
+                described(SOURCE, vec![]),
+                node(TARGET),
+                SND_SETTLED,
+                Value::Uint(0),
+            )
+        };
+        Self::new(
+            ATTACH,
+            vec![
+                Value::String(name.into()),
+                Value::Uint(handle),
+                Value::Bool(receiver),
+                Value::Ubyte(snd_settle_mode),
+                Value::Ubyte(RCV_FIRST),
+                source,
+                target,
+                Value::Null,
+                Value::Null,
+                initial_delivery_count,
+            ],
+        )
+    }
+
+    /// Updates the flow state of the session, and of the link `handle` if any
+    /// with its delivery count and link credit.
+    pub(crate) fn flow(
+        next_incoming_id: u32,
+        incoming_window: u32,
+        next_outgoing_id: u32,
+        outgoing_window: u32,
+        link: Option<(u32, u32, u32)>,
+    ) -> Self {
+        let mut fields = vec![
+            Value::Uint(next_incoming_id),
+            Value::Uint(incoming_window),
+            Value::Uint(next_outgoing_id),
+            Value::Uint(outgoing_window),
+        ];
+        if let Some((handle, delivery_count, link_credit)) = link {
+            fields.extend([
+                Value::Uint(handle),
+                Value::Uint(delivery_count),
+                Value::Uint(link_credit),
+            ]);
+        }
+        Self::new(FLOW, fields)
+    }
+
+    fn settled_transfer(handle: u32, delivery_id: u32, more: bool) -> Self {
+        Self::new(
+            TRANSFER,
+            vec![
+                Value::Uint(handle),
+                Value::Uint(delivery_id),
+                Value::Binary(delivery_id.to_be_bytes().to_vec()),
+                Value::Uint(0),
+                Value::Bool(true),
+                Value::Bool(more),
+            ],
+        )
+    }
+
+    /// Accepts and settles the delivery `delivery_id` received by the bridge.
+    pub(crate) fn accept(delivery_id: u32) -> Self {
+        Self::new(
+            DISPOSITION,
+            vec![
+                Value::Bool(true),
+                Value::Uint(delivery_id),
+                Value::Null,
+                Value::Bool(true),
+                described(ACCEPTED, vec![]),
+            ],
+        )
+    }
+
+    pub(crate) fn detach(handle: u32) -> Self {
+        Self::new(DETACH, vec![Value::Uint(handle), Value::Bool(true)])
+    }
+
+    pub(crate) fn close() -> Self {
+        Self::new(CLOSE, vec![])
+    }
+
+    pub(crate) fn sasl_init(
+        mechanism: &str,
+        initial_response: Option<Vec<u8>>,
+        hostname: &str,
+    ) -> Self {
+        Self::new(
+            SASL_INIT,
+            vec![
+                Value::Symbol(mechanism.into()),
+                initial_response.map_or(Value::Null, Value::Binary),
+                Value::String(hostname.into()),
+            ],
+        )
+    }
+
+    /// The field at `index`, null if it was omitted.
+    pub(crate) fn field(&self, index: usize) -> &Value {
+        self.fields.get(index).unwrap_or(&Value::Null)
+    }
+
+    /// The description of the error carried by the field at `index`, if any.
+    pub(crate) fn error(&self, index: usize) -> Option<String> {
+        let Value::Described(descriptor, error) = self.field(index) else {
+            return None;
+        };
+        let (Some(ERROR), Value::List(fields)) = (descriptor.as_u64(), error.as_ref()) else {
+            return None;
+        };
+        let condition = fields.first().and_then(Value::as_str).unwrap_or("unknown");
+        Some(match fields.get(1).and_then(Value::as_str) {
+            Some(description) => format!("{condition} ({description})"),
+            None => condition.to_string(),
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        described(self.descriptor, self.fields.clone()).encode(&mut buf);
+        buf
+    }
+
+    pub(crate) fn frame(&self, channel: u16) -> Vec<u8> {
+        frame(FRAME_AMQP, channel, &self.encode(), &[])
+    }
+
+    pub(crate) fn sasl_frame(&self) -> Vec<u8> {
+        frame(FRAME_SASL, 0, &self.encode(), &[])
+    }
+}
+
+/// The largest frame accepted by the bridge.
+pub(crate) const MAX_FRAME_SIZE: u32 = 65_536;
+
+/// An empty frame, keeping the connection alive.
+pub(crate) const EMPTY_FRAME: [u8; FRAME_HEADER_SIZE] = [0, 0, 0, 8, 2, FRAME_AMQP, 0, 0];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Frame {
+    pub(crate) channel: u16,
+    /// The performative of the frame, `None` for empty frames.
+    pub(crate) performative: Option<Performative>,
+    pub(crate) payload: Vec<u8>,
+}
+
+fn frame(kind: u8, channel: u16, body: &[u8], payload: &[u8]) -> Vec<u8> {
+    let size = FRAME_HEADER_SIZE + body.len() + payload.len();
+    let mut buf = Vec::with_capacity(size);
+    buf.extend_from_slice(&(size as u32).to_be_bytes());
+    // The data offset is in 4 bytes words
+    buf.extend_from_slice(&[(FRAME_HEADER_SIZE / 4) as u8, kind]);
+    buf.extend_from_slice(&channel.to_be_bytes());
+    buf.extend_from_slice(body);
+    buf.extend_from_slice(payload);
+    buf
+}
+
+/// Encodes the transfer frames of the settled delivery `delivery_id` of `message` on the link `handle`,
+/// the message being split in several frames if it doesn't fit in `max_frame_size` bytes.
+pub(crate) fn transfer_frames(
+    channel: u16,
+    handle: u32,
+    delivery_id: u32,
+    message: &[u8],
+    max_frame_size: usize,
+) -> Vec<Vec<u8>> {
+    let body_size = Performative::settled_transfer(handle, delivery_id, true)
+        .encode()
+        .len();
+    let chunk_size = max_frame_size
+        .saturating_sub(FRAME_HEADER_SIZE + body_size)
+        .max(1);
+    let chunks: Vec<&[u8]> = if message.is_empty() {
+        vec![message]
+    } else {
+        message.chunks(chunk_size).collect()
+    };
+    let count = chunks.len();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let transfer = Performative::settled_transfer(handle, delivery_id, i + 1 < count);
+            frame(FRAME_AMQP, channel, &transfer.encode(), chunk)
+        })
+        .collect()
+}
+
+/// Reads the protocol header sent by the peer.
+pub(crate) async fn read_header<R: AsyncRead + Unpin>(reader: &mut R) -> Result<[u8; 8]> {
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header).await?;
+    Ok(header)
+}
+
+/// Reads the next frame from `reader`, returning `None` if the connection was closed between two frames.
+pub(crate) async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Frame>> {
+    let mut size = [0u8; 4];
+    if reader.read(&mut size[..1]).await? == 0 {
+        return Ok(None);
+    }
+    reader.read_exact(&mut size[1..]).await?;
+    let size = u32::from_be_bytes(size);
+    if (size as usize) < FRAME_HEADER_SIZE || size > MAX_FRAME_SIZE {
+        return Err(invalid(format!("Invalid AMQP frame size {size}")));
+    }
+    let mut frame = vec![0u8; size as usize - 4];
+    reader.read_exact(&mut frame).await?;
+    decode_frame(&frame).map(Some)
+}
+
+// Decodes a frame following its size
+fn decode_frame(frame: &[u8]) -> Result<Frame> {
+    let offset = frame[0] as usize * 4;
+    if offset < FRAME_HEADER_SIZE || offset - 4 > frame.len() {
+        return Err(invalid("Invalid AMQP frame data offset"));
+    }
+    let channel = u16::from_be_bytes([frame[2], frame[3]]);
+    let mut reader = Reader(&frame[offset - 4..]);
+    if reader.0.is_empty() {
+        return Ok(Frame {
+            channel,
+            performative: None,
+            payload: Vec::new(),
+        });
+    }
+    let Value::Described(descriptor, fields) = reader.value()? else {
+        return Err(invalid("Invalid AMQP performative"));
+    };
+    let (Some(descriptor), Value::List(fields)) = (descriptor.as_u64(), *fields) else {
+        return Err(invalid("Invalid AMQP performative"));
+    };
+    Ok(Frame {
+        channel,
+        performative: Some(Performative { descriptor, fields }),
+        payload: reader.0.to_vec(),
+    })
+}
+
+/// The parts of a message mapped to a zenoh sample.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Message {
+    pub(crate) subject: Option<String>,
+    pub(crate) content_type: Option<String>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Message {
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.body.len() + 64);
+        if self.subject.is_some() || self.content_type.is_some() {
+            let properties = vec![
+                Value::Null,
+                Value::Null,
+                Value::Null,
+                self.subject.clone().map_or(Value::Null, Value::String),
+                Value::Null,
+                Value::Null,
+                self.content_type.clone().map_or(Value::Null, Value::Symbol),
+            ];
+            described(PROPERTIES, trimmed(properties)).encode(&mut buf);
+        }
+        let data = Value::Binary(self.body.clone());
+        Value::Described(Box::new(Value::Ulong(DATA)), Box::new(data)).encode(&mut buf);
+        buf
+    }
+
+    /// Decodes a message, whose body is the concatenation of its data sections,
+    /// or its value if it is a binary or a string.
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader(bytes);
+        let mut message = Message::default();
+        while !reader.0.is_empty() {
+            let Value::Described(descriptor, section) = reader.value()? else {
+                return Err(invalid("Invalid AMQP message section"));
+            };
+            match (descriptor.as_u64(), *section) {
+                (Some(PROPERTIES), Value::List(fields)) => {
+                    let field = |i: usize| fields.get(i).and_then(Value::as_str).map(String::from);
+                    message.subject = field(3);
+                    message.content_type = field(6);
+                }
+                (Some(DATA), Value::Binary(data)) => message.body.extend_from_slice(&data),
+                (Some(AMQP_VALUE), Value::Binary(data)) => message.body = data,
+                (Some(AMQP_VALUE), Value::String(s)) => message.body = s.into_bytes(),
+                // The headers, annotations, application properties and footer aren't mapped
+                _ => (),
+            }
+        }
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(value: Value) {
+        let mut buf = Vec::new();
+        value.encode(&mut buf);
+        let mut reader = Reader(&buf);
+        assert_eq!(reader.value().unwrap(), value);
+        assert!(reader.0.is_empty());
+    }
+
+    #[test]
+    fn values_roundtrip() {
+        roundtrip(Value::Null);
+        roundtrip(Value::Bool(false));
+        roundtrip(Value::Ushort(0x1234));
+        for v in [0, 0x12, 0x1234_5678] {
+            roundtrip(Value::Uint(v));
+            roundtrip(Value::Ulong(v as u64));
+        }
+        roundtrip(Value::Binary(vec![7; 300]));
+        roundtrip(Value::Symbol("amqp:not-found".into()));
+        roundtrip(Value::List(vec![
+            Value::String("a".repeat(300)),
+            Value::List(vec![]),
+            Value::Map(vec![(Value::Symbol("k".into()), Value::Ubyte(1))]),
+            Value::Array(vec![Value::Bool(true), Value::Bool(false)]),
+            Value::Array(vec![
+                described(SOURCE, vec![Value::Uint(1)]),
+                described(SOURCE, vec![]),
+            ]),
+            // A timestamp
+            Value::Other(0x83, vec![0, 0, 1, 2, 3, 4, 5, 6]),
+            Value::Array(vec![Value::Other(0x83, vec![0; 8])]),
+        ]));
+    }
+
+    #[test]
+    fn decode_broker_frames() {
+        // sasl-mechanisms offering ANONYMOUS, as sent by a broker
+        let mut frame = vec![0x00, 0x00, 0x00, 0x1c, 0x02, 0x01, 0x00, 0x00];
+        frame.extend_from_slice(&[
+            0x00, 0x53, 0x40, 0xc0, 0x0f, 0x01, 0xe0, 0x0c, 0x01, 0xa3, 0x09,
+        ]);
+        frame.extend_from_slice(b"ANONYMOUS");
+        let frame = async_std::task::block_on(read_frame(&mut &frame[..]))
+            .unwrap()
+            .unwrap();
+        let performative = frame.performative.unwrap();
+        assert_eq!(performative.descriptor, SASL_MECHANISMS);
+        assert_eq!(
+            performative.field(0),
+            &Value::Array(vec![Value::Symbol("ANONYMOUS".into())])
+        );
+        assert_eq!(performative.field(1), &Value::Null);
+
+        let empty = async_std::task::block_on(read_frame(&mut &EMPTY_FRAME[..]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(empty.performative, None);
+        assert!(async_std::task::block_on(read_frame(&mut &[][..]))
+            .unwrap()
+            .is_none());
+        assert!(async_std::task::block_on(read_frame(&mut &[0, 0, 0, 4, 2, 0][..])).is_err());
+
+        let close = Performative::new(
+            CLOSE,
+            vec![described(
+                ERROR,
+                vec![
+                    Value::Symbol("amqp:unauthorized-access".into()),
+                    Value::String("denied".into()),
+                ],
+            )],
+        );
+        let frame = async_std::task::block_on(read_frame(&mut &close.frame(0)[..]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.performative.as_ref(), Some(&close));
+        assert_eq!(
+            close.error(0).as_deref(),
+            Some("amqp:unauthorized-access (denied)")
+        );
+    }
+
+    #[test]
+    fn messages_transfer() {
+        let message = Message {
+            subject: Some("line1/temp".into()),
+            content_type: Some("text/plain".into()),
+            body: vec![b'x'; 1000],
+        };
+        let encoded = message.encode();
+        assert_eq!(Message::decode(&encoded).unwrap(), message);
+
+        let frames = transfer_frames(0, 3, 7, &encoded, 512);
+        assert!(frames.len() > 1);
+        let mut payload = Vec::new();
+        for (i, bytes) in frames.iter().enumerate() {
+            assert!(bytes.len() <= 512);
+            let frame = async_std::task::block_on(read_frame(&mut &bytes[..]))
+                .unwrap()
+                .unwrap();
+            let transfer = frame.performative.unwrap();
+            assert_eq!(transfer.descriptor, TRANSFER);
+            assert_eq!(transfer.field(0).as_u32(), Some(3));
+            assert_eq!(transfer.field(1).as_u32(), Some(7));
+            assert_eq!(transfer.field(5).as_bool(), Some(i + 1 < frames.len()));
+            payload.extend_from_slice(&frame.payload);
+        }
+        assert_eq!(payload, encoded);
+
+        // A message sent with an amqp-value section
+        let mut bytes = Vec::new();
+        Value::Described(
+            Box::new(Value::Ulong(AMQP_VALUE)),
+            Box::new(Value::String("21.5".into())),
+        )
+        .encode(&mut bytes);
+        assert_eq!(Message::decode(&bytes).unwrap().body, b"21.5");
+    }
+}
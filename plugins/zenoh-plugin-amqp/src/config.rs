@@ -0,0 +1,87 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The address of the AMQP 1.0 broker, as `<host>:<port>`.
+    #[serde(default = "default_broker")]
+    pub broker: String,
+    /// The user name authenticating the bridge with SASL PLAIN, the bridge connecting with SASL ANONYMOUS if unset.
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+    /// The container id of the bridge, `zenoh-bridge-<zenoh id>` by default.
+    #[serde(default)]
+    pub container_id: Option<String>,
+    /// The number of messages the broker may send on each link before the bridge grants it more credit.
+    #[serde(default = "default_link_credit")]
+    pub link_credit: u32,
+    /// The delay in milliseconds before reconnecting to the broker after a connection failure.
+    #[serde(default = "default_reconnect_delay")]
+    pub reconnect_delay: u64,
+    /// The translation rules between AMQP addresses and zenoh key expressions.
+    #[serde(default)]
+    pub rules: Vec<RuleConfig>,
+    __path__: Option<String>,
+    __required__: Option<bool>,
+    __on_panic__: Option<String>,
+    __max_restarts__: Option<u32>,
+    __dependencies__: Option<Vec<String>>,
+    __config__: Option<String>,
+}
+
+/// A translation rule between an AMQP address and a zenoh key expression.
+///
+/// If `key_expr` ends with `/**`, the rest of the key expressions is carried by the subject of the
+/// AMQP messages (e.g. with `"factory/**"`, a message with the `line1/temp` subject is mapped to
+/// `factory/line1/temp` and back), the messages without subject being mapped to the prefix.
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    /// The address of the AMQP node, e.g. a queue or a topic.
+    pub address: String,
+    pub key_expr: String,
+    pub direction: Direction,
+}
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// The messages received from the AMQP address are put in zenoh.
+    ToZenoh,
+    /// The samples received by zenoh subscribers are sent to the AMQP address.
+    ToAmqp,
+}
+
+fn default_broker() -> String {
+    "localhost:5672".into()
+}
+
+fn default_link_credit() -> u32 {
+    100
+}
+
+fn default_reconnect_delay() -> u64 {
+    1000
+}
+
+impl From<&Config> for serde_json::Value {
+    fn from(c: &Config) -> Self {
+        serde_json::to_value(c).unwrap()
+    }
+}
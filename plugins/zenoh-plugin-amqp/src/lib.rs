@@ -0,0 +1,739 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! Runs an AMQP 1.0 client bridging the nodes of an AMQP broker with zenoh, following the configured
+//! translation rules: the messages received from the AMQP addresses are put in zenoh, and the samples
+//! received by zenoh subscribers are sent to the AMQP addresses.
+//! The bridge reconnects to the broker when the connection fails.
+use async_std::net::TcpStream;
+use async_std::prelude::FutureExt;
+use futures::AsyncWriteExt;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::plugins::{Metric, MetricKind, Plugin, RunningPluginTrait, TaskMonitor, ZenohPlugin};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod amqp;
+mod config;
+use amqp::{Message, Performative};
+pub use config::{Config, Direction, RuleConfig};
+
+const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
+lazy_static::lazy_static! {
+    static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
+}
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const OUTGOING_QUEUE_SIZE: usize = 256;
+// The session windows of the bridge, large enough to never limit the transfers
+const WINDOW: u32 = i32::MAX as u32;
+// The links of the bridge are all attached to the session of channel 0
+const CHANNEL: u16 = 0;
+
+zenoh_plugin_trait::declare_plugin!(AmqpPlugin);
+pub struct AmqpPlugin {}
+
+impl ZenohPlugin for AmqpPlugin {}
+
+impl Plugin for AmqpPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = zenoh::plugins::RunningPlugin;
+    const STATIC_NAME: &'static str = "amqp";
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+        // Try to initiate login.
+        // Required in case of dynamic lib, otherwise no logs.
+        // But cannot be done twice in case of static link.
+        let _ = env_logger::try_init();
+        log::debug!("AMQP plugin {}", LONG_VERSION.as_str());
+
+        let runtime_conf = runtime.config.lock();
+        let plugin_conf = runtime_conf
+            .plugin(name)
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+
+        let conf: Config = serde_json::from_value(plugin_conf.clone())
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        for rule in &conf.rules {
+            Rule::new(rule).map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        }
+        if conf.link_credit == 0 {
+            bail!(
+                "Plugin `{}` configuration error: link_credit must be positive",
+                name
+            )
+        }
+        let connected = Arc::new(AtomicBool::new(false));
+        let monitor = TaskMonitor::default();
+        let task = async_std::task::spawn(monitor.clone().watch(run(
+            runtime.clone(),
+            conf.clone(),
+            connected.clone(),
+        )));
+        let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
+        if let Ok(Err(e)) = task {
+            bail!("AMQP bridge failed within 1ms: {e}")
+        }
+        Ok(Box::new(RunningPlugin {
+            conf,
+            connected,
+            monitor,
+        }))
+    }
+}
+
+struct RunningPlugin {
+    conf: Config,
+    connected: Arc<AtomicBool>,
+    monitor: TaskMonitor,
+}
+
+impl RunningPluginTrait for RunningPlugin {
+    fn terminated(&self) -> Option<String> {
+        self.monitor.terminated()
+    }
+
+    fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
+        Arc::new(|_, _, _| {
+            bail!("zenoh-plugin-amqp doesn't accept any runtime configuration changes")
+        })
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<zenoh::plugins::Response>> {
+        let mut responses = Vec::new();
+        for (suffix, value) in [
+            ("/version", GIT_VERSION.into()),
+            ("/broker", self.conf.broker.as_str().into()),
+            ("/rules", serde_json::to_value(&self.conf.rules)?),
+            ("/connected", self.connected.load(Ordering::Relaxed).into()),
+        ] {
+            let key = format!("{plugin_status_key}{suffix}");
+            if keyexpr::new(key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(key, value))
+            }
+        }
+        Ok(responses)
+    }
+
+    fn metrics(&self) -> Vec<Metric> {
+        vec![Metric::new(
+            "zenoh_amqp_connected",
+            MetricKind::Gauge,
+            "Whether the bridge is connected to the AMQP broker.",
+            self.connected.load(Ordering::Relaxed) as u8 as f64,
+        )]
+    }
+}
+
+/// A translation rule between an AMQP address and a zenoh key expression.
+#[derive(Clone, Debug)]
+struct Rule {
+    address: String,
+    key_expr: OwnedKeyExpr,
+    // The prefix of the key expression if it ends with `**`, completed by the subjects of the messages
+    prefix: Option<String>,
+    direction: Direction,
+}
+
+impl Rule {
+    fn new(conf: &RuleConfig) -> ZResult<Self> {
+        let key_expr = OwnedKeyExpr::try_from(conf.key_expr.as_str()).map_err(|e| {
+            zerror!(
+                "invalid key expression of AMQP address {}: {}",
+                conf.address,
+                e
+            )
+        })?;
+        let prefix = match key_expr.as_str() {
+            "**" => Some(String::new()),
+            key_expr => key_expr.strip_suffix("/**").map(String::from),
+        };
+        let put_key_expr = prefix.as_deref().unwrap_or(key_expr.as_str());
+        if conf.direction == Direction::ToZenoh && put_key_expr.contains(['*', '$']) {
+            bail!(
+                "the messages of AMQP address {} can't be put on the wildcard key expression {}",
+                conf.address,
+                key_expr
+            )
+        }
+        Ok(Rule {
+            address: conf.address.clone(),
+            key_expr,
+            prefix,
+            direction: conf.direction,
+        })
+    }
+
+    /// The key expression on which a message received from the address is put.
+    fn put_key_expr(&self, subject: Option<&str>) -> ZResult<OwnedKeyExpr> {
+        let key_expr = match (self.prefix.as_deref(), subject) {
+            (Some(""), Some(subject)) => subject.to_string(),
+            (Some(prefix), Some(subject)) if !subject.is_empty() => format!("{prefix}/{subject}"),
+            (Some(""), None) => bail!("a message of {} has no subject", self.address),
+            (Some(prefix), _) => prefix.to_string(),
+            (None, _) => return Ok(self.key_expr.clone()),
+        };
+        let key_expr = OwnedKeyExpr::try_from(key_expr).map_err(|e| {
+            zerror!(
+                "a message of {} can't be mapped to zenoh: {}",
+                self.address,
+                e
+            )
+        })?;
+        if key_expr.is_wild() {
+            bail!(
+                "a message of {} can't be put on the wildcard key expression {}",
+                self.address,
+                key_expr
+            )
+        }
+        Ok(key_expr)
+    }
+
+    /// The subject of the message sent to the address for a sample of `key_expr`.
+    fn subject(&self, key_expr: &keyexpr) -> Option<String> {
+        match self.prefix.as_deref()? {
+            "" => Some(key_expr.to_string()),
+            prefix => key_expr
+                .as_str()
+                .strip_prefix(prefix)
+                .and_then(|subject| subject.strip_prefix('/'))
+                .map(String::from),
+        }
+    }
+}
+
+pub async fn run(runtime: Runtime, conf: Config, connected: Arc<AtomicBool>) -> ZResult<()> {
+    // Try to initiate login.
+    // Required in case of dynamic lib, otherwise no logs.
+    // But cannot be done twice in case of static link.
+    let _ = env_logger::try_init();
+
+    let rules = conf
+        .rules
+        .iter()
+        .map(Rule::new)
+        .collect::<ZResult<Vec<_>>>()?;
+    let session = Arc::new(zenoh::init(runtime).res().await.unwrap());
+    let container_id = conf
+        .container_id
+        .clone()
+        .unwrap_or_else(|| format!("zenoh-bridge-{}", session.zid()));
+    loop {
+        match bridge(&session, &conf, &container_id, &rules, &connected).await {
+            Ok(()) => log::info!("AMQP broker {} closed the connection", conf.broker),
+            Err(e) => log::warn!("AMQP connection to {} failed: {}", conf.broker, e),
+        }
+        connected.store(false, Ordering::Relaxed);
+        async_std::task::sleep(Duration::from_millis(conf.reconnect_delay)).await;
+    }
+}
+
+// The state negotiated with the broker when opening the connection and beginning the session
+struct Peer {
+    max_frame_size: usize,
+    idle_timeout: Option<Duration>,
+    next_outgoing_id: u32,
+    incoming_window: u32,
+}
+
+async fn expect_header(reader: &mut TcpStream, expected: [u8; 8]) -> ZResult<()> {
+    let header = amqp::read_header(reader).await?;
+    if header != expected {
+        bail!("Unsupported AMQP protocol header {:?}", header)
+    }
+    Ok(())
+}
+
+// Reads the next performative, failing if it isn't a `descriptor` one
+async fn expect(reader: &mut TcpStream, descriptor: u64) -> ZResult<Performative> {
+    loop {
+        let Some(frame) = amqp::read_frame(reader).await? else {
+            bail!("Connection closed by the AMQP broker")
+        };
+        match frame.performative {
+            None => continue,
+            Some(performative) if performative.descriptor == descriptor => return Ok(performative),
+            Some(performative) => match performative.error(0) {
+                Some(e) if matches!(performative.descriptor, amqp::END | amqp::CLOSE) => {
+                    bail!("AMQP broker closed the connection: {}", e)
+                }
+                _ => bail!("Unexpected AMQP performative {:?}", performative),
+            },
+        }
+    }
+}
+
+async fn handshake(
+    reader: &mut TcpStream,
+    writer: &mut TcpStream,
+    conf: &Config,
+    container_id: &str,
+    hostname: &str,
+) -> ZResult<Peer> {
+    writer.write_all(&amqp::SASL_HEADER).await?;
+    expect_header(reader, amqp::SASL_HEADER).await?;
+    let mechanisms = expect(reader, amqp::SASL_MECHANISMS).await?;
+    let offered: Vec<&str> = match mechanisms.field(0) {
+        amqp::Value::Array(mechanisms) => mechanisms.iter().filter_map(|m| m.as_str()).collect(),
+        mechanism => mechanism.as_str().into_iter().collect(),
+    };
+    let (mechanism, initial_response) = match &conf.username {
+        Some(username) => {
+            let password = conf.password.as_deref().unwrap_or_default();
+            (
+                "PLAIN",
+                Some(format!("\0{username}\0{password}").into_bytes()),
+            )
+        }
+        None => ("ANONYMOUS", None),
+    };
+    if !offered.contains(&mechanism) {
+        bail!(
+            "AMQP broker doesn't offer SASL {} authentication, only {:?}",
+            mechanism,
+            offered
+        )
+    }
+    let init = Performative::sasl_init(mechanism, initial_response, hostname);
+    writer.write_all(&init.sasl_frame()).await?;
+    let outcome = expect(reader, amqp::SASL_OUTCOME).await?;
+    if outcome.field(0).as_u64() != Some(amqp::SASL_OK as u64) {
+        bail!(
+            "SASL {} authentication failed with code {:?}",
+            mechanism,
+            outcome.field(0)
+        )
+    }
+
+    writer.write_all(&amqp::AMQP_HEADER).await?;
+    expect_header(reader, amqp::AMQP_HEADER).await?;
+    let open = Performative::open(container_id, hostname);
+    writer.write_all(&open.frame(CHANNEL)).await?;
+    let open = expect(reader, amqp::OPEN).await?;
+    let begin = Performative::begin(0, WINDOW, WINDOW);
+    writer.write_all(&begin.frame(CHANNEL)).await?;
+    let begin = expect(reader, amqp::BEGIN).await?;
+    Ok(Peer {
+        max_frame_size: open.field(2).as_u32().unwrap_or(u32::MAX) as usize,
+        idle_timeout: open
+            .field(4)
+            .as_u32()
+            .filter(|timeout| *timeout > 0)
+            .map(|timeout| Duration::from_millis(timeout as u64)),
+        next_outgoing_id: begin.field(1).as_u32().unwrap_or(0),
+        incoming_window: begin.field(2).as_u32().unwrap_or(0),
+    })
+}
+
+// The state of the session and links sending messages to the broker, shared with the zenoh subscribers
+struct Outgoing {
+    next_outgoing_id: u32,
+    remote_incoming_window: u32,
+    max_frame_size: usize,
+    next_delivery_id: u32,
+    // The link credit and delivery count of the sending links attached by the broker, by handle
+    links: HashMap<u32, (u32, u32)>,
+    tx: flume::Sender<Vec<u8>>,
+}
+
+impl Outgoing {
+    fn send(&mut self, handle: u32, message: &Message) -> ZResult<()> {
+        let Some((credit, delivery_count)) = self.links.get_mut(&handle) else {
+            bail!("the link isn't attached")
+        };
+        if *credit == 0 {
+            bail!("the broker granted no credit")
+        }
+        let frames = amqp::transfer_frames(
+            CHANNEL,
+            handle,
+            self.next_delivery_id,
+            &message.encode(),
+            self.max_frame_size,
+        );
+        let count = frames.len() as u32;
+        if count > self.remote_incoming_window {
+            bail!("the session window of the broker is full")
+        }
+        // The frames of a delivery are queued together to be sent in a row
+        self.tx
+            .try_send(frames.concat())
+            .map_err(|_| zerror!("the connection queue is full"))?;
+        *credit -= 1;
+        *delivery_count = delivery_count.wrapping_add(1);
+        self.next_delivery_id = self.next_delivery_id.wrapping_add(1);
+        self.next_outgoing_id = self.next_outgoing_id.wrapping_add(count);
+        self.remote_incoming_window -= count;
+        Ok(())
+    }
+
+    // Updates the session window of the broker, and the link credit of the sending link `handle`
+    fn flow(&mut self, flow: &Performative, handle: Option<u32>) {
+        // A null next-incoming-id is the initial next-outgoing-id of the bridge, i.e. 0
+        let next_incoming_id = flow.field(0).as_u32().unwrap_or(0);
+        if let Some(incoming_window) = flow.field(1).as_u32() {
+            self.remote_incoming_window = next_incoming_id
+                .wrapping_add(incoming_window)
+                .wrapping_sub(self.next_outgoing_id);
+        }
+        let link = handle.and_then(|handle| self.links.get_mut(&handle));
+        if let (Some((credit, delivery_count)), Some(link_credit)) = (link, flow.field(6).as_u32())
+        {
+            // A null delivery-count is the initial delivery count of the link, i.e. 0
+            let remote_delivery_count = flow.field(5).as_u32().unwrap_or(0);
+            *credit = remote_delivery_count
+                .wrapping_add(link_credit)
+                .wrapping_sub(*delivery_count);
+        }
+    }
+}
+
+// A delivery being received, possibly in several transfer frames
+struct Delivery {
+    id: u32,
+    settled: bool,
+    payload: Vec<u8>,
+}
+
+async fn put(session: &Session, rule: &Rule, payload: &[u8]) -> ZResult<()> {
+    let message = Message::decode(payload)?;
+    let key_expr = rule.put_key_expr(message.subject.as_deref())?;
+    let encoding = message
+        .content_type
+        .map_or_else(Encoding::default, Encoding::from);
+    session
+        .put(key_expr, message.body)
+        .encoding(encoding)
+        .res()
+        .await
+}
+
+async fn bridge(
+    session: &Arc<Session>,
+    conf: &Config,
+    container_id: &str,
+    rules: &[Rule],
+    connected: &AtomicBool,
+) -> ZResult<()> {
+    let stream = async_std::io::timeout(CONNECT_TIMEOUT, TcpStream::connect(&conf.broker)).await?;
+    let mut reader = stream.clone();
+    let mut writer = stream;
+    let hostname = conf
+        .broker
+        .rsplit_once(':')
+        .map_or(conf.broker.as_str(), |(host, _)| host);
+    let peer = handshake(&mut reader, &mut writer, conf, container_id, hostname)
+        .timeout(CONNECT_TIMEOUT)
+        .await
+        .map_err(|_| zerror!("AMQP handshake timed out"))??;
+
+    let mut names = HashMap::new();
+    for (handle, rule) in rules.iter().enumerate() {
+        let name = format!("{container_id}-{handle}");
+        let receiver = rule.direction == Direction::ToZenoh;
+        let attach = Performative::attach(&name, handle as u32, receiver, &rule.address);
+        writer.write_all(&attach.frame(CHANNEL)).await?;
+        names.insert(name, handle as u32);
+    }
+    connected.store(true, Ordering::Relaxed);
+    log::info!("Connected to AMQP broker {}", conf.broker);
+
+    let (tx, rx) = flume::bounded::<Vec<u8>>(OUTGOING_QUEUE_SIZE);
+    // Empty frames are sent to keep the connection alive if the broker has an idle timeout
+    let heartbeat = peer.idle_timeout.map(|timeout| timeout / 2);
+    let writer_task = async_std::task::spawn(async move {
+        loop {
+            let bytes = match heartbeat {
+                Some(heartbeat) => match rx.recv_async().timeout(heartbeat).await {
+                    Ok(Ok(bytes)) => bytes,
+                    Ok(Err(_)) => break,
+                    Err(_) => amqp::EMPTY_FRAME.to_vec(),
+                },
+                None => match rx.recv_async().await {
+                    Ok(bytes) => bytes,
+                    Err(_) => break,
+                },
+            };
+            if let Err(e) = writer.write_all(&bytes).await {
+                log::debug!("Error writing to AMQP broker: {}", e);
+                break;
+            }
+        }
+    });
+
+    let outgoing = Arc::new(Mutex::new(Outgoing {
+        next_outgoing_id: 0,
+        remote_incoming_window: peer.incoming_window,
+        max_frame_size: peer.max_frame_size,
+        next_delivery_id: 0,
+        links: HashMap::new(),
+        tx: tx.clone(),
+    }));
+    let mut subscribers = Vec::new();
+    for (handle, rule) in rules.iter().enumerate() {
+        if rule.direction != Direction::ToAmqp {
+            continue;
+        }
+        let outgoing = outgoing.clone();
+        let rule = rule.clone();
+        let subscriber = session
+            .declare_subscriber(rule.key_expr.clone())
+            // The samples put by the bridge aren't sent back to the broker
+            .allowed_origin(Locality::Remote)
+            .callback(move |sample| {
+                let encoding = sample.value.encoding.to_string();
+                let message = Message {
+                    subject: rule.subject(&sample.key_expr),
+                    content_type: Some(encoding).filter(|encoding| !encoding.is_empty()),
+                    body: sample.value.payload.contiguous().into_owned(),
+                };
+                if let Err(e) = outgoing.lock().unwrap().send(handle as u32, &message) {
+                    log::warn!(
+                        "Dropping sample of {} for AMQP address {}: {}",
+                        sample.key_expr,
+                        rule.address,
+                        e
+                    );
+                }
+            })
+            .res()
+            .await?;
+        subscribers.push(subscriber);
+    }
+
+    let mut next_incoming_id = peer.next_outgoing_id;
+    // The handles of the bridge, by handle of the broker
+    let mut handles: HashMap<u32, u32> = HashMap::new();
+    // The link credit and delivery count of the receiving links attached by the broker, by handle
+    let mut receivers: HashMap<u32, (u32, u32)> = HashMap::new();
+    let mut deliveries: HashMap<u32, Delivery> = HashMap::new();
+    let result: ZResult<()> = async {
+        loop {
+            let Some(frame) = amqp::read_frame(&mut reader).await? else {
+                bail!("Connection closed without close performative")
+            };
+            let Some(performative) = frame.performative else {
+                continue;
+            };
+            let remote_handle = performative.field(0).as_u32();
+            match performative.descriptor {
+                amqp::ATTACH => {
+                    let name = performative.field(0).as_str().unwrap_or_default();
+                    let (Some(&handle), Some(remote_handle)) =
+                        (names.get(name), performative.field(1).as_u32())
+                    else {
+                        bail!("Unexpected AMQP attach of link {}", name)
+                    };
+                    let rule = &rules[handle as usize];
+                    // A broker refusing a link attaches it without node before detaching it
+                    let node = match rule.direction {
+                        Direction::ToZenoh => performative.field(5),
+                        Direction::ToAmqp => performative.field(6),
+                    };
+                    if node == &amqp::Value::Null {
+                        continue;
+                    }
+                    handles.insert(remote_handle, handle);
+                    match rule.direction {
+                        Direction::ToZenoh => {
+                            let delivery_count = performative.field(9).as_u32().unwrap_or(0);
+                            receivers.insert(handle, (conf.link_credit, delivery_count));
+                            let next_outgoing_id = outgoing.lock().unwrap().next_outgoing_id;
+                            let flow = Performative::flow(
+                                next_incoming_id,
+                                WINDOW,
+                                next_outgoing_id,
+                                WINDOW,
+                                Some((handle, delivery_count, conf.link_credit)),
+                            );
+                            tx.send_async(flow.frame(CHANNEL)).await?;
+                        }
+                        Direction::ToAmqp => {
+                            outgoing.lock().unwrap().links.insert(handle, (0, 0));
+                        }
+                    }
+                    log::debug!(
+                        "AMQP address {} bridged with {}",
+                        rule.address,
+                        rule.key_expr
+                    );
+                }
+                amqp::FLOW => {
+                    let handle = performative
+                        .field(4)
+                        .as_u32()
+                        .and_then(|remote_handle| handles.get(&remote_handle).copied());
+                    outgoing.lock().unwrap().flow(&performative, handle);
+                }
+                amqp::TRANSFER => {
+                    next_incoming_id = next_incoming_id.wrapping_add(1);
+                    let Some(&handle) = remote_handle.and_then(|h| handles.get(&h)) else {
+                        bail!("AMQP transfer on unknown link {:?}", remote_handle)
+                    };
+                    if let Entry::Vacant(entry) = deliveries.entry(handle) {
+                        let Some(id) = performative.field(1).as_u32() else {
+                            bail!("AMQP transfer without delivery id")
+                        };
+                        entry.insert(Delivery {
+                            id,
+                            settled: false,
+                            payload: Vec::new(),
+                        });
+                        let Some((credit, delivery_count)) = receivers.get_mut(&handle) else {
+                            bail!("AMQP transfer on sending link {}", handle)
+                        };
+                        *credit = credit.saturating_sub(1);
+                        *delivery_count = delivery_count.wrapping_add(1);
+                        // The credit is granted again once half of it was used
+                        if *credit <= conf.link_credit / 2 {
+                            *credit = conf.link_credit;
+                            let next_outgoing_id = outgoing.lock().unwrap().next_outgoing_id;
+                            let flow = Performative::flow(
+                                next_incoming_id,
+                                WINDOW,
+                                next_outgoing_id,
+                                WINDOW,
+                                Some((handle, *delivery_count, *credit)),
+                            );
+                            tx.send_async(flow.frame(CHANNEL)).await?;
+                        }
+                    }
+                    let delivery = deliveries.get_mut(&handle).unwrap();
+                    delivery.settled |= performative.field(4).as_bool().unwrap_or(false);
+                    delivery.payload.extend_from_slice(&frame.payload);
+                    if performative.field(9).as_bool() == Some(true) {
+                        deliveries.remove(&handle);
+                        continue;
+                    }
+                    if performative.field(5).as_bool() == Some(true) {
+                        continue;
+                    }
+                    let delivery = deliveries.remove(&handle).unwrap();
+                    let rule = &rules[handle as usize];
+                    if let Err(e) = put(session, rule, &delivery.payload).await {
+                        log::warn!(
+                            "Error putting message of AMQP address {}: {}",
+                            rule.address,
+                            e
+                        );
+                    }
+                    if !delivery.settled {
+                        let accept = Performative::accept(delivery.id);
+                        tx.send_async(accept.frame(CHANNEL)).await?;
+                    }
+                }
+                amqp::DETACH => {
+                    let Some(handle) = remote_handle.and_then(|h| handles.remove(&h)) else {
+                        continue;
+                    };
+                    receivers.remove(&handle);
+                    deliveries.remove(&handle);
+                    outgoing.lock().unwrap().links.remove(&handle);
+                    log::warn!(
+                        "AMQP broker detached the link of address {}: {}",
+                        rules[handle as usize].address,
+                        performative.error(2).as_deref().unwrap_or("no error")
+                    );
+                    tx.send_async(Performative::detach(handle).frame(CHANNEL))
+                        .await?;
+                }
+                amqp::END | amqp::CLOSE => {
+                    let _ = tx.try_send(Performative::close().frame(CHANNEL));
+                    match performative.error(0) {
+                        Some(e) => bail!("AMQP broker closed the connection: {}", e),
+                        None => return Ok(()),
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    .await;
+    drop(subscribers);
+    drop(outgoing);
+    drop(tx);
+    // The writer task ends once the last frames are sent, the subscribers being undeclared
+    let _ = writer_task.timeout(CONNECT_TIMEOUT).await;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(key_expr: &str, direction: Direction) -> ZResult<Rule> {
+        Rule::new(&RuleConfig {
+            address: "factory.telemetry".into(),
+            key_expr: key_expr.into(),
+            direction,
+        })
+    }
+
+    #[test]
+    fn rules_mapping() {
+        let prefixed = rule("factory/**", Direction::ToZenoh).unwrap();
+        assert_eq!(
+            prefixed.put_key_expr(Some("line1/temp")).unwrap().as_str(),
+            "factory/line1/temp"
+        );
+        assert_eq!(prefixed.put_key_expr(None).unwrap().as_str(), "factory");
+        assert!(prefixed.put_key_expr(Some("line1/*")).is_err());
+        assert!(prefixed.put_key_expr(Some("line1//temp")).is_err());
+        assert_eq!(
+            prefixed
+                .subject(keyexpr::new("factory/line1/temp").unwrap())
+                .as_deref(),
+            Some("line1/temp")
+        );
+        assert_eq!(prefixed.subject(keyexpr::new("factoryx/a").unwrap()), None);
+
+        let exact = rule("factory/line1", Direction::ToZenoh).unwrap();
+        assert_eq!(
+            exact.put_key_expr(Some("temp")).unwrap().as_str(),
+            "factory/line1"
+        );
+        assert_eq!(exact.subject(keyexpr::new("factory/line1").unwrap()), None);
+
+        let all = rule("**", Direction::ToAmqp).unwrap();
+        assert_eq!(
+            all.subject(keyexpr::new("a/b").unwrap()).as_deref(),
+            Some("a/b")
+        );
+        let all = rule("**", Direction::ToZenoh).unwrap();
+        assert_eq!(all.put_key_expr(Some("a/b")).unwrap().as_str(), "a/b");
+        assert!(all.put_key_expr(None).is_err());
+        assert!(rule("factory/*/temp", Direction::ToZenoh).is_err());
+        assert!(rule("factory/*/temp", Direction::ToAmqp).is_ok());
+        assert!(rule("factory//temp", Direction::ToAmqp).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub provider: Provider,
+    /// The host name of the MQTT endpoint of the provider, e.g. `<prefix>-ats.iot.<region>.amazonaws.com`
+    /// for AWS IoT or `<hub>.azure-devices.net` for Azure IoT Hub.
+    pub endpoint: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// The MQTT client identifier: the thing name for AWS IoT, the device id for Azure IoT Hub.
+    pub client_id: String,
+    /// The PEM file of the X.509 certificate authenticating the bridge.
+    #[serde(default)]
+    pub certificate: Option<String>,
+    /// The PEM file of the private key of `certificate`.
+    #[serde(default)]
+    pub private_key: Option<String>,
+    /// A PEM file of root certificates trusted in addition to the webpki ones.
+    #[serde(default)]
+    pub root_ca: Option<String>,
+    /// The base64 shared access key of the Azure IoT Hub device, authenticating the bridge with SAS tokens.
+    #[serde(default, skip_serializing)]
+    pub shared_access_key: Option<String>,
+    /// The validity in seconds of the SAS tokens, the bridge reconnecting with a new token when it expires.
+    #[serde(default = "default_sas_ttl")]
+    pub sas_ttl: u64,
+    /// The key expressions of the samples forwarded to the cloud.
+    pub key_exprs: Vec<String>,
+    /// The prefix of the AWS IoT topics, the samples of `<key>` being published on `<topic_prefix>/<key>`.
+    /// Azure IoT Hub devices publish on their events topic, the key being the `zenoh_key` property.
+    #[serde(default)]
+    pub topic_prefix: Option<String>,
+    /// The MQTT QoS of the publications, 0 or 1.
+    #[serde(default = "default_qos")]
+    pub qos: u8,
+    /// The MQTT keep alive in seconds.
+    #[serde(default = "default_keep_alive")]
+    pub keep_alive: u16,
+    /// The number of samples buffered while the endpoint is unreachable, the oldest being dropped beyond.
+    #[serde(default = "default_buffer_size")]
+    pub buffer_size: usize,
+    /// The maximal number of publications per second, unlimited if unset.
+    #[serde(default)]
+    pub max_rate: Option<f64>,
+    /// The number of publications which can exceed `max_rate` in a burst, `max_rate` by default.
+    #[serde(default)]
+    pub burst: Option<u32>,
+    /// The delay in milliseconds before reconnecting to the endpoint after a connection failure.
+    #[serde(default = "default_reconnect_delay")]
+    pub reconnect_delay: u64,
+    __path__: Option<String>,
+    __required__: Option<bool>,
+    __on_panic__: Option<String>,
+    __max_restarts__: Option<u32>,
+    __dependencies__: Option<Vec<String>>,
+    __config__: Option<String>,
+}
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    /// AWS IoT Core, authenticating with an X.509 certificate.
+    Aws,
+    /// Azure IoT Hub, authenticating with an X.509 certificate or a shared access key.
+    Azure,
+}
+
+fn default_port() -> u16 {
+    8883
+}
+
+fn default_sas_ttl() -> u64 {
+    3600
+}
+
+fn default_qos() -> u8 {
+    1
+}
+
+fn default_keep_alive() -> u16 {
+    60
+}
+
+fn default_buffer_size() -> usize {
+    10000
+}
+
+fn default_reconnect_delay() -> u64 {
+    1000
+}
+
+impl From<&Config> for serde_json::Value {
+    fn from(c: &Config) -> Self {
+        serde_json::to_value(c).unwrap()
+    }
+}
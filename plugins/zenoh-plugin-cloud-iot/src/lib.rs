@@ -0,0 +1,581 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! Forwards the samples of the configured key expressions to the MQTT endpoint of a cloud IoT
+//! provider, AWS IoT Core or Azure IoT Hub, authenticating with an X.509 certificate or a SAS token.
+//! The samples are buffered while the endpoint is unreachable, and their publications can be
+//! shaped to a maximal rate.
+use async_rustls::TlsConnector;
+use async_std::net::TcpStream;
+use async_std::prelude::FutureExt;
+use futures::io::WriteHalf;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use rustls::ServerName;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh::plugins::{Metric, MetricKind, Plugin, RunningPluginTrait, TaskMonitor, ZenohPlugin};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod config;
+mod mqtt;
+mod provider;
+pub use config::{Config, Provider};
+use mqtt::{Connect, Packet, Publish};
+
+const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
+lazy_static::lazy_static! {
+    static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
+}
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+// The number of QoS 1 publications awaiting their acknowledgement
+const MAX_INFLIGHT: usize = 64;
+
+zenoh_plugin_trait::declare_plugin!(CloudIotPlugin);
+pub struct CloudIotPlugin {}
+
+impl ZenohPlugin for CloudIotPlugin {}
+
+impl Plugin for CloudIotPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = zenoh::plugins::RunningPlugin;
+    const STATIC_NAME: &'static str = "cloud_iot";
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+        // Try to initiate login.
+        // Required in case of dynamic lib, otherwise no logs.
+        // But cannot be done twice in case of static link.
+        let _ = env_logger::try_init();
+        log::debug!("Cloud IoT plugin {}", LONG_VERSION.as_str());
+
+        let runtime_conf = runtime.config.lock();
+        let plugin_conf = runtime_conf
+            .plugin(name)
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+
+        let conf: Config = serde_json::from_value(plugin_conf.clone())
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let tls = validate(&conf)
+            .and_then(|_| provider::tls_connector(&conf))
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let state = Arc::new(State::new(conf.buffer_size));
+        let monitor = TaskMonitor::default();
+        let task = async_std::task::spawn(monitor.clone().watch(run(
+            runtime.clone(),
+            conf.clone(),
+            tls,
+            state.clone(),
+        )));
+        let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
+        if let Ok(Err(e)) = task {
+            bail!("Cloud IoT uplink failed within 1ms: {e}")
+        }
+        Ok(Box::new(RunningPlugin {
+            conf,
+            state,
+            monitor,
+        }))
+    }
+}
+
+struct RunningPlugin {
+    conf: Config,
+    state: Arc<State>,
+    monitor: TaskMonitor,
+}
+
+impl RunningPluginTrait for RunningPlugin {
+    fn terminated(&self) -> Option<String> {
+        self.monitor.terminated()
+    }
+
+    fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
+        Arc::new(|_, _, _| {
+            bail!("zenoh-plugin-cloud-iot doesn't accept any runtime configuration changes")
+        })
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<zenoh::plugins::Response>> {
+        let mut responses = Vec::new();
+        for (suffix, value) in [
+            ("/version", GIT_VERSION.into()),
+            ("/endpoint", self.conf.endpoint.as_str().into()),
+            ("/key_exprs", serde_json::to_value(&self.conf.key_exprs)?),
+            (
+                "/connected",
+                self.state.connected.load(Ordering::Relaxed).into(),
+            ),
+            ("/buffered", self.state.len().into()),
+        ] {
+            let key = format!("{plugin_status_key}{suffix}");
+            if keyexpr::new(key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(key, value))
+            }
+        }
+        Ok(responses)
+    }
+
+    fn metrics(&self) -> Vec<Metric> {
+        let state = &self.state;
+        vec![
+            Metric::new(
+                "zenoh_cloud_iot_connected",
+                MetricKind::Gauge,
+                "Whether the bridge is connected to the cloud IoT endpoint.",
+                state.connected.load(Ordering::Relaxed) as u8 as f64,
+            ),
+            Metric::new(
+                "zenoh_cloud_iot_buffered",
+                MetricKind::Gauge,
+                "The number of samples waiting to be published.",
+                state.len() as f64,
+            ),
+            Metric::new(
+                "zenoh_cloud_iot_published_total",
+                MetricKind::Counter,
+                "The number of samples published to the cloud IoT endpoint.",
+                state.published.load(Ordering::Relaxed) as f64,
+            ),
+            Metric::new(
+                "zenoh_cloud_iot_dropped_total",
+                MetricKind::Counter,
+                "The number of samples dropped from the full buffer.",
+                state.dropped.load(Ordering::Relaxed) as f64,
+            ),
+        ]
+    }
+}
+
+fn validate(conf: &Config) -> ZResult<()> {
+    if conf.qos > 1 {
+        bail!("qos must be 0 or 1")
+    }
+    if conf.key_exprs.is_empty() {
+        bail!("key_exprs can't be empty")
+    }
+    for key_expr in &conf.key_exprs {
+        KeyExpr::try_from(key_expr.as_str())
+            .map_err(|e| zerror!("invalid key expression {}: {}", key_expr, e))?;
+    }
+    match conf.provider {
+        Provider::Aws if conf.certificate.is_none() => {
+            bail!("AWS IoT requires a certificate and its private_key")
+        }
+        Provider::Azure if conf.certificate.is_none() && conf.shared_access_key.is_none() => {
+            bail!(
+                "Azure IoT Hub requires a shared_access_key, or a certificate and its private_key"
+            )
+        }
+        _ => {}
+    }
+    if conf.buffer_size == 0 || conf.sas_ttl == 0 {
+        bail!("buffer_size and sas_ttl must be positive")
+    }
+    if conf
+        .max_rate
+        .map_or(false, |rate| !(rate > 0.0 && rate.is_finite()))
+    {
+        bail!("max_rate must be positive")
+    }
+    if conf.burst == Some(0) {
+        bail!("burst must be positive")
+    }
+    // checks the shared access key
+    provider::credentials(conf)?;
+    Ok(())
+}
+
+/// A message waiting to be published.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Message {
+    topic: String,
+    payload: Vec<u8>,
+}
+
+/// The state shared by the subscribers, the uplink and the admin space.
+struct State {
+    // The messages waiting to be published, the oldest first
+    queue: Mutex<VecDeque<Message>>,
+    capacity: usize,
+    // Notifies the uplink of the pushed messages
+    wake_tx: flume::Sender<()>,
+    wake_rx: flume::Receiver<()>,
+    connected: AtomicBool,
+    published: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl State {
+    fn new(capacity: usize) -> Self {
+        let (wake_tx, wake_rx) = flume::bounded(1);
+        State {
+            queue: Mutex::new(VecDeque::new()),
+            capacity,
+            wake_tx,
+            wake_rx,
+            connected: AtomicBool::new(false),
+            published: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    fn push(&self, message: Message) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.len() >= self.capacity {
+            queue.pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        queue.push_back(message);
+        drop(queue);
+        let _ = self.wake_tx.try_send(());
+    }
+
+    fn pop(&self) -> Option<Message> {
+        self.queue.lock().unwrap().pop_front()
+    }
+
+    /// Puts back `messages`, which weren't published, at the head of the queue.
+    fn requeue(&self, messages: impl DoubleEndedIterator<Item = Message>) {
+        let mut queue = self.queue.lock().unwrap();
+        for message in messages.rev() {
+            queue.push_front(message);
+        }
+        while queue.len() > self.capacity {
+            queue.pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A token bucket shaping the rate of the publications.
+struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    fn new(rate: f64, burst: Option<u32>) -> Self {
+        let burst = burst.map_or(rate.ceil(), |burst| burst as f64).max(1.0);
+        RateLimiter {
+            rate,
+            burst,
+            tokens: burst,
+            last: Instant::now(),
+        }
+    }
+
+    /// Takes a token if one is available, otherwise returns when the next one will be.
+    fn acquire(&mut self, now: Instant) -> Result<(), Instant> {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last = self.last.max(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(now + Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+async fn run(runtime: Runtime, conf: Config, tls: TlsConnector, state: Arc<State>) -> ZResult<()> {
+    // Try to initiate login.
+    // Required in case of dynamic lib, otherwise no logs.
+    // But cannot be done twice in case of static link.
+    let _ = env_logger::try_init();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+    let conf = Arc::new(conf);
+    let mut subscribers = Vec::new();
+    for key_expr in &conf.key_exprs {
+        let (conf, state) = (conf.clone(), state.clone());
+        let subscriber = session
+            .declare_subscriber(key_expr.as_str())
+            .callback(move |sample| {
+                if sample.kind == SampleKind::Put {
+                    state.push(Message {
+                        topic: provider::topic(&conf, &sample.key_expr, &sample.value.encoding),
+                        payload: sample.value.payload.contiguous().into_owned(),
+                    });
+                }
+            })
+            .res()
+            .await?;
+        subscribers.push(subscriber);
+    }
+
+    let mut limiter = conf.max_rate.map(|rate| RateLimiter::new(rate, conf.burst));
+    loop {
+        match uplink(&conf, &tls, &state, &mut limiter).await {
+            Ok(()) => log::debug!("Reconnecting to {}", conf.endpoint),
+            Err(e) => log::warn!("Cloud IoT connection to {} failed: {}", conf.endpoint, e),
+        }
+        state.connected.store(false, Ordering::Relaxed);
+        async_std::task::sleep(Duration::from_millis(conf.reconnect_delay)).await;
+    }
+}
+
+type Writer = WriteHalf<async_rustls::client::TlsStream<TcpStream>>;
+
+async fn send(writer: &mut Writer, packet: &Packet) -> ZResult<()> {
+    writer
+        .write_all(&packet.encode())
+        .timeout(WRITE_TIMEOUT)
+        .await
+        .map_err(|_| zerror!("MQTT write timed out"))??;
+    Ok(())
+}
+
+/// Connects to the endpoint and publishes the buffered messages, until the connection fails or its
+/// credentials expire.
+async fn uplink(
+    conf: &Config,
+    tls: &TlsConnector,
+    state: &State,
+    limiter: &mut Option<RateLimiter>,
+) -> ZResult<()> {
+    let credentials = provider::credentials(conf)?;
+    let stream = TcpStream::connect((conf.endpoint.as_str(), conf.port))
+        .timeout(CONNECT_TIMEOUT)
+        .await
+        .map_err(|_| zerror!("Connection timed out"))??;
+    let domain = ServerName::try_from(conf.endpoint.as_str())
+        .map_err(|e| zerror!("{}: {}", conf.endpoint, e))?;
+    let stream = tls
+        .connect(domain, stream)
+        .timeout(CONNECT_TIMEOUT)
+        .await
+        .map_err(|_| zerror!("TLS handshake timed out"))??;
+    let (mut reader, mut writer) = stream.split();
+    let connect = Connect {
+        keep_alive: conf.keep_alive,
+        client_id: conf.client_id.clone(),
+        username: credentials.username,
+        password: credentials.password,
+    };
+    send(&mut writer, &Packet::Connect(connect)).await?;
+    match mqtt::read_packet(&mut reader)
+        .timeout(CONNECT_TIMEOUT)
+        .await
+        .map_err(|_| zerror!("MQTT CONNACK timed out"))??
+    {
+        Some(Packet::ConnAck { return_code: 0 }) => {}
+        Some(Packet::ConnAck { return_code }) => {
+            bail!("Connection refused: {}", mqtt::connack_reason(return_code))
+        }
+        Some(packet) => bail!("Unexpected MQTT packet {:?}", packet),
+        None => bail!("Connection closed"),
+    }
+    log::info!("Connected to {}", conf.endpoint);
+    state.connected.store(true, Ordering::Relaxed);
+
+    let (packets_tx, packets) = flume::unbounded();
+    let reader_task = async_std::task::spawn(async move {
+        loop {
+            let packet = mqtt::read_packet(&mut reader).await;
+            let closed = !matches!(packet, Ok(Some(_)));
+            if packets_tx.send(packet).is_err() || closed {
+                break;
+            }
+        }
+    });
+    let expiry = credentials.ttl.map(|ttl| Instant::now() + ttl);
+    let mut inflight = VecDeque::new();
+    let result = publish(
+        conf,
+        state,
+        limiter,
+        &mut writer,
+        &packets,
+        expiry,
+        &mut inflight,
+    )
+    .await;
+    reader_task.cancel().await;
+    // the unacknowledged messages are published again after reconnecting
+    state.requeue(inflight.into_iter().map(|(_, message)| message));
+    result
+}
+
+enum Event {
+    Packet(std::io::Result<Option<Packet>>),
+    Wake,
+    Timeout,
+}
+
+async fn publish(
+    conf: &Config,
+    state: &State,
+    limiter: &mut Option<RateLimiter>,
+    writer: &mut Writer,
+    packets: &flume::Receiver<std::io::Result<Option<Packet>>>,
+    expiry: Option<Instant>,
+    inflight: &mut VecDeque<(u16, Message)>,
+) -> ZResult<()> {
+    let keep_alive = Duration::from_secs(conf.keep_alive as u64);
+    let mut last_sent = Instant::now();
+    let mut ping_pending = false;
+    let mut packet_id = 0u16;
+    loop {
+        // publishes the buffered messages, as allowed by the window and the rate
+        let mut next_token = None;
+        while inflight.len() < MAX_INFLIGHT {
+            let Some(message) = state.pop() else {
+                break;
+            };
+            let now = Instant::now();
+            if let Some(Err(at)) = limiter.as_mut().map(|limiter| limiter.acquire(now)) {
+                state.requeue(std::iter::once(message));
+                next_token = Some(at);
+                break;
+            }
+            let id = (conf.qos > 0).then(|| {
+                packet_id = packet_id.checked_add(1).unwrap_or(1);
+                packet_id
+            });
+            let publish = Publish {
+                dup: false,
+                qos: conf.qos,
+                topic: message.topic.clone(),
+                packet_id: id,
+                payload: message.payload.clone(),
+            };
+            send(writer, &Packet::Publish(publish)).await?;
+            last_sent = now;
+            match id {
+                Some(id) => inflight.push_back((id, message)),
+                None => {
+                    state.published.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        let ping = (conf.keep_alive > 0).then_some(last_sent + keep_alive);
+        let deadline = [ping, next_token, expiry].into_iter().flatten().min();
+        let event = async { Event::Packet(packets.recv_async().await.unwrap_or(Ok(None))) }
+            .race(async {
+                let _ = state.wake_rx.recv_async().await;
+                Event::Wake
+            })
+            .race(async {
+                match deadline {
+                    Some(deadline) => {
+                        let timeout = deadline.saturating_duration_since(Instant::now());
+                        async_std::task::sleep(timeout).await
+                    }
+                    None => futures::future::pending().await,
+                }
+                Event::Timeout
+            })
+            .await;
+        match event {
+            Event::Packet(Ok(Some(Packet::PubAck(id)))) => {
+                if let Some(i) = inflight.iter().position(|(p, _)| *p == id) {
+                    inflight.remove(i);
+                    state.published.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Event::Packet(Ok(Some(Packet::PingResp))) => ping_pending = false,
+            Event::Packet(Ok(Some(packet))) => bail!("Unexpected MQTT packet {:?}", packet),
+            Event::Packet(Ok(None)) => bail!("Connection closed"),
+            Event::Packet(Err(e)) => return Err(e.into()),
+            Event::Wake => {}
+            Event::Timeout => {
+                let now = Instant::now();
+                if expiry.map_or(false, |expiry| now >= expiry) {
+                    log::debug!("The SAS token of {} expired", conf.client_id);
+                    let _ = send(writer, &Packet::Disconnect).await;
+                    return Ok(());
+                }
+                if conf.keep_alive > 0 && now >= last_sent + keep_alive {
+                    if ping_pending {
+                        bail!("No MQTT ping response")
+                    }
+                    send(writer, &Packet::PingReq).await?;
+                    ping_pending = true;
+                    last_sent = now;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(i: u8) -> Message {
+        Message {
+            topic: "t".into(),
+            payload: vec![i],
+        }
+    }
+
+    #[test]
+    fn buffering() {
+        let state = State::new(3);
+        for i in 0..5 {
+            state.push(message(i));
+        }
+        assert_eq!(state.len(), 3);
+        assert_eq!(state.dropped.load(Ordering::Relaxed), 2);
+        let popped = [state.pop().unwrap(), state.pop().unwrap()];
+        assert_eq!(popped, [message(2), message(3)]);
+        state.push(message(5));
+        state.push(message(6));
+        // the requeued messages are the oldest ones
+        state.requeue(popped.into_iter());
+        assert_eq!(state.len(), 3);
+        assert_eq!(state.pop(), Some(message(4)));
+        assert_eq!(state.dropped.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn rate_limiting() {
+        let mut limiter = RateLimiter::new(10.0, Some(2));
+        let now = limiter.last;
+        assert!(limiter.acquire(now).is_ok());
+        assert!(limiter.acquire(now).is_ok());
+        assert_eq!(limiter.acquire(now), Err(now + Duration::from_millis(100)));
+        assert!(limiter.acquire(now + Duration::from_millis(100)).is_ok());
+        assert!(limiter.acquire(now + Duration::from_millis(150)).is_err());
+        // the tokens don't accumulate beyond the burst
+        let later = now + Duration::from_secs(10);
+        assert!(limiter.acquire(later).is_ok());
+        assert!(limiter.acquire(later).is_ok());
+        assert!(limiter.acquire(later).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! A minimal codec for the MQTT 3.1.1 control packets of a publishing client.
+use futures::{AsyncRead, AsyncReadExt};
+use std::io::{Error, ErrorKind, Result};
+
+const CONNECT: u8 = 1;
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const PUBACK: u8 = 4;
+const PINGREQ: u8 = 12;
+const PINGRESP: u8 = 13;
+const DISCONNECT: u8 = 14;
+
+// The protocol level of MQTT 3.1.1
+const PROTOCOL_LEVEL: u8 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Connect {
+    pub(crate) keep_alive: u16,
+    pub(crate) client_id: String,
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Publish {
+    pub(crate) dup: bool,
+    pub(crate) qos: u8,
+    pub(crate) topic: String,
+    pub(crate) packet_id: Option<u16>,
+    pub(crate) payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Packet {
+    Connect(Connect),
+    ConnAck { return_code: u8 },
+    Publish(Publish),
+    PubAck(u16),
+    PingReq,
+    PingResp,
+    Disconnect,
+}
+
+/// The reason of the rejection of a connection, for the non-zero return codes of CONNACK.
+pub(crate) fn connack_reason(return_code: u8) -> &'static str {
+    match return_code {
+        1 => "unacceptable protocol version",
+        2 => "identifier rejected",
+        3 => "server unavailable",
+        4 => "bad user name or password",
+        5 => "not authorized",
+        _ => "unknown return code",
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+/// Reads the next packet from `reader`, returning `None` if the connection was closed between two packets.
+pub(crate) async fn read_packet<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Option<Packet>> {
+    let mut header = [0u8];
+    if reader.read(&mut header).await? == 0 {
+        return Ok(None);
+    }
+    let mut len = 0usize;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8];
+        reader.read_exact(&mut byte).await?;
+        len |= ((byte[0] & 0x7f) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+        if shift > 21 {
+            return Err(invalid("Malformed MQTT remaining length"));
+        }
+    }
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).await?;
+    decode(header[0], &body).map(Some)
+}
+
+fn decode(header: u8, body: &[u8]) -> Result<Packet> {
+    let packet_id = || match body {
+        [a, b] => Ok(u16::from_be_bytes([*a, *b])),
+        _ => Err(invalid("Invalid MQTT packet identifier")),
+    };
+    match header >> 4 {
+        CONNACK => match body {
+            [_, return_code] => Ok(Packet::ConnAck {
+                return_code: *return_code,
+            }),
+            _ => Err(invalid("Invalid MQTT CONNACK")),
+        },
+        PUBACK => Ok(Packet::PubAck(packet_id()?)),
+        PINGRESP => Ok(Packet::PingResp),
+        packet_type => Err(invalid(format!(
+            "Unexpected MQTT packet type from server: {packet_type}"
+        ))),
+    }
+}
+
+fn push_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+impl Packet {
+    /// Encodes a packet sent by the client side of the connection.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        let header = match self {
+            Packet::Connect(connect) => {
+                push_bytes(&mut body, b"MQTT");
+                body.push(PROTOCOL_LEVEL);
+                // always a clean session, the unacknowledged messages being republished
+                let mut flags = 0x02;
+                if connect.username.is_some() {
+                    flags |= 0x80;
+                }
+                if connect.password.is_some() {
+                    flags |= 0x40;
+                }
+                body.push(flags);
+                body.extend_from_slice(&connect.keep_alive.to_be_bytes());
+                push_bytes(&mut body, connect.client_id.as_bytes());
+                if let Some(username) = &connect.username {
+                    push_bytes(&mut body, username.as_bytes());
+                }
+                if let Some(password) = &connect.password {
+                    push_bytes(&mut body, password.as_bytes());
+                }
+                CONNECT << 4
+            }
+            Packet::Publish(publish) => {
+                push_bytes(&mut body, publish.topic.as_bytes());
+                if let Some(packet_id) = publish.packet_id {
+                    body.extend_from_slice(&packet_id.to_be_bytes());
+                }
+                body.extend_from_slice(&publish.payload);
+                (PUBLISH << 4) | ((publish.dup as u8) << 3) | (publish.qos << 1)
+            }
+            Packet::PingReq => PINGREQ << 4,
+            Packet::Disconnect => DISCONNECT << 4,
+            Packet::ConnAck { .. } | Packet::PubAck(_) | Packet::PingResp => {
+                unreachable!("{self:?} is never sent by the client")
+            }
+        };
+        let mut buf = Vec::with_capacity(body.len() + 5);
+        buf.push(header);
+        let mut len = body.len();
+        loop {
+            let mut byte = (len % 128) as u8;
+            len /= 128;
+            if len > 0 {
+                byte |= 0x80;
+            }
+            buf.push(byte);
+            if len == 0 {
+                break;
+            }
+        }
+        buf.extend_from_slice(&body);
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(bytes: &[u8]) -> Result<Option<Packet>> {
+        async_std::task::block_on(read_packet(&mut &bytes[..]))
+    }
+
+    #[test]
+    fn encode_client_packets() {
+        let connect = Packet::Connect(Connect {
+            keep_alive: 60,
+            client_id: "dev".into(),
+            username: Some("u".into()),
+            password: Some("p".into()),
+        });
+        assert_eq!(
+            connect.encode(),
+            [
+                0x10, 0x15, 0x00, 0x04, b'M', b'Q', b'T', b'T', 0x04, 0xc2, 0x00, 0x3c, 0x00, 0x03,
+                b'd', b'e', b'v', 0x00, 0x01, b'u', 0x00, 0x01, b'p',
+            ]
+        );
+        let publish = Packet::Publish(Publish {
+            dup: true,
+            qos: 1,
+            topic: "a/b".into(),
+            packet_id: Some(7),
+            payload: vec![0; 200],
+        });
+        let encoded = publish.encode();
+        assert_eq!(
+            encoded[..9],
+            [0x3a, 0xcf, 0x01, 0x00, 0x03, b'a', b'/', b'b', 0x00]
+        );
+        assert_eq!(encoded.len(), 3 + 207);
+        assert_eq!(Packet::PingReq.encode(), [0xc0, 0x00]);
+        assert_eq!(Packet::Disconnect.encode(), [0xe0, 0x00]);
+    }
+
+    #[test]
+    fn decode_server_packets() {
+        assert_eq!(
+            read(&[0x20, 0x02, 0x00, 0x05]).unwrap(),
+            Some(Packet::ConnAck { return_code: 5 })
+        );
+        assert_eq!(
+            read(&[0x40, 0x02, 0x01, 0x02]).unwrap(),
+            Some(Packet::PubAck(0x0102))
+        );
+        assert_eq!(read(&[0xd0, 0x00]).unwrap(), Some(Packet::PingResp));
+        assert_eq!(read(&[]).unwrap(), None);
+        assert!(read(&[0x40, 0x01, 0x01]).is_err());
+        assert!(read(&[0x90, 0x03, 0x00, 0x01, 0x00]).is_err());
+    }
+}
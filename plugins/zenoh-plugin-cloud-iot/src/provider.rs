@@ -0,0 +1,214 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! The provider-specific parts of the uplink: the authentication of the bridge and the topics.
+use crate::config::{Config, Provider};
+use async_rustls::TlsConnector;
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use hmac::{Hmac, Mac};
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore};
+use sha2::Sha256;
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zenoh::prelude::*;
+use zenoh_result::{bail, zerror, ZResult};
+
+// The version of the MQTT API of Azure IoT Hub
+const AZURE_API_VERSION: &str = "2021-04-12";
+
+/// The MQTT user name and password of the bridge.
+pub(crate) struct Credentials {
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<String>,
+    /// The validity of `password`, after which the bridge must reconnect with new credentials.
+    pub(crate) ttl: Option<Duration>,
+}
+
+pub(crate) fn credentials(conf: &Config) -> ZResult<Credentials> {
+    match (conf.provider, &conf.shared_access_key) {
+        (Provider::Aws, _) => Ok(Credentials {
+            username: None,
+            password: None,
+            ttl: None,
+        }),
+        (Provider::Azure, key) => {
+            let username = format!(
+                "{}/{}/?api-version={}",
+                conf.endpoint, conf.client_id, AZURE_API_VERSION
+            );
+            let (password, ttl) = match key {
+                Some(key) => {
+                    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                    let resource = format!("{}/devices/{}", conf.endpoint, conf.client_id);
+                    let token = sas_token(&resource, key, now + conf.sas_ttl)?;
+                    (Some(token), Some(Duration::from_secs(conf.sas_ttl)))
+                }
+                None => (None, None),
+            };
+            Ok(Credentials {
+                username: Some(username),
+                password,
+                ttl,
+            })
+        }
+    }
+}
+
+/// The SAS token granting access to `resource` until `expiry`, in seconds since the UNIX epoch,
+/// signed with `key`, a base64 shared access key.
+fn sas_token(resource: &str, key: &str, expiry: u64) -> ZResult<String> {
+    let key = b64_std_engine
+        .decode(key)
+        .map_err(|e| zerror!("Invalid shared access key: {}", e))?;
+    let resource = url_encode(resource);
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key).map_err(|e| zerror!("{}", e))?;
+    mac.update(format!("{resource}\n{expiry}").as_bytes());
+    let signature = b64_std_engine.encode(mac.finalize().into_bytes());
+    Ok(format!(
+        "SharedAccessSignature sr={}&sig={}&se={}",
+        resource,
+        url_encode(&signature),
+        expiry
+    ))
+}
+
+/// The topic on which the samples of `key_expr` are published.
+pub(crate) fn topic(conf: &Config, key_expr: &keyexpr, encoding: &Encoding) -> String {
+    match conf.provider {
+        Provider::Aws => match conf.topic_prefix.as_deref() {
+            Some(prefix) if !prefix.is_empty() => format!("{prefix}/{key_expr}"),
+            _ => key_expr.to_string(),
+        },
+        // the key and the encoding are passed as properties of the device-to-cloud messages
+        Provider::Azure => {
+            let mut topic = format!(
+                "devices/{}/messages/events/zenoh_key={}",
+                conf.client_id,
+                url_encode(key_expr.as_str())
+            );
+            if *encoding != Encoding::EMPTY {
+                topic.push_str("&$.ct=");
+                topic.push_str(&url_encode(&encoding.to_string()));
+            }
+            topic
+        }
+    }
+}
+
+fn url_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Returns a TLS connector trusting the webpki root certificates and `root_ca`, and
+/// authenticating the bridge with its X.509 certificate if configured.
+pub(crate) fn tls_connector(conf: &Config) -> ZResult<TlsConnector> {
+    let mut root_cert_store = RootCertStore::empty();
+    root_cert_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            ta.subject,
+            ta.spki,
+            ta.name_constraints,
+        )
+    }));
+    if let Some(root_ca) = &conf.root_ca {
+        let certs = rustls_pemfile::certs(&mut BufReader::new(read(root_ca)?.as_slice()))?;
+        root_cert_store.add_parsable_certificates(&certs);
+    }
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_cert_store);
+    let config = match (&conf.certificate, &conf.private_key) {
+        (Some(certificate), Some(private_key)) => {
+            let certs = rustls_pemfile::certs(&mut BufReader::new(read(certificate)?.as_slice()))?
+                .into_iter()
+                .map(Certificate)
+                .collect();
+            let key = rustls_pemfile::read_all(&mut BufReader::new(read(private_key)?.as_slice()))?
+                .into_iter()
+                .find_map(|item| match item {
+                    rustls_pemfile::Item::RSAKey(k)
+                    | rustls_pemfile::Item::PKCS8Key(k)
+                    | rustls_pemfile::Item::ECKey(k) => Some(PrivateKey(k)),
+                    _ => None,
+                })
+                .ok_or_else(|| zerror!("No private key found in {}", private_key))?;
+            builder
+                .with_client_auth_cert(certs, key)
+                .map_err(|e| zerror!("Bad certificate/key: {}", e))?
+        }
+        (None, None) => builder.with_no_client_auth(),
+        _ => bail!("`certificate` and `private_key` must be configured together"),
+    };
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+fn read(path: &str) -> ZResult<Vec<u8>> {
+    std::fs::read(path).map_err(|e| zerror!("Failed to read {}: {}", path, e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(provider: &str) -> Config {
+        serde_json::from_value(serde_json::json!({
+            "provider": provider,
+            "endpoint": "hub.azure-devices.net",
+            "client_id": "dev-1",
+            "key_exprs": ["factory/**"],
+            "topic_prefix": "edge",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn topics() {
+        let key_expr = keyexpr::new("factory/line 1/temp").unwrap();
+        assert_eq!(
+            topic(&config("aws"), key_expr, &Encoding::EMPTY),
+            "edge/factory/line 1/temp"
+        );
+        assert_eq!(
+            topic(&config("azure"), key_expr, &Encoding::EMPTY),
+            "devices/dev-1/messages/events/zenoh_key=factory%2Fline%201%2Ftemp"
+        );
+        assert_eq!(
+            topic(&config("azure"), key_expr, &KnownEncoding::AppJson.into()),
+            "devices/dev-1/messages/events/zenoh_key=factory%2Fline%201%2Ftemp&$.ct=application%2Fjson"
+        );
+    }
+
+    #[test]
+    fn sas_tokens() {
+        assert_eq!(
+            sas_token(
+                "hub.azure-devices.net/devices/dev-1",
+                "c2VjcmV0LWtleS0xMjM0NTY3ODkw",
+                1700000000
+            )
+            .unwrap(),
+            "SharedAccessSignature sr=hub.azure-devices.net%2Fdevices%2Fdev-1&sig=vbCgOg3X66GsavfG7jAYfVb%2F%2B3TsMSOQo4DKeRuOpF0%3D&se=1700000000"
+        );
+        assert!(sas_token("hub", "not base64!", 0).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! A minimal codec for the CoAP messages (RFC 7252), with the Observe option (RFC 7641).
+use std::io::{Error, ErrorKind, Result};
+
+const VERSION: u8 = 1;
+const PAYLOAD_MARKER: u8 = 0xff;
+
+pub(crate) const OBSERVE: u16 = 6;
+pub(crate) const URI_PATH: u16 = 11;
+pub(crate) const CONTENT_FORMAT: u16 = 12;
+pub(crate) const URI_QUERY: u16 = 15;
+
+pub(crate) const OBSERVE_REGISTER: u32 = 0;
+pub(crate) const OBSERVE_DEREGISTER: u32 = 1;
+
+/// Builds a code from its class and detail, e.g. `code(2, 5)` for 2.05.
+const fn code(class: u8, detail: u8) -> u8 {
+    (class << 5) | detail
+}
+
+pub(crate) const EMPTY: u8 = code(0, 0);
+pub(crate) const GET: u8 = code(0, 1);
+pub(crate) const POST: u8 = code(0, 2);
+pub(crate) const PUT: u8 = code(0, 3);
+pub(crate) const DELETE: u8 = code(0, 4);
+pub(crate) const DELETED: u8 = code(2, 2);
+pub(crate) const CHANGED: u8 = code(2, 4);
+pub(crate) const CONTENT: u8 = code(2, 5);
+pub(crate) const BAD_REQUEST: u8 = code(4, 0);
+pub(crate) const NOT_FOUND: u8 = code(4, 4);
+pub(crate) const METHOD_NOT_ALLOWED: u8 = code(4, 5);
+pub(crate) const INTERNAL_SERVER_ERROR: u8 = code(5, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MessageType {
+    Confirmable = 0,
+    NonConfirmable = 1,
+    Acknowledgement = 2,
+    Reset = 3,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Message {
+    pub(crate) mtype: MessageType,
+    pub(crate) code: u8,
+    pub(crate) message_id: u16,
+    pub(crate) token: Vec<u8>,
+    /// The options, sorted by number.
+    pub(crate) options: Vec<(u16, Vec<u8>)>,
+    pub(crate) payload: Vec<u8>,
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+/// Encodes an unsigned integer option value, with the minimal number of bytes.
+pub(crate) fn uint(value: u32) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    bytes[zeros..].to_vec()
+}
+
+fn decode_uint(bytes: &[u8]) -> Option<u32> {
+    if bytes.len() > 4 {
+        return None;
+    }
+    Some(bytes.iter().fold(0, |acc, b| (acc << 8) | *b as u32))
+}
+
+impl Message {
+    pub(crate) fn new(mtype: MessageType, code: u8, message_id: u16, token: Vec<u8>) -> Self {
+        Message {
+            mtype,
+            code,
+            message_id,
+            token,
+            options: Vec::new(),
+            payload: Vec::new(),
+        }
+    }
+
+    /// Adds an option, keeping the options sorted by number.
+    pub(crate) fn with_option(mut self, number: u16, value: Vec<u8>) -> Self {
+        let index = self.options.partition_point(|(n, _)| *n <= number);
+        self.options.insert(index, (number, value));
+        self
+    }
+
+    pub(crate) fn with_payload(mut self, payload: Vec<u8>) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    pub(crate) fn options(&self, number: u16) -> impl Iterator<Item = &[u8]> {
+        self.options
+            .iter()
+            .filter(move |(n, _)| *n == number)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    pub(crate) fn uint_option(&self, number: u16) -> Option<u32> {
+        self.options(number).next().and_then(decode_uint)
+    }
+
+    /// The path segments of the Uri-Path options.
+    pub(crate) fn path(&self) -> Result<Vec<&str>> {
+        self.options(URI_PATH)
+            .map(|segment| {
+                std::str::from_utf8(segment).map_err(|_| invalid("Invalid UTF-8 Uri-Path option"))
+            })
+            .collect()
+    }
+
+    /// The arguments of the Uri-Query options.
+    pub(crate) fn query(&self) -> Result<Vec<&str>> {
+        self.options(URI_QUERY)
+            .map(|arg| {
+                std::str::from_utf8(arg).map_err(|_| invalid("Invalid UTF-8 Uri-Query option"))
+            })
+            .collect()
+    }
+
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 4 {
+            return Err(invalid("Truncated CoAP message"));
+        }
+        if bytes[0] >> 6 != VERSION {
+            return Err(invalid(format!("Unknown CoAP version {}", bytes[0] >> 6)));
+        }
+        let mtype = match (bytes[0] >> 4) & 0x03 {
+            0 => MessageType::Confirmable,
+            1 => MessageType::NonConfirmable,
+            2 => MessageType::Acknowledgement,
+            _ => MessageType::Reset,
+        };
+        let token_len = (bytes[0] & 0x0f) as usize;
+        if token_len > 8 {
+            return Err(invalid("Invalid CoAP token length"));
+        }
+        let code = bytes[1];
+        let message_id = u16::from_be_bytes([bytes[2], bytes[3]]);
+        let mut rest = &bytes[4..];
+        if rest.len() < token_len {
+            return Err(invalid("Truncated CoAP message"));
+        }
+        let token = rest[..token_len].to_vec();
+        rest = &rest[token_len..];
+
+        let mut options = Vec::new();
+        let mut number = 0u16;
+        let payload = loop {
+            let Some((&byte, tail)) = rest.split_first() else {
+                break Vec::new();
+            };
+            rest = tail;
+            if byte == PAYLOAD_MARKER {
+                if rest.is_empty() {
+                    return Err(invalid("Empty CoAP payload after payload marker"));
+                }
+                break rest.to_vec();
+            }
+            let delta = extended(byte >> 4, &mut rest)?;
+            let len = extended(byte & 0x0f, &mut rest)? as usize;
+            if rest.len() < len {
+                return Err(invalid("Truncated CoAP option"));
+            }
+            number = number
+                .checked_add(delta)
+                .ok_or_else(|| invalid("Invalid CoAP option number"))?;
+            options.push((number, rest[..len].to_vec()));
+            rest = &rest[len..];
+        };
+        Ok(Message {
+            mtype,
+            code,
+            message_id,
+            token,
+            options,
+            payload,
+        })
+    }
+
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(4 + self.token.len() + self.payload.len() + 16);
+        buf.push((VERSION << 6) | ((self.mtype as u8) << 4) | self.token.len() as u8);
+        buf.push(self.code);
+        buf.extend_from_slice(&self.message_id.to_be_bytes());
+        buf.extend_from_slice(&self.token);
+        let mut number = 0;
+        for (n, value) in &self.options {
+            let (delta, delta_ext) = nibble(n - number);
+            let (len, len_ext) = nibble(value.len() as u16);
+            buf.push((delta << 4) | len);
+            buf.extend_from_slice(&delta_ext);
+            buf.extend_from_slice(&len_ext);
+            buf.extend_from_slice(value);
+            number = *n;
+        }
+        if !self.payload.is_empty() {
+            buf.push(PAYLOAD_MARKER);
+            buf.extend_from_slice(&self.payload);
+        }
+        buf
+    }
+}
+
+// Reads the extended value of an option delta or length nibble
+fn extended(nibble: u8, rest: &mut &[u8]) -> Result<u16> {
+    let (value, size) = match nibble {
+        13 => (rest.first().map(|b| *b as u16 + 13), 1),
+        14 => (
+            rest.get(..2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]).saturating_add(269)),
+            2,
+        ),
+        15 => return Err(invalid("Invalid CoAP option nibble")),
+        nibble => return Ok(nibble as u16),
+    };
+    let value = value.ok_or_else(|| invalid("Truncated CoAP option"))?;
+    *rest = &rest[size..];
+    Ok(value)
+}
+
+// Splits an option delta or length into its nibble and extended bytes
+fn nibble(value: u16) -> (u8, Vec<u8>) {
+    match value {
+        0..=12 => (value as u8, Vec::new()),
+        13..=268 => (13, vec![(value - 13) as u8]),
+        _ => (14, (value - 269).to_be_bytes().to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_roundtrip() {
+        let message = Message::new(MessageType::Confirmable, GET, 0x1234, vec![1, 2, 3])
+            .with_option(URI_PATH, b"sensors".to_vec())
+            .with_option(OBSERVE, uint(OBSERVE_REGISTER))
+            .with_option(URI_PATH, b"temp".to_vec())
+            .with_option(URI_QUERY, vec![b'q'; 300])
+            .with_payload(b"21.5".to_vec());
+        let decoded = Message::decode(&message.encode()).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(decoded.path().unwrap(), vec!["sensors", "temp"]);
+        assert_eq!(decoded.uint_option(OBSERVE), Some(OBSERVE_REGISTER));
+    }
+
+    #[test]
+    fn decode_client_messages() {
+        // CON GET /a with token 0x42, as sent by a CoAP client
+        let get = [0x41, 0x01, 0x00, 0x07, 0x42, 0xb1, b'a'];
+        let message = Message::decode(&get).unwrap();
+        assert_eq!(message.mtype, MessageType::Confirmable);
+        assert_eq!(message.code, GET);
+        assert_eq!(message.message_id, 7);
+        assert_eq!(message.token, vec![0x42]);
+        assert_eq!(message.path().unwrap(), vec!["a"]);
+        assert!(message.payload.is_empty());
+
+        assert!(Message::decode(&[0x41, 0x01, 0x00]).is_err());
+        assert!(Message::decode(&[0x81, 0x01, 0x00, 0x07, 0x42]).is_err());
+        assert!(Message::decode(&[0x40, 0x01, 0x00, 0x07, 0xff]).is_err());
+        assert_eq!(uint(0), Vec::<u8>::new());
+        assert_eq!(uint(0x1234), vec![0x12, 0x34]);
+    }
+}
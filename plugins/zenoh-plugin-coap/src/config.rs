@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use schemars::JsonSchema;
+use serde::de::{Unexpected, Visitor};
+use serde::{de, Deserialize, Deserializer};
+use std::fmt;
+
+const DEFAULT_COAP_INTERFACE: &str = "0.0.0.0";
+const DEFAULT_COAP_PORT: &str = "5683";
+
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(
+        default = "default_coap_port",
+        deserialize_with = "deserialize_coap_port"
+    )]
+    pub port: String,
+    /// A key expression prefixed to CoAP resource paths to build the corresponding zenoh key expressions
+    /// (e.g. with `"coap/demo"`, the `/sensor/temp` resource is mapped to `coap/demo/sensor/temp`).
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// The timeout in milliseconds of the zenoh queries answering CoAP GET requests,
+    /// which should be lower than the retransmission timeout of the CoAP clients.
+    #[serde(default = "default_query_timeout")]
+    pub query_timeout: u64,
+    /// The maximum number of CoAP observations, i.e. of zenoh subscribers declared by the gateway.
+    #[serde(default = "default_max_observers")]
+    pub max_observers: usize,
+    __path__: Option<String>,
+    __required__: Option<bool>,
+    __on_panic__: Option<String>,
+    __max_restarts__: Option<u32>,
+    __dependencies__: Option<Vec<String>>,
+    __config__: Option<String>,
+}
+
+fn default_query_timeout() -> u64 {
+    1500
+}
+
+fn default_max_observers() -> usize {
+    1024
+}
+
+impl From<&Config> for serde_json::Value {
+    fn from(c: &Config) -> Self {
+        serde_json::to_value(c).unwrap()
+    }
+}
+
+fn default_coap_port() -> String {
+    format!("{DEFAULT_COAP_INTERFACE}:{DEFAULT_COAP_PORT}")
+}
+
+fn deserialize_coap_port<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(CoapPortVisitor)
+}
+
+struct CoapPortVisitor;
+
+impl<'de> Visitor<'de> for CoapPortVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(r#"either a port number as an integer or a string, either a string with format "<local_ip>:<port_number>""#)
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(format!("{DEFAULT_COAP_INTERFACE}:{value}"))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let parts: Vec<&str> = value.split(':').collect();
+        if parts.len() > 2 {
+            return Err(E::invalid_value(Unexpected::Str(value), &self));
+        }
+        let (interface, port) = if parts.len() == 1 {
+            (DEFAULT_COAP_INTERFACE, parts[0])
+        } else {
+            (parts[0], parts[1])
+        };
+        if port.parse::<u32>().is_err() {
+            return Err(E::invalid_value(Unexpected::Str(port), &self));
+        }
+        Ok(format!("{interface}:{port}"))
+    }
+}
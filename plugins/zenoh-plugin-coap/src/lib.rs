@@ -0,0 +1,582 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! Runs a CoAP server endpoint bridging CoAP clients (e.g. constrained 6LoWPAN devices) with zenoh:
+//! GET requests are answered with zenoh queries, PUT and DELETE requests are put in zenoh,
+//! and GET requests registering an observation are mapped to zenoh subscribers.
+//! CoAP resource paths are mapped to key expressions prefixed with the configured `scope`.
+use async_std::net::UdpSocket;
+use async_std::prelude::FutureExt;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU16, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::plugins::{Metric, MetricKind, Plugin, RunningPluginTrait, TaskMonitor, ZenohPlugin};
+use zenoh::prelude::r#async::*;
+use zenoh::query::ConsolidationMode;
+use zenoh::runtime::Runtime;
+use zenoh::subscriber::Subscriber;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod coap;
+mod config;
+use coap::{Message, MessageType};
+pub use config::Config;
+
+const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
+lazy_static::lazy_static! {
+    static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
+}
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+// The number of responses kept to answer the retransmissions of the requests
+const EXCHANGES_SIZE: usize = 256;
+const NOTIFICATIONS_QUEUE_SIZE: usize = 256;
+
+zenoh_plugin_trait::declare_plugin!(CoapPlugin);
+pub struct CoapPlugin {}
+
+impl ZenohPlugin for CoapPlugin {}
+
+impl Plugin for CoapPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = zenoh::plugins::RunningPlugin;
+    const STATIC_NAME: &'static str = "coap";
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+        // Try to initiate login.
+        // Required in case of dynamic lib, otherwise no logs.
+        // But cannot be done twice in case of static link.
+        let _ = env_logger::try_init();
+        log::debug!("CoAP plugin {}", LONG_VERSION.as_str());
+
+        let runtime_conf = runtime.config.lock();
+        let plugin_conf = runtime_conf
+            .plugin(name)
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+
+        let conf: Config = serde_json::from_value(plugin_conf.clone())
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        if let Some(scope) = &conf.scope {
+            OwnedKeyExpr::try_from(scope.as_str()).map_err(|e| {
+                zerror!(
+                    "Plugin `{}` configuration error: invalid scope: {}",
+                    name,
+                    e
+                )
+            })?;
+        }
+        let observers = Arc::new(AtomicUsize::new(0));
+        let monitor = TaskMonitor::default();
+        let task = async_std::task::spawn(monitor.clone().watch(run(
+            runtime.clone(),
+            conf.clone(),
+            observers.clone(),
+        )));
+        let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
+        if let Ok(Err(e)) = task {
+            bail!("CoAP server failed within 1ms: {e}")
+        }
+        Ok(Box::new(RunningPlugin {
+            conf,
+            observers,
+            monitor,
+        }))
+    }
+}
+
+struct RunningPlugin {
+    conf: Config,
+    observers: Arc<AtomicUsize>,
+    monitor: TaskMonitor,
+}
+
+impl RunningPluginTrait for RunningPlugin {
+    fn terminated(&self) -> Option<String> {
+        self.monitor.terminated()
+    }
+
+    fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
+        Arc::new(|_, _, _| {
+            bail!("zenoh-plugin-coap doesn't accept any runtime configuration changes")
+        })
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<zenoh::plugins::Response>> {
+        let mut responses = Vec::new();
+        for (suffix, value) in [
+            ("/version", GIT_VERSION.into()),
+            ("/port", (&self.conf).into()),
+            ("/observers", self.observers.load(Ordering::Relaxed).into()),
+        ] {
+            let key = format!("{plugin_status_key}{suffix}");
+            if keyexpr::new(key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(key, value))
+            }
+        }
+        Ok(responses)
+    }
+
+    fn metrics(&self) -> Vec<Metric> {
+        vec![Metric::new(
+            "zenoh_coap_observers",
+            MetricKind::Gauge,
+            "Number of CoAP observations served by the gateway.",
+            self.observers.load(Ordering::Relaxed) as f64,
+        )]
+    }
+}
+
+// An observation registered by a CoAP client, notified by a zenoh subscriber
+struct Observation {
+    _subscriber: Subscriber<'static, ()>,
+    // The message id of the last notification, which a client resets to cancel the observation
+    last_message_id: Arc<AtomicU16>,
+}
+
+type ObservationId = (SocketAddr, Vec<u8>);
+
+// The responses to the last requests, resent when the requests are retransmitted.
+// A request being processed has no response yet, its retransmissions are ignored.
+#[derive(Default)]
+struct Exchanges {
+    responses: HashMap<(SocketAddr, u16), Option<Vec<u8>>>,
+    order: VecDeque<(SocketAddr, u16)>,
+}
+
+impl Exchanges {
+    // Starts the exchange of a request, failing with its response, if any, if it is a retransmission
+    fn start(&mut self, id: (SocketAddr, u16)) -> Result<(), Option<Vec<u8>>> {
+        if let Some(response) = self.responses.get(&id) {
+            return Err(response.clone());
+        }
+        if self.order.len() == EXCHANGES_SIZE {
+            if let Some(oldest) = self.order.pop_front() {
+                self.responses.remove(&oldest);
+            }
+        }
+        self.responses.insert(id, None);
+        self.order.push_back(id);
+        Ok(())
+    }
+
+    fn complete(&mut self, id: (SocketAddr, u16), response: Vec<u8>) {
+        if let Some(entry) = self.responses.get_mut(&id) {
+            *entry = Some(response);
+        }
+    }
+}
+
+struct Gateway {
+    session: Arc<Session>,
+    socket: Arc<UdpSocket>,
+    conf: Config,
+    message_ids: Arc<AtomicU16>,
+    exchanges: Mutex<Exchanges>,
+    observations: Arc<Mutex<HashMap<ObservationId, Observation>>>,
+    observers: Arc<AtomicUsize>,
+    notifications: flume::Sender<(SocketAddr, Vec<u8>)>,
+}
+
+pub async fn run(runtime: Runtime, conf: Config, observers: Arc<AtomicUsize>) -> ZResult<()> {
+    // Try to initiate login.
+    // Required in case of dynamic lib, otherwise no logs.
+    // But cannot be done twice in case of static link.
+    let _ = env_logger::try_init();
+
+    let session = Arc::new(zenoh::init(runtime).res().await.unwrap());
+    let socket = match UdpSocket::bind(conf.port.as_str()).await {
+        Ok(socket) => Arc::new(socket),
+        Err(e) => {
+            log::error!("Unable to start CoAP server on {}: {:?}", conf.port, e);
+            return Err(e.into());
+        }
+    };
+    // The notifications are sent by a dedicated task, as they are produced by the subscribers' callbacks
+    let (notifications, rx) = flume::bounded::<(SocketAddr, Vec<u8>)>(NOTIFICATIONS_QUEUE_SIZE);
+    async_std::task::spawn({
+        let socket = socket.clone();
+        async move {
+            while let Ok((addr, notification)) = rx.recv_async().await {
+                if let Err(e) = socket.send_to(&notification, addr).await {
+                    log::debug!("Error notifying CoAP client {}: {}", addr, e);
+                }
+            }
+        }
+    });
+    let gateway = Arc::new(Gateway {
+        session,
+        socket: socket.clone(),
+        conf,
+        message_ids: Arc::new(AtomicU16::new(0)),
+        exchanges: Mutex::new(Exchanges::default()),
+        observations: Arc::new(Mutex::new(HashMap::new())),
+        observers,
+        notifications,
+    });
+    let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+    loop {
+        let (len, addr) = socket.recv_from(&mut buf).await?;
+        let message = match Message::decode(&buf[..len]) {
+            Ok(message) => message,
+            Err(e) => {
+                log::debug!("Invalid CoAP message from {}: {}", addr, e);
+                continue;
+            }
+        };
+        let gateway = gateway.clone();
+        async_std::task::spawn(async move {
+            if let Err(e) = gateway.handle(addr, message).await {
+                log::warn!("Error handling CoAP message from {}: {}", addr, e);
+            }
+        });
+    }
+}
+
+/// Maps the path of a CoAP resource to a zenoh key expression.
+fn path_to_key_expr(scope: Option<&str>, path: &[&str]) -> ZResult<OwnedKeyExpr> {
+    let key_expr = match (scope, path.join("/")) {
+        (Some(scope), path) if path.is_empty() => scope.to_string(),
+        (Some(scope), path) => format!("{scope}/{path}"),
+        (None, path) => path,
+    };
+    OwnedKeyExpr::try_from(key_expr).map_err(|e| {
+        zerror!(
+            "CoAP resource /{} can't be mapped to zenoh: {}",
+            path.join("/"),
+            e
+        )
+        .into()
+    })
+}
+
+/// The CoAP Content-Format of a zenoh encoding, if it has one.
+fn content_format(encoding: &Encoding) -> Option<u32> {
+    match encoding.prefix() {
+        KnownEncoding::TextPlain | KnownEncoding::AppInteger | KnownEncoding::AppFloat => Some(0),
+        KnownEncoding::AppXml | KnownEncoding::TextXml => Some(41),
+        KnownEncoding::AppOctetStream => Some(42),
+        KnownEncoding::AppJson | KnownEncoding::TextJson => Some(50),
+        _ => None,
+    }
+}
+
+/// The zenoh encoding of a CoAP Content-Format.
+fn encoding(content_format: Option<u32>) -> Encoding {
+    match content_format {
+        Some(0) => KnownEncoding::TextPlain.into(),
+        Some(41) => KnownEncoding::AppXml.into(),
+        Some(42) => KnownEncoding::AppOctetStream.into(),
+        Some(50) => KnownEncoding::AppJson.into(),
+        _ => Encoding::default(),
+    }
+}
+
+impl Gateway {
+    fn next_message_id(&self) -> u16 {
+        self.message_ids.fetch_add(1, Ordering::Relaxed)
+    }
+
+    async fn handle(&self, addr: SocketAddr, request: Message) -> ZResult<()> {
+        match request.mtype {
+            MessageType::Reset => {
+                // a client rejecting a notification cancels its observation
+                let mut observations = self.observations.lock().unwrap();
+                let before = observations.len();
+                observations.retain(|(observer, _), observation| {
+                    *observer != addr
+                        || observation.last_message_id.load(Ordering::Relaxed) != request.message_id
+                });
+                self.observers
+                    .fetch_sub(before - observations.len(), Ordering::Relaxed);
+                return Ok(());
+            }
+            MessageType::Acknowledgement => return Ok(()),
+            MessageType::Confirmable | MessageType::NonConfirmable => (),
+        }
+        if request.code == coap::EMPTY {
+            // a CoAP ping
+            if request.mtype == MessageType::Confirmable {
+                let reset = Message::new(
+                    MessageType::Reset,
+                    coap::EMPTY,
+                    request.message_id,
+                    Vec::new(),
+                );
+                self.socket.send_to(&reset.encode(), addr).await?;
+            }
+            return Ok(());
+        }
+
+        let exchange = (addr, request.message_id);
+        let started = self.exchanges.lock().unwrap().start(exchange);
+        if let Err(response) = started {
+            if let Some(response) = response {
+                self.socket.send_to(&response, addr).await?;
+            }
+            return Ok(());
+        }
+        let (mtype, message_id) = match request.mtype {
+            MessageType::Confirmable => (MessageType::Acknowledgement, request.message_id),
+            _ => (MessageType::NonConfirmable, self.next_message_id()),
+        };
+        let response = Message::new(mtype, coap::CONTENT, message_id, request.token.clone());
+        let response = match self.respond(addr, &request, response).await {
+            Ok(response) => response,
+            Err((code, e)) => {
+                log::debug!("CoAP request from {} failed: {}", addr, e);
+                Message::new(mtype, code, message_id, request.token.clone())
+                    .with_payload(e.to_string().into_bytes())
+            }
+        }
+        .encode();
+        self.exchanges
+            .lock()
+            .unwrap()
+            .complete(exchange, response.clone());
+        self.socket.send_to(&response, addr).await?;
+        Ok(())
+    }
+
+    // Fills the response to a request, or returns the code of its failure
+    async fn respond(
+        &self,
+        addr: SocketAddr,
+        request: &Message,
+        mut response: Message,
+    ) -> Result<Message, (u8, zenoh_result::Error)> {
+        let bad_request = |e| (coap::BAD_REQUEST, e);
+        let path = request.path().map_err(|e| bad_request(e.into()))?;
+        let key_expr = path_to_key_expr(self.conf.scope.as_deref(), &path).map_err(bad_request)?;
+        match request.code {
+            coap::GET => {
+                let id = (addr, request.token.clone());
+                match request.uint_option(coap::OBSERVE) {
+                    Some(coap::OBSERVE_REGISTER) => {
+                        if let Some(sequence) = self.observe(id, &key_expr).await {
+                            response = response.with_option(coap::OBSERVE, coap::uint(sequence));
+                        }
+                    }
+                    Some(coap::OBSERVE_DEREGISTER) => {
+                        if self.observations.lock().unwrap().remove(&id).is_some() {
+                            self.observers.fetch_sub(1, Ordering::Relaxed);
+                        }
+                    }
+                    _ => (),
+                }
+                let parameters = request
+                    .query()
+                    .map_err(|e| bad_request(e.into()))?
+                    .join("&");
+                let selector = Selector::from(&key_expr).with_parameters(&parameters);
+                let replies = self
+                    .session
+                    .get(selector)
+                    .consolidation(ConsolidationMode::None)
+                    .timeout(Duration::from_millis(self.conf.query_timeout))
+                    .res()
+                    .await
+                    .map_err(|e| (coap::INTERNAL_SERVER_ERROR, e))?;
+                while let Ok(reply) = replies.recv_async().await {
+                    if let Ok(sample) = reply.sample {
+                        if let Some(format) = content_format(&sample.value.encoding) {
+                            response =
+                                response.with_option(coap::CONTENT_FORMAT, coap::uint(format));
+                        }
+                        return Ok(
+                            response.with_payload(sample.value.payload.contiguous().into_owned())
+                        );
+                    }
+                }
+                if response.uint_option(coap::OBSERVE).is_some() {
+                    // the observed resource has no value yet
+                    return Ok(response);
+                }
+                Err((coap::NOT_FOUND, zerror!("No value for {}", key_expr).into()))
+            }
+            coap::PUT => {
+                let value = Value::from(request.payload.clone())
+                    .encoding(encoding(request.uint_option(coap::CONTENT_FORMAT)));
+                self.session
+                    .put(&key_expr, value)
+                    .res()
+                    .await
+                    .map_err(|e| (coap::INTERNAL_SERVER_ERROR, e))?;
+                response.code = coap::CHANGED;
+                Ok(response)
+            }
+            coap::DELETE => {
+                self.session
+                    .delete(&key_expr)
+                    .res()
+                    .await
+                    .map_err(|e| (coap::INTERNAL_SERVER_ERROR, e))?;
+                response.code = coap::DELETED;
+                Ok(response)
+            }
+            coap::POST => Err((
+                coap::METHOD_NOT_ALLOWED,
+                zerror!("POST isn't supported, use PUT").into(),
+            )),
+            code => Err((
+                coap::METHOD_NOT_ALLOWED,
+                zerror!("Unknown CoAP method {}.{:02}", code >> 5, code & 0x1f).into(),
+            )),
+        }
+    }
+
+    // Registers an observation of `key_expr`, returning the sequence number of its first notification,
+    // or None if the observation can't be registered
+    async fn observe(&self, id: ObservationId, key_expr: &OwnedKeyExpr) -> Option<u32> {
+        if !self.observations.lock().unwrap().contains_key(&id)
+            && self.observers.load(Ordering::Relaxed) >= self.conf.max_observers
+        {
+            log::warn!(
+                "Maximum number of CoAP observations reached, refusing to observe {key_expr}"
+            );
+            return None;
+        }
+        let (addr, token) = id.clone();
+        let sequence = Arc::new(AtomicU32::new(0));
+        let last_message_id = Arc::new(AtomicU16::new(0));
+        let notifications = self.notifications.clone();
+        let message_ids = self.message_ids.clone();
+        let observations = self.observations.clone();
+        let observers = self.observers.clone();
+        let callback = {
+            let sequence = sequence.clone();
+            let last_message_id = last_message_id.clone();
+            let id = id.clone();
+            move |sample: Sample| {
+                let message_id = message_ids.fetch_add(1, Ordering::Relaxed);
+                last_message_id.store(message_id, Ordering::Relaxed);
+                let notification = match sample.kind {
+                    SampleKind::Put => {
+                        // the sequence numbers of the notifications are 24 bits long
+                        let sequence = (sequence.fetch_add(1, Ordering::Relaxed) + 1) & 0xff_ffff;
+                        let mut notification = Message::new(
+                            MessageType::NonConfirmable,
+                            coap::CONTENT,
+                            message_id,
+                            token.clone(),
+                        )
+                        .with_option(coap::OBSERVE, coap::uint(sequence))
+                        .with_payload(sample.value.payload.contiguous().into_owned());
+                        if let Some(format) = content_format(&sample.value.encoding) {
+                            notification =
+                                notification.with_option(coap::CONTENT_FORMAT, coap::uint(format));
+                        }
+                        notification
+                    }
+                    SampleKind::Delete => {
+                        // an error notification ends the observation, which is removed by another
+                        // task as the subscriber can't be undeclared from its own callback
+                        let observations = observations.clone();
+                        let observers = observers.clone();
+                        let id = id.clone();
+                        async_std::task::spawn(async move {
+                            if observations.lock().unwrap().remove(&id).is_some() {
+                                observers.fetch_sub(1, Ordering::Relaxed);
+                            }
+                        });
+                        Message::new(
+                            MessageType::NonConfirmable,
+                            coap::NOT_FOUND,
+                            message_id,
+                            token.clone(),
+                        )
+                    }
+                };
+                if notifications
+                    .try_send((addr, notification.encode()))
+                    .is_err()
+                {
+                    log::warn!(
+                        "CoAP notifications queue full, dropping notification of {}",
+                        sample.key_expr
+                    );
+                }
+            }
+        };
+        let subscriber = match self
+            .session
+            .declare_subscriber(key_expr)
+            .callback(callback)
+            .res()
+            .await
+        {
+            Ok(subscriber) => subscriber,
+            Err(e) => {
+                log::warn!("Error subscribing to {}: {}", key_expr, e);
+                return None;
+            }
+        };
+        let observation = Observation {
+            _subscriber: subscriber,
+            last_message_id,
+        };
+        if self
+            .observations
+            .lock()
+            .unwrap()
+            .insert(id, observation)
+            .is_none()
+        {
+            self.observers.fetch_add(1, Ordering::Relaxed);
+        }
+        Some(sequence.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_mapping() {
+        assert_eq!(
+            path_to_key_expr(Some("coap"), &["a", "b"])
+                .unwrap()
+                .as_str(),
+            "coap/a/b"
+        );
+        assert_eq!(
+            path_to_key_expr(Some("coap"), &[]).unwrap().as_str(),
+            "coap"
+        );
+        assert_eq!(path_to_key_expr(None, &["a", "*"]).unwrap().as_str(), "a/*");
+        assert!(path_to_key_expr(None, &[]).is_err());
+        assert!(path_to_key_expr(None, &["a", "", "b"]).is_err());
+        assert_eq!(
+            content_format(&encoding(Some(50))),
+            Some(50),
+            "JSON content is kept as JSON"
+        );
+        assert_eq!(encoding(None), Encoding::default());
+    }
+}
@@ -0,0 +1,151 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! Exposes the router's metrics (`@/router/<zid>/metrics` in the adminspace, including the ones of the running plugins)
+//! on the `/metrics` HTTP endpoint, in Prometheus text exposition format.
+use async_std::prelude::FutureExt;
+use std::sync::Arc;
+use tide::{Request, Response, Server, StatusCode};
+use zenoh::plugins::{Plugin, RunningPluginTrait, TaskMonitor, ZenohPlugin};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod config;
+pub use config::Config;
+
+const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
+lazy_static::lazy_static! {
+    static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
+}
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+zenoh_plugin_trait::declare_plugin!(MetricsPlugin);
+pub struct MetricsPlugin {}
+
+impl ZenohPlugin for MetricsPlugin {}
+
+impl Plugin for MetricsPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = zenoh::plugins::RunningPlugin;
+    const STATIC_NAME: &'static str = "metrics";
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+        // Try to initiate login.
+        // Required in case of dynamic lib, otherwise no logs.
+        // But cannot be done twice in case of static link.
+        let _ = env_logger::try_init();
+        log::debug!("Metrics plugin {}", LONG_VERSION.as_str());
+
+        let runtime_conf = runtime.config.lock();
+        let plugin_conf = runtime_conf
+            .plugin(name)
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+
+        let conf: Config = serde_json::from_value(plugin_conf.clone())
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let monitor = TaskMonitor::default();
+        let task =
+            async_std::task::spawn(monitor.clone().watch(run(runtime.clone(), conf.clone())));
+        let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
+        if let Ok(Err(e)) = task {
+            bail!("Metrics server failed within 1ms: {e}")
+        }
+        Ok(Box::new(RunningPlugin(conf, monitor)))
+    }
+}
+
+struct RunningPlugin(Config, TaskMonitor);
+impl RunningPluginTrait for RunningPlugin {
+    fn terminated(&self) -> Option<String> {
+        self.1.terminated()
+    }
+
+    fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
+        Arc::new(|_, _, _| {
+            bail!("zenoh-plugin-metrics doesn't accept any runtime configuration changes")
+        })
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<zenoh::plugins::Response>> {
+        let mut responses = Vec::new();
+        for (suffix, value) in [
+            ("/version", GIT_VERSION.into()),
+            ("/port", (&self.0).into()),
+        ] {
+            let key = format!("{plugin_status_key}{suffix}");
+            if keyexpr::new(key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(key, value))
+            }
+        }
+        Ok(responses)
+    }
+}
+
+pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
+    // Try to initiate login.
+    // Required in case of dynamic lib, otherwise no logs.
+    // But cannot be done twice in case of static link.
+    let _ = env_logger::try_init();
+
+    let metrics_key = format!("@/router/{}/metrics", runtime.zid);
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    let mut app = Server::with_state((Arc::new(session), metrics_key));
+    app.at("/metrics").get(metrics);
+
+    if let Err(e) = app.listen(conf.http_port).await {
+        log::error!("Unable to start http server for metrics: {:?}", e);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+async fn metrics(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+    let (session, metrics_key) = req.state();
+    let text = match session.get(metrics_key.as_str()).res().await {
+        Ok(replies) => match replies.recv_async().await.map(|reply| reply.sample) {
+            Ok(Ok(sample)) => String::from_utf8_lossy(&sample.payload.contiguous()).into_owned(),
+            Ok(Err(e)) => return Ok(error_response(&e.to_string())),
+            Err(_) => return Ok(error_response("No metrics received from the router")),
+        },
+        Err(e) => return Ok(error_response(&e.to_string())),
+    };
+    Ok(Response::builder(StatusCode::Ok)
+        .content_type(METRICS_CONTENT_TYPE)
+        .body(text)
+        .build())
+}
+
+fn error_response(error: &str) -> Response {
+    log::warn!("Error retrieving metrics: {}", error);
+    Response::builder(StatusCode::InternalServerError)
+        .content_type("text/plain")
+        .body(error)
+        .build()
+}
@@ -0,0 +1,168 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The Modbus devices polled by the bridge, by name.
+    #[serde(default)]
+    pub devices: BTreeMap<String, DeviceConfig>,
+    __path__: Option<String>,
+    __required__: Option<bool>,
+    __on_panic__: Option<String>,
+    __max_restarts__: Option<u32>,
+    __dependencies__: Option<Vec<String>>,
+    __config__: Option<String>,
+}
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceConfig {
+    /// The key expression prefixing the keys of the registers of the device.
+    pub key_prefix: String,
+    pub transport: Transport,
+    /// The unit identifier of the device, i.e. its slave address on a serial line.
+    #[serde(default = "default_unit_id")]
+    pub unit_id: u8,
+    /// The timeout in milliseconds of the requests to the device.
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    /// The default polling interval in milliseconds of the registers of the device.
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval: u64,
+    #[serde(default)]
+    pub registers: Vec<RegisterConfig>,
+}
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Transport {
+    /// Modbus TCP, to the `<host>:<port>` address of the device.
+    Tcp(String),
+    /// Modbus RTU, over a serial line.
+    Rtu(SerialConfig),
+}
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SerialConfig {
+    /// The serial port, e.g. `/dev/ttyUSB0` or `COM3`.
+    pub port: String,
+    #[serde(default = "default_baud_rate")]
+    pub baud_rate: u32,
+    #[serde(default)]
+    pub parity: Parity,
+    /// The number of stop bits, 1 or 2.
+    #[serde(default = "default_stop_bits")]
+    pub stop_bits: u8,
+}
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Parity {
+    #[default]
+    None,
+    Even,
+    Odd,
+}
+
+/// A register of a device, published on `<key_prefix>/<name>`.
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterConfig {
+    /// The name of the register, the end of its key.
+    pub name: String,
+    pub table: Table,
+    /// The address of the (first) register in its table, starting from 0.
+    pub address: u16,
+    /// The type of the value, `bool` for coils and discrete inputs, `u16` by default for registers.
+    #[serde(default)]
+    pub data_type: Option<DataType>,
+    /// The order of the registers of the 32-bit values.
+    #[serde(default)]
+    pub word_order: WordOrder,
+    /// The factor applied to the raw values of the register, and inverted for the writes.
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    /// The polling interval in milliseconds of the register, the one of its device by default.
+    #[serde(default)]
+    pub poll_interval: Option<u64>,
+    /// Whether the puts on `<key_prefix>/<name>/set` are written to the register.
+    /// Only coils and holding registers are writable.
+    #[serde(default)]
+    pub writable: bool,
+}
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Table {
+    Coil,
+    DiscreteInput,
+    InputRegister,
+    HoldingRegister,
+}
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DataType {
+    Bool,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+}
+
+#[derive(JsonSchema, Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WordOrder {
+    /// The most significant word first.
+    #[default]
+    Big,
+    /// The least significant word first.
+    Little,
+}
+
+fn default_unit_id() -> u8 {
+    1
+}
+
+fn default_timeout() -> u64 {
+    1000
+}
+
+fn default_poll_interval() -> u64 {
+    1000
+}
+
+fn default_baud_rate() -> u32 {
+    9600
+}
+
+fn default_stop_bits() -> u8 {
+    1
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+impl From<&Config> for serde_json::Value {
+    fn from(c: &Config) -> Self {
+        serde_json::to_value(c).unwrap()
+    }
+}
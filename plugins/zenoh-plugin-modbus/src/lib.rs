@@ -0,0 +1,737 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! Polls the registers of Modbus TCP and RTU devices, publishing their values on
+//! `<key_prefix>/<name>`, and writes the values put on `<key_prefix>/<name>/set` to the writable
+//! registers. The devices on the same serial line, or behind the same TCP address, share a
+//! connection and are polled in turn.
+use async_std::net::TcpStream;
+use async_std::prelude::FutureExt;
+use serialport::{ClearBuffer, DataBits, SerialPort, StopBits};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zenoh::plugins::{Metric, MetricKind, Plugin, RunningPluginTrait, TaskMonitor, ZenohPlugin};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod config;
+mod modbus;
+pub use config::{
+    Config, DataType, DeviceConfig, Parity, RegisterConfig, SerialConfig, Table, Transport,
+    WordOrder,
+};
+use modbus::{Request, Response};
+
+const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
+lazy_static::lazy_static! {
+    static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
+}
+// The last chunk of the keys on which the values to write to the registers are put
+const COMMAND_CHUNK: &str = "set";
+
+zenoh_plugin_trait::declare_plugin!(ModbusPlugin);
+pub struct ModbusPlugin {}
+
+impl ZenohPlugin for ModbusPlugin {}
+
+impl Plugin for ModbusPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = zenoh::plugins::RunningPlugin;
+    const STATIC_NAME: &'static str = "modbus";
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+        // Try to initiate login.
+        // Required in case of dynamic lib, otherwise no logs.
+        // But cannot be done twice in case of static link.
+        let _ = env_logger::try_init();
+        log::debug!("Modbus plugin {}", LONG_VERSION.as_str());
+
+        let runtime_conf = runtime.config.lock();
+        let plugin_conf = runtime_conf
+            .plugin(name)
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+
+        let conf: Config = serde_json::from_value(plugin_conf.clone())
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let statuses = conf
+            .devices
+            .keys()
+            .map(|device| (device.clone(), Arc::new(DeviceStatus::default())))
+            .collect::<BTreeMap<_, _>>();
+        let connections = connections(&conf, &statuses)
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let monitor = TaskMonitor::default();
+        let task = async_std::task::spawn(monitor.clone().watch(run(runtime.clone(), connections)));
+        let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
+        if let Ok(Err(e)) = task {
+            bail!("Modbus bridge failed within 1ms: {e}")
+        }
+        Ok(Box::new(RunningPlugin {
+            conf,
+            statuses,
+            monitor,
+        }))
+    }
+}
+
+struct RunningPlugin {
+    conf: Config,
+    statuses: BTreeMap<String, Arc<DeviceStatus>>,
+    monitor: TaskMonitor,
+}
+
+impl RunningPluginTrait for RunningPlugin {
+    fn terminated(&self) -> Option<String> {
+        self.monitor.terminated()
+    }
+
+    fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
+        Arc::new(|_, _, _| {
+            bail!("zenoh-plugin-modbus doesn't accept any runtime configuration changes")
+        })
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<zenoh::plugins::Response>> {
+        let connected = self
+            .statuses
+            .iter()
+            .map(|(device, status)| {
+                (
+                    device.clone(),
+                    status.connected.load(Ordering::Relaxed).into(),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let mut responses = Vec::new();
+        for (suffix, value) in [
+            ("/version", GIT_VERSION.into()),
+            ("/devices", serde_json::to_value(&self.conf.devices)?),
+            ("/connected", connected.into()),
+        ] {
+            let key = format!("{plugin_status_key}{suffix}");
+            if keyexpr::new(key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(key, value))
+            }
+        }
+        Ok(responses)
+    }
+
+    fn metrics(&self) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        for (device, status) in &self.statuses {
+            for metric in [
+                Metric::new(
+                    "zenoh_modbus_connected",
+                    MetricKind::Gauge,
+                    "Whether the last request to the Modbus device succeeded.",
+                    status.connected.load(Ordering::Relaxed) as u8 as f64,
+                ),
+                Metric::new(
+                    "zenoh_modbus_requests_total",
+                    MetricKind::Counter,
+                    "The number of requests sent to the Modbus device.",
+                    status.requests.load(Ordering::Relaxed) as f64,
+                ),
+                Metric::new(
+                    "zenoh_modbus_errors_total",
+                    MetricKind::Counter,
+                    "The number of requests to the Modbus device which failed.",
+                    status.errors.load(Ordering::Relaxed) as f64,
+                ),
+            ] {
+                metrics.push(metric.with_label("device", device.as_str()));
+            }
+        }
+        metrics
+    }
+}
+
+#[derive(Default)]
+struct DeviceStatus {
+    connected: AtomicBool,
+    requests: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// A register of a device, with its polling schedule.
+struct Register {
+    conf: RegisterConfig,
+    data_type: DataType,
+    key_expr: OwnedKeyExpr,
+    // The key expression of the values to write, if the register is writable
+    command_key_expr: Option<OwnedKeyExpr>,
+    unit: u8,
+    timeout: Duration,
+    poll_interval: Duration,
+    next_poll: Instant,
+    // Whether the last poll failed, not to log the same failure at each poll
+    failing: bool,
+    status: Arc<DeviceStatus>,
+}
+
+impl Register {
+    fn new(
+        device: &DeviceConfig,
+        conf: &RegisterConfig,
+        status: Arc<DeviceStatus>,
+    ) -> ZResult<Self> {
+        let key_expr = OwnedKeyExpr::try_from(format!("{}/{}", device.key_prefix, conf.name))
+            .map_err(|e| zerror!("invalid key of register {}: {}", conf.name, e))?;
+        if key_expr.is_wild() {
+            bail!(
+                "register {} can't be published on the wildcard key expression {}",
+                conf.name,
+                key_expr
+            )
+        }
+        let bits = matches!(conf.table, Table::Coil | Table::DiscreteInput);
+        let data_type = match (conf.data_type, bits) {
+            (None | Some(DataType::Bool), true) => DataType::Bool,
+            (None, false) => DataType::U16,
+            (Some(DataType::Bool), false) | (Some(_), true) => bail!(
+                "register {}: only the coils and discrete inputs have bool values",
+                conf.name
+            ),
+            (Some(data_type), false) => data_type,
+        };
+        if conf.writable && !matches!(conf.table, Table::Coil | Table::HoldingRegister) {
+            bail!(
+                "register {}: only the coils and holding registers are writable",
+                conf.name
+            )
+        }
+        if conf.scale == 0.0 || !conf.scale.is_finite() {
+            bail!("register {}: invalid scale {}", conf.name, conf.scale)
+        }
+        let poll_interval = conf.poll_interval.unwrap_or(device.poll_interval);
+        if poll_interval == 0 {
+            bail!(
+                "register {}: the polling interval must be positive",
+                conf.name
+            )
+        }
+        let command_key_expr = match conf.writable {
+            true => Some(key_expr.join(COMMAND_CHUNK)?),
+            false => None,
+        };
+        Ok(Register {
+            conf: conf.clone(),
+            data_type,
+            key_expr,
+            command_key_expr,
+            unit: device.unit_id,
+            timeout: Duration::from_millis(device.timeout),
+            poll_interval: Duration::from_millis(poll_interval),
+            next_poll: Instant::now(),
+            failing: false,
+            status,
+        })
+    }
+
+    /// The number of bits or registers holding the value.
+    fn count(&self) -> u16 {
+        match self.data_type {
+            DataType::U32 | DataType::I32 | DataType::F32 => 2,
+            DataType::Bool | DataType::U16 | DataType::I16 => 1,
+        }
+    }
+
+    fn read_request(&self) -> Request {
+        let function = match self.conf.table {
+            Table::Coil => modbus::READ_COILS,
+            Table::DiscreteInput => modbus::READ_DISCRETE_INPUTS,
+            Table::InputRegister => modbus::READ_INPUT_REGISTERS,
+            Table::HoldingRegister => modbus::READ_HOLDING_REGISTERS,
+        };
+        Request::Read {
+            function,
+            address: self.conf.address,
+            count: self.count(),
+        }
+    }
+
+    /// The value published for `response`, the response to the read request of the register.
+    fn value(&self, response: &Response) -> ZResult<Value> {
+        let word = |registers: &[u16]| match self.conf.word_order {
+            WordOrder::Big => (registers[0] as u32) << 16 | registers[1] as u32,
+            WordOrder::Little => (registers[1] as u32) << 16 | registers[0] as u32,
+        };
+        let raw = match (self.data_type, response) {
+            (DataType::Bool, Response::Bits(bits)) if bits.len() == 1 => {
+                return Ok(Value::from(bits[0] as i64))
+            }
+            (DataType::U16, Response::Registers(r)) if r.len() == 1 => r[0] as i64,
+            (DataType::I16, Response::Registers(r)) if r.len() == 1 => r[0] as i16 as i64,
+            (DataType::U32, Response::Registers(r)) if r.len() == 2 => word(r) as i64,
+            (DataType::I32, Response::Registers(r)) if r.len() == 2 => word(r) as i32 as i64,
+            (DataType::F32, Response::Registers(r)) if r.len() == 2 => {
+                let value = f32::from_bits(word(r)) as f64;
+                return Ok(Value::from(value * self.conf.scale));
+            }
+            _ => bail!(
+                "Unexpected response {:?} for register {}",
+                response,
+                self.conf.name
+            ),
+        };
+        Ok(match self.conf.scale {
+            scale if scale == 1.0 => Value::from(raw),
+            scale => Value::from(raw as f64 * scale),
+        })
+    }
+
+    /// The request writing `payload`, a number or a boolean, to the register.
+    fn write_request(&self, payload: &[u8]) -> ZResult<Request> {
+        let text = std::str::from_utf8(payload)
+            .map_err(|_| zerror!("Non UTF-8 value for register {}", self.conf.name))?
+            .trim();
+        let value = match text {
+            "true" => 1.0,
+            "false" => 0.0,
+            text => text
+                .parse::<f64>()
+                .map_err(|_| zerror!("Invalid value `{}` for register {}", text, self.conf.name))?,
+        };
+        let address = self.conf.address;
+        if self.data_type == DataType::Bool {
+            return Ok(Request::WriteCoil {
+                address,
+                value: value != 0.0,
+            });
+        }
+        let raw = value / self.conf.scale;
+        let int = |min: f64, max: f64| -> ZResult<i64> {
+            match raw.round() {
+                raw if raw >= min && raw <= max => Ok(raw as i64),
+                _ => bail!(
+                    "Value {} is out of the range of register {}",
+                    value,
+                    self.conf.name
+                ),
+            }
+        };
+        let word = match self.data_type {
+            DataType::U16 => int(0.0, u16::MAX as f64)? as u32,
+            DataType::I16 => int(i16::MIN as f64, i16::MAX as f64)? as i16 as u16 as u32,
+            DataType::U32 => int(0.0, u32::MAX as f64)? as u32,
+            DataType::I32 => int(i32::MIN as f64, i32::MAX as f64)? as i32 as u32,
+            DataType::F32 => (raw as f32).to_bits(),
+            DataType::Bool => unreachable!(),
+        };
+        let (high, low) = ((word >> 16) as u16, word as u16);
+        let values = match (self.count(), self.conf.word_order) {
+            (1, _) => vec![low],
+            (_, WordOrder::Big) => vec![high, low],
+            (_, WordOrder::Little) => vec![low, high],
+        };
+        Ok(Request::WriteRegisters { address, values })
+    }
+}
+
+/// The connection shared by the devices on a serial line, or behind a TCP address.
+/// It's established on demand, and reset after any transport failure.
+enum Connection {
+    Tcp {
+        address: String,
+        stream: Option<TcpStream>,
+        transaction: u16,
+    },
+    Rtu {
+        conf: SerialConfig,
+        port: Option<Box<dyn SerialPort>>,
+    },
+}
+
+impl Connection {
+    fn new(transport: &Transport) -> Self {
+        match transport {
+            Transport::Tcp(address) => Connection::Tcp {
+                address: address.clone(),
+                stream: None,
+                transaction: 0,
+            },
+            Transport::Rtu(conf) => Connection::Rtu {
+                conf: conf.clone(),
+                port: None,
+            },
+        }
+    }
+
+    /// Sends `pdu` to the unit `unit`, and returns the PDU of its response.
+    async fn transaction(&mut self, unit: u8, pdu: Vec<u8>, timeout: Duration) -> ZResult<Vec<u8>> {
+        match self {
+            Connection::Tcp {
+                address,
+                stream,
+                transaction,
+            } => {
+                let mut tcp = match stream.take() {
+                    Some(tcp) => tcp,
+                    None => TcpStream::connect(address.as_str())
+                        .timeout(timeout)
+                        .await
+                        .map_err(|_| zerror!("Connection to {} timed out", address))??,
+                };
+                *transaction = transaction.wrapping_add(1);
+                let response = modbus::tcp_transaction(&mut tcp, *transaction, unit, &pdu)
+                    .timeout(timeout)
+                    .await
+                    .map_err(|_| zerror!("Modbus request to {} timed out", address))??;
+                *stream = Some(tcp);
+                Ok(response)
+            }
+            Connection::Rtu { conf, port } => {
+                let conf = conf.clone();
+                let serial = port.take();
+                let (serial, response) = async_std::task::spawn_blocking(move || {
+                    let mut serial = match serial.map_or_else(|| open(&conf), Ok) {
+                        Ok(serial) => serial,
+                        Err(e) => return (None, Err(e)),
+                    };
+                    if let Err(e) = serial.set_timeout(timeout) {
+                        return (None, Err(e.into()));
+                    }
+                    // the frames are separated by a silent interval of 3.5 characters
+                    std::thread::sleep(silent_interval(conf.baud_rate));
+                    let _ = serial.clear(ClearBuffer::Input);
+                    let response = modbus::rtu_transaction(&mut *serial, unit, &pdu);
+                    (Some(serial), response)
+                })
+                .await;
+                if response.is_ok() {
+                    *port = serial;
+                }
+                response
+            }
+        }
+    }
+}
+
+fn open(conf: &SerialConfig) -> ZResult<Box<dyn SerialPort>> {
+    let parity = match conf.parity {
+        Parity::None => serialport::Parity::None,
+        Parity::Even => serialport::Parity::Even,
+        Parity::Odd => serialport::Parity::Odd,
+    };
+    let stop_bits = match conf.stop_bits {
+        2 => StopBits::Two,
+        _ => StopBits::One,
+    };
+    serialport::new(&conf.port, conf.baud_rate)
+        .data_bits(DataBits::Eight)
+        .parity(parity)
+        .stop_bits(stop_bits)
+        .open()
+        .map_err(|e| zerror!("Failed to open serial port {}: {}", conf.port, e).into())
+}
+
+fn silent_interval(baud_rate: u32) -> Duration {
+    // 3.5 characters of 11 bits, and at least 1.75ms above 19200 bauds
+    Duration::from_micros((38_500_000 / baud_rate.max(1) as u64).max(1750))
+}
+
+/// Groups the registers of the devices by connection.
+fn connections(
+    conf: &Config,
+    statuses: &BTreeMap<String, Arc<DeviceStatus>>,
+) -> ZResult<Vec<(Connection, Vec<Register>)>> {
+    let mut groups = BTreeMap::<String, (&Transport, Vec<Register>)>::new();
+    for (name, device) in &conf.devices {
+        let registers = device
+            .registers
+            .iter()
+            .map(|register| Register::new(device, register, statuses[name].clone()))
+            .collect::<ZResult<Vec<_>>>()
+            .map_err(|e| zerror!("device {}: {}", name, e))?;
+        let id = match &device.transport {
+            Transport::Tcp(address) => format!("tcp/{address}"),
+            Transport::Rtu(serial) => {
+                if !matches!(serial.stop_bits, 1 | 2) {
+                    bail!("device {}: the number of stop bits must be 1 or 2", name)
+                }
+                format!("rtu/{}", serial.port)
+            }
+        };
+        match groups.entry(id) {
+            Entry::Vacant(entry) => {
+                entry.insert((&device.transport, registers));
+            }
+            Entry::Occupied(mut entry) => {
+                let (transport, group) = entry.get_mut();
+                if let (Transport::Rtu(a), Transport::Rtu(b)) = (transport, &device.transport) {
+                    if a != b {
+                        bail!(
+                            "device {}: the devices on serial port {} must use the same settings",
+                            name,
+                            a.port
+                        )
+                    }
+                }
+                group.extend(registers);
+            }
+        }
+    }
+    Ok(groups
+        .into_values()
+        .filter(|(_, registers)| !registers.is_empty())
+        .map(|(transport, registers)| (Connection::new(transport), registers))
+        .collect())
+}
+
+async fn run(runtime: Runtime, connections: Vec<(Connection, Vec<Register>)>) -> ZResult<()> {
+    // Try to initiate login.
+    // Required in case of dynamic lib, otherwise no logs.
+    // But cannot be done twice in case of static link.
+    let _ = env_logger::try_init();
+
+    let session = Arc::new(zenoh::init(runtime).res().await.unwrap());
+    let tasks = connections
+        .into_iter()
+        .map(|(connection, registers)| poll(session.clone(), connection, registers));
+    futures::future::try_join_all(tasks).await?;
+    futures::future::pending().await
+}
+
+/// Polls the registers sharing `connection`, earliest first, and writes the values put on their
+/// command keys.
+async fn poll(
+    session: Arc<Session>,
+    mut connection: Connection,
+    mut registers: Vec<Register>,
+) -> ZResult<()> {
+    let (tx, rx) = flume::unbounded();
+    let mut subscribers = Vec::new();
+    for (i, register) in registers.iter().enumerate() {
+        if let Some(key_expr) = &register.command_key_expr {
+            let tx = tx.clone();
+            let subscriber = session
+                .declare_subscriber(key_expr)
+                .callback(move |sample| {
+                    let _ = tx.send((i, sample));
+                })
+                .res()
+                .await?;
+            subscribers.push(subscriber);
+        }
+    }
+
+    loop {
+        let (i, next_poll) = registers
+            .iter()
+            .enumerate()
+            .map(|(i, register)| (i, register.next_poll))
+            .min_by_key(|(_, next_poll)| *next_poll)
+            .unwrap();
+        let timeout = next_poll.saturating_duration_since(Instant::now());
+        match rx.recv_async().timeout(timeout).await {
+            Ok(Ok((i, sample))) => {
+                if sample.kind == SampleKind::Put {
+                    let register = &mut registers[i];
+                    if let Err(e) = write(&mut connection, register, &sample).await {
+                        log::warn!("Failed to write {}: {}", sample.key_expr, e);
+                    }
+                    // publish the written value right away
+                    register.next_poll = Instant::now();
+                }
+                continue;
+            }
+            Ok(Err(e)) => bail!("{}", e),
+            Err(_) => {}
+        }
+
+        let register = &mut registers[i];
+        let now = Instant::now();
+        register.next_poll = (register.next_poll + register.poll_interval).max(now);
+        let request = register.read_request();
+        let result = match request_register(&mut connection, register, &request).await {
+            Ok(response) => register.value(&response),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(value) => {
+                register.failing = false;
+                session.put(&register.key_expr, value).res().await?;
+            }
+            Err(e) if register.failing => {
+                log::debug!("Failed to poll {}: {}", register.key_expr, e)
+            }
+            Err(e) => {
+                register.failing = true;
+                log::warn!("Failed to poll {}: {}", register.key_expr, e);
+            }
+        }
+    }
+}
+
+async fn write(connection: &mut Connection, register: &Register, sample: &Sample) -> ZResult<()> {
+    let request = register.write_request(&sample.value.payload.contiguous())?;
+    request_register(connection, register, &request).await?;
+    Ok(())
+}
+
+async fn request_register(
+    connection: &mut Connection,
+    register: &Register,
+    request: &Request,
+) -> ZResult<Response> {
+    let status = &register.status;
+    status.requests.fetch_add(1, Ordering::Relaxed);
+    let response = connection
+        .transaction(register.unit, request.encode(), register.timeout)
+        .await;
+    status.connected.store(response.is_ok(), Ordering::Relaxed);
+    let response = response.and_then(|pdu| request.parse_response(&pdu));
+    if response.is_err() {
+        status.errors.fetch_add(1, Ordering::Relaxed);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn register(conf: serde_json::Value) -> ZResult<Register> {
+        let device: DeviceConfig = serde_json::from_value(serde_json::json!({
+            "key_prefix": "factory/plc",
+            "transport": { "tcp": "localhost:502" },
+            "registers": [conf],
+        }))?;
+        Register::new(
+            &device,
+            &device.registers[0],
+            Arc::new(DeviceStatus::default()),
+        )
+    }
+
+    fn value(register: &Register, response: Response) -> String {
+        let value = register.value(&response).unwrap();
+        String::from_utf8(value.payload.contiguous().to_vec()).unwrap()
+    }
+
+    #[test]
+    fn registers() {
+        let r = register(serde_json::json!({
+            "name": "temperature", "table": "input_register", "address": 3,
+            "data_type": "i16", "scale": 0.1,
+        }))
+        .unwrap();
+        assert_eq!(r.key_expr.as_str(), "factory/plc/temperature");
+        assert!(r.command_key_expr.is_none());
+        assert_eq!(
+            r.read_request(),
+            Request::Read {
+                function: modbus::READ_INPUT_REGISTERS,
+                address: 3,
+                count: 1
+            }
+        );
+        assert_eq!(value(&r, Response::Registers(vec![0xff9c])), "-10");
+
+        let r = register(serde_json::json!({
+            "name": "energy", "table": "holding_register", "address": 0,
+            "data_type": "u32", "word_order": "little", "writable": true,
+        }))
+        .unwrap();
+        assert_eq!(
+            r.command_key_expr.as_ref().unwrap().as_str(),
+            "factory/plc/energy/set"
+        );
+        assert_eq!(
+            value(&r, Response::Registers(vec![0x0002, 0x0001])),
+            "65538"
+        );
+        assert_eq!(
+            r.write_request(b"65538").unwrap(),
+            Request::WriteRegisters {
+                address: 0,
+                values: vec![0x0002, 0x0001]
+            }
+        );
+        assert!(r.write_request(b"-1").is_err());
+        assert!(r.write_request(b"warm").is_err());
+
+        let r = register(serde_json::json!({
+            "name": "flow", "table": "holding_register", "address": 0,
+            "data_type": "f32", "writable": true,
+        }))
+        .unwrap();
+        assert_eq!(
+            value(&r, Response::Registers(vec![0x4049, 0x0000])),
+            "3.140625"
+        );
+        assert_eq!(
+            r.write_request(b"3.140625").unwrap(),
+            Request::WriteRegisters {
+                address: 0,
+                values: vec![0x4049, 0x0000]
+            }
+        );
+
+        let r = register(serde_json::json!({
+            "name": "setpoint", "table": "holding_register", "address": 7,
+            "scale": 0.5, "writable": true,
+        }))
+        .unwrap();
+        assert_eq!(
+            r.write_request(b" 21\n").unwrap(),
+            Request::WriteRegisters {
+                address: 7,
+                values: vec![42]
+            }
+        );
+
+        let r = register(serde_json::json!({
+            "name": "pump", "table": "coil", "address": 1, "writable": true,
+        }))
+        .unwrap();
+        assert_eq!(value(&r, Response::Bits(vec![true])), "1");
+        assert_eq!(
+            r.write_request(b"true").unwrap(),
+            Request::WriteCoil {
+                address: 1,
+                value: true
+            }
+        );
+        assert!(r.value(&Response::Registers(vec![1])).is_err());
+
+        for invalid in [
+            serde_json::json!({ "name": "a", "table": "coil", "address": 0, "data_type": "u16" }),
+            serde_json::json!({ "name": "a", "table": "input_register", "address": 0, "data_type": "bool" }),
+            serde_json::json!({ "name": "a", "table": "input_register", "address": 0, "writable": true }),
+            serde_json::json!({ "name": "a", "table": "input_register", "address": 0, "scale": 0.0 }),
+            serde_json::json!({ "name": "a", "table": "input_register", "address": 0, "poll_interval": 0 }),
+            serde_json::json!({ "name": "*", "table": "input_register", "address": 0 }),
+        ] {
+            assert!(register(invalid).is_err());
+        }
+    }
+}
@@ -0,0 +1,394 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! The Modbus application protocol, framed as Modbus TCP or Modbus RTU.
+use async_std::net::TcpStream;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::io::{Read, Write};
+use zenoh_result::{bail, ZResult};
+
+pub(crate) const READ_COILS: u8 = 0x01;
+pub(crate) const READ_DISCRETE_INPUTS: u8 = 0x02;
+pub(crate) const READ_HOLDING_REGISTERS: u8 = 0x03;
+pub(crate) const READ_INPUT_REGISTERS: u8 = 0x04;
+pub(crate) const WRITE_SINGLE_COIL: u8 = 0x05;
+pub(crate) const WRITE_SINGLE_REGISTER: u8 = 0x06;
+pub(crate) const WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+// The flag of the function code of the exception responses
+const EXCEPTION: u8 = 0x80;
+// The length of the MBAP header prefixing the Modbus TCP frames
+const MBAP_HEADER_LEN: usize = 7;
+// The maximal length of a PDU
+const MAX_PDU_LEN: usize = 253;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Request {
+    /// Reads `count` bits or registers, depending on `function`.
+    Read {
+        function: u8,
+        address: u16,
+        count: u16,
+    },
+    WriteCoil {
+        address: u16,
+        value: bool,
+    },
+    WriteRegisters {
+        address: u16,
+        values: Vec<u16>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Response {
+    Bits(Vec<bool>),
+    Registers(Vec<u16>),
+    Written,
+}
+
+impl Request {
+    fn function(&self) -> u8 {
+        match self {
+            Request::Read { function, .. } => *function,
+            Request::WriteCoil { .. } => WRITE_SINGLE_COIL,
+            Request::WriteRegisters { values, .. } if values.len() == 1 => WRITE_SINGLE_REGISTER,
+            Request::WriteRegisters { .. } => WRITE_MULTIPLE_REGISTERS,
+        }
+    }
+
+    /// The PDU of the request.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut pdu = vec![self.function()];
+        match self {
+            Request::Read { address, count, .. } => {
+                pdu.extend_from_slice(&address.to_be_bytes());
+                pdu.extend_from_slice(&count.to_be_bytes());
+            }
+            Request::WriteCoil { address, value } => {
+                pdu.extend_from_slice(&address.to_be_bytes());
+                pdu.extend_from_slice(if *value { &[0xff, 0x00] } else { &[0x00, 0x00] });
+            }
+            Request::WriteRegisters { address, values } => {
+                pdu.extend_from_slice(&address.to_be_bytes());
+                if values.len() > 1 {
+                    pdu.extend_from_slice(&(values.len() as u16).to_be_bytes());
+                    pdu.push(values.len() as u8 * 2);
+                }
+                for value in values {
+                    pdu.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+        }
+        pdu
+    }
+
+    /// Parses `pdu`, the response of the device to this request.
+    pub(crate) fn parse_response(&self, pdu: &[u8]) -> ZResult<Response> {
+        let function = self.function();
+        match pdu {
+            [f, code] if *f == function | EXCEPTION => {
+                bail!("Modbus exception {:#04x}: {}", code, exception_name(*code))
+            }
+            [f, ..] if *f != function => {
+                bail!(
+                    "Unexpected Modbus response to function {:#04x}: {:02x?}",
+                    function,
+                    pdu
+                )
+            }
+            _ => {}
+        }
+        match self {
+            Request::Read {
+                function, count, ..
+            } => {
+                let bits = matches!(*function, READ_COILS | READ_DISCRETE_INPUTS);
+                let len = if bits {
+                    (*count as usize + 7) / 8
+                } else {
+                    *count as usize * 2
+                };
+                match pdu {
+                    [_, n, data @ ..] if *n as usize == len && data.len() == len => Ok(if bits {
+                        Response::Bits(
+                            (0..*count as usize)
+                                .map(|i| data[i / 8] & (1 << (i % 8)) != 0)
+                                .collect(),
+                        )
+                    } else {
+                        Response::Registers(
+                            data.chunks(2)
+                                .map(|w| u16::from_be_bytes([w[0], w[1]]))
+                                .collect(),
+                        )
+                    }),
+                    _ => bail!("Invalid Modbus read response: {:02x?}", pdu),
+                }
+            }
+            // the write responses echo the address, and the value or the count of registers
+            _ if pdu.len() == 5 && pdu[..5] == self.encode()[..5] => Ok(Response::Written),
+            _ => bail!("Invalid Modbus write response: {:02x?}", pdu),
+        }
+    }
+}
+
+fn exception_name(code: u8) -> &'static str {
+    match code {
+        0x01 => "illegal function",
+        0x02 => "illegal data address",
+        0x03 => "illegal data value",
+        0x04 => "server device failure",
+        0x05 => "acknowledge",
+        0x06 => "server device busy",
+        0x08 => "memory parity error",
+        0x0a => "gateway path unavailable",
+        0x0b => "gateway target device failed to respond",
+        _ => "unknown exception",
+    }
+}
+
+/// Sends `pdu` to the unit `unit` in a Modbus TCP frame, and returns the PDU of the response.
+pub(crate) async fn tcp_transaction(
+    stream: &mut TcpStream,
+    transaction: u16,
+    unit: u8,
+    pdu: &[u8],
+) -> ZResult<Vec<u8>> {
+    stream.write_all(&tcp_frame(transaction, unit, pdu)).await?;
+    let mut header = [0; MBAP_HEADER_LEN];
+    stream.read_exact(&mut header).await?;
+    let len = parse_mbap_header(&header, transaction, unit)?;
+    let mut response = vec![0; len];
+    stream.read_exact(&mut response).await?;
+    Ok(response)
+}
+
+fn tcp_frame(transaction: u16, unit: u8, pdu: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(MBAP_HEADER_LEN + pdu.len());
+    frame.extend_from_slice(&transaction.to_be_bytes());
+    // the protocol identifier of Modbus
+    frame.extend_from_slice(&[0, 0]);
+    frame.extend_from_slice(&(pdu.len() as u16 + 1).to_be_bytes());
+    frame.push(unit);
+    frame.extend_from_slice(pdu);
+    frame
+}
+
+/// Checks the MBAP header of a response, and returns the length of its PDU.
+fn parse_mbap_header(header: &[u8; MBAP_HEADER_LEN], transaction: u16, unit: u8) -> ZResult<usize> {
+    let len = u16::from_be_bytes([header[4], header[5]]) as usize;
+    if header[..4] != [(transaction >> 8) as u8, transaction as u8, 0, 0] {
+        bail!("Unexpected Modbus TCP header: {:02x?}", header)
+    }
+    if header[6] != unit || !(2..=MAX_PDU_LEN + 1).contains(&len) {
+        bail!("Invalid Modbus TCP header: {:02x?}", header)
+    }
+    Ok(len - 1)
+}
+
+/// Sends `pdu` to the unit `unit` in a Modbus RTU frame, and returns the PDU of the response.
+/// The reads time out with the timeout of the port.
+pub(crate) fn rtu_transaction<P: Read + Write + ?Sized>(
+    port: &mut P,
+    unit: u8,
+    pdu: &[u8],
+) -> ZResult<Vec<u8>> {
+    port.write_all(&rtu_frame(unit, pdu))?;
+    port.flush()?;
+    let mut frame = vec![0; 3];
+    port.read_exact(&mut frame)?;
+    frame.resize(rtu_response_len(&frame)?, 0);
+    port.read_exact(&mut frame[3..])?;
+    match parse_rtu_frame(&frame)? {
+        (u, pdu) if u == unit => Ok(pdu.to_vec()),
+        (u, _) => bail!("Modbus RTU response from unit {} instead of {}", u, unit),
+    }
+}
+
+fn rtu_frame(unit: u8, pdu: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(pdu.len() + 3);
+    frame.push(unit);
+    frame.extend_from_slice(pdu);
+    frame.extend_from_slice(&crc16(&frame).to_le_bytes());
+    frame
+}
+
+/// The length of the RTU response starting with `head`, its unit, function and the next byte.
+fn rtu_response_len(head: &[u8]) -> ZResult<usize> {
+    Ok(match head[1] {
+        f if f & EXCEPTION != 0 => 5,
+        READ_COILS | READ_DISCRETE_INPUTS | READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => {
+            5 + head[2] as usize
+        }
+        WRITE_SINGLE_COIL | WRITE_SINGLE_REGISTER | WRITE_MULTIPLE_REGISTERS => 8,
+        f => bail!("Unexpected Modbus RTU function {:#04x}", f),
+    })
+}
+
+/// Checks the CRC of an RTU frame, and returns its unit and PDU.
+fn parse_rtu_frame(frame: &[u8]) -> ZResult<(u8, &[u8])> {
+    if frame.len() < 4 {
+        bail!("Truncated Modbus RTU frame: {:02x?}", frame)
+    }
+    let (content, crc) = frame.split_at(frame.len() - 2);
+    if crc16(content).to_le_bytes() != crc {
+        bail!("Invalid CRC of Modbus RTU frame: {:02x?}", frame)
+    }
+    Ok((content[0], &content[1..]))
+}
+
+/// The CRC-16/MODBUS of `data`.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xffff;
+    for byte in data {
+        crc ^= *byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xa001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests() {
+        let read = Request::Read {
+            function: READ_HOLDING_REGISTERS,
+            address: 0x006b,
+            count: 3,
+        };
+        assert_eq!(read.encode(), [0x03, 0x00, 0x6b, 0x00, 0x03]);
+        assert_eq!(
+            read.parse_response(&[0x03, 0x06, 0x02, 0x2b, 0x00, 0x00, 0x00, 0x64])
+                .unwrap(),
+            Response::Registers(vec![0x022b, 0x0000, 0x0064])
+        );
+        assert!(read.parse_response(&[0x03, 0x06, 0x02, 0x2b]).is_err());
+        assert!(read.parse_response(&[0x04, 0x02, 0x00, 0x00]).is_err());
+        let e = read.parse_response(&[0x83, 0x02]).unwrap_err();
+        assert!(e.to_string().contains("illegal data address"));
+
+        let read = Request::Read {
+            function: READ_COILS,
+            address: 0x0013,
+            count: 10,
+        };
+        assert_eq!(
+            read.parse_response(&[0x01, 0x02, 0xcd, 0x01]).unwrap(),
+            Response::Bits(vec![
+                true, false, true, true, false, false, true, true, true, false
+            ])
+        );
+
+        let write = Request::WriteCoil {
+            address: 0x00ac,
+            value: true,
+        };
+        assert_eq!(write.encode(), [0x05, 0x00, 0xac, 0xff, 0x00]);
+        assert_eq!(
+            write.parse_response(&write.encode()).unwrap(),
+            Response::Written
+        );
+
+        let write = Request::WriteRegisters {
+            address: 0x0001,
+            values: vec![0x0003],
+        };
+        assert_eq!(write.encode(), [0x06, 0x00, 0x01, 0x00, 0x03]);
+
+        let write = Request::WriteRegisters {
+            address: 0x0001,
+            values: vec![0x000a, 0x0102],
+        };
+        assert_eq!(
+            write.encode(),
+            [0x10, 0x00, 0x01, 0x00, 0x02, 0x04, 0x00, 0x0a, 0x01, 0x02]
+        );
+        assert_eq!(
+            write
+                .parse_response(&[0x10, 0x00, 0x01, 0x00, 0x02])
+                .unwrap(),
+            Response::Written
+        );
+        assert!(write
+            .parse_response(&[0x10, 0x00, 0x01, 0x00, 0x01])
+            .is_err());
+    }
+
+    #[test]
+    fn tcp_framing() {
+        let frame = tcp_frame(0x1234, 0x11, &[0x03, 0x00, 0x6b, 0x00, 0x03]);
+        assert_eq!(
+            frame,
+            [0x12, 0x34, 0x00, 0x00, 0x00, 0x06, 0x11, 0x03, 0x00, 0x6b, 0x00, 0x03]
+        );
+        let header = [0x12, 0x34, 0x00, 0x00, 0x00, 0x09, 0x11];
+        assert_eq!(parse_mbap_header(&header, 0x1234, 0x11).unwrap(), 8);
+        assert!(parse_mbap_header(&header, 0x1235, 0x11).is_err());
+        assert!(parse_mbap_header(&header, 0x1234, 0x12).is_err());
+        assert!(
+            parse_mbap_header(&[0x12, 0x34, 0x00, 0x01, 0x00, 0x09, 0x11], 0x1234, 0x11).is_err()
+        );
+        assert!(
+            parse_mbap_header(&[0x12, 0x34, 0x00, 0x00, 0x01, 0x09, 0x11], 0x1234, 0x11).is_err()
+        );
+    }
+
+    #[test]
+    fn rtu_framing() {
+        let frame = rtu_frame(0x01, &[0x03, 0x00, 0x00, 0x00, 0x0a]);
+        assert_eq!(frame, [0x01, 0x03, 0x00, 0x00, 0x00, 0x0a, 0xc5, 0xcd]);
+        assert_eq!(
+            parse_rtu_frame(&frame).unwrap(),
+            (0x01, &[0x03, 0x00, 0x00, 0x00, 0x0a][..])
+        );
+        assert!(parse_rtu_frame(&frame[..7]).is_err());
+
+        assert_eq!(rtu_response_len(&[0x01, 0x03, 0x04]).unwrap(), 9);
+        assert_eq!(rtu_response_len(&[0x01, 0x83, 0x02]).unwrap(), 5);
+        assert_eq!(rtu_response_len(&[0x01, 0x06, 0x00]).unwrap(), 8);
+        assert!(rtu_response_len(&[0x01, 0x2b, 0x00]).is_err());
+
+        // a port answering a read of 2 registers
+        struct Port(std::io::Cursor<Vec<u8>>, Vec<u8>);
+        impl Read for Port {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                Read::read(&mut self.0, buf)
+            }
+        }
+        impl Write for Port {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                Write::write(&mut self.1, buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let response = rtu_frame(0x02, &[0x03, 0x04, 0x00, 0x01, 0x00, 0x02]);
+        let mut port = Port(std::io::Cursor::new(response), Vec::new());
+        let request = [0x03, 0x00, 0x00, 0x00, 0x02];
+        assert_eq!(
+            rtu_transaction(&mut port, 0x02, &request).unwrap(),
+            [0x03, 0x04, 0x00, 0x01, 0x00, 0x02]
+        );
+        assert_eq!(port.1, rtu_frame(0x02, &request));
+    }
+}
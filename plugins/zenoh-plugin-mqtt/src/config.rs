@@ -0,0 +1,134 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use schemars::JsonSchema;
+use serde::de::{Unexpected, Visitor};
+use serde::{de, Deserialize, Deserializer};
+use std::fmt;
+
+const DEFAULT_MQTT_INTERFACE: &str = "0.0.0.0";
+const DEFAULT_MQTT_PORT: &str = "1883";
+
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(
+        default = "default_mqtt_port",
+        deserialize_with = "deserialize_mqtt_port"
+    )]
+    pub port: String,
+    /// A key expression prefixed to MQTT topics to build the corresponding zenoh key expressions
+    /// (e.g. with `"mqtt/demo"`, the `sensor/temp` topic is mapped to `mqtt/demo/sensor/temp`).
+    #[serde(default)]
+    pub scope: Option<String>,
+    #[serde(default)]
+    pub qos: QosConfig,
+    /// The largest remaining length of the MQTT packets accepted from the clients, in bytes:
+    /// the clients sending a larger packet are disconnected.
+    #[serde(default = "default_max_packet_size")]
+    pub max_packet_size: usize,
+    __path__: Option<String>,
+    __required__: Option<bool>,
+    __on_panic__: Option<String>,
+    __max_restarts__: Option<u32>,
+    __dependencies__: Option<Vec<String>>,
+    __config__: Option<String>,
+}
+
+/// The translation between MQTT QoS levels and zenoh's congestion control.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct QosConfig {
+    /// The lowest QoS of MQTT publications that are put in zenoh with `CongestionControl::Block`,
+    /// the ones with a lower QoS being put with `CongestionControl::Drop`.
+    #[serde(default = "default_block_from_qos")]
+    pub block_from: u8,
+    /// The highest QoS granted to MQTT subscriptions, i.e. the highest QoS of the publications
+    /// forwarded from zenoh to MQTT clients.
+    #[serde(default = "default_max_subscription_qos")]
+    pub max_subscription: u8,
+}
+
+impl Default for QosConfig {
+    fn default() -> Self {
+        Self {
+            block_from: default_block_from_qos(),
+            max_subscription: default_max_subscription_qos(),
+        }
+    }
+}
+
+fn default_block_from_qos() -> u8 {
+    1
+}
+
+fn default_max_subscription_qos() -> u8 {
+    1
+}
+
+fn default_max_packet_size() -> usize {
+    1024 * 1024
+}
+
+impl From<&Config> for serde_json::Value {
+    fn from(c: &Config) -> Self {
+        serde_json::to_value(c).unwrap()
+    }
+}
+
+fn default_mqtt_port() -> String {
+    format!("{DEFAULT_MQTT_INTERFACE}:{DEFAULT_MQTT_PORT}")
+}
+
+fn deserialize_mqtt_port<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(MqttPortVisitor)
+}
+
+struct MqttPortVisitor;
+
+impl<'de> Visitor<'de> for MqttPortVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(r#"either a port number as an integer or a string, either a string with format "<local_ip>:<port_number>""#)
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(format!("{DEFAULT_MQTT_INTERFACE}:{value}"))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let parts: Vec<&str> = value.split(':').collect();
+        if parts.len() > 2 {
+            return Err(E::invalid_value(Unexpected::Str(value), &self));
+        }
+        let (interface, port) = if parts.len() == 1 {
+            (DEFAULT_MQTT_INTERFACE, parts[0])
+        } else {
+            (parts[0], parts[1])
+        };
+        if port.parse::<u32>().is_err() {
+            return Err(E::invalid_value(Unexpected::Str(port), &self));
+        }
+        Ok(format!("{interface}:{port}"))
+    }
+}
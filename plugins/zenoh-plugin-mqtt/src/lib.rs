@@ -0,0 +1,440 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! Runs an MQTT broker endpoint bridging MQTT clients with zenoh: publications of MQTT clients are put
+//! in zenoh, and MQTT subscriptions are mapped to zenoh subscribers.
+//! MQTT topics are mapped to key expressions prefixed with the configured `scope`, the `+` and `#`
+//! wildcards of topic filters being mapped to `*` and `**`.
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::FutureExt;
+use futures::AsyncWriteExt;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU16, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::plugins::{Metric, MetricKind, Plugin, RunningPluginTrait, TaskMonitor, ZenohPlugin};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh::subscriber::Subscriber;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod config;
+mod mqtt;
+pub use config::Config;
+use mqtt::{Packet, Publish};
+
+const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
+lazy_static::lazy_static! {
+    static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
+}
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const CLIENT_QUEUE_SIZE: usize = 256;
+
+zenoh_plugin_trait::declare_plugin!(MqttPlugin);
+pub struct MqttPlugin {}
+
+impl ZenohPlugin for MqttPlugin {}
+
+impl Plugin for MqttPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = zenoh::plugins::RunningPlugin;
+    const STATIC_NAME: &'static str = "mqtt";
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+        // Try to initiate login.
+        // Required in case of dynamic lib, otherwise no logs.
+        // But cannot be done twice in case of static link.
+        let _ = env_logger::try_init();
+        log::debug!("MQTT plugin {}", LONG_VERSION.as_str());
+
+        let runtime_conf = runtime.config.lock();
+        let plugin_conf = runtime_conf
+            .plugin(name)
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+
+        let conf: Config = serde_json::from_value(plugin_conf.clone())
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        if let Some(scope) = &conf.scope {
+            OwnedKeyExpr::try_from(scope.as_str()).map_err(|e| {
+                zerror!(
+                    "Plugin `{}` configuration error: invalid scope: {}",
+                    name,
+                    e
+                )
+            })?;
+        }
+        if conf.qos.block_from > 2 || conf.qos.max_subscription > 2 {
+            bail!(
+                "Plugin `{}` configuration error: MQTT QoS must be 0, 1 or 2",
+                name
+            )
+        }
+        let clients = Arc::new(AtomicUsize::new(0));
+        let monitor = TaskMonitor::default();
+        let task = async_std::task::spawn(monitor.clone().watch(run(
+            runtime.clone(),
+            conf.clone(),
+            clients.clone(),
+        )));
+        let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
+        if let Ok(Err(e)) = task {
+            bail!("MQTT server failed within 1ms: {e}")
+        }
+        Ok(Box::new(RunningPlugin {
+            conf,
+            clients,
+            monitor,
+        }))
+    }
+}
+
+struct RunningPlugin {
+    conf: Config,
+    clients: Arc<AtomicUsize>,
+    monitor: TaskMonitor,
+}
+
+impl RunningPluginTrait for RunningPlugin {
+    fn terminated(&self) -> Option<String> {
+        self.monitor.terminated()
+    }
+
+    fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
+        Arc::new(|_, _, _| {
+            bail!("zenoh-plugin-mqtt doesn't accept any runtime configuration changes")
+        })
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<zenoh::plugins::Response>> {
+        let mut responses = Vec::new();
+        for (suffix, value) in [
+            ("/version", GIT_VERSION.into()),
+            ("/port", (&self.conf).into()),
+            ("/clients", self.clients.load(Ordering::Relaxed).into()),
+        ] {
+            let key = format!("{plugin_status_key}{suffix}");
+            if keyexpr::new(key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(key, value))
+            }
+        }
+        Ok(responses)
+    }
+
+    fn metrics(&self) -> Vec<Metric> {
+        vec![Metric::new(
+            "zenoh_mqtt_clients",
+            MetricKind::Gauge,
+            "Number of MQTT clients connected to the bridge.",
+            self.clients.load(Ordering::Relaxed) as f64,
+        )]
+    }
+}
+
+pub async fn run(runtime: Runtime, conf: Config, clients: Arc<AtomicUsize>) -> ZResult<()> {
+    // Try to initiate login.
+    // Required in case of dynamic lib, otherwise no logs.
+    // But cannot be done twice in case of static link.
+    let _ = env_logger::try_init();
+
+    let session = Arc::new(zenoh::init(runtime).res().await.unwrap());
+    let listener = match TcpListener::bind(conf.port.as_str()).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!("Unable to start MQTT server on {}: {:?}", conf.port, e);
+            return Err(e.into());
+        }
+    };
+    let conf = Arc::new(conf);
+    loop {
+        let (stream, addr) = listener.accept().await?;
+        log::debug!("MQTT client connected from {}", addr);
+        let session = session.clone();
+        let conf = conf.clone();
+        let clients = clients.clone();
+        async_std::task::spawn(async move {
+            clients.fetch_add(1, Ordering::Relaxed);
+            if let Err(e) = handle_client(session, conf, stream).await {
+                log::warn!("MQTT client {} disconnected: {}", addr, e);
+            }
+            clients.fetch_sub(1, Ordering::Relaxed);
+        });
+    }
+}
+
+/// Maps an MQTT topic (or topic filter if `filter` is true) to a zenoh key expression.
+fn topic_to_key_expr(scope: Option<&str>, topic: &str, filter: bool) -> ZResult<OwnedKeyExpr> {
+    if topic.starts_with('$') {
+        bail!("Topics starting with '$' are reserved: {}", topic)
+    }
+    let mut chunks = Vec::new();
+    let mut levels = topic.split('/').peekable();
+    while let Some(level) = levels.next() {
+        match level {
+            "+" if filter => chunks.push("*"),
+            "#" if filter && levels.peek().is_none() => chunks.push("**"),
+            level if level.contains(['+', '#', '*']) => {
+                bail!("Invalid level '{}' in MQTT topic {}", level, topic)
+            }
+            level => chunks.push(level),
+        }
+    }
+    let key_expr = chunks.join("/");
+    let key_expr = match scope {
+        Some(scope) => format!("{scope}/{key_expr}"),
+        None => key_expr,
+    };
+    OwnedKeyExpr::try_from(key_expr)
+        .map_err(|e| zerror!("MQTT topic {} can't be mapped to zenoh: {}", topic, e).into())
+}
+
+/// Maps a zenoh key expression to an MQTT topic.
+fn key_expr_to_topic(scope: Option<&str>, key_expr: &keyexpr) -> Option<String> {
+    match scope {
+        Some(scope) => key_expr
+            .as_str()
+            .strip_prefix(scope)
+            .and_then(|topic| topic.strip_prefix('/'))
+            .map(String::from),
+        None => Some(key_expr.to_string()),
+    }
+}
+
+async fn handle_client(session: Arc<Session>, conf: Arc<Config>, stream: TcpStream) -> ZResult<()> {
+    let mut reader = stream.clone();
+    let connect = match async_std::io::timeout(
+        CONNECT_TIMEOUT,
+        mqtt::read_packet(&mut reader, conf.max_packet_size),
+    )
+    .await?
+    {
+        Some(Packet::Connect(connect)) => connect,
+        Some(packet) => bail!("Expected CONNECT, received {:?}", packet),
+        None => return Ok(()),
+    };
+    let mut writer = stream.clone();
+    let return_code = if connect.protocol_level != 3 && connect.protocol_level != 4 {
+        mqtt::CONNACK_UNACCEPTABLE_PROTOCOL
+    } else if connect.client_id.is_empty() && !connect.clean_session {
+        mqtt::CONNACK_IDENTIFIER_REJECTED
+    } else {
+        mqtt::CONNACK_ACCEPTED
+    };
+    writer
+        .write_all(&Packet::ConnAck { return_code }.encode())
+        .await?;
+    if return_code != mqtt::CONNACK_ACCEPTED {
+        bail!("Connection refused with return code {}", return_code)
+    }
+    log::debug!("MQTT client '{}' connected", connect.client_id);
+
+    let (tx, rx) = flume::bounded::<Packet>(CLIENT_QUEUE_SIZE);
+    let writer_task = async_std::task::spawn(async move {
+        while let Ok(packet) = rx.recv_async().await {
+            if let Err(e) = writer.write_all(&packet.encode()).await {
+                log::debug!("Error writing to MQTT client: {}", e);
+                break;
+            }
+        }
+    });
+
+    let keep_alive = match connect.keep_alive {
+        0 => None,
+        // A client is disconnected if nothing was received within 1.5 times the keep alive period
+        keep_alive => Some(Duration::from_millis(keep_alive as u64 * 1500)),
+    };
+    let scope = conf.scope.as_deref();
+    let packet_ids = Arc::new(AtomicU16::new(0));
+    let mut subscriptions: HashMap<String, Subscriber<'static, ()>> = HashMap::new();
+    // The packet ids of the QoS 2 publications received and not released yet
+    let mut qos2_received: HashSet<u16> = HashSet::new();
+    let result: ZResult<()> = async {
+        loop {
+            let packet = match keep_alive {
+                Some(keep_alive) => {
+                    async_std::io::timeout(
+                        keep_alive,
+                        mqtt::read_packet(&mut reader, conf.max_packet_size),
+                    )
+                    .await?
+                }
+                None => mqtt::read_packet(&mut reader, conf.max_packet_size).await?,
+            };
+            match packet {
+                Some(Packet::Publish(publish)) => {
+                    let key_expr = topic_to_key_expr(scope, &publish.topic, false)?;
+                    let congestion_control = if publish.qos >= conf.qos.block_from {
+                        CongestionControl::Block
+                    } else {
+                        CongestionControl::Drop
+                    };
+                    // A QoS 2 publication is put once: its retransmissions before its PUBREL are only acknowledged
+                    let duplicate = match (publish.qos, publish.packet_id) {
+                        (2, Some(packet_id)) => !qos2_received.insert(packet_id),
+                        _ => false,
+                    };
+                    if duplicate {
+                        log::debug!("Dropping duplicate MQTT publication on {}", publish.topic);
+                    } else if let Err(e) = session
+                        .put(key_expr, publish.payload)
+                        .congestion_control(congestion_control)
+                        .res()
+                        .await
+                    {
+                        log::warn!("Error putting MQTT publication on {}: {}", publish.topic, e);
+                    }
+                    match (publish.qos, publish.packet_id) {
+                        (1, Some(packet_id)) => tx.send_async(Packet::PubAck(packet_id)).await?,
+                        (2, Some(packet_id)) => tx.send_async(Packet::PubRec(packet_id)).await?,
+                        _ => (),
+                    }
+                }
+                Some(Packet::PubRel(packet_id)) => {
+                    qos2_received.remove(&packet_id);
+                    tx.send_async(Packet::PubComp(packet_id)).await?
+                }
+                Some(Packet::PubRec(packet_id)) => tx.send_async(Packet::PubRel(packet_id)).await?,
+                Some(Packet::PubAck(_)) | Some(Packet::PubComp(_)) => (),
+                Some(Packet::Subscribe { packet_id, filters }) => {
+                    let mut return_codes = Vec::with_capacity(filters.len());
+                    for (filter, qos) in filters {
+                        let qos = qos.min(conf.qos.max_subscription);
+                        let key_expr = match topic_to_key_expr(scope, &filter, true) {
+                            Ok(key_expr) => key_expr,
+                            Err(e) => {
+                                log::warn!("MQTT subscription refused: {}", e);
+                                return_codes.push(mqtt::SUBACK_FAILURE);
+                                continue;
+                            }
+                        };
+                        let tx = tx.clone();
+                        let packet_ids = packet_ids.clone();
+                        let scope = conf.scope.clone();
+                        let subscriber = session
+                            .declare_subscriber(key_expr)
+                            .callback(move |sample| {
+                                let Some(topic) =
+                                    key_expr_to_topic(scope.as_deref(), &sample.key_expr)
+                                else {
+                                    return;
+                                };
+                                let packet_id = (qos > 0).then(|| {
+                                    // Packet identifiers must be non-zero
+                                    packet_ids.fetch_add(1, Ordering::Relaxed) % u16::MAX + 1
+                                });
+                                let publish = Packet::Publish(Publish {
+                                    dup: false,
+                                    qos,
+                                    retain: false,
+                                    topic,
+                                    packet_id,
+                                    payload: sample.value.payload.contiguous().into_owned(),
+                                });
+                                if tx.try_send(publish).is_err() {
+                                    log::warn!(
+                                        "MQTT client queue full, dropping publication on {}",
+                                        sample.key_expr
+                                    );
+                                }
+                            })
+                            .res()
+                            .await;
+                        match subscriber {
+                            Ok(subscriber) => {
+                                subscriptions.insert(filter, subscriber);
+                                return_codes.push(qos);
+                            }
+                            Err(e) => {
+                                log::warn!("Error subscribing to {}: {}", filter, e);
+                                return_codes.push(mqtt::SUBACK_FAILURE);
+                            }
+                        }
+                    }
+                    tx.send_async(Packet::SubAck {
+                        packet_id,
+                        return_codes,
+                    })
+                    .await?;
+                }
+                Some(Packet::Unsubscribe { packet_id, filters }) => {
+                    for filter in filters {
+                        subscriptions.remove(&filter);
+                    }
+                    tx.send_async(Packet::UnsubAck(packet_id)).await?;
+                }
+                Some(Packet::PingReq) => tx.send_async(Packet::PingResp).await?,
+                Some(Packet::Disconnect) => return Ok(()),
+                Some(packet) => bail!("Unexpected packet {:?}", packet),
+                None => bail!("Connection closed without DISCONNECT"),
+            }
+        }
+    }
+    .await;
+    drop(subscriptions);
+    drop(tx);
+    writer_task.await;
+
+    if result.is_err() {
+        if let Some(will) = connect.will {
+            let key_expr = topic_to_key_expr(scope, &will.topic, false)?;
+            session.put(key_expr, will.payload).res().await?;
+        }
+    }
+    log::debug!("MQTT client '{}' disconnected", connect.client_id);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topics_mapping() {
+        assert_eq!(
+            topic_to_key_expr(Some("mqtt"), "a/+/b/#", true)
+                .unwrap()
+                .as_str(),
+            "mqtt/a/*/b/**"
+        );
+        assert_eq!(
+            topic_to_key_expr(None, "a/b", false).unwrap().as_str(),
+            "a/b"
+        );
+        assert!(topic_to_key_expr(None, "a/+", false).is_err());
+        assert!(topic_to_key_expr(None, "a/#/b", true).is_err());
+        assert!(topic_to_key_expr(None, "a//b", false).is_err());
+        assert!(topic_to_key_expr(None, "$SYS/a", true).is_err());
+        assert_eq!(
+            key_expr_to_topic(Some("mqtt"), keyexpr::new("mqtt/a/b").unwrap()).as_deref(),
+            Some("a/b")
+        );
+        assert_eq!(
+            key_expr_to_topic(Some("mqtt"), keyexpr::new("mqttx/a").unwrap()),
+            None
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! A minimal codec for the MQTT 3.1 and 3.1.1 control packets.
+use futures::{AsyncRead, AsyncReadExt};
+use std::io::{Error, ErrorKind, Result};
+
+const CONNECT: u8 = 1;
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const PUBACK: u8 = 4;
+const PUBREC: u8 = 5;
+const PUBREL: u8 = 6;
+const PUBCOMP: u8 = 7;
+const SUBSCRIBE: u8 = 8;
+const SUBACK: u8 = 9;
+const UNSUBSCRIBE: u8 = 10;
+const UNSUBACK: u8 = 11;
+const PINGREQ: u8 = 12;
+const PINGRESP: u8 = 13;
+const DISCONNECT: u8 = 14;
+
+pub(crate) const CONNACK_ACCEPTED: u8 = 0;
+pub(crate) const CONNACK_UNACCEPTABLE_PROTOCOL: u8 = 1;
+pub(crate) const CONNACK_IDENTIFIER_REJECTED: u8 = 2;
+pub(crate) const SUBACK_FAILURE: u8 = 0x80;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Connect {
+    pub(crate) protocol_level: u8,
+    pub(crate) clean_session: bool,
+    pub(crate) keep_alive: u16,
+    pub(crate) client_id: String,
+    pub(crate) will: Option<Publish>,
+    pub(crate) username: Option<String>,
+    pub(crate) password: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Publish {
+    pub(crate) dup: bool,
+    pub(crate) qos: u8,
+    pub(crate) retain: bool,
+    pub(crate) topic: String,
+    pub(crate) packet_id: Option<u16>,
+    pub(crate) payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Packet {
+    Connect(Connect),
+    ConnAck {
+        return_code: u8,
+    },
+    Publish(Publish),
+    PubAck(u16),
+    PubRec(u16),
+    PubRel(u16),
+    PubComp(u16),
+    Subscribe {
+        packet_id: u16,
+        filters: Vec<(String, u8)>,
+    },
+    SubAck {
+        packet_id: u16,
+        return_codes: Vec<u8>,
+    },
+    Unsubscribe {
+        packet_id: u16,
+        filters: Vec<String>,
+    },
+    UnsubAck(u16),
+    PingReq,
+    PingResp,
+    Disconnect,
+}
+
+fn invalid(msg: impl Into<String>) -> Error {
+    Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn u8(&mut self) -> Result<u8> {
+        let (byte, rest) = self
+            .0
+            .split_first()
+            .ok_or_else(|| invalid("Truncated MQTT packet"))?;
+        self.0 = rest;
+        Ok(*byte)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes([self.u8()?, self.u8()?]))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.u16()? as usize;
+        if self.0.len() < len {
+            return Err(invalid("Truncated MQTT packet"));
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        String::from_utf8(self.bytes()?.to_vec())
+            .map_err(|_| invalid("Invalid UTF-8 string in MQTT packet"))
+    }
+}
+
+/// Reads the next packet from `reader`, returning `None` if the connection was closed between two packets.
+///
+/// A packet whose remaining length exceeds `max_packet_size` is rejected before its body is read.
+pub(crate) async fn read_packet<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    max_packet_size: usize,
+) -> Result<Option<Packet>> {
+    let mut header = [0u8];
+    if reader.read(&mut header).await? == 0 {
+        return Ok(None);
+    }
+    let mut len = 0usize;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8];
+        reader.read_exact(&mut byte).await?;
+        len |= ((byte[0] & 0x7f) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+        if shift > 21 {
+            return Err(invalid("Malformed MQTT remaining length"));
+        }
+    }
+    if len > max_packet_size {
+        return Err(invalid(format!(
+            "MQTT packet of {len} bytes exceeds the maximum packet size of {max_packet_size} bytes"
+        )));
+    }
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).await?;
+    decode(header[0], &body).map(Some)
+}
+
+fn decode(header: u8, body: &[u8]) -> Result<Packet> {
+    let flags = header & 0x0f;
+    let mut reader = Reader(body);
+    let packet = match header >> 4 {
+        CONNECT => {
+            let protocol = reader.string()?;
+            let protocol_level = reader.u8()?;
+            if protocol != "MQTT" && protocol != "MQIsdp" {
+                return Err(invalid(format!("Unknown MQTT protocol name: {protocol}")));
+            }
+            let connect_flags = reader.u8()?;
+            let keep_alive = reader.u16()?;
+            let client_id = reader.string()?;
+            let will = if connect_flags & 0x04 != 0 {
+                Some(Publish {
+                    dup: false,
+                    qos: (connect_flags >> 3) & 0x03,
+                    retain: connect_flags & 0x20 != 0,
+                    topic: reader.string()?,
+                    packet_id: None,
+                    payload: reader.bytes()?.to_vec(),
+                })
+            } else {
+                None
+            };
+            let username = if connect_flags & 0x80 != 0 {
+                Some(reader.string()?)
+            } else {
+                None
+            };
+            let password = if connect_flags & 0x40 != 0 {
+                Some(reader.bytes()?.to_vec())
+            } else {
+                None
+            };
+            Packet::Connect(Connect {
+                protocol_level,
+                clean_session: connect_flags & 0x02 != 0,
+                keep_alive,
+                client_id,
+                will,
+                username,
+                password,
+            })
+        }
+        PUBLISH => {
+            let qos = (flags >> 1) & 0x03;
+            if qos > 2 {
+                return Err(invalid("Invalid QoS in MQTT PUBLISH"));
+            }
+            let topic = reader.string()?;
+            let packet_id = if qos > 0 { Some(reader.u16()?) } else { None };
+            Packet::Publish(Publish {
+                dup: flags & 0x08 != 0,
+                qos,
+                retain: flags & 0x01 != 0,
+                topic,
+                packet_id,
+                payload: reader.0.to_vec(),
+            })
+        }
+        PUBACK => Packet::PubAck(reader.u16()?),
+        PUBREC => Packet::PubRec(reader.u16()?),
+        PUBREL => Packet::PubRel(reader.u16()?),
+        PUBCOMP => Packet::PubComp(reader.u16()?),
+        SUBSCRIBE => {
+            let packet_id = reader.u16()?;
+            let mut filters = Vec::new();
+            while !reader.0.is_empty() {
+                let filter = reader.string()?;
+                let qos = reader.u8()?;
+                if qos > 2 {
+                    return Err(invalid("Invalid QoS in MQTT SUBSCRIBE"));
+                }
+                filters.push((filter, qos));
+            }
+            if filters.is_empty() {
+                return Err(invalid("MQTT SUBSCRIBE without topic filter"));
+            }
+            Packet::Subscribe { packet_id, filters }
+        }
+        UNSUBSCRIBE => {
+            let packet_id = reader.u16()?;
+            let mut filters = Vec::new();
+            while !reader.0.is_empty() {
+                filters.push(reader.string()?);
+            }
+            if filters.is_empty() {
+                return Err(invalid("MQTT UNSUBSCRIBE without topic filter"));
+            }
+            Packet::Unsubscribe { packet_id, filters }
+        }
+        PINGREQ => Packet::PingReq,
+        DISCONNECT => Packet::Disconnect,
+        packet_type => {
+            return Err(invalid(format!(
+                "Unexpected MQTT packet type from client: {packet_type}"
+            )))
+        }
+    };
+    Ok(packet)
+}
+
+fn push_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+impl Packet {
+    /// Encodes a packet sent by the broker side of the connection.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        let header = match self {
+            Packet::ConnAck { return_code } => {
+                body.extend_from_slice(&[0, *return_code]);
+                CONNACK << 4
+            }
+            Packet::Publish(publish) => {
+                push_bytes(&mut body, publish.topic.as_bytes());
+                if let Some(packet_id) = publish.packet_id {
+                    body.extend_from_slice(&packet_id.to_be_bytes());
+                }
+                body.extend_from_slice(&publish.payload);
+                (PUBLISH << 4)
+                    | ((publish.dup as u8) << 3)
+                    | (publish.qos << 1)
+                    | publish.retain as u8
+            }
+            Packet::PubAck(packet_id) => {
+                body.extend_from_slice(&packet_id.to_be_bytes());
+                PUBACK << 4
+            }
+            Packet::PubRec(packet_id) => {
+                body.extend_from_slice(&packet_id.to_be_bytes());
+                PUBREC << 4
+            }
+            Packet::PubRel(packet_id) => {
+                body.extend_from_slice(&packet_id.to_be_bytes());
+                (PUBREL << 4) | 0x02
+            }
+            Packet::PubComp(packet_id) => {
+                body.extend_from_slice(&packet_id.to_be_bytes());
+                PUBCOMP << 4
+            }
+            Packet::SubAck {
+                packet_id,
+                return_codes,
+            } => {
+                body.extend_from_slice(&packet_id.to_be_bytes());
+                body.extend_from_slice(return_codes);
+                SUBACK << 4
+            }
+            Packet::UnsubAck(packet_id) => {
+                body.extend_from_slice(&packet_id.to_be_bytes());
+                UNSUBACK << 4
+            }
+            Packet::PingResp => PINGRESP << 4,
+            Packet::Connect(_)
+            | Packet::Subscribe { .. }
+            | Packet::Unsubscribe { .. }
+            | Packet::PingReq
+            | Packet::Disconnect => unreachable!("{self:?} is never sent by the broker"),
+        };
+        let mut buf = Vec::with_capacity(body.len() + 5);
+        buf.push(header);
+        let mut len = body.len();
+        loop {
+            let mut byte = (len % 128) as u8;
+            len /= 128;
+            if len > 0 {
+                byte |= 0x80;
+            }
+            buf.push(byte);
+            if len == 0 {
+                break;
+            }
+        }
+        buf.extend_from_slice(&body);
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(bytes: &[u8]) -> Result<Option<Packet>> {
+        async_std::task::block_on(read_packet(&mut &bytes[..], 1024))
+    }
+
+    #[test]
+    fn publish_roundtrip() {
+        let publish = Packet::Publish(Publish {
+            dup: false,
+            qos: 1,
+            retain: false,
+            topic: "sensor/temp".into(),
+            packet_id: Some(42),
+            payload: vec![0; 300],
+        });
+        assert_eq!(read(&publish.encode()).unwrap(), Some(publish));
+    }
+
+    #[test]
+    fn decode_client_packets() {
+        let connect = [
+            0x10, 0x12, 0x00, 0x04, b'M', b'Q', b'T', b'T', 0x04, 0x02, 0x00, 0x3c, 0x00, 0x06,
+            b'c', b'l', b'i', b'e', b'n', b't',
+        ];
+        assert_eq!(
+            read(&connect).unwrap(),
+            Some(Packet::Connect(Connect {
+                protocol_level: 4,
+                clean_session: true,
+                keep_alive: 60,
+                client_id: "client".into(),
+                will: None,
+                username: None,
+                password: None,
+            }))
+        );
+        let subscribe = [0x82, 0x08, 0x00, 0x01, 0x00, 0x03, b'a', b'/', b'#', 0x01];
+        assert_eq!(
+            read(&subscribe).unwrap(),
+            Some(Packet::Subscribe {
+                packet_id: 1,
+                filters: vec![("a/#".into(), 1)]
+            })
+        );
+        assert_eq!(read(&[]).unwrap(), None);
+        assert!(read(&[0x20, 0x02, 0x00, 0x00]).is_err());
+    }
+
+    #[test]
+    fn oversized_packets() {
+        let publish = Packet::Publish(Publish {
+            dup: false,
+            qos: 0,
+            retain: false,
+            topic: "sensor/temp".into(),
+            packet_id: None,
+            payload: vec![0; 2000],
+        });
+        let e = read(&publish.encode()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        // A remaining length of about 256 MiB is rejected without waiting for the body
+        let e = read(&[0x30, 0xff, 0xff, 0xff, 0x7f]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,93 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the MQTT clients' publications -
+// 1. a QoS 2 publication retransmitted before its PUBREL is put once
+// 2. a client sending a packet larger than `max_packet_size` is disconnected
+
+use async_std::net::TcpStream;
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::{AsyncReadExt, AsyncWriteExt};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_mqtt::MqttPlugin;
+use zenoh_plugin_trait::Plugin;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn expect(client: &mut TcpStream, packet: &[u8]) {
+    let mut received = vec![0; packet.len()];
+    ztimeout!(client.read_exact(&mut received)).unwrap();
+    assert_eq!(received, packet);
+}
+
+#[test]
+fn mqtt_qos2() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5(
+                "plugins/mqtt",
+                r#"{ port: "127.0.0.1:17481", max_packet_size: 1024 }"#,
+            )
+            .unwrap();
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+        let _mqtt = MqttPlugin::start("mqtt", &runtime).unwrap();
+        let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+        let subscriber = ztimeout!(session.declare_subscriber("qos2/test").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        let connect = [
+            0x10, 0x12, 0x00, 0x04, b'M', b'Q', b'T', b'T', 0x04, 0x02, 0x00, 0x3c, 0x00, 0x06,
+            b'c', b'l', b'i', b'e', b'n', b't',
+        ];
+        let mut client = ztimeout!(TcpStream::connect("127.0.0.1:17481")).unwrap();
+        ztimeout!(client.write_all(&connect)).unwrap();
+        expect(&mut client, &[0x20, 0x02, 0x00, 0x00]).await;
+
+        // A QoS 2 PUBLISH with packet id 1 on `qos2/test`, then its retransmission with the DUP flag
+        let body = [
+            0x00, 0x09, b'q', b'o', b's', b'2', b'/', b't', b'e', b's', b't', 0x00, 0x01, b'v',
+        ];
+        for header in [0x34, 0x3c] {
+            ztimeout!(client.write_all(&[&[header, body.len() as u8][..], &body].concat()))
+                .unwrap();
+            expect(&mut client, &[0x50, 0x02, 0x00, 0x01]).await;
+        }
+        ztimeout!(client.write_all(&[0x62, 0x02, 0x00, 0x01])).unwrap();
+        expect(&mut client, &[0x70, 0x02, 0x00, 0x01]).await;
+        let sample = ztimeout!(subscriber.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "v");
+        task::sleep(SLEEP).await;
+        assert!(subscriber.try_recv().is_err());
+
+        // A PUBLISH of about 256 MiB is rejected, closing the connection
+        ztimeout!(client.write_all(&[0x30, 0xff, 0xff, 0xff, 0x7f])).unwrap();
+        let mut buf = [0; 1];
+        assert!(matches!(ztimeout!(client.read(&mut buf)), Ok(0) | Err(_)));
+    });
+}
@@ -0,0 +1,279 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::config::AuthConf;
+use crate::{path_to_key_expr, response};
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use http_types::Method;
+use std::str::FromStr;
+use std::sync::Arc;
+use tide::{Next, Request, StatusCode};
+use zenoh::prelude::r#async::*;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// A middleware rejecting the requests that aren't authenticated as one of the configured users,
+/// or that aren't allowed by this user's rules.
+pub(crate) struct Authenticator {
+    users: Vec<User>,
+}
+
+struct User {
+    name: String,
+    password: Option<String>,
+    token: Option<String>,
+    rules: Option<Vec<Rule>>,
+}
+
+struct Rule {
+    methods: Option<Vec<Method>>,
+    key_exprs: Option<Vec<OwnedKeyExpr>>,
+}
+
+impl Authenticator {
+    pub(crate) fn new(conf: &AuthConf) -> ZResult<Self> {
+        let mut users = Vec::with_capacity(conf.users.len());
+        for user in &conf.users {
+            if user.private.password.is_none() && user.private.token.is_none() {
+                bail!(
+                    "REST user `{}` has neither a password nor a token",
+                    user.name
+                )
+            }
+            let rules = match &user.rules {
+                Some(rules) => Some(
+                    rules
+                        .iter()
+                        .map(|rule| {
+                            Ok(Rule {
+                                methods: match &rule.methods {
+                                    Some(methods) => Some(
+                                        methods
+                                            .iter()
+                                            .map(|m| {
+                                                Method::from_str(&m.to_uppercase()).map_err(|_| {
+                                                    zerror!("Invalid HTTP method `{}` in the rules of REST user `{}`", m, user.name)
+                                                })
+                                            })
+                                            .collect::<Result<_, _>>()?,
+                                    ),
+                                    None => None,
+                                },
+                                key_exprs: match &rule.key_exprs {
+                                    Some(key_exprs) => Some(
+                                        key_exprs
+                                            .iter()
+                                            .map(|k| {
+                                                OwnedKeyExpr::autocanonize(k.clone()).map_err(|e| {
+                                                    zerror!("Invalid key expression `{}` in the rules of REST user `{}`: {}", k, user.name, e)
+                                                })
+                                            })
+                                            .collect::<Result<_, _>>()?,
+                                    ),
+                                    None => None,
+                                },
+                            })
+                        })
+                        .collect::<ZResult<_>>()?,
+                ),
+                None => None,
+            };
+            users.push(User {
+                name: user.name.clone(),
+                password: user.private.password.clone(),
+                token: user.private.token.clone(),
+                rules,
+            })
+        }
+        Ok(Authenticator { users })
+    }
+
+    /// Returns the user matching the credentials held by an `Authorization` header.
+    fn authenticate(&self, authorization: &str) -> Option<&User> {
+        let (scheme, credentials) = authorization.trim().split_once(' ')?;
+        let credentials = credentials.trim();
+        if scheme.eq_ignore_ascii_case("bearer") {
+            self.users.iter().find(|u| {
+                u.token
+                    .as_ref()
+                    .map_or(false, |t| secure_eq(t.as_bytes(), credentials.as_bytes()))
+            })
+        } else if scheme.eq_ignore_ascii_case("basic") {
+            let credentials = b64_std_engine.decode(credentials).ok()?;
+            let credentials = String::from_utf8(credentials).ok()?;
+            let (name, password) = credentials.split_once(':')?;
+            self.users.iter().find(|u| {
+                u.name == name
+                    && u.password
+                        .as_ref()
+                        .map_or(false, |p| secure_eq(p.as_bytes(), password.as_bytes()))
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl User {
+    fn is_allowed(&self, method: Method, key_expr: &keyexpr) -> bool {
+        match &self.rules {
+            None => true,
+            Some(rules) => rules.iter().any(|rule| {
+                rule.methods
+                    .as_ref()
+                    .map_or(true, |methods| methods.contains(&method))
+                    && rule.key_exprs.as_ref().map_or(true, |key_exprs| {
+                        key_exprs.iter().any(|k| k.includes(key_expr))
+                    })
+            }),
+        }
+    }
+}
+
+/// Compares `a` and `b` in a time that doesn't depend on where they differ.
+fn secure_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[tide::utils::async_trait]
+impl tide::Middleware<(Arc<Session>, String)> for Authenticator {
+    async fn handle(
+        &self,
+        req: Request<(Arc<Session>, String)>,
+        next: Next<'_, (Arc<Session>, String)>,
+    ) -> tide::Result {
+        let user = match req
+            .header("authorization")
+            .and_then(|h| self.authenticate(h.last().as_str()))
+        {
+            Some(user) => user,
+            None => {
+                log::debug!("Unauthenticated {} request on {}", req.method(), req.url());
+                let mut response = response(StatusCode::Unauthorized, "text/plain", "Unauthorized");
+                response.insert_header(
+                    "WWW-Authenticate",
+                    r#"Basic realm="zenoh", charset="UTF-8""#,
+                );
+                response.append_header("WWW-Authenticate", r#"Bearer realm="zenoh""#);
+                return Ok(response);
+            }
+        };
+        // Requests on invalid key expressions are answered by the endpoints
+        if let Ok(key_expr) = path_to_key_expr(req.url().path(), &req.state().1) {
+            if !user.is_allowed(req.method(), &key_expr) {
+                log::debug!(
+                    "{} request on {} denied to REST user `{}`",
+                    req.method(),
+                    key_expr,
+                    user.name
+                );
+                return Ok(response(StatusCode::Forbidden, "text/plain", "Forbidden"));
+            }
+        }
+        Ok(next.run(req).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn authenticator() -> Authenticator {
+        let conf: AuthConf = serde_json::from_str(
+            r#"{
+                "users": [
+                    { "name": "alice", "private": { "password": "secret" } },
+                    {
+                        "name": "bob",
+                        "private": { "token": "bobs-token" },
+                        "rules": [
+                            { "methods": ["get"], "key_exprs": ["demo/**"] },
+                            { "key_exprs": ["bob/**"] }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        Authenticator::new(&conf).unwrap()
+    }
+
+    fn basic(credentials: &str) -> String {
+        format!("Basic {}", b64_std_engine.encode(credentials))
+    }
+
+    #[test]
+    fn authenticate() {
+        let auth = authenticator();
+        let name = |authorization: &str| auth.authenticate(authorization).map(|u| u.name.as_str());
+
+        assert_eq!(name(&basic("alice:secret")), Some("alice"));
+        assert_eq!(
+            name(&format!(
+                "  basic {} ",
+                b64_std_engine.encode("alice:secret")
+            )),
+            Some("alice")
+        );
+        assert_eq!(name("Bearer bobs-token"), Some("bob"));
+        assert_eq!(name("bearer  bobs-token"), Some("bob"));
+
+        // Wrong secrets
+        assert_eq!(name(&basic("alice:Secret")), None);
+        assert_eq!(name(&basic("alice:secret2")), None);
+        assert_eq!(name(&basic("bob:bobs-token")), None);
+        assert_eq!(name("Bearer bobs-toke"), None);
+        assert_eq!(name("Bearer secret"), None);
+
+        // Malformed headers
+        assert_eq!(name("alice:secret"), None);
+        assert_eq!(name("Basic"), None);
+        assert_eq!(name("Basic not-base64!"), None);
+        assert_eq!(name(&basic("alice-secret")), None);
+        assert_eq!(name("Digest username=\"alice\""), None);
+        assert_eq!(name(""), None);
+    }
+
+    #[test]
+    fn is_allowed() {
+        let auth = authenticator();
+        let user = |name: &str| auth.users.iter().find(|u| u.name == name).unwrap();
+        let key = |k: &str| keyexpr::new(k).unwrap().to_owned();
+
+        // Without rules, everything is allowed
+        assert!(user("alice").is_allowed(Method::Put, &key("any/key")));
+        // A rule allows its methods on its key expressions
+        let bob = user("bob");
+        assert!(bob.is_allowed(Method::Get, &key("demo/a/b")));
+        assert!(!bob.is_allowed(Method::Put, &key("demo/a")));
+        assert!(!bob.is_allowed(Method::Get, &key("other/a")));
+        // The request's key expression must be included in the rule's, not only intersect it
+        assert!(!bob.is_allowed(Method::Get, &key("**")));
+        // A rule without methods allows them all
+        assert!(bob.is_allowed(Method::Delete, &key("bob/x")));
+        assert!(bob.is_allowed(Method::Get, &key("bob/**")));
+    }
+
+    #[test]
+    fn secrets_are_optional_on_deserialization() {
+        // The configuration shown in the adminspace, without the secrets, is still a valid configuration
+        let conf: AuthConf = serde_json::from_str(
+            r#"{ "users": [{ "name": "alice", "private": { "password": "secret" } }] }"#,
+        )
+        .unwrap();
+        let shown = serde_json::to_value(&conf).unwrap();
+        assert!(shown["users"][0].get("private").is_none());
+        let conf: AuthConf = serde_json::from_value(shown).unwrap();
+        assert!(conf.users[0].private.password.is_none());
+    }
+}
@@ -12,67 +12,140 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use schemars::JsonSchema;
-use serde::de::{Unexpected, Visitor};
-use serde::{de, Deserialize, Deserializer};
-use std::fmt;
-
-const DEFAULT_HTTP_INTERFACE: &str = "[::]";
+use serde::Deserialize;
 
 #[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
-    #[serde(deserialize_with = "deserialize_http_port")]
+    #[serde(deserialize_with = "zenoh_plugin_trait::config::deserialize_http_port")]
     pub http_port: String,
+    /// The maximum size in bytes of the requests' bodies: larger ones are answered with 413 Payload Too Large.
+    #[serde(default = "default_max_body_size")]
+    pub max_body_size: usize,
+    #[serde(default)]
+    pub auth: Option<AuthConf>,
+    #[serde(default)]
+    pub cors: Option<CorsConf>,
+    #[serde(default)]
+    pub tls: Option<TlsConf>,
+    #[serde(default)]
+    pub stats: Option<StatsConf>,
+    #[serde(default)]
+    pub openapi: Option<OpenApiConf>,
     __path__: Option<String>,
     __required__: Option<bool>,
+    __on_panic__: Option<String>,
+    __max_restarts__: Option<u32>,
+    __dependencies__: Option<Vec<String>>,
     __config__: Option<String>,
 }
 
-impl From<&Config> for serde_json::Value {
-    fn from(c: &Config) -> Self {
-        serde_json::to_value(c).unwrap()
-    }
+/// Cross-origin resource sharing settings, allowing browser applications from other origins to use the REST API.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CorsConf {
+    /// The origins allowed to use the REST API (`"*"` allows any origin).
+    #[serde(default = "default_cors_any")]
+    pub allowed_origins: Vec<String>,
+    /// The headers allowed in cross-origin requests (`"*"` allows any header).
+    #[serde(default = "default_cors_any")]
+    pub allowed_headers: Vec<String>,
 }
 
-fn deserialize_http_port<'de, D>(deserializer: D) -> Result<String, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    deserializer.deserialize_any(HttpPortVisitor)
+fn default_max_body_size() -> usize {
+    64 * 1024 * 1024
 }
 
-struct HttpPortVisitor;
+fn default_cors_any() -> Vec<String> {
+    vec!["*".into()]
+}
 
-impl<'de> Visitor<'de> for HttpPortVisitor {
-    type Value = String;
+/// When set, the REST API is served over HTTPS.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConf {
+    /// Path to the server's certificate chain, in PEM format.
+    pub certificate: String,
+    /// Path to the server's private key, in PEM format.
+    pub private_key: String,
+}
 
-    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str(r#"either a port number as an integer or a string, either a string with format "<local_ip>:<port_number>""#)
-    }
+/// When set, the router's metrics are sampled and served under `/@stats` to the Grafana JSON datasource.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct StatsConf {
+    /// The sampling interval of the metrics, in seconds.
+    #[serde(default = "default_stats_interval")]
+    pub interval: u64,
+    /// The duration the samples are kept for, in seconds.
+    #[serde(default = "default_stats_window")]
+    pub window: u64,
+}
 
-    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
-    where
-        E: de::Error,
-    {
-        Ok(format!("{DEFAULT_HTTP_INTERFACE}:{value}"))
-    }
+/// Enriches the OpenAPI document served under `/@openapi` with the key expressions of the application.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct OpenApiConf {
+    /// The title of the document.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// The key expression prefixes documented as paths of their own (e.g. `demo/example`).
+    #[serde(default)]
+    pub key_exprs: Vec<String>,
+}
+
+fn default_stats_interval() -> u64 {
+    10
+}
 
-    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-    where
-        E: de::Error,
-    {
-        let parts: Vec<&str> = value.split(':').collect();
-        if parts.len() > 2 {
-            return Err(E::invalid_value(Unexpected::Str(value), &self));
-        }
-        let (interface, port) = if parts.len() == 1 {
-            (DEFAULT_HTTP_INTERFACE, parts[0])
-        } else {
-            (parts[0], parts[1])
-        };
-        if port.parse::<u32>().is_err() {
-            return Err(E::invalid_value(Unexpected::Str(port), &self));
-        }
-        Ok(format!("{interface}:{port}"))
+fn default_stats_window() -> u64 {
+    3600
+}
+
+/// When set, only requests authenticated as one of the `users` are served, according to their `rules`.
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConf {
+    pub users: Vec<UserConf>,
+}
+
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct UserConf {
+    /// The user's name, which is also its username for basic authentication.
+    pub name: String,
+    /// The user's secrets, hidden from the adminspace.
+    #[serde(default, skip_serializing)]
+    pub private: UserSecretsConf,
+    /// The requests allowed for this user: if unset, all requests are allowed.
+    #[serde(default)]
+    pub rules: Option<Vec<RuleConf>>,
+}
+
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct UserSecretsConf {
+    /// The password to authenticate with basic authentication (`Authorization: Basic ...`).
+    #[serde(default)]
+    pub password: Option<String>,
+    /// The token to authenticate with bearer authentication (`Authorization: Bearer <token>`).
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(JsonSchema, Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConf {
+    /// The HTTP methods allowed by this rule: if unset, all methods are allowed.
+    #[serde(default)]
+    pub methods: Option<Vec<String>>,
+    /// The key expressions that the requests' key expressions must be included in: if unset, all key expressions are allowed.
+    #[serde(default)]
+    pub key_exprs: Option<Vec<String>>,
+}
+
+impl From<&Config> for serde_json::Value {
+    fn from(c: &Config) -> Self {
+        serde_json::to_value(c).unwrap()
     }
 }
@@ -19,15 +19,17 @@
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
 use async_std::prelude::FutureExt;
 use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
-use futures::StreamExt;
+use futures::{AsyncReadExt, StreamExt, TryStreamExt};
 use http_types::Method;
 use std::convert::TryFrom;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tide::http::Mime;
 use tide::sse::Sender;
-use tide::{Request, Response, Server, StatusCode};
-use zenoh::plugins::{Plugin, RunningPluginTrait, ZenohPlugin};
+use tide::{Body, Request, Response, Server, StatusCode};
+use zenoh::buffers::{SplitBuffer, ZBuf};
+use zenoh::plugins::{Plugin, RunningPluginTrait, TaskMonitor, ZenohPlugin};
 use zenoh::prelude::r#async::*;
 use zenoh::properties::Properties;
 use zenoh::query::{QueryConsolidation, Reply};
@@ -36,7 +38,11 @@ use zenoh::selector::TIME_RANGE_KEY;
 use zenoh::Session;
 use zenoh_result::{bail, zerror, ZResult};
 
+mod auth;
 mod config;
+mod openapi;
+mod stats;
+mod tls;
 pub use config::Config;
 
 const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
@@ -44,6 +50,17 @@ lazy_static::lazy_static! {
     static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
 }
 const RAW_KEY: &str = "_raw";
+const CONSOLIDATION_KEY: &str = "_consolidation";
+const TIMEOUT_KEY: &str = "_timeout";
+const SSE_KEEP_ALIVE: Duration = Duration::from_secs(10);
+const SSE_KEEP_ALIVE_EVENT: &str = "keepalive";
+const SSE_SEND_TIMEOUT: Duration = Duration::from_secs(10);
+const BODY_SLICE_SIZE: usize = 1024 * 1024;
+const HEALTH_LIVE_PATH: &str = "/@/health/live";
+const HEALTH_READY_PATH: &str = "/@/health/ready";
+const STATS_PATH: &str = "/@stats";
+const OPENAPI_PATH: &str = "/@openapi";
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
 
 fn value_to_json(value: Value) -> String {
     // @TODO: transcode to JSON when implemented in Value
@@ -99,21 +116,14 @@ fn result_to_json(sample: Result<Sample, Value>) -> String {
     }
 }
 
-async fn to_json(results: flume::Receiver<Reply>) -> String {
-    let values = results
-        .stream()
-        .filter_map(move |reply| async move { Some(result_to_json(reply.sample)) })
-        .collect::<Vec<String>>()
-        .await
-        .join(",\n");
-    format!("[\n{values}\n]\n")
-}
-
-async fn to_json_response(results: flume::Receiver<Reply>) -> Response {
-    response(
-        StatusCode::Ok,
+fn to_json_response(results: flume::Receiver<Reply>) -> Response {
+    streamed_response(
         Mime::from_str("application/json").unwrap(),
-        &to_json(results).await,
+        "[\n",
+        ",\n",
+        "\n]\n",
+        results,
+        result_to_json,
     )
 }
 
@@ -137,38 +147,116 @@ fn result_to_html(sample: Result<Sample, Value>) -> String {
     }
 }
 
-async fn to_html(results: flume::Receiver<Reply>) -> String {
-    let values = results
-        .stream()
-        .filter_map(move |reply| async move { Some(result_to_html(reply.sample)) })
-        .collect::<Vec<String>>()
-        .await
-        .join("\n");
-    format!("<dl>\n{values}\n</dl>\n")
+fn to_html_response(results: flume::Receiver<Reply>) -> Response {
+    streamed_response(
+        Mime::from_str("text/html").unwrap(),
+        "<dl>\n",
+        "\n",
+        "\n</dl>\n",
+        results,
+        result_to_html,
+    )
 }
 
-async fn to_html_response(results: flume::Receiver<Reply>) -> Response {
-    response(StatusCode::Ok, "text/html", &to_html(results).await)
+/// Builds a response whose body is sent (with chunked transfer encoding) as the replies are received,
+/// so that they never need to be buffered all together.
+fn streamed_response(
+    content_type: Mime,
+    header: &'static str,
+    separator: &'static str,
+    footer: &'static str,
+    results: flume::Receiver<Reply>,
+    format: fn(Result<Sample, Value>) -> String,
+) -> Response {
+    let replies = results.into_stream().enumerate().map(move |(i, reply)| {
+        let reply = format(reply.sample);
+        Ok(if i == 0 {
+            reply
+        } else {
+            [separator, &reply].concat()
+        })
+    });
+    let body = futures::stream::iter([Ok(header.to_string())])
+        .chain(replies)
+        .chain(futures::stream::iter([Ok(footer.to_string())]))
+        .into_async_read();
+    let mut response = Response::new(StatusCode::Ok);
+    response.set_body(Body::from_reader(body, None));
+    response.set_content_type(content_type);
+    response
 }
 
 async fn to_raw_response(results: flume::Receiver<Reply>) -> Response {
     match results.recv_async().await {
         Ok(reply) => match reply.sample {
-            Ok(sample) => response(
-                StatusCode::Ok,
-                sample.value.encoding.to_string().as_ref(),
-                String::from_utf8_lossy(&sample.payload.contiguous()).as_ref(),
-            ),
-            Err(value) => response(
-                StatusCode::Ok,
-                value.encoding.to_string().as_ref(),
-                String::from_utf8_lossy(&value.payload.contiguous()).as_ref(),
-            ),
+            Ok(sample) => raw_response(sample.value),
+            Err(value) => raw_response(value),
         },
         Err(_) => response(StatusCode::Ok, "", ""),
     }
 }
 
+/// Builds a response whose body is `value`'s payload, streamed from its slices without copying them.
+fn raw_response(value: Value) -> Response {
+    let len = value.payload.len();
+    let slices = value.payload.zslices().cloned().collect::<Vec<_>>();
+    let body = futures::stream::iter(slices.into_iter().map(Ok)).into_async_read();
+    let mut response = Response::new(StatusCode::Ok);
+    response.set_body(Body::from_reader(body, Some(len)));
+    if let Ok(mime) = Mime::from_str(&value.encoding.to_string()) {
+        response.set_content_type(mime);
+    }
+    response
+}
+
+/// Reads `req`'s body through a buffer of at most [`BODY_SLICE_SIZE`] bytes, each read slice being copied
+/// into a slice of the payload, so that large values are never reallocated nor copied into a contiguous buffer.
+///
+/// Bodies larger than `max_size` bytes are rejected with 413 Payload Too Large, and read errors with 204 No Content.
+async fn read_body<State>(req: &mut Request<State>, max_size: usize) -> tide::Result<ZBuf> {
+    let too_large = || {
+        tide::Error::from_str(
+            StatusCode::PayloadTooLarge,
+            format!("The body exceeds the maximum size of {max_size} bytes"),
+        )
+    };
+    if req.len().map_or(false, |len| len > max_size) {
+        return Err(too_large());
+    }
+    let slice_size = req
+        .len()
+        .map_or(BODY_SLICE_SIZE, |len| len.min(BODY_SLICE_SIZE));
+    let mut buffer = vec![0; slice_size];
+    let mut body = req.take_body();
+    let mut payload = ZBuf::empty();
+    let mut size = 0;
+    loop {
+        let mut len = 0;
+        while len < buffer.len() {
+            match body
+                .read(&mut buffer[len..])
+                .await
+                .map_err(|e| tide::Error::new(StatusCode::NoContent, e))?
+            {
+                0 => break,
+                n => len += n,
+            }
+        }
+        if len == 0 {
+            break;
+        }
+        size += len;
+        if size > max_size {
+            return Err(too_large());
+        }
+        payload.push_zslice(buffer[..len].to_vec().into());
+        if len < buffer.len() {
+            break;
+        }
+    }
+    Ok(payload)
+}
+
 fn method_to_kind(method: Method) -> SampleKind {
     match method {
         Method::Put => SampleKind::Put,
@@ -180,7 +268,6 @@ fn method_to_kind(method: Method) -> SampleKind {
 fn response(status: StatusCode, content_type: impl TryInto<Mime>, body: &str) -> Response {
     let mut builder = Response::builder(status)
         .header("content-length", body.len().to_string())
-        .header("Access-Control-Allow-Origin", "*")
         .body(body);
     if let Ok(mime) = content_type.try_into() {
         builder = builder.content_type(mime);
@@ -232,17 +319,31 @@ impl Plugin for RestPlugin {
 
         let conf: Config = serde_json::from_value(plugin_conf.clone())
             .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
-        let task = async_std::task::spawn(run(runtime.clone(), conf.clone()));
+        if let Some(auth) = &conf.auth {
+            auth::Authenticator::new(auth)
+                .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        }
+        if let Some(tls) = &conf.tls {
+            tls::server_config(tls)
+                .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        }
+        let monitor = TaskMonitor::default();
+        let task =
+            async_std::task::spawn(monitor.clone().watch(run(runtime.clone(), conf.clone())));
         let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
         if let Ok(Err(e)) = task {
             bail!("REST server failed within 1ms: {e}")
         }
-        Ok(Box::new(RunningPlugin(conf)))
+        Ok(Box::new(RunningPlugin(conf, monitor)))
     }
 }
 
-struct RunningPlugin(Config);
+struct RunningPlugin(Config, TaskMonitor);
 impl RunningPluginTrait for RunningPlugin {
+    fn terminated(&self) -> Option<String> {
+        self.1.terminated()
+    }
+
     fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
         Arc::new(|_, _, _| {
             bail!("zenoh-plugin-rest doesn't accept any runtime configuration changes")
@@ -296,7 +397,10 @@ fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     result
 }
 
-async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+async fn query(
+    mut req: Request<(Arc<Session>, String)>,
+    max_body_size: usize,
+) -> tide::Result<Response> {
     log::trace!("Incoming GET request: {:?}", req);
 
     let first_accept = match req.header("accept") {
@@ -312,69 +416,34 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
         None => "application/json".to_string(),
     };
     if first_accept == "text/event-stream" {
+        let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
+            Ok(ke) => ke.into_owned(),
+            Err(e) => {
+                return Ok(response(
+                    StatusCode::BadRequest,
+                    "text/plain",
+                    &e.to_string(),
+                ))
+            }
+        };
         Ok(tide::sse::upgrade(
             req,
-            move |req: Request<(Arc<Session>, String)>, sender: Sender| async move {
-                let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
-                    Ok(ke) => ke.into_owned(),
-                    Err(e) => {
-                        return Err(tide::Error::new(
-                            tide::StatusCode::BadRequest,
-                            anyhow::anyhow!("{}", e),
-                        ))
-                    }
-                };
-                async_std::task::spawn(async move {
-                    log::debug!(
-                        "Subscribe to {} for SSE stream (task {})",
-                        key_expr,
-                        async_std::task::current().id()
-                    );
-                    let sender = &sender;
-                    let sub = req
-                        .state()
-                        .0
-                        .declare_subscriber(&key_expr)
-                        .res()
-                        .await
-                        .unwrap();
-                    loop {
-                        let sample = sub.recv_async().await.unwrap();
-                        match sender
-                            .send(&sample.kind.to_string(), sample_to_json(sample), None)
-                            .timeout(std::time::Duration::new(10, 0))
-                            .await
-                        {
-                            Ok(Ok(_)) => {}
-                            Ok(Err(e)) => {
-                                log::debug!(
-                                    "SSE error ({})! Unsubscribe and terminate (task {})",
-                                    e,
-                                    async_std::task::current().id()
-                                );
-                                if let Err(e) = sub.undeclare().res().await {
-                                    log::error!("Error undeclaring subscriber: {}", e);
-                                }
-                                break;
-                            }
-                            Err(_) => {
-                                log::debug!(
-                                    "SSE timeout! Unsubscribe and terminate (task {})",
-                                    async_std::task::current().id()
-                                );
-                                if let Err(e) = sub.undeclare().res().await {
-                                    log::error!("Error undeclaring subscriber: {}", e);
-                                }
-                                break;
-                            }
-                        }
-                    }
-                });
-                Ok(())
+            move |req: Request<(Arc<Session>, String)>, sender: Sender| {
+                let key_expr = key_expr.clone();
+                async move {
+                    async_std::task::spawn(sse_stream(req.state().0.clone(), key_expr, sender));
+                    Ok(())
+                }
             },
         ))
     } else {
-        let body = req.body_bytes().await.unwrap_or_default();
+        let body = match read_body(&mut req, max_body_size).await {
+            Ok(body) => body,
+            Err(e) if e.status() == StatusCode::PayloadTooLarge => {
+                return Ok(response(e.status(), "text/plain", &e.to_string()))
+            }
+            Err(_) => ZBuf::empty(),
+        };
         let url = req.url();
         let key_expr = match path_to_key_expr(url.path(), &req.state().1) {
             Ok(ke) => ke,
@@ -392,13 +461,15 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
         } else {
             key_expr.into()
         };
-        let consolidation = if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY) {
-            QueryConsolidation::from(zenoh::query::ConsolidationMode::None)
-        } else {
-            QueryConsolidation::from(zenoh::query::ConsolidationMode::Latest)
+        let (consolidation, timeout) = match query_options(&selector) {
+            Ok(options) => options,
+            Err(e) => return Ok(response(StatusCode::BadRequest, "text/plain", &e)),
         };
         let raw = selector.decode().any(|(k, _)| k.as_ref() == RAW_KEY);
         let mut query = req.state().0.get(&selector).consolidation(consolidation);
+        if let Some(timeout) = timeout {
+            query = query.timeout(timeout);
+        }
         if !body.is_empty() {
             let encoding: Encoding = req
                 .content_type()
@@ -411,9 +482,9 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
                 if raw {
                     Ok(to_raw_response(receiver).await)
                 } else if first_accept == "text/html" {
-                    Ok(to_html_response(receiver).await)
+                    Ok(to_html_response(receiver))
                 } else {
-                    Ok(to_json_response(receiver).await)
+                    Ok(to_json_response(receiver))
                 }
             }
             Err(e) => Ok(response(
@@ -425,9 +496,133 @@ async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
     }
 }
 
-async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+/// The consolidation and the timeout requested by the `_consolidation` and `_timeout` parameters of a query.
+///
+/// Without `_consolidation`, the replies are not consolidated if a time range is requested, and only the latest ones are kept otherwise.
+fn query_options(selector: &Selector) -> Result<(QueryConsolidation, Option<Duration>), String> {
+    let mut consolidation = None;
+    let mut timeout = None;
+    for (k, v) in selector.decode() {
+        match k.as_ref() {
+            CONSOLIDATION_KEY => {
+                consolidation = Some(match v.as_ref() {
+                    "none" => zenoh::query::ConsolidationMode::None,
+                    "monotonic" => zenoh::query::ConsolidationMode::Monotonic,
+                    "latest" => zenoh::query::ConsolidationMode::Latest,
+                    _ => {
+                        return Err(format!(
+                            "Invalid {CONSOLIDATION_KEY} `{v}`: expected none, monotonic or latest"
+                        ))
+                    }
+                })
+            }
+            TIMEOUT_KEY => {
+                let millis = v.parse::<u64>().map_err(|e| {
+                    format!("Invalid {TIMEOUT_KEY} `{v}`: expected milliseconds ({e})")
+                })?;
+                timeout = Some(Duration::from_millis(millis));
+            }
+            _ => {}
+        }
+    }
+    let consolidation = consolidation.unwrap_or_else(|| {
+        if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY)
+            && !matches!(selector.time_instant(), Ok(Some(_)))
+        {
+            zenoh::query::ConsolidationMode::None
+        } else {
+            zenoh::query::ConsolidationMode::Latest
+        }
+    });
+    Ok((QueryConsolidation::from(consolidation), timeout))
+}
+
+/// Streams the samples matching `key_expr` as SSE events (named after the samples' kind) until the client disconnects.
+///
+/// A `keepalive` event is sent whenever no sample was received for [`SSE_KEEP_ALIVE`], so that a disconnection is noticed
+/// even on a quiet key expression.
+async fn sse_stream(session: Arc<Session>, key_expr: KeyExpr<'static>, sender: Sender) {
+    let task = async_std::task::current().id();
+    log::debug!("Subscribe to {} for SSE stream (task {})", key_expr, task);
+    let sub = match session.declare_subscriber(&key_expr).res().await {
+        Ok(sub) => sub,
+        Err(e) => {
+            log::error!("Error subscribing to {} for SSE stream: {}", key_expr, e);
+            return;
+        }
+    };
+    loop {
+        let (event, data) = match sub.recv_async().timeout(SSE_KEEP_ALIVE).await {
+            Ok(Ok(sample)) => (sample.kind.to_string(), sample_to_json(sample)),
+            Ok(Err(_)) => {
+                log::debug!("SSE subscriber closed! Terminate (task {})", task);
+                break;
+            }
+            Err(_) => (SSE_KEEP_ALIVE_EVENT.to_string(), String::new()),
+        };
+        match sender
+            .send(&event, data, None)
+            .timeout(SSE_SEND_TIMEOUT)
+            .await
+        {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => {
+                log::debug!(
+                    "SSE error ({})! Unsubscribe and terminate (task {})",
+                    e,
+                    task
+                );
+                break;
+            }
+            Err(_) => {
+                log::debug!("SSE timeout! Unsubscribe and terminate (task {})", task);
+                break;
+            }
+        }
+    }
+    if let Err(e) = sub.undeclare().res().await {
+        log::error!("Error undeclaring subscriber: {}", e);
+    }
+}
+
+/// Answers liveness and readiness probes with the router's health, as reported by its admin space:
+/// `200 OK` if the router is live (resp. ready), `503 Service Unavailable` otherwise.
+async fn health(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+    let check = if req.url().path() == HEALTH_READY_PATH {
+        "ready"
+    } else {
+        "live"
+    };
+    let (session, zid) = req.state();
+    let health = match session
+        .get(format!("@/router/{zid}/health"))
+        .timeout(HEALTH_TIMEOUT)
+        .res()
+        .await
+    {
+        Ok(replies) => match replies.recv_async().await.map(|reply| reply.sample) {
+            Ok(Ok(sample)) => {
+                serde_json::from_slice::<serde_json::Value>(&sample.value.payload.contiguous())
+                    .unwrap_or_default()
+            }
+            _ => serde_json::Value::Null,
+        },
+        Err(_) => serde_json::Value::Null,
+    };
+    let status = if health[check].as_bool().unwrap_or(false) {
+        StatusCode::Ok
+    } else {
+        StatusCode::ServiceUnavailable
+    };
+    Ok(response(status, "application/json", &health.to_string()))
+}
+
+async fn write(
+    mut req: Request<(Arc<Session>, String)>,
+    max_body_size: usize,
+) -> tide::Result<Response> {
     log::trace!("Incoming PUT request: {:?}", req);
-    match req.body_bytes().await {
+    match read_body(&mut req, max_body_size).await {
         Ok(bytes) => {
             let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
                 Ok(ke) => ke,
@@ -462,11 +657,7 @@ async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
                 )),
             }
         }
-        Err(e) => Ok(response(
-            StatusCode::NoContent,
-            "text/plain",
-            &e.to_string(),
-        )),
+        Err(e) => Ok(response(e.status(), "text/plain", &e.to_string())),
     }
 }
 
@@ -479,18 +670,51 @@ pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
     let zid = runtime.zid.to_string();
     let session = zenoh::init(runtime).res().await.unwrap();
 
-    let mut app = Server::with_state((Arc::new(session), zid));
-    app.with(
-        tide::security::CorsMiddleware::new()
-            .allow_methods(
-                "GET, POST, PUT, PATCH, DELETE"
+    let session = Arc::new(session);
+    let stats = conf
+        .stats
+        .as_ref()
+        .map(|stats| stats::server(session.clone(), zid.clone(), stats));
+    let openapi = Arc::new(openapi::document(&conf).to_string());
+    let mut app = Server::with_state((session, zid));
+    let mut cors = tide::security::CorsMiddleware::new()
+        .allow_methods(
+            "GET, POST, PUT, PATCH, DELETE"
+                .parse::<http_types::headers::HeaderValue>()
+                .unwrap(),
+        )
+        .allow_origin(tide::security::Origin::from("*"))
+        .allow_credentials(false);
+    if let Some(cors_conf) = &conf.cors {
+        cors = cors
+            .allow_origin(tide::security::Origin::from(
+                cors_conf.allowed_origins.clone(),
+            ))
+            .allow_headers(
+                cors_conf
+                    .allowed_headers
+                    .join(", ")
                     .parse::<http_types::headers::HeaderValue>()
-                    .unwrap(),
-            )
-            .allow_origin(tide::security::Origin::from("*"))
-            .allow_credentials(false),
-    );
+                    .map_err(|e| zerror!("Invalid CORS allowed headers: {}", e))?,
+            );
+    }
+    app.with(cors);
+    if let Some(auth) = &conf.auth {
+        app.with(auth::Authenticator::new(auth)?);
+    }
 
+    app.at(HEALTH_LIVE_PATH).get(health);
+    app.at(HEALTH_READY_PATH).get(health);
+    if let Some(stats) = stats {
+        app.at(STATS_PATH).nest(stats);
+    }
+    app.at(OPENAPI_PATH).get(move |_| {
+        let openapi = openapi.clone();
+        async move { Ok(response(StatusCode::Ok, "application/json", &openapi)) }
+    });
+    let max_body_size = conf.max_body_size;
+    let query = move |req| query(req, max_body_size);
+    let write = move |req| write(req, max_body_size);
     app.at("/")
         .get(query)
         .post(query)
@@ -504,9 +728,13 @@ pub async fn run(runtime: Runtime, conf: Config) -> ZResult<()> {
         .patch(write)
         .delete(write);
 
-    if let Err(e) = app.listen(conf.http_port).await {
+    let result = match &conf.tls {
+        Some(tls) => tls::listen(app, &conf.http_port, tls).await,
+        None => app.listen(conf.http_port).await.map_err(|e| e.into()),
+    };
+    if let Err(e) = result {
         log::error!("Unable to start http server for REST: {:?}", e);
-        return Err(e.into());
+        return Err(e);
     }
     Ok(())
 }
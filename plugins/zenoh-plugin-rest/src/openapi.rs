@@ -0,0 +1,294 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The `GET /@openapi` endpoint, serving an OpenAPI 3 document describing the REST API,
+//! for client SDKs to be generated against a running router.
+//!
+//! The key expressions are documented by a generic `/{key_expr}` path, and by a path of their own
+//! for each prefix configured in `openapi/key_exprs`.
+use crate::config::Config;
+use crate::{
+    CONSOLIDATION_KEY, GIT_VERSION, HEALTH_LIVE_PATH, HEALTH_READY_PATH, RAW_KEY, STATS_PATH,
+    TIMEOUT_KEY,
+};
+use serde_json::{json, Map, Value};
+use zenoh::prelude::OwnedKeyExpr;
+use zenoh::selector::TIME_RANGE_KEY;
+
+const DEFAULT_TITLE: &str = "Zenoh REST API";
+
+/// Generates the OpenAPI document of the REST API configured by `conf`.
+pub(crate) fn document(conf: &Config) -> Value {
+    let mut paths = Map::new();
+    paths.insert(
+        "/{key_expr}".into(),
+        key_expr_operations("", "the key expression"),
+    );
+    for prefix in openapi_prefixes(conf) {
+        paths.insert(
+            format!("/{prefix}/{{key_expr}}"),
+            key_expr_operations(&prefix, &format!("the key expression under `{prefix}`")),
+        );
+    }
+    for (path, check) in [(HEALTH_LIVE_PATH, "live"), (HEALTH_READY_PATH, "ready")] {
+        paths.insert(
+            path.into(),
+            json!({
+                "get": {
+                    "operationId": format!("health_{check}"),
+                    "summary": format!("Whether the router is {check}"),
+                    "tags": ["health"],
+                    "responses": {
+                        "200": {
+                            "description": format!("The router is {check}"),
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Health" } } },
+                        },
+                        "503": {
+                            "description": format!("The router is not {check}"),
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Health" } } },
+                        },
+                    },
+                },
+            }),
+        );
+    }
+    if conf.stats.is_some() {
+        stats_operations(&mut paths);
+    }
+
+    let mut document = json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": conf.openapi.as_ref().and_then(|o| o.title.as_deref()).unwrap_or(DEFAULT_TITLE),
+            "version": GIT_VERSION,
+        },
+        "paths": paths,
+        "components": {
+            "parameters": parameters(),
+            "schemas": {
+                "Sample": {
+                    "type": "object",
+                    "properties": {
+                        "key": { "type": "string", "description": "The key of the sample, `ERROR` for an error reply." },
+                        "value": { "description": "The value: JSON for the JSON, integer and float encodings, a string for the text encodings, base64 otherwise." },
+                        "encoding": { "type": "string" },
+                        "time": { "type": "string", "description": "The timestamp of the sample, `None` if it has none." },
+                    },
+                    "required": ["key", "value", "encoding"],
+                },
+                "Health": { "type": "object", "additionalProperties": true },
+            },
+        },
+    });
+    if conf.auth.is_some() {
+        document["components"]["securitySchemes"] = json!({
+            "basic": { "type": "http", "scheme": "basic" },
+            "bearer": { "type": "http", "scheme": "bearer" },
+        });
+        document["security"] = json!([{ "basic": [] }, { "bearer": [] }]);
+    }
+    document
+}
+
+// The valid prefixes configured in `openapi/key_exprs`
+fn openapi_prefixes(conf: &Config) -> Vec<String> {
+    let Some(openapi) = &conf.openapi else {
+        return vec![];
+    };
+    openapi
+        .key_exprs
+        .iter()
+        .filter_map(
+            |k| match OwnedKeyExpr::autocanonize(k.trim_matches('/').to_string()) {
+                Ok(k) => Some(k.to_string()),
+                Err(e) => {
+                    log::warn!(
+                        "Invalid key expression `{}` in the OpenAPI configuration: {}",
+                        k,
+                        e
+                    );
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+fn operation_id(method: &str, prefix: &str) -> String {
+    if prefix.is_empty() {
+        return method.to_string();
+    }
+    let prefix = prefix
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    format!("{method}_{prefix}")
+}
+
+fn key_expr_operations(prefix: &str, target: &str) -> Value {
+    let tags = if prefix.is_empty() {
+        json!(["key expressions"])
+    } else {
+        json!([prefix])
+    };
+    let query_parameters = json!([
+        { "$ref": "#/components/parameters/KeyExpr" },
+        { "$ref": "#/components/parameters/Time" },
+        { "$ref": "#/components/parameters/Consolidation" },
+        { "$ref": "#/components/parameters/Timeout" },
+        { "$ref": "#/components/parameters/Raw" },
+        { "$ref": "#/components/parameters/Parameters" },
+    ]);
+    let replies = json!({
+        "200": {
+            "description": "The replies to the query, as JSON (default), HTML, a stream of server-sent events (which subscribes to the key expression instead), or the raw value of the first reply with `_raw`.",
+            "content": {
+                "application/json": { "schema": { "type": "array", "items": { "$ref": "#/components/schemas/Sample" } } },
+                "text/html": { "schema": { "type": "string" } },
+                "text/event-stream": { "schema": { "type": "string" } },
+                "*/*": { "schema": { "type": "string", "format": "binary" } },
+            },
+        },
+        "400": { "description": "Invalid key expression or parameters", "content": { "text/plain": { "schema": { "type": "string" } } } },
+        "413": { "description": "The value exceeds the maximum body size", "content": { "text/plain": { "schema": { "type": "string" } } } },
+        "500": { "description": "The query failed", "content": { "text/plain": { "schema": { "type": "string" } } } },
+    });
+    let publication = |method: &str, summary: String| {
+        let mut operation = json!({
+            "operationId": operation_id(method, prefix),
+            "summary": summary,
+            "tags": tags,
+            "parameters": [{ "$ref": "#/components/parameters/KeyExpr" }],
+            "responses": {
+                "200": { "description": "The publication was sent" },
+                "413": { "description": "The value exceeds the maximum body size", "content": { "text/plain": { "schema": { "type": "string" } } } },
+                "500": { "description": "The publication failed", "content": { "text/plain": { "schema": { "type": "string" } } } },
+            },
+        });
+        if method != "delete" {
+            operation["requestBody"] = json!({
+                "description": "The value, whose encoding is the `Content-Type` of the request.",
+                "content": { "*/*": { "schema": { "type": "string", "format": "binary" } } },
+            });
+        }
+        operation
+    };
+    json!({
+        "get": {
+            "operationId": operation_id("get", prefix),
+            "summary": format!("Query {target}"),
+            "tags": tags,
+            "parameters": query_parameters,
+            "responses": replies,
+        },
+        "post": {
+            "operationId": operation_id("post", prefix),
+            "summary": format!("Query {target} with a value"),
+            "tags": tags,
+            "parameters": query_parameters,
+            "requestBody": {
+                "description": "The value sent with the query, whose encoding is the `Content-Type` of the request.",
+                "content": { "*/*": { "schema": { "type": "string", "format": "binary" } } },
+            },
+            "responses": replies,
+        },
+        "put": publication("put", format!("Put a value on {target}")),
+        "patch": publication("patch", format!("Put a value on {target}")),
+        "delete": publication("delete", format!("Delete {target}")),
+    })
+}
+
+fn parameters() -> Value {
+    json!({
+        "KeyExpr": {
+            "name": "key_expr",
+            "in": "path",
+            "required": true,
+            "description": "A key expression, whose chunks are separated by `/` (e.g. `demo/**`).",
+            "schema": { "type": "string" },
+        },
+        "Time": {
+            "name": TIME_RANGE_KEY,
+            "in": "query",
+            "description": "The time range of the samples (e.g. `[now(-1h)..]`): the replies are not consolidated unless it is a single instant.",
+            "schema": { "type": "string" },
+        },
+        "Consolidation": {
+            "name": CONSOLIDATION_KEY,
+            "in": "query",
+            "description": "The consolidation of the replies, `latest` by default (`none` with a time range).",
+            "schema": { "type": "string", "enum": ["none", "monotonic", "latest"] },
+        },
+        "Timeout": {
+            "name": TIMEOUT_KEY,
+            "in": "query",
+            "description": "The timeout of the query, in milliseconds.",
+            "schema": { "type": "integer", "minimum": 0 },
+        },
+        "Raw": {
+            "name": RAW_KEY,
+            "in": "query",
+            "description": "Answer with the raw value of the first reply.",
+            "allowEmptyValue": true,
+            "schema": { "type": "string" },
+        },
+        "Parameters": {
+            "name": "parameters",
+            "in": "query",
+            "description": "The other parameters of the selector, forwarded to the queryables.",
+            "style": "form",
+            "explode": true,
+            "schema": { "type": "object", "additionalProperties": { "type": "string" } },
+        },
+    })
+}
+
+fn stats_operations(paths: &mut Map<String, Value>) {
+    let json_body =
+        json!({ "content": { "application/json": { "schema": { "type": "object" } } } });
+    let json_array = json!({
+        "description": "The result, in the format of the Grafana JSON datasource",
+        "content": { "application/json": { "schema": { "type": "array", "items": {} } } },
+    });
+    paths.insert(
+        STATS_PATH.into(),
+        json!({ "get": { "operationId": "stats_test", "summary": "Test the connection to the metrics", "tags": ["stats"], "responses": { "200": { "description": "The metrics are available" } } } }),
+    );
+    for (path, id, summary) in [
+        (
+            "metrics",
+            "stats_metrics",
+            "The names of the sampled series",
+        ),
+        ("search", "stats_search", "The names of the sampled series"),
+        (
+            "query",
+            "stats_query",
+            "The samples of the requested series over the requested range",
+        ),
+    ] {
+        paths.insert(
+            format!("{STATS_PATH}/{path}"),
+            json!({
+                "post": {
+                    "operationId": id,
+                    "summary": summary,
+                    "tags": ["stats"],
+                    "requestBody": json_body,
+                    "responses": { "200": json_array, "400": { "description": "Invalid request" } },
+                },
+            }),
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The `/@stats` endpoints, serving the router's metrics sampled over a time window
+//! in the format of the Grafana JSON datasource:
+//! - `GET /@stats`: the connection test;
+//! - `POST /@stats/metrics` and `POST /@stats/search`: the names of the available series;
+//! - `POST /@stats/query`: the samples of the requested series over the requested range.
+//!
+//! A series is named after an OpenMetrics sample of `@/router/<zid>/metrics` (e.g. `zenoh_faces{whatami="client"}`),
+//! and a target without labels (e.g. `zenoh_faces`) selects all the series of this metric.
+use crate::config::StatsConf;
+use crate::response;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tide::{Request, Response, Server, StatusCode};
+use zenoh::buffers::SplitBuffer;
+use zenoh::prelude::r#async::*;
+use zenoh::Session;
+
+const METRICS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The samples of the router's metrics over the configured window.
+pub(crate) struct Stats {
+    capacity: usize,
+    series: RwLock<HashMap<String, VecDeque<(u64, f64)>>>,
+}
+
+impl Stats {
+    fn new(conf: &StatsConf) -> Self {
+        Stats {
+            capacity: (conf.window / conf.interval.max(1)).max(1) as usize,
+            series: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Records the samples of an OpenMetrics text taken at `time` (in milliseconds since the epoch).
+    fn record(&self, text: &str, time: u64) {
+        let mut series = self.series.write().unwrap();
+        for line in text.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, value)) = line.rsplit_once(' ') else {
+                continue;
+            };
+            let Ok(value) = value.parse::<f64>() else {
+                continue;
+            };
+            let samples = series.entry(name.to_string()).or_default();
+            if samples.len() == self.capacity {
+                samples.pop_front();
+            }
+            samples.push_back((time, value));
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names = self
+            .series
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// Returns the datapoints of the series selected by `target` between `from` and `to`,
+    /// evenly thinned out to at most `max` datapoints per series.
+    fn query(&self, target: &str, from: u64, to: u64, max: usize) -> Vec<serde_json::Value> {
+        let series = self.series.read().unwrap();
+        let mut names = series
+            .keys()
+            .filter(|name| {
+                *name == target
+                    || name
+                        .strip_prefix(target)
+                        .map_or(false, |labels| labels.starts_with('{'))
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| {
+                let samples = series[name]
+                    .iter()
+                    .filter(|(time, _)| (from..=to).contains(time))
+                    .collect::<Vec<_>>();
+                let step = match samples.len() {
+                    len if len > max => (len + max - 1) / max,
+                    _ => 1,
+                };
+                let datapoints = samples
+                    .into_iter()
+                    .step_by(step)
+                    .map(|(time, value)| json!([value, time]))
+                    .collect::<Vec<_>>();
+                json!({ "target": name, "datapoints": datapoints })
+            })
+            .collect()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Samples the router's metrics every `interval` seconds.
+async fn sample(session: Arc<Session>, zid: String, stats: Arc<Stats>, interval: u64) {
+    let interval = Duration::from_secs(interval.max(1));
+    loop {
+        match session
+            .get(format!("@/router/{zid}/metrics"))
+            .timeout(METRICS_TIMEOUT)
+            .res()
+            .await
+        {
+            Ok(replies) => {
+                if let Ok(Ok(sample)) = replies.recv_async().await.map(|reply| reply.sample) {
+                    stats.record(
+                        &String::from_utf8_lossy(&sample.value.payload.contiguous()),
+                        now(),
+                    );
+                }
+            }
+            Err(e) => log::warn!("Unable to sample the router's metrics: {}", e),
+        }
+        async_std::task::sleep(interval).await;
+    }
+}
+
+#[derive(Deserialize)]
+struct QueryRange {
+    from: String,
+    to: String,
+}
+
+#[derive(Deserialize)]
+struct Target {
+    target: Option<String>,
+    #[serde(default)]
+    hide: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryRequest {
+    range: QueryRange,
+    #[serde(default)]
+    max_data_points: Option<usize>,
+    targets: Vec<Target>,
+}
+
+fn parse_time(time: &str) -> Option<u64> {
+    humantime::parse_rfc3339_weak(time)
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
+async fn test(_req: Request<Arc<Stats>>) -> tide::Result<Response> {
+    Ok(Response::new(StatusCode::Ok))
+}
+
+async fn metrics(req: Request<Arc<Stats>>) -> tide::Result<Response> {
+    let metrics = req
+        .state()
+        .names()
+        .into_iter()
+        .map(|name| json!({ "label": name, "value": name }))
+        .collect::<Vec<_>>();
+    Ok(response(
+        StatusCode::Ok,
+        "application/json",
+        &serde_json::Value::from(metrics).to_string(),
+    ))
+}
+
+async fn search(req: Request<Arc<Stats>>) -> tide::Result<Response> {
+    Ok(response(
+        StatusCode::Ok,
+        "application/json",
+        &json!(req.state().names()).to_string(),
+    ))
+}
+
+async fn query(mut req: Request<Arc<Stats>>) -> tide::Result<Response> {
+    let request: QueryRequest = match req.body_json().await {
+        Ok(request) => request,
+        Err(e) => {
+            return Ok(response(
+                StatusCode::BadRequest,
+                "text/plain",
+                &e.to_string(),
+            ))
+        }
+    };
+    let (Some(from), Some(to)) = (
+        parse_time(&request.range.from),
+        parse_time(&request.range.to),
+    ) else {
+        return Ok(response(
+            StatusCode::BadRequest,
+            "text/plain",
+            "Invalid time range",
+        ));
+    };
+    let max = request.max_data_points.unwrap_or(usize::MAX).max(1);
+    let result = request
+        .targets
+        .iter()
+        .filter(|target| !target.hide)
+        .filter_map(|target| target.target.as_deref())
+        .flat_map(|target| req.state().query(target, from, to, max))
+        .collect::<Vec<_>>();
+    Ok(response(
+        StatusCode::Ok,
+        "application/json",
+        &serde_json::Value::from(result).to_string(),
+    ))
+}
+
+/// Starts sampling the router's metrics, and returns the server of the `/@stats` endpoints.
+pub(crate) fn server(session: Arc<Session>, zid: String, conf: &StatsConf) -> Server<Arc<Stats>> {
+    let stats = Arc::new(Stats::new(conf));
+    async_std::task::spawn(sample(session, zid, stats.clone(), conf.interval));
+
+    let mut app = Server::with_state(stats);
+    app.at("/").get(test);
+    app.at("/metrics").post(metrics);
+    app.at("/search").post(search);
+    app.at("/query").post(query);
+    app
+}
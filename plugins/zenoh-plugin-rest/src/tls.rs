@@ -0,0 +1,105 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::config::TlsConf;
+use async_rustls::TlsAcceptor;
+use async_std::net::TcpListener;
+use futures::StreamExt;
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use std::io::Cursor;
+use std::sync::Arc;
+use tide::Server;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// Loads the certificate chain and private key configured in `conf`.
+pub(crate) fn server_config(conf: &TlsConf) -> ZResult<ServerConfig> {
+    let certificate = std::fs::read(&conf.certificate)
+        .map_err(|e| zerror!("Invalid TLS certificate file {}: {}", conf.certificate, e))?;
+    let private_key = std::fs::read(&conf.private_key)
+        .map_err(|e| zerror!("Invalid TLS private key file {}: {}", conf.private_key, e))?;
+
+    let certs: Vec<Certificate> = rustls_pemfile::certs(&mut Cursor::new(&certificate))
+        .map_err(|e| zerror!(e))
+        .map(|mut certs| certs.drain(..).map(Certificate).collect())?;
+    if certs.is_empty() {
+        bail!("No certificate found in {}", conf.certificate);
+    }
+
+    let mut keys: Vec<PrivateKey> =
+        rustls_pemfile::rsa_private_keys(&mut Cursor::new(&private_key))
+            .map_err(|e| zerror!(e))
+            .map(|mut keys| keys.drain(..).map(PrivateKey).collect())?;
+    if keys.is_empty() {
+        keys = rustls_pemfile::pkcs8_private_keys(&mut Cursor::new(&private_key))
+            .map_err(|e| zerror!(e))
+            .map(|mut keys| keys.drain(..).map(PrivateKey).collect())?;
+    }
+    if keys.is_empty() {
+        keys = rustls_pemfile::ec_private_keys(&mut Cursor::new(&private_key))
+            .map_err(|e| zerror!(e))
+            .map(|mut keys| keys.drain(..).map(PrivateKey).collect())?;
+    }
+    if keys.is_empty() {
+        bail!("No private key found in {}", conf.private_key);
+    }
+
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, keys.remove(0))
+        .map_err(|e| zerror!(e).into())
+}
+
+/// Serves `app` over HTTPS on `addr`.
+pub(crate) async fn listen<State: Clone + Send + Sync + 'static>(
+    app: Server<State>,
+    addr: &str,
+    conf: &TlsConf,
+) -> ZResult<()> {
+    let acceptor = TlsAcceptor::from(Arc::new(server_config(conf)?));
+    let listener = TcpListener::bind(addr).await?;
+    log::info!("REST server listening on https://{}", addr);
+    let mut incoming = listener.incoming();
+    while let Some(stream) = incoming.next().await {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::warn!("Error accepting REST connection: {}", e);
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        async_std::task::spawn(async move {
+            let local_addr = stream.local_addr().ok();
+            let peer_addr = stream.peer_addr().ok();
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => async_dup::Arc::new(async_dup::Mutex::new(stream)),
+                Err(e) => {
+                    log::debug!("TLS handshake with {:?} failed: {}", peer_addr, e);
+                    return;
+                }
+            };
+            let result = async_h1::accept(stream, |mut req| async {
+                req.set_local_addr(local_addr);
+                req.set_peer_addr(peer_addr);
+                app.respond(req).await
+            })
+            .await;
+            if let Err(e) = result {
+                log::debug!("REST connection with {:?} failed: {}", peer_addr, e);
+            }
+        });
+    }
+    Ok(())
+}
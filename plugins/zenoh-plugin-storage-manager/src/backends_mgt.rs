@@ -13,18 +13,22 @@
 //
 use super::storages_mgt::*;
 use flume::Sender;
+use futures::FutureExt;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 use zenoh::prelude::r#async::*;
 use zenoh::Session;
 use zenoh_backend_traits::config::StorageConfig;
-use zenoh_backend_traits::Capability;
-use zenoh_result::ZResult;
+use zenoh_backend_traits::{Capability, ConflictResolver};
+use zenoh_plugin_trait::{catch_panic, panic_message};
+use zenoh_result::{zerror, ZResult};
 
 pub struct StoreIntercept {
     pub storage: Box<dyn zenoh_backend_traits::Storage>,
     pub capability: Capability,
     pub in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     pub out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
+    pub conflict_resolver: Option<Arc<dyn ConflictResolver>>,
 }
 
 pub(crate) async fn create_and_start_storage(
@@ -33,16 +37,27 @@ pub(crate) async fn create_and_start_storage(
     backend: &mut Box<dyn zenoh_backend_traits::Volume>,
     in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
+    conflict_resolver: Option<Arc<dyn ConflictResolver>>,
     zenoh: Arc<Session>,
 ) -> ZResult<Sender<StorageMessage>> {
     log::trace!("Create storage {}", &admin_key);
-    let capability = backend.get_capability();
-    let storage = backend.create_storage(config.clone()).await?;
+    let capability = catch_panic(|| backend.get_capability())
+        .map_err(|e| zerror!("Backend panicked while reporting its capability: {}", e))?;
+    let storage = AssertUnwindSafe(backend.create_storage(config.clone()))
+        .catch_unwind()
+        .await
+        .map_err(|e| {
+            zerror!(
+                "Backend panicked while creating a storage: {}",
+                panic_message(&*e)
+            )
+        })??;
     let store_intercept = StoreIntercept {
         storage,
         capability,
         in_interceptor,
         out_interceptor,
+        conflict_resolver,
     };
 
     start_storage(store_intercept, config, admin_key, zenoh).await
@@ -30,15 +30,20 @@ use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::sync::Mutex;
 use storages_mgt::StorageMessage;
-use zenoh::plugins::{Plugin, RunningPluginTrait, ValidationFunction, ZenohPlugin};
+use zenoh::config::ValidatedMap;
+use zenoh::plugins::{
+    AdminSpaceWrite, Metric, MetricKind, Plugin, RunningPluginTrait, ValidationFunction,
+    ZenohPlugin,
+};
 use zenoh::prelude::sync::*;
 use zenoh::runtime::Runtime;
 use zenoh::Session;
 use zenoh_backend_traits::CreateVolume;
 use zenoh_backend_traits::CREATE_VOLUME_FN_NAME;
 use zenoh_backend_traits::{config::*, Volume};
+use zenoh_backend_traits::{Compatibility, BACKEND_COMPATIBILITY, BACKEND_COMPATIBILITY_SYMBOL};
 use zenoh_core::zlock;
-use zenoh_result::{bail, ZResult};
+use zenoh_result::{bail, zerror, ZResult};
 use zenoh_util::LibLoader;
 
 mod backends_mgt;
@@ -80,6 +85,9 @@ struct StorageRuntimeInner {
     lib_loader: LibLoader,
     volumes: HashMap<String, VolumeHandle>,
     storages: HashMap<String, HashMap<String, Sender<StorageMessage>>>,
+    secrets: HashMap<String, SecretProvider>,
+    // the values of the secrets resolved for the volumes and storages, redacted from their status
+    resolved_secrets: HashMap<String, String>,
 }
 impl StorageRuntimeInner {
     fn status_key(&self) -> String {
@@ -98,6 +106,7 @@ impl StorageRuntimeInner {
             backend_search_dirs,
             volumes,
             storages,
+            secrets,
             ..
         } = config;
         let lib_loader = backend_search_dirs
@@ -112,6 +121,8 @@ impl StorageRuntimeInner {
             lib_loader,
             volumes: Default::default(),
             storages: Default::default(),
+            secrets,
+            resolved_secrets: Default::default(),
         };
         new_self.spawn_volume(VolumeConfig {
             name: MEMORY_BACKEND_NAME.into(),
@@ -151,8 +162,16 @@ impl StorageRuntimeInner {
         }
         std::mem::drop(self.volumes.remove(&volume.name));
     }
-    fn spawn_volume(&mut self, config: VolumeConfig) -> ZResult<()> {
+    fn spawn_volume(&mut self, mut config: VolumeConfig) -> ZResult<()> {
         let volume_id = config.name.clone();
+        let resolved = config.resolve_secrets(&self.secrets).map_err(|e| {
+            zerror!(
+                "Failed to resolve the secrets of volume {}: {}",
+                volume_id,
+                e
+            )
+        })?;
+        self.resolved_secrets.extend(resolved);
         if volume_id == MEMORY_BACKEND_NAME {
             match create_memory_backend(config) {
                 Ok(backend) => {
@@ -208,8 +227,20 @@ impl StorageRuntimeInner {
         lib: Library,
         lib_path: PathBuf,
     ) -> ZResult<()> {
+        if let Err(e) =
+            Compatibility::check(&lib, BACKEND_COMPATIBILITY_SYMBOL, &BACKEND_COMPATIBILITY)
+        {
+            bail!(
+                "Failed to load Backend {} from {}: {}",
+                volume_id,
+                lib_path.display(),
+                e
+            )
+        }
         if let Ok(create_backend) = lib.get::<CreateVolume>(CREATE_VOLUME_FN_NAME) {
-            match create_backend(config) {
+            match zenoh_plugin_trait::catch_panic(|| create_backend(config))
+                .unwrap_or_else(|e| bail!("the backend panicked: {}", e))
+            {
                 Ok(backend) => {
                     self.volumes.insert(
                         volume_id.to_string(),
@@ -251,19 +282,35 @@ impl StorageRuntimeInner {
             }
         }
     }
-    fn spawn_storage(&mut self, storage: StorageConfig) -> ZResult<()> {
+    fn spawn_storage(&mut self, mut storage: StorageConfig) -> ZResult<()> {
         let admin_key = self.status_key() + "/storages/" + &storage.name;
+        let resolved = storage.resolve_secrets(&self.secrets).map_err(|e| {
+            zerror!(
+                "Failed to resolve the secrets of storage {}: {}",
+                storage.name,
+                e
+            )
+        })?;
+        self.resolved_secrets.extend(resolved);
         let volume_id = storage.volume_id.clone();
         if let Some(backend) = self.volumes.get_mut(&volume_id) {
             let storage_name = storage.name.clone();
-            let in_interceptor = backend.backend.incoming_data_interceptor();
-            let out_interceptor = backend.backend.outgoing_data_interceptor();
+            let (in_interceptor, out_interceptor, conflict_resolver) =
+                zenoh_plugin_trait::catch_panic(|| {
+                    (
+                        backend.backend.incoming_data_interceptor(),
+                        backend.backend.outgoing_data_interceptor(),
+                        backend.backend.conflict_resolver(),
+                    )
+                })
+                .map_err(|e| zerror!("`{}` volume panicked: {}", volume_id, e))?;
             let stopper = async_std::task::block_on(create_and_start_storage(
                 admin_key,
                 storage,
                 &mut backend.backend,
                 in_interceptor,
                 out_interceptor,
+                conflict_resolver,
                 self.session.clone(),
             ))?;
             self.storages
@@ -316,9 +363,14 @@ impl RunningPluginTrait for StorageRuntime {
             let new = PluginConfig::try_from((&name, new))?;
             log::info!("old: {:?}", &old);
             log::info!("new: {:?}", &new);
+            let secrets = new.secrets.clone();
             let diffs = ConfigDiff::diffs(old, new);
             log::info!("diff: {:?}", &diffs);
-            { zlock!(runtime).update(diffs) }?;
+            {
+                let mut runtime = zlock!(runtime);
+                runtime.secrets = secrets;
+                runtime.update(diffs)
+            }?;
             Ok(None)
         })
     }
@@ -360,10 +412,19 @@ impl RunningPluginTrait for StorageRuntime {
                         .unwrap()
                         .intersects(&selector.key_expr)
                     {
-                        responses.push(zenoh::plugins::Response::new(
-                            key.clone(),
-                            volume.backend.get_admin_status(),
-                        ))
+                        let mut status = zenoh_plugin_trait::catch_panic(|| {
+                            volume.backend.get_admin_status()
+                        })
+                        .unwrap_or_else(|e| {
+                            log::error!(
+                                "Volume {} panicked while reporting its status: {}",
+                                volume_id,
+                                e
+                            );
+                            serde_json::json!({ "__error__": format!("Volume panicked: {e}") })
+                        });
+                        redact_secrets(&mut status, &guard.resolved_secrets);
+                        responses.push(zenoh::plugins::Response::new(key.clone(), status))
                     }
                 });
             }
@@ -376,11 +437,12 @@ impl RunningPluginTrait for StorageRuntime {
                             .unwrap()
                             .intersects(&selector.key_expr)
                         {
-                            if let Ok(value) = task::block_on(async {
+                            if let Ok(mut value) = task::block_on(async {
                                 let (tx, rx) = async_std::channel::bounded(1);
                                 let _ = handle.send(StorageMessage::GetStatus(tx));
                                 rx.recv().await
                             }) {
+                                redact_secrets(&mut value, &guard.resolved_secrets);
                                 responses.push(zenoh::plugins::Response::new(key.clone(), value))
                             }
                         }
@@ -390,6 +452,66 @@ impl RunningPluginTrait for StorageRuntime {
         });
         Ok(responses)
     }
+
+    fn adminspace_setter(
+        &self,
+        key: &str,
+        plugin_status_key: &str,
+        write: AdminSpaceWrite,
+    ) -> ZResult<()> {
+        let storage = match key
+            .strip_prefix(plugin_status_key)
+            .and_then(|key| key.strip_prefix("/storages/"))
+        {
+            Some(storage) if !storage.is_empty() && !storage.contains('/') => storage,
+            _ => bail!("`{}` isn't writable", key),
+        };
+        let (name, runtime) = {
+            let guard = zlock!(self.0);
+            (guard.name.clone(), guard.runtime.clone())
+        };
+        // Storages are added and stopped through the configuration, so that it keeps reflecting them
+        let config_key = format!("plugins/{name}/storages/{storage}");
+        match write {
+            AdminSpaceWrite::Put(config) => {
+                Ok((&runtime.config).insert_json5(&config_key, &config.to_string())?)
+            }
+            AdminSpaceWrite::Delete => runtime.config.remove(config_key),
+            _ => bail!("Unsupported write on `{}`", key),
+        }
+    }
+
+    fn is_adminspace_notification(&self, key: &str, plugin_status_key: &str) -> bool {
+        // the changes published by the storages on `.../storages/<name>/cdc`
+        key.strip_prefix(plugin_status_key)
+            .and_then(|key| key.strip_prefix("/storages/"))
+            .and_then(|key| key.split_once('/'))
+            .map_or(false, |(storage, suffix)| {
+                !storage.is_empty() && suffix == replica::cdc::CDC_SUFFIX
+            })
+    }
+
+    fn metrics(&self) -> Vec<Metric> {
+        let guard = self.0.lock().unwrap();
+        let mut metrics = vec![Metric::new(
+            "zenoh_storage_manager_volumes",
+            MetricKind::Gauge,
+            "Number of volumes created by the storage manager.",
+            guard.volumes.len() as f64,
+        )];
+        for (volume_id, storages) in &guard.storages {
+            metrics.push(
+                Metric::new(
+                    "zenoh_storage_manager_storages",
+                    MetricKind::Gauge,
+                    "Number of storages running on a volume.",
+                    storages.len() as f64,
+                )
+                .with_label("volume", volume_id),
+            );
+        }
+        metrics
+    }
 }
 
 const BACKEND_LIB_PREFIX: &str = "zenoh_backend_";
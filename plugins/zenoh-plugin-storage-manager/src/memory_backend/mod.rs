@@ -13,11 +13,12 @@
 //
 use async_std::sync::RwLock;
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use zenoh::buffers::SplitBuffer;
 use zenoh::prelude::r#async::*;
 use zenoh::time::Timestamp;
-use zenoh_backend_traits::config::{StorageConfig, VolumeConfig};
+use zenoh_backend_traits::config::{IndexPath, StorageConfig, VolumeConfig};
 use zenoh_backend_traits::*;
 use zenoh_result::ZResult;
 
@@ -76,20 +77,88 @@ impl Drop for MemoryBackend {
     }
 }
 
+type Index = HashMap<String, HashSet<Option<OwnedKeyExpr>>>;
+
 struct MemoryStorage {
     config: StorageConfig,
     map: Arc<RwLock<HashMap<Option<OwnedKeyExpr>, StoredData>>>,
+    // for each indexed path, the keys by JSON representation of their field at this path
+    indexes: HashMap<IndexPath, Index>,
 }
 
 impl MemoryStorage {
     async fn new(properties: StorageConfig) -> ZResult<MemoryStorage> {
+        let indexes = properties
+            .index
+            .iter()
+            .map(|path| (path.clone(), Index::new()))
+            .collect();
         Ok(MemoryStorage {
             config: properties,
             map: Arc::new(RwLock::new(HashMap::new())),
+            indexes,
         })
     }
 }
 
+// Returns the JSON representation of the fields of a value at the indexed paths
+fn indexed_fields(index: &[IndexPath], value: &Value) -> Vec<(IndexPath, String)> {
+    if index.is_empty() {
+        return Vec::new();
+    }
+    let Ok(payload) = serde_json::from_slice::<serde_json::Value>(&value.payload.contiguous())
+    else {
+        return Vec::new();
+    };
+    index
+        .iter()
+        .filter_map(|path| Some((path.clone(), path.select(&payload)?.to_string())))
+        .collect()
+}
+
+fn unindex(
+    indexes: &mut HashMap<IndexPath, Index>,
+    fields: Vec<(IndexPath, String)>,
+    key: &Option<OwnedKeyExpr>,
+) {
+    for (path, field) in fields {
+        if let Some(index) = indexes.get_mut(&path) {
+            if let Some(keys) = index.get_mut(&field) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    index.remove(&field);
+                }
+            }
+        }
+    }
+}
+
+// Inserts a value in the map and indexes its fields, in place of the previous value of its key
+fn insert(
+    map: &mut HashMap<Option<OwnedKeyExpr>, StoredData>,
+    indexes: &mut HashMap<IndexPath, Index>,
+    index: &[IndexPath],
+    key: Option<OwnedKeyExpr>,
+    value: Value,
+    timestamp: Timestamp,
+) -> StorageInsertionResult {
+    let fields = indexed_fields(index, &value);
+    let previous = map.insert(key.clone(), StoredData { value, timestamp });
+    if let Some(previous) = &previous {
+        let previous_fields = indexed_fields(index, &previous.value);
+        unindex(indexes, previous_fields, &key);
+    }
+    for (path, field) in fields {
+        if let Some(index) = indexes.get_mut(&path) {
+            index.entry(field).or_default().insert(key.clone());
+        }
+    }
+    match previous {
+        Some(_) => StorageInsertionResult::Replaced,
+        None => StorageInsertionResult::Inserted,
+    }
+}
+
 #[async_trait]
 impl Storage for MemoryStorage {
     fn get_admin_status(&self) -> serde_json::Value {
@@ -104,16 +173,36 @@ impl Storage for MemoryStorage {
     ) -> ZResult<StorageInsertionResult> {
         log::trace!("put for {:?}", key);
         let mut map = self.map.write().await;
-        match map.entry(key) {
-            std::collections::hash_map::Entry::Occupied(mut e) => {
-                e.insert(StoredData { value, timestamp });
-                return Ok(StorageInsertionResult::Replaced);
-            }
-            std::collections::hash_map::Entry::Vacant(e) => {
-                e.insert(StoredData { value, timestamp });
-                return Ok(StorageInsertionResult::Inserted);
-            }
-        }
+        Ok(insert(
+            &mut map,
+            &mut self.indexes,
+            &self.config.index,
+            key,
+            value,
+            timestamp,
+        ))
+    }
+
+    async fn put_batch(
+        &mut self,
+        entries: Vec<(Option<OwnedKeyExpr>, Value, Timestamp)>,
+    ) -> ZResult<Vec<StorageInsertionResult>> {
+        log::trace!("put_batch of {} entries", entries.len());
+        // the whole batch is inserted under the same lock, for the readers to never see a part of it
+        let mut map = self.map.write().await;
+        Ok(entries
+            .into_iter()
+            .map(|(key, value, timestamp)| {
+                insert(
+                    &mut map,
+                    &mut self.indexes,
+                    &self.config.index,
+                    key,
+                    value,
+                    timestamp,
+                )
+            })
+            .collect())
     }
 
     async fn delete(
@@ -122,7 +211,10 @@ impl Storage for MemoryStorage {
         _timestamp: Timestamp,
     ) -> ZResult<StorageInsertionResult> {
         log::trace!("delete for {:?}", key);
-        self.map.write().await.remove_entry(&key);
+        if let Some(previous) = self.map.write().await.remove(&key) {
+            let previous_fields = indexed_fields(&self.config.index, &previous.value);
+            unindex(&mut self.indexes, previous_fields, &key);
+        }
         return Ok(StorageInsertionResult::Deleted);
     }
 
@@ -147,6 +239,33 @@ impl Storage for MemoryStorage {
         }
         Ok(result)
     }
+
+    async fn get_by_index(
+        &mut self,
+        predicates: &[IndexPredicate],
+    ) -> ZResult<Option<Vec<(Option<OwnedKeyExpr>, StoredData)>>> {
+        let mut keys: Option<HashSet<Option<OwnedKeyExpr>>> = None;
+        for predicate in predicates {
+            let Some(index) = self.indexes.get(&predicate.path) else {
+                return Ok(None);
+            };
+            let matching = index
+                .get(&predicate.value.to_string())
+                .cloned()
+                .unwrap_or_default();
+            keys = Some(match keys {
+                Some(keys) => keys.intersection(&matching).cloned().collect(),
+                None => matching,
+            });
+        }
+        let map = self.map.read().await;
+        Ok(Some(
+            keys.unwrap_or_default()
+                .into_iter()
+                .filter_map(|key| Some((key.clone(), map.get(&key)?.clone())))
+                .collect(),
+        ))
+    }
 }
 
 impl Drop for MemoryStorage {
@@ -0,0 +1,305 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module downsamples the samples of another key expression mirrored by a storage
+//
+// The samples kept are stored under the keys of the storage: the non-wild prefix of their keys is replaced by the
+// non-wild prefix of the storage key expression. The deletions are all kept, the samples of the source batches are
+// downsampled and stored one by one
+// With `time-bucket: <width> latest`, the latest sample of each key is held until a sample of a later bucket is received
+// for the key, or until its bucket is over: the buckets are checked every `width`
+
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use flume::{Receiver, Sender};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use zenoh::prelude::r#async::*;
+use zenoh::time::NTP64;
+use zenoh_backend_traits::config::{AggregateConfig, AggregateStrategy, BucketSample};
+use zenoh_core::zlock;
+use zenoh_util::{Timed, TimedEvent, Timer};
+
+pub struct Aggregate {
+    // the key expression mirrored by the storage
+    pub source: OwnedKeyExpr,
+    downsampler: Arc<Mutex<Downsampler>>,
+    // the held samples whose bucket is over, to be stored
+    pub flushed: Receiver<Sample>,
+    _timer: Option<Timer>,
+}
+
+impl Aggregate {
+    pub async fn start(key_expr: &OwnedKeyExpr, config: AggregateConfig) -> Self {
+        let downsampler = Arc::new(Mutex::new(Downsampler::new(key_expr, &config)));
+        let (tx, flushed) = flume::unbounded();
+        let timer = match config.strategy {
+            AggregateStrategy::TimeBucket {
+                width,
+                keep: BucketSample::Latest,
+            } => {
+                let timer = Timer::default();
+                let flush = TimedEvent::periodic(
+                    width,
+                    FlushEvent {
+                        downsampler: downsampler.clone(),
+                        tx,
+                    },
+                );
+                timer.add_async(flush).await;
+                Some(timer)
+            }
+            _ => None,
+        };
+        Aggregate {
+            source: config.source,
+            downsampler,
+            flushed,
+            _timer: timer,
+        }
+    }
+
+    // Returns the samples to store upon receiving a timestamped sample of the source
+    pub fn add(&self, sample: Sample) -> Vec<Sample> {
+        zlock!(self.downsampler).add(sample)
+    }
+}
+
+struct Downsampler {
+    strategy: AggregateStrategy,
+    source_prefix: String,
+    prefix: String,
+    // per key: the number of samples received since the latest kept one with `1-in-<n>`,
+    // the bucket of the latest kept sample with `time-bucket: <width> first`
+    counters: HashMap<OwnedKeyExpr, u64>,
+    // per key: the latest sample received, with its bucket, with `time-bucket: <width> latest`
+    held: HashMap<OwnedKeyExpr, (u64, Sample)>,
+}
+
+impl Downsampler {
+    fn new(key_expr: &OwnedKeyExpr, config: &AggregateConfig) -> Self {
+        Downsampler {
+            strategy: config.strategy,
+            source_prefix: nonwild_prefix(&config.source).to_string(),
+            prefix: nonwild_prefix(key_expr).to_string(),
+            counters: HashMap::new(),
+            held: HashMap::new(),
+        }
+    }
+
+    // The key of the storage mirroring a key of the source
+    fn mirrored_key(&self, key: &str) -> Option<OwnedKeyExpr> {
+        let suffix = key
+            .strip_prefix(self.source_prefix.as_str())?
+            .trim_start_matches('/');
+        let key = match (self.prefix.is_empty(), suffix.is_empty()) {
+            (true, _) => suffix.to_string(),
+            (false, true) => self.prefix.clone(),
+            (false, false) => format!("{}/{}", self.prefix, suffix),
+        };
+        OwnedKeyExpr::new(key).ok()
+    }
+
+    fn bucket(width: &std::time::Duration, time: &NTP64) -> u64 {
+        (time.to_duration().as_millis() / width.as_millis()) as u64
+    }
+
+    fn add(&mut self, mut sample: Sample) -> Vec<Sample> {
+        let Some(key) = self.mirrored_key(&sample.key_expr) else {
+            log::warn!(
+                "Sample for `{}` is not mirrored: unexpected key",
+                sample.key_expr
+            );
+            return vec![];
+        };
+        sample.key_expr = KeyExpr::from(key.clone());
+        if sample.kind == SampleKind::Delete {
+            self.counters.remove(&key);
+            self.held.remove(&key);
+            return vec![sample];
+        }
+        let time = *sample.get_timestamp().unwrap().get_time();
+        match self.strategy {
+            AggregateStrategy::OneIn(n) => {
+                let count = self.counters.entry(key).or_insert(0);
+                let keep = *count == 0;
+                *count = (*count + 1) % n;
+                if keep {
+                    vec![sample]
+                } else {
+                    vec![]
+                }
+            }
+            AggregateStrategy::TimeBucket {
+                width,
+                keep: BucketSample::First,
+            } => {
+                let bucket = Self::bucket(&width, &time);
+                match self.counters.get(&key) {
+                    Some(kept) if *kept >= bucket => vec![],
+                    _ => {
+                        self.counters.insert(key, bucket);
+                        vec![sample]
+                    }
+                }
+            }
+            AggregateStrategy::TimeBucket {
+                width,
+                keep: BucketSample::Latest,
+            } => {
+                let bucket = Self::bucket(&width, &time);
+                match self.held.remove(&key) {
+                    Some((held_bucket, held)) if held_bucket < bucket => {
+                        self.held.insert(key, (bucket, sample));
+                        vec![held]
+                    }
+                    // a sample of a past bucket is dropped once a sample of a later one is held
+                    Some((held_bucket, held)) if held_bucket > bucket => {
+                        self.held.insert(key, (held_bucket, held));
+                        vec![]
+                    }
+                    Some((_, held)) if held.timestamp > sample.timestamp => {
+                        self.held.insert(key, (bucket, held));
+                        vec![]
+                    }
+                    _ => {
+                        self.held.insert(key, (bucket, sample));
+                        vec![]
+                    }
+                }
+            }
+        }
+    }
+
+    // Returns the held samples whose bucket is over at `now`
+    fn flush(&mut self, now: &NTP64) -> Vec<Sample> {
+        let AggregateStrategy::TimeBucket { width, .. } = self.strategy else {
+            return vec![];
+        };
+        let now = Self::bucket(&width, now);
+        let over = self
+            .held
+            .iter()
+            .filter(|(_, (bucket, _))| *bucket < now)
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        over.into_iter()
+            .filter_map(|key| self.held.remove(&key))
+            .map(|(_, sample)| sample)
+            .collect()
+    }
+}
+
+fn nonwild_prefix(key_expr: &keyexpr) -> &str {
+    key_expr
+        .get_nonwild_prefix()
+        .map_or("", |prefix| prefix.as_str())
+}
+
+struct FlushEvent {
+    downsampler: Arc<Mutex<Downsampler>>,
+    tx: Sender<Sample>,
+}
+
+#[async_trait]
+impl Timed for FlushEvent {
+    async fn run(&mut self) {
+        let samples = zlock!(self.downsampler).flush(&zenoh::time::clock());
+        for sample in samples {
+            if self.tx.send(sample).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[test]
+fn downsampling() {
+    use std::time::Duration;
+    use zenoh::time::{Timestamp, TimestampId};
+
+    let id = TimestampId::try_from([1]).unwrap();
+    let sample = |key: &str, value: &str, secs: u64| {
+        Sample::try_from(key.to_string(), value)
+            .unwrap()
+            .with_timestamp(Timestamp::new(NTP64::from(Duration::from_secs(secs)), id))
+    };
+    let values = |samples: Vec<Sample>| {
+        samples
+            .into_iter()
+            .map(|s| format!("{}={}", s.key_expr, s.value))
+            .collect::<Vec<_>>()
+    };
+    let downsampler = |strategy: &str| {
+        Downsampler::new(
+            &OwnedKeyExpr::new("history/sensors/**").unwrap(),
+            &AggregateConfig {
+                source: OwnedKeyExpr::new("demo/sensors/**").unwrap(),
+                strategy: strategy.parse().unwrap(),
+            },
+        )
+    };
+
+    // one sample in 3 per key, under the mirrored key
+    let mut one_in = downsampler("1-in-3");
+    let mut kept = vec![];
+    for i in 0..7 {
+        kept.extend(one_in.add(sample("demo/sensors/a", &i.to_string(), i)));
+    }
+    kept.extend(one_in.add(sample("demo/sensors/b", "0", 7)));
+    assert_eq!(
+        values(kept),
+        [
+            "history/sensors/a=0",
+            "history/sensors/a=3",
+            "history/sensors/a=6",
+            "history/sensors/b=0"
+        ]
+    );
+
+    // the first sample of each bucket of a minute
+    let mut first = downsampler("time-bucket: 1m first");
+    let mut kept = vec![];
+    for (value, secs) in [("0", 0), ("1", 30), ("2", 61), ("3", 119), ("4", 250)] {
+        kept.extend(first.add(sample("demo/sensors/a", value, secs)));
+    }
+    assert_eq!(
+        values(kept),
+        [
+            "history/sensors/a=0",
+            "history/sensors/a=2",
+            "history/sensors/a=4"
+        ]
+    );
+
+    // the latest sample of each bucket of a minute, held until the bucket is over
+    let mut latest = downsampler("time-bucket: 1m latest");
+    let mut kept = vec![];
+    for (value, secs) in [("0", 0), ("1", 30), ("2", 61), ("3", 119)] {
+        kept.extend(latest.add(sample("demo/sensors/a", value, secs)));
+    }
+    assert_eq!(values(kept), ["history/sensors/a=1"]);
+    assert!(latest
+        .flush(&NTP64::from(Duration::from_secs(100)))
+        .is_empty());
+    assert_eq!(
+        values(latest.flush(&NTP64::from(Duration::from_secs(120)))),
+        ["history/sensors/a=3"]
+    );
+
+    // the deletions are all kept
+    let mut delete = sample("demo/sensors/a", "", 121);
+    delete.kind = SampleKind::Delete;
+    assert_eq!(latest.add(delete).len(), 1);
+}
@@ -0,0 +1,81 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module gathers the samples of the atomic batches received by a storage, for them to be stored as a unit
+//
+// A subscriber receives in a row the samples of a batch matching its key expression, but the samples of
+// batches received concurrently on several links may interleave: the batches are told apart by their timestamp
+
+use std::collections::HashMap;
+use zenoh::prelude::Sample;
+use zenoh::time::{new_reception_timestamp, Timestamp};
+
+#[derive(Default)]
+pub struct Batches {
+    pending: HashMap<Option<Timestamp>, Vec<Sample>>,
+}
+
+impl Batches {
+    // Adds a sample of a batch, returning all the samples of the batch once they are all received
+    //
+    // The samples of a batch published without timestamp are all timestamped with the same reception timestamp
+    pub fn add(&mut self, sample: Sample) -> Option<Vec<Sample>> {
+        let len = sample.batch.map_or(1, |batch| batch.len);
+        let timestamp = sample.timestamp;
+        let samples = self.pending.entry(timestamp).or_default();
+        samples.push(sample);
+        if samples.len() < len {
+            return None;
+        }
+        let mut samples = self.pending.remove(&timestamp)?;
+        let timestamp = timestamp.unwrap_or_else(new_reception_timestamp);
+        for sample in samples.iter_mut() {
+            sample.timestamp = Some(timestamp);
+        }
+        Some(samples)
+    }
+}
+
+#[test]
+fn batches() {
+    use zenoh::sample::BatchInfo;
+
+    let sample = |key: &str, index: usize, timestamp: Option<Timestamp>| {
+        let mut sample = Sample::try_from(key.to_string(), key).unwrap();
+        sample.timestamp = timestamp;
+        sample.batch = Some(BatchInfo { index, len: 2 });
+        sample
+    };
+    let t1 = Some(new_reception_timestamp());
+    let t2 = Some(new_reception_timestamp());
+
+    // the interleaved batches are told apart by their timestamp
+    let mut batches = Batches::default();
+    assert!(batches.add(sample("demo/a", 0, t1)).is_none());
+    assert!(batches.add(sample("demo/b", 0, t2)).is_none());
+    let batch = batches.add(sample("demo/c", 1, t1)).unwrap();
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch[0].key_expr.as_str(), "demo/a");
+    assert_eq!(batch[1].key_expr.as_str(), "demo/c");
+    let batch = batches.add(sample("demo/d", 1, t2)).unwrap();
+    assert_eq!(batch[0].key_expr.as_str(), "demo/b");
+    assert_eq!(batch[1].key_expr.as_str(), "demo/d");
+
+    // the samples of a batch without timestamp share the same one
+    let batch = batches.add(sample("demo/a", 0, None));
+    assert!(batch.is_none());
+    let batch = batches.add(sample("demo/b", 1, None)).unwrap();
+    assert!(batch[0].timestamp.is_some());
+    assert_eq!(batch[0].timestamp, batch[1].timestamp);
+}
@@ -0,0 +1,64 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module publishes the changes of a storage, so that they can be tracked without subscribing to the data
+//
+// For each sample persisted or deleted by the storage, a notification {key, kind, timestamp} is published on <admin_key>/cdc
+// The notifications are published by a dedicated task, as the adminspace may be waiting for the storage when it receives them
+
+use async_std::sync::Arc;
+use flume::Sender;
+use serde_json::json;
+use std::str::FromStr;
+use zenoh::prelude::r#async::*;
+use zenoh::time::Timestamp;
+use zenoh::{Result as ZResult, Session};
+
+pub const CDC_SUFFIX: &str = "cdc";
+
+pub struct ChangeFeed {
+    tx: Sender<serde_json::Value>,
+}
+
+impl ChangeFeed {
+    pub async fn start(session: Arc<Session>, admin_key: &str) -> ZResult<Self> {
+        let cdc_key = OwnedKeyExpr::from_str(admin_key)?.join(CDC_SUFFIX)?;
+        let publisher = session
+            .declare_publisher(cdc_key)
+            .congestion_control(CongestionControl::Block)
+            .res_async()
+            .await?;
+        let (tx, rx) = flume::unbounded::<serde_json::Value>();
+        async_std::task::spawn(async move {
+            // the task ends with the storage, once the sender is dropped
+            while let Ok(change) = rx.recv_async().await {
+                let value = Value::from(change.to_string()).encoding(KnownEncoding::AppJson.into());
+                if let Err(e) = publisher.put(value).res_async().await {
+                    log::error!("Error publishing a storage change: {}", e);
+                }
+            }
+        });
+        Ok(ChangeFeed { tx })
+    }
+
+    // Notifies that the sample published on `key` at `timestamp` was persisted or deleted
+    pub fn notify(&self, key: &OwnedKeyExpr, kind: SampleKind, timestamp: &Timestamp) {
+        let change = json!({
+            "key": key,
+            "kind": kind.to_string(),
+            "timestamp": timestamp.to_string(),
+        });
+        let _ = self.tx.send(change);
+    }
+}
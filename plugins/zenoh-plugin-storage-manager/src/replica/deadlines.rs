@@ -0,0 +1,108 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module keeps the deadlines of the values stored from samples published with a lifespan
+//
+// The expired values are kept by the backend, but are no longer replied to queries
+// The deadline of a value stored by a storage keeping only the latest values is dropped along with the value
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use zenoh::time::{Timestamp, NTP64};
+use zenoh_backend_traits::{History, StoredData};
+use zenoh_core::zlock;
+use zenoh_keyexpr::key_expr::OwnedKeyExpr;
+
+pub struct Deadlines {
+    history: History,
+    deadlines: Mutex<HashMap<OwnedKeyExpr, Vec<(Timestamp, NTP64)>>>,
+}
+
+impl Deadlines {
+    pub fn new(history: History) -> Self {
+        Deadlines {
+            history,
+            deadlines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Records the deadline, if any, of the value stored (or deleted) for `key` at `timestamp`
+    pub fn record(&self, key: &OwnedKeyExpr, timestamp: Timestamp, deadline: Option<NTP64>) {
+        let mut deadlines = zlock!(self.deadlines);
+        if self.history == History::Latest {
+            deadlines.remove(key);
+        }
+        if let Some(deadline) = deadline {
+            deadlines
+                .entry(key.clone())
+                .or_default()
+                .push((timestamp, deadline));
+        }
+    }
+
+    // Whether the value stored for `key` at `timestamp` is expired
+    pub fn is_expired(&self, key: &OwnedKeyExpr, timestamp: &Timestamp) -> bool {
+        let now = zenoh::time::clock();
+        zlock!(self.deadlines).get(key).map_or(false, |deadlines| {
+            deadlines
+                .iter()
+                .any(|(ts, deadline)| ts == timestamp && *deadline < now)
+        })
+    }
+
+    // Removes the expired values from the values stored for `key`
+    pub fn retain_unexpired(&self, key: &OwnedKeyExpr, stored_data: &mut Vec<StoredData>) {
+        stored_data.retain(|data| !self.is_expired(key, &data.timestamp));
+    }
+}
+
+#[test]
+fn deadlines() {
+    use std::time::Duration;
+    use zenoh::prelude::Value;
+
+    let key = OwnedKeyExpr::new("demo/a").unwrap();
+    let now = zenoh::time::clock();
+    let past = now - NTP64::from(Duration::from_secs(1));
+    let future = now + NTP64::from(Duration::from_secs(60));
+    let t1 = zenoh::time::new_reception_timestamp();
+    let t2 = zenoh::time::new_reception_timestamp();
+
+    let deadlines = Deadlines::new(History::All);
+    deadlines.record(&key, t1, Some(past));
+    deadlines.record(&key, t2, Some(future));
+    assert!(deadlines.is_expired(&key, &t1));
+    assert!(!deadlines.is_expired(&key, &t2));
+    let mut stored_data = vec![
+        StoredData {
+            value: Value::from("1"),
+            timestamp: t1,
+        },
+        StoredData {
+            value: Value::from("2"),
+            timestamp: t2,
+        },
+    ];
+    deadlines.retain_unexpired(&key, &mut stored_data);
+    assert_eq!(stored_data.len(), 1);
+    assert_eq!(stored_data[0].timestamp, t2);
+
+    // the deadline of the latest value is replaced along with the value
+    let deadlines = Deadlines::new(History::Latest);
+    deadlines.record(&key, t1, Some(past));
+    assert!(deadlines.is_expired(&key, &t1));
+    deadlines.record(&key, t2, None);
+    assert!(!deadlines.is_expired(&key, &t1));
+    assert!(!deadlines.is_expired(&key, &t2));
+}
@@ -0,0 +1,292 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module records the samples received by a storage in an append-only log, turning it into a lightweight event log
+//
+// Each sample is appended to the log with the next offset, starting from 0, unless the same (key, timestamp) was already recorded
+// The queries with `_offset=<offset>` and/or `_limit=<count>` parameters are replied with the (at most <count>) entries of the log
+// from <offset> on whose keys intersect the queried key_expr, in place of the stored values
+// The offset of an entry is the source sequence number of its reply
+// The queries on <admin_key>/replay with the same parameters make the storage republish the entries of the range on their keys,
+// to the remote subscribers only, at `_rate` samples per second (`replay_rate` of the configuration by default)
+// The log of a durable storage is also appended to a file in zenoh_home, from which it is reloaded at startup
+
+use async_std::sync::Arc;
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use zenoh::buffers::ZBuf;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::queryable::Queryable;
+use zenoh::sample::SourceInfo;
+use zenoh::time::Timestamp;
+use zenoh::{Result as ZResult, Session};
+use zenoh_backend_traits::config::EventLogConfig;
+use zenoh_core::zlock;
+use zenoh_result::zerror;
+use zenoh_util::zenoh_home;
+
+pub const EVENT_LOG_FILENAME: &str = "event_log";
+pub const REPLAY_SUFFIX: &str = "replay";
+pub const OFFSET_KEY: &str = "_offset";
+pub const LIMIT_KEY: &str = "_limit";
+pub const RATE_KEY: &str = "_rate";
+
+#[derive(Clone)]
+pub struct Event {
+    pub offset: u64,
+    pub key: OwnedKeyExpr,
+    pub kind: SampleKind,
+    pub value: Value,
+    pub timestamp: Timestamp,
+}
+
+impl Event {
+    pub fn to_sample(&self, source_id: ZenohId) -> Sample {
+        let mut sample = Sample::new(self.key.clone(), self.value.clone())
+            .with_timestamp(self.timestamp)
+            .with_source_info(SourceInfo {
+                source_id: Some(source_id),
+                source_sn: Some(self.offset),
+            });
+        sample.kind = self.kind;
+        sample
+    }
+}
+
+// The entries of the log selected by the `_offset` and `_limit` parameters of a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub offset: u64,
+    pub limit: Option<usize>,
+}
+
+impl Range {
+    // Returns `None` if the parameters have neither `_offset` nor `_limit`
+    pub fn from_parameters(parameters: &str) -> ZResult<Option<Self>> {
+        let [offset, limit] = parameters.get_parameters([OFFSET_KEY, LIMIT_KEY])?;
+        if offset.is_none() && limit.is_none() {
+            return Ok(None);
+        }
+        let offset = match offset {
+            Some(offset) => offset
+                .parse()
+                .map_err(|_| zerror!("Invalid `{}` parameter: `{}`", OFFSET_KEY, offset))?,
+            None => 0,
+        };
+        let limit = match limit {
+            Some(limit) => Some(
+                limit
+                    .parse()
+                    .map_err(|_| zerror!("Invalid `{}` parameter: `{}`", LIMIT_KEY, limit))?,
+            ),
+            None => None,
+        };
+        Ok(Some(Range { offset, limit }))
+    }
+}
+
+struct Events {
+    entries: Vec<Event>,
+    recorded: HashSet<(OwnedKeyExpr, Timestamp)>,
+    file: Option<File>,
+}
+
+impl Events {
+    fn select(&self, key_expr: &keyexpr, range: Range) -> Vec<Event> {
+        self.entries
+            .get(range.offset as usize..)
+            .unwrap_or_default()
+            .iter()
+            .filter(|event| key_expr.intersects(&event.key))
+            .take(range.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+}
+
+pub struct EventLog {
+    zid: ZenohId,
+    events: Arc<Mutex<Events>>,
+    _queryable: Queryable<'static, ()>,
+}
+
+impl EventLog {
+    pub async fn start(
+        session: Arc<Session>,
+        name: &str,
+        admin_key: &str,
+        config: EventLogConfig,
+        durable: bool,
+    ) -> ZResult<Self> {
+        let mut events = Events {
+            entries: Vec::new(),
+            recorded: HashSet::new(),
+            file: None,
+        };
+        if durable {
+            let path = zenoh_home().join(format!(
+                "{}_{}",
+                EVENT_LOG_FILENAME,
+                urlencoding::encode(name)
+            ));
+            if path.exists() {
+                for line in std::fs::read_to_string(&path)?.lines() {
+                    let event = deserialize_event(line)?;
+                    events.recorded.insert((event.key.clone(), event.timestamp));
+                    events.entries.push(event);
+                }
+            }
+            events.file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        }
+        let events = Arc::new(Mutex::new(events));
+
+        let replay_key = OwnedKeyExpr::from_str(admin_key)?.join(REPLAY_SUFFIX)?;
+        let c_events = events.clone();
+        let c_session = session.clone();
+        let queryable = session
+            .declare_queryable(replay_key)
+            .callback(move |query| {
+                let request = Range::from_parameters(query.parameters()).and_then(|range| {
+                    let [rate] = query.parameters().get_parameters([RATE_KEY])?;
+                    let rate = match rate {
+                        Some(rate) => rate
+                            .parse::<u64>()
+                            .ok()
+                            .filter(|rate| *rate > 0)
+                            .ok_or_else(|| {
+                                zerror!("Invalid `{}` parameter: `{}`", RATE_KEY, rate)
+                            })?,
+                        None => config.replay_rate,
+                    };
+                    let range = range.unwrap_or(Range {
+                        offset: 0,
+                        limit: None,
+                    });
+                    Ok((zlock!(c_events).select(keyexpr::new("**")?, range), rate))
+                });
+                let reply = match request {
+                    Ok((events, rate)) => {
+                        let status = json!({
+                            "offset": events.first().map(|event| event.offset),
+                            "count": events.len(),
+                            "rate": rate,
+                        });
+                        async_std::task::spawn(replay(c_session.clone(), events, rate));
+                        Ok(Sample::new(
+                            query.key_expr().clone(),
+                            Value::from(status.to_string()).encoding(KnownEncoding::AppJson.into()),
+                        ))
+                    }
+                    Err(e) => Err(Value::from(format!("Invalid replay request: {e}"))),
+                };
+                if let Err(e) = query.reply(reply).res_sync() {
+                    log::error!("Error replying to the replay request: {}", e);
+                }
+            })
+            .res_async()
+            .await?;
+
+        Ok(EventLog {
+            zid: session.zid(),
+            events,
+            _queryable: queryable,
+        })
+    }
+
+    // Appends a sample to the log, unless it was already recorded
+    pub fn append(&self, sample: &Sample) {
+        let Some(timestamp) = sample.timestamp else {
+            return;
+        };
+        let key = OwnedKeyExpr::from(sample.key_expr.as_keyexpr());
+        let mut events = zlock!(self.events);
+        if !events.recorded.insert((key.clone(), timestamp)) {
+            return;
+        }
+        let event = Event {
+            offset: events.entries.len() as u64,
+            key,
+            kind: sample.kind,
+            value: sample.value.clone(),
+            timestamp,
+        };
+        if let Some(file) = &mut events.file {
+            if let Err(e) = writeln!(file, "{}", serialize_event(&event)) {
+                log::error!("Saving the event log failed: {}", e);
+            }
+        }
+        events.entries.push(event);
+    }
+
+    // Returns the samples of the entries of the range whose keys intersect `key_expr`
+    pub fn select(&self, key_expr: &keyexpr, range: Range) -> Vec<Sample> {
+        zlock!(self.events)
+            .select(key_expr, range)
+            .iter()
+            .map(|event| event.to_sample(self.zid))
+            .collect()
+    }
+}
+
+// Republishes the events at `rate` samples per second
+async fn replay(session: Arc<Session>, events: Vec<Event>, rate: u64) {
+    let period = Duration::from_secs_f64(1.0 / rate as f64);
+    for event in events {
+        if let Err(e) = session
+            .put(&event.key, event.value)
+            .kind(event.kind)
+            .allowed_destination(Locality::Remote)
+            .res_async()
+            .await
+        {
+            log::error!("Error replaying the event {}: {}", event.offset, e);
+        }
+        async_std::task::sleep(period).await;
+    }
+}
+
+fn serialize_event(event: &Event) -> String {
+    let result = (
+        event.offset,
+        event.key.as_str(),
+        event.kind.to_string(),
+        event.timestamp.to_string(),
+        event.value.encoding.to_string(),
+        event.value.payload.contiguous().to_vec(),
+    );
+    serde_json::to_string(&result).unwrap()
+}
+
+fn deserialize_event(line: &str) -> ZResult<Event> {
+    let result: (u64, String, String, String, String, Vec<u8>) = serde_json::from_str(line)?;
+    let kind = if result.2.eq(&SampleKind::Delete.to_string()) {
+        SampleKind::Delete
+    } else {
+        SampleKind::Put
+    };
+    Ok(Event {
+        offset: result.0,
+        key: OwnedKeyExpr::from_str(&result.1)?,
+        kind,
+        value: Value::new(ZBuf::from(result.5)).encoding(Encoding::from(result.4)),
+        timestamp: Timestamp::from_str(&result.3)
+            .map_err(|e| zerror!("Invalid timestamp `{}`: {:?}", result.3, e))?,
+    })
+}
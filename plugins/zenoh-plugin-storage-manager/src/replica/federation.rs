@@ -0,0 +1,215 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module federates the storages subscribing to the same key_expr on different sites
+//
+// Each site accepts writes locally, and batches the updates applied to its storage
+// Every `propagation_interval`, the batch is published on <federation_prefix>/<encoded_key_expr>/<site>
+// The updates received from the other sites are applied to the storage after resolving the conflicts
+// with the stored data: by default, the data with the latest HLC timestamp wins
+
+use async_std::sync::Arc;
+use async_trait::async_trait;
+use flume::Receiver;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Mutex;
+use zenoh::prelude::r#async::*;
+use zenoh::subscriber::Subscriber;
+use zenoh::time::Timestamp;
+use zenoh::{Result as ZResult, Session};
+use zenoh_backend_traits::config::FederationConfig;
+use zenoh_backend_traits::{ConflictResolver, LastWriterWins, Resolution, StoredData};
+use zenoh_core::zlock;
+use zenoh_result::zerror;
+use zenoh_util::{Timed, TimedEvent, Timer};
+
+pub const FEDERATION_PREFIX: &str = "@-federation";
+
+// An update as exchanged between the sites
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct FederatedUpdate {
+    key: String,
+    kind: u64,
+    timestamp: String,
+    encoding: String,
+    payload: Vec<u8>,
+}
+
+pub struct Federation {
+    resolver: Arc<dyn ConflictResolver>,
+    pending: Arc<Mutex<Vec<FederatedUpdate>>>,
+    // the updates received from the other sites, to be applied to the storage
+    pub updates: Receiver<Sample>,
+    _subscriber: Subscriber<'static, ()>,
+    _timer: Timer,
+}
+
+impl Federation {
+    pub async fn start(
+        session: Arc<Session>,
+        key_expr: &OwnedKeyExpr,
+        config: FederationConfig,
+        resolver: Option<Arc<dyn ConflictResolver>>,
+    ) -> ZResult<Self> {
+        let prefix =
+            OwnedKeyExpr::from_str(FEDERATION_PREFIX)?.join(&urlencoding::encode(key_expr))?;
+        let site_key = prefix.join(&config.site)?;
+
+        let (tx, updates) = flume::unbounded();
+        let site = config.site.clone();
+        let subscriber = session
+            .declare_subscriber(prefix.join("*")?)
+            .callback(move |sample| {
+                let from = &sample.key_expr.as_str()[prefix.len() + 1..];
+                if from == site {
+                    return;
+                }
+                let batch = match parse_batch(&sample.value) {
+                    Ok(batch) => batch,
+                    Err(e) => {
+                        log::error!("Invalid updates from site {}: {}", from, e);
+                        return;
+                    }
+                };
+                log::trace!(
+                    "[FEDERATION] Received {} updates from {}",
+                    batch.len(),
+                    from
+                );
+                for update in batch {
+                    if tx.send(update).is_err() {
+                        return;
+                    }
+                }
+            })
+            .res_async()
+            .await?;
+
+        let pending = Arc::new(Mutex::new(Vec::new()));
+        let timer = Timer::default();
+        let propagation = TimedEvent::periodic(
+            config.propagation_interval,
+            PropagationEvent {
+                session,
+                site_key,
+                pending: pending.clone(),
+            },
+        );
+        timer.add_async(propagation).await;
+
+        Ok(Federation {
+            resolver: resolver.unwrap_or_else(|| Arc::new(LastWriterWins)),
+            pending,
+            updates,
+            _subscriber: subscriber,
+            _timer: timer,
+        })
+    }
+
+    // Batches an update applied to the storage, to be propagated to the other sites
+    pub fn propagate(&self, key: &OwnedKeyExpr, kind: SampleKind, data: &StoredData) {
+        zlock!(self.pending).push(FederatedUpdate {
+            key: key.to_string(),
+            kind: kind as u64,
+            timestamp: data.timestamp.to_string(),
+            encoding: data.value.encoding.to_string(),
+            payload: data.value.payload.contiguous().to_vec(),
+        });
+    }
+
+    // Resolves the conflict between the data stored for `key` and an update of it
+    pub fn resolve(
+        &self,
+        key: &OwnedKeyExpr,
+        stored: &StoredData,
+        update: &StoredData,
+    ) -> Resolution {
+        // the update was already applied, either directly or through another site
+        if stored.timestamp == update.timestamp
+            && stored.value.encoding == update.value.encoding
+            && stored.value.payload.contiguous() == update.value.payload.contiguous()
+        {
+            return Resolution::Keep;
+        }
+        self.resolver.resolve(key, stored, update)
+    }
+}
+
+fn parse_batch(value: &Value) -> ZResult<Vec<Sample>> {
+    let batch: Vec<FederatedUpdate> = serde_json::from_slice(&value.payload.contiguous())?;
+    let mut samples = Vec::with_capacity(batch.len());
+    for update in batch {
+        let kind =
+            SampleKind::try_from(update.kind).map_err(|k| zerror!("invalid sample kind: {}", k))?;
+        let timestamp = Timestamp::from_str(&update.timestamp)
+            .map_err(|e| zerror!("invalid timestamp: {:?}", e))?;
+        let value = Value::from(update.payload).encoding(Encoding::from(update.encoding));
+        let mut sample =
+            Sample::new(OwnedKeyExpr::new(update.key)?, value).with_timestamp(timestamp);
+        sample.kind = kind;
+        samples.push(sample);
+    }
+    Ok(samples)
+}
+
+// Periodic event publishing the batched updates to the other sites
+struct PropagationEvent {
+    session: Arc<Session>,
+    site_key: OwnedKeyExpr,
+    pending: Arc<Mutex<Vec<FederatedUpdate>>>,
+}
+
+#[async_trait]
+impl Timed for PropagationEvent {
+    async fn run(&mut self) {
+        let batch = std::mem::take(&mut *zlock!(self.pending));
+        if batch.is_empty() {
+            return;
+        }
+        log::trace!("[FEDERATION] Propagating {} updates", batch.len());
+        let payload = match serde_json::to_string(&batch) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::error!("Error serializing the updates to propagate: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.session.put(&self.site_key, payload).res_async().await {
+            log::error!("Error propagating the updates to the other sites: {}", e);
+        }
+    }
+}
+
+#[test]
+fn federated_updates_roundtrip() {
+    let timestamp = zenoh::time::new_reception_timestamp();
+    let update = FederatedUpdate {
+        key: "demo/a".into(),
+        kind: SampleKind::Delete as u64,
+        timestamp: timestamp.to_string(),
+        encoding: Encoding::TEXT_PLAIN.to_string(),
+        payload: b"1".to_vec(),
+    };
+    let batch = serde_json::to_string(&vec![update]).unwrap();
+    let samples = parse_batch(&Value::from(batch)).unwrap();
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].key_expr.as_str(), "demo/a");
+    assert_eq!(samples[0].kind, SampleKind::Delete);
+    assert_eq!(samples[0].timestamp, Some(timestamp));
+    assert_eq!(samples[0].value.encoding, Encoding::TEXT_PLAIN);
+    assert_eq!(samples[0].value.to_string(), "1");
+
+    assert!(parse_batch(&Value::from(r#"[{"key":"demo/a"}]"#)).is_err());
+}
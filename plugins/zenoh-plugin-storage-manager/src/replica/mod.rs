@@ -31,15 +31,29 @@ use zenoh::time::Timestamp;
 use zenoh::Session;
 use zenoh_backend_traits::config::{ReplicaConfig, StorageConfig};
 
+pub mod aggregate;
 pub mod align_queryable;
 pub mod aligner;
+pub mod batches;
+pub mod cdc;
+pub mod deadlines;
 pub mod digest;
+pub mod event_log;
+pub mod federation;
+pub mod quorum;
 pub mod snapshotter;
 pub mod storage;
 
+pub use aggregate::Aggregate;
 pub use align_queryable::AlignQueryable;
 pub use aligner::Aligner;
+pub use batches::Batches;
+pub use cdc::ChangeFeed;
+pub use deadlines::Deadlines;
 pub use digest::{Digest, DigestConfig, EraType, LogEntry};
+pub use event_log::EventLog;
+pub use federation::Federation;
+pub use quorum::WriteQuorum;
 pub use snapshotter::{ReplicationInfo, Snapshotter};
 pub use storage::{ReplicationService, StorageService};
 
@@ -78,6 +92,7 @@ impl Replica {
         store_intercept: StoreIntercept,
         storage_config: StorageConfig,
         name: &str,
+        admin_key: &str,
         rx: Receiver<StorageMessage>,
     ) {
         log::trace!("[REPLICA] Opening session...");
@@ -91,6 +106,11 @@ impl Replica {
                         } else {
                             log::error!("Empty key found with timestamp `{}`", entry.1);
                         }
+                    } else if let Some(key_mapping) = &storage_config.key_mapping {
+                        match key_mapping.to_zenoh(&entry.0.unwrap()) {
+                            Ok(key) => result.push((key, entry.1)),
+                            Err(e) => log::error!("[REPLICA] {}", e),
+                        }
                     } else {
                         result.push((
                             StorageService::get_prefixed(
@@ -165,6 +185,7 @@ impl Replica {
             replica.session.clone(),
             storage_config,
             &replica.name,
+            admin_key,
             store_intercept,
             rx,
             Some(replication),
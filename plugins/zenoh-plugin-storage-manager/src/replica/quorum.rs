@@ -0,0 +1,220 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module acknowledges the publications persisted by a quorum of the replicas of a storage
+//
+// Each replica publishes a confirmation on <ack_prefix>/<encoded_key_expr>/<replica_name> for every sample it persisted
+// Each replica counts the distinct replicas having confirmed a (key, timestamp) pair, itself included
+// Once `write_quorum` replicas confirmed it, the publication is acknowledged: the queries on <admin_key>/acks/<key>
+// are replied with the timestamp of the latest acknowledged publication on <key>
+
+use async_std::sync::Arc;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::queryable::Queryable;
+use zenoh::subscriber::Subscriber;
+use zenoh::time::Timestamp;
+use zenoh::{Result as ZResult, Session};
+use zenoh_backend_traits::config::ReplicationConfig;
+use zenoh_core::zlock;
+use zenoh_result::zerror;
+
+pub const ACK_PREFIX: &str = "@-ack";
+// The confirmations of the publications not acknowledged within this duration are discarded
+const CONFIRMATIONS_LIFESPAN: Duration = Duration::from_secs(60);
+
+type Confirmations = HashMap<(OwnedKeyExpr, Timestamp), HashSet<String>>;
+
+pub struct WriteQuorum {
+    session: Arc<Session>,
+    confirmation_key: OwnedKeyExpr,
+    _subscriber: Subscriber<'static, ()>,
+    _queryable: Queryable<'static, ()>,
+}
+
+impl WriteQuorum {
+    pub async fn start(
+        session: Arc<Session>,
+        key_expr: &OwnedKeyExpr,
+        name: &str,
+        admin_key: &str,
+        config: ReplicationConfig,
+    ) -> ZResult<Self> {
+        let prefix = OwnedKeyExpr::from_str(ACK_PREFIX)?.join(&urlencoding::encode(key_expr))?;
+        let confirmation_key = prefix.join(name)?;
+        let acks_prefix = OwnedKeyExpr::from_str(admin_key)?.join("acks")?;
+        // the timestamp of the latest acknowledged publication of each key
+        let acks = Arc::new(Mutex::new(HashMap::<OwnedKeyExpr, Timestamp>::new()));
+
+        let confirmations = Mutex::new(Confirmations::new());
+        let c_acks = acks.clone();
+        let subscriber = session
+            .declare_subscriber(prefix.join("**")?)
+            .callback(move |sample| {
+                let replica = &sample.key_expr.as_str()[prefix.len() + 1..];
+                let (key, timestamp) = match parse_confirmation(&sample.value) {
+                    Ok(confirmation) => confirmation,
+                    Err(e) => {
+                        log::error!("Invalid confirmation from replica {}: {}", replica, e);
+                        return;
+                    }
+                };
+                let confirmed = confirm(
+                    &mut zlock!(confirmations),
+                    &config,
+                    replica,
+                    key.clone(),
+                    timestamp,
+                );
+                if confirmed == config.write_quorum {
+                    log::trace!("[QUORUM] Publication {} on {} acknowledged", timestamp, key);
+                    let mut acks = zlock!(c_acks);
+                    let latest = acks.entry(key).or_insert(timestamp);
+                    if *latest < timestamp {
+                        *latest = timestamp;
+                    }
+                }
+            })
+            .res_async()
+            .await?;
+
+        let queryable = session
+            .declare_queryable(acks_prefix.join("**")?)
+            .callback(move |query| {
+                let acks = zlock!(acks).clone();
+                for (key, timestamp) in acks {
+                    let Ok(ack_key) = acks_prefix.join(key.as_str()) else {
+                        continue;
+                    };
+                    if query.key_expr().intersects(&ack_key) {
+                        let sample = Sample::new(ack_key, timestamp.to_string());
+                        if let Err(e) = query.reply(Ok(sample)).res_sync() {
+                            log::error!("Error replying to the query on acks: {}", e);
+                        }
+                    }
+                }
+            })
+            .res_async()
+            .await?;
+
+        Ok(WriteQuorum {
+            session,
+            confirmation_key,
+            _subscriber: subscriber,
+            _queryable: queryable,
+        })
+    }
+
+    // Confirms to all the replicas, including this one, that the sample published on `key` at `timestamp` is persisted
+    pub async fn confirm(&self, key: &OwnedKeyExpr, timestamp: &Timestamp) {
+        let confirmation = json!({ "key": key, "timestamp": timestamp.to_string() });
+        if let Err(e) = self
+            .session
+            .put(&self.confirmation_key, confirmation.to_string())
+            .res_async()
+            .await
+        {
+            log::error!("Error confirming the persistence of {}: {}", key, e);
+        }
+    }
+}
+
+fn parse_confirmation(value: &Value) -> ZResult<(OwnedKeyExpr, Timestamp)> {
+    let confirmation: serde_json::Value = serde_json::from_slice(&value.payload.contiguous())?;
+    let key = confirmation["key"]
+        .as_str()
+        .ok_or_else(|| zerror!("missing key"))?;
+    let timestamp = confirmation["timestamp"]
+        .as_str()
+        .ok_or_else(|| zerror!("missing timestamp"))?;
+    let timestamp =
+        Timestamp::from_str(timestamp).map_err(|e| zerror!("invalid timestamp: {:?}", e))?;
+    Ok((OwnedKeyExpr::new(key)?, timestamp))
+}
+
+// Registers the confirmation of `replica` and returns the number of distinct replicas having confirmed the publication
+fn confirm(
+    confirmations: &mut Confirmations,
+    config: &ReplicationConfig,
+    replica: &str,
+    key: OwnedKeyExpr,
+    timestamp: Timestamp,
+) -> usize {
+    let now = zenoh::time::new_reception_timestamp();
+    let lifespan = zenoh::time::NTP64::from(CONFIRMATIONS_LIFESPAN);
+    confirmations
+        .retain(|(_, ts), _| ts.get_time().as_u64() + lifespan.as_u64() > now.get_time().as_u64());
+    let replicas = confirmations.entry((key.clone(), timestamp)).or_default();
+    replicas.insert(replica.to_string());
+    let confirmed = replicas.len();
+    // No more confirmation is expected once all the replicas confirmed
+    if confirmed >= config.factor {
+        confirmations.remove(&(key, timestamp));
+    }
+    confirmed
+}
+
+#[test]
+fn quorum_confirmations() {
+    let config = ReplicationConfig {
+        factor: 3,
+        write_quorum: 2,
+    };
+    let mut confirmations = Confirmations::new();
+    let key = OwnedKeyExpr::new("demo/a").unwrap();
+    let timestamp = zenoh::time::new_reception_timestamp();
+    assert_eq!(
+        confirm(&mut confirmations, &config, "r1", key.clone(), timestamp),
+        1
+    );
+    // The confirmations of a same replica are only counted once
+    assert_eq!(
+        confirm(&mut confirmations, &config, "r1", key.clone(), timestamp),
+        1
+    );
+    assert_eq!(
+        confirm(&mut confirmations, &config, "r2", key.clone(), timestamp),
+        2
+    );
+    let other = zenoh::time::new_reception_timestamp();
+    assert_eq!(
+        confirm(&mut confirmations, &config, "r2", key.clone(), other),
+        1
+    );
+    assert_eq!(
+        confirm(&mut confirmations, &config, "r3", key.clone(), timestamp),
+        3
+    );
+    // The publications confirmed by all the replicas are forgotten
+    assert_eq!(confirmations.len(), 1);
+
+    // The outdated confirmations are discarded
+    let outdated = Timestamp::new(
+        zenoh::time::NTP64::from(
+            std::time::UNIX_EPOCH.elapsed().unwrap() - 2 * CONFIRMATIONS_LIFESPAN,
+        ),
+        *timestamp.get_id(),
+    );
+    confirm(&mut confirmations, &config, "r1", key.clone(), outdated);
+    assert_eq!(
+        confirm(&mut confirmations, &config, "r1", key, timestamp),
+        1
+    );
+    assert!(!confirmations.keys().any(|(_, ts)| ts == &outdated));
+}
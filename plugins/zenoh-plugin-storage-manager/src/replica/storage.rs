@@ -11,23 +11,29 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::event_log::Range;
+use super::{Aggregate, Batches, ChangeFeed, Deadlines, EventLog, Federation, WriteQuorum};
 use crate::backends_mgt::StoreIntercept;
 use crate::storages_mgt::StorageMessage;
 use async_std::sync::Arc;
 use async_std::sync::{Mutex, RwLock};
 use async_trait::async_trait;
 use flume::{Receiver, Sender};
-use futures::select;
+use futures::{select, FutureExt};
 use std::collections::{HashMap, HashSet};
 use std::str::{self, FromStr};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::SystemTime;
 use zenoh::buffers::ZBuf;
 use zenoh::prelude::r#async::*;
 use zenoh::query::ConsolidationMode;
+use zenoh::selector::{TimeBound, TimeExpr, TimeRange};
 use zenoh::time::{Timestamp, NTP64};
 use zenoh::{Result as ZResult, Session};
-use zenoh_backend_traits::config::{GarbageCollectionConfig, StorageConfig};
-use zenoh_backend_traits::{Capability, History, Persistence, StorageInsertionResult, StoredData};
+use zenoh_backend_traits::config::{GarbageCollectionConfig, IndexPath, KeyMapping, StorageConfig};
+use zenoh_backend_traits::{
+    Capability, History, IndexPredicate, Persistence, Resolution, StorageInsertionResult,
+    StoredData,
+};
 use zenoh_keyexpr::key_expr::OwnedKeyExpr;
 use zenoh_keyexpr::keyexpr_tree::impls::KeyedSetProvider;
 use zenoh_keyexpr::keyexpr_tree::IKeyExprTreeMut;
@@ -46,6 +52,14 @@ struct Update {
     data: StoredData,
 }
 
+// An update of the storage required by a received sample
+struct PendingUpdate {
+    key: OwnedKeyExpr,
+    stripped_key: Option<OwnedKeyExpr>,
+    sample: Sample,
+    sample_to_store: Sample,
+}
+
 pub struct ReplicationService {
     pub empty_start: bool,
     pub aligner_updates: Receiver<Sample>,
@@ -58,6 +72,8 @@ pub struct StorageService {
     complete: bool,
     name: String,
     strip_prefix: Option<OwnedKeyExpr>,
+    key_mapping: Option<KeyMapping>,
+    index: Vec<IndexPath>,
     storage: Mutex<Box<dyn zenoh_backend_traits::Storage>>,
     capability: Capability,
     tombstones: Arc<RwLock<KeBoxTree<Timestamp, NonWild, KeyedSetProvider>>>,
@@ -65,6 +81,13 @@ pub struct StorageService {
     in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     replication: Option<ReplicationService>,
+    write_quorum: Option<WriteQuorum>,
+    federation: Option<Federation>,
+    change_feed: Option<ChangeFeed>,
+    event_log: Option<EventLog>,
+    deadlines: Deadlines,
+    reply_priority: Priority,
+    aggregate: Option<Aggregate>,
 }
 
 impl StorageService {
@@ -72,10 +95,85 @@ impl StorageService {
         session: Arc<Session>,
         config: StorageConfig,
         name: &str,
+        admin_key: &str,
         store_intercept: StoreIntercept,
         rx: Receiver<StorageMessage>,
         replication: Option<ReplicationService>,
     ) {
+        let write_quorum = match config.replication {
+            Some(replication_config) => match WriteQuorum::start(
+                session.clone(),
+                &config.key_expr,
+                name,
+                admin_key,
+                replication_config,
+            )
+            .await
+            {
+                Ok(write_quorum) => Some(write_quorum),
+                Err(e) => {
+                    log::error!("Error starting storage {}: {}", name, e);
+                    return;
+                }
+            },
+            None => None,
+        };
+        let federation = match config.federation {
+            Some(federation_config) => match Federation::start(
+                session.clone(),
+                &config.key_expr,
+                federation_config,
+                store_intercept.conflict_resolver,
+            )
+            .await
+            {
+                Ok(federation) => Some(federation),
+                Err(e) => {
+                    log::error!("Error starting storage {}: {}", name, e);
+                    return;
+                }
+            },
+            None => None,
+        };
+        let change_feed = if config.cdc {
+            match ChangeFeed::start(session.clone(), admin_key).await {
+                Ok(change_feed) => Some(change_feed),
+                Err(e) => {
+                    log::error!("Error starting storage {}: {}", name, e);
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let event_log = match config.event_log {
+            Some(event_log_config) => match EventLog::start(
+                session.clone(),
+                name,
+                admin_key,
+                event_log_config,
+                store_intercept
+                    .capability
+                    .persistence
+                    .eq(&Persistence::Durable),
+            )
+            .await
+            {
+                Ok(event_log) => Some(event_log),
+                Err(e) => {
+                    log::error!("Error starting storage {}: {}", name, e);
+                    return;
+                }
+            },
+            None => None,
+        };
+        let deadlines = Deadlines::new(store_intercept.capability.history.clone());
+        let aggregate = match config.aggregate {
+            Some(aggregate_config) => {
+                Some(Aggregate::start(&config.key_expr, aggregate_config).await)
+            }
+            None => None,
+        };
         // @TODO: optimization: if read_cost is high for the storage, initialize a cache for the latest value
         let mut storage_service = StorageService {
             session,
@@ -83,6 +181,8 @@ impl StorageService {
             complete: config.complete,
             name: name.to_string(),
             strip_prefix: config.strip_prefix,
+            key_mapping: config.key_mapping,
+            index: config.index,
             storage: Mutex::new(store_intercept.storage),
             capability: store_intercept.capability,
             tombstones: Arc::new(RwLock::new(KeBoxTree::new())),
@@ -90,6 +190,13 @@ impl StorageService {
             in_interceptor: store_intercept.in_interceptor,
             out_interceptor: store_intercept.out_interceptor,
             replication,
+            write_quorum,
+            federation,
+            change_feed,
+            event_log,
+            deadlines,
+            reply_priority: config.reply_priority,
+            aggregate,
         };
         if storage_service
             .capability
@@ -142,8 +249,12 @@ impl StorageService {
         );
         t.add_async(gc).await;
 
-        // subscribe on key_expr
-        let storage_sub = match self.session.declare_subscriber(&self.key_expr).res().await {
+        // subscribe on key_expr, or on the source of the downsampled mirror
+        let sub_key_expr = self
+            .aggregate
+            .as_ref()
+            .map_or(&self.key_expr, |aggregate| &aggregate.source);
+        let storage_sub = match self.session.declare_subscriber(sub_key_expr).res().await {
             Ok(storage_sub) => storage_sub,
             Err(e) => {
                 log::error!("Error starting storage {}: {}", self.name, e);
@@ -166,6 +277,7 @@ impl StorageService {
             }
         };
 
+        let mut batches = Batches::default();
         if self.replication.is_some() {
             let aligner_updates = &self.replication.as_ref().unwrap().aligner_updates;
             loop {
@@ -184,6 +296,14 @@ impl StorageService {
                         if sample.get_timestamp().is_none() {
                             log::error!("Sample {} is not timestamped. Please timestamp samples meant for replicated storage.", sample);
                         }
+                        else if self.aggregate.is_some() {
+                            self.process_aggregated(sample).await;
+                        }
+                        else if sample.batch.is_some() {
+                            if let Some(samples) = batches.add(sample) {
+                                self.process_batch(samples).await;
+                            }
+                        }
                         else {
                             self.process_sample(sample).await;
                         }
@@ -192,6 +312,24 @@ impl StorageService {
                     query = storage_queryable.recv_async() => {
                         self.reply_query(query).await;
                     },
+                    // on sample held by the downsampling whose bucket is over
+                    sample = recv_flushed_sample(&self.aggregate).fuse() => {
+                        match sample {
+                            Ok(sample) => self.process_sample(sample).await,
+                            Err(e) => {
+                                log::error!("Error in receiving downsampled sample: {}", e);
+                            }
+                        }
+                    },
+                    // on update from another site
+                    update = recv_federated_update(&self.federation).fuse() => {
+                        match update {
+                            Ok(sample) => self.process_sample(sample).await,
+                            Err(e) => {
+                                log::error!("Error in receiving federated update: {}", e);
+                            }
+                        }
+                    },
                     // on aligner update
                     update = aligner_updates.recv_async() => {
                         match update {
@@ -232,13 +370,41 @@ impl StorageService {
                                 continue;
                             }
                         };
-                        sample.ensure_timestamp();
-                        self.process_sample(sample).await;
+                        // the samples of a batch are stored once they are all received
+                        if self.aggregate.is_some() {
+                            sample.ensure_timestamp();
+                            self.process_aggregated(sample).await;
+                        } else if sample.batch.is_some() {
+                            if let Some(samples) = batches.add(sample) {
+                                self.process_batch(samples).await;
+                            }
+                        } else {
+                            sample.ensure_timestamp();
+                            self.process_sample(sample).await;
+                        }
                     },
                     // on query on key_expr
                     query = storage_queryable.recv_async() => {
                         self.reply_query(query).await;
                     },
+                    // on sample held by the downsampling whose bucket is over
+                    sample = recv_flushed_sample(&self.aggregate).fuse() => {
+                        match sample {
+                            Ok(sample) => self.process_sample(sample).await,
+                            Err(e) => {
+                                log::error!("Error in receiving downsampled sample: {}", e);
+                            }
+                        }
+                    },
+                    // on update from another site
+                    update = recv_federated_update(&self.federation).fuse() => {
+                        match update {
+                            Ok(sample) => self.process_sample(sample).await,
+                            Err(e) => {
+                                log::error!("Error in receiving federated update: {}", e);
+                            }
+                        }
+                    },
                     // on storage handle drop
                     message = rx.recv_async() => {
                         match message {
@@ -264,6 +430,32 @@ impl StorageService {
     // The storage should only simply save the key, sample pair while put and retrieve the same during get
     // the trimming during PUT and GET should be handled by the plugin
     async fn process_sample(&self, sample: Sample) {
+        let updates = self.prepare_updates(sample).await;
+        self.store_updates(updates, false).await;
+    }
+
+    // Stores the samples kept by the downsampling of a sample of the mirrored key expression
+    async fn process_aggregated(&self, sample: Sample) {
+        if let Some(aggregate) = &self.aggregate {
+            for sample in aggregate.add(sample) {
+                self.process_sample(sample).await;
+            }
+        }
+    }
+
+    // Stores the samples of an atomic batch as a unit: the storage is not queried while the batch is stored,
+    // and its puts are stored with a single call to the backend
+    async fn process_batch(&self, samples: Vec<Sample>) {
+        log::trace!("[STORAGE] Processing batch of {} samples", samples.len());
+        let mut updates = Vec::with_capacity(samples.len());
+        for sample in samples {
+            updates.extend(self.prepare_updates(sample).await);
+        }
+        self.store_updates(updates, true).await;
+    }
+
+    // Returns the updates of the storage required by a sample, one per matching key to be updated
+    async fn prepare_updates(&self, sample: Sample) -> Vec<PendingUpdate> {
         log::trace!("[STORAGE] Processing sample: {}", sample);
         // Call incoming data interceptor (if any)
         let sample = if let Some(ref interceptor) = self.in_interceptor {
@@ -272,6 +464,11 @@ impl StorageService {
             sample
         };
 
+        // record every sample received in the event log, whether or not it updates the storage
+        if let Some(event_log) = &self.event_log {
+            event_log.append(&sample);
+        }
+
         // if wildcard, update wildcard_updates
         if sample.key_expr.is_wild() {
             self.register_wildcard_update(sample.clone()).await;
@@ -288,13 +485,15 @@ impl StorageService {
             matching_keys
         );
 
+        let mut updates = vec![];
         for k in matching_keys {
             if !self
                 .is_deleted(&k.clone(), sample.get_timestamp().unwrap())
                 .await
                 && (self.capability.history.eq(&History::All)
                     || (self.capability.history.eq(&History::Latest)
-                        && self.is_latest(&k, sample.get_timestamp().unwrap()).await))
+                        && (self.federation.is_some()
+                            || self.is_latest(&k, sample.get_timestamp().unwrap()).await)))
             {
                 log::trace!(
                     "Sample `{}` identified as neded processing for key {}",
@@ -322,51 +521,150 @@ impl StorageService {
                         sample_to_store
                     }
                 };
+                // the conflicts of federated storages are resolved in place of keeping the latest sample
+                let sample_to_store = match &self.federation {
+                    Some(federation) if self.capability.history.eq(&History::Latest) => {
+                        match self.resolve_conflict(federation, &k, sample_to_store).await {
+                            Some(sample_to_store) => sample_to_store,
+                            None => continue,
+                        }
+                    }
+                    _ => sample_to_store,
+                };
 
                 let stripped_key = match self.strip_prefix(&sample_to_store.key_expr) {
                     Ok(stripped) => stripped,
                     Err(e) => {
                         log::error!("{}", e);
-                        return;
+                        return updates;
                     }
                 };
-                let mut storage = self.storage.lock().await;
-                let result = if sample.kind == SampleKind::Put {
-                    storage
-                        .put(
-                            stripped_key,
-                            sample_to_store.value.clone(),
-                            sample_to_store.timestamp.unwrap(),
-                        )
-                        .await
-                } else if sample.kind == SampleKind::Delete {
-                    // register a tombstone
-                    self.mark_tombstone(&k, sample_to_store.timestamp.unwrap())
-                        .await;
-                    storage
-                        .delete(stripped_key, sample_to_store.timestamp.unwrap())
-                        .await
-                } else {
-                    Err("sample kind not implemented".into())
-                };
-                drop(storage);
-                if self.replication.is_some()
-                    && result.is_ok()
-                    && !matches!(result.unwrap(), StorageInsertionResult::Outdated)
-                {
-                    let sending = self
-                        .replication
-                        .as_ref()
-                        .unwrap()
-                        .log_propagation
-                        .send((k.clone(), *sample_to_store.get_timestamp().unwrap()));
-                    match sending {
-                        Ok(_) => (),
-                        Err(e) => {
-                            log::error!("Error in sending the sample to the log: {}", e);
-                        }
+                updates.push(PendingUpdate {
+                    key: k,
+                    stripped_key,
+                    sample: sample.clone(),
+                    sample_to_store,
+                });
+            }
+        }
+        updates
+    }
+
+    // Applies the updates to the storage, the puts with a single call to the backend if `atomic`
+    async fn store_updates(&self, updates: Vec<PendingUpdate>, atomic: bool) {
+        let mut storage = self.storage.lock().await;
+        let mut puts = if atomic {
+            let entries = updates
+                .iter()
+                .filter(|update| update.sample.kind == SampleKind::Put)
+                .map(|update| {
+                    (
+                        update.stripped_key.clone(),
+                        update.sample_to_store.value.clone(),
+                        update.sample_to_store.timestamp.unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let len = entries.len();
+            match storage.put_batch(entries).await {
+                Ok(results) => results.into_iter().map(Ok).collect::<Vec<_>>(),
+                Err(e) => {
+                    log::error!("Error storing batch in storage {}: {}", self.name, e);
+                    (0..len)
+                        .map(|_| Err(e.to_string().into()))
+                        .collect::<Vec<_>>()
+                }
+            }
+            .into_iter()
+        } else {
+            vec![].into_iter()
+        };
+        let mut results = Vec::with_capacity(updates.len());
+        for update in &updates {
+            let timestamp = update.sample_to_store.timestamp.unwrap();
+            let result = if update.sample.kind == SampleKind::Put {
+                match puts.next() {
+                    Some(result) => result,
+                    None => {
+                        storage
+                            .put(
+                                update.stripped_key.clone(),
+                                update.sample_to_store.value.clone(),
+                                timestamp,
+                            )
+                            .await
                     }
                 }
+            } else if update.sample.kind == SampleKind::Delete {
+                // register a tombstone
+                self.mark_tombstone(&update.key, timestamp).await;
+                storage.delete(update.stripped_key.clone(), timestamp).await
+            } else {
+                Err("sample kind not implemented".into())
+            };
+            results.push(result);
+        }
+        drop(storage);
+
+        for (update, result) in updates.into_iter().zip(results) {
+            self.record_update(update, result).await;
+        }
+    }
+
+    // Propagates an update applied to the storage
+    async fn record_update(&self, update: PendingUpdate, result: ZResult<StorageInsertionResult>) {
+        let PendingUpdate {
+            key: k,
+            sample,
+            sample_to_store,
+            ..
+        } = update;
+        if let Ok(insertion) = &result {
+            if !matches!(insertion, StorageInsertionResult::Outdated) {
+                // the deadline of a sample published with a lifespan, unless overridden by a wildcard update
+                let deadline = sample
+                    .deadline
+                    .filter(|_| sample_to_store.timestamp == sample.timestamp)
+                    .filter(|_| sample_to_store.kind == SampleKind::Put);
+                self.deadlines
+                    .record(&k, sample_to_store.timestamp.unwrap(), deadline);
+            }
+        }
+        if let (Ok(_), Some(federation)) = (&result, &self.federation) {
+            federation.propagate(
+                &k,
+                sample_to_store.kind,
+                &StoredData {
+                    value: sample_to_store.value.clone(),
+                    timestamp: sample_to_store.timestamp.unwrap(),
+                },
+            );
+        }
+        if let (Ok(_), Some(write_quorum)) = (&result, &self.write_quorum) {
+            write_quorum
+                .confirm(&k, sample_to_store.get_timestamp().unwrap())
+                .await;
+        }
+        if let (Ok(insertion), Some(change_feed)) = (&result, &self.change_feed) {
+            if !matches!(insertion, StorageInsertionResult::Outdated) {
+                change_feed.notify(&k, sample.kind, sample_to_store.get_timestamp().unwrap());
+            }
+        }
+        if self.replication.is_some()
+            && result.is_ok()
+            && !matches!(result.unwrap(), StorageInsertionResult::Outdated)
+        {
+            let sending = self
+                .replication
+                .as_ref()
+                .unwrap()
+                .log_propagation
+                .send((k.clone(), *sample_to_store.get_timestamp().unwrap()));
+            match sending {
+                Ok(_) => (),
+                Err(e) => {
+                    log::error!("Error in sending the sample to the log: {}", e);
+                }
             }
         }
     }
@@ -492,6 +790,51 @@ impl StorageService {
         true
     }
 
+    // Resolves the conflict between a sample and the data stored for `key`, returning the sample to store if any
+    async fn resolve_conflict(
+        &self,
+        federation: &Federation,
+        key: &OwnedKeyExpr,
+        sample: Sample,
+    ) -> Option<Sample> {
+        let stripped_key = match self.strip_prefix(&key.into()) {
+            Ok(stripped) => stripped,
+            Err(e) => {
+                log::error!("{}", e);
+                return None;
+            }
+        };
+        let mut storage = self.storage.lock().await;
+        let stored = match storage.get(stripped_key, "").await {
+            Ok(stored_data) => stored_data.into_iter().max_by_key(|entry| entry.timestamp),
+            Err(_) => None,
+        };
+        drop(storage);
+        let timestamp = *sample.get_timestamp().unwrap();
+        let Some(stored) = stored else {
+            return Some(sample);
+        };
+        // deletions are resolved by the latest timestamp
+        if sample.kind == SampleKind::Delete {
+            return (timestamp > stored.timestamp).then_some(sample);
+        }
+        let update = StoredData {
+            value: sample.value.clone(),
+            timestamp,
+        };
+        match federation.resolve(key, &stored, &update) {
+            Resolution::Keep => None,
+            Resolution::Accept => Some(sample),
+            Resolution::Merge(value) => {
+                log::trace!("[FEDERATION] Merged the concurrent updates of {}", key);
+                Some(
+                    Sample::new(KeyExpr::from(key.clone()), value)
+                        .with_timestamp(std::cmp::max(timestamp, stored.timestamp)),
+                )
+            }
+        }
+    }
+
     async fn reply_query(&self, query: Result<zenoh::queryable::Query, flume::RecvError>) {
         let q = match query {
             Ok(q) => q,
@@ -501,6 +844,60 @@ impl StorageService {
             }
         };
         log::trace!("[STORAGE] Processing query on key_expr: {}", q.key_expr());
+        if let Some(event_log) = &self.event_log {
+            match Range::from_parameters(q.parameters()) {
+                Ok(Some(range)) => {
+                    for sample in event_log.select(q.key_expr(), range) {
+                        // apply outgoing interceptor on results
+                        let sample = if let Some(ref interceptor) = self.out_interceptor {
+                            interceptor(sample)
+                        } else {
+                            sample
+                        };
+                        if let Err(e) = q
+                            .reply(Ok(sample))
+                            .priority(self.reply_priority)
+                            .res()
+                            .await
+                        {
+                            log::warn!(
+                                "Storage {} raised an error replying a query: {}",
+                                self.name,
+                                e
+                            )
+                        }
+                    }
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    self.reply_invalid_query(&q, e).await;
+                    return;
+                }
+            }
+        }
+        let predicates = match IndexPredicate::from_parameters(q.parameters()) {
+            Ok(predicates) => predicates,
+            Err(e) => {
+                self.reply_invalid_query(&q, e).await;
+                return;
+            }
+        };
+        match q.selector().time_instant() {
+            Ok(Some(instant)) => {
+                self.reply_as_of_query(&q, instant, &predicates).await;
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                self.reply_invalid_query(&q, e).await;
+                return;
+            }
+        }
+        if !predicates.is_empty() {
+            self.reply_filtered_query(&q, &predicates).await;
+            return;
+        }
         if q.key_expr().is_wild() {
             // resolve key expr into individual keys
             let matching_keys = self.get_matching_keys(q.key_expr()).await;
@@ -515,7 +912,8 @@ impl StorageService {
                     }
                 };
                 match storage.get(stripped_key, q.parameters()).await {
-                    Ok(stored_data) => {
+                    Ok(mut stored_data) => {
+                        self.deadlines.retain_unexpired(&key, &mut stored_data);
                         for entry in stored_data {
                             let sample = Sample::new(key.clone(), entry.value)
                                 .with_timestamp(entry.timestamp);
@@ -525,7 +923,12 @@ impl StorageService {
                             } else {
                                 sample
                             };
-                            if let Err(e) = q.reply(Ok(sample)).res().await {
+                            if let Err(e) = q
+                                .reply(Ok(sample))
+                                .priority(self.reply_priority)
+                                .res()
+                                .await
+                            {
                                 log::warn!(
                                     "Storage {} raised an error replying a query: {}",
                                     self.name,
@@ -538,6 +941,7 @@ impl StorageService {
                 };
             }
             drop(storage);
+            self.reply_deletions(&q).await;
         } else {
             let stripped_key = match self.strip_prefix(q.key_expr()) {
                 Ok(k) => k,
@@ -547,13 +951,23 @@ impl StorageService {
                     return;
                 }
             };
+            let deletions = self.reply_deletions(&q).await;
             let mut storage = self.storage.lock().await;
             match storage.get(stripped_key, q.parameters()).await {
-                Ok(stored_data) => {
+                Ok(mut stored_data) => {
+                    self.deadlines.retain_unexpired(
+                        &OwnedKeyExpr::from(q.key_expr().as_keyexpr()),
+                        &mut stored_data,
+                    );
                     // if key is not available, return Error
-                    if stored_data.is_empty() {
+                    if stored_data.is_empty() && deletions == 0 {
                         log::info!("Requested key `{}` not found", q.key_expr());
-                        if let Err(e) = q.reply(Err("Key not found".into())).res().await {
+                        if let Err(e) = q
+                            .reply(Err("Key not found".into()))
+                            .priority(self.reply_priority)
+                            .res()
+                            .await
+                        {
                             log::warn!(
                                 "Storage {} raised an error replying a query: {}",
                                 self.name,
@@ -571,7 +985,12 @@ impl StorageService {
                         } else {
                             sample
                         };
-                        if let Err(e) = q.reply(Ok(sample)).res().await {
+                        if let Err(e) = q
+                            .reply(Ok(sample))
+                            .priority(self.reply_priority)
+                            .res()
+                            .await
+                        {
                             log::warn!(
                                 "Storage {} raised an error replying a query: {}",
                                 self.name,
@@ -580,11 +999,18 @@ impl StorageService {
                         }
                     }
                 }
+                // the key was deleted, as replied
+                Err(_) if deletions > 0 => (),
                 Err(e) => {
                     let err_message =
                         format!("Storage {} raised an error on query: {}", self.name, e);
                     log::warn!("{}", err_message);
-                    if let Err(e) = q.reply(Err(err_message.into())).res().await {
+                    if let Err(e) = q
+                        .reply(Err(err_message.into()))
+                        .priority(self.reply_priority)
+                        .res()
+                        .await
+                    {
                         log::warn!(
                             "Storage {} raised an error replying a query: {}",
                             self.name,
@@ -596,6 +1022,254 @@ impl StorageService {
         }
     }
 
+    async fn reply_invalid_query(&self, q: &zenoh::queryable::Query, e: impl std::fmt::Display) {
+        let err_message = format!("Storage {} received an invalid query: {}", self.name, e);
+        log::warn!("{}", err_message);
+        if let Err(e) = q
+            .reply(Err(err_message.into()))
+            .priority(self.reply_priority)
+            .res()
+            .await
+        {
+            log::warn!(
+                "Storage {} raised an error replying a query: {}",
+                self.name,
+                e
+            )
+        }
+    }
+
+    // Replies the value each key matching a query had at `instant`: its latest value dated up to `instant`,
+    // unless it was deleted after this value and up to `instant`. The backend is asked for the values of the
+    // time range `[..<instant>]` in place of the instant, which only the storages keeping the history can reply.
+    // A replica replies with the values it received, which may lack the latest samples of the other replicas
+    // until they are aligned: the querier consolidates the replies of all the replicas by keeping the latest one.
+    async fn reply_as_of_query(
+        &self,
+        q: &zenoh::queryable::Query,
+        instant: TimeExpr,
+        predicates: &[IndexPredicate],
+    ) {
+        if self.capability.history.ne(&History::All) {
+            self.reply_invalid_query(
+                q,
+                "as-of queries require a storage keeping the history of the keys",
+            )
+            .await;
+            return;
+        }
+        let Some(instant) = instant.checked_resolve_at(SystemTime::now()) else {
+            self.reply_invalid_query(q, "the requested instant is out of range")
+                .await;
+            return;
+        };
+        let mut selector = q.selector().into_owned();
+        selector.with_time_range(TimeRange(
+            TimeBound::Unbounded,
+            TimeBound::Inclusive(TimeExpr::Fixed(instant)),
+        ));
+        let keys = if q.key_expr().is_wild() {
+            self.get_matching_keys(q.key_expr()).await
+        } else {
+            vec![OwnedKeyExpr::from(q.key_expr().as_keyexpr())]
+        };
+        let mut entries = Vec::new();
+        let mut storage = self.storage.lock().await;
+        for key in keys {
+            let stripped_key = match self.strip_prefix(&key.clone().into()) {
+                Ok(k) => k,
+                Err(e) => {
+                    log::error!("{}", e);
+                    continue;
+                }
+            };
+            match storage.get(stripped_key, selector.parameters()).await {
+                Ok(stored_data) => {
+                    // the range is checked again, in case the backend ignores it
+                    let latest = stored_data
+                        .into_iter()
+                        .filter(|data| data.timestamp.get_time().to_system_time() <= instant)
+                        .max_by_key(|data| data.timestamp);
+                    if let Some(latest) = latest {
+                        entries.push((key, latest));
+                    }
+                }
+                Err(e) => log::warn!("Storage {} raised an error on query: {}", self.name, e),
+            }
+        }
+        drop(storage);
+        let tombstones = self.tombstones.read().await;
+        for (key, data) in entries {
+            let deleted = tombstones.weight_at(&key).map_or(false, |deletion| {
+                *deletion > data.timestamp && deletion.get_time().to_system_time() <= instant
+            });
+            if deleted
+                || self.deadlines.is_expired(&key, &data.timestamp)
+                || !predicates.iter().all(|p| p.matches_value(&data.value))
+            {
+                continue;
+            }
+            let sample = Sample::new(key, data.value).with_timestamp(data.timestamp);
+            // apply outgoing interceptor on results
+            let sample = if let Some(ref interceptor) = self.out_interceptor {
+                interceptor(sample)
+            } else {
+                sample
+            };
+            if let Err(e) = q
+                .reply(Ok(sample))
+                .priority(self.reply_priority)
+                .res()
+                .await
+            {
+                log::warn!(
+                    "Storage {} raised an error replying a query: {}",
+                    self.name,
+                    e
+                )
+            }
+        }
+    }
+
+    // Replies the entries matching a query whose JSON payloads satisfy the predicates of its parameters.
+    // If all the predicates are on indexed fields, the backend is asked to resolve them through its indexes,
+    // otherwise (or if it doesn't maintain them) the matching entries are scanned.
+    async fn reply_filtered_query(
+        &self,
+        q: &zenoh::queryable::Query,
+        predicates: &[IndexPredicate],
+    ) {
+        let indexed = if predicates.iter().all(|p| self.index.contains(&p.path)) {
+            let mut storage = self.storage.lock().await;
+            match storage.get_by_index(predicates).await {
+                Ok(indexed) => indexed,
+                Err(e) => {
+                    log::warn!(
+                        "Storage {} raised an error on indexed query: {}",
+                        self.name,
+                        e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let entries = match indexed {
+            Some(indexed) => indexed
+                .into_iter()
+                .filter_map(|(key, data)| match self.get_full_key(key) {
+                    Ok(key) => q.key_expr().intersects(&key).then_some((key, data)),
+                    Err(e) => {
+                        log::warn!("Storage {}: {}", self.name, e);
+                        None
+                    }
+                })
+                .collect::<Vec<_>>(),
+            None => {
+                log::trace!(
+                    "[STORAGE] Scanning the entries matching {} for {:?}",
+                    q.key_expr(),
+                    predicates
+                );
+                let keys = if q.key_expr().is_wild() {
+                    self.get_matching_keys(q.key_expr()).await
+                } else {
+                    vec![OwnedKeyExpr::from(q.key_expr().as_keyexpr())]
+                };
+                let mut storage = self.storage.lock().await;
+                let mut entries = Vec::new();
+                for key in keys {
+                    let stripped_key = match self.strip_prefix(&key.clone().into()) {
+                        Ok(k) => k,
+                        Err(e) => {
+                            log::error!("{}", e);
+                            continue;
+                        }
+                    };
+                    if let Ok(stored_data) = storage.get(stripped_key, q.parameters()).await {
+                        entries.extend(
+                            stored_data
+                                .into_iter()
+                                .filter(|data| {
+                                    predicates.iter().all(|p| p.matches_value(&data.value))
+                                })
+                                .map(|data| (key.clone(), data)),
+                        );
+                    }
+                }
+                entries
+            }
+        };
+        for (key, data) in entries {
+            if self.deadlines.is_expired(&key, &data.timestamp) {
+                continue;
+            }
+            let sample = Sample::new(key, data.value).with_timestamp(data.timestamp);
+            // apply outgoing interceptor on results
+            let sample = if let Some(ref interceptor) = self.out_interceptor {
+                interceptor(sample)
+            } else {
+                sample
+            };
+            if let Err(e) = q
+                .reply(Ok(sample))
+                .priority(self.reply_priority)
+                .res()
+                .await
+            {
+                log::warn!(
+                    "Storage {} raised an error replying a query: {}",
+                    self.name,
+                    e
+                )
+            }
+        }
+    }
+
+    // Replies the deletions dated within the time range of a history query as samples of kind Delete,
+    // so that the querier can reconstruct the state of the keys. Returns the number of deletions replied.
+    // Queries without a time range only get the stored values.
+    async fn reply_deletions(&self, q: &zenoh::queryable::Query) -> usize {
+        let time_range = match q.selector().time_range() {
+            Ok(Some(time_range)) => time_range,
+            _ => return 0,
+        };
+        let mut count = 0;
+        let tombstones = self.tombstones.read().await;
+        for key in tombstones.intersecting_keys(q.key_expr()) {
+            let timestamp = match tombstones.weight_at(&key) {
+                Some(timestamp) => *timestamp,
+                None => continue,
+            };
+            if !time_range.contains(timestamp.get_time().to_system_time()) {
+                continue;
+            }
+            let mut sample = Sample::new(key, Value::empty()).with_timestamp(timestamp);
+            sample.kind = SampleKind::Delete;
+            // apply outgoing interceptor on results
+            let sample = if let Some(ref interceptor) = self.out_interceptor {
+                interceptor(sample)
+            } else {
+                sample
+            };
+            match q
+                .reply(Ok(sample))
+                .priority(self.reply_priority)
+                .res()
+                .await
+            {
+                Ok(()) => count += 1,
+                Err(e) => log::warn!(
+                    "Storage {} raised an error replying a query: {}",
+                    self.name,
+                    e
+                ),
+            }
+        }
+        count
+    }
+
     async fn get_matching_keys(&self, key_expr: &KeyExpr<'_>) -> Vec<OwnedKeyExpr> {
         let mut result = Vec::new();
         // @TODO: if cache exists, use that to get the list
@@ -603,10 +1277,12 @@ impl StorageService {
         match storage.get_all_entries().await {
             Ok(entries) => {
                 for (k, _ts) in entries {
-                    // @TODO: optimize adding back the prefix (possible inspiration from https://github.com/eclipse-zenoh/zenoh/blob/0.5.0-beta.9/backends/traits/src/utils.rs#L79)
-                    let full_key = match k {
-                        Some(key) => StorageService::get_prefixed(&self.strip_prefix, &key.into()),
-                        None => self.strip_prefix.clone().unwrap(),
+                    let full_key = match self.get_full_key(k) {
+                        Ok(full_key) => full_key,
+                        Err(e) => {
+                            log::warn!("Storage {}: {}", self.name, e);
+                            continue;
+                        }
                     };
                     if key_expr.intersects(&full_key.clone()) {
                         result.push(full_key);
@@ -622,7 +1298,20 @@ impl StorageService {
         result
     }
 
+    // Translates a key of the backend into the zenoh key
+    fn get_full_key(&self, key: Option<OwnedKeyExpr>) -> ZResult<OwnedKeyExpr> {
+        // @TODO: optimize adding back the prefix (possible inspiration from https://github.com/eclipse-zenoh/zenoh/blob/0.5.0-beta.9/backends/traits/src/utils.rs#L79)
+        Ok(match (key, &self.key_mapping) {
+            (Some(key), Some(key_mapping)) => key_mapping.to_zenoh(&key)?,
+            (Some(key), None) => StorageService::get_prefixed(&self.strip_prefix, &key.into()),
+            (None, _) => self.strip_prefix.clone().unwrap(),
+        })
+    }
+
     fn strip_prefix(&self, key_expr: &KeyExpr<'_>) -> ZResult<Option<OwnedKeyExpr>> {
+        if let Some(key_mapping) = &self.key_mapping {
+            return Ok(Some(key_mapping.to_native(key_expr)?));
+        }
         let key = match &self.strip_prefix {
             Some(prefix) => {
                 if key_expr.as_str().eq(prefix.as_str()) {
@@ -691,6 +1380,23 @@ impl StorageService {
     }
 }
 
+// Receives the next update from the other sites, if the storage is federated
+async fn recv_federated_update(
+    federation: &Option<Federation>,
+) -> Result<Sample, flume::RecvError> {
+    match federation {
+        Some(federation) => federation.updates.recv_async().await,
+        None => futures::future::pending().await,
+    }
+}
+
+async fn recv_flushed_sample(aggregate: &Option<Aggregate>) -> Result<Sample, flume::RecvError> {
+    match aggregate {
+        Some(aggregate) => aggregate.flushed.recv_async().await,
+        None => futures::future::pending().await,
+    }
+}
+
 fn serialize_update(update: &Update) -> String {
     let result = (
         update.kind.to_string(),
@@ -731,8 +1437,7 @@ struct GarbageCollectionEvent {
 impl Timed for GarbageCollectionEvent {
     async fn run(&mut self) {
         log::trace!("Start garbage collection");
-        let time_limit = NTP64::from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap())
-            - NTP64::from(self.config.lifespan);
+        let time_limit = zenoh::time::clock() - NTP64::from(self.config.lifespan);
 
         // Get lock on fields
         let mut tombstones = self.tombstones.write().await;
@@ -12,8 +12,12 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use async_std::sync::Arc;
+use futures::FutureExt;
+use serde_json::json;
+use std::panic::AssertUnwindSafe;
 use zenoh::Session;
 use zenoh_backend_traits::config::StorageConfig;
+use zenoh_plugin_trait::panic_message;
 use zenoh_result::ZResult;
 
 pub use super::replica::{Replica, StorageService};
@@ -40,12 +44,45 @@ pub(crate) async fn start_storage(
     let (tx, rx) = flume::bounded(1);
 
     async_std::task::spawn(async move {
-        // If a configuration for replica is present, we initialize a replica, else only a storage service
-        // A replica contains a storage service and all metadata required for anti-entropy
-        if config.replica_config.is_some() {
-            Replica::start(zenoh.clone(), store_intercept, config, &name, rx).await;
-        } else {
-            StorageService::start(zenoh.clone(), config, &name, store_intercept, rx, None).await;
+        let status_rx = rx.clone();
+        let storage = async {
+            // If a configuration for replica is present, we initialize a replica, else only a storage service
+            // A replica contains a storage service and all metadata required for anti-entropy
+            if config.replica_config.is_some() {
+                Replica::start(
+                    zenoh.clone(),
+                    store_intercept,
+                    config,
+                    &name,
+                    &admin_key,
+                    rx,
+                )
+                .await;
+            } else {
+                StorageService::start(
+                    zenoh.clone(),
+                    config,
+                    &name,
+                    &admin_key,
+                    store_intercept,
+                    rx,
+                    None,
+                )
+                .await;
+            }
+        };
+        if let Err(e) = AssertUnwindSafe(storage).catch_unwind().await {
+            let error = format!("Storage panicked: {}", panic_message(&*e));
+            log::error!("{} ({})", error, name);
+            // Keep reporting the failure in the adminspace until the storage is removed
+            while let Ok(message) = status_rx.recv_async().await {
+                match message {
+                    StorageMessage::Stop => break,
+                    StorageMessage::GetStatus(tx) => {
+                        let _ = tx.send(json!({ "__error__": error })).await;
+                    }
+                }
+            }
         }
     });
 
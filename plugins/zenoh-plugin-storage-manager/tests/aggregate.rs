@@ -0,0 +1,96 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the downsampled mirrors of storages -
+// 1. the mirror stores one sample in n of the source, under its own key expression
+// 2. the source storage stores all the samples
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::Session;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_value(session: &Session, key_expr: &str) -> Option<String> {
+    let replies = session.get(key_expr).res().await.unwrap();
+    let reply = replies.recv_async().await.ok()?;
+    Some(reply.sample.unwrap().value.to_string())
+}
+
+async fn test_aggregate() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5("timestamping", r#"{ enabled: true }"#)
+        .unwrap();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        full: {
+                            key_expr: "aggregate/full/**",
+                            volume: "memory"
+                        },
+                        history: {
+                            key_expr: "aggregate/history/**",
+                            volume: "memory",
+                            aggregate: {
+                                source: "aggregate/full/**",
+                                strategy: "1-in-3"
+                            }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    for (i, expected) in [(0, "0"), (1, "0"), (2, "0"), (3, "3"), (4, "3")] {
+        session
+            .put("aggregate/full/a", i.to_string())
+            .res()
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(100));
+        assert_eq!(
+            get_value(&session, "aggregate/full/a").await.as_deref(),
+            Some(i.to_string().as_str())
+        );
+        assert_eq!(
+            get_value(&session, "aggregate/history/a").await.as_deref(),
+            Some(expected)
+        );
+    }
+
+    drop(storage);
+}
+
+#[test]
+fn aggregate_test() {
+    task::block_on(async { test_aggregate().await });
+}
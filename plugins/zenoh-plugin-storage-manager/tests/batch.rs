@@ -0,0 +1,98 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the storage of atomic batches -
+// 1. the values of a batch matching the storage are all stored, with the same timestamp
+// 2. the values of a batch not matching the storage are ignored
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn test_batch() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5("timestamping", r#"{ enabled: true }"#)
+        .unwrap();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        demo: {
+                            key_expr: "batch/test/**",
+                            volume: "memory"
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    session
+        .put_batch([
+            ("batch/test/a", "1"),
+            ("batch/test/b", "2"),
+            ("batch/other/c", "3"),
+        ])
+        .res()
+        .await
+        .unwrap();
+    sleep(Duration::from_millis(100));
+
+    let replies = session
+        .get("batch/**")
+        .res()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|reply| reply.sample.unwrap())
+        .collect::<Vec<_>>();
+    let mut stored = replies
+        .iter()
+        .map(|sample| (sample.key_expr.to_string(), sample.value.to_string()))
+        .collect::<Vec<_>>();
+    stored.sort();
+    assert_eq!(
+        stored,
+        vec![
+            ("batch/test/a".to_string(), "1".to_string()),
+            ("batch/test/b".to_string(), "2".to_string()),
+        ]
+    );
+    assert!(replies[0].timestamp.is_some());
+    assert_eq!(replies[0].timestamp, replies[1].timestamp);
+
+    drop(storage);
+}
+
+#[test]
+fn batch_test() {
+    task::block_on(async { test_batch().await });
+}
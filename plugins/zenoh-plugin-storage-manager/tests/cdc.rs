@@ -0,0 +1,110 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the change notifications of storages -
+// 1. a notification is published for each sample persisted or deleted, without its payload
+// 2. no notification is published by the storages not configured for it
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn test_cdc() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5("timestamping", r#"{ enabled: true }"#)
+        .unwrap();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        demo: {
+                            key_expr: "cdc/test/**",
+                            volume: "memory",
+                            cdc: true
+                        },
+                        quiet: {
+                            key_expr: "cdc/quiet/**",
+                            volume: "memory"
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+    let changes = session
+        .declare_subscriber(format!(
+            "@/router/{}/status/plugins/storage-manager/storages/*/cdc",
+            session.zid()
+        ))
+        .res()
+        .await
+        .unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    session.put("cdc/test/a", "1").res().await.unwrap();
+    session.put("cdc/quiet/a", "1").res().await.unwrap();
+    sleep(Duration::from_millis(100));
+    let stored = session
+        .get("cdc/test/a")
+        .res()
+        .await
+        .unwrap()
+        .recv_async()
+        .await
+        .unwrap()
+        .sample
+        .unwrap();
+    session.delete("cdc/test/a").res().await.unwrap();
+    sleep(Duration::from_millis(100));
+
+    let mut notifications = Vec::new();
+    while let Ok(sample) = changes.try_recv() {
+        assert!(sample.key_expr.as_str().ends_with("/storages/demo/cdc"));
+        let change: serde_json::Value = serde_json::from_str(&sample.value.to_string()).unwrap();
+        notifications.push(change);
+    }
+    assert_eq!(notifications.len(), 2);
+    assert_eq!(notifications[0]["key"], "cdc/test/a");
+    assert_eq!(notifications[0]["kind"], "PUT");
+    assert_eq!(
+        notifications[0]["timestamp"],
+        stored.timestamp.unwrap().to_string()
+    );
+    assert!(notifications[0].get("value").is_none());
+    assert_eq!(notifications[1]["key"], "cdc/test/a");
+    assert_eq!(notifications[1]["kind"], "DELETE");
+
+    drop(storage);
+}
+
+#[test]
+fn cdc_test() {
+    task::block_on(async { test_cdc().await });
+}
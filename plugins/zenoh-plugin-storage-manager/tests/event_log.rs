@@ -0,0 +1,130 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the event log of storages -
+// 1. the queries with `_offset` and `_limit` are replied with the recorded samples of the range, offsets included
+// 2. the queries without them are still replied with the stored values
+// 3. the replay requests are acknowledged with the range to be republished
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::Reply;
+use zenoh::Session;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_all(session: &Session, selector: &str) -> Vec<Sample> {
+    let replies = session
+        .get(selector)
+        .consolidation(ConsolidationMode::None)
+        .res()
+        .await
+        .unwrap();
+    let mut samples = Vec::new();
+    while let Ok(Reply { sample, .. }) = replies.recv_async().await {
+        // the errors are not collected
+        if let Ok(sample) = sample {
+            samples.push(sample);
+        }
+    }
+    samples
+}
+
+async fn test_event_log() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5("timestamping", r#"{ enabled: true }"#)
+        .unwrap();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        demo: {
+                            key_expr: "log/test/**",
+                            volume: "memory",
+                            event_log: { replay_rate: 10 }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    session.put("log/test/a", "1").res().await.unwrap();
+    sleep(Duration::from_millis(10));
+    session.put("log/test/a", "2").res().await.unwrap();
+    sleep(Duration::from_millis(10));
+    session.put("log/test/b", "1").res().await.unwrap();
+    sleep(Duration::from_millis(10));
+    session.delete("log/test/a").res().await.unwrap();
+    sleep(Duration::from_millis(100));
+
+    let samples = get_all(&session, "log/test/**?_offset=1&_limit=2").await;
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0].key_expr.as_str(), "log/test/a");
+    assert_eq!(samples[0].value.to_string(), "2");
+    assert_eq!(samples[0].source_info.source_sn, Some(1));
+    assert_eq!(samples[1].key_expr.as_str(), "log/test/b");
+    assert_eq!(samples[1].source_info.source_sn, Some(2));
+
+    let samples = get_all(&session, "log/test/a?_offset=0").await;
+    assert_eq!(samples.len(), 3);
+    assert_eq!(samples[0].value.to_string(), "1");
+    assert_eq!(samples[1].value.to_string(), "2");
+    assert_eq!(samples[2].kind, SampleKind::Delete);
+    assert_eq!(samples[2].source_info.source_sn, Some(3));
+
+    let samples = get_all(&session, "log/test/**").await;
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].key_expr.as_str(), "log/test/b");
+
+    let samples = get_all(&session, "log/test/**?_offset=first").await;
+    assert!(samples.is_empty());
+
+    let replay = get_all(
+        &session,
+        &format!(
+            "@/router/{}/status/plugins/storage-manager/storages/demo/replay?_offset=2",
+            session.zid()
+        ),
+    )
+    .await;
+    assert_eq!(replay.len(), 1);
+    let replay: serde_json::Value = serde_json::from_str(&replay[0].value.to_string()).unwrap();
+    assert_eq!(replay["offset"], 2);
+    assert_eq!(replay["count"], 2);
+    assert_eq!(replay["rate"], 10);
+
+    drop(storage);
+}
+
+#[test]
+fn event_log_test() {
+    task::block_on(async { test_event_log().await });
+}
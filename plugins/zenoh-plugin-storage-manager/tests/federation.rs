@@ -0,0 +1,168 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test federated storages -
+// 1. the updates applied to a site's storage are propagated to the other sites
+// 2. the updates received from another site are applied only if they are the latest ones
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_std::task;
+use serde_json::json;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh::query::ConsolidationMode;
+use zenoh::time::Timestamp;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_values(session: &zenoh::Session, selector: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let replies = session
+        .get(selector)
+        .consolidation(ConsolidationMode::None)
+        .res()
+        .await
+        .unwrap();
+    for reply in replies.into_iter() {
+        if let Ok(sample) = reply.sample {
+            values.push(sample.value.to_string());
+        }
+    }
+    values
+}
+
+async fn put_from_london(session: &zenoh::Session, kind: SampleKind, value: &str, ts: Timestamp) {
+    let batch = json!([{
+        "key": "federation/test/a",
+        "kind": kind as u64,
+        "timestamp": ts.to_string(),
+        "encoding": Encoding::TEXT_PLAIN.to_string(),
+        "payload": value.as_bytes(),
+    }]);
+    let key = format!(
+        "@-federation/{}/london",
+        urlencoding::encode("federation/test/**")
+    );
+    session.put(key, batch.to_string()).res().await.unwrap();
+}
+
+async fn test_federation() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5("timestamping", r#"{ enabled: true }"#)
+        .unwrap();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        paris: {
+                            key_expr: "federation/test/**",
+                            volume: "memory",
+                            federation: { site: "paris", propagation_interval: 100 }
+                        },
+                        tokyo: {
+                            key_expr: "federation/test/**",
+                            volume: "memory",
+                            federation: { site: "tokyo", propagation_interval: 100 }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+    let propagations = session
+        .declare_subscriber("@-federation/**")
+        .res()
+        .await
+        .unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    let outdated = zenoh::time::new_reception_timestamp();
+    session.put("federation/test/a", "1").res().await.unwrap();
+
+    sleep(Duration::from_millis(500));
+
+    // The local update is stored and propagated by both sites
+    assert_eq!(
+        get_values(&session, "federation/test/a").await,
+        vec!["1", "1"]
+    );
+    let mut sites = Vec::new();
+    while let Ok(sample) = propagations.try_recv() {
+        assert!(sample.value.to_string().contains("federation/test/a"));
+        sites.push(
+            sample
+                .key_expr
+                .as_str()
+                .rsplit('/')
+                .next()
+                .unwrap()
+                .to_string(),
+        );
+    }
+    sites.sort();
+    sites.dedup();
+    assert_eq!(sites, vec!["paris", "tokyo"]);
+
+    // An outdated update from another site is discarded
+    put_from_london(&session, SampleKind::Put, "0", outdated).await;
+    sleep(Duration::from_millis(500));
+    assert_eq!(
+        get_values(&session, "federation/test/a").await,
+        vec!["1", "1"]
+    );
+
+    // The latest update from another site wins
+    put_from_london(
+        &session,
+        SampleKind::Put,
+        "2",
+        zenoh::time::new_reception_timestamp(),
+    )
+    .await;
+    sleep(Duration::from_millis(500));
+    assert_eq!(
+        get_values(&session, "federation/test/a").await,
+        vec!["2", "2"]
+    );
+
+    put_from_london(
+        &session,
+        SampleKind::Delete,
+        "",
+        zenoh::time::new_reception_timestamp(),
+    )
+    .await;
+    sleep(Duration::from_millis(500));
+    assert!(get_values(&session, "federation/test/a").await.is_empty());
+
+    drop(storage);
+}
+
+#[test]
+fn federation_test() {
+    task::block_on(async { test_federation().await });
+}
@@ -0,0 +1,134 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the key mapping of storages -
+// 1. zenoh keys are translated into native keys and back
+// 2. a storage with a key mapping replies to queries on the zenoh keys
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh_backend_traits::config::KeyMapping;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_samples(session: &zenoh::Session, selector: &str) -> Vec<(String, String)> {
+    let mut samples = Vec::new();
+    for reply in session.get(selector).res().await.unwrap().into_iter() {
+        if let Ok(sample) = reply.sample {
+            samples.push((sample.key_expr.to_string(), sample.value.to_string()));
+        }
+    }
+    samples.sort();
+    samples
+}
+
+async fn test_key_mapping() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let mapping = KeyMapping::new("demo/{site}/sensors/{id}/temp", "{site}.sensor-{id}").unwrap();
+    let native = mapping
+        .to_native(keyexpr::new("demo/paris/sensors/42/temp").unwrap())
+        .unwrap();
+    assert_eq!(native.as_str(), "paris.sensor-42");
+    let zenoh = mapping.to_zenoh(&native).unwrap();
+    assert_eq!(zenoh.as_str(), "demo/paris/sensors/42/temp");
+    assert_eq!(
+        mapping.key_expr().unwrap().as_str(),
+        "demo/*/sensors/*/temp"
+    );
+    assert!(mapping
+        .to_native(keyexpr::new("demo/paris/sensors/42/hum").unwrap())
+        .is_err());
+    assert!(mapping
+        .to_zenoh(keyexpr::new("paris-sensor-42").unwrap())
+        .is_err());
+    assert!(KeyMapping::new("demo/{id}/**", "{id}").is_err());
+    assert!(KeyMapping::new("demo/{site}/{id}", "{site}{id}").is_err());
+    assert!(KeyMapping::new("demo/{site}/{id}", "{site}").is_err());
+
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        demo: {
+                            key_expr: "mapping/sensors/*/temp",
+                            key_mapping: { zenoh: "mapping/sensors/{id}/temp", native: "{id}" },
+                            volume: "memory"
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    session
+        .put("mapping/sensors/1/temp", "21")
+        .res()
+        .await
+        .unwrap();
+    session
+        .put("mapping/sensors/2/temp", "23")
+        .res()
+        .await
+        .unwrap();
+
+    sleep(Duration::from_millis(100));
+
+    assert_eq!(
+        get_samples(&session, "mapping/sensors/1/temp").await,
+        vec![("mapping/sensors/1/temp".to_string(), "21".to_string())]
+    );
+    assert_eq!(
+        get_samples(&session, "mapping/sensors/*/temp").await,
+        vec![
+            ("mapping/sensors/1/temp".to_string(), "21".to_string()),
+            ("mapping/sensors/2/temp".to_string(), "23".to_string())
+        ]
+    );
+
+    session
+        .delete("mapping/sensors/1/temp")
+        .res()
+        .await
+        .unwrap();
+
+    sleep(Duration::from_millis(100));
+
+    assert_eq!(
+        get_samples(&session, "mapping/sensors/*/temp").await,
+        vec![("mapping/sensors/2/temp".to_string(), "23".to_string())]
+    );
+
+    drop(storage);
+}
+
+#[test]
+fn key_mapping_test() {
+    task::block_on(async { test_key_mapping().await });
+}
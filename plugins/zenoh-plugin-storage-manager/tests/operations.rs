@@ -15,6 +15,7 @@
 // Test wild card updates -
 // 1. normal case, just some wild card puts and deletes on existing keys and ensure it works
 // 2. check for dealing with out of order updates
+// 3. history queries get the deletions
 
 use std::str::FromStr;
 use std::thread::sleep;
@@ -138,6 +139,19 @@ async fn test_updates_in_order() {
     assert_eq!(format!("{}", data[0].value), "2");
     assert_eq!(data[0].key_expr.as_str(), "operation/test/b");
 
+    // a history query also gets the deletions, as samples of kind Delete
+    let data = get_data(&session, "operation/test/a?_time=[..]").await;
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].kind, SampleKind::Delete);
+    let data = get_data(&session, "operation/test/*?_time=[..]").await;
+    assert_eq!(data.len(), 2);
+    assert!(data
+        .iter()
+        .any(|s| s.key_expr.as_str() == "operation/test/a" && s.kind == SampleKind::Delete));
+    assert!(data
+        .iter()
+        .any(|s| s.key_expr.as_str() == "operation/test/b" && s.kind == SampleKind::Put));
+
     drop(storage);
 }
 
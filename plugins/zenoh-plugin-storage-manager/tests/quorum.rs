@@ -0,0 +1,110 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test quorum-acknowledged writes -
+// 1. a publication persisted by as many replicas as the write quorum is acknowledged
+// 2. a publication persisted by fewer replicas is not
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_std::task;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+async fn get_timestamps(session: &zenoh::Session, selector: &str) -> Vec<String> {
+    let mut timestamps = Vec::new();
+    for reply in session.get(selector).res().await.unwrap().into_iter() {
+        if let Ok(sample) = reply.sample {
+            match sample.key_expr.as_str().contains("/acks/") {
+                true => timestamps.push(sample.value.to_string()),
+                false => timestamps.push(sample.timestamp.unwrap().to_string()),
+            }
+        }
+    }
+    timestamps
+}
+
+async fn test_write_quorum() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let mut config = Config::default();
+    config
+        .insert_json5("timestamping", r#"{ enabled: true }"#)
+        .unwrap();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            r#"{
+                    storages: {
+                        replica1: {
+                            key_expr: "quorum/test/**",
+                            volume: "memory",
+                            replication: { factor: 2, write_quorum: 2 }
+                        },
+                        replica2: {
+                            key_expr: "quorum/test/**",
+                            volume: "memory",
+                            replication: { factor: 2, write_quorum: 2 }
+                        },
+                        lonely: {
+                            key_expr: "quorum/lonely/**",
+                            volume: "memory",
+                            replication: { factor: 3 }
+                        }
+                    }
+                }"#,
+        )
+        .unwrap();
+
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+    let admin = format!(
+        "@/router/{}/status/plugins/storage-manager/storages",
+        session.zid()
+    );
+
+    sleep(Duration::from_secs(1));
+
+    session.put("quorum/test/a", "1").res().await.unwrap();
+    session.put("quorum/lonely/a", "1").res().await.unwrap();
+
+    sleep(Duration::from_millis(100));
+
+    // The publication persisted by both replicas is acknowledged with its timestamp
+    let stored = get_timestamps(&session, "quorum/test/a").await;
+    assert!(!stored.is_empty());
+    let acks = get_timestamps(&session, &format!("{admin}/replica1/acks/quorum/test/a")).await;
+    assert_eq!(acks, vec![stored[0].clone()]);
+    let acks = get_timestamps(&session, &format!("{admin}/replica2/acks/**")).await;
+    assert_eq!(acks, vec![stored[0].clone()]);
+
+    // The publication persisted by a single replica out of 3 isn't
+    assert_eq!(get_timestamps(&session, "quorum/lonely/a").await.len(), 1);
+    let acks = get_timestamps(&session, &format!("{admin}/lonely/acks/**")).await;
+    assert!(acks.is_empty());
+
+    drop(storage);
+}
+
+#[test]
+fn write_quorum_test() {
+    task::block_on(async { test_write_quorum().await });
+}
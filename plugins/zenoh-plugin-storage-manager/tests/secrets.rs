@@ -0,0 +1,123 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the secrets referenced in the volume options -
+// 1. they are resolved from files, environment variables and commands
+// 2. their values can be redacted back into references
+// 3. a storage referencing an unknown secret isn't started
+
+use std::collections::HashMap;
+use std::thread::sleep;
+use std::time::Duration;
+
+use async_std::task;
+use serde_json::json;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::Config;
+use zenoh_backend_traits::config::{redact_secrets, resolve_secrets, SecretProvider};
+use zenoh_core::zasync_executor_init;
+use zenoh_plugin_trait::Plugin;
+
+fn storage_manager_config(volume: &str) -> Config {
+    let mut config = Config::default();
+    config
+        .insert_json5(
+            "plugins/storage-manager",
+            &format!(
+                r#"{{
+                    secrets: {{
+                        db_password: {{ env: "ZENOH_TEST_DB_PASSWORD" }},
+                        db_token: {{ exec: ["echo", "t0k3n"] }},
+                    }},
+                    storages: {{
+                        demo: {{
+                            key_expr: "secrets/test/**",
+                            volume: {volume},
+                        }}
+                    }}
+                }}"#
+            ),
+        )
+        .unwrap();
+    config
+}
+
+async fn test_secrets() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    std::env::set_var("ZENOH_TEST_DB_PASSWORD", "p4ssw0rd");
+    let path = std::env::temp_dir().join("zenoh_test_db_user");
+    std::fs::write(&path, "admin\n").unwrap();
+
+    let secrets = HashMap::from([
+        (
+            "user".to_string(),
+            SecretProvider::File(path.to_string_lossy().into_owned()),
+        ),
+        (
+            "password".to_string(),
+            SecretProvider::Env("ZENOH_TEST_DB_PASSWORD".into()),
+        ),
+        (
+            "token".to_string(),
+            SecretProvider::Exec(vec!["echo".into(), "t0k3n".into()]),
+        ),
+    ]);
+    let mut options = json!({ "user": "secret://user", "auth": ["secret://password", "secret://token"], "db": "demo" });
+    let resolved = resolve_secrets(&mut options, &secrets).unwrap();
+    assert_eq!(
+        options,
+        json!({ "user": "admin", "auth": ["p4ssw0rd", "t0k3n"], "db": "demo" })
+    );
+    assert_eq!(resolved.len(), 3);
+    redact_secrets(&mut options, &resolved);
+    assert_eq!(
+        options,
+        json!({ "user": "secret://user", "auth": ["secret://password", "secret://token"], "db": "demo" })
+    );
+    let mut options = json!({ "password": "secret://unknown" });
+    assert!(resolve_secrets(&mut options, &secrets).is_err());
+    std::fs::remove_file(path).unwrap();
+
+    let config = storage_manager_config(
+        r#"{ id: "memory", password: "secret://db_password", token: "secret://db_token" }"#,
+    );
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    let storage =
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).unwrap();
+
+    let session = zenoh::init(runtime).res().await.unwrap();
+
+    sleep(Duration::from_secs(1));
+
+    session.put("secrets/test/a", "1").res().await.unwrap();
+    sleep(Duration::from_millis(100));
+    let replies = session.get("secrets/test/a").res().await.unwrap();
+    let value = replies.recv_async().await.unwrap().sample.unwrap().value;
+    assert_eq!(value.to_string(), "1");
+    drop(storage);
+    session.close().res().await.unwrap();
+
+    let config = storage_manager_config(r#"{ id: "memory", password: "secret://unknown" }"#);
+    let runtime = zenoh::runtime::Runtime::new(config).await.unwrap();
+    assert!(
+        zenoh_plugin_storage_manager::StoragesPlugin::start("storage-manager", &runtime).is_err()
+    );
+}
+
+#[test]
+fn secrets_test() {
+    task::block_on(async { test_secrets().await });
+}
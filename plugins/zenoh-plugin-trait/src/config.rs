@@ -0,0 +1,79 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Settings shared by the configurations of several plugins.
+use serde::de::{self, Unexpected, Visitor};
+use serde::Deserializer;
+use std::fmt;
+
+/// The interface an HTTP server listens on when its configuration only gives a port.
+pub const DEFAULT_HTTP_INTERFACE: &str = "[::]";
+
+/// Deserializes the address an HTTP server listens on, given either as a port number (as an integer or a string),
+/// or as a string with format `"<local_ip>:<port_number>"`, into the latter.
+///
+/// To be used with `#[serde(deserialize_with = "zenoh_plugin_trait::config::deserialize_http_port")]`.
+pub fn deserialize_http_port<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(HttpPortVisitor)
+}
+
+struct HttpPortVisitor;
+
+impl<'de> Visitor<'de> for HttpPortVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(r#"either a port number as an integer or a string, either a string with format "<local_ip>:<port_number>""#)
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(format!("{DEFAULT_HTTP_INTERFACE}:{value}"))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let parts: Vec<&str> = value.split(':').collect();
+        if parts.len() > 2 {
+            return Err(E::invalid_value(Unexpected::Str(value), &self));
+        }
+        let (interface, port) = if parts.len() == 1 {
+            (DEFAULT_HTTP_INTERFACE, parts[0])
+        } else {
+            (parts[0], parts[1])
+        };
+        if port.parse::<u32>().is_err() {
+            return Err(E::invalid_value(Unexpected::Str(port), &self));
+        }
+        Ok(format!("{interface}:{port}"))
+    }
+}
+
+#[test]
+fn http_port() {
+    let parse = |value: serde_json::Value| deserialize_http_port(value).map_err(|e| e.to_string());
+    assert_eq!(parse(8000.into()).unwrap(), "[::]:8000");
+    assert_eq!(parse("8000".into()).unwrap(), "[::]:8000");
+    assert_eq!(parse("127.0.0.1:8000".into()).unwrap(), "127.0.0.1:8000");
+    assert!(parse("127.0.0.1:http".into()).is_err());
+    assert!(parse("a:b:8000".into()).is_err());
+    assert!(parse(true.into()).is_err());
+}
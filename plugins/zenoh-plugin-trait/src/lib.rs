@@ -18,49 +18,172 @@
 //!
 //! If building a plugin for [`zenohd`](https://crates.io/crates/zenoh), you should use the types exported in [`zenoh::plugins`](https://docs.rs/zenoh/latest/zenoh/plugins) to fill [`Plugin`]'s associated types.  
 //! To check your plugin typing for `zenohd`, have your plugin implement [`zenoh::plugins::ZenohPlugin`](https://docs.rs/zenoh/latest/zenoh/plugins/struct.ZenohPlugin)
+pub mod config;
 pub mod loading;
 pub mod vtable;
 
-use zenoh_result::ZResult;
+use libloading::Library;
+use zenoh_result::{bail, ZResult};
 
+/// Build information embedded in plugin and backend libraries as a `#[no_mangle]` static.
+///
+/// Rust doesn't guarantee a stable ABI: calling into a library built with another compiler, or against other versions
+/// of the types it exchanges with its host, is undefined behaviour. Loaders thus read this record with
+/// [`Compatibility::check`] before calling into a library, turning such mismatches into load-time errors.
+///
+/// The definitions of the exchanged types are identified by the version of this crate, released along with `zenoh`
+/// and `zenoh-backend-traits`: a library has to be built against the same release as its host, with the same
+/// features. The hash of the types' sizes only catches some of the builds that don't, e.g. with other features, and
+/// doesn't vouch for their layouts.
+///
+/// Its layout must never change, so that it can be read from libraries built with any version.
 #[repr(C)]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Compatibility {
-    major: u64,
-    minor: u64,
-    patch: u64,
-    stable: bool,
-    commit: &'static str,
+    rustc_release: [u8; 32],
+    rustc_commit: [u8; 64],
+    plugin_trait_version: [u8; 32],
+    sizes_hash: u64,
 }
 const RELEASE_AND_COMMIT: (&str, &str) = zenoh_macros::rustc_version_release!();
+const PLUGIN_TRAIT_VERSION: &str = env!("CARGO_PKG_VERSION");
 impl Compatibility {
-    pub fn new() -> ZResult<Self> {
+    /// Returns the compatibility record of a library exchanging `Args` and `Output` with its host, built with the
+    /// current compiler and version of this crate.
+    pub const fn new<Args, Output>() -> Self {
         let (release, commit) = RELEASE_AND_COMMIT;
-        let (release, stable) = if let Some(p) = release.chars().position(|c| c == '-') {
-            (&release[..p], false)
+        Compatibility {
+            rustc_release: str_to_array(release),
+            rustc_commit: str_to_array(commit),
+            plugin_trait_version: str_to_array(PLUGIN_TRAIT_VERSION),
+            sizes_hash: sizes_hash(&[
+                std::mem::size_of::<Args>(),
+                std::mem::align_of::<Args>(),
+                std::mem::size_of::<Output>(),
+                std::mem::align_of::<Output>(),
+                std::mem::size_of::<ZResult<Output>>(),
+                std::mem::align_of::<ZResult<Output>>(),
+                vtable::PLUGIN_VTABLE_VERSION as usize,
+            ]),
+        }
+    }
+
+    pub fn rustc_release(&self) -> &str {
+        array_to_str(&self.rustc_release)
+    }
+    pub fn rustc_commit(&self) -> &str {
+        array_to_str(&self.rustc_commit)
+    }
+    pub fn plugin_trait_version(&self) -> &str {
+        array_to_str(&self.plugin_trait_version)
+    }
+    pub fn sizes_hash(&self) -> u64 {
+        self.sizes_hash
+    }
+
+    /// Stable releases of rustc are identified by their version, others by their commit.
+    pub fn are_compatible(a: &Self, b: &Self) -> bool {
+        let stable = |c: &Self| !c.rustc_release().contains('-');
+        let same_rustc = if stable(a) && stable(b) {
+            a.rustc_release == b.rustc_release
         } else {
-            (release, true)
+            a.rustc_release == b.rustc_release && a.rustc_commit == b.rustc_commit
         };
-        let mut split = release.split('.').map(|s| s.trim());
-        Ok(Compatibility {
-            major: split.next().unwrap().parse().unwrap(),
-            minor: split.next().unwrap().parse().unwrap(),
-            patch: split.next().unwrap().parse().unwrap(),
-            stable,
-            commit,
-        })
+        same_rustc
+            && a.plugin_trait_version == b.plugin_trait_version
+            && a.sizes_hash == b.sizes_hash
     }
-    pub fn are_compatible(a: &Self, b: &Self) -> bool {
-        if a.stable && b.stable {
-            a.major == b.major && a.minor == b.minor && a.patch == b.patch
+
+    /// Reads the compatibility record exported by `lib` as `symbol`, and checks that it is compatible with `expected`.
+    ///
+    /// # Safety
+    /// If present, `symbol` must designate a [`Compatibility`] static.
+    pub unsafe fn check(lib: &Library, symbol: &[u8], expected: &Self) -> ZResult<()> {
+        let record = match lib.get::<*const Compatibility>(symbol) {
+            Ok(record) => **record,
+            Err(_) => bail!(
+                "no compatibility record (`{}`) found, it was likely built with an older version of Zenoh",
+                String::from_utf8_lossy(symbol)
+            ),
+        };
+        if Self::are_compatible(expected, &record) {
+            Ok(())
         } else {
-            a == b
+            bail!(
+                "compatibility mismatch: host: {} - library: {}",
+                expected,
+                record
+            )
         }
     }
 }
+impl std::fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rustc {} ({}), zenoh-plugin-trait {}, sizes {:016x}",
+            self.rustc_release(),
+            self.rustc_commit(),
+            self.plugin_trait_version(),
+            self.sizes_hash
+        )
+    }
+}
+impl std::fmt::Debug for Compatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Compatibility")
+            .field("rustc_release", &self.rustc_release())
+            .field("rustc_commit", &self.rustc_commit())
+            .field("plugin_trait_version", &self.plugin_trait_version())
+            .field("sizes_hash", &self.sizes_hash)
+            .finish()
+    }
+}
+
+/// Copies `s` in a nul-padded array, truncating it if needed.
+const fn str_to_array<const N: usize>(s: &str) -> [u8; N] {
+    let bytes = s.as_bytes();
+    let mut array = [0; N];
+    let mut i = 0;
+    while i < bytes.len() && i < N {
+        array[i] = bytes[i];
+        i += 1;
+    }
+    array
+}
+fn array_to_str(array: &[u8]) -> &str {
+    let len = array.iter().position(|b| *b == 0).unwrap_or(array.len());
+    std::str::from_utf8(&array[..len]).unwrap_or("<invalid>")
+}
+/// FNV-1a hash of `values`.
+const fn sizes_hash(values: &[usize]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    let mut i = 0;
+    while i < values.len() {
+        hash = (hash ^ values[i] as u64).wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+/// Calls `f`, catching any panic so that a misbehaving plugin or backend can't take its host down.
+///
+/// If `f` panicked, the panic's message is returned as the error.
+pub fn catch_panic<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|e| panic_message(&*e))
+}
+
+/// Recovers the message of a panic from its payload, as returned by [`std::panic::catch_unwind`].
+pub fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+        .unwrap_or_else(|| "the panic message couldn't be recovered".into())
+}
 
 pub mod prelude {
-    pub use crate::{loading::*, vtable::*, Plugin};
+    pub use crate::{catch_panic, loading::*, panic_message, vtable::*, Plugin};
 }
 
 pub trait Plugin: Sized + 'static {
@@ -68,12 +191,6 @@ pub trait Plugin: Sized + 'static {
     type RunningPlugin;
     /// Your plugins' default name when statically linked.
     const STATIC_NAME: &'static str;
-    /// You probabky don't need to override this function.
-    ///
-    /// Returns some build information on your plugin, allowing the host to detect potential ABI changes that would break it.
-    fn compatibility() -> ZResult<Compatibility> {
-        Compatibility::new()
-    }
     /// Starts your plugin. Use `Ok` to return your plugin's control structure
     fn start(name: &str, args: &Self::StartArgs) -> ZResult<Self::RunningPlugin>;
 }
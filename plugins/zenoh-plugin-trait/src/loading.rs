@@ -17,6 +17,7 @@ use libloading::Library;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::SystemTime;
 use zenoh_result::{bail, zerror, ZResult};
 use zenoh_util::LibLoader;
 
@@ -26,6 +27,18 @@ pub struct PluginsManager<StartArgs, RunningPlugin> {
     loader: Option<LibLoader>,
     plugin_starters: Vec<Box<dyn PluginStarter<StartArgs, RunningPlugin> + Send + Sync>>,
     running_plugins: HashMap<String, (String, RunningPlugin)>,
+    /// The names of the running plugins, in the order they were started.
+    start_order: Vec<String>,
+    failed_plugins: HashMap<String, String>,
+    restarts: HashMap<String, Vec<PluginRestart>>,
+}
+
+/// A restart of a plugin, after it failed while running.
+#[derive(Debug, Clone)]
+pub struct PluginRestart {
+    pub time: SystemTime,
+    /// Why the plugin had to be restarted.
+    pub reason: String,
 }
 
 impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, RunningPlugin> {
@@ -35,6 +48,9 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
             loader: Some(loader),
             plugin_starters: Vec::new(),
             running_plugins: HashMap::new(),
+            start_order: Vec::new(),
+            failed_plugins: HashMap::new(),
+            restarts: HashMap::new(),
         }
     }
     /// Constructs a new plugin manager with dynamic library loading enabled.
@@ -43,6 +59,9 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
             loader: None,
             plugin_starters: Vec::new(),
             running_plugins: HashMap::new(),
+            start_order: Vec::new(),
+            failed_plugins: HashMap::new(),
+            restarts: HashMap::new(),
         }
     }
 
@@ -73,8 +92,10 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
                 match self.plugin_starters.iter().find(|p| p.name() == plugin) {
                     Some(s) => {
                         let path = s.path();
-                        let (_, plugin) = e.insert((path.into(), s.start(args).map_err(|e| zerror!(e => "Failed to load plugin {} (from {})", plugin, path))?));
-                        Ok(Some((path, &*plugin)))
+                        let (_, running) = e.insert((path.into(), start_plugin(&**s, args).map_err(|e| zerror!(e => "Failed to load plugin {} (from {})", plugin, path))?));
+                        self.failed_plugins.remove(plugin);
+                        self.start_order.push(plugin.into());
+                        Ok(Some((path, &*running)))
                     }
                     None => bail!("Plugin starter for `{}` not found", plugin),
                 }
@@ -94,9 +115,10 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
         let PluginsManager {
             plugin_starters,
             running_plugins,
+            start_order,
+            failed_plugins,
             ..
         } = self;
-        let compat = crate::Compatibility::new().unwrap();
         plugin_starters.iter().map(move |p| {
             let name = p.name();
             let path = p.path();
@@ -106,26 +128,15 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
                 match running_plugins.entry(name.into()) {
                     std::collections::hash_map::Entry::Occupied(_) => Ok(None),
                     std::collections::hash_map::Entry::Vacant(e) => {
-                        let compatible = match p.compatibility() {
-                            Some(Ok(c)) => {
-                                if Compatibility::are_compatible(&compat, &c) {
-                                    Ok(())
-                                } else {
-                                    Err(zerror!("Plugin compatibility mismatch: host: {:?} - plugin: {:?}. This could lead to segfaults, so wer'e not starting it.", &compat, &c))
-                                }
-                            }
-                            Some(Err(e)) => Err(zerror!(e => "Plugin {} (from {}) compatibility couldn't be recovered. This likely means it's very broken.", name, path)),
-                            None => Ok(()),
-                        };
-                        if let Err(e) = compatible {
-                            Err(e.into())
-                        } else {
-                            match p.start(args) {
-                                Ok(p) => Ok(Some(unsafe {
+                        match start_plugin(&**p, args) {
+                            Ok(p) => {
+                                failed_plugins.remove(name);
+                                start_order.push(name.into());
+                                Ok(Some(unsafe {
                                     std::mem::transmute(&e.insert((path.into(), p)).1)
-                                })),
-                                Err(e) => Err(e),
+                                }))
                             }
+                            Err(e) => Err(e),
                         }
                     }
                 },
@@ -134,13 +145,40 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
     }
 
     /// Stops `plugin`, returning `true` if it was indeed running.
+    ///
+    /// Any failure previously recorded for `plugin` is forgotten.
     pub fn stop(&mut self, plugin: &str) -> bool {
-        let result = self.running_plugins.remove(plugin).is_some();
+        let result = self.stop_running(plugin);
         self.plugin_starters
             .retain(|p| p.name() != plugin || !p.deletable());
         result
     }
 
+    /// Stops `plugin` without unloading its library, so that it may be started again. Returns `true` if it was indeed running.
+    ///
+    /// Any failure previously recorded for `plugin` is forgotten.
+    pub fn stop_running(&mut self, plugin: &str) -> bool {
+        self.failed_plugins.remove(plugin);
+        self.start_order.retain(|name| name != plugin);
+        match self.running_plugins.remove(plugin) {
+            Some((_, running)) => {
+                if let Err(e) = catch_panic(|| drop(running)) {
+                    log::error!("Plugin `{}` panicked while stopping: {}", plugin, e);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops all running plugins, in the reverse order of their starting, so that plugins are stopped before the
+    /// plugins they depend on.
+    pub fn stop_all(&mut self) {
+        while let Some(plugin) = self.start_order.last().cloned() {
+            self.stop_running(&plugin);
+        }
+    }
+
     /// Lists the loaded plugins by name.
     pub fn loaded_plugins(&self) -> impl Iterator<Item = &str> {
         self.plugin_starters.iter().map(|p| p.name())
@@ -156,28 +194,71 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
         }
         result
     }
-    /// Returns an iterator over each running plugin, where the keys are their name, and the values are a tuple of their path and handle.
+    /// Returns an iterator over each running plugin in the order they were started, where the keys are their name, and the values are a tuple of their path and handle.
     pub fn running_plugins(&self) -> impl Iterator<Item = (&str, (&str, &RunningPlugin))> {
-        self.running_plugins
+        self.start_order.iter().map(|name| {
+            let (path, p) = &self.running_plugins[name];
+            (name.as_str(), (path.as_str(), p))
+        })
+    }
+    /// Records that `plugin` failed to load, to start or to keep running because of `error`, so that it may be reported later on.
+    ///
+    /// The record is cleared once `plugin` is successfully started.
+    pub fn mark_failed(&mut self, plugin: &str, error: String) {
+        self.failed_plugins.insert(plugin.into(), error);
+    }
+    /// Returns a map containing the error of each plugin that failed to load, to start or to keep running, associated to its name.
+    pub fn failed_plugins(&self) -> HashMap<&str, &str> {
+        self.failed_plugins
             .iter()
-            .map(|(s, (path, p))| (s.as_str(), (path.as_str(), p)))
+            .map(|(name, error)| (name.as_str(), error.as_str()))
+            .collect()
+    }
+    /// Records that `plugin` is being restarted because of `reason`, returning the number of times it was restarted.
+    ///
+    /// Records are kept until cleared with [`Self::clear_restarts`].
+    pub fn record_restart(&mut self, plugin: &str, reason: String) -> usize {
+        let restarts = self.restarts.entry(plugin.into()).or_default();
+        restarts.push(PluginRestart {
+            time: SystemTime::now(),
+            reason,
+        });
+        restarts.len()
+    }
+    /// Returns the restarts recorded for `plugin`, from the oldest to the latest.
+    pub fn restarts(&self, plugin: &str) -> &[PluginRestart] {
+        self.restarts.get(plugin).map_or(&[], Vec::as_slice)
+    }
+    /// Returns an iterator over the plugins that were restarted, along with their restarts.
+    pub fn restarted_plugins(&self) -> impl Iterator<Item = (&str, &[PluginRestart])> {
+        self.restarts
+            .iter()
+            .map(|(name, restarts)| (name.as_str(), restarts.as_slice()))
+    }
+    /// Forgets the restarts recorded for `plugin`.
+    pub fn clear_restarts(&mut self, plugin: &str) {
+        self.restarts.remove(plugin);
     }
     /// Returns the handle of the requested running plugin if available.
     pub fn plugin(&self, name: &str) -> Option<&RunningPlugin> {
         self.running_plugins.get(name).map(|p| &p.1)
     }
 
-    fn load_plugin(
-        name: &str,
-        lib: Library,
-        path: PathBuf,
-    ) -> ZResult<DynamicPlugin<StartArgs, RunningPlugin>> {
-        DynamicPlugin::new(name.into(), lib, path).map_err(|e|
-            zerror!("Wrong PluginVTable version, your {} doesn't appear to be compatible with this version of Zenoh (vtable versions: plugin v{}, zenoh v{})",
-                name,
-                e.map_or_else(|| "UNKNWON".to_string(), |e| e.to_string()),
-                PLUGIN_VTABLE_VERSION).into()
-        )
+    /// Adds a plugin run by a host of the caller rather than loaded from a shared library, e.g. a sandbox,
+    /// `start` being called with the plugin's name each time the plugin is started.
+    ///
+    /// Like the dynamic plugins, it is removed from the manager once stopped with [`Self::stop`].
+    pub fn add_hosted(
+        &mut self,
+        name: String,
+        path: String,
+        start: impl Fn(&str, &StartArgs) -> ZResult<RunningPlugin> + Send + Sync + 'static,
+    ) {
+        self.plugin_starters.push(Box::new(HostedPlugin {
+            name,
+            path,
+            start: Box::new(start),
+        }));
     }
 
     pub fn load_plugin_by_name(&mut self, name: String) -> ZResult<String> {
@@ -185,7 +266,7 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
             Some(l) => unsafe { l.search_and_load(&format!("zenoh_plugin_{}", &name))? },
             None => bail!("Can't load dynamic plugin ` {}`, as dynamic loading is not enabled for this plugin manager.", name),
         };
-        let plugin = match Self::load_plugin(&name, lib, p.clone()) {
+        let plugin = match DynamicPlugin::new(name.clone(), lib, p.clone()) {
             Ok(p) => p,
             Err(e) => bail!("After loading `{:?}`: {}", &p, e),
         };
@@ -202,7 +283,7 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
             let path = path.as_ref();
             match unsafe { LibLoader::load_file(path) } {
                 Ok((lib, p)) => {
-                    let plugin = Self::load_plugin(&name, lib, p)?;
+                    let plugin = DynamicPlugin::new(name.clone(), lib, p)?;
                     let path = plugin.path().into();
                     self.plugin_starters.push(Box::new(plugin));
                     return Ok(path);
@@ -214,11 +295,33 @@ impl<StartArgs: 'static, RunningPlugin: 'static> PluginsManager<StartArgs, Runni
     }
 }
 
+impl<StartArgs, RunningPlugin> Drop for PluginsManager<StartArgs, RunningPlugin> {
+    fn drop(&mut self) {
+        // Running plugins must also be dropped before the libraries holding their code are unloaded
+        while let Some(plugin) = self.start_order.pop() {
+            if let Some((_, running)) = self.running_plugins.remove(&plugin) {
+                if let Err(e) = catch_panic(|| drop(running)) {
+                    log::error!("Plugin `{}` panicked while stopping: {}", plugin, e);
+                }
+            }
+        }
+    }
+}
+
+/// Starts the plugin of `starter`, turning a panic into an error.
+fn start_plugin<StartArgs, RunningPlugin>(
+    starter: &(dyn PluginStarter<StartArgs, RunningPlugin> + Send + Sync),
+    args: &StartArgs,
+) -> ZResult<RunningPlugin> {
+    catch_panic(|| starter.start(args)).unwrap_or_else(|e| {
+        Err(zerror!("Plugin `{}` panicked while starting: {}", starter.name(), e).into())
+    })
+}
+
 trait PluginStarter<StartArgs, RunningPlugin> {
     fn name(&self) -> &str;
     fn path(&self) -> &str;
     fn start(&self, args: &StartArgs) -> ZResult<RunningPlugin>;
-    fn compatibility(&self) -> Option<ZResult<Compatibility>>;
     fn deletable(&self) -> bool;
 }
 
@@ -244,9 +347,6 @@ where
     fn path(&self) -> &str {
         "<statically_linked>"
     }
-    fn compatibility(&self) -> Option<ZResult<Compatibility>> {
-        None
-    }
     fn start(&self, args: &StartArgs) -> ZResult<RunningPlugin> {
         P::start(P::STATIC_NAME, args)
     }
@@ -267,8 +367,31 @@ impl<StartArgs, RunningPlugin> PluginStarter<StartArgs, RunningPlugin>
     fn start(&self, args: &StartArgs) -> ZResult<RunningPlugin> {
         self.vtable.start(self.name(), args)
     }
-    fn compatibility(&self) -> Option<ZResult<Compatibility>> {
-        Some(self.vtable.compatibility())
+    fn deletable(&self) -> bool {
+        true
+    }
+}
+
+type HostedStart<StartArgs, RunningPlugin> =
+    Box<dyn Fn(&str, &StartArgs) -> ZResult<RunningPlugin> + Send + Sync>;
+
+struct HostedPlugin<StartArgs, RunningPlugin> {
+    name: String,
+    path: String,
+    start: HostedStart<StartArgs, RunningPlugin>,
+}
+
+impl<StartArgs, RunningPlugin> PluginStarter<StartArgs, RunningPlugin>
+    for HostedPlugin<StartArgs, RunningPlugin>
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn path(&self) -> &str {
+        &self.path
+    }
+    fn start(&self, args: &StartArgs) -> ZResult<RunningPlugin> {
+        (self.start)(&self.name, args)
     }
     fn deletable(&self) -> bool {
         true
@@ -283,12 +406,22 @@ pub struct DynamicPlugin<StartArgs, RunningPlugin> {
 }
 
 impl<StartArgs, RunningPlugin> DynamicPlugin<StartArgs, RunningPlugin> {
-    fn new(name: String, lib: Library, path: PathBuf) -> Result<Self, Option<PluginVTableVersion>> {
+    fn new(name: String, lib: Library, path: PathBuf) -> ZResult<Self> {
+        let expected = Compatibility::new::<StartArgs, RunningPlugin>();
+        unsafe { Compatibility::check(&lib, PLUGIN_COMPATIBILITY_SYMBOL, &expected) }.map_err(
+            |e| {
+                zerror!(
+                    "Plugin `{}` isn't compatible with this version of Zenoh: {}",
+                    name,
+                    e
+                )
+            },
+        )?;
         let load_plugin = unsafe {
             lib.get::<fn(PluginVTableVersion) -> LoadPluginResult<StartArgs, RunningPlugin>>(
                 b"load_plugin",
             )
-            .map_err(|_| None)?
+            .map_err(|_| zerror!("Plugin `{}` doesn't declare a `load_plugin` function", name))?
         };
         match load_plugin(PLUGIN_VTABLE_VERSION) {
             Ok(vtable) => Ok(DynamicPlugin {
@@ -297,7 +430,10 @@ impl<StartArgs, RunningPlugin> DynamicPlugin<StartArgs, RunningPlugin> {
                 name,
                 path,
             }),
-            Err(plugin_version) => Err(Some(plugin_version)),
+            Err(plugin_version) => bail!("Wrong PluginVTable version, your {} doesn't appear to be compatible with this version of Zenoh (vtable versions: plugin v{}, zenoh v{})",
+                name,
+                plugin_version,
+                PLUGIN_VTABLE_VERSION),
         }
     }
 }
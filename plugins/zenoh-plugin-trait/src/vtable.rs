@@ -16,18 +16,19 @@ pub use no_mangle::*;
 use zenoh_result::ZResult;
 
 pub type PluginVTableVersion = u16;
-type LoadPluginResultInner = Result<PluginVTableInner<(), ()>, PluginVTableVersion>;
 pub type LoadPluginResult<A, B> = Result<PluginVTable<A, B>, PluginVTableVersion>;
 
 /// This number should change any time the internal structure of [`PluginVTable`] changes
-pub const PLUGIN_VTABLE_VERSION: PluginVTableVersion = 1;
+pub const PLUGIN_VTABLE_VERSION: PluginVTableVersion = 2;
+
+/// The name of the [`Compatibility`] static declared by [`declare_plugin`] in plugin libraries.
+pub const PLUGIN_COMPATIBILITY_SYMBOL: &[u8] = b"ZENOH_PLUGIN_COMPATIBILITY";
 
 type StartFn<StartArgs, RunningPlugin> = fn(&str, &StartArgs) -> ZResult<RunningPlugin>;
 
 #[repr(C)]
 struct PluginVTableInner<StartArgs, RunningPlugin> {
     start: StartFn<StartArgs, RunningPlugin>,
-    compatibility: fn() -> ZResult<crate::Compatibility>,
 }
 
 /// Automagical padding such that [PluginVTable::init]'s result is the size of a cache line
@@ -37,7 +38,7 @@ struct PluginVTablePadding {
 }
 impl PluginVTablePadding {
     const fn padding_length() -> usize {
-        64 - std::mem::size_of::<LoadPluginResultInner>()
+        64 - std::mem::size_of::<PluginVTableInner<(), ()>>()
     }
     fn new() -> Self {
         PluginVTablePadding {
@@ -61,7 +62,6 @@ impl<StartArgs, RunningPlugin> PluginVTable<StartArgs, RunningPlugin> {
         PluginVTable {
             inner: PluginVTableInner {
                 start: ConcretePlugin::start,
-                compatibility: ConcretePlugin::compatibility,
             },
             padding: PluginVTablePadding::new(),
         }
@@ -79,18 +79,24 @@ impl<StartArgs, RunningPlugin> PluginVTable<StartArgs, RunningPlugin> {
     pub fn start(&self, name: &str, start_args: &StartArgs) -> ZResult<RunningPlugin> {
         (self.inner.start)(name, start_args)
     }
-    pub fn compatibility(&self) -> ZResult<Compatibility> {
-        (self.inner.compatibility)()
-    }
 }
 
 pub use no_mangle::*;
 #[cfg(feature = "no_mangle")]
 pub mod no_mangle {
     /// This macro will add a non-mangled `load_plugin` function to the library if feature `no_mangle` is enabled (which it is by default).
+    ///
+    /// It also adds the library's [`Compatibility`](crate::Compatibility) record, which the plugins manager checks before calling `load_plugin`.
     #[macro_export]
     macro_rules! declare_plugin {
         ($ty: path) => {
+            #[no_mangle]
+            pub static ZENOH_PLUGIN_COMPATIBILITY: $crate::Compatibility =
+                $crate::Compatibility::new::<
+                    <$ty as $crate::prelude::Plugin>::StartArgs,
+                    <$ty as $crate::prelude::Plugin>::RunningPlugin,
+                >();
+
             #[no_mangle]
             fn load_plugin(
                 version: $crate::prelude::PluginVTableVersion,
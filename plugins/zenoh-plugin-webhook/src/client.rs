@@ -0,0 +1,83 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_rustls::TlsConnector;
+use async_std::net::TcpStream;
+use http_types::{Method, Request, StatusCode, Url};
+use rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use std::sync::Arc;
+use zenoh::prelude::r#async::*;
+use zenoh_result::{bail, zerror, ZResult};
+
+pub(crate) const KEY_EXPR_HEADER: &str = "X-Zenoh-Key-Expr";
+pub(crate) const KIND_HEADER: &str = "X-Zenoh-Kind";
+pub(crate) const TIMESTAMP_HEADER: &str = "X-Zenoh-Timestamp";
+
+/// Returns a TLS connector trusting the webpki root certificates, used for `https` webhooks.
+pub(crate) fn tls_connector() -> TlsConnector {
+    let mut root_cert_store = RootCertStore::empty();
+    root_cert_store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            ta.subject,
+            ta.spki,
+            ta.name_constraints,
+        )
+    }));
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_cert_store)
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+/// Checks that `url` can be used as a webhook.
+pub(crate) fn parse_url(url: &str) -> ZResult<Url> {
+    let url = Url::parse(url).map_err(|e| zerror!("Invalid webhook URL {}: {}", url, e))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        bail!("Invalid webhook URL {}: scheme must be http or https", url)
+    }
+    if url.host_str().is_none() {
+        bail!("Invalid webhook URL {}: missing host", url)
+    }
+    Ok(url)
+}
+
+/// POSTs `sample` to `url`, its metadata being passed as headers, and returns the response status.
+pub(crate) async fn post(url: &Url, tls: &TlsConnector, sample: &Sample) -> ZResult<StatusCode> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| zerror!("Missing host in {}", url))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| zerror!("Missing port in {}", url))?;
+
+    let mut req = Request::new(Method::Post, url.clone());
+    req.insert_header("Content-Type", sample.value.encoding.to_string());
+    req.insert_header(KEY_EXPR_HEADER, sample.key_expr.as_str());
+    req.insert_header(KIND_HEADER, sample.kind.to_string());
+    if let Some(timestamp) = &sample.timestamp {
+        req.insert_header(TIMESTAMP_HEADER, timestamp.to_string());
+    }
+    req.set_body(sample.value.payload.contiguous().into_owned());
+
+    let stream = TcpStream::connect((host, port)).await?;
+    let res = if url.scheme() == "https" {
+        let domain = ServerName::try_from(host).map_err(|e| zerror!("{}: {}", host, e))?;
+        let stream = tls.connect(domain, stream).await?;
+        async_h1::connect(stream, req).await
+    } else {
+        async_h1::connect(stream, req).await
+    };
+    res.map(|res| res.status())
+        .map_err(|e| zerror!("{}", e).into())
+}
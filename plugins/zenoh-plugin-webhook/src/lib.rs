@@ -0,0 +1,293 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! ⚠️ WARNING ⚠️
+//!
+//! This crate is intended for Zenoh's internal use.
+//!
+//! [Click here for Zenoh's documentation](../zenoh/index.html)
+//!
+//! Subscribes to the key expressions of the configured webhooks and POSTs the received samples to
+//! their URL, the sample's key expression, kind and timestamp being passed as `X-Zenoh-*` headers.
+//! Failed requests are retried with an exponential backoff.
+use async_rustls::TlsConnector;
+use async_std::prelude::FutureExt;
+use http_types::{StatusCode, Url};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::plugins::{Metric, MetricKind, Plugin, RunningPluginTrait, TaskMonitor, ZenohPlugin};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh_result::{bail, zerror, ZResult};
+
+mod client;
+mod config;
+pub use config::{Config, HookConf, RetryConf};
+
+const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
+lazy_static::lazy_static! {
+    static ref LONG_VERSION: String = format!("{} built with {}", GIT_VERSION, env!("RUSTC_VERSION"));
+}
+const HOOK_QUEUE_SIZE: usize = 256;
+
+zenoh_plugin_trait::declare_plugin!(WebhookPlugin);
+pub struct WebhookPlugin {}
+
+impl ZenohPlugin for WebhookPlugin {}
+
+impl Plugin for WebhookPlugin {
+    type StartArgs = Runtime;
+    type RunningPlugin = zenoh::plugins::RunningPlugin;
+    const STATIC_NAME: &'static str = "webhook";
+
+    fn start(name: &str, runtime: &Self::StartArgs) -> ZResult<zenoh::plugins::RunningPlugin> {
+        // Try to initiate login.
+        // Required in case of dynamic lib, otherwise no logs.
+        // But cannot be done twice in case of static link.
+        let _ = env_logger::try_init();
+        log::debug!("Webhook plugin {}", LONG_VERSION.as_str());
+
+        let runtime_conf = runtime.config.lock();
+        let plugin_conf = runtime_conf
+            .plugin(name)
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+
+        let conf: Config = serde_json::from_value(plugin_conf.clone())
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let hooks = conf
+            .hooks
+            .iter()
+            .map(Hook::new)
+            .collect::<ZResult<Vec<_>>>()
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        let hooks = Arc::new(hooks);
+        let monitor = TaskMonitor::default();
+        let task = async_std::task::spawn(monitor.clone().watch(run(
+            runtime.clone(),
+            conf.clone(),
+            hooks.clone(),
+        )));
+        let task = async_std::task::block_on(task.timeout(std::time::Duration::from_millis(1)));
+        if let Ok(Err(e)) = task {
+            bail!("Webhooks failed within 1ms: {e}")
+        }
+        Ok(Box::new(RunningPlugin {
+            conf,
+            hooks,
+            monitor,
+        }))
+    }
+}
+
+/// A configured webhook and its delivery statistics.
+struct Hook {
+    key_expr: OwnedKeyExpr,
+    url: Url,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl Hook {
+    fn new(conf: &HookConf) -> ZResult<Self> {
+        Ok(Hook {
+            key_expr: OwnedKeyExpr::autocanonize(conf.key_expr.clone())?,
+            url: client::parse_url(&conf.url)?,
+            delivered: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    fn status(&self) -> serde_json::Value {
+        serde_json::json!({
+            "key_expr": self.key_expr.as_str(),
+            "url": self.url.as_str(),
+            "delivered": self.delivered.load(Ordering::Relaxed),
+            "failed": self.failed.load(Ordering::Relaxed),
+            "dropped": self.dropped.load(Ordering::Relaxed),
+        })
+    }
+}
+
+struct RunningPlugin {
+    conf: Config,
+    hooks: Arc<Vec<Hook>>,
+    monitor: TaskMonitor,
+}
+
+impl RunningPluginTrait for RunningPlugin {
+    fn terminated(&self) -> Option<String> {
+        self.monitor.terminated()
+    }
+
+    fn config_checker(&self) -> zenoh::plugins::ValidationFunction {
+        Arc::new(|_, _, _| {
+            bail!("zenoh-plugin-webhook doesn't accept any runtime configuration changes")
+        })
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<zenoh::plugins::Response>> {
+        let mut responses = Vec::new();
+        for (suffix, value) in [
+            ("/version", GIT_VERSION.into()),
+            ("/config", (&self.conf).into()),
+            (
+                "/hooks",
+                self.hooks
+                    .iter()
+                    .map(Hook::status)
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+        ] {
+            let key = format!("{plugin_status_key}{suffix}");
+            if keyexpr::new(key.as_str())
+                .unwrap()
+                .intersects(&selector.key_expr)
+            {
+                responses.push(zenoh::plugins::Response::new(key, value))
+            }
+        }
+        Ok(responses)
+    }
+
+    fn metrics(&self) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        for hook in self.hooks.iter() {
+            for (result, count) in [
+                ("delivered", &hook.delivered),
+                ("failed", &hook.failed),
+                ("dropped", &hook.dropped),
+            ] {
+                metrics.push(
+                    Metric::new(
+                        "zenoh_webhook_samples_total",
+                        MetricKind::Counter,
+                        "Number of samples received by a webhook, by delivery result.",
+                        count.load(Ordering::Relaxed) as f64,
+                    )
+                    .with_label("url", hook.url.as_str())
+                    .with_label("result", result),
+                );
+            }
+        }
+        metrics
+    }
+}
+
+async fn run(runtime: Runtime, conf: Config, hooks: Arc<Vec<Hook>>) -> ZResult<()> {
+    // Try to initiate login.
+    // Required in case of dynamic lib, otherwise no logs.
+    // But cannot be done twice in case of static link.
+    let _ = env_logger::try_init();
+
+    let session = zenoh::init(runtime).res().await?.into_arc();
+    let conf = Arc::new(conf);
+    let tls = client::tls_connector();
+    let mut subscribers = Vec::with_capacity(hooks.len());
+    for index in 0..hooks.len() {
+        let hook = &hooks[index];
+        let (tx, rx) = flume::bounded::<Sample>(HOOK_QUEUE_SIZE);
+        let hooks_clone = hooks.clone();
+        let subscriber = session
+            .declare_subscriber(&hook.key_expr)
+            .callback(move |sample| {
+                if tx.try_send(sample).is_err() {
+                    let hook = &hooks_clone[index];
+                    hook.dropped.fetch_add(1, Ordering::Relaxed);
+                    log::warn!("Webhook {} queue full, dropping sample", hook.url);
+                }
+            })
+            .res()
+            .await?;
+        subscribers.push(subscriber);
+        async_std::task::spawn(deliver(hooks.clone(), index, rx, conf.clone(), tls.clone()));
+    }
+    // Keep the subscribers declared for the whole lifetime of the plugin
+    async_std::future::pending::<()>().await;
+    drop(subscribers);
+    Ok(())
+}
+
+/// POSTs the samples received by the webhook at `index` in order, retrying each failed request.
+async fn deliver(
+    hooks: Arc<Vec<Hook>>,
+    index: usize,
+    samples: flume::Receiver<Sample>,
+    conf: Arc<Config>,
+    tls: TlsConnector,
+) {
+    let hook = &hooks[index];
+    let timeout = Duration::from_millis(conf.request_timeout_ms);
+    while let Ok(sample) = samples.recv_async().await {
+        let mut backoff = Duration::from_millis(conf.retry.initial_backoff_ms);
+        let mut attempt = 0;
+        loop {
+            let error = match client::post(&hook.url, &tls, &sample)
+                .timeout(timeout)
+                .await
+            {
+                Ok(Ok(status)) if status.is_success() => {
+                    hook.delivered.fetch_add(1, Ordering::Relaxed);
+                    break;
+                }
+                Ok(Ok(status)) if !is_retriable(status) => {
+                    hook.failed.fetch_add(1, Ordering::Relaxed);
+                    log::warn!(
+                        "Webhook {} rejected sample on {}: {}",
+                        hook.url,
+                        sample.key_expr,
+                        status
+                    );
+                    break;
+                }
+                Ok(Ok(status)) => status.to_string(),
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => "timeout".to_string(),
+            };
+            if attempt >= conf.retry.max_retries {
+                hook.failed.fetch_add(1, Ordering::Relaxed);
+                log::warn!(
+                    "Webhook {} failed for sample on {} after {} attempts: {}",
+                    hook.url,
+                    sample.key_expr,
+                    attempt + 1,
+                    error
+                );
+                break;
+            }
+            log::debug!(
+                "Webhook {} failed ({}), retrying in {:?}",
+                hook.url,
+                error,
+                backoff
+            );
+            async_std::task::sleep(backoff).await;
+            backoff = (backoff * 2).min(Duration::from_millis(conf.retry.max_backoff_ms));
+            attempt += 1;
+        }
+    }
+}
+
+fn is_retriable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TooManyRequests
+        || status == StatusCode::RequestTimeout
+}
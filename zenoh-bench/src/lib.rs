@@ -0,0 +1,296 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Throughput and latency benchmarks over zenoh sessions.
+//!
+//! The latency is measured by a [`ping`] publishing on `<prefix>/ping` and waiting for the [`pong`]
+//! echoing its payloads on `<prefix>/pong`. The throughput is measured by a [`subscribe`]r counting
+//! the samples a saturating [`publish`]er puts on `<prefix>/thr`.
+//! Both end up in a [`Report`] of percentiles, that can be written as CSV or JSON.
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use zenoh::prelude::sync::*;
+use zenoh::publication::CongestionControl;
+use zenoh::subscriber::Subscriber;
+use zenoh::Result as ZResult;
+
+/// The default prefix of the key expressions used by the benchmarks.
+pub const DEFAULT_PREFIX: &str = "zenoh-bench";
+
+/// Builds a payload of `size` bytes.
+pub fn payload(size: usize) -> Value {
+    (0..size)
+        .map(|i| (i % 10) as u8)
+        .collect::<Vec<u8>>()
+        .into()
+}
+
+/// The percentiles of the measures of a benchmark.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    /// `latency` or `throughput`.
+    pub test: String,
+    /// The size of the payloads, in bytes.
+    pub payload_size: usize,
+    /// The number of measures.
+    pub samples: usize,
+    /// The unit of the measures: `us` for the round-trip times, `msg/s` for the throughputs.
+    pub unit: String,
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub p999: f64,
+    pub max: f64,
+}
+
+impl Report {
+    /// Computes the report of the given measures, failing if there is none.
+    pub fn new(
+        test: &str,
+        payload_size: usize,
+        unit: &str,
+        mut measures: Vec<f64>,
+    ) -> ZResult<Self> {
+        if measures.is_empty() {
+            return Err(format!("No measure for the {test} test").into());
+        }
+        measures.sort_by(f64::total_cmp);
+        // Nearest-rank percentiles, given in per mille to keep the ranks exact.
+        let percentile = |permille: usize| {
+            let rank = (permille * measures.len() + 999) / 1000;
+            measures[rank.clamp(1, measures.len()) - 1]
+        };
+        Ok(Report {
+            test: test.to_string(),
+            payload_size,
+            samples: measures.len(),
+            unit: unit.to_string(),
+            min: measures[0],
+            mean: measures.iter().sum::<f64>() / measures.len() as f64,
+            p50: percentile(500),
+            p90: percentile(900),
+            p99: percentile(990),
+            p999: percentile(999),
+            max: measures[measures.len() - 1],
+        })
+    }
+
+    /// The header of the CSV lines written by [`to_csv`](Report::to_csv).
+    pub const CSV_HEADER: &'static str =
+        "test,payload_size,samples,unit,min,mean,p50,p90,p99,p999,max";
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3}",
+            self.test,
+            self.payload_size,
+            self.samples,
+            self.unit,
+            self.min,
+            self.mean,
+            self.p50,
+            self.p90,
+            self.p99,
+            self.p999,
+            self.max
+        )
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Echoes the payloads received on `<prefix>/ping` on `<prefix>/pong`, until the returned subscriber is dropped.
+pub fn pong(session: Arc<Session>, prefix: &keyexpr) -> ZResult<Subscriber<'static, ()>> {
+    let publisher = session
+        .declare_publisher(prefix / keyexpr::new("pong")?)
+        .congestion_control(CongestionControl::Block)
+        .res()?;
+    session
+        .declare_subscriber(prefix / keyexpr::new("ping")?)
+        .callback(move |sample| {
+            if let Err(e) = publisher.put(sample.value).res() {
+                log::warn!("Failed to send pong: {}", e);
+            }
+        })
+        .res()
+}
+
+/// The parameters of a [`ping`].
+#[derive(Debug, Clone)]
+pub struct PingConfig {
+    pub payload_size: usize,
+    /// The number of round-trips to measure.
+    pub samples: usize,
+    /// How long round-trips are performed before the measures start.
+    pub warmup: Duration,
+    /// How long to wait for a pong before failing.
+    pub timeout: Duration,
+}
+
+impl Default for PingConfig {
+    fn default() -> Self {
+        PingConfig {
+            payload_size: 8,
+            samples: 100,
+            warmup: Duration::from_secs(1),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Measures the round-trip times between this session and a [`pong`], in microseconds.
+pub fn ping(session: &Session, prefix: &keyexpr, config: &PingConfig) -> ZResult<Report> {
+    let subscriber = session
+        .declare_subscriber(prefix / keyexpr::new("pong")?)
+        .res()?;
+    let publisher = session
+        .declare_publisher(prefix / keyexpr::new("ping")?)
+        .congestion_control(CongestionControl::Block)
+        .res()?;
+    let data = payload(config.payload_size);
+    let round_trip = || -> ZResult<Duration> {
+        let start = Instant::now();
+        publisher.put(data.clone()).res()?;
+        subscriber
+            .recv_timeout(config.timeout)
+            .map_err(|_| format!("No pong received within {:?}", config.timeout))?;
+        Ok(start.elapsed())
+    };
+
+    let start = Instant::now();
+    while start.elapsed() < config.warmup {
+        round_trip()?;
+    }
+    let mut measures = Vec::with_capacity(config.samples);
+    for _ in 0..config.samples {
+        measures.push(round_trip()?.as_secs_f64() * 1e6);
+    }
+    Report::new("latency", config.payload_size, "us", measures)
+}
+
+/// Puts payloads of `payload_size` bytes on `<prefix>/thr` as fast as possible, for `duration`
+/// or forever if `None`. Returns the number of payloads put.
+pub fn publish(
+    session: &Session,
+    prefix: &keyexpr,
+    payload_size: usize,
+    duration: Option<Duration>,
+) -> ZResult<usize> {
+    let publisher = session
+        .declare_publisher(prefix / keyexpr::new("thr")?)
+        .congestion_control(CongestionControl::Block)
+        .res()?;
+    let data = payload(payload_size);
+    let start = Instant::now();
+    let mut count = 0;
+    while duration.map_or(true, |d| start.elapsed() < d) {
+        publisher.put(data.clone()).res()?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// The parameters of a [`subscribe`].
+#[derive(Debug, Clone)]
+pub struct SubscribeConfig {
+    /// The period over which each throughput is measured.
+    pub interval: Duration,
+    /// The number of throughputs to measure.
+    pub samples: usize,
+    /// How long to wait for the first payload before failing.
+    pub timeout: Duration,
+}
+
+impl Default for SubscribeConfig {
+    fn default() -> Self {
+        SubscribeConfig {
+            interval: Duration::from_secs(1),
+            samples: 10,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Measures the throughput of the payloads put on `<prefix>/thr` by a [`publish`]er, in messages per second.
+/// The measures start with the first received payload.
+pub fn subscribe(session: &Session, prefix: &keyexpr, config: &SubscribeConfig) -> ZResult<Report> {
+    let count = Arc::new(AtomicUsize::new(0));
+    let payload_size = Arc::new(AtomicUsize::new(0));
+    let (c_count, c_payload_size) = (count.clone(), payload_size.clone());
+    let _subscriber = session
+        .declare_subscriber(prefix / keyexpr::new("thr")?)
+        .callback(move |sample| {
+            c_payload_size.store(sample.value.payload.len(), Ordering::Relaxed);
+            c_count.fetch_add(1, Ordering::Relaxed);
+        })
+        .res()?;
+
+    let start = Instant::now();
+    while count.load(Ordering::Relaxed) == 0 {
+        if start.elapsed() > config.timeout {
+            return Err(format!("No payload received within {:?}", config.timeout).into());
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let mut measures = Vec::with_capacity(config.samples);
+    let mut last = (Instant::now(), count.load(Ordering::Relaxed));
+    for _ in 0..config.samples {
+        std::thread::sleep(config.interval);
+        let now = (Instant::now(), count.load(Ordering::Relaxed));
+        measures.push((now.1 - last.1) as f64 / (now.0 - last.0).as_secs_f64());
+        last = now;
+    }
+    Report::new(
+        "throughput",
+        payload_size.load(Ordering::Relaxed),
+        "msg/s",
+        measures,
+    )
+}
+
+#[test]
+fn report_percentiles() {
+    let report = Report::new(
+        "latency",
+        8,
+        "us",
+        (1..=1000).rev().map(f64::from).collect(),
+    )
+    .unwrap();
+    assert_eq!(report.samples, 1000);
+    assert_eq!(report.min, 1.);
+    assert_eq!(report.max, 1000.);
+    assert_eq!(report.mean, 500.5);
+    assert_eq!(report.p50, 500.);
+    assert_eq!(report.p90, 900.);
+    assert_eq!(report.p99, 990.);
+    assert_eq!(report.p999, 999.);
+    assert_eq!(
+        report.to_csv(),
+        "latency,8,1000,us,1.000,500.500,500.000,900.000,990.000,999.000,1000.000"
+    );
+    assert_eq!(
+        Report::CSV_HEADER.split(',').count(),
+        report.to_csv().split(',').count()
+    );
+
+    let report = Report::new("throughput", 8, "msg/s", vec![3.]).unwrap();
+    assert_eq!((report.p50, report.p999), (3., 3.));
+    assert!(Report::new("throughput", 8, "msg/s", vec![]).is_err());
+}
@@ -0,0 +1,158 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use clap::{Arg, ArgMatches, Command};
+use std::time::Duration;
+use zenoh::config::Config;
+use zenoh::prelude::sync::*;
+use zenoh_bench::{PingConfig, Report, SubscribeConfig, DEFAULT_PREFIX};
+
+fn main() {
+    env_logger::init();
+
+    let args = command().get_matches();
+    if let Err(e) = run(&args) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &ArgMatches) -> zenoh::Result<()> {
+    let config = config_from_args(args)?;
+    let prefix = OwnedKeyExpr::try_from(args.value_of("prefix").unwrap())?;
+    let session = zenoh::open(config).res()?;
+    let report = match args.subcommand() {
+        Some(("ping", args)) => {
+            let config = PingConfig {
+                payload_size: args.value_of_t("size")?,
+                samples: args.value_of_t("samples")?,
+                warmup: Duration::from_secs_f64(args.value_of_t("warmup")?),
+                ..Default::default()
+            };
+            zenoh_bench::ping(&session, &prefix, &config)?
+        }
+        Some(("pong", _)) => {
+            let _pong = zenoh_bench::pong(session.into_arc(), &prefix)?;
+            eprintln!("Echoing the pings on {prefix}/ping, press Ctrl-C to quit...");
+            loop {
+                std::thread::park();
+            }
+        }
+        Some(("pub", args)) => {
+            let duration = args.value_of_t::<f64>("duration")?;
+            let duration = (duration > 0.).then(|| Duration::from_secs_f64(duration));
+            let count =
+                zenoh_bench::publish(&session, &prefix, args.value_of_t("size")?, duration)?;
+            eprintln!("Put {count} payloads");
+            return Ok(());
+        }
+        Some(("sub", args)) => {
+            let config = SubscribeConfig {
+                interval: Duration::from_millis(args.value_of_t("interval")?),
+                samples: args.value_of_t("samples")?,
+                ..Default::default()
+            };
+            zenoh_bench::subscribe(&session, &prefix, &config)?
+        }
+        _ => unreachable!("A subcommand is required"),
+    };
+    print_report(
+        &report,
+        args.value_of("format").unwrap(),
+        !args.is_present("no-header"),
+    );
+    Ok(())
+}
+
+fn print_report(report: &Report, format: &str, header: bool) {
+    match format {
+        "json" => println!("{}", report.to_json()),
+        _ => {
+            if header {
+                println!("{}", Report::CSV_HEADER);
+            }
+            println!("{}", report.to_csv());
+        }
+    }
+}
+
+fn command() -> Command<'static> {
+    Command::new("zenoh-bench")
+        .about("Measures the latency and the throughput of zenoh. The reports of percentiles are printed as CSV or JSON.")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .args(&[
+            clap::arg!(-c --config [FILE] "The configuration file. Currently, this file must be a valid JSON5 or YAML file.")
+                .multiple_values(false)
+                .global(true),
+            clap::arg!(-m --mode [MODE] "The zenoh session mode (peer by default).")
+                .possible_values(["peer", "client"])
+                .multiple_values(false)
+                .global(true),
+            Arg::new("connect").short('e').long("connect").value_name("ENDPOINT").help(r"An endpoint to connect to.
+Repeat this option to connect to several endpoints.").takes_value(true).multiple_occurrences(true).global(true),
+            Arg::new("listen").short('l').long("listen").value_name("ENDPOINT").help(r"An endpoint to listen on.
+Repeat this option to listen on several endpoints.").takes_value(true).multiple_occurrences(true).global(true),
+            clap::arg!(--"no-multicast-scouting" "Disable the multicast-based scouting mechanism.").global(true),
+            clap::arg!(-p --prefix [PREFIX] "The prefix of the key expressions used by the benchmark.")
+                .default_value(DEFAULT_PREFIX)
+                .global(true),
+            clap::arg!(-f --format [FORMAT] "The format of the report.")
+                .possible_values(["csv", "json"])
+                .default_value("csv")
+                .global(true),
+            clap::arg!(--"no-header" "Do not print the header of the CSV report, e.g. to append it to an existing file.").global(true),
+        ])
+        .subcommand(
+            Command::new("ping")
+                .about("Measures the round-trip times with a pong, in microseconds.")
+                .arg(clap::arg!(-s --size [SIZE] "The size of the payloads in bytes.").default_value("8"))
+                .arg(clap::arg!(-n --samples [N] "The number of round-trips to measure.").default_value("100"))
+                .arg(clap::arg!(-w --warmup [SECONDS] "How long to warm up before measuring.").default_value("1")),
+        )
+        .subcommand(Command::new("pong").about("Echoes the pings until interrupted."))
+        .subcommand(
+            Command::new("pub")
+                .about("Puts payloads as fast as possible.")
+                .arg(clap::arg!(-s --size [SIZE] "The size of the payloads in bytes.").default_value("8"))
+                .arg(clap::arg!(-d --duration [SECONDS] "How long to put payloads, forever if 0.").default_value("0")),
+        )
+        .subcommand(
+            Command::new("sub")
+                .about("Measures the throughput of a publisher, in messages per second.")
+                .arg(clap::arg!(-i --interval [MILLISECONDS] "The period over which each throughput is measured.").default_value("1000"))
+                .arg(clap::arg!(-n --samples [N] "The number of throughputs to measure.").default_value("10")),
+        )
+}
+
+fn config_from_args(args: &ArgMatches) -> zenoh::Result<Config> {
+    let mut config = match args.value_of("config") {
+        Some(conf_file) => Config::from_file(conf_file)?,
+        None => Config::default(),
+    };
+    if let Some(mode) = args.value_of("mode") {
+        config
+            .set_mode(Some(mode.parse()?))
+            .map_err(|_| zenoh::Error::from(format!("Invalid mode: {mode}")))?;
+    }
+    if let Some(endpoints) = args.values_of("connect") {
+        config.connect.endpoints = endpoints.map(|e| e.parse()).collect::<zenoh::Result<_>>()?;
+    }
+    if let Some(endpoints) = args.values_of("listen") {
+        config.listen.endpoints = endpoints.map(|e| e.parse()).collect::<zenoh::Result<_>>()?;
+    }
+    if args.is_present("no-multicast-scouting") {
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    }
+    Ok(config)
+}
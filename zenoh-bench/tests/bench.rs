@@ -0,0 +1,72 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::time::Duration;
+use zenoh::prelude::sync::*;
+use zenoh_bench::{PingConfig, SubscribeConfig};
+
+fn open_sessions(endpoint: &str) -> (Session, Session) {
+    let mut config = config::peer();
+    config.listen.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer01 = zenoh::open(config).res().unwrap();
+
+    let mut config = config::peer();
+    config.connect.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer02 = zenoh::open(config).res().unwrap();
+
+    std::thread::sleep(Duration::from_secs(1));
+    (peer01, peer02)
+}
+
+#[test]
+fn bench_ping_pong() {
+    let (peer01, peer02) = open_sessions("tcp/127.0.0.1:18451");
+    let prefix = keyexpr::new("test/bench/latency").unwrap();
+
+    let _pong = zenoh_bench::pong(peer01.into_arc(), prefix).unwrap();
+    std::thread::sleep(Duration::from_secs(1));
+    let config = PingConfig {
+        payload_size: 64,
+        samples: 20,
+        warmup: Duration::from_millis(100),
+        ..Default::default()
+    };
+    let report = zenoh_bench::ping(&peer02, prefix, &config).unwrap();
+    assert_eq!(report.test, "latency");
+    assert_eq!(report.payload_size, 64);
+    assert_eq!(report.samples, 20);
+    assert!(report.min > 0. && report.min <= report.p50 && report.p50 <= report.max);
+}
+
+#[test]
+fn bench_throughput() {
+    let (peer01, peer02) = open_sessions("tcp/127.0.0.1:18452");
+    let prefix = keyexpr::new("test/bench/throughput").unwrap();
+
+    let publisher = std::thread::spawn(move || {
+        zenoh_bench::publish(&peer01, prefix, 32, Some(Duration::from_secs(3))).unwrap()
+    });
+    let config = SubscribeConfig {
+        interval: Duration::from_millis(200),
+        samples: 5,
+        ..Default::default()
+    };
+    let report = zenoh_bench::subscribe(&peer02, prefix, &config).unwrap();
+    assert!(publisher.join().unwrap() > 0);
+    assert_eq!(report.test, "throughput");
+    assert_eq!(report.payload_size, 32);
+    assert_eq!(report.samples, 5);
+    assert!(report.max > 0.);
+}
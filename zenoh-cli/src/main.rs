@@ -0,0 +1,287 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use clap::{Arg, ArgMatches, Command};
+use git_version::git_version;
+use serde_json::json;
+use std::time::Duration;
+use zenoh::config::Config;
+use zenoh::prelude::r#async::*;
+use zenoh::query::Reply;
+use zenoh::scouting::{Hello, WhatAmI};
+
+const GIT_VERSION: &str = git_version!(prefix = "v", cargo_prefix = "v");
+
+#[async_std::main]
+async fn main() {
+    env_logger::init();
+
+    let args = command().get_matches();
+    if let Err(e) = run(&args).await {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: &ArgMatches) -> zenoh::Result<()> {
+    let config = config_from_args(args)?;
+    match args.subcommand() {
+        Some(("put", args)) => {
+            let session = zenoh::open(config).res().await?;
+            session
+                .put(
+                    args.value_of("KEYEXPR").unwrap(),
+                    args.value_of("VALUE").unwrap(),
+                )
+                .res()
+                .await?;
+        }
+        Some(("delete", args)) => {
+            let session = zenoh::open(config).res().await?;
+            session
+                .delete(args.value_of("KEYEXPR").unwrap())
+                .res()
+                .await?;
+        }
+        Some(("get", args)) => {
+            let session = zenoh::open(config).res().await?;
+            let selector = args.value_of("SELECTOR").unwrap();
+            let target = match args.value_of("target") {
+                Some("ALL") => QueryTarget::All,
+                Some("ALL_COMPLETE") => QueryTarget::AllComplete,
+                Some("COMPLETE_STORAGE") => QueryTarget::CompleteStorage,
+                _ => QueryTarget::BestMatching,
+            };
+            let timeout = Duration::from_millis(args.value_of_t("timeout")?);
+            let replies = match args.value_of("value") {
+                Some(value) => session.get(selector).with_value(value),
+                None => session.get(selector),
+            }
+            .target(target)
+            .timeout(timeout)
+            .res()
+            .await?;
+            while let Ok(reply) = replies.recv_async().await {
+                println!("{}", reply_to_json(reply));
+            }
+        }
+        Some(("sub", args)) => {
+            let session = zenoh::open(config).res().await?;
+            let subscriber = session
+                .declare_subscriber(args.value_of("KEYEXPR").unwrap())
+                .res()
+                .await?;
+            while let Ok(sample) = subscriber.recv_async().await {
+                println!("{}", sample_to_json(sample));
+            }
+        }
+        Some(("scout", args)) => {
+            let what = match args.value_of("what") {
+                Some("peer") => WhatAmI::Peer.into(),
+                Some("router") => WhatAmI::Router.into(),
+                _ => WhatAmI::Peer | WhatAmI::Router,
+            };
+            let timeout = Duration::from_millis(args.value_of_t("timeout")?);
+            let receiver = zenoh::scout(what, config).res().await?;
+            let _ = async {
+                while let Ok(hello) = receiver.recv_async().await {
+                    println!("{}", hello_to_json(&hello));
+                }
+            }
+            .timeout(timeout)
+            .await;
+        }
+        _ => unreachable!("A subcommand is required"),
+    }
+    Ok(())
+}
+
+fn command() -> Command<'static> {
+    Command::new("zenoh")
+        .about("Puts, gets, subscribes to and scouts zenoh from the command line. The received samples are printed as line-delimited JSON.")
+        .version(GIT_VERSION)
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .args(&[
+            clap::arg!(-c --config [FILE] "The configuration file. Currently, this file must be a valid JSON5 or YAML file.")
+                .multiple_values(false)
+                .global(true),
+            clap::arg!(-m --mode [MODE] "The zenoh session mode (peer by default).")
+                .possible_values(["peer", "client"])
+                .multiple_values(false)
+                .global(true),
+            Arg::new("connect").short('e').long("connect").value_name("ENDPOINT").help(r"An endpoint to connect to.
+Repeat this option to connect to several endpoints.").takes_value(true).multiple_occurrences(true).global(true),
+            Arg::new("listen").short('l').long("listen").value_name("ENDPOINT").help(r"An endpoint to listen on.
+Repeat this option to listen on several endpoints.").takes_value(true).multiple_occurrences(true).global(true),
+            clap::arg!(--"no-multicast-scouting" "Disable the multicast-based scouting mechanism.").global(true),
+        ])
+        .subcommand(
+            Command::new("put")
+                .about("Puts a value on a key expression.")
+                .arg(clap::arg!(<KEYEXPR> "The key expression to put the value on."))
+                .arg(clap::arg!(<VALUE> "The value to put.")),
+        )
+        .subcommand(
+            Command::new("delete")
+                .about("Deletes the values of a key expression.")
+                .arg(clap::arg!(<KEYEXPR> "The key expression to delete.")),
+        )
+        .subcommand(
+            Command::new("get")
+                .about("Queries a selector and prints the replies.")
+                .arg(clap::arg!(<SELECTOR> "The selector to query."))
+                .arg(clap::arg!(-v --value [VALUE] "An optional value to put in the query."))
+                .arg(
+                    clap::arg!(-t --target [TARGET] "The target queryables of the query.")
+                        .possible_values(["BEST_MATCHING", "ALL", "ALL_COMPLETE", "COMPLETE_STORAGE"])
+                        .default_value("BEST_MATCHING"),
+                )
+                .arg(
+                    clap::arg!(-o --timeout [TIME] "The query timeout in milliseconds.")
+                        .default_value("10000"),
+                ),
+        )
+        .subcommand(
+            Command::new("sub")
+                .about("Subscribes to a key expression and prints the received samples until interrupted.")
+                .arg(clap::arg!(<KEYEXPR> "The key expression to subscribe to.")),
+        )
+        .subcommand(
+            Command::new("scout")
+                .about("Scouts the zenoh peers and routers and prints their hello messages.")
+                .arg(
+                    clap::arg!(-w --what [WHAT] "The kind of zenoh nodes to scout.")
+                        .possible_values(["peer", "router", "all"])
+                        .default_value("all"),
+                )
+                .arg(
+                    clap::arg!(-o --timeout [TIME] "The scouting duration in milliseconds.")
+                        .default_value("1000"),
+                ),
+        )
+}
+
+fn config_from_args(args: &ArgMatches) -> zenoh::Result<Config> {
+    let mut config = match args.value_of("config") {
+        Some(conf_file) => Config::from_file(conf_file)?,
+        None => Config::default(),
+    };
+    if let Some(mode) = args.value_of("mode") {
+        config
+            .set_mode(Some(mode.parse()?))
+            .map_err(|_| zenoh::Error::from(format!("Invalid mode: {mode}")))?;
+    }
+    if let Some(endpoints) = args.values_of("connect") {
+        config.connect.endpoints = endpoints.map(|e| e.parse()).collect::<zenoh::Result<_>>()?;
+    }
+    if let Some(endpoints) = args.values_of("listen") {
+        config.listen.endpoints = endpoints.map(|e| e.parse()).collect::<zenoh::Result<_>>()?;
+    }
+    if args.is_present("no-multicast-scouting") {
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    }
+    Ok(config)
+}
+
+fn value_to_json(value: &Value) -> serde_json::Value {
+    let payload = value.payload.contiguous();
+    match &value.encoding {
+        e if e.starts_with(KnownEncoding::AppJson)
+            || e.starts_with(KnownEncoding::AppInteger)
+            || e.starts_with(KnownEncoding::AppFloat) =>
+        {
+            serde_json::from_slice(&payload).unwrap_or_else(|_| json!(value.to_string()))
+        }
+        e if e.starts_with(KnownEncoding::TextPlain)
+            || e.starts_with(KnownEncoding::TextJson)
+            || e.starts_with(KnownEncoding::AppXWwwFormUrlencoded)
+            || e.starts_with(KnownEncoding::AppProperties) =>
+        {
+            json!(value.to_string())
+        }
+        _ => json!(b64_std_engine.encode(payload)),
+    }
+}
+
+fn sample_to_json(sample: Sample) -> serde_json::Value {
+    json!({
+        "key": sample.key_expr.as_str(),
+        "kind": sample.kind.to_string(),
+        "value": value_to_json(&sample.value),
+        "encoding": sample.value.encoding.to_string(),
+        "timestamp": sample.timestamp.map(|ts| ts.to_string()),
+    })
+}
+
+fn reply_to_json(reply: Reply) -> serde_json::Value {
+    match reply.sample {
+        Ok(sample) => sample_to_json(sample),
+        Err(err) => json!({
+            "error": value_to_json(&err),
+            "encoding": err.encoding.to_string(),
+        }),
+    }
+}
+
+fn hello_to_json(hello: &Hello) -> serde_json::Value {
+    json!({
+        "zid": hello.zid.to_string(),
+        "whatami": hello.whatami.to_str(),
+        "locators": hello.locators.iter().map(|l| l.to_string()).collect::<Vec<_>>(),
+    })
+}
+
+#[test]
+fn samples_to_json() {
+    let sample = Sample::new(
+        KeyExpr::try_from("demo/example").unwrap(),
+        Value::from(r#"{"a": 1}"#).encoding(KnownEncoding::AppJson.into()),
+    );
+    assert_eq!(
+        sample_to_json(sample),
+        json!({"key": "demo/example", "kind": "PUT", "value": {"a": 1}, "encoding": "application/json", "timestamp": null})
+    );
+
+    let sample = Sample::new(
+        KeyExpr::try_from("demo/example").unwrap(),
+        "hello \"world\"",
+    );
+    assert_eq!(sample_to_json(sample)["value"], json!("hello \"world\""));
+
+    let sample = Sample::new(
+        KeyExpr::try_from("demo/example").unwrap(),
+        Value::from(vec![0xff_u8, 0x00]),
+    );
+    assert_eq!(sample_to_json(sample)["value"], json!("/wA="));
+
+    let args = command()
+        .try_get_matches_from([
+            "zenoh",
+            "get",
+            "demo/**",
+            "-e",
+            "tcp/127.0.0.1:7447",
+            "-m",
+            "client",
+        ])
+        .unwrap();
+    let config = config_from_args(&args).unwrap();
+    assert_eq!(*config.mode(), Some(WhatAmI::Client));
+    assert_eq!(config.connect.endpoints.len(), 1);
+    assert!(command()
+        .try_get_matches_from(["zenoh", "put", "demo/example"])
+        .is_err());
+}
@@ -23,7 +23,7 @@ async fn main() {
     // Initiate logging
     env_logger::init();
 
-    let (config, key_expr, value, history, prefix) = parse_args();
+    let (config, key_expr, value, history, prefix, ttl, spill_dir) = parse_args();
 
     println!("Opening session...");
     let session = zenoh::open(config).res().await.unwrap();
@@ -35,6 +35,12 @@ async fn main() {
     if let Some(prefix) = prefix {
         publication_cache_builder = publication_cache_builder.queryable_prefix(prefix);
     }
+    if let Some(ttl) = ttl {
+        publication_cache_builder = publication_cache_builder.ttl(ttl);
+    }
+    if let Some(spill_dir) = spill_dir {
+        publication_cache_builder = publication_cache_builder.spill_to(spill_dir);
+    }
     let _publication_cache = publication_cache_builder.res().await.unwrap();
 
     for idx in 0..u32::MAX {
@@ -45,7 +51,15 @@ async fn main() {
     }
 }
 
-fn parse_args() -> (Config, String, String, usize, Option<String>) {
+fn parse_args() -> (
+    Config,
+    String,
+    String,
+    usize,
+    Option<String>,
+    Option<Duration>,
+    Option<String>,
+) {
     let args = App::new("zenoh-ext pub cache example")
         .arg(
             Arg::from_usage("-m, --mode=[MODE] 'The zenoh session mode (peer by default).")
@@ -72,6 +86,12 @@ fn parse_args() -> (Config, String, String, usize, Option<String>) {
         .arg(Arg::from_usage(
             "-x, --prefix=[STRING] 'An optional queryable prefix'",
         ))
+        .arg(Arg::from_usage(
+            "-t, --ttl=[SECONDS] 'An optional time to live of the cached publications'",
+        ))
+        .arg(Arg::from_usage(
+            "-s, --spill=[DIR] 'An optional directory where to save the cache content'",
+        ))
         .arg(Arg::from_usage(
             "-c, --config=[FILE]      'A configuration file.'",
         ))
@@ -114,6 +134,10 @@ fn parse_args() -> (Config, String, String, usize, Option<String>) {
     let value = args.value_of("value").unwrap().to_string();
     let history: usize = args.value_of("history").unwrap().parse().unwrap();
     let prefix = args.value_of("prefix").map(String::from);
+    let ttl = args
+        .value_of("ttl")
+        .map(|ttl| Duration::from_secs_f64(ttl.parse().unwrap()));
+    let spill_dir = args.value_of("spill").map(String::from);
 
-    (config, key_expr, value, history, prefix)
+    (config, key_expr, value, history, prefix, ttl, spill_dir)
 }
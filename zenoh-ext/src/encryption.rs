@@ -0,0 +1,339 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::{Aead, NewAead, Payload};
+use aes_gcm::Aes256Gcm;
+use rand::RngCore;
+use std::convert::TryInto;
+use zenoh::prelude::r#async::*;
+use zenoh::publication::{Publication, Publisher};
+use zenoh::subscriber::FlumeSubscriber;
+use zenoh_result::{bail, zerror, ZResult};
+
+const NONCE_SIZE: usize = 12;
+const KEY_ID_PARAM: &str = ";encrypted=";
+
+struct EncryptionKey {
+    key_expr: OwnedKeyExpr,
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl EncryptionKey {
+    fn encrypt(&self, key_expr: &keyexpr, value: Value) -> ZResult<Value> {
+        let suffix = value.encoding.suffix().as_bytes();
+        let suffix_len = match u8::try_from(suffix.len()) {
+            Ok(len) => len,
+            Err(_) => bail!(
+                "Failed to encrypt value on {}: its encoding suffix is longer than {} bytes",
+                key_expr,
+                u8::MAX
+            ),
+        };
+        let payload = value.payload.contiguous();
+        let mut msg = Vec::with_capacity(2 + suffix.len() + payload.len());
+        msg.push(*value.encoding.prefix() as u8);
+        msg.push(suffix_len);
+        msg.extend_from_slice(suffix);
+        msg.extend_from_slice(&payload);
+
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let encrypted = self
+            .cipher
+            .encrypt(
+                GenericArray::from_slice(&nonce),
+                Payload {
+                    msg: &msg,
+                    aad: key_expr.as_bytes(),
+                },
+            )
+            .map_err(|e| zerror!("Failed to encrypt value on {}: {}", key_expr, e))?;
+        let mut bytes = nonce.to_vec();
+        bytes.extend(encrypted);
+        let encoding =
+            Encoding::APP_OCTET_STREAM.with_suffix(format!("{KEY_ID_PARAM}{}", self.id))?;
+        Ok(Value::from(bytes).encoding(encoding))
+    }
+}
+
+/// The symmetric keys used to encrypt and decrypt the payloads published on some key expressions.
+///
+/// Payloads are encrypted with AES-256-GCM, bound to the key expression they are published on.
+/// The encoding of an encrypted value is `application/octet-stream;encrypted=<key id>`,
+/// its original encoding being encrypted along with its payload. The routers forwarding
+/// encrypted values thus never see their content, nor can they move them to other key expressions.
+#[derive(Default)]
+pub struct EncryptionKeys {
+    keys: Vec<EncryptionKey>,
+}
+
+impl EncryptionKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a 256 bits key identified by `id`, used for the values published on the key expressions
+    /// included in `key_expr`. The first added key including a key expression is used to encrypt its values.
+    pub fn key<TryIntoKeyExpr>(
+        mut self,
+        key_expr: TryIntoKeyExpr,
+        id: &str,
+        key: [u8; 32],
+    ) -> ZResult<Self>
+    where
+        TryIntoKeyExpr: TryInto<OwnedKeyExpr>,
+        <TryIntoKeyExpr as TryInto<OwnedKeyExpr>>::Error: Into<zenoh_result::Error>,
+    {
+        if id.is_empty() || id.len() + KEY_ID_PARAM.len() > u8::MAX as usize {
+            bail!("Invalid encryption key id: {:?}", id);
+        }
+        if self.keys.iter().any(|k| k.id == id) {
+            bail!("Duplicated encryption key id: {}", id);
+        }
+        self.keys.push(EncryptionKey {
+            key_expr: key_expr.try_into().map_err(Into::into)?,
+            id: id.to_string(),
+            cipher: Aes256Gcm::new(GenericArray::from_slice(&key)),
+        });
+        Ok(self)
+    }
+
+    fn key_for(&self, key_expr: &keyexpr) -> ZResult<&EncryptionKey> {
+        self.keys
+            .iter()
+            .find(|k| k.key_expr.includes(key_expr))
+            .ok_or_else(|| zerror!("No encryption key for {}", key_expr).into())
+    }
+
+    /// Encrypts the given value, to be published on the given key expression.
+    pub fn encrypt(&self, key_expr: &keyexpr, value: Value) -> ZResult<Value> {
+        self.key_for(key_expr)?.encrypt(key_expr, value)
+    }
+
+    /// Returns the id of the key the given value was encrypted with, `None` if it is not encrypted.
+    pub fn key_id(value: &Value) -> Option<&str> {
+        match value.encoding.prefix() {
+            KnownEncoding::AppOctetStream => value.encoding.suffix().strip_prefix(KEY_ID_PARAM),
+            _ => None,
+        }
+    }
+
+    /// Decrypts the given value, published on the given key expression.
+    pub fn decrypt(&self, key_expr: &keyexpr, value: Value) -> ZResult<Value> {
+        let id = match Self::key_id(&value) {
+            Some(id) => id,
+            None => bail!("Value on {} is not encrypted", key_expr),
+        };
+        let key = match self.keys.iter().find(|k| k.id == id) {
+            Some(key) => key,
+            None => bail!("Unknown encryption key {} for {}", id, key_expr),
+        };
+        if !key.key_expr.includes(key_expr) {
+            bail!("Encryption key {} is not valid for {}", id, key_expr);
+        }
+        let bytes = value.payload.contiguous();
+        if bytes.len() < NONCE_SIZE {
+            bail!("Invalid encrypted value on {}", key_expr);
+        }
+        let (nonce, encrypted) = bytes.split_at(NONCE_SIZE);
+        let msg = key
+            .cipher
+            .decrypt(
+                GenericArray::from_slice(nonce),
+                Payload {
+                    msg: encrypted,
+                    aad: key_expr.as_bytes(),
+                },
+            )
+            .map_err(|e| zerror!("Failed to decrypt value on {}: {}", key_expr, e))?;
+        let (prefix, suffix_len) = match msg.as_slice() {
+            [prefix, suffix_len, ..] => (*prefix, *suffix_len as usize),
+            _ => bail!("Invalid encrypted value on {}", key_expr),
+        };
+        if msg.len() < 2 + suffix_len {
+            bail!("Invalid encrypted value on {}", key_expr);
+        }
+        let suffix = std::str::from_utf8(&msg[2..2 + suffix_len])
+            .map_err(|e| zerror!("Invalid encrypted value on {}: {}", key_expr, e))?;
+        let encoding = Encoding::new(prefix, suffix.to_string())?;
+        Ok(Value::from(msg[2 + suffix_len..].to_vec()).encoding(encoding))
+    }
+}
+
+/// A [`Publisher`] encrypting the values it puts with the [`EncryptionKeys`] including its key expression.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+/// use zenoh_ext::{DecryptingSubscriber, EncryptedPublisher, EncryptionKeys};
+///
+/// let keys = || EncryptionKeys::new().key("demo/**", "demo-2023", [7u8; 32]).unwrap();
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let subscriber = session.declare_subscriber("demo/example").res().await.unwrap();
+/// let subscriber = DecryptingSubscriber::new(subscriber, keys());
+/// let publisher = session.declare_publisher("demo/example").res().await.unwrap();
+/// let publisher = EncryptedPublisher::new(publisher, keys()).unwrap();
+/// publisher.put("secret").unwrap().res().await.unwrap();
+///
+/// let sample = subscriber.recv_async().await.unwrap();
+/// assert_eq!(sample.value.to_string(), "secret");
+/// # })
+/// ```
+pub struct EncryptedPublisher<'a> {
+    publisher: Publisher<'a>,
+    keys: EncryptionKeys,
+    key: usize,
+}
+
+impl<'a> EncryptedPublisher<'a> {
+    /// Fails if none of the given keys includes the key expression of the publisher.
+    pub fn new(publisher: Publisher<'a>, keys: EncryptionKeys) -> ZResult<Self> {
+        let key = match keys
+            .keys
+            .iter()
+            .position(|k| k.key_expr.includes(publisher.key_expr()))
+        {
+            Some(key) => key,
+            None => bail!("No encryption key for {}", publisher.key_expr()),
+        };
+        Ok(EncryptedPublisher {
+            publisher,
+            keys,
+            key,
+        })
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'a> {
+        self.publisher.key_expr()
+    }
+
+    /// The id of the key the values are encrypted with.
+    pub fn key_id(&self) -> &str {
+        &self.keys.keys[self.key].id
+    }
+
+    /// Encrypts and puts data.
+    pub fn put<IntoValue>(&self, value: IntoValue) -> ZResult<Publication>
+    where
+        IntoValue: Into<Value>,
+    {
+        let value = self.keys.keys[self.key].encrypt(self.publisher.key_expr(), value.into())?;
+        Ok(self.publisher.put(value))
+    }
+
+    /// Deletes data, which carries no payload to encrypt.
+    pub fn delete(&self) -> Publication {
+        self.publisher.delete()
+    }
+}
+
+/// A subscriber decrypting the values it receives with the [`EncryptionKeys`] they were encrypted with.
+///
+/// The samples whose value is not encrypted with one of its keys, or whose decryption fails,
+/// are reported as errors.
+pub struct DecryptingSubscriber<'a> {
+    subscriber: FlumeSubscriber<'a>,
+    keys: EncryptionKeys,
+}
+
+impl<'a> DecryptingSubscriber<'a> {
+    pub fn new(subscriber: FlumeSubscriber<'a>, keys: EncryptionKeys) -> Self {
+        DecryptingSubscriber { subscriber, keys }
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        self.subscriber.key_expr()
+    }
+
+    fn decrypt(&self, mut sample: Sample) -> ZResult<Sample> {
+        if sample.kind == SampleKind::Put {
+            sample.value = self.keys.decrypt(&sample.key_expr, sample.value)?;
+        }
+        Ok(sample)
+    }
+
+    pub fn recv(&self) -> ZResult<Sample> {
+        let sample = self.subscriber.recv().map_err(|e| zerror!("{}", e))?;
+        self.decrypt(sample)
+    }
+
+    pub async fn recv_async(&self) -> ZResult<Sample> {
+        let sample = self
+            .subscriber
+            .recv_async()
+            .await
+            .map_err(|e| zerror!("{}", e))?;
+        self.decrypt(sample)
+    }
+
+    pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
+        self.subscriber.undeclare()
+    }
+}
+
+#[test]
+fn encryption() {
+    let keys = EncryptionKeys::new()
+        .key("demo/**", "demo-2023", [7u8; 32])
+        .unwrap()
+        .key("other/**", "other-2023", [8u8; 32])
+        .unwrap();
+    let key_expr = keyexpr::new("demo/example").unwrap();
+
+    // Round-trip, the encoding being encrypted along with the payload
+    let value =
+        Value::from("secret").encoding(Encoding::TEXT_PLAIN.with_suffix(";charset=utf-8").unwrap());
+    let encrypted = keys.encrypt(key_expr, value.clone()).unwrap();
+    assert_eq!(EncryptionKeys::key_id(&encrypted), Some("demo-2023"));
+    assert_ne!(encrypted.payload.contiguous(), value.payload.contiguous());
+    let decrypted = keys.decrypt(key_expr, encrypted.clone()).unwrap();
+    assert_eq!(decrypted.encoding, value.encoding);
+    assert_eq!(decrypted.to_string(), "secret");
+    assert!(keys.decrypt(key_expr, value).is_err());
+
+    // Tampered ciphertext
+    let mut bytes = encrypted.payload.contiguous().to_vec();
+    *bytes.last_mut().unwrap() ^= 1;
+    let tampered = Value::from(bytes).encoding(encrypted.encoding.clone());
+    assert!(keys.decrypt(key_expr, tampered).is_err());
+
+    // Unknown key id
+    let unknown = EncryptionKeys::new()
+        .key("demo/**", "demo-2024", [7u8; 32])
+        .unwrap();
+    assert!(unknown.decrypt(key_expr, encrypted.clone()).is_err());
+
+    // Key not valid for the key expression, including a value moved to another key expression
+    assert!(keys
+        .encrypt(
+            keyexpr::new("unknown/example").unwrap(),
+            Value::from("secret")
+        )
+        .is_err());
+    let moved =
+        Value::from(encrypted.payload.contiguous().to_vec()).encoding(encrypted.encoding.clone());
+    assert!(keys
+        .decrypt(keyexpr::new("other/example").unwrap(), moved.clone())
+        .is_err());
+    assert!(keys
+        .decrypt(keyexpr::new("demo/moved").unwrap(), moved)
+        .is_err());
+
+    // Encoding suffixes too long to be encrypted, which `Encoding::new` would reject
+    let encoding = Encoding::WithSuffix(KnownEncoding::TextPlain, "x".repeat(256).into());
+    let value = Value::from("secret").encoding(encoding);
+    assert!(keys.encrypt(key_expr, value).is_err());
+}
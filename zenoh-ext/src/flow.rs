@@ -0,0 +1,528 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Building blocks for the dataflow frameworks built on zenoh.
+//!
+//! An [`Operator`] has typed [`Input`] and [`Output`] ports bound to key expressions, and a callback
+//! activated when its inputs receive samples, or periodically. The subscriptions of the inputs are
+//! multiplexed by the operator, which schedules the activations with the earliest release first,
+//! and reports the ones completing after their deadline.
+//!
+//! # Examples
+//! ```
+//! # async_std::task::block_on(async {
+//! use zenoh::prelude::r#async::*;
+//! use zenoh_ext::flow::{Operator, Trigger};
+//!
+//! let session = zenoh::open(config::peer()).res().await.unwrap();
+//! let operator = Operator::new(&session).deadline(std::time::Duration::from_millis(10));
+//! let celsius = operator.input::<f64, _>("demo/celsius").unwrap();
+//! let fahrenheit = operator.output::<f64, _>("demo/fahrenheit").unwrap();
+//! let subscriber = session.declare_subscriber("demo/fahrenheit").res().await.unwrap();
+//!
+//! session.put("demo/celsius", "20.0").encoding(KnownEncoding::AppJson).res().await.unwrap();
+//! operator
+//!     .run_async(|ctx| {
+//!         if ctx.trigger() == Trigger::Input(celsius.id()) {
+//!             while let Some(sample) = celsius.try_recv() {
+//!                 if let Some(celsius) = sample?.value {
+//!                     fahrenheit.put(&(celsius * 1.8 + 32.0))?;
+//!                 }
+//!                 ctx.stop();
+//!             }
+//!         }
+//!         Ok(())
+//!     })
+//!     .await
+//!     .unwrap();
+//! assert_eq!(subscriber.recv_async().await.unwrap().value.to_string(), "68.0");
+//! # })
+//! ```
+use super::{TypedEncoding, TypedPublisher, TypedSample};
+use async_std::prelude::FutureExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::convert::TryInto;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh::subscriber::Subscriber;
+use zenoh::Session;
+use zenoh_core::SyncResolve;
+use zenoh_result::{zerror, ZResult};
+
+const DEFAULT_QUEUE_SIZE: usize = 256;
+
+/// The identifier of an input port, unique within its [`Operator`].
+pub type PortId = usize;
+
+/// What activated an [`Operator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// The reception of a sample by an input port.
+    Input(PortId),
+    /// The period of the operator elapsed.
+    Tick,
+}
+
+/// The context of an activation of an [`Operator`].
+#[derive(Debug)]
+pub struct Context {
+    trigger: Trigger,
+    release: Instant,
+    deadline: Option<Instant>,
+    stopped: bool,
+}
+
+impl Context {
+    pub fn trigger(&self) -> Trigger {
+        self.trigger
+    }
+
+    /// The instant the activation was released at, i.e. the reception of the triggering sample or the tick.
+    pub fn release(&self) -> Instant {
+        self.release
+    }
+
+    /// The instant the activation should complete before, if the operator has a deadline.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// The time left before the deadline, zero if it is missed.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Stops the operator once this activation completes.
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+}
+
+/// An activation of an [`Operator`] which completed after its deadline.
+#[derive(Debug, Clone, Copy)]
+pub struct DeadlineMiss {
+    pub trigger: Trigger,
+    pub deadline: Instant,
+    /// The time the activation completed after its deadline.
+    pub overrun: Duration,
+}
+
+/// An input port of an [`Operator`], deserializing the samples received on its key expression into a `T`.
+///
+/// The samples are queued until they are taken by the activations of the operator, those received
+/// while the queue is full being dropped.
+pub struct Input<'a, T> {
+    id: PortId,
+    subscriber: Subscriber<'a, ()>,
+    samples: flume::Receiver<Sample>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: DeserializeOwned> Input<'a, T> {
+    pub fn id(&self) -> PortId {
+        self.id
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        self.subscriber.key_expr()
+    }
+
+    /// Takes the next queued sample without waiting, failing if its value cannot be deserialized.
+    pub fn try_recv(&self) -> Option<ZResult<TypedSample<T>>> {
+        let sample = self.samples.try_recv().ok()?;
+        Some(
+            TypedSample::try_from(sample).map_err(|(sample, e)| {
+                zerror!("Invalid value on {}: {}", sample.key_expr, e).into()
+            }),
+        )
+    }
+}
+
+/// An output port of an [`Operator`], putting values of type `T` on its key expression.
+pub struct Output<'a, T> {
+    publisher: TypedPublisher<'a, T>,
+}
+
+impl<'a, T: Serialize> Output<'a, T> {
+    pub fn key_expr(&self) -> &KeyExpr<'a> {
+        self.publisher.key_expr()
+    }
+
+    /// Serializes and puts a value, without waiting for the next activation.
+    pub fn put(&self, value: &T) -> ZResult<()> {
+        self.publisher.put(value)?.res_sync()
+    }
+
+    /// Deletes data, which carries no value to serialize.
+    pub fn delete(&self) -> ZResult<()> {
+        self.publisher.delete().res_sync()
+    }
+}
+
+// The reception of a sample by an input port, with its instant
+type Notification = (PortId, Instant);
+
+type DeadlineMissCallback<'a> = Box<dyn FnMut(DeadlineMiss) + Send + 'a>;
+
+/// An operator of a dataflow, whose callback is activated when its [`Input`]s receive samples,
+/// or periodically.
+///
+/// The inputs and outputs are declared with [`input`](Operator::input) and
+/// [`output`](Operator::output), and moved into the callback passed to [`run`](Operator::run).
+/// Each sample received by an input releases an activation, even if the sample was already taken
+/// by a previous one.
+pub struct Operator<'a> {
+    session: &'a Session,
+    next_port: AtomicUsize,
+    notifier: flume::Sender<Notification>,
+    notifications: flume::Receiver<Notification>,
+    queue_size: usize,
+    encoding: TypedEncoding,
+    period: Option<Duration>,
+    deadline: Option<Duration>,
+    on_deadline_miss: Option<DeadlineMissCallback<'a>>,
+}
+
+impl<'a> Operator<'a> {
+    /// The inputs queue 256 samples by default, and the outputs serialize their values in JSON.
+    pub fn new(session: &'a Session) -> Self {
+        let (notifier, notifications) = flume::unbounded();
+        Operator {
+            session,
+            next_port: AtomicUsize::new(0),
+            notifier,
+            notifications,
+            queue_size: DEFAULT_QUEUE_SIZE,
+            encoding: TypedEncoding::default(),
+            period: None,
+            deadline: None,
+            on_deadline_miss: None,
+        }
+    }
+
+    /// Changes the number of samples queued by the inputs declared afterwards.
+    pub fn queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size;
+        self
+    }
+
+    /// Changes the format the values of the outputs declared afterwards are serialized with.
+    pub fn encoding(mut self, encoding: TypedEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Activates the operator with [`Trigger::Tick`] every `period`, the ticks released while
+    /// an activation overruns the period being skipped.
+    pub fn period(mut self, period: Duration) -> Self {
+        self.period = Some(period);
+        self
+    }
+
+    /// Sets the relative deadline of the activations, from their release.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the callback called with the activations completing after their deadline,
+    /// which are logged if there is none.
+    pub fn on_deadline_miss<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(DeadlineMiss) + Send + 'a,
+    {
+        self.on_deadline_miss = Some(Box::new(callback));
+        self
+    }
+
+    /// Declares an input port receiving the samples of `key_expr`.
+    pub fn input<'b, T, TryIntoKeyExpr>(&self, key_expr: TryIntoKeyExpr) -> ZResult<Input<'a, T>>
+    where
+        T: DeserializeOwned,
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        let id = self.next_port.fetch_add(1, Ordering::Relaxed);
+        let (sender, samples) = flume::bounded(self.queue_size);
+        let notifications = self.notifier.clone();
+        let subscriber = self
+            .session
+            .declare_subscriber(key_expr)
+            .callback(move |sample| {
+                let key_expr = sample.key_expr.clone();
+                if sender.try_send(sample).is_err() {
+                    log::warn!("Input {} queue full, dropping sample of {}", id, key_expr);
+                    return;
+                }
+                let _ = notifications.send((id, Instant::now()));
+            })
+            .res_sync()?;
+        Ok(Input {
+            id,
+            subscriber,
+            samples,
+            _type: PhantomData,
+        })
+    }
+
+    /// Declares an output port putting values on `key_expr`.
+    pub fn output<'b, T, TryIntoKeyExpr>(&self, key_expr: TryIntoKeyExpr) -> ZResult<Output<'a, T>>
+    where
+        T: Serialize,
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        'b: 'a,
+    {
+        let publisher = self.session.declare_publisher(key_expr).res_sync()?;
+        Ok(Output {
+            publisher: TypedPublisher::new(publisher).encoding(self.encoding),
+        })
+    }
+
+    // Runs an activation, returning false if the callback stopped the operator
+    fn activate<F>(&mut self, trigger: Trigger, release: Instant, callback: &mut F) -> ZResult<bool>
+    where
+        F: FnMut(&mut Context) -> ZResult<()>,
+    {
+        let mut ctx = Context {
+            trigger,
+            release,
+            deadline: self.deadline.map(|deadline| release + deadline),
+            stopped: false,
+        };
+        callback(&mut ctx)?;
+        if let Some(deadline) = ctx.deadline {
+            let overrun = Instant::now().saturating_duration_since(deadline);
+            if !overrun.is_zero() {
+                let miss = DeadlineMiss {
+                    trigger,
+                    deadline,
+                    overrun,
+                };
+                match &mut self.on_deadline_miss {
+                    Some(on_deadline_miss) => on_deadline_miss(miss),
+                    None => log::warn!("Operator missed its deadline: {:?}", miss),
+                }
+            }
+        }
+        Ok(!ctx.stopped)
+    }
+
+    /// Runs the operator until its callback stops it or fails, blocking the current thread.
+    pub fn run<F>(mut self, mut callback: F) -> ZResult<()>
+    where
+        F: FnMut(&mut Context) -> ZResult<()>,
+    {
+        let mut scheduler = Scheduler::new(self.period);
+        loop {
+            let (trigger, release) = match scheduler.next(&self.notifications) {
+                Some(activation) => activation,
+                None => match scheduler.next_tick {
+                    Some(tick) => match self.notifications.recv_deadline(tick) {
+                        Ok(notification) => scheduler.release(notification),
+                        Err(_) => continue,
+                    },
+                    None => {
+                        let notification =
+                            self.notifications.recv().map_err(|e| zerror!("{}", e))?;
+                        scheduler.release(notification)
+                    }
+                },
+            };
+            if !self.activate(trigger, release, &mut callback)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Runs the operator until its callback stops it or fails.
+    pub async fn run_async<F>(mut self, mut callback: F) -> ZResult<()>
+    where
+        F: FnMut(&mut Context) -> ZResult<()>,
+    {
+        let mut scheduler = Scheduler::new(self.period);
+        loop {
+            let (trigger, release) = match scheduler.next(&self.notifications) {
+                Some(activation) => activation,
+                None => {
+                    let notification = self.notifications.recv_async();
+                    match scheduler.next_tick {
+                        Some(tick) => {
+                            let timeout = tick.saturating_duration_since(Instant::now());
+                            match notification.timeout(timeout).await {
+                                Ok(notification) => {
+                                    scheduler.release(notification.map_err(|e| zerror!("{}", e))?)
+                                }
+                                Err(_) => continue,
+                            }
+                        }
+                        None => {
+                            scheduler.release(notification.await.map_err(|e| zerror!("{}", e))?)
+                        }
+                    }
+                }
+            };
+            if !self.activate(trigger, release, &mut callback)? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// Chooses the next activation among the ticks and the notifications of the inputs,
+// the earliest released first
+struct Scheduler {
+    period: Option<Duration>,
+    next_tick: Option<Instant>,
+    pending: Option<Notification>,
+}
+
+impl Scheduler {
+    fn new(period: Option<Duration>) -> Self {
+        Scheduler {
+            period,
+            next_tick: period.map(|period| Instant::now() + period),
+            pending: None,
+        }
+    }
+
+    // Returns the next activation if one is already released
+    fn next(
+        &mut self,
+        notifications: &flume::Receiver<Notification>,
+    ) -> Option<(Trigger, Instant)> {
+        if self.pending.is_none() {
+            self.pending = notifications.try_recv().ok();
+        }
+        let now = Instant::now();
+        match (self.next_tick, self.pending) {
+            (Some(tick), pending)
+                if tick <= now && pending.map_or(true, |(_, release)| tick <= release) =>
+            {
+                Some(self.tick(tick, now))
+            }
+            (_, Some(notification)) => {
+                self.pending = None;
+                Some((Trigger::Input(notification.0), notification.1))
+            }
+            _ => None,
+        }
+    }
+
+    // Returns the activation of a notification received while waiting, unless the tick is due first
+    fn release(&mut self, notification: Notification) -> (Trigger, Instant) {
+        match self.next_tick {
+            Some(tick) if tick <= notification.1 => {
+                self.pending = Some(notification);
+                self.tick(tick, Instant::now())
+            }
+            _ => (Trigger::Input(notification.0), notification.1),
+        }
+    }
+
+    fn tick(&mut self, tick: Instant, now: Instant) -> (Trigger, Instant) {
+        if let Some(period) = self.period {
+            let mut next_tick = tick + period;
+            while next_tick <= now {
+                next_tick += period;
+            }
+            self.next_tick = Some(next_tick);
+        }
+        (Trigger::Tick, tick)
+    }
+}
+
+#[test]
+fn operator_activations() {
+    use std::sync::{Arc, Mutex};
+    use zenoh::config::Config;
+
+    let mut config = Config::default();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = zenoh::open(config).res_sync().unwrap();
+    let results = session
+        .declare_subscriber("test/flow/sum")
+        .res_sync()
+        .unwrap();
+
+    let operator = Operator::new(&session);
+    let terms = operator.input::<i64, _>("test/flow/term").unwrap();
+    let sum = operator.output::<i64, _>("test/flow/sum").unwrap();
+    for term in [1, 2, 3] {
+        session
+            .put(
+                "test/flow/term",
+                TypedEncoding::Json.serialize(&term).unwrap(),
+            )
+            .res_sync()
+            .unwrap();
+    }
+    session.put("test/flow/term", "NaN").res_sync().unwrap();
+    let mut total = 0;
+    let mut errors = 0;
+    operator
+        .run(|ctx| {
+            assert_eq!(ctx.trigger(), Trigger::Input(terms.id()));
+            assert!(ctx.deadline().is_none());
+            match terms.try_recv() {
+                Some(Ok(term)) => total += term.value.unwrap(),
+                Some(Err(_)) => {
+                    errors += 1;
+                    ctx.stop();
+                }
+                None => (),
+            }
+            sum.put(&total)
+        })
+        .unwrap();
+    assert_eq!((total, errors), (6, 1));
+    let last = std::iter::from_fn(|| results.try_recv().ok())
+        .last()
+        .unwrap();
+    assert_eq!(TypedEncoding::deserialize::<i64>(&last.value).unwrap(), 6);
+    drop((terms, sum, results));
+
+    let misses = Arc::new(Mutex::new(Vec::new()));
+    let operator = Operator::new(&session)
+        .period(Duration::from_millis(50))
+        .deadline(Duration::from_millis(20))
+        .on_deadline_miss({
+            let misses = misses.clone();
+            move |miss| misses.lock().unwrap().push(miss)
+        });
+    let mut ticks = 0;
+    async_std::task::block_on(operator.run_async(|ctx| {
+        assert_eq!(ctx.trigger(), Trigger::Tick);
+        ticks += 1;
+        if ticks % 2 == 0 {
+            std::thread::sleep(Duration::from_millis(30));
+        }
+        if ticks == 4 {
+            ctx.stop();
+        }
+        Ok(())
+    }))
+    .unwrap();
+    let misses = misses.lock().unwrap();
+    assert_eq!(misses.len(), 2);
+    assert!(misses
+        .iter()
+        .all(|miss| miss.trigger == Trigger::Tick && miss.overrun >= Duration::from_millis(2)));
+
+    session.close().res_sync().unwrap();
+}
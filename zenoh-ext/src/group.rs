@@ -13,6 +13,12 @@
 //
 
 //! To manage groups and group memeberships
+//!
+//! Each member of a group declares a liveliness token and a queryable serving its [`Member`] information
+//! on `zenoh/ext/net/group/<group>/<member>`. The members get the information of the members already
+//! in the group when joining it, and then follow the liveliness tokens of the group to maintain their view:
+//! a member leaving the group is removed from the view right away, while a member losing its liveliness
+//! (e.g. on a network partition) is kept in the view until its lease expires.
 
 use async_std::sync::Mutex;
 use async_std::task::JoinHandle;
@@ -25,19 +31,22 @@ use std::convert::TryInto;
 use std::ops::Add;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use zenoh::liveliness::LivelinessToken;
 use zenoh::prelude::r#async::*;
 use zenoh::publication::Publisher;
 use zenoh::query::ConsolidationMode;
+use zenoh::queryable::{Query, Queryable};
+use zenoh::subscriber::FlumeSubscriber;
 use zenoh::Error as ZError;
 use zenoh::Result as ZResult;
 use zenoh::Session;
+use zenoh_core::SyncResolve;
 use zenoh_result::bail;
 use zenoh_sync::Condition;
 
 const GROUP_PREFIX: &str = "zenoh/ext/net/group";
 const EVENT_POSTFIX: &str = "evt";
-const VIEW_REFRESH_LEASE_RATIO: f32 = 0.75f32;
-const DEFAULT_LEASE: Duration = Duration::from_secs(18);
+const DEFAULT_LEASE: Duration = Duration::from_secs(3);
 const DEFAULT_PRIORITY: Priority = Priority::DataHigh;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -60,16 +69,18 @@ pub struct NewLeaderEvent {
     pub mid: OwnedKeyExpr,
 }
 
+/// The view of the group after a change of its membership.
 #[derive(Serialize, Deserialize, Debug)]
-struct KeepAliveEvent {
-    pub mid: OwnedKeyExpr,
+pub struct ViewChangeEvent {
+    /// The identifier of the view, incremented on each change of the membership.
+    pub id: u64,
+    /// The identifiers of the members of the group, the local member included.
+    pub members: Vec<OwnedKeyExpr>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 enum GroupNetEvent {
-    Join(JoinEvent),
     Leave(LeaveEvent),
-    KeepAlive(KeepAliveEvent),
 }
 
 /// Events exposed to the user to be informed for relevant
@@ -80,6 +91,7 @@ pub enum GroupEvent {
     Leave(LeaveEvent),
     LeaseExpired(LeaseExpiredEvent),
     NewLeader(NewLeaderEvent),
+    ViewChange(ViewChangeEvent),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -92,9 +104,7 @@ pub enum MemberLiveliness {
 pub struct Member {
     mid: OwnedKeyExpr,
     info: Option<String>,
-    liveliness: MemberLiveliness,
     lease: Duration,
-    refresh_ratio: f32,
     #[serde(skip)]
     priority: Priority,
 }
@@ -112,9 +122,7 @@ impl Member {
         Ok(Member {
             mid,
             info: None,
-            liveliness: MemberLiveliness::Auto,
             lease: DEFAULT_LEASE,
-            refresh_ratio: VIEW_REFRESH_LEASE_RATIO,
             priority: DEFAULT_PRIORITY,
         })
     }
@@ -131,18 +139,19 @@ impl Member {
         self
     }
 
+    /// The time the member is kept in the view of the other members after losing its liveliness.
     pub fn lease(mut self, d: Duration) -> Self {
         self.lease = d;
         self
     }
 
-    pub fn liveliness(mut self, l: MemberLiveliness) -> Self {
-        self.liveliness = l;
+    #[deprecated = "the liveliness of the members is asserted by their liveliness token"]
+    pub fn liveliness(self, _l: MemberLiveliness) -> Self {
         self
     }
 
-    pub fn refresh_ratio(mut self, r: f32) -> Self {
-        self.refresh_ratio = r;
+    #[deprecated = "the liveliness of the members is asserted by their liveliness token"]
+    pub fn refresh_ratio(self, _r: f32) -> Self {
         self
     }
 
@@ -152,22 +161,88 @@ impl Member {
     }
 }
 
+struct View {
+    id: u64,
+    // The other members, with the expiration of the lease of those which lost their liveliness
+    members: HashMap<OwnedKeyExpr, (Member, Option<Instant>)>,
+    leader: OwnedKeyExpr,
+}
+
+impl View {
+    // Records a change of the membership, returning the events notifying it
+    fn changed(&mut self, local_member: &Member) -> Vec<GroupEvent> {
+        self.id += 1;
+        let mut members: Vec<OwnedKeyExpr> = self.members.keys().cloned().collect();
+        members.push(local_member.mid.clone());
+        members.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        let mut events = vec![];
+        let leader = members.last().unwrap();
+        if *leader != self.leader {
+            self.leader = leader.clone();
+            events.push(GroupEvent::NewLeader(NewLeaderEvent {
+                mid: leader.clone(),
+            }));
+        }
+        events.push(GroupEvent::ViewChange(ViewChangeEvent {
+            id: self.id,
+            members,
+        }));
+        events
+    }
+}
+
 struct GroupState {
     gid: String,
     local_member: Member,
-    members: Mutex<HashMap<OwnedKeyExpr, (Member, Instant)>>,
+    view: Mutex<View>,
     group_publisher: Publisher<'static>,
     user_events_tx: Mutex<Option<Sender<GroupEvent>>>,
     cond: Condition,
 }
 
+impl GroupState {
+    async fn notify(&self, events: Vec<GroupEvent>) {
+        if let Some(tx) = &*self.user_events_tx.lock().await {
+            for evt in events {
+                let _ = tx.send(evt);
+            }
+        }
+    }
+
+    async fn join(&self, member: Member) {
+        let mut view = self.view.lock().await;
+        if view.members.contains_key(&member.mid) {
+            return;
+        }
+        log::debug!("Member join: {:?}", &member);
+        view.members
+            .insert(member.mid.clone(), (member.clone(), None));
+        log::debug!("Other members list: {:?}", view.members.keys());
+        let mut events = vec![GroupEvent::Join(JoinEvent { member })];
+        events.extend(view.changed(&self.local_member));
+        self.cond.notify_all();
+        drop(view);
+        self.notify(events).await;
+    }
+}
+
 pub struct Group {
     state: Arc<GroupState>,
     tasks: Vec<JoinHandle<()>>,
+    _token: LivelinessToken<'static>,
 }
 
 impl Drop for Group {
     fn drop(&mut self) {
+        // let the other members know right away that this one left the group
+        let evt = GroupNetEvent::Leave(LeaveEvent {
+            mid: self.state.local_member.mid.clone(),
+        });
+        let _ = self
+            .state
+            .group_publisher
+            .put(bincode::serialize(&evt).unwrap())
+            .res_sync();
         // cancel background tasks
         async_std::task::block_on(async {
             while let Some(handle) = self.tasks.pop() {
@@ -177,71 +252,51 @@ impl Drop for Group {
     }
 }
 
-async fn keep_alive_task(state: Arc<GroupState>) {
-    let mid = state.local_member.mid.clone();
-    let evt = GroupNetEvent::KeepAlive(KeepAliveEvent { mid });
-    let buf = bincode::serialize(&evt).unwrap();
-    let period = state
-        .local_member
-        .lease
-        .mul_f32(state.local_member.refresh_ratio);
-    loop {
-        async_std::task::sleep(period).await;
-        log::trace!("Sending Keep Alive for: {}", &state.local_member.mid);
-        let _ = state.group_publisher.put(buf.clone()).res().await;
-    }
-}
-
 fn spawn_watchdog(s: Arc<GroupState>, period: Duration) -> JoinHandle<()> {
     let watch_dog = async move {
         loop {
             async_std::task::sleep(period).await;
             let now = Instant::now();
-            let mut ms = s.members.lock().await;
-            let expired_members: Vec<OwnedKeyExpr> = ms
+            let mut view = s.view.lock().await;
+            let expired_members: Vec<OwnedKeyExpr> = view
+                .members
                 .iter()
-                .filter(|e| e.1 .1 < now)
+                .filter(|e| matches!(e.1 .1, Some(expiration) if expiration < now))
                 .map(|e| e.0.clone())
                 .collect();
 
             for e in &expired_members {
                 log::debug!("Member with lease expired: {}", e);
-                ms.remove(e);
+                view.members.remove(e);
             }
             if !expired_members.is_empty() {
-                log::debug!("Other members list: {:?}", ms.keys());
-                drop(ms);
-                let u_evt = &*s.user_events_tx.lock().await;
-                for e in expired_members {
-                    if let Some(tx) = u_evt {
-                        tx.send(GroupEvent::LeaseExpired(LeaseExpiredEvent { mid: e }))
-                            .unwrap()
-                    }
-                }
+                log::debug!("Other members list: {:?}", view.members.keys());
+                let mut events: Vec<GroupEvent> = expired_members
+                    .into_iter()
+                    .map(|mid| GroupEvent::LeaseExpired(LeaseExpiredEvent { mid }))
+                    .collect();
+                events.extend(view.changed(&s.local_member));
+                drop(view);
+                s.notify(events).await;
             }
         }
     };
     async_std::task::spawn(watch_dog)
 }
 
-async fn query_handler(z: Arc<Session>, state: Arc<GroupState>) {
-    let qres: KeyExpr = format!(
-        "{}/{}/{}",
-        GROUP_PREFIX, &state.gid, &state.local_member.mid
-    )
-    .try_into()
-    .unwrap();
+async fn query_handler(
+    qres: KeyExpr<'static>,
+    queryable: Queryable<'static, Receiver<Query>>,
+    state: Arc<GroupState>,
+) {
     log::debug!("Started query handler for: {}", &qres);
     let buf = bincode::serialize(&state.local_member).unwrap();
-    let queryable = z.declare_queryable(&qres).res().await.unwrap();
-
     while let Ok(query) = queryable.recv_async().await {
         log::trace!("Serving query for: {}", &qres);
-        query
+        let _ = query
             .reply(Ok(Sample::new(qres.clone(), buf.clone())))
             .res()
-            .await
-            .unwrap();
+            .await;
     }
 }
 
@@ -253,112 +308,98 @@ async fn net_event_handler(z: Arc<Session>, state: Arc<GroupState>) {
         .unwrap();
     while let Ok(s) = sub.recv_async().await {
         match bincode::deserialize::<GroupNetEvent>(&(s.value.payload.contiguous())) {
-            Ok(evt) => match evt {
-                GroupNetEvent::Join(je) => {
-                    log::debug!("Member join: {:?}", &je.member);
-                    let alive_till = Instant::now().add(je.member.lease);
-                    let mut ms = state.members.lock().await;
-                    ms.insert(je.member.mid.clone(), (je.member.clone(), alive_till));
-                    log::debug!("Other members list: {:?}", ms.keys());
-                    state.cond.notify_all();
-                    drop(ms);
-                    let u_evt = &*state.user_events_tx.lock().await;
-                    if let Some(tx) = u_evt {
-                        tx.send(GroupEvent::Join(je)).unwrap()
-                    }
+            Ok(GroupNetEvent::Leave(le)) => {
+                log::debug!("Member leave: {:?}", &le.mid);
+                let mut view = state.view.lock().await;
+                if view.members.remove(&le.mid).is_some() {
+                    log::debug!("Other members list: {:?}", view.members.keys());
+                    let mut events = vec![GroupEvent::Leave(le)];
+                    events.extend(view.changed(&state.local_member));
+                    drop(view);
+                    state.notify(events).await;
                 }
-                GroupNetEvent::Leave(le) => {
-                    log::debug!("Member leave: {:?}", &le.mid);
-                    let mut ms = state.members.lock().await;
-                    ms.remove(&le.mid);
-                    log::debug!("Other members list: {:?}", ms.keys());
-                    drop(ms);
-                    let u_evt = &*state.user_events_tx.lock().await;
-                    if let Some(tx) = u_evt {
-                        tx.send(GroupEvent::Leave(le)).unwrap()
-                    }
+            }
+            Err(e) => {
+                log::warn!("Failed decoding net-event due to: {:?}", e);
+            }
+        }
+    }
+}
+
+async fn liveliness_handler(
+    z: Arc<Session>,
+    sub: FlumeSubscriber<'static>,
+    state: Arc<GroupState>,
+) {
+    let prefix = format!("{}/{}/", GROUP_PREFIX, &state.gid);
+    while let Ok(s) = sub.recv_async().await {
+        let mid = match s
+            .key_expr
+            .as_str()
+            .strip_prefix(&prefix)
+            .map(OwnedKeyExpr::try_from)
+        {
+            Some(Ok(mid)) if mid != state.local_member.mid => mid,
+            _ => continue,
+        };
+        match s.kind {
+            SampleKind::Put => {
+                if let Some((_, expiration)) = state.view.lock().await.members.get_mut(&mid) {
+                    log::debug!("Member recovered its liveliness: {}", &mid);
+                    *expiration = None;
+                    continue;
                 }
-                GroupNetEvent::KeepAlive(kae) => {
-                    log::debug!(
-                        "KeepAlive from {} ({})",
-                        &kae.mid,
-                        if kae.mid.ne(&state.local_member.mid) {
-                            "other"
-                        } else {
-                            "myself"
-                        }
-                    );
-                    if kae.mid.ne(&state.local_member.mid) {
-                        let mut mm = state.members.lock().await;
-                        let v = mm.remove(&kae.mid);
-                        match v {
-                            Some((m, _)) => {
-                                log::trace!("Updating leasefor: {:?}", &kae.mid);
-                                let alive_till = Instant::now().add(m.lease);
-                                mm.insert(m.mid.clone(), (m, alive_till));
-                            }
-                            None => {
-                                log::debug!(
-                                    "Received Keep Alive from unknown member: {}",
-                                    &kae.mid
-                                );
-                                let qres = format!("{}/{}/{}", GROUP_PREFIX, &state.gid, kae.mid);
-                                // @TODO: we could also send this member info
-                                let qc = ConsolidationMode::None;
-                                log::trace!("Issuing Query for {}", &qres);
-                                let receiver = z.get(&qres).consolidation(qc).res().await.unwrap();
-
-                                while let Ok(reply) = receiver.recv_async().await {
-                                    match reply.sample {
-                                        Ok(sample) => {
-                                            match bincode::deserialize::<Member>(
-                                                &sample.payload.contiguous(),
-                                            ) {
-                                                Ok(m) => {
-                                                    let mut expiry = Instant::now();
-                                                    expiry = expiry.add(m.lease);
-                                                    log::debug!(
-                                                        "Received member information: {:?}",
-                                                        &m
-                                                    );
-                                                    mm.insert(kae.mid.clone(), (m.clone(), expiry));
-                                                    log::debug!(
-                                                        "Other members list: {:?}",
-                                                        mm.keys()
-                                                    );
-                                                    // Advertise a JoinEvent
-                                                    let u_evt = &*state.user_events_tx.lock().await;
-                                                    if let Some(tx) = u_evt {
-                                                        let je = JoinEvent { member: m };
-                                                        tx.send_async(GroupEvent::Join(je))
-                                                            .await
-                                                            .unwrap()
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    log::warn!(
-                                                        "Unable to deserialize the Member info received: {}", e);
-                                                }
-                                            }
-                                        }
-                                        Err(e) => {
-                                            log::warn!("Error received: {}", e);
-                                        }
-                                    }
-                                }
-                                state.cond.notify_all();
-                            }
-                        }
-                    } else {
-                        log::trace!("KeepAlive from Local Participant -- Ignoring");
-                    }
+                match query_member(&z, &state.gid, &mid).await {
+                    Some(member) => state.join(member).await,
+                    None => log::warn!("Unable to get the information of member: {}", &mid),
+                }
+            }
+            SampleKind::Delete => {
+                if let Some((m, expiration)) = state.view.lock().await.members.get_mut(&mid) {
+                    log::debug!("Member lost its liveliness: {}", &mid);
+                    *expiration = Some(Instant::now().add(m.lease));
+                }
+            }
+        }
+    }
+}
+
+async fn query_members(z: &Session, selector: &str) -> ZResult<Vec<Member>> {
+    log::trace!("Issuing Query for {}", selector);
+    let receiver = z
+        .get(selector)
+        .consolidation(ConsolidationMode::None)
+        .res()
+        .await?;
+    let mut members = vec![];
+    while let Ok(reply) = receiver.recv_async().await {
+        match reply.sample {
+            Ok(sample) => match bincode::deserialize::<Member>(&sample.payload.contiguous()) {
+                Ok(m) => {
+                    log::debug!("Received member information: {:?}", &m);
+                    members.push(m)
+                }
+                Err(e) => {
+                    log::warn!("Unable to deserialize the Member info received: {}", e);
                 }
             },
             Err(e) => {
-                log::warn!("Failed decoding net-event due to: {:?}", e);
+                log::warn!("Error received: {}", e);
             }
         }
     }
+    Ok(members)
+}
+
+async fn query_member(z: &Session, gid: &str, mid: &keyexpr) -> Option<Member> {
+    let selector = format!("{}/{}/{}", GROUP_PREFIX, gid, mid);
+    match query_members(z, &selector).await {
+        Ok(members) => members.into_iter().find(|m| m.mid == *mid),
+        Err(e) => {
+            log::warn!("Query for {} failed: {}", selector, e);
+            None
+        }
+    }
 }
 
 impl Group {
@@ -377,37 +418,68 @@ impl Group {
             .declare_publisher(event_expr)
             .priority(with.priority)
             .res()
-            .await
-            .unwrap();
+            .await?;
         let state = Arc::new(GroupState {
             gid: String::from(group),
             local_member: with.clone(),
-            members: Mutex::new(Default::default()),
+            view: Mutex::new(View {
+                id: 0,
+                members: HashMap::new(),
+                leader: with.mid.clone(),
+            }),
             group_publisher: publisher,
             user_events_tx: Mutex::new(Default::default()),
             cond: Condition::new(),
         });
-        let is_auto_liveliness = matches!(with.liveliness, MemberLiveliness::Auto);
 
-        // announce the member:
-        log::debug!("Sending Join Message for local member: {:?}", &with);
-        let join_evt = GroupNetEvent::Join(JoinEvent { member: with });
-        let buf = bincode::serialize(&join_evt).unwrap();
-        let _ = state.group_publisher.put(buf).res().await;
-
-        // If the liveliness is manual it is the user who has to assert it.
-        if is_auto_liveliness {
-            async_std::task::spawn(keep_alive_task(state.clone()));
+        // the queryable serving the member information is declared before the token announcing the member
+        let member_expr: KeyExpr = format!("{}/{}/{}", GROUP_PREFIX, &state.gid, &with.mid)
+            .try_into()
+            .unwrap();
+        let queryable = z.declare_queryable(&member_expr).res().await?;
+        let members_expr = format!("{}/{}/**", GROUP_PREFIX, &state.gid);
+        let sub = z
+            .liveliness()
+            .declare_subscriber(&members_expr)
+            .res()
+            .await?;
+        log::debug!("Declaring liveliness token for local member: {:?}", &with);
+        let token = z.liveliness().declare_token(&member_expr).res().await?;
+
+        // get the members already in the group
+        for member in query_members(&z, &members_expr).await? {
+            if member.mid != with.mid {
+                state.join(member).await;
+            }
         }
+
         let events_task = async_std::task::spawn(net_event_handler(z.clone(), state.clone()));
-        let queries_task = async_std::task::spawn(query_handler(z.clone(), state.clone()));
-        let watchdog_task = spawn_watchdog(state.clone(), Duration::from_secs(1));
+        let liveliness_task =
+            async_std::task::spawn(liveliness_handler(z.clone(), sub, state.clone()));
+        let queries_task =
+            async_std::task::spawn(query_handler(member_expr, queryable, state.clone()));
+        let watchdog_task = spawn_watchdog(state.clone(), Duration::from_millis(100));
         Ok(Group {
             state,
-            tasks: Vec::from([events_task, queries_task, watchdog_task]),
+            tasks: Vec::from([events_task, liveliness_task, queries_task, watchdog_task]),
+            _token: token,
         })
     }
 
+    /// Leaves the group, which is equivalent to dropping the [`Group`]: the other members
+    /// remove this member from their view right away, without waiting for its lease to expire.
+    pub fn leave(self) {}
+
+    /// Queries the current members of a group, without joining it.
+    pub async fn members<T>(z: &Session, group: T) -> ZResult<Vec<Member>>
+    where
+        T: TryInto<OwnedKeyExpr>,
+        <T as TryInto<OwnedKeyExpr>>::Error: Into<ZError>,
+    {
+        let group: OwnedKeyExpr = group.try_into().map_err(|e| e.into())?;
+        query_members(z, &format!("{GROUP_PREFIX}/{group}/**")).await
+    }
+
     /// Returns a receivers that will allow to receive notifications for group events.
     /// Notice that there can be a single subscription at the time, each call to subscribe
     /// will cancel the previous subscription.
@@ -427,16 +499,22 @@ impl Group {
         &self.state.local_member.mid
     }
 
+    /// Returns the identifier of the current group view, incremented on each change of the membership.
+    pub async fn view_id(&self) -> u64 {
+        self.state.view.lock().await.id
+    }
+
     /// Returns the current group view, in other terms the list
     /// of group members.
     pub async fn view(&self) -> Vec<Member> {
         let mut ms: Vec<Member> = self
             .state
-            .members
+            .view
             .lock()
             .await
-            .iter()
-            .map(|e| e.1 .0.clone())
+            .members
+            .values()
+            .map(|e| e.0.clone())
             .collect();
         ms.push(self.state.local_member.clone());
         ms
@@ -445,17 +523,17 @@ impl Group {
     /// Wait for a view size to be established or times out. The resulting selector parameters
     /// indicates whether the desired view size has been established.
     pub async fn wait_for_view_size(&self, size: usize, timeout: Duration) -> bool {
-        if self.state.members.lock().await.len() + 1 >= size {
+        if self.state.view.lock().await.members.len() + 1 >= size {
             true
         } else {
             // let s = self.state.clone();
             let f = async {
                 loop {
-                    let ms = self.state.members.lock().await;
-                    if ms.len() + 1 >= size {
+                    let view = self.state.view.lock().await;
+                    if view.members.len() + 1 >= size {
                         return true;
                     } else {
-                        self.state.cond.wait(ms).await;
+                        self.state.cond.wait(view).await;
                     }
                 }
             };
@@ -469,12 +547,12 @@ impl Group {
 
     /// Returns the current group size.
     pub async fn size(&self) -> usize {
-        let ms = self.state.members.lock().await;
-        ms.len() + 1 // with +1 being the local member
+        let view = self.state.view.lock().await;
+        view.members.len() + 1 // with +1 being the local member
     }
 
-    /// Returns the evental leader for this group. Notice that a view change may cause
-    /// a change on leader. Thus it is wise to always get the leader after a view change.
+    /// Returns the evental leader for this group, the member with the greatest identifier.
+    /// Notice that a view change may cause a change on leader, notified with a [`NewLeaderEvent`].
     pub async fn leader(&self) -> Member {
         use std::cmp::Ordering;
         let group = self.view().await;
@@ -487,3 +565,74 @@ impl Group {
         leader
     }
 }
+
+#[test]
+fn group_membership() {
+    use async_std::task;
+    use zenoh::config::Config;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    task::block_on(async {
+        let config = |listen: Option<&str>| {
+            let mut config = Config::default();
+            config.scouting.multicast.set_enabled(Some(false)).unwrap();
+            match listen {
+                Some(endpoint) => config
+                    .listen
+                    .set_endpoints(vec![endpoint.parse().unwrap()])
+                    .unwrap(),
+                None => config
+                    .connect
+                    .set_endpoints(vec!["tcp/localhost:17464".parse().unwrap()])
+                    .unwrap(),
+            }
+            config
+        };
+        let z1 = Arc::new(
+            zenoh::open(config(Some("tcp/localhost:17464")))
+                .res()
+                .await
+                .unwrap(),
+        );
+        let z2 = Arc::new(zenoh::open(config(None)).res().await.unwrap());
+
+        let a = Group::join(z1.clone(), "test/group", Member::new("a").unwrap())
+            .await
+            .unwrap();
+        let events = a.subscribe().await;
+        let b = Group::join(z2.clone(), "test/group", Member::new("b").unwrap())
+            .await
+            .unwrap();
+        // the joining member gets the members already in the group
+        assert_eq!(b.size().await, 2);
+        assert!(a.wait_for_view_size(2, TIMEOUT).await);
+        assert!(
+            matches!(events.recv_async().await.unwrap(), GroupEvent::Join(je) if je.member.id().as_str() == "b")
+        );
+        assert!(
+            matches!(events.recv_async().await.unwrap(), GroupEvent::NewLeader(le) if le.mid.as_str() == "b")
+        );
+        match events.recv_async().await.unwrap() {
+            GroupEvent::ViewChange(view) => {
+                assert_eq!(view.id, a.view_id().await);
+                assert_eq!(view.members.len(), 2);
+            }
+            evt => panic!("Unexpected event: {:?}", evt),
+        }
+        assert_eq!(a.leader().await.id().as_str(), "b");
+
+        let members = Group::members(&z1, "test/group").await.unwrap();
+        assert_eq!(members.len(), 2);
+
+        // the view changes right away when a member leaves
+        b.leave();
+        assert!(
+            matches!(events.recv_async().await.unwrap(), GroupEvent::Leave(le) if le.mid.as_str() == "b")
+        );
+        assert!(
+            matches!(events.recv_async().await.unwrap(), GroupEvent::NewLeader(le) if le.mid.as_str() == "a")
+        );
+        assert_eq!(a.size().await, 1);
+    });
+}
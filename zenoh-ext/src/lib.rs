@@ -11,18 +11,25 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+mod encryption;
+pub mod flow;
 pub mod group;
 mod publication_cache;
 mod querying_subscriber;
+mod rpc;
 mod session_ext;
 mod subscriber_ext;
+mod typed;
+pub use encryption::{DecryptingSubscriber, EncryptedPublisher, EncryptionKeys};
 pub use publication_cache::{PublicationCache, PublicationCacheBuilder};
 pub use querying_subscriber::{
     FetchingSubscriber, FetchingSubscriberBuilder, QueryingSubscriberBuilder,
 };
+pub use rpc::{ClientEndpoint, ServerEndpoint, RPC_ID_PARAM};
 pub use session_ext::{ArcSessionExt, SessionExt};
 pub use subscriber_ext::SubscriberBuilderExt;
 pub use subscriber_ext::SubscriberForward;
+pub use typed::{TypedEncoding, TypedPublisher, TypedSample, TypedSubscriber};
 
 /// The space of keys to use in a [`FetchingSubscriber`].
 pub enum KeySpace {
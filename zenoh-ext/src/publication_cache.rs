@@ -15,12 +15,18 @@ use async_std::channel::{bounded, Sender};
 use async_std::task;
 use futures::select;
 use futures::{FutureExt, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::convert::TryInto;
+use std::fs::{File, OpenOptions};
 use std::future::Ready;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use zenoh::prelude::r#async::*;
 use zenoh::queryable::{Query, Queryable};
 use zenoh::subscriber::FlumeSubscriber;
+use zenoh::time::Timestamp;
 use zenoh::SessionRef;
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
 use zenoh_result::{bail, ZResult};
@@ -33,7 +39,11 @@ pub struct PublicationCacheBuilder<'a, 'b, 'c> {
     queryable_prefix: Option<ZResult<KeyExpr<'c>>>,
     queryable_origin: Locality,
     history: usize,
+    histories: Vec<(ZResult<OwnedKeyExpr>, usize)>,
     resources_limit: Option<usize>,
+    memory_limit: Option<usize>,
+    ttl: Option<Duration>,
+    spill_dir: Option<PathBuf>,
 }
 
 impl<'a, 'b, 'c> PublicationCacheBuilder<'a, 'b, 'c> {
@@ -47,7 +57,11 @@ impl<'a, 'b, 'c> PublicationCacheBuilder<'a, 'b, 'c> {
             queryable_prefix: None,
             queryable_origin: Locality::default(),
             history: 1,
+            histories: vec![],
             resources_limit: None,
+            memory_limit: None,
+            ttl: None,
+            spill_dir: None,
         }
     }
 
@@ -76,11 +90,48 @@ impl<'a, 'b, 'c> PublicationCacheBuilder<'a, 'b, 'c> {
         self
     }
 
+    /// Change the history size for the resources matching the given key expression,
+    /// e.g. to keep a longer history of a high-rate topic.
+    ///
+    /// The first key expression including a resource gives its history size.
+    pub fn history_for<TryIntoKeyExpr>(mut self, key_expr: TryIntoKeyExpr, history: usize) -> Self
+    where
+        TryIntoKeyExpr: TryInto<OwnedKeyExpr>,
+        <TryIntoKeyExpr as TryInto<OwnedKeyExpr>>::Error: Into<zenoh_result::Error>,
+    {
+        self.histories
+            .push((key_expr.try_into().map_err(Into::into), history));
+        self
+    }
+
     /// Change the limit number of cached resources.
     pub fn resources_limit(mut self, limit: usize) -> Self {
         self.resources_limit = Some(limit);
         self
     }
+
+    /// Limit the total size (in bytes) of the cached payloads, evicting the oldest publications
+    /// of all the resources when exceeded.
+    pub fn memory_limit(mut self, limit: usize) -> Self {
+        self.memory_limit = Some(limit);
+        self
+    }
+
+    /// Evict the publications cached for longer than `ttl`.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Spill the publications evicted because of the [`memory_limit`](Self::memory_limit)
+    /// to files in the given directory (e.g. on a storage volume) instead of dropping them.
+    ///
+    /// The cached publications are also saved in this directory when the [`PublicationCache`]
+    /// is closed, and loaded back by the next [`PublicationCache`] spilling to it.
+    pub fn spill_to<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.spill_dir = Some(dir.into());
+        self
+    }
 }
 
 impl<'a> Resolvable for PublicationCacheBuilder<'a, '_, '_> {
@@ -121,11 +172,21 @@ impl<'a> PublicationCache<'a> {
                 Some(Err(e)) => bail!("Invalid key expression for queryable_prefix: {}", e),
             };
         log::debug!(
-            "Create PublicationCache on {} with history={} resource_limit={:?}",
+            "Create PublicationCache on {} with history={} resource_limit={:?} memory_limit={:?} ttl={:?} spill_dir={:?}",
             &key_expr,
             conf.history,
-            conf.resources_limit
+            conf.resources_limit,
+            conf.memory_limit,
+            conf.ttl,
+            conf.spill_dir
         );
+        let mut histories = Vec::with_capacity(conf.histories.len());
+        for (ke, history) in conf.histories {
+            match ke {
+                Ok(ke) => histories.push((ke, history)),
+                Err(e) => bail!("Invalid key expression for history_for: {}", e),
+            }
+        }
 
         if conf.session.hlc().is_none() {
             bail!(
@@ -162,15 +223,23 @@ impl<'a> PublicationCache<'a> {
         // take local ownership of stuff to be moved into task
         let sub_recv = local_sub.receiver.clone();
         let quer_recv = queryable.receiver.clone();
-        let pub_key_expr = key_expr.into_owned();
-        let resources_limit = conf.resources_limit;
-        let history = conf.history;
+        let mut cache = Cache::new(
+            key_expr.into_owned().into(),
+            conf.history,
+            histories,
+            conf.resources_limit,
+            conf.memory_limit,
+            conf.ttl,
+            conf.spill_dir,
+        );
+        cache.load(&queryable_key_expr);
+        let purge_period = conf.ttl.map_or(Duration::from_secs(1), |ttl| {
+            ttl.clamp(Duration::from_millis(10), Duration::from_secs(1))
+        });
 
         let (stoptx, mut stoprx) = bounded::<bool>(1);
         task::spawn(async move {
-            let mut cache: HashMap<OwnedKeyExpr, VecDeque<Sample>> =
-                HashMap::with_capacity(resources_limit.unwrap_or(32));
-            let limit = resources_limit.unwrap_or(usize::MAX);
+            let mut purge = async_std::stream::interval(purge_period);
 
             loop {
                 select!(
@@ -182,60 +251,34 @@ impl<'a> PublicationCache<'a> {
                             } else {
                                 sample.key_expr.clone()
                             };
-
-                            if let Some(queue) = cache.get_mut(queryable_key_expr.as_keyexpr()) {
-                                if queue.len() >= history {
-                                    queue.pop_front();
-                                }
-                                queue.push_back(sample);
-                            } else if cache.len() >= limit {
-                                log::error!("PublicationCache on {}: resource_limit exceeded - can't cache publication for a new resource",
-                                pub_key_expr);
-                            } else {
-                                let mut queue: VecDeque<Sample> = VecDeque::new();
-                                queue.push_back(sample);
-                                cache.insert(queryable_key_expr.into(), queue);
-                            }
+                            cache.insert(queryable_key_expr.into(), sample, SystemTime::now());
                         }
                     },
 
                     // on query, reply with cach content
                     query = quer_recv.recv_async() => {
                         if let Ok(query) = query {
-                            if !query.selector().key_expr.as_str().contains('*') {
-                                if let Some(queue) = cache.get(query.selector().key_expr.as_keyexpr()) {
-                                    for sample in queue {
-                                        if let (Ok(Some(time_range)), Some(timestamp)) = (query.selector().time_range(), sample.timestamp) {
-                                            if !time_range.contains(timestamp.get_time().to_system_time()){
-                                                continue;
-                                            }
-                                        }
-                                        if let Err(e) = query.reply(Ok(sample.clone())).res_async().await {
-                                            log::warn!("Error replying to query: {}", e);
-                                        }
+                            for sample in cache.samples_matching(&query.selector().key_expr) {
+                                if let (Ok(Some(time_range)), Some(timestamp)) = (query.selector().time_range(), sample.timestamp) {
+                                    if !time_range.contains(timestamp.get_time().to_system_time()){
+                                        continue;
                                     }
                                 }
-                            } else {
-                                for (key_expr, queue) in cache.iter() {
-                                    if query.selector().key_expr.intersects(unsafe{ keyexpr::from_str_unchecked(key_expr) }) {
-                                        for sample in queue {
-                                            if let (Ok(Some(time_range)), Some(timestamp)) = (query.selector().time_range(), sample.timestamp) {
-                                                if !time_range.contains(timestamp.get_time().to_system_time()){
-                                                    continue;
-                                                }
-                                            }
-                                            if let Err(e) = query.reply(Ok(sample.clone())).res_async().await {
-                                                log::warn!("Error replying to query: {}", e);
-                                            }
-                                        }
-                                    }
+                                if let Err(e) = query.reply(Ok(sample)).res_async().await {
+                                    log::warn!("Error replying to query: {}", e);
                                 }
                             }
                         }
                     },
 
-                    // When stoptx is dropped, stop the task
+                    // periodically evict the publications whose ttl expired
+                    _ = purge.next().fuse() => {
+                        cache.evict_expired();
+                    },
+
+                    // When stoptx is dropped, save the cache content (if spilling) and stop the task
                     _ = stoprx.next().fuse() => {
+                        cache.flush();
                         return
                     }
                 );
@@ -269,3 +312,611 @@ impl<'a> PublicationCache<'a> {
         self.local_sub.key_expr()
     }
 }
+
+// A publication spilled to a file, as serialized by bincode
+#[derive(Serialize, Deserialize)]
+struct SpilledSample {
+    key: String,
+    cached_at: u64,
+    payload: Vec<u8>,
+    encoding: String,
+    delete: bool,
+    timestamp: Option<String>,
+}
+
+impl SpilledSample {
+    fn new(sample: &Sample, cached_at: SystemTime) -> Self {
+        SpilledSample {
+            key: sample.key_expr.to_string(),
+            cached_at: cached_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            payload: sample.value.payload.contiguous().to_vec(),
+            encoding: sample.value.encoding.to_string(),
+            delete: sample.kind == SampleKind::Delete,
+            timestamp: sample.timestamp.as_ref().map(ToString::to_string),
+        }
+    }
+
+    fn cached_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.cached_at)
+    }
+
+    fn into_sample(self) -> ZResult<Sample> {
+        let key_expr = KeyExpr::try_from(self.key)?;
+        let value = Value::from(self.payload).encoding(Encoding::from(self.encoding));
+        let mut sample = Sample::new(key_expr, value);
+        if let Some(timestamp) = self.timestamp {
+            match timestamp.parse::<Timestamp>() {
+                Ok(timestamp) => sample = sample.with_timestamp(timestamp),
+                Err(e) => bail!("Invalid timestamp {}: {:?}", timestamp, e),
+            }
+        }
+        if self.delete {
+            sample.kind = SampleKind::Delete;
+        }
+        Ok(sample)
+    }
+}
+
+// The publications of a resource spilled to a file.
+// The first `skip` records of the file were evicted, and `cached_at` lists the caching times of the others.
+struct SpillFile {
+    path: PathBuf,
+    skip: usize,
+    cached_at: VecDeque<SystemTime>,
+}
+
+impl SpillFile {
+    fn append(&mut self, sample: &Sample, cached_at: SystemTime) -> ZResult<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut writer = BufWriter::new(file);
+        bincode::serialize_into(&mut writer, &SpilledSample::new(sample, cached_at))?;
+        writer.flush()?;
+        self.cached_at.push_back(cached_at);
+        Ok(())
+    }
+
+    fn records(&self) -> ZResult<Vec<SpilledSample>> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let mut records = vec![];
+        while let Ok(record) = bincode::deserialize_from::<_, SpilledSample>(&mut reader) {
+            records.push(record);
+        }
+        Ok(records.into_iter().skip(self.skip).collect())
+    }
+
+    // Evicts the oldest spilled publication, rewriting the file once most of its records were evicted
+    fn pop_front(&mut self) {
+        if self.cached_at.pop_front().is_some() {
+            self.skip += 1;
+        }
+        if self.skip > self.cached_at.len() {
+            if let Err(e) = self.compact() {
+                log::warn!("Error compacting {}: {}", self.path.display(), e);
+            }
+        }
+    }
+
+    fn compact(&mut self) -> ZResult<()> {
+        if self.cached_at.is_empty() {
+            self.skip = 0;
+            return Ok(std::fs::remove_file(&self.path)?);
+        }
+        let records = self.records()?;
+        let tmp = self.path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        for record in &records {
+            bincode::serialize_into(&mut writer, record)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(&tmp, &self.path)?;
+        self.skip = 0;
+        Ok(())
+    }
+}
+
+// The publications cached for a resource: the oldest ones may be spilled, the others are in memory
+struct KeyCache {
+    history: usize,
+    samples: VecDeque<(u64, SystemTime, Sample)>,
+    spill: Option<SpillFile>,
+}
+
+impl KeyCache {
+    fn len(&self) -> usize {
+        self.samples.len() + self.spill.as_ref().map_or(0, |spill| spill.cached_at.len())
+    }
+
+    // Evicts the oldest publication, returning its size if it was in memory
+    fn pop_front(&mut self) -> Option<usize> {
+        if let Some(spill) = self
+            .spill
+            .as_mut()
+            .filter(|spill| !spill.cached_at.is_empty())
+        {
+            spill.pop_front();
+            None
+        } else {
+            self.samples
+                .pop_front()
+                .map(|(_, _, sample)| sample_size(&sample))
+        }
+    }
+}
+
+fn sample_size(sample: &Sample) -> usize {
+    sample.key_expr.len() + sample.value.payload.len()
+}
+
+fn spill_path(dir: &std::path::Path, key: &keyexpr) -> PathBuf {
+    let name: String = key.as_bytes().iter().map(|b| format!("{b:02x}")).collect();
+    dir.join(name).with_extension("spill")
+}
+
+fn spill_key(path: &std::path::Path) -> Option<OwnedKeyExpr> {
+    if path.extension()? != "spill" {
+        return None;
+    }
+    let name = path.file_stem()?.to_str()?;
+    if name.len() % 2 != 0 {
+        return None;
+    }
+    let bytes = (0..name.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(name.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    OwnedKeyExpr::try_from(String::from_utf8(bytes).ok()?).ok()
+}
+
+// The content of a PublicationCache, indexed by the key expressions of its queryable
+struct Cache {
+    pub_key_expr: OwnedKeyExpr,
+    history: usize,
+    histories: Vec<(OwnedKeyExpr, usize)>,
+    resources_limit: usize,
+    memory_limit: usize,
+    ttl: Option<Duration>,
+    spill_dir: Option<PathBuf>,
+    keys: HashMap<OwnedKeyExpr, KeyCache>,
+    // the publications in memory from the oldest to the newest, possibly including already evicted ones
+    order: VecDeque<(u64, OwnedKeyExpr)>,
+    seq: u64,
+    len: usize,
+    memory: usize,
+}
+
+impl Cache {
+    fn new(
+        pub_key_expr: OwnedKeyExpr,
+        history: usize,
+        histories: Vec<(OwnedKeyExpr, usize)>,
+        resources_limit: Option<usize>,
+        memory_limit: Option<usize>,
+        ttl: Option<Duration>,
+        spill_dir: Option<PathBuf>,
+    ) -> Self {
+        Cache {
+            pub_key_expr,
+            history,
+            histories,
+            resources_limit: resources_limit.unwrap_or(usize::MAX),
+            memory_limit: memory_limit.unwrap_or(usize::MAX),
+            ttl,
+            spill_dir,
+            keys: HashMap::with_capacity(resources_limit.unwrap_or(32)),
+            order: VecDeque::new(),
+            seq: 0,
+            len: 0,
+            memory: 0,
+        }
+    }
+
+    fn history_of(&self, key_expr: &keyexpr) -> usize {
+        self.histories
+            .iter()
+            .find(|(ke, _)| ke.includes(key_expr))
+            .map_or(self.history, |(_, history)| *history)
+    }
+
+    fn is_expired(&self, cached_at: SystemTime, now: SystemTime) -> bool {
+        self.ttl.map_or(false, |ttl| {
+            now.duration_since(cached_at).unwrap_or_default() >= ttl
+        })
+    }
+
+    // Loads the publications spilled in the spill directory for the resources matching `key_expr`
+    fn load(&mut self, key_expr: &keyexpr) {
+        let dir = match &self.spill_dir {
+            Some(dir) => dir.clone(),
+            None => return,
+        };
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            log::warn!(
+                "PublicationCache on {}: can't create spill directory {}: {}",
+                self.pub_key_expr,
+                dir.display(),
+                e
+            );
+            return;
+        }
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!(
+                    "PublicationCache on {}: can't read spill directory {}: {}",
+                    self.pub_key_expr,
+                    dir.display(),
+                    e
+                );
+                return;
+            }
+        };
+        let now = SystemTime::now();
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let key = match spill_key(&path) {
+                Some(key) if key.intersects(key_expr) => key,
+                _ => continue,
+            };
+            let spill = SpillFile {
+                path: path.clone(),
+                skip: 0,
+                cached_at: VecDeque::new(),
+            };
+            let records = match spill.records() {
+                Ok(records) => records,
+                Err(e) => {
+                    log::warn!(
+                        "PublicationCache on {}: can't read {}: {}",
+                        self.pub_key_expr,
+                        path.display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            // the publications are spilled again if needed when inserted
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::warn!(
+                    "PublicationCache on {}: can't remove {}: {}",
+                    self.pub_key_expr,
+                    path.display(),
+                    e
+                );
+                continue;
+            }
+            for record in records {
+                let cached_at = record.cached_at();
+                if self.is_expired(cached_at, now) {
+                    continue;
+                }
+                match record.into_sample() {
+                    Ok(sample) => self.insert(key.clone(), sample, cached_at),
+                    Err(e) => log::warn!(
+                        "PublicationCache on {}: invalid publication in {}: {}",
+                        self.pub_key_expr,
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+        }
+    }
+
+    fn insert(&mut self, key: OwnedKeyExpr, sample: Sample, cached_at: SystemTime) {
+        if !self.keys.contains_key(&key) {
+            if self.keys.len() >= self.resources_limit {
+                log::error!("PublicationCache on {}: resource_limit exceeded - can't cache publication for a new resource",
+                    self.pub_key_expr);
+                return;
+            }
+            let history = self.history_of(&sample.key_expr);
+            let spill = self.spill_dir.as_ref().map(|dir| SpillFile {
+                path: spill_path(dir, &key),
+                skip: 0,
+                cached_at: VecDeque::new(),
+            });
+            self.keys.insert(
+                key.clone(),
+                KeyCache {
+                    history,
+                    samples: VecDeque::new(),
+                    spill,
+                },
+            );
+        }
+        let seq = self.seq;
+        self.seq += 1;
+        let size = sample_size(&sample);
+        let entry = self.keys.get_mut(&key).unwrap();
+        entry.samples.push_back((seq, cached_at, sample));
+        self.len += 1;
+        self.memory += size;
+        self.order.push_back((seq, key));
+        while entry.len() > entry.history {
+            if let Some(size) = entry.pop_front() {
+                self.len -= 1;
+                self.memory -= size;
+            }
+        }
+        while self.memory > self.memory_limit {
+            if !self.evict_oldest() {
+                break;
+            }
+        }
+        self.compact_order();
+    }
+
+    // Evicts the oldest publication in memory, spilling it if configured so
+    fn evict_oldest(&mut self) -> bool {
+        while let Some((seq, key)) = self.order.pop_front() {
+            let entry = match self.keys.get_mut(&key) {
+                Some(entry) if entry.samples.front().map(|(s, _, _)| *s) == Some(seq) => entry,
+                _ => continue,
+            };
+            let (_, cached_at, sample) = entry.samples.pop_front().unwrap();
+            self.len -= 1;
+            self.memory -= sample_size(&sample);
+            if let Some(spill) = entry.spill.as_mut() {
+                if let Err(e) = spill.append(&sample, cached_at) {
+                    log::warn!(
+                        "PublicationCache on {}: can't spill publication to {}: {}",
+                        self.pub_key_expr,
+                        spill.path.display(),
+                        e
+                    );
+                }
+            }
+            if entry.len() == 0 {
+                self.keys.remove(&key);
+            }
+            return true;
+        }
+        false
+    }
+
+    // Drops the evicted publications from `order` once they are the majority
+    fn compact_order(&mut self) {
+        if self.order.len() > 2 * self.len + 32 {
+            let keys = &self.keys;
+            self.order.retain(|(seq, key)| {
+                keys.get(key).map_or(false, |entry| {
+                    entry.samples.iter().any(|(s, _, _)| s == seq)
+                })
+            });
+        }
+    }
+
+    fn evict_expired(&mut self) {
+        if self.ttl.is_none() {
+            return;
+        }
+        let now = SystemTime::now();
+        let ttl = self.ttl;
+        let is_expired = |cached_at: &SystemTime| {
+            ttl.map_or(false, |ttl| {
+                now.duration_since(*cached_at).unwrap_or_default() >= ttl
+            })
+        };
+        let mut evicted = 0;
+        let mut evicted_size = 0;
+        self.keys.retain(|_, entry| {
+            if let Some(spill) = entry.spill.as_mut() {
+                while spill.cached_at.front().map_or(false, is_expired) {
+                    spill.pop_front();
+                }
+            }
+            while entry
+                .samples
+                .front()
+                .map_or(false, |(_, cached_at, _)| is_expired(cached_at))
+            {
+                let (_, _, sample) = entry.samples.pop_front().unwrap();
+                evicted += 1;
+                evicted_size += sample_size(&sample);
+            }
+            entry.len() > 0
+        });
+        self.len -= evicted;
+        self.memory -= evicted_size;
+        self.compact_order();
+    }
+
+    // Returns the cached publications for the resources matching `key_expr`, from the oldest to the newest
+    fn samples_matching(&self, key_expr: &keyexpr) -> Vec<Sample> {
+        if !key_expr.as_str().contains('*') {
+            self.keys
+                .get(key_expr)
+                .map_or_else(Vec::new, |entry| self.samples(entry))
+        } else {
+            self.keys
+                .iter()
+                .filter(|(ke, _)| key_expr.intersects(ke))
+                .flat_map(|(_, entry)| self.samples(entry))
+                .collect()
+        }
+    }
+
+    fn samples(&self, entry: &KeyCache) -> Vec<Sample> {
+        let now = SystemTime::now();
+        let mut samples = vec![];
+        if let Some(spill) = entry
+            .spill
+            .as_ref()
+            .filter(|spill| !spill.cached_at.is_empty())
+        {
+            match spill.records() {
+                Ok(records) => {
+                    for record in records {
+                        if self.is_expired(record.cached_at(), now) {
+                            continue;
+                        }
+                        match record.into_sample() {
+                            Ok(sample) => samples.push(sample),
+                            Err(e) => log::warn!(
+                                "PublicationCache on {}: invalid publication in {}: {}",
+                                self.pub_key_expr,
+                                spill.path.display(),
+                                e
+                            ),
+                        }
+                    }
+                }
+                Err(e) => log::warn!(
+                    "PublicationCache on {}: can't read {}: {}",
+                    self.pub_key_expr,
+                    spill.path.display(),
+                    e
+                ),
+            }
+        }
+        for (_, cached_at, sample) in &entry.samples {
+            if !self.is_expired(*cached_at, now) {
+                samples.push(sample.clone());
+            }
+        }
+        samples
+    }
+
+    // Spills the publications in memory, for the next PublicationCache spilling to the same directory
+    fn flush(&mut self) {
+        for entry in self.keys.values_mut() {
+            if let Some(spill) = entry.spill.as_mut() {
+                if spill.skip > 0 {
+                    if let Err(e) = spill.compact() {
+                        log::warn!(
+                            "PublicationCache on {}: can't compact {}: {}",
+                            self.pub_key_expr,
+                            spill.path.display(),
+                            e
+                        );
+                    }
+                }
+                for (_, cached_at, sample) in entry.samples.drain(..) {
+                    if let Err(e) = spill.append(&sample, cached_at) {
+                        log::warn!(
+                            "PublicationCache on {}: can't spill publication to {}: {}",
+                            self.pub_key_expr,
+                            spill.path.display(),
+                            e
+                        );
+                    }
+                }
+            }
+        }
+        self.order.clear();
+        self.len = self.keys.values().map(|entry| entry.samples.len()).sum();
+        self.memory = self
+            .keys
+            .values()
+            .flat_map(|entry| entry.samples.iter())
+            .map(|(_, _, sample)| sample_size(sample))
+            .sum();
+    }
+}
+
+#[test]
+fn publication_cache_eviction() {
+    let dir = std::env::temp_dir().join(format!("zenoh-pub-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let key = |s: &str| OwnedKeyExpr::new(s).unwrap();
+    let put = |cache: &mut Cache, k: &str, v: &str| {
+        cache.insert(key(k), Sample::new(key(k), v), SystemTime::now());
+    };
+    let values = |cache: &Cache, k: &str| {
+        cache
+            .samples_matching(&key(k))
+            .into_iter()
+            .map(|sample| sample.value.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // per-key history depth
+    let mut cache = Cache::new(
+        key("test/**"),
+        2,
+        vec![(key("test/fast/**"), 4)],
+        None,
+        None,
+        None,
+        None,
+    );
+    for v in ["1", "2", "3", "4", "5"] {
+        put(&mut cache, "test/slow", v);
+        put(&mut cache, "test/fast/a", v);
+    }
+    assert_eq!(values(&cache, "test/slow"), ["4", "5"]);
+    assert_eq!(values(&cache, "test/fast/a"), ["2", "3", "4", "5"]);
+    assert_eq!(values(&cache, "test/**").len(), 6);
+
+    // memory limit, dropping the oldest publications of all the resources
+    // (each publication takes 7 bytes: a 6 bytes key and a 1 byte payload)
+    let mut cache = Cache::new(key("test/*"), 10, vec![], None, Some(21), None, None);
+    for v in ["1", "2", "3"] {
+        put(&mut cache, "test/a", v);
+        put(&mut cache, "test/b", v);
+    }
+    assert_eq!(cache.memory, 21);
+    assert_eq!(values(&cache, "test/a"), ["3"]);
+    assert_eq!(values(&cache, "test/b"), ["2", "3"]);
+
+    // memory limit, spilling the oldest publications
+    let mut cache = Cache::new(
+        key("test/*"),
+        4,
+        vec![],
+        None,
+        Some(14),
+        None,
+        Some(dir.clone()),
+    );
+    cache.load(&key("test/*"));
+    for v in ["1", "2", "3", "4", "5", "6"] {
+        put(&mut cache, "test/a", v);
+    }
+    assert_eq!(cache.memory, 14);
+    assert_eq!(values(&cache, "test/a"), ["3", "4", "5", "6"]);
+    cache.flush();
+    assert_eq!(cache.memory, 0);
+    assert_eq!(values(&cache, "test/a"), ["3", "4", "5", "6"]);
+
+    // the spilled publications are loaded back
+    let mut cache = Cache::new(
+        key("test/*"),
+        2,
+        vec![],
+        None,
+        None,
+        None,
+        Some(dir.clone()),
+    );
+    cache.load(&key("test/*"));
+    assert_eq!(values(&cache, "test/a"), ["5", "6"]);
+
+    // ttl
+    let mut cache = Cache::new(
+        key("test/*"),
+        10,
+        vec![],
+        None,
+        None,
+        Some(Duration::from_secs(60)),
+        Some(dir.clone()),
+    );
+    cache.insert(
+        key("test/b"),
+        Sample::new(key("test/b"), "old"),
+        SystemTime::now() - Duration::from_secs(120),
+    );
+    put(&mut cache, "test/b", "new");
+    assert_eq!(values(&cache, "test/b"), ["new"]);
+    cache.evict_expired();
+    assert_eq!(cache.keys[&key("test/b")].len(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
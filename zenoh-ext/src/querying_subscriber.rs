@@ -590,6 +590,8 @@ where
 /// Later on, new fetches can be performed again, calling [`FetchingSubscriber::fetch()`](super::FetchingSubscriber::fetch()).
 ///
 /// A typical usage of the `FetchingSubscriber` is to retrieve publications that were made in the past, but stored in some zenoh Storage.
+/// The fetched samples keep their kind: the deletions replied to a history query (e.g. with a `_time` parameter) are delivered
+/// as samples of kind [`SampleKind::Delete`](zenoh::prelude::SampleKind::Delete), in their timestamp order with the other samples.
 ///
 /// # Examples
 /// ```no_run
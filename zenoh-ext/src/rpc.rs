@@ -0,0 +1,313 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::TypedEncoding;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::query::{ConsolidationMode, Reply};
+use zenoh::queryable::{Query, Queryable};
+use zenoh::Session;
+use zenoh_core::SyncResolve;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The selector parameter carrying the correlation id of a call.
+pub const RPC_ID_PARAM: &str = "_rpcid";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The value replied to a call: the response of the handler, or its error
+#[derive(Serialize, Deserialize)]
+struct RpcResponse<T> {
+    id: String,
+    result: Result<T, String>,
+}
+
+/// A client calling the procedures served with [`ServerEndpoint`]s.
+///
+/// A call is a query carrying the serialized request, with its correlation id in the
+/// [`RPC_ID_PARAM`] selector parameter. The first response with this id is returned to the caller.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+/// use zenoh_ext::{ClientEndpoint, ServerEndpoint};
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let server = ServerEndpoint::serve(&session, "demo/sum", |terms: Vec<i64>| {
+///     Ok::<_, String>(terms.iter().sum::<i64>())
+/// })
+/// .unwrap();
+/// let client = ClientEndpoint::new(&session);
+/// let sum: i64 = client.call_async("demo/sum", &vec![1, 2, 3]).await.unwrap();
+/// assert_eq!(sum, 6);
+/// # })
+/// ```
+pub struct ClientEndpoint<'a> {
+    session: &'a Session,
+    encoding: TypedEncoding,
+    timeout: Duration,
+    next_id: AtomicU64,
+}
+
+impl<'a> ClientEndpoint<'a> {
+    /// The requests are serialized in JSON by default, and the calls time out after 10 seconds.
+    pub fn new(session: &'a Session) -> Self {
+        ClientEndpoint {
+            session,
+            encoding: TypedEncoding::default(),
+            timeout: DEFAULT_TIMEOUT,
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Changes the format the requests are serialized with, the responses being serialized with the same.
+    pub fn encoding(mut self, encoding: TypedEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Changes the time after which a call without response fails.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn send<'b, TryIntoKeyExpr, Req>(
+        &self,
+        key_expr: TryIntoKeyExpr,
+        request: &Req,
+    ) -> ZResult<(String, flume::Receiver<Reply>)>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        Req: Serialize + ?Sized,
+    {
+        let id = format!(
+            "{}-{}",
+            self.session.zid(),
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut selector = Selector::from(key_expr.try_into().map_err(Into::into)?);
+        selector.extend([(RPC_ID_PARAM, &id)]);
+        let replies = self
+            .session
+            .get(selector)
+            .with_value(self.encoding.serialize(request)?)
+            .consolidation(ConsolidationMode::None)
+            .timeout(self.timeout)
+            .res_sync()?;
+        Ok((id, replies))
+    }
+
+    /// Sends a request to the server of `key_expr` and waits for its response.
+    pub fn call<'b, TryIntoKeyExpr, Req, Resp>(
+        &self,
+        key_expr: TryIntoKeyExpr,
+        request: &Req,
+    ) -> ZResult<Resp>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        Req: Serialize + ?Sized,
+        Resp: DeserializeOwned,
+    {
+        let (id, replies) = self.send(key_expr, request)?;
+        while let Ok(reply) = replies.recv() {
+            if let Some(response) = response(&id, reply)? {
+                return Ok(response);
+            }
+        }
+        bail!("No response to call {}", id)
+    }
+
+    /// Sends a request to the server of `key_expr` and waits for its response.
+    pub async fn call_async<'b, TryIntoKeyExpr, Req, Resp>(
+        &self,
+        key_expr: TryIntoKeyExpr,
+        request: &Req,
+    ) -> ZResult<Resp>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        Req: Serialize + ?Sized,
+        Resp: DeserializeOwned,
+    {
+        let (id, replies) = self.send(key_expr, request)?;
+        while let Ok(reply) = replies.recv_async().await {
+            if let Some(response) = response(&id, reply)? {
+                return Ok(response);
+            }
+        }
+        bail!("No response to call {}", id)
+    }
+}
+
+// Returns the response carried by a reply to the call `id`, None if the reply is for another call
+fn response<Resp: DeserializeOwned>(id: &str, reply: Reply) -> ZResult<Option<Resp>> {
+    let value = match reply.sample {
+        Ok(sample) => sample.value,
+        Err(e) => bail!("Call {} failed: {}", id, e),
+    };
+    let response: RpcResponse<Resp> = TypedEncoding::deserialize(&value)
+        .map_err(|e| zerror!("Invalid response to call {}: {}", id, e))?;
+    if response.id != id {
+        log::warn!(
+            "Dropping the response to call {} received for call {}",
+            response.id,
+            id
+        );
+        return Ok(None);
+    }
+    match response.result {
+        Ok(response) => Ok(Some(response)),
+        Err(e) => bail!("Call {} failed: {}", id, e),
+    }
+}
+
+/// A queryable serving a procedure to the [`ClientEndpoint`]s calling it.
+///
+/// Each request is deserialized and passed to the handler, whose response or error
+/// is replied with the format of the request. The procedure is served until the endpoint is dropped.
+pub struct ServerEndpoint<'a> {
+    queryable: Queryable<'a, ()>,
+}
+
+impl<'a> ServerEndpoint<'a> {
+    /// Serves the procedure implemented by `handler` on `key_expr`.
+    pub fn serve<'b, TryIntoKeyExpr, Req, Resp, E, F>(
+        session: &'a Session,
+        key_expr: TryIntoKeyExpr,
+        handler: F,
+    ) -> ZResult<Self>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        Req: DeserializeOwned,
+        Resp: Serialize,
+        E: Display,
+        F: Fn(Req) -> Result<Resp, E> + Send + Sync + 'static,
+    {
+        let queryable = session
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                if let Err(e) = reply(&query, &handler) {
+                    log::warn!("Failed to reply to call {}: {}", query.selector(), e);
+                }
+            })
+            .res_sync()?;
+        Ok(ServerEndpoint { queryable })
+    }
+
+    pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
+        self.queryable.undeclare()
+    }
+}
+
+fn reply<Req, Resp, E, F>(query: &Query, handler: &F) -> ZResult<()>
+where
+    Req: DeserializeOwned,
+    Resp: Serialize,
+    E: Display,
+    F: Fn(Req) -> Result<Resp, E>,
+{
+    let id = match query
+        .selector()
+        .parameters_stringmap()?
+        .remove(RPC_ID_PARAM)
+    {
+        Some(id) => id,
+        None => bail!("the query carries no {} parameter", RPC_ID_PARAM),
+    };
+    let (encoding, result) = match query.value() {
+        Some(value) => (
+            TypedEncoding::of(value).unwrap_or_default(),
+            TypedEncoding::deserialize(value)
+                .map_err(|e| format!("invalid request: {e}"))
+                .and_then(|request| handler(request).map_err(|e| e.to_string())),
+        ),
+        None => (
+            TypedEncoding::default(),
+            Err("the query carries no request".to_string()),
+        ),
+    };
+    let value = encoding.serialize(&RpcResponse { id, result })?;
+    query
+        .reply(Ok(Sample::new(query.key_expr().clone(), value)))
+        .res_sync()
+}
+
+#[test]
+fn rpc_calls() {
+    use zenoh::config::Config;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Division {
+        dividend: i64,
+        divisor: i64,
+    }
+
+    let mut config = Config::default();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = zenoh::open(config).res_sync().unwrap();
+    let server = ServerEndpoint::serve(&session, "test/rpc/div", |div: Division| {
+        if div.divisor == 0 {
+            return Err("division by zero");
+        }
+        Ok(div.dividend / div.divisor)
+    })
+    .unwrap();
+
+    let client = ClientEndpoint::new(&session).timeout(Duration::from_secs(1));
+    let quotient: i64 = client
+        .call(
+            "test/rpc/div",
+            &Division {
+                dividend: 7,
+                divisor: 2,
+            },
+        )
+        .unwrap();
+    assert_eq!(quotient, 3);
+    let e = client
+        .call::<_, _, i64>(
+            "test/rpc/div",
+            &Division {
+                dividend: 7,
+                divisor: 0,
+            },
+        )
+        .unwrap_err();
+    assert!(e.to_string().contains("division by zero"));
+    assert!(client.call::<_, _, i64>("test/rpc/div", "7/2").is_err());
+    assert!(client.call::<_, _, i64>("test/rpc/none", &()).is_err());
+
+    let client = client.encoding(TypedEncoding::Bincode);
+    let quotient: i64 = async_std::task::block_on(client.call_async(
+        "test/rpc/div",
+        &Division {
+            dividend: 9,
+            divisor: 3,
+        },
+    ))
+    .unwrap();
+    assert_eq!(quotient, 3);
+
+    server.undeclare().res_sync().unwrap();
+    session.close().res_sync().unwrap();
+}
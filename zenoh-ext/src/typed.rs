@@ -0,0 +1,276 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+use zenoh::prelude::r#async::*;
+use zenoh::publication::{Publication, Publisher};
+use zenoh::subscriber::FlumeSubscriber;
+use zenoh::time::Timestamp;
+use zenoh_result::{bail, zerror, Error, ZResult};
+
+const BINCODE_FORMAT: &str = ";format=bincode";
+
+/// The serialization format of the values exchanged by a [`TypedPublisher`] and a [`TypedSubscriber`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TypedEncoding {
+    /// JSON, with the `application/json` encoding, readable by any subscriber.
+    #[default]
+    Json,
+    /// bincode, with the `application/octet-stream;format=bincode` encoding, more compact but only readable from Rust.
+    Bincode,
+}
+
+impl TypedEncoding {
+    /// Returns the format a value was serialized with, according to its encoding.
+    pub fn of(value: &Value) -> Option<Self> {
+        match value.encoding.prefix() {
+            KnownEncoding::AppJson | KnownEncoding::TextJson => Some(TypedEncoding::Json),
+            KnownEncoding::AppOctetStream if value.encoding.suffix() == BINCODE_FORMAT => {
+                Some(TypedEncoding::Bincode)
+            }
+            _ => None,
+        }
+    }
+
+    /// Serializes `value` into a [`Value`] with the encoding of this format.
+    pub fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> ZResult<Value> {
+        match self {
+            TypedEncoding::Json => {
+                let bytes = serde_json::to_vec(value).map_err(|e| zerror!("{}", e))?;
+                Ok(Value::from(bytes).encoding(Encoding::APP_JSON))
+            }
+            TypedEncoding::Bincode => {
+                let bytes = bincode::serialize(value).map_err(|e| zerror!("{}", e))?;
+                Ok(Value::from(bytes)
+                    .encoding(Encoding::APP_OCTET_STREAM.with_suffix(BINCODE_FORMAT)?))
+            }
+        }
+    }
+
+    /// Deserializes `value`, with the format its encoding designates.
+    pub fn deserialize<T: DeserializeOwned>(value: &Value) -> ZResult<T> {
+        let bytes = value.payload.contiguous();
+        match Self::of(value) {
+            Some(TypedEncoding::Json) => {
+                serde_json::from_slice(&bytes).map_err(|e| zerror!("{}", e).into())
+            }
+            Some(TypedEncoding::Bincode) => {
+                bincode::deserialize(&bytes).map_err(|e| zerror!("{}", e).into())
+            }
+            None => bail!("Unsupported encoding {}", value.encoding),
+        }
+    }
+}
+
+/// A [`Publisher`] putting values of type `T`, serialized with its [`TypedEncoding`].
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use serde::{Deserialize, Serialize};
+/// use zenoh::prelude::r#async::*;
+/// use zenoh_ext::{TypedPublisher, TypedSubscriber};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Temperature {
+///     celsius: f32,
+/// }
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let subscriber = session.declare_subscriber("demo/temperature").res().await.unwrap();
+/// let subscriber = TypedSubscriber::<Temperature>::new(subscriber)
+///     .on_error(|sample, e| eprintln!("Invalid temperature on {}: {}", sample.key_expr, e));
+/// let publisher = session.declare_publisher("demo/temperature").res().await.unwrap();
+/// let publisher = TypedPublisher::<Temperature>::new(publisher);
+/// publisher.put(&Temperature { celsius: 21.5 }).unwrap().res().await.unwrap();
+///
+/// let sample = subscriber.recv_async().await.unwrap();
+/// assert_eq!(sample.value, Some(Temperature { celsius: 21.5 }));
+/// # })
+/// ```
+pub struct TypedPublisher<'a, T> {
+    publisher: Publisher<'a>,
+    encoding: TypedEncoding,
+    _type: PhantomData<fn(&T)>,
+}
+
+impl<'a, T: Serialize> TypedPublisher<'a, T> {
+    /// The values are serialized in JSON by default.
+    pub fn new(publisher: Publisher<'a>) -> Self {
+        TypedPublisher {
+            publisher,
+            encoding: TypedEncoding::default(),
+            _type: PhantomData,
+        }
+    }
+
+    /// Changes the format the values are serialized with.
+    pub fn encoding(mut self, encoding: TypedEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'a> {
+        self.publisher.key_expr()
+    }
+
+    /// Serializes and puts a value.
+    pub fn put(&self, value: &T) -> ZResult<Publication> {
+        Ok(self.publisher.put(self.encoding.serialize(value)?))
+    }
+
+    /// Deletes data, which carries no value to serialize.
+    pub fn delete(&self) -> Publication {
+        self.publisher.delete()
+    }
+
+    pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
+        self.publisher.undeclare()
+    }
+}
+
+/// A sample received by a [`TypedSubscriber`].
+#[derive(Debug, Clone)]
+pub struct TypedSample<T> {
+    pub key_expr: KeyExpr<'static>,
+    pub kind: SampleKind,
+    /// The deserialized value, `None` for a [`SampleKind::Delete`] sample.
+    pub value: Option<T>,
+    pub timestamp: Option<Timestamp>,
+}
+
+impl<T: DeserializeOwned> TryFrom<Sample> for TypedSample<T> {
+    /// The sample whose value cannot be deserialized, with the error.
+    type Error = (Sample, Error);
+
+    fn try_from(sample: Sample) -> Result<Self, Self::Error> {
+        let value = match sample.kind {
+            SampleKind::Put => match TypedEncoding::deserialize(&sample.value) {
+                Ok(value) => Some(value),
+                Err(e) => return Err((sample, e)),
+            },
+            SampleKind::Delete => None,
+        };
+        Ok(TypedSample {
+            key_expr: sample.key_expr,
+            kind: sample.kind,
+            value,
+            timestamp: sample.timestamp,
+        })
+    }
+}
+
+type ErrorCallback<'a> = Box<dyn Fn(Sample, Error) + Send + Sync + 'a>;
+
+/// A subscriber deserializing the values it receives into a `T`, whatever the [`TypedEncoding`]
+/// they were published with.
+///
+/// The samples whose value cannot be deserialized are skipped, and passed along with the error
+/// to the callback set with [`on_error`](TypedSubscriber::on_error), or logged if there is none.
+pub struct TypedSubscriber<'a, T> {
+    subscriber: FlumeSubscriber<'a>,
+    on_error: Option<ErrorCallback<'a>>,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: DeserializeOwned> TypedSubscriber<'a, T> {
+    pub fn new(subscriber: FlumeSubscriber<'a>) -> Self {
+        TypedSubscriber {
+            subscriber,
+            on_error: None,
+            _type: PhantomData,
+        }
+    }
+
+    /// Sets the callback called with the samples whose value cannot be deserialized.
+    pub fn on_error<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(Sample, Error) + Send + Sync + 'a,
+    {
+        self.on_error = Some(Box::new(callback));
+        self
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        self.subscriber.key_expr()
+    }
+
+    fn deserialize(&self, sample: Sample) -> Option<TypedSample<T>> {
+        match TypedSample::try_from(sample) {
+            Ok(sample) => Some(sample),
+            Err((sample, e)) => {
+                match &self.on_error {
+                    Some(on_error) => on_error(sample, e),
+                    None => log::warn!("Invalid value on {}: {}", sample.key_expr, e),
+                }
+                None
+            }
+        }
+    }
+
+    /// Receives the next sample whose value can be deserialized.
+    pub fn recv(&self) -> ZResult<TypedSample<T>> {
+        loop {
+            let sample = self.subscriber.recv().map_err(|e| zerror!("{}", e))?;
+            if let Some(sample) = self.deserialize(sample) {
+                return Ok(sample);
+            }
+        }
+    }
+
+    /// Receives the next sample whose value can be deserialized.
+    pub async fn recv_async(&self) -> ZResult<TypedSample<T>> {
+        loop {
+            let sample = self
+                .subscriber
+                .recv_async()
+                .await
+                .map_err(|e| zerror!("{}", e))?;
+            if let Some(sample) = self.deserialize(sample) {
+                return Ok(sample);
+            }
+        }
+    }
+
+    pub fn undeclare(self) -> impl Resolve<ZResult<()>> + 'a {
+        self.subscriber.undeclare()
+    }
+}
+
+#[test]
+fn typed_encoding() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Reading {
+        id: u32,
+        label: String,
+    }
+
+    let reading = Reading {
+        id: 7,
+        label: "a".into(),
+    };
+    for encoding in [TypedEncoding::Json, TypedEncoding::Bincode] {
+        let value = encoding.serialize(&reading).unwrap();
+        assert_eq!(TypedEncoding::of(&value), Some(encoding));
+        assert_eq!(
+            TypedEncoding::deserialize::<Reading>(&value).unwrap(),
+            reading
+        );
+    }
+    let value = TypedEncoding::Json.serialize(&reading).unwrap();
+    assert_eq!(value.to_string(), r#"{"id":7,"label":"a"}"#);
+    assert!(TypedEncoding::deserialize::<u32>(&value).is_err());
+    assert!(TypedEncoding::deserialize::<Reading>(&Value::from("text")).is_err());
+}
@@ -11,26 +11,31 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use crate::{
-    keyexpr,
-    prelude::sync::{KeyExpr, Locality, SampleKind},
-    queryable::Query,
-    sample::DataInfo,
-    Sample, Session, ZResult,
-};
-use async_std::task;
+use crate::{keyexpr, prelude::sync::SampleKind, sample::DataInfo, Session, ZResult};
 use std::{
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
-    sync::Arc,
+    sync::{Arc, Mutex},
+    time::SystemTime,
 };
-use zenoh_core::SyncResolve;
+use zenoh_core::zlock;
 use zenoh_protocol::{
-    core::{Encoding, KnownEncoding, WireExpr},
+    core::{Encoding, KnownEncoding, WhatAmI, WireExpr},
     network::NetworkMessage,
 };
-use zenoh_transport::{
-    TransportEventHandler, TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
+#[cfg(feature = "transport_multicast")]
+use zenoh_transport::TransportMulticastEventHandler;
+use zenoh_transport::{TransportEventHandler, TransportPeerEventHandler};
+#[cfg(feature = "queries")]
+use {
+    crate::{
+        prelude::sync::{KeyExpr, Locality},
+        queryable::Query,
+        Sample,
+    },
+    async_std::task,
+    zenoh_core::SyncResolve,
+    zenoh_transport::TransportPeer,
 };
 
 macro_rules! ke_for_sure {
@@ -40,12 +45,16 @@ macro_rules! ke_for_sure {
 }
 
 lazy_static::lazy_static!(
+    #[cfg(feature = "queries")]
     static ref KE_STARSTAR: &'static keyexpr = ke_for_sure!("**");
     static ref KE_PREFIX: &'static keyexpr = ke_for_sure!("@/session");
     static ref KE_TRANSPORT_UNICAST: &'static keyexpr = ke_for_sure!("transport/unicast");
+    static ref KE_RECONNECT: &'static keyexpr = ke_for_sure!("reconnect");
+    #[cfg(feature = "queries")]
     static ref KE_LINK: &'static keyexpr = ke_for_sure!("link");
 );
 
+#[cfg(feature = "queries")]
 pub(crate) fn init(session: &Session) {
     if let Ok(own_zid) = keyexpr::new(&session.zid().to_string()) {
         let admin_key = KeyExpr::from(*KE_PREFIX / own_zid / *KE_STARSTAR)
@@ -64,6 +73,7 @@ pub(crate) fn init(session: &Session) {
     }
 }
 
+#[cfg(feature = "queries")]
 pub(crate) fn on_admin_query(session: &Session, query: Query) {
     fn reply_peer(own_zid: &keyexpr, query: &Query, peer: TransportPeer) {
         let zid = peer.zid.to_string();
@@ -97,6 +107,7 @@ pub(crate) fn on_admin_query(session: &Session, query: Query) {
                 reply_peer(own_zid, &query, peer);
             }
         }
+        #[cfg(feature = "transport_multicast")]
         for transport in task::block_on(session.runtime.manager().get_transports_multicast()) {
             for peer in transport.get_peers().unwrap_or_default() {
                 reply_peer(own_zid, &query, peer);
@@ -108,35 +119,44 @@ pub(crate) fn on_admin_query(session: &Session, query: Query) {
 #[derive(Clone)]
 pub(crate) struct Handler {
     pub(crate) session: Arc<Session>,
+    // When a client lost its connection to the router, the time it did
+    pub(crate) disconnected: Arc<Mutex<Option<SystemTime>>>,
 }
 
 impl Handler {
     pub(crate) fn new(session: Session) -> Self {
         Self {
             session: Arc::new(session),
+            disconnected: Arc::new(Mutex::new(None)),
         }
     }
-}
 
-impl TransportEventHandler for Handler {
-    fn new_unicast(
-        &self,
-        peer: zenoh_transport::TransportPeer,
-        _transport: zenoh_transport::TransportUnicast,
-    ) -> ZResult<Arc<dyn zenoh_transport::TransportPeerEventHandler>> {
-        self.new_peer(peer)
-    }
-
-    fn new_multicast(
-        &self,
-        _transport: zenoh_transport::TransportMulticast,
-    ) -> ZResult<Arc<dyn zenoh_transport::TransportMulticastEventHandler>> {
-        Ok(Arc::new(self.clone()))
+    // Publishes the outage window of a client once it reconnected
+    fn reconnected(&self, own_zid: &keyexpr, peer: &zenoh_transport::TransportPeer) {
+        let Some(disconnected) = zlock!(self.disconnected).take() else {
+            return;
+        };
+        let reconnected = SystemTime::now();
+        let outage = reconnected.duration_since(disconnected).unwrap_or_default();
+        let event = serde_json::json!({
+            "zid": peer.zid.to_string(),
+            "disconnected": humantime::format_rfc3339(disconnected).to_string(),
+            "reconnected": humantime::format_rfc3339(reconnected).to_string(),
+            "outage_ms": outage.as_millis() as u64,
+        });
+        let info = DataInfo {
+            encoding: Some(Encoding::Exact(KnownEncoding::AppJson)),
+            ..Default::default()
+        };
+        self.session.handle_data(
+            true,
+            &WireExpr::from(&(*KE_PREFIX / own_zid / *KE_RECONNECT)).to_owned(),
+            Some(info),
+            event.to_string().into_bytes().into(),
+        );
     }
-}
 
-impl TransportMulticastEventHandler for Handler {
-    fn new_peer(
+    fn peer_handler(
         &self,
         peer: zenoh_transport::TransportPeer,
     ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
@@ -154,9 +174,11 @@ impl TransportMulticastEventHandler for Handler {
                     Some(info),
                     serde_json::to_vec(&peer).unwrap().into(),
                 );
+                self.reconnected(own_zid, &peer);
                 Ok(Arc::new(PeerHandler {
                     expr,
                     session: self.session.clone(),
+                    disconnected: self.disconnected.clone(),
                 }))
             } else {
                 bail!("Unable to build keyexpr from zid")
@@ -165,6 +187,34 @@ impl TransportMulticastEventHandler for Handler {
             bail!("Unable to build keyexpr from zid")
         }
     }
+}
+
+impl TransportEventHandler for Handler {
+    fn new_unicast(
+        &self,
+        peer: zenoh_transport::TransportPeer,
+        _transport: zenoh_transport::TransportUnicast,
+    ) -> ZResult<Arc<dyn zenoh_transport::TransportPeerEventHandler>> {
+        self.peer_handler(peer)
+    }
+
+    #[cfg(feature = "transport_multicast")]
+    fn new_multicast(
+        &self,
+        _transport: zenoh_transport::TransportMulticast,
+    ) -> ZResult<Arc<dyn zenoh_transport::TransportMulticastEventHandler>> {
+        Ok(Arc::new(self.clone()))
+    }
+}
+
+#[cfg(feature = "transport_multicast")]
+impl TransportMulticastEventHandler for Handler {
+    fn new_peer(
+        &self,
+        peer: zenoh_transport::TransportPeer,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        self.peer_handler(peer)
+    }
 
     fn closing(&self) {}
 
@@ -178,6 +228,7 @@ impl TransportMulticastEventHandler for Handler {
 pub(crate) struct PeerHandler {
     pub(crate) expr: WireExpr<'static>,
     pub(crate) session: Arc<Session>,
+    pub(crate) disconnected: Arc<Mutex<Option<SystemTime>>>,
 }
 
 impl TransportPeerEventHandler for PeerHandler {
@@ -230,6 +281,10 @@ impl TransportPeerEventHandler for PeerHandler {
         };
         self.session
             .handle_data(true, &self.expr, Some(info), vec![0u8; 0].into());
+        // a client reconnects to a router once its connection dropped
+        if self.session.runtime.whatami == WhatAmI::Client {
+            zlock!(self.disconnected).get_or_insert_with(SystemTime::now);
+        }
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -0,0 +1,196 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Capture primitives: read the messages captured by the transports and replay them on a live session.
+//!
+//! The transports of a zenoh node write the messages they send and receive to the file configured
+//! in `transport/capture/file`. A [`Replay`] re-publishes the captured publications on a session,
+//! at the pace they were captured or at a scaled one, to reproduce a load or a bug.
+
+use crate::prelude::r#async::*;
+use crate::publication::Priority;
+use crate::Session;
+use async_std::task;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use zenoh_protocol::{
+    core::{ExprId, WireExpr, EMPTY_EXPR_ID},
+    network::{Declare, DeclareBody, DeclareKeyExpr, Mapping, NetworkBody, UndeclareKeyExpr},
+    zenoh::PushBody,
+};
+use zenoh_result::{bail, ZResult};
+pub use zenoh_transport::capture::{read_capture, CaptureRecord, Direction};
+
+/// A replay of the publications of a capture on a live [`Session`].
+///
+/// # Examples
+/// ```no_run
+/// # async_std::task::block_on(async {
+/// use zenoh::capture::Replay;
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let replay = Replay::open("zenoh.zcap").unwrap().speed(2.0);
+/// let count = replay.run(&session).await.unwrap();
+/// println!("Replayed {} publications", count);
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct Replay {
+    records: Vec<CaptureRecord>,
+    speed: f64,
+    direction: Direction,
+}
+
+impl Replay {
+    /// Reads the capture file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> ZResult<Replay> {
+        Ok(Replay::from(read_capture(path)?))
+    }
+
+    /// Change the speed factor of the replay (1.0 by default): 2.0 replays the publications twice
+    /// as fast as they were captured, and `f64::INFINITY` replays them without any delay.
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Change the direction of the replayed publications ([`Direction::Rx`] by default).
+    ///
+    /// A router captures both the publications it receives and the ones it forwards:
+    /// replaying a single direction avoids to publish the same data twice.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Re-publishes the captured publications on `session`, returning the number of publications replayed.
+    pub async fn run(&self, session: &Session) -> ZResult<usize> {
+        if self.speed.is_nan() || self.speed <= 0.0 {
+            bail!("Invalid replay speed: {}", self.speed);
+        }
+        let mut key_exprs = KeyExprs::default();
+        let origin = self.records.first().map(|r| r.time).unwrap_or_default();
+        let start = Instant::now();
+        let mut count = 0;
+        for record in self.records.iter() {
+            match &record.msg.body {
+                NetworkBody::Declare(Declare {
+                    body: DeclareBody::DeclareKeyExpr(DeclareKeyExpr { id, wire_expr }),
+                    ..
+                }) => {
+                    if let Some(key_expr) = key_exprs.resolve(record, wire_expr) {
+                        key_exprs.insert(record, *id, key_expr);
+                    }
+                }
+                NetworkBody::Declare(Declare {
+                    body: DeclareBody::UndeclareKeyExpr(UndeclareKeyExpr { id }),
+                    ..
+                }) => key_exprs.remove(record, *id),
+                NetworkBody::Push(push) if record.direction == self.direction => {
+                    let key_expr = match key_exprs
+                        .resolve(record, &push.wire_expr)
+                        .map(KeyExpr::try_from)
+                    {
+                        Some(Ok(key_expr)) => key_expr,
+                        _ => {
+                            log::warn!(
+                                "Unable to resolve the captured key expression {}",
+                                push.wire_expr
+                            );
+                            continue;
+                        }
+                    };
+
+                    if self.speed.is_finite() {
+                        let offset = record.time.saturating_sub(origin) as f64 / self.speed;
+                        let offset = Duration::from_nanos(offset as u64);
+                        let elapsed = start.elapsed();
+                        if offset > elapsed {
+                            task::sleep(offset - elapsed).await;
+                        }
+                    }
+
+                    let priority =
+                        Priority::try_from(push.ext_qos.get_priority() as u8).unwrap_or_default();
+                    let res = match &push.payload {
+                        PushBody::Put(put) => {
+                            let value =
+                                Value::new(put.payload.clone()).encoding(put.encoding.clone());
+                            session.put(key_expr, value)
+                        }
+                        PushBody::Del(_) => session.delete(key_expr),
+                    }
+                    .congestion_control(push.ext_qos.get_congestion_control())
+                    .priority(priority)
+                    .res_async()
+                    .await;
+                    match res {
+                        Ok(()) => count += 1,
+                        Err(e) => log::warn!("Unable to replay a publication: {}", e),
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl From<Vec<CaptureRecord>> for Replay {
+    fn from(records: Vec<CaptureRecord>) -> Self {
+        Replay {
+            records,
+            speed: 1.0,
+            direction: Direction::Rx,
+        }
+    }
+}
+
+// The key expressions declared on each link of the capture, in each direction
+#[derive(Default)]
+struct KeyExprs(HashMap<(ZenohId, Direction), HashMap<ExprId, String>>);
+
+impl KeyExprs {
+    fn resolve(&self, record: &CaptureRecord, wire_expr: &WireExpr) -> Option<String> {
+        if wire_expr.scope == EMPTY_EXPR_ID {
+            return Some(wire_expr.suffix.to_string());
+        }
+        // The scope of a key expression is declared by the sender or by the receiver of the message
+        let direction = match wire_expr.mapping {
+            Mapping::Sender => record.direction,
+            Mapping::Receiver => record.direction.reverse(),
+        };
+        let prefix = self
+            .0
+            .get(&(record.zid, direction))?
+            .get(&wire_expr.scope)?;
+        Some(format!("{}{}", prefix, wire_expr.suffix))
+    }
+
+    fn insert(&mut self, record: &CaptureRecord, id: ExprId, key_expr: String) {
+        self.0
+            .entry((record.zid, record.direction))
+            .or_default()
+            .insert(id, key_expr);
+    }
+
+    fn remove(&mut self, record: &CaptureRecord, id: ExprId) {
+        if let Some(key_exprs) = self.0.get_mut(&(record.zid, record.direction)) {
+            key_exprs.remove(&id);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Codec primitives: encode and decode the zenoh messages, independently of any session.
+//!
+//! This module exposes the messages of the zenoh protocol and their wire format, for tools such
+//! as dissectors, fuzzers or alternative implementations to parse and generate zenoh frames.
+//!
+//! The messages come in three layers:
+//! - the [`ScoutingMessage`]s, sent on their own in the UDP datagrams of the scouting;
+//! - the [`TransportMessage`]s, grouped in batches which are the unit of the links;
+//! - the [`NetworkMessage`]s, carried by the [`Frame`](transport::Frame)s and the
+//!   [`Fragment`](transport::Fragment)s of the transport. The payloads of the consecutive fragments
+//!   of a message are concatenated before being decoded.
+//!
+//! On the stream-oriented links (e.g. TCP), each batch is preceded by its length as a 16-bit
+//! little-endian integer, see [`frame`] and [`Deframer`]. The batches of the links negotiated with
+//! the compression start with an additional flag and are not handled by this module.
+//!
+//! # Examples
+//! ```
+//! use zenoh::codec::{decode_batch, encode_batch, transport::{KeepAlive, TransportMessage}};
+//!
+//! let batch = encode_batch(&[KeepAlive.into()]).unwrap();
+//! let messages: Vec<TransportMessage> = decode_batch(&batch).unwrap();
+//! assert_eq!(messages, vec![TransportMessage::from(KeepAlive)]);
+//! ```
+use zenoh_buffers::{
+    reader::{DidntRead, HasReader, Reader},
+    writer::{DidntWrite, HasWriter},
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::transport::BatchSize;
+use zenoh_result::{bail, zerror, ZResult};
+
+pub use zenoh_protocol::{core, network, scouting, transport, zenoh, VERSION};
+pub use zenoh_protocol::{
+    network::NetworkMessage, scouting::ScoutingMessage, transport::TransportMessage,
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A message of the zenoh protocol that can be encoded and decoded on its own:
+/// a [`ScoutingMessage`], a [`TransportMessage`] or a [`NetworkMessage`].
+pub trait Message: Sized + private::Sealed {
+    #[doc(hidden)]
+    fn write(&self, buffer: &mut Vec<u8>) -> Result<(), DidntWrite>;
+    #[doc(hidden)]
+    fn read(reader: &mut &[u8]) -> Result<Self, DidntRead>;
+}
+
+macro_rules! impl_message {
+    ($($T:ty),*) => {
+        $(
+            impl private::Sealed for $T {}
+
+            impl Message for $T {
+                fn write(&self, buffer: &mut Vec<u8>) -> Result<(), DidntWrite> {
+                    let mut writer = buffer.writer();
+                    Zenoh080::new().write(&mut writer, self)
+                }
+
+                fn read(reader: &mut &[u8]) -> Result<Self, DidntRead> {
+                    Zenoh080::new().read(reader)
+                }
+            }
+        )*
+    };
+}
+impl_message!(ScoutingMessage, TransportMessage, NetworkMessage);
+
+/// Encodes a message.
+pub fn encode<M: Message>(message: &M) -> ZResult<Vec<u8>> {
+    let mut buffer = vec![];
+    message
+        .write(&mut buffer)
+        .map_err(|_| zerror!("Failed to encode the message"))?;
+    Ok(buffer)
+}
+
+/// Decodes the message at the start of `bytes`, returning it along with the number of bytes it spans.
+pub fn decode<M: Message>(bytes: &[u8]) -> ZResult<(M, usize)> {
+    let mut reader = bytes.reader();
+    let message = M::read(&mut reader).map_err(|_| zerror!("Failed to decode the message"))?;
+    Ok((message, bytes.len() - reader.remaining()))
+}
+
+/// Encodes the transport messages as a batch.
+pub fn encode_batch(messages: &[TransportMessage]) -> ZResult<Vec<u8>> {
+    let mut buffer = vec![];
+    for (i, message) in messages.iter().enumerate() {
+        message
+            .write(&mut buffer)
+            .map_err(|_| zerror!("Failed to encode the message {} of the batch", i))?;
+    }
+    if buffer.len() > BatchSize::MAX as usize {
+        bail!(
+            "The batch of {} bytes exceeds the maximum size of {} bytes",
+            buffer.len(),
+            BatchSize::MAX
+        );
+    }
+    Ok(buffer)
+}
+
+/// Decodes the transport messages of a batch.
+pub fn decode_batch(batch: &[u8]) -> ZResult<Vec<TransportMessage>> {
+    let mut reader = batch.reader();
+    let mut messages = vec![];
+    while reader.can_read() {
+        let offset = batch.len() - reader.remaining();
+        let message = TransportMessage::read(&mut reader).map_err(|_| {
+            zerror!(
+                "Failed to decode the message at offset {} of the batch",
+                offset
+            )
+        })?;
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+/// Prefixes a batch with its length, as sent on the stream-oriented links.
+pub fn frame(batch: &[u8]) -> ZResult<Vec<u8>> {
+    let len = BatchSize::try_from(batch.len())
+        .map_err(|_| zerror!("The batch of {} bytes is too large", batch.len()))?;
+    let mut buffer = Vec::with_capacity(batch.len() + len.to_le_bytes().len());
+    buffer.extend_from_slice(&len.to_le_bytes());
+    buffer.extend_from_slice(batch);
+    Ok(buffer)
+}
+
+/// Splits the bytes received on a stream-oriented link into batches.
+///
+/// # Examples
+/// ```
+/// use zenoh::codec::{frame, Deframer};
+///
+/// let bytes = [frame(b"abc").unwrap(), frame(b"de").unwrap()].concat();
+/// let mut deframer = Deframer::default();
+/// deframer.push(&bytes[..4]);
+/// assert_eq!(deframer.next_batch(), None);
+/// deframer.push(&bytes[4..]);
+/// assert_eq!(deframer.next_batch().as_deref(), Some(&b"abc"[..]));
+/// assert_eq!(deframer.next_batch().as_deref(), Some(&b"de"[..]));
+/// ```
+#[derive(Debug, Default)]
+pub struct Deframer {
+    buffer: Vec<u8>,
+}
+
+impl Deframer {
+    /// Appends the bytes received on the link.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Removes the next complete batch, if any.
+    pub fn next_batch(&mut self) -> Option<Vec<u8>> {
+        let header = BatchSize::MIN.to_le_bytes().len();
+        let len = BatchSize::from_le_bytes(self.buffer.get(..header)?.try_into().ok()?) as usize;
+        if self.buffer.len() < header + len {
+            return None;
+        }
+        let batch = self.buffer[header..header + len].to_vec();
+        self.buffer.drain(..header + len);
+        Some(batch)
+    }
+
+    /// The number of bytes received but not yet returned in a batch.
+    pub fn pending(&self) -> usize {
+        self.buffer.len()
+    }
+}
+
+#[test]
+fn codec_batches() {
+    use zenoh_protocol::{
+        core::{Reliability, WireExpr},
+        network::{ext, Push},
+        transport::{Close, Frame, KeepAlive},
+        zenoh::{PushBody, Put},
+    };
+
+    let push: NetworkMessage = Push {
+        wire_expr: WireExpr::from("demo/a").to_owned(),
+        ext_qos: ext::QoSType::default(),
+        ext_tstamp: None,
+        ext_nodeid: ext::NodeIdType::default(),
+        payload: PushBody::Put(Put {
+            timestamp: None,
+            encoding: Default::default(),
+            ext_sinfo: None,
+            #[cfg(feature = "shared-memory")]
+            ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
+            ext_unknown: vec![],
+            payload: vec![1u8, 2, 3].into(),
+        }),
+    }
+    .into();
+    let bytes = encode(&push).unwrap();
+    assert_eq!(
+        decode::<NetworkMessage>(&bytes).unwrap(),
+        (push.clone(), bytes.len())
+    );
+
+    let messages: Vec<TransportMessage> = vec![
+        Frame {
+            reliability: Reliability::Reliable,
+            sn: 42,
+            payload: vec![push.clone(), push],
+            ext_qos: transport::frame::ext::QoSType::default(),
+        }
+        .into(),
+        KeepAlive.into(),
+        Close {
+            reason: 0,
+            session: true,
+        }
+        .into(),
+    ];
+    let batch = encode_batch(&messages).unwrap();
+    assert_eq!(decode_batch(&batch).unwrap(), messages);
+    assert!(decode_batch(&batch[..batch.len() - 1]).is_err());
+
+    let mut deframer = Deframer::default();
+    for byte in [frame(&batch).unwrap(), frame(&batch).unwrap()].concat() {
+        deframer.push(&[byte]);
+    }
+    assert_eq!(deframer.next_batch(), Some(batch.clone()));
+    assert_eq!(deframer.next_batch(), Some(batch));
+    assert_eq!(deframer.next_batch(), None);
+    assert_eq!(deframer.pending(), 0);
+}
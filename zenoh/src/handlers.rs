@@ -14,6 +14,10 @@
 
 //! Callback handler trait.
 use crate::API_DATA_RECEPTION_CHANNEL_SIZE;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh_collections::RingBuffer;
+use zenoh_result::{zerror, ZResult};
 
 /// An alias for `Arc<T>`.
 pub type Dyn<T> = std::sync::Arc<T>;
@@ -39,6 +43,14 @@ where
         (Dyn::from(self), ())
     }
 }
+/// A [`flume`] channel, e.g. `flume::bounded(32)`, whose receiver can be used from both synchronous and asynchronous code:
+/// `recv()` blocks the calling thread until the next item is received, without requiring an async executor,
+/// while `recv_async()` awaits it.
+///
+/// The items are sent from the zenoh thread delivering them: when a bounded channel is full, this thread blocks
+/// until some room is made by the receiver, which stalls the delivery of the other items received on the same link.
+/// Slow receivers should thus either use a channel with a large enough capacity, an unbounded one, or drain it promptly.
+/// The items delivered once the receiver is dropped are discarded, and an error is logged.
 impl<T: Send + 'static> IntoCallbackReceiverPair<'static, T>
     for (flume::Sender<T>, flume::Receiver<T>)
 {
@@ -56,6 +68,8 @@ impl<T: Send + 'static> IntoCallbackReceiverPair<'static, T>
         )
     }
 }
+/// The handler used when none is given: a [`flume`] channel bounded to the `API_DATA_RECEPTION_CHANNEL_SIZE`
+/// environment variable, 256 by default.
 pub struct DefaultHandler;
 impl<T: Send + 'static> IntoCallbackReceiverPair<'static, T> for DefaultHandler {
     type Receiver = flume::Receiver<T>;
@@ -63,6 +77,9 @@ impl<T: Send + 'static> IntoCallbackReceiverPair<'static, T> for DefaultHandler
         flume::bounded(*API_DATA_RECEPTION_CHANNEL_SIZE).into_cb_receiver_pair()
     }
 }
+/// A [`std::sync::mpsc::sync_channel`], whose receiver blocks the calling thread until the next item is received.
+///
+/// Just like with a bounded [`flume`] channel, the zenoh thread delivering the items blocks while the channel is full.
 impl<T: Send + Sync + 'static> IntoCallbackReceiverPair<'static, T>
     for (std::sync::mpsc::SyncSender<T>, std::sync::mpsc::Receiver<T>)
 {
@@ -80,9 +97,206 @@ impl<T: Send + Sync + 'static> IntoCallbackReceiverPair<'static, T>
     }
 }
 
+/// A bounded [`flume`] channel whose receiver is a [`Stream`](futures::Stream) of the received items,
+/// so that they can be processed with the [`StreamExt`](futures::StreamExt) combinators.
+///
+/// The entities declared with a handler whose receiver is a stream, such as [`Subscriber`](crate::subscriber::Subscriber),
+/// are streams themselves. Just like with a bounded channel, the zenoh thread delivering the items blocks while the channel is full.
+///
+/// # Examples
+/// ```no_run
+/// # async_std::task::block_on(async {
+/// use futures::prelude::*;
+/// use zenoh::prelude::r#async::*;
+/// use zenoh::handlers::StreamChannel;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let subscriber = session
+///     .declare_subscriber("key/expression")
+///     .with(StreamChannel::new(32))
+///     .res()
+///     .await
+///     .unwrap();
+/// let mut values = subscriber.map(|sample| sample.value.to_string()).take(10);
+/// while let Some(value) = values.next().await {
+///     println!("Received: {}", value);
+/// }
+///
+/// let mut replies = session.get("key/expression").with(StreamChannel::new(32)).res().await.unwrap();
+/// while let Some(reply) = replies.next().await {
+///     println!("Reply: {:?}", reply.sample);
+/// }
+/// # })
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct StreamChannel {
+    capacity: usize,
+}
+
+impl StreamChannel {
+    pub fn new(capacity: usize) -> Self {
+        StreamChannel { capacity }
+    }
+}
+
+impl<T: Send + 'static> IntoCallbackReceiverPair<'static, T> for StreamChannel {
+    type Receiver = flume::r#async::RecvStream<'static, T>;
+
+    fn into_cb_receiver_pair(self) -> (Callback<'static, T>, Self::Receiver) {
+        let (callback, receiver) = flume::bounded(self.capacity).into_cb_receiver_pair();
+        (callback, receiver.into_stream())
+    }
+}
+
+/// A handler keeping only the `capacity` most recent items, for consumers that only ever want the freshest values.
+///
+/// Contrary to a bounded channel, the zenoh thread delivering the items never blocks: when the ring is full,
+/// the oldest item is silently dropped to make room for the new one.
+///
+/// # Examples
+/// ```no_run
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+/// use zenoh::handlers::RingChannel;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let subscriber = session
+///     .declare_subscriber("key/expression")
+///     .with(RingChannel::new(1))
+///     .res()
+///     .await
+///     .unwrap();
+/// while let Ok(sample) = subscriber.recv_async().await {
+///     println!("Latest: {} {}", sample.key_expr, sample.value);
+/// }
+/// # })
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RingChannel {
+    capacity: usize,
+}
+
+impl RingChannel {
+    /// A capacity of 0 is treated as 1.
+    pub fn new(capacity: usize) -> Self {
+        RingChannel {
+            capacity: capacity.max(1),
+        }
+    }
+}
+
+/// The receiver of a [`RingChannel`].
+///
+/// Once the callback is dropped (e.g. the subscriber is undeclared), the remaining items can still be received,
+/// after which the receiving methods return an error.
+pub struct RingChannelHandler<T> {
+    ring: Arc<Mutex<RingBuffer<T>>>,
+    not_empty: flume::Receiver<()>,
+}
+
+impl<T> RingChannelHandler<T> {
+    /// Returns the oldest item of the ring without blocking, `None` if the ring is empty.
+    pub fn try_recv(&self) -> Option<T> {
+        zlock!(self.ring).pull()
+    }
+
+    /// Blocks until an item is available and returns it.
+    pub fn recv(&self) -> ZResult<T> {
+        loop {
+            if let Some(t) = self.try_recv() {
+                return Ok(t);
+            }
+            self.not_empty.recv().map_err(|e| zerror!("{}", e))?;
+        }
+    }
+
+    /// Blocks until an item is available and returns it, or fails after `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> ZResult<T> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            if let Some(t) = self.try_recv() {
+                return Ok(t);
+            }
+            self.not_empty
+                .recv_deadline(deadline)
+                .map_err(|e| zerror!("{}", e))?;
+        }
+    }
+
+    /// Waits until an item is available and returns it.
+    pub async fn recv_async(&self) -> ZResult<T> {
+        loop {
+            if let Some(t) = self.try_recv() {
+                return Ok(t);
+            }
+            self.not_empty
+                .recv_async()
+                .await
+                .map_err(|e| zerror!("{}", e))?;
+        }
+    }
+}
+
+impl<T: Send + 'static> IntoCallbackReceiverPair<'static, T> for RingChannel {
+    type Receiver = RingChannelHandler<T>;
+
+    fn into_cb_receiver_pair(self) -> (Callback<'static, T>, Self::Receiver) {
+        let ring = Arc::new(Mutex::new(RingBuffer::new(self.capacity)));
+        // A single pending notification is enough to wake the receiver up, which then drains the ring
+        let (notify, not_empty) = flume::bounded(1);
+        let c_ring = ring.clone();
+        (
+            Dyn::new(move |t| {
+                zlock!(c_ring).push_force(t);
+                let _ = notify.try_send(());
+            }),
+            RingChannelHandler { ring, not_empty },
+        )
+    }
+}
+
 /// A function that can transform a [`FnMut`]`(T)` to
 /// a [`Fn`]`(T)` with the help of a [`Mutex`](std::sync::Mutex).
 pub fn locked<T>(fnmut: impl FnMut(T)) -> impl Fn(T) {
     let lock = std::sync::Mutex::new(fnmut);
     move |x| zlock!(lock)(x)
 }
+
+#[test]
+fn ring_channel() {
+    let (callback, receiver) = RingChannel::new(2).into_cb_receiver_pair();
+    for i in 0..10 {
+        callback(i);
+    }
+    // Only the most recent items are kept
+    assert_eq!(receiver.recv().unwrap(), 8);
+    assert_eq!(receiver.try_recv(), Some(9));
+    assert_eq!(receiver.try_recv(), None);
+    assert!(receiver.recv_timeout(Duration::from_millis(10)).is_err());
+
+    let t = std::thread::spawn(move || receiver.recv());
+    std::thread::sleep(Duration::from_millis(10));
+    callback(10);
+    assert_eq!(t.join().unwrap().unwrap(), 10);
+
+    let (callback, receiver) = RingChannel::new(2).into_cb_receiver_pair();
+    callback(0);
+    drop(callback);
+    // The remaining items are still received once the callback is dropped
+    assert_eq!(receiver.recv().unwrap(), 0);
+    assert!(receiver.recv().is_err());
+}
+
+#[test]
+fn stream_channel() {
+    use futures::StreamExt;
+
+    let (callback, receiver) = StreamChannel::new(4).into_cb_receiver_pair();
+    for i in 0..3 {
+        callback(i);
+    }
+    drop(callback);
+    // The stream ends once the callback is dropped and the remaining items are received
+    let items = async_std::task::block_on(receiver.collect::<Vec<_>>());
+    assert_eq!(items, vec![0, 1, 2]);
+}
@@ -504,6 +504,11 @@ impl std::ops::Div<&keyexpr> for &KeyExpr<'_> {
 }
 
 impl<'a> KeyExpr<'a> {
+    /// Fails with a `KeyExprConstructionError` if `self` isn't a valid key expression in canon form,
+    /// as only the unchecked constructors allow: the routers would ignore its declarations.
+    pub(crate) fn check_canon(&self) -> ZResult<()> {
+        keyexpr::new(self.as_str()).map(|_| ())
+    }
     //pub(crate) fn is_optimized(&self, session: &Session) -> bool {
     //    matches!(&self.0, KeyExprInner::Wire { expr_id, session_id, .. } | KeyExprInner::BorrowedWire { expr_id, session_id, .. } if *expr_id != 0 && session.id == *session_id)
     //}
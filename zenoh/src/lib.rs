@@ -84,10 +84,12 @@ use handlers::DefaultHandler;
 #[zenoh_macros::unstable]
 use net::runtime::Runtime;
 use prelude::*;
+#[cfg(feature = "scouting")]
 use scouting::ScoutBuilder;
 use std::future::Ready;
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
 pub use zenoh_macros::{kedefine, keformat, kewrite};
+#[cfg(feature = "scouting")]
 use zenoh_protocol::core::WhatAmIMatcher;
 use zenoh_result::{zerror, ZResult};
 
@@ -99,10 +101,16 @@ pub use zenoh_result::ZResult as Result;
 const GIT_VERSION: &str = git_version!(prefix = "v", cargo_prefix = "v");
 
 mod admin;
+mod offline;
 #[macro_use]
 mod session;
 pub use session::*;
 
+#[cfg(feature = "unstable")]
+pub mod capture;
+#[cfg(feature = "unstable")]
+pub mod codec;
+
 pub mod key_expr;
 pub(crate) mod net;
 pub use net::runtime;
@@ -115,10 +123,16 @@ pub mod info;
 pub mod liveliness;
 pub mod plugins;
 pub mod prelude;
+#[cfg(feature = "unstable")]
+pub mod presence;
 pub mod publication;
+#[cfg(feature = "queries")]
 pub mod query;
+#[cfg(feature = "queries")]
 pub mod queryable;
 pub mod sample;
+#[cfg(feature = "simulation")]
+pub mod simulation;
 pub mod subscriber;
 pub mod value;
 #[cfg(feature = "shared-memory")]
@@ -138,10 +152,17 @@ pub mod time {
     /// This operation should be called if a timestamp is required for an incoming [`zenoh::Sample`](crate::Sample)
     /// that doesn't contain any timestamp.
     pub fn new_reception_timestamp() -> Timestamp {
-        use std::time::{SystemTime, UNIX_EPOCH};
+        Timestamp::new(clock(), TimestampId::try_from([1]).unwrap())
+    }
 
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        Timestamp::new(now.into(), TimestampId::try_from([1]).unwrap())
+    /// The current time of the clock used to timestamp the samples and to compute the deadlines:
+    /// the virtual time of the running simulation with the `simulation` feature, the system time otherwise.
+    pub fn clock() -> NTP64 {
+        #[cfg(feature = "simulation")]
+        if let Some(now) = crate::simulation::virtual_time() {
+            return now;
+        }
+        uhlc::system_time_clock()
     }
 }
 
@@ -163,6 +184,7 @@ pub mod properties {
 }
 
 /// Scouting primitives.
+#[cfg(feature = "scouting")]
 pub mod scouting;
 
 /// Scout for routers and/or peers.
@@ -191,6 +213,7 @@ pub mod scouting;
 /// }
 /// # })
 /// ```
+#[cfg(feature = "scouting")]
 pub fn scout<I: Into<WhatAmIMatcher>, TryIntoConfig>(
     what: I,
     config: TryIntoConfig,
@@ -16,7 +16,8 @@
 //!
 //! see [`Liveliness`]
 
-use crate::query::Reply;
+#[cfg(feature = "queries")]
+use {crate::query::Reply, std::time::Duration, zenoh_config::unwrap_or_default};
 
 #[zenoh_macros::unstable]
 use {
@@ -30,8 +31,6 @@ use {
     std::convert::TryInto,
     std::future::Ready,
     std::sync::Arc,
-    std::time::Duration,
-    zenoh_config::unwrap_or_default,
     zenoh_core::AsyncResolve,
     zenoh_core::Resolvable,
     zenoh_core::Result as ZResult,
@@ -176,6 +175,7 @@ impl<'a> Liveliness<'a> {
     /// # })
     /// ```
     #[zenoh_macros::unstable]
+    #[cfg(feature = "queries")]
     pub fn get<'b: 'a, TryIntoKeyExpr>(
         &'a self,
         key_expr: TryIntoKeyExpr,
@@ -545,6 +545,8 @@ where
                 Locality::default(),
                 callback,
                 &SubscriberInfo::default(),
+                None,
+                None,
             )
             .map(|sub_state| Subscriber {
                 subscriber: SubscriberInner {
@@ -595,6 +597,7 @@ where
 /// }
 /// # })
 /// ```
+#[cfg(feature = "queries")]
 #[derive(Debug)]
 pub struct LivelinessGetBuilder<'a, 'b, Handler> {
     pub(crate) session: &'a Session,
@@ -603,6 +606,7 @@ pub struct LivelinessGetBuilder<'a, 'b, Handler> {
     pub(crate) handler: Handler,
 }
 
+#[cfg(feature = "queries")]
 impl<'a, 'b> LivelinessGetBuilder<'a, 'b, DefaultHandler> {
     /// Receive the replies for this query with a callback.
     ///
@@ -712,6 +716,7 @@ impl<'a, 'b> LivelinessGetBuilder<'a, 'b, DefaultHandler> {
     }
 }
 
+#[cfg(feature = "queries")]
 impl<'a, 'b, Handler> LivelinessGetBuilder<'a, 'b, Handler> {
     /// Set query timeout.
     #[inline]
@@ -721,6 +726,7 @@ impl<'a, 'b, Handler> LivelinessGetBuilder<'a, 'b, Handler> {
     }
 }
 
+#[cfg(feature = "queries")]
 impl<Handler> Resolvable for LivelinessGetBuilder<'_, '_, Handler>
 where
     Handler: IntoCallbackReceiverPair<'static, Reply> + Send,
@@ -729,6 +735,7 @@ where
     type To = ZResult<Handler::Receiver>;
 }
 
+#[cfg(feature = "queries")]
 impl<Handler> SyncResolve for LivelinessGetBuilder<'_, '_, Handler>
 where
     Handler: IntoCallbackReceiverPair<'static, Reply> + Send,
@@ -743,6 +750,7 @@ where
                 &Some(KeyExpr::from(*KE_PREFIX_LIVELINESS)),
                 QueryTarget::default(),
                 QueryConsolidation::default(),
+                None,
                 Locality::default(),
                 self.timeout,
                 None,
@@ -752,6 +760,7 @@ where
     }
 }
 
+#[cfg(feature = "queries")]
 impl<Handler> AsyncResolve for LivelinessGetBuilder<'_, '_, Handler>
 where
     Handler: IntoCallbackReceiverPair<'static, Reply> + Send,
@@ -0,0 +1,87 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::Zenoh080Routing;
+use crate::net::protocol::{dissemination, dissemination::Dissemination};
+use zenoh_buffers::{
+    reader::{DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::{
+    core::{Locator, ZenohId},
+    network::Push,
+};
+
+impl<W> WCodec<&Dissemination, &mut W> for Zenoh080Routing
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &Dissemination) -> Self::Output {
+        let codec = Zenoh080::new();
+        match x {
+            Dissemination::Offer { group } => {
+                codec.write(&mut *writer, dissemination::OFFER)?;
+                codec.write(&mut *writer, group)?;
+            }
+            Dissemination::Join => {
+                codec.write(&mut *writer, dissemination::JOIN)?;
+            }
+            Dissemination::Sample { sn, source, push } => {
+                codec.write(&mut *writer, dissemination::SAMPLE)?;
+                codec.write(&mut *writer, *sn)?;
+                codec.write(&mut *writer, source)?;
+                codec.write(&mut *writer, push)?;
+            }
+            Dissemination::Repair { from, to } => {
+                codec.write(&mut *writer, dissemination::REPAIR)?;
+                codec.write(&mut *writer, *from)?;
+                codec.write(&mut *writer, *to)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R> RCodec<Dissemination, &mut R> for Zenoh080Routing
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Dissemination, Self::Error> {
+        let codec = Zenoh080::new();
+        let id: u8 = codec.read(&mut *reader)?;
+        match id {
+            dissemination::OFFER => {
+                let group: Locator = codec.read(&mut *reader)?;
+                Ok(Dissemination::Offer { group })
+            }
+            dissemination::JOIN => Ok(Dissemination::Join),
+            dissemination::SAMPLE => {
+                let sn: u64 = codec.read(&mut *reader)?;
+                let source: ZenohId = codec.read(&mut *reader)?;
+                let push: Push = codec.read(&mut *reader)?;
+                Ok(Dissemination::Sample { sn, source, push })
+            }
+            dissemination::REPAIR => {
+                let from: u64 = codec.read(&mut *reader)?;
+                let to: u64 = codec.read(&mut *reader)?;
+                Ok(Dissemination::Repair { from, to })
+            }
+            _ => Err(DidntRead),
+        }
+    }
+}
@@ -11,7 +11,10 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+#[cfg(feature = "transport_multicast")]
+pub(crate) mod dissemination;
 pub(crate) mod linkstate;
+pub(crate) mod rendezvous;
 
 #[derive(Clone, Copy)]
 pub struct Zenoh080Routing;
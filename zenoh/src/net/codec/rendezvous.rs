@@ -0,0 +1,67 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::Zenoh080Routing;
+use crate::net::protocol::{rendezvous, rendezvous::Rendezvous};
+use zenoh_buffers::{
+    reader::{DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_protocol::{
+    common::imsg,
+    core::{Locator, ZenohId},
+};
+
+impl<W> WCodec<&Rendezvous, &mut W> for Zenoh080Routing
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &Rendezvous) -> Self::Output {
+        let codec = Zenoh080::new();
+        // Options
+        let mut options = 0;
+        if x.request {
+            options |= rendezvous::REQ;
+        }
+        codec.write(&mut *writer, options)?;
+
+        // Body
+        codec.write(&mut *writer, &x.zid)?;
+        codec.write(&mut *writer, x.locators.as_slice())?;
+
+        Ok(())
+    }
+}
+
+impl<R> RCodec<Rendezvous, &mut R> for Zenoh080Routing
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Rendezvous, Self::Error> {
+        let codec = Zenoh080::new();
+        let options: u64 = codec.read(&mut *reader)?;
+        let zid: ZenohId = codec.read(&mut *reader)?;
+        let locators: Vec<Locator> = codec.read(&mut *reader)?;
+
+        Ok(Rendezvous {
+            request: imsg::has_option(options, rendezvous::REQ),
+            zid,
+            locators,
+        })
+    }
+}
@@ -0,0 +1,38 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_protocol::{
+    core::{Locator, ZenohId},
+    network::Push,
+};
+
+pub const OFFER: u8 = 0x01;
+pub const JOIN: u8 = 0x02;
+pub const SAMPLE: u8 = 0x03;
+pub const REPAIR: u8 = 0x04;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Dissemination {
+    /// Sent by a router to its clients: the multicast group it disseminates samples on.
+    Offer { group: Locator },
+    /// Sent by a client to its router once it joined the offered group.
+    Join,
+    /// Sent by a router on the group: a disseminated sample, published by `source`.
+    Sample {
+        sn: u64,
+        source: ZenohId,
+        push: Push,
+    },
+    /// Sent by a client to its router: the disseminated samples it missed, from `from` to `to` excluded.
+    Repair { from: u64, to: u64 },
+}
@@ -11,4 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+#[cfg(feature = "transport_multicast")]
+pub(crate) mod dissemination;
 pub(crate) mod linkstate;
+pub(crate) mod rendezvous;
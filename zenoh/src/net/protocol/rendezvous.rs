@@ -0,0 +1,35 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_protocol::core::{Locator, ZenohId};
+
+pub const REQ: u64 = 1; // 0x01
+
+// A Rendezvous is sent by a peer to a router to ask for a hole punching attempt
+// towards the peer identified by zid (R == 1). The router then sends a Rendezvous
+// to both peers carrying the endpoints it observed for the other one (R == 0).
+//
+//  7 6 5 4 3 2 1 0
+// +-+-+-+-+-+-+-+-+
+// ~X|X|X|X|X|X|X|R~
+// +-+-+-+-+-+-+-+-+
+// ~      zid      ~
+// +---------------+
+// ~  [locators]   ~
+// +---------------+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Rendezvous {
+    pub(crate) request: bool,
+    pub(crate) zid: ZenohId,
+    pub(crate) locators: Vec<Locator>,
+}
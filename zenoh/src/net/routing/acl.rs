@@ -0,0 +1,112 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_config::{AclAction, AclConf, AclPermission, AclRule};
+use zenoh_protocol::core::key_expr::keyexpr;
+
+/// The access control rules evaluated on the declarations and messages received from remote nodes.
+///
+/// An action on a key expression is denied if it intersects a deny rule matching the node,
+/// otherwise allowed if it is included in an allow rule matching the node,
+/// otherwise ruled by the default permission.
+pub(crate) struct AccessControl {
+    default_permission: AclPermission,
+    rules: Vec<AclRule>,
+}
+
+impl AccessControl {
+    /// Returns `None` if access control is disabled.
+    pub(crate) fn new(config: &AclConf) -> Option<Self> {
+        config.enabled().then(|| AccessControl {
+            default_permission: *config.default_permission(),
+            rules: config.rules().clone(),
+        })
+    }
+
+    pub(crate) fn allows(&self, identities: &[String], action: AclAction, key_expr: &str) -> bool {
+        let key_expr = match keyexpr::new(key_expr) {
+            Ok(key_expr) => key_expr,
+            Err(_) => return false,
+        };
+        let mut allowed = false;
+        for rule in self.rules.iter().filter(|rule| {
+            rule.actions.contains(&action)
+                && (rule.identities.is_empty()
+                    || rule.identities.iter().any(|id| identities.contains(id)))
+        }) {
+            match rule.permission {
+                AclPermission::Deny => {
+                    if rule.key_exprs.iter().any(|ke| ke.intersects(key_expr)) {
+                        return false;
+                    }
+                }
+                AclPermission::Allow => {
+                    allowed |= rule.key_exprs.iter().any(|ke| ke.includes(key_expr));
+                }
+            }
+        }
+        allowed || self.default_permission == AclPermission::Allow
+    }
+}
+
+#[test]
+fn access_control() {
+    use zenoh_protocol::core::key_expr::OwnedKeyExpr;
+
+    let rule = |identities: &[&str], key_exprs: &[&str], actions, permission| AclRule {
+        identities: identities.iter().map(|id| id.to_string()).collect(),
+        key_exprs: key_exprs
+            .iter()
+            .map(|ke| OwnedKeyExpr::new(*ke).unwrap())
+            .collect(),
+        actions,
+        permission,
+    };
+    let mut config = AclConf::default();
+    assert!(AccessControl::new(&config).is_none());
+
+    config.set_enabled(true).unwrap();
+    config.set_default_permission(AclPermission::Deny).unwrap();
+    config
+        .set_rules(vec![
+            rule(
+                &[],
+                &["demo/**"],
+                vec![AclAction::Pub, AclAction::Sub],
+                AclPermission::Allow,
+            ),
+            rule(
+                &["username:bob"],
+                &["demo/secret/**"],
+                vec![AclAction::Sub],
+                AclPermission::Deny,
+            ),
+        ])
+        .unwrap();
+    let acl = AccessControl::new(&config).unwrap();
+    let alice = ["zid:1".to_string(), "username:alice".to_string()];
+    let bob = ["zid:2".to_string(), "username:bob".to_string()];
+
+    assert!(acl.allows(&alice, AclAction::Pub, "demo/a"));
+    assert!(acl.allows(&alice, AclAction::Sub, "demo/**"));
+    assert!(acl.allows(&alice, AclAction::Sub, "demo/secret/a"));
+    // Not included in an allow rule
+    assert!(!acl.allows(&alice, AclAction::Sub, "**"));
+    assert!(!acl.allows(&alice, AclAction::Pub, "other/a"));
+    assert!(!acl.allows(&alice, AclAction::Get, "demo/a"));
+    // Intersecting a deny rule
+    assert!(acl.allows(&bob, AclAction::Sub, "demo/a"));
+    assert!(!acl.allows(&bob, AclAction::Sub, "demo/secret/a"));
+    assert!(!acl.allows(&bob, AclAction::Sub, "demo/**"));
+    assert!(acl.allows(&bob, AclAction::Pub, "demo/secret/a"));
+}
@@ -0,0 +1,281 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde_json::Value;
+use std::cmp::Ordering;
+use zenoh_buffers::{SplitBuffer, ZBuf};
+use zenoh_protocol::core::{Encoding, KnownEncoding};
+use zenoh_result::{bail, zerror, ZResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Comparison {
+    path: Vec<String>,
+    op: Op,
+    value: Value,
+}
+
+impl Comparison {
+    fn matches(&self, payload: &Value) -> bool {
+        let Some(field) = self
+            .path
+            .iter()
+            .try_fold(payload, |value, field| value.get(field.as_str()))
+        else {
+            return false;
+        };
+        let ordering = match (field, &self.value) {
+            (Value::Number(l), Value::Number(r)) => l
+                .as_f64()
+                .zip(r.as_f64())
+                .and_then(|(l, r)| l.partial_cmp(&r)),
+            (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+            (l, r) => (l == r).then_some(Ordering::Equal),
+        };
+        match self.op {
+            Op::Eq => ordering == Some(Ordering::Equal),
+            Op::Ne => ordering != Some(Ordering::Equal),
+            Op::Gt => ordering == Some(Ordering::Greater),
+            Op::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            Op::Lt => ordering == Some(Ordering::Less),
+            Op::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        }
+    }
+}
+
+/// A predicate on the fields of JSON payloads, attached to a subscription.
+///
+/// It combines comparisons `<field>[.<field>] <op> <JSON value>` (e.g. `temp > 90` or `status.code == "ok"`),
+/// with `op` one of `==`, `!=`, `>`, `>=`, `<` and `<=`, with `&&` and `||`, `&&` binding tighter.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ContentFilter {
+    expr: String,
+    // a disjunction of conjunctions of comparisons
+    any: Vec<Vec<Comparison>>,
+}
+
+impl ContentFilter {
+    pub(crate) fn new(expr: &str) -> ZResult<Self> {
+        let mut parser = Parser { rest: expr };
+        let mut any = vec![];
+        loop {
+            let mut all = vec![parser.comparison()?];
+            while parser.eat("&&") {
+                all.push(parser.comparison()?);
+            }
+            any.push(all);
+            if parser.eat("||") {
+                continue;
+            }
+            parser.skip_whitespaces();
+            if !parser.rest.is_empty() {
+                bail!("Invalid filter `{}`: unexpected `{}`", expr, parser.rest)
+            }
+            break;
+        }
+        Ok(ContentFilter {
+            expr: expr.to_string(),
+            any,
+        })
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.expr
+    }
+
+    /// The filter admitting the payloads admitted by either `self` or `other`.
+    pub(crate) fn or(&self, other: &ContentFilter) -> ContentFilter {
+        ContentFilter {
+            expr: format!("{} || {}", self.expr, other.expr),
+            any: self.any.iter().chain(other.any.iter()).cloned().collect(),
+        }
+    }
+
+    /// The filter admitting the payloads admitted by any of `filters`, `None` standing for no filter.
+    pub(crate) fn union<'a>(
+        filters: impl IntoIterator<Item = &'a Option<ContentFilter>>,
+    ) -> Option<ContentFilter> {
+        let mut filters = filters.into_iter();
+        let first = filters.next()?.clone()?;
+        filters.try_fold(first, |union, filter| Some(union.or(filter.as_ref()?)))
+    }
+
+    pub(crate) fn matches(&self, payload: &Value) -> bool {
+        self.any
+            .iter()
+            .any(|all| all.iter().all(|comparison| comparison.matches(payload)))
+    }
+
+    /// Whether a publication is admitted by the filter.
+    /// The publications which are not JSON-encoded, or fail to parse, are not filtered.
+    pub(crate) fn admits(&self, encoding: &Encoding, payload: &ZBuf) -> bool {
+        if !matches!(
+            encoding.prefix(),
+            KnownEncoding::AppJson | KnownEncoding::TextJson
+        ) {
+            return true;
+        }
+        match serde_json::from_slice::<Value>(&payload.contiguous()) {
+            Ok(payload) => self.matches(&payload),
+            Err(_) => true,
+        }
+    }
+}
+
+impl std::fmt::Display for ContentFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespaces(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespaces();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        self.skip_whitespaces();
+        let end = self.rest.find(|c| !f(c)).unwrap_or(self.rest.len());
+        let (token, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        token
+    }
+
+    fn comparison(&mut self) -> ZResult<Comparison> {
+        let path = self.take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '$'));
+        let path = path.strip_prefix("$.").unwrap_or(path);
+        if path.is_empty() || path.split('.').any(str::is_empty) {
+            bail!("Invalid field `{}` in filter", path)
+        }
+        let path = path.split('.').map(str::to_string).collect();
+        // the two-characters operators first
+        let op = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            (">=", Op::Ge),
+            ("<=", Op::Le),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+        ]
+        .into_iter()
+        .find_map(|(token, op)| self.eat(token).then_some(op))
+        .ok_or_else(|| {
+            zerror!(
+                "Expected a comparison operator in filter at `{}`",
+                self.rest
+            )
+        })?;
+        let value = self.value()?;
+        Ok(Comparison { path, op, value })
+    }
+
+    fn value(&mut self) -> ZResult<Value> {
+        self.skip_whitespaces();
+        let token = if self.rest.starts_with('"') {
+            let mut escaped = false;
+            let end = self
+                .rest
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| {
+                    let end = *c == '"' && !escaped;
+                    escaped = *c == '\\' && !escaped;
+                    end
+                })
+                .map(|(i, _)| i + 1)
+                .ok_or_else(|| zerror!("Unterminated string in filter at `{}`", self.rest))?;
+            let (token, rest) = self.rest.split_at(end);
+            self.rest = rest;
+            token
+        } else {
+            self.take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '+' | '.'))
+        };
+        serde_json::from_str(token)
+            .map_err(|_| zerror!("Invalid value `{}` in filter", token).into())
+    }
+}
+
+#[test]
+fn content_filter() {
+    use serde_json::json;
+
+    let filter = ContentFilter::new("temp > 90").unwrap();
+    assert!(filter.matches(&json!({ "temp": 91.5 })));
+    assert!(!filter.matches(&json!({ "temp": 90 })));
+    assert!(!filter.matches(&json!({ "pressure": 100 })));
+    assert!(!filter.matches(&json!({ "temp": "hot" })));
+
+    let filter =
+        ContentFilter::new(r#"$.engine.temp >= 90 && status != "off" || alarm == true"#).unwrap();
+    assert!(filter.matches(&json!({ "engine": { "temp": 90 }, "status": "on" })));
+    assert!(!filter.matches(&json!({ "engine": { "temp": 90 }, "status": "off" })));
+    assert!(filter.matches(&json!({ "engine": { "temp": 20 }, "alarm": true })));
+
+    let filter = ContentFilter::new(r#"name == "a \"b\" || c""#).unwrap();
+    assert!(filter.matches(&json!({ "name": "a \"b\" || c" })));
+
+    let filter = ContentFilter::new("temp < 0").unwrap().or(&filter);
+    assert_eq!(filter.as_str(), r#"temp < 0 || name == "a \"b\" || c""#);
+    assert!(filter.matches(&json!({ "temp": -1 })));
+    assert_eq!(ContentFilter::new(filter.as_str()).unwrap(), filter);
+
+    let union = ContentFilter::union(&[
+        ContentFilter::new("temp > 90").ok(),
+        ContentFilter::new("temp < 0").ok(),
+    ]);
+    assert_eq!(union.unwrap().as_str(), "temp > 90 || temp < 0");
+    assert!(ContentFilter::union(&[ContentFilter::new("temp > 90").ok(), None]).is_none());
+    assert!(ContentFilter::union(&[]).is_none());
+
+    for invalid in [
+        "",
+        "temp",
+        "temp >",
+        "temp ~ 1",
+        "temp > 1 &&",
+        "temp > 1 2",
+        "a..b == 1",
+    ] {
+        assert!(ContentFilter::new(invalid).is_err(), "{invalid}");
+    }
+
+    let filter = ContentFilter::new("temp > 90").unwrap();
+    let payload = ZBuf::from(br#"{"temp": 20}"#.to_vec());
+    assert!(!filter.admits(&Encoding::APP_JSON, &payload));
+    assert!(filter.admits(&Encoding::TEXT_PLAIN, &payload));
+    assert!(filter.admits(&Encoding::APP_JSON, &ZBuf::from(b"not json".to_vec())));
+}
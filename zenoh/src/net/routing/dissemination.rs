@@ -0,0 +1,184 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::face::FaceState;
+use super::resource::Direction;
+use super::router::{Tables, TablesLock};
+use crate::net::codec::Zenoh080Routing;
+use crate::net::protocol::dissemination::Dissemination;
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use zenoh_buffers::{writer::HasWriter, ZBuf};
+use zenoh_codec::WCodec;
+use zenoh_core::{zlock, zread};
+use zenoh_link::Link;
+use zenoh_protocol::common::ZExtBody;
+use zenoh_protocol::core::{Locator, ZenohId};
+use zenoh_protocol::network::oam::id::OAM_DISSEMINATION;
+use zenoh_protocol::network::{oam, NetworkBody, NetworkMessage, Oam, Push};
+use zenoh_result::{zerror, ZResult};
+use zenoh_transport::TransportMulticast;
+
+pub(crate) fn make_msg(dissemination: &Dissemination) -> ZResult<NetworkMessage> {
+    let codec = Zenoh080Routing::new();
+    let mut buf = ZBuf::empty();
+    codec
+        .write(&mut buf.writer(), dissemination)
+        .map_err(|_| zerror!("Failed to encode Dissemination message"))?;
+    Ok(NetworkBody::OAM(Oam {
+        id: OAM_DISSEMINATION,
+        body: ZExtBody::ZBuf(buf),
+        ext_qos: oam::ext::QoSType::oam_default(),
+        ext_tstamp: None,
+    })
+    .into())
+}
+
+/// The dissemination of the samples with many subscribers on a UDP multicast group.
+///
+/// The clients that joined the group are not sent the samples that at least `threshold`
+/// of them subscribe to: those are published once on the group instead.
+pub(crate) struct DisseminationGroup {
+    pub(crate) group: Locator,
+    threshold: usize,
+    history_size: usize,
+    // The faces of the clients that joined the group
+    members: HashSet<usize>,
+    sender: Option<Arc<DisseminationSender>>,
+}
+
+impl DisseminationGroup {
+    pub(crate) fn new(group: Locator, threshold: usize, history_size: usize) -> Self {
+        DisseminationGroup {
+            group,
+            // Disseminating to a single subscriber is pointless
+            threshold: threshold.max(2),
+            history_size,
+            members: HashSet::new(),
+            sender: None,
+        }
+    }
+
+    /// Whether the link is the one of the multicast group.
+    pub(crate) fn is_group(&self, link: &Link) -> bool {
+        link.dst.protocol() == self.group.protocol() && link.dst.address() == self.group.address()
+    }
+
+    pub(crate) fn set_transport(&mut self, transport: TransportMulticast) {
+        self.sender = Some(Arc::new(DisseminationSender {
+            transport,
+            history_size: self.history_size,
+            history: Mutex::new((0, VecDeque::new())),
+        }));
+    }
+
+    /// Removes from the route the faces of the members if there are enough of them,
+    /// in which case the sample must be published on the returned group.
+    pub(crate) fn split(&self, route: &mut Vec<Direction>) -> Option<Arc<DisseminationSender>> {
+        let sender = self.sender.as_ref()?;
+        let members = route
+            .iter()
+            .filter(|(face, _, _)| self.members.contains(&face.id))
+            .count();
+        if members < self.threshold {
+            return None;
+        }
+        route.retain(|(face, _, _)| !self.members.contains(&face.id));
+        Some(sender.clone())
+    }
+}
+
+/// Publishes the disseminated samples on the multicast group and keeps the last ones
+/// to repair the losses reported by the members.
+pub(crate) struct DisseminationSender {
+    transport: TransportMulticast,
+    history_size: usize,
+    // The sequence number of the next sample and the last samples sent
+    history: Mutex<(u64, VecDeque<Push>)>,
+}
+
+impl DisseminationSender {
+    pub(crate) fn send(&self, source: ZenohId, push: Push) {
+        let mut guard = zlock!(self.history);
+        let sn = guard.0;
+        guard.0 += 1;
+        if self.history_size > 0 {
+            if guard.1.len() == self.history_size {
+                guard.1.pop_front();
+            }
+            guard.1.push_back(push.clone());
+        }
+        drop(guard);
+        match make_msg(&Dissemination::Sample { sn, source, push }) {
+            Ok(msg) => {
+                if let Err(e) = self.transport.schedule(msg) {
+                    log::debug!("Unable to disseminate sample {}: {}", sn, e);
+                }
+            }
+            Err(e) => log::error!("{}", e),
+        }
+    }
+
+    /// The samples still in history among the ones from `from` to `to` excluded.
+    fn repair(&self, from: u64, to: u64) -> Vec<Push> {
+        let guard = zlock!(self.history);
+        let first = guard.0 - guard.1.len() as u64;
+        (from.max(first)..to.min(guard.0))
+            .map(|sn| guard.1[(sn - first) as usize].clone())
+            .collect()
+    }
+}
+
+pub(crate) fn dissemination_join(tables: &mut Tables, zid: &ZenohId) {
+    if let Some(face) = tables.get_face(zid).cloned() {
+        if let Some(dissemination) = tables.dissemination.as_mut() {
+            log::debug!(
+                "Client {} joined dissemination group {}",
+                zid,
+                dissemination.group
+            );
+            dissemination.members.insert(face.id);
+        }
+    }
+}
+
+pub(crate) fn dissemination_leave(tables: &mut Tables, face: &FaceState) {
+    if let Some(dissemination) = tables.dissemination.as_mut() {
+        dissemination.members.remove(&face.id);
+    }
+}
+
+/// Sends again over unicast the disseminated samples a member missed.
+pub(crate) fn dissemination_repair(tables_ref: &TablesLock, zid: &ZenohId, from: u64, to: u64) {
+    let tables = zread!(tables_ref.tables);
+    let (Some(face), Some(sender)) = (
+        tables.get_face(zid).cloned(),
+        tables
+            .dissemination
+            .as_ref()
+            .and_then(|dissemination| dissemination.sender.clone()),
+    ) else {
+        return;
+    };
+    drop(tables);
+    let pushes = sender.repair(from, to);
+    log::trace!(
+        "Repair {} of the {} samples missed by {}",
+        pushes.len(),
+        to.saturating_sub(from),
+        zid
+    );
+    for push in pushes {
+        face.primitives.send_push(push);
+    }
+}
@@ -11,13 +11,18 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::content_filter::ContentFilter;
+use super::namespace::Namespace;
+use super::quotas::QuotaUsage;
 use super::router::*;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
+use zenoh_config::AclAction;
+use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
 use zenoh_protocol::zenoh::RequestBody;
 use zenoh_protocol::{
-    core::{ExprId, WhatAmI, ZenohId},
+    core::{ExprId, WhatAmI, WireExpr, ZenohId},
     network::{
         declare::queryable::ext::QueryableInfo, Mapping, Push, Request, RequestId, Response,
         ResponseFinal,
@@ -25,28 +30,41 @@ use zenoh_protocol::{
 };
 #[cfg(feature = "stats")]
 use zenoh_transport::stats::TransportStats;
-use zenoh_transport::{Primitives, TransportMulticast};
+use zenoh_transport::Primitives;
+#[cfg(feature = "transport_multicast")]
+use zenoh_transport::TransportMulticast;
 
 pub struct FaceState {
     pub(super) id: usize,
-    pub(super) zid: ZenohId,
-    pub(super) whatami: WhatAmI,
+    pub(crate) zid: ZenohId,
+    pub(crate) whatami: WhatAmI,
     #[cfg(feature = "stats")]
     pub(super) stats: Option<Arc<TransportStats>>,
     pub(super) primitives: Arc<dyn Primitives + Send + Sync>,
     pub(super) link_id: usize,
     pub(super) local_mappings: HashMap<ExprId, Arc<Resource>>,
-    pub(super) remote_mappings: HashMap<ExprId, Arc<Resource>>,
+    pub(crate) remote_mappings: HashMap<ExprId, Arc<Resource>>,
     pub(super) local_subs: HashSet<Arc<Resource>>,
-    pub(super) remote_subs: HashSet<Arc<Resource>>,
+    pub(crate) remote_subs: HashSet<Arc<Resource>>,
+    /// The content filters of the subscriptions of a client, along with their key expression, evaluated before
+    /// forwarding it the publications.
+    pub(super) content_filters: HashMap<Arc<Resource>, (OwnedKeyExpr, ContentFilter)>,
     pub(super) local_qabls: HashMap<Arc<Resource>, QueryableInfo>,
-    pub(super) remote_qabls: HashSet<Arc<Resource>>,
+    pub(crate) remote_qabls: HashSet<Arc<Resource>>,
     pub(super) next_qid: RequestId,
     pub(super) pending_queries: HashMap<RequestId, Arc<Query>>,
+    #[cfg(feature = "transport_multicast")]
     pub(super) mcast_group: Option<TransportMulticast>,
+    /// The identities the access control rules are evaluated against, `None` for local faces.
+    pub(crate) identities: Option<Vec<String>>,
+    /// The namespace of a client, which the key expressions it declares and sends are prefixed with.
+    pub(crate) namespace: Option<Namespace>,
+    /// The usage the quotas are enforced against.
+    pub(crate) quota_usage: QuotaUsage,
 }
 
 impl FaceState {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         id: usize,
         zid: ZenohId,
@@ -54,7 +72,9 @@ impl FaceState {
         #[cfg(feature = "stats")] stats: Option<Arc<TransportStats>>,
         primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
-        mcast_group: Option<TransportMulticast>,
+        #[cfg(feature = "transport_multicast")] mcast_group: Option<TransportMulticast>,
+        identities: Option<Vec<String>>,
+        namespace: Option<Namespace>,
     ) -> Arc<FaceState> {
         Arc::new(FaceState {
             id,
@@ -68,14 +88,35 @@ impl FaceState {
             remote_mappings: HashMap::new(),
             local_subs: HashSet::new(),
             remote_subs: HashSet::new(),
+            content_filters: HashMap::new(),
             local_qabls: HashMap::new(),
             remote_qabls: HashSet::new(),
             next_qid: 0,
             pending_queries: HashMap::new(),
+            #[cfg(feature = "transport_multicast")]
             mcast_group,
+            identities,
+            namespace,
+            quota_usage: QuotaUsage::default(),
         })
     }
 
+    /// Whether both faces are on the same multicast group, the samples received on a group not being sent back on it.
+    #[cfg(feature = "transport_multicast")]
+    #[inline]
+    pub(super) fn shares_mcast_group(&self, other: &FaceState) -> bool {
+        matches!(
+            (self.mcast_group.as_ref(), other.mcast_group.as_ref()),
+            (Some(l), Some(r)) if l == r
+        )
+    }
+
+    #[cfg(not(feature = "transport_multicast"))]
+    #[inline]
+    pub(super) fn shares_mcast_group(&self, _other: &FaceState) -> bool {
+        false
+    }
+
     #[inline]
     #[allow(clippy::trivially_copy_pass_by_ref)]
     pub(super) fn get_mapping(
@@ -140,6 +181,66 @@ impl FaceState {
             }
         }
     }
+
+    /// Whether the declared expression isn't a valid key expression in canon form,
+    /// which would break its matching with the other declarations.
+    ///
+    /// The sessions refuse to declare such expressions: this only guards against other implementations.
+    pub(super) fn rejects(&self, tables: &Tables, expr: &WireExpr) -> bool {
+        match tables.get_mapping(self, &expr.scope, expr.mapping) {
+            Some(prefix) => {
+                let mut key_expr = RoutingExpr::new(prefix, expr.suffix.as_ref());
+                match keyexpr::new(key_expr.full_expr()) {
+                    Ok(_) => false,
+                    Err(e) => {
+                        log::warn!("Ignoring declaration from {}: {}", self, e);
+                        true
+                    }
+                }
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the namespace and the access control rules allow this face the given action on the given key expression.
+    ///
+    /// The access control rules are evaluated on the key expressions of a namespaced client once prefixed.
+    pub(super) fn allows(&self, tables: &Tables, action: AclAction, key_expr: &str) -> bool {
+        if let Some(namespace) = &self.namespace {
+            if !namespace.includes(key_expr) {
+                return false;
+            }
+        }
+        match (&tables.acl, &self.identities) {
+            (Some(acl), Some(identities)) => acl.allows(identities, action, key_expr),
+            _ => true,
+        }
+    }
+
+    /// Whether the namespace and the access control rules deny this face the given action on the given key expression.
+    pub(super) fn denies(&self, tables: &Tables, action: AclAction, expr: &WireExpr) -> bool {
+        if tables.acl.is_none() && self.namespace.is_none() {
+            return false;
+        }
+        match tables.get_mapping(self, &expr.scope, expr.mapping) {
+            Some(prefix) => {
+                let mut key_expr = RoutingExpr::new(prefix, expr.suffix.as_ref());
+                let key_expr = key_expr.full_expr();
+                if self.allows(tables, action, key_expr) {
+                    false
+                } else {
+                    log::debug!(
+                        "Access control denied {:?} on {} from {}",
+                        action,
+                        key_expr,
+                        self
+                    );
+                    true
+                }
+            }
+            None => false,
+        }
+    }
 }
 
 impl fmt::Display for FaceState {
@@ -155,7 +256,10 @@ pub struct Face {
 }
 
 impl Primitives for Face {
-    fn send_declare(&self, msg: zenoh_protocol::network::Declare) {
+    fn send_declare(&self, mut msg: zenoh_protocol::network::Declare) {
+        if let Some(namespace) = &self.state.namespace {
+            namespace.apply_declare(&mut msg.body);
+        }
         let ctrl_lock = zlock!(self.tables.ctrl_lock);
         match msg.body {
             zenoh_protocol::network::DeclareBody::DeclareKeyExpr(m) => {
@@ -166,6 +270,18 @@ impl Primitives for Face {
             }
             zenoh_protocol::network::DeclareBody::DeclareSubscriber(m) => {
                 let rtables = zread!(self.tables.tables);
+                if self.state.rejects(&rtables, &m.wire_expr)
+                    || self.state.denies(&rtables, AclAction::Sub, &m.wire_expr)
+                {
+                    return;
+                }
+                let filter = m.ext_filter.as_ref().and_then(|ext| {
+                    ContentFilter::new(&ext.filter)
+                        .map_err(|e| {
+                            log::warn!("Ignoring invalid subscription filter from {}: {}", self, e)
+                        })
+                        .ok()
+                });
                 match (rtables.whatami, self.state.whatami) {
                     (WhatAmI::Router, WhatAmI::Router) => {
                         if let Some(router) = self
@@ -206,6 +322,7 @@ impl Primitives for Face {
                                 &mut self.state.clone(),
                                 &m.wire_expr,
                                 &m.ext_info,
+                                filter,
                             )
                         }
                     }
@@ -215,6 +332,7 @@ impl Primitives for Face {
                         &mut self.state.clone(),
                         &m.wire_expr,
                         &m.ext_info,
+                        filter,
                     ),
                 }
             }
@@ -270,6 +388,13 @@ impl Primitives for Face {
             }
             zenoh_protocol::network::DeclareBody::DeclareQueryable(m) => {
                 let rtables = zread!(self.tables.tables);
+                if self.state.rejects(&rtables, &m.wire_expr)
+                    || self
+                        .state
+                        .denies(&rtables, AclAction::Queryable, &m.wire_expr)
+                {
+                    return;
+                }
                 match (rtables.whatami, self.state.whatami) {
                     (WhatAmI::Router, WhatAmI::Router) => {
                         if let Some(router) = self
@@ -381,7 +506,10 @@ impl Primitives for Face {
         drop(ctrl_lock);
     }
 
-    fn send_push(&self, msg: Push) {
+    fn send_push(&self, mut msg: Push) {
+        if let Some(namespace) = &self.state.namespace {
+            namespace.apply_push(&mut msg);
+        }
         full_reentrant_route_data(
             &self.tables.tables,
             &self.state,
@@ -392,7 +520,10 @@ impl Primitives for Face {
         );
     }
 
-    fn send_request(&self, msg: Request) {
+    fn send_request(&self, mut msg: Request) {
+        if let Some(namespace) = &self.state.namespace {
+            namespace.apply(&mut msg.wire_expr);
+        }
         match msg.payload {
             RequestBody::Query(_) => {
                 route_query(
@@ -416,11 +547,15 @@ impl Primitives for Face {
         }
     }
 
-    fn send_response(&self, msg: Response) {
+    fn send_response(&self, mut msg: Response) {
+        if let Some(namespace) = &self.state.namespace {
+            namespace.apply(&mut msg.wire_expr);
+        }
         route_send_response(
             &self.tables,
             &mut self.state.clone(),
             msg.rid,
+            msg.ext_qos,
             msg.ext_respid,
             msg.wire_expr,
             msg.payload,
@@ -0,0 +1,199 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use zenoh_buffers::SplitBuffer;
+use zenoh_core::zlock;
+use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
+use zenoh_protocol::zenoh::PushBody;
+
+/// The upper bounds (in milliseconds) of the latency histograms' buckets, an extra bucket counting the greater latencies.
+const LATENCY_BUCKETS: [u64; 12] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000];
+/// The minimal duration over which the message rates are computed.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+struct Stats {
+    msgs: u64,
+    bytes: u64,
+    // Counters of the current rate window
+    window_start: Instant,
+    window_msgs: u64,
+    window_bytes: u64,
+    // Rates of the last complete window
+    msgs_rate: f64,
+    bytes_rate: f64,
+    latency_buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    latency_count: u64,
+    latency_sum: Duration,
+}
+
+impl Stats {
+    fn new() -> Self {
+        Stats {
+            msgs: 0,
+            bytes: 0,
+            window_start: Instant::now(),
+            window_msgs: 0,
+            window_bytes: 0,
+            msgs_rate: 0.0,
+            bytes_rate: 0.0,
+            latency_buckets: [0; LATENCY_BUCKETS.len() + 1],
+            latency_count: 0,
+            latency_sum: Duration::ZERO,
+        }
+    }
+
+    fn rates(&self, now: Instant) -> (f64, f64) {
+        let elapsed = now.duration_since(self.window_start);
+        if elapsed >= RATE_WINDOW {
+            let secs = elapsed.as_secs_f64();
+            (
+                self.window_msgs as f64 / secs,
+                self.window_bytes as f64 / secs,
+            )
+        } else {
+            (self.msgs_rate, self.bytes_rate)
+        }
+    }
+
+    fn record(&mut self, bytes: usize, latency: Option<Duration>) {
+        let now = Instant::now();
+        if now.duration_since(self.window_start) >= RATE_WINDOW {
+            (self.msgs_rate, self.bytes_rate) = self.rates(now);
+            self.window_start = now;
+            self.window_msgs = 0;
+            self.window_bytes = 0;
+        }
+        self.msgs += 1;
+        self.bytes += bytes as u64;
+        self.window_msgs += 1;
+        self.window_bytes += bytes as u64;
+        if let Some(latency) = latency {
+            let millis = latency.as_millis();
+            let bucket = LATENCY_BUCKETS
+                .iter()
+                .position(|bound| millis <= *bound as u128)
+                .unwrap_or(LATENCY_BUCKETS.len());
+            self.latency_buckets[bucket] += 1;
+            self.latency_count += 1;
+            self.latency_sum += latency;
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let (msgs_rate, bytes_rate) = self.rates(Instant::now());
+        let buckets: Vec<serde_json::Value> = self
+            .latency_buckets
+            .iter()
+            .enumerate()
+            .map(|(i, count)| match LATENCY_BUCKETS.get(i) {
+                Some(bound) => json!({ "le_ms": bound, "count": count }),
+                None => json!({ "le_ms": "+Inf", "count": count }),
+            })
+            .collect();
+        json!({
+            "msgs": self.msgs,
+            "bytes": self.bytes,
+            "msgs_per_sec": msgs_rate,
+            "bytes_per_sec": bytes_rate,
+            "latency": {
+                "count": self.latency_count,
+                "sum_ms": self.latency_sum.as_secs_f64() * 1000.0,
+                "buckets": buckets,
+            },
+        })
+    }
+}
+
+/// Measures the rate and the end-to-end latency of the data routed on a set of key expressions.
+///
+/// Latencies are computed from the samples' timestamps, so only timestamped samples are accounted for,
+/// and are as accurate as the synchronization of the publishers' and router's clocks.
+pub(crate) struct KeyExprMeasurements {
+    measurements: Vec<(OwnedKeyExpr, Mutex<Stats>)>,
+}
+
+impl KeyExprMeasurements {
+    /// Returns `None` if there are no key expressions to measure.
+    pub(crate) fn new(key_exprs: Vec<OwnedKeyExpr>) -> Option<Self> {
+        (!key_exprs.is_empty()).then(|| KeyExprMeasurements {
+            measurements: key_exprs
+                .into_iter()
+                .map(|key_expr| (key_expr, Mutex::new(Stats::new())))
+                .collect(),
+        })
+    }
+
+    pub(crate) fn record(&self, key_expr: &str, payload: &PushBody) {
+        let key_expr = match keyexpr::new(key_expr) {
+            Ok(key_expr) => key_expr,
+            Err(_) => return,
+        };
+        let (bytes, timestamp) = match payload {
+            PushBody::Put(put) => (put.payload.len(), put.timestamp.as_ref()),
+            PushBody::Del(del) => (0, del.timestamp.as_ref()),
+        };
+        let mut latency = None;
+        for (measured, stats) in &self.measurements {
+            if measured.intersects(key_expr) {
+                // Only computed for the measured samples, and at most once
+                let latency = *latency.get_or_insert_with(|| {
+                    timestamp.map(|ts| {
+                        SystemTime::now()
+                            .duration_since(ts.get_time().to_system_time())
+                            .unwrap_or(Duration::ZERO)
+                    })
+                });
+                zlock!(stats).record(bytes, latency);
+            }
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        self.measurements
+            .iter()
+            .map(|(key_expr, stats)| (key_expr.to_string(), zlock!(stats).to_json()))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+#[test]
+fn key_expr_measurements() {
+    use zenoh_protocol::zenoh::Put;
+
+    assert!(KeyExprMeasurements::new(vec![]).is_none());
+    let measurements =
+        KeyExprMeasurements::new(vec![OwnedKeyExpr::new("demo/**").unwrap()]).unwrap();
+    let put = PushBody::Put(Put {
+        timestamp: None,
+        encoding: Default::default(),
+        ext_sinfo: None,
+        #[cfg(feature = "shared-memory")]
+        ext_shm: None,
+        ext_trace: None,
+        ext_deadline: None,
+        ext_batch: None,
+        ext_unknown: vec![],
+        payload: vec![0u8; 10].into(),
+    });
+    measurements.record("demo/a", &put);
+    measurements.record("demo/b", &put);
+    measurements.record("other/a", &put);
+    let json = measurements.to_json();
+    assert_eq!(json["demo/**"]["msgs"], 2);
+    assert_eq!(json["demo/**"]["bytes"], 20);
+    assert_eq!(json["demo/**"]["latency"]["count"], 0);
+}
@@ -17,10 +17,18 @@
 //! This module is intended for Zenoh's internal use.
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
+pub(crate) mod acl;
+pub(crate) mod content_filter;
+#[cfg(feature = "transport_multicast")]
+pub(crate) mod dissemination;
 pub mod face;
+pub(crate) mod measurements;
+pub(crate) mod namespace;
 pub mod network;
 pub mod pubsub;
+pub(crate) mod qos_overrides;
 pub mod queries;
+pub(crate) mod quotas;
 pub mod resource;
 pub mod router;
 
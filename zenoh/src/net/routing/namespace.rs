@@ -0,0 +1,328 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use zenoh_config::NamespaceRule;
+use zenoh_core::zlock;
+use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
+use zenoh_protocol::core::{ExprId, WireExpr};
+use zenoh_protocol::network::declare::common::ext::WireExprType;
+use zenoh_protocol::network::{
+    Declare, DeclareBody, Mapping, Push, Request, Response, ResponseFinal,
+};
+use zenoh_protocol::zenoh::{PushBody, Put};
+use zenoh_transport::Primitives;
+
+/// The namespaces assigned to the clients, from their identities.
+pub(crate) struct Namespaces {
+    rules: Vec<NamespaceRule>,
+}
+
+impl Namespaces {
+    /// Returns `None` if there is no rule.
+    pub(crate) fn new(config: &[NamespaceRule]) -> Option<Self> {
+        (!config.is_empty()).then(|| Namespaces {
+            rules: config.to_vec(),
+        })
+    }
+
+    /// The namespace of the first rule matching one of the identities of a client.
+    pub(crate) fn namespace(&self, identities: &[String]) -> Option<Namespace> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.identities.is_empty()
+                    || rule.identities.iter().any(|id| identities.contains(id))
+            })
+            .map(|rule| Namespace::new(rule.prefix.clone()))
+    }
+}
+
+/// The part of the key space a client is confined to.
+///
+/// The key expressions received from the client are prefixed, the ones sent to it are stripped of the prefix.
+#[derive(Clone, Debug)]
+pub(crate) struct Namespace {
+    prefix: OwnedKeyExpr,
+    // The key expressions included in the namespace: `<prefix>/**`
+    keys: OwnedKeyExpr,
+}
+
+impl Namespace {
+    pub(crate) fn new(prefix: OwnedKeyExpr) -> Self {
+        let keys = &prefix / keyexpr::new("**").unwrap();
+        Namespace { prefix, keys }
+    }
+
+    pub(crate) fn prefix(&self) -> &keyexpr {
+        &self.prefix
+    }
+
+    /// Whether a key expression of the router is included in the namespace.
+    pub(crate) fn includes(&self, key_expr: &str) -> bool {
+        keyexpr::new(key_expr).map_or(false, |key_expr| self.keys.includes(key_expr))
+    }
+
+    /// Prefixes a key expression received from the client.
+    /// The scoped ones are relative to a key expression declared by the client, which is already prefixed.
+    pub(crate) fn apply(&self, expr: &mut WireExpr) {
+        if expr.scope == 0 && expr.has_suffix() {
+            expr.suffix = format!("{}/{}", self.prefix, expr.suffix).into();
+        }
+    }
+
+    /// Prefixes the key expression of a publication received from the client, along with the keys of its batch.
+    /// The keys of a batch are never scoped.
+    pub(crate) fn apply_push(&self, msg: &mut Push) {
+        self.apply(&mut msg.wire_expr);
+        if let PushBody::Put(Put {
+            ext_batch: Some(batch),
+            ..
+        }) = &mut msg.payload
+        {
+            for entry in batch.entries.iter_mut() {
+                entry.key = format!("{}/{}", self.prefix, entry.key);
+            }
+        }
+    }
+
+    /// Prefixes the key expression of a declaration received from the client.
+    pub(crate) fn apply_declare(&self, body: &mut DeclareBody) {
+        match body {
+            DeclareBody::DeclareKeyExpr(m) => self.apply(&mut m.wire_expr),
+            DeclareBody::DeclareSubscriber(m) => self.apply(&mut m.wire_expr),
+            DeclareBody::UndeclareSubscriber(m) => self.apply(&mut m.ext_wire_expr.wire_expr),
+            DeclareBody::DeclareQueryable(m) => self.apply(&mut m.wire_expr),
+            DeclareBody::UndeclareQueryable(m) => self.apply(&mut m.ext_wire_expr.wire_expr),
+            DeclareBody::DeclareToken(m) => self.apply(&mut m.wire_expr),
+            DeclareBody::UndeclareToken(m) => self.apply(&mut m.ext_wire_expr.wire_expr),
+            DeclareBody::DeclareInterest(m) => self.apply(&mut m.wire_expr),
+            DeclareBody::UndeclareInterest(m) => self.apply(&mut m.ext_wire_expr.wire_expr),
+            DeclareBody::UndeclareKeyExpr(_) | DeclareBody::FinalInterest(_) => {}
+        }
+    }
+
+    /// The key expression seen by the client for a key expression of the router,
+    /// `None` if the client can't express it.
+    ///
+    /// The key expressions including the whole namespace (e.g. `**`) are seen as `**`.
+    pub(crate) fn strip(&self, key_expr: &str) -> Option<String> {
+        match key_expr
+            .strip_prefix(self.prefix.as_str())
+            .and_then(|suffix| suffix.strip_prefix('/'))
+        {
+            Some(suffix) if !suffix.is_empty() => Some(suffix.to_string()),
+            _ => match keyexpr::new(key_expr) {
+                Ok(key_expr) if key_expr.includes(&self.keys) => Some("**".to_string()),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// The primitives of a namespaced client, stripping the key expressions sent to it of the namespace prefix.
+///
+/// The key expressions declared by the router are not sent to the client, as it may not be able to express them:
+/// the key expressions relative to them are sent in full.
+pub(crate) struct NamespacePrimitives {
+    namespace: Namespace,
+    primitives: Arc<dyn Primitives + Send + Sync>,
+    mappings: Mutex<HashMap<ExprId, String>>,
+}
+
+impl NamespacePrimitives {
+    pub(crate) fn new(namespace: Namespace, primitives: Arc<dyn Primitives + Send + Sync>) -> Self {
+        NamespacePrimitives {
+            namespace,
+            primitives,
+            mappings: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // The full key expression of a wire expression of the router
+    fn full_expr(&self, expr: &WireExpr) -> Option<String> {
+        match expr.scope {
+            0 => Some(expr.suffix.to_string()),
+            scope => zlock!(self.mappings)
+                .get(&scope)
+                .map(|prefix| format!("{}{}", prefix, expr.suffix)),
+        }
+    }
+
+    fn strip(&self, expr: &WireExpr) -> Option<WireExpr<'static>> {
+        if expr.scope != 0 && expr.mapping == Mapping::Receiver {
+            // relative to a key expression declared by the client, as seen by the client
+            return Some(expr.to_owned());
+        }
+        let stripped = self
+            .full_expr(expr)
+            .and_then(|full_expr| self.namespace.strip(&full_expr));
+        if stripped.is_none() {
+            log::trace!(
+                "Not sending {} out of namespace {}",
+                expr,
+                self.namespace.prefix
+            );
+        }
+        stripped.map(WireExpr::from)
+    }
+
+    // Strips the key expression of an undeclaration, which may be empty
+    fn strip_ext(&self, ext: &mut WireExprType) -> bool {
+        if ext.wire_expr.scope == 0 && !ext.wire_expr.has_suffix() {
+            return true;
+        }
+        match self.strip(&ext.wire_expr) {
+            Some(wire_expr) => {
+                ext.wire_expr = wire_expr;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Primitives for NamespacePrimitives {
+    fn send_declare(&self, mut msg: Declare) {
+        let sent = match &mut msg.body {
+            DeclareBody::DeclareKeyExpr(m) => {
+                if let Some(full_expr) = self.full_expr(&m.wire_expr) {
+                    zlock!(self.mappings).insert(m.id, full_expr);
+                }
+                false
+            }
+            DeclareBody::UndeclareKeyExpr(m) => {
+                zlock!(self.mappings).remove(&m.id);
+                false
+            }
+            DeclareBody::DeclareSubscriber(m) => self
+                .strip(&m.wire_expr)
+                .map(|wire_expr| m.wire_expr = wire_expr)
+                .is_some(),
+            DeclareBody::DeclareQueryable(m) => self
+                .strip(&m.wire_expr)
+                .map(|wire_expr| m.wire_expr = wire_expr)
+                .is_some(),
+            DeclareBody::DeclareToken(m) => self
+                .strip(&m.wire_expr)
+                .map(|wire_expr| m.wire_expr = wire_expr)
+                .is_some(),
+            DeclareBody::DeclareInterest(m) => self
+                .strip(&m.wire_expr)
+                .map(|wire_expr| m.wire_expr = wire_expr)
+                .is_some(),
+            DeclareBody::UndeclareSubscriber(m) => self.strip_ext(&mut m.ext_wire_expr),
+            DeclareBody::UndeclareQueryable(m) => self.strip_ext(&mut m.ext_wire_expr),
+            DeclareBody::UndeclareToken(m) => self.strip_ext(&mut m.ext_wire_expr),
+            DeclareBody::UndeclareInterest(m) => self.strip_ext(&mut m.ext_wire_expr),
+            DeclareBody::FinalInterest(_) => true,
+        };
+        if sent {
+            self.primitives.send_declare(msg);
+        }
+    }
+
+    fn send_push(&self, mut msg: Push) {
+        if let Some(wire_expr) = self.strip(&msg.wire_expr) {
+            msg.wire_expr = wire_expr;
+            if let PushBody::Put(Put {
+                ext_batch: Some(batch),
+                ..
+            }) = &mut msg.payload
+            {
+                // The keys of a batch are included in its key expression, hence in the namespace
+                batch
+                    .entries
+                    .retain_mut(|entry| match self.namespace.strip(&entry.key) {
+                        Some(key) => {
+                            entry.key = key;
+                            true
+                        }
+                        None => false,
+                    });
+            }
+            self.primitives.send_push(msg);
+        }
+    }
+
+    fn send_request(&self, mut msg: Request) {
+        // Only sent for the key expressions the client can express, the other queries aren't routed to it
+        if let Some(wire_expr) = self.strip(&msg.wire_expr) {
+            msg.wire_expr = wire_expr;
+            self.primitives.send_request(msg);
+        }
+    }
+
+    fn send_response(&self, mut msg: Response) {
+        if let Some(wire_expr) = self.strip(&msg.wire_expr) {
+            msg.wire_expr = wire_expr;
+            self.primitives.send_response(msg);
+        }
+    }
+
+    fn send_response_final(&self, msg: ResponseFinal) {
+        self.primitives.send_response_final(msg);
+    }
+
+    fn send_close(&self) {
+        self.primitives.send_close();
+    }
+}
+
+#[test]
+fn namespaces() {
+    let rule = |identities: &[&str], prefix: &str| NamespaceRule {
+        identities: identities.iter().map(|id| id.to_string()).collect(),
+        prefix: OwnedKeyExpr::new(prefix).unwrap(),
+    };
+    assert!(Namespaces::new(&[]).is_none());
+    let namespaces = Namespaces::new(&[
+        rule(&["username:alice"], "tenant/alice"),
+        rule(&["username:bob"], "tenant/bob"),
+    ])
+    .unwrap();
+    assert!(namespaces
+        .namespace(&["zid:1".to_string(), "username:carol".to_string()])
+        .is_none());
+    let alice = namespaces
+        .namespace(&["zid:2".to_string(), "username:alice".to_string()])
+        .unwrap();
+    assert_eq!(alice.prefix().as_str(), "tenant/alice");
+
+    let mut expr = WireExpr::from("demo/**");
+    alice.apply(&mut expr);
+    assert_eq!(expr.suffix, "tenant/alice/demo/**");
+    let mut scoped = WireExpr {
+        scope: 1,
+        suffix: "/a".into(),
+        mapping: Mapping::Sender,
+    };
+    alice.apply(&mut scoped);
+    assert_eq!(scoped.suffix, "/a");
+
+    assert!(alice.includes("tenant/alice/demo/a"));
+    assert!(!alice.includes("tenant/bob/demo/a"));
+    assert!(!alice.includes("tenant/*/demo/a"));
+    assert!(!alice.includes("**"));
+
+    assert_eq!(
+        alice.strip("tenant/alice/demo/a").as_deref(),
+        Some("demo/a")
+    );
+    assert_eq!(alice.strip("**").as_deref(), Some("**"));
+    assert_eq!(alice.strip("tenant/**").as_deref(), Some("**"));
+    assert_eq!(alice.strip("tenant/alice").as_deref(), None);
+    assert_eq!(alice.strip("tenant/alicex/a").as_deref(), None);
+    assert_eq!(alice.strip("tenant/bob/demo/a").as_deref(), None);
+}
@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::content_filter::ContentFilter;
 use super::face::FaceState;
 use super::network::Network;
 use super::resource::{
@@ -23,6 +24,7 @@ use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::sync::RwLock;
 use std::sync::{Arc, RwLockReadGuard};
+use zenoh_config::AclAction;
 use zenoh_core::zread;
 use zenoh_protocol::{
     core::{
@@ -36,7 +38,10 @@ use zenoh_protocol::{
         },
         Push,
     },
-    zenoh::PushBody,
+    zenoh::{
+        ext::{SourceInfoType, TraceHop},
+        PushBody, Put,
+    },
 };
 use zenoh_sync::get_mut_unchecked;
 
@@ -69,6 +74,7 @@ fn send_sourced_subscription_to_net_childs(
                                 id: 0, // TODO
                                 wire_expr: key_expr,
                                 ext_info: *sub_info,
+                                ext_filter: None,
                             }),
                         });
                     }
@@ -121,6 +127,7 @@ fn propagate_simple_subscription_to(
                 id: 0, // TODO
                 wire_expr: key_expr,
                 ext_info: *sub_info,
+                ext_filter: None,
             }),
         });
     }
@@ -242,7 +249,7 @@ pub fn declare_router_subscription(
                     let wtables = zwrite!(tables.tables);
                     (res.unwrap(), wtables)
                 } else {
-                    let mut fullexpr = prefix.expr();
+                    let mut fullexpr = prefix.expr().to_string();
                     fullexpr.push_str(expr.suffix.as_ref());
                     let mut matches = keyexpr::new(fullexpr.as_str())
                         .map(|ke| Resource::get_matches(&rtables, ke))
@@ -263,13 +270,9 @@ pub fn declare_router_subscription(
             let matches_data_routes = compute_matches_data_routes_(&rtables, &res);
             drop(rtables);
 
-            let wtables = zwrite!(tables.tables);
-            for (mut res, data_routes) in matches_data_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_data_routes(data_routes);
+            for (res, data_routes) in matches_data_routes {
+                res.context().update_data_routes(data_routes);
             }
-            drop(wtables);
         }
         None => log::error!(
             "Declare router subscription for unknown scope {}!",
@@ -323,7 +326,7 @@ pub fn declare_peer_subscription(
                     let wtables = zwrite!(tables.tables);
                     (res.unwrap(), wtables)
                 } else {
-                    let mut fullexpr = prefix.expr();
+                    let mut fullexpr = prefix.expr().to_string();
                     fullexpr.push_str(expr.suffix.as_ref());
                     let mut matches = keyexpr::new(fullexpr.as_str())
                         .map(|ke| Resource::get_matches(&rtables, ke))
@@ -350,13 +353,9 @@ pub fn declare_peer_subscription(
             let matches_data_routes = compute_matches_data_routes_(&rtables, &res);
             drop(rtables);
 
-            let wtables = zwrite!(tables.tables);
-            for (mut res, data_routes) in matches_data_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_data_routes(data_routes);
+            for (res, data_routes) in matches_data_routes {
+                res.context().update_data_routes(data_routes);
             }
-            drop(wtables);
         }
         None => log::error!(
             "Declare router subscription for unknown scope {}!",
@@ -370,6 +369,7 @@ fn register_client_subscription(
     face: &mut Arc<FaceState>,
     res: &mut Arc<Resource>,
     sub_info: &SubscriberInfo,
+    filter: Option<ContentFilter>,
 ) {
     // Register subscription
     {
@@ -402,6 +402,22 @@ fn register_client_subscription(
         }
     }
     get_mut_unchecked(face).remote_subs.insert(res.clone());
+    match filter.zip(OwnedKeyExpr::new(res.expr()).ok()) {
+        Some((filter, key_expr)) => {
+            log::debug!(
+                "Filter subscription {} for {} with `{}`",
+                res.expr(),
+                face,
+                filter
+            );
+            get_mut_unchecked(face)
+                .content_filters
+                .insert(res.clone(), (key_expr, filter));
+        }
+        None => {
+            get_mut_unchecked(face).content_filters.remove(res);
+        }
+    }
 }
 
 pub fn declare_client_subscription(
@@ -410,6 +426,7 @@ pub fn declare_client_subscription(
     face: &mut Arc<FaceState>,
     expr: &WireExpr,
     sub_info: &SubscriberInfo,
+    filter: Option<ContentFilter>,
 ) {
     log::debug!("Register client subscription");
     match rtables
@@ -424,7 +441,7 @@ pub fn declare_client_subscription(
                     let wtables = zwrite!(tables.tables);
                     (res.unwrap(), wtables)
                 } else {
-                    let mut fullexpr = prefix.expr();
+                    let mut fullexpr = prefix.expr().to_string();
                     fullexpr.push_str(expr.suffix.as_ref());
                     let mut matches = keyexpr::new(fullexpr.as_str())
                         .map(|ke| Resource::get_matches(&rtables, ke))
@@ -438,7 +455,7 @@ pub fn declare_client_subscription(
                     (res, wtables)
                 };
 
-            register_client_subscription(&mut wtables, face, &mut res, sub_info);
+            register_client_subscription(&mut wtables, face, &mut res, sub_info, filter);
             let mut propa_sub_info = *sub_info;
             propa_sub_info.mode = Mode::Push;
             match wtables.whatami {
@@ -474,8 +491,9 @@ pub fn declare_client_subscription(
                                 ext_nodeid: ext::NodeIdType::default(),
                                 body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
                                     id: 0, // TODO
-                                    wire_expr: res.expr().into(),
+                                    wire_expr: res.expr().to_string().into(),
                                     ext_info: *sub_info,
+                                    ext_filter: None,
                                 }),
                             })
                         }
@@ -493,8 +511,9 @@ pub fn declare_client_subscription(
                             ext_nodeid: ext::NodeIdType::default(),
                             body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
                                 id: 0, // TODO
-                                wire_expr: res.expr().into(),
+                                wire_expr: res.expr().to_string().into(),
                                 ext_info: *sub_info,
+                                ext_filter: None,
                             }),
                         })
                     }
@@ -507,13 +526,9 @@ pub fn declare_client_subscription(
             let matches_data_routes = compute_matches_data_routes_(&rtables, &res);
             drop(rtables);
 
-            let wtables = zwrite!(tables.tables);
-            for (mut res, data_routes) in matches_data_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_data_routes(data_routes);
+            for (res, data_routes) in matches_data_routes {
+                res.context().update_data_routes(data_routes);
             }
-            drop(wtables);
         }
         None => log::error!("Declare subscription for unknown scope {}!", expr.scope),
     }
@@ -736,12 +751,10 @@ pub fn forget_router_subscription(
                 let rtables = zread!(tables.tables);
                 let matches_data_routes = compute_matches_data_routes_(&rtables, &res);
                 drop(rtables);
-                let wtables = zwrite!(tables.tables);
-                for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                for (res, data_routes) in matches_data_routes {
+                    res.context().update_data_routes(data_routes);
                 }
+                let wtables = zwrite!(tables.tables);
                 Resource::clean(&mut res);
                 drop(wtables);
             }
@@ -810,12 +823,10 @@ pub fn forget_peer_subscription(
                 let rtables = zread!(tables.tables);
                 let matches_data_routes = compute_matches_data_routes_(&rtables, &res);
                 drop(rtables);
-                let wtables = zwrite!(tables.tables);
-                for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                for (res, data_routes) in matches_data_routes {
+                    res.context().update_data_routes(data_routes);
                 }
+                let wtables = zwrite!(tables.tables);
                 Resource::clean(&mut res);
                 drop(wtables);
             }
@@ -835,6 +846,7 @@ pub(crate) fn undeclare_client_subscription(
         get_mut_unchecked(ctx).subs = None;
     }
     get_mut_unchecked(face).remote_subs.remove(res);
+    get_mut_unchecked(face).content_filters.remove(res);
 
     let mut client_subs = client_subs(res);
     let router_subs = remote_router_subs(tables, res);
@@ -903,12 +915,10 @@ pub fn forget_client_subscription(
                 let matches_data_routes = compute_matches_data_routes_(&rtables, &res);
                 drop(rtables);
 
-                let wtables = zwrite!(tables.tables);
-                for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                for (res, data_routes) in matches_data_routes {
+                    res.context().update_data_routes(data_routes);
                 }
+                let wtables = zwrite!(tables.tables);
                 Resource::clean(&mut res);
                 drop(wtables);
             }
@@ -918,6 +928,39 @@ pub fn forget_client_subscription(
     }
 }
 
+fn propagate_router_subs_to_peer(
+    tables: &Tables,
+    face: &mut Arc<FaceState>,
+    sub_info: &SubscriberInfo,
+) {
+    for sub in &tables.router_subs {
+        if sub.context.is_some()
+            && !face.local_subs.contains(sub)
+            && (sub.context().router_subs.iter().any(|r| *r != tables.zid)
+                || sub.session_ctxs.values().any(|s| {
+                    s.subs.is_some()
+                        && (s.face.whatami == WhatAmI::Client
+                            || (s.face.whatami == WhatAmI::Peer
+                                && tables.failover_brokering(s.face.zid, face.zid)))
+                }))
+        {
+            get_mut_unchecked(face).local_subs.insert(sub.clone());
+            let key_expr = Resource::decl_key(sub, face);
+            face.primitives.send_declare(Declare {
+                ext_qos: ext::QoSType::declare_default(),
+                ext_tstamp: None,
+                ext_nodeid: ext::NodeIdType::default(),
+                body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
+                    id: 0, // TODO
+                    wire_expr: key_expr,
+                    ext_info: *sub_info,
+                    ext_filter: None,
+                }),
+            });
+        }
+    }
+}
+
 pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
     let sub_info = SubscriberInfo {
         reliability: Reliability::Reliable, // @TODO
@@ -937,34 +980,12 @@ pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
                             id: 0, // TODO
                             wire_expr: key_expr,
                             ext_info: sub_info,
+                            ext_filter: None,
                         }),
                     });
                 }
             } else if face.whatami == WhatAmI::Peer && !tables.full_net(WhatAmI::Peer) {
-                for sub in &tables.router_subs {
-                    if sub.context.is_some()
-                        && (sub.context().router_subs.iter().any(|r| *r != tables.zid)
-                            || sub.session_ctxs.values().any(|s| {
-                                s.subs.is_some()
-                                    && (s.face.whatami == WhatAmI::Client
-                                        || (s.face.whatami == WhatAmI::Peer
-                                            && tables.failover_brokering(s.face.zid, face.zid)))
-                            }))
-                    {
-                        get_mut_unchecked(face).local_subs.insert(sub.clone());
-                        let key_expr = Resource::decl_key(sub, face);
-                        face.primitives.send_declare(Declare {
-                            ext_qos: ext::QoSType::declare_default(),
-                            ext_tstamp: None,
-                            ext_nodeid: ext::NodeIdType::default(),
-                            body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
-                                id: 0, // TODO
-                                wire_expr: key_expr,
-                                ext_info: sub_info,
-                            }),
-                        });
-                    }
-                }
+                propagate_router_subs_to_peer(tables, face, &sub_info);
             }
         }
         WhatAmI::Peer => {
@@ -981,6 +1002,7 @@ pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
                                 id: 0, // TODO
                                 wire_expr: key_expr,
                                 ext_info: sub_info,
+                                ext_filter: None,
                             }),
                         });
                     }
@@ -1040,10 +1062,8 @@ pub(crate) fn pubsub_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
                 unregister_router_subscription(tables, &mut res, node);
 
                 let matches_data_routes = compute_matches_data_routes_(tables, &res);
-                for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                for (res, data_routes) in matches_data_routes {
+                    res.context().update_data_routes(data_routes);
                 }
                 Resource::clean(&mut res)
             }
@@ -1068,10 +1088,8 @@ pub(crate) fn pubsub_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
 
                 // compute_matches_data_routes(tables, &mut res);
                 let matches_data_routes = compute_matches_data_routes_(tables, &res);
-                for (mut res, data_routes) in matches_data_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_data_routes(data_routes);
+                for (res, data_routes) in matches_data_routes {
+                    res.context().update_data_routes(data_routes);
                 }
                 Resource::clean(&mut res)
             }
@@ -1080,6 +1098,8 @@ pub(crate) fn pubsub_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
     }
 }
 
+// Resources are hashed by key expression, the routes of their contexts are not part of their identity
+#[allow(clippy::mutable_key_type)]
 pub(crate) fn pubsub_tree_change(
     tables: &mut Tables,
     new_childs: &[Vec<NodeIndex>],
@@ -1131,7 +1151,7 @@ pub(crate) fn pubsub_tree_change(
 
 pub(crate) fn pubsub_linkstate_change(tables: &mut Tables, zid: &ZenohId, links: &[ZenohId]) {
     if let Some(src_face) = tables.get_face(zid).cloned() {
-        if tables.router_peers_failover_brokering
+        if (tables.router_peers_failover_brokering || !tables.relayed.is_empty())
             && tables.whatami == WhatAmI::Router
             && src_face.whatami == WhatAmI::Peer
         {
@@ -1148,8 +1168,8 @@ pub(crate) fn pubsub_linkstate_change(tables: &mut Tables, zid: &ZenohId, links:
                         let dst_face = &mut get_mut_unchecked(ctx).face;
                         if dst_face.whatami == WhatAmI::Peer && src_face.zid != dst_face.zid {
                             if dst_face.local_subs.contains(res) {
-                                let forget = !Tables::failover_brokering_to(links, dst_face.zid)
-                                    && {
+                                let forget =
+                                    !tables.brokering_to(src_face.zid, links, dst_face.zid) && {
                                         let ctx_links = tables
                                             .peers_net
                                             .as_ref()
@@ -1158,7 +1178,8 @@ pub(crate) fn pubsub_linkstate_change(tables: &mut Tables, zid: &ZenohId, links:
                                         res.session_ctxs.values().any(|ctx2| {
                                             ctx2.face.whatami == WhatAmI::Peer
                                                 && ctx2.subs.is_some()
-                                                && Tables::failover_brokering_to(
+                                                && tables.brokering_to(
+                                                    dst_face.zid,
                                                     ctx_links,
                                                     ctx2.face.zid,
                                                 )
@@ -1180,7 +1201,7 @@ pub(crate) fn pubsub_linkstate_change(tables: &mut Tables, zid: &ZenohId, links:
 
                                     get_mut_unchecked(dst_face).local_subs.remove(res);
                                 }
-                            } else if Tables::failover_brokering_to(links, ctx.face.zid) {
+                            } else if tables.brokering_to(src_face.zid, links, ctx.face.zid) {
                                 let dst_face = &mut get_mut_unchecked(ctx).face;
                                 get_mut_unchecked(dst_face).local_subs.insert(res.clone());
                                 let key_expr = Resource::decl_key(res, dst_face);
@@ -1196,6 +1217,7 @@ pub(crate) fn pubsub_linkstate_change(tables: &mut Tables, zid: &ZenohId, links:
                                         id: 0, // TODO
                                         wire_expr: key_expr,
                                         ext_info: sub_info,
+                                        ext_filter: None,
                                     }),
                                 });
                             }
@@ -1207,6 +1229,24 @@ pub(crate) fn pubsub_linkstate_change(tables: &mut Tables, zid: &ZenohId, links:
     }
 }
 
+pub(crate) fn pubsub_relay_change(tables: &mut Tables) {
+    if tables.whatami == WhatAmI::Router && !tables.full_net(WhatAmI::Peer) {
+        let sub_info = SubscriberInfo {
+            reliability: Reliability::Reliable, // @TODO
+            mode: Mode::Push,
+        };
+        for mut face in tables
+            .faces
+            .values()
+            .filter(|face| face.whatami == WhatAmI::Peer)
+            .cloned()
+            .collect::<Vec<Arc<FaceState>>>()
+        {
+            propagate_router_subs_to_peer(tables, &mut face, &sub_info);
+        }
+    }
+}
+
 #[inline]
 fn insert_faces_for_subs(
     route: &mut Route,
@@ -1458,61 +1498,8 @@ pub(super) fn compute_data_routes_(tables: &Tables, res: &Arc<Resource>) -> Data
 
 pub(crate) fn compute_data_routes(tables: &mut Tables, res: &mut Arc<Resource>) {
     if res.context.is_some() {
-        let mut res_mut = res.clone();
-        let res_mut = get_mut_unchecked(&mut res_mut);
-        let mut expr = RoutingExpr::new(res, "");
-        if tables.whatami == WhatAmI::Router {
-            let indexes = tables
-                .routers_net
-                .as_ref()
-                .unwrap()
-                .graph
-                .node_indices()
-                .collect::<Vec<NodeIndex>>();
-            let max_idx = indexes.iter().max().unwrap();
-            let routers_data_routes = &mut res_mut.context_mut().routers_data_routes;
-            routers_data_routes.clear();
-            routers_data_routes.resize_with(max_idx.index() + 1, || Arc::new(HashMap::new()));
-
-            for idx in &indexes {
-                routers_data_routes[idx.index()] =
-                    compute_data_route(tables, &mut expr, Some(idx.index()), WhatAmI::Router);
-            }
-
-            res_mut.context_mut().peer_data_route =
-                Some(compute_data_route(tables, &mut expr, None, WhatAmI::Peer));
-        }
-        if (tables.whatami == WhatAmI::Router || tables.whatami == WhatAmI::Peer)
-            && tables.full_net(WhatAmI::Peer)
-        {
-            let indexes = tables
-                .peers_net
-                .as_ref()
-                .unwrap()
-                .graph
-                .node_indices()
-                .collect::<Vec<NodeIndex>>();
-            let max_idx = indexes.iter().max().unwrap();
-            let peers_data_routes = &mut res_mut.context_mut().peers_data_routes;
-            peers_data_routes.clear();
-            peers_data_routes.resize_with(max_idx.index() + 1, || Arc::new(HashMap::new()));
-
-            for idx in &indexes {
-                peers_data_routes[idx.index()] =
-                    compute_data_route(tables, &mut expr, Some(idx.index()), WhatAmI::Peer);
-            }
-        }
-        if tables.whatami == WhatAmI::Peer && !tables.full_net(WhatAmI::Peer) {
-            res_mut.context_mut().client_data_route =
-                Some(compute_data_route(tables, &mut expr, None, WhatAmI::Client));
-            res_mut.context_mut().peer_data_route =
-                Some(compute_data_route(tables, &mut expr, None, WhatAmI::Peer));
-        }
-        if tables.whatami == WhatAmI::Client {
-            res_mut.context_mut().client_data_route =
-                Some(compute_data_route(tables, &mut expr, None, WhatAmI::Client));
-        }
-        res_mut.context_mut().matching_pulls = compute_matching_pulls(tables, &mut expr);
+        res.context()
+            .set_data_routes(compute_data_routes_(tables, res));
     }
 }
 
@@ -1544,13 +1531,11 @@ pub(super) fn compute_matches_data_routes_<'a>(
 
 pub(super) fn disable_matches_data_routes(_tables: &mut Tables, res: &mut Arc<Resource>) {
     if res.context.is_some() {
-        get_mut_unchecked(res).context_mut().valid_data_routes = false;
+        res.context().disable_data_routes();
         for match_ in &res.context().matches {
-            let mut match_ = match_.upgrade().unwrap();
+            let match_ = match_.upgrade().unwrap();
             if !Arc::ptr_eq(&match_, res) {
-                get_mut_unchecked(&mut match_)
-                    .context_mut()
-                    .valid_data_routes = false;
+                match_.context().disable_data_routes();
             }
         }
     }
@@ -1592,6 +1577,26 @@ macro_rules! treat_timestamp {
     }
 }
 
+macro_rules! treat_trace {
+    ($tables:expr, $payload:expr, $ingress:expr) => {
+        // if hop tracing is enabled (via Config.routing.router.trace),
+        // append this router's hop to the traced data
+        if let Some(ingress) = $ingress {
+            if let PushBody::Put(Put {
+                ext_trace: Some(trace),
+                ..
+            }) = &mut $payload
+            {
+                trace.hops.push(TraceHop {
+                    zid: $tables.zid,
+                    ingress,
+                    egress: uhlc::system_time_clock(),
+                });
+            }
+        }
+    };
+}
+
 #[inline]
 fn get_data_route(
     tables: &Tables,
@@ -1673,7 +1678,7 @@ fn get_matching_pulls(
 ) -> Arc<PullCaches> {
     res.as_ref()
         .and_then(|res| res.context.as_ref())
-        .map(|ctx| ctx.matching_pulls.clone())
+        .map(|ctx| ctx.matching_pulls.load())
         .unwrap_or_else(|| compute_matching_pulls(tables, expr))
 }
 
@@ -1698,12 +1703,7 @@ fn should_route(
     outface: &Arc<FaceState>,
     expr: &mut RoutingExpr,
 ) -> bool {
-    if src_face.id != outface.id
-        && match (src_face.mcast_group.as_ref(), outface.mcast_group.as_ref()) {
-            (Some(l), Some(r)) => l != r,
-            _ => true,
-        }
-    {
+    if src_face.id != outface.id && !src_face.shares_mcast_group(outface) {
         let dst_master = tables.whatami != WhatAmI::Router
             || outface.whatami != WhatAmI::Peer
             || tables.peers_net.is_none()
@@ -1719,6 +1719,50 @@ fn should_route(
     false
 }
 
+// Whether a publication on `key_expr` passes the content filters of the subscriptions of `outface` matching it,
+// i.e. whether one of them has no filter or a filter admitting the payload. The payload of a batch being empty,
+// a batch passes a filter if one of its values published on a key matching the subscription is admitted.
+// The subscriptions without filter are only looked for when the filtered ones matching the publication reject it.
+#[inline]
+fn content_admitted(outface: &FaceState, key_expr: &str, payload: &PushBody) -> bool {
+    if outface.content_filters.is_empty() {
+        return true;
+    }
+    let PushBody::Put(put) = payload else {
+        return true;
+    };
+    #[cfg(feature = "shared-memory")]
+    if put.ext_shm.is_some() {
+        return true;
+    }
+    let Ok(key_expr) = keyexpr::new(key_expr) else {
+        return true;
+    };
+    let mut filtered = false;
+    for (sub, filter) in outface.content_filters.values() {
+        if !sub.intersects(key_expr) {
+            continue;
+        }
+        filtered = true;
+        let admitted = match &put.ext_batch {
+            Some(batch) => batch.entries.iter().any(|entry| {
+                keyexpr::new(entry.key.as_str()).map_or(false, |key| key.intersects(sub))
+                    && filter.admits(&entry.encoding, &entry.payload)
+            }),
+            None => filter.admits(&put.encoding, &put.payload),
+        };
+        if admitted {
+            return true;
+        }
+    }
+    !filtered
+        || (outface.remote_subs.len() > outface.content_filters.len()
+            && outface.remote_subs.iter().any(|res| {
+                !outface.content_filters.contains_key(res)
+                    && keyexpr::new(res.expr()).map_or(false, |sub| sub.intersects(key_expr))
+            }))
+}
+
 #[cfg(feature = "stats")]
 macro_rules! inc_stats {
     (
@@ -1749,11 +1793,14 @@ pub fn full_reentrant_route_data(
     tables_ref: &RwLock<Tables>,
     face: &FaceState,
     expr: &WireExpr,
-    ext_qos: ext::QoSType,
+    mut ext_qos: ext::QoSType,
     mut payload: PushBody,
     routing_context: u64,
 ) {
     let tables = zread!(tables_ref);
+    let ingress = (tables.trace_hops
+        && matches!(&payload, PushBody::Put(put) if put.ext_trace.is_some()))
+    .then(uhlc::system_time_clock);
     match tables.get_mapping(face, &expr.scope, expr.mapping).cloned() {
         Some(prefix) => {
             log::trace!(
@@ -1772,6 +1819,66 @@ pub fn full_reentrant_route_data(
                 inc_stats!(face, rx, admin, payload)
             }
 
+            // the data outliving its lifespan is not forwarded anymore
+            if matches!(&payload, PushBody::Put(put) if put.is_expired()) {
+                log::trace!(
+                    "Dropping expired data on {} from {}",
+                    expr.full_expr(),
+                    face
+                );
+                return;
+            }
+
+            if !face.allows(&tables, AclAction::Pub, expr.full_expr()) {
+                log::debug!(
+                    "Access control denied publication on {} from {}",
+                    expr.full_expr(),
+                    face
+                );
+                return;
+            }
+
+            if let (Some(quotas), Some(identities)) = (&tables.quotas, &face.identities) {
+                let bytes = match &payload {
+                    PushBody::Put(put) => {
+                        use zenoh_buffers::SplitBuffer;
+                        put.payload.len() as u64
+                    }
+                    PushBody::Del(_) => 0,
+                };
+                if !quotas.admit_publication(identities, &face.quota_usage, bytes) {
+                    log::debug!(
+                        "Quota exceeded by publication on {} from {}",
+                        expr.full_expr(),
+                        face
+                    );
+                    return;
+                }
+            }
+
+            if let Some(qos_overrides) = &tables.qos_overrides {
+                if tables.whatami == WhatAmI::Router {
+                    qos_overrides.apply(expr.full_expr(), &mut ext_qos);
+                }
+            }
+
+            // The adminspace audits its writes with the identities of the node they were received from
+            if expr.full_expr().starts_with("@/router/") {
+                let sinfo = Some(SourceInfoType {
+                    zid: face.zid,
+                    eid: 0,
+                    sn: 0,
+                });
+                match &mut payload {
+                    PushBody::Put(put) => put.ext_sinfo = sinfo,
+                    PushBody::Del(del) => del.ext_sinfo = sinfo,
+                }
+            }
+
+            if let Some(measurements) = &tables.measurements {
+                measurements.record(expr.full_expr(), &payload);
+            }
+
             if tables.whatami != WhatAmI::Router
                 || face.whatami != WhatAmI::Peer
                 || tables.peers_net.is_none()
@@ -1784,10 +1891,13 @@ pub fn full_reentrant_route_data(
 
                 if !(route.is_empty() && matching_pulls.is_empty()) {
                     treat_timestamp!(&tables.hlc, payload, tables.drop_future_timestamp);
+                    treat_trace!(tables, payload, ingress);
 
                     if route.len() == 1 && matching_pulls.len() == 0 {
                         let (outface, key_expr, context) = route.values().next().unwrap();
-                        if should_route(&tables, face, outface, &mut expr) {
+                        if should_route(&tables, face, outface, &mut expr)
+                            && content_admitted(outface, expr.full_expr(), &payload)
+                        {
                             drop(tables);
                             #[cfg(feature = "stats")]
                             if !admin {
@@ -1814,15 +1924,36 @@ pub fn full_reentrant_route_data(
                         }
 
                         if tables.whatami == WhatAmI::Router {
-                            let route = route
+                            #[allow(unused_mut)]
+                            let mut route = route
                                 .values()
                                 .filter(|(outface, _key_expr, _context)| {
                                     should_route(&tables, face, outface, &mut expr)
+                                        && content_admitted(outface, expr.full_expr(), &payload)
                                 })
                                 .cloned()
                                 .collect::<Vec<Direction>>();
+                            #[cfg(feature = "transport_multicast")]
+                            let group = tables
+                                .dissemination
+                                .as_ref()
+                                .and_then(|dissemination| dissemination.split(&mut route))
+                                .map(|group| (group, expr.full_expr().to_string()));
 
                             drop(tables);
+                            #[cfg(feature = "transport_multicast")]
+                            if let Some((group, group_expr)) = group {
+                                group.send(
+                                    face.zid,
+                                    Push {
+                                        wire_expr: group_expr.into(),
+                                        ext_qos,
+                                        ext_tstamp: None,
+                                        ext_nodeid: ext::NodeIdType::default(),
+                                        payload: payload.clone(),
+                                    },
+                                );
+                            }
                             for (outface, key_expr, context) in route {
                                 #[cfg(feature = "stats")]
                                 if !admin {
@@ -1842,17 +1973,16 @@ pub fn full_reentrant_route_data(
                                 })
                             }
                         } else {
+                            let route = route
+                                .values()
+                                .filter(|(outface, _key_expr, _context)| {
+                                    content_admitted(outface, expr.full_expr(), &payload)
+                                })
+                                .cloned()
+                                .collect::<Vec<Direction>>();
                             drop(tables);
-                            for (outface, key_expr, context) in route.values() {
-                                if face.id != outface.id
-                                    && match (
-                                        face.mcast_group.as_ref(),
-                                        outface.mcast_group.as_ref(),
-                                    ) {
-                                        (Some(l), Some(r)) => l != r,
-                                        _ => true,
-                                    }
-                                {
+                            for (outface, key_expr, context) in route {
+                                if face.id != outface.id && !face.shares_mcast_group(&outface) {
                                     #[cfg(feature = "stats")]
                                     if !admin {
                                         inc_stats!(face, tx, user, payload)
@@ -1919,14 +2049,14 @@ pub fn pull_data(tables_ref: &RwLock<Tables>, face: &Arc<FaceState>, expr: WireE
                         None => {
                             log::error!(
                                 "Pull data for unknown subscription {} (no info)!",
-                                prefix.expr() + expr.suffix.as_ref()
+                                [prefix.expr(), expr.suffix.as_ref()].concat()
                             );
                         }
                     },
                     None => {
                         log::error!(
                             "Pull data for unknown subscription {} (no context)!",
-                            prefix.expr() + expr.suffix.as_ref()
+                            [prefix.expr(), expr.suffix.as_ref()].concat()
                         );
                     }
                 }
@@ -1934,7 +2064,7 @@ pub fn pull_data(tables_ref: &RwLock<Tables>, face: &Arc<FaceState>, expr: WireE
             None => {
                 log::error!(
                     "Pull data for unknown subscription {} (no resource)!",
-                    prefix.expr() + expr.suffix.as_ref()
+                    [prefix.expr(), expr.suffix.as_ref()].concat()
                 );
             }
         },
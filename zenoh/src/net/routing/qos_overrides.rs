@@ -0,0 +1,107 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh_config::{QosCongestionControl, QosOverride, QosPriority};
+use zenoh_protocol::core::key_expr::keyexpr;
+use zenoh_protocol::core::{CongestionControl, Priority};
+use zenoh_protocol::network::push::ext::QoSType;
+
+/// The QoS rewritten by a router on the publications it receives.
+pub(crate) struct QosOverrides {
+    overrides: Vec<QosOverride>,
+}
+
+impl QosOverrides {
+    /// Returns `None` if there is no override.
+    pub(crate) fn new(config: &[QosOverride]) -> Option<Self> {
+        (!config.is_empty()).then(|| QosOverrides {
+            overrides: config.to_vec(),
+        })
+    }
+
+    /// Applies the first override including `key_expr` to `qos`.
+    pub(crate) fn apply(&self, key_expr: &str, qos: &mut QoSType) {
+        let Ok(key_expr) = keyexpr::new(key_expr) else {
+            return;
+        };
+        let Some(o) = self.overrides.iter().find(|o| o.keyexpr.includes(key_expr)) else {
+            return;
+        };
+        if let Some(priority) = o.priority {
+            qos.set_priority(match priority {
+                QosPriority::RealTime => Priority::RealTime,
+                QosPriority::InteractiveHigh => Priority::InteractiveHigh,
+                QosPriority::InteractiveLow => Priority::InteractiveLow,
+                QosPriority::DataHigh => Priority::DataHigh,
+                QosPriority::Data => Priority::Data,
+                QosPriority::DataLow => Priority::DataLow,
+                QosPriority::Background => Priority::Background,
+            });
+        }
+        if let Some(congestion_control) = o.congestion_control {
+            qos.set_congestion_control(match congestion_control {
+                QosCongestionControl::Drop => CongestionControl::Drop,
+                QosCongestionControl::Block => CongestionControl::Block,
+            });
+        }
+        if let Some(express) = o.express {
+            qos.set_is_express(express);
+        }
+    }
+}
+
+#[test]
+fn qos_overrides() {
+    use zenoh_protocol::core::key_expr::OwnedKeyExpr;
+
+    let o = |ke: &str, priority, congestion_control, express| QosOverride {
+        keyexpr: OwnedKeyExpr::new(ke).unwrap(),
+        priority,
+        congestion_control,
+        express,
+    };
+    assert!(QosOverrides::new(&[]).is_none());
+
+    let overrides = QosOverrides::new(&[
+        o(
+            "telemetry/critical/**",
+            Some(QosPriority::RealTime),
+            None,
+            Some(true),
+        ),
+        o(
+            "telemetry/**",
+            Some(QosPriority::Background),
+            Some(QosCongestionControl::Drop),
+            None,
+        ),
+    ])
+    .unwrap();
+    let published = QoSType::new(Priority::Data, CongestionControl::Block, false);
+
+    let mut qos = published;
+    overrides.apply("telemetry/critical/engine", &mut qos);
+    assert_eq!(qos.get_priority(), Priority::RealTime);
+    assert_eq!(qos.get_congestion_control(), CongestionControl::Block);
+    assert!(qos.is_express());
+
+    let mut qos = published;
+    overrides.apply("telemetry/cabin", &mut qos);
+    assert_eq!(qos.get_priority(), Priority::Background);
+    assert_eq!(qos.get_congestion_control(), CongestionControl::Drop);
+    assert!(!qos.is_express());
+
+    let mut qos = published;
+    overrides.apply("demo/example", &mut qos);
+    assert!(qos == published);
+}
@@ -24,15 +24,17 @@ use petgraph::graph::NodeIndex;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, RwLockReadGuard, Weak};
 use zenoh_buffers::ZBuf;
+use zenoh_config::AclAction;
 use zenoh_protocol::{
     core::{
         key_expr::{
             include::{Includer, DEFAULT_INCLUDER},
             keyexpr, OwnedKeyExpr,
         },
-        Encoding, WhatAmI, WireExpr, ZenohId,
+        CongestionControl, Encoding, Priority, WhatAmI, WireExpr, ZenohId,
     },
     network::{
         declare::{
@@ -50,6 +52,31 @@ use zenoh_util::Timed;
 pub(crate) struct Query {
     src_face: Arc<FaceState>,
     src_qid: RequestId,
+    // the lowest priority of the routed replies, at which the final reply is routed not to overtake them
+    priority: AtomicU8,
+}
+
+impl Query {
+    fn new(src_face: &Arc<FaceState>, src_qid: RequestId) -> Self {
+        src_face
+            .quota_usage
+            .pending_queries
+            .fetch_add(1, Ordering::Relaxed);
+        Query {
+            src_face: src_face.clone(),
+            src_qid,
+            priority: AtomicU8::new(Priority::default() as u8),
+        }
+    }
+}
+
+impl Drop for Query {
+    fn drop(&mut self) {
+        self.src_face
+            .quota_usage
+            .pending_queries
+            .fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 #[cfg(feature = "complete_n")]
@@ -403,7 +430,7 @@ pub fn declare_router_queryable(
                     let wtables = zwrite!(tables.tables);
                     (res.unwrap(), wtables)
                 } else {
-                    let mut fullexpr = prefix.expr();
+                    let mut fullexpr = prefix.expr().to_string();
                     fullexpr.push_str(expr.suffix.as_ref());
                     log::debug!("Register router queryable {}", fullexpr);
                     let mut matches = keyexpr::new(fullexpr.as_str())
@@ -425,13 +452,9 @@ pub fn declare_router_queryable(
             let matches_query_routes = compute_matches_query_routes_(&rtables, &res);
             drop(rtables);
 
-            let wtables = zwrite!(tables.tables);
-            for (mut res, query_routes) in matches_query_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_query_routes(query_routes);
+            for (res, query_routes) in matches_query_routes {
+                res.context().update_query_routes(query_routes);
             }
-            drop(wtables);
         }
         None => log::error!("Declare router queryable for unknown scope {}!", expr.scope),
     }
@@ -493,7 +516,7 @@ pub fn declare_peer_queryable(
                     let wtables = zwrite!(tables.tables);
                     (res.unwrap(), wtables)
                 } else {
-                    let mut fullexpr = prefix.expr();
+                    let mut fullexpr = prefix.expr().to_string();
                     fullexpr.push_str(expr.suffix.as_ref());
                     log::debug!("Register peer queryable {}", fullexpr);
                     let mut matches = keyexpr::new(fullexpr.as_str())
@@ -521,13 +544,9 @@ pub fn declare_peer_queryable(
             let matches_query_routes = compute_matches_query_routes_(&rtables, &res);
             drop(rtables);
 
-            let wtables = zwrite!(tables.tables);
-            for (mut res, query_routes) in matches_query_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_query_routes(query_routes);
+            for (res, query_routes) in matches_query_routes {
+                res.context().update_query_routes(query_routes);
             }
-            drop(wtables);
         }
         None => log::error!("Declare router queryable for unknown scope {}!", expr.scope),
     }
@@ -577,7 +596,7 @@ pub fn declare_client_queryable(
                     let wtables = zwrite!(tables.tables);
                     (res.unwrap(), wtables)
                 } else {
-                    let mut fullexpr = prefix.expr();
+                    let mut fullexpr = prefix.expr().to_string();
                     fullexpr.push_str(expr.suffix.as_ref());
                     log::debug!("Register client queryable {}", fullexpr);
                     let mut matches = keyexpr::new(fullexpr.as_str())
@@ -632,13 +651,9 @@ pub fn declare_client_queryable(
             let matches_query_routes = compute_matches_query_routes_(&rtables, &res);
             drop(rtables);
 
-            let wtables = zwrite!(tables.tables);
-            for (mut res, query_routes) in matches_query_routes {
-                get_mut_unchecked(&mut res)
-                    .context_mut()
-                    .update_query_routes(query_routes);
+            for (res, query_routes) in matches_query_routes {
+                res.context().update_query_routes(query_routes);
             }
-            drop(wtables);
         }
         None => log::error!("Declare queryable for unknown scope {}!", expr.scope),
     }
@@ -863,12 +878,10 @@ pub fn forget_router_queryable(
                 let matches_query_routes = compute_matches_query_routes_(&rtables, &res);
                 drop(rtables);
 
-                let wtables = zwrite!(tables.tables);
-                for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                for (res, query_routes) in matches_query_routes {
+                    res.context().update_query_routes(query_routes);
                 }
+                let wtables = zwrite!(tables.tables);
                 Resource::clean(&mut res);
                 drop(wtables);
             }
@@ -934,12 +947,10 @@ pub fn forget_peer_queryable(
                 let matches_query_routes = compute_matches_query_routes_(&rtables, &res);
                 drop(rtables);
 
-                let wtables = zwrite!(tables.tables);
-                for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                for (res, query_routes) in matches_query_routes {
+                    res.context().update_query_routes(query_routes);
                 }
+                let wtables = zwrite!(tables.tables);
                 Resource::clean(&mut res);
                 drop(wtables);
             }
@@ -1037,12 +1048,10 @@ pub fn forget_client_queryable(
                 let matches_query_routes = compute_matches_query_routes_(&rtables, &res);
                 drop(rtables);
 
-                let wtables = zwrite!(tables.tables);
-                for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                for (res, query_routes) in matches_query_routes {
+                    res.context().update_query_routes(query_routes);
                 }
+                let wtables = zwrite!(tables.tables);
                 Resource::clean(&mut res);
                 drop(wtables);
             }
@@ -1052,6 +1061,37 @@ pub fn forget_client_queryable(
     }
 }
 
+fn propagate_router_qabls_to_peer(tables: &Tables, face: &mut Arc<FaceState>) {
+    for qabl in tables.router_qabls.iter() {
+        if qabl.context.is_some()
+            && !face.local_qabls.contains_key(qabl)
+            && (qabl.context().router_qabls.keys().any(|r| *r != tables.zid)
+                || qabl.session_ctxs.values().any(|s| {
+                    s.qabl.is_some()
+                        && (s.face.whatami == WhatAmI::Client
+                            || (s.face.whatami == WhatAmI::Peer
+                                && tables.failover_brokering(s.face.zid, face.zid)))
+                }))
+        {
+            let info = local_qabl_info(tables, qabl, face);
+            get_mut_unchecked(face)
+                .local_qabls
+                .insert(qabl.clone(), info);
+            let key_expr = Resource::decl_key(qabl, face);
+            face.primitives.send_declare(Declare {
+                ext_qos: ext::QoSType::declare_default(),
+                ext_tstamp: None,
+                ext_nodeid: ext::NodeIdType::default(),
+                body: DeclareBody::DeclareQueryable(DeclareQueryable {
+                    id: 0, // TODO
+                    wire_expr: key_expr,
+                    ext_info: info,
+                }),
+            });
+        }
+    }
+}
+
 pub(crate) fn queries_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
     match tables.whatami {
         WhatAmI::Router => {
@@ -1076,33 +1116,7 @@ pub(crate) fn queries_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
                     }
                 }
             } else if face.whatami == WhatAmI::Peer && !tables.full_net(WhatAmI::Peer) {
-                for qabl in tables.router_qabls.iter() {
-                    if qabl.context.is_some()
-                        && (qabl.context().router_qabls.keys().any(|r| *r != tables.zid)
-                            || qabl.session_ctxs.values().any(|s| {
-                                s.qabl.is_some()
-                                    && (s.face.whatami == WhatAmI::Client
-                                        || (s.face.whatami == WhatAmI::Peer
-                                            && tables.failover_brokering(s.face.zid, face.zid)))
-                            }))
-                    {
-                        let info = local_qabl_info(tables, qabl, face);
-                        get_mut_unchecked(face)
-                            .local_qabls
-                            .insert(qabl.clone(), info);
-                        let key_expr = Resource::decl_key(qabl, face);
-                        face.primitives.send_declare(Declare {
-                            ext_qos: ext::QoSType::declare_default(),
-                            ext_tstamp: None,
-                            ext_nodeid: ext::NodeIdType::default(),
-                            body: DeclareBody::DeclareQueryable(DeclareQueryable {
-                                id: 0, // TODO
-                                wire_expr: key_expr,
-                                ext_info: info,
-                            }),
-                        });
-                    }
-                }
+                propagate_router_qabls_to_peer(tables, face);
             }
         }
         WhatAmI::Peer => {
@@ -1171,10 +1185,8 @@ pub(crate) fn queries_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
                 unregister_router_queryable(tables, &mut res, node);
 
                 let matches_query_routes = compute_matches_query_routes_(tables, &res);
-                for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                for (res, query_routes) in matches_query_routes {
+                    res.context().update_query_routes(query_routes);
                 }
                 Resource::clean(&mut res);
             }
@@ -1203,10 +1215,8 @@ pub(crate) fn queries_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
                 }
 
                 let matches_query_routes = compute_matches_query_routes_(tables, &res);
-                for (mut res, query_routes) in matches_query_routes {
-                    get_mut_unchecked(&mut res)
-                        .context_mut()
-                        .update_query_routes(query_routes);
+                for (res, query_routes) in matches_query_routes {
+                    res.context().update_query_routes(query_routes);
                 }
                 Resource::clean(&mut res)
             }
@@ -1217,7 +1227,7 @@ pub(crate) fn queries_remove_node(tables: &mut Tables, node: &ZenohId, net_type:
 
 pub(crate) fn queries_linkstate_change(tables: &mut Tables, zid: &ZenohId, links: &[ZenohId]) {
     if let Some(src_face) = tables.get_face(zid) {
-        if tables.router_peers_failover_brokering
+        if (tables.router_peers_failover_brokering || !tables.relayed.is_empty())
             && tables.whatami == WhatAmI::Router
             && src_face.whatami == WhatAmI::Peer
         {
@@ -1234,8 +1244,8 @@ pub(crate) fn queries_linkstate_change(tables: &mut Tables, zid: &ZenohId, links
                         let dst_face = &mut get_mut_unchecked(ctx).face;
                         if dst_face.whatami == WhatAmI::Peer && src_face.zid != dst_face.zid {
                             if dst_face.local_qabls.contains_key(res) {
-                                let forget = !Tables::failover_brokering_to(links, dst_face.zid)
-                                    && {
+                                let forget =
+                                    !tables.brokering_to(src_face.zid, links, dst_face.zid) && {
                                         let ctx_links = tables
                                             .peers_net
                                             .as_ref()
@@ -1244,7 +1254,8 @@ pub(crate) fn queries_linkstate_change(tables: &mut Tables, zid: &ZenohId, links
                                         res.session_ctxs.values().any(|ctx2| {
                                             ctx2.face.whatami == WhatAmI::Peer
                                                 && ctx2.qabl.is_some()
-                                                && Tables::failover_brokering_to(
+                                                && tables.brokering_to(
+                                                    dst_face.zid,
                                                     ctx_links,
                                                     ctx2.face.zid,
                                                 )
@@ -1264,7 +1275,7 @@ pub(crate) fn queries_linkstate_change(tables: &mut Tables, zid: &ZenohId, links
 
                                     get_mut_unchecked(dst_face).local_qabls.remove(res);
                                 }
-                            } else if Tables::failover_brokering_to(links, ctx.face.zid) {
+                            } else if tables.brokering_to(src_face.zid, links, ctx.face.zid) {
                                 let dst_face = &mut get_mut_unchecked(ctx).face;
                                 let info = local_qabl_info(tables, res, dst_face);
                                 get_mut_unchecked(dst_face)
@@ -1290,6 +1301,22 @@ pub(crate) fn queries_linkstate_change(tables: &mut Tables, zid: &ZenohId, links
     }
 }
 
+pub(crate) fn queries_relay_change(tables: &mut Tables) {
+    if tables.whatami == WhatAmI::Router && !tables.full_net(WhatAmI::Peer) {
+        for mut face in tables
+            .faces
+            .values()
+            .filter(|face| face.whatami == WhatAmI::Peer)
+            .cloned()
+            .collect::<Vec<Arc<FaceState>>>()
+        {
+            propagate_router_qabls_to_peer(tables, &mut face);
+        }
+    }
+}
+
+// Resources are hashed by key expression, the routes of their contexts are not part of their identity
+#[allow(clippy::mutable_key_type)]
 pub(crate) fn queries_tree_change(
     tables: &mut Tables,
     new_childs: &[Vec<NodeIndex>],
@@ -1343,6 +1370,7 @@ fn insert_target_for_qabls(
     source: usize,
     qabls: &HashMap<ZenohId, QueryableInfo>,
     complete: bool,
+    coverage: Option<&OwnedKeyExpr>,
 ) {
     if net.trees.len() > source {
         for (qabl, qabl_info) in qabls {
@@ -1369,6 +1397,9 @@ fn insert_target_for_qabls(
                                         } else {
                                             0
                                         },
+                                        coverage: coverage
+                                            .filter(|_| qabl_info.complete > 0)
+                                            .cloned(),
                                         distance: net.distances[qabl_idx.index()],
                                     });
                                 }
@@ -1424,6 +1455,7 @@ fn compute_query_route(
     for mres in matches.iter() {
         let mres = mres.upgrade().unwrap();
         let complete = DEFAULT_INCLUDER.includes(mres.expr().as_bytes(), key_expr.as_bytes());
+        let coverage = OwnedKeyExpr::try_from(mres.expr()).ok();
         if tables.whatami == WhatAmI::Router {
             if master || source_type == WhatAmI::Router {
                 let net = tables.routers_net.as_ref().unwrap();
@@ -1439,6 +1471,7 @@ fn compute_query_route(
                     router_source,
                     &mres.context().router_qabls,
                     complete,
+                    coverage.as_ref(),
                 );
             }
 
@@ -1456,6 +1489,7 @@ fn compute_query_route(
                     peer_source,
                     &mres.context().peer_qabls,
                     complete,
+                    coverage.as_ref(),
                 );
             }
         }
@@ -1474,6 +1508,7 @@ fn compute_query_route(
                 peer_source,
                 &mres.context().peer_qabls,
                 complete,
+                coverage.as_ref(),
             );
         }
 
@@ -1492,6 +1527,7 @@ fn compute_query_route(
                             } else {
                                 0
                             },
+                            coverage: coverage.clone().filter(|_| qabl_info.complete > 0),
                             distance: 0.5,
                         });
                     }
@@ -1573,70 +1609,8 @@ pub(super) fn compute_query_routes_(tables: &Tables, res: &Arc<Resource>) -> Que
 
 pub(crate) fn compute_query_routes(tables: &mut Tables, res: &mut Arc<Resource>) {
     if res.context.is_some() {
-        let mut res_mut = res.clone();
-        let res_mut = get_mut_unchecked(&mut res_mut);
-        let mut expr = RoutingExpr::new(res, "");
-        if tables.whatami == WhatAmI::Router {
-            let indexes = tables
-                .routers_net
-                .as_ref()
-                .unwrap()
-                .graph
-                .node_indices()
-                .collect::<Vec<NodeIndex>>();
-            let max_idx = indexes.iter().max().unwrap();
-            let routers_query_routes = &mut res_mut.context_mut().routers_query_routes;
-            routers_query_routes.clear();
-            routers_query_routes
-                .resize_with(max_idx.index() + 1, || Arc::new(QueryTargetQablSet::new()));
-
-            for idx in &indexes {
-                routers_query_routes[idx.index()] =
-                    compute_query_route(tables, &mut expr, Some(idx.index()), WhatAmI::Router);
-            }
-
-            res_mut.context_mut().peer_query_route =
-                Some(compute_query_route(tables, &mut expr, None, WhatAmI::Peer));
-        }
-        if (tables.whatami == WhatAmI::Router || tables.whatami == WhatAmI::Peer)
-            && tables.full_net(WhatAmI::Peer)
-        {
-            let indexes = tables
-                .peers_net
-                .as_ref()
-                .unwrap()
-                .graph
-                .node_indices()
-                .collect::<Vec<NodeIndex>>();
-            let max_idx = indexes.iter().max().unwrap();
-            let peers_query_routes = &mut res_mut.context_mut().peers_query_routes;
-            peers_query_routes.clear();
-            peers_query_routes
-                .resize_with(max_idx.index() + 1, || Arc::new(QueryTargetQablSet::new()));
-
-            for idx in &indexes {
-                peers_query_routes[idx.index()] =
-                    compute_query_route(tables, &mut expr, Some(idx.index()), WhatAmI::Peer);
-            }
-        }
-        if tables.whatami == WhatAmI::Peer && !tables.full_net(WhatAmI::Peer) {
-            res_mut.context_mut().client_query_route = Some(compute_query_route(
-                tables,
-                &mut expr,
-                None,
-                WhatAmI::Client,
-            ));
-            res_mut.context_mut().peer_query_route =
-                Some(compute_query_route(tables, &mut expr, None, WhatAmI::Peer));
-        }
-        if tables.whatami == WhatAmI::Client {
-            res_mut.context_mut().client_query_route = Some(compute_query_route(
-                tables,
-                &mut expr,
-                None,
-                WhatAmI::Client,
-            ));
-        }
+        res.context()
+            .set_query_routes(compute_query_routes_(tables, res));
     }
 }
 
@@ -1683,6 +1657,11 @@ fn should_route(
     expr: &mut RoutingExpr,
 ) -> bool {
     if src_face.id != outface.id {
+        // The queries a namespaced client can't express aren't routed to it
+        if matches!(&outface.namespace, Some(namespace) if namespace.strip(expr.full_expr()).is_none())
+        {
+            return false;
+        }
         let dst_master = tables.whatami != WhatAmI::Router
             || outface.whatami != WhatAmI::Peer
             || tables.peers_net.is_none()
@@ -1756,6 +1735,50 @@ fn compute_final_route(
             }
             route
         }
+        TargetType::CompleteStorage => {
+            // The queryables are sorted by distance: the nearest one covering the whole queried
+            // key expression is enough, otherwise the nearest ones among those covering the same part
+            // of it are kept, and the ones covering a part already covered by another one are dropped.
+            let mut covering: Vec<&QueryTargetQabl> = vec![];
+            for qabl in qabls.iter() {
+                if let Some(coverage) = &qabl.coverage {
+                    if !should_route(tables, src_face, &qabl.direction.0, expr) {
+                        continue;
+                    }
+                    if qabl.complete > 0 {
+                        covering = vec![qabl];
+                        break;
+                    }
+                    if !covering
+                        .iter()
+                        .any(|c| c.coverage.as_ref().unwrap().includes(coverage))
+                    {
+                        covering.retain(|c| !coverage.includes(c.coverage.as_ref().unwrap()));
+                        covering.push(qabl);
+                    }
+                }
+            }
+            let mut route = HashMap::new();
+            for qabl in covering {
+                #[cfg(feature = "complete_n")]
+                {
+                    route.entry(qabl.direction.0.id).or_insert_with(|| {
+                        let mut direction = qabl.direction.clone();
+                        let qid = insert_pending_query(&mut direction.0, query.clone());
+                        (direction, qid, *target)
+                    });
+                }
+                #[cfg(not(feature = "complete_n"))]
+                {
+                    route.entry(qabl.direction.0.id).or_insert_with(|| {
+                        let mut direction = qabl.direction.clone();
+                        let qid = insert_pending_query(&mut direction.0, query.clone());
+                        (direction, qid)
+                    });
+                }
+            }
+            route
+        }
         #[cfg(feature = "complete_n")]
         TargetType::Complete(n) => {
             let mut route = HashMap::new();
@@ -1770,11 +1793,11 @@ fn compute_final_route(
                     if qabl.direction.0.id != src_face.id
                         && qabl.complete > 0
                         && (qabl.direction.0.whatami != WhatAmI::Peer
-                            || (tables.router_peers_failover_brokering
-                                && Tables::failover_brokering_to(
-                                    source_links,
-                                    qabl.direction.0.zid,
-                                )))
+                            || tables.brokering_to(
+                                src_face.zid,
+                                source_links,
+                                qabl.direction.0.zid,
+                            ))
                     {
                         let nb = std::cmp::min(qabl.complete, remaining);
                         route.entry(qabl.direction.0.id).or_insert_with(|| {
@@ -1843,7 +1866,7 @@ fn compute_local_replies(
     // Only the first routing point in the query route
     // should return the liveliness tokens
     if face.whatami == WhatAmI::Client {
-        let key_expr = prefix.expr() + suffix;
+        let key_expr = [prefix.expr(), suffix].concat();
         let key_expr = match OwnedKeyExpr::try_from(key_expr) {
             Ok(ke) => ke,
             Err(e) => {
@@ -1904,13 +1927,11 @@ impl Timed for QueryCleanup {
 
 pub(super) fn disable_matches_query_routes(_tables: &mut Tables, res: &mut Arc<Resource>) {
     if res.context.is_some() {
-        get_mut_unchecked(res).context_mut().valid_query_routes = false;
+        res.context().disable_query_routes();
         for match_ in &res.context().matches {
-            let mut match_ = match_.upgrade().unwrap();
+            let match_ = match_.upgrade().unwrap();
             if !Arc::ptr_eq(&match_, res) {
-                get_mut_unchecked(&mut match_)
-                    .context_mut()
-                    .valid_query_routes = false;
+                match_.context().disable_query_routes();
             }
         }
     }
@@ -2047,6 +2068,9 @@ macro_rules! inc_res_stats {
                             e.ext_body.as_ref().map(|b| b.payload.len()).unwrap_or(0),
                         );
                     }
+                    ResponseBody::Del(_) => {
+                        stats.[<$txrx _z_del_msgs>].[<inc_ $space>](1);
+                    }
                     ResponseBody::Ack(_) => (),
                 }
             }
@@ -2086,6 +2110,40 @@ pub fn route_query(
                 inc_req_stats!(face, rx, admin, body)
             }
 
+            if !face.allows(&rtables, AclAction::Get, expr.full_expr()) {
+                log::debug!(
+                    "Access control denied query {}:{} on {}",
+                    face,
+                    qid,
+                    expr.full_expr()
+                );
+                drop(rtables);
+                face.primitives.clone().send_response_final(ResponseFinal {
+                    rid: qid,
+                    ext_qos: response::ext::QoSType::response_final_default(),
+                    ext_tstamp: None,
+                });
+                return;
+            }
+
+            if let (Some(quotas), Some(identities)) = (&rtables.quotas, &face.identities) {
+                if !quotas.admit_query(identities, &face.quota_usage) {
+                    log::debug!(
+                        "Quota exceeded by query {}:{} on {}",
+                        face,
+                        qid,
+                        expr.full_expr()
+                    );
+                    drop(rtables);
+                    face.primitives.clone().send_response_final(ResponseFinal {
+                        rid: qid,
+                        ext_qos: response::ext::QoSType::response_final_default(),
+                        ext_tstamp: None,
+                    });
+                    return;
+                }
+            }
+
             if rtables.whatami != WhatAmI::Router
                 || face.whatami != WhatAmI::Peer
                 || rtables.peers_net.is_none()
@@ -2095,10 +2153,7 @@ pub fn route_query(
                 let res = Resource::get_resource(&prefix, expr.suffix);
                 let route = get_query_route(&rtables, face, &res, &mut expr, routing_context);
 
-                let query = Arc::new(Query {
-                    src_face: face.clone(),
-                    src_qid: qid,
-                });
+                let query = Arc::new(Query::new(face, qid));
 
                 let queries_lock = zwrite!(tables_ref.queries_lock);
                 let route = compute_final_route(&rtables, &route, face, &mut expr, &target, query);
@@ -2253,6 +2308,7 @@ pub(crate) fn route_send_response(
     tables_ref: &Arc<TablesLock>,
     face: &mut Arc<FaceState>,
     qid: RequestId,
+    ext_qos: response::ext::QoSType,
     ext_respid: Option<ResponderIdType>,
     key_expr: WireExpr,
     body: ResponseBody,
@@ -2278,11 +2334,15 @@ pub(crate) fn route_send_response(
                 inc_res_stats!(query.src_face, tx, admin, body)
             }
 
+            // the replies keep the priority they were sent with, e.g. to not delay the live publications
+            query
+                .priority
+                .fetch_max(ext_qos.get_priority() as u8, Ordering::Relaxed);
             query.src_face.primitives.clone().send_response(Response {
                 rid: query.src_qid,
                 wire_expr: key_expr.to_owned(),
                 payload: body,
-                ext_qos: response::ext::QoSType::response_default(),
+                ext_qos,
                 ext_tstamp: None,
                 ext_respid,
             });
@@ -2333,13 +2393,15 @@ pub(crate) fn finalize_pending_queries(tables_ref: &TablesLock, face: &mut Arc<F
 pub(crate) fn finalize_pending_query(query: Arc<Query>) {
     if let Some(query) = Arc::into_inner(query) {
         log::debug!("Propagate final reply {}:{}", query.src_face, query.src_qid);
+        let priority =
+            Priority::try_from(query.priority.load(Ordering::Relaxed)).unwrap_or_default();
         query
             .src_face
             .primitives
             .clone()
             .send_response_final(ResponseFinal {
                 rid: query.src_qid,
-                ext_qos: response::ext::QoSType::response_final_default(),
+                ext_qos: response::ext::QoSType::new(priority, CongestionControl::Block, false),
                 ext_tstamp: None,
             });
     }
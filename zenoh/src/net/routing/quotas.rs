@@ -0,0 +1,214 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_config::{QuotaRule, QuotasConf};
+use zenoh_core::zlock;
+
+/// The duration of the windows the publication rates are measured over.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// The limits of a remote node, the lowest ones of the rules matching it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct QuotaLimits {
+    pub(crate) publications_per_sec: Option<u64>,
+    pub(crate) bytes_per_sec: Option<u64>,
+    pub(crate) concurrent_queries: Option<u64>,
+}
+
+/// The quotas enforced on the messages received from remote nodes.
+pub(crate) struct Quotas {
+    rules: Vec<QuotaRule>,
+}
+
+impl Quotas {
+    /// Returns `None` if quotas are disabled.
+    pub(crate) fn new(config: &QuotasConf) -> Option<Self> {
+        config.enabled().then(|| Quotas {
+            rules: config.rules().clone(),
+        })
+    }
+
+    pub(crate) fn limits(&self, identities: &[String]) -> QuotaLimits {
+        fn min(limit: Option<u64>, rule: Option<u64>) -> Option<u64> {
+            match (limit, rule) {
+                (Some(limit), Some(rule)) => Some(limit.min(rule)),
+                (limit, rule) => limit.or(rule),
+            }
+        }
+        self.rules
+            .iter()
+            .filter(|rule| {
+                rule.identities.is_empty()
+                    || rule.identities.iter().any(|id| identities.contains(id))
+            })
+            .fold(QuotaLimits::default(), |limits, rule| QuotaLimits {
+                publications_per_sec: min(
+                    limits.publications_per_sec,
+                    rule.max_publications_per_sec,
+                ),
+                bytes_per_sec: min(limits.bytes_per_sec, rule.max_bytes_per_sec),
+                concurrent_queries: min(limits.concurrent_queries, rule.max_concurrent_queries),
+            })
+    }
+
+    /// Returns whether a publication of `bytes` payload bytes is within the limits of the node, counting it if so.
+    pub(crate) fn admit_publication(
+        &self,
+        identities: &[String],
+        usage: &QuotaUsage,
+        bytes: u64,
+    ) -> bool {
+        let limits = self.limits(identities);
+        if usage.admit_publication(&limits, bytes, Instant::now()) {
+            true
+        } else {
+            usage.rejected_publications.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+    }
+
+    /// Returns whether a new query is within the limits of the node.
+    pub(crate) fn admit_query(&self, identities: &[String], usage: &QuotaUsage) -> bool {
+        let limits = self.limits(identities);
+        match limits.concurrent_queries {
+            Some(limit) if usage.pending_queries.load(Ordering::Relaxed) >= limit => {
+                usage.rejected_queries.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+struct QuotaWindow {
+    start: Instant,
+    publications: u64,
+    bytes: u64,
+}
+
+/// The usage of a remote node: its publications in the current window, its queries awaiting their final reply,
+/// and the number of its messages rejected so far.
+pub(crate) struct QuotaUsage {
+    window: Mutex<QuotaWindow>,
+    pub(crate) pending_queries: AtomicU64,
+    rejected_publications: AtomicU64,
+    rejected_queries: AtomicU64,
+}
+
+impl Default for QuotaUsage {
+    fn default() -> Self {
+        QuotaUsage {
+            window: Mutex::new(QuotaWindow {
+                start: Instant::now(),
+                publications: 0,
+                bytes: 0,
+            }),
+            pending_queries: AtomicU64::new(0),
+            rejected_publications: AtomicU64::new(0),
+            rejected_queries: AtomicU64::new(0),
+        }
+    }
+}
+
+impl QuotaUsage {
+    fn admit_publication(&self, limits: &QuotaLimits, bytes: u64, now: Instant) -> bool {
+        let mut window = zlock!(self.window);
+        if now.duration_since(window.start) >= WINDOW {
+            window.start = now;
+            window.publications = 0;
+            window.bytes = 0;
+        }
+        let exceeds = |limit: Option<u64>, value: u64| limit.map_or(false, |limit| value > limit);
+        if exceeds(limits.publications_per_sec, window.publications + 1)
+            || exceeds(limits.bytes_per_sec, window.bytes + bytes)
+        {
+            return false;
+        }
+        window.publications += 1;
+        window.bytes += bytes;
+        true
+    }
+
+    pub(crate) fn to_json(&self, limits: &QuotaLimits) -> serde_json::Value {
+        json!({
+            "limits": {
+                "max_publications_per_sec": limits.publications_per_sec,
+                "max_bytes_per_sec": limits.bytes_per_sec,
+                "max_concurrent_queries": limits.concurrent_queries,
+            },
+            "pending_queries": self.pending_queries.load(Ordering::Relaxed),
+            "rejected_publications": self.rejected_publications.load(Ordering::Relaxed),
+            "rejected_queries": self.rejected_queries.load(Ordering::Relaxed),
+        })
+    }
+}
+
+#[test]
+fn quotas() {
+    let rule = |identities: &[&str], publications, bytes, queries| QuotaRule {
+        identities: identities.iter().map(|id| id.to_string()).collect(),
+        max_publications_per_sec: publications,
+        max_bytes_per_sec: bytes,
+        max_concurrent_queries: queries,
+    };
+    let mut config = QuotasConf::default();
+    assert!(Quotas::new(&config).is_none());
+
+    config.set_enabled(true).unwrap();
+    config
+        .set_rules(vec![
+            rule(&[], Some(3), None, Some(1)),
+            rule(&["username:bob"], Some(5), Some(10), None),
+        ])
+        .unwrap();
+    let quotas = Quotas::new(&config).unwrap();
+    let alice = ["zid:1".to_string(), "username:alice".to_string()];
+    let bob = ["zid:2".to_string(), "username:bob".to_string()];
+    assert_eq!(
+        quotas.limits(&bob),
+        QuotaLimits {
+            publications_per_sec: Some(3),
+            bytes_per_sec: Some(10),
+            concurrent_queries: Some(1),
+        }
+    );
+
+    let usage = QuotaUsage::default();
+    let limits = quotas.limits(&alice);
+    let start = Instant::now();
+    for _ in 0..3 {
+        assert!(usage.admit_publication(&limits, 100, start));
+    }
+    assert!(!usage.admit_publication(&limits, 100, start));
+    // A new window
+    assert!(usage.admit_publication(&limits, 100, start + WINDOW));
+
+    let usage = QuotaUsage::default();
+    assert!(quotas.admit_publication(&bob, &usage, 6));
+    assert!(!quotas.admit_publication(&bob, &usage, 6));
+    assert!(quotas.admit_publication(&bob, &usage, 4));
+
+    assert!(quotas.admit_query(&bob, &usage));
+    usage.pending_queries.fetch_add(1, Ordering::Relaxed);
+    assert!(!quotas.admit_query(&bob, &usage));
+    usage.pending_queries.fetch_sub(1, Ordering::Relaxed);
+    assert!(quotas.admit_query(&bob, &usage));
+
+    let json = usage.to_json(&quotas.limits(&bob));
+    assert_eq!(json["rejected_publications"], 1);
+    assert_eq!(json["rejected_queries"], 1);
+}
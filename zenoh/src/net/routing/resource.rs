@@ -16,13 +16,17 @@ use super::router::{Tables, TablesLock};
 use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Weak};
 #[cfg(feature = "complete_n")]
 use zenoh_protocol::network::request::ext::TargetType;
 use zenoh_protocol::network::RequestId;
 use zenoh_protocol::zenoh::PushBody;
 use zenoh_protocol::{
-    core::{key_expr::keyexpr, ExprId, WireExpr, ZenohId},
+    core::{
+        key_expr::{keyexpr, OwnedKeyExpr},
+        ExprId, WireExpr, ZenohId,
+    },
     network::{
         declare::{
             ext, queryable::ext::QueryableInfo, subscriber::ext::SubscriberInfo, Declare,
@@ -31,7 +35,7 @@ use zenoh_protocol::{
         Mapping,
     },
 };
-use zenoh_sync::get_mut_unchecked;
+use zenoh_sync::{get_mut_unchecked, RcuCell};
 
 pub(super) type RoutingContext = u16;
 
@@ -44,6 +48,8 @@ pub(super) type QueryRoute = HashMap<usize, (Direction, RequestId)>;
 pub(super) struct QueryTargetQabl {
     pub(super) direction: Direction,
     pub(super) complete: u64,
+    /// The key expression the queryable is complete on, if it is complete on a part of the queried one.
+    pub(super) coverage: Option<OwnedKeyExpr>,
     pub(super) distance: f64,
 }
 pub(super) type QueryTargetQablSet = Vec<QueryTargetQabl>;
@@ -58,6 +64,7 @@ pub(super) struct SessionContext {
     pub(super) last_values: HashMap<String, PushBody>,
 }
 
+#[derive(Default)]
 pub(super) struct DataRoutes {
     pub(super) matching_pulls: Option<Arc<PullCaches>>,
     pub(super) routers_data_routes: Vec<Arc<Route>>,
@@ -66,6 +73,7 @@ pub(super) struct DataRoutes {
     pub(super) client_data_route: Option<Arc<Route>>,
 }
 
+#[derive(Default)]
 pub(super) struct QueryRoutes {
     pub(super) routers_query_routes: Vec<Arc<QueryTargetQablSet>>,
     pub(super) peers_query_routes: Vec<Arc<QueryTargetQablSet>>,
@@ -79,17 +87,13 @@ pub(super) struct ResourceContext {
     pub(super) router_qabls: HashMap<ZenohId, QueryableInfo>,
     pub(super) peer_qabls: HashMap<ZenohId, QueryableInfo>,
     pub(super) matches: Vec<Weak<Resource>>,
-    pub(super) matching_pulls: Arc<PullCaches>,
-    pub(super) valid_data_routes: bool,
-    pub(super) routers_data_routes: Vec<Arc<Route>>,
-    pub(super) peers_data_routes: Vec<Arc<Route>>,
-    pub(super) peer_data_route: Option<Arc<Route>>,
-    pub(super) client_data_route: Option<Arc<Route>>,
-    pub(super) valid_query_routes: bool,
-    pub(super) routers_query_routes: Vec<Arc<QueryTargetQablSet>>,
-    pub(super) peers_query_routes: Vec<Arc<QueryTargetQablSet>>,
-    pub(super) peer_query_route: Option<Arc<QueryTargetQablSet>>,
-    pub(super) client_query_route: Option<Arc<QueryTargetQablSet>>,
+    // The routes are read by the data path without locking, and replaced as a whole
+    // when recomputed after declarations and network changes
+    pub(super) matching_pulls: RcuCell<PullCaches>,
+    pub(super) valid_data_routes: AtomicBool,
+    pub(super) data_routes: RcuCell<DataRoutes>,
+    pub(super) valid_query_routes: AtomicBool,
+    pub(super) query_routes: RcuCell<QueryRoutes>,
 }
 
 impl ResourceContext {
@@ -100,43 +104,63 @@ impl ResourceContext {
             router_qabls: HashMap::new(),
             peer_qabls: HashMap::new(),
             matches: Vec::new(),
-            matching_pulls: Arc::new(Vec::new()),
-            valid_data_routes: false,
-            routers_data_routes: Vec::new(),
-            peers_data_routes: Vec::new(),
-            peer_data_route: None,
-            client_data_route: None,
-            valid_query_routes: false,
-            routers_query_routes: Vec::new(),
-            peers_query_routes: Vec::new(),
-            peer_query_route: None,
-            client_query_route: None,
+            matching_pulls: RcuCell::default(),
+            valid_data_routes: AtomicBool::new(false),
+            data_routes: RcuCell::default(),
+            valid_query_routes: AtomicBool::new(false),
+            query_routes: RcuCell::default(),
         }
     }
 
-    pub(super) fn update_data_routes(&mut self, data_routes: DataRoutes) {
-        self.valid_data_routes = true;
-        if let Some(matching_pulls) = data_routes.matching_pulls {
-            self.matching_pulls = matching_pulls;
+    /// Publishes the given routes, leaving their validity unchanged.
+    pub(super) fn set_data_routes(&self, mut data_routes: DataRoutes) {
+        if let Some(matching_pulls) = data_routes.matching_pulls.take() {
+            self.matching_pulls.store(matching_pulls);
         }
-        self.routers_data_routes = data_routes.routers_data_routes;
-        self.peers_data_routes = data_routes.peers_data_routes;
-        self.peer_data_route = data_routes.peer_data_route;
-        self.client_data_route = data_routes.client_data_route;
+        self.data_routes.store(Arc::new(data_routes));
+    }
+
+    pub(super) fn update_data_routes(&self, data_routes: DataRoutes) {
+        self.set_data_routes(data_routes);
+        self.valid_data_routes.store(true, Ordering::Release);
+    }
+
+    pub(super) fn disable_data_routes(&self) {
+        self.valid_data_routes.store(false, Ordering::Release);
+    }
+
+    pub(super) fn data_routes(&self) -> Option<Arc<DataRoutes>> {
+        self.valid_data_routes
+            .load(Ordering::Acquire)
+            .then(|| self.data_routes.load())
+    }
+
+    /// Publishes the given routes, leaving their validity unchanged.
+    pub(super) fn set_query_routes(&self, query_routes: QueryRoutes) {
+        self.query_routes.store(Arc::new(query_routes));
     }
 
-    pub(super) fn update_query_routes(&mut self, query_routes: QueryRoutes) {
-        self.valid_query_routes = true;
-        self.routers_query_routes = query_routes.routers_query_routes;
-        self.peers_query_routes = query_routes.peers_query_routes;
-        self.peer_query_route = query_routes.peer_query_route;
-        self.client_query_route = query_routes.client_query_route;
+    pub(super) fn update_query_routes(&self, query_routes: QueryRoutes) {
+        self.set_query_routes(query_routes);
+        self.valid_query_routes.store(true, Ordering::Release);
+    }
+
+    pub(super) fn disable_query_routes(&self) {
+        self.valid_query_routes.store(false, Ordering::Release);
+    }
+
+    pub(super) fn query_routes(&self) -> Option<Arc<QueryRoutes>> {
+        self.valid_query_routes
+            .load(Ordering::Acquire)
+            .then(|| self.query_routes.load())
     }
 }
 
 pub struct Resource {
     pub(super) parent: Option<Arc<Resource>>,
     pub(super) suffix: String,
+    // The full key expression, computed once rather than for every routed message
+    pub(super) expr: Arc<str>,
     pub(super) nonwild_prefix: Option<(Arc<Resource>, String)>,
     pub(super) childs: HashMap<String, Arc<Resource>>,
     pub(super) context: Option<ResourceContext>,
@@ -172,6 +196,7 @@ impl Resource {
         Resource {
             parent: Some(parent.clone()),
             suffix: String::from(suffix),
+            expr: [&*parent.expr, suffix].concat().into(),
             nonwild_prefix,
             childs: HashMap::new(),
             context,
@@ -179,11 +204,9 @@ impl Resource {
         }
     }
 
-    pub fn expr(&self) -> String {
-        match &self.parent {
-            Some(parent) => parent.expr() + &self.suffix,
-            None => String::from(""),
-        }
+    #[inline(always)]
+    pub fn expr(&self) -> &str {
+        &self.expr
     }
 
     #[inline(always)]
@@ -203,7 +226,7 @@ impl Resource {
                 if !nonwild_prefix.expr().is_empty() {
                     (Some(nonwild_prefix.clone()), wildsuffix.clone())
                 } else {
-                    (None, res.expr())
+                    (None, res.expr().to_string())
                 }
             }
         }
@@ -211,125 +234,69 @@ impl Resource {
 
     #[inline(always)]
     pub fn routers_data_route(&self, context: usize) -> Option<Arc<Route>> {
-        match &self.context {
-            Some(ctx) => {
-                if ctx.valid_data_routes {
-                    (ctx.routers_data_routes.len() > context)
-                        .then(|| ctx.routers_data_routes[context].clone())
-                } else {
-                    None
-                }
-            }
-
-            None => None,
-        }
+        let routes = self.context.as_ref()?.data_routes()?;
+        routes.routers_data_routes.get(context).cloned()
     }
 
     #[inline(always)]
     pub fn peers_data_route(&self, context: usize) -> Option<Arc<Route>> {
-        match &self.context {
-            Some(ctx) => {
-                if ctx.valid_data_routes {
-                    (ctx.peers_data_routes.len() > context)
-                        .then(|| ctx.peers_data_routes[context].clone())
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
+        let routes = self.context.as_ref()?.data_routes()?;
+        routes.peers_data_routes.get(context).cloned()
     }
 
     #[inline(always)]
     pub fn peer_data_route(&self) -> Option<Arc<Route>> {
-        match &self.context {
-            Some(ctx) => {
-                if ctx.valid_data_routes {
-                    ctx.peer_data_route.clone()
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
+        self.context
+            .as_ref()?
+            .data_routes()?
+            .peer_data_route
+            .clone()
     }
 
     #[inline(always)]
     pub fn client_data_route(&self) -> Option<Arc<Route>> {
-        match &self.context {
-            Some(ctx) => {
-                if ctx.valid_data_routes {
-                    ctx.client_data_route.clone()
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
+        self.context
+            .as_ref()?
+            .data_routes()?
+            .client_data_route
+            .clone()
     }
 
     #[inline(always)]
     pub(super) fn routers_query_route(&self, context: usize) -> Option<Arc<QueryTargetQablSet>> {
-        match &self.context {
-            Some(ctx) => {
-                if ctx.valid_query_routes {
-                    (ctx.routers_query_routes.len() > context)
-                        .then(|| ctx.routers_query_routes[context].clone())
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
+        let routes = self.context.as_ref()?.query_routes()?;
+        routes.routers_query_routes.get(context).cloned()
     }
 
     #[inline(always)]
     pub(super) fn peers_query_route(&self, context: usize) -> Option<Arc<QueryTargetQablSet>> {
-        match &self.context {
-            Some(ctx) => {
-                if ctx.valid_query_routes {
-                    (ctx.peers_query_routes.len() > context)
-                        .then(|| ctx.peers_query_routes[context].clone())
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
+        let routes = self.context.as_ref()?.query_routes()?;
+        routes.peers_query_routes.get(context).cloned()
     }
 
     #[inline(always)]
     pub(super) fn peer_query_route(&self) -> Option<Arc<QueryTargetQablSet>> {
-        match &self.context {
-            Some(ctx) => {
-                if ctx.valid_query_routes {
-                    ctx.peer_query_route.clone()
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
+        self.context
+            .as_ref()?
+            .query_routes()?
+            .peer_query_route
+            .clone()
     }
 
     #[inline(always)]
     pub(super) fn client_query_route(&self) -> Option<Arc<QueryTargetQablSet>> {
-        match &self.context {
-            Some(ctx) => {
-                if ctx.valid_query_routes {
-                    ctx.client_query_route.clone()
-                } else {
-                    None
-                }
-            }
-            None => None,
-        }
+        self.context
+            .as_ref()?
+            .query_routes()?
+            .client_query_route
+            .clone()
     }
 
     pub fn root() -> Arc<Resource> {
         Arc::new(Resource {
             parent: None,
             suffix: String::from(""),
+            expr: "".into(),
             nonwild_prefix: None,
             childs: HashMap::new(),
             context: None,
@@ -364,7 +331,7 @@ impl Resource {
     }
 
     pub fn print_tree(from: &Arc<Resource>) -> String {
-        let mut result = from.expr();
+        let mut result = from.expr().to_string();
         result.push('\n');
         for child in from.childs.values() {
             result.push_str(&Resource::print_tree(child));
@@ -521,7 +488,7 @@ impl Resource {
                         ext_nodeid: ext::NodeIdType::default(),
                         body: DeclareBody::DeclareKeyExpr(DeclareKeyExpr {
                             id: expr_id,
-                            wire_expr: nonwild_prefix.expr().into(),
+                            wire_expr: nonwild_prefix.expr().to_string().into(),
                         }),
                     });
                     WireExpr {
@@ -580,7 +547,15 @@ impl Resource {
                 matches.push(Arc::downgrade(from));
             }
             for child in from.childs.values() {
-                recursive_push(child, matches)
+                // `**` doesn't match the verbatim chunks
+                if !child
+                    .suffix
+                    .strip_prefix('/')
+                    .unwrap_or(&child.suffix)
+                    .starts_with('@')
+                {
+                    recursive_push(child, matches)
+                }
             }
         }
         fn get_matches_from(
@@ -694,7 +669,7 @@ pub fn register_expr(
     {
         Some(mut prefix) => match face.remote_mappings.get(&expr_id) {
             Some(res) => {
-                let mut fullexpr = prefix.expr();
+                let mut fullexpr = prefix.expr().to_string();
                 fullexpr.push_str(expr.suffix.as_ref());
                 if res.expr() != fullexpr {
                     log::error!("Resource {} remapped. Remapping unsupported!", expr_id);
@@ -711,7 +686,7 @@ pub fn register_expr(
                     let wtables = zwrite!(tables.tables);
                     (res.unwrap(), wtables)
                 } else {
-                    let mut fullexpr = prefix.expr();
+                    let mut fullexpr = prefix.expr().to_string();
                     fullexpr.push_str(expr.suffix.as_ref());
                     let mut matches = keyexpr::new(fullexpr.as_str())
                         .map(|ke| Resource::get_matches(&rtables, ke))
@@ -11,10 +11,17 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::acl::AccessControl;
+#[cfg(feature = "transport_multicast")]
+use super::dissemination::{dissemination_leave, DisseminationGroup};
 use super::face::{Face, FaceState};
+use super::measurements::KeyExprMeasurements;
+use super::namespace::{NamespacePrimitives, Namespaces};
 use super::network::{shared_nodes, Network};
 pub use super::pubsub::*;
+use super::qos_overrides::QosOverrides;
 pub use super::queries::*;
+use super::quotas::Quotas;
 pub use super::resource::*;
 use super::runtime::Runtime;
 use crate::net::codec::Zenoh080Routing;
@@ -24,22 +31,23 @@ use std::any::Any;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hasher;
+#[cfg(feature = "transport_multicast")]
 use std::str::FromStr;
 use std::sync::{Arc, Weak};
 use std::sync::{Mutex, RwLock};
 use std::time::Duration;
 use uhlc::HLC;
+use zenoh_config::{AclConf, NamespaceRule, QosOverride, QuotasConf};
 use zenoh_link::Link;
 use zenoh_protocol::common::ZExtBody;
-use zenoh_protocol::core::{ExprId, WhatAmI, WhatAmIMatcher, ZenohId};
-use zenoh_protocol::network::oam::id::OAM_LINKSTATE;
+use zenoh_protocol::core::{key_expr::OwnedKeyExpr, ExprId, WhatAmI, WhatAmIMatcher, ZenohId};
+use zenoh_protocol::network::oam::id::{OAM_LINKSTATE, OAM_RELAY};
 use zenoh_protocol::network::{Mapping, NetworkBody, NetworkMessage};
 #[cfg(feature = "stats")]
 use zenoh_transport::stats::TransportStats;
-use zenoh_transport::{
-    DeMux, DummyPrimitives, McastMux, Mux, Primitives, TransportMulticast, TransportPeer,
-    TransportPeerEventHandler, TransportUnicast,
-};
+use zenoh_transport::{DeMux, Mux, Primitives, TransportPeerEventHandler, TransportUnicast};
+#[cfg(feature = "transport_multicast")]
+use zenoh_transport::{DummyPrimitives, McastMux, TransportMulticast, TransportPeer};
 // use zenoh_collections::Timer;
 use zenoh_core::zconfigurable;
 use zenoh_result::ZResult;
@@ -67,8 +75,15 @@ impl<'a> RoutingExpr<'a> {
 
     #[inline]
     pub(crate) fn full_expr(&mut self) -> &str {
+        // Only allocate when the key expression is split between the prefix and the suffix
+        if self.suffix.is_empty() {
+            return self.prefix.expr();
+        }
+        if self.prefix.expr().is_empty() {
+            return self.suffix;
+        }
         if self.full.is_none() {
-            self.full = Some(self.prefix.expr() + self.suffix);
+            self.full = Some([self.prefix.expr(), self.suffix].concat());
         }
         self.full.as_ref().unwrap()
     }
@@ -98,6 +113,17 @@ pub struct Tables {
     pub(crate) shared_nodes: Vec<ZenohId>,
     pub(crate) routers_trees_task: Option<JoinHandle<()>>,
     pub(crate) peers_trees_task: Option<JoinHandle<()>>,
+    pub(crate) measurements: Option<KeyExprMeasurements>,
+    pub(crate) trace_hops: bool,
+    pub(crate) relay: bool,
+    // The peers that marked this router as their relay
+    pub(crate) relayed: HashSet<ZenohId>,
+    #[cfg(feature = "transport_multicast")]
+    pub(crate) dissemination: Option<DisseminationGroup>,
+    pub(crate) acl: Option<AccessControl>,
+    pub(crate) quotas: Option<Quotas>,
+    pub(crate) qos_overrides: Option<QosOverrides>,
+    pub(crate) namespaces: Option<Namespaces>,
 }
 
 impl Tables {
@@ -132,6 +158,16 @@ impl Tables {
             shared_nodes: vec![],
             routers_trees_task: None,
             peers_trees_task: None,
+            measurements: None,
+            trace_hops: false,
+            relay: false,
+            relayed: HashSet::new(),
+            #[cfg(feature = "transport_multicast")]
+            dissemination: None,
+            acl: None,
+            quotas: None,
+            qos_overrides: None,
+            namespaces: None,
         }
     }
 
@@ -244,14 +280,28 @@ impl Tables {
         !source_links.is_empty() && !source_links.contains(&dest)
     }
 
+    #[inline]
+    pub(crate) fn brokering_to(
+        &self,
+        peer1: ZenohId,
+        peer1_links: &[ZenohId],
+        peer2: ZenohId,
+    ) -> bool {
+        (self.router_peers_failover_brokering
+            && self.peers_net.is_some()
+            && Tables::failover_brokering_to(peer1_links, peer2))
+            || ((self.relayed.contains(&peer1) || self.relayed.contains(&peer2))
+                && !peer1_links.contains(&peer2))
+    }
+
     #[inline]
     pub(crate) fn failover_brokering(&self, peer1: ZenohId, peer2: ZenohId) -> bool {
-        self.router_peers_failover_brokering
-            && self
-                .peers_net
-                .as_ref()
-                .map(|net| Tables::failover_brokering_to(net.get_links(peer1), peer2))
-                .unwrap_or(false)
+        let links = self
+            .peers_net
+            .as_ref()
+            .map(|net| net.get_links(peer1))
+            .unwrap_or_default();
+        self.brokering_to(peer1, links, peer2)
     }
 
     fn open_net_face(
@@ -259,11 +309,20 @@ impl Tables {
         zid: ZenohId,
         whatami: WhatAmI,
         #[cfg(feature = "stats")] stats: Arc<TransportStats>,
-        primitives: Arc<dyn Primitives + Send + Sync>,
+        mut primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
+        identities: Vec<String>,
     ) -> Weak<FaceState> {
         let fid = self.face_counter;
         self.face_counter += 1;
+        let namespace = match &self.namespaces {
+            Some(namespaces) if whatami == WhatAmI::Client => namespaces.namespace(&identities),
+            _ => None,
+        };
+        if let Some(namespace) = &namespace {
+            log::debug!("Client {} in namespace {}", zid, namespace.prefix());
+            primitives = Arc::new(NamespacePrimitives::new(namespace.clone(), primitives));
+        }
         let mut newface = self
             .faces
             .entry(fid)
@@ -276,7 +335,10 @@ impl Tables {
                     Some(stats),
                     primitives.clone(),
                     link_id,
+                    #[cfg(feature = "transport_multicast")]
                     None,
+                    Some(identities),
+                    namespace,
                 )
             })
             .clone();
@@ -308,6 +370,9 @@ impl Tables {
                     None,
                     primitives.clone(),
                     0,
+                    #[cfg(feature = "transport_multicast")]
+                    None,
+                    None,
                     None,
                 )
             })
@@ -363,6 +428,18 @@ impl Tables {
                 pubsub_tree_change(&mut tables, &new_childs, net_type);
                 queries_tree_change(&mut tables, &new_childs, net_type);
 
+                let net = match net_type {
+                    WhatAmI::Router => tables.routers_net.as_ref().unwrap(),
+                    _ => tables.peers_net.as_ref().unwrap(),
+                };
+                net.runtime.events.record(
+                    "routes/change",
+                    serde_json::json!({
+                        "network": net.name,
+                        "nodes": net.graph.node_weights().map(|node| node.zid.to_string()).collect::<Vec<_>>(),
+                    }),
+                );
+
                 log::trace!("Computations completed");
                 match net_type {
                     WhatAmI::Router => tables.routers_trees_task = None,
@@ -405,15 +482,13 @@ pub fn close_face(tables: &TablesLock, face: &Weak<FaceState>) {
 
                 if res.context.is_some() {
                     for match_ in &res.context().matches {
-                        let mut match_ = match_.upgrade().unwrap();
+                        let match_ = match_.upgrade().unwrap();
                         if !Arc::ptr_eq(&match_, &res) {
-                            get_mut_unchecked(&mut match_)
-                                .context_mut()
-                                .valid_data_routes = false;
+                            match_.context().disable_data_routes();
                             subs_matches.push(match_);
                         }
                     }
-                    get_mut_unchecked(&mut res).context_mut().valid_data_routes = false;
+                    res.context().disable_data_routes();
                     subs_matches.push(res);
                 }
             }
@@ -425,15 +500,13 @@ pub fn close_face(tables: &TablesLock, face: &Weak<FaceState>) {
 
                 if res.context.is_some() {
                     for match_ in &res.context().matches {
-                        let mut match_ = match_.upgrade().unwrap();
+                        let match_ = match_.upgrade().unwrap();
                         if !Arc::ptr_eq(&match_, &res) {
-                            get_mut_unchecked(&mut match_)
-                                .context_mut()
-                                .valid_query_routes = false;
+                            match_.context().disable_query_routes();
                             qabls_matches.push(match_);
                         }
                     }
-                    get_mut_unchecked(&mut res).context_mut().valid_query_routes = false;
+                    res.context().disable_query_routes();
                     qabls_matches.push(res);
                 }
             }
@@ -484,6 +557,7 @@ pub struct Router {
 }
 
 impl Router {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         zid: ZenohId,
         whatami: WhatAmI,
@@ -491,18 +565,33 @@ impl Router {
         drop_future_timestamp: bool,
         router_peers_failover_brokering: bool,
         queries_default_timeout: Duration,
+        measured_key_exprs: Vec<OwnedKeyExpr>,
+        trace_hops: bool,
+        relay: bool,
+        access_control: &AclConf,
+        quotas: &QuotasConf,
+        qos_overrides: &[QosOverride],
+        namespaces: &[NamespaceRule],
     ) -> Self {
+        let mut tables = Tables::new(
+            zid,
+            whatami,
+            hlc,
+            drop_future_timestamp,
+            router_peers_failover_brokering,
+            queries_default_timeout,
+        );
+        tables.measurements = KeyExprMeasurements::new(measured_key_exprs);
+        tables.trace_hops = trace_hops && whatami == WhatAmI::Router;
+        tables.relay = relay && whatami == WhatAmI::Router;
+        tables.acl = AccessControl::new(access_control);
+        tables.quotas = Quotas::new(quotas);
+        tables.qos_overrides = QosOverrides::new(qos_overrides);
+        tables.namespaces = Namespaces::new(namespaces);
         Router {
             whatami,
             tables: Arc::new(TablesLock {
-                tables: RwLock::new(Tables::new(
-                    zid,
-                    whatami,
-                    hlc,
-                    drop_future_timestamp,
-                    router_peers_failover_brokering,
-                    queries_default_timeout,
-                )),
+                tables: RwLock::new(tables),
                 ctrl_lock: Mutex::new(()),
                 queries_lock: RwLock::new(()),
             }),
@@ -604,6 +693,15 @@ impl Router {
             );
         }
 
+        #[allow(unused_mut)]
+        let mut identities = vec![format!("zid:{}", transport.get_zid()?)];
+        #[cfg(any(
+            feature = "auth_pubkey",
+            feature = "auth_usrpwd",
+            feature = "auth_token"
+        ))]
+        identities.extend(transport.get_auth_ids()?.iter().map(|id| id.to_string()));
+
         let handler = Arc::new(LinkStateInterceptor::new(
             transport.clone(),
             self.tables.clone(),
@@ -617,6 +715,7 @@ impl Router {
                         transport.get_stats().unwrap(),
                         Arc::new(Mux::new(transport)),
                         link_id,
+                        identities,
                     )
                     .upgrade()
                     .unwrap(),
@@ -641,8 +740,19 @@ impl Router {
         Ok(handler)
     }
 
+    #[cfg(feature = "transport_multicast")]
     pub fn new_transport_multicast(&self, transport: TransportMulticast) -> ZResult<()> {
         let mut tables = zwrite!(self.tables.tables);
+        let whatami = tables.whatami;
+        if let Some(dissemination) = tables.dissemination.as_mut() {
+            if dissemination.is_group(&transport.get_link()?) {
+                // The samples are only published on the group by the router
+                if whatami == WhatAmI::Router {
+                    dissemination.set_transport(transport);
+                }
+                return Ok(());
+            }
+        }
         let fid = tables.face_counter;
         tables.face_counter += 1;
         tables.mcast_groups.push(FaceState::new(
@@ -654,6 +764,8 @@ impl Router {
             Arc::new(McastMux::new(transport.clone())),
             0,
             Some(transport),
+            None,
+            None,
         ));
 
         // recompute routes
@@ -662,6 +774,7 @@ impl Router {
         Ok(())
     }
 
+    #[cfg(feature = "transport_multicast")]
     pub fn new_peer_multicast(
         &self,
         transport: TransportMulticast,
@@ -679,6 +792,8 @@ impl Router {
             Arc::new(DummyPrimitives),
             0,
             Some(transport),
+            Some(vec![format!("zid:{}", peer.zid)]),
+            None,
         );
         tables.mcast_faces.push(face_state.clone());
 
@@ -812,6 +927,21 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
                             drop(ctrl_lock);
                         }
                     }
+                } else if oam.id == OAM_RELAY {
+                    if let Ok(zid) = self.transport.get_zid() {
+                        let ctrl_lock = zlock!(self.tables.ctrl_lock);
+                        let mut tables = zwrite!(self.tables.tables);
+                        if tables.relay
+                            && self.transport.get_whatami()? == WhatAmI::Peer
+                            && tables.relayed.insert(zid)
+                        {
+                            log::debug!("Relay traffic of peer {}", zid);
+                            pubsub_relay_change(&mut tables);
+                            queries_relay_change(&mut tables);
+                        }
+                        drop(tables);
+                        drop(ctrl_lock);
+                    }
                 }
 
                 Ok(())
@@ -831,6 +961,9 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
             (Ok(zid), Ok(whatami)) => {
                 let ctrl_lock = zlock!(tables_ref.ctrl_lock);
                 let mut tables = zwrite!(tables_ref.tables);
+                tables.relayed.remove(&zid);
+                #[cfg(feature = "transport_multicast")]
+                dissemination_leave(&mut tables, &self.face.state);
                 match (tables.whatami, whatami) {
                     (WhatAmI::Router, WhatAmI::Router) => {
                         for (_, removed_node) in
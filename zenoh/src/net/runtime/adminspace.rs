@@ -11,10 +11,11 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 use super::routing::face::Face;
-use super::Runtime;
+use super::routing::resource::Resource;
+use super::{applies_at_runtime, Runtime};
 use crate::key_expr::KeyExpr;
 use crate::plugins::sealed as plugins;
-use crate::prelude::sync::{Sample, SyncResolve};
+use crate::prelude::sync::{Priority, Sample, SyncResolve};
 use crate::queryable::Query;
 use crate::queryable::QueryInner;
 use crate::value::Value;
@@ -24,12 +25,17 @@ use serde_json::json;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::convert::TryInto;
+use std::sync::atomic::AtomicU8;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 use zenoh_buffers::SplitBuffer;
-use zenoh_config::ValidatedMap;
+use zenoh_config::{PluginPanicPolicy, ValidatedMap, ValidationFunction};
+use zenoh_plugin_trait::prelude::catch_panic;
 use zenoh_protocol::{
-    core::{key_expr::OwnedKeyExpr, ExprId, KnownEncoding, WireExpr, ZenohId, EMPTY_EXPR_ID},
+    core::{
+        key_expr::OwnedKeyExpr, ExprId, KnownEncoding, WhatAmI, WireExpr, ZenohId, EMPTY_EXPR_ID,
+    },
     network::{
         declare::{queryable::ext::QueryableInfo, subscriber::ext::SubscriberInfo},
         ext, Declare, DeclareBody, DeclareQueryable, DeclareSubscriber, Push, Request, Response,
@@ -37,19 +43,217 @@ use zenoh_protocol::{
     },
     zenoh::{PushBody, RequestBody},
 };
-use zenoh_result::ZResult;
+use zenoh_result::{bail, zerror, ZResult};
 use zenoh_transport::{Primitives, TransportUnicast};
 
+/// The delay before the first restart of a failed plugin, doubled with each of its restarts.
+const PLUGIN_RESTART_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// The maximum delay before restarting a failed plugin.
+const PLUGIN_RESTART_MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// The period at which running plugins are checked for unexpected terminations.
+const PLUGIN_SUPERVISION_PERIOD: Duration = Duration::from_secs(1);
+
+/// A failure of a running plugin, handled by the plugins' supervisor.
+#[derive(Debug)]
+enum PluginFailure {
+    /// A call into the plugin panicked: its `__on_panic__` policy applies.
+    Panicked(String),
+    /// The plugin's task terminated unexpectedly: it gets restarted.
+    Terminated(String),
+}
+
 pub struct AdminContext {
     runtime: Runtime,
     plugins_mgr: Mutex<plugins::PluginsManager>,
     zid_str: String,
     version: String,
     metadata: serde_json::Value,
+    /// Reports of plugins' failures, handled by the plugins' supervisor.
+    plugin_failures: flume::Sender<(String, PluginFailure)>,
 }
 
 type Handler = Arc<dyn Fn(&AdminContext, Query) + Send + Sync>;
 
+impl AdminContext {
+    /// Stops all the running plugins, in the reverse order of their starting, so that they can flush their state
+    /// (e.g. the storages) before the runtime gets closed.
+    pub fn stop_plugins(&self) {
+        zlock!(self.plugins_mgr).stop_all();
+    }
+
+    /// Reports that `plugin` panicked, so that its `__on_panic__` policy gets applied.
+    fn report_plugin_panic(&self, plugin: &str, message: String) {
+        log::error!("Plugin `{}` {}", plugin, message);
+        let _ = self
+            .plugin_failures
+            .send((plugin.into(), PluginFailure::Panicked(message)));
+    }
+
+    /// Registers the config validator of the running `plugin`, guarded so that its panics are reported
+    /// rather than unwinding through the configuration.
+    fn add_plugin_validator(&self, name: &str, plugin: &plugins::RunningPlugin) {
+        let validator = match catch_panic(|| plugin.config_checker()) {
+            Ok(validator) => validator,
+            Err(e) => {
+                self.report_plugin_panic(
+                    name,
+                    format!("panicked while building its config validator: {e}"),
+                );
+                return;
+            }
+        };
+        let plugin_failures = self.plugin_failures.clone();
+        let plugin = name.to_string();
+        let guarded: ValidationFunction = Arc::new(move |path, current, new| {
+            match catch_panic(|| validator(path, current, new)) {
+                Ok(result) => result,
+                Err(e) => {
+                    let message = format!("panicked while validating its configuration: {e}");
+                    log::error!("Plugin `{}` {}", plugin, message);
+                    let _ =
+                        plugin_failures.send((plugin.clone(), PluginFailure::Panicked(message)));
+                    bail!(
+                        "Plugin `{}` panicked while validating its configuration",
+                        plugin
+                    )
+                }
+            }
+        });
+        self.runtime
+            .config
+            .lock()
+            .add_plugin_validator(name, guarded);
+    }
+
+    /// Reports the running plugins whose task terminated unexpectedly.
+    fn check_plugins(&self) {
+        let plugins_mgr = zlock!(self.plugins_mgr);
+        for (name, (_, plugin)) in plugins_mgr.running_plugins() {
+            match catch_panic(|| plugin.terminated()) {
+                Ok(None) => {}
+                Ok(Some(reason)) => {
+                    log::error!("Plugin `{}` terminated unexpectedly: {}", name, reason);
+                    let _ = self
+                        .plugin_failures
+                        .send((name.into(), PluginFailure::Terminated(reason)));
+                }
+                Err(e) => self.report_plugin_panic(
+                    name,
+                    format!("panicked while checking its termination: {e}"),
+                ),
+            }
+        }
+    }
+
+    /// Handles the `failure` of `plugin`: the plugin is either disabled, restarted after a backoff delay
+    /// (as long as it wasn't restarted `__max_restarts__` times already), or `zenohd` is aborted.
+    fn handle_plugin_failure(self: &Arc<Self>, plugin: &str, failure: PluginFailure) {
+        let request = match self
+            .runtime
+            .config
+            .lock()
+            .plugins()
+            .load_requests()
+            .flatten()
+            .find(|request| request.name == plugin)
+        {
+            Some(request) => request,
+            None => return,
+        };
+        let mut plugins_mgr = zlock!(self.plugins_mgr);
+        // The plugin may have failed several times before being stopped
+        if plugins_mgr.plugin(plugin).is_none() {
+            return;
+        }
+        let reason = match failure {
+            PluginFailure::Panicked(message) => match request.on_panic {
+                PluginPanicPolicy::Abort => {
+                    log::error!("Plugin `{}` panicked: {}. Aborting...", plugin, message);
+                    std::process::abort();
+                }
+                PluginPanicPolicy::Disable => {
+                    self.runtime.config.lock().remove_plugin_validator(plugin);
+                    plugins_mgr.stop(plugin);
+                    let reason = format!("Plugin panicked: {message}");
+                    self.runtime.events.record(
+                        "plugin/disable",
+                        json!({ "plugin": plugin, "reason": &reason }),
+                    );
+                    plugins_mgr.mark_failed(plugin, reason);
+                    log::warn!("Disabled plugin `{}` after it panicked", plugin);
+                    return;
+                }
+                PluginPanicPolicy::Restart => format!("Plugin panicked: {message}"),
+            },
+            PluginFailure::Terminated(reason) => format!("Plugin terminated: {reason}"),
+        };
+        self.runtime.config.lock().remove_plugin_validator(plugin);
+        let restarts = plugins_mgr.restarts(plugin).len();
+        if restarts >= request.max_restarts as usize {
+            plugins_mgr.stop(plugin);
+            log::error!(
+                "Disabled plugin `{}`, which failed again after {} restarts: {}",
+                plugin,
+                restarts,
+                reason
+            );
+            let reason = format!("{reason} (gave up after {restarts} restarts)");
+            self.runtime.events.record(
+                "plugin/disable",
+                json!({ "plugin": plugin, "reason": &reason }),
+            );
+            plugins_mgr.mark_failed(plugin, reason);
+            return;
+        }
+        let restarts = plugins_mgr.record_restart(plugin, reason.clone());
+        plugins_mgr.stop_running(plugin);
+        let backoff = PLUGIN_RESTART_INITIAL_BACKOFF
+            .saturating_mul(2u32.saturating_pow(restarts as u32 - 1))
+            .min(PLUGIN_RESTART_MAX_BACKOFF);
+        self.runtime.events.record(
+            "plugin/restart",
+            json!({
+                "plugin": plugin,
+                "reason": &reason,
+                "restart": restarts,
+                "backoff": humantime::format_duration(backoff).to_string(),
+            }),
+        );
+        plugins_mgr.mark_failed(plugin, reason);
+        log::warn!(
+            "Restarting plugin `{}` in {:?} (restart {}/{})",
+            plugin,
+            backoff,
+            restarts,
+            request.max_restarts
+        );
+        let context = self.clone();
+        let plugin = plugin.to_string();
+        task::spawn(async move {
+            task::sleep(backoff).await;
+            context.restart_plugin(&plugin);
+        });
+    }
+    /// Starts again `plugin`, which was stopped after failing, unless it was removed from the configuration meanwhile.
+    fn restart_plugin(&self, plugin: &str) {
+        if self.runtime.config.lock().plugin(plugin).is_none() {
+            return;
+        }
+        let mut plugins_mgr = zlock!(self.plugins_mgr);
+        match plugins_mgr.start(plugin, &self.runtime) {
+            Ok(Some((path, running))) => {
+                self.add_plugin_validator(plugin, running);
+                log::info!("Successfully restarted plugin `{}` from {}", plugin, path);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::error!("Failed to restart plugin `{}`: {}", plugin, e);
+                plugins_mgr.mark_failed(plugin, e.to_string());
+            }
+        }
+    }
+}
+
 pub struct AdminSpace {
     zid: ZenohId,
     primitives: Mutex<Option<Arc<Face>>>,
@@ -65,7 +269,12 @@ enum PluginDiff {
 }
 
 impl AdminSpace {
-    pub async fn start(runtime: &Runtime, plugins_mgr: plugins::PluginsManager, version: String) {
+    /// Starts the admin space of `runtime`, returning its context through which the plugins can be stopped on shutdown.
+    pub async fn start(
+        runtime: &Runtime,
+        plugins_mgr: plugins::PluginsManager,
+        version: String,
+    ) -> Arc<AdminContext> {
         let zid_str = runtime.zid.to_string();
         let metadata = runtime.metadata.clone();
         let root_key: OwnedKeyExpr = format!("@/router/{zid_str}").try_into().unwrap();
@@ -88,6 +297,39 @@ impl AdminSpace {
                 .unwrap(),
             Arc::new(peers_linkstate_data),
         );
+        handlers.insert(
+            format!("@/router/{zid_str}/health").try_into().unwrap(),
+            Arc::new(health_data),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/events").try_into().unwrap(),
+            Arc::new(events_data),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/measurements")
+                .try_into()
+                .unwrap(),
+            Arc::new(measurements_data),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/quotas").try_into().unwrap(),
+            Arc::new(quotas_data),
+        );
+        #[cfg(feature = "fault_injection")]
+        handlers.insert(
+            format!("@/router/{zid_str}/faults").try_into().unwrap(),
+            Arc::new(faults_data),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/sessions/**")
+                .try_into()
+                .unwrap(),
+            Arc::new(sessions_data),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/clients/**").try_into().unwrap(),
+            Arc::new(clients_data),
+        );
         handlers.insert(
             format!("@/router/{zid_str}/subscriber/**")
                 .try_into()
@@ -100,6 +342,24 @@ impl AdminSpace {
                 .unwrap(),
             Arc::new(queryables_data),
         );
+        handlers.insert(
+            format!("@/router/{zid_str}/status/config/history/*")
+                .try_into()
+                .unwrap(),
+            Arc::new(config_history_data),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/status/config/effective")
+                .try_into()
+                .unwrap(),
+            Arc::new(config_effective_data),
+        );
+        handlers.insert(
+            format!("@/router/{zid_str}/status/config/diff")
+                .try_into()
+                .unwrap(),
+            Arc::new(config_diff_data),
+        );
         handlers.insert(
             format!("@/router/{zid_str}/status/plugins/**")
                 .try_into()
@@ -107,18 +367,37 @@ impl AdminSpace {
             Arc::new(plugins_status),
         );
 
-        let mut active_plugins = plugins_mgr
-            .running_plugins_info()
-            .into_iter()
-            .map(|(a, b)| (a.to_string(), b.to_string()))
-            .collect::<HashMap<_, _>>();
-
+        let (plugin_failures, plugin_failures_rx) = flume::unbounded();
         let context = Arc::new(AdminContext {
             runtime: runtime.clone(),
             plugins_mgr: Mutex::new(plugins_mgr),
             zid_str,
             version,
             metadata,
+            plugin_failures,
+        });
+        {
+            let plugins_mgr = zlock!(context.plugins_mgr);
+            for (name, (_, plugin)) in plugins_mgr.running_plugins() {
+                context.add_plugin_validator(name, plugin);
+            }
+        }
+        task::spawn({
+            let context = context.clone();
+            async move {
+                while let Ok((plugin, failure)) = plugin_failures_rx.recv_async().await {
+                    context.handle_plugin_failure(&plugin, failure);
+                }
+            }
+        });
+        task::spawn({
+            let context = context.clone();
+            async move {
+                loop {
+                    task::sleep(PLUGIN_SUPERVISION_PERIOD).await;
+                    context.check_plugins();
+                }
+            }
         });
         let admin = Arc::new(AdminSpace {
             zid: runtime.zid,
@@ -138,18 +417,43 @@ impl AdminSpace {
                         continue;
                     }
 
-                    let requested_plugins = {
+                    // Plugins are started after their dependencies, and stopped before them
+                    let (requested_plugins, unsatisfied_plugins) = {
                         let cfg_guard = admin.context.runtime.config.lock();
-                        cfg_guard.plugins().load_requests().collect::<Vec<_>>()
+                        match cfg_guard.plugins().ordered_load_requests() {
+                            Ok(requests) => requests,
+                            Err(e) => {
+                                log::error!("{}", e);
+                                continue;
+                            }
+                        }
+                    };
+                    // Plugins may also have been stopped after panicking, so the manager is the source of truth
+                    let (active_plugins, failed_plugins) = {
+                        let plugins_mgr = zlock!(admin.context.plugins_mgr);
+                        let active_plugins = plugins_mgr
+                            .running_plugins()
+                            .map(|(name, (path, _))| (name.to_string(), path.to_string()))
+                            .collect::<Vec<_>>();
+                        let failed_plugins = plugins_mgr
+                            .failed_plugins()
+                            .into_keys()
+                            .map(String::from)
+                            .collect::<Vec<_>>();
+                        (active_plugins, failed_plugins)
                     };
                     let mut diffs = Vec::new();
-                    for plugin in active_plugins.keys() {
+                    let active_names = active_plugins.iter().rev().map(|(name, _)| name);
+                    for plugin in active_names.chain(failed_plugins.iter()) {
                         if !requested_plugins.iter().any(|r| &r.name == plugin) {
                             diffs.push(PluginDiff::Delete(plugin.clone()))
                         }
                     }
                     for request in requested_plugins {
-                        if let Some(active) = active_plugins.get(&request.name) {
+                        let active = active_plugins
+                            .iter()
+                            .find(|(name, _)| name == &request.name);
+                        if let Some((_, active)) = active {
                             if request
                                 .paths
                                 .as_ref()
@@ -166,16 +470,52 @@ impl AdminSpace {
                     for diff in diffs {
                         match diff {
                             PluginDiff::Delete(plugin) => {
-                                active_plugins.remove(plugin.as_str());
-                                plugins_mgr.stop(&plugin);
+                                plugins_mgr.clear_restarts(&plugin);
+                                // The validator's code may belong to the library being unloaded
+                                admin
+                                    .context
+                                    .runtime
+                                    .config
+                                    .lock()
+                                    .remove_plugin_validator(&plugin);
+                                if plugins_mgr.stop(&plugin) {
+                                    log::info!("Stopped plugin `{}`", plugin);
+                                    admin.context.runtime.audit.record(
+                                        &[],
+                                        "plugin/unload",
+                                        json!({ "plugin": plugin }),
+                                        &ZResult::Ok(()),
+                                    );
+                                }
                             }
                             PluginDiff::Start(plugin) => {
-                                let load = match &plugin.paths {
-                                    Some(paths) => {
-                                        plugins_mgr.load_plugin_by_paths(plugin.name.clone(), paths)
-                                    }
-                                    None => plugins_mgr.load_plugin_by_name(plugin.name.clone()),
-                                };
+                                let missing = plugin
+                                    .dependencies
+                                    .iter()
+                                    .find(|d| plugins_mgr.plugin(d).is_none());
+                                if let Some(dependency) = missing {
+                                    log::error!(
+                                        "Plugin `{}` wasn't started, as its dependency `{}` isn't running",
+                                        plugin.name,
+                                        dependency
+                                    );
+                                    plugins_mgr.mark_failed(
+                                        &plugin.name,
+                                        format!("Dependency `{dependency}` isn't running"),
+                                    );
+                                    continue;
+                                }
+                                let load = plugins::load_plugin(
+                                    &mut plugins_mgr,
+                                    &plugin.name,
+                                    plugin.paths.as_deref(),
+                                );
+                                admin.context.runtime.audit.record(
+                                    &[],
+                                    "plugin/load",
+                                    json!({ "plugin": plugin.name }),
+                                    &load.as_ref().map(|_| ()),
+                                );
                                 match load {
                                     Err(e) => {
                                         if plugin.required {
@@ -185,7 +525,8 @@ impl AdminSpace {
                                                 "Failed to load plugin `{}`: {}",
                                                 plugin.name,
                                                 e
-                                            )
+                                            );
+                                            plugins_mgr.mark_failed(&plugin.name, e.to_string());
                                         }
                                     }
                                     Ok(path) => {
@@ -193,13 +534,7 @@ impl AdminSpace {
                                         log::info!("Loaded plugin `{}` from {}", name, &path);
                                         match plugins_mgr.start(name, &admin.context.runtime) {
                                             Ok(Some((path, plugin))) => {
-                                                active_plugins.insert(name.into(), path.into());
-                                                let mut cfg_guard =
-                                                    admin.context.runtime.config.lock();
-                                                cfg_guard.add_plugin_validator(
-                                                    name,
-                                                    plugin.config_checker(),
-                                                );
+                                                admin.context.add_plugin_validator(name, plugin);
                                                 log::info!(
                                                     "Successfully started plugin `{}` from {}",
                                                     name,
@@ -209,14 +544,21 @@ impl AdminSpace {
                                             Ok(None) => {
                                                 log::warn!("Plugin `{}` was already running", name)
                                             }
-                                            Err(e) => log::error!("{}", e),
+                                            Err(e) => {
+                                                log::error!("{}", e);
+                                                plugins_mgr.mark_failed(name, e.to_string());
+                                            }
                                         }
                                     }
                                 }
                             }
                         }
                     }
-                    log::info!("Running plugins: {:?}", &active_plugins)
+                    for (plugin, e) in unsatisfied_plugins {
+                        log::error!("Plugin `{}` {}", plugin.name, e);
+                        plugins_mgr.mark_failed(&plugin.name, format!("Plugin {e}"));
+                    }
+                    log::info!("Running plugins: {:?}", plugins_mgr.running_plugins_info())
                 }
             }
         });
@@ -238,16 +580,197 @@ impl AdminSpace {
             }),
         });
 
-        primitives.send_declare(Declare {
-            ext_qos: ext::QoSType::declare_default(),
-            ext_tstamp: None,
-            ext_nodeid: ext::NodeIdType::default(),
-            body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
-                id: 0, // TODO
-                wire_expr: [&root_key, "/config/**"].concat().into(),
-                ext_info: SubscriberInfo::default(),
-            }),
+        // Each subscriber of the admin space gets its own id
+        let subscribers = ["/config/**", "/operations/**", "/status/plugins/**"];
+        for (id, suffix) in (0..).zip(subscribers) {
+            primitives.send_declare(Declare {
+                ext_qos: ext::QoSType::declare_default(),
+                ext_tstamp: None,
+                ext_nodeid: ext::NodeIdType::default(),
+                body: DeclareBody::DeclareSubscriber(DeclareSubscriber {
+                    id,
+                    wire_expr: [&root_key, suffix].concat().into(),
+                    ext_info: SubscriberInfo::default(),
+                    ext_filter: None,
+                }),
+            });
+        }
+
+        admin.context.clone()
+    }
+
+    fn operation(&self, operation: &str, payload: &[u8]) -> ZResult<()> {
+        match operation {
+            "config/rollback" => {
+                let config = &self.context.runtime.config;
+                // An empty payload rolls back to the previous configuration
+                let version = match std::str::from_utf8(payload).map(str::trim) {
+                    Ok("") => config.history().last().map(|(v, _)| *v),
+                    Ok(version) => version.parse().ok(),
+                    Err(_) => None,
+                };
+                match version {
+                    Some(version) => {
+                        config
+                            .rollback(version)
+                            .map_err(|e| zerror!("Error rolling back configuration: {}", e))?;
+                        log::info!("Rolled back configuration to version {}", version);
+                        Ok(())
+                    }
+                    None => bail!(
+                        "Invalid configuration version {:?} for rollback",
+                        String::from_utf8_lossy(payload)
+                    ),
+                }
+            }
+            "log/level" => match std::str::from_utf8(payload).map(|s| s.trim().parse()) {
+                Ok(Ok(level)) => {
+                    log::set_max_level(level);
+                    log::info!("Set log level to {}", level);
+                    Ok(())
+                }
+                _ => bail!("Invalid log level {:?}", String::from_utf8_lossy(payload)),
+            },
+            #[cfg(any(
+                feature = "auth_pubkey",
+                feature = "auth_usrpwd",
+                feature = "auth_token"
+            ))]
+            "auth/reload" => {
+                // Re-reads the files referred to by the configuration, which may have changed
+                let config = self.context.runtime.config.lock().clone();
+                task::block_on(self.context.runtime.manager().reload_auth(&config))
+            }
+            #[cfg(feature = "fault_injection")]
+            "faults" => {
+                // An empty payload stops injecting faults
+                let profile = match std::str::from_utf8(payload).map(str::trim) {
+                    Ok("") => None,
+                    _ => Some(
+                        serde_json::from_slice(payload)
+                            .map_err(|e| zerror!("Invalid fault profile: {}", e))?,
+                    ),
+                };
+                match &profile {
+                    Some(profile) => log::warn!("Injecting faults: {:?}", profile),
+                    None => log::info!("Stopped injecting faults"),
+                }
+                self.context.runtime.manager().set_fault_profile(profile);
+                Ok(())
+            }
+            "plugins/load" => self
+                .load_plugin(payload)
+                .map_err(|e| zerror!("Error loading plugin: {}", e).into()),
+            "plugins/unload" => self
+                .unload_plugin(payload)
+                .map_err(|e| zerror!("Error unloading plugin: {}", e).into()),
+            _ => match operation
+                .strip_prefix("sessions/")
+                .and_then(|operation| operation.strip_suffix("/close"))
+            {
+                Some(zid) => self
+                    .close_session(zid)
+                    .map_err(|e| zerror!("Error closing session with {}: {}", zid, e).into()),
+                None => bail!("Unknown adminspace operation: {}", operation),
+            },
+        }
+    }
+
+    /// Returns the identities of the node a write was received from, given the source info set by the router:
+    /// its zid and, if it's directly connected, the ones it authenticated with.
+    fn requester(&self, zid: Option<ZenohId>) -> Vec<String> {
+        let zid = match zid {
+            Some(zid) => zid,
+            None => return vec![],
+        };
+        let tables = zread!(self.context.runtime.router.tables.tables);
+        tables
+            .get_face(&zid)
+            .and_then(|face| face.identities.clone())
+            .unwrap_or_else(|| vec![format!("zid:{zid}")])
+    }
+
+    /// Closes the session with the node identified by `zid`, which undeclares all its subscribers, queryables and
+    /// tokens. The session is re-established if the router is configured to connect to that node.
+    fn close_session(&self, zid: &str) -> ZResult<()> {
+        let zid: ZenohId = zid.parse()?;
+        let transport = task::block_on(self.context.runtime.manager().get_transport_unicast(&zid))
+            .ok_or_else(|| zerror!("No session with {}", zid))?;
+        log::info!(
+            "Closing session with {} as requested through the adminspace",
+            zid
+        );
+        task::spawn(async move {
+            if let Err(e) = transport.close().await {
+                log::error!("Error closing session with {}: {}", zid, e);
+            }
         });
+        Ok(())
+    }
+
+    /// Loads and starts the plugin described by `payload`: a JSON object such as
+    /// `{"name": "rest", "config": {"__path__": "/path/to/libzenoh_plugin_rest.so", "http_port": 8000}}`.
+    ///
+    /// The plugin's configuration is added to the runtime configuration, which triggers the loading of the plugin.
+    fn load_plugin(&self, payload: &[u8]) -> ZResult<()> {
+        let mut request: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_slice(payload)?;
+        let name = match request.remove("name") {
+            Some(serde_json::Value::String(name)) if !name.is_empty() && !name.contains('/') => {
+                name
+            }
+            _ => bail!("Expected a `name` property holding the plugin's name"),
+        };
+        let config = request.remove("config").unwrap_or_else(|| json!({}));
+        if let Some(key) = request.keys().next() {
+            bail!("Unexpected property `{}` for plugin `{}`", key, name)
+        }
+        if self.context.runtime.config.lock().plugin(&name).is_some() {
+            bail!("Plugin `{}` is already loaded", name)
+        }
+        (&self.context.runtime.config)
+            .insert_json5(&format!("plugins/{name}"), &config.to_string())?;
+        log::info!("Requested loading of plugin `{}`", name);
+        Ok(())
+    }
+
+    /// Stops the plugin named by `payload` and unloads its library, removing its configuration from the runtime
+    /// configuration.
+    fn unload_plugin(&self, payload: &[u8]) -> ZResult<()> {
+        let name = std::str::from_utf8(payload)?.trim();
+        if self.context.runtime.config.lock().plugin(name).is_none() {
+            bail!("Plugin `{}` is not loaded", name)
+        }
+        self.context
+            .runtime
+            .config
+            .remove(format!("plugins/{name}"))?;
+        log::info!("Requested unloading of plugin `{}`", name);
+        Ok(())
+    }
+
+    // The notifications published by the plugins within their status space aren't writes
+    fn is_plugin_notification(&self, wire_expr: &WireExpr) -> bool {
+        let Ok(key_expr) = self.key_expr_to_string(wire_expr) else {
+            return false;
+        };
+        let Some(plugin_key) = key_expr.as_str().strip_prefix(&format!(
+            "@/router/{}/status/plugins/",
+            &self.context.zid_str
+        )) else {
+            return false;
+        };
+        let name = plugin_key.split('/').next().unwrap_or_default();
+        let plugin_status_key =
+            format!("@/router/{}/status/plugins/{}", &self.context.zid_str, name);
+        let plugins_mgr = zlock!(self.context.plugins_mgr);
+        match plugins_mgr.plugin(name) {
+            Some(plugin) => catch_panic(|| {
+                plugin.is_adminspace_notification(key_expr.as_str(), &plugin_status_key)
+            })
+            .unwrap_or(false),
+            None => false,
+        }
     }
 
     pub fn key_expr_to_string<'a>(&self, key_expr: &'a WireExpr) -> ZResult<KeyExpr<'a>> {
@@ -282,6 +805,9 @@ impl Primitives for AdminSpace {
 
     fn send_push(&self, msg: Push) {
         trace!("recv Push {:?}", msg);
+        if self.is_plugin_notification(&msg.wire_expr) {
+            return;
+        }
         {
             let conf = self.context.runtime.config.lock();
             if !conf.adminspace.permissions().write {
@@ -293,11 +819,32 @@ impl Primitives for AdminSpace {
             }
         }
 
-        if let Some(key) = msg
-            .wire_expr
+        let key_expr = match self.key_expr_to_string(&msg.wire_expr) {
+            Ok(key_expr) => key_expr,
+            Err(e) => {
+                log::error!("Unknown KeyExpr: {}", e);
+                return;
+            }
+        };
+        let requester = self.requester(match &msg.payload {
+            PushBody::Put(put) => put.ext_sinfo.as_ref().map(|sinfo| sinfo.zid),
+            PushBody::Del(del) => del.ext_sinfo.as_ref().map(|sinfo| sinfo.zid),
+        });
+        let audit = &self.context.runtime.audit;
+        if let Some(key) = key_expr
             .as_str()
             .strip_prefix(&format!("@/router/{}/config/", &self.context.zid_str))
         {
+            // Writing a setting only read at startup would change the configuration without any effect
+            let writable = || -> ZResult<()> {
+                if !applies_at_runtime(key) {
+                    bail!(
+                        "`{}` is only applied when the router starts, it can't be changed at runtime",
+                        key
+                    );
+                }
+                Ok(())
+            };
             match msg.payload {
                 PushBody::Put(put) => match std::str::from_utf8(&put.payload.contiguous()) {
                     Ok(json) => {
@@ -307,12 +854,29 @@ impl Primitives for AdminSpace {
                             key,
                             json
                         );
-                        if let Err(e) = (&self.context.runtime.config).insert_json5(key, json) {
-                            error!(
+                        let result = writable().and_then(|()| {
+                            (&self.context.runtime.config)
+                                .insert_json5(key, json)
+                                .map_err(|e| zerror!("{}", e).into())
+                        });
+                        match &result {
+                            Ok(()) => log::info!(
+                                "Updated conf value /@/router/{}/config/{} : {}",
+                                &self.context.zid_str,
+                                key,
+                                json
+                            ),
+                            Err(e) => error!(
                                 "Error inserting conf value /@/router/{}/config/{} : {} - {}",
                                 &self.context.zid_str, key, json, e
-                            );
+                            ),
                         }
+                        audit.record(
+                            &requester,
+                            "config/put",
+                            json!({ "key": key, "value": json }),
+                            &result,
+                        );
                     }
                     Err(e) => error!(
                         "Received non utf8 conf value on /@/router/{}/config/{} : {}",
@@ -325,11 +889,88 @@ impl Primitives for AdminSpace {
                         &self.context.zid_str,
                         key
                     );
-                    if let Err(e) = self.context.runtime.config.remove(key) {
-                        log::error!("Error deleting conf value {} : {}", msg.wire_expr, e)
+                    let result = writable().and_then(|()| self.context.runtime.config.remove(key));
+                    match &result {
+                        Ok(()) => log::info!(
+                            "Deleted conf value /@/router/{}/config/{}",
+                            &self.context.zid_str,
+                            key
+                        ),
+                        Err(e) => log::error!("Error deleting conf value {} : {}", key_expr, e),
                     }
+                    audit.record(&requester, "config/delete", json!({ "key": key }), &result);
                 }
             }
+        } else if let Some(operation) = key_expr
+            .as_str()
+            .strip_prefix(&format!("@/router/{}/operations/", &self.context.zid_str))
+        {
+            match msg.payload {
+                PushBody::Put(put) => {
+                    let payload = put.payload.contiguous();
+                    let result = self.operation(operation, &payload);
+                    if let Err(e) = &result {
+                        error!("{}", e);
+                    }
+                    audit.record(
+                        &requester,
+                        &format!("operations/{operation}"),
+                        json!({ "payload": String::from_utf8_lossy(&payload) }),
+                        &result,
+                    );
+                }
+                PushBody::Del(_) => error!("Received DELETE on adminspace operation {}", key_expr),
+            }
+        } else if let Some(plugin_key) = key_expr.as_str().strip_prefix(&format!(
+            "@/router/{}/status/plugins/",
+            &self.context.zid_str
+        )) {
+            let (write, action, details) = match msg.payload {
+                PushBody::Put(put) => {
+                    let payload = put.payload.contiguous();
+                    let value = serde_json::from_slice(&payload).unwrap_or_else(|_| {
+                        serde_json::Value::String(String::from_utf8_lossy(&payload).into())
+                    });
+                    let details = json!({ "key": key_expr.as_str(), "value": value });
+                    (plugins::AdminSpaceWrite::Put(value), "plugin/put", details)
+                }
+                PushBody::Del(_) => (
+                    plugins::AdminSpaceWrite::Delete,
+                    "plugin/delete",
+                    json!({ "key": key_expr.as_str() }),
+                ),
+            };
+            let name = plugin_key.split('/').next().unwrap_or_default();
+            let plugin_status_key =
+                format!("@/router/{}/status/plugins/{}", &self.context.zid_str, name);
+            let plugins_mgr = zlock!(self.context.plugins_mgr);
+            let result = match plugins_mgr.plugin(name) {
+                Some(plugin) => match catch_panic(|| {
+                    plugin.adminspace_setter(key_expr.as_str(), &plugin_status_key, write)
+                }) {
+                    Ok(Ok(())) => {
+                        log::info!("Plugin `{}` handled write on {}", name, key_expr);
+                        Ok(())
+                    }
+                    Ok(Err(e)) => {
+                        error!("Plugin `{}` rejected write on {}: {}", name, key_expr, e);
+                        Err(e)
+                    }
+                    Err(e) => {
+                        let message = format!("panicked while handling write on {key_expr}: {e}");
+                        self.context.report_plugin_panic(name, message.clone());
+                        Err(zerror!("Plugin `{}` {}", name, message).into())
+                    }
+                },
+                None => {
+                    error!(
+                        "Received write on {} but plugin `{}` isn't running",
+                        key_expr, name
+                    );
+                    Err(zerror!("Plugin `{}` isn't running", name).into())
+                }
+            };
+            audit.record(&requester, action, details, &result);
         }
     }
 
@@ -378,6 +1019,7 @@ impl Primitives for AdminSpace {
                     qid: msg.id,
                     zid,
                     primitives,
+                    reply_priority: AtomicU8::new(Priority::default() as u8),
                 }),
             };
 
@@ -409,11 +1051,19 @@ fn router_data(context: &AdminContext, query: Query) {
 
     // plugins info
     let plugins: serde_json::Value = {
-        zlock!(context.plugins_mgr)
-            .running_plugins_info()
+        let guard = zlock!(context.plugins_mgr);
+        let mut plugins: serde_json::Map<String, serde_json::Value> = guard
+            .failed_plugins()
             .into_iter()
-            .map(|(k, v)| (k, json!({ "path": v })))
-            .collect()
+            .map(|(k, e)| (k.to_string(), json!({ "error": e })))
+            .collect();
+        plugins.extend(
+            guard
+                .running_plugins_info()
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), json!({ "path": v }))),
+        );
+        plugins.into()
     };
 
     // locators info
@@ -493,7 +1143,6 @@ fn router_metrics(context: &AdminContext, query: Query) {
     let reply_key: OwnedKeyExpr = format!("@/router/{}/metrics", context.zid_str)
         .try_into()
         .unwrap();
-    #[allow(unused_mut)]
     let mut metrics = format!(
         r#"# HELP zenoh_build Informations about zenoh.
 # TYPE zenoh_build gauge
@@ -512,6 +1161,78 @@ zenoh_build{{version="{}"}} 1
             .openmetrics_text(),
     );
 
+    let mut router_metrics = Vec::new();
+    {
+        let tables = zread!(context.runtime.router.tables.tables);
+        for whatami in [WhatAmI::Router, WhatAmI::Peer, WhatAmI::Client] {
+            let faces = tables
+                .faces
+                .values()
+                .filter(|face| face.whatami == whatami)
+                .count();
+            router_metrics.push(
+                plugins::Metric::new(
+                    "zenoh_faces",
+                    plugins::MetricKind::Gauge,
+                    "Number of faces (sessions with zenoh nodes or local sessions) in the routing tables.",
+                    faces as f64,
+                )
+                .with_label("whatami", whatami.to_str()),
+            );
+        }
+        for (scope, subs, qabls) in [
+            ("router", &tables.router_subs, &tables.router_qabls),
+            ("peer", &tables.peer_subs, &tables.peer_qabls),
+        ] {
+            router_metrics.push(
+                plugins::Metric::new(
+                    "zenoh_subscriptions",
+                    plugins::MetricKind::Gauge,
+                    "Number of key expressions subscribed to.",
+                    subs.len() as f64,
+                )
+                .with_label("scope", scope),
+            );
+            router_metrics.push(
+                plugins::Metric::new(
+                    "zenoh_queryables",
+                    plugins::MetricKind::Gauge,
+                    "Number of key expressions served by queryables.",
+                    qabls.len() as f64,
+                )
+                .with_label("scope", scope),
+            );
+        }
+        for (net, network) in [
+            ("routers", &tables.routers_net),
+            ("peers", &tables.peers_net),
+        ] {
+            if let Some(network) = network {
+                router_metrics.push(
+                    plugins::Metric::new(
+                        "zenoh_linkstate_nodes",
+                        plugins::MetricKind::Gauge,
+                        "Number of nodes in the link state graph.",
+                        network.graph.node_count() as f64,
+                    )
+                    .with_label("net", net),
+                );
+            }
+        }
+    }
+    for (name, (_, plugin)) in zlock!(context.plugins_mgr).running_plugins() {
+        match catch_panic(|| plugin.metrics()) {
+            Ok(metrics) => router_metrics.extend(
+                metrics
+                    .into_iter()
+                    .map(|metric| metric.with_label("plugin", name)),
+            ),
+            Err(e) => context
+                .report_plugin_panic(name, format!("panicked while reporting its metrics: {e}")),
+        }
+    }
+    metrics.push_str(&metrics_text(router_metrics));
+
     if let Err(e) = query
         .reply(Ok(Sample::new(
             reply_key,
@@ -523,6 +1244,43 @@ zenoh_build{{version="{}"}} 1
     }
 }
 
+/// Formats `metrics` in Prometheus text exposition format, grouping the samples of a same metric.
+fn metrics_text(mut metrics: Vec<plugins::Metric>) -> String {
+    metrics.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut text = String::new();
+    let mut current = None;
+    for metric in metrics {
+        if current != Some(metric.name.clone()) {
+            text.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} {}\n",
+                metric.name, metric.help, metric.name, metric.kind
+            ));
+            current = Some(metric.name.clone());
+        }
+        text.push_str(&metric.name);
+        if !metric.labels.is_empty() {
+            let labels = metric
+                .labels
+                .iter()
+                .map(|(name, value)| {
+                    format!(
+                        "{}=\"{}\"",
+                        name,
+                        value
+                            .replace('\\', "\\\\")
+                            .replace('"', "\\\"")
+                            .replace('\n', "\\n")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            text.push_str(&format!("{{{labels}}}"));
+        }
+        text.push_str(&format!(" {}\n", metric.value));
+    }
+    text
+}
+
 fn routers_linkstate_data(context: &AdminContext, query: Query) {
     let reply_key: OwnedKeyExpr = format!("@/router/{}/linkstate/routers", context.zid_str)
         .try_into()
@@ -577,6 +1335,126 @@ fn peers_linkstate_data(context: &AdminContext, query: Query) {
     }
 }
 
+fn sessions_data(context: &AdminContext, query: Query) {
+    for transport in task::block_on(context.runtime.manager().get_transports_unicast()) {
+        // The transport may get closed while we're inspecting it
+        let peer = match transport.get_peer() {
+            Ok(peer) => peer,
+            Err(_) => continue,
+        };
+        let key = KeyExpr::try_from(format!(
+            "@/router/{}/sessions/{}",
+            context.zid_str, peer.zid
+        ))
+        .unwrap();
+        if !query.key_expr().intersects(&key) {
+            continue;
+        }
+        let links: Vec<serde_json::Value> = peer
+            .links
+            .iter()
+            .map(|link| {
+                json!({
+                    "src": link.src.as_str(),
+                    "dst": link.dst.as_str(),
+                    "mtu": link.mtu,
+                    "reliable": link.is_reliable,
+                    "streamed": link.is_streamed,
+                })
+            })
+            .collect();
+        #[allow(unused_mut)]
+        let mut json = json!({
+            "zid": peer.zid.to_string(),
+            "whatami": peer.whatami.to_string(),
+            "links": links,
+            "qos": peer.is_qos,
+            "uptime": transport.get_uptime().map_or(0, |uptime| uptime.as_secs()),
+        });
+        // The identities the peer proved when opening the session, if it was authenticated by this node
+        #[cfg(any(
+            feature = "auth_pubkey",
+            feature = "auth_usrpwd",
+            feature = "auth_token"
+        ))]
+        if let Ok(auth_ids) = transport.get_auth_ids() {
+            json.as_object_mut().unwrap().insert(
+                "auth_ids".to_string(),
+                json!(auth_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>()),
+            );
+        }
+        // Message and byte counters are only maintained when built with the `stats` feature
+        #[cfg(feature = "stats")]
+        if let Ok(stats) = transport.get_stats() {
+            json.as_object_mut()
+                .unwrap()
+                .insert("stats".to_string(), json!(stats.report()));
+        }
+        if let Err(e) = query
+            .reply(Ok(Sample::new(
+                key,
+                Value::from(json.to_string().as_bytes().to_vec())
+                    .encoding(KnownEncoding::AppJson.into()),
+            )))
+            .res()
+        {
+            log::error!("Error sending AdminSpace reply: {:?}", e);
+        }
+    }
+}
+
+/// Replies with the clients connected to this node and their declarations, so that the owner of a problematic
+/// declaration can be located, and its session closed with the `sessions/<zid>/close` operation.
+/// Publishers aren't declared to routers: the key expressions declared by a client, such as the ones of its
+/// publishers, are reported instead.
+fn clients_data(context: &AdminContext, query: Query) {
+    fn exprs<'a>(resources: impl Iterator<Item = &'a Arc<Resource>>) -> Vec<&'a str> {
+        let mut exprs = resources.map(|res| res.expr()).collect::<Vec<_>>();
+        exprs.sort();
+        exprs.dedup();
+        exprs
+    }
+
+    let tables = zread!(context.runtime.router.tables.tables);
+    let replies = tables
+        .faces
+        .values()
+        // The sessions opened locally, e.g. by the plugins, have no identities
+        .filter(|face| face.whatami == WhatAmI::Client && face.identities.is_some())
+        .filter_map(|face| {
+            let key = KeyExpr::try_from(format!(
+                "@/router/{}/clients/{}",
+                context.zid_str, face.zid
+            ))
+            .unwrap();
+            query.key_expr().intersects(&key).then(|| {
+                let json = json!({
+                    "zid": face.zid.to_string(),
+                    "identities": face.identities,
+                    "namespace": face.namespace.as_ref().map(|namespace| namespace.prefix().as_str()),
+                    "subscribers": exprs(face.remote_subs.iter()),
+                    "queryables": exprs(face.remote_qabls.iter()),
+                    "key_exprs": exprs(face.remote_mappings.values()),
+                });
+                (key, json)
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tables);
+    for (key, json) in replies {
+        if let Err(e) = query
+            .reply(Ok(Sample::new(
+                key,
+                Value::from(json.to_string().as_bytes().to_vec())
+                    .encoding(KnownEncoding::AppJson.into()),
+            )))
+            .res()
+        {
+            log::error!("Error sending AdminSpace reply: {:?}", e);
+        }
+    }
+}
+
 fn subscribers_data(context: &AdminContext, query: Query) {
     let tables = zread!(context.runtime.router.tables.tables);
     for sub in tables.router_subs.iter() {
@@ -611,11 +1489,284 @@ fn queryables_data(context: &AdminContext, query: Query) {
     }
 }
 
+/// Replies with the router's liveness (being able to answer) and readiness: its configured listeners are bound
+/// and its required plugins are running.
+fn health_data(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/health", context.zid_str)
+        .try_into()
+        .unwrap();
+
+    let (listen, required_plugins): (Vec<_>, Vec<_>) = {
+        let config = context.runtime.config.lock();
+        (
+            config.listen().endpoints().clone(),
+            config
+                .plugins()
+                .load_requests()
+                .flatten()
+                .filter(|request| request.required)
+                .map(|request| request.name)
+                .collect(),
+        )
+    };
+    let listeners = context.runtime.manager().get_listeners();
+    let listeners_ready = listeners.len() >= listen.len();
+    let failed_plugins: Vec<String> = {
+        let guard = zlock!(context.plugins_mgr);
+        required_plugins
+            .into_iter()
+            .filter(|plugin| guard.plugin(plugin).is_none())
+            .collect()
+    };
+    let plugins_ready = failed_plugins.is_empty();
+
+    let json = json!({
+        "live": true,
+        "ready": listeners_ready && plugins_ready,
+        "checks": {
+            "config": { "ready": true },
+            "listeners": {
+                "ready": listeners_ready,
+                "configured": listen.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+                "bound": listeners.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            },
+            "required_plugins": {
+                "ready": plugins_ready,
+                "failed": failed_plugins,
+            },
+        },
+    });
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(json.to_string().as_bytes().to_vec())
+                .encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+fn events_data(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/events", context.zid_str)
+        .try_into()
+        .unwrap();
+    let events: Vec<serde_json::Value> = context
+        .runtime
+        .events
+        .events()
+        .iter()
+        .map(|event| event.to_json())
+        .collect();
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(json!(events).to_string().as_bytes().to_vec())
+                .encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+fn measurements_data(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/measurements", context.zid_str)
+        .try_into()
+        .unwrap();
+    let json = match &zread!(context.runtime.router.tables.tables).measurements {
+        Some(measurements) => measurements.to_json(),
+        None => json!({}),
+    };
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(json.to_string().as_bytes().to_vec())
+                .encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+fn quotas_data(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/quotas", context.zid_str)
+        .try_into()
+        .unwrap();
+    let tables = zread!(context.runtime.router.tables.tables);
+    let json = match &tables.quotas {
+        Some(quotas) => tables
+            .faces
+            .values()
+            .filter_map(|face| {
+                let identities = face.identities.as_ref()?;
+                let mut usage = face.quota_usage.to_json(&quotas.limits(identities));
+                usage["identities"] = json!(identities);
+                Some((face.zid.to_string(), usage))
+            })
+            .collect(),
+        None => serde_json::Map::new(),
+    };
+    drop(tables);
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(
+                serde_json::Value::Object(json)
+                    .to_string()
+                    .as_bytes()
+                    .to_vec(),
+            )
+            .encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+#[cfg(feature = "fault_injection")]
+fn faults_data(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/faults", context.zid_str)
+        .try_into()
+        .unwrap();
+    let profile = context.runtime.manager().fault_profile();
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(json!(profile).to_string().as_bytes().to_vec())
+                .encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+fn config_history_data(context: &AdminContext, query: Query) {
+    let config = &context.runtime.config;
+    let mut history = config.history();
+    history.push((config.version(), config.lock().clone()));
+    for (version, config) in history {
+        let key = KeyExpr::try_from(format!(
+            "@/router/{}/status/config/history/{}",
+            context.zid_str, version
+        ))
+        .unwrap();
+        if query.key_expr().intersects(&key) {
+            let json = serde_json::to_string(&config.sift_privates()).unwrap();
+            if let Err(e) = query
+                .reply(Ok(Sample::new(
+                    key,
+                    Value::from(json.as_bytes().to_vec()).encoding(KnownEncoding::AppJson.into()),
+                )))
+                .res()
+            {
+                log::error!("Error sending AdminSpace reply: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Replies with the configuration effectively used by the runtime: its current configuration, including the runtime
+/// changes, where the settings left unset are replaced by their default values.
+fn config_effective_data(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/status/config/effective", context.zid_str)
+        .try_into()
+        .unwrap();
+    let config = context.runtime.config.lock().resolved().sift_privates();
+    let json = serde_json::to_string(&config).unwrap();
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(json.as_bytes().to_vec()).encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
+/// Replies with the settings effectively changed since the runtime was started with its configuration file and
+/// arguments, by `{"from": <value>, "to": <value>}` per setting path.
+fn config_diff_data(context: &AdminContext, query: Query) {
+    let reply_key: OwnedKeyExpr = format!("@/router/{}/status/config/diff", context.zid_str)
+        .try_into()
+        .unwrap();
+    let config = &context.runtime.config;
+    let current = config.lock().resolved();
+    let diff = config.initial().resolved().diff(&current);
+    if let Err(e) = query
+        .reply(Ok(Sample::new(
+            reply_key,
+            Value::from(diff.to_string().as_bytes().to_vec())
+                .encoding(KnownEncoding::AppJson.into()),
+        )))
+        .res()
+    {
+        log::error!("Error sending AdminSpace reply: {:?}", e);
+    }
+}
+
 fn plugins_status(context: &AdminContext, query: Query) {
     let selector = query.selector();
     let guard = zlock!(context.plugins_mgr);
     let mut root_key = format!("@/router/{}/status/plugins/", &context.zid_str);
 
+    for (name, error) in guard.failed_plugins() {
+        with_extended_string(&mut root_key, &[name, "/__error__"], |plugin_error_key| {
+            match KeyExpr::try_from(plugin_error_key.clone()) {
+                Ok(key_expr) => {
+                    if query.key_expr().intersects(&key_expr) {
+                        if let Err(e) = query
+                            .reply(Ok(Sample::new(
+                                key_expr,
+                                Value::from(json!(error).to_string().as_bytes().to_vec())
+                                    .encoding(KnownEncoding::AppJson.into()),
+                            )))
+                            .res()
+                        {
+                            log::error!("Error sending AdminSpace reply: {:?}", e);
+                        }
+                    }
+                }
+                Err(_) => log::error!("Error: invalid plugin error key {}", plugin_error_key),
+            }
+        });
+    }
+
+    for (name, restarts) in guard.restarted_plugins() {
+        with_extended_string(&mut root_key, &[name, "/__restarts__"], |restarts_key| {
+            if let Ok(key_expr) = KeyExpr::try_from(restarts_key.clone()) {
+                if query.key_expr().intersects(&key_expr) {
+                    let restarts = restarts
+                        .iter()
+                        .map(|restart| {
+                            json!({
+                                "time": humantime::format_rfc3339_seconds(restart.time).to_string(),
+                                "reason": restart.reason,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    if let Err(e) = query
+                        .reply(Ok(Sample::new(
+                            key_expr,
+                            Value::from(json!(restarts).to_string().as_bytes().to_vec())
+                                .encoding(KnownEncoding::AppJson.into()),
+                        )))
+                        .res()
+                    {
+                        log::error!("Error sending AdminSpace reply: {:?}", e);
+                    }
+                }
+            } else {
+                log::error!("Error: invalid plugin restarts key {}", restarts_key);
+            }
+        });
+    }
+
     for (name, (path, plugin)) in guard.running_plugins() {
         with_extended_string(&mut root_key, &[name], |plugin_key| {
             with_extended_string(plugin_key, &["/__path__"], |plugin_path_key| {
@@ -643,17 +1794,17 @@ fn plugins_status(context: &AdminContext, query: Query) {
             if !with_extended_string(plugin_key, &["/**"], matches_plugin) {
                 return;
             }
-            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                plugin.adminspace_getter(&selector, plugin_key)
-            })) {
+            match catch_panic(|| plugin.adminspace_getter(&selector, plugin_key)) {
                 Ok(Ok(responses)) => {
                     for response in responses {
                         if let Ok(key_expr) = KeyExpr::try_from(response.key) {
-                            if let Err(e) = query.reply(Ok(Sample::new(
-                                key_expr,
-                                Value::from(response.value).encoding(KnownEncoding::AppJson.into()),
-                            )))
-                            .res()
+                            if let Err(e) = query
+                                .reply(Ok(Sample::new(
+                                    key_expr,
+                                    Value::from(response.value)
+                                        .encoding(KnownEncoding::AppJson.into()),
+                                )))
+                                .res()
                             {
                                 log::error!("Error sending AdminSpace reply: {:?}", e);
                             }
@@ -663,16 +1814,17 @@ fn plugins_status(context: &AdminContext, query: Query) {
                     }
                 }
                 Ok(Err(e)) => {
-                    log::error!("Plugin {} bailed from responding to {}: {}", name, query.key_expr(), e)
+                    log::error!(
+                        "Plugin {} bailed from responding to {}: {}",
+                        name,
+                        query.key_expr(),
+                        e
+                    )
                 }
-                Err(e) => match e
-                    .downcast_ref::<String>()
-                    .map(|s| s.as_str())
-                    .or_else(|| e.downcast_ref::<&str>().copied())
-                {
-                    Some(e) => log::error!("Plugin {} panicked while responding to {}: {}", name, query.key_expr(), e),
-                    None => log::error!("Plugin {} panicked while responding to {}. The panic message couldn't be recovered.", name, query.key_expr()),
-                },
+                Err(e) => context.report_plugin_panic(
+                    name,
+                    format!("panicked while responding to {}: {}", query.key_expr(), e),
+                ),
             }
         });
     }
@@ -691,3 +1843,42 @@ fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     prefix.truncate(prefix_len);
     result
 }
+
+#[test]
+fn metrics_exposition() {
+    use plugins::{Metric, MetricKind};
+
+    let text = metrics_text(vec![
+        Metric::new(
+            "zenoh_queue_size",
+            MetricKind::Gauge,
+            "The queued messages",
+            3.0,
+        ),
+        Metric::new(
+            "zenoh_sent_total",
+            MetricKind::Counter,
+            "The sent messages",
+            42.0,
+        )
+        .with_label("plugin", "rest"),
+        Metric::new(
+            "zenoh_sent_total",
+            MetricKind::Counter,
+            "The sent messages",
+            7.0,
+        )
+        .with_label("plugin", "a \"quoted\\\" name\n")
+        .with_label("priority", "data"),
+    ]);
+    assert_eq!(
+        text,
+        "# HELP zenoh_queue_size The queued messages\n\
+         # TYPE zenoh_queue_size gauge\n\
+         zenoh_queue_size 3\n\
+         # HELP zenoh_sent_total The sent messages\n\
+         # TYPE zenoh_sent_total counter\n\
+         zenoh_sent_total{plugin=\"rest\"} 42\n\
+         zenoh_sent_total{plugin=\"a \\\"quoted\\\\\\\" name\\n\",priority=\"data\"} 7\n"
+    );
+}
@@ -0,0 +1,112 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde_json::json;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use zenoh_core::zlock;
+use zenoh_result::{zerror, ZResult};
+
+/// An append-only log of the administrative actions, each one written as a JSON object on its own line
+/// along with its time, the identities of the node requesting it and its result.
+///
+/// The actions are logged (at the info level) even if no audit file is configured.
+pub struct AuditLog {
+    file: Option<Mutex<File>>,
+}
+
+impl AuditLog {
+    pub fn new(path: Option<&str>) -> ZResult<Self> {
+        let file = match path {
+            Some(path) => Some(Mutex::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| zerror!("Failed to open audit log {}: {}", path, e))?,
+            )),
+            None => None,
+        };
+        Ok(AuditLog { file })
+    }
+
+    /// Records an action, such as `config/put` or `plugin/load`. Empty `identities` stand for the runtime itself.
+    pub fn record<E: std::fmt::Display>(
+        &self,
+        identities: &[String],
+        action: &str,
+        details: serde_json::Value,
+        result: &Result<(), E>,
+    ) {
+        let result = match result {
+            Ok(()) => json!("ok"),
+            Err(e) => json!({ "error": e.to_string() }),
+        };
+        log::info!(
+            "Audit: {} by {:?} ({}): {}",
+            action,
+            identities,
+            details,
+            result
+        );
+        if let Some(file) = &self.file {
+            let entry = json!({
+                "time": humantime::format_rfc3339(SystemTime::now()).to_string(),
+                "identities": identities,
+                "action": action,
+                "details": details,
+                "result": result,
+            });
+            if let Err(e) = writeln!(zlock!(file), "{entry}") {
+                log::error!("Failed to write to audit log: {}", e);
+            }
+        }
+    }
+}
+
+#[test]
+fn audit_log() {
+    let path = std::env::temp_dir().join(format!("zenoh-audit-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let log = AuditLog::new(path.to_str()).unwrap();
+    log.record(
+        &["zid:1".to_string()],
+        "config/put",
+        json!({ "key": "connect/endpoints" }),
+        &ZResult::Ok(()),
+    );
+    drop(log);
+    // Entries are appended to the existing ones
+    let log = AuditLog::new(path.to_str()).unwrap();
+    log.record(
+        &[],
+        "plugin/load",
+        json!({ "plugin": "rest" }),
+        &Err("failed"),
+    );
+
+    let entries: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["identities"], json!(["zid:1"]));
+    assert_eq!(entries[0]["action"], "config/put");
+    assert_eq!(entries[0]["result"], "ok");
+    assert_eq!(entries[1]["identities"], json!([]));
+    assert_eq!(entries[1]["result"]["error"], "failed");
+}
@@ -0,0 +1,233 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::Runtime;
+use crate::net::codec::Zenoh080Routing;
+use crate::net::protocol::dissemination::Dissemination;
+use crate::net::routing::dissemination::{
+    dissemination_join, dissemination_repair, make_msg, DisseminationGroup,
+};
+use std::sync::Mutex;
+use zenoh_buffers::reader::HasReader;
+use zenoh_codec::RCodec;
+use zenoh_config::{unwrap_or_default, Config};
+use zenoh_core::{zlock, zwrite};
+use zenoh_link::{EndPoint, Locator};
+use zenoh_protocol::common::ZExtBody;
+use zenoh_protocol::core::WhatAmI;
+use zenoh_protocol::network::{NetworkBody, NetworkMessage};
+use zenoh_result::{zerror, ZResult};
+use zenoh_transport::TransportUnicast;
+
+/// Disseminates the samples with many subscribers on a UDP multicast group.
+///
+/// A router offers the group to its clients. The clients join it and tell the router, which then
+/// publishes on the group the samples enough of them subscribe to, tagged with a sequence number.
+/// The clients detect the gaps in the sequence numbers and ask the router to send the missed samples
+/// again over their unicast session.
+pub struct DisseminationState {
+    group: Locator,
+    threshold: usize,
+    repair_history: usize,
+    receiver: Mutex<Receiver>,
+}
+
+// The state of a client that joined the group offered by its router
+#[derive(Default)]
+struct Receiver {
+    router: Option<TransportUnicast>,
+    next_sn: Option<u64>,
+}
+
+impl DisseminationState {
+    pub fn from_config(config: &Config) -> ZResult<Option<Self>> {
+        let dissemination = config.routing().dissemination();
+        if !unwrap_or_default!(dissemination.enabled()) {
+            return Ok(None);
+        }
+        let group: Locator = unwrap_or_default!(dissemination.group())
+            .parse()
+            .map_err(|e| zerror!("Invalid dissemination group: {}", e))?;
+        Ok(Some(DisseminationState {
+            group,
+            threshold: unwrap_or_default!(dissemination.threshold()),
+            repair_history: unwrap_or_default!(dissemination.repair_history()),
+            receiver: Mutex::new(Receiver::default()),
+        }))
+    }
+}
+
+impl Runtime {
+    pub(super) fn init_dissemination(&self) {
+        if let Some(state) = self.dissemination.as_ref() {
+            zwrite!(self.router.tables.tables).dissemination = Some(DisseminationGroup::new(
+                state.group.clone(),
+                state.threshold,
+                state.repair_history,
+            ));
+        }
+    }
+
+    /// Opens the multicast group the router disseminates samples on.
+    pub(super) async fn start_dissemination(&self) {
+        let Some(state) = self.dissemination.as_ref() else {
+            return;
+        };
+        if self.whatami != WhatAmI::Router {
+            return;
+        }
+        match self
+            .manager()
+            .open_transport_multicast(state.group.clone().into())
+            .await
+        {
+            Ok(_) => log::info!("Disseminating samples on {}", state.group),
+            Err(e) => log::error!("Unable to open dissemination group {}: {}", state.group, e),
+        }
+    }
+
+    /// Offers a newly connected client to join the dissemination group.
+    pub(super) fn offer_dissemination(&self, transport: &TransportUnicast) {
+        let Some(state) = self.dissemination.as_ref() else {
+            return;
+        };
+        if self.whatami != WhatAmI::Router
+            || !matches!(transport.get_whatami(), Ok(WhatAmI::Client))
+        {
+            return;
+        }
+        let msg = make_msg(&Dissemination::Offer {
+            group: state.group.clone(),
+        });
+        if let Err(e) = msg.and_then(|msg| transport.schedule(msg)) {
+            log::debug!("Error sending dissemination Offer: {}", e);
+        }
+    }
+
+    fn read_dissemination(body: ZExtBody) -> Option<Dissemination> {
+        let ZExtBody::ZBuf(buf) = body else {
+            return None;
+        };
+        let codec = Zenoh080Routing::new();
+        codec.read(&mut buf.reader()).ok()
+    }
+
+    /// Handles the dissemination messages received over a unicast session.
+    pub(super) fn handle_dissemination(&self, transport: &TransportUnicast, body: ZExtBody) {
+        if self.dissemination.is_none() {
+            return;
+        }
+        let (Some(msg), Ok(zid)) = (Runtime::read_dissemination(body), transport.get_zid()) else {
+            log::warn!(
+                "Received invalid Dissemination from {:?}",
+                transport.get_zid()
+            );
+            return;
+        };
+        match (self.whatami, msg) {
+            (WhatAmI::Client, Dissemination::Offer { group }) => {
+                let runtime = self.clone();
+                let transport = transport.clone();
+                self.spawn(async move { runtime.join_dissemination(&transport, group).await });
+            }
+            (WhatAmI::Router, Dissemination::Join) => {
+                dissemination_join(&mut zwrite!(self.router.tables.tables), &zid);
+            }
+            (WhatAmI::Router, Dissemination::Repair { from, to }) => {
+                dissemination_repair(&self.router.tables, &zid, from, to);
+            }
+            (_, msg) => log::debug!("Unexpected Dissemination from {}: {:?}", zid, msg),
+        }
+    }
+
+    async fn join_dissemination(&self, transport: &TransportUnicast, group: Locator) {
+        let state = self.dissemination.as_ref().unwrap();
+        // The group offered by the router prevails over the configured one
+        let dissemination =
+            DisseminationGroup::new(group.clone(), state.threshold, state.repair_history);
+        let joined = self
+            .manager()
+            .get_transports_multicast()
+            .await
+            .iter()
+            .any(|t| {
+                t.get_link()
+                    .map(|l| dissemination.is_group(&l))
+                    .unwrap_or(false)
+            });
+        zwrite!(self.router.tables.tables).dissemination = Some(dissemination);
+        if !joined {
+            let endpoint: EndPoint = group.clone().into();
+            if let Err(e) = self.manager().open_transport_multicast(endpoint).await {
+                log::warn!("Unable to join dissemination group {}: {}", group, e);
+                return;
+            }
+        }
+
+        *zlock!(state.receiver) = Receiver {
+            router: Some(transport.clone()),
+            next_sn: None,
+        };
+        if let Err(e) = make_msg(&Dissemination::Join).and_then(|msg| transport.schedule(msg)) {
+            log::debug!("Error sending dissemination Join: {}", e);
+            return;
+        }
+        log::info!("Joined dissemination group {}", group);
+    }
+
+    /// Handles the samples received on the dissemination group.
+    pub(super) fn handle_dissemination_sample(&self, body: ZExtBody) {
+        let Some(state) = self.dissemination.as_ref() else {
+            return;
+        };
+        let Some(Dissemination::Sample { sn, source, push }) = Runtime::read_dissemination(body)
+        else {
+            return;
+        };
+        let mut receiver = zlock!(state.receiver);
+        let Some(router) = receiver.router.clone() else {
+            return;
+        };
+        match receiver.next_sn {
+            Some(next_sn) if sn < next_sn => {
+                log::trace!("Dropped duplicate disseminated sample {}", sn);
+                return;
+            }
+            Some(next_sn) if sn > next_sn => {
+                log::debug!("Missed disseminated samples {} to {}", next_sn, sn);
+                let msg = make_msg(&Dissemination::Repair {
+                    from: next_sn,
+                    to: sn,
+                });
+                if let Err(e) = msg.and_then(|msg| router.schedule(msg)) {
+                    log::debug!("Error sending dissemination Repair: {}", e);
+                }
+            }
+            _ => (),
+        }
+        receiver.next_sn = Some(sn + 1);
+        drop(receiver);
+
+        // The local subscribers already got the samples published by this client
+        if source == self.zid {
+            return;
+        }
+        // Route the sample as if received from the router
+        if let Ok(Some(handler)) = router.get_callback() {
+            let msg: NetworkMessage = NetworkBody::Push(push).into();
+            if let Err(e) = handler.handle_message(msg) {
+                log::debug!("Error routing disseminated sample {}: {}", sn, e);
+            }
+        }
+    }
+}
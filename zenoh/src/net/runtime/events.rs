@@ -0,0 +1,103 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use zenoh_core::zlock;
+
+/// A significant event of the runtime's life, kept in the [`EventLog`].
+#[derive(Clone, Debug)]
+pub struct RuntimeEvent {
+    /// The sequence number of the event, incremented for each recorded event.
+    pub id: u64,
+    pub time: SystemTime,
+    /// The kind of event, e.g. `session/open` or `plugin/restart`.
+    pub kind: &'static str,
+    pub details: serde_json::Value,
+}
+
+impl RuntimeEvent {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "id": self.id,
+            "time": humantime::format_rfc3339(self.time).to_string(),
+            "kind": self.kind,
+            "details": self.details,
+        })
+    }
+}
+
+struct EventLogInner {
+    capacity: usize,
+    next_id: u64,
+    events: VecDeque<RuntimeEvent>,
+}
+
+/// A bounded log of the runtime's most recent events: once full, recording an event drops the oldest one.
+pub struct EventLog {
+    inner: Mutex<EventLogInner>,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        EventLog {
+            inner: Mutex::new(EventLogInner {
+                capacity,
+                next_id: 0,
+                events: VecDeque::with_capacity(capacity),
+            }),
+        }
+    }
+
+    pub fn record(&self, kind: &'static str, details: serde_json::Value) {
+        log::debug!("Runtime event {}: {}", kind, details);
+        let mut inner = zlock!(self.inner);
+        if inner.capacity == 0 {
+            return;
+        }
+        while inner.events.len() >= inner.capacity {
+            inner.events.pop_front();
+        }
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.events.push_back(RuntimeEvent {
+            id,
+            time: SystemTime::now(),
+            kind,
+            details,
+        });
+    }
+
+    /// Returns the events still kept in the log, from oldest to newest.
+    pub fn events(&self) -> Vec<RuntimeEvent> {
+        zlock!(self.inner).events.iter().cloned().collect()
+    }
+}
+
+#[test]
+fn event_log() {
+    let log = EventLog::new(2);
+    log.record("a", json!(null));
+    log.record("b", json!(null));
+    log.record("c", json!(null));
+    let events = log.events();
+    assert_eq!(events.len(), 2);
+    assert_eq!((events[0].id, events[0].kind), (1, "b"));
+    assert_eq!((events[1].id, events[1].kind), (2, "c"));
+
+    let log = EventLog::new(0);
+    log.record("a", json!(null));
+    assert!(log.events().is_empty());
+}
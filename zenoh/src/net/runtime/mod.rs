@@ -17,19 +17,40 @@
 //! This module is intended for Zenoh's internal use.
 //!
 //! [Click here for Zenoh's documentation](../zenoh/index.html)
+#[cfg(feature = "queries")]
 mod adminspace;
+pub mod audit;
+#[cfg(feature = "transport_multicast")]
+pub mod dissemination;
+pub mod events;
 pub mod orchestrator;
+pub mod rendezvous;
+#[cfg(feature = "scouting")]
+pub mod scouting_auth;
 
 use super::routing;
+use super::routing::acl::AccessControl;
 use super::routing::face::Face;
+use super::routing::namespace::Namespaces;
 use super::routing::pubsub::full_reentrant_route_data;
+use super::routing::qos_overrides::QosOverrides;
+use super::routing::quotas::Quotas;
 use super::routing::router::{LinkStateInterceptor, Router};
 use crate::config::{unwrap_or_default, Config, ModeDependent, Notifier};
 use crate::GIT_VERSION;
-pub use adminspace::AdminSpace;
+#[cfg(feature = "queries")]
+pub use adminspace::{AdminContext, AdminSpace};
 use async_std::task::JoinHandle;
+use audit::AuditLog;
+#[cfg(feature = "transport_multicast")]
+use dissemination::DisseminationState;
+use events::EventLog;
 use futures::stream::StreamExt;
 use futures::Future;
+use rendezvous::RendezvousState;
+#[cfg(feature = "scouting")]
+use scouting_auth::ScoutingAuth;
+use serde_json::json;
 use std::any::Any;
 use std::sync::Arc;
 use std::time::Duration;
@@ -38,12 +59,16 @@ use stop_token::{StopSource, TimedOutError};
 use uhlc::{HLCBuilder, HLC};
 use zenoh_link::{EndPoint, Link};
 use zenoh_protocol::core::{whatami::WhatAmIMatcher, Locator, WhatAmI, ZenohId};
-use zenoh_protocol::network::{NetworkBody, NetworkMessage};
+#[cfg(feature = "transport_multicast")]
+use zenoh_protocol::network::oam::id::OAM_DISSEMINATION;
+use zenoh_protocol::network::{oam::id::OAM_RENDEZVOUS, NetworkBody, NetworkMessage};
 use zenoh_result::{bail, ZResult};
 use zenoh_sync::get_mut_unchecked;
+#[cfg(feature = "transport_multicast")]
+use zenoh_transport::{DeMux, TransportMulticast, TransportMulticastEventHandler};
 use zenoh_transport::{
-    DeMux, TransportEventHandler, TransportManager, TransportMulticast,
-    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+    TransportEventHandler, TransportManager, TransportPeer, TransportPeerEventHandler,
+    TransportUnicast,
 };
 
 pub struct RuntimeState {
@@ -55,8 +80,17 @@ pub struct RuntimeState {
     pub manager: TransportManager,
     pub transport_handlers: std::sync::RwLock<Vec<Arc<dyn TransportEventHandler>>>,
     pub(crate) locators: std::sync::RwLock<Vec<Locator>>,
+    /// The endpoints listened on as configured, along with the locator each one got bound to.
+    pub(crate) listeners: std::sync::Mutex<Vec<(EndPoint, Locator)>>,
     pub hlc: Option<Arc<HLC>>,
+    pub events: EventLog,
+    pub audit: AuditLog,
     pub(crate) stop_source: std::sync::RwLock<Option<StopSource>>,
+    #[cfg(feature = "scouting")]
+    pub(crate) scouting_auth: Option<ScoutingAuth>,
+    pub(crate) rendezvous: Option<RendezvousState>,
+    #[cfg(feature = "transport_multicast")]
+    pub(crate) dissemination: Option<DisseminationState>,
 }
 
 #[derive(Clone)]
@@ -72,6 +106,36 @@ impl std::ops::Deref for Runtime {
     }
 }
 
+/// Whether the runtime applies the changes of the setting `key` as soon as they are written:
+/// the other settings are only read when the runtime starts.
+pub(crate) fn applies_at_runtime(key: &str) -> bool {
+    let section = |prefix: &str| {
+        key.strip_prefix(prefix)
+            .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    #[cfg(any(
+        feature = "auth_pubkey",
+        feature = "auth_usrpwd",
+        feature = "auth_token"
+    ))]
+    if section("transport/auth") {
+        return true;
+    }
+    matches!(
+        key,
+        "connect" | "connect/endpoints" | "listen" | "listen/endpoints"
+    ) || [
+        "access_control",
+        "quotas",
+        "qos_overrides",
+        "namespaces",
+        "plugins",
+        "adminspace/permissions",
+    ]
+    .into_iter()
+    .any(section)
+}
+
 impl Runtime {
     pub async fn new(config: Config) -> ZResult<Runtime> {
         let mut runtime = Runtime::init(config).await?;
@@ -85,6 +149,8 @@ impl Runtime {
         log::debug!("Zenoh Rust API {}", GIT_VERSION);
         // Make sure to have have enough threads spawned in the async futures executor
         zasync_executor_init!();
+        // The mode may have been set after the configuration was built from the profile of another mode
+        let config = config.with_profile();
 
         let zid = *config.id();
 
@@ -92,8 +158,14 @@ impl Runtime {
 
         let whatami = unwrap_or_default!(config.mode());
         let metadata = config.metadata().clone();
-        let hlc = (*unwrap_or_default!(config.timestamping().enabled().get(whatami)))
-            .then(|| Arc::new(HLCBuilder::new().with_id(uhlc::ID::from(&zid)).build()));
+        let hlc = (*unwrap_or_default!(config.timestamping().enabled().get(whatami))).then(|| {
+            Arc::new(
+                HLCBuilder::new()
+                    .with_id(uhlc::ID::from(&zid))
+                    .with_clock(crate::time::clock)
+                    .build(),
+            )
+        });
         let drop_future_timestamp =
             unwrap_or_default!(config.timestamping().drop_future_timestamp());
 
@@ -110,6 +182,8 @@ impl Runtime {
             && unwrap_or_default!(config.routing().peer().mode()) == *"linkstate";
         let router_peers_failover_brokering =
             unwrap_or_default!(config.routing().router().peers_failover_brokering());
+        let router_trace = unwrap_or_default!(config.routing().router().trace());
+        let router_relay = unwrap_or_default!(config.routing().router().relay());
         let queries_default_timeout =
             Duration::from_millis(unwrap_or_default!(config.queries_default_timeout()));
 
@@ -120,6 +194,13 @@ impl Runtime {
             drop_future_timestamp,
             router_peers_failover_brokering,
             queries_default_timeout,
+            config.adminspace().measurements().clone(),
+            router_trace,
+            router_relay,
+            config.access_control(),
+            config.quotas(),
+            config.qos_overrides(),
+            config.namespaces(),
         ));
 
         let handler = Arc::new(RuntimeTransportEventHandler {
@@ -133,7 +214,20 @@ impl Runtime {
             .zid(zid)
             .build(handler.clone())?;
 
+        #[cfg(feature = "scouting")]
+        let scouting_auth = ScoutingAuth::from_config(&config);
+        let rendezvous = RendezvousState::from_config(&config);
+        #[cfg(feature = "transport_multicast")]
+        let dissemination = DisseminationState::from_config(&config)?;
+        #[cfg(not(feature = "transport_multicast"))]
+        if unwrap_or_default!(config.routing().dissemination().enabled()) {
+            bail!("The dissemination requires the `transport_multicast` feature");
+        }
+        let config_history = unwrap_or_default!(config.adminspace().config_history());
+        let events_history = unwrap_or_default!(config.adminspace().events_history());
+        let audit = AuditLog::new(config.adminspace().audit_log().as_deref())?;
         let config = Notifier::new(config);
+        config.set_history_size(config_history);
 
         let runtime = Runtime {
             state: Arc::new(RuntimeState {
@@ -145,8 +239,16 @@ impl Runtime {
                 manager: transport_manager,
                 transport_handlers: std::sync::RwLock::new(vec![]),
                 locators: std::sync::RwLock::new(vec![]),
+                listeners: std::sync::Mutex::new(vec![]),
                 hlc,
+                events: EventLog::new(events_history),
+                audit,
                 stop_source: std::sync::RwLock::new(Some(StopSource::new())),
+                #[cfg(feature = "scouting")]
+                scouting_auth,
+                rendezvous,
+                #[cfg(feature = "transport_multicast")]
+                dissemination,
             }),
         };
         *handler.runtime.write().unwrap() = Some(runtime.clone());
@@ -159,6 +261,8 @@ impl Runtime {
             gossip_multihop,
             autoconnect,
         );
+        #[cfg(feature = "transport_multicast")]
+        runtime.init_dissemination();
 
         let receiver = config.subscribe();
         runtime.spawn({
@@ -166,10 +270,55 @@ impl Runtime {
             async move {
                 let mut stream = receiver.into_stream();
                 while let Some(event) = stream.next().await {
-                    if &*event == "connect/endpoints" {
-                        if let Err(e) = runtime2.update_peers().await {
-                            log::error!("Error updating peers: {}", e);
+                    runtime2
+                        .events
+                        .record("config/change", json!({ "key": &*event }));
+                    match &*event {
+                        "connect" | "connect/endpoints" => {
+                            if let Err(e) = runtime2.update_peers().await {
+                                log::error!("Error updating peers: {}", e);
+                            }
+                        }
+                        "listen" | "listen/endpoints" => {
+                            if let Err(e) = runtime2.update_listeners().await {
+                                log::error!("Error updating listeners: {}", e);
+                            }
                         }
+                        key if key == "access_control" || key.starts_with("access_control/") => {
+                            let acl = AccessControl::new(runtime2.config.lock().access_control());
+                            zwrite!(runtime2.router.tables.tables).acl = acl;
+                            log::info!("Access control rules updated");
+                        }
+                        #[cfg(any(
+                            feature = "auth_pubkey",
+                            feature = "auth_usrpwd",
+                            feature = "auth_token"
+                        ))]
+                        key if key == "transport/auth" || key.starts_with("transport/auth/") => {
+                            let config = runtime2.config.lock().clone();
+                            if let Err(e) = runtime2.manager().reload_auth(&config).await {
+                                log::error!("Error reloading the authentication secrets: {}", e);
+                            }
+                        }
+                        key if key == "quotas" || key.starts_with("quotas/") => {
+                            let quotas = Quotas::new(runtime2.config.lock().quotas());
+                            zwrite!(runtime2.router.tables.tables).quotas = quotas;
+                            log::info!("Quotas updated");
+                        }
+                        key if key == "qos_overrides" || key.starts_with("qos_overrides/") => {
+                            let qos_overrides =
+                                QosOverrides::new(runtime2.config.lock().qos_overrides());
+                            zwrite!(runtime2.router.tables.tables).qos_overrides = qos_overrides;
+                            log::info!("QoS overrides updated");
+                        }
+                        key if key == "namespaces" || key.starts_with("namespaces/") => {
+                            let namespaces = Namespaces::new(runtime2.config.lock().namespaces());
+                            zwrite!(runtime2.router.tables.tables).namespaces = namespaces;
+                            log::info!("Namespaces updated, for the sessions opened from now on");
+                        }
+                        // The plugins are handled by the adminspace, the permissions are read for each write,
+                        // and the other settings can't be written through the adminspace (see `applies_at_runtime`)
+                        _ => {}
                     }
                 }
             }
@@ -227,6 +376,24 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
     ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
         match zread!(self.runtime).as_ref() {
             Some(runtime) => {
+                #[allow(unused_mut)]
+                let mut details = json!({
+                    "zid": peer.zid.to_string(),
+                    "whatami": peer.whatami.to_string(),
+                    "links": peer.links.iter().map(|link| link.dst.to_string()).collect::<Vec<_>>(),
+                });
+                #[cfg(any(
+                    feature = "auth_pubkey",
+                    feature = "auth_usrpwd",
+                    feature = "auth_token"
+                ))]
+                if let Ok(auth_ids) = transport.get_auth_ids() {
+                    details["auth_ids"] =
+                        json!(auth_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>());
+                }
+                runtime.events.record("session/open", details);
+                #[cfg(feature = "transport_multicast")]
+                runtime.offer_dissemination(&transport);
                 let slave_handlers: Vec<Arc<dyn TransportPeerEventHandler>> =
                     zread!(runtime.transport_handlers)
                         .iter()
@@ -245,6 +412,7 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
         }
     }
 
+    #[cfg(feature = "transport_multicast")]
     fn new_multicast(
         &self,
         transport: TransportMulticast,
@@ -291,6 +459,19 @@ impl TransportPeerEventHandler for RuntimeSession {
             );
             return Ok(());
         }
+        if let NetworkBody::OAM(oam) = &msg.body {
+            if oam.id == OAM_RENDEZVOUS {
+                self.runtime
+                    .handle_rendezvous(&self.main_handler.transport, oam.body.clone());
+                return Ok(());
+            }
+            #[cfg(feature = "transport_multicast")]
+            if oam.id == OAM_DISSEMINATION {
+                self.runtime
+                    .handle_dissemination(&self.main_handler.transport, oam.body.clone());
+                return Ok(());
+            }
+        }
 
         self.main_handler.handle_message(msg)
     }
@@ -318,6 +499,11 @@ impl TransportPeerEventHandler for RuntimeSession {
     }
 
     fn closed(&self) {
+        let face = &self.main_handler.face.state;
+        self.runtime.events.record(
+            "session/close",
+            json!({ "zid": face.zid.to_string(), "whatami": face.whatami.to_string() }),
+        );
         self.main_handler.closed();
         for handler in &self.slave_handlers {
             handler.closed();
@@ -329,12 +515,14 @@ impl TransportPeerEventHandler for RuntimeSession {
     }
 }
 
+#[cfg(feature = "transport_multicast")]
 pub(super) struct RuntimeMuticastGroup {
     pub(super) runtime: Runtime,
     pub(super) transport: TransportMulticast,
     pub(super) slave_handlers: Vec<Arc<dyn TransportMulticastEventHandler>>,
 }
 
+#[cfg(feature = "transport_multicast")]
 impl TransportMulticastEventHandler for RuntimeMuticastGroup {
     fn new_peer(&self, peer: TransportPeer) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
         let slave_handlers: Vec<Arc<dyn TransportPeerEventHandler>> = self
@@ -343,6 +531,7 @@ impl TransportMulticastEventHandler for RuntimeMuticastGroup {
             .filter_map(|handler| handler.new_peer(peer.clone()).ok())
             .collect();
         Ok(Arc::new(RuntimeMuticastSession {
+            runtime: self.runtime.clone(),
             main_handler: self
                 .runtime
                 .router
@@ -368,13 +557,22 @@ impl TransportMulticastEventHandler for RuntimeMuticastGroup {
     }
 }
 
+#[cfg(feature = "transport_multicast")]
 pub(super) struct RuntimeMuticastSession {
+    pub(super) runtime: Runtime,
     pub(super) main_handler: Arc<DeMux<Face>>,
     pub(super) slave_handlers: Vec<Arc<dyn TransportPeerEventHandler>>,
 }
 
+#[cfg(feature = "transport_multicast")]
 impl TransportPeerEventHandler for RuntimeMuticastSession {
     fn handle_message(&self, msg: NetworkMessage) -> ZResult<()> {
+        if let NetworkBody::OAM(oam) = &msg.body {
+            if oam.id == OAM_DISSEMINATION {
+                self.runtime.handle_dissemination_sample(oam.body.clone());
+                return Ok(());
+            }
+        }
         self.main_handler.handle_message(msg)
     }
 
@@ -410,3 +608,25 @@ impl TransportPeerEventHandler for RuntimeMuticastSession {
         self
     }
 }
+
+#[test]
+fn runtime_settings() {
+    for key in [
+        "connect",
+        "listen/endpoints",
+        "access_control/rules",
+        "plugins/rest/http_port",
+        "adminspace/permissions/write",
+    ] {
+        assert!(applies_at_runtime(key), "{key}");
+    }
+    for key in [
+        "mode",
+        "connect/proxy",
+        "scouting/multicast/enabled",
+        "quotasx",
+        "adminspace/config_history",
+    ] {
+        assert!(!applies_at_runtime(key), "{key}");
+    }
+}
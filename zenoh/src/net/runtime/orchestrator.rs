@@ -11,27 +11,41 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+#[cfg(feature = "scouting")]
+use super::scouting_auth::ScoutingAuth;
 use super::{Runtime, RuntimeSession};
-use async_std::net::UdpSocket;
 use async_std::prelude::FutureExt;
-use futures::prelude::*;
-use socket2::{Domain, Socket, Type};
-use std::net::{IpAddr, Ipv6Addr, SocketAddr};
-use std::time::Duration;
-use zenoh_buffers::reader::DidntRead;
-use zenoh_buffers::{reader::HasReader, writer::HasWriter};
-use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use serde_json::json;
+use std::time::{Duration, SystemTime};
 use zenoh_config::{unwrap_or_default, ModeDependent};
-use zenoh_link::{Locator, LocatorInspector};
+use zenoh_link::{config::RELAY, Locator, LocatorInspector};
 use zenoh_protocol::{
-    core::{whatami::WhatAmIMatcher, EndPoint, WhatAmI, ZenohId},
-    scouting::{Hello, Scout, ScoutingBody, ScoutingMessage},
+    common::ZExtBody,
+    core::{EndPoint, WhatAmI, ZenohId},
+    network::{oam, oam::id::OAM_RELAY, NetworkBody, NetworkMessage, Oam},
 };
 use zenoh_result::{bail, zerror, ZResult};
+use zenoh_transport::TransportUnicast;
+#[cfg(feature = "scouting")]
+use {
+    async_std::net::UdpSocket,
+    futures::prelude::*,
+    socket2::{Domain, Socket, Type},
+    std::net::{IpAddr, Ipv6Addr, SocketAddr},
+    zenoh_buffers::reader::DidntRead,
+    zenoh_buffers::{reader::HasReader, writer::HasWriter},
+    zenoh_codec::{RCodec, WCodec, Zenoh080},
+    zenoh_protocol::core::whatami::WhatAmIMatcher,
+    zenoh_protocol::scouting::{Hello, Scout, ScoutingBody, ScoutingMessage},
+};
 
+#[cfg(feature = "scouting")]
 const RCV_BUF_SIZE: usize = u16::MAX as usize;
+#[cfg(feature = "scouting")]
 const SCOUT_INITIAL_PERIOD: Duration = Duration::from_millis(1_000);
+#[cfg(feature = "scouting")]
 const SCOUT_MAX_PERIOD: Duration = Duration::from_millis(8_000);
+#[cfg(feature = "scouting")]
 const SCOUT_PERIOD_INCREASE_FACTOR: u32 = 2;
 const CONNECTION_TIMEOUT: Duration = Duration::from_millis(10_000);
 const CONNECTION_RETRY_INITIAL_PERIOD: Duration = Duration::from_millis(1_000);
@@ -66,6 +80,12 @@ impl Runtime {
             )
         };
         match peers.len() {
+            #[cfg(not(feature = "scouting"))]
+            0 => {
+                let _ = (scouting, addr, ifaces, timeout);
+                bail!("No peer specified and multicast scouting not supported in this build!")
+            }
+            #[cfg(feature = "scouting")]
             0 => {
                 if scouting {
                     log::info!("Scouting for router ...");
@@ -147,7 +167,13 @@ impl Runtime {
         }
 
         if scouting {
+            #[cfg(feature = "scouting")]
             self.start_scout(listen, autoconnect, addr, ifaces).await?;
+            #[cfg(not(feature = "scouting"))]
+            {
+                let _ = (listen, autoconnect, addr, ifaces);
+                log::debug!("Multicast scouting not supported in this build");
+            }
         }
         async_std::task::sleep(delay).await;
         Ok(())
@@ -191,12 +217,22 @@ impl Runtime {
         }
 
         if scouting {
+            #[cfg(feature = "scouting")]
             self.start_scout(listen, autoconnect, addr, ifaces).await?;
+            #[cfg(not(feature = "scouting"))]
+            {
+                let _ = (listen, autoconnect, addr, ifaces);
+                log::debug!("Multicast scouting not supported in this build");
+            }
         }
 
+        #[cfg(feature = "transport_multicast")]
+        self.start_dissemination().await;
+
         Ok(())
     }
 
+    #[cfg(feature = "scouting")]
     async fn start_scout(
         &self,
         listen: bool,
@@ -289,11 +325,46 @@ impl Runtime {
         Ok(())
     }
 
+    pub(crate) async fn update_listeners(&self) -> ZResult<()> {
+        let listeners = { self.config.lock().listen().endpoints().clone() };
+        if listeners.is_empty() {
+            // Keep the listeners opened with the mode's default endpoint
+            return Ok(());
+        }
+        // The listeners are diffed as configured, e.g. `tcp/0.0.0.0:0`, rather than as bound
+        let current = self.listeners.lock().unwrap().clone();
+
+        for (listener, locator) in current.iter().filter(|(l, _)| !listeners.contains(l)) {
+            match self.manager().del_listener(&locator.clone().into()).await {
+                Ok(()) => {
+                    self.listeners
+                        .lock()
+                        .unwrap()
+                        .retain(|(l, _)| l != listener);
+                    log::debug!("Listener removed: {}", listener)
+                }
+                Err(err) => log::error!("Unable to close listener {}: {}", listener, err),
+            }
+        }
+
+        let added = listeners
+            .into_iter()
+            .filter(|l| !current.iter().any(|(c, _)| c == l))
+            .collect::<Vec<_>>();
+        self.bind_listeners(&added).await
+    }
+
     async fn bind_listeners(&self, listeners: &[EndPoint]) -> ZResult<()> {
         for listener in listeners {
             let endpoint = listener.clone();
             match self.manager().add_listener(endpoint).await {
-                Ok(listener) => log::debug!("Listener added: {}", listener),
+                Ok(locator) => {
+                    log::debug!("Listener added: {}", locator);
+                    self.listeners
+                        .lock()
+                        .unwrap()
+                        .push((listener.clone(), locator));
+                }
                 Err(err) => {
                     log::error!("Unable to open listener {}: {}", listener, err);
                     return Err(err);
@@ -309,6 +380,7 @@ impl Runtime {
         Ok(())
     }
 
+    #[cfg(feature = "scouting")]
     pub fn get_interfaces(names: &str) -> Vec<IpAddr> {
         if names == "auto" {
             let ifaces = zenoh_util::net::get_multicast_interfaces();
@@ -343,6 +415,7 @@ impl Runtime {
         }
     }
 
+    #[cfg(feature = "scouting")]
     pub async fn bind_mcast_port(sockaddr: &SocketAddr, ifaces: &[IpAddr]) -> ZResult<UdpSocket> {
         let socket = match Socket::new(Domain::IPV4, Type::DGRAM, None) {
             Ok(socket) => socket,
@@ -418,6 +491,7 @@ impl Runtime {
         Ok(std::net::UdpSocket::from(socket).into())
     }
 
+    #[cfg(feature = "scouting")]
     pub fn bind_ucast_port(addr: IpAddr) -> ZResult<UdpSocket> {
         let socket = match Socket::new(Domain::IPV4, Type::DGRAM, None) {
             Ok(socket) => socket,
@@ -475,9 +549,10 @@ impl Runtime {
                             .as_any()
                             .downcast_ref::<super::RuntimeSession>()
                         {
-                            *zwrite!(orch_transport.endpoint) = Some(peer);
+                            *zwrite!(orch_transport.endpoint) = Some(peer.clone());
                         }
                     }
+                    Runtime::request_relay(&peer, &transport);
                     break;
                 }
                 Ok(Err(e)) => {
@@ -505,10 +580,31 @@ impl Runtime {
         }
     }
 
+    /// Asks a router connected through an endpoint with the [`RELAY`] option to relay the traffic
+    /// between this peer and the peers it cannot reach directly.
+    fn request_relay(peer: &EndPoint, transport: &TransportUnicast) {
+        if peer.config().get(RELAY) == Some("true")
+            && transport.get_whatami().ok() == Some(WhatAmI::Router)
+        {
+            let msg: NetworkMessage = NetworkBody::OAM(Oam {
+                id: OAM_RELAY,
+                body: ZExtBody::Unit,
+                ext_qos: oam::ext::QoSType::oam_default(),
+                ext_tstamp: None,
+            })
+            .into();
+            if let Err(e) = transport.schedule(msg) {
+                log::warn!("Unable to request relay from {}: {}", peer, e);
+            }
+        }
+    }
+
+    #[cfg(feature = "scouting")]
     pub async fn scout<Fut, F>(
         sockets: &[UdpSocket],
         matcher: WhatAmIMatcher,
         mcast_addr: &SocketAddr,
+        auth: Option<&ScoutingAuth>,
         f: F,
     ) where
         F: Fn(Hello) -> Fut + std::marker::Send + std::marker::Sync + Clone,
@@ -518,18 +614,24 @@ impl Runtime {
         let send = async {
             let mut delay = SCOUT_INITIAL_PERIOD;
 
-            let scout: ScoutingMessage = Scout {
+            let mut scout: ScoutingMessage = Scout {
                 version: zenoh_protocol::VERSION,
                 what: matcher,
                 zid: None,
+                ext_auth: None,
             }
             .into();
-            let mut wbuf = vec![];
-            let mut writer = wbuf.writer();
-            let codec = Zenoh080::new();
-            codec.write(&mut writer, &scout).unwrap();
 
             loop {
+                // Authenticated scouts are signed anew each time, as they can't be replayed
+                if let Some(auth) = auth {
+                    auth.sign(&mut scout);
+                }
+                let mut wbuf = vec![];
+                let mut writer = wbuf.writer();
+                let codec = Zenoh080::new();
+                codec.write(&mut writer, &scout).unwrap();
+
                 for socket in sockets {
                     log::trace!(
                         "Send {:?} to {} on interface {}",
@@ -572,6 +674,14 @@ impl Runtime {
                             let res: Result<ScoutingMessage, DidntRead> = codec.read(&mut reader);
                             if let Ok(msg) = res {
                                 log::trace!("Received {:?} from {}", msg.body, peer);
+                                if auth.map_or(false, |auth| !auth.verify(&msg)) {
+                                    log::debug!(
+                                        "Ignore unauthenticated {:?} from {}",
+                                        msg.body,
+                                        peer
+                                    );
+                                    continue;
+                                }
                                 if let ScoutingBody::Hello(hello) = &msg.body {
                                     if matcher.matches(hello.whatami) {
                                         if let Loop::Break = f(hello.clone()).await {
@@ -615,6 +725,9 @@ impl Runtime {
             let endpoint = locator.to_owned().into();
             let manager = self.manager();
             if is_multicast {
+                #[cfg(not(feature = "transport_multicast"))]
+                log::trace!("{} {} on {}: multicast not supported", ERR, zid, locator);
+                #[cfg(feature = "transport_multicast")]
                 match manager
                     .open_transport_multicast(endpoint)
                     .timeout(CONNECTION_TIMEOUT)
@@ -661,6 +774,9 @@ impl Runtime {
         let manager = self.manager();
         if zid != &manager.zid() {
             let has_unicast = manager.get_transport_unicast(zid).await.is_some();
+            #[cfg(not(feature = "transport_multicast"))]
+            let has_multicast = false;
+            #[cfg(feature = "transport_multicast")]
             let has_multicast = {
                 let mut hm = manager.get_transport_multicast(zid).await.is_some();
                 for t in manager.get_transports_multicast().await {
@@ -675,13 +791,16 @@ impl Runtime {
 
             if !has_unicast && !has_multicast {
                 log::debug!("Try to connect to peer {} via any of {:?}", zid, locators);
-                let _ = self.connect(zid, locators).await;
+                if !self.connect(zid, locators).await {
+                    self.rendezvous(zid).await;
+                }
             } else {
                 log::trace!("Already connected scouted peer: {}", zid);
             }
         }
     }
 
+    #[cfg(feature = "scouting")]
     async fn connect_first(
         &self,
         sockets: &[UdpSocket],
@@ -690,17 +809,23 @@ impl Runtime {
         timeout: std::time::Duration,
     ) -> ZResult<()> {
         let scout = async {
-            Runtime::scout(sockets, what, addr, move |hello| async move {
-                log::info!("Found {:?}", hello);
-                if !hello.locators.is_empty() {
-                    if self.connect(&hello.zid, &hello.locators).await {
-                        return Loop::Break;
+            Runtime::scout(
+                sockets,
+                what,
+                addr,
+                self.scouting_auth.as_ref(),
+                move |hello| async move {
+                    log::info!("Found {:?}", hello);
+                    if !hello.locators.is_empty() {
+                        if self.connect(&hello.zid, &hello.locators).await {
+                            return Loop::Break;
+                        }
+                    } else {
+                        log::warn!("Received Hello with no locators: {:?}", hello);
                     }
-                } else {
-                    log::warn!("Received Hello with no locators: {:?}", hello);
-                }
-                Loop::Continue
-            })
+                    Loop::Continue
+                },
+            )
             .await;
             Ok(())
         };
@@ -711,23 +836,31 @@ impl Runtime {
         async_std::prelude::FutureExt::race(scout, timeout).await
     }
 
+    #[cfg(feature = "scouting")]
     async fn connect_all(
         &self,
         ucast_sockets: &[UdpSocket],
         what: WhatAmIMatcher,
         addr: &SocketAddr,
     ) {
-        Runtime::scout(ucast_sockets, what, addr, move |hello| async move {
-            if !hello.locators.is_empty() {
-                self.connect_peer(&hello.zid, &hello.locators).await
-            } else {
-                log::warn!("Received Hello with no locators: {:?}", hello);
-            }
-            Loop::Continue
-        })
+        Runtime::scout(
+            ucast_sockets,
+            what,
+            addr,
+            self.scouting_auth.as_ref(),
+            move |hello| async move {
+                if !hello.locators.is_empty() {
+                    self.connect_peer(&hello.zid, &hello.locators).await
+                } else {
+                    log::warn!("Received Hello with no locators: {:?}", hello);
+                }
+                Loop::Continue
+            },
+        )
         .await
     }
 
+    #[cfg(feature = "scouting")]
     async fn responder(&self, mcast_socket: &UdpSocket, ucast_sockets: &[UdpSocket]) {
         fn get_best_match<'a>(addr: &IpAddr, sockets: &'a [UdpSocket]) -> Option<&'a UdpSocket> {
             fn octets(addr: &IpAddr) -> Vec<u8> {
@@ -770,6 +903,12 @@ impl Runtime {
             let res: Result<ScoutingMessage, DidntRead> = codec.read(&mut reader);
             if let Ok(msg) = res {
                 log::trace!("Received {:?} from {}", msg.body, peer);
+                if let Some(auth) = self.scouting_auth.as_ref() {
+                    if !auth.verify(&msg) {
+                        log::debug!("Ignore unauthenticated {:?} from {}", msg.body, peer);
+                        continue;
+                    }
+                }
                 if let ScoutingBody::Scout(Scout { what, .. }) = &msg.body {
                     if what.matches(self.whatami) {
                         let mut wbuf = vec![];
@@ -777,13 +916,17 @@ impl Runtime {
                         let codec = Zenoh080::new();
 
                         let zid = self.manager().zid();
-                        let hello: ScoutingMessage = Hello {
+                        let mut hello: ScoutingMessage = Hello {
                             version: zenoh_protocol::VERSION,
                             whatami: self.whatami,
                             zid,
                             locators: self.get_locators(),
+                            ext_auth: None,
                         }
                         .into();
+                        if let Some(auth) = self.scouting_auth.as_ref() {
+                            auth.sign(&mut hello);
+                        }
                         let socket = get_best_match(&peer.ip(), ucast_sockets).unwrap();
                         log::trace!(
                             "Send {:?} to {} on interface {}",
@@ -815,14 +958,36 @@ impl Runtime {
             WhatAmI::Client => {
                 let runtime = session.runtime.clone();
                 session.runtime.spawn(async move {
-                    let mut delay = CONNECTION_RETRY_INITIAL_PERIOD;
+                    let (mut delay, max_delay, increase_factor) = {
+                        let guard = runtime.config.lock();
+                        (
+                            Duration::from_millis(unwrap_or_default!(guard
+                                .connect()
+                                .retry()
+                                .period_init_ms())),
+                            Duration::from_millis(unwrap_or_default!(guard
+                                .connect()
+                                .retry()
+                                .period_max_ms())),
+                            unwrap_or_default!(guard.connect().retry().period_increase_factor()),
+                        )
+                    };
+                    let disconnected = SystemTime::now();
+                    let mut attempts = 1;
                     while runtime.start_client().await.is_err() {
                         async_std::task::sleep(delay).await;
-                        delay *= CONNECTION_RETRY_PERIOD_INCREASE_FACTOR;
-                        if delay > CONNECTION_RETRY_MAX_PERIOD {
-                            delay = CONNECTION_RETRY_MAX_PERIOD;
-                        }
+                        delay = (delay * increase_factor).min(max_delay);
+                        attempts += 1;
                     }
+                    let reconnected = SystemTime::now();
+                    runtime.events.record(
+                        "session/reconnect",
+                        json!({
+                            "disconnected": humantime::format_rfc3339(disconnected).to_string(),
+                            "reconnected": humantime::format_rfc3339(reconnected).to_string(),
+                            "attempts": attempts,
+                        }),
+                    );
                 });
             }
             _ => {
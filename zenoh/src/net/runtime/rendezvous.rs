@@ -0,0 +1,256 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::Runtime;
+use crate::net::codec::Zenoh080Routing;
+use crate::net::protocol::rendezvous::Rendezvous;
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_buffers::{reader::HasReader, writer::HasWriter, ZBuf};
+use zenoh_codec::{RCodec, WCodec};
+use zenoh_config::{unwrap_or_default, Config};
+use zenoh_core::zlock;
+use zenoh_link::{config::PUNCH, EndPoint, Locator};
+use zenoh_protocol::common::ZExtBody;
+use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_protocol::network::oam::id::OAM_RENDEZVOUS;
+use zenoh_protocol::network::{oam, NetworkBody, NetworkMessage, Oam};
+use zenoh_result::{zerror, ZResult};
+use zenoh_transport::TransportUnicast;
+
+/// The period at which the probes are sent while punching.
+const PUNCH_PERIOD: Duration = Duration::from_millis(250);
+/// How long the opener waits for a transport to be established before retrying.
+const PUNCH_OPEN_TIMEOUT: Duration = Duration::from_millis(1_000);
+/// The protocols hole punching can be attempted with.
+const PUNCH_PROTOCOLS: [&str; 2] = ["udp", "quic"];
+
+/// Brokers and performs NAT traversal between peers.
+///
+/// A peer that fails to connect to another one asks the routers it is connected to for a rendezvous.
+/// A router connected to both peers sends to each of them the endpoints it observed for the other one.
+/// Both peers then punch: the one with the lowest [`ZenohId`] repeatedly opens a transport towards
+/// the other one, which sends probes to open its own NAT binding in the meantime.
+/// When punching fails, the peers keep on communicating through the router.
+pub struct RendezvousState {
+    timeout: Duration,
+    // The peers a rendezvous has been requested with
+    requested: Mutex<HashSet<ZenohId>>,
+    // The peers a hole punching is in progress with
+    punching: Mutex<HashSet<ZenohId>>,
+}
+
+impl RendezvousState {
+    pub fn from_config(config: &Config) -> Option<Self> {
+        unwrap_or_default!(config.scouting().rendezvous().enabled()).then(|| RendezvousState {
+            timeout: Duration::from_millis(unwrap_or_default!(config
+                .scouting()
+                .rendezvous()
+                .timeout())),
+            requested: Mutex::new(HashSet::new()),
+            punching: Mutex::new(HashSet::new()),
+        })
+    }
+}
+
+fn make_msg(rendezvous: &Rendezvous) -> ZResult<NetworkMessage> {
+    let codec = Zenoh080Routing::new();
+    let mut buf = ZBuf::empty();
+    codec
+        .write(&mut buf.writer(), rendezvous)
+        .map_err(|_| zerror!("Failed to encode Rendezvous message"))?;
+    Ok(NetworkBody::OAM(Oam {
+        id: OAM_RENDEZVOUS,
+        body: ZExtBody::ZBuf(buf),
+        ext_qos: oam::ext::QoSType::oam_default(),
+        ext_tstamp: None,
+    })
+    .into())
+}
+
+/// The endpoints of the links of a transport, as observed by the local end, that can be punched.
+fn observed_locators(transport: &TransportUnicast) -> Vec<Locator> {
+    transport
+        .get_links()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|link| link.dst)
+        .filter(|l| PUNCH_PROTOCOLS.contains(&l.protocol().as_str()))
+        .collect()
+}
+
+impl Runtime {
+    /// Asks the connected routers for a rendezvous with a peer that could not be reached directly.
+    pub(crate) async fn rendezvous(&self, zid: &ZenohId) {
+        let Some(state) = self.rendezvous.as_ref() else {
+            return;
+        };
+        if zlock!(state.punching).contains(zid) || !zlock!(state.requested).insert(*zid) {
+            return;
+        }
+
+        let msg = make_msg(&Rendezvous {
+            request: true,
+            zid: *zid,
+            locators: vec![],
+        });
+        let mut sent = false;
+        if let Ok(msg) = msg {
+            for transport in self.manager().get_transports_unicast().await {
+                if matches!(transport.get_whatami(), Ok(WhatAmI::Router)) {
+                    log::debug!(
+                        "Request rendezvous with {} to {:?}",
+                        zid,
+                        transport.get_zid()
+                    );
+                    sent |= transport.schedule(msg.clone()).is_ok();
+                }
+            }
+        }
+        if !sent {
+            zlock!(state.requested).remove(zid);
+            return;
+        }
+
+        // Let a new rendezvous be requested once this one is over
+        let runtime = self.clone();
+        let zid = *zid;
+        let timeout = state.timeout;
+        self.spawn(async move {
+            task::sleep(timeout).await;
+            if let Some(state) = runtime.rendezvous.as_ref() {
+                zlock!(state.requested).remove(&zid);
+            }
+        });
+    }
+
+    pub(super) fn handle_rendezvous(&self, transport: &TransportUnicast, body: ZExtBody) {
+        let Some(state) = self.rendezvous.as_ref() else {
+            return;
+        };
+        let ZExtBody::ZBuf(buf) = body else {
+            return;
+        };
+        let codec = Zenoh080Routing::new();
+        let rendezvous: Rendezvous = match codec.read(&mut buf.reader()) {
+            Ok(rendezvous) => rendezvous,
+            Err(_) => {
+                log::warn!("Received invalid Rendezvous from {:?}", transport.get_zid());
+                return;
+            }
+        };
+
+        if rendezvous.request {
+            if self.whatami != WhatAmI::Router {
+                return;
+            }
+            let runtime = self.clone();
+            let transport = transport.clone();
+            self.spawn(async move { runtime.broker(&transport, &rendezvous.zid).await });
+        } else if !rendezvous.locators.is_empty() {
+            // Only the first rendezvous is honoured when several routers answer
+            if !zlock!(state.punching).insert(rendezvous.zid) {
+                return;
+            }
+            let runtime = self.clone();
+            self.spawn(async move { runtime.punch(&rendezvous.zid, &rendezvous.locators).await });
+        }
+    }
+
+    async fn broker(&self, transport: &TransportUnicast, zid: &ZenohId) {
+        let Ok(src) = transport.get_zid() else {
+            return;
+        };
+        let Some(dst) = self.manager().get_transport_unicast(zid).await else {
+            log::debug!("Can not broker rendezvous of {} with unknown {}", src, zid);
+            return;
+        };
+        let (src_locators, dst_locators) = (observed_locators(transport), observed_locators(&dst));
+        if src_locators.is_empty() || dst_locators.is_empty() {
+            log::debug!(
+                "Can not broker rendezvous of {} with {}: no endpoint to punch",
+                src,
+                zid
+            );
+            return;
+        }
+
+        log::debug!(
+            "Broker rendezvous of {} ({:?}) with {} ({:?})",
+            src,
+            src_locators,
+            zid,
+            dst_locators
+        );
+        for (t, zid, locators) in [(transport, *zid, dst_locators), (&dst, src, src_locators)] {
+            let msg = make_msg(&Rendezvous {
+                request: false,
+                zid,
+                locators,
+            });
+            if let Err(e) = msg.and_then(|msg| t.schedule(msg)) {
+                log::debug!("Error sending Rendezvous: {}", e);
+            }
+        }
+    }
+
+    async fn punch(&self, zid: &ZenohId, locators: &[Locator]) {
+        let opener = self.zid < *zid;
+        let deadline = Instant::now() + self.rendezvous.as_ref().unwrap().timeout;
+        log::debug!(
+            "Punch towards {} via {:?} ({})",
+            zid,
+            locators,
+            if opener { "opener" } else { "responder" }
+        );
+
+        let mut connected = false;
+        while !connected && Instant::now() < deadline {
+            for locator in locators {
+                let mut endpoint: EndPoint = locator.clone().into();
+                if opener {
+                    if endpoint.config_mut().insert(PUNCH, "true").is_err() {
+                        continue;
+                    }
+                    let res = self
+                        .manager()
+                        .open_transport_unicast(endpoint)
+                        .timeout(PUNCH_OPEN_TIMEOUT)
+                        .await;
+                    if let Ok(Ok(_)) = res {
+                        break;
+                    }
+                } else if let Err(e) = self.manager().punch_unicast(endpoint).await {
+                    log::trace!("Unable to punch towards {} via {}: {}", zid, locator, e);
+                }
+            }
+            task::sleep(PUNCH_PERIOD).await;
+            connected = self.manager().get_transport_unicast(zid).await.is_some();
+        }
+
+        if connected {
+            log::debug!("Punched through towards {}", zid);
+        } else {
+            log::debug!(
+                "Unable to punch through towards {}: keep on routing through routers",
+                zid
+            );
+        }
+        if let Some(state) = self.rendezvous.as_ref() {
+            zlock!(state.punching).remove(zid);
+        }
+    }
+}
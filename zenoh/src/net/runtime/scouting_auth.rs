@@ -0,0 +1,167 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zenoh_buffers::{writer::HasWriter, SplitBuffer};
+use zenoh_codec::{WCodec, Zenoh080};
+use zenoh_config::Config;
+use zenoh_core::zlock;
+use zenoh_crypto::hmac;
+use zenoh_protocol::scouting::{hello, scout, ScoutingBody, ScoutingMessage};
+
+/// The maximum difference between the timestamp of an authenticated scouting message and the local time.
+const MAX_AGE: Duration = Duration::from_secs(10);
+const TIMESTAMP_SIZE: usize = 8;
+
+/// Authenticates the scouting messages with an HMAC computed with a pre-shared key.
+///
+/// The auth extension of a message holds the time it was sent at (in microseconds since the UNIX epoch,
+/// little endian) followed by the HMAC of the message without the extension and of this time.
+/// A message is rejected if its time is more than [`MAX_AGE`] away from the local time, or if
+/// it was already received: messages can't be replayed.
+pub struct ScoutingAuth {
+    key: Vec<u8>,
+    // The HMACs of the messages received in the last MAX_AGE, with their time
+    seen: Mutex<HashMap<Vec<u8>, u64>>,
+}
+
+impl ScoutingAuth {
+    pub fn new(key: &str) -> Self {
+        ScoutingAuth {
+            key: key.as_bytes().to_vec(),
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Option<Self> {
+        config
+            .scouting()
+            .multicast()
+            .auth_key()
+            .as_deref()
+            .map(ScoutingAuth::new)
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64
+    }
+
+    fn hmac(&self, msg: &ScoutingMessage, time: u64) -> Vec<u8> {
+        let mut body = msg.body.clone();
+        match &mut body {
+            ScoutingBody::Scout(scout) => scout.ext_auth = None,
+            ScoutingBody::Hello(hello) => hello.ext_auth = None,
+        }
+        let mut data = vec![];
+        let mut writer = data.writer();
+        Zenoh080::new()
+            .write(&mut writer, &ScoutingMessage::from(body))
+            .unwrap();
+        data.extend_from_slice(&time.to_le_bytes());
+        hmac::sign(&self.key, &data).unwrap()
+    }
+
+    /// Sets the auth extension of the given message.
+    pub fn sign(&self, msg: &mut ScoutingMessage) {
+        let time = Self::now();
+        let mut value = time.to_le_bytes().to_vec();
+        value.extend(self.hmac(msg, time));
+        match &mut msg.body {
+            ScoutingBody::Scout(scout) => {
+                scout.ext_auth = Some(scout::ext::Auth::new(value.into()));
+            }
+            ScoutingBody::Hello(hello) => {
+                hello.ext_auth = Some(hello::ext::Auth::new(value.into()));
+            }
+        }
+    }
+
+    /// Returns whether the given message is authenticated, fresh and not already received.
+    pub fn verify(&self, msg: &ScoutingMessage) -> bool {
+        let value = match &msg.body {
+            ScoutingBody::Scout(scout) => scout.ext_auth.as_ref().map(|ext| &ext.value),
+            ScoutingBody::Hello(hello) => hello.ext_auth.as_ref().map(|ext| &ext.value),
+        };
+        let value = match value {
+            Some(value) => value.contiguous(),
+            None => return false,
+        };
+        if value.len() <= TIMESTAMP_SIZE {
+            return false;
+        }
+        let (time, mac) = value.split_at(TIMESTAMP_SIZE);
+        let time = u64::from_le_bytes(time.try_into().unwrap());
+        let now = Self::now();
+        let max_age = MAX_AGE.as_micros() as u64;
+        if time.abs_diff(now) > max_age {
+            return false;
+        }
+        let expected = self.hmac(msg, time);
+        if expected.len() != mac.len()
+            || expected
+                .iter()
+                .zip(mac)
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                != 0
+        {
+            return false;
+        }
+        let mut seen = zlock!(self.seen);
+        seen.retain(|_, time| time.abs_diff(now) <= max_age);
+        seen.insert(expected, time).is_none()
+    }
+}
+
+#[test]
+fn scouting_auth() {
+    use zenoh_protocol::core::{WhatAmI, ZenohId};
+    use zenoh_protocol::scouting::Hello;
+
+    let hello = || -> ScoutingMessage {
+        Hello {
+            version: zenoh_protocol::VERSION,
+            whatami: WhatAmI::Router,
+            zid: ZenohId::rand(),
+            locators: vec!["tcp/127.0.0.1:7447".parse().unwrap()],
+            ext_auth: None,
+        }
+        .into()
+    };
+    let auth = ScoutingAuth::new("secret");
+
+    let mut msg = hello();
+    assert!(!auth.verify(&msg));
+    auth.sign(&mut msg);
+    assert!(auth.verify(&msg));
+    // Replayed
+    assert!(!auth.verify(&msg));
+
+    // Signed with another key
+    let mut msg = hello();
+    ScoutingAuth::new("other").sign(&mut msg);
+    assert!(!auth.verify(&msg));
+
+    // Tampered with
+    let mut msg = hello();
+    auth.sign(&mut msg);
+    if let ScoutingBody::Hello(hello) = &mut msg.body {
+        hello.locators = vec!["tcp/10.0.0.1:7447".parse().unwrap()];
+    }
+    assert!(!auth.verify(&msg));
+}
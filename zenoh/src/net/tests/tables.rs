@@ -26,7 +26,8 @@ use zenoh_protocol::core::{
 use zenoh_protocol::network::declare::subscriber::ext::SubscriberInfo;
 use zenoh_protocol::network::declare::Mode;
 use zenoh_protocol::network::{ext, Declare, DeclareBody, DeclareKeyExpr};
-use zenoh_protocol::zenoh::{PushBody, Put};
+use zenoh_protocol::zenoh::put::ext::BatchType;
+use zenoh_protocol::zenoh::{ext::BatchEntry, PushBody, Put};
 use zenoh_transport::{DummyPrimitives, Primitives};
 
 #[test]
@@ -73,6 +74,7 @@ fn base_test() {
         &mut face.upgrade().unwrap(),
         &WireExpr::from(1).with_suffix("four/five"),
         &sub_info,
+        None,
     );
 
     Tables::print(&zread!(tables.tables));
@@ -165,7 +167,7 @@ fn match_test() {
         for key_expr2 in key_exprs.iter() {
             if res_matches
                 .iter()
-                .map(|m| m.upgrade().unwrap().expr())
+                .map(|m| m.upgrade().unwrap().expr().to_string())
                 .any(|x| x.as_str() == key_expr2.as_str())
             {
                 assert!(dbg!(dbg!(key_expr1).intersects(dbg!(key_expr2))));
@@ -260,6 +262,7 @@ fn clean_test() {
         &mut face0.upgrade().unwrap(),
         &"todrop1/todrop11".into(),
         &sub_info,
+        None,
     );
     let optres2 = Resource::get_resource(zread!(tables.tables)._get_root(), "todrop1/todrop11")
         .map(|res| Arc::downgrade(&res));
@@ -273,6 +276,7 @@ fn clean_test() {
         &mut face0.upgrade().unwrap(),
         &WireExpr::from(1).with_suffix("/todrop12"),
         &sub_info,
+        None,
     );
     let optres3 = Resource::get_resource(zread!(tables.tables)._get_root(), "todrop1/todrop12")
         .map(|res| Arc::downgrade(&res));
@@ -313,6 +317,7 @@ fn clean_test() {
         &mut face0.upgrade().unwrap(),
         &"todrop3".into(),
         &sub_info,
+        None,
     );
     let optres1 = Resource::get_resource(zread!(tables.tables)._get_root(), "todrop3")
         .map(|res| Arc::downgrade(&res));
@@ -340,6 +345,7 @@ fn clean_test() {
         &mut face0.upgrade().unwrap(),
         &"todrop5".into(),
         &sub_info,
+        None,
     );
     declare_client_subscription(
         &tables,
@@ -347,6 +353,7 @@ fn clean_test() {
         &mut face0.upgrade().unwrap(),
         &"todrop6".into(),
         &sub_info,
+        None,
     );
 
     let optres1 = Resource::get_resource(zread!(tables.tables)._get_root(), "todrop4")
@@ -499,6 +506,7 @@ fn client_test() {
         &mut face0.upgrade().unwrap(),
         &WireExpr::from(11).with_suffix("/**"),
         &sub_info,
+        None,
     );
     register_expr(
         &tables,
@@ -543,6 +551,7 @@ fn client_test() {
         &mut face1.upgrade().unwrap(),
         &WireExpr::from(21).with_suffix("/**"),
         &sub_info,
+        None,
     );
     register_expr(
         &tables,
@@ -587,6 +596,7 @@ fn client_test() {
         &mut face2.upgrade().unwrap(),
         &WireExpr::from(31).with_suffix("/**"),
         &sub_info,
+        None,
     );
 
     primitives0.clear_data();
@@ -604,6 +614,9 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -636,6 +649,9 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -668,6 +684,9 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -700,6 +719,9 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -732,6 +754,9 @@ fn client_test() {
             ext_sinfo: None,
             #[cfg(feature = "shared-memory")]
             ext_shm: None,
+            ext_trace: None,
+            ext_deadline: None,
+            ext_batch: None,
             ext_unknown: vec![],
             payload: ZBuf::empty(),
         }),
@@ -750,3 +775,370 @@ fn client_test() {
     // mapping strategy check
     // assert_eq!(primitives2.get_last_key().unwrap(), KeyExpr::IdWithSuffix(31, "/z2_pub1".to_string()));
 }
+
+#[derive(Default)]
+struct QueryablePrimitives {
+    requests: std::sync::atomic::AtomicUsize,
+}
+
+impl Primitives for QueryablePrimitives {
+    fn send_declare(&self, _msg: zenoh_protocol::network::Declare) {}
+
+    fn send_push(&self, _msg: zenoh_protocol::network::Push) {}
+
+    fn send_request(&self, _msg: zenoh_protocol::network::Request) {
+        self.requests
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    fn send_response(&self, _msg: zenoh_protocol::network::Response) {}
+
+    fn send_response_final(&self, _msg: zenoh_protocol::network::ResponseFinal) {}
+
+    fn send_close(&self) {}
+}
+
+#[test]
+fn complete_storage_test() {
+    use zenoh_protocol::core::QueryTarget;
+    use zenoh_protocol::network::declare::queryable::ext::QueryableInfo;
+    use zenoh_protocol::zenoh::{Query, RequestBody};
+
+    let tables = Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    });
+
+    // The storages and the key expressions they are complete or incomplete on
+    let storages = [
+        ("storage/a/**", 1),
+        ("storage/a/**", 1),
+        ("storage/a/b/**", 1),
+        ("storage/c/**", 1),
+        ("storage/**", 0),
+    ];
+    let primitives = storages
+        .iter()
+        .map(|(expr, complete)| {
+            let primitives = Arc::new(QueryablePrimitives::default());
+            let face = zwrite!(tables.tables).open_face(
+                ZenohId::try_from([1]).unwrap(),
+                WhatAmI::Client,
+                primitives.clone(),
+            );
+            declare_client_queryable(
+                &tables,
+                zread!(tables.tables),
+                &mut face.upgrade().unwrap(),
+                &(*expr).into(),
+                &QueryableInfo {
+                    complete: *complete,
+                    distance: 0,
+                },
+            );
+            primitives
+        })
+        .collect::<Vec<_>>();
+    let querier = zwrite!(tables.tables).open_face(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        Arc::new(DummyPrimitives::new()),
+    );
+
+    let query = |expr: &str| {
+        for primitives in &primitives {
+            primitives
+                .requests
+                .store(0, std::sync::atomic::Ordering::Relaxed);
+        }
+        route_query(
+            &tables,
+            &querier.upgrade().unwrap(),
+            &expr.into(),
+            0,
+            QueryTarget::CompleteStorage,
+            RequestBody::Query(Query {
+                parameters: String::new(),
+                ext_sinfo: None,
+                ext_consolidation: Default::default(),
+                ext_body: None,
+                ext_unknown: vec![],
+            }),
+            0,
+        );
+        primitives
+            .iter()
+            .map(|primitives| {
+                primitives
+                    .requests
+                    .load(std::sync::atomic::Ordering::Relaxed)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // A single storage covering the whole selector is queried
+    assert_eq!(query("storage/a/b/c").iter().sum::<usize>(), 1);
+    // The storages covering distinct parts of the selector are queried, once per part
+    let requests = query("storage/**");
+    assert_eq!(requests[0] + requests[1], 1);
+    assert_eq!(requests[2..], [0, 1, 0]);
+    // Nothing is queried when no storage is complete on the selector
+    assert_eq!(query("other/**").iter().sum::<usize>(), 0);
+}
+
+#[test]
+fn content_filter_test() {
+    use crate::net::routing::content_filter::ContentFilter;
+
+    let tables = TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    };
+    let sub_info = SubscriberInfo {
+        reliability: Reliability::Reliable,
+        mode: Mode::Push,
+    };
+    let open_face = || {
+        let primitives = Arc::new(ClientPrimitives::new());
+        let face = zwrite!(tables.tables).open_face(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            primitives.clone(),
+        );
+        (primitives, face)
+    };
+    let publish_on = |face: &std::sync::Weak<crate::net::routing::face::FaceState>,
+                      key_expr: &str,
+                      payload: &str,
+                      ext_batch: Option<BatchType>| {
+        full_reentrant_route_data(
+            &tables.tables,
+            &face.upgrade().unwrap(),
+            &key_expr.into(),
+            ext::QoSType::default(),
+            PushBody::Put(Put {
+                timestamp: None,
+                encoding: Encoding::APP_JSON,
+                ext_sinfo: None,
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_trace: None,
+                ext_deadline: None,
+                ext_batch,
+                ext_unknown: vec![],
+                payload: ZBuf::from(payload.as_bytes().to_vec()),
+            }),
+            0,
+        )
+    };
+    let publish = |face: &std::sync::Weak<crate::net::routing::face::FaceState>, payload: &str| {
+        publish_on(face, "sensors/engine", payload, None)
+    };
+    // A batch has an empty payload, each of its values being filtered along with its own key
+    let publish_batch = |face: &std::sync::Weak<crate::net::routing::face::FaceState>,
+                         values: &[(&str, &str)]| {
+        let entries = values
+            .iter()
+            .map(|(key, payload)| BatchEntry {
+                key: key.to_string(),
+                encoding: Encoding::APP_JSON,
+                payload: ZBuf::from(payload.as_bytes().to_vec()),
+            })
+            .collect();
+        publish_on(face, "sensors/*", "", Some(BatchType { entries }))
+    };
+
+    let (_, publisher) = open_face();
+    let (filtered, filtered_face) = open_face();
+    declare_client_subscription(
+        &tables,
+        zread!(tables.tables),
+        &mut filtered_face.upgrade().unwrap(),
+        &"sensors/**".into(),
+        &sub_info,
+        ContentFilter::new("temp > 90").ok(),
+    );
+
+    publish(&publisher, r#"{"temp": 20}"#);
+    assert!(filtered.get_last_name().is_none());
+    publish(&publisher, r#"{"temp": 95}"#);
+    assert_eq!(filtered.get_last_name().unwrap(), "sensors/engine");
+
+    filtered.clear_data();
+    publish_batch(
+        &publisher,
+        &[
+            ("sensors/engine", r#"{"temp": 20}"#),
+            ("sensors/cabin", r#"{"temp": 18}"#),
+        ],
+    );
+    assert!(filtered.get_last_name().is_none());
+    publish_batch(
+        &publisher,
+        &[
+            ("sensors/engine", r#"{"temp": 95}"#),
+            ("sensors/cabin", r#"{"temp": 18}"#),
+        ],
+    );
+    assert_eq!(filtered.get_last_name().unwrap(), "sensors/*");
+
+    let (unfiltered, face) = open_face();
+    declare_client_subscription(
+        &tables,
+        zread!(tables.tables),
+        &mut face.upgrade().unwrap(),
+        &"sensors/engine".into(),
+        &sub_info,
+        None,
+    );
+
+    filtered.clear_data();
+    publish(&publisher, r#"{"temp": 20}"#);
+    assert!(filtered.get_last_name().is_none());
+    assert_eq!(unfiltered.get_last_name().unwrap(), "sensors/engine");
+
+    // A subscription without filter of the same face only lets pass the publications it matches
+    for (key_expr, admitted) in [("sensors/cabin", false), ("sensors/engine", true)] {
+        declare_client_subscription(
+            &tables,
+            zread!(tables.tables),
+            &mut filtered_face.upgrade().unwrap(),
+            &key_expr.into(),
+            &sub_info,
+            None,
+        );
+        filtered.clear_data();
+        publish(&publisher, r#"{"temp": 20}"#);
+        assert_eq!(filtered.get_last_name().is_some(), admitted, "{key_expr}");
+    }
+}
+
+#[derive(Default)]
+struct ReplyPrimitives {
+    requests: Mutex<Vec<zenoh_protocol::network::RequestId>>,
+    priorities: Mutex<Vec<zenoh_protocol::core::Priority>>,
+}
+
+impl Primitives for ReplyPrimitives {
+    fn send_declare(&self, _msg: zenoh_protocol::network::Declare) {}
+
+    fn send_push(&self, _msg: zenoh_protocol::network::Push) {}
+
+    fn send_request(&self, msg: zenoh_protocol::network::Request) {
+        zlock!(self.requests).push(msg.id);
+    }
+
+    fn send_response(&self, msg: zenoh_protocol::network::Response) {
+        zlock!(self.priorities).push(msg.ext_qos.get_priority());
+    }
+
+    fn send_response_final(&self, msg: zenoh_protocol::network::ResponseFinal) {
+        zlock!(self.priorities).push(msg.ext_qos.get_priority());
+    }
+
+    fn send_close(&self) {}
+}
+
+#[test]
+fn reply_priority_test() {
+    use zenoh_protocol::core::{CongestionControl, Priority, QueryTarget};
+    use zenoh_protocol::network::declare::queryable::ext::QueryableInfo;
+    use zenoh_protocol::network::response;
+    use zenoh_protocol::zenoh::{Query, Reply, RequestBody, ResponseBody};
+
+    let tables = Arc::new(TablesLock {
+        tables: RwLock::new(Tables::new(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            Some(Arc::new(HLC::default())),
+            false,
+            true,
+            Duration::from_millis(queries_default_timeout),
+        )),
+        ctrl_lock: Mutex::new(()),
+        queries_lock: RwLock::new(()),
+    });
+    let open_face = || {
+        let primitives = Arc::new(ReplyPrimitives::default());
+        let face = zwrite!(tables.tables).open_face(
+            ZenohId::try_from([1]).unwrap(),
+            WhatAmI::Client,
+            primitives.clone(),
+        );
+        (primitives, face.upgrade().unwrap())
+    };
+
+    let (storage, mut storage_face) = open_face();
+    declare_client_queryable(
+        &tables,
+        zread!(tables.tables),
+        &mut storage_face.clone(),
+        &"storage/**".into(),
+        &QueryableInfo {
+            complete: 1,
+            distance: 0,
+        },
+    );
+    let (querier, querier_face) = open_face();
+    route_query(
+        &tables,
+        &querier_face,
+        &"storage/**".into(),
+        0,
+        QueryTarget::BestMatching,
+        RequestBody::Query(Query {
+            parameters: String::new(),
+            ext_sinfo: None,
+            ext_consolidation: Default::default(),
+            ext_body: None,
+            ext_unknown: vec![],
+        }),
+        0,
+    );
+    let qid = zlock!(storage.requests)[0];
+
+    let reply = |priority| {
+        route_send_response(
+            &tables,
+            &mut storage_face.clone(),
+            qid,
+            response::ext::QoSType::new(priority, CongestionControl::Block, false),
+            None,
+            "storage/a".into(),
+            ResponseBody::Reply(Reply {
+                timestamp: None,
+                encoding: Encoding::default(),
+                ext_sinfo: None,
+                ext_consolidation: Default::default(),
+                #[cfg(feature = "shared-memory")]
+                ext_shm: None,
+                ext_unknown: vec![],
+                payload: ZBuf::empty(),
+            }),
+        )
+    };
+    // The replies keep their priority, and the final reply doesn't overtake the lowest priority replies
+    reply(Priority::DataLow);
+    reply(Priority::Data);
+    route_send_response_final(&tables, &mut storage_face, qid);
+    assert_eq!(
+        *zlock!(querier.priorities),
+        [Priority::DataLow, Priority::Data, Priority::DataLow]
+    );
+}
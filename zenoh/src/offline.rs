@@ -0,0 +1,344 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The buffering of the publications of a client while it isn't connected to any router.
+//!
+//! Once reconnected, the buffered publications are sent in order, before any new publication.
+//! When kept in a file, the buffered publications that were being sent when the application
+//! stopped are sent again on restart.
+use crate::config::{unwrap_or_default, Config};
+use crate::keyexpr;
+use crate::net::routing::face::Face;
+use crate::net::transport::Primitives;
+use crate::session::SessionState;
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uhlc::{Timestamp, NTP64};
+use zenoh_buffers::reader::{HasReader, Reader};
+use zenoh_buffers::writer::HasWriter;
+use zenoh_codec::{RCodec, WCodec, Zenoh080};
+use zenoh_core::{zlock, zread};
+use zenoh_protocol::core::{WhatAmI, WireExpr, ZenohId};
+use zenoh_protocol::network::{NetworkMessage, Push};
+use zenoh_protocol::zenoh::PushBody;
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    TransportEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+#[cfg(feature = "transport_multicast")]
+use zenoh_transport::{TransportMulticast, TransportMulticastEventHandler};
+
+struct State {
+    // the number of routers (or peers) the client is connected to
+    connected: usize,
+    replaying: bool,
+    queue: VecDeque<Push>,
+    // the number of publications at the start of the file that were already sent or dropped
+    file_skip: usize,
+}
+
+pub(crate) struct OfflineBuffer {
+    zid: ZenohId,
+    capacity: usize,
+    file: Option<PathBuf>,
+    replay_delay: Duration,
+    state: Mutex<State>,
+}
+
+impl OfflineBuffer {
+    pub(crate) fn from_config(
+        config: &Config,
+        whatami: WhatAmI,
+        zid: ZenohId,
+    ) -> Option<Arc<Self>> {
+        if whatami != WhatAmI::Client
+            || !unwrap_or_default!(config.connect().offline_buffer().enabled())
+        {
+            return None;
+        }
+        let buffer = OfflineBuffer {
+            zid,
+            capacity: unwrap_or_default!(config.connect().offline_buffer().capacity()).max(1),
+            file: config
+                .connect()
+                .offline_buffer()
+                .file()
+                .as_ref()
+                .map(PathBuf::from),
+            replay_delay: Duration::from_millis(unwrap_or_default!(config
+                .connect()
+                .offline_buffer()
+                .replay_delay_ms())),
+            state: Mutex::new(State {
+                connected: 0,
+                replaying: false,
+                queue: VecDeque::new(),
+                file_skip: 0,
+            }),
+        };
+        if let Some(file) = &buffer.file {
+            match load(file) {
+                Ok(mut queue) => {
+                    let mut state = zlock!(buffer.state);
+                    while queue.len() > buffer.capacity {
+                        queue.pop_front();
+                        state.file_skip += 1;
+                    }
+                    if !queue.is_empty() {
+                        log::info!(
+                            "Loaded {} publications buffered while disconnected from {}",
+                            queue.len(),
+                            file.display()
+                        );
+                    }
+                    state.queue = queue;
+                }
+                Err(e) => log::error!(
+                    "Unable to load the offline buffer {}: {}",
+                    file.display(),
+                    e
+                ),
+            }
+        }
+        Some(Arc::new(buffer))
+    }
+
+    /// Sends a publication on `key_expr`, or buffers it while disconnected or while the buffered
+    /// publications are being sent.
+    pub(crate) fn send(&self, primitives: &Face, key_expr: &keyexpr, mut push: Push) {
+        let mut state = zlock!(self.state);
+        if state.connected > 0 && !state.replaying && state.queue.is_empty() {
+            drop(state);
+            primitives.send_push(push);
+            return;
+        }
+        // the numerical ids of the key expressions declared by the session may not survive a restart
+        push.wire_expr = WireExpr::from(key_expr).to_owned();
+        let timestamp = match &mut push.payload {
+            PushBody::Put(put) => &mut put.timestamp,
+            PushBody::Del(del) => &mut del.timestamp,
+        };
+        timestamp.get_or_insert_with(|| self.new_timestamp());
+        if state.queue.len() >= self.capacity {
+            state.queue.pop_front();
+            state.file_skip += 1;
+            log::warn!("Offline buffer full: dropping the oldest buffered publication");
+        }
+        if let Some(file) = &self.file {
+            if let Err(e) = append(file, &push) {
+                log::error!(
+                    "Unable to write to the offline buffer {}: {}",
+                    file.display(),
+                    e
+                );
+            }
+        }
+        state.queue.push_back(push);
+        self.compact(&mut state);
+    }
+
+    fn new_timestamp(&self) -> Timestamp {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        Timestamp::new(NTP64::from(now), uhlc::ID::from(&self.zid))
+    }
+
+    // Records the number of sessions the client had when the buffer was created
+    pub(crate) fn init(
+        self: &Arc<Self>,
+        connected: usize,
+        session_state: &Arc<RwLock<SessionState>>,
+    ) {
+        let mut state = zlock!(self.state);
+        state.connected = state.connected.max(connected);
+        self.replay(&mut state, session_state);
+    }
+
+    fn connected(self: &Arc<Self>, session_state: &Arc<RwLock<SessionState>>) {
+        let mut state = zlock!(self.state);
+        state.connected += 1;
+        self.replay(&mut state, session_state);
+    }
+
+    fn disconnected(&self) {
+        let mut state = zlock!(self.state);
+        state.connected = state.connected.saturating_sub(1);
+    }
+
+    fn replay(self: &Arc<Self>, state: &mut State, session_state: &Arc<RwLock<SessionState>>) {
+        if state.connected == 0 || state.replaying || state.queue.is_empty() {
+            return;
+        }
+        state.replaying = true;
+        let buffer = self.clone();
+        let session_state = session_state.clone();
+        async_std::task::spawn(async move {
+            async_std::task::sleep(buffer.replay_delay).await;
+            log::info!(
+                "Sending {} publications buffered while disconnected",
+                zlock!(buffer.state).queue.len()
+            );
+            loop {
+                let push = {
+                    let mut state = zlock!(buffer.state);
+                    if state.connected == 0 {
+                        state.replaying = false;
+                        return;
+                    }
+                    match state.queue.pop_front() {
+                        Some(push) => {
+                            state.file_skip += 1;
+                            buffer.compact(&mut state);
+                            push
+                        }
+                        None => {
+                            state.replaying = false;
+                            return;
+                        }
+                    }
+                };
+                // the session was closed
+                let Some(primitives) = zread!(session_state).primitives.clone() else {
+                    return;
+                };
+                primitives.send_push(push);
+            }
+        });
+    }
+
+    // Rewrites the file once most of its publications were sent or dropped
+    fn compact(&self, state: &mut State) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        if state.file_skip == 0 || state.file_skip < state.queue.len() {
+            return;
+        }
+        let mut bytes = vec![];
+        for push in &state.queue {
+            encode(&mut bytes, push);
+        }
+        match std::fs::write(file, bytes) {
+            Ok(()) => state.file_skip = 0,
+            Err(e) => log::error!(
+                "Unable to write the offline buffer {}: {}",
+                file.display(),
+                e
+            ),
+        }
+    }
+}
+
+fn encode(bytes: &mut Vec<u8>, push: &Push) {
+    let mut writer = bytes.writer();
+    // writing to a Vec doesn't fail
+    let _ = Zenoh080::new().write(&mut writer, push);
+}
+
+fn append(file: &Path, push: &Push) -> ZResult<()> {
+    let mut bytes = vec![];
+    encode(&mut bytes, push);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file)?
+        .write_all(&bytes)?;
+    Ok(())
+}
+
+fn load(file: &Path) -> ZResult<VecDeque<Push>> {
+    let bytes = match std::fs::read(file) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(VecDeque::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let codec = Zenoh080::new();
+    let mut reader = bytes.as_slice().reader();
+    let mut queue = VecDeque::new();
+    while reader.can_read() {
+        let push: Result<Push, _> = codec.read(&mut reader);
+        match push {
+            Ok(push) => queue.push_back(push),
+            // the application stopped while writing the last publication
+            Err(_) => break,
+        }
+    }
+    Ok(queue)
+}
+
+/// Tracks the sessions of the client with the routers, sending the buffered publications once reconnected.
+pub(crate) struct Handler {
+    buffer: Arc<OfflineBuffer>,
+    session_state: Arc<RwLock<SessionState>>,
+}
+
+impl Handler {
+    pub(crate) fn new(
+        buffer: Arc<OfflineBuffer>,
+        session_state: Arc<RwLock<SessionState>>,
+    ) -> Self {
+        Handler {
+            buffer,
+            session_state,
+        }
+    }
+}
+
+impl TransportEventHandler for Handler {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        self.buffer.connected(&self.session_state);
+        Ok(Arc::new(PeerHandler {
+            buffer: self.buffer.clone(),
+        }))
+    }
+
+    #[cfg(feature = "transport_multicast")]
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        zenoh_result::bail!("The offline buffer only tracks the unicast sessions")
+    }
+}
+
+struct PeerHandler {
+    buffer: Arc<OfflineBuffer>,
+}
+
+impl TransportPeerEventHandler for PeerHandler {
+    fn handle_message(&self, _msg: NetworkMessage) -> ZResult<()> {
+        Ok(())
+    }
+
+    fn new_link(&self, _link: zenoh_link::Link) {}
+
+    fn del_link(&self, _link: zenoh_link::Link) {}
+
+    fn closing(&self) {}
+
+    fn closed(&self) {
+        self.buffer.disconnected();
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
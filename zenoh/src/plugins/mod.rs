@@ -18,6 +18,8 @@
 //!
 //! [Click here for Zenoh's documentation](../../zenoh/index.html)
 pub(crate) mod sealed;
+#[cfg(feature = "wasm")]
+pub(crate) mod wasm;
 
 #[zenoh_macros::unstable]
 pub use sealed::*;
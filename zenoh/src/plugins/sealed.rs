@@ -17,7 +17,12 @@
 use crate::prelude::Selector;
 pub use crate::runtime::Runtime;
 pub use crate::Result as ZResult;
-use zenoh_core::zconfigurable;
+use futures::FutureExt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use zenoh_core::{zconfigurable, zlock};
+use zenoh_result::bail;
 
 zconfigurable! {
     pub static ref PLUGIN_PREFIX: String = "zenoh_plugin_".to_string();
@@ -45,6 +50,64 @@ impl Response {
     }
 }
 
+/// A write on a plugin's part of the administration space, handled by [`RunningPluginTrait::adminspace_setter`].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum AdminSpaceWrite {
+    /// A PUT, whose payload is parsed as JSON, or taken as a JSON string if it isn't valid JSON.
+    Put(serde_json::Value),
+    Delete,
+}
+
+/// The type of a [`Metric`], as defined by Prometheus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+}
+
+impl std::fmt::Display for MetricKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetricKind::Counter => write!(f, "counter"),
+            MetricKind::Gauge => write!(f, "gauge"),
+        }
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+/// A metric exported by a plugin through [`RunningPluginTrait::metrics`].
+pub struct Metric {
+    pub name: String,
+    pub kind: MetricKind,
+    pub help: String,
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+}
+
+impl Metric {
+    pub fn new(
+        name: impl Into<String>,
+        kind: MetricKind,
+        help: impl Into<String>,
+        value: f64,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            help: help.into(),
+            labels: Vec::new(),
+            value,
+        }
+    }
+
+    pub fn with_label(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.push((name.into(), value.into()));
+        self
+    }
+}
+
 pub trait RunningPluginTrait: Send + Sync + std::any::Any {
     /// Returns a function that will be called when configuration relevant to the plugin is about to change.
     ///
@@ -65,11 +128,99 @@ pub trait RunningPluginTrait: Send + Sync + std::any::Any {
         selector: &'a Selector<'a>,
         plugin_status_key: &str,
     ) -> ZResult<Vec<Response>>;
+    /// Used to handle PUT and DELETE on `key`, within your plugin's status space (`plugin_status_key/**`), so that
+    /// runtime control operations may be exposed as plain writes on the administration space.
+    ///
+    /// Writes are rejected by default.
+    fn adminspace_setter(
+        &self,
+        key: &str,
+        plugin_status_key: &str,
+        write: AdminSpaceWrite,
+    ) -> ZResult<()> {
+        let _ = (plugin_status_key, write);
+        bail!("`{}` isn't writable", key)
+    }
+    /// Returns whether `key`, within your plugin's status space (`plugin_status_key/**`), is one on which your
+    /// plugin publishes notifications, so that the publications on it aren't handled as writes by the administration space.
+    fn is_adminspace_notification(&self, key: &str, plugin_status_key: &str) -> bool {
+        let _ = (key, plugin_status_key);
+        false
+    }
+    /// Used to request your plugin's metrics, which are exposed along with the router's ones on
+    /// `@/router/<zid>/metrics`, labelled with your plugin's name.
+    fn metrics(&self) -> Vec<Metric> {
+        Vec::new()
+    }
+    /// Polled by `zenohd` to supervise your plugin: returns why your plugin's task terminated if it did so
+    /// unexpectedly, in which case your plugin is restarted. [`TaskMonitor`] keeps track of such terminations.
+    fn terminated(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Keeps track of the termination of a plugin's task, for [`RunningPluginTrait::terminated`].
+#[derive(Debug, Clone, Default)]
+pub struct TaskMonitor(Arc<Mutex<Option<String>>>);
+
+// Only used by plugins, which build zenoh with the `unstable` feature
+#[cfg_attr(not(feature = "unstable"), allow(dead_code))]
+impl TaskMonitor {
+    /// Runs `task`, recording why it terminated once it has.
+    pub async fn watch<T, E: std::fmt::Display>(
+        self,
+        task: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        match AssertUnwindSafe(task).catch_unwind().await {
+            Ok(result) => {
+                *zlock!(self.0) = Some(match &result {
+                    Ok(_) => "the plugin's task returned".into(),
+                    Err(e) => format!("the plugin's task failed: {e}"),
+                });
+                result
+            }
+            Err(panic) => {
+                *zlock!(self.0) = Some(format!(
+                    "the plugin's task panicked: {}",
+                    zenoh_plugin_trait::panic_message(&*panic)
+                ));
+                std::panic::resume_unwind(panic)
+            }
+        }
+    }
+
+    /// Returns why the watched task terminated, if it has.
+    pub fn terminated(&self) -> Option<String> {
+        zlock!(self.0).clone()
+    }
 }
 
 /// The zenoh plugins manager. It handles the full lifetime of plugins, from loading to destruction.
 pub type PluginsManager = zenoh_plugin_trait::loading::PluginsManager<StartArgs, RunningPlugin>;
 
+/// Loads the plugin `name` in `manager`, from its `__path__` if set, returning the path it was loaded from.
+///
+/// The paths ending with `.wasm` are WebAssembly modules, hosted in a sandbox with the `wasm` feature.
+pub fn load_plugin(
+    manager: &mut PluginsManager,
+    name: &str,
+    paths: Option<&[String]>,
+) -> ZResult<String> {
+    match paths {
+        Some(paths) if paths.iter().any(|path| path.ends_with(".wasm")) => {
+            #[cfg(feature = "wasm")]
+            return super::wasm::load(manager, name, paths);
+            #[cfg(not(feature = "wasm"))]
+            bail!(
+                "Plugin `{}` is a WebAssembly module: loading it requires the `wasm` feature",
+                name
+            )
+        }
+        Some(paths) => manager.load_plugin_by_paths(name.into(), paths),
+        None => manager.load_plugin_by_name(name.into()),
+    }
+}
+
 pub use zenoh_plugin_trait::Plugin;
 pub type ValidationFunction = std::sync::Arc<
     dyn Fn(
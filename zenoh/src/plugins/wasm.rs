@@ -0,0 +1,703 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The host of the plugins compiled to WebAssembly modules (e.g. for the `wasm32-wasi` target), run in a sandbox.
+//!
+//! A module only reaches zenoh through the functions it imports from the `zenoh` module, all the strings and
+//! payloads being passed as `(pointer, length)` pairs in its exported `memory`:
+//! - `put(key, key_len, value, value_len) -> i32` and `delete(key, key_len) -> i32` publish on a key;
+//! - `declare_subscriber(key_expr, key_expr_len) -> i32` declares a subscriber, returning its id;
+//! - `declare_queryable(key_expr, key_expr_len) -> i32` declares a queryable, returning its id;
+//! - `get(selector, selector_len) -> i32` sends a query, returning its id;
+//! - `reply(query, key, key_len, value, value_len) -> i32` replies to a query while it is being handled;
+//! - `config(buffer, buffer_len) -> i32` copies the JSON configuration of the plugin into the buffer if it is large
+//!   enough, returning its length;
+//! - `log(level, message, message_len)` logs a message, `level` going from 1 (error) to 5 (trace).
+//!
+//! The functions returning an `i32` return a negative value on failure, e.g. for a key expression out of the
+//! `allowed` ones of the plugin's configuration.
+//! The WASI functions are also provided, with no access to the file system, the environment or the network.
+//!
+//! The module exports `alloc(len) -> ptr`, which the host calls to pass data to the module, and `start() -> i32`,
+//! called once instantiated (after `_initialize` for the WASI reactors), which fails the plugin if it returns a
+//! negative value. It may export the callbacks, each owning the memory of its parameters:
+//! - `on_sample(subscriber, key, key_len, value, value_len)` for the samples received by its subscribers;
+//! - `on_query(queryable, query, selector, selector_len)` for the queries received by its queryables, which are
+//!   finalized once it returns;
+//! - `on_reply(get, key, key_len, value, value_len)` for the replies to its queries, and `on_reply_end(get)`
+//!   once they are all received.
+//!
+//! The module runs in a thread of its own, each call being limited to the `fuel` of the plugin's configuration
+//! and its memory to the `memory` one (in bytes): the plugin terminates if a call fails, e.g. runs out of fuel, and
+//! is restarted by `zenohd` as configured.
+//! At most `queue_size` events wait for the module, the ones received beyond being dropped and counted under the
+//! plugin's `dropped` status key.
+use super::sealed::{Response, RunningPlugin, RunningPluginTrait, ValidationFunction};
+use crate::prelude::sync::*;
+use crate::query::Reply;
+use crate::queryable::{Query, Queryable};
+use crate::runtime::Runtime;
+use crate::subscriber::Subscriber;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use wasmi::{
+    Caller, Config, Engine, Extern, Instance, Linker, Module, Store, StoreLimits,
+    StoreLimitsBuilder, TypedFunc,
+};
+use wasmi_wasi::{WasiCtx, WasiCtxBuilder};
+use zenoh_core::zlock;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The `fuel` of a call to a module when not configured: roughly the number of instructions it may run.
+const DEFAULT_FUEL: u64 = 100_000_000;
+/// The `memory` a module may use when not configured, in bytes.
+const DEFAULT_MEMORY: u64 = 64 * 1024 * 1024;
+/// The `queue_size` of the events waiting for a module when not configured.
+const DEFAULT_QUEUE_SIZE: u64 = 1024;
+
+/// Loads the plugin `name` from the WebAssembly module at the first of `paths` that can be read and compiled,
+/// returning its path.
+pub(crate) fn load(
+    manager: &mut super::sealed::PluginsManager,
+    name: &str,
+    paths: &[String],
+) -> ZResult<String> {
+    let engine = Engine::new(Config::default().consume_fuel(true));
+    for path in paths {
+        let module = std::fs::read(path)
+            .map_err(|e| zerror!("{}", e))
+            .and_then(|bytes| Module::new(&engine, &bytes[..]).map_err(|e| zerror!("{}", e)));
+        match module {
+            Ok(module) => {
+                let (engine, path) = (engine.clone(), path.clone());
+                manager.add_hosted(name.into(), path.clone(), move |name, runtime| {
+                    WasmPlugin::start(name, &path, &engine, &module, runtime)
+                });
+                return Ok(path.clone());
+            }
+            Err(e) => log::warn!("Plugin '{}' load fail at {}: {}", name, path, e),
+        }
+    }
+    bail!("Plugin '{}' not found in {:?}", name, paths)
+}
+
+/// The events handled by the module, in its thread.
+enum Event {
+    Sample {
+        subscriber: i32,
+        key: String,
+        value: Vec<u8>,
+    },
+    Query {
+        queryable: i32,
+        query: Query,
+    },
+    Reply {
+        get: i32,
+        key: String,
+        value: Vec<u8>,
+    },
+    ReplyEnd {
+        get: i32,
+    },
+    Stop,
+}
+
+/// The bounded queue of the events waiting for the module.
+#[derive(Clone)]
+struct Events {
+    sender: flume::Sender<Event>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Events {
+    // Queues an event, dropping it if the queue is full rather than blocking zenoh's threads
+    fn push(&self, event: Event) {
+        if let Err(flume::TrySendError::Full(_)) = self.sender.try_send(event) {
+            if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                log::warn!("WASM plugin lagging behind: dropping the events it can't queue");
+            }
+        }
+    }
+}
+
+/// Notifies the module that all the replies to one of its queries were received, once the query's callback is dropped.
+struct ReplyEnd {
+    get: i32,
+    events: Events,
+}
+
+impl Drop for ReplyEnd {
+    fn drop(&mut self) {
+        self.events.push(Event::ReplyEnd { get: self.get });
+    }
+}
+
+/// The state of the host, accessible to the functions imported by the module.
+struct Host {
+    wasi: WasiCtx,
+    limits: StoreLimits,
+    session: Arc<Session>,
+    events: Events,
+    allowed: Vec<OwnedKeyExpr>,
+    config: Vec<u8>,
+    subscribers: Vec<Subscriber<'static, ()>>,
+    queryables: Vec<Queryable<'static, ()>>,
+    gets: i32,
+    // The queries being handled by the module, by id
+    queries: HashMap<i32, Query>,
+    next_query: i32,
+}
+
+impl Host {
+    /// Whether the module may use `key_expr`.
+    fn allows(&self, key_expr: &keyexpr) -> bool {
+        self.allowed
+            .iter()
+            .any(|allowed| allowed.includes(key_expr))
+    }
+}
+
+// Reads a string passed by the module, `None` if it is out of its memory or isn't valid UTF-8
+fn read_string(caller: &Caller<'_, Host>, ptr: i32, len: i32) -> Option<String> {
+    String::from_utf8(read_bytes(caller, ptr, len)?).ok()
+}
+
+// Reads the bytes passed by the module, `None` if they are out of its memory
+fn read_bytes(caller: &Caller<'_, Host>, ptr: i32, len: i32) -> Option<Vec<u8>> {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    let start = usize::try_from(ptr).ok()?;
+    let end = start.checked_add(usize::try_from(len).ok()?)?;
+    memory.data(caller).get(start..end).map(<[u8]>::to_vec)
+}
+
+// Reads a key expression passed by the module, `None` if it is invalid or not allowed
+fn read_key_expr(caller: &Caller<'_, Host>, ptr: i32, len: i32) -> Option<OwnedKeyExpr> {
+    let key_expr = OwnedKeyExpr::autocanonize(read_string(caller, ptr, len)?).ok()?;
+    if caller.data().allows(&key_expr) {
+        Some(key_expr)
+    } else {
+        log::warn!(
+            "WASM plugin used `{}` out of its allowed key expressions",
+            key_expr
+        );
+        None
+    }
+}
+
+fn link(linker: &mut Linker<Host>) -> ZResult<()> {
+    wasmi_wasi::add_to_linker(linker, |host: &mut Host| &mut host.wasi)
+        .map_err(|e| zerror!("{}", e))?;
+    linker
+        .func_wrap(
+            "zenoh",
+            "put",
+            |caller: Caller<'_, Host>, key: i32, key_len: i32, value: i32, value_len: i32| -> i32 {
+                let (Some(key), Some(value)) = (
+                    read_key_expr(&caller, key, key_len),
+                    read_bytes(&caller, value, value_len),
+                ) else {
+                    return -1;
+                };
+                match caller.data().session.put(key, value).res_sync() {
+                    Ok(()) => 0,
+                    Err(_) => -1,
+                }
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?;
+    linker
+        .func_wrap(
+            "zenoh",
+            "delete",
+            |caller: Caller<'_, Host>, key: i32, key_len: i32| -> i32 {
+                let Some(key) = read_key_expr(&caller, key, key_len) else {
+                    return -1;
+                };
+                match caller.data().session.delete(key).res_sync() {
+                    Ok(()) => 0,
+                    Err(_) => -1,
+                }
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?;
+    linker
+        .func_wrap(
+            "zenoh",
+            "declare_subscriber",
+            |mut caller: Caller<'_, Host>, key_expr: i32, key_expr_len: i32| -> i32 {
+                let Some(key_expr) = read_key_expr(&caller, key_expr, key_expr_len) else {
+                    return -1;
+                };
+                let host = caller.data_mut();
+                let id = host.subscribers.len() as i32;
+                let events = host.events.clone();
+                let subscriber = host
+                    .session
+                    .declare_subscriber(key_expr)
+                    .callback(move |sample: Sample| {
+                        events.push(Event::Sample {
+                            subscriber: id,
+                            key: sample.key_expr.to_string(),
+                            value: sample.value.payload.contiguous().to_vec(),
+                        });
+                    })
+                    .res_sync();
+                match subscriber {
+                    Ok(subscriber) => {
+                        host.subscribers.push(subscriber);
+                        id
+                    }
+                    Err(_) => -1,
+                }
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?;
+    linker
+        .func_wrap(
+            "zenoh",
+            "declare_queryable",
+            |mut caller: Caller<'_, Host>, key_expr: i32, key_expr_len: i32| -> i32 {
+                let Some(key_expr) = read_key_expr(&caller, key_expr, key_expr_len) else {
+                    return -1;
+                };
+                let host = caller.data_mut();
+                let id = host.queryables.len() as i32;
+                let events = host.events.clone();
+                let queryable = host
+                    .session
+                    .declare_queryable(key_expr)
+                    .callback(move |query: Query| {
+                        events.push(Event::Query {
+                            queryable: id,
+                            query,
+                        });
+                    })
+                    .res_sync();
+                match queryable {
+                    Ok(queryable) => {
+                        host.queryables.push(queryable);
+                        id
+                    }
+                    Err(_) => -1,
+                }
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?;
+    linker
+        .func_wrap(
+            "zenoh",
+            "get",
+            |mut caller: Caller<'_, Host>, selector: i32, selector_len: i32| -> i32 {
+                let Some(selector) = read_string(&caller, selector, selector_len)
+                    .and_then(|s| Selector::try_from(s).ok())
+                else {
+                    return -1;
+                };
+                if !caller.data().allows(&selector.key_expr) {
+                    log::warn!(
+                        "WASM plugin queried `{}` out of its allowed key expressions",
+                        selector.key_expr
+                    );
+                    return -1;
+                }
+                let host = caller.data_mut();
+                let get = host.gets;
+                host.gets += 1;
+                let events = host.events.clone();
+                let end = ReplyEnd {
+                    get,
+                    events: events.clone(),
+                };
+                let sent = host
+                    .session
+                    .get(selector)
+                    .callback(move |reply: Reply| {
+                        let _ = &end;
+                        if let Ok(sample) = reply.sample {
+                            events.push(Event::Reply {
+                                get,
+                                key: sample.key_expr.to_string(),
+                                value: sample.value.payload.contiguous().to_vec(),
+                            });
+                        }
+                    })
+                    .res_sync();
+                match sent {
+                    Ok(()) => get,
+                    Err(_) => -1,
+                }
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?;
+    linker
+        .func_wrap(
+            "zenoh",
+            "reply",
+            |caller: Caller<'_, Host>,
+             query: i32,
+             key: i32,
+             key_len: i32,
+             value: i32,
+             value_len: i32|
+             -> i32 {
+                let (Some(key), Some(value)) = (
+                    read_key_expr(&caller, key, key_len),
+                    read_bytes(&caller, value, value_len),
+                ) else {
+                    return -1;
+                };
+                let Some(query) = caller.data().queries.get(&query) else {
+                    return -1;
+                };
+                match query.reply(Ok(Sample::new(key, value))).res_sync() {
+                    Ok(()) => 0,
+                    Err(_) => -1,
+                }
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?;
+    linker
+        .func_wrap(
+            "zenoh",
+            "config",
+            |mut caller: Caller<'_, Host>, buffer: i32, buffer_len: i32| -> i32 {
+                let config = caller.data().config.clone();
+                if config.len() <= buffer_len.max(0) as usize {
+                    let Some(memory) = caller.get_export("memory").and_then(Extern::into_memory)
+                    else {
+                        return -1;
+                    };
+                    if memory
+                        .write(&mut caller, buffer.max(0) as usize, &config)
+                        .is_err()
+                    {
+                        return -1;
+                    }
+                }
+                config.len() as i32
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?;
+    linker
+        .func_wrap(
+            "zenoh",
+            "log",
+            |caller: Caller<'_, Host>, level: i32, message: i32, message_len: i32| {
+                let level = match level {
+                    1 => log::Level::Error,
+                    2 => log::Level::Warn,
+                    3 => log::Level::Info,
+                    4 => log::Level::Debug,
+                    _ => log::Level::Trace,
+                };
+                if let Some(message) = read_string(&caller, message, message_len) {
+                    log::log!(level, "{}", message);
+                }
+            },
+        )
+        .map_err(|e| zerror!("{}", e))?;
+    Ok(())
+}
+
+/// The module of a running plugin, in its thread.
+struct Guest {
+    store: Store<Host>,
+    instance: Instance,
+    fuel: u64,
+    calls: Arc<AtomicU64>,
+}
+
+impl Guest {
+    // Gives the module the fuel of a call
+    fn refuel(&mut self) -> ZResult<()> {
+        let remaining = self.store.consume_fuel(0).map_err(|e| zerror!("{}", e))?;
+        self.store
+            .add_fuel(self.fuel.saturating_sub(remaining))
+            .map_err(|e| zerror!("{}", e))?;
+        Ok(())
+    }
+
+    fn func<Params: wasmi::WasmParams, Results: wasmi::WasmResults>(
+        &self,
+        name: &str,
+    ) -> Option<TypedFunc<Params, Results>> {
+        self.instance.get_typed_func(&self.store, name).ok()
+    }
+
+    fn call<Params: wasmi::WasmParams, Results: wasmi::WasmResults>(
+        &mut self,
+        func: TypedFunc<Params, Results>,
+        params: Params,
+    ) -> ZResult<Results> {
+        self.refuel()?;
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        func.call(&mut self.store, params)
+            .map_err(|e| zerror!("{}", e).into())
+    }
+
+    // Copies bytes in the memory of the module, returning their pointer and length
+    fn pass(&mut self, bytes: &[u8]) -> ZResult<(i32, i32)> {
+        let alloc = self
+            .func::<i32, i32>("alloc")
+            .ok_or_else(|| zerror!("the module doesn't export `alloc`"))?;
+        let len = i32::try_from(bytes.len()).map_err(|e| zerror!("{}", e))?;
+        let ptr = self.call(alloc, len)?;
+        let memory = self
+            .instance
+            .get_memory(&self.store, "memory")
+            .ok_or_else(|| zerror!("the module doesn't export `memory`"))?;
+        memory
+            .write(&mut self.store, ptr.max(0) as usize, bytes)
+            .map_err(|e| zerror!("{}", e))?;
+        Ok((ptr, len))
+    }
+
+    fn handle(&mut self, event: Event) -> ZResult<()> {
+        match event {
+            Event::Sample {
+                subscriber,
+                key,
+                value,
+            } => {
+                if let Some(on_sample) = self.func::<(i32, i32, i32, i32, i32), ()>("on_sample") {
+                    let (key, key_len) = self.pass(key.as_bytes())?;
+                    let (value, value_len) = self.pass(&value)?;
+                    self.call(on_sample, (subscriber, key, key_len, value, value_len))?;
+                }
+            }
+            Event::Query { queryable, query } => {
+                if let Some(on_query) = self.func::<(i32, i32, i32, i32), ()>("on_query") {
+                    let host = self.store.data_mut();
+                    let id = host.next_query;
+                    host.next_query = host.next_query.wrapping_add(1);
+                    let selector = query.selector().to_string();
+                    self.store.data_mut().queries.insert(id, query);
+                    let result = self.pass(selector.as_bytes()).and_then(|(selector, len)| {
+                        self.call(on_query, (queryable, id, selector, len))
+                    });
+                    // the query is finalized once dropped
+                    self.store.data_mut().queries.remove(&id);
+                    result?;
+                }
+            }
+            Event::Reply { get, key, value } => {
+                if let Some(on_reply) = self.func::<(i32, i32, i32, i32, i32), ()>("on_reply") {
+                    let (key, key_len) = self.pass(key.as_bytes())?;
+                    let (value, value_len) = self.pass(&value)?;
+                    self.call(on_reply, (get, key, key_len, value, value_len))?;
+                }
+            }
+            Event::ReplyEnd { get } => {
+                if let Some(on_reply_end) = self.func::<i32, ()>("on_reply_end") {
+                    self.call(on_reply_end, get)?;
+                }
+            }
+            Event::Stop => {}
+        }
+        Ok(())
+    }
+}
+
+// Reads the positive integer `key` of the plugin's configuration, `default` if not set
+fn positive(config: &serde_json::Value, name: &str, key: &str, default: u64) -> ZResult<u64> {
+    match config.get(key) {
+        Some(value) => value.as_u64().filter(|value| *value > 0).ok_or_else(|| {
+            zerror!(
+                "Plugin `{}` configuration error: `{}` must be a positive integer",
+                name,
+                key
+            )
+            .into()
+        }),
+        None => Ok(default),
+    }
+}
+
+/// A running plugin hosted in a WebAssembly sandbox.
+struct WasmPlugin {
+    path: String,
+    events: Events,
+    // Set when the plugin is stopped, before any event still queued is handled
+    stopped: Arc<AtomicBool>,
+    calls: Arc<AtomicU64>,
+    terminated: Arc<Mutex<Option<String>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WasmPlugin {
+    fn start(
+        name: &str,
+        path: &str,
+        engine: &Engine,
+        module: &Module,
+        runtime: &Runtime,
+    ) -> ZResult<RunningPlugin> {
+        let config = runtime
+            .config
+            .lock()
+            .plugin(name)
+            .cloned()
+            .ok_or_else(|| zerror!("Plugin `{}`: missing config", name))?;
+        let allowed = match config.get("allowed") {
+            Some(serde_json::Value::Array(allowed)) => allowed
+                .iter()
+                .map(|k| match k.as_str().map(OwnedKeyExpr::autocanonize) {
+                    Some(Ok(k)) => Ok(k),
+                    _ => bail!(
+                        "Plugin `{}` configuration error: invalid key expression {} in `allowed`",
+                        name,
+                        k
+                    ),
+                })
+                .collect::<ZResult<Vec<_>>>()?,
+            None => vec![OwnedKeyExpr::new("**").unwrap()],
+            _ => bail!(
+                "Plugin `{}` configuration error: `allowed` must be an array of key expressions",
+                name
+            ),
+        };
+        let fuel = positive(&config, name, "fuel", DEFAULT_FUEL)?;
+        let memory = positive(&config, name, "memory", DEFAULT_MEMORY)?;
+        let queue_size = positive(&config, name, "queue_size", DEFAULT_QUEUE_SIZE)?;
+
+        let (sender, rx) = flume::bounded(queue_size.try_into().unwrap_or(usize::MAX));
+        let events = Events {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+        let host = Host {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(memory.try_into().unwrap_or(usize::MAX))
+                .build(),
+            wasi: WasiCtxBuilder::new()
+                .inherit_stdout()
+                .inherit_stderr()
+                .build(),
+            session: Arc::new(crate::init(runtime.clone()).res_sync()?),
+            events: events.clone(),
+            allowed,
+            config: config.to_string().into_bytes(),
+            subscribers: Vec::new(),
+            queryables: Vec::new(),
+            gets: 0,
+            queries: HashMap::new(),
+            next_query: 0,
+        };
+        let mut linker = Linker::new(engine);
+        link(&mut linker)?;
+        let mut store = Store::new(engine, host);
+        store.limiter(|host| &mut host.limits);
+        store.add_fuel(fuel).map_err(|e| zerror!("{}", e))?;
+        let instance = linker
+            .instantiate(&mut store, module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| zerror!("Plugin `{}` failed to instantiate: {}", name, e))?;
+        let calls = Arc::new(AtomicU64::new(0));
+        let mut guest = Guest {
+            store,
+            instance,
+            fuel,
+            calls: calls.clone(),
+        };
+        if let Some(initialize) = guest.func::<(), ()>("_initialize") {
+            guest.call(initialize, ())?;
+        }
+        let start = guest
+            .func::<(), i32>("start")
+            .ok_or_else(|| zerror!("Plugin `{}`: the module doesn't export `start`", name))?;
+        let started = guest.call(start, ())?;
+        if started < 0 {
+            bail!(
+                "Plugin `{}` failed to start: `start` returned {}",
+                name,
+                started
+            )
+        }
+
+        let terminated = Arc::new(Mutex::new(None));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (terminated, stopped) = (terminated.clone(), stopped.clone());
+            std::thread::Builder::new()
+                .name(format!("wasm-plugin-{name}"))
+                .spawn(move || {
+                    for event in rx.iter() {
+                        if stopped.load(Ordering::Relaxed) || matches!(event, Event::Stop) {
+                            return;
+                        }
+                        if let Err(e) = guest.handle(event) {
+                            *zlock!(terminated) = Some(format!("the WASM module failed: {e}"));
+                            return;
+                        }
+                    }
+                })
+                .map_err(|e| zerror!("{}", e))?
+        };
+        Ok(Box::new(WasmPlugin {
+            path: path.into(),
+            events,
+            stopped,
+            calls,
+            terminated,
+            thread: Some(thread),
+        }))
+    }
+}
+
+impl RunningPluginTrait for WasmPlugin {
+    fn config_checker(&self) -> ValidationFunction {
+        Arc::new(|_, _, _| bail!("WASM plugins don't accept any runtime configuration changes"))
+    }
+
+    fn adminspace_getter<'a>(
+        &'a self,
+        selector: &'a Selector<'a>,
+        plugin_status_key: &str,
+    ) -> ZResult<Vec<Response>> {
+        let mut responses = Vec::new();
+        for (suffix, value) in [
+            ("/path", serde_json::Value::from(self.path.as_str())),
+            ("/calls", self.calls.load(Ordering::Relaxed).into()),
+            (
+                "/dropped",
+                self.events.dropped.load(Ordering::Relaxed).into(),
+            ),
+        ] {
+            let key = format!("{plugin_status_key}{suffix}");
+            if keyexpr::new(key.as_str())?.intersects(&selector.key_expr) {
+                responses.push(Response::new(key, value));
+            }
+        }
+        Ok(responses)
+    }
+
+    fn terminated(&self) -> Option<String> {
+        zlock!(self.terminated).clone()
+    }
+}
+
+impl Drop for WasmPlugin {
+    // The queued events are skipped, and a call being made is bounded by the plugin's fuel
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        // wakes the thread up if it waits for an event, the queue being otherwise non-empty
+        let _ = self.events.sender.try_send(Event::Stop);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
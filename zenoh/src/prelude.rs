@@ -35,12 +35,14 @@ pub(crate) mod common {
     pub use crate::selector::{Parameter, Parameters, Selector};
     pub use crate::session::{Session, SessionDeclarations};
 
+    #[cfg(feature = "queries")]
     pub use crate::query::{QueryConsolidation, QueryTarget};
 
     pub use crate::value::Value;
     /// The encoding of a zenoh `Value`.
     pub use zenoh_protocol::core::{Encoding, KnownEncoding};
 
+    #[cfg(feature = "queries")]
     pub use crate::query::ConsolidationMode;
     #[zenoh_macros::unstable]
     pub use crate::sample::Locality;
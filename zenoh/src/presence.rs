@@ -0,0 +1,489 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Presence primitives: discover the publishers and queryables declared on key expressions.
+//!
+//! Each [`Publisher`](crate::publication::Publisher) and
+//! [`Queryable`](crate::queryable::Queryable) declared by a session is advertised with a
+//! liveliness token on `@/liveliness/@entities/<zid>/<id>/<kind>/<qos>/<key_expr>`, which the
+//! routers keep in their routing state along with the other declarations.
+//! The `@entities` chunk being verbatim, these tokens are never matched by the liveliness
+//! subscribers and queries of the applications.
+//!
+//! see [`Session::entities_on`](crate::Session::entities_on) and
+//! [`Session::declare_entities_subscriber`](crate::Session::declare_entities_subscriber)
+
+use crate::handlers::{locked, DefaultHandler};
+use crate::liveliness::{LivelinessToken, KE_PREFIX_LIVELINESS};
+use crate::prelude::*;
+use crate::subscriber::{Subscriber, SubscriberInner};
+use crate::SessionRef;
+use std::convert::{TryFrom, TryInto};
+use std::future::Ready;
+use std::str::FromStr;
+use std::sync::Arc;
+use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+use zenoh_protocol::network::declare::subscriber::ext::SubscriberInfo;
+use zenoh_result::{zerror, ZResult};
+#[cfg(feature = "queries")]
+use {crate::query::Reply, std::time::Duration};
+
+pub(crate) const PREFIX_ENTITIES: &str = "@entities";
+
+/// The kind of an [`Entity`], along with its QoS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Publisher {
+        priority: Priority,
+        congestion_control: CongestionControl,
+    },
+    Queryable {
+        complete: bool,
+    },
+}
+
+/// A publisher or a queryable declared by a (possibly remote) session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entity {
+    /// The [`ZenohId`] of the session which declared the entity.
+    pub zid: ZenohId,
+    /// The identifier of the entity in the session which declared it.
+    pub id: Id,
+    pub kind: EntityKind,
+    pub key_expr: OwnedKeyExpr,
+}
+
+impl Entity {
+    /// The key expression of the liveliness token advertising the entity.
+    fn token_key_expr(&self) -> ZResult<OwnedKeyExpr> {
+        let (kind, qos) = match self.kind {
+            EntityKind::Publisher {
+                priority,
+                congestion_control,
+            } => {
+                let congestion_control = match congestion_control {
+                    CongestionControl::Block => "block",
+                    CongestionControl::Drop => "drop",
+                };
+                ("pub", format!("{}.{}", priority as u8, congestion_control))
+            }
+            EntityKind::Queryable { complete: true } => ("qabl", "complete".to_string()),
+            EntityKind::Queryable { complete: false } => ("qabl", "partial".to_string()),
+        };
+        OwnedKeyExpr::try_from(format!(
+            "{}/{}/{}/{}/{}/{}",
+            PREFIX_ENTITIES, self.zid, self.id, kind, qos, self.key_expr
+        ))
+    }
+}
+
+impl TryFrom<&keyexpr> for Entity {
+    type Error = zenoh_result::Error;
+
+    fn try_from(token: &keyexpr) -> ZResult<Self> {
+        let malformed = || zerror!("`{}` doesn't advertise an entity", token);
+        let mut chunks = token.as_str().splitn(6, '/');
+        let (Some(PREFIX_ENTITIES), Some(zid), Some(id), Some(kind), Some(qos), Some(key_expr)) = (
+            chunks.next(),
+            chunks.next(),
+            chunks.next(),
+            chunks.next(),
+            chunks.next(),
+            chunks.next(),
+        ) else {
+            return Err(malformed().into());
+        };
+        let kind = match (kind, qos) {
+            ("pub", qos) => {
+                let (priority, congestion_control) = qos.split_once('.').ok_or_else(malformed)?;
+                EntityKind::Publisher {
+                    priority: u8::from_str(priority)
+                        .map_err(|_| malformed())?
+                        .try_into()?,
+                    congestion_control: match congestion_control {
+                        "block" => CongestionControl::Block,
+                        "drop" => CongestionControl::Drop,
+                        _ => return Err(malformed().into()),
+                    },
+                }
+            }
+            ("qabl", "complete") => EntityKind::Queryable { complete: true },
+            ("qabl", "partial") => EntityKind::Queryable { complete: false },
+            _ => return Err(malformed().into()),
+        };
+        Ok(Entity {
+            zid: ZenohId::from_str(zid)?,
+            id: Id::from_str(id).map_err(|_| malformed())?,
+            kind,
+            key_expr: OwnedKeyExpr::try_from(key_expr)?,
+        })
+    }
+}
+
+/// The appearance or the disappearance of an [`Entity`],
+/// received by the subscribers declared with
+/// [`Session::declare_entities_subscriber`](crate::Session::declare_entities_subscriber).
+#[derive(Debug, Clone)]
+pub struct EntityEvent {
+    pub entity: Entity,
+    /// [`SampleKind::Put`] when the entity is declared,
+    /// [`SampleKind::Delete`] when it is undeclared or its session is lost.
+    pub kind: SampleKind,
+}
+
+/// Advertises the entity of the given session until the returned token is dropped.
+pub(crate) fn declare_entity<'a>(
+    session: &SessionRef<'a>,
+    id: Id,
+    kind: EntityKind,
+    key_expr: &keyexpr,
+) -> ZResult<LivelinessToken<'a>> {
+    let entity = Entity {
+        zid: session.zid(),
+        id,
+        kind,
+        key_expr: key_expr.into(),
+    };
+    let token = KeyExpr::from(entity.token_key_expr()?);
+    session
+        .declare_liveliness_inner(&token)
+        .map(|state| LivelinessToken {
+            session: session.clone(),
+            state,
+            alive: true,
+        })
+}
+
+/// The key expression matching the tokens of the entities declared on key expressions intersecting `key_expr`.
+fn entities_key_expr(key_expr: &keyexpr) -> ZResult<KeyExpr<'static>> {
+    KeyExpr::try_from(format!("{}/*/*/*/*/{}", PREFIX_ENTITIES, key_expr))
+}
+
+/// A builder for initializing a query of the [`Entities`](Entity) declared on a key expression.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let entities = session.entities_on("key/**").res().await.unwrap();
+/// while let Ok(entity) = entities.recv_async().await {
+///     println!("{:?} on {} by {}", entity.kind, entity.key_expr, entity.zid);
+/// }
+/// # })
+/// ```
+#[cfg(feature = "queries")]
+#[must_use = "Resolvables do nothing unless you resolve them using the `res` method from either `SyncResolve` or `AsyncResolve`"]
+#[derive(Debug)]
+pub struct EntitiesBuilder<'a, 'b, Handler> {
+    pub(crate) session: &'a Session,
+    pub(crate) key_expr: ZResult<KeyExpr<'b>>,
+    pub(crate) timeout: Duration,
+    pub(crate) handler: Handler,
+}
+
+#[cfg(feature = "queries")]
+impl<'a, 'b> EntitiesBuilder<'a, 'b, DefaultHandler> {
+    /// Receive the entities with a callback.
+    #[inline]
+    pub fn callback<Callback>(self, callback: Callback) -> EntitiesBuilder<'a, 'b, Callback>
+    where
+        Callback: Fn(Entity) + Send + Sync + 'static,
+    {
+        let EntitiesBuilder {
+            session,
+            key_expr,
+            timeout,
+            handler: _,
+        } = self;
+        EntitiesBuilder {
+            session,
+            key_expr,
+            timeout,
+            handler: callback,
+        }
+    }
+
+    /// Receive the entities with a mutable callback.
+    ///
+    /// Using this guarantees that your callback will never be called concurrently.
+    /// If your callback is also accepted by the [`callback`](EntitiesBuilder::callback) method, we suggest you use it instead of `callback_mut`
+    #[inline]
+    pub fn callback_mut<CallbackMut>(
+        self,
+        callback: CallbackMut,
+    ) -> EntitiesBuilder<'a, 'b, impl Fn(Entity) + Send + Sync + 'static>
+    where
+        CallbackMut: FnMut(Entity) + Send + Sync + 'static,
+    {
+        self.callback(locked(callback))
+    }
+
+    /// Receive the entities with a [`Handler`](crate::prelude::IntoCallbackReceiverPair).
+    #[inline]
+    pub fn with<Handler>(self, handler: Handler) -> EntitiesBuilder<'a, 'b, Handler>
+    where
+        Handler: IntoCallbackReceiverPair<'static, Entity>,
+    {
+        let EntitiesBuilder {
+            session,
+            key_expr,
+            timeout,
+            handler: _,
+        } = self;
+        EntitiesBuilder {
+            session,
+            key_expr,
+            timeout,
+            handler,
+        }
+    }
+}
+
+#[cfg(feature = "queries")]
+impl<'a, 'b, Handler> EntitiesBuilder<'a, 'b, Handler> {
+    /// Set query timeout.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[cfg(feature = "queries")]
+impl<Handler> Resolvable for EntitiesBuilder<'_, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Entity> + Send,
+    Handler::Receiver: Send,
+{
+    type To = ZResult<Handler::Receiver>;
+}
+
+#[cfg(feature = "queries")]
+impl<Handler> SyncResolve for EntitiesBuilder<'_, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Entity> + Send,
+    Handler::Receiver: Send,
+{
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let key_expr = entities_key_expr(&self.key_expr?)?;
+        let (callback, receiver) = self.handler.into_cb_receiver_pair();
+        self.session
+            .query(
+                &key_expr.into(),
+                &Some(KeyExpr::from(*KE_PREFIX_LIVELINESS)),
+                QueryTarget::default(),
+                QueryConsolidation::default(),
+                None,
+                Locality::default(),
+                self.timeout,
+                None,
+                Arc::new(move |reply: Reply| {
+                    if let Ok(sample) = reply.sample {
+                        match Entity::try_from(&*sample.key_expr) {
+                            Ok(entity) => callback(entity),
+                            Err(e) => log::warn!("{}", e),
+                        }
+                    }
+                }),
+            )
+            .map(|_| receiver)
+    }
+}
+
+#[cfg(feature = "queries")]
+impl<Handler> AsyncResolve for EntitiesBuilder<'_, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Entity> + Send,
+    Handler::Receiver: Send,
+{
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+/// A builder for initializing a [`Subscriber`] to the appearance and disappearance
+/// of the [`Entities`](Entity) declared on a key expression.
+///
+/// # Examples
+/// ```no_run
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let subscriber = session.declare_entities_subscriber("key/**").res().await.unwrap();
+/// while let Ok(event) = subscriber.recv_async().await {
+///     match event.kind {
+///         SampleKind::Put => println!("New entity: {:?}", event.entity),
+///         SampleKind::Delete => println!("Lost entity: {:?}", event.entity),
+///     }
+/// }
+/// # })
+/// ```
+#[must_use = "Resolvables do nothing unless you resolve them using the `res` method from either `SyncResolve` or `AsyncResolve`"]
+#[derive(Debug)]
+pub struct EntitiesSubscriberBuilder<'a, 'b, Handler> {
+    pub(crate) session: SessionRef<'a>,
+    pub(crate) key_expr: ZResult<KeyExpr<'b>>,
+    pub(crate) handler: Handler,
+}
+
+impl<'a, 'b> EntitiesSubscriberBuilder<'a, 'b, DefaultHandler> {
+    /// Receive the entity events with a callback.
+    #[inline]
+    pub fn callback<Callback>(
+        self,
+        callback: Callback,
+    ) -> EntitiesSubscriberBuilder<'a, 'b, Callback>
+    where
+        Callback: Fn(EntityEvent) + Send + Sync + 'static,
+    {
+        let EntitiesSubscriberBuilder {
+            session,
+            key_expr,
+            handler: _,
+        } = self;
+        EntitiesSubscriberBuilder {
+            session,
+            key_expr,
+            handler: callback,
+        }
+    }
+
+    /// Receive the entity events with a mutable callback.
+    ///
+    /// Using this guarantees that your callback will never be called concurrently.
+    /// If your callback is also accepted by the [`callback`](EntitiesSubscriberBuilder::callback) method, we suggest you use it instead of `callback_mut`
+    #[inline]
+    pub fn callback_mut<CallbackMut>(
+        self,
+        callback: CallbackMut,
+    ) -> EntitiesSubscriberBuilder<'a, 'b, impl Fn(EntityEvent) + Send + Sync + 'static>
+    where
+        CallbackMut: FnMut(EntityEvent) + Send + Sync + 'static,
+    {
+        self.callback(locked(callback))
+    }
+
+    /// Receive the entity events with a [`Handler`](crate::prelude::IntoCallbackReceiverPair).
+    #[inline]
+    pub fn with<Handler>(self, handler: Handler) -> EntitiesSubscriberBuilder<'a, 'b, Handler>
+    where
+        Handler: IntoCallbackReceiverPair<'static, EntityEvent>,
+    {
+        let EntitiesSubscriberBuilder {
+            session,
+            key_expr,
+            handler: _,
+        } = self;
+        EntitiesSubscriberBuilder {
+            session,
+            key_expr,
+            handler,
+        }
+    }
+}
+
+impl<'a, Handler> Resolvable for EntitiesSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, EntityEvent> + Send,
+    Handler::Receiver: Send,
+{
+    type To = ZResult<Subscriber<'a, Handler::Receiver>>;
+}
+
+impl<'a, Handler> SyncResolve for EntitiesSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, EntityEvent> + Send,
+    Handler::Receiver: Send,
+{
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let key_expr = entities_key_expr(&self.key_expr?)?;
+        let session = self.session;
+        let (callback, receiver) = self.handler.into_cb_receiver_pair();
+        session
+            .declare_subscriber_inner(
+                &key_expr,
+                &Some(KeyExpr::from(*KE_PREFIX_LIVELINESS)),
+                Locality::default(),
+                Arc::new(
+                    move |sample: Sample| match Entity::try_from(&*sample.key_expr) {
+                        Ok(entity) => callback(EntityEvent {
+                            entity,
+                            kind: sample.kind,
+                        }),
+                        Err(e) => log::warn!("{}", e),
+                    },
+                ),
+                &SubscriberInfo::default(),
+                None,
+                None,
+            )
+            .map(|sub_state| Subscriber {
+                subscriber: SubscriberInner {
+                    session,
+                    state: sub_state,
+                    alive: true,
+                },
+                receiver,
+            })
+    }
+}
+
+impl<'a, Handler> AsyncResolve for EntitiesSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, EntityEvent> + Send,
+    Handler::Receiver: Send,
+{
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+#[test]
+fn entity_token_key_expr() {
+    let entities = [
+        Entity {
+            zid: ZenohId::rand(),
+            id: 3,
+            kind: EntityKind::Publisher {
+                priority: Priority::RealTime,
+                congestion_control: CongestionControl::Block,
+            },
+            key_expr: OwnedKeyExpr::new("demo/**/a").unwrap(),
+        },
+        Entity {
+            zid: ZenohId::rand(),
+            id: 7,
+            kind: EntityKind::Queryable { complete: false },
+            key_expr: OwnedKeyExpr::new("demo/*").unwrap(),
+        },
+    ];
+    let pattern = entities_key_expr(keyexpr::new("demo/a").unwrap()).unwrap();
+    for entity in entities {
+        let token = entity.token_key_expr().unwrap();
+        assert!(pattern.intersects(&token));
+        assert_eq!(Entity::try_from(&*token).unwrap(), entity);
+    }
+    // the liveliness tokens of the applications aren't entities
+    assert!(Entity::try_from(keyexpr::new("demo/a").unwrap()).is_err());
+    assert!(!keyexpr::new("**")
+        .unwrap()
+        .intersects(&entities_key_expr(keyexpr::new("**").unwrap()).unwrap()));
+}
@@ -14,21 +14,31 @@
 
 //! Publishing primitives.
 
-use crate::net::transport::Primitives;
 use crate::prelude::*;
 use crate::sample::DataInfo;
 use crate::Encoding;
 use crate::SessionRef;
 use crate::Undeclarable;
 use std::future::Ready;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use uhlc::NTP64;
 use zenoh_core::{zread, AsyncResolve, Resolvable, Resolve, SyncResolve};
 use zenoh_protocol::network::push::ext;
 use zenoh_protocol::network::Mapping;
 use zenoh_protocol::network::Push;
+use zenoh_protocol::zenoh::ext::TraceHop;
+use zenoh_protocol::zenoh::put::ext::SourceInfoType;
 use zenoh_protocol::zenoh::Del;
 use zenoh_protocol::zenoh::PushBody;
 use zenoh_protocol::zenoh::Put;
 use zenoh_result::ZResult;
+#[zenoh_macros::unstable]
+use {
+    zenoh_buffers::ZBuf,
+    zenoh_protocol::zenoh::{ext::BatchEntry, put::ext::BatchType},
+};
 
 /// The kind of congestion control.
 pub use zenoh_protocol::core::CongestionControl;
@@ -109,6 +119,32 @@ impl PutBuilder<'_, '_> {
         self
     }
 
+    /// Trace the written data: the nodes it goes through until reaching the subscribers are
+    /// recorded with their ingress and egress times, and reported in the received [`Sample`]'s `trace`.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn trace(mut self, trace: bool) -> Self {
+        self.publisher = self.publisher.trace(trace);
+        self
+    }
+
+    /// Expire the written data after `lifespan`: the routers stop forwarding it and the storages
+    /// stop replying it to queries, e.g. for position updates which are worthless after a second.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn lifespan(mut self, lifespan: Duration) -> Self {
+        self.publisher = self.publisher.lifespan(lifespan);
+        self
+    }
+
+    /// Send the written data right away instead of batching it with the following messages,
+    /// trading throughput for latency.
+    #[inline]
+    pub fn express(mut self, express: bool) -> Self {
+        self.publisher = self.publisher.express(express);
+        self
+    }
+
     pub fn kind(mut self, kind: SampleKind) -> Self {
         self.kind = kind;
         self
@@ -129,46 +165,49 @@ impl SyncResolve for PutBuilder<'_, '_> {
         } = self;
         let key_expr = publisher.key_expr?;
         log::trace!("write({:?}, [...])", &key_expr);
-        let primitives = zread!(publisher.session.state)
-            .primitives
-            .as_ref()
-            .unwrap()
-            .clone();
         let timestamp = publisher.session.runtime.new_timestamp();
+        let deadline = new_deadline(publisher.lifespan);
 
         if publisher.destination != Locality::SessionLocal {
-            primitives.send_push(Push {
-                wire_expr: key_expr.to_wire(&publisher.session).to_owned(),
-                ext_qos: ext::QoSType::new(
-                    publisher.priority.into(),
-                    publisher.congestion_control,
-                    false,
-                ),
-                ext_tstamp: None,
-                ext_nodeid: ext::NodeIdType::default(),
-                payload: match kind {
-                    SampleKind::Put => PushBody::Put(Put {
-                        timestamp,
-                        encoding: value.encoding.clone(),
-                        ext_sinfo: None,
-                        #[cfg(feature = "shared-memory")]
-                        ext_shm: None,
-                        ext_unknown: vec![],
-                        payload: value.payload.clone(),
-                    }),
-                    SampleKind::Delete => PushBody::Del(Del {
-                        timestamp,
-                        ext_sinfo: None,
-                        ext_unknown: vec![],
-                    }),
+            publisher.session.send_publication(
+                &key_expr,
+                Push {
+                    wire_expr: key_expr.to_wire(&publisher.session).to_owned(),
+                    ext_qos: ext::QoSType::new(
+                        publisher.priority.into(),
+                        publisher.congestion_control,
+                        publisher.express,
+                    ),
+                    ext_tstamp: None,
+                    ext_nodeid: ext::NodeIdType::default(),
+                    payload: match kind {
+                        SampleKind::Put => PushBody::Put(Put {
+                            timestamp,
+                            encoding: value.encoding.clone(),
+                            ext_sinfo: None,
+                            #[cfg(feature = "shared-memory")]
+                            ext_shm: None,
+                            ext_trace: new_trace(&publisher.session, publisher.trace),
+                            ext_deadline: deadline,
+                            ext_batch: None,
+                            ext_unknown: vec![],
+                            payload: value.payload.clone(),
+                        }),
+                        SampleKind::Delete => PushBody::Del(Del {
+                            timestamp,
+                            ext_sinfo: None,
+                            ext_unknown: vec![],
+                        }),
+                    },
                 },
-            });
+            );
         }
         if publisher.destination != Locality::Remote {
             let data_info = DataInfo {
                 kind,
                 encoding: Some(value.encoding),
                 timestamp,
+                deadline,
                 ..Default::default()
             };
 
@@ -191,6 +230,157 @@ impl AsyncResolve for PutBuilder<'_, '_> {
     }
 }
 
+/// A builder for initializing a [`put_batch`](crate::Session::put_batch) operation.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// session
+///     .put_batch([("robot/pose/x", 1.0), ("robot/pose/y", 2.0)])
+///     .priority(Priority::RealTime)
+///     .res()
+///     .await
+///     .unwrap();
+/// # })
+/// ```
+#[zenoh_macros::unstable]
+#[derive(Debug)]
+pub struct PutBatchBuilder<'a, 'b> {
+    pub(crate) session: &'a Session,
+    pub(crate) entries: ZResult<Vec<(KeyExpr<'b>, Value)>>,
+    pub(crate) congestion_control: CongestionControl,
+    pub(crate) priority: Priority,
+    pub(crate) destination: Locality,
+}
+
+#[zenoh_macros::unstable]
+impl PutBatchBuilder<'_, '_> {
+    /// Change the `congestion_control` to apply when routing the batch.
+    #[inline]
+    pub fn congestion_control(mut self, congestion_control: CongestionControl) -> Self {
+        self.congestion_control = congestion_control;
+        self
+    }
+
+    /// Change the priority of the batch.
+    #[inline]
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Restrict the matching subscribers that will receive the batch
+    /// to the ones that have the given [`Locality`](crate::prelude::Locality).
+    #[inline]
+    pub fn allowed_destination(mut self, destination: Locality) -> Self {
+        self.destination = destination;
+        self
+    }
+}
+
+#[zenoh_macros::unstable]
+impl Resolvable for PutBatchBuilder<'_, '_> {
+    type To = ZResult<()>;
+}
+
+#[zenoh_macros::unstable]
+impl SyncResolve for PutBatchBuilder<'_, '_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        let entries = self.entries?;
+        if entries.is_empty() {
+            return Ok(());
+        }
+        // The batch is routed as a single message, on a key expression including all its keys
+        let key_expr: KeyExpr = covering_key_expr(entries.iter().map(|(k, _)| k))?.into();
+        log::trace!("write_batch({:?}, [...])", &key_expr);
+        let timestamp = self.session.runtime.new_timestamp();
+        let entries: Vec<BatchEntry> = entries
+            .into_iter()
+            .map(|(key_expr, value)| BatchEntry {
+                key: key_expr.to_string(),
+                encoding: value.encoding,
+                payload: value.payload,
+            })
+            .collect();
+
+        if self.destination != Locality::SessionLocal {
+            self.session.send_publication(
+                &key_expr,
+                Push {
+                    wire_expr: key_expr.to_wire(self.session).to_owned(),
+                    ext_qos: ext::QoSType::new(
+                        self.priority.into(),
+                        self.congestion_control,
+                        false,
+                    ),
+                    ext_tstamp: None,
+                    ext_nodeid: ext::NodeIdType::default(),
+                    payload: PushBody::Put(Put {
+                        timestamp,
+                        encoding: Encoding::default(),
+                        ext_sinfo: None,
+                        #[cfg(feature = "shared-memory")]
+                        ext_shm: None,
+                        ext_trace: None,
+                        ext_deadline: None,
+                        ext_batch: Some(BatchType {
+                            entries: entries.clone(),
+                        }),
+                        ext_unknown: vec![],
+                        payload: ZBuf::empty(),
+                    }),
+                },
+            );
+        }
+        if self.destination != Locality::Remote {
+            let data_info = DataInfo {
+                kind: SampleKind::Put,
+                timestamp,
+                ..Default::default()
+            };
+            self.session
+                .handle_batch(true, &key_expr.to_wire(self.session), data_info, entries);
+        }
+        Ok(())
+    }
+}
+
+#[zenoh_macros::unstable]
+impl AsyncResolve for PutBatchBuilder<'_, '_> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+/// The key expression a batch is published on: the given key if all the keys of the batch are the same,
+/// or the `**` suffixed chunks they all start with otherwise.
+#[cfg(feature = "unstable")]
+fn covering_key_expr<'a, 'b: 'a>(
+    mut keys: impl Iterator<Item = &'a KeyExpr<'b>>,
+) -> ZResult<OwnedKeyExpr> {
+    let first = keys.next().unwrap().as_str();
+    let mut prefix: Vec<&str> = first.split('/').collect();
+    let mut same = true;
+    for key in keys {
+        same &= key.as_str() == first;
+        let common = prefix
+            .iter()
+            .zip(key.as_str().split('/'))
+            .take_while(|(a, b)| **a == *b)
+            .count();
+        prefix.truncate(common);
+    }
+    if !same && prefix.last() != Some(&"**") {
+        prefix.push("**");
+    }
+    OwnedKeyExpr::new(prefix.join("/"))
+}
+
 use futures::Sink;
 use std::convert::TryFrom;
 use std::convert::TryInto;
@@ -234,6 +424,13 @@ pub struct Publisher<'a> {
     pub(crate) congestion_control: CongestionControl,
     pub(crate) priority: Priority,
     pub(crate) destination: Locality,
+    pub(crate) trace: bool,
+    pub(crate) lifespan: Option<Duration>,
+    pub(crate) sequence: Option<Arc<Sequence>>,
+    pub(crate) express: bool,
+    // The token advertising the publisher, undeclared with its last clone
+    #[cfg(feature = "unstable")]
+    pub(crate) presence: Option<Arc<crate::liveliness::LivelinessToken<'a>>>,
 }
 
 impl<'a> Publisher<'a> {
@@ -264,6 +461,32 @@ impl<'a> Publisher<'a> {
         self
     }
 
+    /// Trace the written data: the nodes it goes through until reaching the subscribers are
+    /// recorded with their ingress and egress times, and reported in the received [`Sample`]'s `trace`.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Expire the written data after `lifespan`: the routers stop forwarding it and the storages
+    /// stop replying it to queries, e.g. for position updates which are worthless after a second.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn lifespan(mut self, lifespan: Duration) -> Self {
+        self.lifespan = Some(lifespan);
+        self
+    }
+
+    /// Send the written data right away instead of batching it with the following messages,
+    /// trading throughput for latency.
+    #[inline]
+    pub fn express(mut self, express: bool) -> Self {
+        self.express = express;
+        self
+    }
+
     fn _write(&self, kind: SampleKind, value: Value) -> Publication {
         Publication {
             publisher: self,
@@ -311,6 +534,58 @@ impl<'a> Publisher<'a> {
         self._write(SampleKind::Put, value.into())
     }
 
+    /// Put data written by `write` in a buffer of `len` bytes allocated by the given shared memory manager.
+    ///
+    /// The subscribers on the same host supporting shared memory receive the buffer's descriptor only,
+    /// and can access its content without copying it with [`Value::as_shm`]. The payload is copied when
+    /// it's sent to other hosts or to nodes not supporting shared memory.
+    ///
+    /// If the manager is out of memory, its freed buffers are garbage collected and its memory defragmented
+    /// before allocating the buffer again.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh::shm::SharedMemoryManager;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let mut shm = SharedMemoryManager::make(session.zid().to_string(), 4096).unwrap();
+    /// let publisher = session.declare_publisher("key/expression").res().await.unwrap();
+    /// publisher
+    ///     .write_shm(&mut shm, 5, |buf| buf.copy_from_slice(b"value"))
+    ///     .unwrap()
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[cfg(feature = "shared-memory")]
+    pub fn write_shm<F>(
+        &self,
+        shm: &mut zenoh_shm::SharedMemoryManager,
+        len: usize,
+        write: F,
+    ) -> ZResult<Publication>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        let mut smb = match shm.alloc(len) {
+            Ok(smb) => smb,
+            Err(_) => {
+                shm.garbage_collect();
+                shm.defragment();
+                shm.alloc(len)?
+            }
+        };
+        // The allocated buffer may be longer, to keep the alignment of the next ones
+        smb.info.length -= smb.len - len;
+        smb.len = len;
+        // The buffer was just allocated: no one else can access it yet
+        write(unsafe { smb.as_mut_slice() });
+        Ok(self._write(SampleKind::Put, smb.into()))
+    }
+
     /// Delete data.
     ///
     /// # Examples
@@ -422,38 +697,48 @@ impl SyncResolve for Publication<'_> {
             kind,
         } = self;
         log::trace!("write({:?}, [...])", publisher.key_expr);
-        let primitives = zread!(publisher.session.state)
-            .primitives
+        let deadline = new_deadline(publisher.lifespan);
+        let sinfo = publisher
+            .sequence
             .as_ref()
-            .unwrap()
-            .clone();
+            .map(|sequence| sequence.next(publisher.session.runtime.zid));
 
         if publisher.destination != Locality::SessionLocal {
-            primitives.send_push(Push {
-                wire_expr: publisher.key_expr.to_wire(&publisher.session).to_owned(),
-                ext_qos: ext::QoSType::new(
-                    publisher.priority.into(),
-                    publisher.congestion_control,
-                    false,
-                ),
-                ext_tstamp: None,
-                ext_nodeid: ext::NodeIdType::default(),
-                payload: PushBody::Put(Put {
-                    timestamp: publisher.session.runtime.new_timestamp(),
-                    encoding: value.encoding.clone(),
-                    ext_sinfo: None,
-                    #[cfg(feature = "shared-memory")]
-                    ext_shm: None,
-                    ext_unknown: vec![],
-                    payload: value.payload.clone(),
-                }),
-            });
+            publisher.session.send_publication(
+                &publisher.key_expr,
+                Push {
+                    wire_expr: publisher.key_expr.to_wire(&publisher.session).to_owned(),
+                    ext_qos: ext::QoSType::new(
+                        publisher.priority.into(),
+                        publisher.congestion_control,
+                        publisher.express,
+                    ),
+                    ext_tstamp: None,
+                    ext_nodeid: ext::NodeIdType::default(),
+                    payload: PushBody::Put(Put {
+                        timestamp: publisher.session.runtime.new_timestamp(),
+                        encoding: value.encoding.clone(),
+                        ext_sinfo: sinfo.clone(),
+                        #[cfg(feature = "shared-memory")]
+                        ext_shm: None,
+                        ext_trace: new_trace(&publisher.session, publisher.trace),
+                        ext_deadline: deadline,
+                        ext_batch: None,
+                        ext_unknown: vec![],
+                        payload: value.payload.clone(),
+                    }),
+                },
+            );
         }
         if publisher.destination != Locality::Remote {
             let data_info = DataInfo {
                 kind,
                 encoding: Some(value.encoding),
                 timestamp: publisher.session.runtime.new_timestamp(),
+                source_id: sinfo.as_ref().map(|i| i.zid),
+                source_eid: sinfo.as_ref().map(|i| i.eid),
+                source_sn: sinfo.as_ref().map(|i| i.sn as u64),
+                deadline,
                 ..Default::default()
             };
             publisher.session.handle_data(
@@ -526,6 +811,10 @@ pub struct PublisherBuilder<'a, 'b: 'a> {
     pub(crate) congestion_control: CongestionControl,
     pub(crate) priority: Priority,
     pub(crate) destination: Locality,
+    pub(crate) trace: bool,
+    pub(crate) lifespan: Option<Duration>,
+    pub(crate) ordered: bool,
+    pub(crate) express: bool,
 }
 
 impl<'a, 'b> Clone for PublisherBuilder<'a, 'b> {
@@ -539,6 +828,10 @@ impl<'a, 'b> Clone for PublisherBuilder<'a, 'b> {
             congestion_control: self.congestion_control,
             priority: self.priority,
             destination: self.destination,
+            trace: self.trace,
+            lifespan: self.lifespan,
+            ordered: self.ordered,
+            express: self.express,
         }
     }
 }
@@ -566,6 +859,41 @@ impl<'a, 'b> PublisherBuilder<'a, 'b> {
         self.destination = destination;
         self
     }
+
+    /// Trace the written data: the nodes it goes through until reaching the subscribers are
+    /// recorded with their ingress and egress times, and reported in the received [`Sample`]'s `trace`.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Expire the written data after `lifespan`: the routers stop forwarding it and the storages
+    /// stop replying it to queries, e.g. for position updates which are worthless after a second.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn lifespan(mut self, lifespan: Duration) -> Self {
+        self.lifespan = Some(lifespan);
+        self
+    }
+
+    /// Number the written data so that the [`ordered`](crate::subscriber::SubscriberBuilder::ordered)
+    /// subscribers receive it in publication order.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn ordered(mut self, ordered: bool) -> Self {
+        self.ordered = ordered;
+        self
+    }
+
+    /// Send the written data right away instead of batching it with the following messages,
+    /// trading throughput for latency.
+    #[inline]
+    pub fn express(mut self, express: bool) -> Self {
+        self.express = express;
+        self
+    }
 }
 
 impl<'a, 'b> Resolvable for PublisherBuilder<'a, 'b> {
@@ -608,12 +936,45 @@ impl<'a, 'b> SyncResolve for PublisherBuilder<'a, 'b> {
         self.session
             .declare_publication_intent(key_expr.clone())
             .res_sync()?;
+        #[cfg(feature = "unstable")]
+        let presence = if self.destination != Locality::SessionLocal {
+            let id = zread!(self.session.state)
+                .decl_id_counter
+                .fetch_add(1, Ordering::SeqCst);
+            let kind = crate::presence::EntityKind::Publisher {
+                priority: self.priority,
+                congestion_control: self.congestion_control,
+            };
+            Some(Arc::new(crate::presence::declare_entity(
+                &self.session,
+                id,
+                kind,
+                &key_expr,
+            )?))
+        } else {
+            None
+        };
+        let sequence = self.ordered.then(|| {
+            let eid = zread!(self.session.state)
+                .decl_id_counter
+                .fetch_add(1, Ordering::SeqCst);
+            Arc::new(Sequence {
+                eid: eid as u32,
+                sn: AtomicU32::new(0),
+            })
+        });
         let publisher = Publisher {
             session: self.session,
             key_expr,
             congestion_control: self.congestion_control,
             priority: self.priority,
             destination: self.destination,
+            trace: self.trace,
+            lifespan: self.lifespan,
+            sequence,
+            express: self.express,
+            #[cfg(feature = "unstable")]
+            presence,
         };
         log::trace!("publish({:?})", publisher.key_expr);
         Ok(publisher)
@@ -628,6 +989,43 @@ impl<'a, 'b> AsyncResolve for PublisherBuilder<'a, 'b> {
     }
 }
 
+/// Starts the trace of a publication, its first hop being the publishing session.
+fn new_trace(session: &Session, trace: bool) -> Option<zenoh_protocol::zenoh::put::ext::TraceType> {
+    trace.then(|| {
+        let now = uhlc::system_time_clock();
+        zenoh_protocol::zenoh::put::ext::TraceType {
+            hops: vec![TraceHop {
+                zid: session.runtime.zid,
+                ingress: now,
+                egress: now,
+            }],
+        }
+    })
+}
+
+/// The numbering of the publications of an ordered [`Publisher`], shared by its clones.
+#[derive(Debug)]
+pub(crate) struct Sequence {
+    eid: u32,
+    sn: AtomicU32,
+}
+
+impl Sequence {
+    /// The source info of the next publication.
+    fn next(&self, zid: ZenohId) -> SourceInfoType {
+        SourceInfoType {
+            zid,
+            eid: self.eid,
+            sn: self.sn.fetch_add(1, Ordering::SeqCst),
+        }
+    }
+}
+
+/// The deadline of a publication with the given lifespan, according to the local clock.
+fn new_deadline(lifespan: Option<Duration>) -> Option<NTP64> {
+    lifespan.map(|lifespan| crate::time::clock() + NTP64::from(lifespan))
+}
+
 /// The Priority of zenoh messages.
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 #[repr(u8)]
@@ -719,4 +1117,22 @@ mod tests {
             assert_eq!(p as u8, t as u8);
         }
     }
+
+    #[cfg(feature = "unstable")]
+    #[test]
+    fn covering_key_expr() {
+        use crate::prelude::KeyExpr;
+
+        let cover = |keys: &[&str]| {
+            let keys = keys
+                .iter()
+                .map(|key| KeyExpr::try_from(*key).unwrap())
+                .collect::<Vec<_>>();
+            super::covering_key_expr(keys.iter()).unwrap().to_string()
+        };
+        assert_eq!(cover(&["a/b", "a/b"]), "a/b");
+        assert_eq!(cover(&["a/b/c", "a/b/d", "a/b"]), "a/b/**");
+        assert_eq!(cover(&["a/b", "c/d"]), "**");
+        assert_eq!(cover(&["a/**/b", "a/**/c"]), "a/**");
+    }
 }
@@ -16,8 +16,10 @@
 
 use crate::handlers::{locked, Callback, DefaultHandler};
 use crate::prelude::*;
+use crate::time::Timestamp;
 use crate::Session;
 use std::collections::HashMap;
+use std::fmt;
 use std::future::Ready;
 use std::time::Duration;
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
@@ -84,12 +86,128 @@ pub struct Reply {
     pub replier_id: ZenohId,
 }
 
+/// A strategy consolidating the replies to a [`get`](Session::get) on the querier side, as they are received.
+///
+/// Each query gets its own strategy, which can keep some state about the replies received so far
+/// (e.g. the greatest value received for each key). The strategy is applied while the session processes
+/// the replies, it should thus not block nor call the session.
+///
+/// # Examples
+/// ```
+/// use zenoh::prelude::r#async::*;
+/// use zenoh::query::{ConsolidationStrategy, Reply};
+/// use std::collections::HashMap;
+///
+/// // Delivers a reply only if its value is greater than the ones previously delivered for its key
+/// #[derive(Default)]
+/// struct MaxPerKey(HashMap<OwnedKeyExpr, f64>);
+///
+/// impl ConsolidationStrategy for MaxPerKey {
+///     fn consolidate(&mut self, reply: Reply) -> Option<Reply> {
+///         let sample = reply.sample.as_ref().ok()?;
+///         let value: f64 = sample.value.to_string().parse().ok()?;
+///         match self.0.get(sample.key_expr.as_keyexpr()) {
+///             Some(max) if *max >= value => None,
+///             _ => {
+///                 self.0.insert(sample.key_expr.clone().into(), value);
+///                 Some(reply)
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub trait ConsolidationStrategy: Send + Sync {
+    /// Consolidates a received reply, returning the reply to deliver right away, if any.
+    fn consolidate(&mut self, reply: Reply) -> Option<Reply>;
+
+    /// Returns the replies to deliver once all the replies are received, or the query timed out.
+    fn finalize(&mut self) -> Vec<Reply> {
+        Vec::new()
+    }
+}
+
+impl fmt::Debug for dyn ConsolidationStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ConsolidationStrategy")
+    }
+}
+
+/// The [`ConsolidationStrategy`] of [`ConsolidationMode::None`]: all the replies are delivered.
+#[derive(Debug, Default)]
+pub struct NoConsolidation;
+
+impl ConsolidationStrategy for NoConsolidation {
+    fn consolidate(&mut self, reply: Reply) -> Option<Reply> {
+        Some(reply)
+    }
+}
+
+/// The [`ConsolidationStrategy`] of [`ConsolidationMode::Monotonic`]: a reply is delivered right away
+/// if it is more recent than the ones previously delivered for its key.
+#[derive(Debug, Default)]
+pub struct MonotonicConsolidation {
+    timestamps: HashMap<OwnedKeyExpr, Option<Timestamp>>,
+}
+
+impl ConsolidationStrategy for MonotonicConsolidation {
+    fn consolidate(&mut self, reply: Reply) -> Option<Reply> {
+        let sample = match &reply.sample {
+            Ok(sample) => sample,
+            Err(_) => return Some(reply),
+        };
+        match self.timestamps.get(sample.key_expr.as_keyexpr()) {
+            Some(timestamp) if sample.timestamp <= *timestamp => None,
+            _ => {
+                self.timestamps
+                    .insert(sample.key_expr.clone().into(), sample.timestamp);
+                Some(reply)
+            }
+        }
+    }
+}
+
+/// The [`ConsolidationStrategy`] of [`ConsolidationMode::Latest`]: only the most recent reply of each key
+/// is delivered, once all the replies are received.
+#[derive(Debug, Default)]
+pub struct LatestConsolidation {
+    replies: HashMap<OwnedKeyExpr, Reply>,
+}
+
+impl ConsolidationStrategy for LatestConsolidation {
+    fn consolidate(&mut self, reply: Reply) -> Option<Reply> {
+        let sample = match &reply.sample {
+            Ok(sample) => sample,
+            Err(_) => return Some(reply),
+        };
+        match self.replies.get(sample.key_expr.as_keyexpr()) {
+            Some(latest) if sample.timestamp <= latest.sample.as_ref().unwrap().timestamp => {}
+            _ => {
+                self.replies.insert(sample.key_expr.clone().into(), reply);
+            }
+        }
+        None
+    }
+
+    fn finalize(&mut self) -> Vec<Reply> {
+        self.replies.drain().map(|(_, reply)| reply).collect()
+    }
+}
+
+impl From<ConsolidationMode> for Box<dyn ConsolidationStrategy> {
+    fn from(mode: ConsolidationMode) -> Self {
+        match mode {
+            ConsolidationMode::None => Box::<NoConsolidation>::default(),
+            ConsolidationMode::Monotonic => Box::<MonotonicConsolidation>::default(),
+            ConsolidationMode::Latest => Box::<LatestConsolidation>::default(),
+        }
+    }
+}
+
 pub(crate) struct QueryState {
     pub(crate) nb_final: usize,
     pub(crate) selector: Selector<'static>,
     pub(crate) scope: Option<KeyExpr<'static>>,
-    pub(crate) reception_mode: ConsolidationMode,
-    pub(crate) replies: Option<HashMap<OwnedKeyExpr, Reply>>,
+    pub(crate) consolidation: Box<dyn ConsolidationStrategy>,
     pub(crate) callback: Callback<'static, Reply>,
 }
 
@@ -121,6 +239,7 @@ pub struct GetBuilder<'a, 'b, Handler> {
     pub(crate) scope: ZResult<Option<KeyExpr<'b>>>,
     pub(crate) target: QueryTarget,
     pub(crate) consolidation: QueryConsolidation,
+    pub(crate) strategy: Option<Box<dyn ConsolidationStrategy>>,
     pub(crate) destination: Locality,
     pub(crate) timeout: Duration,
     pub(crate) handler: Handler,
@@ -155,6 +274,7 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             scope,
             target,
             consolidation,
+            strategy,
             destination,
             timeout,
             value,
@@ -166,6 +286,7 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             scope,
             target,
             consolidation,
+            strategy,
             destination,
             timeout,
             value,
@@ -234,6 +355,7 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             scope,
             target,
             consolidation,
+            strategy,
             destination,
             timeout,
             value,
@@ -245,6 +367,7 @@ impl<'a, 'b> GetBuilder<'a, 'b, DefaultHandler> {
             scope,
             target,
             consolidation,
+            strategy,
             destination,
             timeout,
             value,
@@ -267,6 +390,19 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
         self
     }
 
+    /// Consolidate the replies with a custom [`ConsolidationStrategy`], applied as they are received.
+    ///
+    /// The strategy replaces the consolidation of the [`consolidation`](GetBuilder::consolidation) mode on the querier side.
+    /// The replies are not consolidated on their way to the querier, unless such a mode is explicitly set.
+    #[inline]
+    pub fn consolidation_strategy<S>(mut self, strategy: S) -> Self
+    where
+        S: ConsolidationStrategy + 'static,
+    {
+        self.strategy = Some(Box::new(strategy));
+        self
+    }
+
     /// Restrict the matching queryables that will receive the query
     /// to the ones that have the given [`Locality`](crate::prelude::Locality).
     #[zenoh_macros::unstable]
@@ -284,6 +420,24 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
     }
 
     /// Set query value.
+    ///
+    /// The value is carried with the query to the queryables, which can read it through
+    /// [`Query::value`](crate::queryable::Query::value), e.g. as the structured request of an RPC.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let replies = session
+    ///     .get("key/expression")
+    ///     .with_value(Value::from(r#"{"x":1}"#).encoding(KnownEncoding::AppJson.into()))
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
     #[inline]
     pub fn with_value<IntoValue>(mut self, value: IntoValue) -> Self
     where
@@ -306,6 +460,7 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
             scope,
             target,
             consolidation,
+            strategy,
             destination,
             timeout,
             value,
@@ -317,6 +472,7 @@ impl<'a, 'b, Handler> GetBuilder<'a, 'b, Handler> {
             scope,
             target,
             consolidation,
+            strategy,
             destination,
             timeout,
             value,
@@ -365,6 +521,7 @@ where
                 &self.scope?,
                 self.target,
                 self.consolidation,
+                self.strategy,
                 self.destination,
                 self.timeout,
                 self.value,
@@ -21,10 +21,14 @@ use crate::query::ReplyKeyExpr;
 use crate::SessionRef;
 use crate::Undeclarable;
 
+use futures::Stream;
 use std::fmt;
 use std::future::Ready;
 use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
+use std::task::{Context, Poll};
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
 use zenoh_protocol::core::WireExpr;
 use zenoh_protocol::network::{response, Mapping, RequestId, Response, ResponseFinal};
@@ -44,13 +48,17 @@ pub(crate) struct QueryInner {
     pub(crate) qid: RequestId,
     pub(crate) zid: ZenohId,
     pub(crate) primitives: Arc<dyn Primitives>,
+    /// The lowest priority of the replies sent so far, which the final response must not overtake.
+    pub(crate) reply_priority: AtomicU8,
 }
 
 impl Drop for QueryInner {
     fn drop(&mut self) {
+        let priority =
+            Priority::try_from(self.reply_priority.load(Ordering::Relaxed)).unwrap_or_default();
         self.primitives.send_response_final(ResponseFinal {
             rid: self.qid,
-            ext_qos: response::ext::QoSType::response_final_default(),
+            ext_qos: response::ext::QoSType::new(priority.into(), CongestionControl::Block, false),
             ext_tstamp: None,
         });
     }
@@ -92,6 +100,9 @@ impl Query {
 
     /// Sends a reply to this Query.
     ///
+    /// The kind of the replied [`Sample`] is propagated to the querier: replying a sample of kind
+    /// [`SampleKind::Delete`] notifies that its key expression was deleted at the sample's timestamp.
+    ///
     /// By default, queries only accept replies whose key expression intersects with the query's.
     /// Unless the query has enabled disjoint replies (you can check this through [`Query::accepts_replies`]),
     /// replying on a disjoint key expression will result in an error when resolving the reply.
@@ -100,6 +111,7 @@ impl Query {
         ReplyBuilder {
             query: self,
             result,
+            priority: Priority::default(),
         }
     }
 
@@ -147,6 +159,20 @@ impl fmt::Display for Query {
 pub struct ReplyBuilder<'a> {
     query: &'a Query,
     result: Result<Sample, Value>,
+    priority: Priority,
+}
+
+impl ReplyBuilder<'_> {
+    /// Changes the priority of the reply, [`Priority::Data`] by default.
+    ///
+    /// Sending large bursts of replies at a lower priority than the live publications (e.g. [`Priority::DataLow`])
+    /// prevents them from delaying these publications on the links they share.
+    /// The end of the query is notified at the lowest priority of its replies, so that it doesn't overtake them.
+    #[inline]
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
 }
 
 impl<'a> Resolvable for ReplyBuilder<'a> {
@@ -163,14 +189,8 @@ impl SyncResolve for ReplyBuilder<'_> {
                     bail!("Attempted to reply on `{}`, which does not intersect with query `{}`, despite query only allowing replies on matching key expressions", sample.key_expr, self.query.key_expr())
                 }
                 let (key_expr, payload, data_info) = sample.split();
-                self.query.inner.primitives.send_response(Response {
-                    rid: self.query.inner.qid,
-                    wire_expr: WireExpr {
-                        scope: 0,
-                        suffix: std::borrow::Cow::Owned(key_expr.into()),
-                        mapping: Mapping::Sender,
-                    },
-                    payload: ResponseBody::Reply(zenoh::Reply {
+                let payload = match data_info.kind {
+                    SampleKind::Put => ResponseBody::Reply(zenoh::Reply {
                         timestamp: data_info.timestamp,
                         encoding: data_info.encoding.unwrap_or_default(),
                         ext_sinfo: if data_info.source_id.is_some() || data_info.source_sn.is_some()
@@ -189,7 +209,38 @@ impl SyncResolve for ReplyBuilder<'_> {
                         ext_unknown: vec![],
                         payload,
                     }),
-                    ext_qos: response::ext::QoSType::response_default(),
+                    SampleKind::Delete => ResponseBody::Del(zenoh::Del {
+                        timestamp: data_info.timestamp,
+                        ext_sinfo: if data_info.source_id.is_some() || data_info.source_sn.is_some()
+                        {
+                            Some(zenoh::del::ext::SourceInfoType {
+                                zid: data_info.source_id.unwrap_or_default(),
+                                eid: 0, // TODO
+                                sn: data_info.source_sn.unwrap_or_default() as u32,
+                            })
+                        } else {
+                            None
+                        },
+                        ext_unknown: vec![],
+                    }),
+                };
+                self.query
+                    .inner
+                    .reply_priority
+                    .fetch_max(self.priority as u8, Ordering::Relaxed);
+                self.query.inner.primitives.send_response(Response {
+                    rid: self.query.inner.qid,
+                    wire_expr: WireExpr {
+                        scope: 0,
+                        suffix: std::borrow::Cow::Owned(key_expr.into()),
+                        mapping: Mapping::Sender,
+                    },
+                    payload,
+                    ext_qos: response::ext::QoSType::new(
+                        self.priority.into(),
+                        CongestionControl::Block,
+                        false,
+                    ),
                     ext_tstamp: None,
                     ext_respid: Some(response::ext::ResponderIdType {
                         zid: self.query.inner.zid,
@@ -260,6 +311,9 @@ pub(crate) struct CallbackQueryable<'a> {
     pub(crate) session: SessionRef<'a>,
     pub(crate) state: Arc<QueryableState>,
     pub(crate) alive: bool,
+    // The token advertising the queryable, undeclared along with it
+    #[cfg(feature = "unstable")]
+    pub(crate) presence: Option<crate::liveliness::LivelinessToken<'a>>,
 }
 
 impl<'a> Undeclarable<(), QueryableUndeclaration<'a>> for CallbackQueryable<'a> {
@@ -423,6 +477,27 @@ impl<'a, 'b> QueryableBuilder<'a, 'b, DefaultHandler> {
     /// }
     /// # })
     /// ```
+    ///
+    /// The receiver of a channel can also be used from a thread without any async executor,
+    /// `recv()` blocking it until the next query is received. Note that the zenoh thread delivering the queries
+    /// blocks while a bounded channel is full, see [`IntoCallbackReceiverPair`](crate::prelude::IntoCallbackReceiverPair).
+    /// ```no_run
+    /// use zenoh::prelude::sync::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().unwrap();
+    /// let queryable = session
+    ///     .declare_queryable("key/expression")
+    ///     .with(flume::bounded(32))
+    ///     .res()
+    ///     .unwrap();
+    /// // The receiver outlives the thread, unlike the queryable which borrows the session
+    /// let receiver = queryable.receiver.clone();
+    /// std::thread::spawn(move || {
+    ///     while let Ok(query) = receiver.recv() {
+    ///         println!(">> Handling query '{}'", query.selector());
+    ///     }
+    /// });
+    /// ```
     #[inline]
     pub fn with<Handler>(self, handler: Handler) -> QueryableBuilder<'a, 'b, Handler>
     where
@@ -520,6 +595,20 @@ impl<Receiver> Deref for Queryable<'_, Receiver> {
     }
 }
 
+impl<Receiver: Stream + Unpin> Stream for Queryable<'_, Receiver> {
+    type Item = Receiver::Item;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.receiver.size_hint()
+    }
+}
+
 impl<'a, Handler> Resolvable for QueryableBuilder<'a, '_, Handler>
 where
     Handler: IntoCallbackReceiverPair<'static, Query> + Send,
@@ -535,22 +624,39 @@ where
 {
     fn res_sync(self) -> <Self as Resolvable>::To {
         let session = self.session;
+        let key_expr = self.key_expr?;
+        key_expr.check_canon()?;
         let (callback, receiver) = self.handler.into_cb_receiver_pair();
-        session
-            .declare_queryable_inner(
-                &self.key_expr?.to_wire(&session),
-                self.complete,
-                self.origin,
-                callback,
-            )
-            .map(|qable_state| Queryable {
-                queryable: CallbackQueryable {
-                    session,
-                    state: qable_state,
-                    alive: true,
-                },
-                receiver,
-            })
+        let qable_state = session.declare_queryable_inner(
+            &key_expr.to_wire(&session),
+            self.complete,
+            self.origin,
+            callback,
+        )?;
+        #[allow(unused_mut)]
+        let mut queryable = CallbackQueryable {
+            session,
+            state: qable_state,
+            alive: true,
+            #[cfg(feature = "unstable")]
+            presence: None,
+        };
+        #[cfg(feature = "unstable")]
+        if self.origin != Locality::SessionLocal {
+            let kind = crate::presence::EntityKind::Queryable {
+                complete: self.complete,
+            };
+            queryable.presence = Some(crate::presence::declare_entity(
+                &queryable.session,
+                queryable.state.id,
+                kind,
+                &key_expr,
+            )?);
+        }
+        Ok(Queryable {
+            queryable,
+            receiver,
+        })
     }
 }
 
@@ -16,12 +16,17 @@
 use crate::buffers::ZBuf;
 use crate::prelude::ZenohId;
 use crate::prelude::{KeyExpr, SampleKind, Value};
+#[cfg(feature = "queries")]
 use crate::query::Reply;
-use crate::time::{new_reception_timestamp, Timestamp};
+use crate::time::{new_reception_timestamp, Timestamp, NTP64};
 #[zenoh_macros::unstable]
 use serde::Serialize;
 use std::convert::{TryFrom, TryInto};
 use zenoh_protocol::core::Encoding;
+#[zenoh_macros::unstable]
+pub use zenoh_protocol::zenoh::ext::TraceHop;
+#[cfg(not(feature = "unstable"))]
+use zenoh_protocol::zenoh::ext::TraceHop;
 
 pub type SourceSn = u64;
 
@@ -49,7 +54,10 @@ pub(crate) struct DataInfo {
     pub encoding: Option<Encoding>,
     pub timestamp: Option<Timestamp>,
     pub source_id: Option<ZenohId>,
+    pub source_eid: Option<u32>,
     pub source_sn: Option<SourceSn>,
+    pub trace: Option<Vec<TraceHop>>,
+    pub deadline: Option<NTP64>,
 }
 
 /// Informations on the source of a zenoh [`Sample`].
@@ -98,6 +106,27 @@ impl From<Option<DataInfo>> for SourceInfo {
     }
 }
 
+/// The position of a [`Sample`] in the atomic batch it was published with.
+///
+/// A subscriber receives in a row all the samples of a batch matching its key expression,
+/// numbered from 0 to `len - 1`.
+#[zenoh_macros::unstable]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchInfo {
+    /// The index of the sample among the samples of the batch received by the subscriber.
+    pub index: usize,
+    /// The number of samples of the batch received by the subscriber.
+    pub len: usize,
+}
+
+#[zenoh_macros::unstable]
+impl BatchInfo {
+    /// Whether the sample is the last one of its batch.
+    pub fn is_last(&self) -> bool {
+        self.index + 1 == self.len
+    }
+}
+
 /// A zenoh sample.
 #[non_exhaustive]
 #[derive(Clone, Debug)]
@@ -120,6 +149,38 @@ pub struct Sample {
     ///
     /// Infos on the source of this Sample.
     pub source_info: SourceInfo,
+
+    #[cfg(feature = "unstable")]
+    /// <div class="stab unstable">
+    ///   <span class="emoji">🔬</span>
+    ///   This API has been marked as unstable: it works as advertised, but we may change it in a future release.
+    ///   To use it, you must enable zenoh's <code>unstable</code> feature flag.
+    /// </div>
+    ///
+    /// The nodes this Sample went through if it was published with tracing enabled, from the publishing
+    /// session to the receiving one. Only the routers with tracing enabled are recorded.
+    pub trace: Option<Vec<TraceHop>>,
+
+    #[cfg(feature = "unstable")]
+    /// <div class="stab unstable">
+    ///   <span class="emoji">🔬</span>
+    ///   This API has been marked as unstable: it works as advertised, but we may change it in a future release.
+    ///   To use it, you must enable zenoh's <code>unstable</code> feature flag.
+    /// </div>
+    ///
+    /// The time after which this Sample is expired, if it was published with a lifespan.
+    pub deadline: Option<NTP64>,
+
+    #[cfg(feature = "unstable")]
+    /// <div class="stab unstable">
+    ///   <span class="emoji">🔬</span>
+    ///   This API has been marked as unstable: it works as advertised, but we may change it in a future release.
+    ///   To use it, you must enable zenoh's <code>unstable</code> feature flag.
+    /// </div>
+    ///
+    /// The position of this Sample in the atomic batch it was published with, if it was published with
+    /// [`put_batch`](crate::Session::put_batch).
+    pub batch: Option<BatchInfo>,
 }
 
 impl Sample {
@@ -137,6 +198,12 @@ impl Sample {
             timestamp: None,
             #[cfg(feature = "unstable")]
             source_info: SourceInfo::empty(),
+            #[cfg(feature = "unstable")]
+            trace: None,
+            #[cfg(feature = "unstable")]
+            deadline: None,
+            #[cfg(feature = "unstable")]
+            batch: None,
         }
     }
     /// Creates a new Sample.
@@ -157,6 +224,12 @@ impl Sample {
             timestamp: None,
             #[cfg(feature = "unstable")]
             source_info: SourceInfo::empty(),
+            #[cfg(feature = "unstable")]
+            trace: None,
+            #[cfg(feature = "unstable")]
+            deadline: None,
+            #[cfg(feature = "unstable")]
+            batch: None,
         })
     }
 
@@ -172,6 +245,10 @@ impl Sample {
             if let Some(encoding) = &data_info.encoding {
                 value.encoding = encoding.clone();
             }
+            #[cfg(feature = "unstable")]
+            let trace = data_info.trace.clone();
+            #[cfg(feature = "unstable")]
+            let deadline = data_info.deadline;
             Sample {
                 key_expr,
                 value,
@@ -179,6 +256,12 @@ impl Sample {
                 timestamp: data_info.timestamp,
                 #[cfg(feature = "unstable")]
                 source_info: data_info.into(),
+                #[cfg(feature = "unstable")]
+                trace,
+                #[cfg(feature = "unstable")]
+                deadline,
+                #[cfg(feature = "unstable")]
+                batch: None,
             }
         } else {
             Sample {
@@ -188,10 +271,28 @@ impl Sample {
                 timestamp: None,
                 #[cfg(feature = "unstable")]
                 source_info: SourceInfo::empty(),
+                #[cfg(feature = "unstable")]
+                trace: None,
+                #[cfg(feature = "unstable")]
+                deadline: None,
+                #[cfg(feature = "unstable")]
+                batch: None,
             }
         }
     }
 
+    /// Records the position of this Sample in the atomic batch it was published with.
+    #[inline]
+    pub(crate) fn in_batch(#[allow(unused_mut)] mut self, index: usize, len: usize) -> Self {
+        #[cfg(feature = "unstable")]
+        {
+            self.batch = Some(BatchInfo { index, len });
+        }
+        #[cfg(not(feature = "unstable"))]
+        let _ = (index, len);
+        self
+    }
+
     #[inline]
     pub(crate) fn split(self) -> (KeyExpr<'static>, ZBuf, DataInfo) {
         let info = DataInfo {
@@ -202,10 +303,19 @@ impl Sample {
             source_id: self.source_info.source_id,
             #[cfg(not(feature = "unstable"))]
             source_id: None,
+            source_eid: None,
             #[cfg(feature = "unstable")]
             source_sn: self.source_info.source_sn,
             #[cfg(not(feature = "unstable"))]
             source_sn: None,
+            #[cfg(feature = "unstable")]
+            trace: self.trace,
+            #[cfg(not(feature = "unstable"))]
+            trace: None,
+            #[cfg(feature = "unstable")]
+            deadline: self.deadline,
+            #[cfg(not(feature = "unstable"))]
+            deadline: None,
         };
         (self.key_expr, self.value.payload, info)
     }
@@ -269,6 +379,7 @@ impl std::fmt::Display for Sample {
     }
 }
 
+#[cfg(feature = "queries")]
 impl TryFrom<Reply> for Sample {
     type Error = Value;
 
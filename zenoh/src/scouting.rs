@@ -12,7 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::handlers::{locked, Callback, DefaultHandler};
-use crate::net::runtime::{orchestrator::Loop, Runtime};
+use crate::net::runtime::{orchestrator::Loop, scouting_auth::ScoutingAuth, Runtime};
 
 use async_std::net::UdpSocket;
 use futures::StreamExt;
@@ -298,6 +298,7 @@ fn scout(
         zenoh_config::defaults::scouting::multicast::interface,
         |s| s.as_ref(),
     );
+    let auth = ScoutingAuth::from_config(&config);
     let (stop_sender, stop_receiver) = flume::bounded::<()>(1);
     let ifaces = Runtime::get_interfaces(ifaces);
     if !ifaces.is_empty() {
@@ -308,7 +309,7 @@ fn scout(
         if !sockets.is_empty() {
             async_std::task::spawn(async move {
                 let mut stop_receiver = stop_receiver.stream();
-                let scout = Runtime::scout(&sockets, what, &addr, move |hello| {
+                let scout = Runtime::scout(&sockets, what, &addr, auth.as_ref(), move |hello| {
                     let callback = callback.clone();
                     async move {
                         callback(hello);
@@ -18,7 +18,9 @@ use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
 use zenoh_result::ZResult;
 pub use zenoh_util::time_range::{TimeBound, TimeExpr, TimeRange};
 
-use crate::{prelude::KeyExpr, queryable::Query};
+use crate::prelude::KeyExpr;
+#[cfg(feature = "queries")]
+use crate::queryable::Query;
 
 use std::{
     borrow::{Borrow, Cow},
@@ -58,6 +60,7 @@ use std::{
 /// Here are the currently standardized parameters for Zenoh (check the specification page for the exhaustive list):
 /// - `_time`: used to express interest in only values dated within a certain time range, values for
 ///   this parameter must be readable by the [Zenoh Time DSL](zenoh_util::time_range::TimeRange) for the value to be considered valid.
+///   A single instant (a [`TimeExpr`]) instead of a range expresses interest in the value each key had at that instant.
 /// - **`[unstable]`** `_anyke`: used in queries to express interest in replies coming from any key expression. By default, only replies
 ///   whose key expression match query's key expression are accepted. `_anyke` disables the query-reply key expression matching check.
 #[non_exhaustive]
@@ -211,7 +214,7 @@ impl<'a> Selector<'a> {
         }
         Ok(res)
     }
-    #[cfg(any(feature = "unstable", test))]
+    #[cfg(all(feature = "queries", any(feature = "unstable", test)))]
     pub(crate) fn accept_any_keyexpr(self, any: bool) -> ZResult<Selector<'static>> {
         use crate::query::_REPLY_KEY_EXPR_ANY_SEL_PARAM;
         let mut s = self.into_owned();
@@ -256,6 +259,7 @@ fn selector_accessors() {
         let mut selector = Selector::try_from(selector).unwrap();
         selector.with_time_range(time_range);
         assert_eq!(selector.time_range().unwrap().unwrap(), time_range);
+        assert_eq!(selector.time_instant().unwrap(), None);
         assert!(dbg!(selector.parameters()).contains("_time=[now(-2s)..now(2s)]"));
         let map_selector = selector.parameters_cowmap().unwrap();
         assert_eq!(
@@ -280,6 +284,34 @@ fn selector_accessors() {
         assert_eq!(selector.to_string(), without_any + "&other");
     }
 }
+
+#[test]
+fn selector_time_instant() {
+    let selector = Selector::try_from("hello/there?_time=2023-06-01T12:00:00Z&_filter").unwrap();
+    assert_eq!(
+        selector.time_instant().unwrap(),
+        Some("2023-06-01T12:00:00Z".parse().unwrap())
+    );
+    let selector = Selector::try_from("hello/there?_time=now(-1h)").unwrap();
+    assert_eq!(
+        selector.time_instant().unwrap(),
+        Some(TimeExpr::Now {
+            offset_secs: -3600.0
+        })
+    );
+    assert!(Selector::try_from("hello/there?_time=yesterday")
+        .unwrap()
+        .time_instant()
+        .is_err());
+    assert_eq!(
+        Selector::try_from("hello/there")
+            .unwrap()
+            .time_instant()
+            .unwrap(),
+        None
+    );
+}
+
 pub trait Parameter: Sized {
     type Name: AsRef<str> + Sized;
     type Value: AsRef<str> + Sized;
@@ -404,6 +436,20 @@ pub trait Parameters<'a> {
             None => None,
         })
     }
+
+    /// Extracts the standardized `_time` argument from the selector parameters when it is a single instant rather than a range
+    /// (e.g. `_time=2023-06-01T12:00:00Z` or `_time=now(-1h)`), expressing interest in the value each key had at that instant.
+    ///
+    /// Returns `None` if the `_time` argument is absent or is a time range.
+    fn time_instant(&'a self) -> ZResult<Option<TimeExpr>>
+    where
+        <Self::Decoder as Iterator>::Item: Parameter,
+    {
+        Ok(match &self.get_parameters([TIME_RANGE_KEY])?[0] {
+            Some(s) if !s.as_ref().starts_with(['[', ']']) => Some(s.as_ref().parse()?),
+            _ => None,
+        })
+    }
 }
 impl<'a> Parameters<'a> for Selector<'a> {
     type Decoder = <str as Parameters<'a>>::Decoder;
@@ -491,6 +537,7 @@ impl<'a> TryFrom<&'a String> for Selector<'a> {
     }
 }
 
+#[cfg(feature = "queries")]
 impl<'a> From<&'a Query> for Selector<'a> {
     fn from(q: &'a Query) -> Self {
         Selector {
@@ -20,25 +20,27 @@ use crate::info::*;
 use crate::key_expr::KeyExprInner;
 #[zenoh_macros::unstable]
 use crate::liveliness::{Liveliness, LivelinessTokenState};
+use crate::net::routing::content_filter::ContentFilter;
 use crate::net::routing::face::Face;
 use crate::net::runtime::Runtime;
 use crate::net::transport::Primitives;
+use crate::offline::{self, OfflineBuffer};
+use crate::prelude::KeyExpr;
 use crate::prelude::Locality;
-use crate::prelude::{KeyExpr, Parameters};
+#[cfg(feature = "queries")]
+#[zenoh_macros::unstable]
+use crate::presence::EntitiesBuilder;
+#[zenoh_macros::unstable]
+use crate::presence::EntitiesSubscriberBuilder;
 use crate::publication::*;
-use crate::query::*;
-use crate::queryable::*;
 use crate::sample::DataInfo;
-use crate::selector::TIME_RANGE_KEY;
 use crate::subscriber::*;
 use crate::Id;
-use crate::Priority;
 use crate::Sample;
 use crate::SampleKind;
-use crate::Selector;
 use crate::Value;
 use async_std::task;
-use log::{error, trace, warn};
+use log::{error, trace};
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::convert::TryInto;
@@ -51,10 +53,10 @@ use std::time::Duration;
 use uhlc::HLC;
 use zenoh_buffers::ZBuf;
 use zenoh_collections::SingleOrVec;
-use zenoh_config::unwrap_or_default;
 use zenoh_core::{zconfigurable, zread, Resolve, ResolveClosure, ResolveFuture, SyncResolve};
+#[cfg(not(feature = "queries"))]
+use zenoh_protocol::network::response;
 use zenoh_protocol::network::AtomicRequestId;
-use zenoh_protocol::network::RequestId;
 use zenoh_protocol::{
     core::{
         key_expr::{keyexpr, OwnedKeyExpr},
@@ -62,24 +64,43 @@ use zenoh_protocol::{
     },
     network::{
         declare::{
-            self, common::ext::WireExprType, queryable::ext::QueryableInfo,
-            subscriber::ext::SubscriberInfo, Declare, DeclareBody, DeclareKeyExpr,
-            DeclareQueryable, DeclareSubscriber, UndeclareQueryable, UndeclareSubscriber,
+            self, common::ext::WireExprType, subscriber::ext::SubscriberInfo, Declare, DeclareBody,
+            DeclareKeyExpr, DeclareSubscriber, UndeclareSubscriber,
         },
         ext,
-        request::{self, ext::TargetType, Request},
+        request::{self, Request},
         Mapping, Push, Response, ResponseFinal,
     },
     zenoh::{
+        ext::{BatchEntry, TraceHop},
+        Pull, PushBody, RequestBody,
+    },
+};
+use zenoh_result::ZResult;
+use zenoh_util::core::AsyncResolve;
+#[cfg(feature = "queries")]
+use {
+    crate::prelude::Parameters,
+    crate::query::*,
+    crate::queryable::*,
+    crate::selector::TIME_RANGE_KEY,
+    crate::Priority,
+    crate::Selector,
+    std::sync::atomic::AtomicU8,
+    zenoh_config::unwrap_or_default,
+    zenoh_protocol::network::declare::{
+        queryable::ext::QueryableInfo, DeclareQueryable, UndeclareQueryable,
+    },
+    zenoh_protocol::network::request::ext::TargetType,
+    zenoh_protocol::network::RequestId,
+    zenoh_protocol::zenoh::{
         query::{
             self,
             ext::{ConsolidationType, QueryBodyType},
         },
-        Pull, PushBody, RequestBody, ResponseBody,
+        ResponseBody,
     },
 };
-use zenoh_result::ZResult;
-use zenoh_util::core::AsyncResolve;
 
 zconfigurable! {
     pub(crate) static ref API_DATA_RECEPTION_CHANNEL_SIZE: usize = 256;
@@ -98,18 +119,26 @@ pub(crate) struct SessionState {
     pub(crate) remote_resources: HashMap<ExprId, Resource>,
     //pub(crate) publications: Vec<OwnedKeyExpr>,
     pub(crate) subscribers: HashMap<Id, Arc<SubscriberState>>,
+    #[cfg(feature = "queries")]
     pub(crate) queryables: HashMap<Id, Arc<QueryableState>>,
     #[cfg(feature = "unstable")]
     pub(crate) tokens: HashMap<Id, Arc<LivelinessTokenState>>,
+    #[cfg(feature = "queries")]
     pub(crate) queries: HashMap<RequestId, QueryState>,
     pub(crate) aggregated_subscribers: Vec<OwnedKeyExpr>,
     //pub(crate) aggregated_publishers: Vec<OwnedKeyExpr>,
+    #[cfg(feature = "queries")]
+    pub(crate) queryables_parallelism: usize,
+    #[cfg(feature = "queries")]
+    pub(crate) dispatched_queries: Arc<AtomicUsize>,
+    pub(crate) offline: Option<Arc<OfflineBuffer>>,
 }
 
 impl SessionState {
     pub(crate) fn new(
         aggregated_subscribers: Vec<OwnedKeyExpr>,
         _aggregated_publishers: Vec<OwnedKeyExpr>,
+        #[cfg(feature = "queries")] queryables_parallelism: usize,
     ) -> SessionState {
         SessionState {
             primitives: None,
@@ -120,12 +149,19 @@ impl SessionState {
             remote_resources: HashMap::new(),
             //publications: Vec::new(),
             subscribers: HashMap::new(),
+            #[cfg(feature = "queries")]
             queryables: HashMap::new(),
             #[cfg(feature = "unstable")]
             tokens: HashMap::new(),
+            #[cfg(feature = "queries")]
             queries: HashMap::new(),
             aggregated_subscribers,
             //aggregated_publishers,
+            #[cfg(feature = "queries")]
+            queryables_parallelism,
+            #[cfg(feature = "queries")]
+            dispatched_queries: Arc::new(AtomicUsize::new(0)),
+            offline: None,
         }
     }
 }
@@ -334,9 +370,18 @@ impl Session {
     ) -> impl Resolve<Session> {
         ResolveClosure::new(move || {
             let router = runtime.router.clone();
+            #[cfg(feature = "queries")]
+            let queryables_parallelism = runtime
+                .config
+                .lock()
+                .queryables_parallelism()
+                .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+                .max(1);
             let state = Arc::new(RwLock::new(SessionState::new(
                 aggregated_subscribers,
                 aggregated_publishers,
+                #[cfg(feature = "queries")]
+                queryables_parallelism,
             )));
             let session = Session {
                 runtime: runtime.clone(),
@@ -347,9 +392,22 @@ impl Session {
 
             runtime.new_handler(Arc::new(admin::Handler::new(session.clone())));
 
+            let buffer =
+                OfflineBuffer::from_config(&runtime.config.lock(), runtime.whatami, runtime.zid);
+            if let Some(buffer) = buffer {
+                runtime.new_handler(Arc::new(offline::Handler::new(
+                    buffer.clone(),
+                    state.clone(),
+                )));
+                let connected = task::block_on(runtime.manager().get_transports_unicast()).len();
+                buffer.init(connected, &state);
+                zwrite!(state).offline = Some(buffer);
+            }
+
             let primitives = Some(router.new_primitives(Arc::new(session.clone())));
             zwrite!(state).primitives = primitives;
 
+            #[cfg(feature = "queries")]
             admin::init(&session);
 
             session
@@ -543,6 +601,8 @@ impl Session {
             reliability: Reliability::default(),
             mode: PushMode,
             origin: Locality::default(),
+            filter: None,
+            ordering: None,
             handler: DefaultHandler,
         }
     }
@@ -569,6 +629,7 @@ impl Session {
     /// }
     /// # })
     /// ```
+    #[cfg(feature = "queries")]
     pub fn declare_queryable<'a, 'b, TryIntoKeyExpr>(
         &'a self,
         key_expr: TryIntoKeyExpr,
@@ -619,6 +680,10 @@ impl Session {
             congestion_control: CongestionControl::default(),
             priority: Priority::default(),
             destination: Locality::default(),
+            trace: false,
+            lifespan: None,
+            ordered: false,
+            express: false,
         }
     }
 
@@ -655,6 +720,7 @@ impl Session {
         let sid = self.id;
         ResolveClosure::new(move || {
             let key_expr: KeyExpr = key_expr?;
+            key_expr.check_canon()?;
             let prefix_len = key_expr.len() as u32;
             let expr_id = self.declare_prefix(key_expr.as_str()).res_sync();
             let key_expr = match key_expr.0 {
@@ -750,6 +816,54 @@ impl Session {
             kind: SampleKind::Delete,
         }
     }
+
+    /// Put an atomic batch of data, for state updates spanning several related keys.
+    ///
+    /// The batch is routed as a single message: the subscribers receive in a row either all the
+    /// values of the batch matching their key expression or none of them, and the storages
+    /// persist the batch as a unit. All the values of the batch share the same timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - The key expressions and values to put
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// session
+    ///     .put_batch([("robot/pose/x", 1.0), ("robot/pose/y", 2.0)])
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// # })
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn put_batch<'a, 'b: 'a, I, TryIntoKeyExpr, IntoValue>(
+        &'a self,
+        entries: I,
+    ) -> PutBatchBuilder<'a, 'b>
+    where
+        I: IntoIterator<Item = (TryIntoKeyExpr, IntoValue)>,
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        IntoValue: Into<Value>,
+    {
+        PutBatchBuilder {
+            session: self,
+            entries: entries
+                .into_iter()
+                .map(|(key_expr, value)| {
+                    Ok((key_expr.try_into().map_err(Into::into)?, value.into()))
+                })
+                .collect(),
+            congestion_control: CongestionControl::default(),
+            priority: Priority::default(),
+            destination: Locality::default(),
+        }
+    }
     /// Query data from the matching queryables in the system.
     ///
     /// Unless explicitly requested via [`GetBuilder::accept_replies`], replies are guaranteed to have
@@ -771,6 +885,7 @@ impl Session {
     /// }
     /// # })
     /// ```
+    #[cfg(feature = "queries")]
     pub fn get<'a, 'b: 'a, IntoSelector>(
         &'a self,
         selector: IntoSelector,
@@ -787,6 +902,7 @@ impl Session {
             scope: Ok(None),
             target: QueryTarget::default(),
             consolidation: QueryConsolidation::default(),
+            strategy: None,
             destination: Locality::default(),
             timeout: Duration::from_millis(unwrap_or_default!(conf.queries_default_timeout())),
             value: None,
@@ -816,6 +932,79 @@ impl Session {
             session: SessionRef::Borrow(self),
         }
     }
+
+    /// Query the publishers and queryables declared on key expressions
+    /// intersecting the given key expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_expr` - The key expression matching the entities to query
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let entities = session.entities_on("key/**").res().await.unwrap();
+    /// while let Ok(entity) = entities.recv_async().await {
+    ///     println!(">> {:?} on {} by {}", entity.kind, entity.key_expr, entity.zid);
+    /// }
+    /// # })
+    /// ```
+    #[zenoh_macros::unstable]
+    #[cfg(feature = "queries")]
+    pub fn entities_on<'a, 'b: 'a, TryIntoKeyExpr>(
+        &'a self,
+        key_expr: TryIntoKeyExpr,
+    ) -> EntitiesBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        let conf = self.runtime.config.lock();
+        EntitiesBuilder {
+            session: self,
+            key_expr: key_expr.try_into().map_err(Into::into),
+            timeout: Duration::from_millis(unwrap_or_default!(conf.queries_default_timeout())),
+            handler: DefaultHandler,
+        }
+    }
+
+    /// Create a [`Subscriber`](Subscriber) notified of the publishers and queryables
+    /// declared and undeclared on key expressions intersecting the given key expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_expr` - The key expression matching the entities to subscribe to
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let subscriber = session.declare_entities_subscriber("key/**").res().await.unwrap();
+    /// while let Ok(event) = subscriber.recv_async().await {
+    ///     println!(">> {:?} {:?}", event.kind, event.entity);
+    /// }
+    /// # })
+    /// ```
+    #[zenoh_macros::unstable]
+    pub fn declare_entities_subscriber<'b, TryIntoKeyExpr>(
+        &self,
+        key_expr: TryIntoKeyExpr,
+    ) -> EntitiesSubscriberBuilder<'_, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        EntitiesSubscriberBuilder {
+            session: SessionRef::Borrow(self),
+            key_expr: key_expr.try_into().map_err(Into::into),
+            handler: DefaultHandler,
+        }
+    }
 }
 
 impl Session {
@@ -987,6 +1176,7 @@ impl Session {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn declare_subscriber_inner(
         &self,
         key_expr: &KeyExpr,
@@ -994,7 +1184,10 @@ impl Session {
         origin: Locality,
         callback: Callback<'static, Sample>,
         info: &SubscriberInfo,
+        filter: Option<ContentFilter>,
+        ordering: Option<OrderingBuffer>,
     ) -> ZResult<Arc<SubscriberState>> {
+        key_expr.check_canon()?;
         let mut state = zwrite!(self.state);
         log::trace!("subscribe({:?})", key_expr);
         let id = state.decl_id_counter.fetch_add(1, Ordering::SeqCst);
@@ -1009,6 +1202,8 @@ impl Session {
             scope: scope.clone().map(|e| e.into_owned()),
             origin,
             callback,
+            filter,
+            ordering,
         });
 
         #[cfg(not(feature = "unstable"))]
@@ -1030,14 +1225,27 @@ impl Session {
                         let joined_sub = state.subscribers.values().any(|s| {
                             s.origin != Locality::SessionLocal && join_sub.includes(&s.key_expr)
                         });
-                        (!joined_sub).then(|| join_sub.clone().into())
+                        (!joined_sub).then(|| (join_sub.clone().into(), None))
                     }
                     None => {
-                        let twin_sub = state
+                        let twin_subs = state
                             .subscribers
                             .values()
-                            .any(|s| s.origin != Locality::SessionLocal && s.key_expr == key_expr);
-                        (!twin_sub).then(|| key_expr.clone())
+                            .filter(|s| {
+                                s.origin != Locality::SessionLocal && s.key_expr == key_expr
+                            })
+                            .collect::<Vec<_>>();
+                        // The filter declared to the router must admit the publications of all the twin subscribers,
+                        // so the subscription is declared again if the new subscriber widens it
+                        let twin_filter = ContentFilter::union(twin_subs.iter().map(|s| &s.filter));
+                        let filter = ContentFilter::union(
+                            twin_subs
+                                .iter()
+                                .map(|s| &s.filter)
+                                .chain(std::iter::once(&sub_state.filter)),
+                        );
+                        (twin_subs.is_empty() || twin_filter != filter)
+                            .then(|| (key_expr.clone(), filter))
                     }
                 }
             })
@@ -1063,7 +1271,7 @@ impl Session {
             }
         }
 
-        if let Some(key_expr) = declared_sub {
+        if let Some((key_expr, filter)) = declared_sub {
             let primitives = state.primitives.as_ref().unwrap().clone();
             drop(state);
             // If key_expr is a pure Expr, remap it to optimal Rid or RidWithSuffix
@@ -1097,6 +1305,9 @@ impl Session {
                     id: id as u32,
                     wire_expr: key_expr.to_wire(self).to_owned(),
                     ext_info: *info,
+                    ext_filter: filter.map(|filter| declare::subscriber::ext::FilterType {
+                        filter: filter.as_str().to_string(),
+                    }),
                 }),
             });
         }
@@ -1188,6 +1399,7 @@ impl Session {
         }
     }
 
+    #[cfg(feature = "queries")]
     pub(crate) fn declare_queryable_inner(
         &self,
         key_expr: &WireExpr,
@@ -1260,6 +1472,7 @@ impl Session {
         Ok(qable_state)
     }
 
+    #[cfg(feature = "queries")]
     pub(crate) fn twin_qabl(state: &SessionState, key: &WireExpr) -> bool {
         state.queryables.values().any(|q| {
             q.origin != Locality::SessionLocal
@@ -1269,6 +1482,7 @@ impl Session {
     }
 
     #[cfg(not(feature = "complete_n"))]
+    #[cfg(feature = "queries")]
     pub(crate) fn complete_twin_qabl(state: &SessionState, key: &WireExpr) -> bool {
         state.queryables.values().any(|q| {
             q.origin != Locality::SessionLocal
@@ -1279,6 +1493,7 @@ impl Session {
     }
 
     #[cfg(feature = "complete_n")]
+    #[cfg(feature = "queries")]
     pub(crate) fn complete_twin_qabls(state: &SessionState, key: &WireExpr) -> u8 {
         state
             .queryables
@@ -1292,6 +1507,7 @@ impl Session {
             .count() as u8
     }
 
+    #[cfg(feature = "queries")]
     pub(crate) fn close_queryable(&self, qid: usize) -> ZResult<()> {
         let mut state = zwrite!(self.state);
         if let Some(qable_state) = state.queryables.remove(&qid) {
@@ -1369,6 +1585,7 @@ impl Session {
         &self,
         key_expr: &KeyExpr,
     ) -> ZResult<Arc<LivelinessTokenState>> {
+        key_expr.check_canon()?;
         let mut state = zwrite!(self.state);
         log::trace!("declare_liveliness({:?})", key_expr);
         let id = state.decl_id_counter.fetch_add(1, Ordering::SeqCst);
@@ -1389,6 +1606,7 @@ impl Session {
                 id: id as u32,
                 wire_expr: key_expr.to_wire(self).to_owned(),
                 ext_info: SubscriberInfo::default(),
+                ext_filter: None,
             }),
         });
         Ok(tok_state)
@@ -1423,6 +1641,21 @@ impl Session {
         }
     }
 
+    // Sends a publication to the network, or buffers it while the client is disconnected if configured so
+    pub(crate) fn send_publication(&self, key_expr: &keyexpr, push: Push) {
+        let (primitives, offline) = {
+            let state = zread!(self.state);
+            (
+                state.primitives.as_ref().unwrap().clone(),
+                state.offline.clone(),
+            )
+        };
+        match offline {
+            Some(offline) => offline.send(&primitives, key_expr, push),
+            None => primitives.send_push(push),
+        }
+    }
+
     pub(crate) fn handle_data(
         &self,
         local: bool,
@@ -1431,13 +1664,26 @@ impl Session {
         payload: ZBuf,
     ) {
         let mut callbacks = SingleOrVec::default();
+        // The subscribers with a content filter only get the publications it admits
+        let admitted = |sub: &SubscriberState| match (&sub.filter, &info) {
+            (
+                Some(filter),
+                Some(DataInfo {
+                    kind: SampleKind::Put,
+                    encoding: Some(encoding),
+                    ..
+                }),
+            ) => filter.admits(encoding, &payload),
+            _ => true,
+        };
         let state = zread!(self.state);
         if key_expr.suffix.is_empty() {
             match state.get_res(&key_expr.scope, key_expr.mapping, local) {
                 Some(Resource::Node(res)) => {
                     for sub in &res.subscribers {
-                        if sub.origin == Locality::Any
-                            || (local == (sub.origin == Locality::SessionLocal))
+                        if (sub.origin == Locality::Any
+                            || (local == (sub.origin == Locality::SessionLocal)))
+                            && admitted(sub)
                         {
                             match &sub.scope {
                                 Some(scope) => {
@@ -1450,10 +1696,9 @@ impl Session {
                                     } else {
                                         match KeyExpr::try_from(&res.key_expr[(scope.len() + 1)..])
                                         {
-                                            Ok(key_expr) => callbacks.push((
-                                                sub.callback.clone(),
-                                                key_expr.into_owned(),
-                                            )),
+                                            Ok(key_expr) => {
+                                                callbacks.push((sub.clone(), key_expr.into_owned()))
+                                            }
                                             Err(e) => {
                                                 log::warn!(
                                                     "Error unscoping received Data for `{}`: {}",
@@ -1464,8 +1709,7 @@ impl Session {
                                         }
                                     }
                                 }
-                                None => callbacks
-                                    .push((sub.callback.clone(), res.key_expr.clone().into())),
+                                None => callbacks.push((sub.clone(), res.key_expr.clone().into())),
                             };
                         }
                     }
@@ -1485,10 +1729,13 @@ impl Session {
         } else {
             match state.wireexpr_to_keyexpr(key_expr, local) {
                 Ok(key_expr) => {
+                    // Shared by the samples of all the matching subscribers
+                    let mut owned_key_expr: Option<KeyExpr<'static>> = None;
                     for sub in state.subscribers.values() {
                         if (sub.origin == Locality::Any
                             || (local == (sub.origin == Locality::SessionLocal)))
                             && key_expr.intersects(&sub.key_expr)
+                            && admitted(sub)
                         {
                             match &sub.scope {
                                 Some(scope) => {
@@ -1500,10 +1747,9 @@ impl Session {
                                         );
                                     } else {
                                         match KeyExpr::try_from(&key_expr[(scope.len() + 1)..]) {
-                                            Ok(key_expr) => callbacks.push((
-                                                sub.callback.clone(),
-                                                key_expr.into_owned(),
-                                            )),
+                                            Ok(key_expr) => {
+                                                callbacks.push((sub.clone(), key_expr.into_owned()))
+                                            }
                                             Err(e) => {
                                                 log::warn!(
                                                     "Error unscoping received Data for `{}`: {}",
@@ -1514,8 +1760,12 @@ impl Session {
                                         }
                                     }
                                 }
-                                None => callbacks
-                                    .push((sub.callback.clone(), key_expr.clone().into_owned())),
+                                None => callbacks.push((
+                                    sub.clone(),
+                                    owned_key_expr
+                                        .get_or_insert_with(|| key_expr.clone().into_owned())
+                                        .clone(),
+                                )),
                             };
                         }
                     }
@@ -1527,12 +1777,112 @@ impl Session {
             }
         };
         drop(state);
+        // The publications of an ordered publisher are numbered by their source info
+        let source = info
+            .as_ref()
+            .and_then(|info| Some((info.source_id?, info.source_eid?, info.source_sn? as u32)));
         let zenoh_collections::single_or_vec::IntoIter { drain, last } = callbacks.into_iter();
-        for (cb, key_expr) in drain {
-            cb(Sample::with_info(key_expr, payload.clone(), info.clone()));
+        for (sub, key_expr) in drain {
+            sub.deliver(
+                source,
+                Sample::with_info(key_expr, payload.clone(), info.clone()),
+            );
+        }
+        if let Some((sub, key_expr)) = last {
+            sub.deliver(source, Sample::with_info(key_expr, payload, info));
+        }
+    }
+
+    // Delivers the values of an atomic batch of publications: each matching subscriber gets in a row
+    // all the values of the batch matching its key expression, numbered among them
+    pub(crate) fn handle_batch(
+        &self,
+        local: bool,
+        key_expr: &WireExpr,
+        info: DataInfo,
+        entries: Vec<BatchEntry>,
+    ) {
+        let mut deliveries: HashMap<Id, (Arc<SubscriberState>, Vec<Sample>)> = HashMap::new();
+        let state = zread!(self.state);
+        let batch_key_expr = match state.wireexpr_to_keyexpr(key_expr, local) {
+            Ok(key_expr) => key_expr,
+            Err(err) => {
+                log::error!("Received Data for unkown key_expr: {}", err);
+                return;
+            }
+        };
+        for entry in entries {
+            // the batch was routed (and access controlled) on a key expression which must include all its keys
+            let key_expr = match KeyExpr::try_from(entry.key) {
+                Ok(key_expr) if batch_key_expr.includes(&key_expr) => key_expr,
+                Ok(key_expr) => {
+                    log::error!(
+                        "Received batched Data for `{}`, which isn't included in `{}`",
+                        key_expr,
+                        batch_key_expr
+                    );
+                    return;
+                }
+                Err(e) => {
+                    log::error!("Received batched Data for invalid key_expr: {}", e);
+                    return;
+                }
+            };
+            let info = DataInfo {
+                encoding: Some(entry.encoding.clone()),
+                ..info.clone()
+            };
+            for sub in state.subscribers.values() {
+                if (sub.origin == Locality::Any
+                    || (local == (sub.origin == Locality::SessionLocal)))
+                    && key_expr.intersects(&sub.key_expr)
+                    && sub.filter.as_ref().map_or(true, |filter| {
+                        filter.admits(&entry.encoding, &entry.payload)
+                    })
+                {
+                    let sub_key_expr = match &sub.scope {
+                        Some(scope) => {
+                            if !key_expr.starts_with(&***scope) {
+                                log::warn!(
+                                    "Received Data for `{}`, which didn't start with scope `{}`: don't deliver to scoped Subscriber.",
+                                    key_expr,
+                                    scope,
+                                );
+                                continue;
+                            }
+                            match KeyExpr::try_from(&key_expr[(scope.len() + 1)..]) {
+                                Ok(key_expr) => key_expr.into_owned(),
+                                Err(e) => {
+                                    log::warn!(
+                                        "Error unscoping received Data for `{}`: {}",
+                                        key_expr,
+                                        e,
+                                    );
+                                    continue;
+                                }
+                            }
+                        }
+                        None => key_expr.clone(),
+                    };
+                    deliveries
+                        .entry(sub.id)
+                        .or_insert_with(|| (sub.clone(), vec![]))
+                        .1
+                        .push(Sample::with_info(
+                            sub_key_expr,
+                            entry.payload.clone(),
+                            Some(info.clone()),
+                        ));
+                }
+            }
         }
-        if let Some((cb, key_expr)) = last {
-            cb(Sample::with_info(key_expr, payload, info));
+        drop(state);
+        for (sub, samples) in deliveries.into_values() {
+            let len = samples.len();
+            for (index, sample) in samples.into_iter().enumerate() {
+                // the values of a batch are not numbered by an ordered publisher
+                sub.deliver(None, sample.in_batch(index, len));
+            }
         }
     }
 
@@ -1559,6 +1909,7 @@ impl Session {
         })
     }
 
+    #[cfg(feature = "queries")]
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn query(
         &self,
@@ -1566,6 +1917,7 @@ impl Session {
         scope: &Option<KeyExpr<'_>>,
         target: QueryTarget,
         consolidation: QueryConsolidation,
+        strategy: Option<Box<dyn ConsolidationStrategy>>,
         destination: Locality,
         timeout: Duration,
         value: Option<Value>,
@@ -1573,16 +1925,22 @@ impl Session {
     ) -> ZResult<()> {
         log::trace!("get({}, {:?}, {:?})", selector, target, consolidation);
         let mut state = zwrite!(self.state);
-        let consolidation = match consolidation.mode {
-            Mode::Auto => {
-                if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY) {
+        let consolidation = match (consolidation.mode, &strategy) {
+            // a custom strategy consolidates all the replies on the querier side
+            (Mode::Auto, Some(_)) => ConsolidationMode::None,
+            (Mode::Auto, None) => {
+                // the queries of a time range get all the values, those of an instant get the latest value of each key
+                if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY)
+                    && !matches!(selector.time_instant(), Ok(Some(_)))
+                {
                     ConsolidationMode::None
                 } else {
                     ConsolidationMode::Latest
                 }
             }
-            Mode::Manual(mode) => mode,
+            (Mode::Manual(mode), _) => mode,
         };
+        let strategy = strategy.unwrap_or_else(|| consolidation.into());
         let qid = state.qid_counter.fetch_add(1, Ordering::SeqCst);
         let nb_final = match destination {
             Locality::Any => 2,
@@ -1594,13 +1952,11 @@ impl Session {
             async move {
                 task::sleep(timeout).await;
                 let mut state = zwrite!(state);
-                if let Some(query) = state.queries.remove(&qid) {
+                if let Some(mut query) = state.queries.remove(&qid) {
                     std::mem::drop(state);
                     log::debug!("Timeout on query {}! Send error and close.", qid);
-                    if query.reception_mode == ConsolidationMode::Latest {
-                        for (_, reply) in query.replies.unwrap().into_iter() {
-                            (query.callback)(reply);
-                        }
+                    for reply in query.consolidation.finalize() {
+                        (query.callback)(reply);
                     }
                     (query.callback)(Reply {
                         sample: Err("Timeout".into()),
@@ -1626,8 +1982,7 @@ impl Session {
                 nb_final,
                 selector: selector.clone().into_owned(),
                 scope: scope.clone().map(|e| e.into_owned()),
-                reception_mode: consolidation,
-                replies: (consolidation != ConsolidationMode::None).then(HashMap::new),
+                consolidation: strategy,
                 callback,
             },
         );
@@ -1678,6 +2033,7 @@ impl Session {
         Ok(())
     }
 
+    #[cfg(feature = "queries")]
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn handle_query(
         &self,
@@ -1689,7 +2045,7 @@ impl Session {
         _consolidation: ConsolidationType,
         body: Option<QueryBodyType>,
     ) {
-        let (primitives, key_expr, callbacks) = {
+        let (primitives, key_expr, callbacks, parallelism, dispatched) = {
             let state = zread!(self.state);
             match state.wireexpr_to_keyexpr(key_expr, local) {
                 Ok(key_expr) => {
@@ -1720,6 +2076,8 @@ impl Session {
                         state.primitives.as_ref().unwrap().clone(),
                         key_expr.into_owned(),
                         callbacks,
+                        state.queryables_parallelism,
+                        state.dispatched_queries.clone(),
                     )
                 }
                 Err(err) => {
@@ -1748,11 +2106,46 @@ impl Session {
                 } else {
                     primitives
                 },
+                reply_priority: AtomicU8::new(Priority::default() as u8),
             }),
         };
-        for callback in callbacks.iter() {
-            callback(query.clone());
+        // The calling thread runs the last callback, the others are dispatched to blocking tasks
+        // as long as less than `parallelism - 1` of them are running. Each callback replies on its
+        // own and the final reply is sent once all the callbacks dropped the query.
+        let (last, others) = match callbacks.split_last() {
+            Some(callbacks) => callbacks,
+            None => return,
+        };
+        for callback in others {
+            if dispatched
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                    (n + 1 < parallelism).then_some(n + 1)
+                })
+                .is_ok()
+            {
+                let callback = callback.clone();
+                let query = query.clone();
+                let dispatched = DispatchedQuery(dispatched.clone());
+                async_std::task::spawn_blocking(move || {
+                    callback(query);
+                    drop(dispatched);
+                });
+            } else {
+                callback(query.clone());
+            }
         }
+        last(query);
+    }
+}
+
+/// Releases its slot of the concurrently dispatched queries when dropped, even if the callback panicked.
+#[cfg(feature = "queries")]
+struct DispatchedQuery(Arc<AtomicUsize>);
+
+#[cfg(feature = "queries")]
+impl Drop for DispatchedQuery {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
     }
 }
 
@@ -1794,6 +2187,8 @@ impl SessionDeclarations for Arc<Session> {
             reliability: Reliability::default(),
             mode: PushMode,
             origin: Locality::default(),
+            filter: None,
+            ordering: None,
             handler: DefaultHandler,
         }
     }
@@ -1825,6 +2220,7 @@ impl SessionDeclarations for Arc<Session> {
     /// }).await;
     /// # })
     /// ```
+    #[cfg(feature = "queries")]
     fn declare_queryable<'b, TryIntoKeyExpr>(
         &self,
         key_expr: TryIntoKeyExpr,
@@ -1844,6 +2240,8 @@ impl SessionDeclarations for Arc<Session> {
 
     /// Create a [`Publisher`](crate::publication::Publisher) for the given key expression.
     ///
+    /// The key expression is copied if it is borrowed, so that the publisher does not borrow it.
+    ///
     /// # Arguments
     ///
     /// * `key_expr` - The key expression matching resources to write
@@ -1864,17 +2262,24 @@ impl SessionDeclarations for Arc<Session> {
     fn declare_publisher<'b, TryIntoKeyExpr>(
         &self,
         key_expr: TryIntoKeyExpr,
-    ) -> PublisherBuilder<'static, 'b>
+    ) -> PublisherBuilder<'static, 'static>
     where
         TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
         <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
     {
         PublisherBuilder {
             session: SessionRef::Shared(self.clone()),
-            key_expr: key_expr.try_into().map_err(Into::into),
+            key_expr: key_expr
+                .try_into()
+                .map(KeyExpr::into_owned)
+                .map_err(Into::into),
             congestion_control: CongestionControl::default(),
             priority: Priority::default(),
             destination: Locality::default(),
+            trace: false,
+            lifespan: None,
+            ordered: false,
+            express: false,
         }
     }
 
@@ -2006,9 +2411,24 @@ impl Primitives for Session {
                     encoding: Some(m.encoding),
                     timestamp: m.timestamp,
                     source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
+                    source_eid: m.ext_sinfo.as_ref().map(|i| i.eid),
                     source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
+                    trace: m.ext_trace.map(|mut trace| {
+                        // Record the reception by this session as the last hop
+                        let now = uhlc::system_time_clock();
+                        trace.hops.push(TraceHop {
+                            zid: self.runtime.zid,
+                            ingress: now,
+                            egress: now,
+                        });
+                        trace.hops
+                    }),
+                    deadline: m.ext_deadline,
                 };
-                self.handle_data(false, &msg.wire_expr, Some(info), m.payload)
+                match m.ext_batch {
+                    Some(batch) => self.handle_batch(false, &msg.wire_expr, info, batch.entries),
+                    None => self.handle_data(false, &msg.wire_expr, Some(info), m.payload),
+                }
             }
             PushBody::Del(m) => {
                 let info = DataInfo {
@@ -2016,7 +2436,10 @@ impl Primitives for Session {
                     encoding: None,
                     timestamp: m.timestamp,
                     source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
+                    source_eid: m.ext_sinfo.as_ref().map(|i| i.eid),
                     source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
+                    trace: None,
+                    deadline: None,
                 };
                 self.handle_data(false, &msg.wire_expr, Some(info), ZBuf::empty())
             }
@@ -2026,6 +2449,7 @@ impl Primitives for Session {
     fn send_request(&self, msg: Request) {
         trace!("recv Request {:?}", msg);
         match msg.payload {
+            #[cfg(feature = "queries")]
             RequestBody::Query(m) => self.handle_query(
                 false,
                 &msg.wire_expr,
@@ -2035,164 +2459,142 @@ impl Primitives for Session {
                 m.ext_consolidation,
                 m.ext_body,
             ),
+            // Without queryables, the queries are only answered with their final response
+            #[cfg(not(feature = "queries"))]
+            RequestBody::Query(_) => {
+                let primitives = zread!(self.state).primitives.clone();
+                if let Some(primitives) = primitives {
+                    primitives.send_response_final(ResponseFinal {
+                        rid: msg.id,
+                        ext_qos: response::ext::QoSType::response_final_default(),
+                        ext_tstamp: None,
+                    });
+                }
+            }
             RequestBody::Put(_) => (),
             RequestBody::Del(_) => (),
             RequestBody::Pull(_) => todo!(),
         }
     }
 
+    #[cfg(not(feature = "queries"))]
     fn send_response(&self, msg: Response) {
         trace!("recv Response {:?}", msg);
-        if let ResponseBody::Reply(m) = msg.payload {
-            let mut state = zwrite!(self.state);
-            let key_expr = match state.remote_key_to_expr(&msg.wire_expr) {
-                Ok(key) => key.into_owned(),
-                Err(e) => {
-                    error!("Received ReplyData for unkown key_expr: {}", e);
+    }
+
+    #[cfg(feature = "queries")]
+    fn send_response(&self, msg: Response) {
+        trace!("recv Response {:?}", msg);
+        let (payload, info) = match msg.payload {
+            ResponseBody::Reply(m) => (
+                m.payload,
+                DataInfo {
+                    kind: SampleKind::Put,
+                    encoding: Some(m.encoding),
+                    timestamp: m.timestamp,
+                    source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
+                    source_eid: m.ext_sinfo.as_ref().map(|i| i.eid),
+                    source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
+                    trace: None,
+                    deadline: None,
+                },
+            ),
+            ResponseBody::Del(m) => (
+                ZBuf::empty(),
+                DataInfo {
+                    kind: SampleKind::Delete,
+                    encoding: None,
+                    timestamp: m.timestamp,
+                    source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
+                    source_eid: m.ext_sinfo.as_ref().map(|i| i.eid),
+                    source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
+                    trace: None,
+                    deadline: None,
+                },
+            ),
+            _ => return,
+        };
+        let mut state = zwrite!(self.state);
+        let key_expr = match state.remote_key_to_expr(&msg.wire_expr) {
+            Ok(key) => key.into_owned(),
+            Err(e) => {
+                error!("Received ReplyData for unkown key_expr: {}", e);
+                return;
+            }
+        };
+        match state.queries.get_mut(&msg.rid) {
+            Some(query) => {
+                if !matches!(
+                    query
+                        .selector
+                        .parameters()
+                        .get_bools([crate::query::_REPLY_KEY_EXPR_ANY_SEL_PARAM]),
+                    Ok([true])
+                ) && !query.selector.key_expr.intersects(&key_expr)
+                {
+                    log::warn!(
+                        "Received Reply for `{}` from `{:?}, which didn't match query `{}`: dropping Reply.",
+                        key_expr,
+                        msg.ext_respid,
+                        query.selector
+                    );
                     return;
                 }
-            };
-            match state.queries.get_mut(&msg.rid) {
-                Some(query) => {
-                    if !matches!(
-                        query
-                            .selector
-                            .parameters()
-                            .get_bools([crate::query::_REPLY_KEY_EXPR_ANY_SEL_PARAM]),
-                        Ok([true])
-                    ) && !query.selector.key_expr.intersects(&key_expr)
-                    {
-                        log::warn!(
-                            "Received Reply for `{}` from `{:?}, which didn't match query `{}`: dropping Reply.",
-                            key_expr,
-                            msg.ext_respid,
-                            query.selector
-                        );
-                        return;
-                    }
-                    let key_expr = match &query.scope {
-                        Some(scope) => {
-                            if !key_expr.starts_with(&***scope) {
+                let key_expr = match &query.scope {
+                    Some(scope) => {
+                        if !key_expr.starts_with(&***scope) {
+                            log::warn!(
+                                "Received Reply for `{}` from `{:?}, which didn't start with scope `{}`: dropping Reply.",
+                                key_expr,
+                                msg.ext_respid,
+                                scope,
+                            );
+                            return;
+                        }
+                        match KeyExpr::try_from(&key_expr[(scope.len() + 1)..]) {
+                            Ok(key_expr) => key_expr,
+                            Err(e) => {
                                 log::warn!(
-                                    "Received Reply for `{}` from `{:?}, which didn't start with scope `{}`: dropping Reply.",
+                                    "Error unscoping received Reply for `{}` from `{:?}: {}",
                                     key_expr,
                                     msg.ext_respid,
-                                    scope,
+                                    e,
                                 );
                                 return;
                             }
-                            match KeyExpr::try_from(&key_expr[(scope.len() + 1)..]) {
-                                Ok(key_expr) => key_expr,
-                                Err(e) => {
-                                    log::warn!(
-                                        "Error unscoping received Reply for `{}` from `{:?}: {}",
-                                        key_expr,
-                                        msg.ext_respid,
-                                        e,
-                                    );
-                                    return;
-                                }
-                            }
-                        }
-                        None => key_expr,
-                    };
-                    let info = DataInfo {
-                        kind: SampleKind::Put,
-                        encoding: Some(m.encoding),
-                        timestamp: m.timestamp,
-                        source_id: m.ext_sinfo.as_ref().map(|i| i.zid),
-                        source_sn: m.ext_sinfo.as_ref().map(|i| i.sn as u64),
-                    };
-                    let new_reply = Reply {
-                        sample: Ok(Sample::with_info(
-                            key_expr.into_owned(),
-                            m.payload,
-                            Some(info),
-                        )),
-                        replier_id: ZenohId::rand(), // TOTO
-                    };
-                    let callback = match query.reception_mode {
-                        ConsolidationMode::None => Some((query.callback.clone(), new_reply)),
-                        ConsolidationMode::Monotonic => {
-                            match query
-                                .replies
-                                .as_ref()
-                                .unwrap()
-                                .get(new_reply.sample.as_ref().unwrap().key_expr.as_keyexpr())
-                            {
-                                Some(reply) => {
-                                    if new_reply.sample.as_ref().unwrap().timestamp
-                                        > reply.sample.as_ref().unwrap().timestamp
-                                    {
-                                        query.replies.as_mut().unwrap().insert(
-                                            new_reply
-                                                .sample
-                                                .as_ref()
-                                                .unwrap()
-                                                .key_expr
-                                                .clone()
-                                                .into(),
-                                            new_reply.clone(),
-                                        );
-                                        Some((query.callback.clone(), new_reply))
-                                    } else {
-                                        None
-                                    }
-                                }
-                                None => {
-                                    query.replies.as_mut().unwrap().insert(
-                                        new_reply.sample.as_ref().unwrap().key_expr.clone().into(),
-                                        new_reply.clone(),
-                                    );
-                                    Some((query.callback.clone(), new_reply))
-                                }
-                            }
-                        }
-                        ConsolidationMode::Latest => {
-                            match query
-                                .replies
-                                .as_ref()
-                                .unwrap()
-                                .get(new_reply.sample.as_ref().unwrap().key_expr.as_keyexpr())
-                            {
-                                Some(reply) => {
-                                    if new_reply.sample.as_ref().unwrap().timestamp
-                                        > reply.sample.as_ref().unwrap().timestamp
-                                    {
-                                        query.replies.as_mut().unwrap().insert(
-                                            new_reply
-                                                .sample
-                                                .as_ref()
-                                                .unwrap()
-                                                .key_expr
-                                                .clone()
-                                                .into(),
-                                            new_reply,
-                                        );
-                                    }
-                                }
-                                None => {
-                                    query.replies.as_mut().unwrap().insert(
-                                        new_reply.sample.as_ref().unwrap().key_expr.clone().into(),
-                                        new_reply,
-                                    );
-                                }
-                            };
-                            None
                         }
-                    };
-                    std::mem::drop(state);
-                    if let Some((callback, new_reply)) = callback {
-                        callback(new_reply);
                     }
-                }
-                None => {
-                    log::warn!("Received ReplyData for unkown Query: {}", msg.rid);
+                    None => key_expr,
+                };
+                let new_reply = Reply {
+                    sample: Ok(Sample::with_info(
+                        key_expr.into_owned(),
+                        payload,
+                        Some(info),
+                    )),
+                    replier_id: ZenohId::rand(), // TOTO
+                };
+                let callback = query
+                    .consolidation
+                    .consolidate(new_reply)
+                    .map(|reply| (query.callback.clone(), reply));
+                std::mem::drop(state);
+                if let Some((callback, new_reply)) = callback {
+                    callback(new_reply);
                 }
             }
+            None => {
+                log::warn!("Received ReplyData for unkown Query: {}", msg.rid);
+            }
         }
     }
 
+    #[cfg(not(feature = "queries"))]
+    fn send_response_final(&self, msg: ResponseFinal) {
+        trace!("recv ResponseFinal {:?}", msg);
+    }
+
+    #[cfg(feature = "queries")]
     fn send_response_final(&self, msg: ResponseFinal) {
         trace!("recv ResponseFinal {:?}", msg);
         let mut state = zwrite!(self.state);
@@ -2200,18 +2602,16 @@ impl Primitives for Session {
             Some(query) => {
                 query.nb_final -= 1;
                 if query.nb_final == 0 {
-                    let query = state.queries.remove(&msg.rid).unwrap();
+                    let mut query = state.queries.remove(&msg.rid).unwrap();
                     std::mem::drop(state);
-                    if query.reception_mode == ConsolidationMode::Latest {
-                        for (_, reply) in query.replies.unwrap().into_iter() {
-                            (query.callback)(reply);
-                        }
+                    for reply in query.consolidation.finalize() {
+                        (query.callback)(reply);
                     }
                     trace!("Close query {}", msg.rid);
                 }
             }
             None => {
-                warn!("Received ResponseFinal for unkown Request: {}", msg.rid);
+                log::warn!("Received ResponseFinal for unkown Request: {}", msg.rid);
             }
         }
     }
@@ -2238,9 +2638,12 @@ impl fmt::Debug for Session {
 /// Functions to create zenoh entities with `'static` lifetime.
 ///
 /// This trait contains functions to create zenoh entities like
-/// [`Subscriber`](crate::subscriber::Subscriber), and
-/// [`Queryable`](crate::queryable::Queryable) with a `'static` lifetime.
-/// This is useful to move zenoh entities to several threads and tasks.
+/// [`Subscriber`](crate::subscriber::Subscriber),
+/// [`Queryable`](crate::queryable::Queryable) and
+/// [`Publisher`](crate::publication::Publisher) with a `'static` lifetime.
+/// This is useful to move zenoh entities to several threads and tasks, or
+/// to store them in structs, whatever the lifetime of the key expressions
+/// they are declared with.
 ///
 /// This trait is implemented for `Arc<Session>`.
 ///
@@ -2320,6 +2723,7 @@ pub trait SessionDeclarations {
     /// }).await;
     /// # })
     /// ```
+    #[cfg(feature = "queries")]
     fn declare_queryable<'a, TryIntoKeyExpr>(
         &self,
         key_expr: TryIntoKeyExpr,
@@ -2330,6 +2734,8 @@ pub trait SessionDeclarations {
 
     /// Create a [`Publisher`](crate::publication::Publisher) for the given key expression.
     ///
+    /// The key expression is copied if it is borrowed, so that the publisher does not borrow it.
+    ///
     /// # Arguments
     ///
     /// * `key_expr` - The key expression matching resources to write
@@ -2350,7 +2756,7 @@ pub trait SessionDeclarations {
     fn declare_publisher<'a, TryIntoKeyExpr>(
         &self,
         key_expr: TryIntoKeyExpr,
-    ) -> PublisherBuilder<'static, 'a>
+    ) -> PublisherBuilder<'static, 'static>
     where
         TryIntoKeyExpr: TryInto<KeyExpr<'a>>,
         <TryIntoKeyExpr as TryInto<KeyExpr<'a>>>::Error: Into<zenoh_result::Error>;
@@ -0,0 +1,208 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! In-process simulations of zenoh systems, to test key spaces, storages and query patterns
+//! without real sockets. Only available with the `simulation` feature.
+//!
+//! The routers, peers and clients of a [`Simulation`] run in the current process and are
+//! connected by in-memory links (`mem/<address>`). Each node gets a zid derived from its rank in
+//! the simulation and the multicast scouting is disabled, for the topology to be the same on each run.
+//!
+//! The time of the clock used by the nodes to timestamp the samples and to compute the deadlines
+//! of the publications (see [`clock`](crate::time::clock)) is virtual: it starts at
+//! [`SIMULATION_EPOCH`] and only moves forward with [`Simulation::advance`].
+//! The timers of the transports and of the plugins remain on real time, and the messages are
+//! still routed asynchronously: the tests wait for the samples and the replies as usual.
+use crate::net::runtime::Runtime;
+use crate::prelude::r#async::*;
+use crate::time::NTP64;
+use crate::Session;
+use async_std::task;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_link::mem::MEM_LOCATOR_PREFIX;
+use zenoh_result::{bail, zerror, ZResult};
+
+/// The virtual time a [`Simulation`] starts at: 2020-01-01T00:00:00Z.
+pub const SIMULATION_EPOCH: Duration = Duration::from_secs(1_577_836_800);
+
+// The simulations of the process run one at a time as they share the virtual clock
+static RUNNING: AtomicBool = AtomicBool::new(false);
+// The virtual time of the running simulation, 0 if none is running
+static VIRTUAL_TIME: AtomicU64 = AtomicU64::new(0);
+// The counter used to give distinct addresses to the nodes of the successive simulations
+static SIMULATION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The virtual time of the running simulation, if any.
+pub(crate) fn virtual_time() -> Option<NTP64> {
+    match VIRTUAL_TIME.load(Ordering::Acquire) {
+        0 => None,
+        time => Some(NTP64(time)),
+    }
+}
+
+struct Node {
+    session: Arc<Session>,
+    endpoint: Option<EndPoint>,
+}
+
+/// A set of zenoh nodes running in the current process over in-memory links, with a virtual clock.
+///
+/// # Examples
+/// ```no_run
+/// # async_std::task::block_on(async {
+/// use std::time::Duration;
+/// use zenoh::prelude::r#async::*;
+/// use zenoh::simulation::Simulation;
+///
+/// let mut simulation = Simulation::new().await;
+/// simulation.add_router("router", &[]).await.unwrap();
+/// let publisher = simulation.add_peer("publisher", &["router"]).await.unwrap();
+/// let subscriber = simulation.add_client("subscriber", &["router"]).await.unwrap();
+///
+/// let samples = subscriber.declare_subscriber("demo/**").res().await.unwrap();
+/// simulation.advance(Duration::from_secs(60));
+/// publisher.put("demo/a", "value").res().await.unwrap();
+/// let sample = samples.recv_async().await.unwrap();
+/// # })
+/// ```
+pub struct Simulation {
+    id: u64,
+    nodes: HashMap<String, Node>,
+}
+
+impl Simulation {
+    /// Starts a simulation, waiting for the running one of the process, if any, to be dropped.
+    pub async fn new() -> Simulation {
+        while RUNNING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            task::sleep(Duration::from_millis(10)).await;
+        }
+        VIRTUAL_TIME.store(NTP64::from(SIMULATION_EPOCH).as_u64(), Ordering::Release);
+        Simulation {
+            id: SIMULATION_ID.fetch_add(1, Ordering::Relaxed),
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Adds a router connected to the nodes named in `connect`.
+    pub async fn add_router(&mut self, name: &str, connect: &[&str]) -> ZResult<Arc<Session>> {
+        self.add_node(name, Config::profile(WhatAmI::Router), connect)
+            .await
+    }
+
+    /// Adds a peer connected to the nodes named in `connect`.
+    pub async fn add_peer(&mut self, name: &str, connect: &[&str]) -> ZResult<Arc<Session>> {
+        self.add_node(name, Config::profile(WhatAmI::Peer), connect)
+            .await
+    }
+
+    /// Adds a client connected to the nodes named in `connect`.
+    pub async fn add_client(&mut self, name: &str, connect: &[&str]) -> ZResult<Arc<Session>> {
+        self.add_node(name, Config::profile(WhatAmI::Client), connect)
+            .await
+    }
+
+    /// Adds a node with the given configuration, e.g. to enable the timestamping or to configure
+    /// the plugins, connected to the nodes named in `connect`.
+    ///
+    /// The zid, the endpoints and the multicast scouting of the configuration are overwritten.
+    pub async fn add_node(
+        &mut self,
+        name: &str,
+        mut config: Config,
+        connect: &[&str],
+    ) -> ZResult<Arc<Session>> {
+        if self.nodes.contains_key(name) {
+            bail!("A node named {} already exists in the simulation", name);
+        }
+        let rank = self.nodes.len() as u128 + 1;
+        let zid = ZenohId::try_from(rank.to_le_bytes())?;
+        config
+            .set_id(zid)
+            .map_err(|_| zerror!("Unable to set the zid of the node {}", name))?;
+        config
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .map_err(|_| zerror!("Unable to disable the scouting of the node {}", name))?;
+
+        let endpoint = match config.mode() {
+            Some(WhatAmI::Client) => None,
+            _ => Some(EndPoint::new(
+                MEM_LOCATOR_PREFIX,
+                format!("sim{}/{}", self.id, name),
+                "",
+                "",
+            )?),
+        };
+        config.listen.endpoints = endpoint.iter().cloned().collect();
+        config.connect.endpoints = connect
+            .iter()
+            .map(|target| match self.nodes.get(*target) {
+                Some(Node {
+                    endpoint: Some(endpoint),
+                    ..
+                }) => Ok(endpoint.clone()),
+                Some(_) => bail!("The node {} does not accept connections", target),
+                None => bail!("No node named {} in the simulation", target),
+            })
+            .collect::<ZResult<_>>()?;
+
+        let session = crate::open(config).res_async().await?.into_arc();
+        self.nodes.insert(
+            name.to_string(),
+            Node {
+                session: session.clone(),
+                endpoint,
+            },
+        );
+        Ok(session)
+    }
+
+    /// The session of the node named `name`.
+    pub fn session(&self, name: &str) -> Option<Arc<Session>> {
+        self.nodes.get(name).map(|node| node.session.clone())
+    }
+
+    /// The runtime of the node named `name`, e.g. to start a plugin on it.
+    pub fn runtime(&self, name: &str) -> Option<Runtime> {
+        self.nodes
+            .get(name)
+            .map(|node| node.session.runtime.clone())
+    }
+
+    /// The current virtual time.
+    pub fn now(&self) -> NTP64 {
+        NTP64(VIRTUAL_TIME.load(Ordering::Acquire))
+    }
+
+    /// Moves the virtual time forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        VIRTUAL_TIME.fetch_add(NTP64::from(duration).as_u64(), Ordering::AcqRel);
+    }
+}
+
+impl Drop for Simulation {
+    fn drop(&mut self) {
+        self.nodes.clear();
+        VIRTUAL_TIME.store(0, Ordering::Release);
+        RUNNING.store(false, Ordering::Release);
+    }
+}
@@ -14,15 +14,20 @@
 
 //! Subscribing primitives.
 use crate::handlers::{locked, Callback, DefaultHandler};
+use crate::net::routing::content_filter::ContentFilter;
 use crate::prelude::Locality;
-use crate::prelude::{Id, IntoCallbackReceiverPair, KeyExpr, Sample};
+use crate::prelude::{Id, IntoCallbackReceiverPair, KeyExpr, Sample, ZenohId};
 use crate::Undeclarable;
 use crate::{Result as ZResult, SessionRef};
+use futures::Stream;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::future::Ready;
 use std::ops::{Deref, DerefMut};
-use std::sync::Arc;
-use zenoh_core::{AsyncResolve, Resolvable, Resolve, SyncResolve};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use zenoh_core::{zlock, AsyncResolve, Resolvable, Resolve, SyncResolve};
 use zenoh_protocol::network::declare::{subscriber::ext::SubscriberInfo, Mode};
 
 /// The subscription mode.
@@ -37,6 +42,19 @@ pub(crate) struct SubscriberState {
     pub(crate) scope: Option<KeyExpr<'static>>,
     pub(crate) origin: Locality,
     pub(crate) callback: Callback<'static, Sample>,
+    pub(crate) filter: Option<ContentFilter>,
+    pub(crate) ordering: Option<OrderingBuffer>,
+}
+
+impl SubscriberState {
+    /// Calls the callback of the subscriber with the given sample, published by the
+    /// given `(source_id, source_eid, source_sn)` if any.
+    pub(crate) fn deliver(&self, source: Option<(ZenohId, u32, u32)>, sample: Sample) {
+        match &self.ordering {
+            Some(ordering) => ordering.deliver(source, sample, &*self.callback),
+            None => (self.callback)(sample),
+        }
+    }
 }
 
 impl fmt::Debug for SubscriberState {
@@ -48,6 +66,111 @@ impl fmt::Debug for SubscriberState {
     }
 }
 
+/// The state of the reordering buffer of an [`ordered`](SubscriberBuilder::ordered) subscriber.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrderingStats {
+    /// The number of samples currently held back until the ones published before them are received.
+    pub buffered: usize,
+    /// The number of samples received before some of the ones published before them.
+    pub reordered: u64,
+    /// The number of missing samples given up on to deliver the following ones, the buffer being full.
+    pub skipped: u64,
+    /// The number of samples dropped because received after following ones were delivered.
+    pub discarded: u64,
+}
+
+/// The samples held back for an ordered publisher, until the one numbered `next_sn` is received.
+struct SourceQueue {
+    next_sn: u32,
+    pending: BTreeMap<u32, Sample>,
+}
+
+impl SourceQueue {
+    fn release(&mut self, stats: &mut OrderingStats, callback: &dyn Fn(Sample)) {
+        while let Some(sample) = self.pending.remove(&self.next_sn) {
+            stats.buffered -= 1;
+            callback(sample);
+            self.next_sn = self.next_sn.wrapping_add(1);
+        }
+    }
+}
+
+#[derive(Default)]
+struct OrderingState {
+    sources: HashMap<(ZenohId, u32), SourceQueue>,
+    stats: OrderingStats,
+}
+
+/// The reordering buffer of an [`ordered`](SubscriberBuilder::ordered) subscriber.
+///
+/// The samples are delivered while the buffer is locked, so that the concurrent deliveries
+/// can't overtake each other.
+pub(crate) struct OrderingBuffer {
+    capacity: usize,
+    state: Mutex<OrderingState>,
+}
+
+impl OrderingBuffer {
+    pub(crate) fn new(capacity: usize) -> Self {
+        OrderingBuffer {
+            capacity,
+            state: Mutex::new(OrderingState::default()),
+        }
+    }
+
+    pub(crate) fn deliver(
+        &self,
+        source: Option<(ZenohId, u32, u32)>,
+        sample: Sample,
+        callback: &dyn Fn(Sample),
+    ) {
+        // The samples of the publishers which are not ordered are delivered right away
+        let Some((source_id, source_eid, sn)) = source else {
+            return callback(sample);
+        };
+        let mut state = zlock!(self.state);
+        let OrderingState { sources, stats } = &mut *state;
+        // The first received sample of a publisher starts its sequence
+        let queue = sources
+            .entry((source_id, source_eid))
+            .or_insert_with(|| SourceQueue {
+                next_sn: sn,
+                pending: BTreeMap::new(),
+            });
+        if sn < queue.next_sn {
+            stats.discarded += 1;
+            return;
+        }
+        if sn > queue.next_sn {
+            if queue.pending.insert(sn, sample).is_none() {
+                stats.buffered += 1;
+                stats.reordered += 1;
+            }
+            while stats.buffered > self.capacity {
+                let first = *queue.pending.keys().next().unwrap();
+                log::debug!(
+                    "Ordering buffer full: skipping {} samples from {}:{}",
+                    first - queue.next_sn,
+                    source_id,
+                    source_eid
+                );
+                stats.skipped += u64::from(first - queue.next_sn);
+                queue.next_sn = first;
+                queue.release(stats, callback);
+            }
+        } else {
+            callback(sample);
+            queue.next_sn = sn.wrapping_add(1);
+            queue.release(stats, callback);
+        }
+    }
+
+    #[cfg_attr(not(feature = "unstable"), allow(dead_code))]
+    pub(crate) fn stats(&self) -> OrderingStats {
+        zlock!(self.state).stats
+    }
+}
+
 /// A subscriber that provides data through a callback.
 ///
 /// CallbackSubscribers can be created from a zenoh [`Session`](crate::Session)
@@ -325,6 +448,16 @@ pub struct SubscriberBuilder<'a, 'b, Mode, Handler> {
     #[cfg(not(feature = "unstable"))]
     pub(crate) origin: Locality,
 
+    #[cfg(feature = "unstable")]
+    pub filter: Option<String>,
+    #[cfg(not(feature = "unstable"))]
+    pub(crate) filter: Option<String>,
+
+    #[cfg(feature = "unstable")]
+    pub ordering: Option<usize>,
+    #[cfg(not(feature = "unstable"))]
+    pub(crate) ordering: Option<usize>,
+
     #[cfg(feature = "unstable")]
     pub handler: Handler,
     #[cfg(not(feature = "unstable"))]
@@ -359,6 +492,8 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            filter,
+            ordering,
             handler: _,
         } = self;
         SubscriberBuilder {
@@ -367,6 +502,8 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            filter,
+            ordering,
             handler: callback,
         }
     }
@@ -421,6 +558,27 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
     /// }
     /// # })
     /// ```
+    ///
+    /// The receiver of a channel can also be used from a thread without any async executor,
+    /// `recv()` blocking it until the next sample is received. Note that the zenoh thread delivering the samples
+    /// blocks while a bounded channel is full, see [`IntoCallbackReceiverPair`](crate::prelude::IntoCallbackReceiverPair).
+    /// ```no_run
+    /// use zenoh::prelude::sync::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().unwrap();
+    /// let subscriber = session
+    ///     .declare_subscriber("key/expression")
+    ///     .with(flume::bounded(32))
+    ///     .res()
+    ///     .unwrap();
+    /// // The receiver outlives the thread, unlike the subscriber which borrows the session
+    /// let receiver = subscriber.receiver.clone();
+    /// std::thread::spawn(move || {
+    ///     while let Ok(sample) = receiver.recv() {
+    ///         println!("Received: {} {}", sample.key_expr, sample.value);
+    ///     }
+    /// });
+    /// ```
     #[inline]
     pub fn with<Handler>(self, handler: Handler) -> SubscriberBuilder<'a, 'b, Mode, Handler>
     where
@@ -432,6 +590,8 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            filter,
+            ordering,
             handler: _,
         } = self;
         SubscriberBuilder {
@@ -440,6 +600,8 @@ impl<'a, 'b, Mode> SubscriberBuilder<'a, 'b, Mode, DefaultHandler> {
             reliability,
             mode,
             origin,
+            filter,
+            ordering,
             handler,
         }
     }
@@ -466,6 +628,21 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
         self
     }
 
+    /// Only receive the publications with a JSON payload satisfying the given predicate, e.g. `temp > 90`.
+    /// The routers evaluate it before forwarding the publications, sparing the bandwidth of the discarded ones.
+    ///
+    /// The predicate combines comparisons `<field>[.<field>] <op> <JSON value>`, with `op` one of
+    /// `==`, `!=`, `>`, `>=`, `<` and `<=`, with `&&` and `||` (e.g. `engine.temp > 90 && status == "on"`).
+    /// The publications which are not JSON-encoded are not filtered.
+    #[inline]
+    pub fn filter<IntoString>(mut self, filter: IntoString) -> Self
+    where
+        IntoString: Into<String>,
+    {
+        self.filter = Some(filter.into());
+        self
+    }
+
     /// Restrict the matching publications that will be receive by this [`Subscriber`]
     /// to the ones that have the given [`Locality`](crate::prelude::Locality).
     #[zenoh_macros::unstable]
@@ -475,6 +652,19 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
         self
     }
 
+    /// Receive the samples of each [`ordered`](crate::publication::PublisherBuilder::ordered) publisher
+    /// in publication order, even when they are routed with different priorities or over different links.
+    ///
+    /// The samples received before some of the ones published before them are held back, in a buffer
+    /// of at most `capacity` samples. When it is full, the missing samples are given up on.
+    /// The [`ordering_stats`](Subscriber::ordering_stats) of the subscriber report the state of the buffer.
+    #[zenoh_macros::unstable]
+    #[inline]
+    pub fn ordered(mut self, capacity: usize) -> Self {
+        self.ordering = Some(capacity);
+        self
+    }
+
     /// Change the subscription mode to Pull.
     #[inline]
     pub fn pull_mode(self) -> SubscriberBuilder<'a, 'b, PullMode, Handler> {
@@ -484,6 +674,8 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: _,
             origin,
+            filter,
+            ordering,
             handler,
         } = self;
         SubscriberBuilder {
@@ -492,6 +684,8 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: PullMode,
             origin,
+            filter,
+            ordering,
             handler,
         }
     }
@@ -505,6 +699,8 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: _,
             origin,
+            filter,
+            ordering,
             handler,
         } = self;
         SubscriberBuilder {
@@ -513,6 +709,8 @@ impl<'a, 'b, Mode, Handler> SubscriberBuilder<'a, 'b, Mode, Handler> {
             reliability,
             mode: PushMode,
             origin,
+            filter,
+            ordering,
             handler,
         }
     }
@@ -534,6 +732,7 @@ where
 {
     fn res_sync(self) -> <Self as Resolvable>::To {
         let key_expr = self.key_expr?;
+        let filter = self.filter.as_deref().map(ContentFilter::new).transpose()?;
         let session = self.session;
         let (callback, receiver) = self.handler.into_cb_receiver_pair();
         session
@@ -546,6 +745,8 @@ where
                     reliability: self.reliability,
                     mode: self.mode.into(),
                 },
+                filter,
+                self.ordering.map(OrderingBuffer::new),
             )
             .map(|sub_state| Subscriber {
                 subscriber: SubscriberInner {
@@ -586,6 +787,7 @@ where
 {
     fn res_sync(self) -> <Self as Resolvable>::To {
         let key_expr = self.key_expr?;
+        let filter = self.filter.as_deref().map(ContentFilter::new).transpose()?;
         let session = self.session;
         let (callback, receiver) = self.handler.into_cb_receiver_pair();
         session
@@ -598,6 +800,8 @@ where
                     reliability: self.reliability,
                     mode: self.mode.into(),
                 },
+                filter,
+                self.ordering.map(OrderingBuffer::new),
             )
             .map(|sub_state| PullSubscriber {
                 subscriber: PullSubscriberInner {
@@ -704,6 +908,20 @@ impl<'a, Receiver> DerefMut for PullSubscriber<'a, Receiver> {
     }
 }
 
+impl<Receiver: Stream + Unpin> Stream for PullSubscriber<'_, Receiver> {
+    type Item = Receiver::Item;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.receiver.size_hint()
+    }
+}
+
 impl<'a, Receiver> PullSubscriber<'a, Receiver> {
     /// Pull available data for a [`PullSubscriber`].
     ///
@@ -760,6 +978,17 @@ impl<'a, Receiver> Subscriber<'a, Receiver> {
         &self.subscriber.state.key_expr
     }
 
+    /// Returns the state of the reordering buffer of this Subscriber,
+    /// if it is [`ordered`](SubscriberBuilder::ordered).
+    #[zenoh_macros::unstable]
+    pub fn ordering_stats(&self) -> Option<OrderingStats> {
+        self.subscriber
+            .state
+            .ordering
+            .as_ref()
+            .map(OrderingBuffer::stats)
+    }
+
     /// Close a [`Subscriber`].
     ///
     /// Subscribers are automatically closed when dropped, but you may want to use this function to handle errors or
@@ -803,5 +1032,69 @@ impl<Receiver> DerefMut for Subscriber<'_, Receiver> {
     }
 }
 
+impl<Receiver: Stream + Unpin> Stream for Subscriber<'_, Receiver> {
+    type Item = Receiver::Item;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.receiver.size_hint()
+    }
+}
+
 /// A [`Subscriber`] that provides data through a `flume` channel.
 pub type FlumeSubscriber<'a> = Subscriber<'a, flume::Receiver<Sample>>;
+
+#[test]
+fn ordering_buffer() {
+    let source_id = ZenohId::rand();
+    let received = Mutex::new(vec![]);
+    let callback = |sample: Sample| received.lock().unwrap().push(sample.value.to_string());
+    let buffer = OrderingBuffer::new(2);
+    let deliver = |eid, sn: u32| {
+        buffer.deliver(
+            Some((source_id, eid, sn)),
+            Sample::new(
+                KeyExpr::try_from("test/ordering").unwrap(),
+                format!("{eid}:{sn}"),
+            ),
+            &callback,
+        )
+    };
+
+    deliver(0, 0);
+    deliver(0, 2);
+    deliver(1, 5);
+    deliver(0, 1);
+    assert_eq!(*received.lock().unwrap(), ["0:0", "1:5", "0:1", "0:2"]);
+    assert_eq!(
+        buffer.stats(),
+        OrderingStats {
+            buffered: 0,
+            reordered: 1,
+            skipped: 0,
+            discarded: 0,
+        }
+    );
+
+    // the missing samples are given up on when the buffer is full
+    received.lock().unwrap().clear();
+    deliver(0, 5);
+    deliver(0, 6);
+    deliver(0, 4);
+    deliver(0, 3);
+    assert_eq!(*received.lock().unwrap(), ["0:4", "0:5", "0:6"]);
+    assert_eq!(
+        buffer.stats(),
+        OrderingStats {
+            buffered: 0,
+            reordered: 4,
+            skipped: 1,
+            discarded: 1,
+        }
+    );
+}
@@ -61,6 +61,21 @@ impl Value {
         self.encoding = encoding;
         self
     }
+
+    /// Returns the shared memory buffer holding the whole payload of this Value, without copying it.
+    ///
+    /// Returns `None` if the payload isn't in shared memory, e.g. if it was copied to be received from another host.
+    /// Also available on [`Sample`], which dereferences to its Value.
+    #[cfg(feature = "shared-memory")]
+    pub fn as_shm(&self) -> Option<&SharedMemoryBuf> {
+        let mut slices = self.payload.zslices();
+        match (slices.next(), slices.next()) {
+            (Some(slice), None) => slice
+                .downcast_ref::<SharedMemoryBuf>()
+                .filter(|smb| slice.range() == (0..smb.len())),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Debug for Value {
@@ -0,0 +1,86 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+#[test]
+fn zenoh_acl() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut c_router = config::default();
+        c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+        c_router
+            .listen
+            .set_endpoints(vec!["tcp/localhost:17470".parse().unwrap()])
+            .unwrap();
+        c_router
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .unwrap();
+        c_router
+            .insert_json5(
+                "access_control",
+                r#"{
+                    enabled: true,
+                    rules: [
+                        { key_exprs: ["test/acl/denied/**"], actions: ["pub"], permission: "deny" },
+                    ],
+                }"#,
+            )
+            .unwrap();
+        let router = ztimeout!(zenoh::open(c_router).res_async()).unwrap();
+
+        let c_client = || config::client(["tcp/localhost:17470".parse::<EndPoint>().unwrap()]);
+        let publisher = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+        let subscriber = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+
+        let sub = ztimeout!(subscriber.declare_subscriber("test/acl/**").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        ztimeout!(publisher.put("test/acl/denied/a", "denied").res_async()).unwrap();
+        ztimeout!(publisher.put("test/acl/allowed", "allowed").res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "test/acl/allowed");
+
+        // Lift the restriction at runtime
+        router
+            .config()
+            .insert_json5("access_control/rules", "[]")
+            .unwrap();
+        task::sleep(SLEEP).await;
+
+        ztimeout!(publisher.put("test/acl/denied/a", "allowed").res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "test/acl/denied/a");
+
+        ztimeout!(sub.undeclare().res_async()).unwrap();
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(subscriber.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,106 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+#[test]
+fn zenoh_auth_reload() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let dictionary =
+            std::env::temp_dir().join(format!("zenoh-auth-reload-{}.txt", std::process::id()));
+        let dictionary_str = dictionary.to_str().unwrap().to_string();
+        std::fs::write(&dictionary, "alice:secret1\n").unwrap();
+
+        let mut c_router = config::default();
+        c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+        c_router
+            .listen
+            .set_endpoints(vec!["tcp/localhost:17472".parse().unwrap()])
+            .unwrap();
+        c_router
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .unwrap();
+        c_router
+            .transport
+            .auth
+            .usrpwd
+            .set_dictionary_file(Some(dictionary_str.clone()))
+            .unwrap();
+        let router = ztimeout!(zenoh::open(c_router).res_async()).unwrap();
+
+        let c_client = |password: &str| {
+            let mut config = config::client(["tcp/localhost:17472".parse::<EndPoint>().unwrap()]);
+            config
+                .transport
+                .auth
+                .usrpwd
+                .set_user(Some("alice".to_string()))
+                .unwrap();
+            config
+                .transport
+                .auth
+                .usrpwd
+                .set_password(Some(password.to_string()))
+                .unwrap();
+            config
+        };
+        let subscriber = ztimeout!(zenoh::open(c_client("secret1")).res_async()).unwrap();
+        let sub = ztimeout!(subscriber
+            .declare_subscriber("test/auth_reload")
+            .res_async())
+        .unwrap();
+
+        // Rotate the password
+        std::fs::write(&dictionary, "alice:secret2\n").unwrap();
+        router
+            .config()
+            .insert_json5(
+                "transport/auth/usrpwd/dictionary_file",
+                &format!("{dictionary_str:?}"),
+            )
+            .unwrap();
+        task::sleep(SLEEP).await;
+
+        assert!(ztimeout!(zenoh::open(c_client("secret1")).res_async()).is_err());
+        let publisher = ztimeout!(zenoh::open(c_client("secret2")).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        // The session authenticated with the previous password is kept
+        ztimeout!(publisher.put("test/auth_reload", "data").res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "data");
+
+        std::fs::remove_file(&dictionary).unwrap();
+        ztimeout!(sub.undeclare().res_async()).unwrap();
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(subscriber.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
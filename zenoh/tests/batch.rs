@@ -0,0 +1,99 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "unstable")]
+#[test]
+fn zenoh_put_batch() {
+    use async_std::prelude::FutureExt;
+    use async_std::task;
+    use std::time::Duration;
+    use zenoh::prelude::r#async::*;
+    use zenoh::sample::BatchInfo;
+    use zenoh_core::zasync_executor_init;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut c_router = config::default();
+        c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+        c_router
+            .listen
+            .set_endpoints(vec!["tcp/localhost:17466".parse().unwrap()])
+            .unwrap();
+        c_router
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .unwrap();
+        let _router = ztimeout!(zenoh::open(c_router).res_async()).unwrap();
+
+        let c_client = || config::client(["tcp/localhost:17466".parse::<EndPoint>().unwrap()]);
+        let publisher = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+        let subscriber = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+
+        let all = ztimeout!(subscriber.declare_subscriber("test/batch/**").res_async()).unwrap();
+        let one = ztimeout!(subscriber.declare_subscriber("test/batch/b").res_async()).unwrap();
+        let local = ztimeout!(publisher.declare_subscriber("test/batch/*").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        ztimeout!(publisher
+            .put_batch([
+                ("test/batch/a", "1"),
+                ("test/batch/b", "2"),
+                ("test/batch/c/d", "3"),
+            ])
+            .res_async())
+        .unwrap();
+
+        // the subscribers get in a row the values of the batch matching their key expression
+        let mut values = vec![];
+        for index in 0..3 {
+            let sample = ztimeout!(all.recv_async()).unwrap();
+            assert_eq!(sample.batch, Some(BatchInfo { index, len: 3 }));
+            values.push((sample.key_expr.to_string(), sample.value.to_string()));
+        }
+        assert_eq!(
+            values,
+            vec![
+                ("test/batch/a".to_string(), "1".to_string()),
+                ("test/batch/b".to_string(), "2".to_string()),
+                ("test/batch/c/d".to_string(), "3".to_string()),
+            ]
+        );
+
+        let sample = ztimeout!(one.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "test/batch/b");
+        assert_eq!(sample.batch, Some(BatchInfo { index: 0, len: 1 }));
+
+        for index in 0..2 {
+            let sample = ztimeout!(local.recv_async()).unwrap();
+            assert_eq!(sample.batch, Some(BatchInfo { index, len: 2 }));
+        }
+
+        // a single put isn't part of a batch
+        ztimeout!(publisher.put("test/batch/a", "4").res_async()).unwrap();
+        let sample = ztimeout!(all.recv_async()).unwrap();
+        assert!(sample.batch.is_none());
+        task::sleep(SLEEP).await;
+        assert!(one.try_recv().is_err());
+    });
+}
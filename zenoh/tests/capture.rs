@@ -0,0 +1,106 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "unstable")]
+#[test]
+fn zenoh_capture_replay() {
+    use async_std::prelude::FutureExt;
+    use async_std::task;
+    use std::time::Duration;
+    use zenoh::capture::{read_capture, Direction, Replay};
+    use zenoh::prelude::r#async::*;
+    use zenoh_core::zasync_executor_init;
+    use zenoh_protocol::network::NetworkBody;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let file = std::env::temp_dir().join(format!("zenoh-capture-{}.zcap", ZenohId::rand()));
+        let mut c_router = config::default();
+        c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+        c_router
+            .listen
+            .set_endpoints(vec!["tcp/localhost:17467".parse().unwrap()])
+            .unwrap();
+        c_router
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .unwrap();
+        c_router
+            .transport
+            .capture
+            .set_file(Some(file.to_string_lossy().to_string()))
+            .unwrap();
+        let router = ztimeout!(zenoh::open(c_router).res_async()).unwrap();
+
+        let c_client = || config::client(["tcp/localhost:17467".parse::<EndPoint>().unwrap()]);
+        let session = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+        let zid = session.zid();
+        let listener = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+        let subscriber =
+            ztimeout!(listener.declare_subscriber("test/capture/*").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        async fn check(subscriber: &zenoh::subscriber::Subscriber<'_, flume::Receiver<Sample>>) {
+            for i in 0..3 {
+                let sample = ztimeout!(subscriber.recv_async()).unwrap();
+                assert_eq!(sample.key_expr.as_str(), "test/capture/a");
+                assert_eq!(sample.kind, SampleKind::Put);
+                assert_eq!(sample.value.to_string(), i.to_string());
+            }
+            let sample = ztimeout!(subscriber.recv_async()).unwrap();
+            assert_eq!(sample.key_expr.as_str(), "test/capture/b");
+            assert_eq!(sample.kind, SampleKind::Delete);
+        }
+
+        // the publications on a declared key expression are captured with its scope
+        let key_expr = ztimeout!(session.declare_keyexpr("test/capture/a").res_async()).unwrap();
+        let publisher = ztimeout!(session.declare_publisher(key_expr).res_async()).unwrap();
+        for i in 0..3 {
+            ztimeout!(publisher.put(i.to_string()).res_async()).unwrap();
+        }
+        ztimeout!(session.delete("test/capture/b").res_async()).unwrap();
+        check(&subscriber).await;
+        task::sleep(SLEEP).await;
+
+        let records = read_capture(&file).unwrap();
+        let pushes = records
+            .iter()
+            .filter(|r| r.zid == zid && matches!(r.msg.body, NetworkBody::Push(_)))
+            .collect::<Vec<_>>();
+        assert_eq!(pushes.len(), 4);
+        assert!(pushes.iter().all(|r| r.direction == Direction::Rx));
+        assert!(pushes.windows(2).all(|w| w[0].time <= w[1].time));
+
+        // the captured publications are replayed on a live session
+        let replay = Replay::from(records).speed(f64::INFINITY);
+        assert_eq!(ztimeout!(replay.run(&session)).unwrap(), 4);
+        check(&subscriber).await;
+
+        // the replay speed must be positive
+        assert!(ztimeout!(Replay::from(vec![]).speed(0.0).run(&session)).is_err());
+
+        drop(router);
+        let _ = std::fs::remove_file(&file);
+    });
+}
@@ -0,0 +1,49 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use zenoh::prelude::keyexpr;
+
+zenoh::kedefine!(
+    sensor_format: "factory/${line}/sensor/${id}",
+    file_format: "user_id/${user_id:*}/file/${file:**}",
+);
+
+#[test]
+fn kedefine_format() {
+    let ke = sensor_format::format("assembly", 42).unwrap();
+    assert_eq!(ke.as_str(), "factory/assembly/sensor/42");
+    // Each value must match the pattern of its field
+    assert!(sensor_format::format("assembly/a", 42).is_err());
+    assert!(sensor_format::format("assembly", "").is_err());
+
+    let ke = file_format::format(7, "hi/there").unwrap();
+    assert_eq!(ke.as_str(), "user_id/7/file/hi/there");
+    let ke = file_format::format(7, "").unwrap();
+    assert_eq!(ke.as_str(), "user_id/7/file");
+}
+
+#[test]
+fn kedefine_parse() {
+    let ke = keyexpr::new("factory/assembly/sensor/42").unwrap();
+    let parsed = sensor_format::parse(ke).unwrap();
+    assert_eq!(parsed.line(), keyexpr::new("assembly").ok());
+    assert_eq!(parsed.line_as::<String>().unwrap(), "assembly");
+    assert_eq!(parsed.id_as::<u32>().unwrap(), 42);
+    assert!(parsed.line_as::<u32>().is_err());
+    assert!(sensor_format::parse(keyexpr::new("factory/assembly/42").unwrap()).is_err());
+
+    let parsed = file_format::parse(keyexpr::new("user_id/7/file").unwrap()).unwrap();
+    assert_eq!(parsed.user_id_as::<u64>().unwrap(), 7);
+    assert_eq!(parsed.file(), None);
+    assert_eq!(parsed.file_as::<String>().unwrap(), "");
+}
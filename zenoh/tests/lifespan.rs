@@ -0,0 +1,75 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "unstable")]
+#[test]
+fn zenoh_lifespan() {
+    use async_std::prelude::FutureExt;
+    use async_std::task;
+    use std::time::Duration;
+    use zenoh::prelude::r#async::*;
+    use zenoh_core::zasync_executor_init;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut c_router = config::default();
+        c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+        c_router
+            .listen
+            .set_endpoints(vec!["tcp/localhost:17461".parse().unwrap()])
+            .unwrap();
+        c_router
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .unwrap();
+        let _router = ztimeout!(zenoh::open(c_router).res_async()).unwrap();
+
+        let c_client = || config::client(["tcp/localhost:17461".parse::<EndPoint>().unwrap()]);
+        let publisher = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+        let subscriber = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+
+        let sub = ztimeout!(subscriber.declare_subscriber("test/lifespan").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        // the data expired before being routed is dropped
+        ztimeout!(publisher
+            .put("test/lifespan", "expired")
+            .lifespan(Duration::ZERO)
+            .res_async())
+        .unwrap();
+        ztimeout!(publisher
+            .put("test/lifespan", "fresh")
+            .lifespan(TIMEOUT)
+            .res_async())
+        .unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "fresh");
+        let deadline = sample.deadline.expect("missing deadline");
+        assert!(deadline.to_system_time() > std::time::SystemTime::now());
+
+        ztimeout!(publisher.put("test/lifespan", "forever").res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert!(sample.deadline.is_none());
+    });
+}
@@ -0,0 +1,81 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// Test the listeners written through the configuration -
+// 1. writing the same endpoints again leaves the listener bound to an ephemeral port as is
+// 2. writing other endpoints opens the added listeners and closes the removed ones
+
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+#[test]
+fn listeners_update() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut config = config::default();
+        config.set_mode(Some(WhatAmI::Router)).unwrap();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .listen
+            .set_endpoints(vec!["tcp/127.0.0.1:0".parse().unwrap()])
+            .unwrap();
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+        let bound = runtime.get_locators();
+        assert_eq!(bound.len(), 1);
+
+        for _ in 0..2 {
+            (&runtime.config)
+                .insert_json5("listen/endpoints", r#"["tcp/127.0.0.1:0"]"#)
+                .unwrap();
+            task::sleep(SLEEP).await;
+            assert_eq!(runtime.get_locators(), bound);
+        }
+
+        (&runtime.config)
+            .insert_json5(
+                "listen/endpoints",
+                r#"["tcp/127.0.0.1:0", "tcp/127.0.0.1:17482"]"#,
+            )
+            .unwrap();
+        task::sleep(SLEEP).await;
+        let locators = runtime.get_locators();
+        assert_eq!(locators.len(), 2);
+        assert!(locators.contains(&bound[0]));
+
+        (&runtime.config)
+            .insert_json5("listen/endpoints", r#"["tcp/127.0.0.1:17482"]"#)
+            .unwrap();
+        task::sleep(SLEEP).await;
+        assert_eq!(
+            runtime.get_locators(),
+            vec!["tcp/127.0.0.1:17482".parse::<Locator>().unwrap()]
+        );
+
+        ztimeout!(runtime.close()).unwrap();
+    });
+}
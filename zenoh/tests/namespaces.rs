@@ -0,0 +1,131 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::str::FromStr;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+#[test]
+fn zenoh_namespaces() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut c_router = config::default();
+        c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+        c_router
+            .listen
+            .set_endpoints(vec!["tcp/localhost:17474".parse().unwrap()])
+            .unwrap();
+        c_router
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .unwrap();
+        c_router
+            .insert_json5(
+                "namespaces",
+                r#"[
+                    { identities: ["zid:a1"], prefix: "tenant/a" },
+                    { identities: ["zid:b1"], prefix: "tenant/b" },
+                ]"#,
+            )
+            .unwrap();
+        let router = ztimeout!(zenoh::open(c_router).res_async()).unwrap();
+
+        let c_client = |zid: Option<&str>| {
+            let mut config = config::client(["tcp/localhost:17474".parse::<EndPoint>().unwrap()]);
+            if let Some(zid) = zid {
+                config.set_id(ZenohId::from_str(zid).unwrap()).unwrap();
+            }
+            config
+        };
+        let tenant_a = ztimeout!(zenoh::open(c_client(Some("a1"))).res_async()).unwrap();
+        let tenant_b = ztimeout!(zenoh::open(c_client(Some("b1"))).res_async()).unwrap();
+        let observer = ztimeout!(zenoh::open(c_client(None)).res_async()).unwrap();
+
+        let sub_a = ztimeout!(tenant_a.declare_subscriber("demo/**").res_async()).unwrap();
+        let sub_observer = ztimeout!(observer.declare_subscriber("tenant/**").res_async()).unwrap();
+        let _queryable_a = ztimeout!(tenant_a
+            .declare_queryable("demo/q")
+            .callback(|query| {
+                let sample = Sample::new(query.key_expr().clone(), "a");
+                query.reply(Ok(sample)).res_sync().unwrap();
+            })
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        // The publications of a tenant are prefixed with its namespace
+        ztimeout!(tenant_b.put("demo/x", "b").res_async()).unwrap();
+        let sample = ztimeout!(sub_observer.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "tenant/b/demo/x");
+
+        // and stripped of it when delivered to the tenant
+        ztimeout!(observer.put("tenant/a/demo/y", "observer").res_async()).unwrap();
+        let sample = ztimeout!(sub_a.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "demo/y");
+        let sample = ztimeout!(sub_observer.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), "tenant/a/demo/y");
+        // The publication of the other tenant on the same key wasn't received
+        assert!(sub_a.try_recv().is_err());
+
+        // The queryables of a tenant are only reachable within its namespace
+        let replies = ztimeout!(observer.get("tenant/a/demo/q").res_async()).unwrap();
+        let reply = ztimeout!(replies.recv_async()).unwrap();
+        assert_eq!(reply.sample.unwrap().key_expr.as_str(), "tenant/a/demo/q");
+        let replies = ztimeout!(tenant_b.get("demo/q").res_async()).unwrap();
+        assert!(ztimeout!(replies.recv_async()).is_err());
+        let replies = ztimeout!(tenant_a.get("demo/q").res_async()).unwrap();
+        let reply = ztimeout!(replies.recv_async()).unwrap();
+        assert_eq!(reply.sample.unwrap().key_expr.as_str(), "demo/q");
+
+        // The keys of the batches are prefixed and stripped as well
+        #[cfg(feature = "unstable")]
+        {
+            ztimeout!(tenant_b
+                .put_batch([("demo/x", "1"), ("demo/y", "2")])
+                .res_async())
+            .unwrap();
+            for key in ["tenant/b/demo/x", "tenant/b/demo/y"] {
+                let sample = ztimeout!(sub_observer.recv_async()).unwrap();
+                assert_eq!(sample.key_expr.as_str(), key);
+            }
+            ztimeout!(observer
+                .put_batch([("tenant/a/demo/x", "1"), ("tenant/a/demo/y", "2")])
+                .res_async())
+            .unwrap();
+            for key in ["demo/x", "demo/y"] {
+                let sample = ztimeout!(sub_a.recv_async()).unwrap();
+                assert_eq!(sample.key_expr.as_str(), key);
+            }
+        }
+
+        ztimeout!(tenant_a.close().res_async()).unwrap();
+        ztimeout!(tenant_b.close().res_async()).unwrap();
+        ztimeout!(observer.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
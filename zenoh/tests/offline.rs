@@ -0,0 +1,89 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+#[test]
+fn zenoh_offline_buffer() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let c_router = || {
+            let mut c_router = config::default();
+            c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+            c_router
+                .listen
+                .set_endpoints(vec!["tcp/localhost:17465".parse().unwrap()])
+                .unwrap();
+            c_router
+                .scouting
+                .multicast
+                .set_enabled(Some(false))
+                .unwrap();
+            c_router
+        };
+        let c_client = || config::client(["tcp/localhost:17465".parse::<EndPoint>().unwrap()]);
+
+        let router = ztimeout!(zenoh::open(c_router()).res_async()).unwrap();
+        let mut c_publisher = c_client();
+        c_publisher
+            .connect
+            .offline_buffer
+            .set_enabled(Some(true))
+            .unwrap();
+        c_publisher
+            .connect
+            .offline_buffer
+            .set_replay_delay_ms(Some(100))
+            .unwrap();
+        let publisher = ztimeout!(zenoh::open(c_publisher).res_async()).unwrap();
+
+        // the publications are buffered while the router is down
+        ztimeout!(router.close().res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        for value in ["1", "2", "3"] {
+            ztimeout!(publisher.put("test/offline", value).res_async()).unwrap();
+        }
+
+        let _router = ztimeout!(zenoh::open(c_router()).res_async()).unwrap();
+        let subscriber = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+        let sub = ztimeout!(subscriber.declare_subscriber("test/offline").res_async()).unwrap();
+
+        // and sent in order with their original timestamps once reconnected
+        let mut timestamps = vec![];
+        for value in ["1", "2", "3"] {
+            let sample = ztimeout!(sub.recv_async()).unwrap();
+            assert_eq!(sample.value.to_string(), value);
+            timestamps.push(sample.timestamp.expect("missing timestamp"));
+        }
+        assert!(timestamps.windows(2).all(|ts| ts[0] <= ts[1]));
+
+        task::sleep(SLEEP).await;
+        ztimeout!(publisher.put("test/offline", "4").res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "4");
+    });
+}
@@ -0,0 +1,84 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "unstable")]
+#[test]
+fn zenoh_ordering() {
+    use async_std::prelude::FutureExt;
+    use async_std::task;
+    use std::time::Duration;
+    use zenoh::prelude::r#async::*;
+    use zenoh::publication::Priority;
+    use zenoh_core::zasync_executor_init;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+    const MSG_COUNT: usize = 100;
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut c_router = config::default();
+        c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+        c_router
+            .listen
+            .set_endpoints(vec!["tcp/localhost:17462".parse().unwrap()])
+            .unwrap();
+        c_router
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .unwrap();
+        let _router = ztimeout!(zenoh::open(c_router).res_async()).unwrap();
+
+        let c_client = || config::client(["tcp/localhost:17462".parse::<EndPoint>().unwrap()]);
+        let publisher = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+        let subscriber = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+
+        let sub = ztimeout!(subscriber
+            .declare_subscriber("test/ordering")
+            .ordered(MSG_COUNT)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        // the samples published with a lower priority may be overtaken by the following ones
+        let background = ztimeout!(publisher
+            .declare_publisher("test/ordering")
+            .priority(Priority::Background)
+            .ordered(true)
+            .res_async())
+        .unwrap();
+        // the clones of an ordered publisher share its numbering
+        let realtime = background.clone().priority(Priority::RealTime);
+        for i in 0..MSG_COUNT {
+            let publication = if i % 2 == 0 { &background } else { &realtime };
+            ztimeout!(publication.put(i.to_string()).res_async()).unwrap();
+        }
+
+        for i in 0..MSG_COUNT {
+            let sample = ztimeout!(sub.recv_async()).unwrap();
+            assert_eq!(sample.value.to_string(), i.to_string());
+        }
+        let stats = sub.ordering_stats().expect("missing ordering stats");
+        assert_eq!(stats.buffered, 0);
+        assert_eq!(stats.skipped, 0);
+        assert_eq!(stats.discarded, 0);
+    });
+}
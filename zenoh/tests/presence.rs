@@ -0,0 +1,119 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "unstable")]
+#[test]
+fn zenoh_presence() {
+    use async_std::prelude::FutureExt;
+    use async_std::task;
+    use std::time::Duration;
+    use zenoh::prelude::r#async::*;
+    use zenoh::presence::EntityKind;
+    use zenoh_core::zasync_executor_init;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut c_router = config::default();
+        c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+        c_router
+            .listen
+            .set_endpoints(vec!["tcp/localhost:17463".parse().unwrap()])
+            .unwrap();
+        c_router
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .unwrap();
+        let _router = ztimeout!(zenoh::open(c_router).res_async()).unwrap();
+
+        let c_client = || config::client(["tcp/localhost:17463".parse::<EndPoint>().unwrap()]);
+        let provider = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+        let observer = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+
+        let events = ztimeout!(observer
+            .declare_entities_subscriber("test/presence/**")
+            .res_async())
+        .unwrap();
+
+        let publisher = ztimeout!(provider
+            .declare_publisher("test/presence/a")
+            .priority(Priority::RealTime)
+            .congestion_control(CongestionControl::Block)
+            .res_async())
+        .unwrap();
+        let _queryable = ztimeout!(provider
+            .declare_queryable("test/presence/*")
+            .complete(true)
+            .res_async())
+        .unwrap();
+        // the publishers restricted to the local subscribers aren't advertised
+        let _local = ztimeout!(provider
+            .declare_publisher("test/presence/local")
+            .allowed_destination(Locality::SessionLocal)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        let entities = ztimeout!(observer.entities_on("test/presence/a").res_async()).unwrap();
+        let mut entities = entities.into_iter().collect::<Vec<_>>();
+        entities.sort_by_key(|entity| entity.key_expr.to_string());
+        assert_eq!(entities.len(), 2);
+        assert!(entities.iter().all(|entity| entity.zid == provider.zid()));
+        assert_eq!(entities[0].key_expr.as_str(), "test/presence/*");
+        assert_eq!(entities[0].kind, EntityKind::Queryable { complete: true });
+        assert_eq!(entities[1].key_expr.as_str(), "test/presence/a");
+        assert_eq!(
+            entities[1].kind,
+            EntityKind::Publisher {
+                priority: Priority::RealTime,
+                congestion_control: CongestionControl::Block,
+            }
+        );
+
+        // the liveliness queries of the applications don't see the entities
+        let tokens = ztimeout!(observer.liveliness().get("**").res_async()).unwrap();
+        assert_eq!(tokens.into_iter().count(), 0);
+
+        let mut declared = vec![];
+        for _ in 0..2 {
+            let event = ztimeout!(events.recv_async()).unwrap();
+            assert_eq!(event.kind, SampleKind::Put);
+            declared.push(event.entity);
+        }
+        let advertised = declared
+            .iter()
+            .find(|entity| entity.key_expr.as_str() == "test/presence/a")
+            .unwrap()
+            .clone();
+
+        // the publisher is advertised until its last clone is dropped
+        let clone = publisher.clone();
+        drop(publisher);
+        task::sleep(SLEEP).await;
+        assert!(events.try_recv().is_err());
+        drop(clone);
+        let event = ztimeout!(events.recv_async()).unwrap();
+        assert_eq!(event.kind, SampleKind::Delete);
+        assert_eq!(event.entity, advertised);
+    });
+}
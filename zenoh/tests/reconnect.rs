@@ -0,0 +1,87 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const SLEEP: Duration = Duration::from_secs(1);
+const ENDPOINT: &str = "tcp/127.0.0.1:18460";
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_router() -> Session {
+    let mut config = config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec![ENDPOINT.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][01a] Opening router session");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn open_client() -> Session {
+    let mut config = config::client([ENDPOINT.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.connect.retry.set_period_init_ms(Some(100)).unwrap();
+    config.connect.retry.set_period_max_ms(Some(500)).unwrap();
+    println!("[  ][02a] Opening client session");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn zenoh_reconnect() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let router = open_router().await;
+        let client = open_client().await;
+        let zid = client.zid();
+        let events = ztimeout!(client
+            .declare_subscriber(format!("@/session/{zid}/reconnect"))
+            .res_async())
+        .unwrap();
+        let sub = ztimeout!(client.declare_subscriber("test/reconnect").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        ztimeout!(router.put("test/reconnect", "before").res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "before");
+
+        println!("[  ][03a] Restarting router session");
+        ztimeout!(router.close().res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        let router = open_router().await;
+
+        // the outage is surfaced once the client reconnected
+        let event = ztimeout!(events.recv_async()).unwrap();
+        let event: serde_json::Value = serde_json::from_str(&event.value.to_string()).unwrap();
+        assert_eq!(event["zid"], router.zid().to_string());
+        assert!(event["outage_ms"].as_u64().unwrap() >= SLEEP.as_millis() as u64);
+        task::sleep(SLEEP).await;
+
+        // the subscriber was redeclared to the new router
+        ztimeout!(router.put("test/reconnect", "after").res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "after");
+
+        ztimeout!(client.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
@@ -208,3 +208,458 @@ fn zenoh_session_multicast() {
         close_session(peer01, peer02).await;
     });
 }
+
+#[test]
+fn zenoh_session_parallel_queryables() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let key_expr = "test/session/parallel";
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.set_queryables_parallelism(Some(2)).unwrap();
+        let peer = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+        // Each queryable waits for the other one to be called before replying
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut qbls = vec![];
+        for _ in 0..2 {
+            let c_calls = calls.clone();
+            let qbl = ztimeout!(peer
+                .declare_queryable(key_expr)
+                .callback(move |query| {
+                    c_calls.fetch_add(1, Ordering::AcqRel);
+                    let start = std::time::Instant::now();
+                    while c_calls.load(Ordering::Acquire) < 2 && start.elapsed() < 5 * SLEEP {
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+                    let concurrent = c_calls.load(Ordering::Acquire) == 2;
+                    let rep = Sample::try_from(key_expr, concurrent.to_string()).unwrap();
+                    task::block_on(async { ztimeout!(query.reply(Ok(rep)).res_async()).unwrap() });
+                })
+                .res_async())
+            .unwrap();
+            qbls.push(qbl);
+        }
+
+        let rs = ztimeout!(peer
+            .get(key_expr)
+            .consolidation(ConsolidationMode::None)
+            .res_async())
+        .unwrap();
+        let mut cnt = 0;
+        while let Ok(s) = ztimeout!(rs.recv_async()) {
+            assert_eq!(s.sample.unwrap().value.to_string(), "true");
+            cnt += 1;
+        }
+        assert_eq!(cnt, 2);
+
+        for qbl in qbls {
+            ztimeout!(qbl.undeclare().res_async()).unwrap();
+        }
+        ztimeout!(peer.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn zenoh_session_sync_handlers() {
+    use zenoh::prelude::sync::SyncResolve;
+
+    let _ = env_logger::try_init();
+
+    let key_expr = "test/session/sync";
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer = zenoh::open(config).res_sync().unwrap();
+
+    // The queries and samples are consumed from threads without any async executor
+    let queryable = peer
+        .declare_queryable(key_expr)
+        .with(flume::bounded(32))
+        .res_sync()
+        .unwrap();
+    let queries = queryable.receiver.clone();
+    let replier = std::thread::spawn(move || {
+        let query = queries.recv_timeout(TIMEOUT).unwrap();
+        let rep = Sample::try_from(key_expr, "reply").unwrap();
+        query.reply(Ok(rep)).res_sync().unwrap();
+    });
+    let subscriber = peer
+        .declare_subscriber(key_expr)
+        .with(flume::bounded(MSG_COUNT))
+        .res_sync()
+        .unwrap();
+    let samples = subscriber.receiver.clone();
+    let receiver = std::thread::spawn(move || {
+        (0..MSG_COUNT)
+            .map(|_| samples.recv_timeout(TIMEOUT).unwrap().value.to_string())
+            .collect::<Vec<_>>()
+    });
+
+    for i in 0..MSG_COUNT {
+        peer.put(key_expr, i.to_string()).res_sync().unwrap();
+    }
+    let received = receiver.join().unwrap();
+    assert_eq!(
+        received,
+        (0..MSG_COUNT).map(|i| i.to_string()).collect::<Vec<_>>()
+    );
+
+    let replies = peer
+        .get(key_expr)
+        .with(flume::bounded(32))
+        .res_sync()
+        .unwrap();
+    let reply = replies.recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(reply.sample.unwrap().value.to_string(), "reply");
+    replier.join().unwrap();
+
+    subscriber.undeclare().res_sync().unwrap();
+    queryable.undeclare().res_sync().unwrap();
+    peer.close().res_sync().unwrap();
+}
+
+#[test]
+fn zenoh_session_arc_entities() {
+    use zenoh::prelude::sync::SyncResolve;
+    use zenoh::publication::Publisher;
+    use zenoh::subscriber::FlumeSubscriber;
+
+    // The entities declared on a shared session can be stored in structs and moved to threads
+    struct Echo {
+        publisher: Publisher<'static>,
+        subscriber: FlumeSubscriber<'static>,
+    }
+
+    let _ = env_logger::try_init();
+
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let peer = zenoh::open(config).res_sync().unwrap().into_arc();
+
+    // The entities outlive the key expressions they are declared with
+    let echo = {
+        let prefix = String::from("test/session/arc");
+        Echo {
+            publisher: peer
+                .declare_publisher(format!("{prefix}/pong").as_str())
+                .res_sync()
+                .unwrap(),
+            subscriber: peer
+                .declare_subscriber(format!("{prefix}/ping").as_str())
+                .res_sync()
+                .unwrap(),
+        }
+    };
+    let pongs = peer
+        .declare_subscriber("test/session/arc/pong")
+        .res_sync()
+        .unwrap();
+    let echo = std::thread::spawn(move || {
+        for _ in 0..MSG_COUNT {
+            let sample = echo.subscriber.recv_timeout(TIMEOUT).unwrap();
+            echo.publisher.put(sample.value).res_sync().unwrap();
+        }
+    });
+
+    let pinger = peer.clone();
+    let pinger = std::thread::spawn(move || {
+        for i in 0..MSG_COUNT {
+            pinger
+                .put("test/session/arc/ping", i.to_string())
+                .res_sync()
+                .unwrap();
+        }
+    });
+    for i in 0..MSG_COUNT {
+        let sample = pongs.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(sample.value.to_string(), i.to_string());
+    }
+    pinger.join().unwrap();
+    echo.join().unwrap();
+
+    pongs.undeclare().res_sync().unwrap();
+    Arc::try_unwrap(peer).unwrap().close().res_sync().unwrap();
+}
+
+#[test]
+fn zenoh_session_streams() {
+    use futures::{SinkExt, StreamExt};
+    use zenoh::handlers::StreamChannel;
+
+    let _ = env_logger::try_init();
+
+    task::block_on(async {
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let peer = ztimeout!(zenoh::open(config).res_async())
+            .unwrap()
+            .into_arc();
+
+        // The samples of the subscriber are forwarded by a publisher to another subscriber
+        let subscriber = ztimeout!(peer
+            .declare_subscriber("test/session/streams/in")
+            .with(StreamChannel::new(MSG_COUNT))
+            .res_async())
+        .unwrap();
+        let out = ztimeout!(peer
+            .declare_subscriber("test/session/streams/out")
+            .with(StreamChannel::new(MSG_COUNT))
+            .res_async())
+        .unwrap();
+        let mut publisher = ztimeout!(peer
+            .declare_publisher("test/session/streams/out")
+            .res_async())
+        .unwrap();
+
+        for i in 0..MSG_COUNT {
+            ztimeout!(peer
+                .put("test/session/streams/in", i.to_string())
+                .res_async())
+            .unwrap();
+        }
+        let mut values = subscriber.take(MSG_COUNT).map(|s| Ok(s.value));
+        ztimeout!(publisher.send_all(&mut values)).unwrap();
+        let received = ztimeout!(out
+            .take(MSG_COUNT)
+            .map(|s| s.value.to_string())
+            .collect::<Vec<_>>());
+        assert_eq!(
+            received,
+            (0..MSG_COUNT).map(|i| i.to_string()).collect::<Vec<_>>()
+        );
+
+        // The queries and replies are streams too
+        let queryable = ztimeout!(peer
+            .declare_queryable("test/session/streams/query")
+            .with(StreamChannel::new(1))
+            .res_async())
+        .unwrap();
+        let replier = task::spawn(async move {
+            let query = queryable.take(1).next().await.unwrap();
+            let rep = Sample::try_from("test/session/streams/query", "reply").unwrap();
+            query.reply(Ok(rep)).res_async().await.unwrap();
+        });
+        let replies = ztimeout!(peer
+            .get("test/session/streams/query")
+            .with(StreamChannel::new(1))
+            .res_async())
+        .unwrap();
+        let replies = ztimeout!(replies.collect::<Vec<_>>());
+        assert_eq!(replies.len(), 1);
+        assert_eq!(
+            replies[0].sample.as_ref().unwrap().value.to_string(),
+            "reply"
+        );
+        ztimeout!(replier);
+
+        drop((values, publisher));
+        ztimeout!(Arc::try_unwrap(peer).unwrap().close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn zenoh_session_query_value() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let (peer01, peer02) = open_session_unicast(&["tcp/127.0.0.1:17450"]).await;
+        let key_expr = "test/session/query_value";
+
+        // The queryable echoes the value of the queries, or replies "none"
+        let qbl = ztimeout!(peer01
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                let value = match query.value() {
+                    Some(value) => value.clone(),
+                    None => Value::from("none"),
+                };
+                let rep = Sample::new(KeyExpr::try_from(key_expr).unwrap(), value);
+                task::block_on(async { ztimeout!(query.reply(Ok(rep)).res_async()).unwrap() });
+            })
+            .res_async())
+        .unwrap();
+
+        // Wait for the declaration to propagate
+        task::sleep(SLEEP).await;
+
+        let request = Value::from(r#"{"method":"sum","params":[1,2]}"#)
+            .encoding(KnownEncoding::AppJson.into());
+        for peer in [&peer02, &peer01] {
+            let rs = ztimeout!(peer.get(key_expr).with_value(request.clone()).res_async()).unwrap();
+            let reply = ztimeout!(rs.recv_async()).unwrap().sample.unwrap();
+            assert_eq!(reply.value.to_string(), request.to_string());
+            assert_eq!(reply.value.encoding, request.encoding);
+
+            let rs = ztimeout!(peer.get(key_expr).res_async()).unwrap();
+            let reply = ztimeout!(rs.recv_async()).unwrap().sample.unwrap();
+            assert_eq!(reply.value.to_string(), "none");
+        }
+
+        ztimeout!(qbl.undeclare().res_async()).unwrap();
+        close_session(peer01, peer02).await;
+    });
+}
+
+#[test]
+fn zenoh_session_delete_replies() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let (peer01, peer02) = open_session_unicast(&["tcp/127.0.0.1:17451"]).await;
+        let key_expr = "test/session/delete_replies";
+
+        // The queryable replies the history of the key: a put, then its deletion
+        let put_ts = zenoh::time::new_reception_timestamp();
+        let del_ts = zenoh::time::new_reception_timestamp();
+        let qbl = ztimeout!(peer01
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                let put = Sample::new(KeyExpr::try_from(key_expr).unwrap(), "value")
+                    .with_timestamp(put_ts);
+                let mut del = Sample::new(KeyExpr::try_from(key_expr).unwrap(), Value::empty())
+                    .with_timestamp(del_ts);
+                del.kind = SampleKind::Delete;
+                task::block_on(async {
+                    ztimeout!(query.reply(Ok(put)).res_async()).unwrap();
+                    ztimeout!(query.reply(Ok(del)).res_async()).unwrap();
+                });
+            })
+            .res_async())
+        .unwrap();
+
+        // Wait for the declaration to propagate
+        task::sleep(SLEEP).await;
+
+        for peer in [&peer02, &peer01] {
+            let rs = ztimeout!(peer
+                .get(key_expr)
+                .consolidation(ConsolidationMode::None)
+                .res_async())
+            .unwrap();
+            let mut samples = Vec::new();
+            while let Ok(reply) = ztimeout!(rs.recv_async()) {
+                samples.push(reply.sample.unwrap());
+            }
+            assert_eq!(samples.len(), 2);
+            assert_eq!(samples[0].kind, SampleKind::Put);
+            assert_eq!(samples[0].value.to_string(), "value");
+            assert_eq!(samples[1].kind, SampleKind::Delete);
+            assert_eq!(samples[1].timestamp, Some(del_ts));
+
+            // The deletion is the latest state of the key
+            let rs = ztimeout!(peer
+                .get(key_expr)
+                .consolidation(ConsolidationMode::Latest)
+                .res_async())
+            .unwrap();
+            let reply = ztimeout!(rs.recv_async()).unwrap().sample.unwrap();
+            assert_eq!(reply.kind, SampleKind::Delete);
+            assert!(ztimeout!(rs.recv_async()).is_err());
+        }
+
+        ztimeout!(qbl.undeclare().res_async()).unwrap();
+        close_session(peer01, peer02).await;
+    });
+}
+
+#[test]
+fn zenoh_session_consolidation_strategy() {
+    use std::collections::HashMap;
+    use zenoh::query::{ConsolidationStrategy, Reply};
+
+    // Delivers the replies greater than the ones previously delivered for their key,
+    // and the last reply dropped once all the replies are received
+    #[derive(Default)]
+    struct MaxPerKey {
+        max: HashMap<OwnedKeyExpr, u64>,
+        dropped: Option<Reply>,
+    }
+
+    impl ConsolidationStrategy for MaxPerKey {
+        fn consolidate(&mut self, reply: Reply) -> Option<Reply> {
+            let sample = reply.sample.as_ref().unwrap();
+            let value: u64 = sample.value.to_string().parse().unwrap();
+            match self.max.get(sample.key_expr.as_keyexpr()) {
+                Some(max) if *max >= value => {
+                    self.dropped = Some(reply);
+                    None
+                }
+                _ => {
+                    self.max.insert(sample.key_expr.clone().into(), value);
+                    Some(reply)
+                }
+            }
+        }
+
+        fn finalize(&mut self) -> Vec<Reply> {
+            self.dropped.take().into_iter().collect()
+        }
+    }
+
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let (peer01, peer02) = open_session_unicast(&["tcp/127.0.0.1:17452"]).await;
+        let key_expr = "test/session/strategy/a";
+
+        let qbl = ztimeout!(peer01
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                task::block_on(async {
+                    for value in [3u64, 7, 5, 9, 1] {
+                        let rep = Sample::try_from(key_expr, value).unwrap();
+                        ztimeout!(query.reply(Ok(rep)).res_async()).unwrap();
+                    }
+                });
+            })
+            .res_async())
+        .unwrap();
+
+        // Wait for the declaration to propagate
+        task::sleep(SLEEP).await;
+
+        for peer in [&peer02, &peer01] {
+            let rs = ztimeout!(peer
+                .get(key_expr)
+                .consolidation_strategy(MaxPerKey::default())
+                .res_async())
+            .unwrap();
+            let mut values = Vec::new();
+            while let Ok(reply) = ztimeout!(rs.recv_async()) {
+                values.push(reply.sample.unwrap().value.to_string());
+            }
+            assert_eq!(values, vec!["3", "7", "9", "1"]);
+        }
+
+        ztimeout!(qbl.undeclare().res_async()).unwrap();
+        close_session(peer01, peer02).await;
+    });
+}
+
+#[test]
+fn zenoh_session_non_canon_declarations() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let _ = env_logger::try_init();
+
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let session = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+        // `a/**/**` isn't in canon form (`a/**`), which only the unchecked constructors allow
+        let key_expr = KeyExpr::from(unsafe { keyexpr::from_str_unchecked("a/**/**") });
+        assert!(ztimeout!(session.declare_keyexpr(key_expr.clone()).res_async()).is_err());
+        assert!(ztimeout!(session.declare_subscriber(key_expr.clone()).res_async()).is_err());
+        assert!(ztimeout!(session.declare_queryable(key_expr).res_async()).is_err());
+
+        let key_expr = KeyExpr::from(unsafe { keyexpr::from_str_unchecked("a/**") });
+        assert!(ztimeout!(session.declare_subscriber(key_expr).res_async()).is_ok());
+
+        ztimeout!(session.close().res_async()).unwrap();
+    });
+}
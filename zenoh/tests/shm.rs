@@ -0,0 +1,78 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "shared-memory")]
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::shm::SharedMemoryManager;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_session(listen: &[&str], connect: &[&str], shm: bool) -> Session {
+    let mut config = config::peer();
+    config.listen.endpoints = listen.iter().map(|e| e.parse().unwrap()).collect();
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.transport.shared_memory.set_enabled(shm).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn zenoh_shm_payload() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let publisher = open_session(&["tcp/127.0.0.1:17473"], &[], true).await;
+        let shm_peer = open_session(&[], &["tcp/127.0.0.1:17473"], true).await;
+        let copy_peer = open_session(&[], &["tcp/127.0.0.1:17473"], false).await;
+
+        let local_sub = ztimeout!(publisher.declare_subscriber("test/shm").res_async()).unwrap();
+        let shm_sub = ztimeout!(shm_peer.declare_subscriber("test/shm").res_async()).unwrap();
+        let copy_sub = ztimeout!(copy_peer.declare_subscriber("test/shm").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        let mut shm = SharedMemoryManager::make(publisher.zid().to_string(), 4096).unwrap();
+        let pub_ = ztimeout!(publisher.declare_publisher("test/shm").res_async()).unwrap();
+        let publication = pub_
+            .write_shm(&mut shm, 5, |buf| buf.copy_from_slice(b"value"))
+            .unwrap();
+        ztimeout!(publication.res_async()).unwrap();
+
+        for sub in [&local_sub, &shm_sub] {
+            let sample = ztimeout!(sub.recv_async()).unwrap();
+            assert_eq!(sample.as_shm().unwrap().as_slice(), b"value");
+        }
+        // Copied, as that peer doesn't support shared memory
+        let sample = ztimeout!(copy_sub.recv_async()).unwrap();
+        assert!(sample.as_shm().is_none());
+        assert_eq!(sample.value.to_string(), "value");
+
+        ztimeout!(pub_.undeclare().res_async()).unwrap();
+        ztimeout!(local_sub.undeclare().res_async()).unwrap();
+        ztimeout!(shm_sub.undeclare().res_async()).unwrap();
+        ztimeout!(copy_sub.undeclare().res_async()).unwrap();
+        ztimeout!(copy_peer.close().res_async()).unwrap();
+        ztimeout!(shm_peer.close().res_async()).unwrap();
+        ztimeout!(publisher.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,85 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "simulation")]
+#[test]
+fn zenoh_simulation() {
+    use async_std::prelude::FutureExt;
+    use async_std::task;
+    use std::time::Duration;
+    use zenoh::prelude::r#async::*;
+    use zenoh::prelude::sync::SyncResolve;
+    use zenoh::simulation::{Simulation, SIMULATION_EPOCH};
+    use zenoh::time::NTP64;
+    use zenoh_core::zasync_executor_init;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut simulation = Simulation::new().await;
+        assert_eq!(simulation.now(), NTP64::from(SIMULATION_EPOCH));
+
+        // two routers, a peer behind the first one and a client behind the second one
+        ztimeout!(simulation.add_router("r1", &[])).unwrap();
+        let r2 = ztimeout!(simulation.add_router("r2", &["r1"])).unwrap();
+        let peer = ztimeout!(simulation.add_peer("p", &["r1"])).unwrap();
+        let client = ztimeout!(simulation.add_client("c", &["r2"])).unwrap();
+        assert!(ztimeout!(simulation.add_peer("p", &[])).is_err());
+        assert!(ztimeout!(simulation.add_peer("q", &["c"])).is_err());
+        assert!(ztimeout!(simulation.add_peer("q", &["unknown"])).is_err());
+        assert_eq!(r2.zid(), simulation.session("r2").unwrap().zid());
+
+        let subscriber = ztimeout!(client.declare_subscriber("test/sim/*").res_async()).unwrap();
+        let _queryable = ztimeout!(peer
+            .declare_queryable("test/sim/q")
+            .callback(|query| {
+                let sample = Sample::new(query.key_expr().clone(), "answer");
+                query.reply(Ok(sample)).res_sync().unwrap();
+            })
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        // the samples are timestamped by the routers with the virtual time
+        ztimeout!(peer.put("test/sim/a", "1").res_async()).unwrap();
+        let sample = ztimeout!(subscriber.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "1");
+        let time = *sample.timestamp.unwrap().get_time();
+        assert!(time >= NTP64::from(SIMULATION_EPOCH));
+        assert!(time < NTP64::from(SIMULATION_EPOCH + SLEEP));
+
+        simulation.advance(Duration::from_secs(3600));
+        ztimeout!(peer.put("test/sim/a", "2").res_async()).unwrap();
+        let sample = ztimeout!(subscriber.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "2");
+        let time = *sample.timestamp.unwrap().get_time();
+        assert!(time >= NTP64::from(SIMULATION_EPOCH + Duration::from_secs(3600)));
+
+        // the queries are routed through the routers
+        let replies = ztimeout!(client.get("test/sim/q").res_async()).unwrap();
+        let reply = ztimeout!(replies.recv_async()).unwrap();
+        assert_eq!(reply.sample.unwrap().value.to_string(), "answer");
+
+        drop(simulation);
+        assert!(zenoh::time::clock() > NTP64::from(SIMULATION_EPOCH + Duration::from_secs(3600)));
+    });
+}
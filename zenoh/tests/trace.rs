@@ -0,0 +1,73 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#[cfg(feature = "unstable")]
+#[test]
+fn zenoh_trace() {
+    use async_std::prelude::FutureExt;
+    use async_std::task;
+    use std::time::Duration;
+    use zenoh::prelude::r#async::*;
+    use zenoh_core::zasync_executor_init;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_secs(1);
+
+    macro_rules! ztimeout {
+        ($f:expr) => {
+            $f.timeout(TIMEOUT).await.unwrap()
+        };
+    }
+
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let mut c_router = config::default();
+        c_router.set_mode(Some(WhatAmI::Router)).unwrap();
+        c_router
+            .listen
+            .set_endpoints(vec!["tcp/localhost:17460".parse().unwrap()])
+            .unwrap();
+        c_router
+            .scouting
+            .multicast
+            .set_enabled(Some(false))
+            .unwrap();
+        c_router.routing.router.set_trace(Some(true)).unwrap();
+        let router = ztimeout!(zenoh::open(c_router).res_async()).unwrap();
+
+        let c_client = || config::client(["tcp/localhost:17460".parse::<EndPoint>().unwrap()]);
+        let publisher = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+        let subscriber = ztimeout!(zenoh::open(c_client()).res_async()).unwrap();
+
+        let sub = ztimeout!(subscriber.declare_subscriber("test/trace").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        ztimeout!(publisher
+            .put("test/trace", "traced")
+            .trace(true)
+            .res_async())
+        .unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        let hops = sample.trace.expect("missing trace");
+        assert_eq!(
+            hops.iter().map(|hop| hop.zid).collect::<Vec<_>>(),
+            vec![publisher.zid(), router.zid(), subscriber.zid()]
+        );
+        assert!(hops.windows(2).all(|w| w[0].egress <= w[1].ingress));
+
+        ztimeout!(publisher.put("test/trace", "untraced").res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert!(sample.trace.is_none());
+    });
+}
@@ -0,0 +1,126 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(feature = "wasm")]
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::plugins::{load_plugin, PluginsManager};
+use zenoh::prelude::r#async::*;
+use zenoh::runtime::Runtime;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Republishes on `wasm/out` the samples received on `wasm/in`, and tries to publish on `forbidden`
+const ECHO: &str = r#"
+(module
+  (import "zenoh" "declare_subscriber" (func $declare_subscriber (param i32 i32) (result i32)))
+  (import "zenoh" "put" (func $put (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "wasm/in")
+  (data (i32.const 16) "wasm/out")
+  (data (i32.const 32) "forbidden")
+  (global $heap (mut i32) (i32.const 1024))
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+  (func (export "start") (result i32)
+    (if (i32.ge_s (call $put (i32.const 32) (i32.const 9) (i32.const 0) (i32.const 0)) (i32.const 0))
+      (then (return (i32.const -1))))
+    (call $declare_subscriber (i32.const 0) (i32.const 7)))
+  (func (export "on_sample") (param $sub i32) (param $key i32) (param $key_len i32) (param $value i32) (param $value_len i32)
+    (drop (call $put (i32.const 16) (i32.const 8) (local.get $value) (local.get $value_len)))
+    (global.set $heap (i32.const 1024))))
+"#;
+
+#[test]
+fn wasm_plugin_echo() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let path = std::env::temp_dir().join(format!("zenoh-echo-{}.wasm", ZenohId::rand()));
+        std::fs::write(&path, wat::parse_str(ECHO).unwrap()).unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5(
+                "plugins/echo",
+                &format!(r#"{{ __path__: ["{path}"], allowed: ["wasm/**"] }}"#),
+            )
+            .unwrap();
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+        let session = ztimeout!(zenoh::init(runtime.clone()).res_async()).unwrap();
+
+        let mut plugins = PluginsManager::static_plugins_only();
+        assert_eq!(
+            load_plugin(&mut plugins, "echo", Some(&[path.clone()])).unwrap(),
+            path
+        );
+        let subscriber = ztimeout!(session.declare_subscriber("wasm/out").res_async()).unwrap();
+        plugins.start("echo", &runtime).unwrap();
+
+        ztimeout!(session.put("wasm/in", "hello").res_async()).unwrap();
+        let sample = ztimeout!(subscriber.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "hello");
+        assert!(plugins.plugin("echo").unwrap().terminated().is_none());
+
+        plugins.stop("echo");
+        let _ = std::fs::remove_file(path);
+    });
+}
+
+// Needs 2 pages (128 KiB) of memory
+const GREEDY: &str = r#"
+(module
+  (memory (export "memory") 2)
+  (func (export "alloc") (param $len i32) (result i32) (i32.const 0))
+  (func (export "start") (result i32) (i32.const 0)))
+"#;
+
+#[test]
+fn wasm_plugin_memory_limit() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let path = std::env::temp_dir().join(format!("zenoh-greedy-{}.wasm", ZenohId::rand()));
+        std::fs::write(&path, wat::parse_str(GREEDY).unwrap()).unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config
+            .insert_json5(
+                "plugins/greedy",
+                &format!(r#"{{ __path__: ["{path}"], memory: 65536 }}"#),
+            )
+            .unwrap();
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+
+        let mut plugins = PluginsManager::static_plugins_only();
+        load_plugin(&mut plugins, "greedy", Some(&[path.clone()])).unwrap();
+        assert!(plugins.start("greedy", &runtime).is_err());
+
+        let _ = std::fs::remove_file(path);
+    });
+}
@@ -0,0 +1,171 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::task;
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::panic::PanicInfo;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zenoh::config::Config;
+use zenoh::runtime::Runtime;
+
+/// How long the crash report waits for the runtime's summary, which may be unavailable if the panicking thread holds its locks.
+const SUMMARY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Set while a crash report is being written, so that panics occurring meanwhile don't trigger reports of their own.
+static REPORTING: AtomicBool = AtomicBool::new(false);
+
+/// Writes a crash report in a directory whenever zenohd panics, before the default panic handling takes place.
+///
+/// The report is a JSON file holding the panic's message, location and backtrace, a digest of the configuration,
+/// and once the runtime is started, a summary of its sessions, storages and plugins.
+/// Note that panics raised within dynamically loaded plugins go through the panic hook of their own copy of `std`,
+/// and thus aren't reported.
+pub(crate) struct CrashReporter {
+    runtime: Arc<Mutex<Option<Runtime>>>,
+}
+
+impl CrashReporter {
+    pub(crate) fn install(dir: PathBuf, config: &Config, version: String) -> Self {
+        let runtime: Arc<Mutex<Option<Runtime>>> = Arc::new(Mutex::new(None));
+        let initial_config = config.sift_privates();
+        let default_hook = std::panic::take_hook();
+        let hook_runtime = runtime.clone();
+        std::panic::set_hook(Box::new(move |info| {
+            if !REPORTING.swap(true, Ordering::SeqCst) {
+                let runtime = hook_runtime.lock().ok().and_then(|r| r.clone());
+                let report = crash_report(info, &version, &initial_config, runtime);
+                match write_report(&dir, &report) {
+                    Ok(path) => eprintln!(
+                        "zenohd panicked, crash report written to {}",
+                        path.display()
+                    ),
+                    Err(e) => eprintln!(
+                        "zenohd panicked, failed to write crash report in {}: {}",
+                        dir.display(),
+                        e
+                    ),
+                }
+                REPORTING.store(false, Ordering::SeqCst);
+            }
+            default_hook(info)
+        }));
+        CrashReporter { runtime }
+    }
+
+    /// Includes the summary of `runtime` in the crash reports.
+    pub(crate) fn set_runtime(&self, runtime: Runtime) {
+        if let Ok(mut guard) = self.runtime.lock() {
+            *guard = Some(runtime);
+        }
+    }
+}
+
+fn config_digest(config: &Config) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(config)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn crash_report(
+    info: &PanicInfo,
+    version: &str,
+    initial_config: &Config,
+    runtime: Option<Runtime>,
+) -> serde_json::Value {
+    let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    };
+    let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+    let summary = runtime
+        .map(runtime_summary)
+        .unwrap_or_else(|| json!({ "config_digest": config_digest(initial_config) }));
+    json!({
+        "time": humantime::format_rfc3339(SystemTime::now()).to_string(),
+        "version": version,
+        "thread": std::thread::current().name().unwrap_or("<unnamed>"),
+        "message": message,
+        "location": info.location().map(|l| l.to_string()),
+        "backtrace": backtrace.lines().collect::<Vec<_>>(),
+        "runtime": summary,
+    })
+}
+
+/// Summarizes the state of the runtime from another thread, giving up after [`SUMMARY_TIMEOUT`].
+fn runtime_summary(runtime: Runtime) -> serde_json::Value {
+    let zid = runtime.zid.to_string();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let spawned = std::thread::Builder::new()
+        .name("crash-report".into())
+        .spawn(move || {
+            let sessions: Vec<serde_json::Value> =
+                task::block_on(runtime.manager().get_transports_unicast())
+                    .iter()
+                    .filter_map(|t| {
+                        Some(json!({
+                            "zid": t.get_zid().ok()?.to_string(),
+                            "whatami": t.get_whatami().ok()?.to_string(),
+                        }))
+                    })
+                    .collect();
+            let config = runtime.config.lock().sift_privates();
+            let storages = serde_json::to_value(&config)
+                .ok()
+                .and_then(|c| c.pointer("/plugins/storage_manager/storages").cloned())
+                .unwrap_or_else(|| json!({}));
+            let _ = tx.send(json!({
+                "config_digest": config_digest(&config),
+                "sessions": sessions,
+                "storages": storages,
+                "plugins": config.plugins().load_requests().flatten().map(|p| p.name).collect::<Vec<_>>(),
+            }));
+        });
+    match spawned
+        .ok()
+        .and_then(|_| rx.recv_timeout(SUMMARY_TIMEOUT).ok())
+    {
+        Some(mut summary) => {
+            summary["zid"] = json!(zid);
+            summary
+        }
+        None => json!({ "zid": zid, "error": "runtime summary unavailable" }),
+    }
+}
+
+fn write_report(dir: &Path, report: &serde_json::Value) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = dir.join(format!(
+        "zenohd-crash-{}-{}.json",
+        millis,
+        std::process::id()
+    ));
+    std::fs::write(
+        &path,
+        serde_json::to_string_pretty(report).unwrap_or_default(),
+    )?;
+    Ok(path)
+}
@@ -13,14 +13,19 @@
 //
 use async_std::task;
 use clap::{ArgMatches, Command};
+use crash_report::CrashReporter;
 use futures::future;
 use git_version::git_version;
-use std::collections::HashSet;
-use zenoh::config::{Config, ModeDependentValue, PermissionsConf, PluginLoad, ValidatedMap};
-use zenoh::plugins::PluginsManager;
+use zenoh::config::{
+    Config, ExecutorConf, ModeDependentValue, PermissionsConf, PluginLoad, ValidatedMap,
+};
+use zenoh::plugins::{load_plugin, PluginsManager};
 use zenoh::prelude::{EndPoint, WhatAmI};
 use zenoh::runtime::{AdminSpace, Runtime};
 
+mod crash_report;
+mod service;
+
 const GIT_VERSION: &str = git_version!(prefix = "v", cargo_prefix = "v");
 
 lazy_static::lazy_static!(
@@ -30,79 +35,80 @@ lazy_static::lazy_static!(
 const DEFAULT_LISTENER: &str = "tcp/[::]:7447";
 
 fn main() {
-    task::block_on(async {
-        let mut log_builder =
-            env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("z=info"));
-        #[cfg(feature = "stats")]
-        log_builder.format_timestamp_millis().init();
-        #[cfg(not(feature = "stats"))]
-        log_builder.init();
+    let mut log_builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("z=info"));
+    #[cfg(feature = "stats")]
+    log_builder.format_timestamp_millis().init();
+    #[cfg(not(feature = "stats"))]
+    log_builder.init();
 
-        log::info!("zenohd {}", *LONG_VERSION);
+    log::info!("zenohd {}", *LONG_VERSION);
 
-        let app = Command::new("The zenoh router")
-            .version(GIT_VERSION)
-            .long_version(LONG_VERSION.as_str()).args(
-                &[
-clap::arg!(-c --config [FILE] "The configuration file. Currently, this file must be a valid JSON5 or YAML file."),
-clap::Arg::new("listen").short('l').long("listen").value_name("ENDPOINT").help(r"A locator on which this router will listen for incoming sessions.
-Repeat this option to open several listeners.").takes_value(true).multiple_occurrences(true),
-clap::Arg::new("connect").short('e').long("connect").value_name("ENDPOINT").help(r"A peer locator this router will try to connect to.
-Repeat this option to connect to several peers.").takes_value(true).multiple_occurrences(true),
-clap::Arg::new("id").short('i').long("id").value_name("HEX_STRING").help(r"The identifier (as an hexadecimal string, with odd number of chars - e.g.: A0B23...) that zenohd must use. If not set, a random unsigned 128bit integer will be used.
-WARNING: this identifier must be unique in the system and must be 16 bytes maximum (32 chars)!").multiple_values(false).multiple_occurrences(false),
-clap::Arg::new("plugin").short('P').long("plugin").value_name("PLUGIN").takes_value(true).multiple_occurrences(true).help(r#"A plugin that MUST be loaded. You can give just the name of the plugin, zenohd will search for a library named 'libzenoh_plugin_<name>.so' (exact name depending the OS). Or you can give such a string: "<plugin_name>:<library_path>".
-Repeat this option to load several plugins. If loading failed, zenohd will exit."#),
-clap::Arg::new("plugin-search-dir").long("plugin-search-dir").takes_value(true).multiple_occurrences(true).value_name("DIRECTORY").help(r"A directory where to search for plugins libraries to load.
-Repeat this option to specify several search directories."),
-clap::arg!(--"no-timestamp" r"By default zenohd adds a HLC-generated Timestamp to each routed Data if there isn't already one. This option disables this feature."),
-clap::arg!(--"no-multicast-scouting" r"By default zenohd replies to multicast scouting messages for being discovered by peers and clients. This option disables this feature."),
-clap::arg!(--"rest-http-port" [SOCKET] r"Configures HTTP interface for the REST API (enabled by default). Accepted values:
-  - a port number
-  - a string with format `<local_ip>:<port_number>` (to bind the HTTP server to a specific interface)
-  - `none` to disable the REST API
-").default_value("8000").multiple_values(false).multiple_occurrences(false),
-clap::Arg::new("cfg").long("cfg").takes_value(true).multiple_occurrences(true).value_name("KEY:VALUE").help(
-r#"Allows arbitrary configuration changes as column-separated KEY:VALUE pairs, where:
-  - KEY must be a valid config path.
-  - VALUE must be a valid JSON5 string that can be deserialized to the expected type for the KEY field.
-Examples:
---cfg='startup/subscribe:["demo/**"]'
---cfg='plugins/storage_manager/storages/demo:{key_expr:"demo/example/**",volume:"memory"}'"#),
-clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_name("[r|w|rw|none]").help(r"Configure the read and/or write permissions on the admin space. Default is read only."),
-                ]
-            );
-        let args = app.get_matches();
-        let config = config_from_args(&args);
+    let args = command().get_matches();
+    if args.is_present("dump-config-schema") {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&Config::json_schema()).unwrap()
+        );
+        return;
+    }
+    let config = config_from_args(&args);
+    // Forking is only safe before any thread is spawned
+    if args.is_present("daemonize") {
+        if let Err(e) = service::daemonize() {
+            println!("{e}. Exiting...");
+            std::process::exit(-1);
+        }
+    }
+    // The executor must be configured before its first use
+    init_executor(config.executor());
+
+    task::block_on(async {
         log::info!("Initial conf: {}", &config);
+        let crash_reporter = args
+            .value_of("crash-report-dir")
+            .map(|dir| CrashReporter::install(dir.into(), &config, LONG_VERSION.clone()));
 
         let mut plugins = PluginsManager::dynamic(config.libloader());
         // Static plugins are to be added here, with `.add_static::<PluginType>()`
-        let mut required_plugins = HashSet::new();
-        for plugin_load in config.plugins().load_requests() {
+        // Plugins are loaded and started after their dependencies
+        let (mut plugin_loads, unsatisfied) = match config.plugins().ordered_load_requests() {
+            Ok(requests) => requests,
+            Err(e) => {
+                println!("{e}. Exiting...");
+                std::process::exit(-1);
+            }
+        };
+        for plugin_load in &plugin_loads {
             let PluginLoad {
                 name,
                 paths,
                 required,
+                ..
             } = plugin_load;
             log::info!(
                 "Loading {req} plugin \"{name}\"",
-                req = if required { "required" } else { "" }
+                req = if *required { "required" } else { "optional" }
             );
-            if let Err(e) = match paths {
-                None => plugins.load_plugin_by_name(name.clone()),
-                Some(paths) => plugins.load_plugin_by_paths(name.clone(), &paths),
-            } {
-                if required {
-                    panic!("Plugin load failure: {}", e)
-                } else {
-                    log::error!("Plugin load failure: {}", e)
-                }
-            }
-            if required {
-                required_plugins.insert(name);
+            if let Err(e) = load_plugin(&mut plugins, name, paths.as_deref()) {
+                log::error!("Plugin load failure: {}", e);
+                plugins.mark_failed(name, e.to_string());
             }
         }
+        for (plugin_load, e) in unsatisfied {
+            log::error!("Plugin \"{}\" {}", plugin_load.name, e);
+            plugins.mark_failed(&plugin_load.name, format!("Plugin {e}"));
+            plugin_loads.push(plugin_load);
+        }
+        let failed = plugins.failed_plugins();
+        if plugin_loads
+            .iter()
+            .any(|p| p.required && failed.contains_key(p.name.as_str()))
+        {
+            report_plugins(&plugins, &plugin_loads);
+            println!("A required plugin failed to load. Exiting...");
+            std::process::exit(-1);
+        }
 
         let runtime = match Runtime::new(config).await {
             Ok(runtime) => runtime,
@@ -111,51 +117,164 @@ clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_na
                 std::process::exit(-1);
             }
         };
+        if let Some(crash_reporter) = &crash_reporter {
+            crash_reporter.set_runtime(runtime.clone());
+        }
 
-        for (name, path, start_result) in plugins.start_all(&runtime) {
-            let required = required_plugins.contains(name);
-            log::info!(
-                "Starting {req} plugin \"{name}\"",
-                req = if required { "required" } else { "" }
-            );
-            match start_result {
-                Ok(Some(_)) => log::info!("Successfully started plugin {} from {:?}", name, path),
-                Ok(None) => log::warn!("Plugin {} from {:?} wasn't loaded, as an other plugin by the same name is already running", name, path),
+        for PluginLoad {
+            name, dependencies, ..
+        } in &plugin_loads
+        {
+            if plugins.failed_plugins().contains_key(name.as_str()) {
+                continue;
+            }
+            if let Some(dependency) = dependencies.iter().find(|d| plugins.plugin(d).is_none()) {
+                log::error!("Plugin \"{name}\" wasn't started, as its dependency \"{dependency}\" isn't running");
+                plugins.mark_failed(name, format!("Dependency `{dependency}` isn't running"));
+                continue;
+            }
+            log::info!("Starting plugin \"{name}\"");
+            let failure = match plugins.start(name, &runtime) {
+                Ok(Some((path, _))) => {
+                    log::info!("Successfully started plugin {} from {:?}", name, path);
+                    None
+                }
+                Ok(None) => {
+                    log::warn!("Plugin {} wasn't loaded, as an other plugin by the same name is already running", name);
+                    None
+                }
                 Err(e) => {
                     let report = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| e.to_string())) {
                         Ok(s) => s,
-                        Err(_) => panic!("Formatting the error from plugin {} ({:?}) failed, this is likely due to ABI unstability.\r\nMake sure your plugin was built with the same version of cargo as zenohd", name, path),
+                        Err(_) => panic!("Formatting the error from plugin {} failed, this is likely due to ABI unstability.\r\nMake sure your plugin was built with the same version of cargo as zenohd", name),
+                    };
+                    let report = if report.is_empty() {
+                        "no details provided".to_string()
+                    } else {
+                        report
                     };
-                    if required {
-                        panic!("Plugin \"{name}\" failed to start: {}", if report.is_empty() {"no details provided"} else {report.as_str()});
-                    }else {
-                        log::error!("Required plugin \"{name}\" failed to start: {}", if report.is_empty() {"no details provided"} else {report.as_str()});
-                    }
+                    log::error!("Plugin \"{name}\" failed to start: {}", report);
+                    Some(report)
                 }
+            };
+            if let Some(report) = failure {
+                plugins.mark_failed(name, report);
             }
         }
+        if !report_plugins(&plugins, &plugin_loads) {
+            println!("A required plugin failed to start. Exiting...");
+            std::process::exit(-1);
+        }
         log::info!("Finished loading plugins");
 
-        {
-            let mut config_guard = runtime.config.lock();
-            for (name, (_, plugin)) in plugins.running_plugins() {
-                let hook = plugin.config_checker();
-                config_guard.add_plugin_validator(name, hook)
+        let admin = AdminSpace::start(&runtime, plugins, LONG_VERSION.clone()).await;
+        service::notify_ready();
+
+        match service::termination().await {
+            Ok(signal) => log::info!("Received {}, shutting down...", signal),
+            Err(e) => {
+                log::error!("Unable to handle the termination signals: {}", e);
+                future::pending::<()>().await;
             }
         }
+        service::notify_stopping();
+        // Plugins are stopped first, so that the storages are flushed while the sessions are still open
+        admin.stop_plugins();
+        if let Err(e) = runtime.close().await {
+            log::error!("Failed to close the runtime: {}", e);
+        }
+        log::info!("zenohd stopped");
+    });
+}
 
-        AdminSpace::start(&runtime, plugins, LONG_VERSION.clone()).await;
+/// Sizes the thread pool of the executor and pins its threads, which inherit the affinity of the main thread.
+fn init_executor(config: &ExecutorConf) {
+    if let Some(cores) = config.affinity() {
+        if let Err(e) = zenoh_util::set_current_thread_affinity(cores) {
+            println!("{e}. Exiting...");
+            std::process::exit(-1);
+        }
+        log::info!("Executor pinned to the CPU cores {:?}", cores);
+    }
+    let executor = async_global_executor::GlobalExecutorConfig::default()
+        .with_thread_name_fn(|| "async-std/runtime".to_string());
+    let executor = match config.threads() {
+        Some(threads) => executor
+            .with_min_threads(*threads)
+            .with_max_threads(*threads),
+        None => executor.with_env_var("ASYNC_STD_THREAD_COUNT"),
+    };
+    async_global_executor::init_with_config(executor);
+}
 
-        future::pending::<()>().await;
-    });
+fn command() -> Command<'static> {
+    Command::new("The zenoh router")
+            .version(GIT_VERSION)
+            .long_version(LONG_VERSION.as_str()).args(
+                &[
+clap::arg!(-c --config [FILE] "The configuration file. Currently, this file must be a valid JSON5 or YAML file."),
+clap::Arg::new("listen").short('l').long("listen").value_name("ENDPOINT").help(r"A locator on which this router will listen for incoming sessions.
+Repeat this option to open several listeners.").takes_value(true).multiple_occurrences(true),
+clap::Arg::new("connect").short('e').long("connect").value_name("ENDPOINT").help(r"A peer locator this router will try to connect to.
+Repeat this option to connect to several peers.").takes_value(true).multiple_occurrences(true),
+clap::Arg::new("id").short('i').long("id").value_name("HEX_STRING").help(r"The identifier (as an hexadecimal string, with odd number of chars - e.g.: A0B23...) that zenohd must use. If not set, a random unsigned 128bit integer will be used.
+WARNING: this identifier must be unique in the system and must be 16 bytes maximum (32 chars)!").multiple_values(false).multiple_occurrences(false),
+clap::Arg::new("plugin").short('P').long("plugin").value_name("PLUGIN").takes_value(true).multiple_occurrences(true).help(r#"A plugin that MUST be loaded. You can give just the name of the plugin, zenohd will search for a library named 'libzenoh_plugin_<name>.so' (exact name depending the OS). Or you can give such a string: "<plugin_name>:<library_path>".
+Repeat this option to load several plugins. If loading failed, zenohd will exit."#),
+clap::Arg::new("plugin-search-dir").long("plugin-search-dir").takes_value(true).multiple_occurrences(true).value_name("DIRECTORY").help(r"A directory where to search for plugins libraries to load.
+Repeat this option to specify several search directories."),
+clap::arg!(--"no-timestamp" r"By default zenohd adds a HLC-generated Timestamp to each routed Data if there isn't already one. This option disables this feature."),
+clap::arg!(--"no-multicast-scouting" r"By default zenohd replies to multicast scouting messages for being discovered by peers and clients. This option disables this feature."),
+clap::arg!(--"rest-http-port" [SOCKET] r"Configures HTTP interface for the REST API (enabled by default). Accepted values:
+  - a port number
+  - a string with format `<local_ip>:<port_number>` (to bind the HTTP server to a specific interface)
+  - `none` to disable the REST API
+").default_value("8000").multiple_values(false).multiple_occurrences(false),
+clap::Arg::new("cfg").long("cfg").takes_value(true).multiple_occurrences(true).value_name("KEY:VALUE").help(
+r#"Allows arbitrary configuration changes as column-separated KEY:VALUE pairs, where:
+  - KEY must be a valid config path.
+  - VALUE must be a valid JSON5 string that can be deserialized to the expected type for the KEY field.
+Those changes are applied on top of the configuration file and of the other options. Repeat this option to apply several changes.
+Examples:
+--cfg='transport/link/tx/batch_size:16384'
+--cfg='plugins/rest/http_port:8080'
+--cfg='plugins/storage_manager/storages/demo:{key_expr:"demo/example/**",volume:"memory"}'"#),
+clap::Arg::new("crash-report-dir").long("crash-report-dir").takes_value(true).value_name("DIRECTORY").help(r"A directory where zenohd writes a crash report (backtrace, configuration digest, summary of the sessions, storages and plugins) whenever it panics."),
+clap::Arg::new("adminspace-permissions").long("adminspace-permissions").value_name("[r|w|rw|none]").help(r"Configure the read and/or write permissions on the admin space. Default is read only."),
+clap::arg!(--daemonize r"Detaches zenohd from its terminal and runs it in the background. The launching process exits once zenohd is ready, i.e. once its listeners and required plugins are up.
+The standard input and outputs are redirected to /dev/null. When running zenohd as a systemd service, prefer `Type=notify` without this option: zenohd notifies systemd of its readiness through `NOTIFY_SOCKET`."),
+clap::arg!(--"dump-config-schema" r"Prints the JSON Schema of the configuration on the standard output and exits.
+Plugins' sections are only described by their common properties (`__required__`, `__on_panic__`, `__max_restarts__`, `__dependencies__`, `__path__`, `__config__`)."),
+                ]
+            )
+}
+
+/// Logs the status of every requested plugin, returning `false` if any required plugin isn't running.
+fn report_plugins(plugins: &PluginsManager, plugin_loads: &[PluginLoad]) -> bool {
+    let running = plugins.running_plugins_info();
+    let failed = plugins.failed_plugins();
+    let mut required_ok = true;
+    log::info!("Plugins status:");
+    for PluginLoad { name, required, .. } in plugin_loads {
+        let req = if *required { "required" } else { "optional" };
+        match (running.get(name.as_str()), failed.get(name.as_str())) {
+            (Some(path), _) => log::info!("  - {name} ({req}): running from {path}"),
+            (None, Some(e)) if *required => {
+                required_ok = false;
+                log::error!("  - {name} ({req}): failed: {e}")
+            }
+            (None, Some(e)) => log::warn!("  - {name} ({req}): failed: {e}"),
+            (None, None) => log::info!("  - {name} ({req}): not started"),
+        }
+    }
+    required_ok
 }
 
 fn config_from_args(args: &ArgMatches) -> Config {
-    let mut config = args
-        .value_of("config")
-        .map_or_else(Config::default, |conf_file| {
-            Config::from_file(conf_file).unwrap()
-        });
+    let mut config = args.value_of("config").map_or_else(
+        || Config::profile(WhatAmI::Router),
+        |conf_file| Config::from_file(conf_file).unwrap(),
+    );
 
     if config.mode().is_none() {
         config.set_mode(Some(WhatAmI::Router)).unwrap();
@@ -291,19 +410,50 @@ fn config_from_args(args: &ArgMatches) -> Config {
         };
     };
     for json in args.values_of("cfg").unwrap_or_default() {
-        if let Some((key, value)) = json.split_once(':') {
-            match json5::Deserializer::from_str(value) {
-                Ok(mut deserializer) => {
-                    if let Err(e) =
-                        config.insert(key.strip_prefix('/').unwrap_or(key), &mut deserializer)
-                    {
-                        log::warn!("Couldn't perform configuration {}: {}", json, e);
-                    }
-                }
-                Err(e) => log::warn!("Couldn't perform configuration {}: {}", json, e),
-            }
+        let Some((key, value)) = json.split_once(':') else {
+            panic!(
+                "Invalid option --cfg={} - Expected KEY:VALUE, where VALUE is a JSON5 value",
+                json
+            );
+        };
+        if let Err(e) = json5::Deserializer::from_str(value)
+            .map_err(|e| e.to_string())
+            .and_then(|mut deserializer| {
+                config
+                    .insert(key.strip_prefix('/').unwrap_or(key), &mut deserializer)
+                    .map_err(|e| e.to_string())
+            })
+        {
+            panic!("Couldn't perform configuration --cfg={}: {}", json, e);
         }
     }
     log::debug!("Config: {:?}", &config);
     config
 }
+
+#[test]
+fn cfg_overrides() {
+    let args = command().get_matches_from([
+        "zenohd",
+        "--cfg=transport/link/tx/batch_size:16384",
+        "--cfg=/plugins/rest/http_port:8080",
+        "--cfg=connect/endpoints:[\"tcp/127.0.0.1:7448\"]",
+    ]);
+    let config = config_from_args(&args);
+    assert_eq!(*config.transport().link().tx().batch_size(), 16384);
+    assert_eq!(
+        config.plugin("rest").unwrap()["http_port"],
+        serde_json::json!(8080)
+    );
+    assert_eq!(
+        config.connect().endpoints(),
+        &vec!["tcp/127.0.0.1:7448".parse::<EndPoint>().unwrap()]
+    );
+}
+
+#[test]
+#[should_panic]
+fn cfg_override_invalid_value() {
+    let args = command().get_matches_from(["zenohd", "--cfg=transport/link/tx/batch_size:\"big\""]);
+    config_from_args(&args);
+}
@@ -0,0 +1,194 @@
+//
+// Copyright (c) 2023 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+//! Integration of zenohd as a system service: daemonization, readiness notification to its supervisor
+//! (systemd's `sd_notify` protocol or the parent of a daemonized zenohd) and graceful termination.
+use zenoh::Result as ZResult;
+
+/// Detaches zenohd from its terminal and its parent, which waits until zenohd is [ready](notify_ready)
+/// and exits with a success status, or with a failure one if zenohd exits before being ready.
+///
+/// The standard input and outputs are redirected to `/dev/null`, the logs are thus discarded.
+/// Must be called before any thread is spawned, i.e. before the executor starts.
+#[cfg(unix)]
+pub(crate) fn daemonize() -> ZResult<()> {
+    use std::sync::atomic::Ordering;
+
+    fn error(context: &str) -> zenoh::Error {
+        format!("{}: {}", context, std::io::Error::last_os_error()).into()
+    }
+
+    let mut fds = [0; 2];
+    // SAFETY: only async-signal-safe calls are made between `fork` and `exit` in the parent,
+    // no other thread is running in the child.
+    unsafe {
+        if libc::pipe(fds.as_mut_ptr()) != 0 {
+            return Err(error("Unable to create the readiness pipe"));
+        }
+        match libc::fork() {
+            -1 => return Err(error("Unable to fork")),
+            0 => {}
+            _ => {
+                libc::close(fds[1]);
+                let mut ready = 0_u8;
+                let status = match libc::read(fds[0], &mut ready as *mut u8 as *mut libc::c_void, 1)
+                {
+                    1 => 0,
+                    _ => 1,
+                };
+                libc::_exit(status);
+            }
+        }
+        libc::close(fds[0]);
+        if libc::setsid() == -1 {
+            return Err(error("Unable to create a new session"));
+        }
+        let null = libc::open(b"/dev/null\0".as_ptr() as *const libc::c_char, libc::O_RDWR);
+        if null == -1 {
+            return Err(error("Unable to open /dev/null"));
+        }
+        for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+            libc::dup2(null, fd);
+        }
+        if null > libc::STDERR_FILENO {
+            libc::close(null);
+        }
+    }
+    READINESS_PIPE.store(fds[1], Ordering::SeqCst);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn daemonize() -> ZResult<()> {
+    Err("Daemonizing zenohd is only supported on Unix".into())
+}
+
+/// The writing end of the pipe through which a daemonized zenohd notifies its parent that it's ready.
+#[cfg(unix)]
+static READINESS_PIPE: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(-1);
+
+/// Notifies the supervisor of zenohd that it's ready, i.e. that its listeners and required plugins are up.
+pub(crate) fn notify_ready() {
+    #[cfg(unix)]
+    {
+        let fd = READINESS_PIPE.swap(-1, std::sync::atomic::Ordering::SeqCst);
+        if fd != -1 {
+            // SAFETY: the pipe is owned by zenohd and closed once.
+            unsafe {
+                libc::write(fd, [1_u8].as_ptr() as *const libc::c_void, 1);
+                libc::close(fd);
+            }
+        }
+    }
+    sd_notify("READY=1");
+}
+
+/// Notifies the supervisor of zenohd that it's shutting down.
+pub(crate) fn notify_stopping() {
+    sd_notify("STOPPING=1");
+}
+
+/// Sends a state to systemd, through the socket it provides in `NOTIFY_SOCKET` when zenohd runs as a
+/// `Type=notify` service. Does nothing otherwise.
+fn sd_notify(state: &str) {
+    #[cfg(unix)]
+    if let Some(socket) = std::env::var_os("NOTIFY_SOCKET") {
+        if let Err(e) = sd_notify_to(&socket, state) {
+            log::warn!("Unable to notify systemd of {}: {}", state, e);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = state;
+}
+
+#[cfg(unix)]
+fn sd_notify_to(socket: &std::ffi::OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = socket.as_bytes();
+    // SAFETY: `sockaddr_un` is a plain C struct, for which zero is a valid value.
+    let mut addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+    if path.is_empty() || path.len() >= addr.sun_path.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "invalid NOTIFY_SOCKET",
+        ));
+    }
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+    for (dst, src) in addr.sun_path.iter_mut().zip(path) {
+        *dst = *src as libc::c_char;
+    }
+    // Sockets starting with '@' live in the abstract namespace, where the name starts with a NUL byte
+    if path[0] == b'@' {
+        addr.sun_path[0] = 0;
+    }
+    let len = std::mem::size_of::<libc::sa_family_t>() + path.len();
+    // SAFETY: the socket is closed before returning, `addr` is a valid address of `len` bytes.
+    unsafe {
+        let fd = libc::socket(libc::AF_UNIX, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0);
+        if fd == -1 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let sent = libc::sendto(
+            fd,
+            state.as_ptr() as *const libc::c_void,
+            state.len(),
+            libc::MSG_NOSIGNAL,
+            &addr as *const libc::sockaddr_un as *const libc::sockaddr,
+            len as libc::socklen_t,
+        );
+        let result = if sent == -1 {
+            Err(std::io::Error::last_os_error())
+        } else {
+            Ok(())
+        };
+        libc::close(fd);
+        result
+    }
+}
+
+/// Waits until zenohd is asked to terminate by SIGTERM or SIGINT, returning the received signal's name.
+#[cfg(unix)]
+pub(crate) async fn termination() -> ZResult<&'static str> {
+    use signal_hook::consts::{SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+
+    let mut signals = Signals::new([SIGTERM, SIGINT])?;
+    let signal = async_std::task::spawn_blocking(move || signals.forever().next()).await;
+    Ok(match signal {
+        Some(SIGTERM) => "SIGTERM",
+        _ => "SIGINT",
+    })
+}
+
+#[cfg(not(unix))]
+pub(crate) async fn termination() -> ZResult<&'static str> {
+    futures::future::pending().await
+}
+
+#[cfg(unix)]
+#[test]
+fn sd_notify_datagram() {
+    let dir = std::env::temp_dir().join(format!("zenohd-sd-notify-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("notify");
+    let _ = std::fs::remove_file(&path);
+    let socket = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+
+    sd_notify_to(path.as_os_str(), "READY=1").unwrap();
+    let mut buf = [0_u8; 32];
+    let n = socket.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"READY=1");
+    assert!(sd_notify_to(std::ffi::OsStr::new(""), "READY=1").is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}